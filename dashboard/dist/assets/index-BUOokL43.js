@@ -1 +1 @@
-(function(){const t=document.createElement("link").relList;if(t&&t.supports&&t.supports("modulepreload"))return;for(const l of document.querySelectorAll('link[rel="modulepreload"]'))r(l);new MutationObserver(l=>{for(const i of l)if(i.type==="childList")for(const o of i.addedNodes)o.tagName==="LINK"&&o.rel==="modulepreload"&&r(o)}).observe(document,{childList:!0,subtree:!0});function n(l){const i={};return l.integrity&&(i.integrity=l.integrity),l.referrerPolicy&&(i.referrerPolicy=l.referrerPolicy),l.crossOrigin==="use-credentials"?i.credentials="include":l.crossOrigin==="anonymous"?i.credentials="omit":i.credentials="same-origin",i}function r(l){if(l.ep)return;l.ep=!0;const i=n(l);fetch(l.href,i)}})();const Pe=!1,Le=(e,t)=>e===t,De=Symbol("solid-track"),z={equals:Le};let $e=ye;const R=1,Q=2,pe={owned:null,cleanups:null,context:null,owner:null};var E=null;let re=null,Ie=null,x=null,A=null,U=null,te=0;function W(e,t){const n=x,r=E,l=e.length===0,i=t===void 0?r:t,o=l?pe:{owned:null,cleanups:null,context:i?i.context:null,owner:i},s=l?e:()=>e(()=>V(()=>G(o)));E=o,x=null;try{return J(s,!0)}finally{x=n,E=r}}function L(e,t){t=t?Object.assign({},z,t):z;const n={value:e,observers:null,observerSlots:null,comparator:t.equals||void 0},r=l=>(typeof l=="function"&&(l=l(n.value)),we(n,l));return[be.bind(n),r]}function B(e,t,n){const r=se(e,t,!1,R);q(r)}function Ne(e,t,n){$e=Be;const r=se(e,t,!1,R);r.user=!0,U?U.push(r):q(r)}function F(e,t,n){n=n?Object.assign({},z,n):z;const r=se(e,t,!0,0);return r.observers=null,r.observerSlots=null,r.comparator=n.equals||void 0,q(r),be.bind(r)}function V(e){if(x===null)return e();const t=x;x=null;try{return e()}finally{x=t}}function ie(e){Ne(()=>V(e))}function me(e){return E===null||(E.cleanups===null?E.cleanups=[e]:E.cleanups.push(e)),e}function be(){if(this.sources&&this.state)if(this.state===R)q(this);else{const e=A;A=null,J(()=>Y(this),!1),A=e}if(x){const e=this.observers?this.observers.length:0;x.sources?(x.sources.push(this),x.sourceSlots.push(e)):(x.sources=[this],x.sourceSlots=[e]),this.observers?(this.observers.push(x),this.observerSlots.push(x.sources.length-1)):(this.observers=[x],this.observerSlots=[x.sources.length-1])}return this.value}function we(e,t,n){let r=e.value;return(!e.comparator||!e.comparator(r,t))&&(e.value=t,e.observers&&e.observers.length&&J(()=>{for(let l=0;l<e.observers.length;l+=1){const i=e.observers[l],o=re&&re.running;o&&re.disposed.has(i),(o?!i.tState:!i.state)&&(i.pure?A.push(i):U.push(i),i.observers&&Se(i)),o||(i.state=R)}if(A.length>1e6)throw A=[],new Error},!1)),t}function q(e){if(!e.fn)return;G(e);const t=te;Oe(e,e.value,t)}function Oe(e,t,n){let r;const l=E,i=x;x=E=e;try{r=e.fn(t)}catch(o){return e.pure&&(e.state=R,e.owned&&e.owned.forEach(G),e.owned=null),e.updatedAt=n+1,ke(o)}finally{x=i,E=l}(!e.updatedAt||e.updatedAt<=n)&&(e.updatedAt!=null&&"observers"in e?we(e,r):e.value=r,e.updatedAt=n)}function se(e,t,n,r=R,l){const i={fn:e,state:r,updatedAt:null,owned:null,sources:null,sourceSlots:null,cleanups:null,value:t,owner:E,context:E?E.context:null,pure:n};return E===null||E!==pe&&(E.owned?E.owned.push(i):E.owned=[i]),i}function X(e){if(e.state===0)return;if(e.state===Q)return Y(e);if(e.suspense&&V(e.suspense.inFallback))return e.suspense.effects.push(e);const t=[e];for(;(e=e.owner)&&(!e.updatedAt||e.updatedAt<te);)e.state&&t.push(e);for(let n=t.length-1;n>=0;n--)if(e=t[n],e.state===R)q(e);else if(e.state===Q){const r=A;A=null,J(()=>Y(e,t[0]),!1),A=r}}function J(e,t){if(A)return e();let n=!1;t||(A=[]),U?n=!0:U=[],te++;try{const r=e();return Ue(n),r}catch(r){n||(U=null),A=null,ke(r)}}function Ue(e){if(A&&(ye(A),A=null),e)return;const t=U;U=null,t.length&&J(()=>$e(t),!1)}function ye(e){for(let t=0;t<e.length;t++)X(e[t])}function Be(e){let t,n=0;for(t=0;t<e.length;t++){const r=e[t];r.user?e[n++]=r:X(r)}for(t=0;t<n;t++)X(e[t])}function Y(e,t){e.state=0;for(let n=0;n<e.sources.length;n+=1){const r=e.sources[n];if(r.sources){const l=r.state;l===R?r!==t&&(!r.updatedAt||r.updatedAt<te)&&X(r):l===Q&&Y(r,t)}}}function Se(e){for(let t=0;t<e.observers.length;t+=1){const n=e.observers[t];n.state||(n.state=Q,n.pure?A.push(n):U.push(n),n.observers&&Se(n))}}function G(e){let t;if(e.sources)for(;e.sources.length;){const n=e.sources.pop(),r=e.sourceSlots.pop(),l=n.observers;if(l&&l.length){const i=l.pop(),o=n.observerSlots.pop();r<l.length&&(i.sourceSlots[o]=r,l[r]=i,n.observerSlots[r]=o)}}if(e.tOwned){for(t=e.tOwned.length-1;t>=0;t--)G(e.tOwned[t]);delete e.tOwned}if(e.owned){for(t=e.owned.length-1;t>=0;t--)G(e.owned[t]);e.owned=null}if(e.cleanups){for(t=e.cleanups.length-1;t>=0;t--)e.cleanups[t]();e.cleanups=null}e.state=0}function Me(e){return e instanceof Error?e:new Error(typeof e=="string"?e:"Unknown error",{cause:e})}function ke(e,t=E){throw Me(e)}const Re=Symbol("fallback");function de(e){for(let t=0;t<e.length;t++)e[t]()}function Fe(e,t,n={}){let r=[],l=[],i=[],o=0,s=t.length>1?[]:null;return me(()=>de(i)),()=>{let u=e()||[],h=u.length,g,a;return u[De],V(()=>{let S,$,d,f,j,y,k,p,w;if(h===0)o!==0&&(de(i),i=[],r=[],l=[],o=0,s&&(s=[])),n.fallback&&(r=[Re],l[0]=W(T=>(i[0]=T,n.fallback())),o=1);else if(o===0){for(l=new Array(h),a=0;a<h;a++)r[a]=u[a],l[a]=W(_);o=h}else{for(d=new Array(h),f=new Array(h),s&&(j=new Array(h)),y=0,k=Math.min(o,h);y<k&&r[y]===u[y];y++);for(k=o-1,p=h-1;k>=y&&p>=y&&r[k]===u[p];k--,p--)d[p]=l[k],f[p]=i[k],s&&(j[p]=s[k]);for(S=new Map,$=new Array(p+1),a=p;a>=y;a--)w=u[a],g=S.get(w),$[a]=g===void 0?-1:g,S.set(w,a);for(g=y;g<=k;g++)w=r[g],a=S.get(w),a!==void 0&&a!==-1?(d[a]=l[g],f[a]=i[g],s&&(j[a]=s[g]),a=$[a],S.set(w,a)):i[g]();for(a=y;a<h;a++)a in d?(l[a]=d[a],i[a]=f[a],s&&(s[a]=j[a],s[a](a))):l[a]=W(_);l=l.slice(0,o=h),r=u.slice(0)}return l});function _(S){if(i[a]=S,s){const[$,d]=L(a);return s[a]=d,t(u[a],$)}return t(u[a])}}}function b(e,t){return V(()=>e(t||{}))}const Ve=e=>`Stale read from <${e}>.`;function Z(e){const t="fallback"in e&&{fallback:()=>e.fallback};return F(Fe(()=>e.each,e.children,t||void 0))}function v(e){const t=e.keyed,n=F(()=>e.when,void 0,void 0),r=t?n:F(n,void 0,{equals:(l,i)=>!l==!i});return F(()=>{const l=r();if(l){const i=e.children;return typeof i=="function"&&i.length>0?V(()=>i(t?l:()=>{if(!V(r))throw Ve("Show");return n()})):i}return e.fallback},void 0,void 0)}const P=e=>F(()=>e());function He(e,t,n){let r=n.length,l=t.length,i=r,o=0,s=0,u=t[l-1].nextSibling,h=null;for(;o<l||s<i;){if(t[o]===n[s]){o++,s++;continue}for(;t[l-1]===n[i-1];)l--,i--;if(l===o){const g=i<r?s?n[s-1].nextSibling:n[i-s]:u;for(;s<i;)e.insertBefore(n[s++],g)}else if(i===s)for(;o<l;)(!h||!h.has(t[o]))&&t[o].remove(),o++;else if(t[o]===n[i-1]&&n[s]===t[l-1]){const g=t[--l].nextSibling;e.insertBefore(n[s++],t[o++].nextSibling),e.insertBefore(n[--i],g),t[l]=n[i]}else{if(!h){h=new Map;let a=s;for(;a<i;)h.set(n[a],a++)}const g=h.get(t[o]);if(g!=null)if(s<g&&g<i){let a=o,_=1,S;for(;++a<l&&a<i&&!((S=h.get(t[a]))==null||S!==g+_);)_++;if(_>g-s){const $=t[o];for(;s<g;)e.insertBefore(n[s++],$)}else e.replaceChild(n[s++],t[o++])}else o++;else t[o++].remove()}}}const fe="_$DX_DELEGATE";function Ge(e,t,n,r={}){let l;return W(i=>{l=i,t===document?e():c(t,e(),t.firstChild?null:void 0,n)},r.owner),()=>{l(),t.textContent=""}}function m(e,t,n,r){let l;const i=()=>{const s=document.createElement("template");return s.innerHTML=e,s.content.firstChild},o=()=>(l||(l=i())).cloneNode(!0);return o.cloneNode=o,o}function Ce(e,t=window.document){const n=t[fe]||(t[fe]=new Set);for(let r=0,l=e.length;r<l;r++){const i=e[r];n.has(i)||(n.add(i),t.addEventListener(i,qe))}}function ve(e,t,n){n==null?e.removeAttribute(t):e.setAttribute(t,n)}function O(e,t,n){n!=null?e.style.setProperty(t,n):e.style.removeProperty(t)}function c(e,t,n,r){if(n!==void 0&&!r&&(r=[]),typeof t!="function")return ee(e,t,r,n);B(l=>ee(e,t(),l,n),r)}function qe(e){let t=e.target;const n=`$$${e.type}`,r=e.target,l=e.currentTarget,i=u=>Object.defineProperty(e,"target",{configurable:!0,value:u}),o=()=>{const u=t[n];if(u&&!t.disabled){const h=t[`${n}Data`];if(h!==void 0?u.call(t,h,e):u.call(t,e),e.cancelBubble)return}return t.host&&typeof t.host!="string"&&!t.host._$host&&t.contains(e.target)&&i(t.host),!0},s=()=>{for(;o()&&(t=t._$host||t.parentNode||t.host););};if(Object.defineProperty(e,"currentTarget",{configurable:!0,get(){return t||document}}),e.composedPath){const u=e.composedPath();i(u[0]);for(let h=0;h<u.length-2&&(t=u[h],!!o());h++){if(t._$host){t=t._$host,s();break}if(t.parentNode===l)break}}else s();i(r)}function ee(e,t,n,r,l){for(;typeof n=="function";)n=n();if(t===n)return n;const i=typeof t,o=r!==void 0;if(e=o&&n[0]&&n[0].parentNode||e,i==="string"||i==="number"){if(i==="number"&&(t=t.toString(),t===n))return n;if(o){let s=n[0];s&&s.nodeType===3?s.data!==t&&(s.data=t):s=document.createTextNode(t),n=H(e,n,r,s)}else n!==""&&typeof n=="string"?n=e.firstChild.data=t:n=e.textContent=t}else if(t==null||i==="boolean")n=H(e,n,r);else{if(i==="function")return B(()=>{let s=t();for(;typeof s=="function";)s=s();n=ee(e,s,n,r)}),()=>n;if(Array.isArray(t)){const s=[],u=n&&Array.isArray(n);if(le(s,t,n,l))return B(()=>n=ee(e,s,n,r,!0)),()=>n;if(s.length===0){if(n=H(e,n,r),o)return n}else u?n.length===0?he(e,s,r):He(e,n,s):(n&&H(e),he(e,s));n=s}else if(t.nodeType){if(Array.isArray(n)){if(o)return n=H(e,n,r,t);H(e,n,null,t)}else n==null||n===""||!e.firstChild?e.appendChild(t):e.replaceChild(t,e.firstChild);n=t}}return n}function le(e,t,n,r){let l=!1;for(let i=0,o=t.length;i<o;i++){let s=t[i],u=n&&n[e.length],h;if(!(s==null||s===!0||s===!1))if((h=typeof s)=="object"&&s.nodeType)e.push(s);else if(Array.isArray(s))l=le(e,s,u)||l;else if(h==="function")if(r){for(;typeof s=="function";)s=s();l=le(e,Array.isArray(s)?s:[s],Array.isArray(u)?u:[u])||l}else e.push(s),l=!0;else{const g=String(s);u&&u.nodeType===3&&u.data===g?e.push(u):e.push(document.createTextNode(g))}}return l}function he(e,t,n=null){for(let r=0,l=t.length;r<l;r++)e.insertBefore(t[r],n)}function H(e,t,n,r){if(n===void 0)return e.textContent="";const l=r||document.createTextNode("");if(t.length){let i=!1;for(let o=t.length-1;o>=0;o--){const s=t[o];if(l!==s){const u=s.parentNode===e;!i&&!o?u?e.replaceChild(l,s):e.insertBefore(l,n):u&&s.remove()}else i=!0}}else e.insertBefore(l,n);return[l]}const ne="/api";async function Je(){const e=await fetch(`${ne}/projects`);if(!e.ok)throw new Error(`Failed to fetch projects: ${e.statusText}`);return e.json()}async function Ke(e){const t=await fetch(`${ne}/projects/${encodeURIComponent(e)}`);if(!t.ok)throw new Error(`Failed to fetch project: ${t.statusText}`);return t.json()}async function ge(e){const t=await fetch(`${ne}/projects/${encodeURIComponent(e)}/tickets`);if(!t.ok)throw new Error(`Failed to fetch tickets: ${t.statusText}`);return t.json()}async function We(e,t){const n=await fetch(`${ne}/projects/${encodeURIComponent(e)}/tickets/${encodeURIComponent(t)}`);if(!n.ok)throw new Error(`Failed to fetch ticket: ${n.statusText}`);return n.json()}function ze(e){const t=new EventSource("/sse");return t.onmessage=e,t.onerror=n=>{console.error("SSE connection error:",n)},()=>t.close()}var Qe=m("<article><header><h3>Select Project</h3></header><select><option value disabled>Choose a project..."),Xe=m("<option>");function Ye(e){return(()=>{var t=Qe(),n=t.firstChild,r=n.nextSibling;return r.firstChild,r.addEventListener("change",l=>e.onSelect(l.currentTarget.value)),c(r,b(Z,{get each(){return e.projects},children:l=>(()=>{var i=Xe();return c(i,()=>l.repository_name,null),c(i,(()=>{var o=P(()=>!!l.short_description);return()=>o()?` - ${l.short_description}`:""})(),null),B(()=>i.value=l.repository_name),i})()}),null),B(()=>r.value=e.selectedProjectId||""),t})()}var Ze=m("<dt><strong>Description"),et=m("<dd>"),tt=m("<dt>URL"),nt=m('<dd><a target=_blank rel="noopener noreferrer">'),rt=m("<details><summary><strong>JBCT Configuration</strong></summary><dl><dt>Version</dt><dd>"),lt=m("<details><summary><strong>Project Rules</strong></summary><pre style=overflow-x:auto;font-size:0.85rem><code>"),it=m("<details><summary><strong>Project Patterns</strong></summary><pre style=overflow-x:auto;font-size:0.85rem><code>"),st=m("<article><header><h3>Project Details</h3></header><dl><dt><strong>Name</strong></dt><dd></dd><dt><strong>Prefix</strong></dt><dd><code></code></dd><dt><strong>Path</strong></dt><dd><code></code></dd><dt><strong>Created</strong></dt><dd></dd><dt><strong>Updated</strong></dt><dd>");function ot(e){return(()=>{var t=st(),n=t.firstChild,r=n.nextSibling,l=r.firstChild,i=l.nextSibling,o=i.nextSibling,s=o.nextSibling,u=s.firstChild,h=s.nextSibling,g=h.nextSibling,a=g.firstChild,_=g.nextSibling,S=_.nextSibling,$=S.nextSibling,d=$.nextSibling;return c(i,()=>e.project.repository_name),c(u,()=>e.project.project_prefix),c(a,()=>e.project.path),c(r,b(v,{get when(){return e.project.short_description},get children(){return[Ze(),(()=>{var f=et();return c(f,()=>e.project.short_description),f})()]}}),_),c(S,()=>new Date(e.project.created_at).toLocaleString()),c(d,()=>new Date(e.project.updated_at).toLocaleString()),c(t,b(v,{get when(){return e.project.jbct_enabled},get children(){var f=rt(),j=f.firstChild,y=j.nextSibling,k=y.firstChild,p=k.nextSibling;return c(p,()=>e.project.jbct_version||"Unknown"),c(y,b(v,{get when(){return e.project.jbct_url},get children(){return[tt(),(()=>{var w=nt(),T=w.firstChild;return c(T,()=>e.project.jbct_url),B(()=>ve(T,"href",e.project.jbct_url)),w})()]}}),null),f}}),null),c(t,b(v,{get when(){return e.project.rules},get children(){var f=lt(),j=f.firstChild,y=j.nextSibling,k=y.firstChild;return c(k,()=>e.project.rules),f}}),null),c(t,b(v,{get when(){return e.project.patterns},get children(){var f=it(),j=f.firstChild,y=j.nextSibling,k=y.firstChild;return c(k,()=>e.project.patterns),f}}),null),t})()}var ct=m("<dt><strong>Parent Ticket"),at=m("<dd><code>"),ut=m("<dt><strong>Processing Worker"),dt=m("<dd><code style=font-size:0.85rem>"),ft=m("<dt><strong>Closed"),_e=m("<dd>"),ht=m("<dt><strong>Resolution"),gt=m("<p aria-busy=true>Loading comments..."),_t=m("<p>No comments yet."),$t=m("<div style=max-height:400px;overflow-y:auto>"),pt=m("<article style=margin:1rem;background-color:var(--pico-background-color)><header><h4>Ticket Details</h4></header><dl><dt><strong>Execution Plan</strong></dt><dd></dd><dt><strong>Created</strong></dt><dd></dd><dt><strong>Updated</strong></dt><dd></dd></dl><details open><summary><strong>Comments (<!>)"),mt=m("<span><code style=font-size:0.85rem>"),bt=m("<article style=margin-bottom:0.5rem;padding:0.75rem><header style=margin-bottom:0.5rem><small><strong></strong> • </small></header><p style=white-space:pre-wrap;margin:0;font-size:0.9rem>");function wt(e){const[t,n]=L([]),[r,l]=L(!0),i=F(()=>{try{return JSON.parse(e.ticket.execution_plan)}catch{return[]}});return ie(async()=>{try{const o=await We(e.projectId,e.ticket.ticket_id);n(o.comments)}catch(o){console.error("Failed to load comments:",o)}finally{l(!1)}}),(()=>{var o=pt(),s=o.firstChild,u=s.nextSibling,h=u.firstChild,g=h.nextSibling,a=g.nextSibling,_=a.nextSibling,S=_.nextSibling,$=S.nextSibling,d=u.nextSibling,f=d.firstChild,j=f.firstChild,y=j.firstChild,k=y.nextSibling;return k.nextSibling,c(g,b(Z,{get each(){return i()},children:(p,w)=>(()=>{var T=mt(),D=T.firstChild;return c(D,p),c(T,()=>w()<i().length-1&&" → ",null),B(I=>O(D,"background-color",p===e.ticket.current_stage?"var(--pico-primary-background)":void 0)),T})()})),c(u,b(v,{get when(){return e.ticket.parent_ticket_id},get children(){return[ct(),(()=>{var p=at(),w=p.firstChild;return c(w,()=>e.ticket.parent_ticket_id),p})()]}}),a),c(u,b(v,{get when(){return e.ticket.processing_worker_id},get children(){return[ut(),(()=>{var p=dt(),w=p.firstChild;return c(w,()=>e.ticket.processing_worker_id),p})()]}}),a),c(_,()=>new Date(e.ticket.created_at).toLocaleString()),c($,()=>new Date(e.ticket.updated_at).toLocaleString()),c(u,b(v,{get when(){return e.ticket.closed_at},get children(){return[ft(),(()=>{var p=_e();return c(p,()=>new Date(e.ticket.closed_at).toLocaleString()),p})()]}}),null),c(u,b(v,{get when(){return e.ticket.resolution},get children(){return[ht(),(()=>{var p=_e();return c(p,()=>e.ticket.resolution),p})()]}}),null),c(j,()=>t().length,k),c(d,b(v,{get when(){return r()},get children(){return gt()}}),null),c(d,b(v,{get when(){return P(()=>!r())()&&t().length===0},get children(){return _t()}}),null),c(d,b(v,{get when(){return P(()=>!r())()&&t().length>0},get children(){var p=$t();return c(p,b(Z,{get each(){return t()},children:w=>(()=>{var T=bt(),D=T.firstChild,I=D.firstChild,K=I.firstChild,C=K.nextSibling,N=D.nextSibling;return c(K,()=>w.worker_type||"system"),c(I,(()=>{var M=P(()=>!!w.worker_id);return()=>M()&&` (${w.worker_id})`})(),C),c(I,()=>new Date(w.created_at).toLocaleString(),null),c(I,(()=>{var M=P(()=>w.stage_index!==null);return()=>M()&&` • Stage ${w.stage_index}`})(),null),c(N,()=>w.content),T})()})),p}}),null),o})()}var yt=m("<p aria-busy=true>Loading tickets..."),St=m("<p>No tickets found for this project."),kt=m("<table><thead><tr><th style=user-select:none>ID </th><th style=user-select:none>Title </th><th style=user-select:none>Stage </th><th style=user-select:none>State </th><th style=user-select:none>Created </th><th>Priority</th></tr></thead><tbody>"),Ct=m("<article><header><h3>Tickets (<!>)"),vt=m("<tr><td><code style=font-size:0.85rem></code></td><td></td><td><code style=font-size:0.85rem></code></td><td></td><td><small></small></td><td>"),xt=m("<tr><td colspan=6 style=padding:0>");function jt(e){const[t,n]=L(null),[r,l]=L("created"),[i,o]=L("desc");function s(_){n(t()===_?null:_)}function u(_){r()===_?o(i()==="asc"?"desc":"asc"):(l(_),o("asc"))}const h=F(()=>{const _=[...e.tickets],S=i()==="asc"?1:-1;return _.sort(($,d)=>{let f=0;switch(r()){case"id":f=$.ticket_id.localeCompare(d.ticket_id);break;case"title":f=$.title.localeCompare(d.title);break;case"stage":f=$.current_stage.localeCompare(d.current_stage);break;case"state":f=$.state.localeCompare(d.state);break;case"created":f=new Date($.created_at).getTime()-new Date(d.created_at).getTime();break}return f*S})});function g(_){return{open:"🟢 Open",closed:"✅ Closed",on_hold:"⏸️  On Hold"}[_]||_}function a(_){return{low:"🔵 Low",medium:"🟡 Medium",high:"🟠 High",urgent:"🔴 Urgent"}[_]||_}return(()=>{var _=Ct(),S=_.firstChild,$=S.firstChild,d=$.firstChild,f=d.nextSibling;return f.nextSibling,c($,()=>e.tickets.length,f),c(_,b(v,{get when(){return e.loading},get children(){return yt()}}),null),c(_,b(v,{get when(){return P(()=>!e.loading)()&&e.tickets.length===0},get children(){return St()}}),null),c(_,b(v,{get when(){return P(()=>!e.loading)()&&e.tickets.length>0},get children(){var j=kt(),y=j.firstChild,k=y.firstChild,p=k.firstChild;p.firstChild;var w=p.nextSibling;w.firstChild;var T=w.nextSibling;T.firstChild;var D=T.nextSibling;D.firstChild;var I=D.nextSibling;I.firstChild;var K=y.nextSibling;return p.$$click=()=>u("id"),O(p,"cursor","pointer"),c(p,(()=>{var C=P(()=>r()==="id");return()=>C()&&(i()==="asc"?"▲":"▼")})(),null),w.$$click=()=>u("title"),O(w,"cursor","pointer"),c(w,(()=>{var C=P(()=>r()==="title");return()=>C()&&(i()==="asc"?"▲":"▼")})(),null),T.$$click=()=>u("stage"),O(T,"cursor","pointer"),c(T,(()=>{var C=P(()=>r()==="stage");return()=>C()&&(i()==="asc"?"▲":"▼")})(),null),D.$$click=()=>u("state"),O(D,"cursor","pointer"),c(D,(()=>{var C=P(()=>r()==="state");return()=>C()&&(i()==="asc"?"▲":"▼")})(),null),I.$$click=()=>u("created"),O(I,"cursor","pointer"),c(I,(()=>{var C=P(()=>r()==="created");return()=>C()&&(i()==="asc"?"▲":"▼")})(),null),c(K,b(Z,{get each(){return h()},children:C=>[(()=>{var N=vt(),M=N.firstChild,je=M.firstChild,oe=M.nextSibling,ce=oe.nextSibling,Ee=ce.firstChild,ae=ce.nextSibling,ue=ae.nextSibling,Te=ue.firstChild,Ae=ue.nextSibling;return N.$$click=()=>s(C.ticket_id),O(N,"cursor","pointer"),c(je,()=>C.ticket_id),c(oe,()=>C.title),c(Ee,()=>C.current_stage),c(ae,()=>g(C.state)),c(Te,()=>new Date(C.created_at).toLocaleString()),c(Ae,()=>a(C.priority)),B(Nt=>O(N,"background-color",t()===C.ticket_id?"var(--pico-table-row-selected-background-color, rgba(0,0,0,0.1))":void 0)),N})(),b(v,{get when(){return t()===C.ticket_id},get children(){var N=xt(),M=N.firstChild;return c(M,b(wt,{ticket:C,get projectId(){return e.projectId}})),N}})]})),j}}),null),_})()}Ce(["click"]);var Et=m('<button aria-label="Toggle theme">');function Tt(){const[e,t]=L(!0);ie(()=>{const r=window.matchMedia("(prefers-color-scheme: dark)").matches,l=localStorage.getItem("theme"),i=l==="dark"||!l&&r;t(i),document.documentElement.setAttribute("data-theme",i?"dark":"light")});function n(){const r=!e();t(r);const l=r?"dark":"light";document.documentElement.setAttribute("data-theme",l),localStorage.setItem("theme",l)}return(()=>{var r=Et();return r.$$click=n,O(r,"width","auto"),O(r,"padding","0.5rem 1rem"),c(r,()=>e()?"☀️ Light":"🌙 Dark"),B(()=>ve(r,"title",e()?"Switch to light mode":"Switch to dark mode")),r})()}Ce(["click"]);var At=m("<article aria-label=Error><p><strong>Error:</strong> "),Pt=m("<article aria-busy=true>Loading projects..."),Lt=m("<article><p>No projects found. Create a project using the MCP server to get started."),Dt=m("<div class=container><header><hgroup><h1>Vibe Ensemble Dashboard</h1><p>Multi-agent coordination monitoring</p></hgroup></header><footer><small>Vibe Ensemble MCP v1.0.0 | <a href=https://github.com/siy/vibe-ensemble-mcp target=_blank>GitHub");function It(){const[e,t]=L([]),[n,r]=L(null),[l,i]=L(null),[o,s]=L([]),[u,h]=L(!0),[g,a]=L(null);ie(async()=>{try{const d=await Je();t(d),h(!1),d.length>0&&_(d[0].repository_name)}catch(d){a(d.message),h(!1)}const $=ze(d=>{try{const f=JSON.parse(d.data);console.log("SSE event:",f),n()&&(f.event_type==="ticket_created"||f.event_type==="ticket_updated"||f.event_type==="ticket_closed")&&S(n())}catch(f){console.error("Failed to parse SSE event:",f)}});me($)});async function _($){h(!0),a(null),r($);try{const[d,f]=await Promise.all([Ke($),ge($)]);i(d),s(f)}catch(d){a(d.message)}finally{h(!1)}}async function S($){try{const d=await ge($);s(d)}catch(d){console.error("Failed to reload tickets:",d)}}return(()=>{var $=Dt(),d=$.firstChild;d.firstChild;var f=d.nextSibling;return c(d,b(Tt,{}),null),c($,b(v,{get when(){return g()},get children(){var j=At(),y=j.firstChild,k=y.firstChild;return k.nextSibling,c(y,g,null),j}}),f),c($,b(v,{get when(){return P(()=>!!u())()&&e().length===0},get children(){return Pt()}}),f),c($,b(v,{get when(){return P(()=>!u()&&e().length===0)()&&!g()},get children(){return Lt()}}),f),c($,b(v,{get when(){return e().length>0},get children(){return[b(Ye,{get projects(){return e()},get selectedProjectId(){return n()},onSelect:_}),b(v,{get when(){return l()},get children(){return[b(ot,{get project(){return l()}}),b(jt,{get tickets(){return o()},get projectId(){return n()},get loading(){return u()}})]}})]}}),f),$})()}const xe=document.getElementById("root");if(!xe)throw new Error("Root element not found. Did you forget to add it to your index.html? Or maybe the id attribute got misspelled?");Ge(()=>b(It,{}),xe);
+(function(){const t=document.createElement("link").relList;if(t&&t.supports&&t.supports("modulepreload"))return;for(const l of document.querySelectorAll('link[rel="modulepreload"]'))r(l);new MutationObserver(l=>{for(const i of l)if(i.type==="childList")for(const o of i.addedNodes)o.tagName==="LINK"&&o.rel==="modulepreload"&&r(o)}).observe(document,{childList:!0,subtree:!0});function n(l){const i={};return l.integrity&&(i.integrity=l.integrity),l.referrerPolicy&&(i.referrerPolicy=l.referrerPolicy),l.crossOrigin==="use-credentials"?i.credentials="include":l.crossOrigin==="anonymous"?i.credentials="omit":i.credentials="same-origin",i}function r(l){if(l.ep)return;l.ep=!0;const i=n(l);fetch(l.href,i)}})();const Pe=!1,Le=(e,t)=>e===t,De=Symbol("solid-track"),z={equals:Le};let $e=ye;const R=1,Q=2,pe={owned:null,cleanups:null,context:null,owner:null};var E=null;let re=null,Ie=null,x=null,A=null,U=null,te=0;function W(e,t){const n=x,r=E,l=e.length===0,i=t===void 0?r:t,o=l?pe:{owned:null,cleanups:null,context:i?i.context:null,owner:i},s=l?e:()=>e(()=>V(()=>G(o)));E=o,x=null;try{return J(s,!0)}finally{x=n,E=r}}function L(e,t){t=t?Object.assign({},z,t):z;const n={value:e,observers:null,observerSlots:null,comparator:t.equals||void 0},r=l=>(typeof l=="function"&&(l=l(n.value)),we(n,l));return[be.bind(n),r]}function B(e,t,n){const r=se(e,t,!1,R);q(r)}function Ne(e,t,n){$e=Be;const r=se(e,t,!1,R);r.user=!0,U?U.push(r):q(r)}function F(e,t,n){n=n?Object.assign({},z,n):z;const r=se(e,t,!0,0);return r.observers=null,r.observerSlots=null,r.comparator=n.equals||void 0,q(r),be.bind(r)}function V(e){if(x===null)return e();const t=x;x=null;try{return e()}finally{x=t}}function ie(e){Ne(()=>V(e))}function me(e){return E===null||(E.cleanups===null?E.cleanups=[e]:E.cleanups.push(e)),e}function be(){if(this.sources&&this.state)if(this.state===R)q(this);else{const e=A;A=null,J(()=>Y(this),!1),A=e}if(x){const e=this.observers?this.observers.length:0;x.sources?(x.sources.push(this),x.sourceSlots.push(e)):(x.sources=[this],x.sourceSlots=[e]),this.observers?(this.observers.push(x),this.observerSlots.push(x.sources.length-1)):(this.observers=[x],this.observerSlots=[x.sources.length-1])}return this.value}function we(e,t,n){let r=e.value;return(!e.comparator||!e.comparator(r,t))&&(e.value=t,e.observers&&e.observers.length&&J(()=>{for(let l=0;l<e.observers.length;l+=1){const i=e.observers[l],o=re&&re.running;o&&re.disposed.has(i),(o?!i.tState:!i.state)&&(i.pure?A.push(i):U.push(i),i.observers&&Se(i)),o||(i.state=R)}if(A.length>1e6)throw A=[],new Error},!1)),t}function q(e){if(!e.fn)return;G(e);const t=te;Oe(e,e.value,t)}function Oe(e,t,n){let r;const l=E,i=x;x=E=e;try{r=e.fn(t)}catch(o){return e.pure&&(e.state=R,e.owned&&e.owned.forEach(G),e.owned=null),e.updatedAt=n+1,ke(o)}finally{x=i,E=l}(!e.updatedAt||e.updatedAt<=n)&&(e.updatedAt!=null&&"observers"in e?we(e,r):e.value=r,e.updatedAt=n)}function se(e,t,n,r=R,l){const i={fn:e,state:r,updatedAt:null,owned:null,sources:null,sourceSlots:null,cleanups:null,value:t,owner:E,context:E?E.context:null,pure:n};return E===null||E!==pe&&(E.owned?E.owned.push(i):E.owned=[i]),i}function X(e){if(e.state===0)return;if(e.state===Q)return Y(e);if(e.suspense&&V(e.suspense.inFallback))return e.suspense.effects.push(e);const t=[e];for(;(e=e.owner)&&(!e.updatedAt||e.updatedAt<te);)e.state&&t.push(e);for(let n=t.length-1;n>=0;n--)if(e=t[n],e.state===R)q(e);else if(e.state===Q){const r=A;A=null,J(()=>Y(e,t[0]),!1),A=r}}function J(e,t){if(A)return e();let n=!1;t||(A=[]),U?n=!0:U=[],te++;try{const r=e();return Ue(n),r}catch(r){n||(U=null),A=null,ke(r)}}function Ue(e){if(A&&(ye(A),A=null),e)return;const t=U;U=null,t.length&&J(()=>$e(t),!1)}function ye(e){for(let t=0;t<e.length;t++)X(e[t])}function Be(e){let t,n=0;for(t=0;t<e.length;t++){const r=e[t];r.user?e[n++]=r:X(r)}for(t=0;t<n;t++)X(e[t])}function Y(e,t){e.state=0;for(let n=0;n<e.sources.length;n+=1){const r=e.sources[n];if(r.sources){const l=r.state;l===R?r!==t&&(!r.updatedAt||r.updatedAt<te)&&X(r):l===Q&&Y(r,t)}}}function Se(e){for(let t=0;t<e.observers.length;t+=1){const n=e.observers[t];n.state||(n.state=Q,n.pure?A.push(n):U.push(n),n.observers&&Se(n))}}function G(e){let t;if(e.sources)for(;e.sources.length;){const n=e.sources.pop(),r=e.sourceSlots.pop(),l=n.observers;if(l&&l.length){const i=l.pop(),o=n.observerSlots.pop();r<l.length&&(i.sourceSlots[o]=r,l[r]=i,n.observerSlots[r]=o)}}if(e.tOwned){for(t=e.tOwned.length-1;t>=0;t--)G(e.tOwned[t]);delete e.tOwned}if(e.owned){for(t=e.owned.length-1;t>=0;t--)G(e.owned[t]);e.owned=null}if(e.cleanups){for(t=e.cleanups.length-1;t>=0;t--)e.cleanups[t]();e.cleanups=null}e.state=0}function Me(e){return e instanceof Error?e:new Error(typeof e=="string"?e:"Unknown error",{cause:e})}function ke(e,t=E){throw Me(e)}const Re=Symbol("fallback");function de(e){for(let t=0;t<e.length;t++)e[t]()}function Fe(e,t,n={}){let r=[],l=[],i=[],o=0,s=t.length>1?[]:null;return me(()=>de(i)),()=>{let u=e()||[],h=u.length,g,a;return u[De],V(()=>{let S,$,d,f,j,y,k,p,w;if(h===0)o!==0&&(de(i),i=[],r=[],l=[],o=0,s&&(s=[])),n.fallback&&(r=[Re],l[0]=W(T=>(i[0]=T,n.fallback())),o=1);else if(o===0){for(l=new Array(h),a=0;a<h;a++)r[a]=u[a],l[a]=W(_);o=h}else{for(d=new Array(h),f=new Array(h),s&&(j=new Array(h)),y=0,k=Math.min(o,h);y<k&&r[y]===u[y];y++);for(k=o-1,p=h-1;k>=y&&p>=y&&r[k]===u[p];k--,p--)d[p]=l[k],f[p]=i[k],s&&(j[p]=s[k]);for(S=new Map,$=new Array(p+1),a=p;a>=y;a--)w=u[a],g=S.get(w),$[a]=g===void 0?-1:g,S.set(w,a);for(g=y;g<=k;g++)w=r[g],a=S.get(w),a!==void 0&&a!==-1?(d[a]=l[g],f[a]=i[g],s&&(j[a]=s[g]),a=$[a],S.set(w,a)):i[g]();for(a=y;a<h;a++)a in d?(l[a]=d[a],i[a]=f[a],s&&(s[a]=j[a],s[a](a))):l[a]=W(_);l=l.slice(0,o=h),r=u.slice(0)}return l});function _(S){if(i[a]=S,s){const[$,d]=L(a);return s[a]=d,t(u[a],$)}return t(u[a])}}}function b(e,t){return V(()=>e(t||{}))}const Ve=e=>`Stale read from <${e}>.`;function Z(e){const t="fallback"in e&&{fallback:()=>e.fallback};return F(Fe(()=>e.each,e.children,t||void 0))}function v(e){const t=e.keyed,n=F(()=>e.when,void 0,void 0),r=t?n:F(n,void 0,{equals:(l,i)=>!l==!i});return F(()=>{const l=r();if(l){const i=e.children;return typeof i=="function"&&i.length>0?V(()=>i(t?l:()=>{if(!V(r))throw Ve("Show");return n()})):i}return e.fallback},void 0,void 0)}const P=e=>F(()=>e());function He(e,t,n){let r=n.length,l=t.length,i=r,o=0,s=0,u=t[l-1].nextSibling,h=null;for(;o<l||s<i;){if(t[o]===n[s]){o++,s++;continue}for(;t[l-1]===n[i-1];)l--,i--;if(l===o){const g=i<r?s?n[s-1].nextSibling:n[i-s]:u;for(;s<i;)e.insertBefore(n[s++],g)}else if(i===s)for(;o<l;)(!h||!h.has(t[o]))&&t[o].remove(),o++;else if(t[o]===n[i-1]&&n[s]===t[l-1]){const g=t[--l].nextSibling;e.insertBefore(n[s++],t[o++].nextSibling),e.insertBefore(n[--i],g),t[l]=n[i]}else{if(!h){h=new Map;let a=s;for(;a<i;)h.set(n[a],a++)}const g=h.get(t[o]);if(g!=null)if(s<g&&g<i){let a=o,_=1,S;for(;++a<l&&a<i&&!((S=h.get(t[a]))==null||S!==g+_);)_++;if(_>g-s){const $=t[o];for(;s<g;)e.insertBefore(n[s++],$)}else e.replaceChild(n[s++],t[o++])}else o++;else t[o++].remove()}}}const fe="_$DX_DELEGATE";function Ge(e,t,n,r={}){let l;return W(i=>{l=i,t===document?e():c(t,e(),t.firstChild?null:void 0,n)},r.owner),()=>{l(),t.textContent=""}}function m(e,t,n,r){let l;const i=()=>{const s=document.createElement("template");return s.innerHTML=e,s.content.firstChild},o=()=>(l||(l=i())).cloneNode(!0);return o.cloneNode=o,o}function Ce(e,t=window.document){const n=t[fe]||(t[fe]=new Set);for(let r=0,l=e.length;r<l;r++){const i=e[r];n.has(i)||(n.add(i),t.addEventListener(i,qe))}}function ve(e,t,n){n==null?e.removeAttribute(t):e.setAttribute(t,n)}function O(e,t,n){n!=null?e.style.setProperty(t,n):e.style.removeProperty(t)}function c(e,t,n,r){if(n!==void 0&&!r&&(r=[]),typeof t!="function")return ee(e,t,r,n);B(l=>ee(e,t(),l,n),r)}function qe(e){let t=e.target;const n=`$$${e.type}`,r=e.target,l=e.currentTarget,i=u=>Object.defineProperty(e,"target",{configurable:!0,value:u}),o=()=>{const u=t[n];if(u&&!t.disabled){const h=t[`${n}Data`];if(h!==void 0?u.call(t,h,e):u.call(t,e),e.cancelBubble)return}return t.host&&typeof t.host!="string"&&!t.host._$host&&t.contains(e.target)&&i(t.host),!0},s=()=>{for(;o()&&(t=t._$host||t.parentNode||t.host););};if(Object.defineProperty(e,"currentTarget",{configurable:!0,get(){return t||document}}),e.composedPath){const u=e.composedPath();i(u[0]);for(let h=0;h<u.length-2&&(t=u[h],!!o());h++){if(t._$host){t=t._$host,s();break}if(t.parentNode===l)break}}else s();i(r)}function ee(e,t,n,r,l){for(;typeof n=="function";)n=n();if(t===n)return n;const i=typeof t,o=r!==void 0;if(e=o&&n[0]&&n[0].parentNode||e,i==="string"||i==="number"){if(i==="number"&&(t=t.toString(),t===n))return n;if(o){let s=n[0];s&&s.nodeType===3?s.data!==t&&(s.data=t):s=document.createTextNode(t),n=H(e,n,r,s)}else n!==""&&typeof n=="string"?n=e.firstChild.data=t:n=e.textContent=t}else if(t==null||i==="boolean")n=H(e,n,r);else{if(i==="function")return B(()=>{let s=t();for(;typeof s=="function";)s=s();n=ee(e,s,n,r)}),()=>n;if(Array.isArray(t)){const s=[],u=n&&Array.isArray(n);if(le(s,t,n,l))return B(()=>n=ee(e,s,n,r,!0)),()=>n;if(s.length===0){if(n=H(e,n,r),o)return n}else u?n.length===0?he(e,s,r):He(e,n,s):(n&&H(e),he(e,s));n=s}else if(t.nodeType){if(Array.isArray(n)){if(o)return n=H(e,n,r,t);H(e,n,null,t)}else n==null||n===""||!e.firstChild?e.appendChild(t):e.replaceChild(t,e.firstChild);n=t}}return n}function le(e,t,n,r){let l=!1;for(let i=0,o=t.length;i<o;i++){let s=t[i],u=n&&n[e.length],h;if(!(s==null||s===!0||s===!1))if((h=typeof s)=="object"&&s.nodeType)e.push(s);else if(Array.isArray(s))l=le(e,s,u)||l;else if(h==="function")if(r){for(;typeof s=="function";)s=s();l=le(e,Array.isArray(s)?s:[s],Array.isArray(u)?u:[u])||l}else e.push(s),l=!0;else{const g=String(s);u&&u.nodeType===3&&u.data===g?e.push(u):e.push(document.createTextNode(g))}}return l}function he(e,t,n=null){for(let r=0,l=t.length;r<l;r++)e.insertBefore(t[r],n)}function H(e,t,n,r){if(n===void 0)return e.textContent="";const l=r||document.createTextNode("");if(t.length){let i=!1;for(let o=t.length-1;o>=0;o--){const s=t[o];if(l!==s){const u=s.parentNode===e;!i&&!o?u?e.replaceChild(l,s):e.insertBefore(l,n):u&&s.remove()}else i=!0}}else e.insertBefore(l,n);return[l]}const ne=(window.__VIBE_BASE_PATH__??"")+"/api";async function Je(){const e=await fetch(`${ne}/projects`);if(!e.ok)throw new Error(`Failed to fetch projects: ${e.statusText}`);return e.json()}async function Ke(e){const t=await fetch(`${ne}/projects/${encodeURIComponent(e)}`);if(!t.ok)throw new Error(`Failed to fetch project: ${t.statusText}`);return t.json()}async function ge(e){const t=await fetch(`${ne}/projects/${encodeURIComponent(e)}/tickets`);if(!t.ok)throw new Error(`Failed to fetch tickets: ${t.statusText}`);return t.json()}async function We(e,t){const n=await fetch(`${ne}/projects/${encodeURIComponent(e)}/tickets/${encodeURIComponent(t)}`);if(!n.ok)throw new Error(`Failed to fetch ticket: ${n.statusText}`);return n.json()}function ze(e){const t=new EventSource((window.__VIBE_BASE_PATH__??"")+"/sse");return t.onmessage=e,t.onerror=n=>{console.error("SSE connection error:",n)},()=>t.close()}var Qe=m("<article><header><h3>Select Project</h3></header><select><option value disabled>Choose a project..."),Xe=m("<option>");function Ye(e){return(()=>{var t=Qe(),n=t.firstChild,r=n.nextSibling;return r.firstChild,r.addEventListener("change",l=>e.onSelect(l.currentTarget.value)),c(r,b(Z,{get each(){return e.projects},children:l=>(()=>{var i=Xe();return c(i,()=>l.repository_name,null),c(i,(()=>{var o=P(()=>!!l.short_description);return()=>o()?` - ${l.short_description}`:""})(),null),B(()=>i.value=l.repository_name),i})()}),null),B(()=>r.value=e.selectedProjectId||""),t})()}var Ze=m("<dt><strong>Description"),et=m("<dd>"),tt=m("<dt>URL"),nt=m('<dd><a target=_blank rel="noopener noreferrer">'),rt=m("<details><summary><strong>JBCT Configuration</strong></summary><dl><dt>Version</dt><dd>"),lt=m("<details><summary><strong>Project Rules</strong></summary><pre style=overflow-x:auto;font-size:0.85rem><code>"),it=m("<details><summary><strong>Project Patterns</strong></summary><pre style=overflow-x:auto;font-size:0.85rem><code>"),st=m("<article><header><h3>Project Details</h3></header><dl><dt><strong>Name</strong></dt><dd></dd><dt><strong>Prefix</strong></dt><dd><code></code></dd><dt><strong>Path</strong></dt><dd><code></code></dd><dt><strong>Created</strong></dt><dd></dd><dt><strong>Updated</strong></dt><dd>");function ot(e){return(()=>{var t=st(),n=t.firstChild,r=n.nextSibling,l=r.firstChild,i=l.nextSibling,o=i.nextSibling,s=o.nextSibling,u=s.firstChild,h=s.nextSibling,g=h.nextSibling,a=g.firstChild,_=g.nextSibling,S=_.nextSibling,$=S.nextSibling,d=$.nextSibling;return c(i,()=>e.project.repository_name),c(u,()=>e.project.project_prefix),c(a,()=>e.project.path),c(r,b(v,{get when(){return e.project.short_description},get children(){return[Ze(),(()=>{var f=et();return c(f,()=>e.project.short_description),f})()]}}),_),c(S,()=>new Date(e.project.created_at).toLocaleString()),c(d,()=>new Date(e.project.updated_at).toLocaleString()),c(t,b(v,{get when(){return e.project.jbct_enabled},get children(){var f=rt(),j=f.firstChild,y=j.nextSibling,k=y.firstChild,p=k.nextSibling;return c(p,()=>e.project.jbct_version||"Unknown"),c(y,b(v,{get when(){return e.project.jbct_url},get children(){return[tt(),(()=>{var w=nt(),T=w.firstChild;return c(T,()=>e.project.jbct_url),B(()=>ve(T,"href",e.project.jbct_url)),w})()]}}),null),f}}),null),c(t,b(v,{get when(){return e.project.rules},get children(){var f=lt(),j=f.firstChild,y=j.nextSibling,k=y.firstChild;return c(k,()=>e.project.rules),f}}),null),c(t,b(v,{get when(){return e.project.patterns},get children(){var f=it(),j=f.firstChild,y=j.nextSibling,k=y.firstChild;return c(k,()=>e.project.patterns),f}}),null),t})()}var ct=m("<dt><strong>Parent Ticket"),at=m("<dd><code>"),ut=m("<dt><strong>Processing Worker"),dt=m("<dd><code style=font-size:0.85rem>"),ft=m("<dt><strong>Closed"),_e=m("<dd>"),ht=m("<dt><strong>Resolution"),gt=m("<p aria-busy=true>Loading comments..."),_t=m("<p>No comments yet."),$t=m("<div style=max-height:400px;overflow-y:auto>"),pt=m("<article style=margin:1rem;background-color:var(--pico-background-color)><header><h4>Ticket Details</h4></header><dl><dt><strong>Execution Plan</strong></dt><dd></dd><dt><strong>Created</strong></dt><dd></dd><dt><strong>Updated</strong></dt><dd></dd></dl><details open><summary><strong>Comments (<!>)"),mt=m("<span><code style=font-size:0.85rem>"),bt=m("<article style=margin-bottom:0.5rem;padding:0.75rem><header style=margin-bottom:0.5rem><small><strong></strong> • </small></header><p style=white-space:pre-wrap;margin:0;font-size:0.9rem>");function wt(e){const[t,n]=L([]),[r,l]=L(!0),i=F(()=>{try{return JSON.parse(e.ticket.execution_plan)}catch{return[]}});return ie(async()=>{try{const o=await We(e.projectId,e.ticket.ticket_id);n(o.comments)}catch(o){console.error("Failed to load comments:",o)}finally{l(!1)}}),(()=>{var o=pt(),s=o.firstChild,u=s.nextSibling,h=u.firstChild,g=h.nextSibling,a=g.nextSibling,_=a.nextSibling,S=_.nextSibling,$=S.nextSibling,d=u.nextSibling,f=d.firstChild,j=f.firstChild,y=j.firstChild,k=y.nextSibling;return k.nextSibling,c(g,b(Z,{get each(){return i()},children:(p,w)=>(()=>{var T=mt(),D=T.firstChild;return c(D,p),c(T,()=>w()<i().length-1&&" → ",null),B(I=>O(D,"background-color",p===e.ticket.current_stage?"var(--pico-primary-background)":void 0)),T})()})),c(u,b(v,{get when(){return e.ticket.parent_ticket_id},get children(){return[ct(),(()=>{var p=at(),w=p.firstChild;return c(w,()=>e.ticket.parent_ticket_id),p})()]}}),a),c(u,b(v,{get when(){return e.ticket.processing_worker_id},get children(){return[ut(),(()=>{var p=dt(),w=p.firstChild;return c(w,()=>e.ticket.processing_worker_id),p})()]}}),a),c(_,()=>new Date(e.ticket.created_at).toLocaleString()),c($,()=>new Date(e.ticket.updated_at).toLocaleString()),c(u,b(v,{get when(){return e.ticket.closed_at},get children(){return[ft(),(()=>{var p=_e();return c(p,()=>new Date(e.ticket.closed_at).toLocaleString()),p})()]}}),null),c(u,b(v,{get when(){return e.ticket.resolution},get children(){return[ht(),(()=>{var p=_e();return c(p,()=>e.ticket.resolution),p})()]}}),null),c(j,()=>t().length,k),c(d,b(v,{get when(){return r()},get children(){return gt()}}),null),c(d,b(v,{get when(){return P(()=>!r())()&&t().length===0},get children(){return _t()}}),null),c(d,b(v,{get when(){return P(()=>!r())()&&t().length>0},get children(){var p=$t();return c(p,b(Z,{get each(){return t()},children:w=>(()=>{var T=bt(),D=T.firstChild,I=D.firstChild,K=I.firstChild,C=K.nextSibling,N=D.nextSibling;return c(K,()=>w.worker_type||"system"),c(I,(()=>{var M=P(()=>!!w.worker_id);return()=>M()&&` (${w.worker_id})`})(),C),c(I,()=>new Date(w.created_at).toLocaleString(),null),c(I,(()=>{var M=P(()=>w.stage_index!==null);return()=>M()&&` • Stage ${w.stage_index}`})(),null),c(N,()=>w.content),T})()})),p}}),null),o})()}var yt=m("<p aria-busy=true>Loading tickets..."),St=m("<p>No tickets found for this project."),kt=m("<table><thead><tr><th style=user-select:none>ID </th><th style=user-select:none>Title </th><th style=user-select:none>Stage </th><th style=user-select:none>State </th><th style=user-select:none>Created </th><th>Priority</th></tr></thead><tbody>"),Ct=m("<article><header><h3>Tickets (<!>)"),vt=m("<tr><td><code style=font-size:0.85rem></code></td><td></td><td><code style=font-size:0.85rem></code></td><td></td><td><small></small></td><td>"),xt=m("<tr><td colspan=6 style=padding:0>");function jt(e){const[t,n]=L(null),[r,l]=L("created"),[i,o]=L("desc");function s(_){n(t()===_?null:_)}function u(_){r()===_?o(i()==="asc"?"desc":"asc"):(l(_),o("asc"))}const h=F(()=>{const _=[...e.tickets],S=i()==="asc"?1:-1;return _.sort(($,d)=>{let f=0;switch(r()){case"id":f=$.ticket_id.localeCompare(d.ticket_id);break;case"title":f=$.title.localeCompare(d.title);break;case"stage":f=$.current_stage.localeCompare(d.current_stage);break;case"state":f=$.state.localeCompare(d.state);break;case"created":f=new Date($.created_at).getTime()-new Date(d.created_at).getTime();break}return f*S})});function g(_){return{open:"🟢 Open",closed:"✅ Closed",on_hold:"⏸️  On Hold"}[_]||_}function a(_){return{low:"🔵 Low",medium:"🟡 Medium",high:"🟠 High",urgent:"🔴 Urgent"}[_]||_}return(()=>{var _=Ct(),S=_.firstChild,$=S.firstChild,d=$.firstChild,f=d.nextSibling;return f.nextSibling,c($,()=>e.tickets.length,f),c(_,b(v,{get when(){return e.loading},get children(){return yt()}}),null),c(_,b(v,{get when(){return P(()=>!e.loading)()&&e.tickets.length===0},get children(){return St()}}),null),c(_,b(v,{get when(){return P(()=>!e.loading)()&&e.tickets.length>0},get children(){var j=kt(),y=j.firstChild,k=y.firstChild,p=k.firstChild;p.firstChild;var w=p.nextSibling;w.firstChild;var T=w.nextSibling;T.firstChild;var D=T.nextSibling;D.firstChild;var I=D.nextSibling;I.firstChild;var K=y.nextSibling;return p.$$click=()=>u("id"),O(p,"cursor","pointer"),c(p,(()=>{var C=P(()=>r()==="id");return()=>C()&&(i()==="asc"?"▲":"▼")})(),null),w.$$click=()=>u("title"),O(w,"cursor","pointer"),c(w,(()=>{var C=P(()=>r()==="title");return()=>C()&&(i()==="asc"?"▲":"▼")})(),null),T.$$click=()=>u("stage"),O(T,"cursor","pointer"),c(T,(()=>{var C=P(()=>r()==="stage");return()=>C()&&(i()==="asc"?"▲":"▼")})(),null),D.$$click=()=>u("state"),O(D,"cursor","pointer"),c(D,(()=>{var C=P(()=>r()==="state");return()=>C()&&(i()==="asc"?"▲":"▼")})(),null),I.$$click=()=>u("created"),O(I,"cursor","pointer"),c(I,(()=>{var C=P(()=>r()==="created");return()=>C()&&(i()==="asc"?"▲":"▼")})(),null),c(K,b(Z,{get each(){return h()},children:C=>[(()=>{var N=vt(),M=N.firstChild,je=M.firstChild,oe=M.nextSibling,ce=oe.nextSibling,Ee=ce.firstChild,ae=ce.nextSibling,ue=ae.nextSibling,Te=ue.firstChild,Ae=ue.nextSibling;return N.$$click=()=>s(C.ticket_id),O(N,"cursor","pointer"),c(je,()=>C.ticket_id),c(oe,()=>C.title),c(Ee,()=>C.current_stage),c(ae,()=>g(C.state)),c(Te,()=>new Date(C.created_at).toLocaleString()),c(Ae,()=>a(C.priority)),B(Nt=>O(N,"background-color",t()===C.ticket_id?"var(--pico-table-row-selected-background-color, rgba(0,0,0,0.1))":void 0)),N})(),b(v,{get when(){return t()===C.ticket_id},get children(){var N=xt(),M=N.firstChild;return c(M,b(wt,{ticket:C,get projectId(){return e.projectId}})),N}})]})),j}}),null),_})()}Ce(["click"]);var Et=m('<button aria-label="Toggle theme">');function Tt(){const[e,t]=L(!0);ie(()=>{const r=window.matchMedia("(prefers-color-scheme: dark)").matches,l=localStorage.getItem("theme"),i=l==="dark"||!l&&r;t(i),document.documentElement.setAttribute("data-theme",i?"dark":"light")});function n(){const r=!e();t(r);const l=r?"dark":"light";document.documentElement.setAttribute("data-theme",l),localStorage.setItem("theme",l)}return(()=>{var r=Et();return r.$$click=n,O(r,"width","auto"),O(r,"padding","0.5rem 1rem"),c(r,()=>e()?"☀️ Light":"🌙 Dark"),B(()=>ve(r,"title",e()?"Switch to light mode":"Switch to dark mode")),r})()}Ce(["click"]);var At=m("<article aria-label=Error><p><strong>Error:</strong> "),Pt=m("<article aria-busy=true>Loading projects..."),Lt=m("<article><p>No projects found. Create a project using the MCP server to get started."),Dt=m("<div class=container><header><hgroup><h1>Vibe Ensemble Dashboard</h1><p>Multi-agent coordination monitoring</p></hgroup></header><footer><small>Vibe Ensemble MCP v1.0.0 | <a href=https://github.com/siy/vibe-ensemble-mcp target=_blank>GitHub");function It(){const[e,t]=L([]),[n,r]=L(null),[l,i]=L(null),[o,s]=L([]),[u,h]=L(!0),[g,a]=L(null);ie(async()=>{try{const d=await Je();t(d),h(!1),d.length>0&&_(d[0].repository_name)}catch(d){a(d.message),h(!1)}const $=ze(d=>{try{const f=JSON.parse(d.data);console.log("SSE event:",f),n()&&(f.event_type==="ticket_created"||f.event_type==="ticket_updated"||f.event_type==="ticket_closed")&&S(n())}catch(f){console.error("Failed to parse SSE event:",f)}});me($)});async function _($){h(!0),a(null),r($);try{const[d,f]=await Promise.all([Ke($),ge($)]);i(d),s(f)}catch(d){a(d.message)}finally{h(!1)}}async function S($){try{const d=await ge($);s(d)}catch(d){console.error("Failed to reload tickets:",d)}}return(()=>{var $=Dt(),d=$.firstChild;d.firstChild;var f=d.nextSibling;return c(d,b(Tt,{}),null),c($,b(v,{get when(){return g()},get children(){var j=At(),y=j.firstChild,k=y.firstChild;return k.nextSibling,c(y,g,null),j}}),f),c($,b(v,{get when(){return P(()=>!!u())()&&e().length===0},get children(){return Pt()}}),f),c($,b(v,{get when(){return P(()=>!u()&&e().length===0)()&&!g()},get children(){return Lt()}}),f),c($,b(v,{get when(){return e().length>0},get children(){return[b(Ye,{get projects(){return e()},get selectedProjectId(){return n()},onSelect:_}),b(v,{get when(){return l()},get children(){return[b(ot,{get project(){return l()}}),b(jt,{get tickets(){return o()},get projectId(){return n()},get loading(){return u()}})]}})]}}),f),$})()}const xe=document.getElementById("root");if(!xe)throw new Error("Root element not found. Did you forget to add it to your index.html? Or maybe the id attribute got misspelled?");Ge(()=>b(It,{}),xe);
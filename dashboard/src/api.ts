@@ -46,7 +46,11 @@ export interface TicketWithComments {
   comments: Comment[];
 }
 
-const API_BASE = '/api';
+// Path prefix the server is mounted under (injected into index.html when configured)
+const BASE_PATH: string =
+  (window as { __VIBE_BASE_PATH__?: string }).__VIBE_BASE_PATH__ ?? '';
+
+const API_BASE = `${BASE_PATH}/api`;
 
 export async function fetchProjects(): Promise<Project[]> {
   const response = await fetch(`${API_BASE}/projects`);
@@ -86,7 +90,7 @@ export async function fetchTicketWithComments(
 }
 
 export function subscribeToEvents(callback: (event: MessageEvent) => void): () => void {
-  const eventSource = new EventSource('/sse');
+  const eventSource = new EventSource(`${BASE_PATH}/sse`);
 
   eventSource.onmessage = callback;
 
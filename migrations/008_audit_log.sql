@@ -0,0 +1,16 @@
+-- Migration 008: Add audit_log table for security-relevant operations
+-- Records who did what, with a digest of the parameters and the outcome.
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    actor TEXT NOT NULL,
+    session_id TEXT,
+    method TEXT NOT NULL,
+    params_digest TEXT,
+    outcome TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_actor ON audit_log(actor);
+CREATE INDEX IF NOT EXISTS idx_audit_log_method ON audit_log(method);
@@ -0,0 +1,13 @@
+-- Migration 009: Add ticket templates and custom field storage on tickets
+-- Templates define default priority and required custom fields enforced at creation.
+
+CREATE TABLE IF NOT EXISTS ticket_templates (
+    name TEXT PRIMARY KEY,
+    description TEXT,
+    default_priority TEXT NOT NULL DEFAULT 'medium' CHECK (default_priority IN ('low', 'medium', 'high', 'urgent')),
+    required_fields TEXT NOT NULL DEFAULT '[]', -- JSON array of {name, type}
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+ALTER TABLE tickets ADD COLUMN custom_fields TEXT; -- JSON object
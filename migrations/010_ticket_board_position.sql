@@ -0,0 +1,6 @@
+-- Migration 010: Add manual ordering for the dashboard ticket board
+-- board_position orders tickets within a board column; NULL sorts after positioned tickets.
+
+ALTER TABLE tickets ADD COLUMN board_position INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_tickets_project_board_position ON tickets(project_id, board_position);
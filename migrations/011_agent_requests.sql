@@ -0,0 +1,19 @@
+-- Migration 011: Add agent_requests table for correlated request/response between agents
+-- A request stays pending until the target responds, rejects it, or its deadline passes.
+
+CREATE TABLE IF NOT EXISTS agent_requests (
+    correlation_id TEXT PRIMARY KEY,
+    requester TEXT NOT NULL,
+    target TEXT NOT NULL,
+    ticket_id TEXT,
+    payload TEXT NOT NULL,
+    response TEXT,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'answered', 'expired', 'rejected')),
+    deadline TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    responded_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_agent_requests_status_deadline ON agent_requests(status, deadline);
+CREATE INDEX IF NOT EXISTS idx_agent_requests_target ON agent_requests(target);
+CREATE INDEX IF NOT EXISTS idx_agent_requests_requester ON agent_requests(requester);
@@ -0,0 +1,12 @@
+-- Migration 012: Add SLA tracking to tickets
+-- due_at is either given explicitly or derived from sla_minutes at creation.
+-- sla_warned_state / sla_breached_state record the state an escalation fired in,
+-- so each escalation fires at most once per ticket state.
+
+ALTER TABLE tickets ADD COLUMN due_at TEXT;
+ALTER TABLE tickets ADD COLUMN sla_minutes INTEGER;
+ALTER TABLE tickets ADD COLUMN sla_breached BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE tickets ADD COLUMN sla_warned_state TEXT;
+ALTER TABLE tickets ADD COLUMN sla_breached_state TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_tickets_due_at ON tickets(due_at) WHERE due_at IS NOT NULL;
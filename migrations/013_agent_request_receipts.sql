@@ -0,0 +1,7 @@
+-- Migration 013: Track delivery and acknowledgement of agent requests
+-- delivered_at is set when the target fetches the request, acknowledged_at when it confirms receipt.
+
+ALTER TABLE agent_requests ADD COLUMN delivered_at TEXT;
+ALTER TABLE agent_requests ADD COLUMN acknowledged_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_agent_requests_target_ack ON agent_requests(target, acknowledged_at);
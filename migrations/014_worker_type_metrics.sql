@@ -0,0 +1,26 @@
+-- Migration 014: Performance metrics per worker type, fed by worker outcomes
+-- worker_type_metrics holds all-time aggregates; worker_type_outcomes keeps the
+-- individual outcomes of the last 30 days for the rolling window.
+
+CREATE TABLE IF NOT EXISTS worker_type_metrics (
+    project_id TEXT NOT NULL,
+    worker_type TEXT NOT NULL,
+    success_count INTEGER NOT NULL DEFAULT 0,
+    failure_count INTEGER NOT NULL DEFAULT 0,
+    total_success_secs INTEGER NOT NULL DEFAULT 0,
+    last_outcome_at TEXT,
+    PRIMARY KEY (project_id, worker_type)
+);
+
+CREATE TABLE IF NOT EXISTS worker_type_outcomes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id TEXT NOT NULL,
+    worker_type TEXT NOT NULL,
+    ticket_id TEXT NOT NULL,
+    outcome TEXT NOT NULL CHECK (outcome IN ('success', 'failure')),
+    duration_secs INTEGER,
+    recorded_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_worker_type_outcomes_type_time
+    ON worker_type_outcomes(project_id, worker_type, recorded_at);
@@ -0,0 +1,23 @@
+-- Migration 015: Recurring ticket schedules
+-- A background scheduler creates a ticket from each enabled schedule when next_run_at passes.
+
+CREATE TABLE IF NOT EXISTS ticket_schedules (
+    name TEXT PRIMARY KEY,
+    project_id TEXT NOT NULL REFERENCES projects(repository_name) ON DELETE CASCADE,
+    cron TEXT NOT NULL, -- @daily, @weekly or five-field "0 9 * * MON"; evaluated in UTC
+    template_name TEXT,
+    title TEXT NOT NULL,
+    description TEXT,
+    priority TEXT CHECK (priority IN ('low', 'medium', 'high', 'urgent')),
+    custom_fields TEXT, -- JSON object
+    initial_stage TEXT NOT NULL, -- worker type the created ticket is queued for
+    enabled INTEGER NOT NULL DEFAULT 1,
+    skip_if_open INTEGER NOT NULL DEFAULT 1, -- no new ticket while the previous one is open
+    last_run_at TEXT,
+    last_ticket_id TEXT,
+    next_run_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_schedules_due ON ticket_schedules(enabled, next_run_at);
@@ -0,0 +1,27 @@
+-- Migration 016: Worker run history and per-worker-type spawn overrides
+-- Each spawned worker process gets a row recording the sandbox it ran with, spawn
+-- policy violations, and whether it had to be terminated for exceeding its runtime.
+
+CREATE TABLE IF NOT EXISTS worker_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    worker_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    worker_type TEXT NOT NULL,
+    ticket_id TEXT NOT NULL,
+    pid INTEGER,
+    working_dir TEXT NOT NULL,
+    max_runtime_secs INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'completed', 'failed', 'killed')),
+    exit_status TEXT,
+    -- How the runtime limit was enforced: 'terminated' (SIGTERM) or 'killed' (SIGKILL)
+    enforcement TEXT,
+    policy_violations TEXT, -- JSON array of messages
+    started_at TEXT NOT NULL DEFAULT (datetime('now')),
+    finished_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_worker_runs_ticket ON worker_runs(ticket_id, started_at);
+CREATE INDEX IF NOT EXISTS idx_worker_runs_project ON worker_runs(project_id, started_at);
+
+-- JSON object with the spawn policy fields this worker type overrides
+ALTER TABLE worker_types ADD COLUMN spawn_overrides TEXT;
@@ -0,0 +1,33 @@
+-- Migration 017: Tags as first-class entities
+-- Canonical tag names (trimmed, lowercase) with optional colors, linked to tickets through
+-- a join table so renames and merges update every ticket at once.
+
+CREATE TABLE IF NOT EXISTS tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    color TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS ticket_tags (
+    ticket_id TEXT NOT NULL REFERENCES tickets(ticket_id) ON DELETE CASCADE,
+    tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (ticket_id, tag_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_tags_tag ON ticket_tags(tag_id);
+
+-- Backfill from the free-form "tags" custom field (an array of strings, or a single string)
+INSERT OR IGNORE INTO tags (name)
+SELECT DISTINCT lower(trim(tag.value))
+FROM tickets t,
+     json_each(CASE WHEN json_valid(t.custom_fields) THEN t.custom_fields ELSE '{}' END, '$.tags') tag
+WHERE tag.type = 'text' AND trim(tag.value) <> '';
+
+INSERT OR IGNORE INTO ticket_tags (ticket_id, tag_id)
+SELECT t.ticket_id, tags.id
+FROM tickets t,
+     json_each(CASE WHEN json_valid(t.custom_fields) THEN t.custom_fields ELSE '{}' END, '$.tags') tag
+JOIN tags ON tags.name = lower(trim(tag.value))
+WHERE tag.type = 'text';
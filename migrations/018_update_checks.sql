@@ -0,0 +1,11 @@
+-- Migration 018: Last update check per release channel
+-- Lets restarts within the check interval reuse the previous result instead of
+-- querying GitHub again.
+
+CREATE TABLE IF NOT EXISTS update_checks (
+    channel TEXT PRIMARY KEY CHECK (channel IN ('stable', 'beta')),
+    checked_at TEXT NOT NULL DEFAULT (datetime('now')),
+    -- Newest verified release on the channel, if it is newer than the running version
+    latest_version TEXT,
+    release_url TEXT
+);
@@ -0,0 +1,5 @@
+-- Migration 020: Per-project coordination settings
+-- JSON object overriding global defaults for one project; NULL means every
+-- setting falls back to the server configuration.
+
+ALTER TABLE projects ADD COLUMN project_settings TEXT;
@@ -0,0 +1,5 @@
+-- Indexes covering the dashboard counts in GET /api/stats
+CREATE INDEX IF NOT EXISTS idx_tickets_project_state ON tickets(project_id, state);
+CREATE INDEX IF NOT EXISTS idx_tickets_project_priority ON tickets(project_id, priority);
+CREATE INDEX IF NOT EXISTS idx_workers_project_status ON workers(project_id, status);
+CREATE INDEX IF NOT EXISTS idx_comments_created_at ON comments(created_at);
@@ -0,0 +1,93 @@
+-- Full-text search over tickets for GET /api/tickets/search and the search_tickets tool.
+-- The description is the stage 0 comment; every other comment, including the resolution
+-- written when a ticket is closed, is indexed as comments.
+
+-- Ticket rowids may change on VACUUM, so index rows are keyed by a stable doc id
+CREATE TABLE IF NOT EXISTS ticket_search_docs (
+    doc_id INTEGER PRIMARY KEY,
+    ticket_id TEXT NOT NULL UNIQUE
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS ticket_search USING fts5(
+    title,
+    description,
+    comments,
+    tokenize = 'porter unicode61'
+);
+
+CREATE INDEX IF NOT EXISTS idx_comments_ticket_id ON comments(ticket_id);
+
+CREATE TRIGGER IF NOT EXISTS ticket_search_after_ticket_insert AFTER INSERT ON tickets
+BEGIN
+    INSERT INTO ticket_search_docs (ticket_id) VALUES (NEW.ticket_id);
+    INSERT INTO ticket_search (rowid, title, description, comments)
+    SELECT doc_id, NEW.title, '', '' FROM ticket_search_docs WHERE ticket_id = NEW.ticket_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS ticket_search_after_title_update AFTER UPDATE OF title ON tickets
+BEGIN
+    UPDATE ticket_search SET title = NEW.title
+    WHERE rowid = (SELECT doc_id FROM ticket_search_docs WHERE ticket_id = NEW.ticket_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS ticket_search_after_ticket_delete AFTER DELETE ON tickets
+BEGIN
+    DELETE FROM ticket_search
+    WHERE rowid = (SELECT doc_id FROM ticket_search_docs WHERE ticket_id = OLD.ticket_id);
+    DELETE FROM ticket_search_docs WHERE ticket_id = OLD.ticket_id;
+END;
+
+-- New comments are appended; edits and deletions rebuild the ticket's text
+CREATE TRIGGER IF NOT EXISTS ticket_search_after_description_insert AFTER INSERT ON comments
+WHEN NEW.stage_number = 0
+BEGIN
+    UPDATE ticket_search SET description = CASE WHEN description = '' THEN NEW.content
+                                 ELSE description || char(10) || NEW.content END
+    WHERE rowid = (SELECT doc_id FROM ticket_search_docs WHERE ticket_id = NEW.ticket_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS ticket_search_after_comment_insert AFTER INSERT ON comments
+WHEN NEW.stage_number IS NOT 0
+BEGIN
+    UPDATE ticket_search SET comments = CASE WHEN comments = '' THEN NEW.content
+                              ELSE comments || char(10) || NEW.content END
+    WHERE rowid = (SELECT doc_id FROM ticket_search_docs WHERE ticket_id = NEW.ticket_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS ticket_search_after_comment_update
+AFTER UPDATE OF content, stage_number, ticket_id ON comments
+BEGIN
+    UPDATE ticket_search SET
+        description = COALESCE((SELECT group_concat(content, char(10)) FROM comments
+                                WHERE ticket_id = t.ticket_id AND stage_number = 0), ''),
+        comments = COALESCE((SELECT group_concat(content, char(10)) FROM comments
+                             WHERE ticket_id = t.ticket_id AND stage_number IS NOT 0), '')
+    FROM (SELECT doc_id, ticket_id FROM ticket_search_docs
+          WHERE ticket_id IN (OLD.ticket_id, NEW.ticket_id)) AS t
+    WHERE ticket_search.rowid = t.doc_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS ticket_search_after_comment_delete AFTER DELETE ON comments
+BEGIN
+    UPDATE ticket_search SET
+        description = COALESCE((SELECT group_concat(content, char(10)) FROM comments
+                                WHERE ticket_id = OLD.ticket_id AND stage_number = 0), ''),
+        comments = COALESCE((SELECT group_concat(content, char(10)) FROM comments
+                             WHERE ticket_id = OLD.ticket_id AND stage_number IS NOT 0), '')
+    WHERE rowid = (SELECT doc_id FROM ticket_search_docs WHERE ticket_id = OLD.ticket_id);
+END;
+
+-- Backfill existing tickets
+INSERT INTO ticket_search_docs (ticket_id)
+SELECT ticket_id FROM tickets
+WHERE ticket_id NOT IN (SELECT ticket_id FROM ticket_search_docs);
+
+INSERT INTO ticket_search (rowid, title, description, comments)
+SELECT d.doc_id,
+       t.title,
+       COALESCE((SELECT group_concat(content, char(10)) FROM comments
+                 WHERE ticket_id = t.ticket_id AND stage_number = 0), ''),
+       COALESCE((SELECT group_concat(content, char(10)) FROM comments
+                 WHERE ticket_id = t.ticket_id AND stage_number IS NOT 0), '')
+FROM tickets t
+JOIN ticket_search_docs d ON d.ticket_id = t.ticket_id;
@@ -0,0 +1,68 @@
+-- Migration 024: Ticket estimates and history for burndown charts
+-- ticket_history keeps a snapshot of a ticket's state, estimate and processing worker
+-- after every change to them, so burndowns reflect what was open on each day rather
+-- than today's state. actual_minutes accumulates the time between a worker claiming a
+-- ticket and releasing it.
+
+ALTER TABLE tickets ADD COLUMN estimate_minutes INTEGER;
+ALTER TABLE tickets ADD COLUMN actual_minutes INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS ticket_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ticket_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    change TEXT NOT NULL CHECK (change IN ('created', 'updated', 'assigned', 'released')),
+    state TEXT NOT NULL,
+    estimate_minutes INTEGER,
+    processing_worker_id TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (ticket_id) REFERENCES tickets(ticket_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_history_project ON ticket_history(project_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_ticket_history_ticket ON ticket_history(ticket_id, change);
+
+-- Existing tickets: their creation, and their current state when it isn't the initial one
+INSERT INTO ticket_history (ticket_id, project_id, change, state, created_at)
+SELECT ticket_id, project_id, 'created', 'open', created_at FROM tickets;
+
+INSERT INTO ticket_history (ticket_id, project_id, change, state, processing_worker_id, created_at)
+SELECT ticket_id, project_id,
+       CASE WHEN processing_worker_id IS NOT NULL THEN 'assigned' ELSE 'updated' END,
+       state, processing_worker_id, COALESCE(closed_at, updated_at)
+FROM tickets
+WHERE state != 'open' OR processing_worker_id IS NOT NULL;
+
+CREATE TRIGGER IF NOT EXISTS ticket_history_after_insert AFTER INSERT ON tickets
+BEGIN
+    INSERT INTO ticket_history (ticket_id, project_id, change, state, estimate_minutes, processing_worker_id)
+    VALUES (NEW.ticket_id, NEW.project_id, 'created', NEW.state, NEW.estimate_minutes, NEW.processing_worker_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS ticket_history_after_update
+AFTER UPDATE OF state, estimate_minutes ON tickets
+WHEN (OLD.state IS NOT NEW.state OR OLD.estimate_minutes IS NOT NEW.estimate_minutes)
+ AND OLD.processing_worker_id IS NEW.processing_worker_id
+BEGIN
+    INSERT INTO ticket_history (ticket_id, project_id, change, state, estimate_minutes, processing_worker_id)
+    VALUES (NEW.ticket_id, NEW.project_id, 'updated', NEW.state, NEW.estimate_minutes, NEW.processing_worker_id);
+END;
+
+-- A released assignment adds the time since it was claimed to actual_minutes
+CREATE TRIGGER IF NOT EXISTS ticket_history_after_worker_change
+AFTER UPDATE OF processing_worker_id ON tickets
+WHEN OLD.processing_worker_id IS NOT NEW.processing_worker_id
+BEGIN
+    UPDATE tickets
+    SET actual_minutes = actual_minutes + COALESCE((
+        SELECT CAST(ROUND((julianday('now') - julianday(MAX(created_at))) * 1440) AS INTEGER)
+        FROM ticket_history
+        WHERE ticket_id = NEW.ticket_id AND change = 'assigned'
+    ), 0)
+    WHERE ticket_id = NEW.ticket_id AND OLD.processing_worker_id IS NOT NULL;
+
+    INSERT INTO ticket_history (ticket_id, project_id, change, state, estimate_minutes, processing_worker_id)
+    VALUES (NEW.ticket_id, NEW.project_id,
+            CASE WHEN NEW.processing_worker_id IS NOT NULL THEN 'assigned' ELSE 'released' END,
+            NEW.state, NEW.estimate_minutes, NEW.processing_worker_id);
+END;
@@ -0,0 +1,17 @@
+-- Migration 025: Follow-up instructions sent to running workers
+-- Each instruction written to a worker's stdin is kept with the run it was sent to, in
+-- the order the worker received it.
+
+CREATE TABLE IF NOT EXISTS worker_run_inputs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_id INTEGER NOT NULL,
+    input_id TEXT NOT NULL,
+    -- Position of the instruction among those the run received, starting at 1
+    seq INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    sent_by TEXT,
+    sent_at TEXT NOT NULL,
+    FOREIGN KEY (run_id) REFERENCES worker_runs(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_worker_run_inputs_run ON worker_run_inputs(run_id, seq);
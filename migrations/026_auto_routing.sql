@@ -0,0 +1,7 @@
+-- Migration 026: Capability-based routing of new tickets
+-- capabilities lists the tags (as a JSON array of canonical names) a worker type handles;
+-- projects with auto_assign start new tickets in the worker type whose capabilities best
+-- cover the ticket's tags. The decision is noted on the ticket's 'created' history row.
+
+ALTER TABLE worker_types ADD COLUMN capabilities TEXT;
+ALTER TABLE ticket_history ADD COLUMN note TEXT;
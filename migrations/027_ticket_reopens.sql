@@ -0,0 +1,28 @@
+-- Migration 027: Count ticket reopens
+-- reopen_count grows whenever a closed ticket leaves the closed state, whichever code
+-- path changes it. flaky_at records when a ticket crossed the flaky threshold and was
+-- tagged, so each ticket is reported once.
+
+ALTER TABLE tickets ADD COLUMN reopen_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE tickets ADD COLUMN flaky_at TEXT;
+
+-- Existing tickets: reopens recorded in their history so far
+UPDATE tickets
+SET reopen_count = (
+    SELECT COUNT(*)
+    FROM (
+        SELECT state, LAG(state) OVER (ORDER BY created_at, id) AS previous_state
+        FROM ticket_history
+        WHERE ticket_history.ticket_id = tickets.ticket_id
+    )
+    WHERE previous_state = 'closed' AND state != 'closed'
+);
+
+CREATE INDEX IF NOT EXISTS idx_tickets_reopen_count ON tickets(reopen_count) WHERE reopen_count > 0;
+
+CREATE TRIGGER IF NOT EXISTS tickets_count_reopens
+AFTER UPDATE OF state ON tickets
+WHEN OLD.state = 'closed' AND NEW.state != 'closed'
+BEGIN
+    UPDATE tickets SET reopen_count = reopen_count + 1 WHERE ticket_id = NEW.ticket_id;
+END;
@@ -0,0 +1,21 @@
+-- Migration 028: Summaries of long ticket comment threads
+-- A coordinator condenses a ticket's comments up to through_comment_id into a summary;
+-- readers then get the summary plus only the newer comments. A newer summary supersedes
+-- the previous one, which is kept with superseded_at set.
+
+CREATE TABLE IF NOT EXISTS thread_summaries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ticket_id TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    from_comment_id INTEGER NOT NULL,
+    through_comment_id INTEGER NOT NULL,
+    comment_count INTEGER NOT NULL,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    superseded_at TEXT,
+    FOREIGN KEY (ticket_id) REFERENCES tickets(ticket_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_thread_summaries_ticket ON thread_summaries(ticket_id, id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_thread_summaries_current
+    ON thread_summaries(ticket_id) WHERE superseded_at IS NULL;
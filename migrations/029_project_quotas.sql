@@ -0,0 +1,58 @@
+-- Migration 029: Per-project quotas
+-- project_usage keeps each project's count of tickets that are not closed, maintained by
+-- triggers on every insert, state change and delete so quota checks never count tickets.
+-- Comments per hour are counted through idx_comments_created_at instead. warned_*_at records
+-- when a project passed 90% of a quota, so each crossing is reported once.
+
+CREATE TABLE IF NOT EXISTS project_usage (
+    project_id TEXT PRIMARY KEY REFERENCES projects(repository_name) ON DELETE CASCADE,
+    open_tickets INTEGER NOT NULL DEFAULT 0,
+    warned_open_tickets_at TEXT,
+    warned_comments_per_hour_at TEXT
+);
+
+INSERT OR IGNORE INTO project_usage (project_id, open_tickets)
+SELECT p.repository_name,
+       (SELECT COUNT(*) FROM tickets t WHERE t.project_id = p.repository_name AND t.state != 'closed')
+FROM projects p;
+
+CREATE TRIGGER IF NOT EXISTS project_usage_after_project_insert AFTER INSERT ON projects
+BEGIN
+    INSERT OR IGNORE INTO project_usage (project_id) VALUES (NEW.repository_name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS project_usage_after_ticket_insert AFTER INSERT ON tickets
+WHEN NEW.state != 'closed'
+BEGIN
+    UPDATE project_usage SET open_tickets = open_tickets + 1 WHERE project_id = NEW.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS project_usage_after_ticket_update
+AFTER UPDATE OF state, project_id ON tickets
+WHEN (OLD.state != 'closed') != (NEW.state != 'closed') OR OLD.project_id != NEW.project_id
+BEGIN
+    UPDATE project_usage SET open_tickets = open_tickets - 1
+    WHERE project_id = OLD.project_id AND OLD.state != 'closed';
+    UPDATE project_usage SET open_tickets = open_tickets + 1
+    WHERE project_id = NEW.project_id AND NEW.state != 'closed';
+END;
+
+CREATE TRIGGER IF NOT EXISTS project_usage_after_ticket_delete AFTER DELETE ON tickets
+WHEN OLD.state != 'closed'
+BEGIN
+    UPDATE project_usage SET open_tickets = open_tickets - 1 WHERE project_id = OLD.project_id;
+END;
+
+-- Temporary limits set by the coordinator; the newest unexpired one for a quota applies
+CREATE TABLE IF NOT EXISTS quota_overrides (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id TEXT NOT NULL REFERENCES projects(repository_name) ON DELETE CASCADE,
+    quota TEXT NOT NULL CHECK (quota IN ('open_tickets', 'comments_per_hour')),
+    quota_limit INTEGER NOT NULL,
+    reason TEXT,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    expires_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_quota_overrides_project ON quota_overrides(project_id, quota, expires_at);
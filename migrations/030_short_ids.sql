@@ -0,0 +1,57 @@
+-- Migration 030: Short ids for tickets and slugs for worker types
+-- Each ticket gets a per-project sequence number, shown with the project prefix as its
+-- short id (e.g. "VEM-142"). projects.last_ticket_number holds the sequence, so numbers
+-- are never reused after a delete; a trigger assigns them on every insert, in the same
+-- transaction. Prefixes are not unique, so a short id alone can name tickets in several
+-- projects.
+
+ALTER TABLE projects ADD COLUMN last_ticket_number INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE tickets ADD COLUMN number INTEGER;
+ALTER TABLE tickets ADD COLUMN short_id TEXT;
+
+-- Existing tickets are numbered in creation order
+UPDATE tickets
+SET number = numbered.n
+FROM (
+    SELECT ticket_id, ROW_NUMBER() OVER (PARTITION BY project_id ORDER BY created_at, rowid) AS n
+    FROM tickets
+) AS numbered
+WHERE numbered.ticket_id = tickets.ticket_id;
+
+UPDATE tickets
+SET short_id = (SELECT upper(project_prefix) FROM projects WHERE repository_name = tickets.project_id)
+    || '-' || number;
+
+UPDATE projects
+SET last_ticket_number = (SELECT COALESCE(MAX(number), 0) FROM tickets WHERE project_id = projects.repository_name);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tickets_project_number ON tickets(project_id, number);
+CREATE INDEX IF NOT EXISTS idx_tickets_short_id ON tickets(short_id);
+
+CREATE TRIGGER IF NOT EXISTS tickets_assign_number AFTER INSERT ON tickets
+BEGIN
+    UPDATE projects SET last_ticket_number = last_ticket_number + 1
+    WHERE repository_name = NEW.project_id;
+    UPDATE tickets
+    SET number = (SELECT last_ticket_number FROM projects WHERE repository_name = NEW.project_id),
+        short_id = (SELECT upper(project_prefix) || '-' || last_ticket_number
+                    FROM projects WHERE repository_name = NEW.project_id)
+    WHERE ticket_id = NEW.ticket_id;
+END;
+
+-- Worker types get a slug unique across projects: the name in lowercase with spaces and
+-- underscores as dashes, and "-2", "-3", ... for later worker types with the same one
+ALTER TABLE worker_types ADD COLUMN slug TEXT;
+
+UPDATE worker_types
+SET slug = ranked.base || CASE WHEN ranked.n > 1 THEN '-' || ranked.n ELSE '' END
+FROM (
+    SELECT id, base, ROW_NUMBER() OVER (PARTITION BY base ORDER BY created_at, id) AS n
+    FROM (
+        SELECT id, created_at, lower(replace(replace(trim(worker_type), ' ', '-'), '_', '-')) AS base
+        FROM worker_types
+    )
+) AS ranked
+WHERE ranked.id = worker_types.id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_worker_types_slug ON worker_types(slug);
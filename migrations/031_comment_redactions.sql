@@ -0,0 +1,5 @@
+-- Migration 031: Count secrets masked in stored comments
+-- With --redact-at-rest, secrets in a comment are replaced by a marker before it is stored;
+-- redactions records how many, so readers know the text was altered.
+
+ALTER TABLE comments ADD COLUMN redactions INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- Migration 032: Warm worker runs
+-- Whether a run was served by an idle worker from the warm pool instead of a new process.
+
+ALTER TABLE worker_runs ADD COLUMN warm INTEGER NOT NULL DEFAULT 0;
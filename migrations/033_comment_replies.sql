@@ -0,0 +1,14 @@
+-- Migration 033: Comment replies
+-- reply_to_comment_id links a comment to the comment on the same ticket it answers, so
+-- threads can be shown as trees. Deleting a comment moves its replies up to its own parent
+-- instead of leaving them pointing at nothing.
+
+ALTER TABLE comments ADD COLUMN reply_to_comment_id INTEGER REFERENCES comments(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_comments_reply_to ON comments(reply_to_comment_id);
+
+CREATE TRIGGER IF NOT EXISTS comments_reparent_replies BEFORE DELETE ON comments
+BEGIN
+    UPDATE comments SET reply_to_comment_id = OLD.reply_to_comment_id
+    WHERE reply_to_comment_id = OLD.id;
+END;
@@ -0,0 +1,20 @@
+-- Migration 034: Coordinator digests
+-- A digest rolls up the activity between period_start and period_end (tickets created and
+-- changed, failed and killed workers, claim conflicts, SLA escalations) into one
+-- notification. content holds the digest as JSON. sla_warned_at / sla_breached_at record
+-- when each escalation fired, so a digest can list the ones in its period.
+
+CREATE TABLE IF NOT EXISTS digests (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    period_start TEXT NOT NULL,
+    period_end TEXT NOT NULL,
+    item_count INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+ALTER TABLE tickets ADD COLUMN sla_warned_at TEXT;
+ALTER TABLE tickets ADD COLUMN sla_breached_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_tickets_updated_at ON tickets(updated_at);
+CREATE INDEX IF NOT EXISTS idx_worker_runs_finished_at ON worker_runs(finished_at) WHERE finished_at IS NOT NULL;
@@ -0,0 +1,8 @@
+-- Migration 035: Duplicate tickets
+-- duplicate_of links a ticket closed as a duplicate to the ticket it duplicates. New
+-- tickets are compared with a project's open tickets by title and tags; the title search
+-- index narrows the comparison to tickets sharing a title word.
+
+ALTER TABLE tickets ADD COLUMN duplicate_of TEXT REFERENCES tickets(ticket_id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_tickets_duplicate_of ON tickets(duplicate_of) WHERE duplicate_of IS NOT NULL;
@@ -0,0 +1,5 @@
+-- Migration 036: Workspace boundary violations
+-- Files outside a worker's working directory and allowed paths that changed during its run,
+-- as a JSON array of {path, change}. A run with any is failed.
+
+ALTER TABLE worker_runs ADD COLUMN boundary_violations TEXT;
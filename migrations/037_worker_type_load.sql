@@ -0,0 +1,116 @@
+-- Migration 037: Worker type load
+-- worker_type_load keeps each worker type's count of open tickets in its stage, maintained by
+-- triggers on every insert, stage or state change and delete like project_usage. A worker
+-- type with a capacity (max_open_tickets) turns busy when the count reaches it and online
+-- again when it drops below; these derived changes are recorded in
+-- worker_type_status_history apart from reported ones. Reported maintenance and error
+-- statuses are left alone by the triggers.
+
+CREATE TABLE IF NOT EXISTS worker_type_load (
+    project_id TEXT NOT NULL,
+    worker_type TEXT NOT NULL,
+    open_tickets INTEGER NOT NULL DEFAULT 0,
+    max_open_tickets INTEGER,
+    status TEXT NOT NULL DEFAULT 'online' CHECK (status IN ('online', 'busy', 'maintenance', 'error')),
+    status_source TEXT NOT NULL DEFAULT 'derived' CHECK (status_source IN ('derived', 'reported')),
+    status_reason TEXT,
+    status_changed_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (project_id, worker_type),
+    FOREIGN KEY (project_id, worker_type) REFERENCES worker_types(project_id, worker_type) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS worker_type_status_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id TEXT NOT NULL,
+    worker_type TEXT NOT NULL,
+    status TEXT NOT NULL,
+    source TEXT NOT NULL CHECK (source IN ('derived', 'reported')),
+    reason TEXT,
+    reported_by TEXT,
+    open_tickets INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (project_id, worker_type) REFERENCES worker_types(project_id, worker_type) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_worker_type_status_history ON worker_type_status_history(project_id, worker_type, id);
+
+INSERT OR IGNORE INTO worker_type_load (project_id, worker_type, open_tickets)
+SELECT w.project_id, w.worker_type,
+       (SELECT COUNT(*) FROM tickets t
+        WHERE t.project_id = w.project_id AND t.current_stage = w.worker_type AND t.state = 'open')
+FROM worker_types w;
+
+CREATE TRIGGER IF NOT EXISTS worker_type_load_after_worker_type_insert AFTER INSERT ON worker_types
+BEGIN
+    INSERT OR IGNORE INTO worker_type_load (project_id, worker_type, open_tickets)
+    VALUES (
+        NEW.project_id,
+        NEW.worker_type,
+        (SELECT COUNT(*) FROM tickets t
+         WHERE t.project_id = NEW.project_id AND t.current_stage = NEW.worker_type AND t.state = 'open')
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS worker_type_load_after_ticket_insert AFTER INSERT ON tickets
+WHEN NEW.state = 'open'
+BEGIN
+    UPDATE worker_type_load SET open_tickets = open_tickets + 1
+    WHERE project_id = NEW.project_id AND worker_type = NEW.current_stage;
+END;
+
+CREATE TRIGGER IF NOT EXISTS worker_type_load_after_ticket_update
+AFTER UPDATE OF state, current_stage, project_id ON tickets
+WHEN (OLD.state = 'open') != (NEW.state = 'open')
+  OR OLD.current_stage != NEW.current_stage
+  OR OLD.project_id != NEW.project_id
+BEGIN
+    UPDATE worker_type_load SET open_tickets = open_tickets - 1
+    WHERE project_id = OLD.project_id AND worker_type = OLD.current_stage AND OLD.state = 'open';
+    UPDATE worker_type_load SET open_tickets = open_tickets + 1
+    WHERE project_id = NEW.project_id AND worker_type = NEW.current_stage AND NEW.state = 'open';
+END;
+
+CREATE TRIGGER IF NOT EXISTS worker_type_load_after_ticket_delete AFTER DELETE ON tickets
+WHEN OLD.state = 'open'
+BEGIN
+    UPDATE worker_type_load SET open_tickets = open_tickets - 1
+    WHERE project_id = OLD.project_id AND worker_type = OLD.current_stage;
+END;
+
+CREATE TRIGGER IF NOT EXISTS worker_type_load_at_capacity
+AFTER UPDATE OF open_tickets, max_open_tickets ON worker_type_load
+WHEN NEW.status = 'online' AND NEW.max_open_tickets IS NOT NULL
+  AND NEW.open_tickets >= NEW.max_open_tickets
+BEGIN
+    UPDATE worker_type_load
+    SET status = 'busy', status_source = 'derived',
+        status_reason = 'At capacity: ' || NEW.open_tickets || ' of ' || NEW.max_open_tickets || ' open tickets',
+        status_changed_at = datetime('now')
+    WHERE project_id = NEW.project_id AND worker_type = NEW.worker_type;
+    INSERT INTO worker_type_status_history (project_id, worker_type, status, source, reason, open_tickets)
+    VALUES (
+        NEW.project_id, NEW.worker_type, 'busy', 'derived',
+        'At capacity: ' || NEW.open_tickets || ' of ' || NEW.max_open_tickets || ' open tickets',
+        NEW.open_tickets
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS worker_type_load_below_capacity
+AFTER UPDATE OF open_tickets, max_open_tickets ON worker_type_load
+WHEN NEW.status = 'busy'
+  AND (NEW.max_open_tickets IS NULL OR NEW.open_tickets < NEW.max_open_tickets)
+BEGIN
+    UPDATE worker_type_load
+    SET status = 'online', status_source = 'derived',
+        status_reason = CASE WHEN NEW.max_open_tickets IS NULL THEN 'No capacity limit'
+            ELSE 'Below capacity: ' || NEW.open_tickets || ' of ' || NEW.max_open_tickets || ' open tickets' END,
+        status_changed_at = datetime('now')
+    WHERE project_id = NEW.project_id AND worker_type = NEW.worker_type;
+    INSERT INTO worker_type_status_history (project_id, worker_type, status, source, reason, open_tickets)
+    VALUES (
+        NEW.project_id, NEW.worker_type, 'online', 'derived',
+        CASE WHEN NEW.max_open_tickets IS NULL THEN 'No capacity limit'
+            ELSE 'Below capacity: ' || NEW.open_tickets || ' of ' || NEW.max_open_tickets || ' open tickets' END,
+        NEW.open_tickets
+    );
+END;
@@ -0,0 +1,141 @@
+-- Migration 038: Ticket state history for as-of reconstruction
+-- ticket_history rows now also record the ticket's title, stage, priority, dependency
+-- status and tags (a JSON array of names), and a row is added whenever one of them
+-- changes, so a ticket's state at any instant is its last row up to then. Rows written
+-- before this migration leave these columns NULL; a baseline row records every existing
+-- ticket as it is now. ticket_state_snapshots materialize every ticket of a project as of
+-- taken_at, folded from the history rows up to last_history_id, so reconstructions replay
+-- only the rows after the nearest snapshot.
+
+ALTER TABLE ticket_history ADD COLUMN title TEXT;
+ALTER TABLE ticket_history ADD COLUMN current_stage TEXT;
+ALTER TABLE ticket_history ADD COLUMN priority TEXT;
+ALTER TABLE ticket_history ADD COLUMN dependency_status TEXT;
+ALTER TABLE ticket_history ADD COLUMN tags TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_ticket_history_ticket_time ON ticket_history(ticket_id, created_at);
+
+INSERT INTO ticket_history
+    (ticket_id, project_id, change, state, estimate_minutes, processing_worker_id, note,
+     title, current_stage, priority, dependency_status, tags)
+SELECT t.ticket_id, t.project_id, 'updated', t.state, t.estimate_minutes, t.processing_worker_id,
+       'Baseline for as-of reconstruction',
+       t.title, t.current_stage, t.priority, t.dependency_status,
+       (SELECT json_group_array(name) FROM (
+            SELECT g.name FROM ticket_tags tt JOIN tags g ON g.id = tt.tag_id
+            WHERE tt.ticket_id = t.ticket_id ORDER BY g.name))
+FROM tickets t;
+
+DROP TRIGGER IF EXISTS ticket_history_after_insert;
+DROP TRIGGER IF EXISTS ticket_history_after_update;
+DROP TRIGGER IF EXISTS ticket_history_after_worker_change;
+
+CREATE TRIGGER IF NOT EXISTS ticket_history_after_insert AFTER INSERT ON tickets
+BEGIN
+    INSERT INTO ticket_history
+        (ticket_id, project_id, change, state, estimate_minutes, processing_worker_id,
+         title, current_stage, priority, dependency_status, tags)
+    VALUES (NEW.ticket_id, NEW.project_id, 'created', NEW.state, NEW.estimate_minutes,
+            NEW.processing_worker_id, NEW.title, NEW.current_stage, NEW.priority,
+            NEW.dependency_status, '[]');
+END;
+
+CREATE TRIGGER IF NOT EXISTS ticket_history_after_update
+AFTER UPDATE OF state, estimate_minutes, title, current_stage, priority, dependency_status ON tickets
+WHEN (OLD.state IS NOT NEW.state OR OLD.estimate_minutes IS NOT NEW.estimate_minutes
+      OR OLD.title IS NOT NEW.title OR OLD.current_stage IS NOT NEW.current_stage
+      OR OLD.priority IS NOT NEW.priority OR OLD.dependency_status IS NOT NEW.dependency_status)
+ AND OLD.processing_worker_id IS NEW.processing_worker_id
+BEGIN
+    INSERT INTO ticket_history
+        (ticket_id, project_id, change, state, estimate_minutes, processing_worker_id,
+         title, current_stage, priority, dependency_status, tags)
+    VALUES (NEW.ticket_id, NEW.project_id, 'updated', NEW.state, NEW.estimate_minutes,
+            NEW.processing_worker_id, NEW.title, NEW.current_stage, NEW.priority,
+            NEW.dependency_status,
+            (SELECT json_group_array(name) FROM (
+                 SELECT g.name FROM ticket_tags tt JOIN tags g ON g.id = tt.tag_id
+                 WHERE tt.ticket_id = NEW.ticket_id ORDER BY g.name)));
+END;
+
+-- A released assignment adds the time since it was claimed to actual_minutes
+CREATE TRIGGER IF NOT EXISTS ticket_history_after_worker_change
+AFTER UPDATE OF processing_worker_id ON tickets
+WHEN OLD.processing_worker_id IS NOT NEW.processing_worker_id
+BEGIN
+    UPDATE tickets
+    SET actual_minutes = actual_minutes + COALESCE((
+        SELECT CAST(ROUND((julianday('now') - julianday(MAX(created_at))) * 1440) AS INTEGER)
+        FROM ticket_history
+        WHERE ticket_id = NEW.ticket_id AND change = 'assigned'
+    ), 0)
+    WHERE ticket_id = NEW.ticket_id AND OLD.processing_worker_id IS NOT NULL;
+
+    INSERT INTO ticket_history
+        (ticket_id, project_id, change, state, estimate_minutes, processing_worker_id,
+         title, current_stage, priority, dependency_status, tags)
+    VALUES (NEW.ticket_id, NEW.project_id,
+            CASE WHEN NEW.processing_worker_id IS NOT NULL THEN 'assigned' ELSE 'released' END,
+            NEW.state, NEW.estimate_minutes, NEW.processing_worker_id, NEW.title,
+            NEW.current_stage, NEW.priority, NEW.dependency_status,
+            (SELECT json_group_array(name) FROM (
+                 SELECT g.name FROM ticket_tags tt JOIN tags g ON g.id = tt.tag_id
+                 WHERE tt.ticket_id = NEW.ticket_id ORDER BY g.name)));
+END;
+
+-- Tag changes, except the removals cascading from a deleted ticket
+CREATE TRIGGER IF NOT EXISTS ticket_history_after_tag_insert AFTER INSERT ON ticket_tags
+BEGIN
+    INSERT INTO ticket_history
+        (ticket_id, project_id, change, state, estimate_minutes, processing_worker_id,
+         title, current_stage, priority, dependency_status, tags)
+    SELECT t.ticket_id, t.project_id, 'updated', t.state, t.estimate_minutes,
+           t.processing_worker_id, t.title, t.current_stage, t.priority, t.dependency_status,
+           (SELECT json_group_array(name) FROM (
+                SELECT g.name FROM ticket_tags tt JOIN tags g ON g.id = tt.tag_id
+                WHERE tt.ticket_id = t.ticket_id ORDER BY g.name))
+    FROM tickets t
+    WHERE t.ticket_id = NEW.ticket_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS ticket_history_after_tag_delete AFTER DELETE ON ticket_tags
+BEGIN
+    INSERT INTO ticket_history
+        (ticket_id, project_id, change, state, estimate_minutes, processing_worker_id,
+         title, current_stage, priority, dependency_status, tags)
+    SELECT t.ticket_id, t.project_id, 'updated', t.state, t.estimate_minutes,
+           t.processing_worker_id, t.title, t.current_stage, t.priority, t.dependency_status,
+           (SELECT json_group_array(name) FROM (
+                SELECT g.name FROM ticket_tags tt JOIN tags g ON g.id = tt.tag_id
+                WHERE tt.ticket_id = t.ticket_id ORDER BY g.name))
+    FROM tickets t
+    WHERE t.ticket_id = OLD.ticket_id;
+END;
+
+CREATE TABLE IF NOT EXISTS ticket_state_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id TEXT NOT NULL,
+    taken_at TEXT NOT NULL,
+    last_history_id INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (project_id) REFERENCES projects(repository_name) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_state_snapshots_project ON ticket_state_snapshots(project_id, taken_at);
+
+CREATE TABLE IF NOT EXISTS ticket_state_snapshot_entries (
+    snapshot_id INTEGER NOT NULL,
+    ticket_id TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    changed_at TEXT NOT NULL,
+    state TEXT NOT NULL,
+    estimate_minutes INTEGER,
+    processing_worker_id TEXT,
+    title TEXT,
+    current_stage TEXT,
+    priority TEXT,
+    dependency_status TEXT,
+    tags TEXT,
+    PRIMARY KEY (snapshot_id, ticket_id),
+    FOREIGN KEY (snapshot_id) REFERENCES ticket_state_snapshots(id) ON DELETE CASCADE
+);
@@ -0,0 +1,10 @@
+-- Migration 039: Worker heartbeats
+-- Running workers report progress with report_worker_progress. last_heartbeat_at and
+-- progress hold the latest report; stalled_at is set when a running worker went quiet for
+-- longer than its stall threshold and cleared by its next report.
+
+ALTER TABLE worker_runs ADD COLUMN last_heartbeat_at TEXT;
+ALTER TABLE worker_runs ADD COLUMN progress TEXT;
+ALTER TABLE worker_runs ADD COLUMN stalled_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_worker_runs_worker ON worker_runs(worker_id, status);
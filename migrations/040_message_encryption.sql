@@ -0,0 +1,63 @@
+-- Field-level encryption of message content (--encrypt-messages). Each encrypted field
+-- records the id of the key it was sealed with; NULL means the field is plaintext.
+
+ALTER TABLE comments ADD COLUMN content_key_id TEXT;
+ALTER TABLE agent_requests ADD COLUMN payload_key_id TEXT;
+ALTER TABLE agent_requests ADD COLUMN response_key_id TEXT;
+
+-- Startup checks and `rekey` look for encrypted rows only
+CREATE INDEX IF NOT EXISTS idx_comments_content_key_id
+    ON comments(content_key_id) WHERE content_key_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_agent_requests_payload_key_id
+    ON agent_requests(payload_key_id) WHERE payload_key_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_agent_requests_response_key_id
+    ON agent_requests(response_key_id) WHERE response_key_id IS NOT NULL;
+
+-- Encrypted comments are left out of full-text search; ciphertext matches nothing
+DROP TRIGGER IF EXISTS ticket_search_after_description_insert;
+DROP TRIGGER IF EXISTS ticket_search_after_comment_insert;
+DROP TRIGGER IF EXISTS ticket_search_after_comment_update;
+DROP TRIGGER IF EXISTS ticket_search_after_comment_delete;
+
+CREATE TRIGGER ticket_search_after_description_insert AFTER INSERT ON comments
+WHEN NEW.stage_number = 0 AND NEW.content_key_id IS NULL
+BEGIN
+    UPDATE ticket_search SET description = CASE WHEN description = '' THEN NEW.content
+                                 ELSE description || char(10) || NEW.content END
+    WHERE rowid = (SELECT doc_id FROM ticket_search_docs WHERE ticket_id = NEW.ticket_id);
+END;
+
+CREATE TRIGGER ticket_search_after_comment_insert AFTER INSERT ON comments
+WHEN NEW.stage_number IS NOT 0 AND NEW.content_key_id IS NULL
+BEGIN
+    UPDATE ticket_search SET comments = CASE WHEN comments = '' THEN NEW.content
+                              ELSE comments || char(10) || NEW.content END
+    WHERE rowid = (SELECT doc_id FROM ticket_search_docs WHERE ticket_id = NEW.ticket_id);
+END;
+
+CREATE TRIGGER ticket_search_after_comment_update
+AFTER UPDATE OF content, content_key_id, stage_number, ticket_id ON comments
+BEGIN
+    UPDATE ticket_search SET
+        description = COALESCE((SELECT group_concat(content, char(10)) FROM comments
+                                WHERE ticket_id = t.ticket_id AND stage_number = 0
+                                  AND content_key_id IS NULL), ''),
+        comments = COALESCE((SELECT group_concat(content, char(10)) FROM comments
+                             WHERE ticket_id = t.ticket_id AND stage_number IS NOT 0
+                               AND content_key_id IS NULL), '')
+    FROM (SELECT doc_id, ticket_id FROM ticket_search_docs
+          WHERE ticket_id IN (OLD.ticket_id, NEW.ticket_id)) AS t
+    WHERE ticket_search.rowid = t.doc_id;
+END;
+
+CREATE TRIGGER ticket_search_after_comment_delete AFTER DELETE ON comments
+BEGIN
+    UPDATE ticket_search SET
+        description = COALESCE((SELECT group_concat(content, char(10)) FROM comments
+                                WHERE ticket_id = OLD.ticket_id AND stage_number = 0
+                                  AND content_key_id IS NULL), ''),
+        comments = COALESCE((SELECT group_concat(content, char(10)) FROM comments
+                             WHERE ticket_id = OLD.ticket_id AND stage_number IS NOT 0
+                               AND content_key_id IS NULL), '')
+    WHERE rowid = (SELECT doc_id FROM ticket_search_docs WHERE ticket_id = OLD.ticket_id);
+END;
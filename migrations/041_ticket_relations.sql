@@ -0,0 +1,85 @@
+-- Migration 041: Ticket relations
+-- Typed links between tickets: parent_of (epics and their subtasks), relates_to,
+-- duplicates and follows (stage pipelines). parent_of, duplicates and follows mirror
+-- tickets.parent_ticket_id, tickets.duplicate_of and the `follows` custom field, which stay
+-- the source of truth and are kept in sync by the triggers below; relates_to rows are
+-- written directly, with the smaller ticket id first.
+
+CREATE TABLE IF NOT EXISTS ticket_relations (
+    from_ticket_id TEXT NOT NULL,
+    to_ticket_id TEXT NOT NULL,
+    relation_type TEXT NOT NULL CHECK (relation_type IN ('parent_of', 'relates_to', 'duplicates', 'follows')),
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (from_ticket_id, to_ticket_id, relation_type),
+    FOREIGN KEY (from_ticket_id) REFERENCES tickets(ticket_id) ON DELETE CASCADE,
+    FOREIGN KEY (to_ticket_id) REFERENCES tickets(ticket_id) ON DELETE CASCADE,
+    CHECK (from_ticket_id != to_ticket_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_relations_to ON ticket_relations(to_ticket_id, relation_type);
+CREATE INDEX IF NOT EXISTS idx_ticket_relations_type ON ticket_relations(relation_type, from_ticket_id);
+
+INSERT OR IGNORE INTO ticket_relations (from_ticket_id, to_ticket_id, relation_type)
+SELECT parent_ticket_id, ticket_id, 'parent_of' FROM tickets
+WHERE parent_ticket_id IN (SELECT ticket_id FROM tickets);
+
+INSERT OR IGNORE INTO ticket_relations (from_ticket_id, to_ticket_id, relation_type)
+SELECT ticket_id, duplicate_of, 'duplicates' FROM tickets
+WHERE duplicate_of IN (SELECT ticket_id FROM tickets);
+
+INSERT OR IGNORE INTO ticket_relations (from_ticket_id, to_ticket_id, relation_type)
+SELECT ticket_id, follows, 'follows' FROM (
+    SELECT ticket_id,
+           CASE WHEN json_valid(custom_fields) THEN json_extract(custom_fields, '$.follows') END AS follows
+    FROM tickets
+)
+WHERE follows IN (SELECT ticket_id FROM tickets);
+
+CREATE TRIGGER IF NOT EXISTS ticket_relations_after_ticket_insert AFTER INSERT ON tickets
+BEGIN
+    INSERT OR IGNORE INTO ticket_relations (from_ticket_id, to_ticket_id, relation_type)
+    SELECT NEW.parent_ticket_id, NEW.ticket_id, 'parent_of'
+    WHERE NEW.parent_ticket_id IN (SELECT ticket_id FROM tickets);
+    INSERT OR IGNORE INTO ticket_relations (from_ticket_id, to_ticket_id, relation_type)
+    SELECT NEW.ticket_id, NEW.duplicate_of, 'duplicates'
+    WHERE NEW.duplicate_of IN (SELECT ticket_id FROM tickets);
+    INSERT OR IGNORE INTO ticket_relations (from_ticket_id, to_ticket_id, relation_type)
+    SELECT NEW.ticket_id, follows, 'follows' FROM (
+        SELECT CASE WHEN json_valid(NEW.custom_fields)
+                    THEN json_extract(NEW.custom_fields, '$.follows') END AS follows
+    )
+    WHERE follows IN (SELECT ticket_id FROM tickets);
+END;
+
+CREATE TRIGGER IF NOT EXISTS ticket_relations_after_parent_update
+AFTER UPDATE OF parent_ticket_id ON tickets
+WHEN OLD.parent_ticket_id IS NOT NEW.parent_ticket_id
+BEGIN
+    DELETE FROM ticket_relations WHERE to_ticket_id = NEW.ticket_id AND relation_type = 'parent_of';
+    INSERT OR IGNORE INTO ticket_relations (from_ticket_id, to_ticket_id, relation_type)
+    SELECT NEW.parent_ticket_id, NEW.ticket_id, 'parent_of'
+    WHERE NEW.parent_ticket_id IN (SELECT ticket_id FROM tickets);
+END;
+
+CREATE TRIGGER IF NOT EXISTS ticket_relations_after_duplicate_update
+AFTER UPDATE OF duplicate_of ON tickets
+WHEN OLD.duplicate_of IS NOT NEW.duplicate_of
+BEGIN
+    DELETE FROM ticket_relations WHERE from_ticket_id = NEW.ticket_id AND relation_type = 'duplicates';
+    INSERT OR IGNORE INTO ticket_relations (from_ticket_id, to_ticket_id, relation_type)
+    SELECT NEW.ticket_id, NEW.duplicate_of, 'duplicates'
+    WHERE NEW.duplicate_of IN (SELECT ticket_id FROM tickets);
+END;
+
+CREATE TRIGGER IF NOT EXISTS ticket_relations_after_follows_update
+AFTER UPDATE OF custom_fields ON tickets
+WHEN OLD.custom_fields IS NOT NEW.custom_fields
+BEGIN
+    DELETE FROM ticket_relations WHERE from_ticket_id = NEW.ticket_id AND relation_type = 'follows';
+    INSERT OR IGNORE INTO ticket_relations (from_ticket_id, to_ticket_id, relation_type)
+    SELECT NEW.ticket_id, follows, 'follows' FROM (
+        SELECT CASE WHEN json_valid(NEW.custom_fields)
+                    THEN json_extract(NEW.custom_fields, '$.follows') END AS follows
+    )
+    WHERE follows IN (SELECT ticket_id FROM tickets);
+END;
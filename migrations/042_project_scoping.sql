@@ -0,0 +1,25 @@
+-- Migration 042: Project scoping
+-- Agent requests record the project they belong to, so dashboards can be limited to one
+-- project. New requests take it from their ticket, a `project_id` in the payload, or the
+-- worker that sent or receives them; existing ones are backfilled the same way where the
+-- payload is not encrypted.
+
+ALTER TABLE agent_requests ADD COLUMN project_id TEXT;
+
+UPDATE agent_requests
+SET project_id = COALESCE(
+    (SELECT project_id FROM tickets WHERE tickets.ticket_id = agent_requests.ticket_id),
+    CASE WHEN payload_key_id IS NULL AND json_valid(payload) AND json_type(payload) = 'object'
+         THEN (SELECT repository_name FROM projects
+               WHERE repository_name = json_extract(agent_requests.payload, '$.project_id'))
+    END,
+    (SELECT project_id FROM workers WHERE workers.worker_id = agent_requests.requester),
+    (SELECT project_id FROM workers WHERE workers.worker_id = agent_requests.target)
+);
+
+CREATE INDEX IF NOT EXISTS idx_agent_requests_project_created
+    ON agent_requests(project_id, created_at);
+
+-- Tickets assigned to an agent, for the agents of a project
+CREATE INDEX IF NOT EXISTS idx_tickets_processing_worker
+    ON tickets(processing_worker_id) WHERE processing_worker_id IS NOT NULL;
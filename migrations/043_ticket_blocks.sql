@@ -0,0 +1,39 @@
+-- Migration 043: Ticket blocks
+-- A blocked ticket records what it waits on: another ticket, or an agent responsible for
+-- unblocking it by an expected date. Blocks are kept after they are lifted, with who
+-- lifted them, so the time spent blocked can be reported. nudged_at and escalated_at
+-- record when the responsible agent and then the coordinator were reminded.
+
+CREATE TABLE IF NOT EXISTS ticket_blocks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ticket_id TEXT NOT NULL REFERENCES tickets(ticket_id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    blocking_ticket_id TEXT,
+    responsible_agent TEXT,
+    expected_unblock_at TEXT,
+    blocked_by TEXT NOT NULL,
+    blocked_at TEXT NOT NULL DEFAULT (datetime('now')),
+    nudged_at TEXT,
+    escalated_at TEXT,
+    unblocked_at TEXT,
+    unblocked_by TEXT
+);
+
+-- At most one block in force per ticket
+CREATE UNIQUE INDEX IF NOT EXISTS idx_ticket_blocks_active
+    ON ticket_blocks(ticket_id) WHERE unblocked_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_ticket_blocks_blocking
+    ON ticket_blocks(blocking_ticket_id) WHERE unblocked_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS ticket_block_nudges (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    block_id INTEGER NOT NULL REFERENCES ticket_blocks(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('nudge', 'escalation')),
+    target TEXT NOT NULL,
+    -- Agent request carrying the nudge
+    correlation_id TEXT,
+    sent_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_block_nudges_block ON ticket_block_nudges(block_id);
@@ -0,0 +1,9 @@
+-- Migration 044: Ticket history indexes for throughput metrics
+-- Throughput reads the history rows of a time window across every project, and finds the
+-- first close of each ticket after its first pickup.
+
+CREATE INDEX IF NOT EXISTS idx_ticket_history_created_at
+    ON ticket_history(created_at);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_history_ticket_state
+    ON ticket_history(ticket_id, state, created_at);
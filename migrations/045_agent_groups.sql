@@ -0,0 +1,27 @@
+-- Migration 045: Agent groups
+-- Named groups of agents (worker ids or the coordinator) that a request can be addressed
+-- to as a whole. A request to a group is stored as one request per member at the time it
+-- was sent, each delivered, acknowledged and answered on its own; group_id records the
+-- group it went out to.
+
+CREATE TABLE IF NOT EXISTS agent_groups (
+    group_id TEXT PRIMARY KEY,
+    description TEXT,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS agent_group_members (
+    group_id TEXT NOT NULL REFERENCES agent_groups(group_id) ON DELETE CASCADE,
+    agent_id TEXT NOT NULL,
+    added_by TEXT NOT NULL,
+    joined_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (group_id, agent_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_agent_group_members_agent ON agent_group_members(agent_id);
+
+ALTER TABLE agent_requests ADD COLUMN group_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_agent_requests_group
+    ON agent_requests(group_id) WHERE group_id IS NOT NULL;
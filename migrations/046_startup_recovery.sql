@@ -0,0 +1,75 @@
+-- Migration 046: Startup recovery statuses
+-- After a crash no worker process survives the restart. Startup recovery marks the workers
+-- the database still has as running 'offline' and closes their open runs as 'interrupted'.
+-- SQLite cannot change a CHECK constraint, so both tables are recreated.
+
+CREATE TABLE workers_new (
+    worker_id TEXT PRIMARY KEY,
+    project_id TEXT NOT NULL,
+    worker_type TEXT NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('spawning', 'active', 'idle', 'finished', 'failed', 'offline')),
+    pid INTEGER,
+    queue_name TEXT NOT NULL,
+    started_at TEXT NOT NULL DEFAULT (datetime('now')),
+    last_activity TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (project_id) REFERENCES projects(repository_name) ON DELETE CASCADE
+);
+
+INSERT INTO workers_new (worker_id, project_id, worker_type, status, pid, queue_name, started_at, last_activity)
+SELECT worker_id, project_id, worker_type, status, pid, queue_name, started_at, last_activity
+FROM workers;
+
+DROP TABLE workers;
+ALTER TABLE workers_new RENAME TO workers;
+
+CREATE INDEX IF NOT EXISTS idx_workers_project_type ON workers(project_id, worker_type);
+CREATE INDEX IF NOT EXISTS idx_workers_status ON workers(status);
+CREATE INDEX IF NOT EXISTS idx_workers_project_status ON workers(project_id, status);
+
+-- Dropping worker_runs would cascade to the inputs sent to each run, so they are set aside
+-- and put back once the table is recreated
+CREATE TEMP TABLE worker_run_inputs_saved AS SELECT * FROM worker_run_inputs;
+
+CREATE TABLE worker_runs_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    worker_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    worker_type TEXT NOT NULL,
+    ticket_id TEXT NOT NULL,
+    pid INTEGER,
+    working_dir TEXT NOT NULL,
+    max_runtime_secs INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'completed', 'failed', 'killed', 'interrupted')),
+    exit_status TEXT,
+    -- How the runtime limit was enforced: 'terminated' (SIGTERM) or 'killed' (SIGKILL)
+    enforcement TEXT,
+    policy_violations TEXT, -- JSON array of messages
+    started_at TEXT NOT NULL DEFAULT (datetime('now')),
+    finished_at TEXT,
+    warm INTEGER NOT NULL DEFAULT 0,
+    boundary_violations TEXT,
+    last_heartbeat_at TEXT,
+    progress TEXT,
+    stalled_at TEXT
+);
+
+INSERT INTO worker_runs_new (
+    id, worker_id, project_id, worker_type, ticket_id, pid, working_dir, max_runtime_secs,
+    status, exit_status, enforcement, policy_violations, started_at, finished_at, warm,
+    boundary_violations, last_heartbeat_at, progress, stalled_at
+)
+SELECT id, worker_id, project_id, worker_type, ticket_id, pid, working_dir, max_runtime_secs,
+       status, exit_status, enforcement, policy_violations, started_at, finished_at, warm,
+       boundary_violations, last_heartbeat_at, progress, stalled_at
+FROM worker_runs;
+
+DROP TABLE worker_runs;
+ALTER TABLE worker_runs_new RENAME TO worker_runs;
+
+CREATE INDEX IF NOT EXISTS idx_worker_runs_ticket ON worker_runs(ticket_id, started_at);
+CREATE INDEX IF NOT EXISTS idx_worker_runs_project ON worker_runs(project_id, started_at);
+CREATE INDEX IF NOT EXISTS idx_worker_runs_finished_at ON worker_runs(finished_at) WHERE finished_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_worker_runs_worker ON worker_runs(worker_id, status);
+
+INSERT INTO worker_run_inputs SELECT * FROM worker_run_inputs_saved;
+DROP TABLE worker_run_inputs_saved;
@@ -0,0 +1,34 @@
+-- Migration 047: Effective priority
+-- effective_priority is the explicit priority raised by the escalation rules (age in the
+-- current state, open tickets the ticket blocks, SLA proximity); it never falls below
+-- priority. escalation_reasons is a JSON array of the rules that raised it, NULL when none
+-- did, and escalated_at the last time it went up.
+
+ALTER TABLE tickets ADD COLUMN effective_priority TEXT NOT NULL DEFAULT 'medium'
+    CHECK (effective_priority IN ('low', 'medium', 'high', 'urgent'));
+ALTER TABLE tickets ADD COLUMN escalation_reasons TEXT;
+ALTER TABLE tickets ADD COLUMN escalated_at TEXT;
+
+UPDATE tickets SET effective_priority = priority;
+
+CREATE INDEX IF NOT EXISTS idx_tickets_escalated_at ON tickets(escalated_at)
+    WHERE escalated_at IS NOT NULL;
+
+CREATE TRIGGER IF NOT EXISTS effective_priority_after_insert AFTER INSERT ON tickets
+BEGIN
+    UPDATE tickets SET effective_priority = NEW.priority WHERE ticket_id = NEW.ticket_id;
+END;
+
+-- The effective priority follows the explicit one, except that lowering the priority of an
+-- escalated ticket keeps the escalation until the rules are evaluated again
+CREATE TRIGGER IF NOT EXISTS effective_priority_after_priority_change
+AFTER UPDATE OF priority ON tickets
+WHEN OLD.priority IS NOT NEW.priority
+BEGIN
+    UPDATE tickets
+    SET effective_priority = NEW.priority, escalation_reasons = NULL
+    WHERE ticket_id = NEW.ticket_id
+      AND (escalation_reasons IS NULL
+       OR (CASE NEW.priority WHEN 'low' THEN 0 WHEN 'medium' THEN 1 WHEN 'high' THEN 2 ELSE 3 END)
+       >= (CASE effective_priority WHEN 'low' THEN 0 WHEN 'medium' THEN 1 WHEN 'high' THEN 2 ELSE 3 END));
+END;
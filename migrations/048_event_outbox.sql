@@ -0,0 +1,28 @@
+-- Migration 048: Event outbox
+-- Domain changes queue their events here in the transaction that makes the change. The
+-- outbox dispatcher logs each event in `events`, queues its webhook deliveries and marks it
+-- dispatched in one transaction, then broadcasts it to SSE and WebSocket clients. Events of
+-- the same entity are dispatched in id order; an event that keeps failing is 'dead' and no
+-- longer holds back the ones after it.
+
+CREATE TABLE IF NOT EXISTS event_outbox (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_type TEXT NOT NULL,
+    -- Ordering key, e.g. the ticket id for ticket events
+    entity_id TEXT NOT NULL,
+    payload TEXT NOT NULL, -- EventPayload JSON
+    -- Columns of the `events` row written on dispatch
+    ticket_id TEXT,
+    worker_id TEXT,
+    stage TEXT,
+    reason TEXT,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'dispatched', 'dead')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TEXT NOT NULL DEFAULT (datetime('now')),
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    dispatched_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_event_outbox_due ON event_outbox(status, next_attempt_at);
+CREATE INDEX IF NOT EXISTS idx_event_outbox_entity ON event_outbox(entity_id, status, id);
@@ -0,0 +1,7 @@
+-- Thread summaries condense comments, so they are encrypted like the comments themselves
+-- (--encrypt-messages). NULL means the summary is plaintext.
+
+ALTER TABLE thread_summaries ADD COLUMN summary_key_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_thread_summaries_summary_key_id
+    ON thread_summaries(summary_key_id) WHERE summary_key_id IS NOT NULL;
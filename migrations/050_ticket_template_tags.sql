@@ -0,0 +1,3 @@
+-- Tags a template puts on the tickets created from it, next to any given at creation
+
+ALTER TABLE ticket_templates ADD COLUMN default_tags TEXT NOT NULL DEFAULT '[]'; -- JSON array of tag names
@@ -0,0 +1,9 @@
+-- The ticket an audit entry concerns, kept apart from the MCP session it came from
+
+ALTER TABLE audit_log ADD COLUMN ticket_id TEXT;
+
+-- Worker lifecycle entries used to carry their ticket in session_id
+UPDATE audit_log SET ticket_id = session_id, session_id = NULL
+WHERE method IN ('worker_spawn', 'worker_shutdown', 'worker_spawn_policy', 'worker_boundary');
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_ticket_id ON audit_log(ticket_id);
@@ -0,0 +1,162 @@
+use anyhow::Result;
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::{
+    config::Config,
+    database::{agent_requests::AgentRequest, DbPool},
+    events::EventPayload,
+    sse::EventBroadcaster,
+};
+
+const EXPIRY_SWEEP_INTERVAL_SECS: u64 = 5;
+const RETENTION_SWEEP_INTERVAL_SECS: u64 = 3600;
+
+/// Push a request lifecycle notification addressed to `recipient`. Returns whether a
+/// connected client received it.
+pub fn notify(broadcaster: &EventBroadcaster, request: &AgentRequest, recipient: &str) -> bool {
+    let message = match request.status.as_str() {
+        "pending" => format!(
+            "Request {} from '{}' awaits a response",
+            request.correlation_id, request.requester
+        ),
+        "expired" => format!(
+            "Request {} to '{}' timed out",
+            request.correlation_id, request.target
+        ),
+        status => format!(
+            "Request {} to '{}' was {}",
+            request.correlation_id, request.target, status
+        ),
+    };
+
+    broadcaster.broadcast(EventPayload::system_message(
+        "agent_requests",
+        &message,
+        Some(json!({
+            "correlation_id": request.correlation_id,
+            "recipient": recipient,
+            "requester": request.requester,
+            "target": request.target,
+            "ticket_id": request.ticket_id,
+            "status": request.status,
+            "deadline": request.deadline,
+        })),
+    )) > 0
+}
+
+/// Push a new request to its target. A push that reached a connected client counts as
+/// delivery, just as a poll through `take_pending` does; otherwise the request stays
+/// undelivered until the target polls for it.
+pub async fn deliver(
+    db: &DbPool,
+    broadcaster: &EventBroadcaster,
+    request: &AgentRequest,
+) -> Result<()> {
+    if notify(broadcaster, request, &request.target) {
+        AgentRequest::mark_delivered(db, &request.correlation_id).await?;
+    }
+    Ok(())
+}
+
+/// Periodically expire overdue requests and notify their requesters
+pub fn start_expiry_sweeper(db: DbPool, broadcaster: EventBroadcaster) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(EXPIRY_SWEEP_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            match AgentRequest::expire_overdue(&db).await {
+                Ok(expired) => {
+                    for request in &expired {
+                        info!(
+                            "Request {} from '{}' to '{}' expired",
+                            request.correlation_id, request.requester, request.target
+                        );
+                        notify(&broadcaster, request, &request.requester);
+                    }
+                }
+                Err(e) => warn!("Failed to expire overdue requests: {}", e),
+            }
+        }
+    });
+}
+
+/// Periodically delete settled requests past their retention period. Always runs, since
+/// a project can set a retention period even when the global one keeps requests forever.
+pub fn start_retention_sweeper(db: DbPool, config: watch::Receiver<Config>) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(RETENTION_SWEEP_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let default_days = config.borrow().message_retention_days;
+            match AgentRequest::purge_settled(&db, default_days).await {
+                Ok(0) => {}
+                Ok(count) => info!("Purged {} settled agent requests", count),
+                Err(e) => warn!("Failed to purge settled agent requests: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::agent_requests::RequestDeliveryStats, events::EventData,
+        test_support::memory_pool,
+    };
+
+    #[tokio::test]
+    async fn test_push_to_a_connected_client_counts_as_delivery() {
+        let pool = memory_pool().await.unwrap();
+        let broadcaster = EventBroadcaster::new();
+        let unseen = AgentRequest::create(&pool, "coordinator", "w-1", None, "{}", 60)
+            .await
+            .unwrap();
+        let pushed = AgentRequest::create(&pool, "coordinator", "w-1", None, "{}", 60)
+            .await
+            .unwrap();
+
+        // Nobody is listening, so the request waits for the target to poll
+        deliver(&pool, &broadcaster, &unseen).await.unwrap();
+        let unseen = AgentRequest::get_by_id(&pool, &unseen.correlation_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(unseen.delivered_at.is_none());
+
+        let mut client = broadcaster.subscribe_websocket();
+        deliver(&pool, &broadcaster, &pushed).await.unwrap();
+        let EventData::System(event) = client.recv().await.unwrap().data else {
+            panic!("request notifications are system messages");
+        };
+        assert_eq!(
+            event.metadata.unwrap()["correlation_id"],
+            json!(pushed.correlation_id)
+        );
+        let pushed = AgentRequest::get_by_id(&pool, &pushed.correlation_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(pushed.delivered_at.is_some());
+        assert!(pushed.acknowledged_at.is_none());
+
+        // Polling delivers the other; the pushed one keeps its delivery time
+        let polled = AgentRequest::take_pending(&pool, "w-1", None, 10)
+            .await
+            .unwrap();
+        assert_eq!(polled.len(), 2);
+        let stats = RequestDeliveryStats::load(&pool, None).await.unwrap();
+        assert_eq!((stats.delivered, stats.undelivered), (2, 0));
+        let repolled = AgentRequest::get_by_id(&pool, &pushed.correlation_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(repolled.delivered_at, pushed.delivered_at);
+
+        pool.close().await;
+    }
+}
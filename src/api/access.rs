@@ -0,0 +1,474 @@
+use anyhow::{bail, Context, Result};
+use axum::{
+    extract::{Request, State},
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
+    routing::MethodRouter,
+    Extension,
+};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, LazyLock},
+};
+use tracing::{debug, info};
+use utoipa::ToSchema;
+
+use crate::{config::Config, error::error_response, server::AppState};
+
+/// What a REST API route lets its caller do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub enum Permission {
+    /// Projects, tickets, boards, stats and other lookups
+    #[serde(rename = "read")]
+    Read,
+    /// Ticket attachments, board positions, tags, templates and schedules
+    #[serde(rename = "tickets:write")]
+    WriteTickets,
+    /// Worker types, agent sessions, agent requests and agent groups
+    #[serde(rename = "workers:write")]
+    ManageWorkers,
+    /// Admin actions, diagnostics, the audit log, webhooks and project settings
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl Permission {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Permission::Read => "read",
+            Permission::WriteTickets => "tickets:write",
+            Permission::ManageWorkers => "workers:write",
+            Permission::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Role of a REST API caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Read-only access, for sharing the dashboard
+    Viewer,
+    /// Day-to-day work on tickets and workers
+    Operator,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Operator => "operator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn permissions(self) -> &'static [Permission] {
+        match self {
+            Role::Viewer => &[Permission::Read],
+            Role::Operator => &[
+                Permission::Read,
+                Permission::WriteTickets,
+                Permission::ManageWorkers,
+            ],
+            Role::Admin => &[
+                Permission::Read,
+                Permission::WriteTickets,
+                Permission::ManageWorkers,
+                Permission::Admin,
+            ],
+        }
+    }
+
+    pub fn grants(self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+}
+
+/// A static API token and the role it is given
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiToken {
+    /// Who the token belongs to, for logs
+    pub name: String,
+    pub token: String,
+    pub role: Role,
+}
+
+/// Contents of the `--api-tokens` file
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ApiTokenFile {
+    #[serde(default)]
+    pub tokens: Vec<ApiToken>,
+}
+
+/// The caller of a REST API request, resolved by [`authenticate`]
+#[derive(Debug, Clone)]
+pub struct ApiCaller {
+    /// Token name, the worker id for a spawned worker's token, or "anonymous" for requests
+    /// without a token
+    pub name: String,
+    pub role: Role,
+    /// The spawned worker the token was issued to
+    pub worker: Option<WorkerIdentity>,
+}
+
+/// A worker this process spawned, as named by the token it was given
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerIdentity {
+    pub worker_id: String,
+    pub worker_type: String,
+}
+
+/// Tokens handed to the workers this process spawned, one per worker, so they can work
+/// once API tokens are configured and are known by who they are. A token is never written
+/// anywhere but its worker's MCP config, and is revoked when the worker ends.
+static WORKER_TOKENS: LazyLock<DashMap<String, WorkerIdentity>> = LazyLock::new(DashMap::new);
+
+/// Issue the token of a worker about to be spawned
+pub fn issue_worker_token(worker_id: &str, worker_type: &str) -> String {
+    let token = format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    WORKER_TOKENS.insert(
+        token.clone(),
+        WorkerIdentity {
+            worker_id: worker_id.to_string(),
+            worker_type: worker_type.to_string(),
+        },
+    );
+    token
+}
+
+/// Let a worker's token name the worker it now runs as, e.g. a warm worker that was
+/// handed a ticket
+pub fn rebind_worker_token(token: &str, worker_id: &str) {
+    if let Some(mut worker) = WORKER_TOKENS.get_mut(token) {
+        worker.worker_id = worker_id.to_string();
+    }
+}
+
+pub fn revoke_worker_token(token: &str) {
+    WORKER_TOKENS.remove(token);
+}
+
+/// Maps bearer tokens to roles. Requests without a token get admin only while no token is
+/// configured and the dashboard is not read-only; otherwise they are viewers.
+#[derive(Debug, Clone)]
+pub struct AccessControl {
+    tokens: HashMap<String, ApiToken>,
+    anonymous_role: Role,
+    /// Whether `--api-tokens` configured any token
+    configured: bool,
+    /// Whether the tokens of spawned workers are accepted too
+    workers: bool,
+}
+
+impl AccessControl {
+    pub fn new(tokens: Vec<ApiToken>, read_only: bool) -> Result<Self> {
+        let mut by_token = HashMap::new();
+        for token in tokens {
+            if token.token.trim().len() < 16 {
+                bail!("API token '{}' must be at least 16 characters", token.name);
+            }
+            if let Some(other) = by_token.insert(token.token.clone(), token) {
+                bail!("API token of '{}' is used more than once", other.name);
+            }
+        }
+        let configured = !by_token.is_empty();
+        Ok(Self {
+            tokens: by_token,
+            // A token would be pointless if leaving it out granted more
+            anonymous_role: if read_only || configured {
+                Role::Viewer
+            } else {
+                Role::Admin
+            },
+            configured,
+            workers: false,
+        })
+    }
+
+    /// The same tokens for the MCP transports, to which `--dashboard-read-only` does not
+    /// apply: without configured tokens, agents connecting without one keep full access
+    pub fn for_agents(&self) -> Self {
+        Self {
+            anonymous_role: if self.configured {
+                Role::Viewer
+            } else {
+                Role::Admin
+            },
+            ..self.clone()
+        }
+    }
+
+    /// Also accept the tokens of spawned workers, as their callers; for the transports
+    /// that tell workers apart from the coordinator
+    pub fn with_workers(&self) -> Self {
+        Self {
+            workers: true,
+            ..self.clone()
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let file = match &config.api_tokens_path {
+            Some(path) => {
+                let content = std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read API token file: {}", path))?;
+                serde_json::from_str::<ApiTokenFile>(&content)
+                    .with_context(|| format!("Failed to parse API token file: {}", path))?
+            }
+            None => ApiTokenFile::default(),
+        };
+        let count = file.tokens.len();
+        let access = Self::new(file.tokens, config.dashboard_read_only)?;
+        if access.configured {
+            info!(
+                "Loaded {} API token(s): requests and MCP connections without one are viewers",
+                count
+            );
+        } else if config.dashboard_read_only {
+            info!("Dashboard is read-only: requests without an API token are viewers");
+        }
+        Ok(access)
+    }
+
+    /// The caller named by the request's bearer token; `None` for an unknown token
+    pub fn resolve(&self, headers: &HeaderMap) -> Option<ApiCaller> {
+        let bearer = headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(str::trim);
+
+        match bearer {
+            None => Some(ApiCaller {
+                name: "anonymous".to_string(),
+                role: self.anonymous_role,
+                worker: None,
+            }),
+            Some(token) => match WORKER_TOKENS.get(token).filter(|_| self.workers) {
+                Some(worker) => Some(ApiCaller {
+                    name: worker.worker_id.clone(),
+                    role: Role::Operator,
+                    worker: Some(worker.clone()),
+                }),
+                None => self.tokens.get(token).map(|token| ApiCaller {
+                    name: token.name.clone(),
+                    role: token.role,
+                    worker: None,
+                }),
+            },
+        }
+    }
+}
+
+/// Resolve the caller of every API request, rejecting unknown tokens
+pub async fn authenticate(
+    State(access): State<Arc<AccessControl>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    match access.resolve(request.headers()) {
+        Some(caller) => {
+            request.extensions_mut().insert(caller);
+            next.run(request).await
+        }
+        None => error_response(StatusCode::UNAUTHORIZED, "Unknown API token"),
+    }
+}
+
+/// Body of a 403 response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PermissionDenied {
+    pub error: String,
+    pub missing_permission: Permission,
+    pub role: Role,
+}
+
+async fn check_permission(
+    State(permission): State<Permission>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // Without authenticate in front, nothing is granted beyond reading
+    let role = request
+        .extensions()
+        .get::<ApiCaller>()
+        .map_or(Role::Viewer, |caller| caller.role);
+    if role.grants(permission) {
+        return next.run(request).await;
+    }
+
+    debug!(
+        "Denied {} {}: role '{}' lacks permission '{}'",
+        request.method(),
+        request.uri().path(),
+        role.as_str(),
+        permission
+    );
+    (
+        StatusCode::FORBIDDEN,
+        Json(PermissionDenied {
+            error: format!(
+                "Permission '{}' is required; role '{}' does not have it",
+                permission,
+                role.as_str()
+            ),
+            missing_permission: permission,
+            role,
+        }),
+    )
+        .into_response()
+}
+
+/// Only let callers holding `permission` reach `route`
+pub fn requires(permission: Permission, route: MethodRouter<AppState>) -> MethodRouter<AppState> {
+    route.route_layer(middleware::from_fn_with_state(permission, check_permission))
+}
+
+/// Resolve the caller with `access`, then only let those holding `permission` reach
+/// `route`; for routes outside `/api`, which has `authenticate` in front already
+pub fn authenticated(
+    access: &Arc<AccessControl>,
+    permission: Permission,
+    route: MethodRouter<AppState>,
+) -> MethodRouter<AppState> {
+    // Not route_layer as in `requires`: `any` routes have no method routes to attach it to
+    route
+        .layer(middleware::from_fn_with_state(permission, check_permission))
+        .layer(middleware::from_fn_with_state(access.clone(), authenticate))
+}
+
+/// The caller of a request and what it may do
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccessInfo {
+    pub name: String,
+    pub role: Role,
+    pub permissions: Vec<Permission>,
+}
+
+/// GET /api/access - The caller's role and permissions, so clients can hide what they
+/// may not do
+#[utoipa::path(
+    get,
+    path = "/api/access",
+    tag = "access",
+    responses(
+        (status = 200, description = "The caller's role and permissions", body = AccessInfo)
+    )
+)]
+pub async fn get_access(Extension(caller): Extension<ApiCaller>) -> impl IntoResponse {
+    Json(AccessInfo {
+        name: caller.name,
+        role: caller.role,
+        permissions: caller.role.permissions().to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_permission_matrix() {
+        use Permission::*;
+        let matrix = [
+            (Role::Viewer, [true, false, false, false]),
+            (Role::Operator, [true, true, true, false]),
+            (Role::Admin, [true, true, true, true]),
+        ];
+        for (role, expected) in matrix {
+            let granted = [Read, WriteTickets, ManageWorkers, Admin].map(|p| role.grants(p));
+            assert_eq!(granted, expected, "{:?}", role);
+        }
+    }
+
+    #[test]
+    fn test_resolve_callers() {
+        let token = |name: &str, role| ApiToken {
+            name: name.to_string(),
+            token: format!("{}-0123456789abcdef", name),
+            role,
+        };
+        let access = AccessControl::new(vec![token("ops", Role::Operator)], true).unwrap();
+        let headers = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(AUTHORIZATION, value.parse().unwrap());
+            headers
+        };
+
+        assert_eq!(
+            access.resolve(&HeaderMap::new()).unwrap().role,
+            Role::Viewer
+        );
+        let ops = access
+            .resolve(&headers("Bearer ops-0123456789abcdef"))
+            .unwrap();
+        assert_eq!((ops.name.as_str(), ops.role), ("ops", Role::Operator));
+        assert!(access.resolve(&headers("Bearer nope")).is_none());
+
+        let open = AccessControl::new(Vec::new(), false).unwrap();
+        assert_eq!(open.resolve(&HeaderMap::new()).unwrap().role, Role::Admin);
+        assert_eq!(
+            open.for_agents().resolve(&HeaderMap::new()).unwrap().role,
+            Role::Admin
+        );
+
+        // Once a token is configured, leaving it out no longer grants more than reading,
+        // whether or not the dashboard is read-only
+        let tokens = AccessControl::new(vec![token("ops", Role::Operator)], false).unwrap();
+        assert_eq!(
+            tokens.resolve(&HeaderMap::new()).unwrap().role,
+            Role::Viewer
+        );
+        assert_eq!(
+            tokens.for_agents().resolve(&HeaderMap::new()).unwrap().role,
+            Role::Viewer
+        );
+        // A read-only dashboard alone leaves agents without a token alone
+        let shared = AccessControl::new(Vec::new(), true).unwrap();
+        assert_eq!(
+            shared.resolve(&HeaderMap::new()).unwrap().role,
+            Role::Viewer
+        );
+        assert_eq!(
+            shared.for_agents().resolve(&HeaderMap::new()).unwrap().role,
+            Role::Admin
+        );
+
+        // A worker's token names its worker, and only where worker tokens are accepted
+        let worker_token = issue_worker_token("w-1", "coding");
+        let bearer = headers(&format!("Bearer {}", worker_token));
+        assert!(tokens.resolve(&bearer).is_none());
+        let worker = tokens.with_workers().resolve(&bearer).unwrap();
+        assert_eq!((worker.name.as_str(), worker.role), ("w-1", Role::Operator));
+        assert_eq!(worker.worker.unwrap().worker_type, "coding");
+        rebind_worker_token(&worker_token, "w-2");
+        let worker = tokens.with_workers().resolve(&bearer).unwrap();
+        assert_eq!(worker.worker.unwrap().worker_id, "w-2");
+        revoke_worker_token(&worker_token);
+        assert!(tokens.with_workers().resolve(&bearer).is_none());
+
+        let mut short = token("short", Role::Admin);
+        short.token = "abc".to_string();
+        assert!(AccessControl::new(vec![short], false).is_err());
+        assert!(AccessControl::new(
+            vec![token("a", Role::Admin), token("a", Role::Viewer)],
+            false
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,342 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+    Extension,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    api::access::ApiCaller,
+    audit::AuditRecord,
+    backup,
+    database::outbox::OutboxEvent,
+    demo::{self, DemoSummary},
+    error::{error_response, AppError},
+    logging,
+    mcp::access::ToolCaller,
+    reload,
+    server::AppState,
+};
+
+/// POST /api/admin/backup - Write an online snapshot of the database to the backups directory
+#[utoipa::path(
+    post,
+    path = "/api/admin/backup",
+    tag = "admin",
+    responses(
+        (status = 201, description = "Backup written", body = BackupInfo),
+        (status = 409, description = "A backup is already in progress", body = ErrorResponse)
+    )
+)]
+pub async fn create_backup(State(state): State<AppState>) -> Result<Response, AppError> {
+    if backup::status().in_progress {
+        return Ok(error_response(
+            StatusCode::CONFLICT,
+            "A backup is already in progress",
+        ));
+    }
+
+    let dir = backup::backup_dir(&state.config.database_path);
+    let info = backup::create_backup(&state.db, &dir).await?;
+
+    Ok((StatusCode::CREATED, Json(info)).into_response())
+}
+
+/// Outcome of reloading the `--tool-policy` file
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ToolPolicyReload {
+    pub changed: bool,
+    /// Tools the coordinator sees after the reload
+    pub coordinator_tools: Vec<String>,
+    pub notified_websocket_clients: usize,
+}
+
+/// POST /api/admin/reload-tool-policy - Load the `--tool-policy` file again. When the
+/// coordinator's tool list changes, clients that declared `tools.listChanged` are sent
+/// `notifications/tools/list_changed`.
+#[utoipa::path(
+    post,
+    path = "/api/admin/reload-tool-policy",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Tool policy reloaded", body = ToolPolicyReload),
+        (status = 400, description = "The tool policy file is invalid", body = ErrorResponse),
+        (status = 409, description = "No tool policy file is configured", body = ErrorResponse)
+    )
+)]
+pub async fn reload_tool_policy(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+) -> Result<Response, AppError> {
+    if state.mcp_server.tool_policy_path.is_none() {
+        return Ok(error_response(
+            StatusCode::CONFLICT,
+            "No tool policy file is configured (--tool-policy)",
+        ));
+    }
+
+    let changed = state
+        .mcp_server
+        .reload_access_policy()
+        .map_err(|e| AppError::BadRequest(format!("{:#}", e)))?;
+    let mut notified_clients = 0;
+    if changed {
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/tools/list_changed",
+        });
+        notified_clients = state
+            .websocket_manager
+            .broadcast_notification(&notification);
+        state.event_stream.publish(None, &notification);
+    }
+
+    let tools: Vec<String> = state
+        .mcp_server
+        .visible_tools(&ToolCaller::Coordinator)
+        .into_iter()
+        .map(|tool| tool.name)
+        .collect();
+    state.audit_logger.record(
+        AuditRecord::new(&caller.name, "reload_tool_policy", "success")
+            .with_params(Some(&json!({ "changed": changed }))),
+    );
+
+    Ok(Json(ToolPolicyReload {
+        changed,
+        coordinator_tools: tools,
+        notified_websocket_clients: notified_clients,
+    })
+    .into_response())
+}
+
+/// POST /api/admin/reload-config - Read the `--config` file again and apply its reloadable
+/// settings; changes to settings that need a restart are listed but not applied
+#[utoipa::path(
+    post,
+    path = "/api/admin/reload-config",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Config file reloaded", body = ReloadOutcome),
+        (status = 400, description = "The config file is invalid", body = ErrorResponse),
+        (status = 409, description = "No config file is configured", body = ErrorResponse)
+    )
+)]
+pub async fn reload_config(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+) -> Result<Response, AppError> {
+    if state.live_config.borrow().config_path.is_none() {
+        return Ok(error_response(
+            StatusCode::CONFLICT,
+            "No config file is configured (--config)",
+        ));
+    }
+
+    let outcome = reload::reload(&state, &caller.name)
+        .map_err(|e| AppError::BadRequest(format!("{:#}", e)))?;
+    Ok(Json(outcome).into_response())
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SeedDemoQuery {
+    /// Seed even though the database has projects, replacing an earlier demo project
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DemoSeeded {
+    pub summary: DemoSummary,
+    pub dashboard_url: String,
+}
+
+/// POST /api/admin/seed-demo - Load the demo project into the database (query: force).
+/// Only available with `--allow-demo-seeding`.
+#[utoipa::path(
+    post,
+    path = "/api/admin/seed-demo",
+    tag = "admin",
+    params(SeedDemoQuery),
+    responses(
+        (status = 201, description = "Demo project seeded", body = DemoSeeded),
+        (status = 403, description = "Demo seeding is disabled", body = ErrorResponse),
+        (status = 409, description = "The database already has projects", body = ErrorResponse)
+    )
+)]
+pub async fn seed_demo(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Query(query): Query<SeedDemoQuery>,
+) -> Result<Response, AppError> {
+    if !state.config.allow_demo_seeding {
+        return Ok(error_response(
+            StatusCode::FORBIDDEN,
+            "Demo seeding is disabled (--allow-demo-seeding)",
+        ));
+    }
+    if !query.force && !demo::is_empty(&state.db).await? {
+        return Ok(error_response(
+            StatusCode::CONFLICT,
+            "The database already has projects; pass force=true to seed anyway",
+        ));
+    }
+
+    let summary = demo::seed(&state.db, query.force).await?;
+    state.audit_logger.record(
+        AuditRecord::new(&caller.name, "seed_demo", "success")
+            .with_params(Some(&json!({ "force": query.force }))),
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(DemoSeeded {
+            summary,
+            dashboard_url: format!("{}/dashboard", state.config.http_base_url()),
+        }),
+    )
+        .into_response())
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetLogLevelRequest {
+    /// A level (`debug`) or EnvFilter directives (`vibe_ensemble_mcp=trace,sqlx=warn`)
+    #[serde(alias = "level")]
+    pub filter: String,
+    /// Restore the startup filter after this many seconds
+    pub ttl_secs: Option<u64>,
+}
+
+fn log_control_unavailable() -> Response {
+    error_response(
+        StatusCode::CONFLICT,
+        "Runtime log control is not available in this process",
+    )
+}
+
+/// GET /api/admin/log-level - The log filter in effect and when it reverts
+#[utoipa::path(
+    get,
+    path = "/api/admin/log-level",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Log filter in effect", body = LogFilterStatus),
+        (status = 409, description = "Runtime log control is not available", body = ErrorResponse)
+    )
+)]
+pub async fn get_log_level() -> Result<Response, AppError> {
+    match logging::status() {
+        Some(status) => Ok(Json(status).into_response()),
+        None => Ok(log_control_unavailable()),
+    }
+}
+
+/// PUT /api/admin/log-level - Replace the log filter without a restart, optionally only
+/// for `ttl_secs`
+#[utoipa::path(
+    put,
+    path = "/api/admin/log-level",
+    tag = "admin",
+    request_body = SetLogLevelRequest,
+    responses(
+        (status = 200, description = "Log filter replaced", body = LogFilterStatus),
+        (status = 400, description = "Invalid filter", body = ErrorResponse),
+        (status = 409, description = "Runtime log control is not available", body = ErrorResponse)
+    )
+)]
+pub async fn set_log_level(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Json(req): Json<SetLogLevelRequest>,
+) -> Result<Response, AppError> {
+    let Some(control) = logging::control() else {
+        return Ok(log_control_unavailable());
+    };
+
+    let status = control
+        .set(&req.filter, req.ttl_secs.map(Duration::from_secs))
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    state.audit_logger.record(
+        AuditRecord::new(&caller.name, "set_log_level", "success").with_params(Some(
+            &json!({ "filter": req.filter, "ttl_secs": req.ttl_secs }),
+        )),
+    );
+
+    Ok(Json(status).into_response())
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListOutboxQuery {
+    /// pending, dispatched or dead
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// GET /api/admin/outbox - Events queued for publication, most recent first (query:
+/// status, limit); `status=dead` lists those given up on
+#[utoipa::path(
+    get,
+    path = "/api/admin/outbox",
+    tag = "admin",
+    params(ListOutboxQuery),
+    responses(
+        (status = 200, description = "Outbox events", body = [OutboxEvent]),
+        (status = 400, description = "Invalid status", body = ErrorResponse)
+    )
+)]
+pub async fn list_outbox(
+    State(state): State<AppState>,
+    Query(query): Query<ListOutboxQuery>,
+) -> Result<Response, AppError> {
+    if let Some(status) = &query.status {
+        if !matches!(status.as_str(), "pending" | "dispatched" | "dead") {
+            return Err(AppError::BadRequest(format!(
+                "Invalid outbox status '{}'. Valid statuses are: pending, dispatched, dead",
+                status
+            )));
+        }
+    }
+
+    let events = OutboxEvent::list(
+        &state.db,
+        query.status.as_deref(),
+        query.limit.unwrap_or(100).clamp(1, 1000),
+    )
+    .await?;
+
+    Ok(Json(events).into_response())
+}
+
+/// POST /api/admin/outbox/:id/requeue - Queue a dead outbox event for dispatch again
+#[utoipa::path(
+    post,
+    path = "/api/admin/outbox/{id}/requeue",
+    tag = "admin",
+    params(("id" = i64, Path, description = "Outbox event id")),
+    responses(
+        (status = 200, description = "Event queued again", body = OutboxEvent),
+        (status = 404, description = "No dead outbox event with that id", body = ErrorResponse)
+    )
+)]
+pub async fn requeue_outbox_event(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Path(id): Path<i64>,
+) -> Result<Response, AppError> {
+    let event = OutboxEvent::requeue(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No dead outbox event {}", id)))?;
+    state.event_broadcaster.notify_outbox();
+    state.audit_logger.record(
+        AuditRecord::new(&caller.name, "requeue_outbox_event", "success")
+            .with_params(Some(&json!({ "id": id }))),
+    );
+
+    Ok(Json(event).into_response())
+}
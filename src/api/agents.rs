@@ -0,0 +1,151 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    database::{agent_groups::AgentGroup, workers::Worker},
+    error::AppError,
+    mcp::session::SessionInfo,
+    server::AppState,
+};
+
+use super::projects::require_project;
+
+/// Worker statuses of a running worker process
+const LIVE_WORKER_STATUSES: &[&str] = &["spawning", "active", "idle"];
+
+/// An agent working on a project: one of its workers, a worker processing one of its
+/// tickets, or a client whose session metadata names the project
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ProjectAgent {
+    /// Worker id, or the session id of a client that is not a worker
+    pub agent_id: String,
+    pub worker_type: Option<String>,
+    /// Worker process status
+    pub status: Option<String>,
+    /// Project the worker belongs to, when it is known to be a worker
+    pub project_id: Option<String>,
+    /// Tickets of the project the agent is processing
+    pub assigned_tickets: Vec<String>,
+    /// Live MCP sessions of the agent
+    pub sessions: Vec<SessionInfo>,
+    /// Has a live session or a running worker process
+    pub online: bool,
+    /// Agent groups the agent belongs to
+    pub groups: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProjectAgentsQuery {
+    /// Only members of this agent group
+    pub group_id: Option<String>,
+}
+
+impl ProjectAgent {
+    fn new(agent_id: String) -> ProjectAgent {
+        ProjectAgent {
+            agent_id,
+            worker_type: None,
+            status: None,
+            project_id: None,
+            assigned_tickets: Vec::new(),
+            sessions: Vec::new(),
+            online: false,
+            groups: Vec::new(),
+        }
+    }
+}
+
+/// Agents of a project, online or holding one of its tickets, ordered by id
+pub(super) async fn project_agents(
+    state: &AppState,
+    project_id: &str,
+) -> Result<Vec<ProjectAgent>, AppError> {
+    let mut assignments = Worker::assignments(&state.db, project_id).await?;
+    let mut workers = Worker::list_by_project(&state.db, Some(project_id)).await?;
+    for worker_id in assignments.keys() {
+        if !workers.iter().any(|w| &w.worker_id == worker_id) {
+            workers.extend(Worker::get_by_id(&state.db, worker_id).await?);
+        }
+    }
+
+    let mut agents: BTreeMap<String, ProjectAgent> = BTreeMap::new();
+    for worker in workers {
+        let mut agent = ProjectAgent::new(worker.worker_id.clone());
+        agent.online = LIVE_WORKER_STATUSES.contains(&worker.status.as_str());
+        agent.assigned_tickets = assignments.remove(&worker.worker_id).unwrap_or_default();
+        agent.worker_type = Some(worker.worker_type);
+        agent.status = Some(worker.status);
+        agent.project_id = Some(worker.project_id);
+        agents.insert(worker.worker_id, agent);
+    }
+    // Tickets held by workers whose records are gone
+    for (worker_id, tickets) in assignments {
+        let mut agent = ProjectAgent::new(worker_id.clone());
+        agent.assigned_tickets = tickets;
+        agents.insert(worker_id, agent);
+    }
+    for session in state.mcp_server.sessions.list() {
+        let agent_id = match &session.worker_id {
+            Some(worker_id) if agents.contains_key(worker_id) => worker_id.clone(),
+            _ if session.metadata_project() == Some(project_id) => session
+                .worker_id
+                .clone()
+                .unwrap_or_else(|| session.session_id.clone()),
+            _ => continue,
+        };
+        let agent = agents
+            .entry(agent_id.clone())
+            .or_insert_with(|| ProjectAgent::new(agent_id));
+        agent.online = true;
+        agent.sessions.push(session);
+    }
+
+    let mut memberships = AgentGroup::memberships(&state.db).await?;
+    for agent in agents.values_mut() {
+        agent.groups = memberships.remove(&agent.agent_id).unwrap_or_default();
+    }
+
+    // Finished and failed workers holding nothing are history, not agents
+    Ok(agents
+        .into_values()
+        .filter(|agent| agent.online || !agent.assigned_tickets.is_empty())
+        .collect())
+}
+
+/// GET /api/projects/:project_id/agents - Agents working on the project: its running workers,
+/// workers processing its tickets, and MCP clients whose initialize `_meta` names it as
+/// `project_id`, with their tickets, live sessions and agent groups
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/agents",
+    tag = "projects",
+    params(("project_id" = String, Path, description = "Project id"), ProjectAgentsQuery),
+    responses(
+        (status = 200, description = "Agents of the project", body = [ProjectAgent]),
+        (status = 404, description = "Project or agent group not found", body = ErrorResponse)
+    )
+)]
+pub async fn list_project_agents(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+    Query(query): Query<ProjectAgentsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    require_project(&state, &project_id).await?;
+
+    let mut agents = project_agents(&state, &project_id).await?;
+    if let Some(group_id) = &query.group_id {
+        let group = AgentGroup::get(&state.db, group_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Agent group '{}' not found", group_id)))?;
+        agents.retain(|agent| agent.groups.contains(&group.group_id));
+    }
+
+    Ok((StatusCode::OK, Json(agents)))
+}
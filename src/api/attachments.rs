@@ -0,0 +1,184 @@
+use axum::{
+    extract::{Multipart, Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json},
+};
+
+use crate::{
+    attachments::AttachmentStore, database::attachments::Attachment, error::AppError,
+    server::AppState,
+};
+
+use super::tickets::resolve_ticket_path;
+
+/// GET /api/projects/:project_id/tickets/:ticket_id/attachments - List a ticket's attachments
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/tickets/{ticket_id}/attachments",
+    tag = "attachments",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("ticket_id" = String, Path, description = "Ticket id, short id or number"),
+    ),
+    responses(
+        (status = 200, description = "The ticket's attachments", body = [Attachment]),
+        (status = 404, description = "Ticket not found", body = ErrorResponse)
+    )
+)]
+pub async fn list_attachments(
+    State(state): State<AppState>,
+    Path((project_id, ticket_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let ticket_id = resolve_ticket_path(&state, &project_id, &ticket_id).await?;
+    let attachments = Attachment::list_for_ticket(&state.db, &ticket_id).await?;
+
+    Ok((StatusCode::OK, Json(attachments)))
+}
+
+/// POST /api/projects/:project_id/tickets/:ticket_id/attachments - Upload a file (multipart field
+/// `file`, optional text field `uploaded_by`)
+#[utoipa::path(
+    post,
+    path = "/api/projects/{project_id}/tickets/{ticket_id}/attachments",
+    tag = "attachments",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("ticket_id" = String, Path, description = "Ticket id, short id or number"),
+    ),
+    request_body(content = AttachmentUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Attachment stored", body = Attachment),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Ticket not found", body = ErrorResponse)
+    )
+)]
+pub async fn upload_attachment(
+    State(state): State<AppState>,
+    Path((project_id, ticket_id)): Path<(String, String)>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, AppError> {
+    let ticket_id = resolve_ticket_path(&state, &project_id, &ticket_id).await?;
+
+    let mut file = None;
+    let mut uploaded_by = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?
+    {
+        match field.name() {
+            Some("file") => {
+                let filename = field.file_name().unwrap_or_default().to_string();
+                let content_type = field.content_type().map(str::to_string);
+                let bytes = field
+                    .bytes()
+                    .await
+                    .map_err(|e| AppError::BadRequest(e.to_string()))?;
+                file = Some((filename, content_type, bytes));
+            }
+            Some("uploaded_by") => {
+                uploaded_by = Some(
+                    field
+                        .text()
+                        .await
+                        .map_err(|e| AppError::BadRequest(e.to_string()))?,
+                );
+            }
+            _ => {}
+        }
+    }
+    let Some((filename, content_type, bytes)) = file else {
+        return Err(AppError::BadRequest(
+            "Missing multipart field 'file'".to_string(),
+        ));
+    };
+
+    let attachment = AttachmentStore::new(&state.config)
+        .save(
+            &state.db,
+            &ticket_id,
+            &filename,
+            content_type.as_deref(),
+            &bytes,
+            uploaded_by.as_deref(),
+        )
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    Ok((StatusCode::CREATED, Json(attachment)))
+}
+
+/// GET /api/projects/:project_id/tickets/:ticket_id/attachments/:id - Download an attachment
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/tickets/{ticket_id}/attachments/{id}",
+    tag = "attachments",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("ticket_id" = String, Path, description = "Ticket id, short id or number"),
+        ("id" = i64, Path, description = "Attachment id"),
+    ),
+    responses(
+        (status = 200, description = "The file, with its stored content type"),
+        (status = 404, description = "Attachment not found", body = ErrorResponse)
+    )
+)]
+pub async fn download_attachment(
+    State(state): State<AppState>,
+    Path((project_id, ticket_id, id)): Path<(String, String, i64)>,
+) -> Result<impl IntoResponse, AppError> {
+    let ticket_id = resolve_ticket_path(&state, &project_id, &ticket_id).await?;
+    let Some(attachment) = Attachment::get(&state.db, &ticket_id, id).await? else {
+        return Err(AppError::NotFound(format!("Attachment {} not found", id)));
+    };
+    let path = AttachmentStore::new(&state.config).path(&attachment)?;
+    let content = tokio::fs::read(&path).await.map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            AppError::NotFound(format!("File of attachment {} is missing", id))
+        } else {
+            AppError::Io(e)
+        }
+    })?;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, attachment.content_type),
+            (
+                header::CONTENT_DISPOSITION,
+                // Stored file names are sanitized to characters that are safe here
+                format!("attachment; filename=\"{}\"", attachment.filename),
+            ),
+        ],
+        content,
+    ))
+}
+
+/// DELETE /api/projects/:project_id/tickets/:ticket_id/attachments/:id - Delete an attachment and its file
+#[utoipa::path(
+    delete,
+    path = "/api/projects/{project_id}/tickets/{ticket_id}/attachments/{id}",
+    tag = "attachments",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("ticket_id" = String, Path, description = "Ticket id, short id or number"),
+        ("id" = i64, Path, description = "Attachment id"),
+    ),
+    responses(
+        (status = 204, description = "Attachment deleted"),
+        (status = 404, description = "Attachment not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_attachment(
+    State(state): State<AppState>,
+    Path((project_id, ticket_id, id)): Path<(String, String, i64)>,
+) -> Result<impl IntoResponse, AppError> {
+    let ticket_id = resolve_ticket_path(&state, &project_id, &ticket_id).await?;
+    match AttachmentStore::new(&state.config)
+        .remove(&state.db, &ticket_id, id)
+        .await?
+    {
+        Some(_) => Ok(StatusCode::NO_CONTENT),
+        None => Err(AppError::NotFound(format!("Attachment {} not found", id))),
+    }
+}
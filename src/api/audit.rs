@@ -0,0 +1,52 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{
+    database::audit::{AuditEntry, AuditQuery},
+    error::AppError,
+    server::AppState,
+};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    /// Audit records dropped because the buffer overflowed
+    pub dropped: u64,
+}
+
+/// GET /api/audit - Query audit log entries (filters: actor, method, from, to; paging: limit, offset)
+#[utoipa::path(
+    get,
+    path = "/api/audit",
+    tag = "admin",
+    params(AuditQuery),
+    responses(
+        (status = 200, description = "Audit log entries", body = AuditPage),
+        (status = 400, description = "Invalid from or to", body = ErrorResponse)
+    )
+)]
+pub async fn list_audit_entries(
+    State(state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let query = query.normalized().map_err(|value| {
+        AppError::BadRequest(format!(
+            "Invalid timestamp '{}': expected RFC 3339, 'YYYY-MM-DD HH:MM:SS' or a date",
+            value
+        ))
+    })?;
+    let entries = AuditEntry::list(&state.db, &query).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(AuditPage {
+            entries,
+            dropped: state.audit_logger.dropped_count(),
+        }),
+    ))
+}
@@ -0,0 +1,171 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+    Extension,
+};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    api::access::ApiCaller,
+    audit::AuditRecord,
+    database::blocks::{self, BlockOutcome, Blocker},
+    error::AppError,
+    server::AppState,
+};
+
+use super::{projects::require_project, relations::resolve_related, tickets::resolve_ticket_path};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BlockedQuery {
+    pub project_id: Option<String>,
+}
+
+/// Either `blocking_ticket_id`, or `responsible_agent` with `expected_unblock_at`
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BlockTicketRequest {
+    pub reason: String,
+    /// Ticket id, or a short id or number within the project; the block is lifted when it
+    /// closes
+    pub blocking_ticket_id: Option<String>,
+    /// Agent (worker id or `coordinator`) expected to unblock the ticket
+    pub responsible_agent: Option<String>,
+    /// RFC 3339 timestamp; the responsible agent is reminded once it passes
+    pub expected_unblock_at: Option<String>,
+    /// Recorded as who blocked the ticket; defaults to `api`
+    pub blocked_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UnblockTicketRequest {
+    /// Recorded as who unblocked the ticket; defaults to `api`
+    pub unblocked_by: Option<String>,
+    /// Added to the unblock comment
+    pub note: Option<String>,
+}
+
+/// GET /api/blocked - Tickets with a block in force, with their age, blocker and reminders,
+/// longest blocked first
+#[utoipa::path(
+    get,
+    path = "/api/blocked",
+    tag = "tickets",
+    params(BlockedQuery),
+    responses(
+        (status = 200, description = "Blocked tickets", body = [BlockedTicket])
+    )
+)]
+pub async fn list_blocked(
+    State(state): State<AppState>,
+    Query(query): Query<BlockedQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    if let Some(project_id) = &query.project_id {
+        require_project(&state, project_id).await?;
+    }
+    Ok((
+        StatusCode::OK,
+        Json(blocks::list_blocked(&state.db, query.project_id.as_deref()).await?),
+    ))
+}
+
+/// POST /api/projects/:project_id/tickets/:ticket_id/block - Block a ticket on another
+/// ticket or on an agent; the ticket is put on hold until it is unblocked
+#[utoipa::path(
+    post,
+    path = "/api/projects/{project_id}/tickets/{ticket_id}/block",
+    tag = "tickets",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("ticket_id" = String, Path, description = "Ticket id, short id or number"),
+    ),
+    request_body = BlockTicketRequest,
+    responses(
+        (status = 200, description = "The block", body = TicketBlock),
+        (status = 400, description = "The block is not allowed", body = ErrorResponse),
+        (status = 404, description = "Ticket not found", body = ErrorResponse)
+    )
+)]
+pub async fn block_ticket(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Path((project_id, ticket_id)): Path<(String, String)>,
+    Json(req): Json<BlockTicketRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let ticket_id = resolve_ticket_path(&state, &project_id, &ticket_id).await?;
+    let blocking_ticket_id = match &req.blocking_ticket_id {
+        Some(reference) => Some(resolve_related(&state, &project_id, reference).await?),
+        None => None,
+    };
+    let blocker = Blocker::from_args(
+        blocking_ticket_id,
+        req.responsible_agent.clone(),
+        req.expected_unblock_at.clone(),
+    )
+    .map_err(AppError::BadRequest)?;
+    let blocked_by = req.blocked_by.as_deref().unwrap_or("api");
+    let block = match state
+        .queue_manager
+        .block_ticket(&ticket_id, &req.reason, &blocker, blocked_by)
+        .await?
+    {
+        BlockOutcome::Blocked(block) => *block,
+        BlockOutcome::Rejected(reason) => return Err(AppError::BadRequest(reason)),
+    };
+    state.audit_logger.record(
+        AuditRecord::new(&caller.name, "block_ticket", "success").with_params(Some(
+            &serde_json::json!({
+                "ticket_id": ticket_id,
+                "reason": req.reason,
+                "blocking_ticket_id": block.blocking_ticket_id,
+                "responsible_agent": block.responsible_agent,
+                "expected_unblock_at": block.expected_unblock_at
+            }),
+        )),
+    );
+
+    Ok((StatusCode::OK, Json(block)))
+}
+
+/// POST /api/projects/:project_id/tickets/:ticket_id/unblock - Lift a ticket's block and
+/// queue it again; records who unblocked it and how long it was blocked
+#[utoipa::path(
+    post,
+    path = "/api/projects/{project_id}/tickets/{ticket_id}/unblock",
+    tag = "tickets",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("ticket_id" = String, Path, description = "Ticket id, short id or number"),
+    ),
+    request_body = UnblockTicketRequest,
+    responses(
+        (status = 200, description = "The lifted block", body = LiftedBlock),
+        (status = 400, description = "The ticket is not blocked", body = ErrorResponse),
+        (status = 404, description = "Ticket not found", body = ErrorResponse)
+    )
+)]
+pub async fn unblock_ticket(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Path((project_id, ticket_id)): Path<(String, String)>,
+    Json(req): Json<UnblockTicketRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let ticket_id = resolve_ticket_path(&state, &project_id, &ticket_id).await?;
+    let unblocked_by = req.unblocked_by.as_deref().unwrap_or("api");
+    let lifted = state
+        .queue_manager
+        .unblock_ticket(&ticket_id, unblocked_by, req.note.as_deref())
+        .await?
+        .ok_or_else(|| AppError::BadRequest(format!("Ticket '{}' is not blocked", ticket_id)))?;
+    state.audit_logger.record(
+        AuditRecord::new(&caller.name, "unblock_ticket", "success").with_params(Some(
+            &serde_json::json!({
+                "ticket_id": ticket_id,
+                "blocked_minutes": lifted.blocked_minutes
+            }),
+        )),
+    );
+
+    Ok((StatusCode::OK, Json(lifted)))
+}
@@ -0,0 +1,331 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use tracing::info;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    database::{
+        board::{BoardCard, BoardColumn},
+        projects::Project,
+        relations,
+        ticket_timeline::{self, TicketStateAt},
+        tickets::Ticket,
+    },
+    error::AppError,
+    server::AppState,
+    workers::queue::QueueManager,
+};
+
+use super::tickets::{as_of_instant, resolve_ticket_path};
+
+const DEFAULT_COLUMN_LIMIT: usize = 50;
+const MAX_COLUMN_LIMIT: usize = 200;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BoardQuery {
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BoardColumnView {
+    pub column: BoardColumn,
+    pub count: usize,
+    pub has_more: bool,
+    pub items: Vec<BoardCard>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BoardView {
+    pub project_id: String,
+    pub columns: Vec<BoardColumnView>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BoardAsOfQuery {
+    /// RFC 3339, `YYYY-MM-DD HH:MM:SS` (UTC) or a date (its start)
+    pub timestamp: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BoardColumnAsOf {
+    pub column: BoardColumn,
+    pub count: usize,
+    pub items: Vec<TicketStateAt>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BoardAsOfView {
+    pub project_id: String,
+    /// The instant, in SQLite's UTC timestamp format
+    pub as_of: String,
+    pub columns: Vec<BoardColumnAsOf>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MoveTicketRequest {
+    pub column: BoardColumn,
+    pub position: usize,
+}
+
+/// GET /api/projects/:project_id/board - Tickets grouped into board columns; parent tickets
+/// carry their closed and total child counts
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/board",
+    tag = "board",
+    params(("project_id" = String, Path, description = "Project id"), BoardQuery),
+    responses(
+        (status = 200, description = "Board columns with their cards", body = BoardView)
+    )
+)]
+pub async fn get_board(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+    Query(query): Query<BoardQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_COLUMN_LIMIT)
+        .clamp(1, MAX_COLUMN_LIMIT);
+    let mut cards = BoardCard::list_by_project(&state.db, &project_id).await?;
+    let mut progress = relations::progress_by_parent(&state.db, &project_id).await?;
+    for card in &mut cards {
+        card.progress = progress.remove(&card.ticket_id);
+    }
+
+    let columns = BoardColumn::all()
+        .into_iter()
+        .map(|column| {
+            let mut items: Vec<BoardCard> = cards
+                .iter()
+                .filter(|c| c.column() == column)
+                .cloned()
+                .collect();
+            let count = items.len();
+            items.truncate(limit);
+            BoardColumnView {
+                column,
+                count,
+                has_more: count > limit,
+                items,
+            }
+        })
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(BoardView {
+            project_id,
+            columns,
+        }),
+    ))
+}
+
+/// GET /api/projects/:project_id/board/as-of - Every ticket that existed at `timestamp`,
+/// in the column it was in then, oldest first
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/board/as-of",
+    tag = "board",
+    params(("project_id" = String, Path, description = "Project id"), BoardAsOfQuery),
+    responses(
+        (status = 200, description = "Board columns at that instant", body = BoardAsOfView),
+        (status = 400, description = "Invalid timestamp", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_board_as_of(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+    Query(query): Query<BoardAsOfQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let at = as_of_instant(&query.timestamp)?;
+    if Project::get_by_id(&state.db, &project_id).await?.is_none() {
+        return Err(AppError::NotFound(format!(
+            "Project '{}' not found",
+            project_id
+        )));
+    }
+    let tickets = ticket_timeline::project_at(&state.db, &project_id, &at).await?;
+
+    let columns = BoardColumn::all()
+        .into_iter()
+        .map(|column| {
+            let items: Vec<TicketStateAt> = tickets
+                .iter()
+                .filter(|t| t.column() == column)
+                .cloned()
+                .collect();
+            BoardColumnAsOf {
+                column,
+                count: items.len(),
+                items,
+            }
+        })
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(BoardAsOfView {
+            project_id,
+            as_of: at,
+            columns,
+        }),
+    ))
+}
+
+/// PATCH /api/projects/:project_id/tickets/:ticket_id/position - Move a ticket on the board
+#[utoipa::path(
+    patch,
+    path = "/api/projects/{project_id}/tickets/{ticket_id}/position",
+    tag = "board",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("ticket_id" = String, Path, description = "Ticket id, short id or number"),
+    ),
+    request_body = MoveTicketRequest,
+    responses(
+        (status = 200, description = "The moved ticket", body = Ticket),
+        (status = 400, description = "The move is not allowed", body = ErrorResponse),
+        (status = 404, description = "Ticket not found", body = ErrorResponse)
+    )
+)]
+pub async fn move_ticket(
+    State(state): State<AppState>,
+    Path((project_id, ticket_id)): Path<(String, String)>,
+    Json(req): Json<MoveTicketRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let ticket_id = resolve_ticket_path(&state, &project_id, &ticket_id).await?;
+    let cards = BoardCard::list_by_project(&state.db, &project_id).await?;
+    let card = cards
+        .iter()
+        .find(|c| c.ticket_id == ticket_id)
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Ticket '{}' not found in project '{}'",
+                ticket_id, project_id
+            ))
+        })?;
+
+    // The stage transition and the new column order are committed together
+    let from = card.column();
+    let mut tx = state.db.begin().await?;
+    let (moved, after) = if from != req.column {
+        transition(&state, &mut tx, card, from, req.column).await?
+    } else {
+        (from, AfterMove::Nothing)
+    };
+
+    let mut order: Vec<String> = cards
+        .iter()
+        .filter(|c| c.column() == moved && c.ticket_id != ticket_id)
+        .map(|c| c.ticket_id.clone())
+        .collect();
+    order.insert(req.position.min(order.len()), ticket_id.clone());
+    BoardCard::save_order(&mut tx, &order).await?;
+    tx.commit().await?;
+
+    match after {
+        AfterMove::Nothing => {}
+        AfterMove::Closed(closed) => state.queue_manager.after_close(&closed).await?,
+        AfterMove::Reopened => {
+            state
+                .queue_manager
+                .submit_task(&project_id, &card.current_stage, &card.ticket_id)
+                .await?;
+        }
+    }
+
+    let ticket = Ticket::get_by_id(&state.db, &ticket_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found", ticket_id)))?;
+
+    Ok((StatusCode::OK, Json(ticket)))
+}
+
+/// Work left once a board move is committed
+enum AfterMove {
+    Nothing,
+    Closed(Box<Ticket>),
+    Reopened,
+}
+
+/// Apply the state change implied by moving a card between columns within `conn`'s
+/// transaction, returning the column the card ends up in
+async fn transition(
+    state: &AppState,
+    conn: &mut SqliteConnection,
+    card: &BoardCard,
+    from: BoardColumn,
+    to: BoardColumn,
+) -> Result<(BoardColumn, AfterMove), AppError> {
+    if to.is_derived() {
+        return Err(AppError::BadRequest(format!(
+            "Cannot move ticket into '{}': column is derived from processing status",
+            to
+        )));
+    }
+    if from == BoardColumn::Closed {
+        return Err(AppError::BadRequest(format!(
+            "Ticket '{}' is closed and cannot be moved out of the closed column",
+            card.ticket_id
+        )));
+    }
+
+    info!(
+        "Board move for ticket {}: {} -> {}",
+        card.ticket_id, from, to
+    );
+
+    match to {
+        BoardColumn::Closed => {
+            let open_children = relations::children(&state.db, &card.ticket_id)
+                .await?
+                .into_iter()
+                .filter(|child| child.state != "closed")
+                .count();
+            if open_children > 0 {
+                return Err(AppError::BadRequest(format!(
+                    "Ticket '{}' has {} open child tickets; close them first, or close it with close_ticket and cascade",
+                    card.ticket_id, open_children
+                )));
+            }
+            let closed = QueueManager::close_with_comment(
+                conn,
+                &card.ticket_id,
+                "completed",
+                "Closed from the ticket board",
+            )
+            .await?;
+            Ok((BoardColumn::Closed, AfterMove::Closed(Box::new(closed))))
+        }
+        BoardColumn::OnHold => {
+            Ticket::place_on_hold_in(
+                conn,
+                &card.ticket_id,
+                "Placed on hold from the ticket board",
+            )
+            .await?;
+            Ok((BoardColumn::OnHold, AfterMove::Nothing))
+        }
+        BoardColumn::Open if from == BoardColumn::OnHold => {
+            Ticket::update_state_in(conn, &card.ticket_id, "open").await?;
+            let reopened = BoardCard {
+                state: "open".to_string(),
+                ..card.clone()
+            };
+            Ok((reopened.column(), AfterMove::Reopened))
+        }
+        _ => Err(AppError::BadRequest(format!(
+            "Cannot move ticket from '{}' to '{}'",
+            from, to
+        ))),
+    }
+}
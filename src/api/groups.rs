@@ -0,0 +1,220 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+    Extension,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    api::access::ApiCaller,
+    audit::AuditRecord,
+    database::agent_groups::{
+        AgentGroup, CreateAgentGroupRequest, DeleteGroupOutcome, GroupMember,
+    },
+    error::AppError,
+    server::AppState,
+};
+
+/// An agent group with its members
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AgentGroupDetails {
+    #[serde(flatten)]
+    pub group: AgentGroup,
+    pub members: Vec<GroupMember>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteGroupQuery {
+    /// Delete the group even though it has members
+    pub force: Option<bool>,
+}
+
+fn group_not_found(group_id: &str) -> AppError {
+    AppError::NotFound(format!("Agent group '{}' not found", group_id))
+}
+
+/// GET /api/groups - List agent groups with their member counts
+#[utoipa::path(
+    get,
+    path = "/api/groups",
+    tag = "requests",
+    responses(
+        (status = 200, description = "Agent groups", body = [AgentGroup])
+    )
+)]
+pub async fn list_groups(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    Ok((StatusCode::OK, Json(AgentGroup::list(&state.db).await?)))
+}
+
+/// POST /api/groups - Create an agent group
+#[utoipa::path(
+    post,
+    path = "/api/groups",
+    tag = "requests",
+    request_body = CreateAgentGroupRequest,
+    responses(
+        (status = 201, description = "Agent group created", body = AgentGroup),
+        (status = 400, description = "Invalid request", body = ErrorResponse)
+    )
+)]
+pub async fn create_group(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Json(req): Json<CreateAgentGroupRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let group = AgentGroup::create(&state.db, &req)
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    state.audit_logger.record(
+        AuditRecord::new(&caller.name, "create_agent_group", "success")
+            .with_params(Some(&json!({ "group_id": group.group_id }))),
+    );
+
+    Ok((StatusCode::CREATED, Json(group)))
+}
+
+/// GET /api/groups/:group_id - An agent group with its members
+#[utoipa::path(
+    get,
+    path = "/api/groups/{group_id}",
+    tag = "requests",
+    params(("group_id" = String, Path, description = "Agent group id")),
+    responses(
+        (status = 200, description = "Agent group", body = AgentGroupDetails),
+        (status = 404, description = "Agent group not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_group(
+    State(state): State<AppState>,
+    Path(group_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let group = AgentGroup::get(&state.db, &group_id)
+        .await?
+        .ok_or_else(|| group_not_found(&group_id))?;
+    let members = AgentGroup::members(&state.db, &group.group_id)
+        .await?
+        .unwrap_or_default();
+
+    Ok((StatusCode::OK, Json(AgentGroupDetails { group, members })))
+}
+
+/// DELETE /api/groups/:group_id - Delete an agent group; one with members needs `force=true`.
+/// Requests already sent to the group are kept.
+#[utoipa::path(
+    delete,
+    path = "/api/groups/{group_id}",
+    tag = "requests",
+    params(("group_id" = String, Path, description = "Agent group id"), DeleteGroupQuery),
+    responses(
+        (status = 204, description = "Agent group deleted"),
+        (status = 404, description = "Agent group not found", body = ErrorResponse),
+        (status = 409, description = "The group has members and force was not set", body = ErrorResponse)
+    )
+)]
+pub async fn delete_group(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Path(group_id): Path<String>,
+    Query(query): Query<DeleteGroupQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let force = query.force.unwrap_or(false);
+    match AgentGroup::delete(&state.db, &group_id, force).await? {
+        DeleteGroupOutcome::Deleted { members } => {
+            state.audit_logger.record(
+                AuditRecord::new(&caller.name, "delete_agent_group", "success").with_params(Some(
+                    &json!({ "group_id": group_id, "members": members, "force": force }),
+                )),
+            );
+            Ok(StatusCode::NO_CONTENT.into_response())
+        }
+        DeleteGroupOutcome::NotFound => Err(group_not_found(&group_id)),
+        DeleteGroupOutcome::HasMembers { members } => Ok(crate::error::error_response(
+            StatusCode::CONFLICT,
+            format!(
+                "Agent group '{}' has {} members; delete with force=true to remove it anyway",
+                group_id, members
+            ),
+        )),
+    }
+}
+
+/// PUT /api/groups/:group_id/members/:agent_id - Add an agent to a group; adding a member
+/// again changes nothing
+#[utoipa::path(
+    put,
+    path = "/api/groups/{group_id}/members/{agent_id}",
+    tag = "requests",
+    params(
+        ("group_id" = String, Path, description = "Agent group id"),
+        ("agent_id" = String, Path, description = "Worker id or coordinator"),
+    ),
+    responses(
+        (status = 200, description = "Members of the group", body = AgentGroupDetails),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Agent group not found", body = ErrorResponse)
+    )
+)]
+pub async fn add_group_member(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Path((group_id, agent_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let joined = AgentGroup::join(&state.db, &group_id, &agent_id, "api")
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?
+        .ok_or_else(|| group_not_found(&group_id))?;
+    if joined {
+        state.audit_logger.record(
+            AuditRecord::new(&caller.name, "join_agent_group", "success").with_params(Some(
+                &json!({
+                    "group_id": group_id,
+                    "agent": agent_id
+                }),
+            )),
+        );
+    }
+
+    get_group(State(state), Path(group_id)).await
+}
+
+/// DELETE /api/groups/:group_id/members/:agent_id - Remove an agent from a group
+#[utoipa::path(
+    delete,
+    path = "/api/groups/{group_id}/members/{agent_id}",
+    tag = "requests",
+    params(
+        ("group_id" = String, Path, description = "Agent group id"),
+        ("agent_id" = String, Path, description = "Worker id or coordinator"),
+    ),
+    responses(
+        (status = 200, description = "Members of the group", body = AgentGroupDetails),
+        (status = 404, description = "Agent group or member not found", body = ErrorResponse)
+    )
+)]
+pub async fn remove_group_member(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Path((group_id, agent_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let left = AgentGroup::leave(&state.db, &group_id, &agent_id)
+        .await?
+        .ok_or_else(|| group_not_found(&group_id))?;
+    if !left {
+        return Err(AppError::NotFound(format!(
+            "'{}' is not a member of agent group '{}'",
+            agent_id, group_id
+        )));
+    }
+    state.audit_logger.record(
+        AuditRecord::new(&caller.name, "leave_agent_group", "success").with_params(Some(&json!({
+            "group_id": group_id,
+            "agent": agent_id
+        }))),
+    );
+
+    get_group(State(state), Path(group_id)).await
+}
@@ -0,0 +1,145 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+
+use crate::{
+    database::{cache, timing},
+    error::AppError,
+    mcp::{connections, limits},
+    outbox, redaction,
+    server::AppState,
+};
+
+/// GET /api/internal/cache-stats - Hit/miss counters for cached lookups per entity type
+#[utoipa::path(
+    get,
+    path = "/api/internal/cache-stats",
+    tag = "diagnostics",
+    responses(
+        (status = 200, description = "Cache counters per entity type", body = [CacheStats])
+    )
+)]
+pub async fn cache_stats(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    Ok((StatusCode::OK, Json(cache::for_pool(&state.db).stats())))
+}
+
+/// GET /api/internal/spawn-queue-stats - Queued worker spawns by priority with their longest waits
+#[utoipa::path(
+    get,
+    path = "/api/internal/spawn-queue-stats",
+    tag = "diagnostics",
+    responses(
+        (status = 200, description = "Spawn queue depth and waits", body = SpawnQueueStats)
+    )
+)]
+pub async fn spawn_queue_stats(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok((
+        StatusCode::OK,
+        Json(state.queue_manager.spawn_limiter().stats()),
+    ))
+}
+
+/// GET /api/internal/outbox-stats - Events waiting in the outbox, how long the oldest has
+/// waited, and dispatch counters since startup
+#[utoipa::path(
+    get,
+    path = "/api/internal/outbox-stats",
+    tag = "diagnostics",
+    responses(
+        (status = 200, description = "Outbox lag and counters", body = OutboxStats)
+    )
+)]
+pub async fn outbox_stats(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    Ok((StatusCode::OK, Json(outbox::stats(&state.db).await?)))
+}
+
+/// GET /api/internal/connection-limits - Oversized messages and notification overflows since startup
+#[utoipa::path(
+    get,
+    path = "/api/internal/connection-limits",
+    tag = "diagnostics",
+    responses(
+        (status = 200, description = "Connection limit counters", body = LimitStats)
+    )
+)]
+pub async fn connection_limit_stats() -> Result<impl IntoResponse, AppError> {
+    Ok((StatusCode::OK, Json(limits::stats())))
+}
+
+/// GET /api/internal/query-stats - Latency histograms of repository queries since startup, the
+/// slow query threshold and current pool usage
+#[utoipa::path(
+    get,
+    path = "/api/internal/query-stats",
+    tag = "diagnostics",
+    responses(
+        (status = 200, description = "Query latencies, slowest on average first", body = QueryTimingReport)
+    )
+)]
+pub async fn query_stats(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    Ok((StatusCode::OK, Json(timing::report(&state.db))))
+}
+
+/// GET /api/internal/redaction-stats - Secrets masked in logs, dashboard responses and stored
+/// comments since startup
+#[utoipa::path(
+    get,
+    path = "/api/internal/redaction-stats",
+    tag = "diagnostics",
+    responses(
+        (status = 200, description = "Redaction counters", body = RedactionStats)
+    )
+)]
+pub async fn redaction_stats() -> Result<impl IntoResponse, AppError> {
+    Ok((StatusCode::OK, Json(redaction::stats())))
+}
+
+/// GET /api/internal/session-stats - Tracked MCP sessions by transport, and sessions expired or dropped since startup
+#[utoipa::path(
+    get,
+    path = "/api/internal/session-stats",
+    tag = "diagnostics",
+    responses(
+        (status = 200, description = "Session counters", body = SessionStats)
+    )
+)]
+pub async fn session_stats(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    Ok((StatusCode::OK, Json(state.mcp_server.sessions.stats())))
+}
+
+/// GET /api/internal/warm-pool-stats - Warm pool claims and misses, recycled workers, and
+/// claim latency next to cold spawn latency
+#[utoipa::path(
+    get,
+    path = "/api/internal/warm-pool-stats",
+    tag = "diagnostics",
+    responses(
+        (status = 200, description = "Warm pool counters and latencies", body = WarmPoolStats)
+    )
+)]
+pub async fn warm_pool_stats(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    Ok((
+        StatusCode::OK,
+        Json(state.queue_manager.warm_pool().stats()),
+    ))
+}
+
+/// GET /api/debug/connections - Live MCP connections with their traffic and queued notifications,
+/// and connections opened and closed since startup; sensitive client metadata is masked
+#[utoipa::path(
+    get,
+    path = "/api/debug/connections",
+    tag = "diagnostics",
+    responses(
+        (status = 200, description = "Live MCP connections and totals", body = ConnectionReport)
+    )
+)]
+pub async fn debug_connections(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok((StatusCode::OK, Json(connections::snapshot(&state))))
+}
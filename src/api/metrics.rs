@@ -0,0 +1,70 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{
+    database::{
+        cache,
+        throughput::{self, DEFAULT_THROUGHPUT_WEEKS, MAX_THROUGHPUT_WEEKS},
+    },
+    error::AppError,
+    server::AppState,
+};
+
+use super::projects::require_project;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ThroughputQuery {
+    /// Weeks ending today to cover, 8 by default
+    pub weeks: Option<i64>,
+    pub project_id: Option<String>,
+}
+
+/// GET /api/metrics/throughput - Tickets created, resolved and reopened per day, median and
+/// p90 time to pickup and to resolution, resolutions per agent and an activity heatmap by
+/// day of week and hour, over the last `weeks` weeks. Results are cached for
+/// `cache_ttl_secs`, or until a ticket changes.
+#[utoipa::path(
+    get,
+    path = "/api/metrics/throughput",
+    tag = "stats",
+    params(ThroughputQuery),
+    responses(
+        (status = 200, description = "Throughput metrics", body = ThroughputMetrics),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_throughput(
+    State(state): State<AppState>,
+    Query(query): Query<ThroughputQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let weeks = query.weeks.unwrap_or(DEFAULT_THROUGHPUT_WEEKS);
+    if !(1..=MAX_THROUGHPUT_WEEKS).contains(&weeks) {
+        return Err(AppError::BadRequest(format!(
+            "weeks must be between 1 and {}",
+            MAX_THROUGHPUT_WEEKS
+        )));
+    }
+    let project_id = query.project_id.as_deref();
+    if let Some(project_id) = project_id {
+        require_project(&state, project_id).await?;
+    }
+
+    let watermark = throughput::watermark(&state.db).await?;
+    let metrics = cache::for_pool(&state.db)
+        .throughput
+        .get_or_load(
+            &cache::throughput_key(project_id, weeks, watermark),
+            || async { Ok(Some(throughput::load(&state.db, project_id, weeks).await?)) },
+        )
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Throughput metrics were not computed"))?;
+
+    Ok((StatusCode::OK, Json(metrics)))
+}
@@ -1,18 +1,291 @@
+pub mod access;
+pub mod admin;
+pub mod agents;
+pub mod attachments;
+pub mod audit;
+pub mod blocks;
+pub mod board;
+pub mod groups;
+pub mod internal;
+pub mod metrics;
+pub mod openapi;
 pub mod projects;
+pub mod relations;
+pub mod requests;
+pub mod schedules;
+pub mod sessions;
+pub mod stats;
+pub mod tags;
+pub mod ticket_templates;
 pub mod tickets;
+pub mod timeline;
+pub mod webhooks;
+pub mod worker_types;
 
-use axum::{routing::get, Router};
+use axum::{
+    extract::DefaultBodyLimit,
+    routing::{delete, get, patch, post, put},
+    Router,
+};
 
 use crate::server::AppState;
+use access::{requires, Permission::*};
 
-/// Create the API router with all endpoint routes
+/// Create the API router with all endpoint routes. Routes not wrapped in `requires` only
+/// read, which every role may do.
 pub fn create_api_router() -> Router<AppState> {
     Router::new()
+        .route("/access", get(access::get_access))
+        .route("/audit", requires(Admin, get(audit::list_audit_entries)))
+        .route("/admin/backup", requires(Admin, post(admin::create_backup)))
+        .route(
+            "/admin/reload-config",
+            requires(Admin, post(admin::reload_config)),
+        )
+        .route(
+            "/admin/reload-tool-policy",
+            requires(Admin, post(admin::reload_tool_policy)),
+        )
+        .route("/admin/seed-demo", requires(Admin, post(admin::seed_demo)))
+        .route("/admin/outbox", requires(Admin, get(admin::list_outbox)))
+        .route(
+            "/admin/outbox/:id/requeue",
+            requires(Admin, post(admin::requeue_outbox_event)),
+        )
+        .route(
+            "/admin/log-level",
+            requires(Admin, get(admin::get_log_level).put(admin::set_log_level)),
+        )
+        .route("/blocked", get(blocks::list_blocked))
+        .route(
+            "/debug/connections",
+            requires(Admin, get(internal::debug_connections)),
+        )
+        .route(
+            "/groups",
+            get(groups::list_groups).merge(requires(ManageWorkers, post(groups::create_group))),
+        )
+        .route(
+            "/groups/:group_id",
+            get(groups::get_group).merge(requires(ManageWorkers, delete(groups::delete_group))),
+        )
+        .route(
+            "/groups/:group_id/members/:agent_id",
+            requires(
+                ManageWorkers,
+                put(groups::add_group_member).delete(groups::remove_group_member),
+            ),
+        )
+        .route(
+            "/internal/cache-stats",
+            requires(Admin, get(internal::cache_stats)),
+        )
+        .route(
+            "/internal/connection-limits",
+            requires(Admin, get(internal::connection_limit_stats)),
+        )
+        .route(
+            "/internal/outbox-stats",
+            requires(Admin, get(internal::outbox_stats)),
+        )
+        .route(
+            "/internal/query-stats",
+            requires(Admin, get(internal::query_stats)),
+        )
+        .route(
+            "/internal/redaction-stats",
+            requires(Admin, get(internal::redaction_stats)),
+        )
+        .route(
+            "/internal/session-stats",
+            requires(Admin, get(internal::session_stats)),
+        )
+        .route(
+            "/internal/spawn-queue-stats",
+            requires(Admin, get(internal::spawn_queue_stats)),
+        )
+        .route(
+            "/internal/warm-pool-stats",
+            requires(Admin, get(internal::warm_pool_stats)),
+        )
+        .route("/metrics/throughput", get(metrics::get_throughput))
         .route("/projects", get(projects::list_projects))
         .route("/projects/:project_id", get(projects::get_project))
+        .route(
+            "/projects/:project_id/settings",
+            get(projects::get_project_settings)
+                .merge(requires(Admin, put(projects::update_project_settings))),
+        )
+        .route(
+            "/projects/:project_id/quota",
+            get(projects::get_project_quota),
+        )
+        .route(
+            "/projects/:project_id/agents",
+            get(agents::list_project_agents),
+        )
+        .route("/projects/:project_id/board", get(board::get_board))
+        .route(
+            "/projects/:project_id/board/as-of",
+            get(board::get_board_as_of),
+        )
+        .route(
+            "/projects/:project_id/burndown",
+            get(projects::get_burndown),
+        )
+        .route(
+            "/projects/:project_id/messages",
+            get(requests::list_project_messages),
+        )
+        .route("/projects/:project_id/stats", get(stats::get_project_stats))
         .route("/projects/:project_id/tickets", get(tickets::list_tickets))
+        .route(
+            "/projects/:project_id/tickets/duplicates",
+            get(tickets::check_duplicates),
+        )
+        .route(
+            "/projects/:project_id/tickets/import",
+            // reject_oversized_body applies the import size limit
+            requires(WriteTickets, post(tickets::import_tickets_csv))
+                .layer(DefaultBodyLimit::disable()),
+        )
         .route(
             "/projects/:project_id/tickets/:ticket_id",
             get(tickets::get_ticket_with_comments),
         )
+        .route(
+            "/projects/:project_id/tickets/:ticket_id/comments/:comment_id",
+            requires(WriteTickets, delete(tickets::delete_comment)),
+        )
+        .route(
+            "/projects/:project_id/tickets/:ticket_id/comments/:comment_id/thread",
+            get(tickets::get_comment_thread),
+        )
+        .route(
+            "/projects/:project_id/tickets/:ticket_id/summaries",
+            get(tickets::list_thread_summaries),
+        )
+        .route(
+            "/projects/:project_id/tickets/:ticket_id/attachments",
+            // reject_oversized_body applies the attachment size limit to uploads
+            get(attachments::list_attachments)
+                .merge(requires(WriteTickets, post(attachments::upload_attachment)))
+                .layer(DefaultBodyLimit::disable()),
+        )
+        .route(
+            "/projects/:project_id/tickets/:ticket_id/attachments/:id",
+            get(attachments::download_attachment).merge(requires(
+                WriteTickets,
+                delete(attachments::delete_attachment),
+            )),
+        )
+        .route(
+            "/projects/:project_id/tickets/:ticket_id/relations",
+            get(relations::list_relations)
+                .merge(requires(WriteTickets, post(relations::add_relation))),
+        )
+        .route(
+            "/projects/:project_id/tickets/:ticket_id/relations/:relation_type/:related_ticket_id",
+            requires(WriteTickets, delete(relations::remove_relation)),
+        )
+        .route(
+            "/projects/:project_id/tickets/:ticket_id/block",
+            requires(WriteTickets, post(blocks::block_ticket)),
+        )
+        .route(
+            "/projects/:project_id/tickets/:ticket_id/unblock",
+            requires(WriteTickets, post(blocks::unblock_ticket)),
+        )
+        .route(
+            "/projects/:project_id/tickets/:ticket_id/position",
+            requires(WriteTickets, patch(board::move_ticket)),
+        )
+        .route(
+            "/projects/:project_id/worker-types",
+            get(worker_types::list_worker_types).merge(requires(
+                ManageWorkers,
+                post(worker_types::create_worker_type),
+            )),
+        )
+        .route(
+            "/projects/:project_id/worker-types/:worker_type",
+            get(worker_types::get_worker_type).merge(requires(
+                ManageWorkers,
+                put(worker_types::update_worker_type).delete(worker_types::delete_worker_type),
+            )),
+        )
+        .route(
+            "/projects/:project_id/worker-types/:worker_type/status",
+            requires(ManageWorkers, put(worker_types::report_worker_type_status)),
+        )
+        .route("/requests", get(requests::list_requests))
+        .route(
+            "/requests/:correlation_id/ack",
+            requires(ManageWorkers, post(requests::acknowledge_request)),
+        )
+        .route(
+            "/schedules",
+            get(schedules::list_schedules)
+                .merge(requires(WriteTickets, post(schedules::create_schedule))),
+        )
+        .route(
+            "/schedules/:name",
+            get(schedules::get_schedule).merge(requires(
+                WriteTickets,
+                put(schedules::update_schedule).delete(schedules::delete_schedule),
+            )),
+        )
+        .route("/sessions", get(sessions::list_sessions))
+        .route(
+            "/sessions/:session_id",
+            requires(ManageWorkers, delete(sessions::delete_session)),
+        )
+        .route("/stats", get(stats::get_stats))
+        .route(
+            "/tags",
+            get(tags::list_tags).merge(requires(WriteTickets, post(tags::create_tag))),
+        )
+        .route("/tags/:id", requires(WriteTickets, patch(tags::update_tag)))
+        .route(
+            "/tags/:id/merge-into/:other",
+            requires(WriteTickets, post(tags::merge_tag)),
+        )
+        .route(
+            "/ticket-templates",
+            get(ticket_templates::list_templates).merge(requires(
+                WriteTickets,
+                post(ticket_templates::create_template),
+            )),
+        )
+        .route(
+            "/ticket-templates/:name",
+            get(ticket_templates::get_template).merge(requires(
+                WriteTickets,
+                put(ticket_templates::update_template).delete(ticket_templates::delete_template),
+            )),
+        )
+        .route("/tickets/search", get(tickets::search_tickets))
+        .route("/tickets/:ticket_ref", get(tickets::get_ticket_by_ref))
+        .route("/tickets/:ticket_ref/as-of", get(tickets::get_ticket_as_of))
+        .route("/timeline", get(timeline::get_timeline))
+        .route(
+            "/webhooks",
+            requires(
+                Admin,
+                get(webhooks::list_webhooks).post(webhooks::create_webhook),
+            ),
+        )
+        .route(
+            "/webhooks/:id",
+            requires(
+                Admin,
+                get(webhooks::get_webhook)
+                    .put(webhooks::update_webhook)
+                    .delete(webhooks::delete_webhook),
+            ),
+        )
+        .route(
+            "/webhooks/:id/deliveries",
+            requires(Admin, get(webhooks::list_deliveries)),
+        )
 }
@@ -0,0 +1,509 @@
+//! OpenAPI document of the REST API, generated from the handler annotations. Served with a
+//! Swagger UI when `--api-docs` is on.
+
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse, Json},
+    routing::get,
+    Router,
+};
+use utoipa::{openapi::server::Server, OpenApi, ToSchema};
+
+use crate::{
+    api::{
+        access::{AccessInfo, Permission, PermissionDenied, Role},
+        admin::{DemoSeeded, SetLogLevelRequest, ToolPolicyReload},
+        agents::ProjectAgent,
+        audit::AuditPage,
+        blocks::{BlockTicketRequest, UnblockTicketRequest},
+        board::{BoardAsOfView, BoardColumnAsOf, BoardColumnView, BoardView, MoveTicketRequest},
+        groups::AgentGroupDetails,
+        projects::{Burndown, ProjectQuotas, ProjectSettingsView},
+        relations::AddRelationRequest,
+        requests::AckRequest,
+        stats::StatsResponse,
+        tickets::{CommentThread, DeletedComment, DuplicateCheck},
+        timeline::{TimelineLane, TimelinePage},
+        webhooks::CreatedWebhook,
+        worker_types::{CreateWorkerTypeBody, UpdateWorkerTypeBody, WorkerTypeStatusBody},
+    },
+    backup::{BackupInfo, BackupStatus},
+    config::FieldChange,
+    database::{
+        agent_groups::{AgentGroup, CreateAgentGroupRequest, GroupMember},
+        agent_requests::{AgentRequest, RequestDeliveryStats, RequestStatus},
+        attachments::Attachment,
+        audit::AuditEntry,
+        blocks::{BlockNudge, BlockStats, BlockedTicket, LiftedBlock, TicketBlock},
+        board::{BoardCard, BoardColumn},
+        burndown::BurndownDay,
+        cache::CacheStats,
+        comments::{Comment, CommentNode},
+        coordination_timeline::TimelineEntry,
+        duplicates::PossibleDuplicate,
+        outbox::{OutboxBacklog, OutboxEvent},
+        pipeline::PipelineLinks,
+        project_settings::{EffectiveSettings, ProjectSettings},
+        projects::Project,
+        quotas::{Quota, QuotaOverride, QuotaStatus},
+        relations::{ChildProgress, ParentProgress, RelatedTicket, RelationType, TicketRelations},
+        schedules::{CreateScheduleRequest, TicketSchedule, UpdateScheduleRequest},
+        sla::SlaSummary,
+        stats::{CommentCounts, EstimateTotals, ProjectSummary, SystemStats, TicketCounts},
+        tags::{CreateTagRequest, Tag, TaggedTicket, UpdateTagRequest},
+        thread_summaries::{SummaryStatus, ThreadSummary},
+        throughput::{AgentThroughput, CycleTime, ThroughputDay, ThroughputMetrics},
+        ticket_search::TicketSearchHit,
+        ticket_templates::{
+            CreateTicketTemplateRequest, FieldType, TemplateField, TicketTemplate,
+            UpdateTicketTemplateRequest,
+        },
+        ticket_timeline::TicketStateAt,
+        tickets::{Priority, Ticket, TicketState, TicketWithComments},
+        timing::{LatencyBucket, PoolUsage, QueryStats, QueryTimingReport},
+        webhooks::{CreateWebhookRequest, UpdateWebhookRequest, Webhook, WebhookDelivery},
+        worker_load::{StatusChange, WorkerTypeLoad},
+        worker_metrics::{OutcomeSummary, WorkerTypeMetrics, WorkerTypeWithMetrics},
+        worker_types::WorkerType,
+    },
+    demo::DemoSummary,
+    error::ErrorResponse,
+    logging::LogFilterStatus,
+    mcp::{
+        connections::{ConnectionInfo, ConnectionReport, ConnectionTotals},
+        limits::LimitStats,
+        session::{CloseClass, SessionInfo, SessionPhase, SessionStats, Traffic, Transport},
+        types::ClientInfo,
+    },
+    outbox::OutboxStats,
+    redaction::RedactionStats,
+    reload::ReloadOutcome,
+    server::{AppState, DatabaseHealth, HealthStatus},
+    ticket_import::{
+        DuplicatePolicy, ImportReport, ImportSummary, ImportedTicket, RowIssue, SkippedDuplicate,
+    },
+    workers::{spawn_limiter::SpawnQueueStats, warm_pool::WarmPoolStats},
+};
+
+/// Multipart body of `POST .../tickets/import`
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct FileUpload {
+    /// CSV file with a header row
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+}
+
+/// Multipart body of `POST .../attachments`
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct AttachmentUpload {
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+    uploaded_by: Option<String>,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Vibe Ensemble REST API",
+        description = "Projects, tickets, worker types and diagnostics of a vibe-ensemble-mcp \
+            server. Requests without an `Authorization: Bearer <token>` header act as admin, \
+            or as viewer with --dashboard-read-only; see --api-tokens."
+    ),
+    paths(
+        crate::server::health_check,
+        super::access::get_access,
+        super::audit::list_audit_entries,
+        super::admin::create_backup,
+        super::admin::reload_config,
+        super::admin::reload_tool_policy,
+        super::admin::seed_demo,
+        super::admin::get_log_level,
+        super::admin::set_log_level,
+        super::admin::list_outbox,
+        super::admin::requeue_outbox_event,
+        super::internal::debug_connections,
+        super::internal::cache_stats,
+        super::internal::connection_limit_stats,
+        super::internal::outbox_stats,
+        super::internal::query_stats,
+        super::internal::redaction_stats,
+        super::internal::session_stats,
+        super::internal::spawn_queue_stats,
+        super::internal::warm_pool_stats,
+        super::projects::list_projects,
+        super::projects::get_project,
+        super::projects::get_project_settings,
+        super::projects::update_project_settings,
+        super::projects::get_project_quota,
+        super::projects::get_burndown,
+        super::agents::list_project_agents,
+        super::board::get_board,
+        super::board::get_board_as_of,
+        super::board::move_ticket,
+        super::tickets::list_tickets,
+        super::tickets::check_duplicates,
+        super::tickets::import_tickets_csv,
+        super::tickets::get_ticket_with_comments,
+        super::tickets::delete_comment,
+        super::tickets::get_comment_thread,
+        super::tickets::list_thread_summaries,
+        super::tickets::search_tickets,
+        super::tickets::get_ticket_by_ref,
+        super::tickets::get_ticket_as_of,
+        super::timeline::get_timeline,
+        super::relations::list_relations,
+        super::relations::add_relation,
+        super::relations::remove_relation,
+        super::blocks::list_blocked,
+        super::blocks::block_ticket,
+        super::blocks::unblock_ticket,
+        super::attachments::list_attachments,
+        super::attachments::upload_attachment,
+        super::attachments::download_attachment,
+        super::attachments::delete_attachment,
+        super::worker_types::list_worker_types,
+        super::worker_types::create_worker_type,
+        super::worker_types::get_worker_type,
+        super::worker_types::update_worker_type,
+        super::worker_types::delete_worker_type,
+        super::worker_types::report_worker_type_status,
+        super::requests::list_requests,
+        super::requests::list_project_messages,
+        super::requests::acknowledge_request,
+        super::groups::list_groups,
+        super::groups::create_group,
+        super::groups::get_group,
+        super::groups::delete_group,
+        super::groups::add_group_member,
+        super::groups::remove_group_member,
+        super::schedules::list_schedules,
+        super::schedules::create_schedule,
+        super::schedules::get_schedule,
+        super::schedules::update_schedule,
+        super::schedules::delete_schedule,
+        super::sessions::list_sessions,
+        super::sessions::delete_session,
+        super::stats::get_stats,
+        super::stats::get_project_stats,
+        super::metrics::get_throughput,
+        super::tags::list_tags,
+        super::tags::create_tag,
+        super::tags::update_tag,
+        super::tags::merge_tag,
+        super::ticket_templates::list_templates,
+        super::ticket_templates::create_template,
+        super::ticket_templates::get_template,
+        super::ticket_templates::update_template,
+        super::ticket_templates::delete_template,
+        super::webhooks::list_webhooks,
+        super::webhooks::create_webhook,
+        super::webhooks::get_webhook,
+        super::webhooks::update_webhook,
+        super::webhooks::delete_webhook,
+        super::webhooks::list_deliveries,
+    ),
+    components(schemas(
+        ErrorResponse,
+        PermissionDenied,
+        HealthStatus,
+        DatabaseHealth,
+        AccessInfo,
+        Role,
+        Permission,
+        AuditPage,
+        AuditEntry,
+        BackupInfo,
+        BackupStatus,
+        ReloadOutcome,
+        FieldChange,
+        ToolPolicyReload,
+        DemoSeeded,
+        DemoSummary,
+        SetLogLevelRequest,
+        LogFilterStatus,
+        CacheStats,
+        SpawnQueueStats,
+        LimitStats,
+        OutboxStats,
+        OutboxBacklog,
+        OutboxEvent,
+        QueryTimingReport,
+        QueryStats,
+        LatencyBucket,
+        PoolUsage,
+        RedactionStats,
+        SessionStats,
+        SessionInfo,
+        SessionPhase,
+        Transport,
+        Traffic,
+        CloseClass,
+        ClientInfo,
+        WarmPoolStats,
+        ConnectionReport,
+        ConnectionInfo,
+        ConnectionTotals,
+        Project,
+        ProjectQuotas,
+        QuotaStatus,
+        Quota,
+        QuotaOverride,
+        ProjectSettingsView,
+        ProjectSettings,
+        EffectiveSettings,
+        Burndown,
+        BurndownDay,
+        BoardView,
+        BoardColumnView,
+        BoardAsOfView,
+        BoardColumnAsOf,
+        BoardColumn,
+        BoardCard,
+        MoveTicketRequest,
+        Ticket,
+        TicketState,
+        Priority,
+        PipelineLinks,
+        TaggedTicket,
+        SummaryStatus,
+        TicketWithComments,
+        TicketStateAt,
+        TimelineEntry,
+        TimelineLane,
+        TimelinePage,
+        TicketRelations,
+        RelatedTicket,
+        RelationType,
+        AddRelationRequest,
+        ChildProgress,
+        ParentProgress,
+        TicketBlock,
+        BlockNudge,
+        BlockedTicket,
+        LiftedBlock,
+        BlockStats,
+        BlockTicketRequest,
+        UnblockTicketRequest,
+        Comment,
+        CommentNode,
+        ThreadSummary,
+        CommentThread,
+        DeletedComment,
+        DuplicateCheck,
+        PossibleDuplicate,
+        FileUpload,
+        ImportReport,
+        ImportSummary,
+        ImportedTicket,
+        RowIssue,
+        SkippedDuplicate,
+        DuplicatePolicy,
+        TicketSearchHit,
+        AttachmentUpload,
+        Attachment,
+        WorkerType,
+        WorkerTypeWithMetrics,
+        WorkerTypeMetrics,
+        OutcomeSummary,
+        WorkerTypeLoad,
+        StatusChange,
+        CreateWorkerTypeBody,
+        UpdateWorkerTypeBody,
+        WorkerTypeStatusBody,
+        AgentRequest,
+        RequestStatus,
+        RequestDeliveryStats,
+        AckRequest,
+        AgentGroup,
+        AgentGroupDetails,
+        GroupMember,
+        CreateAgentGroupRequest,
+        TicketSchedule,
+        CreateScheduleRequest,
+        UpdateScheduleRequest,
+        StatsResponse,
+        SystemStats,
+        TicketCounts,
+        CommentCounts,
+        EstimateTotals,
+        ProjectSummary,
+        ProjectAgent,
+        SlaSummary,
+        ThroughputMetrics,
+        ThroughputDay,
+        CycleTime,
+        AgentThroughput,
+        Tag,
+        CreateTagRequest,
+        UpdateTagRequest,
+        TicketTemplate,
+        TemplateField,
+        FieldType,
+        CreateTicketTemplateRequest,
+        UpdateTicketTemplateRequest,
+        Webhook,
+        CreatedWebhook,
+        CreateWebhookRequest,
+        UpdateWebhookRequest,
+        WebhookDelivery,
+    )),
+    tags(
+        (name = "health"),
+        (name = "access", description = "The caller's role"),
+        (name = "admin", description = "Backups, reloads, log level, demo data and the audit log"),
+        (name = "diagnostics", description = "Cache, queue and connection counters"),
+        (name = "projects"),
+        (name = "board", description = "Ticket board columns and card order"),
+        (name = "tickets"),
+        (name = "attachments"),
+        (name = "worker-types"),
+        (name = "requests", description = "Requests between agents"),
+        (name = "schedules", description = "Recurring tickets"),
+        (name = "sessions", description = "MCP sessions"),
+        (name = "stats"),
+        (name = "tags"),
+        (name = "ticket-templates"),
+        (name = "timeline", description = "What agents did when, across tickets, requests and workers"),
+        (name = "webhooks"),
+    )
+)]
+pub struct ApiDoc;
+
+/// The OpenAPI document, with the server at `base_path` so requests from Swagger UI reach it
+pub fn spec(base_path: &str) -> utoipa::openapi::OpenApi {
+    let mut doc = ApiDoc::openapi();
+    doc.info.version = env!("CARGO_PKG_VERSION").to_string();
+    let url = if base_path.is_empty() { "/" } else { base_path };
+    doc.servers = Some(vec![Server::new(url)]);
+    doc
+}
+
+/// GET /api/openapi.json - The OpenAPI document of the REST API
+pub async fn openapi_json(State(state): State<AppState>) -> impl IntoResponse {
+    Json(spec(&state.config.base_path))
+}
+
+/// GET /api/docs - Swagger UI for the OpenAPI document, loaded from a CDN
+pub async fn docs() -> Html<&'static str> {
+    Html(SWAGGER_UI_PAGE)
+}
+
+const SWAGGER_UI_PAGE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>Vibe Ensemble REST API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    window.ui = SwaggerUIBundle({ url: "openapi.json", dom_id: "#swagger-ui" });
+  </script>
+</body>
+</html>
+"##;
+
+/// Routes of the document and its Swagger UI, added under /api with `--api-docs`
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/openapi.json", get(openapi_json))
+        .route("/docs", get(docs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+    use serde_json::Value;
+    use std::collections::BTreeSet;
+
+    /// Every path and method `create_api_router` registers, as `("/api/x/{id}", "get")`
+    fn registered_routes() -> BTreeSet<(String, String)> {
+        let source = include_str!("mod.rs");
+        let router = &source[source.find("pub fn create_api_router").unwrap()..];
+        let route = Regex::new(r#"\.route\(\s*"([^"]+)""#).unwrap();
+        let method = Regex::new(r"\b(get|post|put|patch|delete)\(").unwrap();
+        let param = Regex::new(r":(\w+)").unwrap();
+
+        let starts: Vec<_> = route.captures_iter(router).collect();
+        let mut routes = BTreeSet::new();
+        for (i, captures) in starts.iter().enumerate() {
+            let body_start = captures.get(0).unwrap().end();
+            let body_end = starts
+                .get(i + 1)
+                .map_or(router.len(), |next| next.get(0).unwrap().start());
+            let path = format!("/api{}", param.replace_all(&captures[1], "{$1}"));
+            for m in method.captures_iter(&router[body_start..body_end]) {
+                routes.insert((path.clone(), m[1].to_string()));
+            }
+        }
+        routes
+    }
+
+    fn refs(value: &Value, found: &mut BTreeSet<String>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    match (key.as_str(), value) {
+                        ("$ref", Value::String(reference)) => {
+                            found.insert(reference.clone());
+                        }
+                        _ => refs(value, found),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|item| refs(item, found)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_spec_covers_every_route() {
+        let json = serde_json::to_string(&spec("/vibe")).unwrap();
+        let parsed: utoipa::openapi::OpenApi = serde_json::from_str(&json).unwrap();
+        let doc: Value = serde_json::to_value(&parsed).unwrap();
+        assert!(doc["openapi"].as_str().unwrap().starts_with("3."));
+        assert_eq!(doc["servers"][0]["url"], "/vibe");
+
+        let documented: BTreeSet<(String, String)> = doc["paths"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .flat_map(|(path, item)| {
+                item.as_object()
+                    .unwrap()
+                    .keys()
+                    .map(move |method| (path.clone(), method.clone()))
+            })
+            .collect();
+        let mut expected = registered_routes();
+        assert!(expected.len() > 60, "parsed {} routes", expected.len());
+        expected.insert(("/health".to_string(), "get".to_string()));
+
+        let missing: Vec<_> = expected.difference(&documented).collect();
+        assert!(missing.is_empty(), "undocumented routes: {:?}", missing);
+        let stale: Vec<_> = documented.difference(&expected).collect();
+        assert!(
+            stale.is_empty(),
+            "documented routes not served: {:?}",
+            stale
+        );
+
+        let mut found = BTreeSet::new();
+        refs(&doc, &mut found);
+        let schemas = doc["components"]["schemas"].as_object().unwrap();
+        let dangling: Vec<_> = found
+            .iter()
+            .filter(|reference| {
+                reference
+                    .strip_prefix("#/components/schemas/")
+                    .is_none_or(|name| !schemas.contains_key(name))
+            })
+            .collect();
+        assert!(dangling.is_empty(), "unresolved references: {:?}", dangling);
+    }
+}
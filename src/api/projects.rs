@@ -1,12 +1,67 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Json},
+    Extension,
 };
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{info, warn};
+use utoipa::{IntoParams, ToSchema};
 
-use crate::{database::projects::Project, error::AppError, server::AppState};
+use crate::{
+    api::access::ApiCaller,
+    audit::AuditRecord,
+    database::{
+        burndown::{self, BurndownDay, DEFAULT_BURNDOWN_DAYS, MAX_BURNDOWN_DAYS},
+        project_settings::{EffectiveSettings, ProjectSettings},
+        projects::Project,
+        quotas::{self, QuotaStatus},
+    },
+    error::AppError,
+    server::AppState,
+};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProjectQuotas {
+    pub project_id: String,
+    pub quotas: Vec<QuotaStatus>,
+}
+
+/// A project's setting overrides next to the values in effect
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProjectSettingsView {
+    pub project_id: String,
+    pub settings: ProjectSettings,
+    pub effective: EffectiveSettings,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Burndown {
+    pub project_id: String,
+    pub days: Vec<BurndownDay>,
+}
+
+/// Not found unless the project exists
+pub(super) async fn require_project(state: &AppState, project_id: &str) -> Result<(), AppError> {
+    if Project::get_by_id(&state.db, project_id).await?.is_none() {
+        return Err(AppError::NotFound(format!(
+            "Project '{}' not found",
+            project_id
+        )));
+    }
+    Ok(())
+}
 
 /// GET /api/projects - List all projects
+#[utoipa::path(
+    get,
+    path = "/api/projects",
+    tag = "projects",
+    responses(
+        (status = 200, description = "All projects", body = [Project])
+    )
+)]
 pub async fn list_projects(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
     let projects = Project::list_all(&state.db).await?;
 
@@ -14,6 +69,16 @@ pub async fn list_projects(State(state): State<AppState>) -> Result<impl IntoRes
 }
 
 /// GET /api/projects/:project_id - Get specific project by ID
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}",
+    tag = "projects",
+    params(("project_id" = String, Path, description = "Project id")),
+    responses(
+        (status = 200, description = "The project", body = Project),
+        (status = 404, description = "Project not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_project(
     State(state): State<AppState>,
     Path(project_id): Path<String>,
@@ -28,3 +93,183 @@ pub async fn get_project(
         ))),
     }
 }
+
+/// GET /api/projects/:project_id/quota - Usage and limit of each project quota, with any
+/// override in effect
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/quota",
+    tag = "projects",
+    params(("project_id" = String, Path, description = "Project id")),
+    responses(
+        (status = 200, description = "Quota usage and limits", body = ProjectQuotas),
+        (status = 404, description = "Project not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_project_quota(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(quotas) = quotas::report(&state.db, &project_id).await? else {
+        return Err(AppError::NotFound(format!(
+            "Project '{}' not found",
+            project_id
+        )));
+    };
+
+    Ok((StatusCode::OK, Json(ProjectQuotas { project_id, quotas })))
+}
+
+fn settings_response(
+    state: &AppState,
+    project_id: &str,
+    settings: ProjectSettings,
+) -> ProjectSettingsView {
+    ProjectSettingsView {
+        project_id: project_id.to_string(),
+        effective: settings.effective(&state.live_config.borrow()),
+        settings,
+    }
+}
+
+/// GET /api/projects/:project_id/settings - Project overrides and the values in effect
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/settings",
+    tag = "projects",
+    params(("project_id" = String, Path, description = "Project id")),
+    responses(
+        (status = 200, description = "Overrides and values in effect", body = ProjectSettingsView),
+        (status = 404, description = "Project not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_project_settings(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(settings) = ProjectSettings::get(&state.db, &project_id).await? else {
+        return Err(AppError::NotFound(format!(
+            "Project '{}' not found",
+            project_id
+        )));
+    };
+
+    Ok((
+        StatusCode::OK,
+        Json(settings_response(&state, &project_id, settings)),
+    ))
+}
+
+/// PUT /api/projects/:project_id/settings - Replace the project's overrides; omitted
+/// settings fall back to the server configuration
+#[utoipa::path(
+    put,
+    path = "/api/projects/{project_id}/settings",
+    tag = "projects",
+    params(("project_id" = String, Path, description = "Project id")),
+    request_body = ProjectSettings,
+    responses(
+        (status = 200, description = "Overrides replaced", body = ProjectSettingsView),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse)
+    )
+)]
+pub async fn update_project_settings(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Path(project_id): Path<String>,
+    Json(settings): Json<ProjectSettings>,
+) -> Result<impl IntoResponse, AppError> {
+    if ProjectSettings::get(&state.db, &project_id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::NotFound(format!(
+            "Project '{}' not found",
+            project_id
+        )));
+    }
+    let config = state.live_config.current();
+    let errors = settings.validate(&state.db, &project_id, &config).await?;
+    if !errors.is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "Invalid project settings: {}",
+            errors.join("; ")
+        )));
+    }
+
+    ProjectSettings::set(&state.db, &project_id, &settings).await?;
+    state
+        .queue_manager
+        .spawn_limiter()
+        .set_project_limit(&project_id, settings.max_concurrent_workers);
+    info!("Updated settings of project '{}'", project_id);
+
+    let settings_json = serde_json::to_value(&settings)?;
+    state.audit_logger.record(
+        AuditRecord::new(&caller.name, "update_project_settings", "success").with_params(Some(
+            &json!({ "project_id": project_id, "settings": settings_json }),
+        )),
+    );
+    if let Err(e) = state
+        .event_emitter()
+        .emit_project_settings_updated(&project_id, &settings_json)
+        .await
+    {
+        warn!("Failed to emit project_settings_updated event: {}", e);
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(settings_response(&state, &project_id, settings)),
+    ))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BurndownQuery {
+    pub days: Option<i64>,
+}
+
+/// GET /api/projects/:project_id/burndown - Open and closed ticket counts and estimate
+/// totals at the end of each of the last `days` days, replayed from the ticket history
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/burndown",
+    tag = "projects",
+    params(("project_id" = String, Path, description = "Project id"), BurndownQuery),
+    responses(
+        (status = 200, description = "Daily burndown series", body = Burndown),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_burndown(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+    Query(query): Query<BurndownQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let days = query.days.unwrap_or(DEFAULT_BURNDOWN_DAYS);
+    if !(1..=MAX_BURNDOWN_DAYS).contains(&days) {
+        return Err(AppError::BadRequest(format!(
+            "days must be between 1 and {}",
+            MAX_BURNDOWN_DAYS
+        )));
+    }
+    if Project::get_by_id(&state.db, &project_id).await?.is_none() {
+        return Err(AppError::NotFound(format!(
+            "Project '{}' not found",
+            project_id
+        )));
+    }
+
+    let series = burndown::load(&state.db, &project_id, days).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(Burndown {
+            project_id,
+            days: series,
+        }),
+    ))
+}
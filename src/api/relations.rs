@@ -0,0 +1,167 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+    Extension,
+};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    api::access::ApiCaller,
+    audit::AuditRecord,
+    database::{
+        relations::{self, RelationOutcome, RelationType},
+        ticket_refs,
+    },
+    error::AppError,
+    server::AppState,
+};
+
+use super::tickets::resolve_ticket_path;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AddRelationRequest {
+    pub relation_type: RelationType,
+    /// Ticket id, or a short id or number within the project
+    pub related_ticket_id: String,
+}
+
+/// Other end of a relation: a ticket of the project by any reference, else any ticket by id
+pub(super) async fn resolve_related(
+    state: &AppState,
+    project_id: &str,
+    reference: &str,
+) -> Result<String, AppError> {
+    if let Some(ticket_id) = ticket_refs::resolve(&state.db, Some(project_id), reference).await? {
+        return Ok(ticket_id);
+    }
+    ticket_refs::resolve(&state.db, None, reference)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found", reference)))
+}
+
+fn applied(outcome: RelationOutcome) -> Result<(), AppError> {
+    match outcome {
+        RelationOutcome::Applied => Ok(()),
+        RelationOutcome::Rejected(reason) => Err(AppError::BadRequest(reason)),
+    }
+}
+
+/// GET /api/projects/:project_id/tickets/:ticket_id/relations - A ticket's relations grouped by
+/// type and direction, with child progress for parent tickets
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/tickets/{ticket_id}/relations",
+    tag = "tickets",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("ticket_id" = String, Path, description = "Ticket id, short id or number"),
+    ),
+    responses(
+        (status = 200, description = "The ticket's relations", body = TicketRelations),
+        (status = 404, description = "Ticket not found", body = ErrorResponse)
+    )
+)]
+pub async fn list_relations(
+    State(state): State<AppState>,
+    Path((project_id, ticket_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let ticket_id = resolve_ticket_path(&state, &project_id, &ticket_id).await?;
+    Ok((
+        StatusCode::OK,
+        Json(relations::list(&state.db, &ticket_id).await?),
+    ))
+}
+
+/// POST /api/projects/:project_id/tickets/:ticket_id/relations - Relate the ticket to another;
+/// an open ticket recorded as a duplicate is closed as one
+#[utoipa::path(
+    post,
+    path = "/api/projects/{project_id}/tickets/{ticket_id}/relations",
+    tag = "tickets",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("ticket_id" = String, Path, description = "Ticket id, short id or number"),
+    ),
+    request_body = AddRelationRequest,
+    responses(
+        (status = 200, description = "The ticket's relations after the change", body = TicketRelations),
+        (status = 400, description = "The relation is not allowed", body = ErrorResponse),
+        (status = 404, description = "Ticket not found", body = ErrorResponse)
+    )
+)]
+pub async fn add_relation(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Path((project_id, ticket_id)): Path<(String, String)>,
+    Json(req): Json<AddRelationRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let ticket_id = resolve_ticket_path(&state, &project_id, &ticket_id).await?;
+    let related = resolve_related(&state, &project_id, &req.related_ticket_id).await?;
+    applied(
+        state
+            .queue_manager
+            .add_relation(&ticket_id, &related, req.relation_type)
+            .await?,
+    )?;
+    state.audit_logger.record(
+        AuditRecord::new(&caller.name, "add_ticket_relation", "success").with_params(Some(
+            &serde_json::json!({
+                "ticket_id": ticket_id,
+                "relation_type": req.relation_type,
+                "related_ticket_id": related
+            }),
+        )),
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(relations::list(&state.db, &ticket_id).await?),
+    ))
+}
+
+/// DELETE /api/projects/:project_id/tickets/:ticket_id/relations/:relation_type/:related_ticket_id
+/// - Remove a relation; removing duplicates leaves the duplicate closed
+#[utoipa::path(
+    delete,
+    path = "/api/projects/{project_id}/tickets/{ticket_id}/relations/{relation_type}/{related_ticket_id}",
+    tag = "tickets",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("ticket_id" = String, Path, description = "Ticket id, short id or number"),
+        ("relation_type" = RelationType, Path, description = "Relation type"),
+        ("related_ticket_id" = String, Path, description = "Ticket id, short id or number"),
+    ),
+    responses(
+        (status = 200, description = "The ticket's relations after the change", body = TicketRelations),
+        (status = 400, description = "No such relation", body = ErrorResponse),
+        (status = 404, description = "Ticket not found", body = ErrorResponse)
+    )
+)]
+pub async fn remove_relation(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Path((project_id, ticket_id, relation_type, related)): Path<(String, String, String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let relation_type = RelationType::parse(&relation_type).ok_or_else(|| {
+        AppError::BadRequest(format!("Invalid relation type '{}'", relation_type))
+    })?;
+    let ticket_id = resolve_ticket_path(&state, &project_id, &ticket_id).await?;
+    let related = resolve_related(&state, &project_id, &related).await?;
+    applied(relations::remove(&state.db, &ticket_id, &related, relation_type).await?)?;
+    state.audit_logger.record(
+        AuditRecord::new(&caller.name, "remove_ticket_relation", "success").with_params(Some(
+            &serde_json::json!({
+                "ticket_id": ticket_id,
+                "relation_type": relation_type,
+                "related_ticket_id": related
+            }),
+        )),
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(relations::list(&state.db, &ticket_id).await?),
+    ))
+}
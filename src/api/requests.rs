@@ -0,0 +1,111 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    database::agent_requests::{AckOutcome, AgentRequest, AgentRequestQuery, RequestStatus},
+    error::AppError,
+    server::AppState,
+};
+
+use super::projects::require_project;
+
+/// GET /api/requests - List agent requests (filters: project_id, status, requester, target,
+/// group_id; paging: limit)
+#[utoipa::path(
+    get,
+    path = "/api/requests",
+    tag = "requests",
+    params(AgentRequestQuery),
+    responses(
+        (status = 200, description = "Agent requests", body = [AgentRequest]),
+        (status = 400, description = "Invalid request", body = ErrorResponse)
+    )
+)]
+pub async fn list_requests(
+    State(state): State<AppState>,
+    Query(query): Query<AgentRequestQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    if let Some(status) = &query.status {
+        status
+            .parse::<RequestStatus>()
+            .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    }
+
+    let requests = AgentRequest::list(&state.db, &query).await?;
+
+    Ok((StatusCode::OK, Json(requests)))
+}
+
+/// GET /api/projects/:project_id/messages - Agent requests of one project, with the filters
+/// of `GET /api/requests`
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/messages",
+    tag = "requests",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        AgentRequestQuery
+    ),
+    responses(
+        (status = 200, description = "Agent requests of the project", body = [AgentRequest]),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse)
+    )
+)]
+pub async fn list_project_messages(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+    Query(query): Query<AgentRequestQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    require_project(&state, &project_id).await?;
+    list_requests(
+        State(state),
+        Query(AgentRequestQuery {
+            project_id: Some(project_id),
+            ..query
+        }),
+    )
+    .await
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AckRequest {
+    pub agent: String,
+}
+
+/// POST /api/requests/:correlation_id/ack - Acknowledge receipt of a request as its target agent
+#[utoipa::path(
+    post,
+    path = "/api/requests/{correlation_id}/ack",
+    tag = "requests",
+    params(("correlation_id" = String, Path, description = "Correlation id of the request")),
+    request_body = AckRequest,
+    responses(
+        (status = 200, description = "Request acknowledged", body = AgentRequest),
+        (status = 400, description = "The agent is not the target", body = ErrorResponse),
+        (status = 404, description = "Request not found", body = ErrorResponse)
+    )
+)]
+pub async fn acknowledge_request(
+    State(state): State<AppState>,
+    Path(correlation_id): Path<String>,
+    Json(body): Json<AckRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    match AgentRequest::acknowledge(&state.db, &correlation_id, &body.agent).await? {
+        AckOutcome::Acknowledged(request) => Ok((StatusCode::OK, Json(*request))),
+        AckOutcome::NotFound => Err(AppError::NotFound(format!(
+            "Request {} not found",
+            correlation_id
+        ))),
+        AckOutcome::NotRecipient { target } => Err(AppError::BadRequest(format!(
+            "Request {} is addressed to '{}', not '{}'",
+            correlation_id, target, body.agent
+        ))),
+    }
+}
@@ -0,0 +1,148 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{
+    database::schedules::{CreateScheduleRequest, TicketSchedule, UpdateScheduleRequest},
+    error::AppError,
+    schedules,
+    server::AppState,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListSchedulesQuery {
+    pub project_id: Option<String>,
+}
+
+/// GET /api/schedules - List recurring ticket schedules, optionally for one project
+#[utoipa::path(
+    get,
+    path = "/api/schedules",
+    tag = "schedules",
+    params(ListSchedulesQuery),
+    responses(
+        (status = 200, description = "Ticket schedules", body = [TicketSchedule])
+    )
+)]
+pub async fn list_schedules(
+    State(state): State<AppState>,
+    Query(query): Query<ListSchedulesQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let schedules = TicketSchedule::list(&state.db, query.project_id.as_deref()).await?;
+
+    Ok((StatusCode::OK, Json(schedules)))
+}
+
+/// POST /api/schedules - Create a recurring ticket schedule
+#[utoipa::path(
+    post,
+    path = "/api/schedules",
+    tag = "schedules",
+    request_body = CreateScheduleRequest,
+    responses(
+        (status = 201, description = "Schedule created", body = TicketSchedule),
+        (status = 400, description = "Invalid request", body = ErrorResponse)
+    )
+)]
+pub async fn create_schedule(
+    State(state): State<AppState>,
+    Json(req): Json<CreateScheduleRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    if TicketSchedule::get_by_name(&state.db, &req.name)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::BadRequest(format!(
+            "Schedule '{}' already exists",
+            req.name
+        )));
+    }
+
+    let next_run_at = schedules::validate(&state, &req)
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let schedule = TicketSchedule::create(&state.db, &req, next_run_at.as_deref()).await?;
+
+    Ok((StatusCode::CREATED, Json(schedule)))
+}
+
+/// GET /api/schedules/:name - Get a specific schedule
+#[utoipa::path(
+    get,
+    path = "/api/schedules/{name}",
+    tag = "schedules",
+    params(("name" = String, Path, description = "Schedule name")),
+    responses(
+        (status = 200, description = "The schedule", body = TicketSchedule),
+        (status = 404, description = "Schedule not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_schedule(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    match TicketSchedule::get_by_name(&state.db, &name).await? {
+        Some(schedule) => Ok((StatusCode::OK, Json(schedule))),
+        None => Err(AppError::NotFound(format!("Schedule '{}' not found", name))),
+    }
+}
+
+/// PUT /api/schedules/:name - Update a schedule; its next run is recomputed
+#[utoipa::path(
+    put,
+    path = "/api/schedules/{name}",
+    tag = "schedules",
+    params(("name" = String, Path, description = "Schedule name")),
+    request_body = UpdateScheduleRequest,
+    responses(
+        (status = 200, description = "Schedule updated", body = TicketSchedule),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Schedule not found", body = ErrorResponse)
+    )
+)]
+pub async fn update_schedule(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<UpdateScheduleRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(existing) = TicketSchedule::get_by_name(&state.db, &name).await? else {
+        return Err(AppError::NotFound(format!("Schedule '{}' not found", name)));
+    };
+
+    let updated = req.apply_to(&existing)?;
+    let next_run_at = schedules::validate(&state, &updated)
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    match TicketSchedule::update(&state.db, &updated, next_run_at.as_deref()).await? {
+        Some(schedule) => Ok((StatusCode::OK, Json(schedule))),
+        None => Err(AppError::NotFound(format!("Schedule '{}' not found", name))),
+    }
+}
+
+/// DELETE /api/schedules/:name - Delete a schedule; tickets it created are kept
+#[utoipa::path(
+    delete,
+    path = "/api/schedules/{name}",
+    tag = "schedules",
+    params(("name" = String, Path, description = "Schedule name")),
+    responses(
+        (status = 204, description = "Schedule deleted"),
+        (status = 404, description = "Schedule not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_schedule(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    if TicketSchedule::delete(&state.db, &name).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::NotFound(format!("Schedule '{}' not found", name)))
+    }
+}
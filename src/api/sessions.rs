@@ -0,0 +1,81 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{error::AppError, mcp::session, server::AppState};
+
+use super::agents::project_agents;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SessionListQuery {
+    /// Only sessions of the project's agents (see `GET /api/projects/:id/agents`)
+    pub project_id: Option<String>,
+}
+
+/// GET /api/sessions - MCP sessions over HTTP and WebSocket with their last activity
+#[utoipa::path(
+    get,
+    path = "/api/sessions",
+    tag = "sessions",
+    params(SessionListQuery),
+    responses(
+        (status = 200, description = "Tracked MCP sessions", body = [SessionInfo])
+    )
+)]
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    Query(query): Query<SessionListQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let sessions = match query.project_id {
+        Some(project_id) => {
+            let mut sessions: Vec<_> = project_agents(&state, &project_id)
+                .await?
+                .into_iter()
+                .flat_map(|agent| agent.sessions)
+                .collect();
+            sessions.sort_by(|a, b| {
+                (a.connected_at, &a.session_id).cmp(&(b.connected_at, &b.session_id))
+            });
+            sessions
+        }
+        None => state.mcp_server.sessions.list(),
+    };
+
+    Ok((StatusCode::OK, Json(sessions)))
+}
+
+/// DELETE /api/sessions/:session_id - Drop a session and close its connection
+#[utoipa::path(
+    delete,
+    path = "/api/sessions/{session_id}",
+    tag = "sessions",
+    params(("session_id" = String, Path, description = "MCP session id")),
+    responses(
+        (status = 204, description = "Session dropped"),
+        (status = 404, description = "Session not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(dropped) = state.mcp_server.sessions.drop_session(&session_id) else {
+        return Err(AppError::NotFound(format!(
+            "Session '{}' not found",
+            session_id
+        )));
+    };
+    session::end_session(
+        &state,
+        &dropped,
+        "dropped",
+        "Session closed by an administrator",
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,88 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    database::{agent_requests::RequestDeliveryStats, sla::SlaSummary, stats::SystemStats},
+    error::AppError,
+    server::AppState,
+};
+
+use super::projects::require_project;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatsQuery {
+    pub project_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StatsResponse {
+    #[serde(flatten)]
+    pub stats: SystemStats,
+    pub sla: SlaSummary,
+    pub requests: RequestDeliveryStats,
+}
+
+async fn load_stats(state: &AppState, project_id: Option<&str>) -> Result<StatsResponse, AppError> {
+    let stats = SystemStats::load(&state.db, project_id).await?;
+    let warning_minutes = state.live_config.borrow().sla_warning_minutes;
+    let sla = SlaSummary::load(&state.db, project_id, warning_minutes).await?;
+    let requests = RequestDeliveryStats::load(&state.db, project_id).await?;
+
+    Ok(StatsResponse {
+        stats,
+        sla,
+        requests,
+    })
+}
+
+/// GET /api/stats - Ticket counts by state and priority, worker processes by status,
+/// comments posted in the last 24 hours, open estimated work per project, open parent tickets
+/// with their child progress, at-risk and breached SLA counts, agent request delivery counts,
+/// and a summary of each project for a project switcher
+#[utoipa::path(
+    get,
+    path = "/api/stats",
+    tag = "stats",
+    params(StatsQuery),
+    responses(
+        (status = 200, description = "System counts", body = StatsResponse)
+    )
+)]
+pub async fn get_stats(
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok((
+        StatusCode::OK,
+        Json(load_stats(&state, query.project_id.as_deref()).await?),
+    ))
+}
+
+/// GET /api/projects/:project_id/stats - The counts of `GET /api/stats` for one project
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/stats",
+    tag = "stats",
+    params(("project_id" = String, Path, description = "Project id")),
+    responses(
+        (status = 200, description = "Project counts", body = StatsResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_project_stats(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    require_project(&state, &project_id).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(load_stats(&state, Some(&project_id)).await?),
+    ))
+}
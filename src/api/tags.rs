@@ -0,0 +1,106 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+
+use crate::{
+    database::tags::{CreateTagRequest, Tag, UpdateTagRequest},
+    error::AppError,
+    server::AppState,
+};
+
+/// GET /api/tags - List tags with their ticket counts
+#[utoipa::path(
+    get,
+    path = "/api/tags",
+    tag = "tags",
+    responses(
+        (status = 200, description = "Tags with their ticket counts", body = [Tag])
+    )
+)]
+pub async fn list_tags(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let tags = Tag::list(&state.db).await?;
+
+    Ok((StatusCode::OK, Json(tags)))
+}
+
+/// POST /api/tags - Create a tag
+#[utoipa::path(
+    post,
+    path = "/api/tags",
+    tag = "tags",
+    request_body = CreateTagRequest,
+    responses(
+        (status = 201, description = "Tag created", body = Tag),
+        (status = 400, description = "Invalid request", body = ErrorResponse)
+    )
+)]
+pub async fn create_tag(
+    State(state): State<AppState>,
+    Json(req): Json<CreateTagRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let tag = Tag::create(&state.db, &req)
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    Ok((StatusCode::CREATED, Json(tag)))
+}
+
+/// PATCH /api/tags/:id - Rename or recolor a tag; every tagged ticket follows the rename
+#[utoipa::path(
+    patch,
+    path = "/api/tags/{id}",
+    tag = "tags",
+    params(("id" = i64, Path, description = "Tag id")),
+    request_body = UpdateTagRequest,
+    responses(
+        (status = 200, description = "Tag updated", body = Tag),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Tag not found", body = ErrorResponse)
+    )
+)]
+pub async fn update_tag(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateTagRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    match Tag::update(&state.db, id, &req)
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?
+    {
+        Some(tag) => Ok((StatusCode::OK, Json(tag))),
+        None => Err(AppError::NotFound(format!("Tag {} not found", id))),
+    }
+}
+
+/// POST /api/tags/:id/merge-into/:other - Re-tag tickets with `other` and delete the duplicate
+#[utoipa::path(
+    post,
+    path = "/api/tags/{id}/merge-into/{other}",
+    tag = "tags",
+    params(
+        ("id" = i64, Path, description = "Tag id"),
+        ("other" = i64, Path, description = "Tag to keep"),
+    ),
+    responses(
+        (status = 200, description = "The tag kept", body = Tag),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Tag not found", body = ErrorResponse)
+    )
+)]
+pub async fn merge_tag(
+    State(state): State<AppState>,
+    Path((id, other)): Path<(i64, i64)>,
+) -> Result<impl IntoResponse, AppError> {
+    match Tag::merge_into(&state.db, id, other)
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?
+    {
+        Some(tag) => Ok((StatusCode::OK, Json(tag))),
+        None => Err(AppError::NotFound(format!(
+            "Tag {} or {} not found",
+            id, other
+        ))),
+    }
+}
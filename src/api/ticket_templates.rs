@@ -0,0 +1,169 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+
+use crate::{
+    database::{
+        tags::canonical_name,
+        ticket_templates::{
+            CreateTicketTemplateRequest, TicketTemplate, UpdateTicketTemplateRequest,
+        },
+        tickets::Priority,
+    },
+    error::AppError,
+    server::AppState,
+};
+
+fn validate_priority(priority: Option<&str>) -> Result<(), AppError> {
+    if let Some(priority) = priority {
+        priority
+            .parse::<Priority>()
+            .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Default tags in their canonical form, without repeats
+fn canonical_tags(tags: &[String]) -> Result<Vec<String>, AppError> {
+    let mut canonical: Vec<String> = Vec::new();
+    for tag in tags {
+        let name = canonical_name(tag).map_err(|e| AppError::BadRequest(e.to_string()))?;
+        if !canonical.contains(&name) {
+            canonical.push(name);
+        }
+    }
+    Ok(canonical)
+}
+
+/// GET /api/ticket-templates - List all ticket templates
+#[utoipa::path(
+    get,
+    path = "/api/ticket-templates",
+    tag = "ticket-templates",
+    responses(
+        (status = 200, description = "Ticket templates", body = [TicketTemplate])
+    )
+)]
+pub async fn list_templates(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let templates = TicketTemplate::list_all(&state.db).await?;
+
+    Ok((StatusCode::OK, Json(templates)))
+}
+
+/// POST /api/ticket-templates - Create a ticket template
+#[utoipa::path(
+    post,
+    path = "/api/ticket-templates",
+    tag = "ticket-templates",
+    request_body = CreateTicketTemplateRequest,
+    responses(
+        (status = 201, description = "Template created", body = TicketTemplate),
+        (status = 400, description = "Invalid request", body = ErrorResponse)
+    )
+)]
+pub async fn create_template(
+    State(state): State<AppState>,
+    Json(mut req): Json<CreateTicketTemplateRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    validate_priority(req.default_priority.as_deref())?;
+    req.default_tags = canonical_tags(&req.default_tags)?;
+
+    if TicketTemplate::get_by_name(&state.db, &req.name)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::BadRequest(format!(
+            "Ticket template '{}' already exists",
+            req.name
+        )));
+    }
+
+    let template = TicketTemplate::create(&state.db, req).await?;
+
+    Ok((StatusCode::CREATED, Json(template)))
+}
+
+/// GET /api/ticket-templates/:name - Get specific ticket template
+#[utoipa::path(
+    get,
+    path = "/api/ticket-templates/{name}",
+    tag = "ticket-templates",
+    params(("name" = String, Path, description = "Template name")),
+    responses(
+        (status = 200, description = "The template", body = TicketTemplate),
+        (status = 404, description = "Template not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_template(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    match TicketTemplate::get_by_name(&state.db, &name).await? {
+        Some(t) => Ok((StatusCode::OK, Json(t))),
+        None => Err(AppError::NotFound(format!(
+            "Ticket template '{}' not found",
+            name
+        ))),
+    }
+}
+
+/// PUT /api/ticket-templates/:name - Update a ticket template
+#[utoipa::path(
+    put,
+    path = "/api/ticket-templates/{name}",
+    tag = "ticket-templates",
+    params(("name" = String, Path, description = "Template name")),
+    request_body = UpdateTicketTemplateRequest,
+    responses(
+        (status = 200, description = "Template updated", body = TicketTemplate),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Template not found", body = ErrorResponse)
+    )
+)]
+pub async fn update_template(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(mut req): Json<UpdateTicketTemplateRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    validate_priority(req.default_priority.as_deref())?;
+    req.default_tags = req
+        .default_tags
+        .as_deref()
+        .map(canonical_tags)
+        .transpose()?;
+
+    match TicketTemplate::update(&state.db, &name, req).await? {
+        Some(t) => Ok((StatusCode::OK, Json(t))),
+        None => Err(AppError::NotFound(format!(
+            "Ticket template '{}' not found",
+            name
+        ))),
+    }
+}
+
+/// DELETE /api/ticket-templates/:name - Delete a ticket template
+#[utoipa::path(
+    delete,
+    path = "/api/ticket-templates/{name}",
+    tag = "ticket-templates",
+    params(("name" = String, Path, description = "Template name")),
+    responses(
+        (status = 204, description = "Template deleted"),
+        (status = 404, description = "Template not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_template(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    if TicketTemplate::delete(&state.db, &name).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::NotFound(format!(
+            "Ticket template '{}' not found",
+            name
+        )))
+    }
+}
@@ -1,43 +1,690 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::{IntoResponse, Json},
+    extract::{Multipart, Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
+    Extension,
 };
 
-use crate::{database::tickets::Ticket, error::AppError, server::AppState};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use utoipa::{IntoParams, ToSchema};
 
-/// GET /api/projects/:project_id/tickets - List all tickets for a project
+use crate::{
+    api::access::ApiCaller,
+    audit::AuditRecord,
+    database::{
+        comments::{Comment, CommentNode},
+        duplicates::{find_possible_duplicates, PossibleDuplicate},
+        projects::Project,
+        tags::{canonical_name, Tag},
+        thread_summaries::ThreadSummary,
+        ticket_refs,
+        ticket_search::{match_query, TicketSearchHit, DEFAULT_SEARCH_LIMIT},
+        ticket_timeline,
+        tickets::{Ticket, TicketFilter, TicketWithComments},
+    },
+    error::AppError,
+    mcp::pagination::{KeysetCursor, KeysetRequest},
+    redaction::{self, Site},
+    server::AppState,
+    ticket_import::{import_tickets, DuplicatePolicy, ImportFile, ImportOptions},
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TicketListQuery {
+    /// Only tickets past their due date that are not closed
+    pub overdue: Option<bool>,
+    /// `due` sorts by time remaining until due (tickets without a due date last)
+    pub sort: Option<String>,
+    /// Only tickets with this tag
+    pub tag: Option<String>,
+    /// Only tickets reopened at least this many times
+    pub min_reopens: Option<i64>,
+    /// Page size; with `cursor`, switches to keyset pagination
+    pub limit: Option<i64>,
+    /// `X-Next-Cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TicketDetailQuery {
+    /// Include comments already covered by the thread summary
+    #[serde(default)]
+    pub full_history: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TicketSearchQuery {
+    /// Words that must all appear in the title, description or comments
+    pub q: Option<String>,
+    pub project_id: Option<String>,
+    /// open or closed
+    pub status: Option<String>,
+    pub tag: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// A comment with every reply below it
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CommentThread {
+    pub thread: Option<CommentNode>,
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeletedComment {
+    pub deleted: i64,
+    /// Replies moved up to the comment the deleted one answered
+    pub reparented_replies: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DuplicateCheck {
+    pub possible_duplicates: Vec<PossibleDuplicate>,
+}
+
+/// Response header carrying the cursor of the next page, when there is one
+pub const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
+
+/// A page of a list endpoint: the JSON array, plus the next cursor as a header
+pub(crate) fn page_response<T: serde::Serialize>(
+    items: Vec<T>,
+    next_cursor: Option<String>,
+) -> Response {
+    let mut response = (StatusCode::OK, Json(items)).into_response();
+    if let Some(value) = next_cursor.and_then(|cursor| HeaderValue::from_str(&cursor).ok()) {
+        response.headers_mut().insert(NEXT_CURSOR_HEADER, value);
+    }
+    response
+}
+
+/// GET /api/projects/:project_id/tickets - List all tickets for a project (filters: overdue, tag,
+/// min_reopens; sort: due; paging: limit, cursor)
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/tickets",
+    tag = "tickets",
+    params(("project_id" = String, Path, description = "Project id"), TicketListQuery),
+    responses(
+        (
+            status = 200,
+            description = "Tickets of the project",
+            body = [TaggedTicket],
+            headers(("x-next-cursor" = String, description = "Cursor of the next page, if any"))
+        ),
+        (status = 400, description = "Invalid request", body = ErrorResponse)
+    )
+)]
 pub async fn list_tickets(
     State(state): State<AppState>,
     Path(project_id): Path<String>,
-) -> Result<impl IntoResponse, AppError> {
-    // list_by_project expects (project_id: Option<&str>, status_filter: Option<&str>)
-    let tickets = Ticket::list_by_project(&state.db, Some(&project_id), None).await?;
+    Query(query): Query<TicketListQuery>,
+) -> Result<Response, AppError> {
+    let tag = query
+        .tag
+        .as_deref()
+        .map(canonical_name)
+        .transpose()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if query.min_reopens.is_some_and(|n| n < 0) {
+        return Err(AppError::BadRequest(
+            "min_reopens must not be negative".to_string(),
+        ));
+    }
+    let filter = TicketFilter {
+        project_id: Some(&project_id),
+        status: None,
+        tag: tag.as_deref(),
+        overdue: query.overdue == Some(true),
+        min_reopens: query.min_reopens,
+    };
 
-    Ok((StatusCode::OK, Json(tickets)))
+    if query.limit.is_some() || query.cursor.is_some() {
+        // Pages follow the same order and cursors as the list_tickets MCP tool
+        if query.sort.is_some() {
+            return Err(AppError::BadRequest(
+                "sort cannot be combined with limit or cursor".to_string(),
+            ));
+        }
+        let page_request =
+            KeysetRequest::new(query.cursor.as_deref(), query.limit, filter.fingerprint())
+                .map_err(AppError::BadRequest)?;
+        let rows = Ticket::list_page(
+            &state.db,
+            &filter,
+            page_request.after.as_ref().map(KeysetCursor::after),
+            page_request.fetch_limit(),
+        )
+        .await?;
+        let page = page_request.page(rows, |t| (t.created_at.clone(), t.ticket_id.clone()));
+        let mut tickets = Tag::tag_tickets(&state.db, page.items).await?;
+        ThreadSummary::annotate(&state.db, &mut tickets).await?;
+        return Ok(page_response(tickets, page.next_cursor));
+    }
+
+    let mut tickets = Ticket::list_page(&state.db, &filter, None, -1).await?;
+
+    match query.sort.as_deref() {
+        None => {}
+        Some("due") => tickets.sort_by(|a, b| match (&a.due_at, &b.due_at) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }),
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "Invalid sort '{}'. Valid values are: due",
+                other
+            )))
+        }
+    }
+
+    let mut tickets = Tag::tag_tickets(&state.db, tickets).await?;
+    ThreadSummary::annotate(&state.db, &mut tickets).await?;
+    Ok((StatusCode::OK, Json(tickets)).into_response())
 }
 
-/// GET /api/projects/:project_id/tickets/:ticket_id - Get specific ticket with comments
+/// GET /api/projects/:project_id/tickets/:ticket_id - Get specific ticket with comments, as a
+/// list and as reply trees, and its relations by type; once the thread is summarized, only
+/// the comments after the summary (unless full_history=true)
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/tickets/{ticket_id}",
+    tag = "tickets",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("ticket_id" = String, Path, description = "Ticket id, short id or number"),
+        TicketDetailQuery,
+    ),
+    responses(
+        (status = 200, description = "The ticket with its comments", body = TicketWithComments),
+        (status = 404, description = "Ticket not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_ticket_with_comments(
     State(state): State<AppState>,
     Path((project_id, ticket_id)): Path<(String, String)>,
+    Query(query): Query<TicketDetailQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let t = ticket_in_project(&state, &project_id, &ticket_id).await?;
+    let t = t
+        .with_summary(&state.db, query.full_history)
+        .await?
+        .with_relations(&state.db)
+        .await?;
+    Ok((StatusCode::OK, Json(redact_thread(t).with_comment_tree())))
+}
+
+/// GET /api/projects/:project_id/tickets/:ticket_id/summaries - Every summary of the ticket's
+/// comment thread, newest first
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/tickets/{ticket_id}/summaries",
+    tag = "tickets",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("ticket_id" = String, Path, description = "Ticket id, short id or number"),
+    ),
+    responses(
+        (status = 200, description = "Thread summaries, newest first", body = [ThreadSummary]),
+        (status = 404, description = "Ticket not found", body = ErrorResponse)
+    )
+)]
+pub async fn list_thread_summaries(
+    State(state): State<AppState>,
+    Path((project_id, ticket_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let t = ticket_in_project(&state, &project_id, &ticket_id).await?;
+    let mut summaries = ThreadSummary::history(&state.db, &t.ticket.ticket_id).await?;
+    for summary in &mut summaries {
+        redact_in_place(&mut summary.summary);
+    }
+    Ok(Json(summaries))
+}
+
+/// GET /api/projects/:project_id/tickets/:ticket_id/comments/:comment_id/thread - A comment
+/// with every reply below it, as a tree and as a list
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/tickets/{ticket_id}/comments/{comment_id}/thread",
+    tag = "tickets",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("ticket_id" = String, Path, description = "Ticket id, short id or number"),
+        ("comment_id" = i64, Path, description = "Comment id"),
+    ),
+    responses(
+        (status = 200, description = "The comment and its replies", body = CommentThread),
+        (status = 404, description = "Comment not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_comment_thread(
+    State(state): State<AppState>,
+    Path((project_id, ticket_id, comment_id)): Path<(String, String, i64)>,
+) -> Result<impl IntoResponse, AppError> {
+    let ticket_id = resolve_ticket_path(&state, &project_id, &ticket_id).await?;
+    let mut comments = Comment::subtree(&state.db, comment_id).await?;
+    if comments.first().map(|c| c.ticket_id.as_str()) != Some(ticket_id.as_str()) {
+        return Err(AppError::NotFound(format!(
+            "Comment {} not found on ticket {}",
+            comment_id, ticket_id
+        )));
+    }
+    for comment in &mut comments {
+        redact_in_place(&mut comment.content);
+    }
+    let tree = CommentNode::tree(&comments).pop();
+    Ok(Json(CommentThread {
+        thread: tree,
+        comments,
+    }))
+}
+
+/// DELETE /api/projects/:project_id/tickets/:ticket_id/comments/:comment_id - Delete a
+/// comment; its replies move up to the comment it answered, or become top-level
+#[utoipa::path(
+    delete,
+    path = "/api/projects/{project_id}/tickets/{ticket_id}/comments/{comment_id}",
+    tag = "tickets",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("ticket_id" = String, Path, description = "Ticket id, short id or number"),
+        ("comment_id" = i64, Path, description = "Comment id"),
+    ),
+    responses(
+        (status = 200, description = "Comment deleted", body = DeletedComment),
+        (status = 404, description = "Comment not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_comment(
+    State(state): State<AppState>,
+    Path((project_id, ticket_id, comment_id)): Path<(String, String, i64)>,
+) -> Result<impl IntoResponse, AppError> {
+    let ticket_id = resolve_ticket_path(&state, &project_id, &ticket_id).await?;
+    match Comment::get_by_id(&state.db, comment_id).await? {
+        Some(comment) if comment.ticket_id == ticket_id => {}
+        _ => {
+            return Err(AppError::NotFound(format!(
+                "Comment {} not found on ticket {}",
+                comment_id, ticket_id
+            )))
+        }
+    }
+    let Some(reparented) = Comment::delete(&state.db, comment_id).await? else {
+        return Err(AppError::NotFound(format!(
+            "Comment {} not found on ticket {}",
+            comment_id, ticket_id
+        )));
+    };
+
+    // ticket_updated was queued with the deletion
+    state.event_broadcaster.notify_outbox();
+
+    Ok(Json(DeletedComment {
+        deleted: comment_id,
+        reparented_replies: reparented,
+    }))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TicketRefQuery {
+    /// Only look in this project, so short ids shared between projects resolve
+    pub project_id: Option<String>,
+    #[serde(default)]
+    pub full_history: bool,
+}
+
+/// GET /api/tickets/:ticket_ref - Get a ticket with comments by ticket id or short id (e.g.
+/// VEM-142); 409 with the candidates when a short id matches tickets in several projects and
+/// no project_id is given
+#[utoipa::path(
+    get,
+    path = "/api/tickets/{ticket_ref}",
+    tag = "tickets",
+    params(("ticket_ref" = String, Path, description = "Ticket id or short id"), TicketRefQuery),
+    responses(
+        (status = 200, description = "The ticket with its comments", body = TicketWithComments),
+        (status = 404, description = "Ticket not found", body = ErrorResponse),
+        (status = 409, description = "Short id found in several projects", body = ErrorResponse)
+    )
+)]
+pub async fn get_ticket_by_ref(
+    State(state): State<AppState>,
+    Path(reference): Path<String>,
+    Query(query): Query<TicketRefQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let ticket_id = ticket_refs::resolve(&state.db, query.project_id.as_deref(), &reference)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found", reference)))?;
+    let t = Ticket::get_by_id(&state.db, &ticket_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found", reference)))?;
+    let t = t
+        .with_summary(&state.db, query.full_history)
+        .await?
+        .with_relations(&state.db)
+        .await?;
+    Ok((StatusCode::OK, Json(redact_thread(t).with_comment_tree())))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AsOfQuery {
+    /// RFC 3339, `YYYY-MM-DD HH:MM:SS` (UTC) or a date (its start)
+    pub timestamp: String,
+    /// Only look in this project, so short ids shared between projects resolve
+    pub project_id: Option<String>,
+}
+
+/// Instant of an as-of query in SQLite's timestamp format; 400 when it doesn't parse
+pub(crate) fn as_of_instant(timestamp: &str) -> Result<String, AppError> {
+    ticket_timeline::parse_instant(timestamp).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "Invalid timestamp '{}': expected RFC 3339, 'YYYY-MM-DD HH:MM:SS' or a date",
+            timestamp
+        ))
+    })
+}
+
+/// GET /api/tickets/:ticket_ref/as-of - A ticket's status, stage, worker, priority and tags
+/// as they were at `timestamp`, replayed from its history; 404 when it did not exist yet
+#[utoipa::path(
+    get,
+    path = "/api/tickets/{ticket_ref}/as-of",
+    tag = "tickets",
+    params(("ticket_ref" = String, Path, description = "Ticket id or short id"), AsOfQuery),
+    responses(
+        (status = 200, description = "The ticket at that instant", body = TicketStateAt),
+        (status = 400, description = "Invalid timestamp", body = ErrorResponse),
+        (status = 404, description = "Ticket not found or not created yet", body = ErrorResponse),
+        (status = 409, description = "Short id found in several projects", body = ErrorResponse)
+    )
+)]
+pub async fn get_ticket_as_of(
+    State(state): State<AppState>,
+    Path(reference): Path<String>,
+    Query(query): Query<AsOfQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let at = as_of_instant(&query.timestamp)?;
+    let ticket_id = ticket_refs::resolve(&state.db, query.project_id.as_deref(), &reference)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found", reference)))?;
+    let ticket = Ticket::get_by_id(&state.db, &ticket_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found", reference)))?
+        .ticket;
+    let state_at = ticket_timeline::ticket_at(&state.db, &ticket.project_id, &ticket_id, &at)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Ticket '{}' did not exist yet at {} (created {})",
+                reference, at, ticket.created_at
+            ))
+        })?;
+    Ok((StatusCode::OK, Json(state_at)))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DuplicateCheckQuery {
+    pub title: String,
+    /// Comma-separated tags of the ticket about to be created
+    pub tags: Option<String>,
+}
+
+/// GET /api/projects/:project_id/tickets/duplicates - Open tickets in the project that look
+/// like duplicates of a ticket about to be created (title, tags), most similar first
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/tickets/duplicates",
+    tag = "tickets",
+    params(("project_id" = String, Path, description = "Project id"), DuplicateCheckQuery),
+    responses(
+        (status = 200, description = "Most similar first", body = DuplicateCheck),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse)
+    )
+)]
+pub async fn check_duplicates(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+    Query(query): Query<DuplicateCheckQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    if Project::get_by_name(&state.db, &project_id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::NotFound(format!(
+            "Project '{}' not found",
+            project_id
+        )));
+    }
+    let tags = query
+        .tags
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter(|tag| !tag.trim().is_empty())
+        .map(canonical_name)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let possible_duplicates =
+        find_possible_duplicates(&state.db, &project_id, &query.title, &tags, None).await?;
+    Ok((
+        StatusCode::OK,
+        Json(DuplicateCheck {
+            possible_duplicates,
+        }),
+    ))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportQuery {
+    /// Validate the file and report what would be created, without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+    /// `skip` (default) or `create` rows whose title matches an existing ticket
+    #[serde(default)]
+    pub duplicates: DuplicatePolicy,
+}
+
+/// POST /api/projects/:project_id/tickets/import - Create tickets from a CSV file (multipart
+/// field `file`; options: dry_run, duplicates)
+#[utoipa::path(
+    post,
+    path = "/api/projects/{project_id}/tickets/import",
+    tag = "tickets",
+    params(("project_id" = String, Path, description = "Project id"), ImportQuery),
+    request_body(content = FileUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Nothing created", body = ImportReport),
+        (status = 201, description = "Tickets created", body = ImportReport),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse)
+    )
+)]
+pub async fn import_tickets_csv(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Path(project_id): Path<String>,
+    Query(query): Query<ImportQuery>,
+    mut multipart: Multipart,
 ) -> Result<impl IntoResponse, AppError> {
-    let ticket_with_comments = Ticket::get_by_id(&state.db, &ticket_id).await?;
-
-    match ticket_with_comments {
-        Some(t) => {
-            // Verify ticket belongs to the specified project
-            if t.ticket.project_id != project_id {
-                return Err(AppError::NotFound(format!(
-                    "Ticket '{}' not found in project '{}'",
-                    ticket_id, project_id
-                )));
-            }
-            Ok((StatusCode::OK, Json(t)))
+    let Some(project) = Project::get_by_name(&state.db, &project_id).await? else {
+        return Err(AppError::NotFound(format!(
+            "Project '{}' not found",
+            project_id
+        )));
+    };
+
+    let mut file = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?
+    {
+        if field.name() == Some("file") {
+            file = Some(
+                field
+                    .bytes()
+                    .await
+                    .map_err(|e| AppError::BadRequest(e.to_string()))?,
+            );
         }
-        None => Err(AppError::NotFound(format!(
-            "Ticket '{}' not found",
-            ticket_id
-        ))),
     }
+    let Some(bytes) = file else {
+        return Err(AppError::BadRequest(
+            "Missing multipart field 'file'".to_string(),
+        ));
+    };
+    if bytes.len() > state.config.max_import_bytes {
+        return Err(AppError::BadRequest(format!(
+            "The file is {} bytes; imports are limited to {} bytes",
+            bytes.len(),
+            state.config.max_import_bytes
+        )));
+    }
+    let text = std::str::from_utf8(&bytes)
+        .map_err(|_| AppError::BadRequest("The file is not UTF-8 text".to_string()))?;
+    let file = ImportFile::parse(text, state.config.max_import_rows)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let options = ImportOptions {
+        dry_run: query.dry_run,
+        duplicates: query.duplicates,
+        create_missing_tags: !state.config.strict_tags,
+    };
+    let report = import_tickets(&state.db, &project, file, &options).await?;
+    if !report.dry_run {
+        state.audit_logger.record(
+            AuditRecord::new(&caller.name, "import_tickets", "success").with_params(Some(
+                &serde_json::json!({ "project_id": project_id, "summary": report.summary }),
+            )),
+        );
+    }
+
+    let status = if report.created.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::CREATED
+    };
+    Ok((status, Json(report)))
+}
+
+/// Mask secrets in comment text before the dashboard shows it; stored comments keep theirs
+/// unless `--redact-at-rest` is on
+fn redact_in_place(text: &mut String) {
+    if let Cow::Owned(redacted) = redaction::redact(text, Site::Response) {
+        *text = redacted;
+    }
+}
+
+fn redact_thread(mut t: TicketWithComments) -> TicketWithComments {
+    for comment in &mut t.comments {
+        redact_in_place(&mut comment.content);
+    }
+    if let Some(summary) = &mut t.summary {
+        redact_in_place(&mut summary.summary);
+    }
+    t
+}
+
+async fn ticket_in_project(
+    state: &AppState,
+    project_id: &str,
+    ticket_id: &str,
+) -> Result<TicketWithComments, AppError> {
+    let ticket_id = resolve_ticket_path(state, project_id, ticket_id).await?;
+    Ticket::get_by_id(&state.db, &ticket_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found", ticket_id)))
+}
+
+/// Ticket id named by a `:ticket_id` path segment, which may also be the ticket's short id
+/// or number within the project; 404 unless the project has that ticket
+pub(crate) async fn resolve_ticket_path(
+    state: &AppState,
+    project_id: &str,
+    reference: &str,
+) -> Result<String, AppError> {
+    ticket_refs::resolve(&state.db, Some(project_id), reference)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Ticket '{}' not found in project '{}'",
+                reference, project_id
+            ))
+        })
+}
+
+/// GET /api/tickets/search - Full-text search over ticket titles, descriptions and comments,
+/// best match first (filters: project_id, status, tag; limit). A `Warning` header says how
+/// many encrypted comments were not searched.
+#[utoipa::path(
+    get,
+    path = "/api/tickets/search",
+    tag = "tickets",
+    params(TicketSearchQuery),
+    responses(
+        (status = 200, description = "Best match first; a Warning header counts encrypted comments left out", body = [TicketSearchHit]),
+        (status = 400, description = "Invalid request", body = ErrorResponse)
+    )
+)]
+pub async fn search_tickets(
+    State(state): State<AppState>,
+    Query(query): Query<TicketSearchQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let text = query.q.unwrap_or_default();
+    if match_query(&text).is_none() {
+        return Err(AppError::BadRequest(
+            "q must contain at least one word".to_string(),
+        ));
+    }
+    if let Some(status) = query.status.as_deref() {
+        if status != "open" && status != "closed" {
+            return Err(AppError::BadRequest(format!(
+                "Invalid status filter: {}",
+                status
+            )));
+        }
+    }
+    let tag = query
+        .tag
+        .as_deref()
+        .map(canonical_name)
+        .transpose()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let filter = TicketFilter {
+        project_id: query.project_id.as_deref(),
+        status: query.status.as_deref(),
+        tag: tag.as_deref(),
+        overdue: false,
+        min_reopens: None,
+    };
+
+    let hits = TicketSearchHit::search(
+        &state.db,
+        &text,
+        &filter,
+        query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+    )
+    .await?;
+    let mut headers = HeaderMap::new();
+    if let Some(warning) =
+        TicketSearchHit::encryption_warning(&state.db, query.project_id.as_deref()).await?
+    {
+        headers.insert(
+            header::WARNING,
+            HeaderValue::from_str(&format!("199 vibe-ensemble \"{}\"", warning))
+                .map_err(|e| AppError::Internal(e.into()))?,
+        );
+    }
+    Ok((StatusCode::OK, headers, Json(hits)))
 }
@@ -0,0 +1,136 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    database::coordination_timeline::{self, TimelineEntry, TimelineFilter},
+    error::AppError,
+    mcp::pagination::{KeysetCursor, KeysetRequest},
+    server::AppState,
+};
+
+use super::{projects::require_project, tickets::as_of_instant};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TimelineQuery {
+    /// Start (inclusive): RFC 3339, `YYYY-MM-DD HH:MM:SS` (UTC) or a date (its start)
+    pub from: Option<String>,
+    /// End (exclusive), in the same formats as `from`
+    pub to: Option<String>,
+    pub project_id: Option<String>,
+    /// Only events this agent acted in or was the other party of
+    pub agent_id: Option<String>,
+    /// `agent` groups the page into one swimlane per acting agent
+    pub group_by: Option<String>,
+    /// Page size, 50 by default
+    pub limit: Option<i64>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+/// The events of one agent within a page, in timeline order
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TimelineLane {
+    /// None for events no agent acted in
+    pub agent_id: Option<String>,
+    pub entries: Vec<TimelineEntry>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TimelinePage {
+    /// The page in timeline order; empty when it is grouped into `lanes`
+    pub entries: Vec<TimelineEntry>,
+    /// With `group_by=agent`, lanes in the order their agents first appear in the page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lanes: Option<Vec<TimelineLane>>,
+    pub next_cursor: Option<String>,
+}
+
+fn into_lanes(entries: Vec<TimelineEntry>) -> Vec<TimelineLane> {
+    let mut lanes: Vec<TimelineLane> = Vec::new();
+    for entry in entries {
+        match lanes
+            .iter_mut()
+            .find(|lane| lane.agent_id == entry.agent_id)
+        {
+            Some(lane) => lane.entries.push(entry),
+            None => lanes.push(TimelineLane {
+                agent_id: entry.agent_id.clone(),
+                entries: vec![entry],
+            }),
+        }
+    }
+    lanes
+}
+
+/// GET /api/timeline - Ticket changes and claims, blocks, requests between agents and
+/// worker runs merged into one chronological stream (filters: from, to, project_id,
+/// agent_id; grouping: group_by=agent; paging: limit, cursor)
+#[utoipa::path(
+    get,
+    path = "/api/timeline",
+    tag = "timeline",
+    params(TimelineQuery),
+    responses(
+        (status = 200, description = "A page of the coordination timeline", body = TimelinePage),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_timeline(
+    State(state): State<AppState>,
+    Query(query): Query<TimelineQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let by_agent = match query.group_by.as_deref() {
+        None => false,
+        Some("agent") => true,
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "Invalid group_by '{}': the only grouping is 'agent'",
+                other
+            )))
+        }
+    };
+    if let Some(project_id) = &query.project_id {
+        require_project(&state, project_id).await?;
+    }
+    let from = query.from.as_deref().map(as_of_instant).transpose()?;
+    let to = query.to.as_deref().map(as_of_instant).transpose()?;
+    let filter = TimelineFilter {
+        from: from.as_deref(),
+        to: to.as_deref(),
+        project_id: query.project_id.as_deref(),
+        agent_id: query.agent_id.as_deref(),
+    };
+
+    let page_request =
+        KeysetRequest::new(query.cursor.as_deref(), query.limit, filter.fingerprint())
+            .map_err(AppError::BadRequest)?;
+    let rows = coordination_timeline::list(
+        &state.db,
+        &filter,
+        page_request.after.as_ref().map(KeysetCursor::after),
+        page_request.fetch_limit(),
+    )
+    .await?;
+    let page = page_request.page(rows, |e| (e.at.clone(), e.id.clone()));
+
+    let (entries, lanes) = if by_agent {
+        (Vec::new(), Some(into_lanes(page.items)))
+    } else {
+        (page.items, None)
+    };
+    Ok((
+        StatusCode::OK,
+        Json(TimelinePage {
+            entries,
+            lanes,
+            next_cursor: page.next_cursor,
+        }),
+    ))
+}
@@ -0,0 +1,186 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    database::webhooks::{CreateWebhookRequest, UpdateWebhookRequest, Webhook, WebhookDelivery},
+    error::AppError,
+    server::AppState,
+    webhooks,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListDeliveriesQuery {
+    /// pending, delivered or dead
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// A new webhook with its secret, which later responses leave out
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+/// GET /api/webhooks - List webhooks
+#[utoipa::path(
+    get,
+    path = "/api/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "Webhooks", body = [Webhook])
+    )
+)]
+pub async fn list_webhooks(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let webhooks = Webhook::list(&state.db).await?;
+
+    Ok((StatusCode::OK, Json(webhooks)))
+}
+
+/// POST /api/webhooks - Create a webhook; the response is the only one including its secret
+#[utoipa::path(
+    post,
+    path = "/api/webhooks",
+    tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 201, description = "Webhook created, with its secret", body = CreatedWebhook),
+        (status = 400, description = "Invalid request", body = ErrorResponse)
+    )
+)]
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Json(req): Json<CreateWebhookRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    webhooks::validate(&req).map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let secret = req.secret.clone().unwrap_or_else(webhooks::generate_secret);
+    let webhook = Webhook::create(&state.db, &req, &secret).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedWebhook { webhook, secret }),
+    ))
+}
+
+/// GET /api/webhooks/:id - Get a specific webhook
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = i64, Path, description = "Webhook id")),
+    responses(
+        (status = 200, description = "The webhook", body = Webhook),
+        (status = 404, description = "Webhook not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    match Webhook::get(&state.db, id).await? {
+        Some(webhook) => Ok((StatusCode::OK, Json(webhook))),
+        None => Err(AppError::NotFound(format!("Webhook {} not found", id))),
+    }
+}
+
+/// PUT /api/webhooks/:id - Update the given fields of a webhook
+#[utoipa::path(
+    put,
+    path = "/api/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = i64, Path, description = "Webhook id")),
+    request_body = UpdateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook updated", body = Webhook),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse)
+    )
+)]
+pub async fn update_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateWebhookRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(existing) = Webhook::get(&state.db, id).await? else {
+        return Err(AppError::NotFound(format!("Webhook {} not found", id)));
+    };
+
+    let updated = req.apply_to(&existing)?;
+    webhooks::validate(&updated).map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    match Webhook::update(&state.db, id, &updated).await? {
+        Some(webhook) => Ok((StatusCode::OK, Json(webhook))),
+        None => Err(AppError::NotFound(format!("Webhook {} not found", id))),
+    }
+}
+
+/// DELETE /api/webhooks/:id - Delete a webhook and its delivery history
+#[utoipa::path(
+    delete,
+    path = "/api/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = i64, Path, description = "Webhook id")),
+    responses(
+        (status = 204, description = "Webhook deleted"),
+        (status = 404, description = "Webhook not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    if Webhook::delete(&state.db, id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::NotFound(format!("Webhook {} not found", id)))
+    }
+}
+
+/// GET /api/webhooks/:id/deliveries - Recent deliveries of a webhook, newest first
+/// (query: status, limit)
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    params(("id" = i64, Path, description = "Webhook id"), ListDeliveriesQuery),
+    responses(
+        (status = 200, description = "Deliveries, newest first", body = [WebhookDelivery]),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse)
+    )
+)]
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(query): Query<ListDeliveriesQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    if let Some(status) = &query.status {
+        if !matches!(status.as_str(), "pending" | "delivered" | "dead") {
+            return Err(AppError::BadRequest(format!(
+                "Invalid delivery status '{}'. Valid statuses are: pending, delivered, dead",
+                status
+            )));
+        }
+    }
+    if Webhook::get(&state.db, id).await?.is_none() {
+        return Err(AppError::NotFound(format!("Webhook {} not found", id)));
+    }
+
+    let deliveries = WebhookDelivery::list(
+        &state.db,
+        id,
+        query.status.as_deref(),
+        query.limit.unwrap_or(100).clamp(1, 1000),
+    )
+    .await?;
+
+    Ok((StatusCode::OK, Json(deliveries)))
+}
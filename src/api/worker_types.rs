@@ -0,0 +1,393 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::warn;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    database::{
+        projects::Project,
+        worker_load::{WorkerTypeLoad, WorkerTypeStatus},
+        worker_metrics::{WorkerTypeMetrics, WorkerTypeWithMetrics},
+        worker_types::{
+            encode_capabilities, CreateWorkerTypeRequest, UpdateWorkerTypeRequest, WorkerType,
+        },
+    },
+    error::AppError,
+    mcp::pagination::{KeysetCursor, KeysetRequest},
+    server::AppState,
+    workers::sandbox::SpawnOverrides,
+};
+
+use super::tickets::page_response;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWorkerTypeBody {
+    pub worker_type: String,
+    /// May use the placeholders in `workers::prompt::PROMPT_VARIABLES`
+    pub system_prompt: String,
+    pub short_description: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub spawn_overrides: Option<Value>,
+    /// Tags this worker type handles, for routing new tickets
+    pub capabilities: Option<Vec<String>>,
+    /// Open tickets at which the worker type turns busy (0 for no limit)
+    pub max_open_tickets: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateWorkerTypeBody {
+    pub system_prompt: Option<String>,
+    pub short_description: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub spawn_overrides: Option<Value>,
+    pub capabilities: Option<Vec<String>>,
+    pub max_open_tickets: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct WorkerTypeStatusBody {
+    pub status: String,
+    pub reason: Option<String>,
+}
+
+fn check_capacity(max_open_tickets: Option<i64>) -> Result<(), AppError> {
+    match max_open_tickets {
+        Some(max) if max < 0 => Err(AppError::BadRequest(
+            "max_open_tickets must be 0 (no limit) or more".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Overrides checked against the override fields and serialized for storage
+fn stored_spawn_overrides(overrides: Option<Value>) -> Result<Option<String>, AppError> {
+    overrides
+        .map(|value| {
+            serde_json::from_value::<SpawnOverrides>(value.clone())
+                .map_err(|e| AppError::BadRequest(format!("Invalid spawn_overrides: {}", e)))?;
+            Ok(value.to_string())
+        })
+        .transpose()
+}
+
+fn stored_capabilities(capabilities: Option<Vec<String>>) -> Result<Option<String>, AppError> {
+    capabilities
+        .map(|names| {
+            encode_capabilities(&names)
+                .map_err(|e| AppError::BadRequest(format!("Invalid capabilities: {}", e)))
+        })
+        .transpose()
+}
+
+/// Event payload for worker type changes, as the MCP tools send it
+fn event_data(worker_type: &WorkerType) -> Value {
+    json!({
+        "id": worker_type.id,
+        "project_id": worker_type.project_id,
+        "worker_type": worker_type.worker_type,
+        "short_description": worker_type.short_description,
+        "created_at": worker_type.created_at,
+        "updated_at": worker_type.updated_at
+    })
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WorkerTypeListQuery {
+    /// Page size; with `cursor`, switches to keyset pagination
+    pub limit: Option<i64>,
+    /// `X-Next-Cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+/// GET /api/projects/:project_id/worker-types - Worker types of a project with their performance metrics
+/// (paging: limit, cursor)
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/worker-types",
+    tag = "worker-types",
+    params(("project_id" = String, Path, description = "Project id"), WorkerTypeListQuery),
+    responses(
+        (
+            status = 200,
+            description = "Worker types with their metrics",
+            body = [WorkerTypeWithMetrics],
+            headers(("x-next-cursor" = String, description = "Cursor of the next page, if any"))
+        ),
+        (status = 400, description = "Invalid request", body = ErrorResponse)
+    )
+)]
+pub async fn list_worker_types(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+    Query(query): Query<WorkerTypeListQuery>,
+) -> Result<Response, AppError> {
+    if query.limit.is_none() && query.cursor.is_none() {
+        let worker_types = WorkerType::list_by_project(&state.db, Some(&project_id)).await?;
+        let worker_types = WorkerTypeMetrics::attach(&state.db, worker_types).await?;
+
+        return Ok((StatusCode::OK, Json(worker_types)).into_response());
+    }
+
+    // Pages follow the same order and cursors as the list_worker_types MCP tool
+    let page_request = KeysetRequest::new(
+        query.cursor.as_deref(),
+        query.limit,
+        json!([Some(&project_id)]).to_string(),
+    )
+    .map_err(AppError::BadRequest)?;
+    let after = page_request
+        .after
+        .as_ref()
+        .map(KeysetCursor::after_numeric)
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+    let rows = WorkerType::list_page(
+        &state.db,
+        Some(&project_id),
+        after,
+        page_request.fetch_limit(),
+    )
+    .await?;
+    let page = page_request.page(rows, |w| (w.created_at.clone(), w.id.to_string()));
+
+    Ok(page_response(
+        WorkerTypeMetrics::attach(&state.db, page.items).await?,
+        page.next_cursor,
+    ))
+}
+
+/// GET /api/projects/:project_id/worker-types/:worker_type - One worker type with its performance metrics and load
+#[utoipa::path(
+    get,
+    path = "/api/projects/{project_id}/worker-types/{worker_type}",
+    tag = "worker-types",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("worker_type" = String, Path, description = "Worker type name"),
+    ),
+    responses(
+        (status = 200, description = "Worker type, metrics and load", body = WorkerTypeWithMetrics),
+        (status = 404, description = "Worker type not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_worker_type(
+    State(state): State<AppState>,
+    Path((project_id, worker_type)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(found) = WorkerType::get_by_type(&state.db, &project_id, &worker_type).await? else {
+        return Err(AppError::NotFound(format!(
+            "Worker type '{}' not found for project '{}'",
+            worker_type, project_id
+        )));
+    };
+    let metrics = WorkerTypeMetrics::get(&state.db, &project_id, &worker_type).await?;
+    let load = WorkerTypeLoad::get(&state.db, &project_id, &worker_type).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(WorkerTypeWithMetrics {
+            worker_type: found,
+            metrics,
+            load,
+        }),
+    ))
+}
+
+/// POST /api/projects/:project_id/worker-types - Create a worker type
+#[utoipa::path(
+    post,
+    path = "/api/projects/{project_id}/worker-types",
+    tag = "worker-types",
+    params(("project_id" = String, Path, description = "Project id")),
+    request_body = CreateWorkerTypeBody,
+    responses(
+        (status = 201, description = "Worker type created", body = WorkerType),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse)
+    )
+)]
+pub async fn create_worker_type(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+    Json(body): Json<CreateWorkerTypeBody>,
+) -> Result<impl IntoResponse, AppError> {
+    if Project::get_by_name(&state.db, &project_id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::NotFound(format!(
+            "Project '{}' not found",
+            project_id
+        )));
+    }
+    if WorkerType::get_by_type(&state.db, &project_id, &body.worker_type)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::BadRequest(format!(
+            "Worker type '{}' already exists for project '{}'",
+            body.worker_type, project_id
+        )));
+    }
+    check_capacity(body.max_open_tickets)?;
+
+    let created = WorkerType::create(
+        &state.db,
+        CreateWorkerTypeRequest {
+            project_id: project_id.clone(),
+            worker_type: body.worker_type,
+            short_description: body.short_description,
+            system_prompt: body.system_prompt,
+            spawn_overrides: stored_spawn_overrides(body.spawn_overrides)?,
+            capabilities: stored_capabilities(body.capabilities)?,
+        },
+    )
+    .await?;
+    if let Some(max) = body.max_open_tickets {
+        WorkerTypeLoad::set_capacity(&state.db, &project_id, &created.worker_type, max).await?;
+    }
+    if let Err(e) = state
+        .event_emitter()
+        .emit_worker_type_created(&project_id, &created.worker_type, &event_data(&created))
+        .await
+    {
+        warn!("Failed to emit worker_type_created event: {}", e);
+    }
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+/// PUT /api/projects/:project_id/worker-types/:worker_type - Update the given fields of a worker type
+#[utoipa::path(
+    put,
+    path = "/api/projects/{project_id}/worker-types/{worker_type}",
+    tag = "worker-types",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("worker_type" = String, Path, description = "Worker type name"),
+    ),
+    request_body = UpdateWorkerTypeBody,
+    responses(
+        (status = 200, description = "Worker type updated", body = WorkerType),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Worker type not found", body = ErrorResponse)
+    )
+)]
+pub async fn update_worker_type(
+    State(state): State<AppState>,
+    Path((project_id, worker_type)): Path<(String, String)>,
+    Json(body): Json<UpdateWorkerTypeBody>,
+) -> Result<impl IntoResponse, AppError> {
+    check_capacity(body.max_open_tickets)?;
+    let request = UpdateWorkerTypeRequest {
+        short_description: body.short_description,
+        system_prompt: body.system_prompt,
+        spawn_overrides: stored_spawn_overrides(body.spawn_overrides)?,
+        capabilities: stored_capabilities(body.capabilities)?,
+    };
+    let Some(updated) = WorkerType::update(&state.db, &project_id, &worker_type, request).await?
+    else {
+        return Err(AppError::NotFound(format!(
+            "Worker type '{}' not found for project '{}'",
+            worker_type, project_id
+        )));
+    };
+    if let Some(max) = body.max_open_tickets {
+        WorkerTypeLoad::set_capacity(&state.db, &project_id, &worker_type, max).await?;
+    }
+    if let Err(e) = state
+        .event_emitter()
+        .emit_worker_type_updated(&project_id, &worker_type, &event_data(&updated))
+        .await
+    {
+        warn!("Failed to emit worker_type_updated event: {}", e);
+    }
+
+    Ok((StatusCode::OK, Json(updated)))
+}
+
+/// PUT /api/projects/:project_id/worker-types/:worker_type/status - Report the worker type
+/// online, busy, in maintenance or in error
+#[utoipa::path(
+    put,
+    path = "/api/projects/{project_id}/worker-types/{worker_type}/status",
+    tag = "worker-types",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("worker_type" = String, Path, description = "Worker type name"),
+    ),
+    request_body = WorkerTypeStatusBody,
+    responses(
+        (status = 200, description = "Status recorded", body = WorkerTypeLoad),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Worker type not found", body = ErrorResponse)
+    )
+)]
+pub async fn report_worker_type_status(
+    State(state): State<AppState>,
+    Path((project_id, worker_type)): Path<(String, String)>,
+    Json(body): Json<WorkerTypeStatusBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let status: WorkerTypeStatus = body
+        .status
+        .parse()
+        .map_err(|e: anyhow::Error| AppError::BadRequest(e.to_string()))?;
+    let Some(load) = WorkerTypeLoad::report(
+        &state.db,
+        &project_id,
+        &worker_type,
+        status,
+        body.reason.as_deref(),
+        "api",
+    )
+    .await?
+    else {
+        return Err(AppError::NotFound(format!(
+            "Worker type '{}' not found for project '{}'",
+            worker_type, project_id
+        )));
+    };
+
+    Ok((StatusCode::OK, Json(load)))
+}
+
+/// DELETE /api/projects/:project_id/worker-types/:worker_type - Delete a worker type
+#[utoipa::path(
+    delete,
+    path = "/api/projects/{project_id}/worker-types/{worker_type}",
+    tag = "worker-types",
+    params(
+        ("project_id" = String, Path, description = "Project id"),
+        ("worker_type" = String, Path, description = "Worker type name"),
+    ),
+    responses(
+        (status = 204, description = "Worker type deleted"),
+        (status = 404, description = "Worker type not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_worker_type(
+    State(state): State<AppState>,
+    Path((project_id, worker_type)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    if !WorkerType::delete(&state.db, &project_id, &worker_type).await? {
+        return Err(AppError::NotFound(format!(
+            "Worker type '{}' not found for project '{}'",
+            worker_type, project_id
+        )));
+    }
+    if let Err(e) = state
+        .event_emitter()
+        .emit_worker_type_deleted(&project_id, &worker_type)
+        .await
+    {
+        warn!("Failed to emit worker_type_deleted event: {}", e);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
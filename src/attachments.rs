@@ -0,0 +1,335 @@
+use anyhow::{bail, Result};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::SqliteConnection;
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+use tracing::{info, warn};
+
+use crate::{
+    config::Config,
+    database::{
+        attachments::{Attachment, NewAttachment},
+        DbPool,
+    },
+};
+
+/// Content types accepted when `--attachment-content-types` is not given
+pub const DEFAULT_CONTENT_TYPES: &[&str] = &[
+    "text/*",
+    "application/json",
+    "application/x-ndjson",
+    "application/pdf",
+    "application/zip",
+    "application/gzip",
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+];
+
+/// Attachments live next to the database file, in `attachments/<ticket_id>/`
+pub fn attachments_dir(database_path: &str) -> PathBuf {
+    Path::new(database_path)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .join("attachments")
+}
+
+/// Reduce an uploaded file name to its last path component with only safe characters,
+/// so it can be echoed in `Content-Disposition`
+pub fn sanitize_filename(name: &str) -> Option<String> {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or_default().trim();
+    let sanitized: String = base
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | ' ') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let sanitized = sanitized.trim_start_matches('.').to_string();
+    (!sanitized.is_empty()).then_some(sanitized)
+}
+
+fn content_type_allowed(content_type: &str, allowed: &[String]) -> bool {
+    allowed
+        .iter()
+        .any(|pattern| match pattern.strip_suffix("/*") {
+            Some(family) => content_type
+                .split_once('/')
+                .is_some_and(|(kind, _)| kind == family),
+            None => pattern == content_type,
+        })
+}
+
+/// Saves, serves and removes attachment files under the configured size and type limits
+pub struct AttachmentStore<'a> {
+    dir: PathBuf,
+    max_bytes: usize,
+    allowed_types: &'a [String],
+}
+
+impl<'a> AttachmentStore<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Self {
+            dir: attachments_dir(&config.database_path),
+            max_bytes: config.max_attachment_bytes,
+            allowed_types: &config.attachment_content_types,
+        }
+    }
+
+    fn ticket_dir(&self, ticket_id: &str) -> Result<PathBuf> {
+        if ticket_id.is_empty() || ticket_id.starts_with('.') || ticket_id.contains(['/', '\\']) {
+            bail!("Invalid ticket id '{}'", ticket_id);
+        }
+        Ok(self.dir.join(ticket_id))
+    }
+
+    pub fn path(&self, attachment: &Attachment) -> Result<PathBuf> {
+        Ok(self
+            .ticket_dir(&attachment.ticket_id)?
+            .join(&attachment.stored_name))
+    }
+
+    /// Validate and store a file, then record it. The declared content type wins over
+    /// one guessed from the file name.
+    pub async fn save(
+        &self,
+        pool: &DbPool,
+        ticket_id: &str,
+        filename: &str,
+        declared_content_type: Option<&str>,
+        content: &[u8],
+        uploaded_by: Option<&str>,
+    ) -> Result<Attachment> {
+        let Some(filename) = sanitize_filename(filename) else {
+            bail!("Attachment needs a file name");
+        };
+        if content.len() > self.max_bytes {
+            bail!(
+                "Attachment of {} bytes exceeds the {} byte limit",
+                content.len(),
+                self.max_bytes
+            );
+        }
+        let content_type = declared_content_type
+            .map(|declared| {
+                // Drop parameters such as "; charset=utf-8"
+                declared
+                    .split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_ascii_lowercase()
+            })
+            // Clients send octet-stream when they do not know better; the name may tell more
+            .filter(|declared| !declared.is_empty() && declared != "application/octet-stream")
+            .unwrap_or_else(|| {
+                mime_guess::from_path(&filename)
+                    .first_or_octet_stream()
+                    .essence_str()
+                    .to_string()
+            });
+        if !content_type_allowed(&content_type, self.allowed_types) {
+            bail!(
+                "Content type '{}' is not allowed for attachments (allowed: {})",
+                content_type,
+                self.allowed_types.join(", ")
+            );
+        }
+
+        let dir = self.ticket_dir(ticket_id)?;
+        tokio::fs::create_dir_all(&dir).await?;
+        let stored_name = uuid::Uuid::new_v4().to_string();
+        let path = dir.join(&stored_name);
+        tokio::fs::write(&path, content).await?;
+
+        let sha256: String = Sha256::digest(content)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        let created = Attachment::create(
+            pool,
+            &NewAttachment {
+                ticket_id,
+                filename: &filename,
+                content_type: &content_type,
+                size_bytes: content.len() as i64,
+                sha256: &sha256,
+                uploaded_by,
+                stored_name: &stored_name,
+            },
+        )
+        .await;
+        match created {
+            Ok(attachment) => {
+                info!(
+                    "Attached '{}' ({} bytes) to ticket {}",
+                    attachment.filename, attachment.size_bytes, ticket_id
+                );
+                Ok(attachment)
+            }
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&path).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Delete the record and its file; a file that is already gone is not an error
+    pub async fn remove(
+        &self,
+        pool: &DbPool,
+        ticket_id: &str,
+        id: i64,
+    ) -> Result<Option<Attachment>> {
+        let Some(attachment) = Attachment::delete(pool, ticket_id, id).await? else {
+            return Ok(None);
+        };
+        match tokio::fs::remove_file(self.path(&attachment)?).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                warn!("Attachment {} had no file on disk", id)
+            }
+            Err(e) => return Err(e.into()),
+        }
+        Ok(Some(attachment))
+    }
+}
+
+/// Attachment records and files that have lost their counterpart
+#[derive(Debug, Default, Serialize)]
+pub struct Orphans {
+    /// Records whose file is missing, as `ticket_id/stored_name`
+    pub missing_files: Vec<String>,
+    /// Files no record points to
+    pub untracked_files: Vec<PathBuf>,
+}
+
+impl Orphans {
+    pub fn is_empty(&self) -> bool {
+        self.missing_files.is_empty() && self.untracked_files.is_empty()
+    }
+}
+
+/// Compare the attachment records with the files under `dir`
+pub async fn find_orphans(conn: &mut SqliteConnection, dir: &Path) -> Result<Orphans> {
+    let rows: Vec<(String, String)> =
+        sqlx::query_as("SELECT ticket_id, stored_name FROM ticket_attachments")
+            .fetch_all(conn)
+            .await?;
+    let recorded: HashSet<PathBuf> = rows
+        .iter()
+        .map(|(ticket_id, stored_name)| dir.join(ticket_id).join(stored_name))
+        .collect();
+
+    let mut orphans = Orphans::default();
+    for (ticket_id, stored_name) in &rows {
+        if !dir.join(ticket_id).join(stored_name).is_file() {
+            orphans
+                .missing_files
+                .push(format!("{}/{}", ticket_id, stored_name));
+        }
+    }
+
+    if dir.is_dir() {
+        for ticket_dir in std::fs::read_dir(dir)? {
+            let ticket_dir = ticket_dir?.path();
+            if !ticket_dir.is_dir() {
+                orphans.untracked_files.push(ticket_dir);
+                continue;
+            }
+            for file in std::fs::read_dir(&ticket_dir)? {
+                let file = file?.path();
+                if !recorded.contains(&file) {
+                    orphans.untracked_files.push(file);
+                }
+            }
+        }
+    }
+    orphans.untracked_files.sort();
+    Ok(orphans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::{Connection, Executor};
+
+    #[tokio::test]
+    async fn test_save_remove_and_orphans() {
+        let dir = std::env::temp_dir().join(format!("vibe-attachments-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("t.db");
+        let pool = crate::database::create_pool(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .unwrap();
+        pool.execute(
+            r#"
+            INSERT INTO projects (repository_name, path, short_description, project_prefix)
+            VALUES ('p', '/tmp/p', 'd', 'P');
+            INSERT INTO tickets (ticket_id, project_id, title, execution_plan, current_stage)
+            VALUES ('P-1', 'p', 'a', '["planning"]', 'planning');
+            "#,
+        )
+        .await
+        .unwrap();
+        let config = Config {
+            database_path: db_path.display().to_string(),
+            max_attachment_bytes: 16,
+            ..Config::default()
+        };
+        let store = AttachmentStore::new(&config);
+
+        let log = store
+            .save(&pool, "P-1", "../../test.log", None, b"failed", Some("w1"))
+            .await
+            .unwrap();
+        assert_eq!(log.filename, "test.log");
+        assert_eq!(log.content_type, "text/plain");
+        assert_eq!(log.sha256.len(), 64);
+        assert_eq!(std::fs::read(store.path(&log).unwrap()).unwrap(), b"failed");
+
+        let too_big = store.save(&pool, "P-1", "big.txt", None, &[b'x'; 17], None);
+        assert!(too_big.await.unwrap_err().to_string().contains("limit"));
+        let binary = store.save(&pool, "P-1", "a.exe", None, b"MZ", None);
+        assert!(binary
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("not allowed"));
+        let png = store
+            .save(&pool, "P-1", "shot", Some("image/png"), b"png", None)
+            .await
+            .unwrap();
+
+        // Drop one record behind the store's back and leave a stray file
+        sqlx::query("DELETE FROM ticket_attachments WHERE id = ?1")
+            .bind(png.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        std::fs::remove_file(store.path(&log).unwrap()).unwrap();
+        let mut conn = SqliteConnection::connect(&format!("sqlite:{}", db_path.display()))
+            .await
+            .unwrap();
+        let orphans = find_orphans(&mut conn, &attachments_dir(&config.database_path))
+            .await
+            .unwrap();
+        assert_eq!(orphans.missing_files.len(), 1);
+        assert_eq!(orphans.untracked_files, [store.path(&png).unwrap()]);
+
+        assert!(store.remove(&pool, "P-1", log.id).await.unwrap().is_some());
+        assert!(store.remove(&pool, "P-1", log.id).await.unwrap().is_none());
+
+        conn.close().await.unwrap();
+        pool.close().await;
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::{mpsc, watch};
+use tracing::{error, info, warn};
+
+use crate::{
+    config::Config,
+    database::{audit::AuditEntry, DbPool},
+};
+
+const AUDIT_CHANNEL_SIZE: usize = 1024;
+const RETENTION_SWEEP_INTERVAL_SECS: u64 = 3600;
+
+/// A single audit record waiting to be persisted
+#[derive(Debug, Clone)]
+pub struct AuditRecord {
+    pub actor: String,
+    pub session_id: Option<String>,
+    pub ticket_id: Option<String>,
+    pub method: String,
+    pub params_digest: Option<String>,
+    pub outcome: String,
+}
+
+impl AuditRecord {
+    pub fn new(actor: &str, method: &str, outcome: &str) -> Self {
+        Self {
+            actor: actor.to_string(),
+            session_id: None,
+            ticket_id: None,
+            method: method.to_string(),
+            params_digest: None,
+            outcome: outcome.to_string(),
+        }
+    }
+
+    pub fn with_session(mut self, session_id: Option<&str>) -> Self {
+        self.session_id = session_id.map(|s| s.to_string());
+        self
+    }
+
+    pub fn with_ticket(mut self, ticket_id: &str) -> Self {
+        self.ticket_id = Some(ticket_id.to_string());
+        self
+    }
+
+    pub fn with_params(mut self, params: Option<&Value>) -> Self {
+        self.params_digest = params.map(params_digest);
+        self
+    }
+}
+
+/// SHA-256 of the request parameters, so raw arguments are not stored. Object keys
+/// serialize sorted, so the same arguments give the same digest across runs and releases.
+pub fn params_digest(params: &Value) -> String {
+    Sha256::digest(params.to_string().as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Non-blocking audit log writer backed by a bounded channel and a background task
+#[derive(Clone)]
+pub struct AuditLogger {
+    sender: mpsc::Sender<AuditRecord>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl AuditLogger {
+    /// Start the writer task
+    pub fn start(db: DbPool) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<AuditRecord>(AUDIT_CHANNEL_SIZE);
+
+        tokio::spawn(async move {
+            while let Some(record) = receiver.recv().await {
+                if let Err(e) = AuditEntry::create(
+                    &db,
+                    &record.actor,
+                    record.session_id.as_deref(),
+                    record.ticket_id.as_deref(),
+                    &record.method,
+                    record.params_digest.as_deref(),
+                    &record.outcome,
+                )
+                .await
+                {
+                    error!("Failed to persist audit record: {}", e);
+                }
+            }
+            info!("Audit log writer stopped");
+        });
+
+        Self {
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Queue a record for persistence without waiting; drops it if the buffer is full
+    pub fn record(&self, record: AuditRecord) {
+        if let Err(e) = self.sender.try_send(record) {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            warn!(
+                "Dropped audit record (total dropped: {}): {}",
+                dropped,
+                match e {
+                    mpsc::error::TrySendError::Full(_) => "buffer full",
+                    mpsc::error::TrySendError::Closed(_) => "writer stopped",
+                }
+            );
+        }
+    }
+
+    /// Number of records dropped because the buffer overflowed
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Periodically purge audit entries older than `audit_retention_days` (0 keeps them)
+pub fn start_retention_sweeper(db: DbPool, config: watch::Receiver<Config>) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(RETENTION_SWEEP_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let retention_days = config.borrow().audit_retention_days;
+            if retention_days == 0 {
+                continue;
+            }
+            match AuditEntry::purge_older_than(&db, retention_days).await {
+                Ok(0) => {}
+                Ok(count) => info!("Purged {} expired audit entries", count),
+                Err(e) => warn!("Failed to purge audit entries: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,377 @@
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use sqlx::{sqlite::SqliteConnectOptions, ConnectOptions, Connection, SqliteConnection};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+use crate::database::{migrations::latest_migration_version, DbPool};
+
+/// File name prefix of generated snapshots; pruning only touches files with it
+const BACKUP_PREFIX: &str = "vibe-ensemble-";
+
+static STATUS: LazyLock<Mutex<BackupStatus>> =
+    LazyLock::new(|| Mutex::new(BackupStatus::default()));
+
+/// Progress of the most recent backups, reported by /health
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct BackupStatus {
+    pub in_progress: bool,
+    pub last_success_at: Option<String>,
+    pub last_backup_path: Option<String>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BackupInfo {
+    pub path: String,
+    pub size_bytes: u64,
+    pub created_at: String,
+}
+
+pub fn status() -> BackupStatus {
+    STATUS.lock().unwrap().clone()
+}
+
+/// Backups live next to the database file, in `backups/`
+pub fn backup_dir(database_path: &str) -> PathBuf {
+    Path::new(database_path)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .join("backups")
+}
+
+/// Write a consistent snapshot of the live database into `dir` using `VACUUM INTO`.
+/// Fails if another backup is already running.
+pub async fn create_backup(pool: &DbPool, dir: &Path) -> Result<BackupInfo> {
+    {
+        let mut status = STATUS.lock().unwrap();
+        if status.in_progress {
+            bail!("A backup is already in progress");
+        }
+        status.in_progress = true;
+    }
+
+    let result = write_snapshot(pool, dir).await;
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut status = STATUS.lock().unwrap();
+    status.in_progress = false;
+    match &result {
+        Ok(info) => {
+            status.last_success_at = Some(now);
+            status.last_backup_path = Some(info.path.clone());
+        }
+        Err(e) => {
+            status.last_error = Some(e.to_string());
+            status.last_error_at = Some(now);
+        }
+    }
+    result
+}
+
+async fn write_snapshot(pool: &DbPool, dir: &Path) -> Result<BackupInfo> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create backup directory {}", dir.display()))?;
+
+    let created_at = chrono::Utc::now();
+    let path = dir.join(format!(
+        "{}{}.db",
+        BACKUP_PREFIX,
+        created_at.format("%Y%m%d-%H%M%S-%3f")
+    ));
+
+    sqlx::query("VACUUM INTO ?1")
+        .bind(path.to_string_lossy().to_string())
+        .execute(pool)
+        .await
+        .inspect_err(|e| error!("Failed to write backup {}: {:?}", path.display(), e))?;
+
+    let size_bytes = fs::metadata(&path)?.len();
+    info!("Wrote backup {} ({} bytes)", path.display(), size_bytes);
+
+    Ok(BackupInfo {
+        path: path.to_string_lossy().to_string(),
+        size_bytes,
+        created_at: created_at.to_rfc3339(),
+    })
+}
+
+/// Delete all but the newest `retain` snapshots in `dir`, returning how many were removed
+pub fn prune_backups(dir: &Path, retain: usize) -> Result<usize> {
+    let mut backups: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(BACKUP_PREFIX) && n.ends_with(".db"))
+        })
+        .collect();
+
+    // Timestamped names sort chronologically
+    backups.sort();
+    let excess = backups.len().saturating_sub(retain);
+    for path in &backups[..excess] {
+        fs::remove_file(path)
+            .with_context(|| format!("Failed to remove old backup {}", path.display()))?;
+        info!("Removed old backup {}", path.display());
+    }
+    Ok(excess)
+}
+
+/// Periodically snapshot the database, keeping the newest `retention` backups
+pub fn start_backup_scheduler(db: DbPool, dir: PathBuf, interval_hours: u64, retention: usize) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_hours * 3600));
+        // The first tick fires immediately; wait a full interval before the first backup
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            match create_backup(&db, &dir).await {
+                Ok(_) => {
+                    if let Err(e) = prune_backups(&dir, retention) {
+                        warn!("Failed to prune backups in {}: {}", dir.display(), e);
+                    }
+                }
+                Err(e) => warn!("Scheduled backup failed: {}", e),
+            }
+        }
+    });
+}
+
+async fn open_read_only(path: &Path) -> Result<SqliteConnection> {
+    SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(true)
+        .connect()
+        .await
+        .with_context(|| format!("Failed to open {}", path.display()))
+}
+
+/// Run `PRAGMA integrity_check` on a database file
+pub async fn verify_integrity(path: &Path) -> Result<()> {
+    let mut conn = open_read_only(path).await?;
+    let rows = sqlx::query_scalar::<_, String>("PRAGMA integrity_check")
+        .fetch_all(&mut conn)
+        .await
+        .with_context(|| format!("Integrity check on {} failed", path.display()))?;
+    let _ = conn.close().await;
+
+    let problems = integrity_problems(rows);
+    if !problems.is_empty() {
+        bail!(
+            "{} failed the integrity check: {}",
+            path.display(),
+            problems.into_iter().take(3).collect::<Vec<_>>().join("; ")
+        );
+    }
+    Ok(())
+}
+
+/// `PRAGMA integrity_check` rows that report actual problems. The bundled SQLite checks
+/// FTS5 indexes by issuing a no-op write, which read-only connections refuse; the ticket
+/// search index is rebuilt from tickets and comments, so that refusal is not a problem.
+pub fn integrity_problems(rows: Vec<String>) -> Vec<String> {
+    rows.into_iter()
+        .filter(|row| {
+            row != "ok"
+                && !(row.contains("for FTS5 table")
+                    && row.ends_with("attempt to write a readonly database"))
+        })
+        .collect()
+}
+
+/// Highest applied migration in a database file; None if it has never been migrated
+pub async fn schema_version(path: &Path) -> Result<Option<i64>> {
+    let mut conn = open_read_only(path).await?;
+    let has_migrations: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+    )
+    .fetch_one(&mut conn)
+    .await?;
+
+    let version = if has_migrations {
+        sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(version) FROM _sqlx_migrations")
+            .fetch_one(&mut conn)
+            .await?
+    } else {
+        None
+    };
+    let _ = conn.close().await;
+    Ok(version)
+}
+
+/// Replace the database at `database_path` with the snapshot at `source`.
+///
+/// Must run before the pool is opened. The snapshot has to pass an integrity check,
+/// must not be newer than this binary's schema, and must not be older than the
+/// database it replaces. The replaced database is kept next to it with a
+/// `.pre-restore-<timestamp>` suffix, which is returned.
+pub async fn restore_from(source: &Path, database_path: &Path) -> Result<Option<PathBuf>> {
+    if !source.is_file() {
+        bail!("Backup {} does not exist", source.display());
+    }
+    verify_integrity(source).await?;
+
+    let backup_version = schema_version(source).await?.unwrap_or(0);
+    let supported_version = latest_migration_version();
+    if backup_version > supported_version {
+        bail!(
+            "Backup {} has schema version {} but this build only supports up to {}; upgrade first",
+            source.display(),
+            backup_version,
+            supported_version
+        );
+    }
+
+    let mut moved = None;
+    if database_path.exists() {
+        let current_version = schema_version(database_path).await?.unwrap_or(0);
+        if current_version > backup_version {
+            bail!(
+                "Refusing to restore schema version {} over newer schema version {} in {}",
+                backup_version,
+                current_version,
+                database_path.display()
+            );
+        }
+
+        let suffix = format!(
+            ".pre-restore-{}",
+            chrono::Utc::now().format("%Y%m%d-%H%M%S")
+        );
+        let aside = PathBuf::from(format!("{}{}", database_path.display(), suffix));
+        fs::rename(database_path, &aside)
+            .with_context(|| format!("Failed to move {} aside", database_path.display()))?;
+        for ext in ["-wal", "-shm"] {
+            let sidecar = PathBuf::from(format!("{}{}", database_path.display(), ext));
+            if sidecar.exists() {
+                fs::rename(
+                    &sidecar,
+                    format!("{}{}{}", database_path.display(), suffix, ext),
+                )?;
+            }
+        }
+        moved = Some(aside);
+    } else if let Some(parent) = database_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::copy(source, database_path).with_context(|| {
+        format!(
+            "Failed to copy {} to {}",
+            source.display(),
+            database_path.display()
+        )
+    })?;
+    info!(
+        "Restored {} from {} (schema version {})",
+        database_path.display(),
+        source.display(),
+        backup_version
+    );
+
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Executor;
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vibe-backup-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_backup_and_restore_round_trip() {
+        let dir = scratch_dir();
+        let db_path = dir.join("live.db");
+        let pool = crate::database::create_pool(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .unwrap();
+        pool.execute("INSERT INTO projects (repository_name, path, short_description) VALUES ('p', '/tmp/p', 'd')")
+            .await
+            .unwrap();
+
+        let info = write_snapshot(&pool, &dir.join("backups")).await.unwrap();
+        pool.close().await;
+        assert!(info.size_bytes > 0);
+
+        let restored = dir.join("restored.db");
+        assert!(restore_from(Path::new(&info.path), &restored)
+            .await
+            .unwrap()
+            .is_none());
+        verify_integrity(&restored).await.unwrap();
+        assert_eq!(
+            schema_version(&restored).await.unwrap(),
+            Some(latest_migration_version())
+        );
+
+        // Restoring over an existing database keeps the old file aside
+        let moved = restore_from(Path::new(&info.path), &restored)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(moved.exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_newer_schema() {
+        let dir = scratch_dir();
+        let source = dir.join("future.db");
+        let mut conn = SqliteConnectOptions::new()
+            .filename(&source)
+            .create_if_missing(true)
+            .connect()
+            .await
+            .unwrap();
+        conn.execute("CREATE TABLE _sqlx_migrations (version BIGINT PRIMARY KEY)")
+            .await
+            .unwrap();
+        conn.execute("INSERT INTO _sqlx_migrations VALUES (99999)")
+            .await
+            .unwrap();
+        conn.close().await.unwrap();
+
+        let err = restore_from(&source, &dir.join("live.db"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("only supports"), "{}", err);
+        assert!(!dir.join("live.db").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_prune_keeps_newest() {
+        let dir = scratch_dir();
+        for name in [
+            "vibe-ensemble-20260101-000000-000.db",
+            "vibe-ensemble-20260102-000000-000.db",
+            "vibe-ensemble-20260103-000000-000.db",
+            "unrelated.db",
+        ] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+
+        assert_eq!(prune_backups(&dir, 2).unwrap(), 1);
+        assert!(!dir.join("vibe-ensemble-20260101-000000-000.db").exists());
+        assert!(dir.join("vibe-ensemble-20260103-000000-000.db").exists());
+        assert!(dir.join("unrelated.db").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
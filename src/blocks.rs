@@ -0,0 +1,114 @@
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::{
+    agent_requests::notify,
+    config::Config,
+    database::{
+        agent_requests::AgentRequest,
+        blocks::{TicketBlock, NUDGE},
+        DbPool,
+    },
+    sse::EventBroadcaster,
+};
+
+/// Deadline of a reminder when blocks are never escalated
+const NUDGE_TIMEOUT_SECS: u64 = 86400;
+
+/// Periodically remind responsible agents of blocks past their expected unblock date, and
+/// tell the coordinator about blocks still in force `block_escalation_mins` after that;
+/// an escalation delay of 0 only sends the reminders
+pub fn start_block_monitor(
+    db: DbPool,
+    broadcaster: EventBroadcaster,
+    interval_secs: u64,
+    config: watch::Receiver<Config>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            let escalation_mins = config.borrow().block_escalation_mins;
+            evaluate(&db, &broadcaster, escalation_mins).await;
+        }
+    });
+}
+
+async fn evaluate(db: &DbPool, broadcaster: &EventBroadcaster, escalation_mins: u64) {
+    match TicketBlock::mark_due_nudges(db).await {
+        Ok(blocks) => {
+            for block in blocks {
+                if let Err(e) = nudge(db, broadcaster, &block, escalation_mins).await {
+                    warn!(
+                        "Failed to remind the blocker of ticket {}: {}",
+                        block.ticket_id, e
+                    );
+                }
+            }
+        }
+        Err(e) => warn!("Failed to evaluate overdue blocks: {}", e),
+    }
+
+    if escalation_mins == 0 {
+        return;
+    }
+    match TicketBlock::mark_due_escalations(db, escalation_mins).await {
+        Ok(blocks) => {
+            for block in &blocks {
+                warn!("Ticket {}: {}", block.ticket_id, block.escalation_message());
+            }
+            // ticket_block_escalated was queued with each escalation
+            if !blocks.is_empty() {
+                broadcaster.notify_outbox();
+            }
+        }
+        Err(e) => warn!("Failed to evaluate blocks to escalate: {}", e),
+    }
+}
+
+/// Send the responsible agent a direct request asking them to unblock the ticket
+async fn nudge(
+    db: &DbPool,
+    broadcaster: &EventBroadcaster,
+    block: &TicketBlock,
+    escalation_mins: u64,
+) -> anyhow::Result<()> {
+    let Some(agent) = block.responsible_agent.as_deref() else {
+        return Ok(());
+    };
+    let payload = json!({
+        "kind": "unblock_reminder",
+        "message": format!(
+            "Ticket {} was expected to be unblocked by {} UTC and is still blocked: {}. Unblock it with unblock_ticket once resolved, or reply with a new estimate",
+            block.ticket_id,
+            block.expected_unblock_at.as_deref().unwrap_or("now"),
+            block.reason
+        ),
+        "ticket_id": block.ticket_id,
+        "reason": block.reason,
+        "blocked_at": block.blocked_at,
+        "expected_unblock_at": block.expected_unblock_at,
+    });
+    let timeout_secs = match escalation_mins {
+        0 => NUDGE_TIMEOUT_SECS,
+        mins => (mins * 60).min(NUDGE_TIMEOUT_SECS),
+    };
+    let request = AgentRequest::create(
+        db,
+        "coordinator",
+        agent,
+        Some(&block.ticket_id),
+        &payload.to_string(),
+        timeout_secs,
+    )
+    .await?;
+    TicketBlock::record_nudge(db, block.id, NUDGE, agent, Some(&request.correlation_id)).await?;
+    info!(
+        "Reminded '{}' that ticket {} is past its expected unblock date (request {})",
+        agent, block.ticket_id, request.correlation_id
+    );
+    notify(broadcaster, &request, agent);
+    Ok(())
+}
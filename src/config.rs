@@ -1,4 +1,11 @@
-use crate::permissions::PermissionMode;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    database::recovery::OrphanedTicketPolicy, mcp::limits::OverflowPolicy,
+    permissions::PermissionMode, updates::ReleaseChannel,
+};
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -6,12 +13,172 @@ pub struct Config {
     pub host: String,
     pub port: u16,
     pub no_respawn: bool,
+    pub no_migrate: bool,
+    /// What startup recovery does with open tickets a worker held when the server stopped
+    pub orphaned_tickets: OrphanedTicketPolicy,
+    /// Startup recovery already ran, before demo data was seeded
+    pub skip_recovery: bool,
     pub permission_mode: PermissionMode,
     pub client_tool_timeout_secs: u64,
     pub max_concurrent_client_requests: usize,
     pub update_check_interval_hours: u64,
     pub disable_update_checks: bool,
+    pub release_channel: ReleaseChannel,
     pub model: Option<String>,
+    pub tool_policy_path: Option<String>,
+    pub worker_policy_path: Option<String>,
+    pub audit_retention_days: u64,
+    pub message_retention_days: u64,
+    pub max_open_tickets: u64,
+    pub max_comments_per_hour: u64,
+    pub max_concurrent_workers: usize,
+    pub max_workers_per_project: usize,
+    pub spawn_priority_aging_secs: u64,
+    /// Idle workers kept per project and worker type (0 disables the warm pool)
+    pub warm_pool_size: usize,
+    pub warm_pool_max_idle_secs: u64,
+    /// Worker types kept warm; empty for all of them
+    pub warm_pool_worker_types: Vec<String>,
+    pub strict_client_metadata: bool,
+    pub strict_tags: bool,
+    pub cache_ttl_secs: u64,
+    /// Repository calls slower than this are logged, in milliseconds (0 disables it)
+    pub slow_query_ms: u64,
+    /// Log the query plan of each slow statement once
+    pub explain_slow_queries: bool,
+    pub sla_check_interval_secs: u64,
+    pub sla_warning_minutes: u64,
+    pub flaky_reopen_threshold: u32,
+    /// Minutes a block may stay in force after its responsible agent was reminded before
+    /// the coordinator is told (0 disables escalation)
+    pub block_escalation_mins: u64,
+    /// Hours in the same state after which a ticket's effective priority goes up a level
+    /// (0 disables the rule)
+    pub escalation_age_hours: u64,
+    /// Open tickets blocked per level of effective priority added (0 disables the rule)
+    pub escalation_dependents: u64,
+    /// Minutes between coordinator digests (0 disables them)
+    pub digest_interval_mins: u64,
+    pub allowed_origins: Vec<String>,
+    pub base_path: String,
+    pub trust_proxy_headers: bool,
+    pub backup_interval_hours: u64,
+    pub backup_retention: usize,
+    pub max_message_bytes: usize,
+    pub max_attachment_bytes: usize,
+    pub attachment_content_types: Vec<String>,
+    pub max_import_bytes: usize,
+    pub max_import_rows: usize,
+    /// Secret patterns masked on top of `redaction::DEFAULT_PATTERNS`
+    pub redact_patterns: Vec<String>,
+    pub redact_at_rest: bool,
+    /// Encrypt comments and agent request payloads before they are stored
+    pub encrypt_messages: bool,
+    /// File of message keys, current first; `VIBE_ENSEMBLE_MESSAGE_KEYS` when unset
+    pub message_key_file: Option<String>,
+    pub max_queued_notifications: usize,
+    pub notification_overflow_policy: OverflowPolicy,
+    pub request_timeout_secs: u64,
+    pub session_idle_timeout_secs: u64,
+    /// Seconds open connections get to finish after SIGINT or SIGTERM
+    pub shutdown_grace_secs: u64,
+    pub allow_demo_seeding: bool,
+    pub webhook_max_attempts: u32,
+    pub api_tokens_path: Option<String>,
+    pub dashboard_read_only: bool,
+    pub auto_assign: bool,
+    pub auto_assign_min_score: f64,
+    pub auto_assign_max_open_tickets: usize,
+    /// Refuse, rather than warn about, tickets sent to a busy or unavailable worker type
+    pub refuse_assignments_at_capacity: bool,
+    /// Serve the OpenAPI document at /api/openapi.json and Swagger UI at /api/docs
+    pub api_docs: bool,
+    /// Point `.mcp.json` in the working directory at the port the server bound
+    pub update_mcp_json: bool,
+    /// Log filter from the config file, replacing RUST_LOG and --log-level
+    pub log_filter: Option<String>,
+    /// The `--config` file, read again on SIGHUP and `POST /api/admin/reload-config`
+    pub config_path: Option<String>,
+}
+
+/// Defaults matching the command-line defaults, with no database or address
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            database_path: String::new(),
+            host: String::new(),
+            port: 0,
+            no_respawn: false,
+            no_migrate: false,
+            orphaned_tickets: OrphanedTicketPolicy::Release,
+            skip_recovery: false,
+            permission_mode: PermissionMode::File,
+            client_tool_timeout_secs: 30,
+            max_concurrent_client_requests: 50,
+            update_check_interval_hours: 4,
+            disable_update_checks: false,
+            release_channel: ReleaseChannel::Stable,
+            model: None,
+            tool_policy_path: None,
+            worker_policy_path: None,
+            audit_retention_days: 90,
+            message_retention_days: 0,
+            max_open_tickets: 5000,
+            max_comments_per_hour: 1000,
+            max_concurrent_workers: 4,
+            max_workers_per_project: 2,
+            spawn_priority_aging_secs: 300,
+            warm_pool_size: 0,
+            warm_pool_max_idle_secs: 900,
+            warm_pool_worker_types: Vec::new(),
+            strict_client_metadata: false,
+            strict_tags: false,
+            cache_ttl_secs: 30,
+            slow_query_ms: crate::database::timing::DEFAULT_SLOW_QUERY_MS,
+            explain_slow_queries: false,
+            sla_check_interval_secs: 60,
+            sla_warning_minutes: 30,
+            flaky_reopen_threshold: 3,
+            block_escalation_mins: 60,
+            escalation_age_hours: 72,
+            escalation_dependents: 2,
+            digest_interval_mins: 10,
+            allowed_origins: Vec::new(),
+            base_path: String::new(),
+            trust_proxy_headers: false,
+            backup_interval_hours: 0,
+            backup_retention: 7,
+            max_message_bytes: 1024 * 1024,
+            max_attachment_bytes: 10 * 1024 * 1024,
+            attachment_content_types: crate::attachments::DEFAULT_CONTENT_TYPES
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+            max_import_bytes: 5 * 1024 * 1024,
+            max_import_rows: 5000,
+            redact_patterns: Vec::new(),
+            redact_at_rest: false,
+            encrypt_messages: false,
+            message_key_file: None,
+            max_queued_notifications: 256,
+            notification_overflow_policy: OverflowPolicy::DropOldest,
+            request_timeout_secs: 120,
+            session_idle_timeout_secs: crate::mcp::session::DEFAULT_SESSION_IDLE_TIMEOUT_SECS,
+            shutdown_grace_secs: 10,
+            allow_demo_seeding: false,
+            webhook_max_attempts: crate::webhooks::DEFAULT_MAX_ATTEMPTS,
+            api_tokens_path: None,
+            dashboard_read_only: false,
+            auto_assign: false,
+            auto_assign_min_score: 0.5,
+            auto_assign_max_open_tickets: 5,
+            refuse_assignments_at_capacity: false,
+            api_docs: false,
+            update_mcp_json: false,
+            log_filter: None,
+            config_path: None,
+        }
+    }
 }
 
 impl Config {
@@ -23,7 +190,142 @@ impl Config {
         format!("{}:{}", self.host, self.port)
     }
 
+    /// Base URL for HTTP endpoints, including the configured path prefix
+    pub fn http_base_url(&self) -> String {
+        format!("http://{}:{}{}", self.host, self.port, self.base_path)
+    }
+
     pub fn websocket_url(&self) -> String {
         format!("ws://{}:{}/ws", self.host, self.port)
     }
 }
+
+/// A setting whose value differs between two configs
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub from: String,
+    pub to: String,
+}
+
+macro_rules! config_file {
+    (
+        reloadable { $($reloadable:ident: $reloadable_ty:ty,)* }
+        restart { $($restart:ident: $restart_ty:ty,)* }
+    ) => {
+        /// Contents of the `--config` file: settings replacing the matching flags. Keys left
+        /// out keep their current value.
+        #[derive(Debug, Clone, Default, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        pub struct ConfigFile {
+            $(pub $reloadable: Option<$reloadable_ty>,)*
+            $(pub $restart: Option<$restart_ty>,)*
+        }
+
+        impl ConfigFile {
+            /// Overwrite the settings this file holds
+            pub fn apply(&self, config: &mut Config) {
+                $(if let Some(value) = &self.$reloadable {
+                    config.$reloadable = value.clone().into();
+                })*
+                $(if let Some(value) = &self.$restart {
+                    config.$restart = value.clone().into();
+                })*
+            }
+        }
+
+        impl Config {
+            /// Settings that differ in `other`, split into those applied while running
+            /// and those that need a restart
+            pub fn diff(&self, other: &Config) -> (Vec<FieldChange>, Vec<FieldChange>) {
+                let mut reloadable = Vec::new();
+                let mut restart = Vec::new();
+                $(if self.$reloadable != other.$reloadable {
+                    reloadable.push(FieldChange {
+                        field: stringify!($reloadable),
+                        from: format!("{:?}", self.$reloadable),
+                        to: format!("{:?}", other.$reloadable),
+                    });
+                })*
+                $(if self.$restart != other.$restart {
+                    restart.push(FieldChange {
+                        field: stringify!($restart),
+                        from: format!("{:?}", self.$restart),
+                        to: format!("{:?}", other.$restart),
+                    });
+                })*
+                (reloadable, restart)
+            }
+
+            /// Take the reloadable settings of `other`, keeping everything else
+            pub fn with_reloadable(&self, other: &Config) -> Config {
+                let mut config = self.clone();
+                $(config.$reloadable = other.$reloadable.clone();)*
+                config
+            }
+        }
+    };
+}
+
+config_file! {
+    reloadable {
+        log_filter: String,
+        request_timeout_secs: u64,
+        session_idle_timeout_secs: u64,
+        audit_retention_days: u64,
+        message_retention_days: u64,
+        max_concurrent_workers: usize,
+        max_workers_per_project: usize,
+        sla_warning_minutes: u64,
+        flaky_reopen_threshold: u32,
+        block_escalation_mins: u64,
+        escalation_age_hours: u64,
+        escalation_dependents: u64,
+        auto_assign: bool,
+        auto_assign_min_score: f64,
+        auto_assign_max_open_tickets: usize,
+        refuse_assignments_at_capacity: bool,
+        slow_query_ms: u64,
+    }
+    restart {
+        database_path: String,
+        host: String,
+        port: u16,
+        base_path: String,
+        allowed_origins: Vec<String>,
+        trust_proxy_headers: bool,
+        max_concurrent_client_requests: usize,
+        max_message_bytes: usize,
+        max_queued_notifications: usize,
+        cache_ttl_secs: u64,
+        explain_slow_queries: bool,
+        max_open_tickets: u64,
+        max_comments_per_hour: u64,
+        warm_pool_size: usize,
+        warm_pool_max_idle_secs: u64,
+        warm_pool_worker_types: Vec<String>,
+        redact_patterns: Vec<String>,
+        redact_at_rest: bool,
+        encrypt_messages: bool,
+        message_key_file: String,
+        sla_check_interval_secs: u64,
+        digest_interval_mins: u64,
+        shutdown_grace_secs: u64,
+        backup_interval_hours: u64,
+        backup_retention: usize,
+        tool_policy_path: String,
+        worker_policy_path: String,
+        api_tokens_path: String,
+        dashboard_read_only: bool,
+        api_docs: bool,
+    }
+}
+
+impl ConfigFile {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse config file: {}", path))
+    }
+}
@@ -2,6 +2,7 @@ use anyhow::Result;
 use std::fs;
 use std::path::Path;
 
+use crate::discovery::{read_live, DISCOVERY_DIR};
 use crate::lockfile::LockFileManager;
 use crate::mcp::constants::{build_claude_permissions, build_mcp_config};
 use crate::permissions::PermissionMode;
@@ -10,10 +11,28 @@ use crate::permissions::PermissionMode;
 pub async fn configure_claude_code(
     host: &str,
     port: u16,
+    base_path: &str,
     permission_mode: PermissionMode,
 ) -> Result<()> {
     println!("🔧 Configuring Claude Code integration...");
 
+    // A running server knows the port it actually bound; the flags only name the default
+    let live = read_live(Path::new(DISCOVERY_DIR));
+    let (host, port, base_path) = match &live {
+        Some(endpoint) => {
+            println!(
+                "📡 Found a running server (PID {}) at {}",
+                endpoint.pid, endpoint.http_url
+            );
+            (
+                endpoint.host.as_str(),
+                endpoint.port,
+                endpoint.base_path.as_str(),
+            )
+        }
+        None => (host, port, base_path),
+    };
+
     // Client mode: Check if Claude IDE lock file exists and validate workspace folder
     let lock_manager = LockFileManager::new(host.to_string(), port);
     let websocket_token = match lock_manager.validate_claude_lock_file_for_client() {
@@ -31,7 +50,7 @@ pub async fn configure_claude_code(
     };
 
     // Create .mcp.json file with WebSocket auth
-    create_mcp_config(host, port, &websocket_token).await?;
+    create_mcp_config(host, port, base_path, &websocket_token).await?;
 
     // Create .claude directory and files
     create_claude_directory().await?;
@@ -51,7 +70,12 @@ pub async fn configure_claude_code(
     Ok(())
 }
 
-async fn create_mcp_config(host: &str, port: u16, _websocket_token: &str) -> Result<()> {
+async fn create_mcp_config(
+    host: &str,
+    port: u16,
+    base_path: &str,
+    _websocket_token: &str,
+) -> Result<()> {
     let config_path = ".mcp.json";
 
     // If config exists, preserve user customizations and only update port
@@ -65,8 +89,8 @@ async fn create_mcp_config(host: &str, port: u16, _websocket_token: &str) -> Res
                             if let Some(vibe_server) = mcp_servers.get_mut("vibe-ensemble-mcp") {
                                 if let Some(url) = vibe_server.get_mut("url") {
                                     *url = serde_json::Value::String(format!(
-                                        "http://{}:{}/mcp",
-                                        host, port
+                                        "http://{}:{}{}/mcp",
+                                        host, port, base_path
                                     ));
                                 }
                             }
@@ -91,7 +115,7 @@ async fn create_mcp_config(host: &str, port: u16, _websocket_token: &str) -> Res
     }
 
     // Create new config if doesn't exist or couldn't parse existing
-    let config = build_mcp_config(host, port);
+    let config = build_mcp_config(host, port, base_path);
     fs::write(config_path, serde_json::to_string_pretty(&config)?)?;
     println!("  ✓ Created new .mcp.json configuration");
     Ok(())
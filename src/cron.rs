@@ -0,0 +1,283 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc};
+use std::str::FromStr;
+
+/// Years searched for the next occurrence; covers February 29th schedules
+const SEARCH_YEARS: i64 = 8;
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// Parsed cron expression, evaluated in UTC.
+///
+/// Accepts the `@hourly`, `@daily` (`@midnight`), `@weekly`, `@monthly` and `@yearly`
+/// (`@annually`) shorthands and five-field expressions (minute, hour, day of month,
+/// month, day of week) with `*`, lists, ranges, steps and `JAN`/`MON`-style names.
+/// As in classic cron, a day matches either day field when both are restricted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    days_of_month_restricted: bool,
+    days_of_week_restricted: bool,
+}
+
+struct FieldSpec {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    names: &'static [&'static str],
+    /// Value the first name stands for
+    names_start: u32,
+}
+
+const MINUTE: FieldSpec = FieldSpec {
+    name: "minute",
+    min: 0,
+    max: 59,
+    names: &[],
+    names_start: 0,
+};
+const HOUR: FieldSpec = FieldSpec {
+    name: "hour",
+    min: 0,
+    max: 23,
+    names: &[],
+    names_start: 0,
+};
+const DAY_OF_MONTH: FieldSpec = FieldSpec {
+    name: "day of month",
+    min: 1,
+    max: 31,
+    names: &[],
+    names_start: 0,
+};
+const MONTH: FieldSpec = FieldSpec {
+    name: "month",
+    min: 1,
+    max: 12,
+    names: &MONTH_NAMES,
+    names_start: 1,
+};
+// 7 is accepted as an alias for Sunday and folded onto 0
+const DAY_OF_WEEK: FieldSpec = FieldSpec {
+    name: "day of week",
+    min: 0,
+    max: 7,
+    names: &DAY_NAMES,
+    names_start: 0,
+};
+
+impl FromStr for CronSchedule {
+    type Err = anyhow::Error;
+
+    fn from_str(expression: &str) -> Result<Self> {
+        let expression = expression.trim();
+        let expanded = match expression.to_ascii_lowercase().as_str() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other if other.starts_with('@') => {
+                bail!("Unknown cron shorthand '{}'", expression)
+            }
+            _ => expression,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            bail!(
+                "Cron expression '{}' must have 5 fields (minute hour day-of-month month day-of-week), found {}",
+                expression,
+                fields.len()
+            );
+        }
+
+        let mut days_of_week = parse_field(fields[4], &DAY_OF_WEEK)?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+
+        let schedule = Self {
+            minutes: parse_field(fields[0], &MINUTE)?,
+            hours: parse_field(fields[1], &HOUR)?,
+            days_of_month: parse_field(fields[2], &DAY_OF_MONTH)?,
+            months: parse_field(fields[3], &MONTH)?,
+            days_of_week,
+            days_of_month_restricted: !fields[2].starts_with('*'),
+            days_of_week_restricted: !fields[4].starts_with('*'),
+        };
+
+        // Rejects expressions such as "0 0 30 2 *" that can never fire
+        if schedule.next_after(Utc::now()).is_none() {
+            bail!("Cron expression '{}' never matches a date", expression);
+        }
+        Ok(schedule)
+    }
+}
+
+impl CronSchedule {
+    /// First matching minute strictly after `after`, or None if there is none in the
+    /// search window
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after
+            .with_second(0)?
+            .with_nanosecond(0)?
+            .checked_add_signed(Duration::minutes(1))?;
+
+        let mut date = start.date_naive();
+        let mut from_minute = start.hour() * 60 + start.minute();
+        let last_date = date.checked_add_signed(Duration::days(366 * SEARCH_YEARS))?;
+
+        while date <= last_date {
+            if self.day_matches(date) {
+                for minute_of_day in from_minute..24 * 60 {
+                    let (hour, minute) = (minute_of_day / 60, minute_of_day % 60);
+                    if has(self.hours, hour) && has(self.minutes, minute) {
+                        return Some(date.and_hms_opt(hour, minute, 0)?.and_utc());
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+            from_minute = 0;
+        }
+        None
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        if !has(self.months, date.month()) {
+            return false;
+        }
+        let day_of_month = has(self.days_of_month, date.day());
+        let day_of_week = has(self.days_of_week, date.weekday().num_days_from_sunday());
+        if self.days_of_month_restricted && self.days_of_week_restricted {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+fn parse_field(field: &str, spec: &FieldSpec) -> Result<u64> {
+    let mut set = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .with_context(|| format!("Invalid step '{}' in {} field", step, spec.name))?;
+                (range, step)
+            }
+            None => (item, 1),
+        };
+
+        let (low, high) = if range == "*" {
+            (spec.min, spec.max)
+        } else if let Some((low, high)) = range.split_once('-') {
+            (parse_value(low, spec)?, parse_value(high, spec)?)
+        } else {
+            let value = parse_value(range, spec)?;
+            // "5/15" means every 15 starting at 5
+            (value, if step > 1 { spec.max } else { value })
+        };
+        if low > high {
+            bail!("Invalid range '{}' in {} field", range, spec.name);
+        }
+
+        for value in (low..=high).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+fn parse_value(value: &str, spec: &FieldSpec) -> Result<u32> {
+    let parsed = match value.parse::<u32>() {
+        Ok(number) => number,
+        Err(_) => spec
+            .names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(value))
+            .map(|index| index as u32 + spec.names_start)
+            .with_context(|| format!("Invalid value '{}' in {} field", value, spec.name))?,
+    };
+    if parsed < spec.min || parsed > spec.max {
+        bail!(
+            "Value {} out of range {}-{} in {} field",
+            parsed,
+            spec.min,
+            spec.max,
+            spec.name
+        );
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    fn next(expression: &str, after: DateTime<Utc>) -> DateTime<Utc> {
+        expression
+            .parse::<CronSchedule>()
+            .unwrap()
+            .next_after(after)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_shorthands_and_fields() {
+        // 2026-10-16 is a Friday
+        let now = at(2026, 10, 16, 10, 30);
+        assert_eq!(next("@daily", now), at(2026, 10, 17, 0, 0));
+        assert_eq!(next("@weekly", now), at(2026, 10, 18, 0, 0));
+        assert_eq!(next("@hourly", now), at(2026, 10, 16, 11, 0));
+        assert_eq!(next("0 9 * * MON", now), at(2026, 10, 19, 9, 0));
+        assert_eq!(next("0 9 * * mon-fri", now), at(2026, 10, 19, 9, 0));
+        assert_eq!(next("*/15 * * * *", now), at(2026, 10, 16, 10, 45));
+        assert_eq!(next("0 0 1 JAN *", now), at(2027, 1, 1, 0, 0));
+        assert_eq!(next("0 12 * * 7", now), at(2026, 10, 18, 12, 0));
+        // Both day fields restricted: either may match
+        assert_eq!(next("0 0 1 * FRI", now), at(2026, 10, 23, 0, 0));
+        // Strictly after, even when `after` is itself a match
+        assert_eq!(next("30 10 * * *", now), at(2026, 10, 17, 10, 30));
+        assert_eq!(next("0 0 29 2 *", now), at(2028, 2, 29, 0, 0));
+    }
+
+    #[test]
+    fn test_invalid_expressions_rejected() {
+        for expression in [
+            "",
+            "@fortnightly",
+            "0 9 * *",
+            "60 * * * *",
+            "0 9 * * FUNDAY",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "0 0 30 2 *",
+        ] {
+            assert!(
+                expression.parse::<CronSchedule>().is_err(),
+                "{} should be rejected",
+                expression
+            );
+        }
+    }
+}
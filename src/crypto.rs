@@ -0,0 +1,323 @@
+//! Field-level encryption of message content at rest. With `--encrypt-messages`, comments
+//! and agent request payloads and responses are sealed with AES-256-GCM before they are
+//! stored, next to the id of the key that sealed them, and opened again when read.
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose, Engine};
+use sha2::{Digest, Sha256};
+use std::{
+    borrow::Cow,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, LazyLock, RwLock,
+    },
+};
+
+/// Environment variable holding the message keys when no key file is given
+pub const KEYS_ENV: &str = "VIBE_ENSEMBLE_MESSAGE_KEYS";
+
+const NONCE_BYTES: usize = 12;
+
+static ACTIVE: LazyLock<RwLock<Option<Arc<Keyring>>>> = LazyLock::new(|| RwLock::new(None));
+static ENCRYPT: AtomicBool = AtomicBool::new(false);
+
+/// One 256-bit key, identified by the start of its SHA-256 digest
+pub struct MessageKey {
+    id: String,
+    cipher: Aes256Gcm,
+}
+
+impl MessageKey {
+    /// Key from 32 base64-encoded bytes, as printed by `openssl rand -base64 32`
+    pub fn parse(encoded: &str) -> Result<MessageKey> {
+        let bytes = general_purpose::STANDARD
+            .decode(encoded.trim())
+            .context("Message key is not valid base64")?;
+        if bytes.len() != 32 {
+            bail!("Message key must be 32 bytes, got {}", bytes.len());
+        }
+        let id = Sha256::digest(&bytes)[..4]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        let cipher = Aes256Gcm::new_from_slice(&bytes).expect("key length was checked");
+        Ok(MessageKey { id, cipher })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Base64 of a fresh nonce followed by the ciphertext
+    fn seal(&self, plaintext: &str) -> Result<String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| anyhow!("Failed to encrypt with key {}", self.id))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(general_purpose::STANDARD.encode(sealed))
+    }
+
+    fn open(&self, sealed: &str) -> Result<String> {
+        let bytes = general_purpose::STANDARD
+            .decode(sealed)
+            .with_context(|| format!("Content sealed with key {} is not valid base64", self.id))?;
+        if bytes.len() < NONCE_BYTES {
+            bail!("Content sealed with key {} is truncated", self.id);
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_BYTES);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Content does not decrypt with key {}", self.id))?;
+        String::from_utf8(plaintext).context("Decrypted content is not UTF-8")
+    }
+}
+
+impl fmt::Debug for MessageKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageKey").field("id", &self.id).finish()
+    }
+}
+
+/// The key new content is sealed with, followed by older keys kept to open what they sealed
+#[derive(Debug)]
+pub struct Keyring {
+    keys: Vec<MessageKey>,
+}
+
+impl Keyring {
+    /// Keys separated by whitespace or commas, current key first; lines starting with `#`
+    /// are skipped
+    pub fn parse(text: &str) -> Result<Keyring> {
+        let keys = text
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .flat_map(|line| line.split([',', ' ', '\t']))
+            .filter(|key| !key.trim().is_empty())
+            .map(MessageKey::parse)
+            .collect::<Result<Vec<_>>>()?;
+        if keys.is_empty() {
+            bail!("No message keys given");
+        }
+        Ok(Keyring { keys })
+    }
+
+    /// Keys from `key_file` when given, else from `VIBE_ENSEMBLE_MESSAGE_KEYS`; None when
+    /// neither is set
+    pub fn load(key_file: Option<&str>) -> Result<Option<Keyring>> {
+        let text = match key_file {
+            Some(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read message key file: {}", path))?,
+            None => match std::env::var(KEYS_ENV) {
+                Ok(keys) if !keys.trim().is_empty() => keys,
+                _ => return Ok(None),
+            },
+        };
+        let source = key_file.unwrap_or(KEYS_ENV);
+        Self::parse(&text)
+            .with_context(|| format!("Invalid message keys in {}", source))
+            .map(Some)
+    }
+
+    pub fn current(&self) -> &MessageKey {
+        &self.keys[0]
+    }
+
+    pub fn contains(&self, key_id: &str) -> bool {
+        self.keys.iter().any(|key| key.id == key_id)
+    }
+
+    /// `plaintext` sealed with the current key, and that key's id
+    pub fn seal(&self, plaintext: &str) -> Result<(String, String)> {
+        let key = self.current();
+        Ok((key.seal(plaintext)?, key.id.clone()))
+    }
+
+    /// Content sealed with the key `key_id`
+    pub fn open(&self, key_id: &str, sealed: &str) -> Result<String> {
+        let key = self
+            .keys
+            .iter()
+            .find(|key| key.id == key_id)
+            .ok_or_else(|| anyhow!("Message key {} is not configured", key_id))?;
+        key.open(sealed)
+    }
+}
+
+/// Keys used to open stored content from now on, and whether new content is sealed
+pub fn configure(keyring: Option<Keyring>, encrypt: bool) {
+    // Tests of this binary share the configuration; wait for one holding a `TestKeys`
+    #[cfg(test)]
+    let _serialized = test_keys::lock();
+    set(keyring.map(Arc::new), encrypt);
+}
+
+fn set(keyring: Option<Arc<Keyring>>, encrypt: bool) {
+    *ACTIVE.write().unwrap() = keyring;
+    ENCRYPT.store(encrypt, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+pub(crate) use test_keys::TestKeys;
+
+#[cfg(test)]
+mod test_keys {
+    use super::*;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+
+    static CONFIGURATION: Mutex<()> = Mutex::new(());
+
+    pub(super) fn lock() -> MutexGuard<'static, ()> {
+        CONFIGURATION.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// A key configuration for one test. Other tests cannot change the configuration while
+    /// it is held, and the previous one is restored when it is dropped.
+    pub(crate) struct TestKeys {
+        previous: (Option<Arc<Keyring>>, bool),
+        _serialized: MutexGuard<'static, ()>,
+    }
+
+    impl TestKeys {
+        pub(crate) fn configure(keyring: Option<Keyring>, encrypt: bool) -> TestKeys {
+            let serialized = lock();
+            let previous = (
+                ACTIVE.read().unwrap().clone(),
+                ENCRYPT.load(Ordering::SeqCst),
+            );
+            set(keyring.map(Arc::new), encrypt);
+            TestKeys {
+                previous,
+                _serialized: serialized,
+            }
+        }
+    }
+
+    impl Drop for TestKeys {
+        fn drop(&mut self) {
+            set(self.previous.0.take(), self.previous.1);
+        }
+    }
+}
+
+/// Content as it should be stored and the id of the key that sealed it; unchanged and
+/// without a key id unless `--encrypt-messages` is on
+pub fn seal(content: &str) -> Result<(Cow<'_, str>, Option<String>)> {
+    if !ENCRYPT.load(Ordering::SeqCst) {
+        return Ok((Cow::Borrowed(content), None));
+    }
+    let Some(keyring) = ACTIVE.read().unwrap().clone() else {
+        return Ok((Cow::Borrowed(content), None));
+    };
+    let (sealed, key_id) = keyring.seal(content)?;
+    Ok((Cow::Owned(sealed), Some(key_id)))
+}
+
+/// Stored content as written; content without a key id is plaintext
+pub fn open(content: String, key_id: Option<&str>) -> Result<String> {
+    let Some(key_id) = key_id else {
+        return Ok(content);
+    };
+    let Some(keyring) = ACTIVE.read().unwrap().clone() else {
+        bail!(
+            "Content is encrypted with key {} but no message keys are configured",
+            key_id
+        );
+    };
+    keyring.open(key_id, &content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_A: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+    const KEY_B: &str = "HyAhIiMkJSYnKCkqKywtLi8wMTIzNDU2Nzg5Ojs8PT4=";
+
+    #[test]
+    fn test_round_trip_and_wrong_key() {
+        let keyring = Keyring::parse(KEY_A).unwrap();
+        let (sealed, key_id) = keyring.seal("staging password: hunter2").unwrap();
+        assert!(!sealed.contains("hunter2"));
+        assert_eq!(key_id, keyring.current().id());
+        assert_eq!(key_id.len(), 8);
+        assert_eq!(
+            keyring.open(&key_id, &sealed).unwrap(),
+            "staging password: hunter2"
+        );
+        // A fresh nonce every time
+        assert_ne!(
+            keyring.seal("same").unwrap().0,
+            keyring.seal("same").unwrap().0
+        );
+
+        let other = Keyring::parse(KEY_B).unwrap();
+        assert!(other
+            .open(&key_id, &sealed)
+            .unwrap_err()
+            .to_string()
+            .contains("not configured"));
+        // Same id, different key: authentication fails
+        let imposter = MessageKey {
+            id: key_id.clone(),
+            cipher: Aes256Gcm::new_from_slice(&[7u8; 32]).unwrap(),
+        };
+        assert!(imposter.open(&sealed).is_err());
+
+        let mut tampered = general_purpose::STANDARD.decode(&sealed).unwrap();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(keyring
+            .open(&key_id, &general_purpose::STANDARD.encode(tampered))
+            .is_err());
+    }
+
+    #[test]
+    fn test_test_keys_are_exclusive_and_restored() {
+        let snapshot = || {
+            let _serialized = test_keys::lock();
+            let keyring = ACTIVE.read().unwrap().clone();
+            (
+                keyring.map(|k| k.current().id().to_string()),
+                ENCRYPT.load(Ordering::SeqCst),
+            )
+        };
+        let before = snapshot();
+
+        let keys = TestKeys::configure(Some(Keyring::parse(KEY_A).unwrap()), true);
+        let key_a = seal("hello").unwrap().1.unwrap();
+        let other = std::thread::spawn(|| {
+            let _keys = TestKeys::configure(Some(Keyring::parse(KEY_B).unwrap()), true);
+            seal("hello").unwrap().1.unwrap()
+        });
+        // The other test waits until this one is done with its keys
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_eq!(seal("hello").unwrap().1.unwrap(), key_a);
+        drop(keys);
+        assert_ne!(other.join().unwrap(), key_a);
+
+        assert_eq!(snapshot(), before);
+    }
+
+    #[test]
+    fn test_keyring_parsing() {
+        let keyring =
+            Keyring::parse(&format!("# rotated 2026-10\n{}\n{}, \n", KEY_B, KEY_A)).unwrap();
+        let a = MessageKey::parse(KEY_A).unwrap();
+        assert_eq!(
+            keyring.current().id(),
+            MessageKey::parse(KEY_B).unwrap().id()
+        );
+        assert!(keyring.contains(a.id()));
+        assert!(Keyring::parse("# nothing\n").is_err());
+        assert!(MessageKey::parse("c2hvcnQ=").is_err());
+        assert!(MessageKey::parse("not base64!").is_err());
+    }
+}
@@ -1,16 +1,20 @@
 use axum::{
     body::Body,
+    extract::State,
     http::{header, StatusCode, Uri},
     response::{IntoResponse, Response},
 };
 use rust_embed::RustEmbed;
 
+use crate::server::AppState;
+
 #[derive(RustEmbed)]
 #[folder = "dashboard/dist"]
 struct DashboardAssets;
 
 /// Serve the dashboard SPA with proper fallback to index.html for client-side routing
-pub async fn serve_dashboard(uri: Uri) -> impl IntoResponse {
+pub async fn serve_dashboard(State(state): State<AppState>, uri: Uri) -> impl IntoResponse {
+    let base_path = &state.config.base_path;
     let path = uri.path().trim_start_matches('/');
 
     // Determine the actual file path to serve
@@ -25,12 +29,15 @@ pub async fn serve_dashboard(uri: Uri) -> impl IntoResponse {
 
     // Try to serve the requested file
     if let Some(content) = DashboardAssets::get(&file_path) {
+        if file_path == "index.html" {
+            return serve_index(&content.data, base_path);
+        }
         return serve_file(&file_path, content.data.into());
     }
 
     // Try index.html for SPA routing (all unknown routes serve index.html)
     if let Some(content) = DashboardAssets::get("index.html") {
-        return serve_file("index.html", content.data.into());
+        return serve_index(&content.data, base_path);
     }
 
     // Fallback if dashboard is not built
@@ -41,6 +48,37 @@ pub async fn serve_dashboard(uri: Uri) -> impl IntoResponse {
         .into_response()
 }
 
+/// Serve index.html with root-relative links rewritten under `base_path`
+fn serve_index(data: &[u8], base_path: &str) -> Response {
+    if base_path.is_empty() {
+        return serve_file("index.html", data.to_vec());
+    }
+    let html = String::from_utf8_lossy(data);
+    serve_file("index.html", rebase_index(&html, base_path).into_bytes())
+}
+
+/// Prefix root-relative `src`/`href` attributes with `base_path` and expose it to the SPA
+fn rebase_index(html: &str, base_path: &str) -> String {
+    let mut rebased = String::with_capacity(html.len() + 128);
+    let mut rest = html;
+    while let Some(pos) = rest.find("=\"/") {
+        let (head, tail) = rest.split_at(pos + 2);
+        rebased.push_str(head);
+        // Protocol-relative URLs ("//host/...") point elsewhere and are left alone
+        if !tail[1..].starts_with('/') {
+            rebased.push_str(base_path);
+        }
+        rest = tail;
+    }
+    rebased.push_str(rest);
+
+    let script = format!(
+        "<script>window.__VIBE_BASE_PATH__ = {};</script>\n  </head>",
+        serde_json::Value::String(base_path.to_string())
+    );
+    rebased.replacen("</head>", &script, 1)
+}
+
 fn serve_file(path: &str, data: Vec<u8>) -> Response {
     let mime_type = mime_guess::from_path(path)
         .first_or_octet_stream()
@@ -53,3 +91,20 @@ fn serve_file(path: &str, data: Vec<u8>) -> Response {
         .body(Body::from(data))
         .unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebase_index() {
+        let html = r#"<head><link href="/favicon.ico" /><link href="https://cdn.example.com/x.css" /><script src="//cdn.example.com/y.js"></script><script src="/assets/index.js"></script></head>"#;
+
+        let rebased = rebase_index(html, "/vibe");
+        assert!(rebased.contains(r#"href="/vibe/favicon.ico""#));
+        assert!(rebased.contains(r#"src="/vibe/assets/index.js""#));
+        assert!(rebased.contains(r#"href="https://cdn.example.com/x.css""#));
+        assert!(rebased.contains(r#"src="//cdn.example.com/y.js""#));
+        assert!(rebased.contains(r#"window.__VIBE_BASE_PATH__ = "/vibe";"#));
+    }
+}
@@ -0,0 +1,397 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::HashMap;
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use super::DbPool;
+
+const MAX_GROUP_ID_LEN: usize = 64;
+
+const GROUP_COLUMNS: &str = "group_id, description, created_by, created_at, \
+     (SELECT COUNT(*) FROM agent_group_members m WHERE m.group_id = agent_groups.group_id) \
+     AS member_count";
+
+/// A named set of agents that requests can be addressed to as a whole
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct AgentGroup {
+    /// Canonical name: trimmed and lowercase
+    pub group_id: String,
+    pub description: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+    pub member_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct GroupMember {
+    /// Worker id or `coordinator`
+    pub agent_id: String,
+    pub added_by: String,
+    pub joined_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateAgentGroupRequest {
+    pub group_id: String,
+    pub description: Option<String>,
+    /// Recorded as who created the group; defaults to `api`
+    pub created_by: Option<String>,
+}
+
+/// Result of deleting a group
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeleteGroupOutcome {
+    /// Deleted, with the members it had
+    Deleted {
+        members: i64,
+    },
+    NotFound,
+    /// Not deleted: the group still has members and deletion was not forced
+    HasMembers {
+        members: i64,
+    },
+}
+
+/// Canonical form of a group id, so "Frontend" and " frontend" are the same group
+pub fn canonical_group_id(group_id: &str) -> Result<String> {
+    let group_id = group_id.trim().to_lowercase();
+    if group_id.is_empty() {
+        bail!("Group id must not be empty");
+    }
+    if group_id.len() > MAX_GROUP_ID_LEN
+        || !group_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        bail!(
+            "Invalid group id '{}': use up to {} letters, digits, '-', '_' or '.'",
+            group_id,
+            MAX_GROUP_ID_LEN
+        );
+    }
+    Ok(group_id)
+}
+
+impl AgentGroup {
+    pub async fn list(pool: &DbPool) -> Result<Vec<AgentGroup>> {
+        let groups = sqlx::query_as::<_, AgentGroup>(&format!(
+            "SELECT {} FROM agent_groups ORDER BY group_id ASC",
+            GROUP_COLUMNS
+        ))
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to list agent groups: {:?}", e))?;
+
+        Ok(groups)
+    }
+
+    /// None as well for ids no group can have
+    pub async fn get(pool: &DbPool, group_id: &str) -> Result<Option<AgentGroup>> {
+        let Ok(canonical) = canonical_group_id(group_id) else {
+            return Ok(None);
+        };
+        let group = sqlx::query_as::<_, AgentGroup>(&format!(
+            "SELECT {} FROM agent_groups WHERE group_id = ?1",
+            GROUP_COLUMNS
+        ))
+        .bind(canonical)
+        .fetch_optional(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to fetch agent group '{}': {:?}", group_id, e))?;
+
+        Ok(group)
+    }
+
+    pub async fn create(pool: &DbPool, req: &CreateAgentGroupRequest) -> Result<AgentGroup> {
+        let group_id = canonical_group_id(&req.group_id)?;
+        if Self::get(pool, &group_id).await?.is_some() {
+            bail!("Agent group '{}' already exists", group_id);
+        }
+
+        sqlx::query(
+            "INSERT INTO agent_groups (group_id, description, created_by) VALUES (?1, ?2, ?3)",
+        )
+        .bind(&group_id)
+        .bind(&req.description)
+        .bind(req.created_by.as_deref().unwrap_or("api"))
+        .execute(pool)
+        .await
+        .inspect_err(|e| error!("Failed to create agent group '{}': {:?}", group_id, e))?;
+
+        Self::get(pool, &group_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Failed to create agent group '{}'", group_id))
+    }
+
+    /// Delete a group; one with members is only deleted when `force` is set
+    pub async fn delete(pool: &DbPool, group_id: &str, force: bool) -> Result<DeleteGroupOutcome> {
+        let Some(group) = Self::get(pool, group_id).await? else {
+            return Ok(DeleteGroupOutcome::NotFound);
+        };
+        if group.member_count > 0 && !force {
+            return Ok(DeleteGroupOutcome::HasMembers {
+                members: group.member_count,
+            });
+        }
+
+        // Members go with the group through the cascade
+        sqlx::query("DELETE FROM agent_groups WHERE group_id = ?1")
+            .bind(&group.group_id)
+            .execute(pool)
+            .await
+            .inspect_err(|e| error!("Failed to delete agent group '{}': {:?}", group_id, e))?;
+
+        Ok(DeleteGroupOutcome::Deleted {
+            members: group.member_count,
+        })
+    }
+
+    /// Members of a group, longest-standing first; None when the group does not exist
+    pub async fn members(pool: &DbPool, group_id: &str) -> Result<Option<Vec<GroupMember>>> {
+        let Some(group) = Self::get(pool, group_id).await? else {
+            return Ok(None);
+        };
+        let members = sqlx::query_as::<_, GroupMember>(
+            r#"
+            SELECT agent_id, added_by, joined_at FROM agent_group_members
+            WHERE group_id = ?1
+            ORDER BY joined_at ASC, agent_id ASC
+            "#,
+        )
+        .bind(&group.group_id)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to list members of '{}': {:?}", group_id, e))?;
+
+        Ok(Some(members))
+    }
+
+    /// Add `agent_id` to a group. Returns None when the group does not exist, and whether
+    /// the agent was newly added otherwise.
+    pub async fn join(
+        pool: &DbPool,
+        group_id: &str,
+        agent_id: &str,
+        added_by: &str,
+    ) -> Result<Option<bool>> {
+        let agent_id = agent_id.trim();
+        if agent_id.is_empty() {
+            bail!("Agent id must not be empty");
+        }
+        let Some(group) = Self::get(pool, group_id).await? else {
+            return Ok(None);
+        };
+
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO agent_group_members (group_id, agent_id, added_by) VALUES (?1, ?2, ?3)",
+        )
+        .bind(&group.group_id)
+        .bind(agent_id)
+        .bind(added_by)
+        .execute(pool)
+        .await
+        .inspect_err(|e| {
+            error!(
+                "Failed to add '{}' to agent group '{}': {:?}",
+                agent_id, group_id, e
+            )
+        })?;
+
+        Ok(Some(result.rows_affected() > 0))
+    }
+
+    /// Remove `agent_id` from a group. Returns None when the group does not exist, and
+    /// whether the agent was a member otherwise.
+    pub async fn leave(pool: &DbPool, group_id: &str, agent_id: &str) -> Result<Option<bool>> {
+        let Some(group) = Self::get(pool, group_id).await? else {
+            return Ok(None);
+        };
+
+        let result =
+            sqlx::query("DELETE FROM agent_group_members WHERE group_id = ?1 AND agent_id = ?2")
+                .bind(&group.group_id)
+                .bind(agent_id.trim())
+                .execute(pool)
+                .await
+                .inspect_err(|e| {
+                    error!(
+                        "Failed to remove '{}' from agent group '{}': {:?}",
+                        agent_id, group_id, e
+                    )
+                })?;
+
+        Ok(Some(result.rows_affected() > 0))
+    }
+
+    /// Groups of each agent that belongs to any, by agent id
+    pub async fn memberships(pool: &DbPool) -> Result<HashMap<String, Vec<String>>> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT agent_id, group_id FROM agent_group_members ORDER BY agent_id, group_id",
+        )
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to list agent group memberships: {:?}", e))?;
+
+        let mut memberships: HashMap<String, Vec<String>> = HashMap::new();
+        for (agent_id, group_id) in rows {
+            memberships.entry(agent_id).or_default().push(group_id);
+        }
+        Ok(memberships)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::memory_pool;
+
+    #[tokio::test]
+    async fn test_membership_and_forced_delete() {
+        let pool = memory_pool().await.unwrap();
+        let group = AgentGroup::create(
+            &pool,
+            &CreateAgentGroupRequest {
+                group_id: " Frontend ".to_string(),
+                description: Some("UI workers".to_string()),
+                created_by: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(group.group_id, "frontend");
+        assert_eq!(group.created_by, "api");
+        assert!(AgentGroup::create(
+            &pool,
+            &CreateAgentGroupRequest {
+                group_id: "FRONTEND".to_string(),
+                description: None,
+                created_by: None,
+            },
+        )
+        .await
+        .is_err());
+        assert!(canonical_group_id("front end").is_err());
+
+        assert_eq!(
+            AgentGroup::join(&pool, "frontend", "w-1", "coordinator")
+                .await
+                .unwrap(),
+            Some(true)
+        );
+        assert_eq!(
+            AgentGroup::join(&pool, "Frontend", "w-1", "w-1")
+                .await
+                .unwrap(),
+            Some(false)
+        );
+        AgentGroup::join(&pool, "frontend", "w-2", "w-2")
+            .await
+            .unwrap();
+        assert_eq!(
+            AgentGroup::join(&pool, "backend", "w-1", "w-1")
+                .await
+                .unwrap(),
+            None
+        );
+
+        let members = AgentGroup::members(&pool, "frontend")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].added_by, "coordinator");
+        assert_eq!(
+            AgentGroup::memberships(&pool).await.unwrap()["w-2"],
+            ["frontend"]
+        );
+
+        assert_eq!(
+            AgentGroup::leave(&pool, "frontend", "w-2").await.unwrap(),
+            Some(true)
+        );
+        assert_eq!(
+            AgentGroup::leave(&pool, "frontend", "w-2").await.unwrap(),
+            Some(false)
+        );
+
+        assert_eq!(
+            AgentGroup::delete(&pool, "frontend", false).await.unwrap(),
+            DeleteGroupOutcome::HasMembers { members: 1 }
+        );
+        assert_eq!(
+            AgentGroup::delete(&pool, "frontend", true).await.unwrap(),
+            DeleteGroupOutcome::Deleted { members: 1 }
+        );
+        assert_eq!(
+            AgentGroup::delete(&pool, "frontend", true).await.unwrap(),
+            DeleteGroupOutcome::NotFound
+        );
+        assert!(AgentGroup::memberships(&pool).await.unwrap().is_empty());
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_group_request_fans_out_to_current_members() {
+        use crate::database::agent_requests::{AckOutcome, AgentRequest, AgentRequestQuery};
+
+        let pool = memory_pool().await.unwrap();
+        AgentGroup::create(
+            &pool,
+            &CreateAgentGroupRequest {
+                group_id: "frontend".to_string(),
+                description: None,
+                created_by: None,
+            },
+        )
+        .await
+        .unwrap();
+        for agent in ["w-1", "w-2"] {
+            AgentGroup::join(&pool, "frontend", agent, agent)
+                .await
+                .unwrap();
+        }
+
+        let sent = AgentRequest::create_for_group(&pool, "coordinator", "frontend", None, "{}", 60)
+            .await
+            .unwrap();
+        let targets: Vec<_> = sent.iter().map(|r| r.target.as_str()).collect();
+        assert_eq!(targets, ["w-1", "w-2"]);
+        assert_ne!(sent[0].correlation_id, sent[1].correlation_id);
+
+        // Joining later does not add a copy; each member gets and acknowledges its own
+        AgentGroup::join(&pool, "frontend", "w-3", "w-3")
+            .await
+            .unwrap();
+        assert!(AgentRequest::take_pending(&pool, "w-3", None, 50)
+            .await
+            .unwrap()
+            .is_empty());
+        let pending = AgentRequest::take_pending(&pool, "w-1", None, 50)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].group_id.as_deref(), Some("frontend"));
+        assert!(matches!(
+            AgentRequest::acknowledge(&pool, &sent[1].correlation_id, "w-1")
+                .await
+                .unwrap(),
+            AckOutcome::NotRecipient { .. }
+        ));
+
+        let listed = AgentRequest::list(
+            &pool,
+            &AgentRequestQuery {
+                group_id: Some("frontend".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(listed.len(), 2);
+
+        pool.close().await;
+    }
+}
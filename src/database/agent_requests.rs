@@ -0,0 +1,956 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection};
+use std::{fmt, str::FromStr};
+use tracing::{error, warn};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::{returning::FetchReturning, DbPool};
+use crate::crypto;
+
+const REQUEST_COLUMNS: &str = "correlation_id, requester, target, ticket_id, project_id, payload, response, status, deadline, created_at, responded_at, delivered_at, acknowledged_at, payload_key_id, response_key_id, group_id";
+
+/// Unacknowledged, live requests to ?1, after the sort key (?2, ?3) when one is given
+const PENDING_FOR_TARGET: &str =
+    "target = ?1 AND status = 'pending' AND deadline > datetime('now') \
+     AND acknowledged_at IS NULL \
+     AND (?2 IS NULL OR created_at > ?2 OR (created_at = ?2 AND correlation_id > ?3))";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct AgentRequest {
+    pub correlation_id: String,
+    pub requester: String,
+    pub target: String,
+    pub ticket_id: Option<String>,
+    /// Project of the ticket, the payload's `project_id`, or the sending or receiving worker
+    pub project_id: Option<String>,
+    pub payload: String,          // JSON
+    pub response: Option<String>, // JSON
+    pub status: String,
+    pub deadline: String,
+    pub created_at: String,
+    pub responded_at: Option<String>,
+    pub delivered_at: Option<String>,
+    pub acknowledged_at: Option<String>,
+    /// Keys the stored payload and response are encrypted with (with `--encrypt-messages`)
+    #[serde(skip)]
+    pub payload_key_id: Option<String>,
+    #[serde(skip)]
+    pub response_key_id: Option<String>,
+    /// Agent group the request was sent to, one copy per member
+    pub group_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestStatus {
+    Pending,
+    Answered,
+    Expired,
+    Rejected,
+}
+
+impl fmt::Display for RequestStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestStatus::Pending => write!(f, "pending"),
+            RequestStatus::Answered => write!(f, "answered"),
+            RequestStatus::Expired => write!(f, "expired"),
+            RequestStatus::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
+impl FromStr for RequestStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pending" => Ok(RequestStatus::Pending),
+            "answered" => Ok(RequestStatus::Answered),
+            "expired" => Ok(RequestStatus::Expired),
+            "rejected" => Ok(RequestStatus::Rejected),
+            _ => Err(anyhow::anyhow!(
+                "Invalid request status '{}'. Valid statuses are: pending, answered, expired, rejected",
+                s
+            )),
+        }
+    }
+}
+
+/// Delivery breakdown of agent requests
+#[derive(Debug, Clone, Default, Serialize, FromRow, ToSchema)]
+pub struct RequestDeliveryStats {
+    pub total: i64,
+    pub undelivered: i64,
+    pub delivered: i64,
+    pub acknowledged: i64,
+    pub unacknowledged: i64,
+}
+
+/// Result of acknowledging a request
+#[derive(Debug, Clone)]
+pub enum AckOutcome {
+    Acknowledged(Box<AgentRequest>),
+    NotFound,
+    NotRecipient { target: String },
+}
+
+/// Result of answering or rejecting a request
+#[derive(Debug, Clone)]
+pub enum SettleOutcome {
+    Settled(Box<AgentRequest>),
+    NotFound,
+    /// Only the request's target may answer it
+    NotRecipient {
+        target: String,
+    },
+    PastDeadline,
+    AlreadySettled {
+        status: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AgentRequestQuery {
+    pub project_id: Option<String>,
+    pub status: Option<String>,
+    pub requester: Option<String>,
+    pub target: Option<String>,
+    pub group_id: Option<String>,
+    pub limit: Option<i64>,
+}
+
+impl AgentRequest {
+    /// The request with its payload and response decrypted when they were stored encrypted
+    fn opened(mut self) -> Result<AgentRequest> {
+        let payload = std::mem::take(&mut self.payload);
+        self.payload = crypto::open(payload, self.payload_key_id.as_deref()).map_err(|e| {
+            anyhow::anyhow!("Failed to decrypt request {}: {}", self.correlation_id, e)
+        })?;
+        if let Some(response) = self.response.take() {
+            self.response = Some(
+                crypto::open(response, self.response_key_id.as_deref()).map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to decrypt response to request {}: {}",
+                        self.correlation_id,
+                        e
+                    )
+                })?,
+            );
+        }
+        Ok(self)
+    }
+
+    pub async fn create(
+        pool: &DbPool,
+        requester: &str,
+        target: &str,
+        ticket_id: Option<&str>,
+        payload: &str,
+        timeout_secs: u64,
+    ) -> Result<AgentRequest> {
+        let mut conn = pool.acquire().await?;
+        Self::insert(
+            &mut conn,
+            requester,
+            target,
+            ticket_id,
+            payload,
+            timeout_secs,
+            None,
+        )
+        .await
+    }
+
+    /// Send a request to every current member of `group_id`, one request per member, in a
+    /// single transaction. Members joining later do not receive it. Empty when the group has
+    /// no members.
+    pub async fn create_for_group(
+        pool: &DbPool,
+        requester: &str,
+        group_id: &str,
+        ticket_id: Option<&str>,
+        payload: &str,
+        timeout_secs: u64,
+    ) -> Result<Vec<AgentRequest>> {
+        let mut tx = pool.begin().await?;
+        let members: Vec<String> = sqlx::query_scalar(
+            "SELECT agent_id FROM agent_group_members WHERE group_id = ?1 ORDER BY agent_id",
+        )
+        .bind(group_id)
+        .fetch_all(&mut *tx)
+        .await
+        .inspect_err(|e| warn!("Failed to list members of '{}': {:?}", group_id, e))?;
+
+        let mut requests = Vec::with_capacity(members.len());
+        for target in &members {
+            requests.push(
+                Self::insert(
+                    &mut tx,
+                    requester,
+                    target,
+                    ticket_id,
+                    payload,
+                    timeout_secs,
+                    Some(group_id),
+                )
+                .await?,
+            );
+        }
+        tx.commit().await.inspect_err(|e| {
+            error!(
+                "Failed to send request from '{}' to group '{}': {:?}",
+                requester, group_id, e
+            )
+        })?;
+
+        Ok(requests)
+    }
+
+    async fn insert(
+        conn: &mut SqliteConnection,
+        requester: &str,
+        target: &str,
+        ticket_id: Option<&str>,
+        payload: &str,
+        timeout_secs: u64,
+        group_id: Option<&str>,
+    ) -> Result<AgentRequest> {
+        let correlation_id = Uuid::new_v4().to_string();
+        let payload_project = serde_json::from_str::<serde_json::Value>(payload)
+            .ok()
+            .and_then(|p| p.get("project_id")?.as_str().map(str::to_string));
+        let (payload, key_id) = crypto::seal(payload)?;
+        let request = sqlx::query_as::<_, AgentRequest>(&format!(
+            r#"
+            INSERT INTO agent_requests (
+                correlation_id, requester, target, ticket_id, payload, deadline, payload_key_id,
+                project_id, group_id
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, datetime('now', ?6), ?7, COALESCE(
+                (SELECT project_id FROM tickets WHERE ticket_id = ?4),
+                (SELECT repository_name FROM projects WHERE repository_name = ?8),
+                (SELECT project_id FROM workers WHERE worker_id = ?2),
+                (SELECT project_id FROM workers WHERE worker_id = ?3)
+            ), ?9)
+            RETURNING {}
+        "#,
+            REQUEST_COLUMNS
+        ))
+        .bind(&correlation_id)
+        .bind(requester)
+        .bind(target)
+        .bind(ticket_id)
+        .bind(&payload)
+        .bind(format!("+{} seconds", timeout_secs))
+        .bind(key_id)
+        .bind(payload_project)
+        .bind(group_id)
+        .fetch_returned_one(&mut *conn)
+        .await
+        .inspect_err(|e| {
+            error!(
+                "Failed to create request from '{}' to '{}': {:?}",
+                requester, target, e
+            )
+        })?;
+
+        request.opened()
+    }
+
+    pub async fn get_by_id(pool: &DbPool, correlation_id: &str) -> Result<Option<AgentRequest>> {
+        let request = sqlx::query_as::<_, AgentRequest>(&format!(
+            "SELECT {} FROM agent_requests WHERE correlation_id = ?1",
+            REQUEST_COLUMNS
+        ))
+        .bind(correlation_id)
+        .fetch_optional(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to fetch request {}: {:?}", correlation_id, e))?;
+
+        request.map(AgentRequest::opened).transpose()
+    }
+
+    /// Record `responder`'s answer to a pending request. Only the request's target may
+    /// settle it, once, and only before its deadline.
+    pub async fn settle(
+        pool: &DbPool,
+        correlation_id: &str,
+        responder: &str,
+        status: RequestStatus,
+        response: &str,
+    ) -> Result<SettleOutcome> {
+        let (response, key_id) = crypto::seal(response)?;
+        let settled = sqlx::query_as::<_, AgentRequest>(&format!(
+            r#"
+            UPDATE agent_requests
+            SET status = ?1, response = ?2, responded_at = datetime('now'), response_key_id = ?4
+            WHERE correlation_id = ?3 AND target = ?5
+              AND status = 'pending' AND deadline > datetime('now')
+            RETURNING {}
+        "#,
+            REQUEST_COLUMNS
+        ))
+        .bind(status.to_string())
+        .bind(&response)
+        .bind(correlation_id)
+        .bind(key_id)
+        .bind(responder)
+        .fetch_returned(pool)
+        .await
+        .inspect_err(|e| error!("Failed to settle request {}: {:?}", correlation_id, e))?;
+        if let Some(request) = settled {
+            return Ok(SettleOutcome::Settled(Box::new(request.opened()?)));
+        }
+
+        Ok(match Self::get_by_id(pool, correlation_id).await? {
+            None => SettleOutcome::NotFound,
+            Some(request) if request.target != responder => SettleOutcome::NotRecipient {
+                target: request.target,
+            },
+            Some(request) if request.status == "pending" => SettleOutcome::PastDeadline,
+            Some(request) => SettleOutcome::AlreadySettled {
+                status: request.status,
+            },
+        })
+    }
+
+    /// Mark pending requests past their deadline as expired, returning them
+    pub async fn expire_overdue(pool: &DbPool) -> Result<Vec<AgentRequest>> {
+        let expired = sqlx::query_as::<_, AgentRequest>(&format!(
+            r#"
+            UPDATE agent_requests
+            SET status = 'expired', responded_at = datetime('now')
+            WHERE status = 'pending' AND deadline <= datetime('now')
+            RETURNING {}
+        "#,
+            REQUEST_COLUMNS
+        ))
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to expire overdue requests: {:?}", e))?;
+
+        expired.into_iter().map(AgentRequest::opened).collect()
+    }
+
+    /// Delete settled requests older than their retention period: the
+    /// `message_retention_days` setting of the ticket's project, else `default_days`.
+    /// A period of 0 keeps them forever.
+    pub async fn purge_settled(pool: &DbPool, default_days: u64) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM agent_requests
+            WHERE correlation_id IN (
+                SELECT correlation_id FROM (
+                    SELECT r.correlation_id, r.responded_at,
+                           COALESCE(json_extract(p.project_settings, '$.message_retention_days'), ?1)
+                               AS retention_days
+                    FROM agent_requests r
+                    LEFT JOIN tickets t ON t.ticket_id = r.ticket_id
+                    LEFT JOIN projects p ON p.repository_name = COALESCE(t.project_id, r.project_id)
+                    WHERE r.status != 'pending' AND r.responded_at IS NOT NULL
+                )
+                WHERE retention_days > 0
+                  AND datetime(responded_at) < datetime('now', '-' || retention_days || ' days')
+            )
+            "#,
+        )
+        .bind(default_days as i64)
+        .execute(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to purge settled requests: {:?}", e))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete the requests of a project, returning how many were removed
+    pub async fn delete_for_project(pool: &DbPool, project_id: &str) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM agent_requests WHERE project_id = ?1 OR ticket_id IN (SELECT ticket_id FROM tickets WHERE project_id = ?1)",
+        )
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to delete requests of '{}': {:?}", project_id, e))?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn list(pool: &DbPool, query: &AgentRequestQuery) -> Result<Vec<AgentRequest>> {
+        let mut builder = sqlx::QueryBuilder::new(format!(
+            "SELECT {} FROM agent_requests WHERE 1=1",
+            REQUEST_COLUMNS
+        ));
+
+        if let Some(project_id) = &query.project_id {
+            builder.push(" AND project_id = ").push_bind(project_id);
+        }
+        if let Some(status) = &query.status {
+            builder.push(" AND status = ").push_bind(status);
+        }
+        if let Some(requester) = &query.requester {
+            builder.push(" AND requester = ").push_bind(requester);
+        }
+        if let Some(target) = &query.target {
+            builder.push(" AND target = ").push_bind(target);
+        }
+        if let Some(group_id) = &query.group_id {
+            builder.push(" AND group_id = ").push_bind(group_id);
+        }
+
+        builder
+            .push(" ORDER BY created_at DESC LIMIT ")
+            .push_bind(query.limit.unwrap_or(100).clamp(1, 1000));
+
+        let requests = builder
+            .build_query_as::<AgentRequest>()
+            .fetch_all(pool)
+            .await?;
+
+        requests.into_iter().map(AgentRequest::opened).collect()
+    }
+
+    /// A page of the unacknowledged pending requests addressed to `target`, oldest first,
+    /// resuming after the `(created_at, correlation_id)` of the previous page. Requests on the
+    /// page not yet delivered are marked delivered as they are returned. Up to `limit + 1`
+    /// requests are returned; the extra one only tells whether another page follows.
+    pub async fn take_pending(
+        pool: &DbPool,
+        target: &str,
+        after: Option<(&str, &str)>,
+        limit: i64,
+    ) -> Result<Vec<AgentRequest>> {
+        let (after_created, after_id) = after.unzip();
+        sqlx::query(&format!(
+            r#"
+            UPDATE agent_requests
+            SET delivered_at = datetime('now')
+            WHERE delivered_at IS NULL AND correlation_id IN (
+                SELECT correlation_id FROM agent_requests
+                WHERE {}
+                ORDER BY created_at ASC, correlation_id ASC
+                LIMIT ?4
+            )
+        "#,
+            PENDING_FOR_TARGET
+        ))
+        .bind(target)
+        .bind(after_created)
+        .bind(after_id)
+        .bind(limit)
+        .execute(pool)
+        .await
+        .inspect_err(|e| error!("Failed to mark requests to '{}' delivered: {:?}", target, e))?;
+
+        let requests = sqlx::query_as::<_, AgentRequest>(&format!(
+            r#"
+            SELECT {} FROM agent_requests
+            WHERE {}
+            ORDER BY created_at ASC, correlation_id ASC
+            LIMIT ?4
+        "#,
+            REQUEST_COLUMNS, PENDING_FOR_TARGET
+        ))
+        .bind(target)
+        .bind(after_created)
+        .bind(after_id)
+        .bind(limit + 1)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to fetch pending requests for '{}': {:?}", target, e))?;
+
+        requests.into_iter().map(AgentRequest::opened).collect()
+    }
+
+    /// Record that a pending request reached its target by push. Keeps an earlier delivery
+    /// time.
+    pub async fn mark_delivered(pool: &DbPool, correlation_id: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE agent_requests
+            SET delivered_at = COALESCE(delivered_at, datetime('now'))
+            WHERE correlation_id = ?1 AND status = 'pending'
+        "#,
+        )
+        .bind(correlation_id)
+        .execute(pool)
+        .await
+        .inspect_err(|e| {
+            warn!(
+                "Failed to mark request {} delivered: {:?}",
+                correlation_id, e
+            )
+        })?;
+
+        Ok(())
+    }
+
+    /// Number of unacknowledged pending requests addressed to `target`
+    pub async fn count_pending(pool: &DbPool, target: &str) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(&format!(
+            "SELECT COUNT(*) FROM agent_requests WHERE {}",
+            PENDING_FOR_TARGET
+        ))
+        .bind(target)
+        .bind(None::<&str>)
+        .bind(None::<&str>)
+        .fetch_one(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to count pending requests for '{}': {:?}", target, e))?;
+
+        Ok(count)
+    }
+
+    /// Record that `agent`, which must be the request's target, has received the request.
+    /// Acknowledging twice keeps the first timestamp.
+    pub async fn acknowledge(
+        pool: &DbPool,
+        correlation_id: &str,
+        agent: &str,
+    ) -> Result<AckOutcome> {
+        let Some(request) = Self::get_by_id(pool, correlation_id).await? else {
+            return Ok(AckOutcome::NotFound);
+        };
+        if request.target != agent {
+            return Ok(AckOutcome::NotRecipient {
+                target: request.target,
+            });
+        }
+
+        let request = sqlx::query_as::<_, AgentRequest>(&format!(
+            r#"
+            UPDATE agent_requests
+            SET delivered_at = COALESCE(delivered_at, datetime('now')),
+                acknowledged_at = COALESCE(acknowledged_at, datetime('now'))
+            WHERE correlation_id = ?1
+            RETURNING {}
+        "#,
+            REQUEST_COLUMNS
+        ))
+        .bind(correlation_id)
+        .fetch_returned_one(pool)
+        .await
+        .inspect_err(|e| error!("Failed to acknowledge request {}: {:?}", correlation_id, e))?;
+
+        Ok(AckOutcome::Acknowledged(Box::new(request.opened()?)))
+    }
+}
+
+impl RequestDeliveryStats {
+    /// Delivery counts of all requests, or of one project's
+    pub async fn load(pool: &DbPool, project_id: Option<&str>) -> Result<RequestDeliveryStats> {
+        let stats = sqlx::query_as::<_, RequestDeliveryStats>(
+            r#"
+            SELECT
+                COUNT(*) AS total,
+                COALESCE(SUM(delivered_at IS NULL), 0) AS undelivered,
+                COALESCE(SUM(delivered_at IS NOT NULL), 0) AS delivered,
+                COALESCE(SUM(acknowledged_at IS NOT NULL), 0) AS acknowledged,
+                COALESCE(SUM(acknowledged_at IS NULL), 0) AS unacknowledged
+            FROM agent_requests
+            WHERE ?1 IS NULL OR project_id = ?1
+        "#,
+        )
+        .bind(project_id)
+        .fetch_one(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to load request delivery stats: {:?}", e))?;
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::agent_groups::{AgentGroup, CreateAgentGroupRequest};
+    use crate::database::workers::Worker;
+    use crate::test_support::{memory_pool, Fixtures};
+    use sqlx::Executor;
+
+    #[tokio::test]
+    async fn test_offline_agent_replays_pending_in_batches() {
+        let pool = memory_pool().await.unwrap();
+        AgentGroup::create(
+            &pool,
+            &CreateAgentGroupRequest {
+                group_id: "reviewers".to_string(),
+                description: None,
+                created_by: None,
+            },
+        )
+        .await
+        .unwrap();
+        for agent in ["w-1", "w-2"] {
+            AgentGroup::join(&pool, "reviewers", agent, agent)
+                .await
+                .unwrap();
+        }
+
+        // w-1 is offline while two direct requests and one to its group arrive
+        for n in 0..2 {
+            AgentRequest::create(
+                &pool,
+                "coordinator",
+                "w-1",
+                None,
+                &format!("{{\"n\":{}}}", n),
+                60,
+            )
+            .await
+            .unwrap();
+        }
+        AgentRequest::create_for_group(&pool, "w-3", "reviewers", None, "{}", 60)
+            .await
+            .unwrap();
+        assert_eq!(AgentRequest::count_pending(&pool, "w-1").await.unwrap(), 3);
+
+        // On reconnect it replays them two at a time; only the returned batch is delivered
+        let first = AgentRequest::take_pending(&pool, "w-1", None, 2)
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 3, "one extra row signals another batch");
+        let stats = RequestDeliveryStats::load(&pool, None).await.unwrap();
+        assert_eq!((stats.delivered, stats.undelivered), (2, 2));
+
+        let last = &first[1];
+        let rest = AgentRequest::take_pending(
+            &pool,
+            "w-1",
+            Some((&last.created_at, &last.correlation_id)),
+            2,
+        )
+        .await
+        .unwrap();
+        assert_eq!(rest.len(), 1);
+        let replayed: Vec<_> = first.iter().take(2).chain(&rest).collect();
+        assert_eq!(
+            replayed.iter().filter(|r| r.group_id.is_some()).count(),
+            1,
+            "the group request is replayed with the direct ones"
+        );
+
+        for request in replayed {
+            assert!(matches!(
+                AgentRequest::acknowledge(&pool, &request.correlation_id, "w-1")
+                    .await
+                    .unwrap(),
+                AckOutcome::Acknowledged(_)
+            ));
+        }
+        assert_eq!(AgentRequest::count_pending(&pool, "w-1").await.unwrap(), 0);
+        assert!(AgentRequest::take_pending(&pool, "w-1", None, 2)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // The other member's copy of the group request is untouched
+        let other = AgentRequest::take_pending(&pool, "w-2", None, 2)
+            .await
+            .unwrap();
+        assert_eq!(other.len(), 1);
+        assert!(other[0].acknowledged_at.is_none());
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_only_the_target_settles_its_request_once() {
+        let pool = memory_pool().await.unwrap();
+        let first = AgentRequest::create(&pool, "w-1", "w-2", None, "{\"q\":1}", 60)
+            .await
+            .unwrap();
+        let second = AgentRequest::create(&pool, "w-1", "w-2", None, "{\"q\":2}", 60)
+            .await
+            .unwrap();
+        assert_ne!(first.correlation_id, second.correlation_id);
+
+        // A third agent cannot answer on the target's behalf, nor can the requester
+        for impostor in ["w-3", "w-1"] {
+            match AgentRequest::settle(
+                &pool,
+                &first.correlation_id,
+                impostor,
+                RequestStatus::Answered,
+                "\"forged\"",
+            )
+            .await
+            .unwrap()
+            {
+                SettleOutcome::NotRecipient { target } => assert_eq!(target, "w-2"),
+                other => panic!("expected NotRecipient, got {:?}", other),
+            }
+        }
+
+        // The answer lands on the request it correlates with, and only there
+        let SettleOutcome::Settled(answered) = AgentRequest::settle(
+            &pool,
+            &first.correlation_id,
+            "w-2",
+            RequestStatus::Answered,
+            "{\"a\":1}",
+        )
+        .await
+        .unwrap() else {
+            panic!("the target's answer should settle the request");
+        };
+        assert_eq!(answered.status, "answered");
+        assert_eq!(answered.response.as_deref(), Some("{\"a\":1}"));
+        let untouched = AgentRequest::get_by_id(&pool, &second.correlation_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(untouched.status, "pending");
+        assert!(untouched.response.is_none());
+
+        // A second response does not overwrite the first
+        match AgentRequest::settle(
+            &pool,
+            &first.correlation_id,
+            "w-2",
+            RequestStatus::Rejected,
+            "{\"a\":2}",
+        )
+        .await
+        .unwrap()
+        {
+            SettleOutcome::AlreadySettled { status } => assert_eq!(status, "answered"),
+            other => panic!("expected AlreadySettled, got {:?}", other),
+        }
+        let stored = AgentRequest::get_by_id(&pool, &first.correlation_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.response.as_deref(), Some("{\"a\":1}"));
+
+        assert!(matches!(
+            AgentRequest::settle(&pool, "missing", "w-2", RequestStatus::Answered, "null")
+                .await
+                .unwrap(),
+            SettleOutcome::NotFound
+        ));
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_overdue_request_expires_and_cannot_be_answered() {
+        let pool = memory_pool().await.unwrap();
+        let overdue = AgentRequest::create(&pool, "coordinator", "w-1", None, "{}", 60)
+            .await
+            .unwrap();
+        let current = AgentRequest::create(&pool, "coordinator", "w-1", None, "{}", 60)
+            .await
+            .unwrap();
+        sqlx::query(
+            "UPDATE agent_requests SET deadline = datetime('now', '-1 second') WHERE correlation_id = ?1",
+        )
+        .bind(&overdue.correlation_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // Past the deadline, the answer is refused even before the sweeper runs
+        assert!(matches!(
+            AgentRequest::settle(
+                &pool,
+                &overdue.correlation_id,
+                "w-1",
+                RequestStatus::Answered,
+                "null"
+            )
+            .await
+            .unwrap(),
+            SettleOutcome::PastDeadline
+        ));
+
+        let expired = AgentRequest::expire_overdue(&pool).await.unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].correlation_id, overdue.correlation_id);
+        assert_eq!(expired[0].status, "expired");
+        assert!(matches!(
+            AgentRequest::settle(
+                &pool,
+                &overdue.correlation_id,
+                "w-1",
+                RequestStatus::Answered,
+                "null"
+            )
+            .await
+            .unwrap(),
+            SettleOutcome::AlreadySettled { status } if status == "expired"
+        ));
+
+        let current = AgentRequest::get_by_id(&pool, &current.correlation_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(current.status, "pending");
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_only_the_target_acknowledges_and_the_first_ack_stands() {
+        let pool = memory_pool().await.unwrap();
+        let request = AgentRequest::create(&pool, "coordinator", "w-1", None, "{}", 60)
+            .await
+            .unwrap();
+        assert!(request.delivered_at.is_none());
+
+        match AgentRequest::acknowledge(&pool, &request.correlation_id, "w-2")
+            .await
+            .unwrap()
+        {
+            AckOutcome::NotRecipient { target } => assert_eq!(target, "w-1"),
+            other => panic!("expected NotRecipient, got {:?}", other),
+        }
+        let untouched = AgentRequest::get_by_id(&pool, &request.correlation_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(untouched.acknowledged_at.is_none());
+        assert!(untouched.delivered_at.is_none());
+
+        // Acknowledging implies delivery, even when neither push nor poll recorded it
+        let AckOutcome::Acknowledged(first) =
+            AgentRequest::acknowledge(&pool, &request.correlation_id, "w-1")
+                .await
+                .unwrap()
+        else {
+            panic!("the target's acknowledgement should be recorded");
+        };
+        assert!(first.delivered_at.is_some());
+        assert!(first.acknowledged_at.is_some());
+
+        sqlx::query(
+            "UPDATE agent_requests SET acknowledged_at = '2026-01-01 00:00:00' WHERE correlation_id = ?1",
+        )
+        .bind(&request.correlation_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let AckOutcome::Acknowledged(again) =
+            AgentRequest::acknowledge(&pool, &request.correlation_id, "w-1")
+                .await
+                .unwrap()
+        else {
+            panic!("acknowledging twice should succeed");
+        };
+        assert_eq!(
+            again.acknowledged_at.as_deref(),
+            Some("2026-01-01 00:00:00")
+        );
+
+        assert!(matches!(
+            AgentRequest::acknowledge(&pool, "missing", "w-1")
+                .await
+                .unwrap(),
+            AckOutcome::NotFound
+        ));
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_one_projects_requests_are_not_visible_from_another() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let web = fx.project("scope-web").create().await.unwrap();
+        let api = fx.project("scope-api").create().await.unwrap();
+        let web_ticket = fx
+            .ticket(&web, "web")
+            .claimed_by("w-web")
+            .create()
+            .await
+            .unwrap();
+        let api_ticket = fx
+            .ticket(&api, "api")
+            .claimed_by("w-api")
+            .create()
+            .await
+            .unwrap();
+        pool.execute(
+            r#"
+            INSERT INTO workers (worker_id, project_id, worker_type, status, queue_name)
+            VALUES ('w-web', 'scope-web', 'coder', 'active', 'q'),
+                   ('w-api', 'scope-api', 'coder', 'active', 'q');
+            "#,
+        )
+        .await
+        .unwrap();
+
+        for (requester, ticket_id) in [
+            ("w-web", Some(web_ticket.ticket_id.as_str())),
+            ("w-web", None),
+            ("w-api", Some(api_ticket.ticket_id.as_str())),
+        ] {
+            AgentRequest::create(&pool, requester, "coordinator", ticket_id, "{}", 60)
+                .await
+                .unwrap();
+        }
+        // Names the other project in its payload, but is about a ticket of this one
+        AgentRequest::create(
+            &pool,
+            "coordinator",
+            "w-api",
+            Some(&api_ticket.ticket_id),
+            r#"{"project_id": "scope-web"}"#,
+            60,
+        )
+        .await
+        .unwrap();
+
+        let listed = |project_id: &str| {
+            let query = AgentRequestQuery {
+                project_id: Some(project_id.to_string()),
+                ..Default::default()
+            };
+            let pool = pool.clone();
+            async move { AgentRequest::list(&pool, &query).await.unwrap() }
+        };
+        let web_requests = listed("scope-web").await;
+        assert_eq!(web_requests.len(), 2);
+        assert!(web_requests
+            .iter()
+            .all(|r| r.project_id.as_deref() == Some("scope-web")));
+        let api_requests = listed("scope-api").await;
+        assert_eq!(api_requests.len(), 2);
+        assert!(api_requests
+            .iter()
+            .all(|r| r.ticket_id.as_deref() == Some(api_ticket.ticket_id.as_str())));
+
+        let web_stats = RequestDeliveryStats::load(&pool, Some("scope-web"))
+            .await
+            .unwrap();
+        let api_stats = RequestDeliveryStats::load(&pool, Some("scope-api"))
+            .await
+            .unwrap();
+        let all_stats = RequestDeliveryStats::load(&pool, None).await.unwrap();
+        assert_eq!(
+            (web_stats.total, api_stats.total, all_stats.total),
+            (2, 2, 4)
+        );
+
+        let web_assignments = Worker::assignments(&pool, "scope-web").await.unwrap();
+        assert_eq!(
+            web_assignments.into_iter().collect::<Vec<_>>(),
+            [("w-web".to_string(), vec![web_ticket.ticket_id.clone()])]
+        );
+
+        // Removing one project's requests leaves the other's in place
+        assert_eq!(
+            AgentRequest::delete_for_project(&pool, "scope-web")
+                .await
+                .unwrap(),
+            2
+        );
+        assert!(listed("scope-web").await.is_empty());
+        assert_eq!(listed("scope-api").await.len(), 2);
+
+        pool.close().await;
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use super::{returning::FetchReturning, DbPool};
+
+const ATTACHMENT_COLUMNS: &str =
+    "id, ticket_id, filename, content_type, size_bytes, sha256, uploaded_by, stored_name, created_at";
+
+/// A file attached to a ticket; the content is stored on disk
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Attachment {
+    pub id: i64,
+    pub ticket_id: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub sha256: String,
+    pub uploaded_by: Option<String>,
+    #[serde(skip_serializing)]
+    pub stored_name: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewAttachment<'a> {
+    pub ticket_id: &'a str,
+    pub filename: &'a str,
+    pub content_type: &'a str,
+    pub size_bytes: i64,
+    pub sha256: &'a str,
+    pub uploaded_by: Option<&'a str>,
+    pub stored_name: &'a str,
+}
+
+impl Attachment {
+    pub async fn create(pool: &DbPool, new: &NewAttachment<'_>) -> Result<Attachment> {
+        let id = sqlx::query(
+            r#"
+            INSERT INTO ticket_attachments (
+                ticket_id, filename, content_type, size_bytes, sha256, uploaded_by, stored_name
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+        )
+        .bind(new.ticket_id)
+        .bind(new.filename)
+        .bind(new.content_type)
+        .bind(new.size_bytes)
+        .bind(new.sha256)
+        .bind(new.uploaded_by)
+        .bind(new.stored_name)
+        .execute(pool)
+        .await
+        .inspect_err(|e| {
+            error!(
+                "Failed to record attachment '{}' of ticket {}: {:?}",
+                new.filename, new.ticket_id, e
+            )
+        })?
+        .last_insert_rowid();
+
+        Self::get(pool, new.ticket_id, id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Attachment {} disappeared after creation", id))
+    }
+
+    pub async fn get(pool: &DbPool, ticket_id: &str, id: i64) -> Result<Option<Attachment>> {
+        let attachment = sqlx::query_as::<_, Attachment>(&format!(
+            "SELECT {} FROM ticket_attachments WHERE id = ?1 AND ticket_id = ?2",
+            ATTACHMENT_COLUMNS
+        ))
+        .bind(id)
+        .bind(ticket_id)
+        .fetch_optional(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to get attachment {}: {:?}", id, e))?;
+
+        Ok(attachment)
+    }
+
+    /// Attachments of a ticket, oldest first
+    pub async fn list_for_ticket(pool: &DbPool, ticket_id: &str) -> Result<Vec<Attachment>> {
+        let attachments = sqlx::query_as::<_, Attachment>(&format!(
+            "SELECT {} FROM ticket_attachments WHERE ticket_id = ?1 ORDER BY id",
+            ATTACHMENT_COLUMNS
+        ))
+        .bind(ticket_id)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| {
+            warn!(
+                "Failed to list attachments of ticket {}: {:?}",
+                ticket_id, e
+            )
+        })?;
+
+        Ok(attachments)
+    }
+
+    pub async fn delete(pool: &DbPool, ticket_id: &str, id: i64) -> Result<Option<Attachment>> {
+        let attachment = sqlx::query_as::<_, Attachment>(&format!(
+            "DELETE FROM ticket_attachments WHERE id = ?1 AND ticket_id = ?2 RETURNING {}",
+            ATTACHMENT_COLUMNS
+        ))
+        .bind(id)
+        .bind(ticket_id)
+        .fetch_returned(pool)
+        .await
+        .inspect_err(|e| error!("Failed to delete attachment {}: {:?}", id, e))?;
+
+        Ok(attachment)
+    }
+}
@@ -0,0 +1,221 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tracing::error;
+use utoipa::{IntoParams, ToSchema};
+
+use super::{ticket_timeline::parse_instant, DbPool};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub actor: String,
+    pub session_id: Option<String>,
+    pub ticket_id: Option<String>,
+    pub method: String,
+    pub params_digest: Option<String>,
+    pub outcome: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    pub ticket_id: Option<String>,
+    pub method: Option<String>,
+    /// Start (inclusive): RFC 3339, `YYYY-MM-DD HH:MM:SS` (UTC) or a date (its start)
+    pub from: Option<String>,
+    /// End (inclusive), in the same formats as `from`
+    pub to: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl AuditQuery {
+    /// The query with `from` and `to` in SQLite's timestamp format, so they compare with
+    /// `created_at`; the offending value when one does not parse
+    pub fn normalized(mut self) -> std::result::Result<AuditQuery, String> {
+        for bound in [&mut self.from, &mut self.to] {
+            if let Some(value) = bound.as_deref() {
+                *bound = Some(parse_instant(value).ok_or_else(|| value.to_string())?);
+            }
+        }
+        Ok(self)
+    }
+}
+
+impl AuditEntry {
+    pub async fn create(
+        pool: &DbPool,
+        actor: &str,
+        session_id: Option<&str>,
+        ticket_id: Option<&str>,
+        method: &str,
+        params_digest: Option<&str>,
+        outcome: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log (actor, session_id, ticket_id, method, params_digest, outcome)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        "#,
+        )
+        .bind(actor)
+        .bind(session_id)
+        .bind(ticket_id)
+        .bind(method)
+        .bind(params_digest)
+        .bind(outcome)
+        .execute(pool)
+        .await
+        .inspect_err(|e| error!("Failed to write audit entry for '{}': {:?}", method, e))?;
+
+        Ok(())
+    }
+
+    /// Entries matching `query`, newest first; `from` and `to` must already be in SQLite's
+    /// timestamp format (see `AuditQuery::normalized`)
+    pub async fn list(pool: &DbPool, query: &AuditQuery) -> Result<Vec<AuditEntry>> {
+        let mut builder = sqlx::QueryBuilder::new(
+            "SELECT id, actor, session_id, ticket_id, method, params_digest, outcome, created_at FROM audit_log WHERE 1=1",
+        );
+
+        if let Some(actor) = &query.actor {
+            builder.push(" AND actor = ").push_bind(actor);
+        }
+        if let Some(ticket_id) = &query.ticket_id {
+            builder.push(" AND ticket_id = ").push_bind(ticket_id);
+        }
+        if let Some(method) = &query.method {
+            builder.push(" AND method = ").push_bind(method);
+        }
+        if let Some(from) = &query.from {
+            builder.push(" AND created_at >= ").push_bind(from);
+        }
+        if let Some(to) = &query.to {
+            builder.push(" AND created_at <= ").push_bind(to);
+        }
+
+        builder
+            .push(" ORDER BY created_at DESC, id DESC LIMIT ")
+            .push_bind(query.limit.unwrap_or(100).clamp(1, 1000))
+            .push(" OFFSET ")
+            .push_bind(query.offset.unwrap_or(0).max(0));
+
+        let entries = builder
+            .build_query_as::<AuditEntry>()
+            .fetch_all(pool)
+            .await?;
+
+        Ok(entries)
+    }
+
+    /// Delete entries older than the given number of days, returning how many were removed
+    pub async fn purge_older_than(pool: &DbPool, days: u64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM audit_log WHERE created_at < datetime('now', ?1)")
+            .bind(format!("-{} days", days))
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{audit::params_digest, test_support::memory_pool};
+    use serde_json::json;
+
+    async fn backdate(pool: &DbPool, method: &str, created_at: &str) {
+        sqlx::query("UPDATE audit_log SET created_at = ?2 WHERE method = ?1")
+            .bind(method)
+            .bind(created_at)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_entries_are_filtered_by_normalized_range_and_purged() {
+        let pool = memory_pool().await.unwrap();
+        let digest = params_digest(&json!({"ticket_id": "T-1", "stage": "review"}));
+        assert_eq!(
+            digest,
+            params_digest(&json!({"stage": "review", "ticket_id": "T-1"}))
+        );
+        assert_eq!(digest.len(), 64);
+        for (actor, method) in [
+            ("coordinator", "create_ticket"),
+            ("w-1", "add_ticket_comment"),
+            ("w-1", "close_ticket"),
+        ] {
+            let ticket_id = (method == "close_ticket").then_some("T-1");
+            AuditEntry::create(
+                &pool,
+                actor,
+                Some("s-1"),
+                ticket_id,
+                method,
+                Some(&digest),
+                "ok",
+            )
+            .await
+            .unwrap();
+        }
+        backdate(&pool, "create_ticket", "2026-01-01 09:00:00").await;
+        backdate(&pool, "add_ticket_comment", "2026-01-01 12:00:00").await;
+
+        let list = |query: AuditQuery| {
+            let pool = pool.clone();
+            async move {
+                let entries = AuditEntry::list(&pool, &query.normalized().unwrap())
+                    .await
+                    .unwrap();
+                entries.into_iter().map(|e| e.method).collect::<Vec<_>>()
+            }
+        };
+        // An RFC 3339 bound with an offset compares as UTC, not as text
+        let morning = AuditQuery {
+            from: Some("2026-01-01T10:00:00+02:00".to_string()),
+            to: Some("2026-01-01 11:00".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(list(morning).await, ["create_ticket"]);
+        let that_day = AuditQuery {
+            from: Some("2026-01-01".to_string()),
+            to: Some("2026-01-01T23:59:59Z".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            list(that_day).await,
+            ["add_ticket_comment", "create_ticket"]
+        );
+        let by_worker = AuditQuery {
+            actor: Some("w-1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            list(by_worker).await,
+            ["close_ticket", "add_ticket_comment"]
+        );
+        let by_ticket = AuditQuery {
+            ticket_id: Some("T-1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(list(by_ticket).await, ["close_ticket"]);
+        let garbage = AuditQuery {
+            to: Some("yesterday".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(garbage.normalized().unwrap_err(), "yesterday");
+
+        // Only entries past the retention window go
+        assert_eq!(AuditEntry::purge_older_than(&pool, 30).await.unwrap(), 2);
+        assert_eq!(AuditEntry::purge_older_than(&pool, 30).await.unwrap(), 0);
+        assert_eq!(list(AuditQuery::default()).await, ["close_ticket"]);
+
+        pool.close().await;
+    }
+}
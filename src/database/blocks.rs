@@ -0,0 +1,735 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tracing::error;
+use utoipa::ToSchema;
+
+use super::{outbox::OutboxEvent, returning::FetchReturning, tickets::TicketState, DbPool};
+use crate::{crypto, events::EventPayload};
+
+/// Kinds of reminders recorded in a block's nudge history
+pub const NUDGE: &str = "nudge";
+pub const ESCALATION: &str = "escalation";
+
+const BLOCK_COLUMNS: &str = "id, ticket_id, reason, blocking_ticket_id, responsible_agent, \
+     expected_unblock_at, blocked_by, blocked_at, nudged_at, escalated_at, unblocked_at, \
+     unblocked_by";
+
+/// What a blocked ticket waits on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Blocker {
+    /// Another ticket; the block is lifted when it closes
+    Ticket(String),
+    /// An agent expected to unblock the ticket by a date (SQLite datetime, UTC)
+    Agent {
+        agent_id: String,
+        expected_unblock_at: String,
+    },
+}
+
+impl Blocker {
+    /// Read the blocker from tool or API arguments: a blocking ticket, or a responsible
+    /// agent with an RFC 3339 expected unblock date
+    pub fn from_args(
+        blocking_ticket_id: Option<String>,
+        responsible_agent: Option<String>,
+        expected_unblock_at: Option<String>,
+    ) -> std::result::Result<Blocker, String> {
+        match (blocking_ticket_id, responsible_agent, expected_unblock_at) {
+            (Some(ticket_id), None, None) => Ok(Blocker::Ticket(ticket_id)),
+            (None, Some(agent_id), Some(expected)) => {
+                // Stored in SQLite datetime format (UTC) so it compares with datetime('now')
+                let expected_unblock_at = chrono::DateTime::parse_from_rfc3339(&expected)
+                    .map_err(|e| {
+                        format!(
+                            "Invalid expected_unblock_at '{}': expected RFC 3339 timestamp ({})",
+                            expected, e
+                        )
+                    })?
+                    .with_timezone(&chrono::Utc)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string();
+                Ok(Blocker::Agent {
+                    agent_id,
+                    expected_unblock_at,
+                })
+            }
+            (None, None, None) => Err(
+                "A block needs blocking_ticket_id, or responsible_agent and expected_unblock_at"
+                    .to_string(),
+            ),
+            (Some(_), _, _) => Err(
+                "Give either blocking_ticket_id, or responsible_agent and expected_unblock_at, not both"
+                    .to_string(),
+            ),
+            (None, _, _) => Err(
+                "responsible_agent and expected_unblock_at must be given together".to_string(),
+            ),
+        }
+    }
+}
+
+/// A ticket's block, in force until `unblocked_at` is set
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct TicketBlock {
+    pub id: i64,
+    pub ticket_id: String,
+    pub reason: String,
+    /// Ticket this one waits on
+    pub blocking_ticket_id: Option<String>,
+    /// Agent expected to unblock the ticket, with `expected_unblock_at`
+    pub responsible_agent: Option<String>,
+    pub expected_unblock_at: Option<String>,
+    pub blocked_by: String,
+    pub blocked_at: String,
+    /// When the responsible agent was reminded, once the expected date passed
+    pub nudged_at: Option<String>,
+    /// When the coordinator was told the block is still in force after the reminder
+    pub escalated_at: Option<String>,
+    pub unblocked_at: Option<String>,
+    pub unblocked_by: Option<String>,
+}
+
+/// Reminder about a block, sent to the responsible agent or the coordinator
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct BlockNudge {
+    /// `nudge` or `escalation`
+    pub kind: String,
+    pub target: String,
+    /// Agent request carrying a nudge
+    pub correlation_id: Option<String>,
+    pub sent_at: String,
+}
+
+/// Ticket with a block in force, as listed for the dashboard
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct BlockedTicket {
+    pub project_id: String,
+    pub short_id: String,
+    pub title: String,
+    /// Minutes since the ticket was blocked
+    pub age_minutes: i64,
+    #[sqlx(flatten)]
+    pub block: TicketBlock,
+    /// Oldest first
+    #[sqlx(skip)]
+    pub nudges: Vec<BlockNudge>,
+}
+
+/// Block lifted by `unblock`, with how long it was in force
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LiftedBlock {
+    pub project_id: String,
+    pub block: TicketBlock,
+    pub blocked_minutes: i64,
+}
+
+/// Blocks in force and how long lifted ones lasted
+#[derive(Debug, Clone, Default, Serialize, FromRow, ToSchema)]
+pub struct BlockStats {
+    pub blocked: i64,
+    /// Blocks in force past their expected unblock date
+    pub overdue: i64,
+    pub unblocked: i64,
+    /// Mean minutes lifted blocks were in force
+    pub mean_blocked_minutes: Option<f64>,
+    pub max_blocked_minutes: Option<i64>,
+}
+
+/// Result of blocking a ticket
+#[derive(Debug, Clone)]
+pub enum BlockOutcome {
+    Blocked(Box<TicketBlock>),
+    /// The block is not allowed; the reason is meant for the caller
+    Rejected(String),
+}
+
+impl TicketBlock {
+    /// Block in force on `ticket_id`, if any
+    pub async fn active(pool: &DbPool, ticket_id: &str) -> Result<Option<TicketBlock>> {
+        Ok(sqlx::query_as::<_, TicketBlock>(&format!(
+            "SELECT {} FROM ticket_blocks WHERE ticket_id = ?1 AND unblocked_at IS NULL",
+            BLOCK_COLUMNS
+        ))
+        .bind(ticket_id)
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Tickets with a block in force waiting on `blocking_ticket_id`
+    pub async fn waiting_on(pool: &DbPool, blocking_ticket_id: &str) -> Result<Vec<String>> {
+        Ok(sqlx::query_scalar(
+            "SELECT ticket_id FROM ticket_blocks
+             WHERE blocking_ticket_id = ?1 AND unblocked_at IS NULL ORDER BY id",
+        )
+        .bind(blocking_ticket_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Block `ticket_id` on `blocker`: record the block, put the ticket on hold, release its
+    /// worker, note the reason on it and queue `ticket_updated`
+    pub async fn block(
+        pool: &DbPool,
+        ticket_id: &str,
+        reason: &str,
+        blocker: &Blocker,
+        blocked_by: &str,
+    ) -> Result<BlockOutcome> {
+        let reject = |reason: String| Ok(BlockOutcome::Rejected(reason));
+        if reason.trim().is_empty() {
+            return reject("A block needs a reason".to_string());
+        }
+        let Some(state) = Self::state_of(pool, ticket_id).await? else {
+            return reject(format!("Ticket '{}' not found", ticket_id));
+        };
+        if state == TicketState::Closed.as_sql_value() {
+            return reject(format!("Ticket '{}' is closed", ticket_id));
+        }
+        if Self::active(pool, ticket_id).await?.is_some() {
+            return reject(format!("Ticket '{}' is already blocked", ticket_id));
+        }
+        let (blocking_ticket_id, responsible_agent, expected_unblock_at) = match blocker {
+            Blocker::Ticket(blocking) => {
+                if blocking == ticket_id {
+                    return reject("A ticket cannot block itself".to_string());
+                }
+                match Self::state_of(pool, blocking).await? {
+                    None => return reject(format!("Blocking ticket '{}' not found", blocking)),
+                    Some(state) if state == TicketState::Closed.as_sql_value() => {
+                        return reject(format!("Blocking ticket '{}' is already closed", blocking))
+                    }
+                    Some(_) => {}
+                }
+                if Self::blocks_chain_reaches(pool, blocking, ticket_id).await? {
+                    return reject(format!(
+                        "Ticket '{}' already waits on '{}'; blocking would deadlock them",
+                        blocking, ticket_id
+                    ));
+                }
+                (Some(blocking.as_str()), None, None)
+            }
+            Blocker::Agent {
+                agent_id,
+                expected_unblock_at,
+            } => (
+                None,
+                Some(agent_id.as_str()),
+                Some(expected_unblock_at.as_str()),
+            ),
+        };
+
+        let mut tx = pool.begin().await?;
+        let block = sqlx::query_as::<_, TicketBlock>(&format!(
+            r#"
+            INSERT INTO ticket_blocks (
+                ticket_id, reason, blocking_ticket_id, responsible_agent, expected_unblock_at,
+                blocked_by
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            RETURNING {}
+        "#,
+            BLOCK_COLUMNS
+        ))
+        .bind(ticket_id)
+        .bind(reason)
+        .bind(blocking_ticket_id)
+        .bind(responsible_agent)
+        .bind(expected_unblock_at)
+        .bind(blocked_by)
+        .fetch_returned_one(&mut *tx)
+        .await
+        .inspect_err(|e| error!("Failed to block ticket {}: {:?}", ticket_id, e))?;
+
+        let project_id: String = sqlx::query_scalar(
+            r#"
+            UPDATE tickets
+            SET state = ?1, processing_worker_id = NULL, updated_at = datetime('now')
+            WHERE ticket_id = ?2
+            RETURNING project_id
+        "#,
+        )
+        .bind(TicketState::OnHold.as_sql_value())
+        .bind(ticket_id)
+        .fetch_returned_one(&mut *tx)
+        .await?;
+
+        let waiting_on = match blocker {
+            Blocker::Ticket(blocking) => format!("ticket {}", blocking),
+            Blocker::Agent {
+                agent_id,
+                expected_unblock_at,
+            } => format!("{} (expected by {} UTC)", agent_id, expected_unblock_at),
+        };
+        let note = format!("Blocked by {} on {}: {}", blocked_by, waiting_on, reason);
+        Self::comment(&mut tx, ticket_id, &note).await?;
+        OutboxEvent::write(
+            &mut tx,
+            ticket_id,
+            &EventPayload::ticket_updated(ticket_id, &project_id, "blocked"),
+            None,
+            Some(reason),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(BlockOutcome::Blocked(Box::new(block)))
+    }
+
+    /// Lift the block in force on `ticket_id`, reopening the ticket if it is still on hold,
+    /// and queue `ticket_unblocked`; None when the ticket is not blocked
+    pub async fn unblock(
+        pool: &DbPool,
+        ticket_id: &str,
+        unblocked_by: &str,
+        note: Option<&str>,
+    ) -> Result<Option<LiftedBlock>> {
+        let mut tx = pool.begin().await?;
+        let Some(block) = sqlx::query_as::<_, TicketBlock>(&format!(
+            r#"
+            UPDATE ticket_blocks
+            SET unblocked_at = datetime('now'), unblocked_by = ?2
+            WHERE ticket_id = ?1 AND unblocked_at IS NULL
+            RETURNING {}
+        "#,
+            BLOCK_COLUMNS
+        ))
+        .bind(ticket_id)
+        .bind(unblocked_by)
+        .fetch_all(&mut *tx)
+        .await
+        .inspect_err(|e| error!("Failed to unblock ticket {}: {:?}", ticket_id, e))?
+        .into_iter()
+        .next() else {
+            return Ok(None);
+        };
+
+        let project_id: String = sqlx::query_scalar(
+            r#"
+            UPDATE tickets
+            SET state = CASE WHEN state = ?2 THEN ?3 ELSE state END,
+                updated_at = datetime('now')
+            WHERE ticket_id = ?1
+            RETURNING project_id
+        "#,
+        )
+        .bind(ticket_id)
+        .bind(TicketState::OnHold.as_sql_value())
+        .bind(TicketState::Open.as_sql_value())
+        .fetch_returned_one(&mut *tx)
+        .await?;
+
+        let blocked_minutes = blocked_minutes(&block);
+        let mut comment = format!(
+            "Unblocked by {} after {} minutes blocked",
+            unblocked_by, blocked_minutes
+        );
+        OutboxEvent::write(
+            &mut tx,
+            ticket_id,
+            &EventPayload::ticket_unblocked(ticket_id, &project_id),
+            None,
+            Some(&format!(
+                "Ticket unblocked by {} after {} minutes blocked",
+                unblocked_by, blocked_minutes
+            )),
+        )
+        .await?;
+        if let Some(note) = note.filter(|n| !n.trim().is_empty()) {
+            comment.push_str(": ");
+            comment.push_str(note);
+        }
+        Self::comment(&mut tx, ticket_id, &comment).await?;
+        tx.commit().await?;
+        Ok(Some(LiftedBlock {
+            project_id,
+            block,
+            blocked_minutes,
+        }))
+    }
+
+    /// Blocks past their expected unblock date whose responsible agent was not reminded
+    /// yet; each block is returned once
+    pub async fn mark_due_nudges(pool: &DbPool) -> Result<Vec<TicketBlock>> {
+        Ok(sqlx::query_as::<_, TicketBlock>(&format!(
+            r#"
+            UPDATE ticket_blocks
+            SET nudged_at = datetime('now')
+            WHERE unblocked_at IS NULL AND nudged_at IS NULL
+              AND responsible_agent IS NOT NULL
+              AND expected_unblock_at <= datetime('now')
+            RETURNING {}
+        "#,
+            BLOCK_COLUMNS
+        ))
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| error!("Failed to mark blocks to nudge: {:?}", e))?)
+    }
+
+    /// Blocks still in force `delay_mins` after their reminder that were not escalated
+    /// yet, recording the escalation to the coordinator and queueing
+    /// `ticket_block_escalated` with it; each block is returned once
+    pub async fn mark_due_escalations(pool: &DbPool, delay_mins: u64) -> Result<Vec<TicketBlock>> {
+        let mut tx = pool.begin().await?;
+        let blocks = sqlx::query_as::<_, TicketBlock>(&format!(
+            r#"
+            UPDATE ticket_blocks
+            SET escalated_at = datetime('now')
+            WHERE unblocked_at IS NULL AND escalated_at IS NULL
+              AND nudged_at <= datetime('now', ?1)
+            RETURNING {}
+        "#,
+            BLOCK_COLUMNS
+        ))
+        .bind(format!("-{} minutes", delay_mins))
+        .fetch_all(&mut *tx)
+        .await
+        .inspect_err(|e| error!("Failed to mark blocks to escalate: {:?}", e))?;
+
+        for block in &blocks {
+            sqlx::query(
+                "INSERT INTO ticket_block_nudges (block_id, kind, target) VALUES (?1, ?2, 'coordinator')",
+            )
+            .bind(block.id)
+            .bind(ESCALATION)
+            .execute(&mut *tx)
+            .await?;
+            let project_id: String =
+                sqlx::query_scalar("SELECT project_id FROM tickets WHERE ticket_id = ?1")
+                    .bind(&block.ticket_id)
+                    .fetch_one(&mut *tx)
+                    .await?;
+            OutboxEvent::write(
+                &mut tx,
+                &block.ticket_id,
+                &EventPayload::ticket_block_escalated(&block.ticket_id, &project_id),
+                None,
+                Some(&block.escalation_message()),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(blocks)
+    }
+
+    /// What the coordinator is told about a block still in force after its reminder
+    pub fn escalation_message(&self) -> String {
+        format!(
+            "Ticket still blocked on {} after a reminder (expected by {} UTC): {}",
+            self.responsible_agent.as_deref().unwrap_or("its blocker"),
+            self.expected_unblock_at.as_deref().unwrap_or("-"),
+            self.reason
+        )
+    }
+
+    pub async fn record_nudge(
+        pool: &DbPool,
+        block_id: i64,
+        kind: &str,
+        target: &str,
+        correlation_id: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO ticket_block_nudges (block_id, kind, target, correlation_id)
+             VALUES (?1, ?2, ?3, ?4)",
+        )
+        .bind(block_id)
+        .bind(kind)
+        .bind(target)
+        .bind(correlation_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn state_of(pool: &DbPool, ticket_id: &str) -> Result<Option<String>> {
+        Ok(
+            sqlx::query_scalar("SELECT state FROM tickets WHERE ticket_id = ?1")
+                .bind(ticket_id)
+                .fetch_optional(pool)
+                .await?,
+        )
+    }
+
+    /// Whether following blocks in force from `from` through the tickets they wait on
+    /// reaches `to`
+    async fn blocks_chain_reaches(pool: &DbPool, from: &str, to: &str) -> Result<bool> {
+        let reaches: bool = sqlx::query_scalar(
+            r#"
+            WITH RECURSIVE chain(ticket_id) AS (
+                SELECT ?1
+                UNION
+                SELECT b.blocking_ticket_id FROM ticket_blocks b
+                JOIN chain ON b.ticket_id = chain.ticket_id
+                WHERE b.unblocked_at IS NULL AND b.blocking_ticket_id IS NOT NULL
+            )
+            SELECT EXISTS (SELECT 1 FROM chain WHERE ticket_id = ?2)
+        "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_one(pool)
+        .await?;
+        Ok(reaches)
+    }
+
+    async fn comment(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        ticket_id: &str,
+        content: &str,
+    ) -> Result<()> {
+        let (content, key_id) = crypto::seal(content)?;
+        sqlx::query(
+            r#"
+            INSERT INTO comments (ticket_id, worker_type, worker_id, stage_number, content, content_key_id)
+            VALUES (?1, 'system', 'system', 999, ?2, ?3)
+        "#,
+        )
+        .bind(ticket_id)
+        .bind(&content)
+        .bind(key_id)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+}
+
+fn blocked_minutes(block: &TicketBlock) -> i64 {
+    let parse = |at: &str| chrono::NaiveDateTime::parse_from_str(at, "%Y-%m-%d %H:%M:%S").ok();
+    match (
+        parse(&block.blocked_at),
+        block.unblocked_at.as_deref().and_then(parse),
+    ) {
+        (Some(from), Some(to)) => (to - from).num_minutes().max(0),
+        _ => 0,
+    }
+}
+
+/// Tickets with a block in force with their reminders, longest blocked first
+pub async fn list_blocked(pool: &DbPool, project_id: Option<&str>) -> Result<Vec<BlockedTicket>> {
+    let mut tickets = sqlx::query_as::<_, BlockedTicket>(
+        r#"
+        SELECT t.project_id, t.short_id, t.title,
+               CAST((julianday('now') - julianday(b.blocked_at)) * 1440 AS INTEGER) AS age_minutes,
+               b.*
+        FROM ticket_blocks b JOIN tickets t ON t.ticket_id = b.ticket_id
+        WHERE b.unblocked_at IS NULL AND (?1 IS NULL OR t.project_id = ?1)
+        ORDER BY b.blocked_at, b.id
+    "#,
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await?;
+
+    for ticket in &mut tickets {
+        ticket.nudges = sqlx::query_as::<_, BlockNudge>(
+            "SELECT kind, target, correlation_id, sent_at FROM ticket_block_nudges
+             WHERE block_id = ?1 ORDER BY id",
+        )
+        .bind(ticket.block.id)
+        .fetch_all(pool)
+        .await?;
+    }
+    Ok(tickets)
+}
+
+/// Blocks in force and lifted, optionally limited to one project
+pub async fn stats(pool: &DbPool, project_id: Option<&str>) -> Result<BlockStats> {
+    Ok(sqlx::query_as::<_, BlockStats>(
+        r#"
+        SELECT COALESCE(SUM(b.unblocked_at IS NULL), 0) AS blocked,
+               COALESCE(SUM(b.unblocked_at IS NULL
+                            AND b.expected_unblock_at <= datetime('now')), 0) AS overdue,
+               COALESCE(SUM(b.unblocked_at IS NOT NULL), 0) AS unblocked,
+               AVG(CASE WHEN b.unblocked_at IS NOT NULL
+                        THEN (julianday(b.unblocked_at) - julianday(b.blocked_at)) * 1440
+                   END) AS mean_blocked_minutes,
+               CAST(MAX(CASE WHEN b.unblocked_at IS NOT NULL
+                             THEN (julianday(b.unblocked_at) - julianday(b.blocked_at)) * 1440
+                        END) AS INTEGER) AS max_blocked_minutes
+        FROM ticket_blocks b JOIN tickets t ON t.ticket_id = b.ticket_id
+        WHERE ?1 IS NULL OR t.project_id = ?1
+    "#,
+    )
+    .bind(project_id)
+    .fetch_one(pool)
+    .await
+    .inspect_err(|e| error!("Failed to load block stats: {:?}", e))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tickets::Ticket;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    fn rejected(outcome: BlockOutcome) -> String {
+        match outcome {
+            BlockOutcome::Rejected(reason) => reason,
+            BlockOutcome::Blocked(block) => panic!("unexpected block {:?}", block),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_block_nudge_escalate_and_unblock() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("blocks").create().await.unwrap();
+        let api = fx.ticket(&project, "API").create().await.unwrap();
+        let ui = fx.ticket(&project, "UI").create().await.unwrap();
+        let docs = fx.ticket(&project, "Docs").create().await.unwrap();
+
+        // A block names what it waits on
+        assert!(Blocker::from_args(None, Some("worker-1".to_string()), None).is_err());
+        assert!(Blocker::from_args(
+            Some(api.ticket_id.clone()),
+            Some("worker-1".to_string()),
+            Some("2026-01-01T00:00:00Z".to_string())
+        )
+        .is_err());
+        let overdue = Blocker::from_args(
+            None,
+            Some("worker-1".to_string()),
+            Some("2026-01-01T02:00:00+02:00".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            overdue,
+            Blocker::Agent {
+                agent_id: "worker-1".to_string(),
+                expected_unblock_at: "2026-01-01 00:00:00".to_string()
+            }
+        );
+
+        let on_api = Blocker::Ticket(api.ticket_id.clone());
+        let BlockOutcome::Blocked(block) =
+            TicketBlock::block(&pool, &ui.ticket_id, "Needs the API", &on_api, "planner")
+                .await
+                .unwrap()
+        else {
+            panic!("ticket not blocked");
+        };
+        assert_eq!(
+            block.blocking_ticket_id.as_deref(),
+            Some(api.ticket_id.as_str())
+        );
+        let held = Ticket::get_by_id(&pool, &ui.ticket_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(held.ticket.state, "on_hold");
+        assert_eq!(held.block.unwrap().id, block.id);
+        assert_eq!(
+            TicketBlock::waiting_on(&pool, &api.ticket_id)
+                .await
+                .unwrap(),
+            [ui.ticket_id.as_str()]
+        );
+
+        // Blocking again, on itself or in a circle is refused
+        assert!(rejected(
+            TicketBlock::block(&pool, &ui.ticket_id, "Again", &on_api, "planner")
+                .await
+                .unwrap()
+        )
+        .contains("already blocked"));
+        let on_ui = Blocker::Ticket(ui.ticket_id.clone());
+        assert!(rejected(
+            TicketBlock::block(&pool, &api.ticket_id, "Circle", &on_ui, "planner")
+                .await
+                .unwrap()
+        )
+        .contains("deadlock"));
+
+        // Blocks on an agent are reminded once past their date, then escalated
+        assert!(matches!(
+            TicketBlock::block(
+                &pool,
+                &docs.ticket_id,
+                "Waiting on review",
+                &overdue,
+                "writer"
+            )
+            .await
+            .unwrap(),
+            BlockOutcome::Blocked(_)
+        ));
+        let nudged = TicketBlock::mark_due_nudges(&pool).await.unwrap();
+        assert_eq!(nudged.len(), 1);
+        assert_eq!(nudged[0].ticket_id, docs.ticket_id);
+        assert!(TicketBlock::mark_due_nudges(&pool)
+            .await
+            .unwrap()
+            .is_empty());
+        TicketBlock::record_nudge(&pool, nudged[0].id, NUDGE, "worker-1", Some("corr-1"))
+            .await
+            .unwrap();
+        assert!(TicketBlock::mark_due_escalations(&pool, 60)
+            .await
+            .unwrap()
+            .is_empty());
+        sqlx::query("UPDATE ticket_blocks SET nudged_at = datetime('now', '-2 hours') WHERE nudged_at IS NOT NULL")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            TicketBlock::mark_due_escalations(&pool, 60)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let listed = list_blocked(&pool, Some("blocks")).await.unwrap();
+        assert_eq!(listed.len(), 2);
+        let docs_listed = listed
+            .iter()
+            .find(|t| t.block.ticket_id == docs.ticket_id)
+            .unwrap();
+        let kinds: Vec<_> = docs_listed.nudges.iter().map(|n| n.kind.as_str()).collect();
+        assert_eq!(kinds, [NUDGE, ESCALATION]);
+        assert_eq!(
+            docs_listed.nudges[0].correlation_id.as_deref(),
+            Some("corr-1")
+        );
+        let counts = stats(&pool, Some("blocks")).await.unwrap();
+        assert_eq!(
+            (counts.blocked, counts.overdue, counts.unblocked),
+            (2, 1, 0)
+        );
+        assert_eq!(counts.mean_blocked_minutes, None);
+
+        // Unblocking reopens the ticket and is counted with its duration
+        sqlx::query("UPDATE ticket_blocks SET blocked_at = datetime('now', '-90 minutes') WHERE ticket_id = ?1")
+            .bind(&ui.ticket_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let lifted = TicketBlock::unblock(&pool, &ui.ticket_id, "reviewer", Some("API merged"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lifted.project_id, "blocks");
+        assert_eq!(lifted.block.unblocked_by.as_deref(), Some("reviewer"));
+        assert!((89..=91).contains(&lifted.blocked_minutes));
+        let reopened = Ticket::get_by_id(&pool, &ui.ticket_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reopened.ticket.state, "open");
+        assert!(reopened.block.is_none());
+        assert!(reopened.comments.iter().any(
+            |c| c.content.contains("Unblocked by reviewer") && c.content.contains("API merged")
+        ));
+        assert!(TicketBlock::unblock(&pool, &ui.ticket_id, "reviewer", None)
+            .await
+            .unwrap()
+            .is_none());
+
+        let counts = stats(&pool, None).await.unwrap();
+        assert_eq!((counts.blocked, counts.unblocked), (1, 1));
+        assert!(counts.mean_blocked_minutes.unwrap() >= 89.0);
+        assert!(counts.max_blocked_minutes.unwrap() >= 89);
+        pool.close().await;
+    }
+}
@@ -0,0 +1,174 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection};
+use std::fmt;
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use super::{relations::ChildProgress, timing::timed, DbPool};
+
+/// Board column a ticket is displayed in, derived from its state and processing status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BoardColumn {
+    Open,
+    InProgress,
+    Blocked,
+    OnHold,
+    Closed,
+}
+
+impl BoardColumn {
+    pub fn all() -> [BoardColumn; 5] {
+        [
+            BoardColumn::Open,
+            BoardColumn::InProgress,
+            BoardColumn::Blocked,
+            BoardColumn::OnHold,
+            BoardColumn::Closed,
+        ]
+    }
+
+    /// Columns derived from runtime status cannot be targeted by a manual move
+    pub fn is_derived(&self) -> bool {
+        matches!(self, BoardColumn::InProgress | BoardColumn::Blocked)
+    }
+}
+
+impl fmt::Display for BoardColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardColumn::Open => write!(f, "open"),
+            BoardColumn::InProgress => write!(f, "in_progress"),
+            BoardColumn::Blocked => write!(f, "blocked"),
+            BoardColumn::OnHold => write!(f, "on_hold"),
+            BoardColumn::Closed => write!(f, "closed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct BoardCard {
+    pub ticket_id: String,
+    pub short_id: String,
+    pub title: String,
+    pub priority: String,
+    /// `priority` raised by the escalation rules
+    pub effective_priority: String,
+    /// Why `effective_priority` is above `priority`, JSON array; None when it is not
+    pub escalation_reasons: Option<String>,
+    pub current_stage: String,
+    pub state: String,
+    pub dependency_status: String,
+    pub processing_worker_id: Option<String>,
+    pub board_position: Option<i64>,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Closed children out of all children, for parent tickets
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<ChildProgress>,
+}
+
+impl BoardCard {
+    pub fn column(&self) -> BoardColumn {
+        match self.state.as_str() {
+            "closed" => BoardColumn::Closed,
+            "on_hold" => BoardColumn::OnHold,
+            _ if self.dependency_status == "blocked" => BoardColumn::Blocked,
+            _ if self.processing_worker_id.is_some() => BoardColumn::InProgress,
+            _ => BoardColumn::Open,
+        }
+    }
+
+    /// All cards for a project in board order (manual position first, then highest effective
+    /// priority, then newest)
+    pub async fn list_by_project(pool: &DbPool, project_id: &str) -> Result<Vec<BoardCard>> {
+        let cards = timed!(
+            pool,
+            "BoardCard::list_by_project",
+            sqlx::query_as::<_, BoardCard>(
+                r#"
+                SELECT ticket_id, short_id, title, priority, effective_priority, escalation_reasons,
+                       current_stage, state, dependency_status, processing_worker_id, board_position,
+                       created_at, updated_at
+                FROM tickets
+                WHERE project_id = ?1
+                ORDER BY board_position IS NULL, board_position ASC,
+                         CASE effective_priority
+                             WHEN 'urgent' THEN 1
+                             WHEN 'high' THEN 2
+                             WHEN 'medium' THEN 3
+                             WHEN 'low' THEN 4
+                             ELSE 5
+                         END,
+                         created_at DESC
+            "#,
+            )
+            .bind(project_id),
+            fetch_all
+        )
+        .inspect_err(|e| warn!("Failed to load board for project {}: {:?}", project_id, e))?;
+
+        Ok(cards)
+    }
+
+    /// Persist the given ordering of tickets as consecutive board positions, within the
+    /// caller's transaction
+    pub async fn save_order(
+        conn: &mut SqliteConnection,
+        ordered_ticket_ids: &[String],
+    ) -> Result<()> {
+        for (position, ticket_id) in ordered_ticket_ids.iter().enumerate() {
+            sqlx::query("UPDATE tickets SET board_position = ?1 WHERE ticket_id = ?2")
+                .bind(position as i64)
+                .bind(ticket_id)
+                .execute(&mut *conn)
+                .await
+                .inspect_err(|e| error!("Failed to save board order: {:?}", e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(state: &str, dependency_status: &str, worker: Option<&str>) -> BoardCard {
+        BoardCard {
+            ticket_id: "T-1".to_string(),
+            short_id: "T-1".to_string(),
+            title: "t".to_string(),
+            priority: "medium".to_string(),
+            effective_priority: "medium".to_string(),
+            escalation_reasons: None,
+            current_stage: "planning".to_string(),
+            state: state.to_string(),
+            dependency_status: dependency_status.to_string(),
+            processing_worker_id: worker.map(|w| w.to_string()),
+            board_position: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+            progress: None,
+        }
+    }
+
+    #[test]
+    fn test_column_classification() {
+        assert_eq!(card("open", "ready", None).column(), BoardColumn::Open);
+        assert_eq!(
+            card("open", "ready", Some("w")).column(),
+            BoardColumn::InProgress
+        );
+        assert_eq!(
+            card("open", "blocked", Some("w")).column(),
+            BoardColumn::Blocked
+        );
+        assert_eq!(card("on_hold", "ready", None).column(), BoardColumn::OnHold);
+        assert_eq!(
+            card("closed", "blocked", None).column(),
+            BoardColumn::Closed
+        );
+    }
+}
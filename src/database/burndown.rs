@@ -0,0 +1,234 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use std::collections::{HashMap, HashSet};
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::DbPool;
+
+pub const DEFAULT_BURNDOWN_DAYS: i64 = 14;
+pub const MAX_BURNDOWN_DAYS: i64 = 365;
+
+/// Tickets of a project as they stood at the end of a day (UTC). On-hold tickets count
+/// as open.
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct BurndownDay {
+    pub date: String,
+    pub open_tickets: i64,
+    pub closed_tickets: i64,
+    pub open_estimate_minutes: i64,
+    pub closed_estimate_minutes: i64,
+    /// Open tickets without an estimate, which the open total leaves out
+    pub unestimated_open_tickets: i64,
+    /// Open tickets that had been closed before
+    pub reopened_open_tickets: i64,
+}
+
+/// Snapshot of a ticket recorded in ticket_history
+#[derive(Debug, Clone, FromRow)]
+pub struct HistoryEntry {
+    pub ticket_id: String,
+    pub state: String,
+    pub estimate_minutes: Option<i64>,
+    pub created_at: String,
+}
+
+/// Per-day series replayed from ticket_history, oldest day first and ending today
+pub async fn load(pool: &DbPool, project_id: &str, days: i64) -> Result<Vec<BurndownDay>> {
+    let days = days.clamp(1, MAX_BURNDOWN_DAYS);
+    let entries = sqlx::query_as::<_, HistoryEntry>(
+        r#"
+        SELECT ticket_id, state, estimate_minutes, created_at
+        FROM ticket_history
+        WHERE project_id = ?1
+        ORDER BY created_at ASC, id ASC
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await
+    .inspect_err(|e| warn!("Failed to load ticket history of '{}': {:?}", project_id, e))?;
+
+    let today = Utc::now().date_naive();
+    Ok(series(&entries, today - Duration::days(days - 1), today))
+}
+
+/// Replay `entries` (ordered by time) and record the totals after each day from `first`
+/// through `last`
+pub fn series(entries: &[HistoryEntry], first: NaiveDate, last: NaiveDate) -> Vec<BurndownDay> {
+    let mut tickets: HashMap<&str, &HistoryEntry> = HashMap::new();
+    let mut closed_before: HashSet<&str> = HashSet::new();
+    let mut pending = entries.iter().peekable();
+    let mut days = Vec::new();
+
+    for date in first.iter_days().take_while(|date| *date <= last) {
+        // SQLite timestamps sort after the bare date of their day and before the next one
+        let next_day = (date + Duration::days(1)).format("%Y-%m-%d").to_string();
+        while let Some(entry) = pending.next_if(|entry| entry.created_at < next_day) {
+            tickets.insert(&entry.ticket_id, entry);
+            if entry.state == "closed" {
+                closed_before.insert(&entry.ticket_id);
+            }
+        }
+
+        let mut day = BurndownDay {
+            date: date.format("%Y-%m-%d").to_string(),
+            ..Default::default()
+        };
+        for (ticket_id, ticket) in &tickets {
+            let estimate = ticket.estimate_minutes.unwrap_or(0);
+            if ticket.state == "closed" {
+                day.closed_tickets += 1;
+                day.closed_estimate_minutes += estimate;
+            } else {
+                day.open_tickets += 1;
+                day.open_estimate_minutes += estimate;
+                if ticket.estimate_minutes.is_none() {
+                    day.unestimated_open_tickets += 1;
+                }
+                if closed_before.contains(ticket_id) {
+                    day.reopened_open_tickets += 1;
+                }
+            }
+        }
+        days.push(day);
+    }
+    days
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tickets::Ticket;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    fn entry(ticket_id: &str, state: &str, estimate: Option<i64>, at: &str) -> HistoryEntry {
+        HistoryEntry {
+            ticket_id: ticket_id.to_string(),
+            state: state.to_string(),
+            estimate_minutes: estimate,
+            created_at: at.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_series_replays_history_per_day() {
+        let entries = [
+            entry("A", "open", Some(60), "2026-03-01 09:00:00"),
+            entry("B", "open", None, "2026-03-01 23:59:59"),
+            entry("B", "open", Some(30), "2026-03-02 10:00:00"),
+            entry("A", "closed", Some(60), "2026-03-03 00:00:00"),
+            entry("C", "on_hold", Some(15), "2026-03-03 12:00:00"),
+        ];
+        let day = |d: u32| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let days = series(&entries, day(1), day(4));
+
+        let totals: Vec<_> = days
+            .iter()
+            .map(|d| {
+                (
+                    d.date.as_str(),
+                    d.open_tickets,
+                    d.open_estimate_minutes,
+                    d.closed_tickets,
+                    d.closed_estimate_minutes,
+                    d.unestimated_open_tickets,
+                )
+            })
+            .collect();
+        assert_eq!(
+            totals,
+            [
+                ("2026-03-01", 2, 60, 0, 0, 1),
+                ("2026-03-02", 2, 90, 0, 0, 0),
+                ("2026-03-03", 2, 45, 1, 60, 0),
+                ("2026-03-04", 2, 45, 1, 60, 0),
+            ]
+        );
+        assert!(series(&entries, day(4), day(3)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_history_tracks_estimates_and_worker_time() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("burndown").create().await.unwrap();
+        let ticket = fx
+            .ticket(&project, "Estimated")
+            .estimate(120)
+            .create()
+            .await
+            .unwrap();
+        assert_eq!(ticket.estimate_minutes, Some(120));
+
+        assert!(Ticket::update_estimate(&pool, &ticket.ticket_id, Some(0))
+            .await
+            .is_err());
+        Ticket::update_estimate(&pool, &ticket.ticket_id, Some(90))
+            .await
+            .unwrap();
+
+        // A worker holds the ticket for 45 minutes, then it is closed
+        let set_worker = |worker: Option<&'static str>| {
+            sqlx::query("UPDATE tickets SET processing_worker_id = ?2 WHERE ticket_id = ?1")
+                .bind(ticket.ticket_id.clone())
+                .bind(worker)
+                .execute(&pool)
+        };
+        set_worker(Some("w-1")).await.unwrap();
+        sqlx::query(
+            "UPDATE ticket_history SET created_at = datetime('now', '-45 minutes')
+             WHERE ticket_id = ?1 AND change = 'assigned'",
+        )
+        .bind(&ticket.ticket_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        set_worker(None).await.unwrap();
+        Ticket::close_ticket(&pool, &ticket.ticket_id, "closed")
+            .await
+            .unwrap();
+
+        let closed = Ticket::get_by_id(&pool, &ticket.ticket_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .ticket;
+        assert_eq!(closed.actual_minutes, 45);
+
+        let changes: Vec<(String, String, Option<i64>)> = sqlx::query_as(
+            "SELECT change, state, estimate_minutes FROM ticket_history
+             WHERE ticket_id = ?1 ORDER BY id",
+        )
+        .bind(&ticket.ticket_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let changes: Vec<_> = changes
+            .iter()
+            .map(|(c, s, e)| (c.as_str(), s.as_str(), *e))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("created", "open", Some(120)),
+                ("updated", "open", Some(90)),
+                ("assigned", "open", Some(90)),
+                ("released", "open", Some(90)),
+                ("updated", "closed", Some(90)),
+            ]
+        );
+
+        let days = load(&pool, "burndown", 3).await.unwrap();
+        assert_eq!(days.len(), 3);
+        let today = days.last().unwrap();
+        assert_eq!(
+            (today.closed_tickets, today.closed_estimate_minutes),
+            (1, 90)
+        );
+
+        pool.close().await;
+    }
+}
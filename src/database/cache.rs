@@ -0,0 +1,299 @@
+use dashmap::DashMap;
+use serde::Serialize;
+use sqlx::sqlite::SqliteConnectOptions;
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, LazyLock, Mutex, Weak,
+    },
+    time::{Duration, Instant},
+};
+use utoipa::ToSchema;
+
+use super::{projects::Project, throughput::ThroughputMetrics, worker_types::WorkerType, DbPool};
+
+const DEFAULT_TTL_SECS: u64 = 30;
+
+/// The caches of a pool, by the connect options that the pool and all its clones share
+type PoolCaches = (Weak<SqliteConnectOptions>, Arc<Caches>);
+
+static REGISTRY: LazyLock<Mutex<Vec<PoolCaches>>> = LazyLock::new(Default::default);
+
+/// Lookup caches of one database, dropped with the last clone of its pool
+pub struct Caches {
+    pub projects: EntityCache<Project>,
+    pub worker_types: EntityCache<WorkerType>,
+    /// Keyed by the latest ticket history row as well, so every ticket write that can
+    /// change the metrics moves them to a new entry
+    pub throughput: EntityCache<ThroughputMetrics>,
+}
+
+impl Caches {
+    fn new() -> Self {
+        Self {
+            projects: EntityCache::new("project"),
+            worker_types: EntityCache::new("worker_type"),
+            throughput: EntityCache::new("throughput"),
+        }
+    }
+
+    /// Configure lookup caching; a TTL of 0 disables it
+    pub fn configure(&self, ttl_secs: u64) {
+        self.projects.configure(ttl_secs);
+        self.worker_types.configure(ttl_secs);
+        self.throughput.configure(ttl_secs);
+    }
+
+    pub fn stats(&self) -> Vec<CacheStats> {
+        vec![
+            self.projects.stats(),
+            self.worker_types.stats(),
+            self.throughput.stats(),
+        ]
+    }
+}
+
+/// The caches of the database behind `pool`
+pub fn for_pool(pool: &DbPool) -> Arc<Caches> {
+    let options = pool.connect_options();
+    let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    registry.retain(|(owner, _)| owner.strong_count() > 0);
+    if let Some((_, caches)) = registry
+        .iter()
+        .find(|(owner, _)| std::ptr::eq(owner.as_ptr(), Arc::as_ptr(&options)))
+    {
+        return caches.clone();
+    }
+    let caches = Arc::new(Caches::new());
+    registry.push((Arc::downgrade(&options), caches.clone()));
+    caches
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CacheStats {
+    pub entity: String,
+    pub enabled: bool,
+    pub ttl_secs: u64,
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_ratio: f64,
+}
+
+/// Read-through cache for a single entity type.
+///
+/// Every invalidation bumps a generation counter; a lookup only stores its result
+/// if no invalidation happened while it was reading, so a concurrent update can
+/// never be overwritten by the value it replaced.
+pub struct EntityCache<T> {
+    entity: &'static str,
+    ttl_secs: AtomicU64,
+    entries: DashMap<String, (Instant, T)>,
+    generation: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<T: Clone> EntityCache<T> {
+    fn new(entity: &'static str) -> Self {
+        Self {
+            entity,
+            ttl_secs: AtomicU64::new(DEFAULT_TTL_SECS),
+            entries: DashMap::new(),
+            generation: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs.load(Ordering::SeqCst))
+    }
+
+    fn configure(&self, ttl_secs: u64) {
+        self.ttl_secs.store(ttl_secs, Ordering::SeqCst);
+        self.clear();
+    }
+
+    /// Return the cached value for `key` or load it; `None` results are not cached
+    pub async fn get_or_load<F, Fut>(&self, key: &str, load: F) -> anyhow::Result<Option<T>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<Option<T>>>,
+    {
+        let ttl = self.ttl();
+        if ttl.is_zero() {
+            return load().await;
+        }
+
+        if let Some(entry) = self.entries.get(key) {
+            if entry.0.elapsed() < ttl {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(Some(entry.1.clone()));
+            }
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let generation = self.generation.load(Ordering::SeqCst);
+        let value = load().await?;
+        if let Some(ref value) = value {
+            if self.generation.load(Ordering::SeqCst) == generation {
+                // Entries under keys no longer looked up would otherwise stay forever
+                self.entries.retain(|_, (at, _)| at.elapsed() < ttl);
+                self.entries
+                    .insert(key.to_string(), (Instant::now(), value.clone()));
+            }
+        }
+        Ok(value)
+    }
+
+    pub fn invalidate(&self, key: &str) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.entries.remove(key);
+    }
+
+    pub fn invalidate_prefix(&self, prefix: &str) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.entries.retain(|key, _| !key.starts_with(prefix));
+    }
+
+    pub fn clear(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.entries.clear();
+    }
+
+    fn stats(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let total = hits + misses;
+        CacheStats {
+            entity: self.entity.to_string(),
+            enabled: !self.ttl().is_zero(),
+            ttl_secs: self.ttl_secs.load(Ordering::SeqCst),
+            entries: self.entries.len(),
+            hits,
+            misses,
+            hit_ratio: if total == 0 {
+                0.0
+            } else {
+                hits as f64 / total as f64
+            },
+        }
+    }
+}
+
+/// Cache key for a worker type; prefixed by project so a project delete can drop them all
+pub fn worker_type_key(project_id: &str, worker_type: &str) -> String {
+    format!("{}\u{0}{}", project_id, worker_type)
+}
+
+/// Cache key for the throughput of a project, or of every project, over `weeks` as of the
+/// ticket history row `watermark`
+pub fn throughput_key(project_id: Option<&str>, weeks: i64, watermark: i64) -> String {
+    format!(
+        "{}\u{0}{}\u{0}{}",
+        project_id.unwrap_or_default(),
+        weeks,
+        watermark
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::{throughput, tickets::Ticket},
+        test_support::{memory_pool, Fixtures},
+    };
+
+    #[tokio::test]
+    async fn test_read_through_and_invalidation() {
+        let cache = EntityCache::<String>::new("test");
+
+        let v = cache
+            .get_or_load("a", || async { Ok(Some("v1".to_string())) })
+            .await
+            .unwrap();
+        assert_eq!(v.as_deref(), Some("v1"));
+
+        // Served from cache: the loader is not consulted
+        let v = cache
+            .get_or_load("a", || async { Ok(Some("v2".to_string())) })
+            .await
+            .unwrap();
+        assert_eq!(v.as_deref(), Some("v1"));
+
+        cache.invalidate("a");
+        let v = cache
+            .get_or_load("a", || async { Ok(Some("v2".to_string())) })
+            .await
+            .unwrap();
+        assert_eq!(v.as_deref(), Some("v2"));
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_invalidation_during_load_is_not_overwritten() {
+        let cache = EntityCache::<String>::new("test");
+
+        cache
+            .get_or_load("a", || async {
+                // An update lands while the stale row is being read
+                cache.invalidate("a");
+                Ok(Some("stale".to_string()))
+            })
+            .await
+            .unwrap();
+
+        assert!(cache.entries.get("a").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_caches_are_per_pool() {
+        let pool = memory_pool().await.unwrap();
+        let other = memory_pool().await.unwrap();
+        assert!(Arc::ptr_eq(&for_pool(&pool), &for_pool(&pool.clone())));
+        assert!(!Arc::ptr_eq(&for_pool(&pool), &for_pool(&other)));
+
+        Fixtures::new(&pool)
+            .project("cached")
+            .create()
+            .await
+            .unwrap();
+        assert!(Project::get_by_name(&pool, "cached")
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(for_pool(&pool).projects.stats().entries, 1);
+
+        // The other database neither sees the entry nor counts the lookup
+        assert!(Project::get_by_name(&other, "cached")
+            .await
+            .unwrap()
+            .is_none());
+        let stats = for_pool(&other).projects.stats();
+        assert_eq!((stats.entries, stats.misses), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_ticket_writes_move_the_throughput_key() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("throughput").create().await.unwrap();
+        let key = |watermark| throughput_key(Some(&project.repository_name), 8, watermark);
+
+        let before = throughput::watermark(&pool).await.unwrap();
+        let ticket = fx.ticket(&project, "T").create().await.unwrap();
+        let created = throughput::watermark(&pool).await.unwrap();
+        Ticket::close_ticket(&pool, &ticket.ticket_id, "closed")
+            .await
+            .unwrap();
+        let closed = throughput::watermark(&pool).await.unwrap();
+
+        assert_ne!(key(before), key(created));
+        assert_ne!(key(created), key(closed));
+    }
+}
@@ -1,11 +1,20 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{FromRow, SqliteConnection};
+use std::collections::HashMap;
 use tracing::{error, warn};
+use utoipa::ToSchema;
 
-use super::DbPool;
+use super::{
+    outbox::OutboxEvent, quotas, returning::FetchReturning, tickets::TicketWithComments,
+    timing::timed, DbPool,
+};
+use crate::{crypto, error::AppError, events::EventPayload, redaction};
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub(crate) const COMMENT_COLUMNS: &str = "id, ticket_id, worker_type, worker_id, stage_number, \
+     content, content_key_id, redactions, reply_to_comment_id, created_at";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Comment {
     pub id: i64,
     pub ticket_id: String,
@@ -13,6 +22,13 @@ pub struct Comment {
     pub worker_id: Option<String>,
     pub stage_number: Option<i32>,
     pub content: String,
+    /// Key the stored content is encrypted with (with `--encrypt-messages`)
+    #[serde(skip)]
+    pub content_key_id: Option<String>,
+    /// Secrets masked before the comment was stored (with `--redact-at-rest`)
+    pub redactions: i64,
+    /// Comment on the same ticket this one answers
+    pub reply_to_comment_id: Option<i64>,
     pub created_at: String,
 }
 
@@ -23,9 +39,82 @@ pub struct CreateCommentRequest {
     pub worker_id: String,
     pub stage_number: i32,
     pub content: String,
+    #[serde(default)]
+    pub reply_to_comment_id: Option<i64>,
+}
+
+/// A comment with its replies, oldest first
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CommentNode {
+    #[serde(flatten)]
+    pub comment: Comment,
+    pub children: Vec<CommentNode>,
+}
+
+impl CommentNode {
+    /// Arrange `comments` into reply trees, keeping their order among siblings. Comments
+    /// whose parent is not in `comments`, such as replies to comments hidden by a thread
+    /// summary, become roots.
+    pub fn tree(comments: &[Comment]) -> Vec<CommentNode> {
+        let present: HashMap<i64, usize> = comments
+            .iter()
+            .enumerate()
+            .map(|(index, comment)| (comment.id, index))
+            .collect();
+        let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut roots = Vec::new();
+        for (index, comment) in comments.iter().enumerate() {
+            match comment
+                .reply_to_comment_id
+                .and_then(|parent| present.get(&parent))
+            {
+                Some(&parent) => children.entry(parent).or_default().push(index),
+                None => roots.push(index),
+            }
+        }
+
+        fn build(
+            index: usize,
+            comments: &[Comment],
+            children: &HashMap<usize, Vec<usize>>,
+        ) -> CommentNode {
+            CommentNode {
+                comment: comments[index].clone(),
+                children: children
+                    .get(&index)
+                    .map(|replies| {
+                        replies
+                            .iter()
+                            .map(|&reply| build(reply, comments, children))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            }
+        }
+        roots
+            .into_iter()
+            .map(|root| build(root, comments, &children))
+            .collect()
+    }
+}
+
+impl TicketWithComments {
+    /// Attach `comments` arranged into reply trees, next to the flat list
+    pub fn with_comment_tree(mut self) -> Self {
+        self.comment_tree = Some(CommentNode::tree(&self.comments));
+        self
+    }
 }
 
 impl Comment {
+    /// The comment with its content decrypted when it was stored encrypted
+    pub(crate) fn opened(mut self) -> Result<Comment> {
+        let content = std::mem::take(&mut self.content);
+        self.content = crypto::open(content, self.content_key_id.as_deref())
+            .map_err(|e| anyhow::anyhow!("Failed to decrypt comment {}: {}", self.id, e))?;
+        Ok(self)
+    }
+
     pub async fn create(
         pool: &DbPool,
         ticket_id: &str,
@@ -34,19 +123,47 @@ impl Comment {
         stage_number: Option<i32>,
         content: &str,
     ) -> Result<Comment> {
-        let comment = sqlx::query_as::<_, Comment>(
+        Self::create_in(
+            &mut *pool.acquire().await?,
+            ticket_id,
+            worker_type,
+            worker_id,
+            stage_number,
+            content,
+        )
+        .await
+    }
+
+    /// `create` within the caller's transaction
+    pub async fn create_in(
+        conn: &mut SqliteConnection,
+        ticket_id: &str,
+        worker_type: Option<&str>,
+        worker_id: Option<&str>,
+        stage_number: Option<i32>,
+        content: &str,
+    ) -> Result<Comment> {
+        let (content, redactions) = redaction::for_storage(content);
+        let (content, key_id) = crypto::seal(&content)?;
+        let comment = sqlx::query_as::<_, Comment>(&format!(
             r#"
-            INSERT INTO comments (ticket_id, worker_type, worker_id, stage_number, content)
-            VALUES (?1, ?2, ?3, ?4, ?5)
-            RETURNING id, ticket_id, worker_type, worker_id, stage_number, content, created_at
+            INSERT INTO comments (
+                ticket_id, worker_type, worker_id, stage_number, content, redactions,
+                content_key_id
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            RETURNING {}
         "#,
-        )
+            COMMENT_COLUMNS
+        ))
         .bind(ticket_id)
         .bind(worker_type)
         .bind(worker_id)
         .bind(stage_number)
-        .bind(content)
-        .fetch_one(pool)
+        .bind(&content)
+        .bind(redactions)
+        .bind(key_id)
+        .fetch_returned_one(&mut *conn)
         .await
         .inspect_err(|e| {
             error!(
@@ -55,23 +172,40 @@ impl Comment {
             )
         })?;
 
-        Ok(comment)
+        comment.opened()
     }
 
+    /// Post a comment on behalf of a worker or the coordinator; refused once the project
+    /// has used up its comments quota, or when it replies to a comment on another ticket.
+    /// Queues `ticket_updated` with the comment.
     pub async fn create_from_request(pool: &DbPool, req: CreateCommentRequest) -> Result<Comment> {
-        let comment = sqlx::query_as::<_, Comment>(
+        if let Some(parent) = req.reply_to_comment_id {
+            Self::reply_target(pool, Some(&req.ticket_id), parent).await?;
+        }
+        quotas::check_comment(&mut *pool.acquire().await?, &req.ticket_id).await?;
+        let (content, redactions) = redaction::for_storage(&req.content);
+        let (content, key_id) = crypto::seal(&content)?;
+        let mut tx = pool.begin().await?;
+        let comment = sqlx::query_as::<_, Comment>(&format!(
             r#"
-            INSERT INTO comments (ticket_id, worker_type, worker_id, stage_number, content)
-            VALUES (?1, ?2, ?3, ?4, ?5)
-            RETURNING id, ticket_id, worker_type, worker_id, stage_number, content, created_at
+            INSERT INTO comments (
+                ticket_id, worker_type, worker_id, stage_number, content, redactions,
+                reply_to_comment_id, content_key_id
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            RETURNING {}
         "#,
-        )
+            COMMENT_COLUMNS
+        ))
         .bind(&req.ticket_id)
         .bind(&req.worker_type)
         .bind(&req.worker_id)
         .bind(req.stage_number)
-        .bind(&req.content)
-        .fetch_one(pool)
+        .bind(&content)
+        .bind(redactions)
+        .bind(req.reply_to_comment_id)
+        .bind(&key_id)
+        .fetch_returned_one(&mut *tx)
         .await
         .inspect_err(|e| {
             error!(
@@ -80,21 +214,40 @@ impl Comment {
             )
         })?;
 
-        Ok(comment)
+        let project_id: String =
+            sqlx::query_scalar("SELECT project_id FROM tickets WHERE ticket_id = ?1")
+                .bind(&req.ticket_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        OutboxEvent::write(
+            &mut tx,
+            &req.ticket_id,
+            &EventPayload::ticket_updated(&req.ticket_id, &project_id, "comment_added"),
+            None,
+            Some(&format!("Comment added: {}", comment.id)),
+        )
+        .await?;
+        tx.commit().await?;
+
+        comment.opened()
     }
 
     pub async fn get_by_ticket_id(pool: &DbPool, ticket_id: &str) -> Result<Vec<Comment>> {
-        let comments = sqlx::query_as::<_, Comment>(
+        let sql = format!(
             r#"
-            SELECT id, ticket_id, worker_type, worker_id, stage_number, content, created_at
+            SELECT {}
             FROM comments
             WHERE ticket_id = ?1
             ORDER BY created_at ASC
         "#,
+            COMMENT_COLUMNS
+        );
+        let comments = timed!(
+            pool,
+            "Comment::get_by_ticket_id",
+            sqlx::query_as::<_, Comment>(&sql).bind(ticket_id),
+            fetch_all
         )
-        .bind(ticket_id)
-        .fetch_all(pool)
-        .await
         .inspect_err(|e| {
             warn!(
                 "Failed to fetch comments for ticket '{}': {:?}",
@@ -102,7 +255,7 @@ impl Comment {
             )
         })?;
 
-        Ok(comments)
+        comments.into_iter().map(Comment::opened).collect()
     }
 
     pub async fn add_with_stage_update(
@@ -110,6 +263,12 @@ impl Comment {
         req: CreateCommentRequest,
         new_stage: &str,
     ) -> Result<(Comment, bool)> {
+        if let Some(parent) = req.reply_to_comment_id {
+            Self::reply_target(pool, Some(&req.ticket_id), parent).await?;
+        }
+        quotas::check_comment(&mut *pool.acquire().await?, &req.ticket_id).await?;
+        let (content, redactions) = redaction::for_storage(&req.content);
+        let (content, key_id) = crypto::seal(&content)?;
         let mut tx = pool.begin().await.inspect_err(|e| {
             error!(
                 "Failed to begin transaction for comment with stage update for ticket '{}': {:?}",
@@ -118,19 +277,26 @@ impl Comment {
         })?;
 
         // Add comment
-        let comment = sqlx::query_as::<_, Comment>(
+        let comment = sqlx::query_as::<_, Comment>(&format!(
             r#"
-            INSERT INTO comments (ticket_id, worker_type, worker_id, stage_number, content)
-            VALUES (?1, ?2, ?3, ?4, ?5)
-            RETURNING id, ticket_id, worker_type, worker_id, stage_number, content, created_at
+            INSERT INTO comments (
+                ticket_id, worker_type, worker_id, stage_number, content, redactions,
+                reply_to_comment_id, content_key_id
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            RETURNING {}
         "#,
-        )
+            COMMENT_COLUMNS
+        ))
         .bind(&req.ticket_id)
         .bind(&req.worker_type)
         .bind(&req.worker_id)
         .bind(req.stage_number)
-        .bind(&req.content)
-        .fetch_one(&mut *tx)
+        .bind(&content)
+        .bind(redactions)
+        .bind(req.reply_to_comment_id)
+        .bind(&key_id)
+        .fetch_returned_one(&mut *tx)
         .await
         .inspect_err(|e| {
             error!(
@@ -165,6 +331,193 @@ impl Comment {
             )
         })?;
 
-        Ok((comment, updated_rows.rows_affected() > 0))
+        Ok((comment.opened()?, updated_rows.rows_affected() > 0))
+    }
+
+    /// Ticket of the comment a reply answers. With `ticket_id`, the comment must be on
+    /// that ticket; without one, the reply goes to the comment's ticket.
+    pub async fn reply_target(
+        pool: &DbPool,
+        ticket_id: Option<&str>,
+        reply_to_comment_id: i64,
+    ) -> Result<String> {
+        let parent_ticket: Option<String> =
+            sqlx::query_scalar("SELECT ticket_id FROM comments WHERE id = ?1")
+                .bind(reply_to_comment_id)
+                .fetch_optional(pool)
+                .await?;
+        match (parent_ticket, ticket_id) {
+            (None, _) => {
+                Err(AppError::NotFound(format!("Comment {} not found", reply_to_comment_id)).into())
+            }
+            (Some(parent_ticket), Some(ticket_id)) if parent_ticket != ticket_id => {
+                Err(AppError::BadRequest(format!(
+                    "Comment {} is on ticket {}, not {}; replies must stay on the same ticket",
+                    reply_to_comment_id, parent_ticket, ticket_id
+                ))
+                .into())
+            }
+            (Some(parent_ticket), _) => Ok(parent_ticket),
+        }
+    }
+
+    pub async fn get_by_id(pool: &DbPool, id: i64) -> Result<Option<Comment>> {
+        let comment = sqlx::query_as::<_, Comment>(&format!(
+            "SELECT {} FROM comments WHERE id = ?1",
+            COMMENT_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        comment.map(Comment::opened).transpose()
+    }
+
+    /// A comment and every reply below it, oldest first, in one query
+    pub async fn subtree(pool: &DbPool, id: i64) -> Result<Vec<Comment>> {
+        let comments = sqlx::query_as::<_, Comment>(&format!(
+            r#"
+            WITH RECURSIVE thread(id) AS (
+                SELECT id FROM comments WHERE id = ?1
+                UNION
+                SELECT c.id FROM comments c JOIN thread t ON c.reply_to_comment_id = t.id
+            )
+            SELECT {}
+            FROM comments
+            WHERE id IN (SELECT id FROM thread)
+            ORDER BY created_at ASC, id ASC
+            "#,
+            COMMENT_COLUMNS
+        ))
+        .bind(id)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to fetch replies to comment {}: {:?}", id, e))?;
+
+        comments.into_iter().map(Comment::opened).collect()
+    }
+
+    /// Delete a comment; its replies move up to its own parent (or become top-level). Queues
+    /// `ticket_updated` with the deletion. Returns how many replies moved, or None when there
+    /// is no such comment.
+    pub async fn delete(pool: &DbPool, id: i64) -> Result<Option<u64>> {
+        let mut tx = pool.begin().await?;
+        let Some((ticket_id, project_id)) = sqlx::query_as::<_, (String, String)>(
+            r#"
+            SELECT c.ticket_id, t.project_id
+            FROM comments c JOIN tickets t ON t.ticket_id = c.ticket_id
+            WHERE c.id = ?1
+        "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let replies: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM comments WHERE reply_to_comment_id = ?1")
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await?;
+        sqlx::query("DELETE FROM comments WHERE id = ?1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .inspect_err(|e| error!("Failed to delete comment {}: {:?}", id, e))?;
+
+        OutboxEvent::write(
+            &mut tx,
+            &ticket_id,
+            &EventPayload::ticket_updated(&ticket_id, &project_id, "comment_deleted"),
+            None,
+            Some(&format!("Comment deleted: {}", id)),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(replies as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    fn reply(
+        ticket_id: &str,
+        content: &str,
+        reply_to_comment_id: Option<i64>,
+    ) -> CreateCommentRequest {
+        CreateCommentRequest {
+            ticket_id: ticket_id.to_string(),
+            worker_type: "coding".to_string(),
+            worker_id: "w1".to_string(),
+            stage_number: 1,
+            content: content.to_string(),
+            reply_to_comment_id,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replies_form_trees_and_move_up_when_parent_is_deleted() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("threads").create().await.unwrap();
+        let ticket = fx.ticket(&project, "threaded").create().await.unwrap();
+        let other = fx.ticket(&project, "elsewhere").create().await.unwrap();
+        let id = ticket.ticket_id.as_str();
+
+        let question = Comment::create_from_request(&pool, reply(id, "question", None))
+            .await
+            .unwrap();
+        let answer = Comment::create_from_request(&pool, reply(id, "answer", Some(question.id)))
+            .await
+            .unwrap();
+        let follow_up =
+            Comment::create_from_request(&pool, reply(id, "follow-up", Some(answer.id)))
+                .await
+                .unwrap();
+        let aside = Comment::create_from_request(&pool, reply(id, "aside", Some(question.id)))
+            .await
+            .unwrap();
+
+        // Replies stay on their parent's ticket, and adopt it when none is given
+        let err =
+            Comment::create_from_request(&pool, reply(&other.ticket_id, "x", Some(answer.id)))
+                .await
+                .unwrap_err();
+        assert!(err.to_string().contains("same ticket"), "{}", err);
+        assert_eq!(
+            Comment::reply_target(&pool, None, answer.id).await.unwrap(),
+            id
+        );
+        assert!(Comment::reply_target(&pool, None, 9999).await.is_err());
+
+        let subtree = Comment::subtree(&pool, question.id).await.unwrap();
+        let ids: Vec<i64> = subtree.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [question.id, answer.id, follow_up.id, aside.id]);
+        let tree = CommentNode::tree(&subtree);
+        assert_eq!(tree.len(), 1);
+        let replies: Vec<i64> = tree[0].children.iter().map(|n| n.comment.id).collect();
+        assert_eq!(replies, [answer.id, aside.id]);
+        assert_eq!(tree[0].children[0].children[0].comment.id, follow_up.id);
+
+        // Deleting the answer hands its reply to the question
+        assert_eq!(Comment::delete(&pool, answer.id).await.unwrap(), Some(1));
+        let moved = Comment::get_by_id(&pool, follow_up.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(moved.reply_to_comment_id, Some(question.id));
+        assert_eq!(Comment::delete(&pool, answer.id).await.unwrap(), None);
+
+        // Deleting the root makes its replies top-level
+        Comment::delete(&pool, question.id).await.unwrap();
+        let thread = Comment::get_by_ticket_id(&pool, id).await.unwrap();
+        let tree = CommentNode::tree(&thread);
+        for reply in [follow_up.id, aside.id] {
+            assert!(tree.iter().any(|node| node.comment.id == reply));
+        }
     }
 }
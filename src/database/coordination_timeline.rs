@@ -0,0 +1,348 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::DbPool;
+
+/// Every coordination event as one row shape. `id` is `<source>:<row>:<phase>`, zero-padded
+/// so that it orders the events sharing a timestamp: ticket changes, then blocks, then
+/// requests, then worker runs, each in the order their rows were written. Ticket claims
+/// are the only locks the server takes, so they double as lock acquisitions and releases.
+const TIMELINE_UNION: &str = r#"
+    SELECT h.created_at AS at, printf('1:%010d:0', h.id) AS id, 'ticket_' || h.change AS kind,
+           h.project_id,
+           CASE WHEN h.change = 'released' THEN (
+               SELECT p.processing_worker_id FROM ticket_history p
+               WHERE p.ticket_id = h.ticket_id AND p.change = 'assigned' AND p.id < h.id
+               ORDER BY p.id DESC LIMIT 1
+           ) ELSE h.processing_worker_id END AS agent_id,
+           NULL AS counterpart_agent_id, h.ticket_id, NULL AS related_ticket_id,
+           NULL AS correlation_id,
+           CASE WHEN h.change = 'released' THEN (
+               SELECT p.processing_worker_id FROM ticket_history p
+               WHERE p.ticket_id = h.ticket_id AND p.change = 'assigned' AND p.id < h.id
+               ORDER BY p.id DESC LIMIT 1
+           ) ELSE h.processing_worker_id END AS worker_id,
+           h.state, NULL AS detail
+    FROM ticket_history h
+    UNION ALL
+    SELECT b.blocked_at, printf('2:%010d:0', b.id), 'ticket_blocked', t.project_id,
+           b.blocked_by, b.responsible_agent, b.ticket_id, b.blocking_ticket_id, NULL, NULL,
+           NULL, b.reason
+    FROM ticket_blocks b JOIN tickets t ON t.ticket_id = b.ticket_id
+    UNION ALL
+    SELECT b.unblocked_at, printf('2:%010d:1', b.id), 'ticket_unblocked', t.project_id,
+           b.unblocked_by, b.responsible_agent, b.ticket_id, b.blocking_ticket_id, NULL, NULL,
+           NULL, b.reason
+    FROM ticket_blocks b JOIN tickets t ON t.ticket_id = b.ticket_id
+    WHERE b.unblocked_at IS NOT NULL
+    UNION ALL
+    SELECT r.created_at, printf('3:%010d:0', r.rowid), 'request_sent', r.project_id,
+           r.requester, r.target, r.ticket_id, NULL, r.correlation_id, NULL, r.status,
+           r.group_id
+    FROM agent_requests r
+    UNION ALL
+    SELECT r.responded_at, printf('3:%010d:1', r.rowid), 'request_' || r.status, r.project_id,
+           r.target, r.requester, r.ticket_id, NULL, r.correlation_id, NULL, r.status,
+           r.group_id
+    FROM agent_requests r
+    WHERE r.responded_at IS NOT NULL
+    UNION ALL
+    SELECT w.started_at, printf('4:%010d:0', w.id), 'worker_started', w.project_id,
+           w.worker_id, NULL, w.ticket_id, NULL, NULL, w.worker_id, w.status, w.worker_type
+    FROM worker_runs w
+    UNION ALL
+    SELECT w.finished_at, printf('4:%010d:1', w.id), 'worker_stopped', w.project_id,
+           w.worker_id, NULL, w.ticket_id, NULL, NULL, w.worker_id, w.status, w.exit_status
+    FROM worker_runs w
+    WHERE w.finished_at IS NOT NULL
+"#;
+
+/// One event of the coordination timeline. Every kind of event has the same fields; the
+/// ids that don't apply to it are null.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct TimelineEntry {
+    /// When it happened, in SQLite's UTC timestamp format
+    pub at: String,
+    /// Stable id, which also orders events with the same `at`
+    pub id: String,
+    /// ticket_created, ticket_updated, ticket_assigned, ticket_released, ticket_blocked,
+    /// ticket_unblocked, request_sent, request_answered, request_rejected, request_expired,
+    /// worker_started or worker_stopped
+    pub kind: String,
+    pub project_id: Option<String>,
+    /// The agent that acted: the claiming worker, whoever placed or lifted a block, the
+    /// requester of a sent request and the target of a resolved one, or the run's worker
+    pub agent_id: Option<String>,
+    /// The other agent involved: the target or requester of a request, or the agent
+    /// responsible for lifting a block
+    pub counterpart_agent_id: Option<String>,
+    pub ticket_id: Option<String>,
+    /// The ticket a blocked ticket waits on
+    pub related_ticket_id: Option<String>,
+    /// Links a request to its answer
+    pub correlation_id: Option<String>,
+    pub worker_id: Option<String>,
+    /// Ticket state after a ticket change, or the request or run status
+    pub state: Option<String>,
+    /// Block reason, agent group of a request, worker type of a started run or exit status
+    /// of a stopped one
+    pub detail: Option<String>,
+}
+
+/// Filters of a timeline query; times in SQLite's timestamp format
+#[derive(Debug, Clone, Default)]
+pub struct TimelineFilter<'a> {
+    /// Inclusive
+    pub from: Option<&'a str>,
+    /// Exclusive
+    pub to: Option<&'a str>,
+    pub project_id: Option<&'a str>,
+    /// Events the agent acted in or was the counterpart of
+    pub agent_id: Option<&'a str>,
+}
+
+impl TimelineFilter<'_> {
+    /// Stable description of the filters, for binding pagination cursors to them
+    pub fn fingerprint(&self) -> String {
+        serde_json::json!([self.from, self.to, self.project_id, self.agent_id]).to_string()
+    }
+}
+
+/// Timeline events matching `filter` in chronological order, after the sort key `after`
+/// (`at`, `id`) when one is given. A negative limit returns them all.
+pub async fn list(
+    pool: &DbPool,
+    filter: &TimelineFilter<'_>,
+    after: Option<(&str, &str)>,
+    limit: i64,
+) -> Result<Vec<TimelineEntry>> {
+    let (after_at, after_id) = after.unzip();
+    let entries = sqlx::query_as::<_, TimelineEntry>(&format!(
+        r#"
+        SELECT at, id, kind, project_id, agent_id, counterpart_agent_id, ticket_id,
+               related_ticket_id, correlation_id, worker_id, state, detail
+        FROM ({}) timeline
+        WHERE (?1 IS NULL OR at >= ?1) AND (?2 IS NULL OR at < ?2)
+          AND (?3 IS NULL OR project_id = ?3)
+          AND (?4 IS NULL OR agent_id = ?4 OR counterpart_agent_id = ?4)
+          AND (?5 IS NULL OR at > ?5 OR (at = ?5 AND id > ?6))
+        ORDER BY at ASC, id ASC
+        LIMIT ?7
+        "#,
+        TIMELINE_UNION
+    ))
+    .bind(filter.from)
+    .bind(filter.to)
+    .bind(filter.project_id)
+    .bind(filter.agent_id)
+    .bind(after_at)
+    .bind(after_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .inspect_err(|e| warn!("Failed to fetch the coordination timeline: {:?}", e))?;
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    const AT: &str = "2026-05-04 10:00:00";
+
+    /// A project whose ticket is created, claimed, blocked, messaged about and worked on,
+    /// all within the same second
+    async fn same_second_project(pool: &DbPool) -> String {
+        let fx = Fixtures::new(pool);
+        let project = fx.project("timeline").create().await.unwrap();
+        fx.worker_type(&project, "coding").create().await.unwrap();
+        let ticket = fx.ticket(&project, "Add login").create().await.unwrap();
+        let ticket_id = ticket.ticket_id.as_str();
+
+        sqlx::query("UPDATE tickets SET processing_worker_id = 'coding-1' WHERE ticket_id = ?1")
+            .bind(ticket_id)
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO ticket_blocks (ticket_id, reason, responsible_agent, blocked_by) \
+             VALUES (?1, 'needs the API key', 'coordinator', 'coding-1')",
+        )
+        .bind(ticket_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        for (correlation_id, status) in [("corr-b", "answered"), ("corr-a", "pending")] {
+            sqlx::query(
+                "INSERT INTO agent_requests \
+                 (correlation_id, requester, target, ticket_id, project_id, payload, status, \
+                  deadline, responded_at) \
+                 VALUES (?1, 'coding-1', 'coordinator', ?2, ?3, '{}', ?4, ?5, \
+                         CASE WHEN ?4 = 'answered' THEN ?5 END)",
+            )
+            .bind(correlation_id)
+            .bind(ticket_id)
+            .bind(&project.repository_name)
+            .bind(status)
+            .bind(AT)
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO worker_runs \
+             (worker_id, project_id, worker_type, ticket_id, working_dir, max_runtime_secs, \
+              status, started_at, finished_at, exit_status) \
+             VALUES ('coding-1', ?1, 'coding', ?2, '/tmp', 60, 'completed', ?3, ?3, 'exit 0')",
+        )
+        .bind(&project.repository_name)
+        .bind(ticket_id)
+        .bind(AT)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        for table in [
+            "ticket_history SET created_at",
+            "ticket_blocks SET blocked_at",
+        ] {
+            sqlx::query(&format!("UPDATE {} = ?1", table))
+                .bind(AT)
+                .execute(pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("UPDATE agent_requests SET created_at = ?1")
+            .bind(AT)
+            .execute(pool)
+            .await
+            .unwrap();
+        project.repository_name
+    }
+
+    fn kinds(entries: &[TimelineEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.kind.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_same_timestamp_events_keep_a_stable_order() {
+        let pool = memory_pool().await.unwrap();
+        let project_id = same_second_project(&pool).await;
+        let filter = TimelineFilter {
+            project_id: Some(&project_id),
+            ..Default::default()
+        };
+
+        let all = list(&pool, &filter, None, -1).await.unwrap();
+        assert!(all.iter().all(|e| e.at == AT));
+        assert_eq!(
+            kinds(&all),
+            [
+                "ticket_created",
+                "ticket_assigned",
+                "ticket_blocked",
+                "request_sent",
+                "request_answered",
+                "request_sent",
+                "worker_started",
+                "worker_stopped"
+            ]
+        );
+        // Requests keep the order they were sent in, not that of their correlation ids
+        assert_eq!(all[3].correlation_id.as_deref(), Some("corr-b"));
+        assert_eq!(all[4].correlation_id.as_deref(), Some("corr-b"));
+        assert_eq!(all[5].correlation_id.as_deref(), Some("corr-a"));
+        assert_eq!(all[1].worker_id.as_deref(), Some("coding-1"));
+        assert_eq!(all[2].counterpart_agent_id.as_deref(), Some("coordinator"));
+
+        // The same query gives the same order, and pages of any size add up to it
+        assert_eq!(list(&pool, &filter, None, -1).await.unwrap(), all);
+        for page_size in 1..=3 {
+            let mut paged = Vec::new();
+            loop {
+                let after = paged
+                    .last()
+                    .map(|e: &TimelineEntry| (e.at.as_str(), e.id.as_str()));
+                let page = list(&pool, &filter, after, page_size).await.unwrap();
+                if page.is_empty() {
+                    break;
+                }
+                paged.extend(page);
+            }
+            assert_eq!(paged, all, "page size {}", page_size);
+        }
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_filters_by_time_and_agent() {
+        let pool = memory_pool().await.unwrap();
+        let project_id = same_second_project(&pool).await;
+        sqlx::query("UPDATE tickets SET processing_worker_id = NULL WHERE project_id = ?1")
+            .bind(&project_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE ticket_history SET created_at = '2026-05-04 11:00:00' WHERE change = 'released'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // The release is attributed to the worker that held the claim
+        let released = list(
+            &pool,
+            &TimelineFilter {
+                from: Some("2026-05-04 10:00:01"),
+                ..Default::default()
+            },
+            None,
+            -1,
+        )
+        .await
+        .unwrap();
+        assert_eq!(kinds(&released), ["ticket_released"]);
+        assert_eq!(released[0].agent_id.as_deref(), Some("coding-1"));
+
+        let before = list(
+            &pool,
+            &TimelineFilter {
+                to: Some(AT),
+                ..Default::default()
+            },
+            None,
+            -1,
+        )
+        .await
+        .unwrap();
+        assert!(before.is_empty());
+
+        let coordinator = list(
+            &pool,
+            &TimelineFilter {
+                agent_id: Some("coordinator"),
+                ..Default::default()
+            },
+            None,
+            -1,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            kinds(&coordinator),
+            [
+                "ticket_blocked",
+                "request_sent",
+                "request_answered",
+                "request_sent"
+            ]
+        );
+
+        pool.close().await;
+    }
+}
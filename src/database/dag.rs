@@ -4,7 +4,7 @@ use sqlx::FromRow;
 use std::collections::{HashMap, HashSet, VecDeque};
 use tracing::{debug, warn};
 
-use super::DbPool;
+use super::{returning::FetchReturning, DbPool};
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct TicketDependency {
@@ -83,7 +83,7 @@ impl TicketDependency {
         .bind(parent_ticket_id)
         .bind(child_ticket_id)
         .bind(dependency_type)
-        .fetch_one(pool)
+        .fetch_returned_one(pool)
         .await?;
 
         debug!(
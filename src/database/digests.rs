@@ -0,0 +1,459 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqliteRow, FromRow};
+use tracing::error;
+
+use super::{returning::FetchReturning, DbPool};
+use crate::workers::claims::{ClaimConflict, ConflictLog};
+
+/// Items listed per digest section; the counts cover everything in the period
+pub const TOP_K: usize = 10;
+
+/// Digests older than this are deleted when a new one is stored
+const RETENTION_DAYS: i64 = 30;
+
+const DIGEST_COLUMNS: &str = "id, period_start, period_end, item_count, content, created_at";
+
+const TICKET_ITEM_COLUMNS: &str =
+    "ticket_id, short_id, project_id, title, priority, state, current_stage";
+
+const RUN_ITEM_COLUMNS: &str =
+    "worker_id, project_id, worker_type, ticket_id, exit_status, enforcement, finished_at AS at";
+
+/// One kind of activity in a digest: how much there was, and the latest `TOP_K` of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestSection<T> {
+    pub count: i64,
+    pub items: Vec<T>,
+}
+
+impl<T> Default for DigestSection<T> {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            items: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DigestTicket {
+    pub ticket_id: String,
+    pub short_id: String,
+    pub project_id: String,
+    pub title: String,
+    pub priority: String,
+    pub state: String,
+    pub current_stage: String,
+    /// When the ticket was created or last changed
+    pub at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DigestWorkerRun {
+    pub worker_id: String,
+    pub project_id: String,
+    pub worker_type: String,
+    pub ticket_id: String,
+    pub exit_status: Option<String>,
+    pub enforcement: Option<String>,
+    /// When the run ended
+    pub at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DigestSlaAlert {
+    pub ticket_id: String,
+    pub short_id: String,
+    pub project_id: String,
+    pub title: String,
+    pub priority: String,
+    pub state: String,
+    pub due_at: String,
+    /// warning (due soon) or breach (past due)
+    pub escalation: String,
+    pub at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DigestEscalation {
+    pub ticket_id: String,
+    pub short_id: String,
+    pub project_id: String,
+    pub title: String,
+    pub priority: String,
+    pub effective_priority: String,
+    /// JSON array of the rules that raised it
+    pub escalation_reasons: Option<String>,
+    /// When it was last raised
+    pub at: String,
+}
+
+/// Activity in a digest period, by kind
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DigestContent {
+    pub tickets_created: DigestSection<DigestTicket>,
+    /// Tickets created before the period that changed during it
+    pub tickets_changed: DigestSection<DigestTicket>,
+    pub workers_failed: DigestSection<DigestWorkerRun>,
+    /// Workers that stopped responding and were killed at their runtime limit
+    pub workers_offline: DigestSection<DigestWorkerRun>,
+    /// Workers that tried to claim a ticket another worker held
+    pub claim_conflicts: DigestSection<ClaimConflict>,
+    pub sla_escalations: DigestSection<DigestSlaAlert>,
+    /// Tickets whose effective priority went up and is still above their priority
+    #[serde(default)]
+    pub priority_escalations: DigestSection<DigestEscalation>,
+}
+
+/// Count and latest `TOP_K` rows of `source`, whose timestamps are bound as ?1 (exclusive)
+/// and ?2 (inclusive)
+async fn section<T>(
+    pool: &DbPool,
+    columns: &str,
+    source: &str,
+    since: &str,
+    until: &str,
+) -> Result<DigestSection<T>>
+where
+    T: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+{
+    let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", source))
+        .bind(since)
+        .bind(until)
+        .fetch_one(pool)
+        .await?;
+    if count == 0 {
+        return Ok(DigestSection::default());
+    }
+    let items = sqlx::query_as::<_, T>(&format!(
+        "SELECT {} FROM {} ORDER BY at DESC LIMIT ?3",
+        columns, source
+    ))
+    .bind(since)
+    .bind(until)
+    .bind(TOP_K as i64)
+    .fetch_all(pool)
+    .await?;
+    Ok(DigestSection { count, items })
+}
+
+impl DigestContent {
+    /// Activity after `since` up to and including `until`, with the claim conflicts taken
+    /// from the claim log over the same period
+    pub async fn compile(
+        pool: &DbPool,
+        since: &str,
+        until: &str,
+        conflicts: ConflictLog,
+    ) -> Result<DigestContent> {
+        let tickets_created = section(
+            pool,
+            &format!("{}, created_at AS at", TICKET_ITEM_COLUMNS),
+            "tickets WHERE created_at > ?1 AND created_at <= ?2",
+            since,
+            until,
+        )
+        .await?;
+        let tickets_changed = section(
+            pool,
+            &format!("{}, updated_at AS at", TICKET_ITEM_COLUMNS),
+            "tickets WHERE updated_at > ?1 AND updated_at <= ?2 AND created_at <= ?1",
+            since,
+            until,
+        )
+        .await?;
+        let workers_failed = section(
+            pool,
+            RUN_ITEM_COLUMNS,
+            "worker_runs WHERE status = 'failed' AND finished_at > ?1 AND finished_at <= ?2",
+            since,
+            until,
+        )
+        .await?;
+        let workers_offline = section(
+            pool,
+            RUN_ITEM_COLUMNS,
+            "worker_runs WHERE status = 'killed' AND finished_at > ?1 AND finished_at <= ?2",
+            since,
+            until,
+        )
+        .await?;
+        let sla_escalations = section(
+            pool,
+            "*",
+            &format!(
+                r#"(
+                SELECT {columns}, due_at, 'breach' AS escalation, sla_breached_at AS at
+                FROM tickets WHERE sla_breached_at > ?1 AND sla_breached_at <= ?2
+                UNION ALL
+                SELECT {columns}, due_at, 'warning' AS escalation, sla_warned_at AS at
+                FROM tickets WHERE sla_warned_at > ?1 AND sla_warned_at <= ?2
+            )"#,
+                columns = "ticket_id, short_id, project_id, title, priority, state"
+            ),
+            since,
+            until,
+        )
+        .await?;
+        let priority_escalations = section(
+            pool,
+            "ticket_id, short_id, project_id, title, priority, effective_priority, \
+             escalation_reasons, escalated_at AS at",
+            "tickets WHERE escalated_at > ?1 AND escalated_at <= ?2 \
+             AND effective_priority != priority AND state != 'closed'",
+            since,
+            until,
+        )
+        .await?;
+
+        Ok(DigestContent {
+            tickets_created,
+            tickets_changed,
+            workers_failed,
+            workers_offline,
+            claim_conflicts: DigestSection {
+                count: conflicts.count as i64,
+                items: conflicts.recent.into_iter().rev().take(TOP_K).collect(),
+            },
+            sla_escalations,
+            priority_escalations,
+        })
+    }
+
+    /// Sum of the section counts
+    pub fn item_count(&self) -> i64 {
+        self.tickets_created.count
+            + self.tickets_changed.count
+            + self.workers_failed.count
+            + self.workers_offline.count
+            + self.claim_conflicts.count
+            + self.sla_escalations.count
+            + self.priority_escalations.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.item_count() == 0
+    }
+
+    /// One line naming the counts that are not zero, e.g. "2 tickets created, 1 worker failed"
+    pub fn headline(&self) -> String {
+        let counts = [
+            (self.tickets_created.count, "ticket", "created"),
+            (self.tickets_changed.count, "ticket", "changed"),
+            (self.workers_failed.count, "worker", "failed"),
+            (self.workers_offline.count, "worker", "went offline"),
+            (self.claim_conflicts.count, "claim conflict", ""),
+            (self.sla_escalations.count, "SLA escalation", ""),
+            (self.priority_escalations.count, "ticket", "escalated"),
+        ];
+        let parts: Vec<String> = counts
+            .iter()
+            .filter(|(count, _, _)| *count > 0)
+            .map(|(count, noun, verb)| {
+                let plural = if *count == 1 { "" } else { "s" };
+                format!("{} {}{} {}", count, noun, plural, verb)
+                    .trim_end()
+                    .to_string()
+            })
+            .collect();
+        if parts.is_empty() {
+            "no activity".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// A stored roll-up of the activity between `period_start` and `period_end`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Digest {
+    pub id: i64,
+    pub period_start: String,
+    pub period_end: String,
+    pub item_count: i64,
+    pub created_at: String,
+    #[serde(flatten)]
+    pub content: DigestContent,
+}
+
+#[derive(FromRow)]
+struct DigestRow {
+    id: i64,
+    period_start: String,
+    period_end: String,
+    item_count: i64,
+    content: String,
+    created_at: String,
+}
+
+impl TryFrom<DigestRow> for Digest {
+    type Error = anyhow::Error;
+
+    fn try_from(row: DigestRow) -> Result<Digest> {
+        Ok(Digest {
+            id: row.id,
+            period_start: row.period_start,
+            period_end: row.period_end,
+            item_count: row.item_count,
+            created_at: row.created_at,
+            content: serde_json::from_str(&row.content)?,
+        })
+    }
+}
+
+impl Digest {
+    /// The database clock, in the format of its timestamps, for the end of a period
+    pub async fn clock(pool: &DbPool) -> Result<String> {
+        Ok(sqlx::query_scalar("SELECT datetime('now')")
+            .fetch_one(pool)
+            .await?)
+    }
+
+    /// Store a digest of `period_start..=period_end`, dropping digests past retention
+    pub async fn create(
+        pool: &DbPool,
+        period_start: &str,
+        period_end: &str,
+        content: &DigestContent,
+    ) -> Result<Digest> {
+        let row = sqlx::query_as::<_, DigestRow>(&format!(
+            r#"
+            INSERT INTO digests (period_start, period_end, item_count, content)
+            VALUES (?1, ?2, ?3, ?4)
+            RETURNING {}
+        "#,
+            DIGEST_COLUMNS
+        ))
+        .bind(period_start)
+        .bind(period_end)
+        .bind(content.item_count())
+        .bind(serde_json::to_string(content)?)
+        .fetch_returned_one(pool)
+        .await
+        .inspect_err(|e| error!("Failed to store digest: {:?}", e))?;
+
+        sqlx::query("DELETE FROM digests WHERE created_at < datetime('now', ?1)")
+            .bind(format!("-{} days", RETENTION_DAYS))
+            .execute(pool)
+            .await?;
+
+        row.try_into()
+    }
+
+    pub async fn latest(pool: &DbPool) -> Result<Option<Digest>> {
+        sqlx::query_as::<_, DigestRow>(&format!(
+            "SELECT {} FROM digests ORDER BY id DESC LIMIT 1",
+            DIGEST_COLUMNS
+        ))
+        .fetch_optional(pool)
+        .await?
+        .map(Digest::try_from)
+        .transpose()
+    }
+
+    /// Where the next digest's period starts: the end of the latest one, if there is one
+    pub async fn last_period_end(pool: &DbPool) -> Result<Option<String>> {
+        Ok(
+            sqlx::query_scalar("SELECT period_end FROM digests ORDER BY id DESC LIMIT 1")
+                .fetch_optional(pool)
+                .await?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::sla::SlaAlert;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    #[tokio::test]
+    async fn test_compile_bounds_sections_to_the_period() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("digests").create().await.unwrap();
+        let old = fx.ticket(&project, "Before").create().await.unwrap();
+        sqlx::query(
+            "UPDATE tickets SET created_at = '2000-01-01 00:00:00', updated_at = '2000-01-01 00:00:00'",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let since = "2000-01-01 00:00:00";
+        assert!(DigestContent::compile(
+            &pool,
+            since,
+            &Digest::clock(&pool).await.unwrap(),
+            ConflictLog::default()
+        )
+        .await
+        .unwrap()
+        .is_empty());
+
+        for i in 0..TOP_K + 2 {
+            fx.ticket(&project, &format!("New {}", i))
+                .create()
+                .await
+                .unwrap();
+        }
+        sqlx::query("UPDATE tickets SET updated_at = datetime('now'), due_at = datetime('now', '+5 minutes') WHERE ticket_id = ?1")
+            .bind(&old.ticket_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(SlaAlert::mark_warnings(&pool, 30).await.unwrap().len(), 1);
+        sqlx::query(
+            "UPDATE tickets SET effective_priority = 'high', escalation_reasons = '[\"due within 30 minutes\"]', escalated_at = datetime('now') WHERE ticket_id = ?1",
+        )
+        .bind(&old.ticket_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let mut conflicts = ConflictLog::default();
+        conflicts.recent.push_back(ClaimConflict {
+            ticket_id: old.ticket_id.clone(),
+            worker_id: "w2".to_string(),
+            held_by: "w1".to_string(),
+            at: since.to_string(),
+        });
+        conflicts.count = 3;
+
+        let until = Digest::clock(&pool).await.unwrap();
+        let content = DigestContent::compile(&pool, since, &until, conflicts)
+            .await
+            .unwrap();
+        assert_eq!(content.tickets_created.count, TOP_K as i64 + 2);
+        assert_eq!(content.tickets_created.items.len(), TOP_K);
+        assert_eq!(content.tickets_changed.count, 1);
+        assert_eq!(content.tickets_changed.items[0].ticket_id, old.ticket_id);
+        assert_eq!(content.sla_escalations.items[0].escalation, "warning");
+        assert_eq!(
+            content.priority_escalations.items[0].effective_priority,
+            "high"
+        );
+        assert_eq!(content.claim_conflicts.count, 3);
+        assert_eq!(content.item_count(), TOP_K as i64 + 2 + 1 + 1 + 3 + 1);
+        assert_eq!(
+            content.headline(),
+            "12 tickets created, 1 ticket changed, 3 claim conflicts, 1 SLA escalation, 1 ticket escalated"
+        );
+
+        let digest = Digest::create(&pool, since, &until, &content)
+            .await
+            .unwrap();
+        let latest = Digest::latest(&pool).await.unwrap().unwrap();
+        assert_eq!(latest.id, digest.id);
+        assert_eq!(latest.content.tickets_created.items.len(), TOP_K);
+        assert_eq!(
+            Digest::last_period_end(&pool).await.unwrap(),
+            Some(until.clone())
+        );
+
+        // Nothing after the period end shows up in the next one
+        let next = DigestContent::compile(&pool, &until, &until, ConflictLog::default())
+            .await
+            .unwrap();
+        assert!(next.is_empty());
+    }
+}
@@ -0,0 +1,466 @@
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::FromRow;
+use std::{
+    collections::{BTreeSet, HashSet},
+    hash::Hash,
+};
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::{outbox::OutboxEvent, tags::Tag, DbPool};
+use crate::events::EventPayload;
+
+/// Similarity from which an open ticket is reported as a possible duplicate
+pub const DUPLICATE_THRESHOLD: f64 = 0.55;
+
+/// Open tickets sharing a title word that are scored, best title matches first
+const CANDIDATE_LIMIT: i64 = 50;
+
+/// Possible duplicates reported per check
+const MAX_DUPLICATES: usize = 5;
+
+/// Words too common to say two titles are about the same thing
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "in", "is", "it", "of", "on",
+    "or", "the", "to", "when", "with",
+];
+
+/// Open ticket similar enough to a new one that it may describe the same problem
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PossibleDuplicate {
+    pub ticket_id: String,
+    pub short_id: String,
+    pub title: String,
+    pub state: String,
+    pub tags: Vec<String>,
+    /// 0 to 1; title trigram and word overlap, plus shared tags when either side has any
+    pub similarity: f64,
+}
+
+#[derive(FromRow)]
+struct Candidate {
+    ticket_id: String,
+    short_id: String,
+    title: String,
+    state: String,
+}
+
+/// Lowercased words of `title`, without punctuation
+fn normalize(title: &str) -> String {
+    title
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Significant words of a normalized title, with plural and tense endings removed so
+/// "fails" and "failing" count as the same word
+fn words(normalized: &str) -> BTreeSet<String> {
+    normalized
+        .split(' ')
+        .filter(|word| !word.is_empty() && !STOPWORDS.contains(word))
+        .map(|word| {
+            for suffix in ["ing", "ed", "es", "s"] {
+                if let Some(stem) = word.strip_suffix(suffix) {
+                    if stem.chars().count() >= 3 {
+                        return stem.to_string();
+                    }
+                }
+            }
+            word.to_string()
+        })
+        .collect()
+}
+
+/// Character trigrams of a normalized title, padded so short words still have some
+fn trigrams(normalized: &str) -> HashSet<[char; 3]> {
+    let padded: Vec<char> = format!("  {} ", normalized).chars().collect();
+    padded.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+fn jaccard<T: Eq + Hash + Ord>(a: &HashSet<T>, b: &HashSet<T>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// How alike two tickets look, from 0 to 1, by title and tag names
+pub fn similarity(title: &str, tags: &[String], other_title: &str, other_tags: &[String]) -> f64 {
+    let (a, b) = (normalize(title), normalize(other_title));
+    let title_words = |normalized: &str| words(normalized).into_iter().collect::<HashSet<_>>();
+    let title_score = 0.7 * jaccard(&trigrams(&a), &trigrams(&b))
+        + 0.3 * jaccard(&title_words(&a), &title_words(&b));
+    if tags.is_empty() && other_tags.is_empty() {
+        return title_score;
+    }
+    let tag_set = |tags: &[String]| {
+        tags.iter()
+            .map(|t| t.to_lowercase())
+            .collect::<HashSet<_>>()
+    };
+    0.85 * title_score + 0.15 * jaccard(&tag_set(tags), &tag_set(other_tags))
+}
+
+/// FTS5 query matching tickets whose title contains any significant word of `title`
+fn candidate_query(title: &str) -> Option<String> {
+    let normalized = normalize(title);
+    let terms: Vec<String> = normalized
+        .split(' ')
+        .filter(|word| !word.is_empty() && !STOPWORDS.contains(word))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|word| format!("\"{}\"", word))
+        .collect();
+    (!terms.is_empty()).then(|| format!("title : ({})", terms.join(" OR ")))
+}
+
+/// Open tickets of `project_id` that look like a ticket titled `title` with `tags`, most
+/// similar first. Only tickets sharing a title word are compared, found through the
+/// search index, so the check stays cheap on busy projects.
+pub async fn find_possible_duplicates(
+    pool: &DbPool,
+    project_id: &str,
+    title: &str,
+    tags: &[String],
+    exclude_ticket_id: Option<&str>,
+) -> Result<Vec<PossibleDuplicate>> {
+    let Some(query) = candidate_query(title) else {
+        return Ok(Vec::new());
+    };
+    let candidates = sqlx::query_as::<_, Candidate>(
+        r#"
+        SELECT t.ticket_id, t.short_id, t.title, t.state
+        FROM ticket_search
+        JOIN ticket_search_docs d ON d.doc_id = ticket_search.rowid
+        JOIN tickets t ON t.ticket_id = d.ticket_id
+        WHERE ticket_search MATCH ?1
+          AND t.project_id = ?2
+          AND t.state != 'closed'
+          AND (?3 IS NULL OR t.ticket_id != ?3)
+        ORDER BY bm25(ticket_search)
+        LIMIT ?4
+    "#,
+    )
+    .bind(&query)
+    .bind(project_id)
+    .bind(exclude_ticket_id)
+    .bind(CANDIDATE_LIMIT)
+    .fetch_all(pool)
+    .await
+    .inspect_err(|e| {
+        warn!(
+            "Failed to find duplicate candidates for {:?}: {:?}",
+            title, e
+        )
+    })?;
+
+    let ids: Vec<&str> = candidates.iter().map(|c| c.ticket_id.as_str()).collect();
+    let mut candidate_tags = Tag::names_for_tickets(pool, &ids).await?;
+    let mut duplicates: Vec<PossibleDuplicate> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let tags_of_candidate = candidate_tags
+                .remove(&candidate.ticket_id)
+                .unwrap_or_default();
+            let score = similarity(title, tags, &candidate.title, &tags_of_candidate);
+            (score >= DUPLICATE_THRESHOLD).then(|| PossibleDuplicate {
+                ticket_id: candidate.ticket_id,
+                short_id: candidate.short_id,
+                title: candidate.title,
+                state: candidate.state,
+                tags: tags_of_candidate,
+                similarity: (score * 1000.0).round() / 1000.0,
+            })
+        })
+        .collect();
+    duplicates.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    duplicates.truncate(MAX_DUPLICATES);
+    Ok(duplicates)
+}
+
+/// Result of linking a ticket to the ticket it duplicates
+#[derive(Debug, Clone)]
+pub enum LinkOutcome {
+    /// Linked to `original`, which is the requested ticket or the ticket it duplicates;
+    /// `moved_dependents` tickets now wait on it instead
+    Linked {
+        original: String,
+        moved_dependents: Vec<String>,
+    },
+    /// The link is not allowed; the reason is meant for the caller
+    Rejected(String),
+}
+
+/// Mark `ticket_id` as a duplicate of `original_id` and move the tickets waiting on it over
+/// to the original, so closing the duplicate neither unblocks nor strands them; queues
+/// `ticket_updated` of the original. Closing the duplicate is left to the caller, which runs
+/// the usual close cascade.
+pub async fn link_duplicate(
+    pool: &DbPool,
+    ticket_id: &str,
+    original_id: &str,
+) -> Result<LinkOutcome> {
+    let lookup = |id: &str| {
+        sqlx::query_as::<_, (String, String, Option<String>)>(
+            "SELECT project_id, state, duplicate_of FROM tickets WHERE ticket_id = ?1",
+        )
+        .bind(id.to_string())
+        .fetch_optional(pool)
+    };
+    let Some((project_id, state, _)) = lookup(ticket_id).await? else {
+        return Ok(LinkOutcome::Rejected(format!(
+            "Ticket '{}' not found",
+            ticket_id
+        )));
+    };
+    let Some((original_project, _, original_of)) = lookup(original_id).await? else {
+        return Ok(LinkOutcome::Rejected(format!(
+            "Ticket '{}' not found",
+            original_id
+        )));
+    };
+    if original_id == ticket_id {
+        return Ok(LinkOutcome::Rejected(
+            "A ticket cannot be a duplicate of itself".to_string(),
+        ));
+    }
+    // Link to the end of a duplicate chain so every duplicate points at a live ticket
+    let original = original_of.unwrap_or_else(|| original_id.to_string());
+    if original == ticket_id {
+        return Ok(LinkOutcome::Rejected(format!(
+            "Ticket '{}' is already a duplicate of '{}'",
+            original_id, ticket_id
+        )));
+    }
+    if original_project != project_id {
+        return Ok(LinkOutcome::Rejected(format!(
+            "Ticket '{}' belongs to project '{}', not '{}'",
+            original_id, original_project, project_id
+        )));
+    }
+    if state == "closed" {
+        return Ok(LinkOutcome::Rejected(format!(
+            "Ticket '{}' is already closed",
+            ticket_id
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("UPDATE tickets SET duplicate_of = ?2 WHERE ticket_id = ?1")
+        .bind(ticket_id)
+        .bind(&original)
+        .execute(&mut *tx)
+        .await?;
+    // Earlier duplicates of this ticket follow it to the original
+    sqlx::query("UPDATE tickets SET duplicate_of = ?2 WHERE duplicate_of = ?1")
+        .bind(ticket_id)
+        .bind(&original)
+        .execute(&mut *tx)
+        .await?;
+    let moved_dependents: Vec<String> = sqlx::query_scalar(
+        r#"
+        DELETE FROM ticket_dependencies
+        WHERE parent_ticket_id = ?1 AND child_ticket_id != ?2
+        RETURNING child_ticket_id
+    "#,
+    )
+    .bind(ticket_id)
+    .bind(&original)
+    .fetch_all(&mut *tx)
+    .await?;
+    for child in &moved_dependents {
+        sqlx::query(
+            "INSERT OR IGNORE INTO ticket_dependencies (parent_ticket_id, child_ticket_id, dependency_type)
+             VALUES (?1, ?2, 'blocks')",
+        )
+        .bind(&original)
+        .bind(child)
+        .execute(&mut *tx)
+        .await?;
+    }
+    OutboxEvent::write(
+        &mut tx,
+        &original,
+        &EventPayload::ticket_updated(&original, &project_id, "duplicate_linked"),
+        None,
+        Some(&format!(
+            "Ticket {} was closed as a duplicate of this ticket",
+            ticket_id
+        )),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(LinkOutcome::Linked {
+        original,
+        moved_dependents,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::dag::TicketDependency;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    #[test]
+    fn test_similarity_of_reworded_titles() {
+        let same = similarity(
+            "WebSocket reconnect fails after server restart",
+            &[],
+            "Websocket reconnects failing after the server restarts",
+            &[],
+        );
+        assert!(same >= DUPLICATE_THRESHOLD, "{}", same);
+        let different = similarity(
+            "WebSocket reconnect fails after server restart",
+            &[],
+            "Add dark mode to the dashboard",
+            &[],
+        );
+        assert!(different < 0.2, "{}", different);
+        assert_eq!(candidate_query("The  -- "), None);
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_in_a_seeded_corpus() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("dupes").create().await.unwrap();
+        let other = fx.project("elsewhere").create().await.unwrap();
+        let mut corpus = Vec::new();
+        for title in [
+            "Login page returns 500 when password is empty",
+            "WebSocket reconnect fails after server restart",
+            "Add dark mode to the dashboard",
+            "Dashboard chart legend overlaps labels",
+            "Upgrade sqlx to the next minor release",
+            "Flaky test in queue consumer shutdown",
+            "Document the backup rotation settings",
+            "Rate limiter ignores per-project overrides",
+        ] {
+            corpus.push(fx.ticket(&project, title).create().await.unwrap());
+        }
+        let tag_ids = Tag::resolve(&pool, &["auth".to_string()], true)
+            .await
+            .unwrap();
+        Tag::set_for_ticket(&pool, &corpus[0].ticket_id, &tag_ids)
+            .await
+            .unwrap();
+        // Same title in another project, and a closed one, never match
+        fx.ticket(&other, "WebSocket reconnect fails after server restart")
+            .create()
+            .await
+            .unwrap();
+        let closed = fx
+            .ticket(&project, "Flaky test in queue consumer shutdown path")
+            .create()
+            .await
+            .unwrap();
+        sqlx::query(
+            "UPDATE tickets SET state = 'closed', closed_at = datetime('now') WHERE ticket_id = ?1",
+        )
+        .bind(&closed.ticket_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let check = |title: &'static str, tags: Vec<String>| {
+            let pool = pool.clone();
+            async move {
+                find_possible_duplicates(&pool, "dupes", title, &tags, None)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let hits = check(
+            "Websocket reconnects failing after the server restarts",
+            vec![],
+        )
+        .await;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].ticket_id, corpus[1].ticket_id);
+
+        let hits = check(
+            "Login page returns 500 on empty password",
+            vec!["auth".to_string()],
+        )
+        .await;
+        assert_eq!(hits.len(), 1, "{:?}", hits);
+        assert_eq!(hits[0].ticket_id, corpus[0].ticket_id);
+        assert_eq!(hits[0].tags, vec!["auth".to_string()]);
+
+        let hits = check("Flaky test in queue consumer shutdown", vec![]).await;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].ticket_id, corpus[5].ticket_id);
+        assert_eq!(hits[0].similarity, 1.0);
+
+        // Sharing a word or two is not enough
+        for title in [
+            "Dashboard loads slowly",
+            "Add a login audit trail",
+            "Server restart script for staging",
+            "Upgrade tokio",
+            "the and of",
+        ] {
+            assert!(check(title, vec![]).await.is_empty(), "{}", title);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_link_duplicate_moves_dependents_to_the_original() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("linking").create().await.unwrap();
+        let original = fx.ticket(&project, "Original").create().await.unwrap();
+        let duplicate = fx.ticket(&project, "Duplicate").create().await.unwrap();
+        let waiting = fx.ticket(&project, "Waiting").create().await.unwrap();
+        TicketDependency::create(&pool, &duplicate.ticket_id, &waiting.ticket_id, "blocks")
+            .await
+            .unwrap();
+
+        let LinkOutcome::Linked {
+            original: linked,
+            moved_dependents,
+        } = link_duplicate(&pool, &duplicate.ticket_id, &original.ticket_id)
+            .await
+            .unwrap()
+        else {
+            panic!("link rejected");
+        };
+        assert_eq!(linked, original.ticket_id);
+        assert_eq!(moved_dependents, vec![waiting.ticket_id.clone()]);
+        assert_eq!(
+            TicketDependency::get_parents(&pool, &waiting.ticket_id)
+                .await
+                .unwrap(),
+            vec![original.ticket_id.clone()]
+        );
+
+        // A later duplicate of the duplicate links to the original
+        let third = fx.ticket(&project, "Third").create().await.unwrap();
+        let LinkOutcome::Linked {
+            original: linked, ..
+        } = link_duplicate(&pool, &third.ticket_id, &duplicate.ticket_id)
+            .await
+            .unwrap()
+        else {
+            panic!("link rejected");
+        };
+        assert_eq!(linked, original.ticket_id);
+        assert!(matches!(
+            link_duplicate(&pool, &original.ticket_id, &original.ticket_id)
+                .await
+                .unwrap(),
+            LinkOutcome::Rejected(_)
+        ));
+    }
+}
@@ -0,0 +1,317 @@
+use anyhow::{bail, Result};
+use serde::Serialize;
+use sqlx::Row;
+use std::collections::BTreeMap;
+use tracing::{info, warn};
+
+use super::DbPool;
+use crate::crypto::{Keyring, KEYS_ENV};
+
+/// Encrypted message fields: table, row id column, content column and key id column
+const SEALED_FIELDS: &[(&str, &str, &str, &str)] = &[
+    ("comments", "id", "content", "content_key_id"),
+    (
+        "agent_requests",
+        "correlation_id",
+        "payload",
+        "payload_key_id",
+    ),
+    (
+        "agent_requests",
+        "correlation_id",
+        "response",
+        "response_key_id",
+    ),
+    ("thread_summaries", "id", "summary", "summary_key_id"),
+];
+
+/// Rows re-encrypted by `rekey`, by field
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RekeyReport {
+    pub comments: u64,
+    pub request_payloads: u64,
+    pub request_responses: u64,
+    pub thread_summaries: u64,
+    pub batches: u64,
+}
+
+/// Number of encrypted fields per key id
+pub async fn key_usage(pool: &DbPool) -> Result<BTreeMap<String, i64>> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        r#"
+        SELECT key_id, COUNT(*) FROM (
+            SELECT content_key_id AS key_id FROM comments WHERE content_key_id IS NOT NULL
+            UNION ALL
+            SELECT payload_key_id FROM agent_requests WHERE payload_key_id IS NOT NULL
+            UNION ALL
+            SELECT response_key_id FROM agent_requests WHERE response_key_id IS NOT NULL
+            UNION ALL
+            SELECT summary_key_id FROM thread_summaries WHERE summary_key_id IS NOT NULL
+        )
+        GROUP BY key_id
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .inspect_err(|e| warn!("Failed to count encrypted message fields: {:?}", e))?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Refuse to go on when stored messages are encrypted with keys that are not configured
+pub async fn verify_keys(pool: &DbPool, keyring: Option<&Keyring>) -> Result<()> {
+    let usage = key_usage(pool).await?;
+    let missing: Vec<String> = usage
+        .iter()
+        .filter(|(key_id, _)| !keyring.is_some_and(|keyring| keyring.contains(key_id)))
+        .map(|(key_id, count)| format!("{} ({} fields)", key_id, count))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    match keyring {
+        None => bail!(
+            "The database holds encrypted messages (keys {}) but no message keys are configured; set {} or --message-key-file",
+            missing.join(", "),
+            KEYS_ENV
+        ),
+        Some(_) => bail!(
+            "The database holds messages encrypted with keys that are not configured: {}. List the old keys after the current one",
+            missing.join(", ")
+        ),
+    }
+}
+
+/// Re-encrypt every message field not sealed with the current key, plaintext ones included,
+/// committing `batch_size` rows at a time so an interrupted run can be resumed
+pub async fn rekey(pool: &DbPool, keyring: &Keyring, batch_size: i64) -> Result<RekeyReport> {
+    let current = keyring.current().id().to_string();
+    let mut report = RekeyReport::default();
+    for &(table, id_column, column, key_column) in SEALED_FIELDS {
+        let mut rekeyed = 0;
+        loop {
+            let mut tx = pool.begin().await?;
+            let rows = sqlx::query(&format!(
+                "SELECT {id}, {column}, {key} FROM {table}
+                 WHERE {column} IS NOT NULL AND {key} IS NOT ?1
+                 ORDER BY {id} LIMIT ?2",
+                id = id_column,
+                column = column,
+                key = key_column,
+                table = table
+            ))
+            .bind(&current)
+            .bind(batch_size.max(1))
+            .fetch_all(&mut *tx)
+            .await?;
+            if rows.is_empty() {
+                break;
+            }
+
+            for row in &rows {
+                let content: String = row.try_get(1)?;
+                let plaintext = match row.try_get::<Option<String>, _>(2)? {
+                    Some(key_id) => keyring.open(&key_id, &content)?,
+                    None => content,
+                };
+                let (sealed, key_id) = keyring.seal(&plaintext)?;
+                let update = format!(
+                    "UPDATE {table} SET {column} = ?1, {key} = ?2 WHERE {id} = ?3",
+                    table = table,
+                    column = column,
+                    key = key_column,
+                    id = id_column
+                );
+                // Bound as stored, so the lookup uses the primary key
+                let update = sqlx::query(&update).bind(sealed).bind(key_id);
+                let update = match row.try_get::<i64, _>(0) {
+                    Ok(id) => update.bind(id),
+                    Err(_) => update.bind(row.try_get::<String, _>(0)?),
+                };
+                update.execute(&mut *tx).await?;
+            }
+            tx.commit().await?;
+
+            rekeyed += rows.len() as u64;
+            report.batches += 1;
+            info!("Re-encrypted {} {}.{} fields", rekeyed, table, column);
+        }
+        match column {
+            "content" => report.comments = rekeyed,
+            "payload" => report.request_payloads = rekeyed,
+            "summary" => report.thread_summaries = rekeyed,
+            _ => report.request_responses = rekeyed,
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{
+        agent_requests::AgentRequest,
+        comments::Comment,
+        thread_summaries::{SummarizeOutcome, ThreadSummary},
+        ticket_search::TicketSearchHit,
+        tickets::TicketFilter,
+    };
+    use crate::test_support::{memory_pool, Fixtures};
+
+    const OLD_KEY: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+    const NEW_KEY: &str = "HyAhIiMkJSYnKCkqKywtLi8wMTIzNDU2Nzg5Ojs8PT4=";
+
+    async fn seal_comment(pool: &DbPool, keyring: &Keyring, id: i64, content: &str) {
+        let (sealed, key_id) = keyring.seal(content).unwrap();
+        sqlx::query("UPDATE comments SET content = ?1, content_key_id = ?2 WHERE id = ?3")
+            .bind(sealed)
+            .bind(key_id)
+            .bind(id)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_keys_verified_and_rotated() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("sealed").create().await.unwrap();
+        let ticket = fx
+            .ticket(&project, "Staging access")
+            .create()
+            .await
+            .unwrap();
+        let secret = Comment::create(
+            &pool,
+            &ticket.ticket_id,
+            Some("coding"),
+            Some("w1"),
+            Some(1),
+            "staging password is hunter2",
+        )
+        .await
+        .unwrap();
+        let plain = Comment::create(&pool, &ticket.ticket_id, None, None, Some(1), "all good")
+            .await
+            .unwrap();
+        let request = AgentRequest::create(&pool, "a", "b", None, r#"{"q":"creds?"}"#, 60)
+            .await
+            .unwrap();
+        let SummarizeOutcome::Created(summary) = ThreadSummary::create(
+            &pool,
+            &ticket.ticket_id,
+            "Password shared in the thread",
+            None,
+            plain.id,
+            "coordinator",
+        )
+        .await
+        .unwrap() else {
+            panic!("summary was rejected");
+        };
+        verify_keys(&pool, None).await.unwrap();
+
+        let old = Keyring::parse(OLD_KEY).unwrap();
+        seal_comment(&pool, &old, secret.id, "staging password is hunter2").await;
+        let (sealed, key_id) = old.seal(r#"{"q":"creds?"}"#).unwrap();
+        sqlx::query(
+            "UPDATE agent_requests SET payload = ?1, payload_key_id = ?2 WHERE correlation_id = ?3",
+        )
+        .bind(sealed)
+        .bind(key_id)
+        .bind(&request.correlation_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let (sealed, key_id) = old.seal("Password shared in the thread").unwrap();
+        sqlx::query("UPDATE thread_summaries SET summary = ?1, summary_key_id = ?2 WHERE id = ?3")
+            .bind(sealed)
+            .bind(key_id)
+            .bind(summary.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Encrypted content is not searchable
+        let filter = TicketFilter {
+            project_id: Some("sealed"),
+            status: None,
+            tag: None,
+            overdue: false,
+            min_reopens: None,
+        };
+        assert!(TicketSearchHit::search(&pool, "hunter2", &filter, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            TicketSearchHit::search(&pool, "good", &filter, 10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // No keys, or only a key that did not seal them, refuses to start
+        let err = verify_keys(&pool, None).await.unwrap_err().to_string();
+        assert!(err.contains("no message keys"), "{}", err);
+        let new_only = Keyring::parse(NEW_KEY).unwrap();
+        let err = verify_keys(&pool, Some(&new_only))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(old.current().id()), "{}", err);
+        verify_keys(&pool, Some(&old)).await.unwrap();
+
+        // Rotation moves everything, plaintext included, under the new key
+        let rotating = Keyring::parse(&format!("{}\n{}", NEW_KEY, OLD_KEY)).unwrap();
+        let report = rekey(&pool, &rotating, 1).await.unwrap();
+        assert_eq!(report.request_payloads, 1);
+        assert_eq!(report.request_responses, 0);
+        assert_eq!(report.thread_summaries, 1);
+        assert!(report.comments >= 2);
+        assert_eq!(
+            report.batches,
+            report.comments
+                + report.request_payloads
+                + report.request_responses
+                + report.thread_summaries
+        );
+        let usage = key_usage(&pool).await.unwrap();
+        assert_eq!(usage.keys().collect::<Vec<_>>(), [new_only.current().id()]);
+        verify_keys(&pool, Some(&new_only)).await.unwrap();
+        assert_eq!(rekey(&pool, &rotating, 10).await.unwrap().batches, 0);
+
+        // Reads decrypt transparently with the configured keys
+        let _keys = crate::crypto::TestKeys::configure(Some(new_only), false);
+        let comments = Comment::get_by_ticket_id(&pool, &ticket.ticket_id)
+            .await
+            .unwrap();
+        let contents: Vec<&str> = comments.iter().map(|c| c.content.as_str()).collect();
+        assert!(contents.contains(&"staging password is hunter2"));
+        assert!(contents.contains(&"all good"));
+        assert!(comments.iter().all(|c| c.content_key_id.is_some()));
+        assert_eq!(
+            Comment::get_by_id(&pool, plain.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .content,
+            "all good"
+        );
+        let request = AgentRequest::get_by_id(&pool, &request.correlation_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(request.payload, r#"{"q":"creds?"}"#);
+        let summary = ThreadSummary::current(&pool, &ticket.ticket_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(summary.summary_key_id.is_some());
+        assert_eq!(summary.summary, "Password shared in the thread");
+
+        pool.close().await;
+    }
+}
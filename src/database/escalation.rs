@@ -0,0 +1,394 @@
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::FromRow;
+use tracing::{error, warn};
+
+use super::{tickets::Priority, DbPool};
+use crate::config::Config;
+
+/// Priorities from lowest to highest, one escalation level apart
+const LEVELS: [Priority; 4] = [
+    Priority::Low,
+    Priority::Medium,
+    Priority::High,
+    Priority::Urgent,
+];
+
+fn level(priority: &Priority) -> usize {
+    LEVELS.iter().position(|p| p == priority).unwrap_or(1)
+}
+
+/// Thresholds of the escalation rules; a threshold of 0 turns its rule off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EscalationRules {
+    /// Hours in the same state before a ticket goes up a level
+    pub age_hours: u64,
+    /// Open tickets blocked per level added
+    pub dependents: u64,
+    /// A ticket due within this many minutes goes up a level; one past due goes up two
+    pub sla_warning_minutes: u64,
+}
+
+impl EscalationRules {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            age_hours: config.escalation_age_hours,
+            dependents: config.escalation_dependents,
+            sla_warning_minutes: config.sla_warning_minutes,
+        }
+    }
+
+    /// Effective priority of a ticket and the reasons it is above `inputs.priority`
+    pub fn evaluate(&self, inputs: &EscalationInputs) -> (Priority, Vec<String>) {
+        let mut levels = 0;
+        let mut reasons = Vec::new();
+
+        if self.age_hours > 0 && inputs.minutes_in_state >= self.age_hours as i64 * 60 {
+            levels += 1;
+            reasons.push(format!(
+                "{} for {} hours",
+                inputs.state.replace('_', " "),
+                inputs.minutes_in_state / 60
+            ));
+        }
+        if self.dependents > 0 && inputs.open_dependents >= self.dependents as i64 {
+            levels += (inputs.open_dependents / self.dependents as i64) as usize;
+            reasons.push(format!("blocks {} open tickets", inputs.open_dependents));
+        }
+        match inputs.due_in_minutes {
+            Some(minutes) if minutes <= 0 => {
+                levels += 2;
+                reasons.push("past due".to_string());
+            }
+            Some(minutes) if minutes <= self.sla_warning_minutes as i64 => {
+                levels += 1;
+                reasons.push(format!("due within {} minutes", self.sla_warning_minutes));
+            }
+            _ => {}
+        }
+
+        let base = level(&inputs.priority);
+        let effective = (base + levels).min(LEVELS.len() - 1);
+        if effective == base {
+            // Nothing to raise, e.g. an urgent ticket, so nothing to explain either
+            return (inputs.priority.clone(), Vec::new());
+        }
+        (LEVELS[effective].clone(), reasons)
+    }
+}
+
+/// What the escalation rules look at for one ticket
+#[derive(Debug, Clone)]
+pub struct EscalationInputs {
+    pub priority: Priority,
+    pub state: String,
+    pub minutes_in_state: i64,
+    /// Open tickets this one blocks
+    pub open_dependents: i64,
+    /// Minutes until the due date, negative once it has passed
+    pub due_in_minutes: Option<i64>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+struct EscalationCandidate {
+    ticket_id: String,
+    project_id: String,
+    short_id: String,
+    title: String,
+    state: String,
+    priority: String,
+    effective_priority: String,
+    escalation_reasons: Option<String>,
+    minutes_in_state: i64,
+    open_dependents: i64,
+    due_in_minutes: Option<i64>,
+}
+
+/// A ticket whose effective priority changed during evaluation
+#[derive(Debug, Clone, Serialize)]
+pub struct EscalationChange {
+    pub ticket_id: String,
+    pub project_id: String,
+    pub short_id: String,
+    pub title: String,
+    pub priority: String,
+    pub from: String,
+    pub to: String,
+    pub reasons: Vec<String>,
+}
+
+impl EscalationChange {
+    pub fn escalated(&self) -> bool {
+        let parse = |p: &str| p.parse().map(|p| level(&p)).unwrap_or(1);
+        parse(&self.to) > parse(&self.from)
+    }
+}
+
+/// Apply the escalation rules to the tickets that are not closed, of one project or all,
+/// storing their effective priority and its reasons. Returns the tickets whose effective
+/// priority went up or down; a ticket falls back to its explicit priority once no rule
+/// applies, e.g. after the tickets it blocked were closed.
+pub async fn reevaluate(
+    pool: &DbPool,
+    rules: &EscalationRules,
+    project_id: Option<&str>,
+) -> Result<Vec<EscalationChange>> {
+    let candidates = sqlx::query_as::<_, EscalationCandidate>(
+        r#"
+        WITH transitions AS (
+            SELECT ticket_id, state, created_at,
+                   LAG(state) OVER (PARTITION BY ticket_id ORDER BY id) AS previous_state
+            FROM ticket_history
+            WHERE ticket_id IN (
+                SELECT ticket_id FROM tickets
+                WHERE state != 'closed' AND (?1 IS NULL OR project_id = ?1)
+            )
+        ),
+        entered AS (
+            SELECT ticket_id, MAX(created_at) AS entered_at FROM transitions
+            WHERE previous_state IS NULL OR previous_state != state
+            GROUP BY ticket_id
+        )
+        SELECT t.ticket_id, t.project_id, t.short_id, t.title, t.state, t.priority,
+               t.effective_priority, t.escalation_reasons,
+               CAST((julianday('now') - julianday(COALESCE(e.entered_at, t.created_at))) * 1440
+                    AS INTEGER) AS minutes_in_state,
+               (SELECT COUNT(*) FROM ticket_dependencies d
+                JOIN tickets c ON c.ticket_id = d.child_ticket_id
+                WHERE d.parent_ticket_id = t.ticket_id AND d.dependency_type = 'blocks'
+                  AND c.state != 'closed') AS open_dependents,
+               CAST((julianday(t.due_at) - julianday('now')) * 1440 AS INTEGER) AS due_in_minutes
+        FROM tickets t
+        LEFT JOIN entered e ON e.ticket_id = t.ticket_id
+        WHERE t.state != 'closed' AND (?1 IS NULL OR t.project_id = ?1)
+    "#,
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await
+    .inspect_err(|e| warn!("Failed to load tickets for escalation: {:?}", e))?;
+
+    let mut changes = Vec::new();
+    let mut tx = pool.begin().await?;
+    for candidate in candidates {
+        let Ok(priority) = candidate.priority.parse::<Priority>() else {
+            continue;
+        };
+        let (effective, reasons) = rules.evaluate(&EscalationInputs {
+            priority,
+            state: candidate.state.clone(),
+            minutes_in_state: candidate.minutes_in_state,
+            open_dependents: candidate.open_dependents,
+            due_in_minutes: candidate.due_in_minutes,
+        });
+        let effective = effective.to_string();
+        let reasons_json = (!reasons.is_empty())
+            .then(|| serde_json::to_string(&reasons))
+            .transpose()?;
+        if effective == candidate.effective_priority && reasons_json == candidate.escalation_reasons
+        {
+            continue;
+        }
+
+        let change = EscalationChange {
+            ticket_id: candidate.ticket_id,
+            project_id: candidate.project_id,
+            short_id: candidate.short_id,
+            title: candidate.title,
+            priority: candidate.priority,
+            from: candidate.effective_priority,
+            to: effective,
+            reasons,
+        };
+        sqlx::query(
+            r#"
+            UPDATE tickets
+            SET effective_priority = ?2, escalation_reasons = ?3,
+                escalated_at = CASE WHEN ?4 THEN datetime('now') ELSE escalated_at END
+            WHERE ticket_id = ?1
+        "#,
+        )
+        .bind(&change.ticket_id)
+        .bind(&change.to)
+        .bind(&reasons_json)
+        .bind(change.escalated())
+        .execute(&mut *tx)
+        .await
+        .inspect_err(|e| {
+            error!(
+                "Failed to store effective priority of {}: {:?}",
+                change.ticket_id, e
+            )
+        })?;
+        // Only changes of level are reported; new reasons for the same level are just stored
+        if change.from != change.to {
+            changes.push(change);
+        }
+    }
+    tx.commit().await?;
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{dag::TicketDependency, tickets::Ticket};
+    use crate::test_support::{memory_pool, Fixtures};
+
+    const RULES: EscalationRules = EscalationRules {
+        age_hours: 72,
+        dependents: 2,
+        sla_warning_minutes: 30,
+    };
+
+    async fn load(pool: &DbPool, ticket_id: &str) -> Ticket {
+        Ticket::get_by_id(pool, ticket_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .ticket
+    }
+
+    fn inputs(priority: Priority) -> EscalationInputs {
+        EscalationInputs {
+            priority,
+            state: "open".to_string(),
+            minutes_in_state: 10,
+            open_dependents: 0,
+            due_in_minutes: None,
+        }
+    }
+
+    #[test]
+    fn test_each_rule_raises_the_priority() {
+        assert_eq!(
+            RULES.evaluate(&inputs(Priority::Medium)),
+            (Priority::Medium, vec![])
+        );
+
+        let stale = EscalationInputs {
+            minutes_in_state: 80 * 60,
+            ..inputs(Priority::Low)
+        };
+        assert_eq!(
+            RULES.evaluate(&stale),
+            (Priority::Medium, vec!["open for 80 hours".to_string()])
+        );
+
+        let blocking = EscalationInputs {
+            open_dependents: 5,
+            ..inputs(Priority::Medium)
+        };
+        assert_eq!(
+            RULES.evaluate(&blocking),
+            (Priority::Urgent, vec!["blocks 5 open tickets".to_string()])
+        );
+
+        let due_soon = EscalationInputs {
+            due_in_minutes: Some(20),
+            ..inputs(Priority::Medium)
+        };
+        assert_eq!(RULES.evaluate(&due_soon).0, Priority::High);
+        let overdue = EscalationInputs {
+            due_in_minutes: Some(-5),
+            ..inputs(Priority::Low)
+        };
+        assert_eq!(
+            RULES.evaluate(&overdue),
+            (Priority::High, vec!["past due".to_string()])
+        );
+
+        // Already at the top, and rules that are off do nothing
+        assert_eq!(
+            RULES.evaluate(&EscalationInputs {
+                open_dependents: 5,
+                ..inputs(Priority::Urgent)
+            }),
+            (Priority::Urgent, vec![])
+        );
+        let off = EscalationRules {
+            age_hours: 0,
+            dependents: 0,
+            sla_warning_minutes: 0,
+        };
+        assert_eq!(off.evaluate(&blocking).0, Priority::Medium);
+        assert_eq!(off.evaluate(&overdue).0, Priority::High);
+    }
+
+    #[tokio::test]
+    async fn test_blocker_escalates_and_falls_back_when_blocked_tickets_close() {
+        // Fixture tickets are created days ago, so leave the age rule out
+        let rules = EscalationRules {
+            age_hours: 0,
+            ..RULES
+        };
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("escalation").create().await.unwrap();
+        fx.worker_type(&project, "coding").create().await.unwrap();
+        let blocker = fx.ticket(&project, "Schema change").create().await.unwrap();
+        assert_eq!(blocker.effective_priority, "medium");
+
+        let mut blocked = Vec::new();
+        for n in 0..2 {
+            let ticket = fx
+                .ticket(&project, &format!("Needs schema {}", n))
+                .create()
+                .await
+                .unwrap();
+            TicketDependency::create(&pool, &blocker.ticket_id, &ticket.ticket_id, "blocks")
+                .await
+                .unwrap();
+            blocked.push(ticket);
+        }
+
+        let changes = reevaluate(&pool, &rules, Some(&project.repository_name))
+            .await
+            .unwrap();
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].escalated());
+        assert_eq!(
+            (changes[0].from.as_str(), changes[0].to.as_str()),
+            ("medium", "high")
+        );
+        let ticket = load(&pool, &blocker.ticket_id).await;
+        assert_eq!(ticket.priority, "medium");
+        assert_eq!(ticket.effective_priority, "high");
+        assert_eq!(
+            ticket.escalation_reasons.as_deref(),
+            Some(r#"["blocks 2 open tickets"]"#)
+        );
+
+        // Nothing changed, so nothing is reported again
+        assert!(reevaluate(&pool, &rules, None).await.unwrap().is_empty());
+
+        // Raising the explicit priority past the escalation replaces it
+        Ticket::update_priority(&pool, &blocker.ticket_id, "urgent")
+            .await
+            .unwrap();
+        let ticket = load(&pool, &blocker.ticket_id).await;
+        assert_eq!(ticket.effective_priority, "urgent");
+        assert!(ticket.escalation_reasons.is_none());
+        Ticket::update_priority(&pool, &blocker.ticket_id, "medium")
+            .await
+            .unwrap();
+        reevaluate(&pool, &rules, None).await.unwrap();
+
+        // Closing a blocked ticket clears the blocker rule; it falls back, never below medium
+        sqlx::query("UPDATE tickets SET state = 'closed' WHERE ticket_id = ?1")
+            .bind(&blocked[0].ticket_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let changes = reevaluate(&pool, &rules, None).await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert!(!changes[0].escalated());
+        assert_eq!(changes[0].to, "medium");
+        let ticket = load(&pool, &blocker.ticket_id).await;
+        assert_eq!(ticket.effective_priority, "medium");
+        assert!(ticket.escalation_reasons.is_none());
+
+        pool.close().await;
+    }
+}
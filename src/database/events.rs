@@ -1,9 +1,9 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{FromRow, SqliteConnection};
 use tracing::{error, warn};
 
-use super::DbPool;
+use super::{returning::FetchReturning, DbPool};
 use crate::events::EventType;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -27,6 +27,26 @@ impl Event {
         worker_id: Option<&str>,
         stage: Option<&str>,
         reason: Option<&str>,
+    ) -> Result<Event> {
+        Self::create_in(
+            &mut *pool.acquire().await?,
+            event_type,
+            ticket_id,
+            worker_id,
+            stage,
+            reason,
+        )
+        .await
+    }
+
+    /// Like `create`, on the caller's connection or transaction
+    pub async fn create_in(
+        conn: &mut SqliteConnection,
+        event_type: EventType,
+        ticket_id: Option<&str>,
+        worker_id: Option<&str>,
+        stage: Option<&str>,
+        reason: Option<&str>,
     ) -> Result<Event> {
         let event = sqlx::query_as::<_, Event>(
             r#"
@@ -40,7 +60,7 @@ impl Event {
         .bind(worker_id)
         .bind(stage)
         .bind(reason)
-        .fetch_one(pool)
+        .fetch_returned_one(&mut *conn)
         .await
         .inspect_err(|e| error!("Failed to create event of type '{}': {:?}", event_type, e))?;
 
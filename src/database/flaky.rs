@@ -0,0 +1,154 @@
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::FromRow;
+use tracing::error;
+
+use super::{outbox::OutboxEvent, DbPool};
+use crate::events::EventPayload;
+
+/// Tag given to tickets that keep being reopened
+pub const FLAKY_TAG: &str = "flaky";
+
+/// Ticket that reached the reopen threshold during evaluation
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FlakyTicket {
+    pub ticket_id: String,
+    pub project_id: String,
+    pub title: String,
+    pub reopen_count: i64,
+}
+
+impl FlakyTicket {
+    /// Tag tickets reopened at least `threshold` times as flaky and queue `ticket_flaky` for
+    /// each; each ticket is returned once
+    pub async fn mark(pool: &DbPool, threshold: u32) -> Result<Vec<FlakyTicket>> {
+        let mut tx = pool.begin().await?;
+        let flaky = sqlx::query_as::<_, FlakyTicket>(
+            r#"
+            UPDATE tickets
+            SET flaky_at = datetime('now')
+            WHERE reopen_count >= ?1 AND flaky_at IS NULL
+            RETURNING ticket_id, project_id, title, reopen_count
+        "#,
+        )
+        .bind(threshold)
+        .fetch_all(&mut *tx)
+        .await
+        .inspect_err(|e| error!("Failed to mark flaky tickets: {:?}", e))?;
+        if flaky.is_empty() {
+            return Ok(flaky);
+        }
+
+        sqlx::query("INSERT OR IGNORE INTO tags (name) VALUES (?1)")
+            .bind(FLAKY_TAG)
+            .execute(&mut *tx)
+            .await?;
+        for ticket in &flaky {
+            sqlx::query(
+                "INSERT OR IGNORE INTO ticket_tags (ticket_id, tag_id)
+                 SELECT ?1, id FROM tags WHERE name = ?2",
+            )
+            .bind(&ticket.ticket_id)
+            .bind(FLAKY_TAG)
+            .execute(&mut *tx)
+            .await?;
+            OutboxEvent::write(
+                &mut tx,
+                &ticket.ticket_id,
+                &EventPayload::ticket_flaky(&ticket.ticket_id, &ticket.project_id),
+                None,
+                Some(&format!(
+                    "Ticket reopened {} times; it was tagged '{}'",
+                    ticket.reopen_count, FLAKY_TAG
+                )),
+            )
+            .await?;
+        }
+        tx.commit()
+            .await
+            .inspect_err(|e| error!("Failed to tag flaky tickets: {:?}", e))?;
+        Ok(flaky)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{
+        pipeline::{transition_pipeline, PipelineTransitionRequest},
+        tags::Tag,
+        tickets::{Ticket, TicketFilter},
+    };
+    use crate::test_support::{memory_pool, Fixtures};
+
+    #[tokio::test]
+    async fn test_reopens_are_counted_and_flagged_once() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("reopens").create().await.unwrap();
+        fx.worker_type(&project, "coding").create().await.unwrap();
+        let ticket = fx.ticket(&project, "Bouncing").create().await.unwrap();
+        let steady = fx.ticket(&project, "Steady").create().await.unwrap();
+        assert_eq!(ticket.reopen_count, 0);
+
+        // Closing again or holding an open ticket is not a reopen
+        let reopen = || async {
+            Ticket::update_state(&pool, &ticket.ticket_id, "closed")
+                .await
+                .unwrap();
+            Ticket::update_state(&pool, &ticket.ticket_id, "closed")
+                .await
+                .unwrap();
+            Ticket::update_state(&pool, &ticket.ticket_id, "open")
+                .await
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(reopen().await.reopen_count, 1);
+        Ticket::update_state(&pool, &ticket.ticket_id, "on_hold")
+            .await
+            .unwrap();
+        assert_eq!(reopen().await.reopen_count, 2);
+        assert!(FlakyTicket::mark(&pool, 3).await.unwrap().is_empty());
+
+        // Reopening through a pipeline transition counts as well
+        Ticket::update_state(&pool, &ticket.ticket_id, "closed")
+            .await
+            .unwrap();
+        let transition = transition_pipeline(
+            &pool,
+            PipelineTransitionRequest {
+                source_ticket_id: steady.ticket_id.clone(),
+                resolution: "Done".to_string(),
+                next_ticket_id: Some(ticket.ticket_id.clone()),
+                stage: "coding".to_string(),
+                title: None,
+                description: None,
+                priority: None,
+                custom_fields: Default::default(),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(transition.next.reopen_count, 3);
+
+        let flaky = FlakyTicket::mark(&pool, 3).await.unwrap();
+        assert_eq!(flaky.len(), 1);
+        assert_eq!(flaky[0].ticket_id, ticket.ticket_id);
+        assert!(FlakyTicket::mark(&pool, 3).await.unwrap().is_empty());
+        let tags = Tag::names_for_tickets(&pool, &[&ticket.ticket_id])
+            .await
+            .unwrap();
+        assert_eq!(tags[&ticket.ticket_id], [FLAKY_TAG]);
+
+        let filter = TicketFilter {
+            project_id: Some("reopens"),
+            min_reopens: Some(1),
+            ..Default::default()
+        };
+        let reopened = Ticket::list_page(&pool, &filter, None, -1).await.unwrap();
+        assert_eq!(reopened.len(), 1);
+
+        pool.close().await;
+    }
+}
@@ -1,12 +1,35 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use serde::Serialize;
+use sqlx::migrate::Migrator;
 use sqlx::sqlite::SqlitePool;
-use tracing::info;
+use std::{collections::HashMap, time::Duration};
+use tracing::{info, warn};
 
-/// Run database migrations using sqlx::migrate!() macro
+use crate::server_lock::process_alive;
+
+/// Migrations bundled with this build
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// How long to wait for another instance to finish migrating the same database
+pub const MIGRATION_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A migration lock this old was left by an instance that died mid-migration
+const MIGRATION_LOCK_STALE_SECS: i64 = 600;
+
+/// Run database migrations, refusing databases whose schema is newer than this build.
+/// Holds the migration lock throughout, so instances sharing a database take turns.
 pub async fn run_migrations(pool: &SqlitePool) -> Result<()> {
+    let lock = MigrationLock::acquire(pool, MIGRATION_LOCK_TIMEOUT).await?;
+    let result = apply_migrations(pool).await;
+    lock.release().await?;
+    result
+}
+
+async fn apply_migrations(pool: &SqlitePool) -> Result<()> {
+    check_not_newer(pool).await?;
     info!("Running database migrations using sqlx::migrate!()");
 
-    sqlx::migrate!("./migrations")
+    MIGRATOR
         .run(pool)
         .await
         .map_err(|e| anyhow::anyhow!("Migration failed: {}", e))?;
@@ -14,3 +37,427 @@ pub async fn run_migrations(pool: &SqlitePool) -> Result<()> {
     info!("Database migrations completed successfully");
     Ok(())
 }
+
+/// Advisory lock on migrating a database: the single row of `_migration_lock`. SQLite
+/// migrations take no lock of their own, and two instances applying them at once can
+/// leave `_sqlx_migrations` inconsistent. Dropping the guard without `release` still
+/// frees the lock in the background.
+pub struct MigrationLock {
+    pool: SqlitePool,
+    token: String,
+    released: bool,
+}
+
+impl MigrationLock {
+    /// Wait up to `timeout` for the lock. Locks of processes that are no longer running
+    /// or that are older than ten minutes are taken over.
+    pub async fn acquire(pool: &SqlitePool, timeout: Duration) -> Result<MigrationLock> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS _migration_lock (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                token TEXT NOT NULL,
+                pid INTEGER NOT NULL,
+                acquired_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        let token = uuid::Uuid::new_v4().to_string();
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let inserted = sqlx::query(
+                "INSERT OR IGNORE INTO _migration_lock (id, token, pid) VALUES (1, ?1, ?2)",
+            )
+            .bind(&token)
+            .bind(std::process::id() as i64)
+            .execute(pool)
+            .await?
+            .rows_affected();
+            if inserted == 1 {
+                return Ok(MigrationLock {
+                    pool: pool.clone(),
+                    token,
+                    released: false,
+                });
+            }
+
+            let holder: Option<(String, i64, String, bool)> = sqlx::query_as(
+                "SELECT token, pid, acquired_at,
+                        acquired_at < datetime('now', '-' || ?1 || ' seconds')
+                 FROM _migration_lock WHERE id = 1",
+            )
+            .bind(MIGRATION_LOCK_STALE_SECS)
+            .fetch_optional(pool)
+            .await?;
+            let Some((holder_token, pid, acquired_at, expired)) = holder else {
+                continue;
+            };
+            if expired || !process_alive(pid as u32) {
+                warn!(
+                    "Taking over the migration lock held by PID {} since {}",
+                    pid, acquired_at
+                );
+                sqlx::query("DELETE FROM _migration_lock WHERE id = 1 AND token = ?1")
+                    .bind(&holder_token)
+                    .execute(pool)
+                    .await?;
+                continue;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                bail!(
+                    "Another instance (PID {}) has been migrating this database since {}; \
+                     start again once it has finished",
+                    pid,
+                    acquired_at
+                );
+            }
+            info!("Waiting for PID {} to finish migrating the database", pid);
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+    }
+
+    pub async fn release(mut self) -> Result<()> {
+        self.released = true;
+        Self::delete(&self.pool, &self.token).await
+    }
+
+    async fn delete(pool: &SqlitePool, token: &str) -> Result<()> {
+        sqlx::query("DELETE FROM _migration_lock WHERE token = ?1")
+            .bind(token)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+impl Drop for MigrationLock {
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+        let (pool, token) = (self.pool.clone(), std::mem::take(&mut self.token));
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move {
+                    if let Err(e) = Self::delete(&pool, &token).await {
+                        warn!("Failed to release the migration lock: {}", e);
+                    }
+                });
+            }
+            Err(_) => warn!("Migration lock not released; it expires once this process exits"),
+        }
+    }
+}
+
+/// Version of the newest migration bundled with this build
+pub fn latest_migration_version() -> i64 {
+    MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the file bundled with this build has changed since
+    ChecksumMismatch,
+    /// Applied by a newer build; this build does not know it
+    Unknown,
+}
+
+impl MigrationState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MigrationState::Applied => "applied",
+            MigrationState::Pending => "pending",
+            MigrationState::ChecksumMismatch => "checksum mismatch",
+            MigrationState::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    /// Hex SHA-384 of the migration SQL, as recorded by sqlx
+    pub checksum: String,
+    pub installed_on: Option<String>,
+    pub state: MigrationState,
+}
+
+#[derive(sqlx::FromRow)]
+struct AppliedMigration {
+    version: i64,
+    description: String,
+    checksum: Vec<u8>,
+    installed_on: String,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Migrations recorded in the database; empty when it has never been migrated
+async fn applied_migrations(pool: &SqlitePool) -> Result<Vec<AppliedMigration>> {
+    let has_table: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+    )
+    .fetch_one(pool)
+    .await?;
+    if !has_table {
+        return Ok(Vec::new());
+    }
+
+    let applied = sqlx::query_as::<_, AppliedMigration>(
+        r#"
+        SELECT version, description, checksum, CAST(installed_on AS TEXT) AS installed_on
+        FROM _sqlx_migrations
+        WHERE success = 1
+        ORDER BY version
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(applied)
+}
+
+/// Every bundled migration and every migration the database has applied, by version
+pub async fn migration_status(pool: &SqlitePool) -> Result<Vec<MigrationStatus>> {
+    let mut applied: HashMap<i64, AppliedMigration> = applied_migrations(pool)
+        .await?
+        .into_iter()
+        .map(|m| (m.version, m))
+        .collect();
+
+    let mut statuses: Vec<MigrationStatus> = MIGRATOR
+        .iter()
+        .map(|migration| {
+            let record = applied.remove(&migration.version);
+            let state = match &record {
+                None => MigrationState::Pending,
+                Some(record) if record.checksum != *migration.checksum => {
+                    MigrationState::ChecksumMismatch
+                }
+                Some(_) => MigrationState::Applied,
+            };
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                checksum: hex(&migration.checksum),
+                installed_on: record.map(|r| r.installed_on),
+                state,
+            }
+        })
+        .collect();
+
+    // Whatever is left was applied by a build with more migrations than this one
+    statuses.extend(applied.into_values().map(|record| MigrationStatus {
+        version: record.version,
+        description: record.description,
+        checksum: hex(&record.checksum),
+        installed_on: Some(record.installed_on),
+        state: MigrationState::Unknown,
+    }));
+    statuses.sort_by_key(|status| status.version);
+    Ok(statuses)
+}
+
+/// Fail when the database has migrations this build does not know, which means it was
+/// written by a newer version; running against it could corrupt data
+pub async fn check_not_newer(pool: &SqlitePool) -> Result<()> {
+    let unknown: Vec<i64> = migration_status(pool)
+        .await?
+        .into_iter()
+        .filter(|status| status.state == MigrationState::Unknown)
+        .map(|status| status.version)
+        .collect();
+    if let Some(newest) = unknown.iter().max() {
+        bail!(
+            "Database schema version {} is newer than this build supports ({}); \
+             upgrade vibe-ensemble-mcp or restore a backup taken with this version",
+            newest,
+            latest_migration_version()
+        );
+    }
+    Ok(())
+}
+
+/// Fail unless every bundled migration is applied unchanged, for starting with `--no-migrate`
+pub async fn require_up_to_date(pool: &SqlitePool) -> Result<()> {
+    check_not_newer(pool).await?;
+    let statuses = migration_status(pool).await?;
+    let pending = statuses
+        .iter()
+        .filter(|status| status.state == MigrationState::Pending)
+        .count();
+    if pending > 0 {
+        bail!(
+            "{} pending database migration(s); run `vibe-ensemble-mcp migrate run` or start without --no-migrate",
+            pending
+        );
+    }
+    if let Some(changed) = statuses
+        .iter()
+        .find(|status| status.state == MigrationState::ChecksumMismatch)
+    {
+        bail!(
+            "Migration {} ({}) differs from the one applied to the database",
+            changed.version,
+            changed.description
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Executor;
+
+    #[tokio::test]
+    async fn test_status_and_newer_schema_refused() {
+        let dir = std::env::temp_dir().join(format!("vibe-migrations-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let url = format!("sqlite:{}?mode=rwc", dir.join("t.db").display());
+
+        let pool = crate::database::open_pool(&url).await.unwrap();
+        let statuses = migration_status(&pool).await.unwrap();
+        assert_eq!(statuses.len(), MIGRATOR.iter().count());
+        assert!(statuses
+            .iter()
+            .all(|s| s.state == MigrationState::Pending && s.installed_on.is_none()));
+        assert!(require_up_to_date(&pool).await.is_err());
+
+        run_migrations(&pool).await.unwrap();
+        let statuses = migration_status(&pool).await.unwrap();
+        assert!(statuses.iter().all(|s| s.state == MigrationState::Applied));
+        assert_eq!(statuses[0].checksum.len(), 96);
+        require_up_to_date(&pool).await.unwrap();
+
+        // A migration from a newer build blocks startup instead of failing mid-query
+        pool.execute(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+             VALUES (99999, 'from the future', 1, x'00', 0)",
+        )
+        .await
+        .unwrap();
+        let statuses = migration_status(&pool).await.unwrap();
+        assert_eq!(statuses.last().unwrap().state, MigrationState::Unknown);
+        let error = run_migrations(&pool).await.unwrap_err().to_string();
+        assert!(error.contains("99999 is newer than this build supports"));
+
+        pool.close().await;
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_migration_lock_is_exclusive_and_released() {
+        let pool = crate::test_support::memory_pool().await.unwrap();
+        let short = Duration::from_millis(300);
+
+        let lock = MigrationLock::acquire(&pool, short).await.unwrap();
+        let error = MigrationLock::acquire(&pool, short)
+            .await
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains(&format!("PID {}", std::process::id())));
+        lock.release().await.unwrap();
+
+        // Dropped without release, e.g. when a migration panics
+        drop(MigrationLock::acquire(&pool, short).await.unwrap());
+        MigrationLock::acquire(&pool, Duration::from_secs(2))
+            .await
+            .unwrap()
+            .release()
+            .await
+            .unwrap();
+
+        // Left by a process that died mid-migration
+        sqlx::query("INSERT INTO _migration_lock (id, token, pid) VALUES (1, 'gone', ?1)")
+            .bind(i32::MAX)
+            .execute(&pool)
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_project_scoping_backfills_existing_requests() {
+        let dir = std::env::temp_dir().join(format!("vibe-migrations-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let url = format!("sqlite:{}?mode=rwc", dir.join("t.db").display());
+        let pool = crate::database::open_pool(&url).await.unwrap();
+        for migration in MIGRATOR.iter().filter(|m| m.version < 42) {
+            pool.execute(&*migration.sql).await.unwrap();
+        }
+
+        // Requests stored before 042, one for each source of their project
+        pool.execute(
+            r#"
+            INSERT INTO projects (repository_name, path) VALUES ('web', '/web'), ('api', '/api');
+            INSERT INTO tickets (ticket_id, project_id, title, execution_plan)
+            VALUES ('WEB-1', 'web', 'Login', '[]');
+            INSERT INTO workers (worker_id, project_id, worker_type, status, queue_name)
+            VALUES ('w-web', 'web', 'coder', 'active', 'q'),
+                   ('w-api', 'api', 'coder', 'active', 'q');
+            INSERT INTO agent_requests (correlation_id, requester, target, ticket_id, payload,
+                                        deadline, payload_key_id)
+            VALUES ('by-ticket', 'w-api', 'w-api', 'WEB-1', '{"project_id": "api"}',
+                    '2026-01-01', NULL),
+                   ('by-payload', 'w-web', 'coordinator', NULL, '{"project_id": "api"}',
+                    '2026-01-01', NULL),
+                   ('sealed-payload', 'coordinator', 'w-web', NULL, '{"project_id": "api"}',
+                    '2026-01-01', 'k1'),
+                   ('unknown-project', 'w-api', 'coordinator', NULL, '{"project_id": "gone"}',
+                    '2026-01-01', NULL),
+                   ('not-an-object', 'coordinator', 'w-web', NULL, '["api"]',
+                    '2026-01-01', NULL),
+                   ('by-requester', 'w-api', 'w-web', NULL, '{}', '2026-01-01', NULL),
+                   ('unresolved', 'coordinator', 'gone', 'GONE-1', 'not json',
+                    '2026-01-01', NULL);
+            "#,
+        )
+        .await
+        .unwrap();
+
+        let scoping = MIGRATOR.iter().find(|m| m.version == 42).unwrap();
+        pool.execute(&*scoping.sql).await.unwrap();
+
+        let backfilled: HashMap<String, Option<String>> =
+            sqlx::query_as("SELECT correlation_id, project_id FROM agent_requests")
+                .fetch_all(&pool)
+                .await
+                .unwrap()
+                .into_iter()
+                .collect();
+        let expected = [
+            ("by-ticket", Some("web")),
+            ("by-payload", Some("api")),
+            // The payload of a sealed request cannot be read; the target's project is used
+            ("sealed-payload", Some("web")),
+            ("unknown-project", Some("api")),
+            ("not-an-object", Some("web")),
+            ("by-requester", Some("api")),
+            ("unresolved", None),
+        ];
+        assert_eq!(backfilled.len(), expected.len());
+        for (correlation_id, project_id) in expected {
+            assert_eq!(
+                backfilled[correlation_id].as_deref(),
+                project_id,
+                "{}",
+                correlation_id
+            );
+        }
+
+        pool.close().await;
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,11 +1,48 @@
+pub mod agent_groups;
+pub mod agent_requests;
+pub mod attachments;
+pub mod audit;
+pub mod blocks;
+pub mod board;
+pub mod burndown;
+pub mod cache;
 pub mod comments;
+pub mod coordination_timeline;
 pub mod dag;
+pub mod digests;
+pub mod duplicates;
+pub mod encryption;
+pub mod escalation;
 pub mod events;
+pub mod flaky;
 pub mod migrations;
+pub mod outbox;
+pub mod pipeline;
+pub mod project_settings;
 pub mod projects;
+pub mod quotas;
 pub mod recovery;
+pub mod relations;
+pub mod returning;
+pub mod routing;
+pub mod schedules;
 pub mod schema;
+pub mod sla;
+pub mod stats;
+pub mod tags;
+pub mod thread_summaries;
+pub mod throughput;
+pub mod ticket_refs;
+pub mod ticket_search;
+pub mod ticket_templates;
+pub mod ticket_timeline;
 pub mod tickets;
+pub mod timing;
+pub mod update_checks;
+pub mod webhooks;
+pub mod worker_load;
+pub mod worker_metrics;
+pub mod worker_runs;
 pub mod worker_types;
 pub mod workers;
 
@@ -61,7 +98,18 @@ pub fn get_project_logs_dir(database_path: &str, project_name: &str) -> Result<S
     }
 }
 
+/// Connect and bring the schema up to date
 pub async fn create_pool(database_url: &str) -> Result<DbPool> {
+    let pool = open_pool(database_url).await?;
+
+    info!("Running database migrations");
+    migrations::run_migrations(&pool).await?;
+
+    Ok(pool)
+}
+
+/// Connect without touching the schema, for migration introspection and `--no-migrate`
+pub async fn open_pool(database_url: &str) -> Result<DbPool> {
     info!("Connecting to SQLite database");
 
     // Ensure directory structure exists
@@ -73,9 +121,6 @@ pub async fn create_pool(database_url: &str) -> Result<DbPool> {
         .busy_timeout(Duration::from_secs(5));
     let pool = SqlitePoolOptions::new().connect_with(connect_opts).await?;
 
-    info!("Running database migrations");
-    migrations::run_migrations(&pool).await?;
-
     Ok(pool)
 }
 
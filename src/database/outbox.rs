@@ -0,0 +1,230 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection};
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use super::{returning::FetchReturning, DbPool};
+use crate::events::{EventData, EventPayload};
+
+const OUTBOX_COLUMNS: &str = "id, event_type, entity_id, payload, ticket_id, worker_id, stage, \
+     reason, status, attempts, last_error, next_attempt_at, created_at, dispatched_at";
+
+/// An event queued by a domain change, published once that change is committed
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct OutboxEvent {
+    pub id: i64,
+    pub event_type: String,
+    /// Events of the same entity are dispatched in order
+    pub entity_id: String,
+    pub payload: String, // EventPayload JSON
+    pub ticket_id: Option<String>,
+    pub worker_id: Option<String>,
+    pub stage: Option<String>,
+    pub reason: Option<String>,
+    /// pending, dispatched or dead
+    pub status: String,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    pub next_attempt_at: String,
+    pub created_at: String,
+    pub dispatched_at: Option<String>,
+}
+
+/// How far the dispatcher is behind
+#[derive(Debug, Clone, Default, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct OutboxBacklog {
+    pub pending: i64,
+    pub dead: i64,
+    /// Seconds the oldest pending event has waited; 0 when none is pending
+    pub oldest_pending_secs: i64,
+}
+
+impl OutboxEvent {
+    /// Queue `event` for `entity_id` in the caller's transaction. `stage` and `reason` go
+    /// into the `events` row written when it is dispatched.
+    pub async fn write(
+        conn: &mut SqliteConnection,
+        entity_id: &str,
+        event: &EventPayload,
+        stage: Option<&str>,
+        reason: Option<&str>,
+    ) -> Result<i64> {
+        let (ticket_id, worker_id) = match &event.data {
+            EventData::Ticket(data) => (Some(data.ticket_id.as_str()), None),
+            EventData::Worker(data) => (None, Some(data.worker_id.as_str())),
+            _ => (None, None),
+        };
+        let id = sqlx::query(
+            r#"
+            INSERT INTO event_outbox (event_type, entity_id, payload, ticket_id, worker_id, stage, reason)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+        )
+        .bind(event.event_type.to_string())
+        .bind(entity_id)
+        .bind(serde_json::to_string(event)?)
+        .bind(ticket_id)
+        .bind(worker_id)
+        .bind(stage)
+        .bind(reason)
+        .execute(&mut *conn)
+        .await
+        .inspect_err(|e| {
+            error!(
+                "Failed to queue '{}' for {} in the outbox: {:?}",
+                event.event_type, entity_id, e
+            )
+        })?
+        .last_insert_rowid();
+
+        Ok(id)
+    }
+
+    /// Pending events whose next attempt is due and that no earlier pending event of the
+    /// same entity holds back, oldest first
+    pub async fn due(pool: &DbPool, limit: i64) -> Result<Vec<OutboxEvent>> {
+        let events = sqlx::query_as::<_, OutboxEvent>(&format!(
+            r#"
+            SELECT {} FROM event_outbox o
+            WHERE status = 'pending' AND next_attempt_at <= datetime('now')
+              AND NOT EXISTS (
+                  SELECT 1 FROM event_outbox earlier
+                  WHERE earlier.entity_id = o.entity_id AND earlier.status = 'pending'
+                    AND earlier.id < o.id
+              )
+            ORDER BY id ASC
+            LIMIT ?1
+            "#,
+            OUTBOX_COLUMNS
+        ))
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to fetch due outbox events: {:?}", e))?;
+
+        Ok(events)
+    }
+
+    pub fn payload(&self) -> Result<EventPayload> {
+        Ok(serde_json::from_str(&self.payload)?)
+    }
+
+    /// Mark the event dispatched in the transaction that published it; false when it was
+    /// no longer pending
+    pub async fn mark_dispatched(conn: &mut SqliteConnection, id: i64) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE event_outbox
+            SET status = 'dispatched', attempts = attempts + 1, last_error = NULL,
+                dispatched_at = datetime('now')
+            WHERE id = ?1 AND status = 'pending'
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *conn)
+        .await
+        .inspect_err(|e| error!("Failed to mark outbox event {} dispatched: {:?}", id, e))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record a failed dispatch. The event is tried again after `retry_in_secs`, or is
+    /// dead when that is None.
+    pub async fn record_failure(
+        pool: &DbPool,
+        id: i64,
+        error: &str,
+        retry_in_secs: Option<u64>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE event_outbox
+            SET attempts = attempts + 1, last_error = ?2,
+                status = CASE WHEN ?3 IS NULL THEN 'dead' ELSE 'pending' END,
+                next_attempt_at = datetime('now', '+' || COALESCE(?3, 0) || ' seconds')
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .bind(error)
+        .bind(retry_in_secs.map(|secs| secs as i64))
+        .execute(pool)
+        .await
+        .inspect_err(|e| error!("Failed to record outbox event {} failure: {:?}", id, e))?;
+
+        Ok(())
+    }
+
+    pub async fn get(pool: &DbPool, id: i64) -> Result<Option<OutboxEvent>> {
+        let event = sqlx::query_as::<_, OutboxEvent>(&format!(
+            "SELECT {} FROM event_outbox WHERE id = ?1",
+            OUTBOX_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to fetch outbox event {}: {:?}", id, e))?;
+
+        Ok(event)
+    }
+
+    /// Outbox events, most recent first
+    pub async fn list(pool: &DbPool, status: Option<&str>, limit: i64) -> Result<Vec<OutboxEvent>> {
+        let events = sqlx::query_as::<_, OutboxEvent>(&format!(
+            r#"
+            SELECT {} FROM event_outbox
+            WHERE ?1 IS NULL OR status = ?1
+            ORDER BY id DESC
+            LIMIT ?2
+            "#,
+            OUTBOX_COLUMNS
+        ))
+        .bind(status)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to list outbox events: {:?}", e))?;
+
+        Ok(events)
+    }
+
+    /// Put a dead event back in the queue with a fresh set of attempts; None when there is
+    /// no dead event with that id
+    pub async fn requeue(pool: &DbPool, id: i64) -> Result<Option<OutboxEvent>> {
+        let event = sqlx::query_as::<_, OutboxEvent>(&format!(
+            r#"
+            UPDATE event_outbox
+            SET status = 'pending', attempts = 0, next_attempt_at = datetime('now')
+            WHERE id = ?1 AND status = 'dead'
+            RETURNING {}
+            "#,
+            OUTBOX_COLUMNS
+        ))
+        .bind(id)
+        .fetch_returned(pool)
+        .await
+        .inspect_err(|e| error!("Failed to requeue outbox event {}: {:?}", id, e))?;
+
+        Ok(event)
+    }
+
+    pub async fn backlog(pool: &DbPool) -> Result<OutboxBacklog> {
+        let backlog = sqlx::query_as::<_, OutboxBacklog>(
+            r#"
+            SELECT COUNT(*) FILTER (WHERE status = 'pending') AS pending,
+                   COUNT(*) FILTER (WHERE status = 'dead') AS dead,
+                   COALESCE(CAST((julianday('now') - julianday(MIN(created_at)
+                       FILTER (WHERE status = 'pending'))) * 86400 AS INTEGER), 0)
+                       AS oldest_pending_secs
+            FROM event_outbox
+            WHERE status != 'dispatched'
+            "#,
+        )
+        .fetch_one(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to measure the outbox backlog: {:?}", e))?;
+
+        Ok(backlog)
+    }
+}
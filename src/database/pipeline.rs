@@ -0,0 +1,417 @@
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tracing::error;
+use utoipa::ToSchema;
+
+use super::{
+    outbox::OutboxEvent,
+    returning::FetchReturning,
+    tickets::{Ticket, TicketState, TICKET_COLUMNS},
+    DbPool,
+};
+use crate::{crypto, error::AppError, events::EventPayload};
+
+/// Custom field on the next-stage ticket naming the ticket it follows
+pub const FOLLOWS_FIELD: &str = "follows";
+
+/// Move a stage pipeline forward: close `source_ticket_id` and open the next-stage
+/// ticket, either a new one or a reopened `next_ticket_id`
+#[derive(Debug, Clone)]
+pub struct PipelineTransitionRequest {
+    pub source_ticket_id: String,
+    pub resolution: String,
+    /// Existing ticket to reopen instead of creating a new one
+    pub next_ticket_id: Option<String>,
+    /// Stage (worker type) the next ticket is assigned to
+    pub stage: String,
+    /// Title and description for a newly created ticket; ignored when reopening
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub priority: Option<String>,
+    /// Merged over the custom fields copied from the source ticket
+    pub custom_fields: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineRelation {
+    pub from: String,
+    pub to: String,
+    pub kind: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineTransition {
+    pub source: Ticket,
+    pub next: Ticket,
+    pub reopened: bool,
+    pub relation: PipelineRelation,
+}
+
+/// `follows` relations of a ticket, for rendering the stage chain
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct PipelineLinks {
+    pub follows: Option<String>,
+    pub followed_by: Vec<String>,
+}
+
+/// Close the source ticket and create or reopen the next-stage ticket in a single
+/// transaction, with their events queued in the outbox; any failure leaves both tickets
+/// untouched
+pub async fn transition_pipeline(
+    pool: &DbPool,
+    req: PipelineTransitionRequest,
+) -> Result<PipelineTransition> {
+    let mut tx = pool.begin().await?;
+
+    let source = fetch_ticket(&mut tx, &req.source_ticket_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!("Ticket '{}' not found", req.source_ticket_id))
+        })?;
+    if source.is_closed() {
+        bail!("Ticket '{}' is already closed", source.ticket_id);
+    }
+
+    // The next ticket inherits the source's custom fields, then the supplied overrides
+    let mut custom_fields = source.custom_field_map();
+    custom_fields.extend(req.custom_fields.clone());
+    custom_fields.insert(
+        FOLLOWS_FIELD.to_string(),
+        Value::String(source.ticket_id.clone()),
+    );
+    let custom_fields_json = serde_json::to_string(&custom_fields)?;
+    let priority = req.priority.clone().unwrap_or(source.priority.clone());
+
+    let dep_status = if req.resolution == "Completed" {
+        "ready"
+    } else {
+        "blocked"
+    };
+    let source = sqlx::query_as::<_, Ticket>(
+        r#"
+        UPDATE tickets
+        SET current_stage = ?1, state = ?2, dependency_status = ?3, processing_worker_id = NULL,
+            updated_at = datetime('now'), closed_at = datetime('now')
+        WHERE ticket_id = ?4
+        RETURNING ticket_id, project_id, title, execution_plan, current_stage, state, priority,
+                 processing_worker_id, created_at, updated_at, closed_at,
+                 parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
+                 rules_version, patterns_version, inherited_from_parent, custom_fields,
+                 due_at, sla_minutes, sla_breached, estimate_minutes, actual_minutes, reopen_count, short_id, duplicate_of, effective_priority, escalation_reasons
+        "#,
+    )
+    .bind(&req.resolution)
+    .bind(TicketState::Closed.as_sql_value())
+    .bind(dep_status)
+    .bind(&source.ticket_id)
+    .fetch_returned_one(&mut *tx)
+    .await
+    .inspect_err(|e| error!("Failed to close ticket {}: {:?}", source.ticket_id, e))?;
+
+    let (next, reopened) = match &req.next_ticket_id {
+        Some(next_id) => {
+            let existing = fetch_ticket(&mut tx, next_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found", next_id)))?;
+            if existing.ticket_id == source.ticket_id {
+                bail!("A ticket cannot follow itself");
+            }
+            if existing.project_id != source.project_id {
+                bail!(
+                    "Ticket '{}' belongs to project '{}', not '{}'",
+                    existing.ticket_id,
+                    existing.project_id,
+                    source.project_id
+                );
+            }
+
+            sqlx::query(
+                r#"
+                UPDATE tickets
+                SET state = ?1, current_stage = ?2, priority = ?3, custom_fields = ?4,
+                    dependency_status = 'ready', processing_worker_id = NULL,
+                    closed_at = NULL, updated_at = datetime('now')
+                WHERE ticket_id = ?5
+                "#,
+            )
+            .bind(TicketState::Open.as_sql_value())
+            .bind(&req.stage)
+            .bind(&priority)
+            .bind(&custom_fields_json)
+            .bind(next_id)
+            .execute(&mut *tx)
+            .await
+            .inspect_err(|e| error!("Failed to reopen ticket {}: {:?}", next_id, e))?;
+            // Read back after the trigger counting reopens has run
+            let next = fetch_ticket(&mut tx, next_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found", next_id)))?;
+            (next, true)
+        }
+        None => {
+            let project_prefix: Option<String> = sqlx::query_scalar(
+                "SELECT project_prefix FROM projects WHERE repository_name = ?1",
+            )
+            .bind(&source.project_id)
+            .fetch_one(&mut *tx)
+            .await?;
+            let project_prefix = project_prefix.unwrap_or_else(|| {
+                crate::workers::ticket_id::generate_project_prefix(&source.project_id)
+            });
+            let execution_plan = vec![req.stage.clone()];
+            let subsystem = crate::workers::ticket_id::infer_subsystem_from_stages(&execution_plan);
+            let next_id = crate::workers::ticket_id::generate_ticket_id_tx(
+                &mut tx,
+                &project_prefix,
+                &subsystem,
+            )
+            .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO tickets (
+                    ticket_id, project_id, title, execution_plan, current_stage, state, priority,
+                    parent_ticket_id, dependency_status, ticket_type, rules_version,
+                    patterns_version, inherited_from_parent, custom_fields
+                )
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'ready', ?9, ?10, ?11, ?12, ?13)
+                "#,
+            )
+            .bind(&next_id)
+            .bind(&source.project_id)
+            .bind(req.title.as_deref().unwrap_or(&source.title))
+            .bind(serde_json::to_string(&execution_plan)?)
+            .bind(&req.stage)
+            .bind(TicketState::Open.as_sql_value())
+            .bind(&priority)
+            .bind(&source.parent_ticket_id)
+            .bind(&source.ticket_type)
+            .bind(source.rules_version)
+            .bind(source.patterns_version)
+            .bind(source.inherited_from_parent)
+            .bind(&custom_fields_json)
+            .execute(&mut *tx)
+            .await
+            .inspect_err(|e| error!("Failed to create next-stage ticket {}: {:?}", next_id, e))?;
+            // Selected afterwards so the short_id assigned by a trigger is included
+            let next = sqlx::query_as::<_, Ticket>(&format!(
+                "SELECT {} FROM tickets WHERE ticket_id = ?1",
+                TICKET_COLUMNS
+            ))
+            .bind(&next_id)
+            .fetch_one(&mut *tx)
+            .await?;
+            (next, false)
+        }
+    };
+
+    let comments = [
+        (
+            &source.ticket_id,
+            format!(
+                "Ticket closed with resolution '{}'; pipeline continues in {} at stage '{}'.",
+                req.resolution, next.ticket_id, req.stage
+            ),
+        ),
+        (
+            &next.ticket_id,
+            match (&req.description, reopened) {
+                (Some(description), false) => description.clone(),
+                _ => format!(
+                    "Follows {} (closed with resolution '{}'); assigned to stage '{}'.",
+                    source.ticket_id, req.resolution, req.stage
+                ),
+            },
+        ),
+    ];
+    for (ticket_id, content) in comments {
+        let (content, key_id) = crypto::seal(&content)?;
+        sqlx::query(
+            r#"
+            INSERT INTO comments (ticket_id, worker_type, worker_id, stage_number, content, content_key_id)
+            VALUES (?1, 'coordinator', 'coordinator', 0, ?2, ?3)
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(&content)
+        .bind(key_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    OutboxEvent::write(
+        &mut tx,
+        &source.ticket_id,
+        &EventPayload::ticket_closed(&source.ticket_id, &source.project_id),
+        None,
+        Some(&format!(
+            "Ticket closed with resolution: {}",
+            req.resolution
+        )),
+    )
+    .await?;
+    let next_event = if reopened {
+        EventPayload::ticket_updated(&next.ticket_id, &next.project_id, "reopened")
+    } else {
+        EventPayload::ticket_created_with_data(
+            &next.ticket_id,
+            &next.project_id,
+            &next.title,
+            &req.stage,
+        )
+    };
+    let next_reason = if reopened {
+        format!("Follows {}", source.ticket_id)
+    } else {
+        format!("Ticket '{}' created", next.title)
+    };
+    OutboxEvent::write(
+        &mut tx,
+        &next.ticket_id,
+        &next_event,
+        Some(&req.stage),
+        Some(&next_reason),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    let relation = PipelineRelation {
+        from: next.ticket_id.clone(),
+        to: source.ticket_id.clone(),
+        kind: FOLLOWS_FIELD,
+    };
+    Ok(PipelineTransition {
+        source,
+        next,
+        reopened,
+        relation,
+    })
+}
+
+/// Tickets the given one follows and is followed by
+pub async fn pipeline_links(pool: &DbPool, ticket: &Ticket) -> Result<PipelineLinks> {
+    let follows = ticket
+        .custom_field_map()
+        .get(FOLLOWS_FIELD)
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    let followed_by = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT ticket_id FROM tickets
+        WHERE project_id = ?1 AND json_valid(custom_fields)
+          AND json_extract(custom_fields, '$.follows') = ?2
+        ORDER BY created_at
+        "#,
+    )
+    .bind(&ticket.project_id)
+    .bind(&ticket.ticket_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(PipelineLinks {
+        follows,
+        followed_by,
+    })
+}
+
+async fn fetch_ticket(
+    conn: &mut sqlx::SqliteConnection,
+    ticket_id: &str,
+) -> Result<Option<Ticket>> {
+    Ok(sqlx::query_as::<_, Ticket>(
+        r#"
+        SELECT ticket_id, project_id, title, execution_plan, current_stage, state, priority,
+               processing_worker_id, created_at, updated_at, closed_at,
+               parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
+               rules_version, patterns_version, inherited_from_parent, custom_fields,
+               due_at, sla_minutes, sla_breached, estimate_minutes, actual_minutes, reopen_count, short_id, duplicate_of, effective_priority, escalation_reasons
+        FROM tickets
+        WHERE ticket_id = ?1
+        "#,
+    )
+    .bind(ticket_id)
+    .fetch_optional(conn)
+    .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tickets::Priority;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    async fn scratch_pool() -> DbPool {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let web = fx.project("pipeline-web").create().await.unwrap();
+        let other = fx.project("pipeline-other").create().await.unwrap();
+        fx.ticket(&web, "Design login")
+            .id("P-DES-001")
+            .stages(&["design"])
+            .priority(Priority::High)
+            .custom_fields(serde_json::json!({"area": "auth"}))
+            .create()
+            .await
+            .unwrap();
+        fx.ticket(&other, "Elsewhere")
+            .id("Q-GEN-001")
+            .stages(&["review"])
+            .priority(Priority::Low)
+            .create()
+            .await
+            .unwrap();
+        pool
+    }
+
+    fn request(next_ticket_id: Option<&str>) -> PipelineTransitionRequest {
+        PipelineTransitionRequest {
+            source_ticket_id: "P-DES-001".to_string(),
+            resolution: "Completed".to_string(),
+            next_ticket_id: next_ticket_id.map(str::to_string),
+            stage: "implement".to_string(),
+            title: None,
+            description: None,
+            priority: None,
+            custom_fields: Map::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transition_creates_follower_and_rolls_back_on_failure() {
+        let pool = scratch_pool().await;
+
+        // Reopening a ticket from another project fails and must leave the source open
+        let err = transition_pipeline(&pool, request(Some("Q-GEN-001")))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("belongs to project"), "{}", err);
+        let source: String =
+            sqlx::query_scalar("SELECT state FROM tickets WHERE ticket_id = 'P-DES-001'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(source, "open");
+
+        let transition = transition_pipeline(&pool, request(None)).await.unwrap();
+        assert!(transition.source.is_closed());
+        assert!(!transition.reopened);
+        assert_eq!(transition.next.current_stage, "implement");
+        assert_eq!(transition.next.priority, "high");
+        let fields = transition.next.custom_field_map();
+        assert_eq!(fields["area"], "auth");
+        assert_eq!(fields["follows"], "P-DES-001");
+
+        let links = pipeline_links(&pool, &transition.source).await.unwrap();
+        assert_eq!(links.followed_by, vec![transition.next.ticket_id.clone()]);
+        let links = pipeline_links(&pool, &transition.next).await.unwrap();
+        assert_eq!(links.follows.as_deref(), Some("P-DES-001"));
+
+        // The source is closed now, so a second transition is rejected
+        assert!(transition_pipeline(&pool, request(None)).await.is_err());
+
+        pool.close().await;
+    }
+}
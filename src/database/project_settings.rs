@@ -0,0 +1,371 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use super::{tickets::Priority, DbPool};
+use crate::config::Config;
+
+/// Coordination settings a project overrides; unset fields fall back to the server config
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectSettings {
+    /// Workers must hold a ticket's processing claim before editing it (comments, attachments)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_lock_for_edit: Option<bool>,
+    /// Priority of tickets created without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_issue_priority: Option<Priority>,
+    /// Worker processes this project may run at once, instead of `--max-workers-per-project`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_workers: Option<usize>,
+    /// Worker type that new tickets start in when no stage or execution plan is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_assign_capability: Option<String>,
+    /// Start new tickets in the worker type whose capabilities match their tags, instead of
+    /// `--auto-assign`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_assign: Option<bool>,
+    /// Days to keep settled agent requests, instead of `--message-retention-days`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_retention_days: Option<u64>,
+    /// Tickets that are not closed, instead of `--max-open-tickets`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_open_tickets: Option<u64>,
+    /// Comments posted in the last hour, instead of `--max-comments-per-hour`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_comments_per_hour: Option<u64>,
+}
+
+/// Settings after falling back to the server config
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct EffectiveSettings {
+    pub required_lock_for_edit: bool,
+    pub default_issue_priority: Priority,
+    /// 0 means unlimited
+    pub max_concurrent_workers: usize,
+    pub auto_assign_capability: String,
+    pub auto_assign: bool,
+    /// 0 keeps them forever
+    pub message_retention_days: u64,
+    /// 0 means unlimited
+    pub max_open_tickets: u64,
+    /// 0 means unlimited
+    pub max_comments_per_hour: u64,
+}
+
+impl ProjectSettings {
+    /// Settings for a project; None if the project does not exist
+    pub async fn get(pool: &DbPool, project_id: &str) -> Result<Option<ProjectSettings>> {
+        let row: Option<(Option<String>,)> =
+            sqlx::query_as("SELECT project_settings FROM projects WHERE repository_name = ?1")
+                .bind(project_id)
+                .fetch_optional(pool)
+                .await
+                .inspect_err(|e| {
+                    warn!(
+                        "Failed to load settings for project '{}': {:?}",
+                        project_id, e
+                    )
+                })?;
+
+        Ok(row.map(|(json,)| Self::parse(project_id, json.as_deref())))
+    }
+
+    /// Settings of the project a ticket belongs to, with its current processing claim
+    pub async fn for_ticket(
+        pool: &DbPool,
+        ticket_id: &str,
+    ) -> Result<Option<(ProjectSettings, Option<String>)>> {
+        let row: Option<(String, Option<String>, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT p.repository_name, p.project_settings, t.processing_worker_id
+            FROM tickets t
+            JOIN projects p ON p.repository_name = t.project_id
+            WHERE t.ticket_id = ?1
+            "#,
+        )
+        .bind(ticket_id)
+        .fetch_optional(pool)
+        .await
+        .inspect_err(|e| {
+            warn!(
+                "Failed to load settings for ticket '{}': {:?}",
+                ticket_id, e
+            )
+        })?;
+
+        Ok(row.map(|(project_id, json, claim)| (Self::parse(&project_id, json.as_deref()), claim)))
+    }
+
+    /// Replace a project's settings; returns false if the project does not exist
+    pub async fn set(pool: &DbPool, project_id: &str, settings: &ProjectSettings) -> Result<bool> {
+        let json = if *settings == ProjectSettings::default() {
+            None
+        } else {
+            Some(serde_json::to_string(settings)?)
+        };
+        let result = sqlx::query(
+            r#"
+            UPDATE projects
+            SET project_settings = ?2, updated_at = datetime('now')
+            WHERE repository_name = ?1
+            "#,
+        )
+        .bind(project_id)
+        .bind(json)
+        .execute(pool)
+        .await
+        .inspect_err(|e| {
+            error!(
+                "Failed to save settings for project '{}': {:?}",
+                project_id, e
+            )
+        })?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// A stored value that no longer parses is ignored rather than blocking the project
+    fn parse(project_id: &str, json: Option<&str>) -> ProjectSettings {
+        json.and_then(|json| {
+            serde_json::from_str(json)
+                .inspect_err(|e| {
+                    warn!(
+                        "Ignoring invalid settings for project '{}': {}",
+                        project_id, e
+                    )
+                })
+                .ok()
+        })
+        .unwrap_or_default()
+    }
+
+    /// Problems with these settings for the project, empty if they are valid
+    pub async fn validate(
+        &self,
+        pool: &DbPool,
+        project_id: &str,
+        config: &Config,
+    ) -> Result<Vec<String>> {
+        let mut errors = Vec::new();
+        if let Some(max) = self.max_concurrent_workers {
+            if config.max_concurrent_workers > 0 && max > config.max_concurrent_workers {
+                errors.push(format!(
+                    "max_concurrent_workers {} exceeds the global limit of {}",
+                    max, config.max_concurrent_workers
+                ));
+            }
+        }
+        if let Some(worker_type) = &self.auto_assign_capability {
+            if worker_type.trim().is_empty() {
+                errors.push("auto_assign_capability must not be empty".to_string());
+            } else if super::worker_types::WorkerType::get_by_type(pool, project_id, worker_type)
+                .await?
+                .is_none()
+            {
+                errors.push(format!(
+                    "auto_assign_capability '{}' is not a worker type of project '{}'",
+                    worker_type, project_id
+                ));
+            }
+        }
+        Ok(errors)
+    }
+
+    pub fn effective(&self, config: &Config) -> EffectiveSettings {
+        EffectiveSettings {
+            required_lock_for_edit: self.required_lock_for_edit.unwrap_or(false),
+            default_issue_priority: self
+                .default_issue_priority
+                .clone()
+                .unwrap_or(Priority::Medium),
+            max_concurrent_workers: self
+                .max_concurrent_workers
+                .unwrap_or(config.max_workers_per_project),
+            auto_assign_capability: self
+                .auto_assign_capability
+                .clone()
+                .unwrap_or_else(|| "planning".to_string()),
+            auto_assign: self.auto_assign.unwrap_or(config.auto_assign),
+            message_retention_days: self
+                .message_retention_days
+                .unwrap_or(config.message_retention_days),
+            max_open_tickets: self.max_open_tickets.unwrap_or(config.max_open_tickets),
+            max_comments_per_hour: self
+                .max_comments_per_hour
+                .unwrap_or(config.max_comments_per_hour),
+        }
+    }
+
+    /// Why `worker_id` may not edit a ticket under these settings, if it may not
+    pub fn edit_denied(
+        &self,
+        ticket_id: &str,
+        claim: Option<&str>,
+        worker_id: &str,
+    ) -> Option<String> {
+        if !self.required_lock_for_edit.unwrap_or(false) || claim == Some(worker_id) {
+            return None;
+        }
+        Some(match claim {
+            Some(holder) => format!(
+                "Ticket {} is claimed by '{}'; this project requires holding the claim to edit it",
+                ticket_id, holder
+            ),
+            None => format!(
+                "Ticket {} is not claimed; this project requires holding the claim to edit it",
+                ticket_id
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_pool, Fixtures};
+    use sqlx::Executor;
+
+    #[tokio::test]
+    async fn test_conflicting_projects_side_by_side() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let strict_project = fx.project("strict").create().await.unwrap();
+        let relaxed_project = fx.project("relaxed").create().await.unwrap();
+        let triage = fx
+            .worker_type(&strict_project, "triage")
+            .create()
+            .await
+            .unwrap();
+        fx.ticket(&strict_project, "a")
+            .id("S-1")
+            .assigned_to(&triage)
+            .claimed_by("w1")
+            .create()
+            .await
+            .unwrap();
+        fx.ticket(&relaxed_project, "a")
+            .id("R-1")
+            .claimed_by("w1")
+            .create()
+            .await
+            .unwrap();
+        let config = Config {
+            max_concurrent_workers: 8,
+            max_workers_per_project: 2,
+            message_retention_days: 30,
+            ..Config::default()
+        };
+
+        let strict = ProjectSettings {
+            required_lock_for_edit: Some(true),
+            default_issue_priority: Some(Priority::High),
+            max_concurrent_workers: Some(1),
+            auto_assign_capability: Some("triage".to_string()),
+            auto_assign: Some(true),
+            message_retention_days: Some(7),
+            max_open_tickets: Some(50),
+            max_comments_per_hour: None,
+        };
+        assert!(strict
+            .validate(&pool, "strict", &config)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(ProjectSettings::set(&pool, "strict", &strict)
+            .await
+            .unwrap());
+        let relaxed: ProjectSettings =
+            serde_json::from_str(r#"{"max_concurrent_workers": 4}"#).unwrap();
+        assert!(ProjectSettings::set(&pool, "relaxed", &relaxed)
+            .await
+            .unwrap());
+
+        // The same worker type is valid in one project and not the other
+        let errors = strict.validate(&pool, "relaxed", &config).await.unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("not a worker type"));
+        let too_many = ProjectSettings {
+            max_concurrent_workers: Some(9),
+            ..Default::default()
+        };
+        assert_eq!(
+            too_many
+                .validate(&pool, "relaxed", &config)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(serde_json::from_str::<ProjectSettings>(r#"{"max_workers": 1}"#).is_err());
+        assert!(!ProjectSettings::set(&pool, "missing", &relaxed)
+            .await
+            .unwrap());
+
+        let strict = ProjectSettings::get(&pool, "strict")
+            .await
+            .unwrap()
+            .unwrap();
+        let relaxed = ProjectSettings::get(&pool, "relaxed")
+            .await
+            .unwrap()
+            .unwrap();
+        let (strict_effective, relaxed_effective) =
+            (strict.effective(&config), relaxed.effective(&config));
+        assert_eq!(strict_effective.max_concurrent_workers, 1);
+        assert_eq!(relaxed_effective.max_concurrent_workers, 4);
+        assert_eq!(strict_effective.default_issue_priority, Priority::High);
+        assert_eq!(relaxed_effective.default_issue_priority, Priority::Medium);
+        assert_eq!(strict_effective.auto_assign_capability, "triage");
+        assert_eq!(relaxed_effective.auto_assign_capability, "planning");
+        assert!(strict_effective.auto_assign);
+        assert!(!relaxed_effective.auto_assign);
+        assert_eq!(strict_effective.message_retention_days, 7);
+        assert_eq!(relaxed_effective.message_retention_days, 30);
+
+        // Only the strict project turns away a worker that does not hold the claim
+        let (settings, claim) = ProjectSettings::for_ticket(&pool, "S-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(settings
+            .edit_denied("S-1", claim.as_deref(), "w1")
+            .is_none());
+        assert!(settings
+            .edit_denied("S-1", claim.as_deref(), "w2")
+            .is_some());
+        let (settings, claim) = ProjectSettings::for_ticket(&pool, "R-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(settings
+            .edit_denied("R-1", claim.as_deref(), "w2")
+            .is_none());
+
+        // Requests settled 10 days ago outlive the strict project's 7 days but not the global 30
+        pool.execute(
+            r#"
+            INSERT INTO agent_requests (correlation_id, requester, target, ticket_id, payload,
+                                        status, deadline, responded_at)
+            VALUES ('c-strict', 'a', 'b', 'S-1', '{}', 'answered', datetime('now', '-10 days'),
+                    datetime('now', '-10 days')),
+                   ('c-relaxed', 'a', 'b', 'R-1', '{}', 'answered', datetime('now', '-10 days'),
+                    datetime('now', '-10 days'));
+            "#,
+        )
+        .await
+        .unwrap();
+        let purged = super::super::agent_requests::AgentRequest::purge_settled(&pool, 30)
+            .await
+            .unwrap();
+        assert_eq!(purged, 1);
+        let left: Vec<(String,)> = sqlx::query_as("SELECT correlation_id FROM agent_requests")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(left, [("c-relaxed".to_string(),)]);
+
+        pool.close().await;
+    }
+}
@@ -2,10 +2,11 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 
-use super::DbPool;
+use super::{cache, returning::FetchReturning, DbPool};
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Project {
     pub repository_name: String,
     pub project_prefix: String,
@@ -25,6 +26,18 @@ pub struct Project {
     pub jbct_url: Option<String>,
 }
 
+/// Everything deleting a project removes with it
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectDeletion {
+    pub repository_name: String,
+    pub tickets: Vec<String>,
+    pub worker_types: Vec<String>,
+    pub workers: Vec<String>,
+    pub schedules: Vec<String>,
+    /// Comments on the removed tickets
+    pub comments: i64,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateProjectRequest {
     pub repository_name: String,
@@ -64,25 +77,30 @@ impl Project {
         .bind(&req.short_description)
         .bind(&req.rules)
         .bind(&req.patterns)
-        .fetch_one(pool)
+        .fetch_returned_one(pool)
         .await?;
 
         Ok(project)
     }
 
     pub async fn get_by_name(pool: &DbPool, repository_name: &str) -> Result<Option<Project>> {
-        let project = sqlx::query_as::<_, Project>(
-            r#"
+        cache::for_pool(pool)
+            .projects
+            .get_or_load(repository_name, || async {
+                let project = sqlx::query_as::<_, Project>(
+                    r#"
             SELECT repository_name, project_prefix, path, short_description, rules, patterns, created_at, updated_at, rules_version, patterns_version, jbct_enabled, jbct_version, jbct_url
             FROM projects
             WHERE repository_name = ?1
         "#,
-        )
-        .bind(repository_name)
-        .fetch_optional(pool)
-        .await?;
+                )
+                .bind(repository_name)
+                .fetch_optional(pool)
+                .await?;
 
-        Ok(project)
+                Ok(project)
+            })
+            .await
     }
 
     /// Alias for get_by_name since repository_name serves as the project ID
@@ -195,16 +213,63 @@ impl Project {
 
         let project = query_builder
             .build_query_as::<Project>()
-            .fetch_optional(pool)
+            .fetch_returned(pool)
             .await?;
+        cache::for_pool(pool).projects.invalidate(repository_name);
         Ok(project)
     }
 
+    /// What [`Project::delete`] would remove; None when the project does not exist
+    pub async fn plan_delete(
+        pool: &DbPool,
+        repository_name: &str,
+    ) -> Result<Option<ProjectDeletion>> {
+        if Self::get_by_name(pool, repository_name).await?.is_none() {
+            return Ok(None);
+        }
+        let ids = |sql: &'static str| {
+            sqlx::query_scalar::<_, String>(sql)
+                .bind(repository_name)
+                .fetch_all(pool)
+        };
+        let tickets =
+            ids("SELECT ticket_id FROM tickets WHERE project_id = ?1 ORDER BY ticket_id").await?;
+        let worker_types =
+            ids("SELECT worker_type FROM worker_types WHERE project_id = ?1 ORDER BY worker_type")
+                .await?;
+        let workers =
+            ids("SELECT worker_id FROM workers WHERE project_id = ?1 ORDER BY worker_id").await?;
+        let schedules =
+            ids("SELECT name FROM ticket_schedules WHERE project_id = ?1 ORDER BY name").await?;
+        let comments: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM comments c JOIN tickets t ON t.ticket_id = c.ticket_id WHERE t.project_id = ?1",
+        )
+        .bind(repository_name)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(Some(ProjectDeletion {
+            repository_name: repository_name.to_string(),
+            tickets,
+            worker_types,
+            workers,
+            schedules,
+            comments,
+        }))
+    }
+
     pub async fn delete(pool: &DbPool, repository_name: &str) -> Result<bool> {
         let result = sqlx::query("DELETE FROM projects WHERE repository_name = ?1")
             .bind(repository_name)
             .execute(pool)
             .await?;
+        let caches = cache::for_pool(pool);
+        caches.projects.invalidate(repository_name);
+        caches
+            .worker_types
+            .invalidate_prefix(&cache::worker_type_key(repository_name, ""));
+        // The project's ticket history is gone with it
+        caches.throughput.clear();
 
         Ok(result.rows_affected() > 0)
     }
@@ -0,0 +1,540 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection};
+use std::{
+    fmt,
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use tracing::error;
+use utoipa::ToSchema;
+
+use super::{returning::FetchReturning, DbPool};
+
+/// Share of a quota in use at which the project is warned
+pub const WARNING_RATIO: f64 = 0.9;
+
+const DEFAULT_MAX_OPEN_TICKETS: u64 = 5000;
+const DEFAULT_MAX_COMMENTS_PER_HOUR: u64 = 1000;
+
+static MAX_OPEN_TICKETS: AtomicU64 = AtomicU64::new(DEFAULT_MAX_OPEN_TICKETS);
+static MAX_COMMENTS_PER_HOUR: AtomicU64 = AtomicU64::new(DEFAULT_MAX_COMMENTS_PER_HOUR);
+
+/// Set the quotas of projects that do not configure their own; 0 means unlimited
+pub fn configure(max_open_tickets: u64, max_comments_per_hour: u64) {
+    MAX_OPEN_TICKETS.store(max_open_tickets, Ordering::SeqCst);
+    MAX_COMMENTS_PER_HOUR.store(max_comments_per_hour, Ordering::SeqCst);
+}
+
+const OVERRIDE_COLUMNS: &str =
+    "id, project_id, quota, quota_limit, reason, created_by, created_at, expires_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Quota {
+    /// Tickets that are not closed
+    OpenTickets,
+    /// Comments posted on the project's tickets in the last hour
+    CommentsPerHour,
+}
+
+impl Quota {
+    pub const ALL: [Quota; 2] = [Quota::OpenTickets, Quota::CommentsPerHour];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Quota::OpenTickets => "open_tickets",
+            Quota::CommentsPerHour => "comments_per_hour",
+        }
+    }
+
+    /// Project setting that sets this quota
+    fn setting(&self) -> &'static str {
+        match self {
+            Quota::OpenTickets => "max_open_tickets",
+            Quota::CommentsPerHour => "max_comments_per_hour",
+        }
+    }
+
+    fn server_default(&self) -> u64 {
+        match self {
+            Quota::OpenTickets => MAX_OPEN_TICKETS.load(Ordering::SeqCst),
+            Quota::CommentsPerHour => MAX_COMMENTS_PER_HOUR.load(Ordering::SeqCst),
+        }
+    }
+}
+
+impl fmt::Display for Quota {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Quota {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "open_tickets" => Ok(Quota::OpenTickets),
+            "comments_per_hour" => Ok(Quota::CommentsPerHour),
+            _ => Err(anyhow::anyhow!(
+                "Invalid quota '{}'. Valid quotas are: open_tickets, comments_per_hour",
+                s
+            )),
+        }
+    }
+}
+
+/// A creation refused because the project has used up a quota. Creation calls return it
+/// inside their `anyhow::Error`; callers find it with `downcast_ref`.
+#[derive(Debug, Clone, Serialize, thiserror::Error)]
+#[error("Project '{project_id}' is at its {quota} quota ({usage} of {limit} used)")]
+pub struct QuotaExceeded {
+    pub project_id: String,
+    pub quota: Quota,
+    pub limit: u64,
+    pub usage: u64,
+}
+
+/// Temporary limit set by the coordinator
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct QuotaOverride {
+    pub id: i64,
+    pub project_id: String,
+    pub quota: String,
+    pub quota_limit: i64,
+    pub reason: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+    pub expires_at: String,
+}
+
+/// Usage of one quota
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QuotaStatus {
+    pub quota: Quota,
+    pub usage: u64,
+    /// Limit in effect; 0 means unlimited
+    pub limit: u64,
+    /// Limit from the project settings or server default, before any override
+    pub base_limit: u64,
+    #[serde(rename = "override", skip_serializing_if = "Option::is_none")]
+    pub active_override: Option<QuotaOverride>,
+    /// `ok`, `warning` (at least 90% used), `exceeded` or `unlimited`
+    pub status: &'static str,
+}
+
+/// A quota a project passed 90% of since the last evaluation
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaWarning {
+    pub project_id: String,
+    pub quota: Quota,
+    pub usage: u64,
+    pub limit: u64,
+}
+
+/// Result of overriding a quota
+#[derive(Debug, Clone)]
+pub enum OverrideOutcome {
+    Created(Box<QuotaOverride>),
+    /// The new limit does not raise the quota; the reason is meant for the caller
+    Rejected(String),
+}
+
+/// Current usage of a quota
+async fn usage(conn: &mut SqliteConnection, project_id: &str, quota: Quota) -> Result<u64> {
+    let usage: Option<i64> = match quota {
+        Quota::OpenTickets => {
+            sqlx::query_scalar("SELECT open_tickets FROM project_usage WHERE project_id = ?1")
+                .bind(project_id)
+                .fetch_optional(&mut *conn)
+                .await?
+        }
+        // comments first: only the last hour is read from the created_at index
+        Quota::CommentsPerHour => Some(
+            sqlx::query_scalar(
+                r#"
+                SELECT COUNT(*)
+                FROM comments c CROSS JOIN tickets t
+                WHERE c.created_at >= datetime('now', '-1 hour')
+                  AND t.ticket_id = c.ticket_id AND t.project_id = ?1
+                "#,
+            )
+            .bind(project_id)
+            .fetch_one(&mut *conn)
+            .await?,
+        ),
+    };
+    Ok(usage.unwrap_or(0).max(0) as u64)
+}
+
+/// Limit from the project settings or the server default, and the override raising it
+async fn limit(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    quota: Quota,
+) -> Result<(u64, Option<QuotaOverride>)> {
+    let setting: Option<i64> = sqlx::query_scalar(&format!(
+        "SELECT CASE WHEN json_valid(project_settings)
+                THEN json_extract(project_settings, '$.{}') END
+         FROM projects WHERE repository_name = ?1",
+        quota.setting()
+    ))
+    .bind(project_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .flatten();
+    let base = setting.map_or_else(|| quota.server_default(), |limit| limit.max(0) as u64);
+
+    let active_override = sqlx::query_as::<_, QuotaOverride>(&format!(
+        "SELECT {} FROM quota_overrides
+         WHERE project_id = ?1 AND quota = ?2 AND expires_at > datetime('now')
+         ORDER BY id DESC LIMIT 1",
+        OVERRIDE_COLUMNS
+    ))
+    .bind(project_id)
+    .bind(quota.as_str())
+    .fetch_optional(&mut *conn)
+    .await?;
+    Ok((base, active_override))
+}
+
+fn effective_limit(base: u64, active_override: Option<&QuotaOverride>) -> u64 {
+    active_override.map_or(base, |o| o.quota_limit.max(0) as u64)
+}
+
+/// Fail with [`QuotaExceeded`] if `adding` more would take the project past the quota.
+/// The limit is soft: creations racing with the check can overshoot it slightly.
+pub async fn check(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    quota: Quota,
+    adding: u64,
+) -> Result<()> {
+    let (base, active_override) = limit(conn, project_id, quota).await?;
+    let limit = effective_limit(base, active_override.as_ref());
+    if limit == 0 {
+        return Ok(());
+    }
+    let usage = usage(conn, project_id, quota).await?;
+    if usage + adding > limit {
+        return Err(QuotaExceeded {
+            project_id: project_id.to_string(),
+            quota,
+            limit,
+            usage,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// [`check`] the comments quota of the project a ticket belongs to
+pub async fn check_comment(conn: &mut SqliteConnection, ticket_id: &str) -> Result<()> {
+    let project_id: Option<String> =
+        sqlx::query_scalar("SELECT project_id FROM tickets WHERE ticket_id = ?1")
+            .bind(ticket_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    match project_id {
+        Some(project_id) => check(conn, &project_id, Quota::CommentsPerHour, 1).await,
+        None => Ok(()),
+    }
+}
+
+async fn status(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    quota: Quota,
+) -> Result<QuotaStatus> {
+    let (base_limit, active_override) = limit(conn, project_id, quota).await?;
+    let limit = effective_limit(base_limit, active_override.as_ref());
+    let usage = usage(conn, project_id, quota).await?;
+    let status = if limit == 0 {
+        "unlimited"
+    } else if usage >= limit {
+        "exceeded"
+    } else if usage as f64 >= limit as f64 * WARNING_RATIO {
+        "warning"
+    } else {
+        "ok"
+    };
+    Ok(QuotaStatus {
+        quota,
+        usage,
+        limit,
+        base_limit,
+        active_override,
+        status,
+    })
+}
+
+/// Usage of every quota of a project; None if the project does not exist
+pub async fn report(pool: &DbPool, project_id: &str) -> Result<Option<Vec<QuotaStatus>>> {
+    let mut conn = pool.acquire().await?;
+    let exists: Option<i64> =
+        sqlx::query_scalar("SELECT 1 FROM projects WHERE repository_name = ?1")
+            .bind(project_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    if exists.is_none() {
+        return Ok(None);
+    }
+
+    let mut statuses = Vec::with_capacity(Quota::ALL.len());
+    for quota in Quota::ALL {
+        statuses.push(status(&mut conn, project_id, quota).await?);
+    }
+    Ok(Some(statuses))
+}
+
+/// Raise a project's quota to `quota_limit` until `expires_at` (a SQLite datetime). The
+/// limit must be above the one in effect, or 0 to lift the quota.
+pub async fn set_override(
+    pool: &DbPool,
+    project_id: &str,
+    quota: Quota,
+    quota_limit: u64,
+    expires_at: &str,
+    created_by: &str,
+    reason: Option<&str>,
+) -> Result<OverrideOutcome> {
+    let mut tx = pool.begin().await?;
+    let (base, active_override) = limit(&mut tx, project_id, quota).await?;
+    let current = effective_limit(base, active_override.as_ref());
+    if current == 0 {
+        return Ok(OverrideOutcome::Rejected(format!(
+            "The {} quota of project '{}' is already unlimited",
+            quota, project_id
+        )));
+    }
+    if quota_limit != 0 && quota_limit <= current {
+        return Ok(OverrideOutcome::Rejected(format!(
+            "An override must raise the {} quota above its current limit of {}",
+            quota, current
+        )));
+    }
+
+    let created = sqlx::query_as::<_, QuotaOverride>(&format!(
+        r#"
+        INSERT INTO quota_overrides (project_id, quota, quota_limit, reason, created_by, expires_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        RETURNING {}
+    "#,
+        OVERRIDE_COLUMNS
+    ))
+    .bind(project_id)
+    .bind(quota.as_str())
+    .bind(quota_limit as i64)
+    .bind(reason)
+    .bind(created_by)
+    .bind(expires_at)
+    .fetch_returned_one(&mut *tx)
+    .await?;
+    tx.commit().await.inspect_err(|e| {
+        error!(
+            "Failed to override {} quota of project '{}': {:?}",
+            quota, project_id, e
+        )
+    })?;
+    Ok(OverrideOutcome::Created(Box::new(created)))
+}
+
+/// Quotas that reached 90% since they were last below it, across all projects; each
+/// crossing is returned once
+pub async fn mark_warnings(pool: &DbPool) -> Result<Vec<QuotaWarning>> {
+    let mut conn = pool.acquire().await?;
+    let projects: Vec<String> = sqlx::query_scalar("SELECT project_id FROM project_usage")
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let mut warnings = Vec::new();
+    for project_id in projects {
+        for quota in Quota::ALL {
+            let status = status(&mut conn, &project_id, quota).await?;
+            let warned_column = format!("warned_{}_at", quota);
+            if matches!(status.status, "warning" | "exceeded") {
+                let marked = sqlx::query(&format!(
+                    "UPDATE project_usage SET {0} = datetime('now')
+                     WHERE project_id = ?1 AND {0} IS NULL",
+                    warned_column
+                ))
+                .bind(&project_id)
+                .execute(&mut *conn)
+                .await?;
+                if marked.rows_affected() > 0 {
+                    warnings.push(QuotaWarning {
+                        project_id: project_id.clone(),
+                        quota,
+                        usage: status.usage,
+                        limit: status.limit,
+                    });
+                }
+            } else {
+                sqlx::query(&format!(
+                    "UPDATE project_usage SET {0} = NULL WHERE project_id = ?1",
+                    warned_column
+                ))
+                .bind(&project_id)
+                .execute(&mut *conn)
+                .await?;
+            }
+        }
+    }
+    Ok(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{
+        comments::{Comment, CreateCommentRequest},
+        project_settings::ProjectSettings,
+    };
+    use crate::test_support::{memory_pool, Fixtures};
+
+    fn open_tickets(statuses: &[QuotaStatus]) -> &QuotaStatus {
+        statuses
+            .iter()
+            .find(|s| s.quota == Quota::OpenTickets)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_open_tickets_quota_boundary_and_override() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("quotas").create().await.unwrap();
+        let id = project.repository_name.clone();
+        let settings = ProjectSettings {
+            max_open_tickets: Some(3),
+            ..Default::default()
+        };
+        ProjectSettings::set(&pool, &id, &settings).await.unwrap();
+
+        for title in ["a", "b", "c"] {
+            fx.ticket(&project, title).create().await.unwrap();
+        }
+        let refused = fx.ticket(&project, "d").create().await.unwrap_err();
+        let exceeded = refused.downcast_ref::<QuotaExceeded>().unwrap();
+        assert_eq!((exceeded.limit, exceeded.usage), (3, 3));
+        assert_eq!(exceeded.quota, Quota::OpenTickets);
+        let statuses = report(&pool, &id).await.unwrap().unwrap();
+        assert_eq!(open_tickets(&statuses).status, "exceeded");
+
+        assert!(matches!(
+            set_override(
+                &pool,
+                &id,
+                Quota::OpenTickets,
+                3,
+                "2999-01-01 00:00:00",
+                "coord",
+                None
+            )
+            .await
+            .unwrap(),
+            OverrideOutcome::Rejected(_)
+        ));
+        set_override(
+            &pool,
+            &id,
+            Quota::OpenTickets,
+            4,
+            "2999-01-01 00:00:00",
+            "coord",
+            None,
+        )
+        .await
+        .unwrap();
+        fx.ticket(&project, "d").create().await.unwrap();
+        assert!(fx.ticket(&project, "e").create().await.is_err());
+
+        // An expired override no longer applies
+        sqlx::query("UPDATE quota_overrides SET expires_at = '2000-01-01 00:00:00'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let statuses = report(&pool, &id).await.unwrap().unwrap();
+        let status = open_tickets(&statuses);
+        assert_eq!((status.usage, status.limit), (4, 3));
+        assert!(status.active_override.is_none());
+
+        let warnings = mark_warnings(&pool).await.unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(mark_warnings(&pool).await.unwrap().is_empty());
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_usage_stays_accurate_after_deletes() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("usage").create().await.unwrap();
+        let other = fx.project("other").create().await.unwrap();
+        let id = project.repository_name.clone();
+        let mut tickets = Vec::new();
+        for title in ["a", "b", "c", "d"] {
+            tickets.push(fx.ticket(&project, title).create().await.unwrap());
+        }
+        fx.ticket(&other, "elsewhere").create().await.unwrap();
+        sqlx::query("UPDATE tickets SET state = 'closed' WHERE ticket_id = ?1")
+            .bind(&tickets[0].ticket_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE tickets SET state = 'on_hold' WHERE ticket_id = ?1")
+            .bind(&tickets[1].ticket_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for ticket in [&tickets[0], &tickets[2]] {
+            sqlx::query("DELETE FROM tickets WHERE ticket_id = ?1")
+                .bind(&ticket.ticket_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let counted: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM tickets WHERE project_id = ?1 AND state != 'closed'",
+        )
+        .bind(&id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let statuses = report(&pool, &id).await.unwrap().unwrap();
+        assert_eq!(open_tickets(&statuses).usage, counted as u64);
+        assert_eq!(counted, 2);
+
+        let comment = Comment::create_from_request(
+            &pool,
+            CreateCommentRequest {
+                ticket_id: tickets[1].ticket_id.clone(),
+                worker_type: "coding".to_string(),
+                worker_id: "w1".to_string(),
+                stage_number: 1,
+                content: "progress".to_string(),
+                reply_to_comment_id: None,
+            },
+        )
+        .await
+        .unwrap();
+        let comments = |statuses: &[QuotaStatus]| {
+            statuses
+                .iter()
+                .find(|s| s.quota == Quota::CommentsPerHour)
+                .unwrap()
+                .usage
+        };
+        let before = comments(&report(&pool, &id).await.unwrap().unwrap());
+        sqlx::query("DELETE FROM comments WHERE id = ?1")
+            .bind(comment.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let after = comments(&report(&pool, &id).await.unwrap().unwrap());
+        assert_eq!(after, before - 1);
+        pool.close().await;
+    }
+}
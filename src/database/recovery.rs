@@ -1,8 +1,61 @@
 use anyhow::Result;
+use serde::Serialize;
 use sqlx::Row;
 use tracing::{info, warn};
 
-use super::{tickets::TicketState, DbPool};
+use super::{
+    blocks::{BlockOutcome, Blocker, TicketBlock},
+    comments::Comment,
+    tickets::TicketState,
+    DbPool,
+};
+
+/// What startup recovery does with open tickets a worker held when the server stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum OrphanedTicketPolicy {
+    /// Release the claim so the ticket is queued again
+    Release,
+    /// Block the ticket on the coordinator, who reviews the interrupted work and unblocks it
+    Review,
+}
+
+/// What startup recovery changed; empty when the server had stopped cleanly
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StartupRecovery {
+    /// Workers recorded as running, now offline
+    pub workers_offline: Vec<String>,
+    /// Runs that never finished, now interrupted
+    pub runs_interrupted: Vec<i64>,
+    /// Open tickets whose claim was released so they are queued again
+    pub tickets_released: Vec<String>,
+    /// Open tickets blocked on the coordinator for review
+    pub tickets_held_for_review: Vec<String>,
+    /// Claims left on tickets that are on hold or closed
+    pub stale_claims_released: Vec<String>,
+}
+
+impl StartupRecovery {
+    pub fn is_empty(&self) -> bool {
+        self.workers_offline.is_empty()
+            && self.runs_interrupted.is_empty()
+            && self.tickets_released.is_empty()
+            && self.tickets_held_for_review.is_empty()
+            && self.stale_claims_released.is_empty()
+    }
+
+    /// One line for the log and the recovery event
+    pub fn summary(&self) -> String {
+        format!(
+            "Recovered from an unclean shutdown: {} workers marked offline, {} runs interrupted, {} tickets released, {} held for review, {} stale claims released",
+            self.workers_offline.len(),
+            self.runs_interrupted.len(),
+            self.tickets_released.len(),
+            self.tickets_held_for_review.len(),
+            self.stale_claims_released.len()
+        )
+    }
+}
 
 /// Recovery statistics for ticket processing
 #[derive(Debug, Default)]
@@ -61,8 +114,12 @@ impl TicketRecovery {
                 (state = 'open' AND processing_worker_id IS NOT NULL
                  AND (julianday('now') - julianday(updated_at)) * 24 * 60 > 5)
                 OR
-                -- Case 3: On-hold tickets that may be recoverable
-                (state = 'on_hold')
+                -- Case 3: On-hold tickets that may be recoverable; blocked ones wait for
+                -- their block to be lifted
+                (state = 'on_hold' AND NOT EXISTS (
+                    SELECT 1 FROM ticket_blocks b
+                    WHERE b.ticket_id = tickets.ticket_id AND b.unblocked_at IS NULL
+                ))
               )
             ORDER BY project_id, current_stage, priority DESC, created_at ASC
             "#,
@@ -85,6 +142,101 @@ impl TicketRecovery {
         Ok(tickets)
     }
 
+    /// Reconcile the database with a server that has just started: no worker process and
+    /// no session outlives a restart, so every worker still recorded as running is marked
+    /// offline, its open runs interrupted and the tickets it held released or held for review
+    /// per `policy`. Running it again changes nothing.
+    pub async fn recover_after_restart(
+        db: &DbPool,
+        policy: OrphanedTicketPolicy,
+    ) -> Result<StartupRecovery> {
+        let runs_interrupted = sqlx::query_scalar(
+            r#"
+            UPDATE worker_runs SET status = 'interrupted', finished_at = datetime('now')
+            WHERE status = 'running'
+            RETURNING id
+            "#,
+        )
+        .fetch_all(db)
+        .await?;
+
+        let workers_offline = sqlx::query_scalar(
+            r#"
+            UPDATE workers SET status = 'offline'
+            WHERE status IN ('spawning', 'active', 'idle')
+            RETURNING worker_id
+            "#,
+        )
+        .fetch_all(db)
+        .await?;
+
+        let orphaned: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT ticket_id, processing_worker_id FROM tickets
+            WHERE state = 'open' AND processing_worker_id IS NOT NULL
+            ORDER BY ticket_id
+            "#,
+        )
+        .fetch_all(db)
+        .await?;
+        let mut recovery = StartupRecovery {
+            workers_offline,
+            runs_interrupted,
+            ..Default::default()
+        };
+        for (ticket_id, worker_id) in orphaned {
+            if policy == OrphanedTicketPolicy::Review {
+                let reason = format!(
+                    "Worker {} was working on this ticket when the server stopped; review its work before unblocking",
+                    worker_id
+                );
+                let coordinator = Blocker::Agent {
+                    agent_id: "coordinator".to_string(),
+                    expected_unblock_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                };
+                match TicketBlock::block(db, &ticket_id, &reason, &coordinator, "system").await? {
+                    BlockOutcome::Blocked(_) => {
+                        recovery.tickets_held_for_review.push(ticket_id);
+                        continue;
+                    }
+                    BlockOutcome::Rejected(why) => {
+                        warn!(
+                            "Could not hold ticket {} for review, releasing it instead: {}",
+                            ticket_id, why
+                        );
+                    }
+                }
+            }
+            if Self::release_stalled_claim(db, &ticket_id).await? {
+                Comment::create(
+                    db,
+                    &ticket_id,
+                    Some("system"),
+                    Some("system"),
+                    Some(999),
+                    &format!(
+                        "Worker {} was working on this ticket when the server stopped; it is queued again",
+                        worker_id
+                    ),
+                )
+                .await?;
+                recovery.tickets_released.push(ticket_id);
+            }
+        }
+
+        recovery.stale_claims_released = sqlx::query_scalar(
+            r#"
+            UPDATE tickets SET processing_worker_id = NULL, updated_at = datetime('now')
+            WHERE processing_worker_id IS NOT NULL AND state != 'open'
+            RETURNING ticket_id
+            "#,
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(recovery)
+    }
+
     /// Release a stalled claim on a ticket
     pub async fn release_stalled_claim(db: &DbPool, ticket_id: &str) -> Result<bool> {
         let result = sqlx::query(
@@ -207,3 +359,166 @@ impl TicketRecovery {
         Ok(tickets)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{
+        tickets::Ticket,
+        worker_runs::{RunFilter, StartRunRequest, WorkerRun},
+        workers::Worker,
+    };
+    use crate::test_support::{memory_pool, Fixtures};
+
+    /// The state a crash leaves behind: two workers still recorded as running with open
+    /// runs, each holding an open ticket, and a claim left on a ticket put on hold
+    async fn crashed(pool: &DbPool) -> (String, String, String) {
+        let fx = Fixtures::new(pool);
+        let project = fx.project("crashed").create().await.unwrap();
+        let coder = fx.worker_type(&project, "coder").create().await.unwrap();
+        let mut held = Vec::new();
+        for (worker_id, status) in [
+            ("coder-1", "active"),
+            ("coder-2", "idle"),
+            ("coder-3", "finished"),
+        ] {
+            Worker::create(
+                pool,
+                Worker {
+                    worker_id: worker_id.to_string(),
+                    project_id: project.repository_name.clone(),
+                    worker_type: "coder".to_string(),
+                    status: status.to_string(),
+                    pid: Some(4242),
+                    queue_name: "crashed-coder".to_string(),
+                    started_at: "2026-01-01 00:00:00".to_string(),
+                    last_activity: "2026-01-01 00:00:00".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+            let ticket = fx
+                .ticket(&project, worker_id)
+                .assigned_to(&coder)
+                .claimed_by(worker_id)
+                .create()
+                .await
+                .unwrap();
+            let run = WorkerRun::start(
+                pool,
+                &StartRunRequest {
+                    worker_id,
+                    project_id: &project.repository_name,
+                    worker_type: "coder",
+                    ticket_id: &ticket.ticket_id,
+                    working_dir: "/tmp",
+                    max_runtime_secs: 3600,
+                    policy_violations: &[],
+                },
+            )
+            .await
+            .unwrap();
+            if status == "finished" {
+                WorkerRun::finish(pool, run, "completed", None, None, None, false)
+                    .await
+                    .unwrap();
+                Ticket::update_state(pool, &ticket.ticket_id, "on_hold")
+                    .await
+                    .unwrap();
+            }
+            held.push(ticket.ticket_id);
+        }
+        (held[0].clone(), held[1].clone(), held[2].clone())
+    }
+
+    async fn comment_count(pool: &DbPool, ticket_id: &str) -> usize {
+        Comment::get_by_ticket_id(pool, ticket_id)
+            .await
+            .unwrap()
+            .len()
+    }
+
+    #[tokio::test]
+    async fn test_recovery_releases_orphaned_work_once() {
+        let pool = memory_pool().await.unwrap();
+        let (first, second, on_hold) = crashed(&pool).await;
+
+        let recovery = TicketRecovery::recover_after_restart(&pool, OrphanedTicketPolicy::Release)
+            .await
+            .unwrap();
+        let mut offline = recovery.workers_offline.clone();
+        offline.sort();
+        assert_eq!(offline, ["coder-1", "coder-2"]);
+        assert_eq!(recovery.runs_interrupted.len(), 2);
+        assert_eq!(recovery.tickets_released, [first.clone(), second.clone()]);
+        assert!(recovery.tickets_held_for_review.is_empty());
+        assert_eq!(recovery.stale_claims_released, [on_hold.as_str()]);
+
+        for ticket_id in [&first, &second, &on_hold] {
+            let ticket = Ticket::get_by_id(&pool, ticket_id).await.unwrap().unwrap();
+            assert!(ticket.ticket.processing_worker_id.is_none());
+        }
+        assert_eq!(
+            Worker::get_by_id(&pool, "coder-3")
+                .await
+                .unwrap()
+                .unwrap()
+                .status,
+            "finished"
+        );
+        let runs = WorkerRun::list(
+            &pool,
+            &RunFilter {
+                limit: 10,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut statuses: Vec<_> = runs.iter().map(|run| run.status.as_str()).collect();
+        statuses.sort();
+        assert_eq!(statuses, ["completed", "interrupted", "interrupted"]);
+        assert!(runs.iter().all(|run| run.finished_at.is_some()));
+
+        // A second restart finds nothing left to do and adds nothing
+        let comments = comment_count(&pool, &first).await;
+        let again = TicketRecovery::recover_after_restart(&pool, OrphanedTicketPolicy::Release)
+            .await
+            .unwrap();
+        assert!(again.is_empty());
+        assert_eq!(comment_count(&pool, &first).await, comments);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_holds_orphaned_tickets_for_review() {
+        let pool = memory_pool().await.unwrap();
+        let (first, second, _) = crashed(&pool).await;
+
+        let recovery = TicketRecovery::recover_after_restart(&pool, OrphanedTicketPolicy::Review)
+            .await
+            .unwrap();
+        assert_eq!(recovery.tickets_held_for_review, [first.clone(), second]);
+        assert!(recovery.tickets_released.is_empty());
+
+        let ticket = Ticket::get_by_id(&pool, &first).await.unwrap().unwrap();
+        assert_eq!(ticket.ticket.state, "on_hold");
+        assert!(ticket.ticket.processing_worker_id.is_none());
+        let block = TicketBlock::active(&pool, &first).await.unwrap().unwrap();
+        assert_eq!(block.responsible_agent.as_deref(), Some("coordinator"));
+        assert!(block.reason.contains("coder-1"));
+
+        // Held tickets stay on hold through the respawn pass and later restarts
+        let unprocessed = TicketRecovery::find_unprocessed_tickets(&pool)
+            .await
+            .unwrap();
+        assert!(unprocessed.iter().all(|t| t.ticket_id != first));
+        let comments = comment_count(&pool, &first).await;
+        assert!(
+            TicketRecovery::recover_after_restart(&pool, OrphanedTicketPolicy::Review)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(comment_count(&pool, &first).await, comments);
+    }
+}
@@ -0,0 +1,714 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::{collections::HashMap, fmt};
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::{blocks::TicketBlock, pipeline::FOLLOWS_FIELD, tickets::TicketWithComments, DbPool};
+
+/// Typed link between two tickets, read as "from <relation> to"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationType {
+    /// An epic or parent ticket and one of its subtasks; a ticket has at most one parent
+    ParentOf,
+    /// Lightweight link without ordering; stored once for both directions
+    RelatesTo,
+    /// A closed ticket and the ticket it duplicates
+    Duplicates,
+    /// A stage pipeline ticket and the ticket it continues
+    Follows,
+}
+
+impl RelationType {
+    pub fn all() -> [RelationType; 4] {
+        [
+            RelationType::ParentOf,
+            RelationType::RelatesTo,
+            RelationType::Duplicates,
+            RelationType::Follows,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelationType::ParentOf => "parent_of",
+            RelationType::RelatesTo => "relates_to",
+            RelationType::Duplicates => "duplicates",
+            RelationType::Follows => "follows",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<RelationType> {
+        Self::all().into_iter().find(|t| t.as_str() == value)
+    }
+}
+
+impl fmt::Display for RelationType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The other end of a relation
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct RelatedTicket {
+    pub ticket_id: String,
+    pub short_id: String,
+    pub title: String,
+    pub state: String,
+    pub current_stage: String,
+}
+
+/// What closing a ticket changes, computed before anything is written so the same plan can
+/// be shown as a dry run or applied
+#[derive(Debug, Clone, Serialize)]
+pub struct ClosePlan {
+    pub ticket: RelatedTicket,
+    /// Closed along with the ticket, parents before their children
+    pub descendants: Vec<RelatedTicket>,
+    /// Direct children that are still open; without a cascade the close is refused
+    #[serde(skip)]
+    pub open_children: Vec<String>,
+    /// Tickets blocked on one of the closed tickets, whose blocks are lifted
+    pub unblocks: Vec<String>,
+}
+
+/// Closed children of a parent ticket out of all its children
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ChildProgress {
+    pub closed: i64,
+    pub total: i64,
+}
+
+/// Open parent ticket with how far its children are
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ParentProgress {
+    pub ticket_id: String,
+    pub short_id: String,
+    pub title: String,
+    pub progress: ChildProgress,
+}
+
+/// Relations of one ticket grouped by type and direction
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct TicketRelations {
+    /// Children of this ticket
+    pub parent_of: Vec<RelatedTicket>,
+    /// Parent of this ticket, at most one
+    pub child_of: Vec<RelatedTicket>,
+    pub relates_to: Vec<RelatedTicket>,
+    /// Ticket this closed ticket duplicates
+    pub duplicates: Vec<RelatedTicket>,
+    /// Tickets closed as duplicates of this one
+    pub duplicated_by: Vec<RelatedTicket>,
+    /// Ticket this one continues in a stage pipeline
+    pub follows: Vec<RelatedTicket>,
+    pub followed_by: Vec<RelatedTicket>,
+    /// Closed children out of all children, when the ticket has any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<ChildProgress>,
+}
+
+/// Result of adding or removing a relation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelationOutcome {
+    Applied,
+    /// The change is not allowed; the reason is meant for the caller
+    Rejected(String),
+}
+
+#[derive(FromRow)]
+struct Endpoint {
+    project_id: String,
+    state: String,
+    parent_ticket_id: Option<String>,
+    duplicate_of: Option<String>,
+    follows: Option<String>,
+}
+
+#[derive(FromRow)]
+struct RelationRow {
+    relation_type: String,
+    outgoing: bool,
+    #[sqlx(flatten)]
+    ticket: RelatedTicket,
+}
+
+async fn endpoint(pool: &DbPool, ticket_id: &str) -> Result<Option<Endpoint>> {
+    Ok(sqlx::query_as::<_, Endpoint>(
+        r#"
+        SELECT project_id, state, parent_ticket_id, duplicate_of,
+               CASE WHEN json_valid(custom_fields)
+                    THEN json_extract(custom_fields, '$.follows') END AS follows
+        FROM tickets WHERE ticket_id = ?1
+    "#,
+    )
+    .bind(ticket_id)
+    .fetch_optional(pool)
+    .await?)
+}
+
+/// Ancestors of `ticket_id` through parent_of, nearest first
+pub async fn ancestors(pool: &DbPool, ticket_id: &str) -> Result<Vec<RelatedTicket>> {
+    let tickets = sqlx::query_as::<_, RelatedTicket>(
+        r#"
+        WITH RECURSIVE up(ticket_id, depth) AS (
+            SELECT from_ticket_id, 1 FROM ticket_relations
+            WHERE to_ticket_id = ?1 AND relation_type = 'parent_of'
+            UNION
+            SELECT r.from_ticket_id, up.depth + 1 FROM ticket_relations r
+            JOIN up ON r.to_ticket_id = up.ticket_id AND r.relation_type = 'parent_of'
+        )
+        SELECT t.ticket_id, t.short_id, t.title, t.state, t.current_stage
+        FROM up JOIN tickets t ON t.ticket_id = up.ticket_id
+        ORDER BY up.depth
+    "#,
+    )
+    .bind(ticket_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(tickets)
+}
+
+/// Direct children of `ticket_id`, oldest first
+pub async fn children(pool: &DbPool, ticket_id: &str) -> Result<Vec<RelatedTicket>> {
+    let tickets = sqlx::query_as::<_, RelatedTicket>(
+        r#"
+        SELECT t.ticket_id, t.short_id, t.title, t.state, t.current_stage
+        FROM ticket_relations r JOIN tickets t ON t.ticket_id = r.to_ticket_id
+        WHERE r.from_ticket_id = ?1 AND r.relation_type = 'parent_of'
+        ORDER BY t.created_at, t.ticket_id
+    "#,
+    )
+    .bind(ticket_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(tickets)
+}
+
+/// The plan for closing `ticket_id`; None when the ticket does not exist
+pub async fn plan_close(pool: &DbPool, ticket_id: &str) -> Result<Option<ClosePlan>> {
+    let ticket = sqlx::query_as::<_, RelatedTicket>(
+        "SELECT ticket_id, short_id, title, state, current_stage FROM tickets WHERE ticket_id = ?1",
+    )
+    .bind(ticket_id)
+    .fetch_optional(pool)
+    .await?;
+    let Some(ticket) = ticket else {
+        return Ok(None);
+    };
+
+    let open_children = children(pool, ticket_id)
+        .await?
+        .into_iter()
+        .filter(|child| child.state != "closed")
+        .map(|child| child.ticket_id)
+        .collect();
+    let descendants = open_descendants(pool, ticket_id).await?;
+    let mut unblocks = Vec::new();
+    for closing in std::iter::once(&ticket).chain(&descendants) {
+        for waiting in TicketBlock::waiting_on(pool, &closing.ticket_id).await? {
+            if !unblocks.contains(&waiting) {
+                unblocks.push(waiting);
+            }
+        }
+    }
+
+    Ok(Some(ClosePlan {
+        ticket,
+        descendants,
+        open_children,
+        unblocks,
+    }))
+}
+
+/// Descendants of `ticket_id` that are not closed, parents before their children
+pub async fn open_descendants(pool: &DbPool, ticket_id: &str) -> Result<Vec<RelatedTicket>> {
+    let tickets = sqlx::query_as::<_, RelatedTicket>(
+        r#"
+        WITH RECURSIVE down(ticket_id, depth) AS (
+            SELECT to_ticket_id, 1 FROM ticket_relations
+            WHERE from_ticket_id = ?1 AND relation_type = 'parent_of'
+            UNION
+            SELECT r.to_ticket_id, down.depth + 1 FROM ticket_relations r
+            JOIN down ON r.from_ticket_id = down.ticket_id AND r.relation_type = 'parent_of'
+        )
+        SELECT t.ticket_id, t.short_id, t.title, t.state, t.current_stage
+        FROM down JOIN tickets t ON t.ticket_id = down.ticket_id
+        WHERE t.state != 'closed'
+        GROUP BY t.ticket_id
+        ORDER BY MIN(down.depth), t.created_at
+    "#,
+    )
+    .bind(ticket_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(tickets)
+}
+
+/// Child progress of every parent ticket in `project_id`, by parent ticket id
+pub async fn progress_by_parent(
+    pool: &DbPool,
+    project_id: &str,
+) -> Result<HashMap<String, ChildProgress>> {
+    let rows: Vec<(String, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT r.from_ticket_id, COALESCE(SUM(c.state = 'closed'), 0), COUNT(*)
+        FROM ticket_relations r
+        JOIN tickets p ON p.ticket_id = r.from_ticket_id
+        JOIN tickets c ON c.ticket_id = r.to_ticket_id
+        WHERE r.relation_type = 'parent_of' AND p.project_id = ?1
+        GROUP BY r.from_ticket_id
+    "#,
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await
+    .inspect_err(|e| warn!("Failed to roll up child progress: {:?}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(ticket_id, closed, total)| (ticket_id, ChildProgress { closed, total }))
+        .collect())
+}
+
+/// Parent tickets that are not closed, with their child progress, least done first
+pub async fn open_parents(pool: &DbPool, project_id: Option<&str>) -> Result<Vec<ParentProgress>> {
+    let rows: Vec<(String, String, String, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT p.ticket_id, p.short_id, p.title, COALESCE(SUM(c.state = 'closed'), 0), COUNT(*)
+        FROM ticket_relations r
+        JOIN tickets p ON p.ticket_id = r.from_ticket_id
+        JOIN tickets c ON c.ticket_id = r.to_ticket_id
+        WHERE r.relation_type = 'parent_of' AND p.state != 'closed'
+          AND (?1 IS NULL OR p.project_id = ?1)
+        GROUP BY p.ticket_id
+        ORDER BY CAST(SUM(c.state = 'closed') AS REAL) / COUNT(*), p.created_at
+    "#,
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await
+    .inspect_err(|e| warn!("Failed to list open parent tickets: {:?}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(ticket_id, short_id, title, closed, total)| ParentProgress {
+                ticket_id,
+                short_id,
+                title,
+                progress: ChildProgress { closed, total },
+            },
+        )
+        .collect())
+}
+
+/// Every relation of `ticket_id`, in both directions
+pub async fn list(pool: &DbPool, ticket_id: &str) -> Result<TicketRelations> {
+    let rows = sqlx::query_as::<_, RelationRow>(
+        r#"
+        SELECT r.relation_type, 1 AS outgoing,
+               t.ticket_id, t.short_id, t.title, t.state, t.current_stage
+        FROM ticket_relations r JOIN tickets t ON t.ticket_id = r.to_ticket_id
+        WHERE r.from_ticket_id = ?1
+        UNION ALL
+        SELECT r.relation_type, 0 AS outgoing,
+               t.ticket_id, t.short_id, t.title, t.state, t.current_stage
+        FROM ticket_relations r JOIN tickets t ON t.ticket_id = r.from_ticket_id
+        WHERE r.to_ticket_id = ?1
+        ORDER BY 1, 3
+    "#,
+    )
+    .bind(ticket_id)
+    .fetch_all(pool)
+    .await
+    .inspect_err(|e| warn!("Failed to list relations of {}: {:?}", ticket_id, e))?;
+
+    let mut relations = TicketRelations::default();
+    for row in rows {
+        let group = match (RelationType::parse(&row.relation_type), row.outgoing) {
+            (Some(RelationType::ParentOf), true) => &mut relations.parent_of,
+            (Some(RelationType::ParentOf), false) => &mut relations.child_of,
+            (Some(RelationType::RelatesTo), _) => &mut relations.relates_to,
+            (Some(RelationType::Duplicates), true) => &mut relations.duplicates,
+            (Some(RelationType::Duplicates), false) => &mut relations.duplicated_by,
+            (Some(RelationType::Follows), true) => &mut relations.follows,
+            (Some(RelationType::Follows), false) => &mut relations.followed_by,
+            (None, _) => continue,
+        };
+        group.push(row.ticket);
+    }
+    if !relations.parent_of.is_empty() {
+        relations.progress = Some(ChildProgress {
+            closed: relations
+                .parent_of
+                .iter()
+                .filter(|t| t.state == "closed")
+                .count() as i64,
+            total: relations.parent_of.len() as i64,
+        });
+    }
+    Ok(relations)
+}
+
+/// Link `from_ticket_id` to `to_ticket_id`. parent_of must keep the hierarchy a forest
+/// within one project, duplicates needs the duplicate closed already (an open ticket is
+/// closed as a duplicate through the queue manager instead), and follows stays within one
+/// project with one predecessor per ticket. Adding an existing relation is a no-op.
+pub async fn add(
+    pool: &DbPool,
+    from_ticket_id: &str,
+    to_ticket_id: &str,
+    relation_type: RelationType,
+) -> Result<RelationOutcome> {
+    let reject = |reason: String| Ok(RelationOutcome::Rejected(reason));
+    if from_ticket_id == to_ticket_id {
+        return reject(format!(
+            "A ticket cannot be related to itself ({})",
+            relation_type
+        ));
+    }
+    let Some(from) = endpoint(pool, from_ticket_id).await? else {
+        return reject(format!("Ticket '{}' not found", from_ticket_id));
+    };
+    let Some(to) = endpoint(pool, to_ticket_id).await? else {
+        return reject(format!("Ticket '{}' not found", to_ticket_id));
+    };
+    if relation_type != RelationType::RelatesTo && from.project_id != to.project_id {
+        return reject(format!(
+            "{} links tickets of one project; '{}' is in '{}', '{}' in '{}'",
+            relation_type, from_ticket_id, from.project_id, to_ticket_id, to.project_id
+        ));
+    }
+
+    match relation_type {
+        RelationType::ParentOf => {
+            match to.parent_ticket_id.as_deref() {
+                Some(parent) if parent == from_ticket_id => return Ok(RelationOutcome::Applied),
+                Some(parent) => {
+                    return reject(format!(
+                        "Ticket '{}' already has parent '{}'; remove that relation first",
+                        to_ticket_id, parent
+                    ))
+                }
+                None => {}
+            }
+            if ancestors(pool, from_ticket_id)
+                .await?
+                .iter()
+                .any(|t| t.ticket_id == to_ticket_id)
+            {
+                return reject(format!(
+                    "Ticket '{}' is an ancestor of '{}'; parent_of would make a cycle",
+                    to_ticket_id, from_ticket_id
+                ));
+            }
+            sqlx::query(
+                "UPDATE tickets SET parent_ticket_id = ?2, updated_at = datetime('now') WHERE ticket_id = ?1",
+            )
+            .bind(to_ticket_id)
+            .bind(from_ticket_id)
+            .execute(pool)
+            .await?;
+        }
+        RelationType::Duplicates => {
+            if from.state != "closed" {
+                return reject(format!(
+                    "Ticket '{}' is not closed; close it as a duplicate instead",
+                    from_ticket_id
+                ));
+            }
+            // Link to the end of a duplicate chain so every duplicate points at a live ticket
+            let original = to.duplicate_of.as_deref().unwrap_or(to_ticket_id);
+            if original == from_ticket_id {
+                return reject(format!(
+                    "Ticket '{}' is already a duplicate of '{}'",
+                    to_ticket_id, from_ticket_id
+                ));
+            }
+            sqlx::query("UPDATE tickets SET duplicate_of = ?2 WHERE ticket_id = ?1")
+                .bind(from_ticket_id)
+                .bind(original)
+                .execute(pool)
+                .await?;
+        }
+        RelationType::Follows => {
+            match from.follows.as_deref() {
+                Some(follows) if follows == to_ticket_id => return Ok(RelationOutcome::Applied),
+                Some(follows) => {
+                    return reject(format!(
+                        "Ticket '{}' already follows '{}'; remove that relation first",
+                        from_ticket_id, follows
+                    ))
+                }
+                None => {}
+            }
+            sqlx::query(&format!(
+                "UPDATE tickets
+                 SET custom_fields = json_set(CASE WHEN json_valid(custom_fields)
+                                                   THEN custom_fields ELSE '{{}}' END,
+                                              '$.{}', ?2),
+                     updated_at = datetime('now')
+                 WHERE ticket_id = ?1",
+                FOLLOWS_FIELD
+            ))
+            .bind(from_ticket_id)
+            .bind(to_ticket_id)
+            .execute(pool)
+            .await?;
+        }
+        RelationType::RelatesTo => {
+            let (a, b) = if from_ticket_id < to_ticket_id {
+                (from_ticket_id, to_ticket_id)
+            } else {
+                (to_ticket_id, from_ticket_id)
+            };
+            sqlx::query(
+                "INSERT OR IGNORE INTO ticket_relations (from_ticket_id, to_ticket_id, relation_type)
+                 VALUES (?1, ?2, 'relates_to')",
+            )
+            .bind(a)
+            .bind(b)
+            .execute(pool)
+            .await?;
+        }
+    }
+    Ok(RelationOutcome::Applied)
+}
+
+/// Unlink `from_ticket_id` from `to_ticket_id`; relates_to is removed in either direction.
+/// Removing a duplicates relation leaves the ticket closed.
+pub async fn remove(
+    pool: &DbPool,
+    from_ticket_id: &str,
+    to_ticket_id: &str,
+    relation_type: RelationType,
+) -> Result<RelationOutcome> {
+    let result =
+        match relation_type {
+            RelationType::ParentOf => {
+                sqlx::query(
+                    "UPDATE tickets SET parent_ticket_id = NULL, updated_at = datetime('now')
+             WHERE ticket_id = ?2 AND parent_ticket_id = ?1",
+                )
+                .bind(from_ticket_id)
+                .bind(to_ticket_id)
+                .execute(pool)
+                .await?
+            }
+            RelationType::Duplicates => sqlx::query(
+                "UPDATE tickets SET duplicate_of = NULL WHERE ticket_id = ?1 AND duplicate_of = ?2",
+            )
+            .bind(from_ticket_id)
+            .bind(to_ticket_id)
+            .execute(pool)
+            .await?,
+            RelationType::Follows => {
+                sqlx::query(&format!(
+                    "UPDATE tickets
+             SET custom_fields = json_remove(custom_fields, '$.{field}'),
+                 updated_at = datetime('now')
+             WHERE ticket_id = ?1 AND json_valid(custom_fields)
+               AND json_extract(custom_fields, '$.{field}') = ?2",
+                    field = FOLLOWS_FIELD
+                ))
+                .bind(from_ticket_id)
+                .bind(to_ticket_id)
+                .execute(pool)
+                .await?
+            }
+            RelationType::RelatesTo => {
+                sqlx::query(
+                    "DELETE FROM ticket_relations
+             WHERE relation_type = 'relates_to'
+               AND ((from_ticket_id = ?1 AND to_ticket_id = ?2)
+                 OR (from_ticket_id = ?2 AND to_ticket_id = ?1))",
+                )
+                .bind(from_ticket_id)
+                .bind(to_ticket_id)
+                .execute(pool)
+                .await?
+            }
+        };
+    if result.rows_affected() == 0 {
+        return Ok(RelationOutcome::Rejected(format!(
+            "Ticket '{}' has no {} relation to '{}'",
+            from_ticket_id, relation_type, to_ticket_id
+        )));
+    }
+    Ok(RelationOutcome::Applied)
+}
+
+impl TicketWithComments {
+    /// Attach the ticket's relations, grouped by type
+    pub async fn with_relations(mut self, pool: &DbPool) -> Result<Self> {
+        self.relations = Some(list(pool, &self.ticket.ticket_id).await?);
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    async fn close(pool: &DbPool, ticket_id: &str) {
+        sqlx::query(
+            "UPDATE tickets SET state = 'closed', closed_at = datetime('now') WHERE ticket_id = ?1",
+        )
+        .bind(ticket_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    fn ids(tickets: &[RelatedTicket]) -> Vec<&str> {
+        tickets.iter().map(|t| t.ticket_id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_parent_of_stays_a_single_parent_forest() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("epics").create().await.unwrap();
+        let other = fx.project("elsewhere").create().await.unwrap();
+        let epic = fx.ticket(&project, "Billing epic").create().await.unwrap();
+        let story = fx.ticket(&project, "Invoices").create().await.unwrap();
+        let task = fx.ticket(&project, "Invoice PDF").create().await.unwrap();
+        let second_epic = fx.ticket(&project, "Reports epic").create().await.unwrap();
+        let foreign = fx.ticket(&other, "Elsewhere").create().await.unwrap();
+        let (epic, story, task) = (&epic.ticket_id, &story.ticket_id, &task.ticket_id);
+
+        let add_parent = |parent: &str, child: &str| {
+            let (pool, parent, child) = (pool.clone(), parent.to_string(), child.to_string());
+            async move {
+                add(&pool, &parent, &child, RelationType::ParentOf)
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(add_parent(epic, story).await, RelationOutcome::Applied);
+        assert_eq!(add_parent(story, task).await, RelationOutcome::Applied);
+        assert_eq!(add_parent(epic, story).await, RelationOutcome::Applied);
+
+        // Cycles, second parents, self links and other projects are rejected
+        for (parent, child, reason) in [
+            (task.as_str(), epic.as_str(), "cycle"),
+            (epic.as_str(), epic.as_str(), "itself"),
+            (
+                second_epic.ticket_id.as_str(),
+                story.as_str(),
+                "already has parent",
+            ),
+            (foreign.ticket_id.as_str(), epic.as_str(), "one project"),
+        ] {
+            let RelationOutcome::Rejected(message) = add_parent(parent, child).await else {
+                panic!("{} -> {} was allowed", parent, child);
+            };
+            assert!(message.contains(reason), "{}", message);
+        }
+
+        assert_eq!(ids(&ancestors(&pool, task).await.unwrap()), [story, epic]);
+        assert_eq!(ids(&children(&pool, epic).await.unwrap()), [story]);
+        assert_eq!(
+            ids(&open_descendants(&pool, epic).await.unwrap()),
+            [story, task]
+        );
+
+        close(&pool, task).await;
+        let relations = list(&pool, story).await.unwrap();
+        assert_eq!(ids(&relations.child_of), [epic]);
+        assert_eq!(ids(&relations.parent_of), [task]);
+        assert_eq!(
+            relations.progress,
+            Some(ChildProgress {
+                closed: 1,
+                total: 1
+            })
+        );
+        assert_eq!(
+            progress_by_parent(&pool, "epics").await.unwrap()[epic],
+            ChildProgress {
+                closed: 0,
+                total: 1
+            }
+        );
+        let parents = open_parents(&pool, Some("epics")).await.unwrap();
+        assert_eq!(
+            parents
+                .iter()
+                .map(|p| p.ticket_id.as_str())
+                .collect::<Vec<_>>(),
+            [epic, story]
+        );
+
+        // Removing the relation clears the parent column it mirrors
+        assert_eq!(
+            remove(&pool, epic, story, RelationType::ParentOf)
+                .await
+                .unwrap(),
+            RelationOutcome::Applied
+        );
+        assert!(list(&pool, epic).await.unwrap().progress.is_none());
+        assert!(matches!(
+            remove(&pool, epic, story, RelationType::ParentOf)
+                .await
+                .unwrap(),
+            RelationOutcome::Rejected(_)
+        ));
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_relates_duplicates_and_follows() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("links").create().await.unwrap();
+        let a = fx.ticket(&project, "Login fails").create().await.unwrap();
+        let b = fx.ticket(&project, "Login broken").create().await.unwrap();
+        let c = fx.ticket(&project, "Review login").create().await.unwrap();
+        let (a, b, c) = (&a.ticket_id, &b.ticket_id, &c.ticket_id);
+
+        // relates_to is one link seen from both ends
+        add(&pool, b, a, RelationType::RelatesTo).await.unwrap();
+        add(&pool, a, b, RelationType::RelatesTo).await.unwrap();
+        assert_eq!(ids(&list(&pool, a).await.unwrap().relates_to), [b]);
+        assert_eq!(ids(&list(&pool, b).await.unwrap().relates_to), [a]);
+
+        // Only a closed ticket can be recorded as a duplicate
+        assert!(matches!(
+            add(&pool, b, a, RelationType::Duplicates).await.unwrap(),
+            RelationOutcome::Rejected(_)
+        ));
+        close(&pool, b).await;
+        add(&pool, b, a, RelationType::Duplicates).await.unwrap();
+        let duplicate_of: Option<String> =
+            sqlx::query_scalar("SELECT duplicate_of FROM tickets WHERE ticket_id = ?1")
+                .bind(b)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(duplicate_of.as_deref(), Some(a.as_str()));
+        assert_eq!(ids(&list(&pool, a).await.unwrap().duplicated_by), [b]);
+
+        // follows writes the pipeline field, so the pipeline view agrees
+        add(&pool, c, a, RelationType::Follows).await.unwrap();
+        let ticket = crate::database::tickets::Ticket::get_by_id(&pool, c)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ticket.pipeline.follows.as_deref(), Some(a.as_str()));
+        assert_eq!(ids(&list(&pool, a).await.unwrap().followed_by), [c]);
+        remove(&pool, c, a, RelationType::Follows).await.unwrap();
+        assert!(list(&pool, a).await.unwrap().followed_by.is_empty());
+
+        remove(&pool, a, b, RelationType::RelatesTo).await.unwrap();
+        let relations = list(&pool, b).await.unwrap();
+        assert!(relations.relates_to.is_empty());
+        assert_eq!(ids(&relations.duplicates), [a]);
+        pool.close().await;
+    }
+}
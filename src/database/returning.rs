@@ -0,0 +1,74 @@
+//! Fetching the rows of `INSERT`/`UPDATE`/`DELETE ... RETURNING` statements.
+//!
+//! `fetch_one` and `fetch_optional` resolve with the first row while the connection's worker
+//! thread is still stepping the statement, and outside a transaction SQLite only commits
+//! once the statement is done. A caller acting on the row, e.g. replying with a new id, can
+//! then race the commit: other connections find nothing yet, or get `database is locked`.
+//! These helpers collect every row, so they resolve only after the statement has finished.
+
+use futures::future::BoxFuture;
+use sqlx::{
+    query::{QueryAs, QueryScalar},
+    sqlite::{SqliteArguments, SqliteRow},
+    Executor, FromRow, Sqlite,
+};
+
+pub trait FetchReturning<'q, O>: Sized {
+    /// Every row the statement returned
+    fn fetch_rows<'e, E>(self, executor: E) -> BoxFuture<'e, sqlx::Result<Vec<O>>>
+    where
+        'q: 'e,
+        O: 'e,
+        E: 'e + Executor<'e, Database = Sqlite>;
+
+    /// The row of a statement that returns at most one
+    fn fetch_returned<'e, E>(self, executor: E) -> BoxFuture<'e, sqlx::Result<Option<O>>>
+    where
+        'q: 'e,
+        O: Send + 'e,
+        E: 'e + Executor<'e, Database = Sqlite>,
+    {
+        let rows = self.fetch_rows(executor);
+        Box::pin(async move { Ok(rows.await?.pop()) })
+    }
+
+    /// The row of a statement that returns exactly one, `RowNotFound` otherwise
+    fn fetch_returned_one<'e, E>(self, executor: E) -> BoxFuture<'e, sqlx::Result<O>>
+    where
+        'q: 'e,
+        O: Send + 'e,
+        E: 'e + Executor<'e, Database = Sqlite>,
+    {
+        let row = self.fetch_returned(executor);
+        Box::pin(async move { row.await?.ok_or(sqlx::Error::RowNotFound) })
+    }
+}
+
+impl<'q, O> FetchReturning<'q, O> for QueryAs<'q, Sqlite, O, SqliteArguments<'q>>
+where
+    O: Send + Unpin + for<'r> FromRow<'r, SqliteRow>,
+{
+    fn fetch_rows<'e, E>(self, executor: E) -> BoxFuture<'e, sqlx::Result<Vec<O>>>
+    where
+        'q: 'e,
+        O: 'e,
+        E: 'e + Executor<'e, Database = Sqlite>,
+    {
+        Box::pin(self.fetch_all(executor))
+    }
+}
+
+impl<'q, O> FetchReturning<'q, O> for QueryScalar<'q, Sqlite, O, SqliteArguments<'q>>
+where
+    O: Send + Unpin,
+    (O,): Send + Unpin + for<'r> FromRow<'r, SqliteRow>,
+{
+    fn fetch_rows<'e, E>(self, executor: E) -> BoxFuture<'e, sqlx::Result<Vec<O>>>
+    where
+        'q: 'e,
+        O: 'e,
+        E: 'e + Executor<'e, Database = Sqlite>,
+    {
+        Box::pin(self.fetch_all(executor))
+    }
+}
@@ -0,0 +1,349 @@
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::SqliteConnection;
+use std::collections::HashMap;
+use tracing::warn;
+
+use super::worker_types::WorkerType;
+use crate::config::Config;
+
+/// Limits for assigning new tickets to worker types automatically
+#[derive(Debug, Clone, Copy)]
+pub struct RoutingPolicy {
+    /// Share of a ticket's tags a worker type must cover
+    pub min_score: f64,
+    /// Open tickets a worker type may already have (0 for unlimited)
+    pub max_open_tickets: usize,
+}
+
+impl From<&Config> for RoutingPolicy {
+    fn from(config: &Config) -> Self {
+        Self {
+            min_score: config.auto_assign_min_score,
+            max_open_tickets: config.auto_assign_max_open_tickets,
+        }
+    }
+}
+
+/// A worker type considered for a ticket
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Candidate {
+    pub worker_type: String,
+    /// Share of the ticket's tags among the worker type's capabilities
+    pub score: f64,
+    pub matched_tags: Vec<String>,
+    pub open_tickets: i64,
+}
+
+/// Why a ticket was not assigned automatically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotRoutedReason {
+    NoTags,
+    NoCapabilityMatch,
+    AllCandidatesOverloaded,
+}
+
+/// Outcome of automatic assignment for a new ticket
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RoutingDecision {
+    pub assigned: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worker_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<NotRoutedReason>,
+    /// Human-readable summary, also noted in the ticket's history when assigned
+    pub detail: String,
+    /// Worker types whose capabilities cover enough of the ticket's tags, best first
+    pub candidates: Vec<Candidate>,
+}
+
+impl RoutingDecision {
+    fn not_routed(reason: NotRoutedReason, detail: String, candidates: Vec<Candidate>) -> Self {
+        Self {
+            assigned: false,
+            worker_type: None,
+            reason: Some(reason),
+            detail,
+            candidates,
+        }
+    }
+}
+
+/// Pick the worker type for a ticket with `tags` among `worker_types`, each given with its
+/// capabilities and open ticket count. Candidates are ranked by score, then by fewest open
+/// tickets; the best one with room for another ticket that is not `unavailable` is assigned.
+pub fn decide(
+    tags: &[String],
+    worker_types: &[(String, Vec<String>, i64)],
+    unavailable: &[String],
+    policy: RoutingPolicy,
+) -> RoutingDecision {
+    if tags.is_empty() {
+        return RoutingDecision::not_routed(
+            NotRoutedReason::NoTags,
+            "Ticket has no tags to match against worker type capabilities".to_string(),
+            Vec::new(),
+        );
+    }
+
+    let mut candidates: Vec<Candidate> = worker_types
+        .iter()
+        .filter_map(|(worker_type, capabilities, open_tickets)| {
+            let matched_tags: Vec<String> = tags
+                .iter()
+                .filter(|tag| capabilities.contains(tag))
+                .cloned()
+                .collect();
+            let score = matched_tags.len() as f64 / tags.len() as f64;
+            (!matched_tags.is_empty() && score >= policy.min_score).then(|| Candidate {
+                worker_type: worker_type.clone(),
+                score,
+                matched_tags,
+                open_tickets: *open_tickets,
+            })
+        })
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.open_tickets.cmp(&b.open_tickets))
+            .then(a.worker_type.cmp(&b.worker_type))
+    });
+
+    if candidates.is_empty() {
+        return RoutingDecision::not_routed(
+            NotRoutedReason::NoCapabilityMatch,
+            format!(
+                "No worker type's capabilities cover at least {:.0}% of the tags {}",
+                policy.min_score * 100.0,
+                tags.join(", ")
+            ),
+            candidates,
+        );
+    }
+
+    let has_room = |candidate: &Candidate| {
+        !unavailable.contains(&candidate.worker_type)
+            && (policy.max_open_tickets == 0
+                || candidate.open_tickets < policy.max_open_tickets as i64)
+    };
+    match candidates.iter().find(|candidate| has_room(candidate)) {
+        Some(chosen) => RoutingDecision {
+            assigned: true,
+            worker_type: Some(chosen.worker_type.clone()),
+            reason: None,
+            detail: format!(
+                "Automatically assigned to worker type '{}' (score {:.2}, matched tags: {})",
+                chosen.worker_type,
+                chosen.score,
+                chosen.matched_tags.join(", ")
+            ),
+            candidates: candidates.clone(),
+        },
+        None => RoutingDecision::not_routed(
+            NotRoutedReason::AllCandidatesOverloaded,
+            format!(
+                "Every matching worker type is busy, unavailable or has {} or more open tickets: {}",
+                policy.max_open_tickets,
+                candidates
+                    .iter()
+                    .map(|c| match unavailable.contains(&c.worker_type) {
+                        true => format!("{} ({}, unavailable)", c.worker_type, c.open_tickets),
+                        false => format!("{} ({})", c.worker_type, c.open_tickets),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            candidates,
+        ),
+    }
+}
+
+/// Decide the worker type for a new ticket of `project_id`, reading capabilities and open
+/// ticket counts on `conn` so the decision and the ticket's creation share a transaction
+pub async fn route(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    tags: &[String],
+    policy: RoutingPolicy,
+) -> Result<RoutingDecision> {
+    let worker_types = sqlx::query_as::<_, WorkerType>(
+        r#"
+        SELECT id, project_id, worker_type, slug, short_description, system_prompt, spawn_overrides,
+               capabilities, created_at, updated_at
+        FROM worker_types
+        WHERE project_id = ?1 AND capabilities IS NOT NULL
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(&mut *conn)
+    .await
+    .inspect_err(|e| warn!("Failed to load capabilities of '{}': {:?}", project_id, e))?;
+
+    let open: HashMap<String, i64> = sqlx::query_as::<_, (String, i64)>(
+        r#"
+        SELECT current_stage, COUNT(*)
+        FROM tickets
+        WHERE project_id = ?1 AND state = 'open'
+        GROUP BY current_stage
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(&mut *conn)
+    .await
+    .inspect_err(|e| warn!("Failed to count open tickets of '{}': {:?}", project_id, e))?
+    .into_iter()
+    .collect();
+
+    // Worker types at their own capacity or reported in maintenance or error
+    let unavailable: Vec<String> = sqlx::query_scalar(
+        "SELECT worker_type FROM worker_type_load WHERE project_id = ?1 AND status != 'online'",
+    )
+    .bind(project_id)
+    .fetch_all(&mut *conn)
+    .await
+    .inspect_err(|e| {
+        warn!(
+            "Failed to load worker type status of '{}': {:?}",
+            project_id, e
+        )
+    })?;
+
+    let worker_types: Vec<(String, Vec<String>, i64)> = worker_types
+        .into_iter()
+        .map(|wt| {
+            let open_tickets = open.get(&wt.worker_type).copied().unwrap_or(0);
+            let capabilities = wt.capabilities();
+            (wt.worker_type, capabilities, open_tickets)
+        })
+        .collect();
+    Ok(decide(tags, &worker_types, &unavailable, policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tickets::Ticket;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_decide_ranks_and_explains() {
+        let policy = RoutingPolicy {
+            min_score: 0.5,
+            max_open_tickets: 2,
+        };
+        let worker_types = [
+            ("backend".to_string(), strings(&["api", "db"]), 1),
+            ("frontend".to_string(), strings(&["ui", "css"]), 0),
+            ("fullstack".to_string(), strings(&["api", "db", "ui"]), 0),
+        ];
+
+        // Equal scores go to the worker type with fewer open tickets
+        let decision = decide(&strings(&["api", "db"]), &worker_types, &[], policy);
+        assert_eq!(decision.worker_type.as_deref(), Some("fullstack"));
+        assert_eq!(decision.candidates.len(), 2);
+
+        let decision = decide(&strings(&["ui", "docs"]), &worker_types, &[], policy);
+        assert_eq!(decision.worker_type.as_deref(), Some("frontend"));
+        assert_eq!(decision.candidates[0].score, 0.5);
+
+        let decision = decide(&strings(&["docs", "ops", "ui"]), &worker_types, &[], policy);
+        assert_eq!(decision.reason, Some(NotRoutedReason::NoCapabilityMatch));
+        assert!(!decision.assigned);
+
+        let busy = [("backend".to_string(), strings(&["api"]), 2)];
+        let decision = decide(&strings(&["api"]), &busy, &[], policy);
+        assert_eq!(
+            decision.reason,
+            Some(NotRoutedReason::AllCandidatesOverloaded)
+        );
+        assert!(decision.detail.contains("backend (2)"));
+        let unlimited = RoutingPolicy {
+            max_open_tickets: 0,
+            ..policy
+        };
+        assert!(decide(&strings(&["api"]), &busy, &[], unlimited).assigned);
+        // Reported unavailable, or busy at the worker type's own capacity
+        let decision = decide(&strings(&["api"]), &busy, &strings(&["backend"]), unlimited);
+        assert!(decision.detail.contains("backend (2, unavailable)"));
+
+        assert_eq!(
+            decide(&[], &worker_types, &[], policy).reason,
+            Some(NotRoutedReason::NoTags)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_routed_ticket_created_in_worker_type() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("routing").create().await.unwrap();
+        fx.worker_type(&project, "planning").create().await.unwrap();
+        let backend = fx
+            .worker_type(&project, "backend")
+            .capabilities(&["API", "db"])
+            .create()
+            .await
+            .unwrap();
+        assert_eq!(backend.capabilities(), ["api", "db"]);
+        fx.ticket(&project, "Busy")
+            .assigned_to(&backend)
+            .create()
+            .await
+            .unwrap();
+
+        let policy = RoutingPolicy {
+            min_score: 0.5,
+            max_open_tickets: 2,
+        };
+        let request = |id: &str| crate::database::tickets::CreateTicketRequest {
+            ticket_id: id.to_string(),
+            project_id: "routing".to_string(),
+            title: "Add endpoint".to_string(),
+            description: String::new(),
+            execution_plan: strings(&["planning"]),
+            parent_ticket_id: None,
+            ticket_type: None,
+            dependency_status: None,
+            created_by_worker_id: None,
+            priority: None,
+            custom_fields: None,
+            due_at: None,
+            sla_minutes: None,
+            estimate_minutes: None,
+        };
+
+        let (ticket, decision) =
+            Ticket::create_routed(&pool, request("R-1"), Some((&strings(&["api"]), policy)))
+                .await
+                .unwrap();
+        assert!(decision.unwrap().assigned);
+        assert_eq!(ticket.current_stage, "backend");
+        assert_eq!(ticket.execution_plan, r#"["backend"]"#);
+        let note: Option<String> = sqlx::query_scalar(
+            "SELECT note FROM ticket_history WHERE ticket_id = 'R-1' AND change = 'created'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(note.unwrap().contains("'backend'"));
+
+        // backend now has two open tickets, so the next one stays where it was asked to start
+        let (ticket, decision) =
+            Ticket::create_routed(&pool, request("R-2"), Some((&strings(&["api"]), policy)))
+                .await
+                .unwrap();
+        assert_eq!(
+            decision.unwrap().reason,
+            Some(NotRoutedReason::AllCandidatesOverloaded)
+        );
+        assert_eq!(ticket.current_stage, "planning");
+
+        pool.close().await;
+    }
+}
@@ -0,0 +1,348 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::FromRow;
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use super::{returning::FetchReturning, DbPool};
+
+const SCHEDULE_COLUMNS: &str = "name, project_id, cron, template_name, title, description, priority, \
+     custom_fields, initial_stage, enabled, skip_if_open, last_run_at, last_ticket_id, next_run_at, \
+     created_at, updated_at";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct TicketSchedule {
+    pub name: String,
+    pub project_id: String,
+    pub cron: String,
+    pub template_name: Option<String>,
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Option<String>,
+    pub custom_fields: Option<String>, // JSON object
+    pub initial_stage: String,
+    pub enabled: bool,
+    pub skip_if_open: bool,
+    pub last_run_at: Option<String>,
+    pub last_ticket_id: Option<String>,
+    /// None while the schedule is disabled
+    pub next_run_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateScheduleRequest {
+    pub name: String,
+    pub project_id: String,
+    pub cron: String,
+    pub template_name: Option<String>,
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub custom_fields: Option<Map<String, Value>>,
+    /// Worker type the created tickets are queued for (default: planning)
+    pub initial_stage: Option<String>,
+    pub enabled: Option<bool>,
+    pub skip_if_open: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateScheduleRequest {
+    pub cron: Option<String>,
+    pub template_name: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub priority: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub custom_fields: Option<Map<String, Value>>,
+    pub initial_stage: Option<String>,
+    pub enabled: Option<bool>,
+    pub skip_if_open: Option<bool>,
+}
+
+impl UpdateScheduleRequest {
+    /// The full schedule definition after applying this update
+    pub fn apply_to(self, schedule: &TicketSchedule) -> Result<CreateScheduleRequest> {
+        let custom_fields = match self.custom_fields {
+            Some(fields) => Some(fields),
+            None => schedule.custom_fields()?,
+        };
+        Ok(CreateScheduleRequest {
+            name: schedule.name.clone(),
+            project_id: schedule.project_id.clone(),
+            cron: self.cron.unwrap_or_else(|| schedule.cron.clone()),
+            template_name: self
+                .template_name
+                .or_else(|| schedule.template_name.clone()),
+            title: self.title.unwrap_or_else(|| schedule.title.clone()),
+            description: self.description.or_else(|| schedule.description.clone()),
+            priority: self.priority.or_else(|| schedule.priority.clone()),
+            custom_fields,
+            initial_stage: Some(
+                self.initial_stage
+                    .unwrap_or_else(|| schedule.initial_stage.clone()),
+            ),
+            enabled: Some(self.enabled.unwrap_or(schedule.enabled)),
+            skip_if_open: Some(self.skip_if_open.unwrap_or(schedule.skip_if_open)),
+        })
+    }
+}
+
+impl CreateScheduleRequest {
+    pub fn initial_stage(&self) -> &str {
+        self.initial_stage.as_deref().unwrap_or("planning")
+    }
+}
+
+impl TicketSchedule {
+    pub async fn create(
+        pool: &DbPool,
+        req: &CreateScheduleRequest,
+        next_run_at: Option<&str>,
+    ) -> Result<TicketSchedule> {
+        let schedule = sqlx::query_as::<_, TicketSchedule>(&format!(
+            r#"
+            INSERT INTO ticket_schedules (
+                name, project_id, cron, template_name, title, description, priority,
+                custom_fields, initial_stage, enabled, skip_if_open, next_run_at
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+            RETURNING {}
+            "#,
+            SCHEDULE_COLUMNS
+        ))
+        .bind(&req.name)
+        .bind(&req.project_id)
+        .bind(&req.cron)
+        .bind(&req.template_name)
+        .bind(&req.title)
+        .bind(&req.description)
+        .bind(&req.priority)
+        .bind(
+            req.custom_fields
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?,
+        )
+        .bind(req.initial_stage())
+        .bind(req.enabled.unwrap_or(true))
+        .bind(req.skip_if_open.unwrap_or(true))
+        .bind(next_run_at)
+        .fetch_returned_one(pool)
+        .await
+        .inspect_err(|e| error!("Failed to create schedule '{}': {:?}", req.name, e))?;
+
+        Ok(schedule)
+    }
+
+    pub async fn get_by_name(pool: &DbPool, name: &str) -> Result<Option<TicketSchedule>> {
+        let schedule = sqlx::query_as::<_, TicketSchedule>(&format!(
+            "SELECT {} FROM ticket_schedules WHERE name = ?1",
+            SCHEDULE_COLUMNS
+        ))
+        .bind(name)
+        .fetch_optional(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to fetch schedule '{}': {:?}", name, e))?;
+
+        Ok(schedule)
+    }
+
+    pub async fn list(pool: &DbPool, project_id: Option<&str>) -> Result<Vec<TicketSchedule>> {
+        let schedules = sqlx::query_as::<_, TicketSchedule>(&format!(
+            "SELECT {} FROM ticket_schedules WHERE ?1 IS NULL OR project_id = ?1 ORDER BY name ASC",
+            SCHEDULE_COLUMNS
+        ))
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to list schedules: {:?}", e))?;
+
+        Ok(schedules)
+    }
+
+    /// Replace the definition of a schedule; its run history is kept
+    pub async fn update(
+        pool: &DbPool,
+        req: &CreateScheduleRequest,
+        next_run_at: Option<&str>,
+    ) -> Result<Option<TicketSchedule>> {
+        let schedule = sqlx::query_as::<_, TicketSchedule>(&format!(
+            r#"
+            UPDATE ticket_schedules
+            SET cron = ?2, template_name = ?3, title = ?4, description = ?5, priority = ?6,
+                custom_fields = ?7, initial_stage = ?8, enabled = ?9, skip_if_open = ?10,
+                next_run_at = ?11, updated_at = datetime('now')
+            WHERE name = ?1
+            RETURNING {}
+            "#,
+            SCHEDULE_COLUMNS
+        ))
+        .bind(&req.name)
+        .bind(&req.cron)
+        .bind(&req.template_name)
+        .bind(&req.title)
+        .bind(&req.description)
+        .bind(&req.priority)
+        .bind(
+            req.custom_fields
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?,
+        )
+        .bind(req.initial_stage())
+        .bind(req.enabled.unwrap_or(true))
+        .bind(req.skip_if_open.unwrap_or(true))
+        .bind(next_run_at)
+        .fetch_returned(pool)
+        .await
+        .inspect_err(|e| error!("Failed to update schedule '{}': {:?}", req.name, e))?;
+
+        Ok(schedule)
+    }
+
+    pub async fn delete(pool: &DbPool, name: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM ticket_schedules WHERE name = ?1")
+            .bind(name)
+            .execute(pool)
+            .await
+            .inspect_err(|e| error!("Failed to delete schedule '{}': {:?}", name, e))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Enabled schedules whose next run is due
+    pub async fn due(pool: &DbPool) -> Result<Vec<TicketSchedule>> {
+        let schedules = sqlx::query_as::<_, TicketSchedule>(&format!(
+            r#"
+            SELECT {} FROM ticket_schedules
+            WHERE enabled = 1 AND next_run_at IS NOT NULL AND next_run_at <= datetime('now')
+            ORDER BY next_run_at ASC
+            "#,
+            SCHEDULE_COLUMNS
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        Ok(schedules)
+    }
+
+    /// Move a due schedule to its next run; false when the run was already claimed,
+    /// so each occurrence is handled at most once
+    pub async fn claim_run(
+        pool: &DbPool,
+        name: &str,
+        due_at: &str,
+        next_run_at: Option<&str>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE ticket_schedules
+            SET next_run_at = ?3, last_run_at = datetime('now')
+            WHERE name = ?1 AND next_run_at = ?2
+            "#,
+        )
+        .bind(name)
+        .bind(due_at)
+        .bind(next_run_at)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn record_ticket(pool: &DbPool, name: &str, ticket_id: &str) -> Result<()> {
+        sqlx::query("UPDATE ticket_schedules SET last_ticket_id = ?2 WHERE name = ?1")
+            .bind(name)
+            .bind(ticket_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Whether the ticket created by the previous run is still open
+    pub async fn previous_ticket_open(&self, pool: &DbPool) -> Result<bool> {
+        let Some(ticket_id) = &self.last_ticket_id else {
+            return Ok(false);
+        };
+        let state: Option<String> =
+            sqlx::query_scalar("SELECT state FROM tickets WHERE ticket_id = ?1")
+                .bind(ticket_id)
+                .fetch_optional(pool)
+                .await?;
+        Ok(state.is_some_and(|state| state != "closed"))
+    }
+
+    pub fn custom_fields(&self) -> Result<Option<Map<String, Value>>> {
+        Ok(self
+            .custom_fields
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_pool, Fixtures};
+    use sqlx::Executor;
+
+    #[tokio::test]
+    async fn test_run_claimed_once_and_open_ticket_detected() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("scheduled").create().await.unwrap();
+        fx.ticket(&project, "Dependency audit")
+            .id("P-PLN-001")
+            .create()
+            .await
+            .unwrap();
+
+        let req: CreateScheduleRequest = serde_json::from_value(serde_json::json!({
+            "name": "audit",
+            "project_id": "scheduled",
+            "cron": "@weekly",
+            "title": "Dependency audit"
+        }))
+        .unwrap();
+        TicketSchedule::create(&pool, &req, Some("2000-01-01 00:00:00"))
+            .await
+            .unwrap();
+        assert_eq!(TicketSchedule::due(&pool).await.unwrap().len(), 1);
+
+        // A second claim of the same occurrence loses
+        let next = Some("2999-01-01 00:00:00");
+        assert!(
+            TicketSchedule::claim_run(&pool, "audit", "2000-01-01 00:00:00", next)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !TicketSchedule::claim_run(&pool, "audit", "2000-01-01 00:00:00", next)
+                .await
+                .unwrap()
+        );
+        assert!(TicketSchedule::due(&pool).await.unwrap().is_empty());
+
+        TicketSchedule::record_ticket(&pool, "audit", "P-PLN-001")
+            .await
+            .unwrap();
+        let schedule = TicketSchedule::get_by_name(&pool, "audit")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(schedule.last_run_at.is_some());
+        assert!(schedule.previous_ticket_open(&pool).await.unwrap());
+
+        pool.execute("UPDATE tickets SET state = 'closed' WHERE ticket_id = 'P-PLN-001'")
+            .await
+            .unwrap();
+        assert!(!schedule.previous_ticket_open(&pool).await.unwrap());
+
+        pool.close().await;
+    }
+}
@@ -0,0 +1,218 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use super::{outbox::OutboxEvent, DbPool};
+use crate::events::EventPayload;
+
+/// Ticket that crossed an SLA threshold during evaluation
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SlaAlert {
+    pub ticket_id: String,
+    pub project_id: String,
+    pub title: String,
+    pub priority: String,
+    pub state: String,
+    pub due_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct SlaSummary {
+    /// Not yet due, but due within the warning window
+    pub at_risk: i64,
+    /// Past due and not closed
+    pub breached: i64,
+}
+
+/// A due date given as RFC 3339, in SQLite's datetime format (UTC) so it compares with
+/// `datetime('now')`
+pub fn normalize_due_at(input: &str) -> std::result::Result<String, chrono::ParseError> {
+    Ok(chrono::DateTime::parse_from_rfc3339(input)?
+        .with_timezone(&chrono::Utc)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string())
+}
+
+impl SlaAlert {
+    /// Flag open tickets that are past due and queue `ticket_sla_breached` for each; each
+    /// ticket is returned once per state
+    pub async fn mark_breaches(pool: &DbPool) -> Result<Vec<SlaAlert>> {
+        let mut tx = pool.begin().await?;
+        let alerts = sqlx::query_as::<_, SlaAlert>(
+            r#"
+            UPDATE tickets
+            SET sla_breached = 1, sla_breached_state = state, sla_breached_at = datetime('now')
+            WHERE state != 'closed'
+              AND due_at IS NOT NULL
+              AND due_at <= datetime('now')
+              AND (sla_breached_state IS NULL OR sla_breached_state != state)
+            RETURNING ticket_id, project_id, title, priority, state, due_at
+        "#,
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .inspect_err(|e| error!("Failed to mark SLA breaches: {:?}", e))?;
+
+        for alert in &alerts {
+            OutboxEvent::write(
+                &mut tx,
+                &alert.ticket_id,
+                &EventPayload::ticket_sla_breached(
+                    &alert.ticket_id,
+                    &alert.project_id,
+                    &alert.state,
+                ),
+                None,
+                Some(&format!(
+                    "Ticket past due (due at {}, state {})",
+                    alert.due_at, alert.state
+                )),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(alerts)
+    }
+
+    /// Find tickets due within `warning_minutes`; each ticket is returned once per state
+    pub async fn mark_warnings(pool: &DbPool, warning_minutes: u64) -> Result<Vec<SlaAlert>> {
+        let alerts = sqlx::query_as::<_, SlaAlert>(
+            r#"
+            UPDATE tickets
+            SET sla_warned_state = state, sla_warned_at = datetime('now')
+            WHERE state != 'closed'
+              AND due_at IS NOT NULL
+              AND due_at > datetime('now')
+              AND due_at <= datetime('now', ?1)
+              AND (sla_warned_state IS NULL OR sla_warned_state != state)
+            RETURNING ticket_id, project_id, title, priority, state, due_at
+        "#,
+        )
+        .bind(format!("+{} minutes", warning_minutes))
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| error!("Failed to mark SLA warnings: {:?}", e))?;
+
+        Ok(alerts)
+    }
+}
+
+impl SlaSummary {
+    pub async fn load(
+        pool: &DbPool,
+        project_id: Option<&str>,
+        warning_minutes: u64,
+    ) -> Result<SlaSummary> {
+        let summary = sqlx::query_as::<_, SlaSummary>(
+            r#"
+            SELECT
+                COALESCE(SUM(CASE WHEN due_at > datetime('now') AND due_at <= datetime('now', ?1) THEN 1 ELSE 0 END), 0) AS at_risk,
+                COALESCE(SUM(CASE WHEN due_at <= datetime('now') THEN 1 ELSE 0 END), 0) AS breached
+            FROM tickets
+            WHERE state != 'closed' AND due_at IS NOT NULL
+              AND (?2 IS NULL OR project_id = ?2)
+        "#,
+        )
+        .bind(format!("+{} minutes", warning_minutes))
+        .bind(project_id)
+        .fetch_one(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to load SLA summary: {:?}", e))?;
+
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::outbox::OutboxEvent;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    async fn set(pool: &DbPool, ticket_id: &str, column: &str, value: &str) {
+        sqlx::query(&format!(
+            "UPDATE tickets SET {} = ?2 WHERE ticket_id = ?1",
+            column
+        ))
+        .bind(ticket_id)
+        .bind(value)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn test_due_at_is_normalized_to_utc() {
+        assert_eq!(
+            normalize_due_at("2026-03-01T17:30:00+02:00").unwrap(),
+            "2026-03-01 15:30:00"
+        );
+        assert_eq!(
+            normalize_due_at("2026-03-01T15:30:00Z").unwrap(),
+            "2026-03-01 15:30:00"
+        );
+        for garbage in ["tomorrow", "2026-03-01", "2026-13-01T00:00:00Z", ""] {
+            assert!(normalize_due_at(garbage).is_err(), "{}", garbage);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_breach_is_marked_once_per_state_and_never_for_closed_tickets() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("sla").create().await.unwrap();
+        let late = fx.ticket(&project, "Late").create().await.unwrap();
+        let done = fx.ticket(&project, "Done late").create().await.unwrap();
+        let soon = fx.ticket(&project, "Due soon").create().await.unwrap();
+        let past = normalize_due_at("2000-01-01T00:00:00Z").unwrap();
+        set(&pool, &late.ticket_id, "due_at", &past).await;
+        set(&pool, &done.ticket_id, "due_at", &past).await;
+        set(&pool, &done.ticket_id, "state", "closed").await;
+        sqlx::query(
+            "UPDATE tickets SET due_at = datetime('now', '+10 minutes') WHERE ticket_id = ?1",
+        )
+        .bind(&soon.ticket_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let breached = SlaAlert::mark_breaches(&pool).await.unwrap();
+        assert_eq!(breached.len(), 1);
+        assert_eq!(
+            (breached[0].ticket_id.as_str(), breached[0].state.as_str()),
+            (late.ticket_id.as_str(), "open")
+        );
+        assert!(SlaAlert::mark_breaches(&pool).await.unwrap().is_empty());
+
+        // A new state of the same overdue ticket is a new breach
+        set(&pool, &late.ticket_id, "state", "on_hold").await;
+        let breached = SlaAlert::mark_breaches(&pool).await.unwrap();
+        assert_eq!(breached.len(), 1);
+        assert_eq!(breached[0].state, "on_hold");
+        assert!(SlaAlert::mark_breaches(&pool).await.unwrap().is_empty());
+        let queued = OutboxEvent::list(&pool, Some("pending"), 20).await.unwrap();
+        assert_eq!(
+            queued
+                .iter()
+                .filter(|e| e.event_type == "ticket_sla_breached")
+                .count(),
+            2
+        );
+
+        let warned = SlaAlert::mark_warnings(&pool, 30).await.unwrap();
+        assert_eq!(warned.len(), 1);
+        assert_eq!(warned[0].ticket_id, soon.ticket_id);
+        assert!(SlaAlert::mark_warnings(&pool, 30).await.unwrap().is_empty());
+
+        // Closing a ticket takes it out of the SLA counts
+        set(&pool, &late.ticket_id, "state", "closed").await;
+        set(&pool, &soon.ticket_id, "state", "closed").await;
+        let summary = SlaSummary::load(&pool, Some("sla"), 30).await.unwrap();
+        assert_eq!((summary.at_risk, summary.breached), (0, 0));
+        assert!(SlaAlert::mark_breaches(&pool).await.unwrap().is_empty());
+
+        pool.close().await;
+    }
+}
@@ -0,0 +1,368 @@
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::FromRow;
+use std::collections::BTreeMap;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::{
+    blocks::{self, BlockStats},
+    relations::{self, ParentProgress},
+    worker_runs::WorkerRun,
+    DbPool,
+};
+
+const TICKET_STATES: &[&str] = &["open", "closed", "on_hold"];
+const TICKET_PRIORITIES: &[&str] = &["low", "medium", "high", "urgent"];
+const WORKER_STATUSES: &[&str] = &["spawning", "active", "idle", "finished", "failed"];
+/// Worker statuses counted as online agents
+const ONLINE_WORKER_STATUSES: &str = "'spawning', 'active', 'idle'";
+
+/// Counts for the dashboard, optionally limited to one project. Every known state,
+/// priority and status is present, with 0 when nothing is in it.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SystemStats {
+    pub tickets: TicketCounts,
+    /// Worker processes by status
+    pub workers: BTreeMap<String, i64>,
+    /// Running workers flagged as stalled
+    pub stalled_workers: i64,
+    pub comments: CommentCounts,
+    pub estimates: EstimateTotals,
+    /// Parent tickets not closed yet with their closed and total child counts, least done
+    /// first
+    pub open_parents: Vec<ParentProgress>,
+    /// Blocks in force and how long lifted ones lasted
+    pub blocks: BlockStats,
+    /// Every project, or the one asked for, most recently active first
+    pub projects: Vec<ProjectSummary>,
+}
+
+/// A project as listed by a project switcher
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ProjectSummary {
+    pub project_id: String,
+    pub short_description: Option<String>,
+    /// Tickets that are not closed
+    pub open_tickets: i64,
+    /// Workers spawning, active or idle
+    pub online_agents: i64,
+    /// Latest ticket update, comment, agent request or worker activity, else when the
+    /// project was last changed
+    pub last_activity: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TicketCounts {
+    pub total: i64,
+    pub by_state: BTreeMap<String, i64>,
+    pub by_priority: BTreeMap<String, i64>,
+    /// Tickets not closed that were closed before
+    pub reopened_open: i64,
+    /// Tickets not closed that were never closed
+    pub fresh_open: i64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CommentCounts {
+    pub last_24h: i64,
+}
+
+/// Estimated work of tickets that are not closed
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct EstimateTotals {
+    pub open_minutes: i64,
+    pub open_minutes_by_project: BTreeMap<String, i64>,
+    pub unestimated_open_tickets: i64,
+}
+
+impl SystemStats {
+    pub async fn load(pool: &DbPool, project_id: Option<&str>) -> Result<SystemStats> {
+        let by_state = Self::count_by(pool, "tickets", "state", project_id, TICKET_STATES).await?;
+        let by_priority =
+            Self::count_by(pool, "tickets", "priority", project_id, TICKET_PRIORITIES).await?;
+        let workers =
+            Self::count_by(pool, "workers", "status", project_id, WORKER_STATUSES).await?;
+
+        let mut builder = sqlx::QueryBuilder::new(
+            "SELECT COUNT(*) FROM comments c WHERE c.created_at >= datetime('now', '-1 day')",
+        );
+        if let Some(project_id) = project_id {
+            builder
+                .push(" AND c.ticket_id IN (SELECT ticket_id FROM tickets WHERE project_id = ")
+                .push_bind(project_id)
+                .push(")");
+        }
+        let last_24h: i64 = builder
+            .build_query_scalar()
+            .fetch_one(pool)
+            .await
+            .inspect_err(|e| warn!("Failed to count recent comments: {:?}", e))?;
+
+        let mut builder = sqlx::QueryBuilder::new(
+            "SELECT COALESCE(SUM(reopen_count > 0), 0), COALESCE(SUM(reopen_count = 0), 0) \
+             FROM tickets WHERE state != 'closed'",
+        );
+        if let Some(project_id) = project_id {
+            builder.push(" AND project_id = ").push_bind(project_id);
+        }
+        let (reopened_open, fresh_open): (i64, i64) = builder
+            .build_query_as()
+            .fetch_one(pool)
+            .await
+            .inspect_err(|e| warn!("Failed to count reopened tickets: {:?}", e))?;
+
+        Ok(SystemStats {
+            tickets: TicketCounts {
+                total: by_state.values().sum(),
+                by_state,
+                by_priority,
+                reopened_open,
+                fresh_open,
+            },
+            workers,
+            stalled_workers: WorkerRun::count_stalled(pool, project_id).await?,
+            comments: CommentCounts { last_24h },
+            estimates: Self::open_estimates(pool, project_id).await?,
+            open_parents: relations::open_parents(pool, project_id).await?,
+            blocks: blocks::stats(pool, project_id).await?,
+            projects: Self::project_summaries(pool, project_id).await?,
+        })
+    }
+
+    async fn project_summaries(
+        pool: &DbPool,
+        project_id: Option<&str>,
+    ) -> Result<Vec<ProjectSummary>> {
+        let projects = sqlx::query_as::<_, ProjectSummary>(&format!(
+            r#"
+            SELECT p.repository_name AS project_id, p.short_description,
+                   (SELECT COUNT(*) FROM tickets t
+                    WHERE t.project_id = p.repository_name AND t.state != 'closed') AS open_tickets,
+                   (SELECT COUNT(*) FROM workers w
+                    WHERE w.project_id = p.repository_name AND w.status IN ({})) AS online_agents,
+                   COALESCE((
+                       SELECT MAX(at) FROM (
+                           SELECT datetime(MAX(t.updated_at)) AS at FROM tickets t
+                           WHERE t.project_id = p.repository_name
+                           UNION ALL
+                           SELECT datetime(MAX(c.created_at)) FROM comments c
+                           JOIN tickets t ON t.ticket_id = c.ticket_id
+                           WHERE t.project_id = p.repository_name
+                           UNION ALL
+                           SELECT datetime(MAX(r.created_at)) FROM agent_requests r
+                           WHERE r.project_id = p.repository_name
+                           UNION ALL
+                           SELECT datetime(MAX(w.last_activity)) FROM workers w
+                           WHERE w.project_id = p.repository_name
+                       )
+                   ), datetime(p.updated_at)) AS last_activity
+            FROM projects p
+            WHERE ?1 IS NULL OR p.repository_name = ?1
+            ORDER BY last_activity DESC, p.repository_name
+            "#,
+            ONLINE_WORKER_STATUSES
+        ))
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to summarize projects: {:?}", e))?;
+
+        Ok(projects)
+    }
+
+    async fn open_estimates(pool: &DbPool, project_id: Option<&str>) -> Result<EstimateTotals> {
+        let mut builder = sqlx::QueryBuilder::new(
+            "SELECT project_id, COALESCE(SUM(estimate_minutes), 0), \
+             SUM(estimate_minutes IS NULL) FROM tickets WHERE state != 'closed'",
+        );
+        if let Some(project_id) = project_id {
+            builder.push(" AND project_id = ").push_bind(project_id);
+        }
+        builder.push(" GROUP BY project_id");
+
+        let rows: Vec<(String, i64, i64)> = builder
+            .build_query_as()
+            .fetch_all(pool)
+            .await
+            .inspect_err(|e| warn!("Failed to total open estimates: {:?}", e))?;
+
+        let mut totals = EstimateTotals::default();
+        for (project_id, minutes, unestimated) in rows {
+            totals.open_minutes += minutes;
+            totals.unestimated_open_tickets += unestimated;
+            totals.open_minutes_by_project.insert(project_id, minutes);
+        }
+        Ok(totals)
+    }
+
+    /// Row counts grouped by `column`, each covered by an index on
+    /// `(column)` or `(project_id, column)`
+    async fn count_by(
+        pool: &DbPool,
+        table: &str,
+        column: &str,
+        project_id: Option<&str>,
+        buckets: &[&str],
+    ) -> Result<BTreeMap<String, i64>> {
+        let mut builder =
+            sqlx::QueryBuilder::new(format!("SELECT {}, COUNT(*) FROM {}", column, table));
+        if let Some(project_id) = project_id {
+            builder.push(" WHERE project_id = ").push_bind(project_id);
+        }
+        builder.push(format!(" GROUP BY {}", column));
+
+        let rows: Vec<(String, i64)> = builder
+            .build_query_as()
+            .fetch_all(pool)
+            .await
+            .inspect_err(|e| warn!("Failed to count {} by {}: {:?}", table, column, e))?;
+
+        let mut counts: BTreeMap<String, i64> =
+            buckets.iter().map(|b| (b.to_string(), 0)).collect();
+        counts.extend(rows);
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::agent_requests::{AgentRequest, RequestDeliveryStats};
+    use crate::database::tickets::Priority;
+    use crate::test_support::{memory_pool, Fixtures};
+    use sqlx::Executor;
+
+    #[tokio::test]
+    async fn test_exact_counts_for_seeded_dataset() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let web = fx.project("stats-web").create().await.unwrap();
+        let api = fx.project("stats-api").create().await.unwrap();
+        let reviewer = fx.worker_type(&web, "reviewer").create().await.unwrap();
+        let coder = fx.worker_type(&web, "coder").create().await.unwrap();
+
+        for priority in [Priority::High, Priority::High, Priority::Low] {
+            fx.ticket(&web, "t")
+                .priority(priority)
+                .create()
+                .await
+                .unwrap();
+        }
+        let discussed = fx
+            .ticket(&web, "discussed")
+            .priority(Priority::Urgent)
+            .create()
+            .await
+            .unwrap();
+        fx.ticket(&api, "elsewhere").create().await.unwrap();
+        fx.conversation_between(&discussed, &reviewer, &coder)
+            .messages(4)
+            .create()
+            .await
+            .unwrap();
+        pool.execute(
+            r#"
+            UPDATE tickets SET state = 'closed' WHERE priority = 'low';
+            UPDATE tickets SET state = 'on_hold' WHERE priority = 'urgent';
+            UPDATE tickets SET estimate_minutes = 45 WHERE priority IN ('high', 'low');
+            -- Fixture comments are dated in the past; two of them were just posted
+            UPDATE comments SET created_at = datetime('now')
+            WHERE id IN (SELECT id FROM comments WHERE stage_number = 1 LIMIT 2);
+            INSERT INTO workers (worker_id, project_id, worker_type, status, queue_name)
+            VALUES ('w1', 'stats-web', 'coder', 'active', 'q'),
+                   ('w2', 'stats-web', 'coder', 'failed', 'q'),
+                   ('w3', 'stats-api', 'coder', 'active', 'q');
+            "#,
+        )
+        .await
+        .unwrap();
+
+        let all = SystemStats::load(&pool, None).await.unwrap();
+        assert_eq!(all.tickets.total, 5);
+        assert_eq!(all.tickets.by_state["open"], 3);
+        assert_eq!(all.tickets.by_state["closed"], 1);
+        assert_eq!(all.tickets.by_state["on_hold"], 1);
+        assert_eq!(all.tickets.by_priority["medium"], 1);
+        assert_eq!(all.tickets.by_priority["high"], 2);
+        assert_eq!((all.tickets.reopened_open, all.tickets.fresh_open), (0, 4));
+        assert_eq!(all.workers["active"], 2);
+        assert_eq!(all.workers["idle"], 0);
+        assert_eq!(all.comments.last_24h, 2);
+        // The closed low priority ticket's estimate is done work
+        assert_eq!(all.estimates.open_minutes, 90);
+        assert_eq!(all.estimates.open_minutes_by_project["stats-web"], 90);
+        assert_eq!(all.estimates.open_minutes_by_project["stats-api"], 0);
+        assert_eq!(all.estimates.unestimated_open_tickets, 2);
+        let summaries: Vec<(&str, i64, i64)> = all
+            .projects
+            .iter()
+            .map(|p| (p.project_id.as_str(), p.open_tickets, p.online_agents))
+            .collect();
+        assert_eq!(summaries.len(), 2);
+        assert!(summaries.contains(&("stats-web", 3, 1)));
+        assert!(summaries.contains(&("stats-api", 1, 1)));
+
+        let web = SystemStats::load(&pool, Some("stats-web")).await.unwrap();
+        assert_eq!(web.tickets.total, 4);
+        assert_eq!(web.tickets.by_state["open"], 2);
+        assert_eq!(web.tickets.by_priority["medium"], 0);
+        assert_eq!(web.workers["active"], 1);
+        assert_eq!(web.workers["failed"], 1);
+        assert_eq!(web.comments.last_24h, 2);
+        assert_eq!(web.projects.len(), 1);
+        assert_eq!(web.projects[0].project_id, "stats-web");
+
+        let api = SystemStats::load(&pool, Some("stats-api")).await.unwrap();
+        assert_eq!(api.tickets.total, 1);
+        assert_eq!(api.comments.last_24h, 0);
+        assert_eq!(
+            serde_json::to_value(&api).unwrap()["tickets"]["by_state"],
+            serde_json::json!({"closed": 0, "on_hold": 0, "open": 1})
+        );
+
+        // Requests belong to their ticket's project, else the payload's, else a worker's
+        let mut projects = Vec::new();
+        for (requester, target, ticket_id, payload) in [
+            (
+                "coordinator",
+                "w9",
+                Some(discussed.ticket_id.as_str()),
+                "{}",
+            ),
+            ("coordinator", "w9", None, r#"{"project_id": "stats-api"}"#),
+            ("w3", "coordinator", None, "{}"),
+            ("coordinator", "w9", None, r#"{"project_id": "elsewhere"}"#),
+        ] {
+            let request = AgentRequest::create(&pool, requester, target, ticket_id, payload, 60)
+                .await
+                .unwrap();
+            projects.push(request.project_id);
+        }
+        assert_eq!(
+            projects,
+            [
+                Some("stats-web".to_string()),
+                Some("stats-api".to_string()),
+                Some("stats-api".to_string()),
+                None
+            ]
+        );
+        let api_requests = RequestDeliveryStats::load(&pool, Some("stats-api"))
+            .await
+            .unwrap();
+        assert_eq!(api_requests.total, 2);
+        let listed = AgentRequest::list(
+            &pool,
+            &crate::database::agent_requests::AgentRequestQuery {
+                project_id: Some("stats-web".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(listed.len(), 1);
+
+        pool.close().await;
+    }
+}
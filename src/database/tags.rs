@@ -0,0 +1,378 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, QueryBuilder};
+use std::collections::HashMap;
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use super::{thread_summaries::SummaryStatus, tickets::Ticket, DbPool};
+
+const TAG_COLUMNS: &str = "id, name, color, \
+     (SELECT COUNT(*) FROM ticket_tags WHERE ticket_tags.tag_id = tags.id) AS ticket_count, \
+     created_at, updated_at";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Tag {
+    pub id: i64,
+    /// Canonical name: trimmed and lowercase
+    pub name: String,
+    /// Display color as `#rrggbb`
+    pub color: Option<String>,
+    pub ticket_count: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateTagRequest {
+    pub name: String,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateTagRequest {
+    pub name: Option<String>,
+    pub color: Option<String>,
+}
+
+/// A ticket with its canonical tag names
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TaggedTicket {
+    #[serde(flatten)]
+    pub ticket: Ticket,
+    pub tags: Vec<String>,
+    /// Present when the comment thread has a current summary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_summary: Option<SummaryStatus>,
+}
+
+/// Canonical form of a tag name, so "FrontEnd" and " frontend" are the same tag
+pub fn canonical_name(name: &str) -> Result<String> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        bail!("Tag name must not be empty");
+    }
+    Ok(name)
+}
+
+fn validate_color(color: &str) -> Result<()> {
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        bail!("Invalid tag color '{}': expected #rrggbb", color);
+    }
+    Ok(())
+}
+
+impl Tag {
+    pub async fn list(pool: &DbPool) -> Result<Vec<Tag>> {
+        let tags = sqlx::query_as::<_, Tag>(&format!(
+            "SELECT {} FROM tags ORDER BY name ASC",
+            TAG_COLUMNS
+        ))
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to list tags: {:?}", e))?;
+
+        Ok(tags)
+    }
+
+    pub async fn get(pool: &DbPool, id: i64) -> Result<Option<Tag>> {
+        let tag =
+            sqlx::query_as::<_, Tag>(&format!("SELECT {} FROM tags WHERE id = ?1", TAG_COLUMNS))
+                .bind(id)
+                .fetch_optional(pool)
+                .await
+                .inspect_err(|e| warn!("Failed to fetch tag {}: {:?}", id, e))?;
+
+        Ok(tag)
+    }
+
+    pub async fn get_by_name(pool: &DbPool, name: &str) -> Result<Option<Tag>> {
+        let tag =
+            sqlx::query_as::<_, Tag>(&format!("SELECT {} FROM tags WHERE name = ?1", TAG_COLUMNS))
+                .bind(canonical_name(name)?)
+                .fetch_optional(pool)
+                .await
+                .inspect_err(|e| warn!("Failed to fetch tag '{}': {:?}", name, e))?;
+
+        Ok(tag)
+    }
+
+    pub async fn create(pool: &DbPool, req: &CreateTagRequest) -> Result<Tag> {
+        let name = canonical_name(&req.name)?;
+        if let Some(color) = &req.color {
+            validate_color(color)?;
+        }
+        if Self::get_by_name(pool, &name).await?.is_some() {
+            bail!("Tag '{}' already exists", name);
+        }
+
+        let id = sqlx::query("INSERT INTO tags (name, color) VALUES (?1, ?2)")
+            .bind(&name)
+            .bind(&req.color)
+            .execute(pool)
+            .await
+            .inspect_err(|e| error!("Failed to create tag '{}': {:?}", name, e))?
+            .last_insert_rowid();
+
+        Self::get(pool, id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Failed to create tag '{}'", name))
+    }
+
+    /// Rename or recolor a tag; tickets reference tags by id, so a rename applies to all of them
+    pub async fn update(pool: &DbPool, id: i64, req: &UpdateTagRequest) -> Result<Option<Tag>> {
+        let name = req.name.as_deref().map(canonical_name).transpose()?;
+        if let Some(color) = &req.color {
+            validate_color(color)?;
+        }
+        if let Some(name) = &name {
+            if let Some(existing) = Self::get_by_name(pool, name).await? {
+                if existing.id != id {
+                    bail!(
+                        "Tag '{}' already exists; merge into it instead of renaming",
+                        name
+                    );
+                }
+            }
+        }
+
+        let result = sqlx::query(
+            r#"
+            UPDATE tags
+            SET name = COALESCE(?2, name), color = COALESCE(?3, color), updated_at = datetime('now')
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .bind(&name)
+        .bind(&req.color)
+        .execute(pool)
+        .await
+        .inspect_err(|e| error!("Failed to update tag {}: {:?}", id, e))?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        Self::get(pool, id).await
+    }
+
+    /// Move every ticket tagged `id` to `target` and delete `id`; None when either tag is missing
+    pub async fn merge_into(pool: &DbPool, id: i64, target: i64) -> Result<Option<Tag>> {
+        if id == target {
+            bail!("Cannot merge a tag into itself");
+        }
+
+        let mut tx = pool.begin().await?;
+        let found: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tags WHERE id IN (?1, ?2)")
+            .bind(id)
+            .bind(target)
+            .fetch_one(&mut *tx)
+            .await?;
+        if found != 2 {
+            return Ok(None);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO ticket_tags (ticket_id, tag_id)
+            SELECT ticket_id, ?2 FROM ticket_tags WHERE tag_id = ?1
+            "#,
+        )
+        .bind(id)
+        .bind(target)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM ticket_tags WHERE tag_id = ?1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM tags WHERE id = ?1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit()
+            .await
+            .inspect_err(|e| error!("Failed to merge tag {} into {}: {:?}", id, target, e))?;
+
+        Self::get(pool, target).await
+    }
+
+    /// Tag ids for `names`; unknown tags are created unless `create_missing` is false,
+    /// in which case they are an error
+    pub async fn resolve(
+        pool: &DbPool,
+        names: &[String],
+        create_missing: bool,
+    ) -> Result<Vec<i64>> {
+        let mut ids = Vec::new();
+        let mut unknown = Vec::new();
+        for name in names {
+            let name = canonical_name(name)?;
+            match Self::get_by_name(pool, &name).await? {
+                Some(tag) => ids.push(tag.id),
+                None if create_missing => {
+                    sqlx::query("INSERT OR IGNORE INTO tags (name) VALUES (?1)")
+                        .bind(&name)
+                        .execute(pool)
+                        .await?;
+                    let tag = Self::get_by_name(pool, &name)
+                        .await?
+                        .ok_or_else(|| anyhow::anyhow!("Failed to create tag '{}'", name))?;
+                    ids.push(tag.id);
+                }
+                None => unknown.push(name),
+            }
+        }
+        if !unknown.is_empty() {
+            bail!("Unknown tag(s): {}", unknown.join(", "));
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        Ok(ids)
+    }
+
+    /// Replace a ticket's tags
+    pub async fn set_for_ticket(pool: &DbPool, ticket_id: &str, tag_ids: &[i64]) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM ticket_tags WHERE ticket_id = ?1")
+            .bind(ticket_id)
+            .execute(&mut *tx)
+            .await?;
+        for tag_id in tag_ids {
+            sqlx::query("INSERT INTO ticket_tags (ticket_id, tag_id) VALUES (?1, ?2)")
+                .bind(ticket_id)
+                .bind(tag_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit()
+            .await
+            .inspect_err(|e| error!("Failed to set tags of ticket {}: {:?}", ticket_id, e))?;
+        Ok(())
+    }
+
+    /// Canonical tag names of each of `ticket_ids`, sorted by name
+    pub async fn names_for_tickets(
+        pool: &DbPool,
+        ticket_ids: &[&str],
+    ) -> Result<HashMap<String, Vec<String>>> {
+        let mut names: HashMap<String, Vec<String>> = HashMap::new();
+        if ticket_ids.is_empty() {
+            return Ok(names);
+        }
+
+        let mut query_builder = QueryBuilder::new(
+            "SELECT ticket_tags.ticket_id, tags.name FROM ticket_tags
+             JOIN tags ON tags.id = ticket_tags.tag_id
+             WHERE ticket_tags.ticket_id IN (",
+        );
+        let mut separated = query_builder.separated(", ");
+        for ticket_id in ticket_ids {
+            separated.push_bind(*ticket_id);
+        }
+        query_builder.push(") ORDER BY tags.name ASC");
+
+        let rows: Vec<(String, String)> = query_builder.build_query_as().fetch_all(pool).await?;
+        for (ticket_id, name) in rows {
+            names.entry(ticket_id).or_default().push(name);
+        }
+        Ok(names)
+    }
+
+    /// Attach tag names to tickets
+    pub async fn tag_tickets(pool: &DbPool, tickets: Vec<Ticket>) -> Result<Vec<TaggedTicket>> {
+        let ids: Vec<&str> = tickets.iter().map(|t| t.ticket_id.as_str()).collect();
+        let mut names = Self::names_for_tickets(pool, &ids).await?;
+        Ok(tickets
+            .into_iter()
+            .map(|ticket| TaggedTicket {
+                tags: names.remove(&ticket.ticket_id).unwrap_or_default(),
+                thread_summary: None,
+                ticket,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_pool, Fixtures};
+    use sqlx::Executor;
+
+    #[tokio::test]
+    async fn test_backfill_rename_and_merge() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("tagged").create().await.unwrap();
+        for (id, fields) in [
+            ("P-1", r#"{"tags": ["FrontEnd", "ui"]}"#),
+            ("P-2", r#"{"tags": ["front-end"]}"#),
+            ("P-3", "not json"),
+        ] {
+            fx.ticket(&project, id)
+                .id(id)
+                .custom_fields_raw(fields)
+                .create()
+                .await
+                .unwrap();
+        }
+        // The migration already ran on the empty database; run its backfill again
+        let migration = include_str!("../../migrations/017_ticket_tags.sql");
+        let backfill = &migration[migration.find("INSERT OR IGNORE INTO tags").unwrap()..];
+        pool.execute(backfill).await.unwrap();
+
+        let names = |tags: Vec<Tag>| tags.into_iter().map(|t| t.name).collect::<Vec<_>>();
+        assert_eq!(
+            names(Tag::list(&pool).await.unwrap()),
+            ["front-end", "frontend", "ui"]
+        );
+
+        let frontend = Tag::get_by_name(&pool, " FRONTEND ")
+            .await
+            .unwrap()
+            .unwrap();
+        let dashed = Tag::get_by_name(&pool, "front-end").await.unwrap().unwrap();
+        let merged = Tag::merge_into(&pool, dashed.id, frontend.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(merged.ticket_count, 2);
+        assert!(Tag::get(&pool, dashed.id).await.unwrap().is_none());
+
+        // Renames apply to every ticket; renaming onto an existing tag is refused
+        let rename = |name: &str| UpdateTagRequest {
+            name: Some(name.to_string()),
+            color: None,
+        };
+        assert!(Tag::update(&pool, frontend.id, &rename("ui"))
+            .await
+            .is_err());
+        Tag::update(&pool, frontend.id, &rename("Web"))
+            .await
+            .unwrap()
+            .unwrap();
+        let tickets = Ticket::list_by_project(&pool, Some("tagged"), None, Some("web"))
+            .await
+            .unwrap();
+        let tagged = Tag::tag_tickets(&pool, tickets).await.unwrap();
+        let ids: Vec<_> = tagged.iter().map(|t| t.ticket.ticket_id.as_str()).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&"P-1") && ids.contains(&"P-2"));
+        let p1 = tagged.iter().find(|t| t.ticket.ticket_id == "P-1").unwrap();
+        assert_eq!(p1.tags, ["ui", "web"]);
+
+        // Unknown tags are only created when allowed
+        let requested = vec!["Backend".to_string(), "web".to_string()];
+        assert!(Tag::resolve(&pool, &requested, false).await.is_err());
+        let ids = Tag::resolve(&pool, &requested, true).await.unwrap();
+        Tag::set_for_ticket(&pool, "P-3", &ids).await.unwrap();
+        let names = Tag::names_for_tickets(&pool, &["P-3"]).await.unwrap();
+        assert_eq!(names["P-3"], ["backend", "web"]);
+
+        pool.close().await;
+    }
+}
@@ -0,0 +1,332 @@
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{FromRow, QueryBuilder};
+use std::collections::HashMap;
+use tracing::error;
+use utoipa::ToSchema;
+
+use super::{returning::FetchReturning, tags::TaggedTicket, tickets::TicketWithComments, DbPool};
+use crate::crypto;
+
+const SUMMARY_COLUMNS: &str = "id, ticket_id, summary, summary_key_id, from_comment_id, \
+     through_comment_id, comment_count, created_by, created_at, superseded_at";
+
+/// Coordinator-written summary of a ticket's comments up to `through_comment_id`
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ThreadSummary {
+    pub id: i64,
+    pub ticket_id: String,
+    pub summary: String,
+    /// Key the stored summary is encrypted with (with `--encrypt-messages`)
+    #[serde(skip)]
+    pub summary_key_id: Option<String>,
+    pub from_comment_id: i64,
+    pub through_comment_id: i64,
+    /// Comments in the summarized range
+    pub comment_count: i64,
+    pub created_by: String,
+    pub created_at: String,
+    /// Set once a newer summary replaced this one
+    pub superseded_at: Option<String>,
+}
+
+/// Whether a ticket's thread has a current summary, for list payloads
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SummaryStatus {
+    pub summary_id: i64,
+    pub through_comment_id: i64,
+    pub summarized_at: String,
+    pub age_secs: i64,
+}
+
+/// Result of summarizing a thread
+#[derive(Debug, Clone)]
+pub enum SummarizeOutcome {
+    Created(Box<ThreadSummary>),
+    /// The range does not fit the thread; the reason is meant for the caller
+    Rejected(String),
+}
+
+impl ThreadSummary {
+    /// The summary with its text decrypted when it was stored encrypted
+    fn opened(mut self) -> Result<ThreadSummary> {
+        let summary = std::mem::take(&mut self.summary);
+        self.summary = crypto::open(summary, self.summary_key_id.as_deref())
+            .map_err(|e| anyhow::anyhow!("Failed to decrypt thread summary {}: {}", self.id, e))?;
+        Ok(self)
+    }
+
+    /// Record a summary of the ticket's comments `from_comment_id..=through_comment_id`
+    /// (from the first comment when not given), superseding the current summary. The range
+    /// must end past the current summary's cutoff.
+    pub async fn create(
+        pool: &DbPool,
+        ticket_id: &str,
+        summary: &str,
+        from_comment_id: Option<i64>,
+        through_comment_id: i64,
+        created_by: &str,
+    ) -> Result<SummarizeOutcome> {
+        if summary.trim().is_empty() {
+            return Ok(SummarizeOutcome::Rejected(
+                "summary must not be empty".to_string(),
+            ));
+        }
+
+        let mut tx = pool.begin().await?;
+        let comment_ids: Vec<i64> =
+            sqlx::query_scalar("SELECT id FROM comments WHERE ticket_id = ?1 ORDER BY id ASC")
+                .bind(ticket_id)
+                .fetch_all(&mut *tx)
+                .await?;
+        if !comment_ids.contains(&through_comment_id) {
+            return Ok(SummarizeOutcome::Rejected(format!(
+                "Comment {} is not part of ticket {}'s thread",
+                through_comment_id, ticket_id
+            )));
+        }
+        let from_comment_id = from_comment_id.unwrap_or(comment_ids[0]);
+        if !comment_ids.contains(&from_comment_id) {
+            return Ok(SummarizeOutcome::Rejected(format!(
+                "Comment {} is not part of ticket {}'s thread",
+                from_comment_id, ticket_id
+            )));
+        }
+        if from_comment_id > through_comment_id {
+            return Ok(SummarizeOutcome::Rejected(format!(
+                "from_comment_id {} comes after through_comment_id {}",
+                from_comment_id, through_comment_id
+            )));
+        }
+
+        let current: Option<i64> = sqlx::query_scalar(
+            "SELECT through_comment_id FROM thread_summaries
+             WHERE ticket_id = ?1 AND superseded_at IS NULL",
+        )
+        .bind(ticket_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(cutoff) = current.filter(|cutoff| through_comment_id <= *cutoff) {
+            return Ok(SummarizeOutcome::Rejected(format!(
+                "The current summary already covers comments through {}; a newer summary must extend past it",
+                cutoff
+            )));
+        }
+
+        sqlx::query(
+            "UPDATE thread_summaries SET superseded_at = datetime('now')
+             WHERE ticket_id = ?1 AND superseded_at IS NULL",
+        )
+        .bind(ticket_id)
+        .execute(&mut *tx)
+        .await?;
+        let comment_count = comment_ids
+            .iter()
+            .filter(|id| (from_comment_id..=through_comment_id).contains(id))
+            .count() as i64;
+        let (summary, key_id) = crypto::seal(summary)?;
+        let created = sqlx::query_as::<_, ThreadSummary>(&format!(
+            r#"
+            INSERT INTO thread_summaries (
+                ticket_id, summary, summary_key_id, from_comment_id, through_comment_id,
+                comment_count, created_by
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            RETURNING {}
+        "#,
+            SUMMARY_COLUMNS
+        ))
+        .bind(ticket_id)
+        .bind(&summary)
+        .bind(key_id)
+        .bind(from_comment_id)
+        .bind(through_comment_id)
+        .bind(comment_count)
+        .bind(created_by)
+        .fetch_returned_one(&mut *tx)
+        .await?;
+        tx.commit().await.inspect_err(|e| {
+            error!(
+                "Failed to summarize thread of ticket '{}': {:?}",
+                ticket_id, e
+            )
+        })?;
+        Ok(SummarizeOutcome::Created(Box::new(created.opened()?)))
+    }
+
+    /// The summary in effect for a ticket's thread
+    pub async fn current(pool: &DbPool, ticket_id: &str) -> Result<Option<ThreadSummary>> {
+        let summary = sqlx::query_as::<_, ThreadSummary>(&format!(
+            "SELECT {} FROM thread_summaries WHERE ticket_id = ?1 AND superseded_at IS NULL",
+            SUMMARY_COLUMNS
+        ))
+        .bind(ticket_id)
+        .fetch_optional(pool)
+        .await?;
+        summary.map(ThreadSummary::opened).transpose()
+    }
+
+    /// Every summary of a ticket's thread, newest first
+    pub async fn history(pool: &DbPool, ticket_id: &str) -> Result<Vec<ThreadSummary>> {
+        let summaries = sqlx::query_as::<_, ThreadSummary>(&format!(
+            "SELECT {} FROM thread_summaries WHERE ticket_id = ?1 ORDER BY id DESC",
+            SUMMARY_COLUMNS
+        ))
+        .bind(ticket_id)
+        .fetch_all(pool)
+        .await?;
+        summaries.into_iter().map(ThreadSummary::opened).collect()
+    }
+
+    /// Current summary status of each ticket that has one
+    pub async fn status_for_tickets(
+        pool: &DbPool,
+        ticket_ids: &[&str],
+    ) -> Result<HashMap<String, SummaryStatus>> {
+        if ticket_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut query_builder = QueryBuilder::new(
+            "SELECT ticket_id, id AS summary_id, through_comment_id, created_at AS summarized_at,
+                    CAST((julianday('now') - julianday(created_at)) * 86400 AS INTEGER) AS age_secs
+             FROM thread_summaries
+             WHERE superseded_at IS NULL AND ticket_id IN (",
+        );
+        let mut separated = query_builder.separated(", ");
+        for ticket_id in ticket_ids {
+            separated.push_bind(*ticket_id);
+        }
+        query_builder.push(")");
+
+        let rows: Vec<(String, i64, i64, String, i64)> =
+            query_builder.build_query_as().fetch_all(pool).await?;
+        Ok(rows
+            .into_iter()
+            .map(
+                |(ticket_id, summary_id, through_comment_id, summarized_at, age_secs)| {
+                    (
+                        ticket_id,
+                        SummaryStatus {
+                            summary_id,
+                            through_comment_id,
+                            summarized_at,
+                            age_secs,
+                        },
+                    )
+                },
+            )
+            .collect())
+    }
+
+    /// Fill in each listed ticket's summary status
+    pub async fn annotate(pool: &DbPool, tickets: &mut [TaggedTicket]) -> Result<()> {
+        let ids: Vec<&str> = tickets
+            .iter()
+            .map(|t| t.ticket.ticket_id.as_str())
+            .collect();
+        let mut statuses = Self::status_for_tickets(pool, &ids).await?;
+        for tagged in tickets {
+            tagged.thread_summary = statuses.remove(&tagged.ticket.ticket_id);
+        }
+        Ok(())
+    }
+}
+
+impl TicketWithComments {
+    /// Attach the thread's current summary and, unless `full_history` is set, drop the
+    /// comments it covers
+    pub async fn with_summary(mut self, pool: &DbPool, full_history: bool) -> Result<Self> {
+        let summary = ThreadSummary::current(pool, &self.ticket.ticket_id).await?;
+        if let (Some(summary), false) = (&summary, full_history) {
+            self.comments
+                .retain(|comment| comment.id > summary.through_comment_id);
+        }
+        self.summary = summary;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{comments::Comment, tickets::Ticket};
+    use crate::test_support::{memory_pool, Fixtures};
+
+    #[tokio::test]
+    async fn test_newer_summary_supersedes_and_hides_covered_comments() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("summaries").create().await.unwrap();
+        let reviewer = fx.worker_type(&project, "review").create().await.unwrap();
+        let coder = fx.worker_type(&project, "coding").create().await.unwrap();
+        let ticket = fx.ticket(&project, "Long thread").create().await.unwrap();
+        let comments = fx
+            .conversation_between(&ticket, &reviewer, &coder)
+            .messages(6)
+            .create()
+            .await
+            .unwrap();
+        let id = |i: usize| comments[i].id;
+        // The thread also holds comments posted when the ticket was created
+        let thread = Comment::get_by_ticket_id(&pool, &ticket.ticket_id)
+            .await
+            .unwrap();
+        let opening = thread.len() - comments.len();
+
+        let created = |through: i64| {
+            ThreadSummary::create(&pool, &ticket.ticket_id, "so far", None, through, "coord")
+        };
+        let SummarizeOutcome::Created(first) = created(id(2)).await.unwrap() else {
+            panic!("first summary was rejected");
+        };
+        assert_eq!(
+            (first.from_comment_id, first.comment_count),
+            (thread[0].id, opening as i64 + 3)
+        );
+        assert!(matches!(
+            created(id(1)).await.unwrap(),
+            SummarizeOutcome::Rejected(_)
+        ));
+        assert!(matches!(
+            created(id(4) + 100).await.unwrap(),
+            SummarizeOutcome::Rejected(_)
+        ));
+
+        let condensed = Ticket::get_by_id(&pool, &ticket.ticket_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .with_summary(&pool, false)
+            .await
+            .unwrap();
+        assert_eq!(condensed.summary.unwrap().id, first.id);
+        assert_eq!(condensed.comments.len(), 3);
+
+        assert!(matches!(
+            created(id(4)).await.unwrap(),
+            SummarizeOutcome::Created(_)
+        ));
+        let history = ThreadSummary::history(&pool, &ticket.ticket_id)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history[0].superseded_at.is_none());
+        assert!(history[1].superseded_at.is_some());
+
+        let full = Ticket::get_by_id(&pool, &ticket.ticket_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .with_summary(&pool, true)
+            .await
+            .unwrap();
+        assert_eq!(full.comments.len(), thread.len());
+        let status = ThreadSummary::status_for_tickets(&pool, &[&ticket.ticket_id])
+            .await
+            .unwrap();
+        assert_eq!(status[&ticket.ticket_id].through_comment_id, id(4));
+
+        pool.close().await;
+    }
+}
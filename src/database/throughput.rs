@@ -0,0 +1,500 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use std::collections::HashMap;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::{timing::timed, DbPool};
+
+pub const DEFAULT_THROUGHPUT_WEEKS: i64 = 8;
+pub const MAX_THROUGHPUT_WEEKS: i64 = 52;
+
+/// History rows from `?1` on, with the state and worker of the row before each one, of
+/// the tickets (of project `?2`, when given) that changed since `?1`. A row is a
+/// resolution when it closes the ticket and a reopening when it moves it out of closed.
+const WINDOW_ROWS: &str = r#"
+    window_rows AS (
+        SELECT * FROM (
+            SELECT id, ticket_id, change, state, processing_worker_id, created_at,
+                   LAG(state) OVER (PARTITION BY ticket_id ORDER BY created_at, id) AS prev_state,
+                   LAG(processing_worker_id)
+                       OVER (PARTITION BY ticket_id ORDER BY created_at, id) AS prev_worker
+            FROM ticket_history
+            WHERE ticket_id IN (
+                SELECT ticket_id FROM ticket_history
+                WHERE created_at >= ?1 AND (?2 IS NULL OR project_id = ?2)
+            )
+        )
+        WHERE created_at >= ?1
+    )
+"#;
+
+/// Creation and first pickup by a worker of the tickets that changed since `?1`
+const FIRSTS: &str = r#"
+    firsts AS (
+        SELECT ticket_id,
+               MIN(CASE WHEN change = 'created' THEN created_at END) AS created_at,
+               MIN(CASE WHEN change = 'assigned' THEN created_at END) AS started_at
+        FROM ticket_history
+        WHERE ticket_id IN (
+            SELECT ticket_id FROM ticket_history
+            WHERE created_at >= ?1 AND (?2 IS NULL OR project_id = ?2)
+        )
+        GROUP BY ticket_id
+    )
+"#;
+
+/// Minutes from creation to the first pickup, for pickups from `?1` until `?3`
+const OPEN_TO_IN_PROGRESS: &str = r#"
+    durations AS (
+        SELECT (julianday(started_at) - julianday(created_at)) * 1440 AS minutes
+        FROM firsts
+        WHERE started_at >= ?1 AND started_at < ?3 AND created_at IS NOT NULL
+    )
+"#;
+
+/// Minutes from the first pickup to the first close after it, for closes from `?1`
+/// until `?3`
+const IN_PROGRESS_TO_RESOLVED: &str = r#"
+    durations AS (
+        SELECT (julianday(MIN(h.created_at)) - julianday(f.started_at)) * 1440 AS minutes
+        FROM firsts f
+        JOIN ticket_history h
+          ON h.ticket_id = f.ticket_id AND h.state = 'closed' AND h.created_at >= f.started_at
+        GROUP BY f.ticket_id
+        HAVING MIN(h.created_at) >= ?1 AND MIN(h.created_at) < ?3
+    )
+"#;
+
+/// Tickets created, resolved and reopened on one day (UTC)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema, FromRow)]
+pub struct ThroughputDay {
+    pub date: String,
+    pub created: i64,
+    pub resolved: i64,
+    pub reopened: i64,
+}
+
+/// Spread of one phase of a ticket's life in whole minutes, by nearest rank; `null`
+/// without samples
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+pub struct CycleTime {
+    pub samples: i64,
+    pub median_minutes: Option<i64>,
+    pub p90_minutes: Option<i64>,
+}
+
+/// Resolutions counted for the worker holding the ticket as it was closed
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema, FromRow)]
+pub struct AgentThroughput {
+    pub agent_id: String,
+    /// None once the worker's record is gone
+    pub worker_type: Option<String>,
+    pub resolved: i64,
+}
+
+/// Ticket throughput over the last `weeks` weeks, from the ticket history. A ticket is in
+/// progress once a worker picks it up; it is resolved when it is closed.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ThroughputMetrics {
+    pub project_id: Option<String>,
+    pub weeks: i64,
+    /// First day of the window
+    pub since: String,
+    /// Last day of the window, today
+    pub until: String,
+    /// One entry per day of the window, oldest first, including quiet days
+    pub days: Vec<ThroughputDay>,
+    pub created: i64,
+    pub resolved: i64,
+    pub reopened: i64,
+    /// From creation to the first pickup, for pickups in the window
+    pub open_to_in_progress: CycleTime,
+    /// From the first pickup to the first close after it, for closes in the window
+    pub in_progress_to_resolved: CycleTime,
+    /// Resolutions per worker, most first
+    pub by_agent: Vec<AgentThroughput>,
+    /// Resolutions of tickets no worker held, such as those closed by the coordinator
+    pub resolved_without_agent: i64,
+    /// Ticket history rows per day of week (0 = Sunday) and hour (UTC)
+    #[schema(value_type = Vec<Vec<i64>>)]
+    pub heatmap: [[i64; 24]; 7],
+    pub generated_at: String,
+}
+
+#[derive(FromRow)]
+struct Percentiles {
+    samples: i64,
+    median_minutes: Option<i64>,
+    p90_minutes: Option<i64>,
+}
+
+/// Id of the latest ticket history row. Every ticket change that can move the metrics
+/// adds a row, so metrics computed at the same watermark on the same day are the same.
+pub async fn watermark(pool: &DbPool) -> Result<i64> {
+    let watermark = sqlx::query_scalar("SELECT COALESCE(MAX(id), 0) FROM ticket_history")
+        .fetch_one(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to read the ticket history watermark: {:?}", e))?;
+    Ok(watermark)
+}
+
+/// Throughput of the last `weeks` weeks ending today, optionally of one project
+pub async fn load(
+    pool: &DbPool,
+    project_id: Option<&str>,
+    weeks: i64,
+) -> Result<ThroughputMetrics> {
+    let weeks = weeks.clamp(1, MAX_THROUGHPUT_WEEKS);
+    let today = Utc::now().date_naive();
+    let mut metrics = compute(
+        pool,
+        project_id,
+        today - Duration::days(weeks * 7 - 1),
+        today,
+    )
+    .await?;
+    metrics.weeks = weeks;
+    Ok(metrics)
+}
+
+/// Throughput from the start of `first` through the end of `last`
+pub async fn compute(
+    pool: &DbPool,
+    project_id: Option<&str>,
+    first: NaiveDate,
+    last: NaiveDate,
+) -> Result<ThroughputMetrics> {
+    let since = first.format("%Y-%m-%d").to_string();
+    let until = last.format("%Y-%m-%d").to_string();
+    // SQLite timestamps sort after the bare date of their day and before the next one
+    let end = (last + Duration::days(1)).format("%Y-%m-%d").to_string();
+
+    let daily_sql = format!(
+        r#"
+        WITH {WINDOW_ROWS}
+        SELECT date(created_at) AS date,
+               SUM(change = 'created') AS created,
+               SUM(state = 'closed' AND prev_state IS NOT NULL AND prev_state <> 'closed')
+                   AS resolved,
+               SUM(state <> 'closed' AND prev_state = 'closed') AS reopened
+        FROM window_rows
+        WHERE created_at < ?3
+        GROUP BY date(created_at)
+        "#
+    );
+    let daily = timed!(
+        pool,
+        "throughput::daily",
+        sqlx::query_as::<_, ThroughputDay>(&daily_sql)
+            .bind(&since)
+            .bind(project_id)
+            .bind(&end),
+        fetch_all
+    )
+    .inspect_err(|e| warn!("Failed to count daily throughput: {:?}", e))?;
+
+    let agents_sql = format!(
+        r#"
+        WITH {WINDOW_ROWS},
+        resolutions AS (
+            SELECT COALESCE(processing_worker_id, prev_worker) AS agent_id
+            FROM window_rows
+            WHERE state = 'closed' AND prev_state IS NOT NULL AND prev_state <> 'closed'
+              AND created_at < ?3
+        )
+        SELECT r.agent_id, MAX(w.worker_type) AS worker_type, COUNT(*) AS resolved
+        FROM resolutions r
+        LEFT JOIN workers w ON w.worker_id = r.agent_id
+        WHERE r.agent_id IS NOT NULL
+        GROUP BY r.agent_id
+        ORDER BY resolved DESC, r.agent_id
+        "#
+    );
+    let by_agent = timed!(
+        pool,
+        "throughput::by_agent",
+        sqlx::query_as::<_, AgentThroughput>(&agents_sql)
+            .bind(&since)
+            .bind(project_id)
+            .bind(&end),
+        fetch_all
+    )
+    .inspect_err(|e| warn!("Failed to count resolutions per agent: {:?}", e))?;
+
+    let open_to_in_progress =
+        cycle_time(pool, OPEN_TO_IN_PROGRESS, &since, project_id, &end).await?;
+    let in_progress_to_resolved =
+        cycle_time(pool, IN_PROGRESS_TO_RESOLVED, &since, project_id, &end).await?;
+
+    let cells: Vec<(i64, i64, i64)> = timed!(
+        pool,
+        "throughput::heatmap",
+        sqlx::query_as(
+            r#"
+            SELECT CAST(strftime('%w', created_at) AS INTEGER) AS weekday,
+                   CAST(strftime('%H', created_at) AS INTEGER) AS hour,
+                   COUNT(*) AS events
+            FROM ticket_history
+            WHERE created_at >= ?1 AND created_at < ?3 AND (?2 IS NULL OR project_id = ?2)
+            GROUP BY weekday, hour
+            "#,
+        )
+        .bind(&since)
+        .bind(project_id)
+        .bind(&end),
+        fetch_all
+    )
+    .inspect_err(|e| warn!("Failed to build the activity heatmap: {:?}", e))?;
+    let mut heatmap = [[0; 24]; 7];
+    for (weekday, hour, events) in cells {
+        if let Some(cell) = heatmap
+            .get_mut(weekday as usize)
+            .and_then(|day| day.get_mut(hour as usize))
+        {
+            *cell = events;
+        }
+    }
+
+    let mut by_date: HashMap<String, ThroughputDay> = daily
+        .into_iter()
+        .map(|day| (day.date.clone(), day))
+        .collect();
+    let days: Vec<ThroughputDay> = first
+        .iter_days()
+        .take_while(|date| *date <= last)
+        .map(|date| {
+            let date = date.format("%Y-%m-%d").to_string();
+            by_date.remove(&date).unwrap_or(ThroughputDay {
+                date,
+                ..Default::default()
+            })
+        })
+        .collect();
+    let resolved: i64 = days.iter().map(|day| day.resolved).sum();
+    let attributed: i64 = by_agent.iter().map(|agent| agent.resolved).sum();
+
+    Ok(ThroughputMetrics {
+        project_id: project_id.map(str::to_string),
+        weeks: (days.len() as i64 + 6) / 7,
+        since,
+        until,
+        created: days.iter().map(|day| day.created).sum(),
+        resolved,
+        reopened: days.iter().map(|day| day.reopened).sum(),
+        days,
+        open_to_in_progress,
+        in_progress_to_resolved,
+        by_agent,
+        resolved_without_agent: resolved - attributed,
+        heatmap,
+        generated_at: Utc::now().to_rfc3339(),
+    })
+}
+
+/// Median and 90th percentile of the samples of a `durations` CTE
+async fn cycle_time(
+    pool: &DbPool,
+    durations: &str,
+    since: &str,
+    project_id: Option<&str>,
+    end: &str,
+) -> Result<CycleTime> {
+    let sql = format!(
+        r#"
+        WITH {FIRSTS}, {durations},
+        ranked AS (
+            SELECT CAST(ROUND(minutes) AS INTEGER) AS minutes,
+                   ROW_NUMBER() OVER (ORDER BY minutes) AS rank,
+                   COUNT(*) OVER () AS samples
+            FROM durations
+            WHERE minutes IS NOT NULL
+        )
+        SELECT COALESCE(MAX(samples), 0) AS samples,
+               MAX(CASE WHEN rank = (samples * 50 + 99) / 100 THEN minutes END) AS median_minutes,
+               MAX(CASE WHEN rank = (samples * 90 + 99) / 100 THEN minutes END) AS p90_minutes
+        FROM ranked
+        "#
+    );
+    let row = timed!(
+        pool,
+        "throughput::cycle_time",
+        sqlx::query_as::<_, Percentiles>(&sql)
+            .bind(since)
+            .bind(project_id)
+            .bind(end),
+        fetch_one
+    )
+    .inspect_err(|e| warn!("Failed to compute cycle times: {:?}", e))?;
+    Ok(CycleTime {
+        samples: row.samples,
+        median_minutes: row.median_minutes,
+        p90_minutes: row.p90_minutes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    #[tokio::test]
+    async fn test_throughput_over_seeded_history() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("throughput").create().await.unwrap();
+        let other = fx.project("elsewhere").create().await.unwrap();
+        for id in ["A", "B", "C"] {
+            fx.ticket(&project, id).id(id).create().await.unwrap();
+        }
+        fx.ticket(&other, "E").id("E").create().await.unwrap();
+        sqlx::query("DELETE FROM ticket_history")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO workers (worker_id, project_id, worker_type, status, queue_name)
+             VALUES ('w-1', 'throughput', 'coding', 'finished', 'q')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // The window is Monday 2026-03-02 through Sunday 2026-03-08
+        let history = [
+            ("A", "created", "open", None, "2026-03-01 10:00:00"),
+            ("A", "assigned", "open", Some("w-1"), "2026-03-02 10:00:00"),
+            ("A", "updated", "closed", Some("w-1"), "2026-03-03 10:00:00"),
+            ("B", "created", "open", None, "2026-03-02 09:00:00"),
+            ("B", "assigned", "open", Some("w-2"), "2026-03-02 09:30:00"),
+            // Closed as the worker let go of it, then reopened and closed by the coordinator
+            ("B", "released", "closed", None, "2026-03-04 09:30:00"),
+            ("B", "updated", "open", None, "2026-03-05 12:00:00"),
+            ("B", "updated", "closed", None, "2026-03-06 12:00:00"),
+            ("C", "created", "open", None, "2026-03-03 08:00:00"),
+            ("C", "assigned", "open", Some("w-1"), "2026-03-03 09:00:00"),
+            ("C", "updated", "closed", Some("w-1"), "2026-03-03 11:00:00"),
+            ("E", "created", "open", None, "2026-03-02 15:00:00"),
+            ("A", "updated", "open", None, "2026-03-09 00:00:00"),
+        ];
+        for (ticket_id, change, state, worker, at) in history {
+            let project_id = if ticket_id == "E" {
+                "elsewhere"
+            } else {
+                "throughput"
+            };
+            sqlx::query(
+                "INSERT INTO ticket_history
+                     (ticket_id, project_id, change, state, processing_worker_id, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .bind(ticket_id)
+            .bind(project_id)
+            .bind(change)
+            .bind(state)
+            .bind(worker)
+            .bind(at)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let day = |d: u32| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let metrics = compute(&pool, Some("throughput"), day(2), day(8))
+            .await
+            .unwrap();
+
+        let days: Vec<_> = metrics
+            .days
+            .iter()
+            .map(|d| (&d.date[8..], d.created, d.resolved, d.reopened))
+            .collect();
+        assert_eq!(
+            days,
+            [
+                ("02", 1, 0, 0),
+                ("03", 1, 2, 0),
+                ("04", 0, 1, 0),
+                ("05", 0, 0, 1),
+                ("06", 0, 1, 0),
+                ("07", 0, 0, 0),
+                ("08", 0, 0, 0),
+            ]
+        );
+        assert_eq!(
+            (metrics.created, metrics.resolved, metrics.reopened),
+            (2, 4, 1)
+        );
+        assert_eq!(
+            metrics.open_to_in_progress,
+            CycleTime {
+                samples: 3,
+                median_minutes: Some(60),
+                p90_minutes: Some(1440),
+            }
+        );
+        assert_eq!(
+            metrics.in_progress_to_resolved,
+            CycleTime {
+                samples: 3,
+                median_minutes: Some(1440),
+                p90_minutes: Some(2880),
+            }
+        );
+        assert_eq!(
+            metrics.by_agent,
+            [
+                AgentThroughput {
+                    agent_id: "w-1".to_string(),
+                    worker_type: Some("coding".to_string()),
+                    resolved: 2,
+                },
+                AgentThroughput {
+                    agent_id: "w-2".to_string(),
+                    worker_type: None,
+                    resolved: 1,
+                },
+            ]
+        );
+        assert_eq!(metrics.resolved_without_agent, 1);
+
+        // Monday is 1; the row of 2026-03-09 falls outside the window
+        let cells: Vec<_> = (0..7)
+            .flat_map(|weekday| (0..24).map(move |hour| (weekday, hour)))
+            .filter_map(|(weekday, hour)| {
+                let events = metrics.heatmap[weekday][hour];
+                (events > 0).then_some((weekday, hour, events))
+            })
+            .collect();
+        assert_eq!(
+            cells,
+            [
+                (1, 9, 2),
+                (1, 10, 1),
+                (2, 8, 1),
+                (2, 9, 1),
+                (2, 10, 1),
+                (2, 11, 1),
+                (3, 9, 1),
+                (4, 12, 1),
+                (5, 12, 1),
+            ]
+        );
+
+        // Without a project every project counts
+        let all = compute(&pool, None, day(2), day(8)).await.unwrap();
+        assert_eq!((all.created, all.days[0].created), (3, 2));
+        assert_eq!(all.heatmap[1][15], 1);
+
+        let empty = compute(&pool, Some("throughput"), day(10), day(16))
+            .await
+            .unwrap();
+        assert_eq!(empty.days.len(), 7);
+        assert_eq!(empty.open_to_in_progress, CycleTime::default());
+        assert!(empty.by_agent.is_empty());
+
+        pool.close().await;
+    }
+}
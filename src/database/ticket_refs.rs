@@ -0,0 +1,215 @@
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::FromRow;
+
+use super::DbPool;
+
+/// Most tickets listed in an ambiguity error
+const MAX_CANDIDATES: i64 = 20;
+
+/// A ticket a short id could stand for
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TicketCandidate {
+    pub ticket_id: String,
+    pub short_id: String,
+    pub project_id: String,
+}
+
+/// A short id given without a project that names tickets in several projects, whose
+/// prefixes collide. `resolve` returns it inside its `anyhow::Error`; callers find it with
+/// `downcast_ref`.
+#[derive(Debug, Clone, Serialize, thiserror::Error)]
+#[error(
+    "'{reference}' matches tickets in {} projects; pass project_id or the full ticket id",
+    candidates.len()
+)]
+pub struct AmbiguousTicketRef {
+    pub reference: String,
+    pub candidates: Vec<TicketCandidate>,
+}
+
+#[derive(Debug, PartialEq)]
+enum ShortRef {
+    /// "VEM-142", normalized to the stored form
+    ShortId(String),
+    /// "142" or "#142", only meaningful within a project
+    Number(i64),
+}
+
+fn parse_short_ref(reference: &str) -> Option<ShortRef> {
+    fn number(digits: &str) -> Option<i64> {
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        digits.parse().ok().filter(|n| *n > 0)
+    }
+
+    let bare = reference.strip_prefix('#').unwrap_or(reference);
+    if let Some(n) = number(bare) {
+        return Some(ShortRef::Number(n));
+    }
+    let (prefix, digits) = reference.rsplit_once('-')?;
+    if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let n = number(digits)?;
+    Some(ShortRef::ShortId(format!(
+        "{}-{}",
+        prefix.to_uppercase(),
+        n
+    )))
+}
+
+/// Ticket id that `reference` names: a ticket id, a short id such as "VEM-142" (any case),
+/// or within a project just its number ("142" or "#142"). With `project_id`, only that
+/// project's tickets match. None when no ticket matches; an `AmbiguousTicketRef` error
+/// when a short id without a project matches tickets in several projects.
+pub async fn resolve(
+    pool: &DbPool,
+    project_id: Option<&str>,
+    reference: &str,
+) -> Result<Option<String>> {
+    let reference = reference.trim();
+    let exact: Option<String> = sqlx::query_scalar(
+        "SELECT ticket_id FROM tickets WHERE ticket_id = ?1 AND (?2 IS NULL OR project_id = ?2)",
+    )
+    .bind(reference)
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await?;
+    if exact.is_some() {
+        return Ok(exact);
+    }
+
+    let candidates = match (parse_short_ref(reference), project_id) {
+        (Some(ShortRef::ShortId(short_id)), _) => {
+            sqlx::query_as::<_, TicketCandidate>(
+                r#"
+                SELECT ticket_id, short_id, project_id
+                FROM tickets
+                WHERE short_id = ?1 AND (?2 IS NULL OR project_id = ?2)
+                ORDER BY project_id
+                LIMIT ?3
+                "#,
+            )
+            .bind(&short_id)
+            .bind(project_id)
+            .bind(MAX_CANDIDATES)
+            .fetch_all(pool)
+            .await?
+        }
+        (Some(ShortRef::Number(number)), Some(project_id)) => {
+            sqlx::query_as::<_, TicketCandidate>(
+                "SELECT ticket_id, short_id, project_id FROM tickets WHERE project_id = ?1 AND number = ?2",
+            )
+            .bind(project_id)
+            .bind(number)
+            .fetch_all(pool)
+            .await?
+        }
+        _ => Vec::new(),
+    };
+
+    match candidates.len() {
+        0 => Ok(None),
+        1 => Ok(candidates.into_iter().next().map(|c| c.ticket_id)),
+        _ => Err(AmbiguousTicketRef {
+            reference: reference.to_string(),
+            candidates,
+        }
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    #[test]
+    fn test_parse_short_ref() {
+        assert_eq!(
+            parse_short_ref("vem-007"),
+            Some(ShortRef::ShortId("VEM-7".to_string()))
+        );
+        assert_eq!(parse_short_ref("#12"), Some(ShortRef::Number(12)));
+        assert_eq!(parse_short_ref("12"), Some(ShortRef::Number(12)));
+        assert_eq!(parse_short_ref("VEM-BE-001"), None);
+        assert_eq!(parse_short_ref("VEM-0"), None);
+        assert_eq!(parse_short_ref("-5"), None);
+    }
+
+    #[tokio::test]
+    async fn test_short_ids_resolve_within_project_and_report_ambiguity() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        // Both projects get the prefix "AB"
+        let alpha = fx.project("alpha-beta").create().await.unwrap();
+        let other = fx.project("another-build").create().await.unwrap();
+        let first = fx.ticket(&alpha, "first").create().await.unwrap();
+        let second = fx.ticket(&alpha, "second").create().await.unwrap();
+        let elsewhere = fx.ticket(&other, "elsewhere").create().await.unwrap();
+        assert_eq!(
+            (first.short_id.as_str(), second.short_id.as_str()),
+            ("AB-1", "AB-2")
+        );
+        assert_eq!(elsewhere.short_id, "AB-1");
+
+        let alpha_id = Some(alpha.repository_name.as_str());
+        for reference in ["AB-2", "ab-2", "2", "#2", second.ticket_id.as_str()] {
+            assert_eq!(
+                resolve(&pool, alpha_id, reference).await.unwrap(),
+                Some(second.ticket_id.clone()),
+                "{}",
+                reference
+            );
+        }
+        // Tickets of other projects do not match, even by ticket id
+        assert_eq!(
+            resolve(&pool, alpha_id, &elsewhere.ticket_id)
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(resolve(&pool, alpha_id, "AB-3").await.unwrap(), None);
+
+        // Without a project: unique short ids and ticket ids resolve, shared ones are ambiguous
+        assert_eq!(
+            resolve(&pool, None, "AB-2").await.unwrap(),
+            Some(second.ticket_id.clone())
+        );
+        assert_eq!(
+            resolve(&pool, None, &elsewhere.ticket_id).await.unwrap(),
+            Some(elsewhere.ticket_id.clone())
+        );
+        assert_eq!(resolve(&pool, None, "2").await.unwrap(), None);
+        let err = resolve(&pool, None, "AB-1").await.unwrap_err();
+        let ambiguous = err.downcast_ref::<AmbiguousTicketRef>().unwrap();
+        let mut candidates: Vec<_> = ambiguous
+            .candidates
+            .iter()
+            .map(|c| c.ticket_id.as_str())
+            .collect();
+        candidates.sort();
+        let mut expected = vec![first.ticket_id.as_str(), elsewhere.ticket_id.as_str()];
+        expected.sort();
+        assert_eq!(candidates, expected);
+    }
+
+    #[tokio::test]
+    async fn test_numbers_are_not_reused_after_delete() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("numbering").create().await.unwrap();
+        fx.ticket(&project, "one").create().await.unwrap();
+        let two = fx.ticket(&project, "two").create().await.unwrap();
+        sqlx::query("DELETE FROM tickets WHERE ticket_id = ?1")
+            .bind(&two.ticket_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let three = fx.ticket(&project, "three").create().await.unwrap();
+        assert_eq!(three.short_id, "N-3");
+    }
+}
@@ -0,0 +1,263 @@
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::FromRow;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::{
+    tickets::{Ticket, TicketFilter, TICKET_COLUMNS},
+    timing::timed,
+    DbPool,
+};
+
+/// Results per search unless the caller asks for fewer
+pub const DEFAULT_SEARCH_LIMIT: i64 = 20;
+pub const MAX_SEARCH_LIMIT: i64 = 100;
+
+/// Ticket matching a full-text search, best match first
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct TicketSearchHit {
+    #[sqlx(flatten)]
+    pub ticket: Ticket,
+    /// Title with matched terms wrapped in `<mark>`; the rest is not HTML-escaped
+    pub title_highlight: String,
+    /// Passage of the best-matching field with matched terms wrapped in `<mark>`
+    pub snippet: String,
+    /// bm25 rank, lower is better; title matches weigh more than description, and
+    /// description more than comments
+    pub score: f64,
+}
+
+/// FTS5 query requiring every word of `text`, or None when it has no words. Words are
+/// quoted so punctuation and FTS5 operators in user input are searched for literally.
+pub fn match_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+impl TicketSearchHit {
+    /// Tickets whose title, description or comments contain every word of `text`,
+    /// narrowed by `filter` in the same query
+    pub async fn search(
+        pool: &DbPool,
+        text: &str,
+        filter: &TicketFilter<'_>,
+        limit: i64,
+    ) -> Result<Vec<TicketSearchHit>> {
+        let Some(query) = match_query(text) else {
+            anyhow::bail!("Search text must contain at least one word");
+        };
+
+        let mut builder = sqlx::QueryBuilder::new(format!(
+            r#"
+            SELECT {}, hits.title_highlight, hits.snippet, hits.score
+            FROM tickets
+            JOIN (
+                SELECT d.ticket_id AS hit_ticket_id,
+                       highlight(ticket_search, 0, '<mark>', '</mark>') AS title_highlight,
+                       snippet(ticket_search, -1, '<mark>', '</mark>', '…', 16) AS snippet,
+                       bm25(ticket_search, 10.0, 4.0, 1.0) AS score
+                FROM ticket_search
+                JOIN ticket_search_docs d ON d.doc_id = ticket_search.rowid
+                WHERE ticket_search MATCH "#,
+            TICKET_COLUMNS
+        ));
+        builder
+            .push_bind(query)
+            .push(") hits ON hits.hit_ticket_id = tickets.ticket_id WHERE 1=1");
+        filter.push_conditions(&mut builder)?;
+        builder
+            .push(" ORDER BY hits.score, tickets.created_at DESC LIMIT ")
+            .push_bind(limit.clamp(1, MAX_SEARCH_LIMIT));
+
+        let hits = timed!(
+            pool,
+            "TicketSearchHit::search",
+            builder.build_query_as::<TicketSearchHit>(),
+            fetch_all
+        )
+        .inspect_err(|e| warn!("Failed to search tickets for {:?}: {:?}", text, e))?;
+        Ok(hits)
+    }
+
+    /// Comments left out of the search index because they are encrypted, optionally of
+    /// one project's tickets
+    pub async fn encrypted_comments(pool: &DbPool, project_id: Option<&str>) -> Result<i64> {
+        let count = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM comments
+            WHERE content_key_id IS NOT NULL
+              AND (?1 IS NULL OR ticket_id IN (SELECT ticket_id FROM tickets WHERE project_id = ?1))
+            "#,
+        )
+        .bind(project_id)
+        .fetch_one(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to count encrypted comments: {:?}", e))?;
+        Ok(count)
+    }
+
+    /// Note for search results when encrypted comments could not be searched
+    pub async fn encryption_warning(
+        pool: &DbPool,
+        project_id: Option<&str>,
+    ) -> Result<Option<String>> {
+        let count = Self::encrypted_comments(pool, project_id).await?;
+        Ok((count > 0).then(|| {
+            format!(
+                "{} encrypted comments were not searched; message encryption keeps them out of the search index",
+                count
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{comments::Comment, tags::Tag};
+    use crate::test_support::{memory_pool, Fixtures};
+
+    #[test]
+    fn test_match_query_quotes_words() {
+        assert_eq!(
+            match_query(r#"flaky web-socket "test" OR -"#).as_deref(),
+            Some(r#""flaky" "web-socket" """test""" "OR""#)
+        );
+        assert_eq!(match_query("  - * "), None);
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_and_tracks_writes() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let web = fx.project("search-web").create().await.unwrap();
+        let api = fx.project("search-api").create().await.unwrap();
+
+        let in_title = fx
+            .ticket(&web, "Flaky websocket test")
+            .description("Fails on CI now and then")
+            .create()
+            .await
+            .unwrap();
+        let in_description = fx
+            .ticket(&web, "Stabilize CI")
+            .description("The websocket suite is flaky under load")
+            .create()
+            .await
+            .unwrap();
+        let in_comments = fx
+            .ticket(&api, "Reconnect handling")
+            .description("Clients lose their session")
+            .create()
+            .await
+            .unwrap();
+        let ids = |hits: Vec<TicketSearchHit>| {
+            hits.into_iter()
+                .map(|hit| hit.ticket.ticket_id)
+                .collect::<Vec<_>>()
+        };
+        let search = |text: &'static str, filter: TicketFilter<'static>| {
+            let pool = pool.clone();
+            async move {
+                TicketSearchHit::search(&pool, text, &filter, DEFAULT_SEARCH_LIMIT)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // Title matches outrank description matches; other tickets are left out
+        let hits = search("flaky websocket", TicketFilter::default()).await;
+        assert_eq!(
+            hits[0].title_highlight,
+            "<mark>Flaky</mark> <mark>websocket</mark> test"
+        );
+        assert_eq!(
+            ids(hits),
+            [
+                in_title.ticket_id.as_str(),
+                in_description.ticket_id.as_str()
+            ]
+        );
+
+        // Comments and resolutions are searchable as soon as they are written
+        assert!(search("flaky", TicketFilter::default())
+            .await
+            .iter()
+            .all(|hit| hit.ticket.ticket_id != in_comments.ticket_id));
+        let comment = Comment::create(
+            &pool,
+            &in_comments.ticket_id,
+            Some("coordinator"),
+            Some("coordinator"),
+            Some(999),
+            "Resolved: the flaky reconnect was a race",
+        )
+        .await
+        .unwrap();
+        let hits = search("race", TicketFilter::default()).await;
+        assert_eq!(ids(hits.clone()), [in_comments.ticket_id.as_str()]);
+        assert!(hits[0].snippet.contains("<mark>race</mark>"));
+
+        // Filters apply in the same query
+        let api_only = TicketFilter {
+            project_id: Some("search-api"),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(search("flaky", api_only).await),
+            [in_comments.ticket_id.as_str()]
+        );
+        let tag = Tag::resolve(&pool, &["ci".to_string()], true)
+            .await
+            .unwrap();
+        Tag::set_for_ticket(&pool, &in_description.ticket_id, &tag)
+            .await
+            .unwrap();
+        let tagged = TicketFilter {
+            tag: Some("CI"),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(search("flaky", tagged).await),
+            [in_description.ticket_id.as_str()]
+        );
+        let closed = TicketFilter {
+            status: Some("closed"),
+            ..Default::default()
+        };
+        assert!(search("flaky", closed).await.is_empty());
+
+        // Edits, deletions and renames keep the index in sync
+        sqlx::query("UPDATE comments SET content = 'Resolved: timing issue' WHERE id = ?1")
+            .bind(comment.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(search("race", TicketFilter::default()).await.is_empty());
+        sqlx::query("UPDATE tickets SET title = 'Session drops' WHERE ticket_id = ?1")
+            .bind(&in_comments.ticket_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            ids(search("drops timing", TicketFilter::default()).await),
+            [in_comments.ticket_id.as_str()]
+        );
+        sqlx::query("DELETE FROM tickets WHERE ticket_id = ?1")
+            .bind(&in_title.ticket_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            ids(search("websocket", TicketFilter::default()).await),
+            [in_description.ticket_id.as_str()]
+        );
+
+        pool.close().await;
+    }
+}
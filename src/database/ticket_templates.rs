@@ -0,0 +1,318 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{types::Json, FromRow};
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use super::{returning::FetchReturning, DbPool};
+
+const TEMPLATE_COLUMNS: &str =
+    "name, description, default_priority, required_fields, default_tags, created_at, updated_at";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct TicketTemplate {
+    pub name: String,
+    pub description: Option<String>,
+    pub default_priority: String,
+    #[schema(value_type = Vec<TemplateField>)]
+    pub required_fields: Json<Vec<TemplateField>>,
+    /// Tags of the tickets created from the template, besides the ones given
+    #[schema(value_type = Vec<String>)]
+    pub default_tags: Json<Vec<String>>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Value type of a custom field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    String,
+    Number,
+    Boolean,
+    Array,
+    Object,
+}
+
+impl FieldType {
+    fn matches(&self, value: &Value) -> bool {
+        match self {
+            FieldType::String => value.as_str().is_some_and(|s| !s.trim().is_empty()),
+            FieldType::Number => value.is_number(),
+            FieldType::Boolean => value.is_boolean(),
+            FieldType::Array => value.is_array(),
+            FieldType::Object => value.is_object(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TemplateField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    pub description: Option<String>,
+}
+
+/// Validation failure for a single custom field
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub error: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateTicketTemplateRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub default_priority: Option<String>,
+    #[serde(default)]
+    pub required_fields: Vec<TemplateField>,
+    #[serde(default)]
+    pub default_tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateTicketTemplateRequest {
+    pub description: Option<String>,
+    pub default_priority: Option<String>,
+    pub required_fields: Option<Vec<TemplateField>>,
+    pub default_tags: Option<Vec<String>>,
+}
+
+impl TicketTemplate {
+    pub async fn create(pool: &DbPool, req: CreateTicketTemplateRequest) -> Result<TicketTemplate> {
+        let template = sqlx::query_as::<_, TicketTemplate>(&format!(
+            r#"
+            INSERT INTO ticket_templates (
+                name, description, default_priority, required_fields, default_tags
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            RETURNING {}
+        "#,
+            TEMPLATE_COLUMNS
+        ))
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(req.default_priority.as_deref().unwrap_or("medium"))
+        .bind(Json(&req.required_fields))
+        .bind(Json(&req.default_tags))
+        .fetch_returned_one(pool)
+        .await
+        .inspect_err(|e| error!("Failed to create ticket template '{}': {:?}", req.name, e))?;
+
+        Ok(template)
+    }
+
+    pub async fn get_by_name(pool: &DbPool, name: &str) -> Result<Option<TicketTemplate>> {
+        let template = sqlx::query_as::<_, TicketTemplate>(&format!(
+            "SELECT {} FROM ticket_templates WHERE name = ?1",
+            TEMPLATE_COLUMNS
+        ))
+        .bind(name)
+        .fetch_optional(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to fetch ticket template '{}': {:?}", name, e))?;
+
+        Ok(template)
+    }
+
+    pub async fn list_all(pool: &DbPool) -> Result<Vec<TicketTemplate>> {
+        let templates = sqlx::query_as::<_, TicketTemplate>(&format!(
+            "SELECT {} FROM ticket_templates ORDER BY name ASC",
+            TEMPLATE_COLUMNS
+        ))
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to list ticket templates: {:?}", e))?;
+
+        Ok(templates)
+    }
+
+    pub async fn update(
+        pool: &DbPool,
+        name: &str,
+        req: UpdateTicketTemplateRequest,
+    ) -> Result<Option<TicketTemplate>> {
+        if req.description.is_none()
+            && req.default_priority.is_none()
+            && req.required_fields.is_none()
+            && req.default_tags.is_none()
+        {
+            return Self::get_by_name(pool, name).await;
+        }
+
+        let mut query_builder = sqlx::QueryBuilder::new("UPDATE ticket_templates SET ");
+        let mut separated = query_builder.separated(", ");
+
+        if let Some(ref description) = req.description {
+            separated.push("description = ");
+            separated.push_bind_unseparated(description);
+        }
+        if let Some(ref priority) = req.default_priority {
+            separated.push("default_priority = ");
+            separated.push_bind_unseparated(priority);
+        }
+        if let Some(ref fields) = req.required_fields {
+            separated.push("required_fields = ");
+            separated.push_bind_unseparated(Json(fields));
+        }
+        if let Some(ref tags) = req.default_tags {
+            separated.push("default_tags = ");
+            separated.push_bind_unseparated(Json(tags));
+        }
+        separated.push("updated_at = datetime('now')");
+
+        query_builder.push(" WHERE name = ");
+        query_builder.push_bind(name);
+        query_builder.push(" RETURNING ");
+        query_builder.push(TEMPLATE_COLUMNS);
+
+        let template = query_builder
+            .build_query_as::<TicketTemplate>()
+            .fetch_returned(pool)
+            .await
+            .inspect_err(|e| error!("Failed to update ticket template '{}': {:?}", name, e))?;
+
+        Ok(template)
+    }
+
+    pub async fn delete(pool: &DbPool, name: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM ticket_templates WHERE name = ?1")
+            .bind(name)
+            .execute(pool)
+            .await
+            .inspect_err(|e| error!("Failed to delete ticket template '{}': {:?}", name, e))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Check supplied custom field values against the template's required fields
+    pub fn validate_custom_fields(&self, values: &Map<String, Value>) -> Result<Vec<FieldError>> {
+        Ok(validate_fields(&self.required_fields, values))
+    }
+
+    /// The template's default tags followed by `tags`, without repeats
+    pub fn tags_with(&self, tags: &[String]) -> Vec<String> {
+        let mut merged = self.default_tags.0.clone();
+        for tag in tags {
+            if !merged.contains(tag) {
+                merged.push(tag.clone());
+            }
+        }
+        merged
+    }
+}
+
+fn validate_fields(fields: &[TemplateField], values: &Map<String, Value>) -> Vec<FieldError> {
+    fields
+        .iter()
+        .filter_map(|field| match values.get(&field.name) {
+            None | Some(Value::Null) => Some(FieldError {
+                field: field.name.clone(),
+                error: "required field is missing".to_string(),
+            }),
+            Some(value) if !field.field_type.matches(value) => Some(FieldError {
+                field: field.name.clone(),
+                error: format!(
+                    "expected non-empty value of type {}",
+                    serde_json::to_string(&field.field_type).unwrap_or_default()
+                ),
+            }),
+            Some(_) => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_pool, Fixtures};
+    use serde_json::json;
+
+    fn bug_fields() -> Vec<TemplateField> {
+        serde_json::from_value(json!([
+            { "name": "reproduction_steps", "type": "string" },
+            { "name": "affected_project", "type": "string" },
+            { "name": "severity", "type": "number" }
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_all_required_fields_present() {
+        let values = json!({
+            "reproduction_steps": "1. run\n2. crash",
+            "affected_project": "api",
+            "severity": 2,
+            "extra": true
+        });
+        let errors = validate_fields(&bug_fields(), values.as_object().unwrap());
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_missing_and_mistyped_fields_reported_individually() {
+        let values = json!({ "reproduction_steps": "  ", "severity": "high" });
+        let errors = validate_fields(&bug_fields(), values.as_object().unwrap());
+
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec!["reproduction_steps", "affected_project", "severity"]
+        );
+        assert_eq!(errors[1].error, "required field is missing");
+    }
+
+    #[tokio::test]
+    async fn test_template_and_ticket_fields_are_json() {
+        let pool = memory_pool().await.unwrap();
+        let template = TicketTemplate::create(
+            &pool,
+            CreateTicketTemplateRequest {
+                name: "bug".to_string(),
+                description: None,
+                default_priority: Some("high".to_string()),
+                required_fields: bug_fields(),
+                default_tags: vec!["bug".to_string(), "triage".to_string()],
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            template.tags_with(&["ui".to_string(), "bug".to_string()]),
+            ["bug", "triage", "ui"]
+        );
+
+        let updated = TicketTemplate::update(
+            &pool,
+            "bug",
+            UpdateTicketTemplateRequest {
+                description: None,
+                default_priority: None,
+                required_fields: None,
+                default_tags: Some(vec!["bug".to_string()]),
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let listed = serde_json::to_value(&updated).unwrap();
+        assert_eq!(listed["default_tags"], json!(["bug"]));
+        assert_eq!(listed["required_fields"][2]["name"], "severity");
+
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("templated").create().await.unwrap();
+        let ticket = fx
+            .ticket(&project, "crash")
+            .custom_fields(json!({"severity": 2}))
+            .create()
+            .await
+            .unwrap();
+        let read = serde_json::to_value(&ticket).unwrap();
+        assert_eq!(read["custom_fields"], json!({"severity": 2}));
+
+        pool.close().await;
+    }
+}
@@ -0,0 +1,523 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use std::collections::BTreeMap;
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use super::{board::BoardColumn, returning::FetchReturning, DbPool};
+
+/// History rows recorded since a project's last snapshot before another one is taken
+pub const SNAPSHOT_EVERY_ROWS: i64 = 500;
+
+const SQLITE_TIMESTAMP: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A ticket as it stood at an instant, replayed from ticket_history. Fields that history
+/// recorded before migration 038 did not track are None until the ticket's next change.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct TicketStateAt {
+    pub ticket_id: String,
+    pub project_id: String,
+    pub created_at: String,
+    /// Time of the last change at or before the instant
+    pub changed_at: String,
+    pub state: String,
+    pub estimate_minutes: Option<i64>,
+    pub processing_worker_id: Option<String>,
+    pub title: Option<String>,
+    /// Stage, i.e. the worker type the ticket was assigned to
+    pub current_stage: Option<String>,
+    pub priority: Option<String>,
+    pub dependency_status: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+impl TicketStateAt {
+    /// Board column the ticket was displayed in, as [`super::board::BoardCard::column`]
+    pub fn column(&self) -> BoardColumn {
+        match self.state.as_str() {
+            "closed" => BoardColumn::Closed,
+            "on_hold" => BoardColumn::OnHold,
+            _ if self.dependency_status.as_deref() == Some("blocked") => BoardColumn::Blocked,
+            _ if self.processing_worker_id.is_some() => BoardColumn::InProgress,
+            _ => BoardColumn::Open,
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct HistoryRow {
+    ticket_id: String,
+    change: String,
+    state: String,
+    estimate_minutes: Option<i64>,
+    processing_worker_id: Option<String>,
+    title: Option<String>,
+    current_stage: Option<String>,
+    priority: Option<String>,
+    dependency_status: Option<String>,
+    tags: Option<String>,
+    created_at: String,
+}
+
+#[derive(Debug, FromRow)]
+struct SnapshotEntry {
+    ticket_id: String,
+    created_at: String,
+    changed_at: String,
+    state: String,
+    estimate_minutes: Option<i64>,
+    processing_worker_id: Option<String>,
+    title: Option<String>,
+    current_stage: Option<String>,
+    priority: Option<String>,
+    dependency_status: Option<String>,
+    tags: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct Snapshot {
+    id: i64,
+    taken_at: String,
+    last_history_id: i64,
+}
+
+/// Parse an instant given as RFC 3339, `YYYY-MM-DD HH:MM:SS` (UTC) or a date (its start)
+/// into SQLite's timestamp format
+pub fn parse_instant(value: &str) -> Option<String> {
+    let instant = match DateTime::parse_from_rfc3339(value) {
+        Ok(parsed) => parsed.with_timezone(&Utc),
+        Err(_) => ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+            .or_else(|| {
+                NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+            })?
+            .and_utc(),
+    };
+    Some(instant.format(SQLITE_TIMESTAMP).to_string())
+}
+
+fn parse_tags(tags: Option<String>) -> Option<Vec<String>> {
+    tags.and_then(|json| serde_json::from_str(&json).ok())
+}
+
+/// State of `ticket_id` at `at` (SQLite format); None when it did not exist yet
+pub async fn ticket_at(
+    pool: &DbPool,
+    project_id: &str,
+    ticket_id: &str,
+    at: &str,
+) -> Result<Option<TicketStateAt>> {
+    let mut states = reconstruct(pool, project_id, Some(ticket_id), at).await?;
+    Ok(states.pop())
+}
+
+/// Every ticket of a project that existed at `at` (SQLite format), oldest first
+pub async fn project_at(pool: &DbPool, project_id: &str, at: &str) -> Result<Vec<TicketStateAt>> {
+    reconstruct(pool, project_id, None, at).await
+}
+
+/// Start from the project's latest snapshot taken at or before `at` and replay the history
+/// rows it doesn't cover. Rows recorded after the snapshot but backdated before it (ticket
+/// imports) are replayed too. Deleted tickets, whose history is gone, are left out.
+async fn reconstruct(
+    pool: &DbPool,
+    project_id: &str,
+    ticket_id: Option<&str>,
+    at: &str,
+) -> Result<Vec<TicketStateAt>> {
+    let snapshot = sqlx::query_as::<_, Snapshot>(
+        r#"
+        SELECT id, taken_at, last_history_id
+        FROM ticket_state_snapshots
+        WHERE project_id = ?1 AND taken_at <= ?2
+        ORDER BY taken_at DESC, id DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(project_id)
+    .bind(at)
+    .fetch_optional(pool)
+    .await?;
+
+    let mut states: BTreeMap<String, TicketStateAt> = BTreeMap::new();
+    if let Some(snapshot) = &snapshot {
+        let entries = sqlx::query_as::<_, SnapshotEntry>(
+            r#"
+            SELECT e.ticket_id, e.created_at, e.changed_at, e.state, e.estimate_minutes,
+                   e.processing_worker_id, e.title, e.current_stage, e.priority,
+                   e.dependency_status, e.tags
+            FROM ticket_state_snapshot_entries e
+            JOIN tickets t ON t.ticket_id = e.ticket_id
+            WHERE e.snapshot_id = ?1 AND (?2 IS NULL OR e.ticket_id = ?2)
+            "#,
+        )
+        .bind(snapshot.id)
+        .bind(ticket_id)
+        .fetch_all(pool)
+        .await?;
+        for entry in entries {
+            states.insert(
+                entry.ticket_id.clone(),
+                TicketStateAt {
+                    ticket_id: entry.ticket_id,
+                    project_id: project_id.to_string(),
+                    created_at: entry.created_at,
+                    changed_at: entry.changed_at,
+                    state: entry.state,
+                    estimate_minutes: entry.estimate_minutes,
+                    processing_worker_id: entry.processing_worker_id,
+                    title: entry.title,
+                    current_stage: entry.current_stage,
+                    priority: entry.priority,
+                    dependency_status: entry.dependency_status,
+                    tags: parse_tags(entry.tags),
+                },
+            );
+        }
+    }
+
+    let (taken_at, last_history_id) = snapshot
+        .as_ref()
+        .map(|s| (s.taken_at.as_str(), s.last_history_id))
+        .unwrap_or(("", 0));
+    let rows = sqlx::query_as::<_, HistoryRow>(
+        r#"
+        SELECT ticket_id, change, state, estimate_minutes, processing_worker_id, title,
+               current_stage, priority, dependency_status, tags, created_at
+        FROM ticket_history
+        WHERE project_id = ?1 AND (?2 IS NULL OR ticket_id = ?2) AND created_at <= ?3
+          AND (created_at > ?4 OR id > ?5)
+        ORDER BY created_at ASC, id ASC
+        "#,
+    )
+    .bind(project_id)
+    .bind(ticket_id)
+    .bind(at)
+    .bind(taken_at)
+    .bind(last_history_id)
+    .fetch_all(pool)
+    .await
+    .inspect_err(|e| warn!("Failed to load ticket history of '{}': {:?}", project_id, e))?;
+
+    for row in rows {
+        apply(&mut states, project_id, row);
+    }
+
+    let mut states: Vec<TicketStateAt> = states.into_values().collect();
+    states.sort_by(|a, b| {
+        (a.created_at.as_str(), a.ticket_id.as_str())
+            .cmp(&(b.created_at.as_str(), b.ticket_id.as_str()))
+    });
+    Ok(states)
+}
+
+/// Fold one history row into the ticket's state. State, estimate and worker are recorded on
+/// every row; the other fields only on rows written since migration 038.
+fn apply(states: &mut BTreeMap<String, TicketStateAt>, project_id: &str, row: HistoryRow) {
+    let state = states
+        .entry(row.ticket_id.clone())
+        .or_insert_with(|| TicketStateAt {
+            ticket_id: row.ticket_id.clone(),
+            project_id: project_id.to_string(),
+            created_at: row.created_at.clone(),
+            changed_at: row.created_at.clone(),
+            state: row.state.clone(),
+            estimate_minutes: None,
+            processing_worker_id: None,
+            title: None,
+            current_stage: None,
+            priority: None,
+            dependency_status: None,
+            tags: None,
+        });
+    if row.change == "created" {
+        state.created_at = row.created_at.clone();
+    }
+    state.changed_at = row.created_at;
+    state.state = row.state;
+    state.estimate_minutes = row.estimate_minutes;
+    state.processing_worker_id = row.processing_worker_id;
+    if row.title.is_some() {
+        state.title = row.title;
+    }
+    if row.current_stage.is_some() {
+        state.current_stage = row.current_stage;
+    }
+    if row.priority.is_some() {
+        state.priority = row.priority;
+    }
+    if row.dependency_status.is_some() {
+        state.dependency_status = row.dependency_status;
+    }
+    if let Some(tags) = parse_tags(row.tags) {
+        state.tags = Some(tags);
+    }
+}
+
+/// Materialize every ticket of a project as of `at` (SQLite format), so later
+/// reconstructions replay from here
+pub async fn take_snapshot(pool: &DbPool, project_id: &str, at: &str) -> Result<i64> {
+    // Read before replaying, so rows recorded meanwhile are replayed again rather than lost
+    let last_history_id: i64 =
+        sqlx::query_scalar("SELECT COALESCE(MAX(id), 0) FROM ticket_history")
+            .fetch_one(pool)
+            .await?;
+    let states = project_at(pool, project_id, at).await?;
+
+    let mut tx = pool.begin().await?;
+    let snapshot_id: i64 = sqlx::query_scalar(
+        r#"
+        INSERT INTO ticket_state_snapshots (project_id, taken_at, last_history_id)
+        VALUES (?1, ?2, ?3)
+        RETURNING id
+        "#,
+    )
+    .bind(project_id)
+    .bind(at)
+    .bind(last_history_id)
+    .fetch_returned_one(&mut *tx)
+    .await?;
+    for state in &states {
+        let tags = state.tags.as_ref().map(serde_json::to_string).transpose()?;
+        sqlx::query(
+            r#"
+            INSERT INTO ticket_state_snapshot_entries
+                (snapshot_id, ticket_id, created_at, changed_at, state, estimate_minutes,
+                 processing_worker_id, title, current_stage, priority, dependency_status, tags)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+            "#,
+        )
+        .bind(snapshot_id)
+        .bind(&state.ticket_id)
+        .bind(&state.created_at)
+        .bind(&state.changed_at)
+        .bind(&state.state)
+        .bind(state.estimate_minutes)
+        .bind(&state.processing_worker_id)
+        .bind(&state.title)
+        .bind(&state.current_stage)
+        .bind(&state.priority)
+        .bind(&state.dependency_status)
+        .bind(tags)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit()
+        .await
+        .inspect_err(|e| error!("Failed to snapshot tickets of '{}': {:?}", project_id, e))?;
+    Ok(snapshot_id)
+}
+
+/// Snapshot the project now once [`SNAPSHOT_EVERY_ROWS`] history rows have been recorded
+/// since its last snapshot; the new snapshot's id, if one was taken
+pub async fn snapshot_if_due(pool: &DbPool, project_id: &str) -> Result<Option<i64>> {
+    let pending: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM ticket_history
+        WHERE project_id = ?1 AND id > COALESCE((
+            SELECT MAX(last_history_id) FROM ticket_state_snapshots WHERE project_id = ?1
+        ), 0)
+        "#,
+    )
+    .bind(project_id)
+    .fetch_one(pool)
+    .await?;
+    if pending < SNAPSHOT_EVERY_ROWS {
+        return Ok(None);
+    }
+
+    let now = Utc::now().format(SQLITE_TIMESTAMP).to_string();
+    take_snapshot(pool, project_id, &now).await.map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{tags::Tag, tickets::Ticket};
+    use crate::test_support::{memory_pool, Fixtures};
+
+    /// Backdate the history rows recorded after `last` to `at`; the new last id
+    async fn backdate(pool: &DbPool, last: i64, at: &str) -> i64 {
+        sqlx::query("UPDATE ticket_history SET created_at = ?2 WHERE id > ?1")
+            .bind(last)
+            .bind(at)
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query_scalar("SELECT COALESCE(MAX(id), 0) FROM ticket_history")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    type Summary = (
+        String,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<Vec<String>>,
+    );
+
+    fn summary(state: &TicketStateAt) -> Summary {
+        (
+            state.state.clone(),
+            state.current_stage.clone(),
+            state.priority.clone(),
+            state.processing_worker_id.clone(),
+            state.tags.clone(),
+        )
+    }
+
+    fn expected(
+        state: &str,
+        stage: &str,
+        priority: &str,
+        worker: Option<&str>,
+        tags: &[&str],
+    ) -> Summary {
+        (
+            state.to_string(),
+            Some(stage.to_string()),
+            Some(priority.to_string()),
+            worker.map(str::to_string),
+            Some(tags.iter().map(|t| t.to_string()).collect()),
+        )
+    }
+
+    #[test]
+    fn test_parse_instant() {
+        assert_eq!(
+            parse_instant("2026-03-01T10:00:00+02:00").as_deref(),
+            Some("2026-03-01 08:00:00")
+        );
+        assert_eq!(
+            parse_instant("2026-03-01 10:00:00").as_deref(),
+            Some("2026-03-01 10:00:00")
+        );
+        assert_eq!(
+            parse_instant("2026-03-01").as_deref(),
+            Some("2026-03-01 00:00:00")
+        );
+        assert_eq!(parse_instant("yesterday"), None);
+    }
+
+    #[tokio::test]
+    async fn test_reconstructs_state_at_past_instants() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("timeline").create().await.unwrap();
+        let dev = fx.worker_type(&project, "dev").create().await.unwrap();
+        fx.worker_type(&project, "review").create().await.unwrap();
+
+        let ticket = fx
+            .ticket(&project, "Login fails")
+            .assigned_to(&dev)
+            .create()
+            .await
+            .unwrap();
+        let id = ticket.ticket_id.clone();
+        let mut last = backdate(&pool, 0, "2026-03-01 09:00:00").await;
+
+        let tag_ids = Tag::resolve(&pool, &["bug".to_string()], true)
+            .await
+            .unwrap();
+        Tag::set_for_ticket(&pool, &id, &tag_ids).await.unwrap();
+        Ticket::update_priority(&pool, &id, "high").await.unwrap();
+        last = backdate(&pool, last, "2026-03-01 10:00:00").await;
+
+        sqlx::query("UPDATE tickets SET processing_worker_id = 'w-1' WHERE ticket_id = ?1")
+            .bind(&id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        last = backdate(&pool, last, "2026-03-01 11:00:00").await;
+
+        sqlx::query("UPDATE tickets SET processing_worker_id = NULL WHERE ticket_id = ?1")
+            .bind(&id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        Ticket::update_stage(&pool, &id, "review").await.unwrap();
+        last = backdate(&pool, last, "2026-03-01 12:00:00").await;
+
+        let later = fx.ticket(&project, "Logout fails").create().await.unwrap();
+        last = backdate(&pool, last, "2026-03-01 13:00:00").await;
+
+        Ticket::close_ticket(&pool, &id, "Completed").await.unwrap();
+        backdate(&pool, last, "2026-03-01 14:00:00").await;
+
+        let checks = |at: &'static str| {
+            let pool = pool.clone();
+            let id = id.clone();
+            async move {
+                ticket_at(&pool, "timeline", &id, at)
+                    .await
+                    .unwrap()
+                    .map(|s| summary(&s))
+            }
+        };
+        let expectations = [
+            ("2026-03-01 08:59:59", None),
+            (
+                "2026-03-01 09:30:00",
+                Some(expected("open", "dev", "medium", None, &[])),
+            ),
+            (
+                "2026-03-01 10:00:00",
+                Some(expected("open", "dev", "high", None, &["bug"])),
+            ),
+            (
+                "2026-03-01 11:30:00",
+                Some(expected("open", "dev", "high", Some("w-1"), &["bug"])),
+            ),
+            (
+                "2026-03-01 12:30:00",
+                Some(expected("open", "review", "high", None, &["bug"])),
+            ),
+            (
+                "2026-03-01 14:00:00",
+                Some(expected("closed", "Completed", "high", None, &["bug"])),
+            ),
+        ];
+        for (at, want) in &expectations {
+            assert_eq!(checks(at).await, *want, "as of {}", at);
+        }
+
+        // The same answers when replaying from a snapshot taken mid-sequence
+        take_snapshot(&pool, "timeline", "2026-03-01 11:30:00")
+            .await
+            .unwrap();
+        for (at, want) in &expectations {
+            assert_eq!(checks(at).await, *want, "as of {} with snapshot", at);
+        }
+
+        let board = project_at(&pool, "timeline", "2026-03-01 11:30:00")
+            .await
+            .unwrap();
+        assert_eq!(board.len(), 1);
+        assert_eq!(board[0].column(), BoardColumn::InProgress);
+        assert_eq!(board[0].title.as_deref(), Some("Login fails"));
+
+        let board = project_at(&pool, "timeline", "2026-03-01 13:30:00")
+            .await
+            .unwrap();
+        let columns: Vec<_> = board
+            .iter()
+            .map(|s| (s.ticket_id.as_str(), s.column()))
+            .collect();
+        assert_eq!(
+            columns,
+            [
+                (id.as_str(), BoardColumn::Open),
+                (later.ticket_id.as_str(), BoardColumn::Open)
+            ]
+        );
+
+        pool.close().await;
+    }
+}
@@ -1,12 +1,30 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Row};
+use serde_json::{Map, Value};
+use sqlx::{
+    sqlite::{SqliteTypeInfo, SqliteValueRef},
+    FromRow, Row, Sqlite, SqliteConnection,
+};
 use std::fmt;
-
-use super::DbPool;
+use utoipa::ToSchema;
+
+use super::{
+    blocks::TicketBlock,
+    comments::{Comment, CommentNode},
+    outbox::OutboxEvent,
+    pipeline::PipelineLinks,
+    quotas::{self, Quota},
+    relations::TicketRelations,
+    returning::FetchReturning,
+    routing::{self, RoutingDecision, RoutingPolicy},
+    thread_summaries::ThreadSummary,
+    timing::timed,
+    DbPool,
+};
+use crate::{crypto, error::AppError, events::EventPayload};
 
 /// Ticket state enum for type safety
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum TicketState {
     Open,
@@ -23,7 +41,7 @@ pub enum DependencyStatus {
 }
 
 /// Priority enum for type safety
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Priority {
     Low,
@@ -142,9 +160,11 @@ impl Priority {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Ticket {
     pub ticket_id: String,
+    /// Project prefix and per-project sequence number, e.g. "VEM-142"
+    pub short_id: String,
     pub project_id: String,
     pub title: String,
     pub execution_plan: String, // JSON array
@@ -163,6 +183,46 @@ pub struct Ticket {
     pub rules_version: Option<i32>,
     pub patterns_version: Option<i32>,
     pub inherited_from_parent: bool,
+    /// Custom field values, a JSON object
+    #[schema(value_type = Option<Object>)]
+    pub custom_fields: Option<CustomFields>,
+    // SLA tracking
+    pub due_at: Option<String>,
+    pub sla_minutes: Option<i64>,
+    pub sla_breached: bool,
+    /// Estimated work, set by the coordinator
+    pub estimate_minutes: Option<i64>,
+    /// Time workers spent on the ticket, from claim to release
+    pub actual_minutes: i64,
+    /// Times the ticket left the closed state again
+    pub reopen_count: i64,
+    /// Ticket this one was closed as a duplicate of
+    pub duplicate_of: Option<String>,
+    /// `priority` raised by the escalation rules; never below it
+    pub effective_priority: String,
+    /// Why `effective_priority` is above `priority`, JSON array; None when it is not
+    pub escalation_reasons: Option<String>,
+}
+
+/// Custom field values of a ticket, a JSON object. Values stored before they were checked
+/// to be JSON are kept as a string rather than failing the read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CustomFields(pub Value);
+
+impl sqlx::Type<Sqlite> for CustomFields {
+    fn type_info() -> SqliteTypeInfo {
+        <String as sqlx::Type<Sqlite>>::type_info()
+    }
+}
+
+impl<'r> sqlx::Decode<'r, Sqlite> for CustomFields {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        let text = <&str as sqlx::Decode<Sqlite>>::decode(value)?;
+        Ok(CustomFields(
+            serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string())),
+        ))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -178,6 +238,10 @@ pub struct CreateTicketRequest {
     pub dependency_status: Option<String>,
     pub created_by_worker_id: Option<String>,
     pub priority: Option<String>,
+    pub custom_fields: Option<String>,
+    pub due_at: Option<String>,
+    pub sla_minutes: Option<i64>,
+    pub estimate_minutes: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -185,10 +249,26 @@ pub struct UpdateTicketStageRequest {
     pub new_stage: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct TicketWithComments {
     pub ticket: Ticket,
-    pub comments: Vec<crate::database::comments::Comment>,
+    pub comments: Vec<Comment>,
+    /// Stage pipeline chain this ticket is part of
+    pub pipeline: PipelineLinks,
+    /// Canonical tag names
+    pub tags: Vec<String>,
+    /// Current summary of the comment thread, once attached with `with_summary`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<ThreadSummary>,
+    /// `comments` arranged by replies, once attached with `with_comment_tree`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_tree: Option<Vec<CommentNode>>,
+    /// Related tickets grouped by relation type, once attached with `with_relations`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relations: Option<TicketRelations>,
+    /// Block in force on the ticket, with what it waits on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block: Option<TicketBlock>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -198,17 +278,154 @@ pub struct TicketWithProjectInfo {
     pub project_patterns: Option<String>,
 }
 
+pub(crate) const TICKET_COLUMNS: &str =
+    "ticket_id, project_id, title, execution_plan, current_stage, state, \
+     priority, processing_worker_id, created_at, updated_at, closed_at, parent_ticket_id, \
+     dependency_status, created_by_worker_id, ticket_type, rules_version, patterns_version, \
+     inherited_from_parent, custom_fields, due_at, sla_minutes, sla_breached, \
+     estimate_minutes, actual_minutes, reopen_count, short_id, duplicate_of, effective_priority, escalation_reasons";
+
+/// Largest accepted estimate, 30 working days of 8 hours
+pub const MAX_ESTIMATE_MINUTES: i64 = 30 * 8 * 60;
+
+pub fn validate_estimate_minutes(minutes: i64) -> Result<()> {
+    if !(1..=MAX_ESTIMATE_MINUTES).contains(&minutes) {
+        anyhow::bail!(
+            "estimate_minutes must be between 1 and {} minutes",
+            MAX_ESTIMATE_MINUTES
+        );
+    }
+    Ok(())
+}
+
+/// Filters shared by ticket listings
+#[derive(Debug, Clone, Default)]
+pub struct TicketFilter<'a> {
+    pub project_id: Option<&'a str>,
+    /// open or closed
+    pub status: Option<&'a str>,
+    pub tag: Option<&'a str>,
+    /// Only tickets past their due date that are not closed
+    pub overdue: bool,
+    /// Only tickets reopened at least this many times
+    pub min_reopens: Option<i64>,
+}
+
+impl TicketFilter<'_> {
+    /// Stable description of the filters, for binding pagination cursors to them
+    pub fn fingerprint(&self) -> String {
+        serde_json::json!([
+            self.project_id,
+            self.status,
+            self.tag,
+            self.overdue,
+            self.min_reopens
+        ])
+        .to_string()
+    }
+
+    fn query(&self, select: &str) -> Result<sqlx::QueryBuilder<'static, sqlx::Sqlite>> {
+        // Use QueryBuilder for safe parameterized queries
+        let mut query_builder = sqlx::QueryBuilder::new(select);
+        query_builder.push(" WHERE 1=1");
+        self.push_conditions(&mut query_builder)?;
+        Ok(query_builder)
+    }
+
+    /// Append the filters as `AND ...` conditions on unqualified `tickets` columns
+    pub(crate) fn push_conditions(
+        &self,
+        query_builder: &mut sqlx::QueryBuilder<'_, sqlx::Sqlite>,
+    ) -> Result<()> {
+        // Validate status_filter with explicit check
+        if let Some(status) = self.status {
+            if status != "open" && status != "closed" {
+                return Err(anyhow::anyhow!("Invalid status filter: {}", status));
+            }
+        }
+
+        if let Some(pid) = self.project_id {
+            query_builder.push(" AND project_id = ");
+            query_builder.push_bind(pid.to_string());
+        }
+
+        match self.status {
+            Some("open") => {
+                query_builder.push(" AND closed_at IS NULL");
+            }
+            Some("closed") => {
+                query_builder.push(" AND closed_at IS NOT NULL");
+            }
+            _ => {}
+        }
+
+        if let Some(tag) = self.tag {
+            query_builder.push(
+                " AND ticket_id IN (SELECT ticket_tags.ticket_id FROM ticket_tags
+                   JOIN tags ON tags.id = ticket_tags.tag_id WHERE tags.name = ",
+            );
+            query_builder.push_bind(crate::database::tags::canonical_name(tag)?);
+            query_builder.push(")");
+        }
+
+        if self.overdue {
+            // due_at uses SQLite's datetime format, which orders lexicographically
+            query_builder.push(
+                " AND closed_at IS NULL AND due_at IS NOT NULL AND due_at <= datetime('now')",
+            );
+        }
+
+        if let Some(min_reopens) = self.min_reopens {
+            query_builder.push(" AND reopen_count >= ");
+            query_builder.push_bind(min_reopens);
+        }
+
+        Ok(())
+    }
+}
+
 impl Ticket {
     pub async fn create(pool: &DbPool, req: CreateTicketRequest) -> Result<Ticket> {
+        Ok(Self::create_routed(pool, req, None).await?.0)
+    }
+
+    /// Create a ticket, first routing it to the worker type whose capabilities best match
+    /// `auto_route`'s tags when given. A routed ticket's execution plan is that worker type
+    /// alone; otherwise the requested plan is kept. The decision is made in the ticket's
+    /// creation transaction and, when assigned, noted on its history. The ticket_created
+    /// event is queued in the outbox by the same transaction.
+    pub async fn create_routed(
+        pool: &DbPool,
+        mut req: CreateTicketRequest,
+        auto_route: Option<(&[String], RoutingPolicy)>,
+    ) -> Result<(Ticket, Option<RoutingDecision>)> {
+        // Soft limit, checked before the transaction takes its read snapshot
+        quotas::check(
+            &mut *pool.acquire().await?,
+            &req.project_id,
+            Quota::OpenTickets,
+            1,
+        )
+        .await?;
         let mut tx = pool.begin().await?;
 
+        let decision = match auto_route {
+            Some((tags, policy)) => {
+                Some(routing::route(&mut tx, &req.project_id, tags, policy).await?)
+            }
+            None => None,
+        };
+        if let Some(worker_type) = decision.as_ref().and_then(|d| d.worker_type.as_ref()) {
+            req.execution_plan = vec![worker_type.clone()];
+        }
+
         // Create ticket
         let execution_plan_json = serde_json::to_string(&req.execution_plan)?;
 
         // Get project info for rules/patterns versioning
         let project = crate::database::projects::Project::get_by_name(pool, &req.project_id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Project '{}' not found", req.project_id))?;
+            .ok_or_else(|| AppError::NotFound(format!("Project '{}' not found", req.project_id)))?;
 
         // Determine initial stage from execution plan
         let initial_stage = if req.execution_plan.is_empty() {
@@ -217,18 +434,19 @@ impl Ticket {
             req.execution_plan[0].clone()
         };
 
-        let ticket = sqlx::query_as::<_, Ticket>(
+        sqlx::query(
             r#"
             INSERT INTO tickets (
                 ticket_id, project_id, title, execution_plan, current_stage, state, priority,
                 parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                rules_version, patterns_version, inherited_from_parent
+                rules_version, patterns_version, inherited_from_parent, custom_fields,
+                due_at, sla_minutes, estimate_minutes
+            )
+            VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15,
+                COALESCE(?16, CASE WHEN ?17 IS NOT NULL THEN datetime('now', '+' || ?17 || ' minutes') END),
+                ?17, ?18
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
-            RETURNING ticket_id, project_id, title, execution_plan, current_stage, state, priority,
-                     processing_worker_id, created_at, updated_at, closed_at,
-                     parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                     rules_version, patterns_version, inherited_from_parent
         "#,
         )
         .bind(&req.ticket_id)
@@ -245,44 +463,101 @@ impl Ticket {
         .bind(project.rules_version.unwrap_or(1))
         .bind(project.patterns_version.unwrap_or(1))
         .bind(req.parent_ticket_id.is_some()) // inherited_from_parent
+        .bind(&req.custom_fields)
+        .bind(&req.due_at)
+        .bind(req.sla_minutes)
+        .bind(req.estimate_minutes)
+        .execute(&mut *tx)
+        .await?;
+
+        // RETURNING would not show short_id, which a trigger assigns afterwards
+        let ticket = sqlx::query_as::<_, Ticket>(&format!(
+            "SELECT {} FROM tickets WHERE ticket_id = ?1",
+            TICKET_COLUMNS
+        ))
+        .bind(&req.ticket_id)
         .fetch_one(&mut *tx)
         .await?;
 
         // Add initial comment with description
+        let (description, key_id) = crypto::seal(&req.description)?;
         sqlx::query(
             r#"
-            INSERT INTO comments (ticket_id, worker_type, worker_id, stage_number, content)
-            VALUES (?1, 'coordinator', 'coordinator', 0, ?2)
+            INSERT INTO comments (ticket_id, worker_type, worker_id, stage_number, content, content_key_id)
+            VALUES (?1, 'coordinator', 'coordinator', 0, ?2, ?3)
         "#,
         )
         .bind(&req.ticket_id)
-        .bind(&req.description)
+        .bind(&description)
+        .bind(key_id)
         .execute(&mut *tx)
         .await?;
 
+        if let Some(decision) = decision.as_ref().filter(|d| d.assigned) {
+            sqlx::query(
+                "UPDATE ticket_history SET note = ?2 WHERE ticket_id = ?1 AND change = 'created'",
+            )
+            .bind(&req.ticket_id)
+            .bind(&decision.detail)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        OutboxEvent::write(
+            &mut tx,
+            &ticket.ticket_id,
+            &EventPayload::ticket_created_with_data(
+                &ticket.ticket_id,
+                &ticket.project_id,
+                &ticket.title,
+                &ticket.current_stage,
+            ),
+            Some(&ticket.current_stage),
+            Some(&format!("Ticket '{}' created", ticket.title)),
+        )
+        .await?;
+
         tx.commit().await?;
-        Ok(ticket)
+        Ok((ticket, decision))
     }
 
     pub async fn get_by_id(pool: &DbPool, ticket_id: &str) -> Result<Option<TicketWithComments>> {
-        let ticket = sqlx::query_as::<_, Ticket>(
-            r#"
-            SELECT ticket_id, project_id, title, execution_plan, current_stage, state, priority,
-                   processing_worker_id, created_at, updated_at, closed_at,
-                   parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                   rules_version, patterns_version, inherited_from_parent
-            FROM tickets
-            WHERE ticket_id = ?1
-        "#,
-        )
-        .bind(ticket_id)
-        .fetch_optional(pool)
-        .await?;
+        let ticket = timed!(
+            pool,
+            "Ticket::get_by_id",
+            sqlx::query_as::<_, Ticket>(
+                r#"
+                SELECT ticket_id, project_id, title, execution_plan, current_stage, state, priority,
+                       processing_worker_id, created_at, updated_at, closed_at,
+                       parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
+                       rules_version, patterns_version, inherited_from_parent, custom_fields,
+                       due_at, sla_minutes, sla_breached, estimate_minutes, actual_minutes, reopen_count, short_id, duplicate_of, effective_priority, escalation_reasons
+                FROM tickets
+                WHERE ticket_id = ?1
+            "#,
+            )
+            .bind(ticket_id),
+            fetch_optional
+        )?;
 
         if let Some(ticket) = ticket {
-            let comments =
-                crate::database::comments::Comment::get_by_ticket_id(pool, ticket_id).await?;
-            Ok(Some(TicketWithComments { ticket, comments }))
+            let comments = Comment::get_by_ticket_id(pool, ticket_id).await?;
+            let pipeline = crate::database::pipeline::pipeline_links(pool, &ticket).await?;
+            let tags = crate::database::tags::Tag::names_for_tickets(pool, &[ticket_id])
+                .await?
+                .remove(ticket_id)
+                .unwrap_or_default();
+            let block = TicketBlock::active(pool, ticket_id).await?;
+            Ok(Some(TicketWithComments {
+                ticket,
+                comments,
+                pipeline,
+                tags,
+                summary: None,
+                comment_tree: None,
+                relations: None,
+                block,
+            }))
         } else {
             Ok(None)
         }
@@ -292,43 +567,47 @@ impl Ticket {
         pool: &DbPool,
         project_id: Option<&str>,
         status_filter: Option<&str>,
+        tag: Option<&str>,
     ) -> Result<Vec<Ticket>> {
-        use sqlx::QueryBuilder;
-
-        // Validate status_filter with explicit check
-        if let Some(status) = status_filter {
-            if status != "open" && status != "closed" {
-                return Err(anyhow::anyhow!("Invalid status filter: {}", status));
-            }
-        }
-
-        // Use QueryBuilder for safe parameterized queries
-        let mut query_builder = QueryBuilder::new(
-            "SELECT ticket_id, project_id, title, execution_plan, current_stage, state, priority,
-                    processing_worker_id, created_at, updated_at, closed_at,
-                    parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                    rules_version, patterns_version, inherited_from_parent
-             FROM tickets WHERE 1=1",
-        );
+        let filter = TicketFilter {
+            project_id,
+            status: status_filter,
+            tag,
+            overdue: false,
+            min_reopens: None,
+        };
+        let mut query_builder = filter.query(&format!("SELECT {} FROM tickets", TICKET_COLUMNS))?;
+        query_builder.push(" ORDER BY created_at DESC");
 
-        if let Some(pid) = project_id {
-            query_builder.push(" AND project_id = ");
-            query_builder.push_bind(pid);
-        }
+        let tickets = timed!(
+            pool,
+            "Ticket::list_by_project",
+            query_builder.build_query_as::<Ticket>(),
+            fetch_all
+        )?;
+        Ok(tickets)
+    }
 
-        if let Some(status) = status_filter {
-            match status {
-                "open" => {
-                    query_builder.push(" AND closed_at IS NULL");
-                }
-                "closed" => {
-                    query_builder.push(" AND closed_at IS NOT NULL");
-                }
-                _ => unreachable!("status already validated"),
-            }
+    /// Up to `limit` tickets ordered by `created_at DESC, ticket_id DESC`, starting after the
+    /// `(created_at, ticket_id)` key of the previous page's last ticket
+    pub async fn list_page(
+        pool: &DbPool,
+        filter: &TicketFilter<'_>,
+        after: Option<(&str, &str)>,
+        limit: i64,
+    ) -> Result<Vec<Ticket>> {
+        let mut query_builder = filter.query(&format!("SELECT {} FROM tickets", TICKET_COLUMNS))?;
+        if let Some((created_at, ticket_id)) = after {
+            query_builder.push(" AND (created_at < ");
+            query_builder.push_bind(created_at.to_string());
+            query_builder.push(" OR (created_at = ");
+            query_builder.push_bind(created_at.to_string());
+            query_builder.push(" AND ticket_id < ");
+            query_builder.push_bind(ticket_id.to_string());
+            query_builder.push("))");
         }
-
-        query_builder.push(" ORDER BY created_at DESC");
+        query_builder.push(" ORDER BY created_at DESC, ticket_id DESC LIMIT ");
+        query_builder.push_bind(limit);
 
         let tickets = query_builder
             .build_query_as::<Ticket>()
@@ -337,6 +616,15 @@ impl Ticket {
         Ok(tickets)
     }
 
+    pub async fn count(pool: &DbPool, filter: &TicketFilter<'_>) -> Result<i64> {
+        let count = filter
+            .query("SELECT COUNT(*) FROM tickets")?
+            .build_query_scalar::<i64>()
+            .fetch_one(pool)
+            .await?;
+        Ok(count)
+    }
+
     pub async fn update_stage(
         pool: &DbPool,
         ticket_id: &str,
@@ -350,24 +638,36 @@ impl Ticket {
             RETURNING ticket_id, project_id, title, execution_plan, current_stage, state, priority,
                      processing_worker_id, created_at, updated_at, closed_at,
                      parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                     rules_version, patterns_version, inherited_from_parent
+                     rules_version, patterns_version, inherited_from_parent, custom_fields,
+                     due_at, sla_minutes, sla_breached, estimate_minutes, actual_minutes, reopen_count, short_id, duplicate_of, effective_priority, escalation_reasons
         "#,
         )
         .bind(new_stage)
         .bind(ticket_id)
-        .fetch_optional(pool)
+        .fetch_returned(pool)
         .await?;
 
         Ok(ticket)
     }
 
+    /// Close a ticket with `status` as its resolution, queueing ticket_closed in the outbox
     pub async fn close_ticket(
         pool: &DbPool,
         ticket_id: &str,
         status: &str,
     ) -> Result<Option<Ticket>> {
         let mut tx = pool.begin().await?;
+        let ticket = Self::close_in(&mut tx, ticket_id, status).await?;
+        tx.commit().await?;
+        Ok(ticket)
+    }
 
+    /// `close_ticket` within the caller's transaction
+    pub async fn close_in(
+        conn: &mut SqliteConnection,
+        ticket_id: &str,
+        status: &str,
+    ) -> Result<Option<Ticket>> {
         // Determine dependency_status based on completion type
         // - Completed tickets: 'ready' (dependents can proceed)
         // - Stopped tickets: 'blocked' (dependents should not proceed)
@@ -387,17 +687,18 @@ impl Ticket {
             RETURNING ticket_id, project_id, title, execution_plan, current_stage, state, priority,
                      processing_worker_id, created_at, updated_at, closed_at,
                      parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                     rules_version, patterns_version, inherited_from_parent
+                     rules_version, patterns_version, inherited_from_parent, custom_fields,
+                     due_at, sla_minutes, sla_breached, estimate_minutes, actual_minutes, reopen_count, short_id, duplicate_of, effective_priority, escalation_reasons
         "#,
         )
         .bind(status)
         .bind(TicketState::Closed.as_sql_value())
         .bind(ticket_id)
         .bind(dep_status)
-        .fetch_optional(&mut *tx)
+        .fetch_returned(&mut *conn)
         .await?;
 
-        if let Some(ref _ticket) = ticket {
+        if let Some(ref closed) = ticket {
             // Add closing comment
             let closing_message = match status {
                 "Completed" => "Ticket completed successfully by coordinator.",
@@ -413,17 +714,35 @@ impl Ticket {
             )
             .bind(ticket_id)
             .bind(closing_message)
-            .execute(&mut *tx)
+            .execute(&mut *conn)
+            .await?;
+
+            OutboxEvent::write(
+                conn,
+                ticket_id,
+                &EventPayload::ticket_closed(ticket_id, &closed.project_id),
+                None,
+                Some(&format!("Ticket closed with resolution: {}", status)),
+            )
             .await?;
         }
 
-        tx.commit().await?;
         Ok(ticket)
     }
 
     pub async fn place_on_hold(pool: &DbPool, ticket_id: &str, reason: &str) -> Result<()> {
         let mut tx = pool.begin().await?;
+        Self::place_on_hold_in(&mut tx, ticket_id, reason).await?;
+        tx.commit().await?;
+        Ok(())
+    }
 
+    /// `place_on_hold` within the caller's transaction
+    pub async fn place_on_hold_in(
+        conn: &mut SqliteConnection,
+        ticket_id: &str,
+        reason: &str,
+    ) -> Result<()> {
         // Update ticket state to on_hold and release processing worker
         sqlx::query(
             r#"
@@ -434,25 +753,34 @@ impl Ticket {
         )
         .bind(TicketState::OnHold.as_sql_value())
         .bind(ticket_id)
-        .execute(&mut *tx)
+        .execute(&mut *conn)
         .await?;
 
         // Add comment explaining why ticket is on hold
+        let (reason, key_id) = crypto::seal(reason)?;
         sqlx::query(
             r#"
-            INSERT INTO comments (ticket_id, worker_type, worker_id, stage_number, content)
-            VALUES (?1, 'system', 'system', 999, ?2)
+            INSERT INTO comments (ticket_id, worker_type, worker_id, stage_number, content, content_key_id)
+            VALUES (?1, 'system', 'system', 999, ?2, ?3)
             "#,
         )
         .bind(ticket_id)
-        .bind(reason)
-        .execute(&mut *tx)
+        .bind(&reason)
+        .bind(key_id)
+        .execute(&mut *conn)
         .await?;
 
-        tx.commit().await?;
         Ok(())
     }
 
+    /// Custom field values, empty when there are none
+    pub fn custom_field_map(&self) -> Map<String, Value> {
+        match &self.custom_fields {
+            Some(CustomFields(Value::Object(fields))) => fields.clone(),
+            _ => Map::new(),
+        }
+    }
+
     pub fn get_execution_plan(&self) -> Result<Vec<String>> {
         Ok(serde_json::from_str(&self.execution_plan)?)
     }
@@ -483,20 +811,37 @@ impl Ticket {
         ticket_id: &str,
         state: &str,
     ) -> Result<Option<Ticket>> {
-        let ticket = sqlx::query_as::<_, Ticket>(
+        Self::update_state_in(&mut *pool.acquire().await?, ticket_id, state).await
+    }
+
+    /// `update_state` within the caller's transaction
+    pub async fn update_state_in(
+        conn: &mut SqliteConnection,
+        ticket_id: &str,
+        state: &str,
+    ) -> Result<Option<Ticket>> {
+        let updated = sqlx::query(
             r#"
             UPDATE tickets
             SET state = ?1, updated_at = datetime('now')
             WHERE ticket_id = ?2
-            RETURNING ticket_id, project_id, title, execution_plan, current_stage, state, priority,
-                     processing_worker_id, created_at, updated_at, closed_at,
-                     parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                     rules_version, patterns_version, inherited_from_parent
         "#,
         )
         .bind(state)
         .bind(ticket_id)
-        .fetch_optional(pool)
+        .execute(&mut *conn)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        // RETURNING would not show reopen_count, which a trigger updates afterwards
+        let ticket = sqlx::query_as::<_, Ticket>(&format!(
+            "SELECT {} FROM tickets WHERE ticket_id = ?1",
+            TICKET_COLUMNS
+        ))
+        .bind(ticket_id)
+        .fetch_optional(&mut *conn)
         .await?;
 
         Ok(ticket)
@@ -515,17 +860,60 @@ impl Ticket {
             RETURNING ticket_id, project_id, title, execution_plan, current_stage, state, priority,
                      processing_worker_id, created_at, updated_at, closed_at,
                      parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                     rules_version, patterns_version, inherited_from_parent
+                     rules_version, patterns_version, inherited_from_parent, custom_fields,
+                     due_at, sla_minutes, sla_breached, estimate_minutes, actual_minutes, reopen_count, short_id, duplicate_of, effective_priority, escalation_reasons
         "#,
         )
         .bind(priority)
         .bind(ticket_id)
-        .fetch_optional(pool)
+        .fetch_returned(pool)
         .await?;
 
         Ok(ticket)
     }
 
+    /// Set or clear the estimate, queueing `ticket_updated` with it; None when the ticket
+    /// doesn't exist
+    pub async fn update_estimate(
+        pool: &DbPool,
+        ticket_id: &str,
+        estimate_minutes: Option<i64>,
+    ) -> Result<Option<Ticket>> {
+        if let Some(minutes) = estimate_minutes {
+            validate_estimate_minutes(minutes)?;
+        }
+        let mut tx = pool.begin().await?;
+        let ticket = sqlx::query_as::<_, Ticket>(&format!(
+            r#"
+            UPDATE tickets
+            SET estimate_minutes = ?1, updated_at = datetime('now')
+            WHERE ticket_id = ?2
+            RETURNING {}
+            "#,
+            TICKET_COLUMNS
+        ))
+        .bind(estimate_minutes)
+        .bind(ticket_id)
+        .fetch_returned(&mut *tx)
+        .await?;
+
+        if let Some(ticket) = &ticket {
+            OutboxEvent::write(
+                &mut tx,
+                ticket_id,
+                &EventPayload::ticket_updated(ticket_id, &ticket.project_id, "estimate_changed"),
+                None,
+                Some(&match estimate_minutes {
+                    Some(minutes) => format!("Estimate set to {} minutes", minutes),
+                    None => "Estimate cleared".to_string(),
+                }),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(ticket)
+    }
+
     pub async fn get_by_stage_unclaimed(
         pool: &DbPool,
         project_id: &str,
@@ -536,13 +924,22 @@ impl Ticket {
             SELECT ticket_id, project_id, title, execution_plan, current_stage, state, priority,
                    processing_worker_id, created_at, updated_at, closed_at,
                    parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                   rules_version, patterns_version, inherited_from_parent
+                   rules_version, patterns_version, inherited_from_parent, custom_fields,
+                   due_at, sla_minutes, sla_breached, estimate_minutes, actual_minutes, reopen_count, short_id, duplicate_of, effective_priority, escalation_reasons
             FROM tickets
             WHERE project_id = ?1
               AND current_stage = ?2
               AND processing_worker_id IS NULL
               AND state = 'open'
-            ORDER BY priority DESC, created_at ASC
+            ORDER BY
+                CASE effective_priority
+                    WHEN 'urgent' THEN 1
+                    WHEN 'high' THEN 2
+                    WHEN 'medium' THEN 3
+                    WHEN 'low' THEN 4
+                    ELSE 5
+                END,
+                created_at ASC
         "#,
         )
         .bind(project_id)
@@ -586,8 +983,9 @@ impl Ticket {
             SELECT t.ticket_id, t.project_id, t.title, t.execution_plan, t.current_stage,
                    t.state, t.priority, t.processing_worker_id, t.created_at, t.updated_at, t.closed_at,
                    t.parent_ticket_id, t.dependency_status, t.created_by_worker_id, t.ticket_type,
-                   t.rules_version, t.patterns_version, t.inherited_from_parent,
-                   p.rules, p.patterns
+                   t.rules_version, t.patterns_version, t.inherited_from_parent, t.custom_fields,
+                   t.due_at, t.sla_minutes, t.sla_breached, t.estimate_minutes, t.actual_minutes, t.reopen_count, t.short_id,
+                   t.duplicate_of, t.effective_priority, t.escalation_reasons, p.rules, p.patterns
             FROM tickets t
             LEFT JOIN projects p ON t.project_id = p.repository_name
             WHERE t.ticket_id = ?1
@@ -600,6 +998,7 @@ impl Ticket {
         if let Some(row) = result {
             let ticket = Ticket {
                 ticket_id: row.get("ticket_id"),
+                short_id: row.get("short_id"),
                 project_id: row.get("project_id"),
                 title: row.get("title"),
                 execution_plan: row.get("execution_plan"),
@@ -617,6 +1016,16 @@ impl Ticket {
                 rules_version: row.get("rules_version"),
                 patterns_version: row.get("patterns_version"),
                 inherited_from_parent: row.get("inherited_from_parent"),
+                custom_fields: row.get("custom_fields"),
+                due_at: row.get("due_at"),
+                sla_minutes: row.get("sla_minutes"),
+                sla_breached: row.get("sla_breached"),
+                estimate_minutes: row.get("estimate_minutes"),
+                actual_minutes: row.get("actual_minutes"),
+                reopen_count: row.get("reopen_count"),
+                duplicate_of: row.get("duplicate_of"),
+                effective_priority: row.get("effective_priority"),
+                escalation_reasons: row.get("escalation_reasons"),
             };
 
             let ticket_with_info = TicketWithProjectInfo {
@@ -650,71 +1059,83 @@ impl Ticket {
 
     /// Get all child tickets of a parent ticket
     pub async fn get_children(pool: &DbPool, parent_ticket_id: &str) -> Result<Vec<Ticket>> {
-        let tickets = sqlx::query_as::<_, Ticket>(
-            r#"
-            SELECT ticket_id, project_id, title, execution_plan, current_stage, state, priority,
-                   processing_worker_id, created_at, updated_at, closed_at,
-                   parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                   rules_version, patterns_version, inherited_from_parent
-            FROM tickets
-            WHERE parent_ticket_id = ?1
-            ORDER BY created_at ASC
-        "#,
-        )
-        .bind(parent_ticket_id)
-        .fetch_all(pool)
-        .await?;
-
-        Ok(tickets)
-    }
-
-    /// Get all tickets that are ready to process (dependency_status = 'ready' and state = 'open')
-    pub async fn get_ready_tickets(pool: &DbPool, project_id: Option<&str>) -> Result<Vec<Ticket>> {
-        let tickets = if let Some(project_id) = project_id {
+        let tickets = timed!(
+            pool,
+            "Ticket::get_children",
             sqlx::query_as::<_, Ticket>(
                 r#"
                 SELECT ticket_id, project_id, title, execution_plan, current_stage, state, priority,
                        processing_worker_id, created_at, updated_at, closed_at,
                        parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                       rules_version, patterns_version, inherited_from_parent
+                       rules_version, patterns_version, inherited_from_parent, custom_fields,
+                       due_at, sla_minutes, sla_breached, estimate_minutes, actual_minutes, reopen_count, short_id, duplicate_of, effective_priority, escalation_reasons
                 FROM tickets
-                WHERE project_id = ?1 AND dependency_status = 'ready' AND state = 'open'
-                ORDER BY
-                    CASE priority
-                        WHEN 'urgent' THEN 1
-                        WHEN 'high' THEN 2
-                        WHEN 'medium' THEN 3
-                        WHEN 'low' THEN 4
-                        ELSE 5
-                    END,
-                    created_at ASC
+                WHERE parent_ticket_id = ?1
+                ORDER BY created_at ASC
             "#,
             )
-            .bind(project_id)
-            .fetch_all(pool)
-            .await?
+            .bind(parent_ticket_id),
+            fetch_all
+        )?;
+
+        Ok(tickets)
+    }
+
+    /// Get all tickets that are ready to process (dependency_status = 'ready' and state = 'open')
+    pub async fn get_ready_tickets(pool: &DbPool, project_id: Option<&str>) -> Result<Vec<Ticket>> {
+        let tickets = if let Some(project_id) = project_id {
+            timed!(
+                pool,
+                "Ticket::get_ready_tickets",
+                sqlx::query_as::<_, Ticket>(
+                    r#"
+                    SELECT ticket_id, project_id, title, execution_plan, current_stage, state, priority,
+                           processing_worker_id, created_at, updated_at, closed_at,
+                           parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
+                           rules_version, patterns_version, inherited_from_parent, custom_fields,
+                           due_at, sla_minutes, sla_breached, estimate_minutes, actual_minutes, reopen_count, short_id, duplicate_of, effective_priority, escalation_reasons
+                    FROM tickets
+                    WHERE project_id = ?1 AND dependency_status = 'ready' AND state = 'open'
+                    ORDER BY
+                        CASE effective_priority
+                            WHEN 'urgent' THEN 1
+                            WHEN 'high' THEN 2
+                            WHEN 'medium' THEN 3
+                            WHEN 'low' THEN 4
+                            ELSE 5
+                        END,
+                        created_at ASC
+                "#,
+                )
+                .bind(project_id),
+                fetch_all
+            )?
         } else {
-            sqlx::query_as::<_, Ticket>(
-                r#"
-                SELECT ticket_id, project_id, title, execution_plan, current_stage, state, priority,
-                       processing_worker_id, created_at, updated_at, closed_at,
-                       parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                       rules_version, patterns_version, inherited_from_parent
-                FROM tickets
-                WHERE dependency_status = 'ready' AND state = 'open'
-                ORDER BY
-                    CASE priority
-                        WHEN 'urgent' THEN 1
-                        WHEN 'high' THEN 2
-                        WHEN 'medium' THEN 3
-                        WHEN 'low' THEN 4
-                        ELSE 5
-                    END,
-                    created_at ASC
-            "#,
-            )
-            .fetch_all(pool)
-            .await?
+            timed!(
+                pool,
+                "Ticket::get_ready_tickets",
+                sqlx::query_as::<_, Ticket>(
+                    r#"
+                    SELECT ticket_id, project_id, title, execution_plan, current_stage, state, priority,
+                           processing_worker_id, created_at, updated_at, closed_at,
+                           parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
+                           rules_version, patterns_version, inherited_from_parent, custom_fields,
+                           due_at, sla_minutes, sla_breached, estimate_minutes, actual_minutes, reopen_count, short_id, duplicate_of, effective_priority, escalation_reasons
+                    FROM tickets
+                    WHERE dependency_status = 'ready' AND state = 'open'
+                    ORDER BY
+                        CASE effective_priority
+                            WHEN 'urgent' THEN 1
+                            WHEN 'high' THEN 2
+                            WHEN 'medium' THEN 3
+                            WHEN 'low' THEN 4
+                            ELSE 5
+                        END,
+                        created_at ASC
+                "#,
+                ),
+                fetch_all
+            )?
         };
 
         Ok(tickets)
@@ -731,7 +1152,8 @@ impl Ticket {
                 SELECT ticket_id, project_id, title, execution_plan, current_stage, state, priority,
                        processing_worker_id, created_at, updated_at, closed_at,
                        parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                       rules_version, patterns_version, inherited_from_parent
+                       rules_version, patterns_version, inherited_from_parent, custom_fields,
+                       due_at, sla_minutes, sla_breached, estimate_minutes, actual_minutes, reopen_count, short_id, duplicate_of, effective_priority, escalation_reasons
                 FROM tickets
                 WHERE project_id = ?1 AND dependency_status = 'blocked' AND state = 'open'
                 ORDER BY created_at ASC
@@ -746,7 +1168,8 @@ impl Ticket {
                 SELECT ticket_id, project_id, title, execution_plan, current_stage, state, priority,
                        processing_worker_id, created_at, updated_at, closed_at,
                        parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                       rules_version, patterns_version, inherited_from_parent
+                       rules_version, patterns_version, inherited_from_parent, custom_fields,
+                       due_at, sla_minutes, sla_breached, estimate_minutes, actual_minutes, reopen_count, short_id, duplicate_of, effective_priority, escalation_reasons
                 FROM tickets
                 WHERE dependency_status = 'blocked' AND state = 'open'
                 ORDER BY created_at ASC
@@ -766,11 +1189,12 @@ impl Ticket {
             SELECT ticket_id, project_id, title, execution_plan, current_stage, state, priority,
                    processing_worker_id, created_at, updated_at, closed_at,
                    parent_ticket_id, dependency_status, created_by_worker_id, ticket_type,
-                   rules_version, patterns_version, inherited_from_parent
+                   rules_version, patterns_version, inherited_from_parent, custom_fields,
+                   due_at, sla_minutes, sla_breached, estimate_minutes, actual_minutes, reopen_count, short_id, duplicate_of, effective_priority, escalation_reasons
             FROM tickets
             WHERE current_stage = ?1 AND state = 'open'
             ORDER BY
-                CASE priority
+                CASE effective_priority
                     WHEN 'urgent' THEN 1
                     WHEN 'high' THEN 2
                     WHEN 'medium' THEN 3
@@ -833,3 +1257,53 @@ impl Ticket {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    #[tokio::test]
+    async fn test_list_page_is_stable_across_equal_timestamps() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("paging").create().await.unwrap();
+        for (id, created_at) in [
+            ("P-1", "2026-01-01 00:00:00"),
+            ("P-2", "2026-01-02 00:00:00"),
+            ("P-3", "2026-01-02 00:00:00"),
+            ("P-4", "2026-01-03 00:00:00"),
+        ] {
+            fx.ticket(&project, id)
+                .id(id)
+                .created_at(created_at)
+                .create()
+                .await
+                .unwrap();
+        }
+
+        let filter = TicketFilter {
+            project_id: Some("paging"),
+            ..Default::default()
+        };
+        let mut seen = Vec::new();
+        let mut after: Option<(String, String)> = None;
+        loop {
+            let page = Ticket::list_page(
+                &pool,
+                &filter,
+                after.as_ref().map(|(c, i)| (c.as_str(), i.as_str())),
+                2,
+            )
+            .await
+            .unwrap();
+            let Some(last) = page.last() else { break };
+            after = Some((last.created_at.clone(), last.ticket_id.clone()));
+            seen.extend(page.into_iter().map(|t| t.ticket_id));
+        }
+        assert_eq!(seen, ["P-4", "P-3", "P-2", "P-1"]);
+        assert_eq!(Ticket::count(&pool, &filter).await.unwrap(), 4);
+
+        pool.close().await;
+    }
+}
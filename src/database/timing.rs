@@ -0,0 +1,332 @@
+//! Latency of repository queries. Calls wrapped in [`timed!`] land in a histogram per
+//! repository method, served at `/api/internal/query-stats`, and calls slower than
+//! `--slow-query-ms` are logged with how busy the pool was, so waiting for a connection can
+//! be told apart from a slow query. With `--explain-slow-queries` the query plan of each
+//! slow statement is logged once.
+
+use dashmap::{DashMap, DashSet};
+use serde::Serialize;
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        LazyLock,
+    },
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+use utoipa::ToSchema;
+
+use super::DbPool;
+
+pub const DEFAULT_SLOW_QUERY_MS: u64 = 100;
+
+/// Upper bounds of the histogram buckets in milliseconds; slower calls go in a last,
+/// unbounded bucket
+const BUCKETS_MS: [u64; 9] = [1, 5, 10, 25, 50, 100, 250, 500, 1000];
+
+static SLOW_QUERY_MS: AtomicU64 = AtomicU64::new(DEFAULT_SLOW_QUERY_MS);
+static EXPLAIN: AtomicBool = AtomicBool::new(false);
+
+static HISTOGRAMS: LazyLock<DashMap<&'static str, Histogram>> = LazyLock::new(DashMap::new);
+/// Statements whose plan was logged already
+static EXPLAINED: LazyLock<DashSet<String>> = LazyLock::new(DashSet::new);
+
+/// Run `$query` with `$fetch` (`fetch_one`, `fetch_all`, `execute`, ...) against `$pool`,
+/// timed as the repository method `$name`
+macro_rules! timed {
+    ($pool:expr, $name:literal, $query:expr, $fetch:ident) => {{
+        let query = $query;
+        let sql = sqlx::Execute::sql(&query);
+        $crate::database::timing::time($pool, $name, sql, query.$fetch($pool)).await
+    }};
+}
+pub(crate) use timed;
+
+/// Slow query threshold in milliseconds (0 disables the warning) and whether slow
+/// statements have their query plan logged
+pub fn configure(slow_query_ms: u64, explain: bool) {
+    SLOW_QUERY_MS.store(slow_query_ms, Ordering::SeqCst);
+    EXPLAIN.store(explain, Ordering::SeqCst);
+}
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [AtomicU64; BUCKETS_MS.len() + 1],
+    count: AtomicU64,
+    slow: AtomicU64,
+    total_micros: AtomicU64,
+    max_micros: AtomicU64,
+}
+
+impl Histogram {
+    fn record(&self, elapsed: Duration, slow: bool) {
+        let ms = elapsed.as_millis() as u64;
+        let bucket = BUCKETS_MS
+            .iter()
+            .position(|&bound| ms <= bound)
+            .unwrap_or(BUCKETS_MS.len());
+        let micros = elapsed.as_micros() as u64;
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_micros.fetch_add(micros, Ordering::Relaxed);
+        self.max_micros.fetch_max(micros, Ordering::Relaxed);
+        if slow {
+            self.slow.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Connections of the pool in use, open and allowed
+#[derive(Debug, Clone, Copy, Serialize, ToSchema)]
+pub struct PoolUsage {
+    pub in_use: u32,
+    pub size: u32,
+    pub max: u32,
+}
+
+impl PoolUsage {
+    pub fn of(pool: &DbPool) -> PoolUsage {
+        let size = pool.size();
+        PoolUsage {
+            in_use: size.saturating_sub(pool.num_idle() as u32),
+            size,
+            max: pool.options().get_max_connections(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LatencyBucket {
+    /// Calls that took at most this many milliseconds and more than the previous bucket;
+    /// null for the last bucket
+    pub le_ms: Option<u64>,
+    pub count: u64,
+}
+
+/// Latency of one repository method since startup
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QueryStats {
+    pub query: String,
+    pub count: u64,
+    /// Calls over the slow query threshold
+    pub slow: u64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    pub buckets: Vec<LatencyBucket>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QueryTimingReport {
+    /// 0 when slow queries are not logged
+    pub slow_query_ms: u64,
+    pub explain_slow_queries: bool,
+    pub pool: PoolUsage,
+    /// Slowest on average first
+    pub queries: Vec<QueryStats>,
+}
+
+pub fn report(pool: &DbPool) -> QueryTimingReport {
+    let mut queries: Vec<QueryStats> = HISTOGRAMS
+        .iter()
+        .map(|entry| {
+            let histogram = entry.value();
+            let count = histogram.count.load(Ordering::Relaxed);
+            let total_ms = histogram.total_micros.load(Ordering::Relaxed) as f64 / 1000.0;
+            QueryStats {
+                query: entry.key().to_string(),
+                count,
+                slow: histogram.slow.load(Ordering::Relaxed),
+                mean_ms: if count == 0 {
+                    0.0
+                } else {
+                    total_ms / count as f64
+                },
+                max_ms: histogram.max_micros.load(Ordering::Relaxed) as f64 / 1000.0,
+                buckets: histogram
+                    .buckets
+                    .iter()
+                    .enumerate()
+                    .map(|(i, bucket)| LatencyBucket {
+                        le_ms: BUCKETS_MS.get(i).copied(),
+                        count: bucket.load(Ordering::Relaxed),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    queries.sort_by(|a, b| b.mean_ms.total_cmp(&a.mean_ms));
+    QueryTimingReport {
+        slow_query_ms: SLOW_QUERY_MS.load(Ordering::SeqCst),
+        explain_slow_queries: EXPLAIN.load(Ordering::SeqCst),
+        pool: PoolUsage::of(pool),
+        queries,
+    }
+}
+
+/// Await `query`, the statement `sql` of the repository method `name`, and record how long
+/// it took; use through [`timed!`]
+pub async fn time<T>(
+    pool: &DbPool,
+    name: &'static str,
+    sql: &str,
+    query: impl Future<Output = T>,
+) -> T {
+    let started = Instant::now();
+    let result = query.await;
+    let elapsed = started.elapsed();
+
+    let threshold_ms = SLOW_QUERY_MS.load(Ordering::Relaxed);
+    let slow = threshold_ms > 0 && elapsed >= Duration::from_millis(threshold_ms);
+    HISTOGRAMS.entry(name).or_default().record(elapsed, slow);
+    if slow {
+        let usage = PoolUsage::of(pool);
+        warn!(
+            query = name,
+            elapsed_ms = elapsed.as_millis() as u64,
+            threshold_ms,
+            pool_in_use = usage.in_use,
+            pool_size = usage.size,
+            pool_max = usage.max,
+            "Slow query {} took {:?} ({} of {} connections in use)",
+            name,
+            elapsed,
+            usage.in_use,
+            usage.max
+        );
+        if EXPLAIN.load(Ordering::Relaxed) && EXPLAINED.insert(sql.to_string()) {
+            let (pool, sql) = (pool.clone(), sql.to_string());
+            tokio::spawn(async move { explain(&pool, name, &sql).await });
+        }
+    }
+    result
+}
+
+/// Log the query plan of `sql`; parameters are left unbound, which SQLite plans as NULL
+async fn explain(pool: &DbPool, name: &str, sql: &str) {
+    let plan: Result<Vec<(i64, i64, i64, String)>, _> =
+        sqlx::query_as(&format!("EXPLAIN QUERY PLAN {}", sql))
+            .fetch_all(pool)
+            .await;
+    match plan {
+        Ok(rows) => {
+            let plan: Vec<String> = rows.into_iter().map(|(_, _, _, detail)| detail).collect();
+            warn!(
+                query = name,
+                plan = plan.join("; "),
+                "Query plan of slow query {}: {}",
+                name,
+                plan.join("; ")
+            );
+        }
+        Err(e) => debug!("Failed to explain slow query {}: {}", name, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::memory_pool;
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+    use tracing_subscriber::fmt::MakeWriter;
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Captured {
+        type Writer = Captured;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    fn stats_of(pool: &DbPool, name: &str) -> QueryStats {
+        report(pool)
+            .queries
+            .into_iter()
+            .find(|q| q.query == name)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_histogram_and_slow_query_log() {
+        let pool = memory_pool().await.unwrap();
+        let logs = Captured::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let one: i64 = timed!(
+            &pool,
+            "timing_test::fast",
+            sqlx::query_scalar("SELECT 1"),
+            fetch_one
+        )
+        .unwrap();
+        assert_eq!(one, 1);
+        let fast = stats_of(&pool, "timing_test::fast");
+        assert_eq!(fast.count, 1);
+        assert_eq!(fast.slow, 0);
+        assert_eq!(fast.buckets.len(), BUCKETS_MS.len() + 1);
+        assert_eq!(fast.buckets.iter().map(|b| b.count).sum::<u64>(), 1);
+
+        // A query held up past the default threshold is logged with the pool usage
+        let delayed = async {
+            tokio::time::sleep(Duration::from_millis(DEFAULT_SLOW_QUERY_MS + 20)).await;
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM tickets WHERE ticket_id = ?1")
+                .bind("missing")
+                .fetch_one(&pool)
+                .await
+        };
+        let count = time(
+            &pool,
+            "timing_test::slow",
+            "SELECT COUNT(*) FROM tickets WHERE ticket_id = ?1",
+            delayed,
+        )
+        .await
+        .unwrap();
+        assert_eq!(count, 0);
+        let slow = stats_of(&pool, "timing_test::slow");
+        assert_eq!((slow.count, slow.slow), (1, 1));
+        assert!(slow.max_ms >= DEFAULT_SLOW_QUERY_MS as f64);
+        assert_eq!(slow.buckets[5].count + slow.buckets[6].count, 1);
+
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(
+            output.contains("Slow query timing_test::slow"),
+            "{}",
+            output
+        );
+        assert!(output.contains("pool_in_use="), "{}", output);
+        assert!(!output.contains("timing_test::fast"), "{}", output);
+
+        // The plan of a statement with unbound parameters can still be read
+        explain(
+            &pool,
+            "timing_test::slow",
+            "SELECT COUNT(*) FROM tickets WHERE ticket_id = ?1",
+        )
+        .await;
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("Query plan of slow query"), "{}", output);
+        pool.close().await;
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tracing::warn;
+
+use super::DbPool;
+
+/// Result of the last update check on a release channel
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UpdateCheck {
+    pub channel: String,
+    pub checked_at: String,
+    pub latest_version: Option<String>,
+    pub release_url: Option<String>,
+    /// Seconds since the check
+    pub age_secs: i64,
+}
+
+impl UpdateCheck {
+    pub async fn get(pool: &DbPool, channel: &str) -> Result<Option<UpdateCheck>> {
+        let check = sqlx::query_as::<_, UpdateCheck>(
+            r#"
+            SELECT channel, checked_at, latest_version, release_url,
+                   CAST((julianday('now') - julianday(checked_at)) * 86400 AS INTEGER) AS age_secs
+            FROM update_checks
+            WHERE channel = ?1
+            "#,
+        )
+        .bind(channel)
+        .fetch_optional(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to read cached update check: {:?}", e))?;
+
+        Ok(check)
+    }
+
+    pub async fn record(
+        pool: &DbPool,
+        channel: &str,
+        latest_version: Option<&str>,
+        release_url: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO update_checks (channel, checked_at, latest_version, release_url)
+            VALUES (?1, datetime('now'), ?2, ?3)
+            ON CONFLICT(channel) DO UPDATE SET
+                checked_at = excluded.checked_at,
+                latest_version = excluded.latest_version,
+                release_url = excluded.release_url
+            "#,
+        )
+        .bind(channel)
+        .bind(latest_version)
+        .bind(release_url)
+        .execute(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to cache update check: {:?}", e))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,329 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection};
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use super::{returning::FetchReturning, DbPool};
+
+const WEBHOOK_COLUMNS: &str =
+    "id, url, secret, enabled, event_types, max_per_minute, created_at, updated_at";
+
+const DELIVERY_COLUMNS: &str = "id, webhook_id, event_type, payload, status, attempts, \
+     response_status, last_error, next_attempt_at, created_at, delivered_at";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Webhook {
+    pub id: i64,
+    pub url: String,
+    /// Key of the payload signatures; only returned when the webhook is created
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub enabled: bool,
+    pub event_types: String, // JSON array, empty for every webhook event
+    /// Delivery attempts per minute, 0 for unlimited
+    pub max_per_minute: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Generated when not given
+    pub secret: Option<String>,
+    pub enabled: Option<bool>,
+    /// Event types to deliver (default: every webhook event)
+    pub event_types: Option<Vec<String>>,
+    pub max_per_minute: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    pub enabled: Option<bool>,
+    pub event_types: Option<Vec<String>>,
+    pub max_per_minute: Option<i64>,
+}
+
+impl UpdateWebhookRequest {
+    /// The full webhook definition after applying this update
+    pub fn apply_to(self, webhook: &Webhook) -> Result<CreateWebhookRequest> {
+        let event_types = match self.event_types {
+            Some(types) => types,
+            None => webhook.event_types()?,
+        };
+        Ok(CreateWebhookRequest {
+            url: self.url.unwrap_or_else(|| webhook.url.clone()),
+            secret: Some(self.secret.unwrap_or_else(|| webhook.secret.clone())),
+            enabled: Some(self.enabled.unwrap_or(webhook.enabled)),
+            event_types: Some(event_types),
+            max_per_minute: Some(self.max_per_minute.unwrap_or(webhook.max_per_minute)),
+        })
+    }
+}
+
+impl Webhook {
+    pub async fn create(
+        pool: &DbPool,
+        req: &CreateWebhookRequest,
+        secret: &str,
+    ) -> Result<Webhook> {
+        let webhook = sqlx::query_as::<_, Webhook>(&format!(
+            r#"
+            INSERT INTO webhooks (url, secret, enabled, event_types, max_per_minute)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            RETURNING {}
+            "#,
+            WEBHOOK_COLUMNS
+        ))
+        .bind(&req.url)
+        .bind(secret)
+        .bind(req.enabled.unwrap_or(true))
+        .bind(serde_json::to_string(
+            req.event_types.as_deref().unwrap_or_default(),
+        )?)
+        .bind(req.max_per_minute.unwrap_or(60))
+        .fetch_returned_one(pool)
+        .await
+        .inspect_err(|e| error!("Failed to create webhook for '{}': {:?}", req.url, e))?;
+
+        Ok(webhook)
+    }
+
+    pub async fn get(pool: &DbPool, id: i64) -> Result<Option<Webhook>> {
+        let webhook = sqlx::query_as::<_, Webhook>(&format!(
+            "SELECT {} FROM webhooks WHERE id = ?1",
+            WEBHOOK_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to fetch webhook {}: {:?}", id, e))?;
+
+        Ok(webhook)
+    }
+
+    pub async fn list(pool: &DbPool) -> Result<Vec<Webhook>> {
+        let webhooks = sqlx::query_as::<_, Webhook>(&format!(
+            "SELECT {} FROM webhooks ORDER BY id ASC",
+            WEBHOOK_COLUMNS
+        ))
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to list webhooks: {:?}", e))?;
+
+        Ok(webhooks)
+    }
+
+    /// Replace the definition of a webhook; its deliveries are kept
+    pub async fn update(
+        pool: &DbPool,
+        id: i64,
+        req: &CreateWebhookRequest,
+    ) -> Result<Option<Webhook>> {
+        let webhook = sqlx::query_as::<_, Webhook>(&format!(
+            r#"
+            UPDATE webhooks
+            SET url = ?2, secret = COALESCE(?3, secret), enabled = ?4, event_types = ?5,
+                max_per_minute = ?6, updated_at = datetime('now')
+            WHERE id = ?1
+            RETURNING {}
+            "#,
+            WEBHOOK_COLUMNS
+        ))
+        .bind(id)
+        .bind(&req.url)
+        .bind(&req.secret)
+        .bind(req.enabled.unwrap_or(true))
+        .bind(serde_json::to_string(
+            req.event_types.as_deref().unwrap_or_default(),
+        )?)
+        .bind(req.max_per_minute.unwrap_or(60))
+        .fetch_returned(pool)
+        .await
+        .inspect_err(|e| error!("Failed to update webhook {}: {:?}", id, e))?;
+
+        Ok(webhook)
+    }
+
+    /// Delete a webhook along with its deliveries
+    pub async fn delete(pool: &DbPool, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM webhooks WHERE id = ?1")
+            .bind(id)
+            .execute(pool)
+            .await
+            .inspect_err(|e| error!("Failed to delete webhook {}: {:?}", id, e))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Enabled webhooks whose filter accepts `event_type`
+    pub async fn subscribed_to(
+        conn: &mut SqliteConnection,
+        event_type: &str,
+    ) -> Result<Vec<Webhook>> {
+        let webhooks = sqlx::query_as::<_, Webhook>(&format!(
+            r#"
+            SELECT {} FROM webhooks
+            WHERE enabled = 1
+              AND (json_array_length(event_types) = 0
+                   OR EXISTS (SELECT 1 FROM json_each(event_types) WHERE value = ?1))
+            ORDER BY id ASC
+            "#,
+            WEBHOOK_COLUMNS
+        ))
+        .bind(event_type)
+        .fetch_all(&mut *conn)
+        .await
+        .inspect_err(|e| warn!("Failed to find webhooks for '{}': {:?}", event_type, e))?;
+
+        Ok(webhooks)
+    }
+
+    pub fn event_types(&self) -> Result<Vec<String>> {
+        Ok(serde_json::from_str(&self.event_types)?)
+    }
+}
+
+/// One event queued for, or sent to, one webhook
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub event_type: String,
+    pub payload: String, // JSON, sent as is
+    /// pending, delivered or dead
+    pub status: String,
+    pub attempts: i64,
+    pub response_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: String,
+    pub created_at: String,
+    pub delivered_at: Option<String>,
+}
+
+impl WebhookDelivery {
+    pub async fn enqueue(
+        conn: &mut SqliteConnection,
+        webhook_id: i64,
+        event_type: &str,
+        payload: &str,
+    ) -> Result<i64> {
+        let id = sqlx::query(
+            "INSERT INTO webhook_deliveries (webhook_id, event_type, payload) VALUES (?1, ?2, ?3)",
+        )
+        .bind(webhook_id)
+        .bind(event_type)
+        .bind(payload)
+        .execute(&mut *conn)
+        .await
+        .inspect_err(|e| {
+            error!(
+                "Failed to queue '{}' for webhook {}: {:?}",
+                event_type, webhook_id, e
+            )
+        })?
+        .last_insert_rowid();
+
+        Ok(id)
+    }
+
+    /// Pending deliveries whose next attempt is due, oldest first
+    pub async fn due(pool: &DbPool, limit: i64) -> Result<Vec<WebhookDelivery>> {
+        let deliveries = sqlx::query_as::<_, WebhookDelivery>(&format!(
+            r#"
+            SELECT {} FROM webhook_deliveries
+            WHERE status = 'pending' AND next_attempt_at <= datetime('now')
+            ORDER BY id ASC
+            LIMIT ?1
+            "#,
+            DELIVERY_COLUMNS
+        ))
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to fetch due webhook deliveries: {:?}", e))?;
+
+        Ok(deliveries)
+    }
+
+    pub async fn mark_delivered(pool: &DbPool, id: i64, response_status: u16) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'delivered', attempts = attempts + 1, response_status = ?2,
+                last_error = NULL, delivered_at = datetime('now')
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .bind(response_status as i64)
+        .execute(pool)
+        .await
+        .inspect_err(|e| error!("Failed to mark webhook delivery {} delivered: {:?}", id, e))?;
+
+        Ok(())
+    }
+
+    /// Record a failed attempt. The delivery is tried again after `retry_in_secs`, or is
+    /// dead when that is None.
+    pub async fn record_failure(
+        pool: &DbPool,
+        id: i64,
+        response_status: Option<u16>,
+        error: &str,
+        retry_in_secs: Option<u64>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET attempts = attempts + 1, response_status = ?2, last_error = ?3,
+                status = CASE WHEN ?4 IS NULL THEN 'dead' ELSE 'pending' END,
+                next_attempt_at = datetime('now', '+' || COALESCE(?4, 0) || ' seconds')
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .bind(response_status.map(i64::from))
+        .bind(error)
+        .bind(retry_in_secs.map(|secs| secs as i64))
+        .execute(pool)
+        .await
+        .inspect_err(|e| error!("Failed to record webhook delivery {} failure: {:?}", id, e))?;
+
+        Ok(())
+    }
+
+    /// Deliveries of a webhook, most recent first
+    pub async fn list(
+        pool: &DbPool,
+        webhook_id: i64,
+        status: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let deliveries = sqlx::query_as::<_, WebhookDelivery>(&format!(
+            r#"
+            SELECT {} FROM webhook_deliveries
+            WHERE webhook_id = ?1 AND (?2 IS NULL OR status = ?2)
+            ORDER BY id DESC
+            LIMIT ?3
+            "#,
+            DELIVERY_COLUMNS
+        ))
+        .bind(webhook_id)
+        .bind(status)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| {
+            warn!(
+                "Failed to list deliveries of webhook {}: {:?}",
+                webhook_id, e
+            )
+        })?;
+
+        Ok(deliveries)
+    }
+}
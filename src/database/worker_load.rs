@@ -0,0 +1,365 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::{fmt, str::FromStr};
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use super::DbPool;
+
+/// `derived_status` is what the load alone says; `load_factor` is None without a capacity
+const LOAD_COLUMNS: &str = "project_id, worker_type, open_tickets, max_open_tickets, \
+     CAST(open_tickets AS REAL) / max_open_tickets AS load_factor, \
+     CASE WHEN max_open_tickets IS NOT NULL AND open_tickets >= max_open_tickets \
+          THEN 'busy' ELSE 'online' END AS derived_status, \
+     status, status_source, status_reason, status_changed_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkerTypeStatus {
+    /// Taking tickets
+    Online,
+    /// At capacity, or reported busy until its ticket count next changes
+    Busy,
+    /// Reported; kept until another status is reported
+    Maintenance,
+    /// Reported; kept until another status is reported
+    Error,
+}
+
+impl WorkerTypeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkerTypeStatus::Online => "online",
+            WorkerTypeStatus::Busy => "busy",
+            WorkerTypeStatus::Maintenance => "maintenance",
+            WorkerTypeStatus::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for WorkerTypeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WorkerTypeStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "online" => Ok(WorkerTypeStatus::Online),
+            "busy" => Ok(WorkerTypeStatus::Busy),
+            "maintenance" => Ok(WorkerTypeStatus::Maintenance),
+            "error" => Ok(WorkerTypeStatus::Error),
+            _ => Err(anyhow::anyhow!(
+                "Invalid status '{}'. Valid statuses are: online, busy, maintenance, error",
+                s
+            )),
+        }
+    }
+}
+
+/// A worker type's open tickets against its capacity, and the status that follows. Kept up
+/// to date by triggers, so it is never cached.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct WorkerTypeLoad {
+    pub project_id: String,
+    pub worker_type: String,
+    /// Open tickets in the worker type's stage
+    pub open_tickets: i64,
+    /// Open tickets at which the worker type turns busy; None for no limit
+    pub max_open_tickets: Option<i64>,
+    pub load_factor: Option<f64>,
+    /// online or busy, from the load alone
+    pub derived_status: String,
+    pub status: String,
+    /// derived when the load set `status`, reported when someone did
+    pub status_source: String,
+    pub status_reason: Option<String>,
+    pub status_changed_at: String,
+}
+
+/// An entry of a worker type's status history
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct StatusChange {
+    pub id: i64,
+    pub status: String,
+    /// derived or reported
+    pub source: String,
+    pub reason: Option<String>,
+    pub reported_by: Option<String>,
+    pub open_tickets: i64,
+    pub created_at: String,
+}
+
+impl WorkerTypeLoad {
+    pub async fn get(pool: &DbPool, project_id: &str, worker_type: &str) -> Result<Option<Self>> {
+        let load = sqlx::query_as::<_, WorkerTypeLoad>(&format!(
+            "SELECT {} FROM worker_type_load WHERE project_id = ?1 AND worker_type = ?2",
+            LOAD_COLUMNS
+        ))
+        .bind(project_id)
+        .bind(worker_type)
+        .fetch_optional(pool)
+        .await
+        .inspect_err(|e| {
+            warn!(
+                "Failed to load load of worker type '{}': {:?}",
+                worker_type, e
+            )
+        })?;
+
+        Ok(load)
+    }
+
+    pub async fn list(pool: &DbPool, project_id: Option<&str>) -> Result<Vec<Self>> {
+        let loads = sqlx::query_as::<_, WorkerTypeLoad>(&format!(
+            "SELECT {} FROM worker_type_load WHERE (?1 IS NULL OR project_id = ?1)",
+            LOAD_COLUMNS
+        ))
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to list worker type loads: {:?}", e))?;
+
+        Ok(loads)
+    }
+
+    /// Set the worker type's capacity, 0 for no limit; its status follows at once when the
+    /// load crosses the new capacity
+    pub async fn set_capacity(
+        pool: &DbPool,
+        project_id: &str,
+        worker_type: &str,
+        max_open_tickets: i64,
+    ) -> Result<Option<Self>> {
+        if max_open_tickets < 0 {
+            anyhow::bail!("max_open_tickets must be 0 (no limit) or more");
+        }
+        let max_open_tickets = (max_open_tickets > 0).then_some(max_open_tickets);
+        sqlx::query(
+            r#"
+            UPDATE worker_type_load SET max_open_tickets = ?3
+            WHERE project_id = ?1 AND worker_type = ?2
+            "#,
+        )
+        .bind(project_id)
+        .bind(worker_type)
+        .bind(max_open_tickets)
+        .execute(pool)
+        .await
+        .inspect_err(|e| error!("Failed to set capacity of '{}': {:?}", worker_type, e))?;
+
+        Self::get(pool, project_id, worker_type).await
+    }
+
+    /// Record a status reported for the worker type. Maintenance and error hold until another
+    /// status is reported; online and busy until its ticket count next changes the status.
+    pub async fn report(
+        pool: &DbPool,
+        project_id: &str,
+        worker_type: &str,
+        status: WorkerTypeStatus,
+        reason: Option<&str>,
+        reported_by: &str,
+    ) -> Result<Option<Self>> {
+        let mut tx = pool.begin().await?;
+        let updated = sqlx::query(
+            r#"
+            UPDATE worker_type_load
+            SET status = ?3, status_source = 'reported', status_reason = ?4,
+                status_changed_at = datetime('now')
+            WHERE project_id = ?1 AND worker_type = ?2
+            "#,
+        )
+        .bind(project_id)
+        .bind(worker_type)
+        .bind(status.as_str())
+        .bind(reason)
+        .execute(&mut *tx)
+        .await
+        .inspect_err(|e| error!("Failed to report status of '{}': {:?}", worker_type, e))?;
+        if updated.rows_affected() == 0 {
+            return Ok(None);
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO worker_type_status_history
+                (project_id, worker_type, status, source, reason, reported_by, open_tickets)
+            SELECT project_id, worker_type, ?3, 'reported', ?4, ?5, open_tickets
+            FROM worker_type_load
+            WHERE project_id = ?1 AND worker_type = ?2
+            "#,
+        )
+        .bind(project_id)
+        .bind(worker_type)
+        .bind(status.as_str())
+        .bind(reason)
+        .bind(reported_by)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Self::get(pool, project_id, worker_type).await
+    }
+
+    /// The worker type's status changes, newest first
+    pub async fn history(
+        pool: &DbPool,
+        project_id: &str,
+        worker_type: &str,
+        limit: i64,
+    ) -> Result<Vec<StatusChange>> {
+        let changes = sqlx::query_as::<_, StatusChange>(
+            r#"
+            SELECT id, status, source, reason, reported_by, open_tickets, created_at
+            FROM worker_type_status_history
+            WHERE project_id = ?1 AND worker_type = ?2
+            ORDER BY id DESC
+            LIMIT ?3
+            "#,
+        )
+        .bind(project_id)
+        .bind(worker_type)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| {
+            warn!(
+                "Failed to load status history of '{}': {:?}",
+                worker_type, e
+            )
+        })?;
+
+        Ok(changes)
+    }
+
+    /// Why the worker type should not be given another ticket, if it should not
+    pub fn unavailable(&self) -> Option<String> {
+        if self.status == WorkerTypeStatus::Online.as_str() {
+            return None;
+        }
+        Some(match &self.status_reason {
+            Some(reason) => format!(
+                "Worker type '{}' is {}: {}",
+                self.worker_type, self.status, reason
+            ),
+            None => format!("Worker type '{}' is {}", self.worker_type, self.status),
+        })
+    }
+
+    /// Why `worker_type` should not be given another ticket, if it should not
+    pub async fn check_assignment(
+        pool: &DbPool,
+        project_id: &str,
+        worker_type: &str,
+    ) -> Result<Option<String>> {
+        Ok(Self::get(pool, project_id, worker_type)
+            .await?
+            .and_then(|load| load.unavailable()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tickets::Ticket;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    #[tokio::test]
+    async fn test_status_follows_open_tickets() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("load").create().await.unwrap();
+        let coder = fx.worker_type(&project, "coder").create().await.unwrap();
+        let reviewer = fx.worker_type(&project, "reviewer").create().await.unwrap();
+        let load = |pool| async move {
+            WorkerTypeLoad::get(pool, "load", "coder")
+                .await
+                .unwrap()
+                .unwrap()
+        };
+
+        let first = fx
+            .ticket(&project, "First")
+            .assigned_to(&coder)
+            .create()
+            .await
+            .unwrap();
+        let capped = WorkerTypeLoad::set_capacity(&pool, "load", "coder", 2)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(capped.open_tickets, 1);
+        assert_eq!(capped.load_factor, Some(0.5));
+        assert_eq!(capped.status, "online");
+
+        // Reaching capacity turns the worker type busy
+        let second = fx
+            .ticket(&project, "Second")
+            .assigned_to(&coder)
+            .create()
+            .await
+            .unwrap();
+        let busy = load(&pool).await;
+        assert_eq!(
+            (busy.status.as_str(), busy.status_source.as_str()),
+            ("busy", "derived")
+        );
+        assert!(busy.unavailable().unwrap().contains("2 of 2"));
+
+        // Closing a ticket frees a slot
+        Ticket::close_ticket(&pool, &second.ticket_id, "Completed")
+            .await
+            .unwrap();
+        let online = load(&pool).await;
+        assert_eq!((online.status.as_str(), online.open_tickets), ("online", 1));
+
+        // Maintenance holds whatever the load does, until another status is reported
+        WorkerTypeLoad::report(
+            &pool,
+            "load",
+            "coder",
+            WorkerTypeStatus::Maintenance,
+            Some("Prompt rework"),
+            "coordinator",
+        )
+        .await
+        .unwrap();
+        fx.ticket(&project, "Third")
+            .assigned_to(&coder)
+            .create()
+            .await
+            .unwrap();
+        Ticket::update_stage(&pool, &first.ticket_id, &reviewer.worker_type)
+            .await
+            .unwrap();
+        let maintenance = load(&pool).await;
+        assert_eq!(
+            (
+                maintenance.status.as_str(),
+                maintenance.derived_status.as_str()
+            ),
+            ("maintenance", "online")
+        );
+
+        let history = WorkerTypeLoad::history(&pool, "load", "coder", 10)
+            .await
+            .unwrap();
+        let statuses: Vec<_> = history
+            .iter()
+            .map(|change| (change.status.as_str(), change.source.as_str()))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("maintenance", "reported"),
+                ("online", "derived"),
+                ("busy", "derived")
+            ]
+        );
+        assert_eq!(history[0].reported_by.as_deref(), Some("coordinator"));
+    }
+}
@@ -0,0 +1,308 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tracing::error;
+use utoipa::ToSchema;
+
+use super::{worker_load::WorkerTypeLoad, worker_types::WorkerType, DbPool};
+
+/// Days of individual outcomes kept for the rolling window
+pub const WINDOW_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// The worker finished its stage and the ticket moved on
+    Success,
+    /// The worker failed, needed coordinator attention, or its work was sent back
+    Failure,
+}
+
+impl Outcome {
+    fn as_sql_value(&self) -> &'static str {
+        match self {
+            Outcome::Success => "success",
+            Outcome::Failure => "failure",
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+struct MetricsRow {
+    project_id: String,
+    worker_type: String,
+    success_count: i64,
+    failure_count: i64,
+    total_success_secs: i64,
+    last_outcome_at: Option<String>,
+    window_successes: i64,
+    window_failures: i64,
+    window_success_secs: i64,
+}
+
+/// Success counts and completion times over a period
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct OutcomeSummary {
+    pub successes: i64,
+    pub failures: i64,
+    /// None until at least one outcome has been recorded
+    pub success_rate: Option<f64>,
+    pub avg_completion_secs: Option<f64>,
+}
+
+impl OutcomeSummary {
+    fn new(successes: i64, failures: i64, success_secs: i64) -> Self {
+        let total = successes + failures;
+        Self {
+            successes,
+            failures,
+            success_rate: (total > 0).then(|| successes as f64 / total as f64),
+            avg_completion_secs: (successes > 0).then(|| success_secs as f64 / successes as f64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct WorkerTypeMetrics {
+    pub project_id: String,
+    pub worker_type: String,
+    pub all_time: OutcomeSummary,
+    /// Outcomes of the last `WINDOW_DAYS` days
+    pub last_30_days: OutcomeSummary,
+    pub last_outcome_at: Option<String>,
+}
+
+impl From<MetricsRow> for WorkerTypeMetrics {
+    fn from(row: MetricsRow) -> Self {
+        Self {
+            all_time: OutcomeSummary::new(
+                row.success_count,
+                row.failure_count,
+                row.total_success_secs,
+            ),
+            last_30_days: OutcomeSummary::new(
+                row.window_successes,
+                row.window_failures,
+                row.window_success_secs,
+            ),
+            project_id: row.project_id,
+            worker_type: row.worker_type,
+            last_outcome_at: row.last_outcome_at,
+        }
+    }
+}
+
+/// Worker type with its metrics and load; `metrics` is None until it has recorded an outcome
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WorkerTypeWithMetrics {
+    #[serde(flatten)]
+    pub worker_type: WorkerType,
+    pub metrics: Option<WorkerTypeMetrics>,
+    pub load: Option<WorkerTypeLoad>,
+}
+
+fn metrics_query() -> String {
+    format!(
+        r#"
+    SELECT m.project_id, m.worker_type, m.success_count, m.failure_count,
+           m.total_success_secs, m.last_outcome_at,
+           COALESCE(SUM(o.outcome = 'success'), 0) AS window_successes,
+           COALESCE(SUM(o.outcome = 'failure'), 0) AS window_failures,
+           COALESCE(SUM(CASE WHEN o.outcome = 'success' THEN o.duration_secs END), 0) AS window_success_secs
+    FROM worker_type_metrics m
+    LEFT JOIN worker_type_outcomes o
+           ON o.project_id = m.project_id AND o.worker_type = m.worker_type
+          AND o.recorded_at >= datetime('now', '-{} days')
+"#,
+        WINDOW_DAYS
+    )
+}
+
+impl WorkerTypeMetrics {
+    /// Record a worker outcome; the outcome log and the aggregates change together
+    pub async fn record(
+        pool: &DbPool,
+        project_id: &str,
+        worker_type: &str,
+        ticket_id: &str,
+        outcome: Outcome,
+        duration_secs: Option<i64>,
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO worker_type_outcomes (project_id, worker_type, ticket_id, outcome, duration_secs)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+        )
+        .bind(project_id)
+        .bind(worker_type)
+        .bind(ticket_id)
+        .bind(outcome.as_sql_value())
+        .bind(duration_secs)
+        .execute(&mut *tx)
+        .await
+        .inspect_err(|e| {
+            error!(
+                "Failed to record {} outcome for worker type '{}': {:?}",
+                outcome.as_sql_value(),
+                worker_type,
+                e
+            )
+        })?;
+
+        let (successes, failures, success_secs) = match outcome {
+            Outcome::Success => (1, 0, duration_secs.unwrap_or(0)),
+            Outcome::Failure => (0, 1, 0),
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO worker_type_metrics
+                (project_id, worker_type, success_count, failure_count, total_success_secs, last_outcome_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'))
+            ON CONFLICT (project_id, worker_type) DO UPDATE SET
+                success_count = success_count + excluded.success_count,
+                failure_count = failure_count + excluded.failure_count,
+                total_success_secs = total_success_secs + excluded.total_success_secs,
+                last_outcome_at = excluded.last_outcome_at
+            "#,
+        )
+        .bind(project_id)
+        .bind(worker_type)
+        .bind(successes)
+        .bind(failures)
+        .bind(success_secs)
+        .execute(&mut *tx)
+        .await?;
+
+        // Outcomes outside the window only matter through the aggregates
+        sqlx::query("DELETE FROM worker_type_outcomes WHERE recorded_at < datetime('now', ?1)")
+            .bind(format!("-{} days", WINDOW_DAYS))
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get(
+        pool: &DbPool,
+        project_id: &str,
+        worker_type: &str,
+    ) -> Result<Option<WorkerTypeMetrics>> {
+        let row = sqlx::query_as::<_, MetricsRow>(&format!(
+            "{} WHERE m.project_id = ?1 AND m.worker_type = ?2 GROUP BY m.project_id, m.worker_type",
+            metrics_query()
+        ))
+        .bind(project_id)
+        .bind(worker_type)
+        .fetch_optional(pool)
+        .await?;
+        Ok(row.map(Into::into))
+    }
+
+    /// Metrics of all worker types that have recorded outcomes, optionally for one project
+    pub async fn list(pool: &DbPool, project_id: Option<&str>) -> Result<Vec<WorkerTypeMetrics>> {
+        let rows = sqlx::query_as::<_, MetricsRow>(&format!(
+            "{} WHERE ?1 IS NULL OR m.project_id = ?1 GROUP BY m.project_id, m.worker_type",
+            metrics_query()
+        ))
+        .bind(project_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Pair worker types with their metrics and load
+    pub async fn attach(
+        pool: &DbPool,
+        worker_types: Vec<WorkerType>,
+    ) -> Result<Vec<WorkerTypeWithMetrics>> {
+        let mut metrics = Self::list(pool, None).await?;
+        let mut loads = WorkerTypeLoad::list(pool, None).await?;
+        Ok(worker_types
+            .into_iter()
+            .map(|worker_type| {
+                let index = metrics.iter().position(|m| {
+                    m.project_id == worker_type.project_id
+                        && m.worker_type == worker_type.worker_type
+                });
+                let load_index = loads.iter().position(|l| {
+                    l.project_id == worker_type.project_id
+                        && l.worker_type == worker_type.worker_type
+                });
+                WorkerTypeWithMetrics {
+                    metrics: index.map(|i| metrics.swap_remove(i)),
+                    load: load_index.map(|i| loads.swap_remove(i)),
+                    worker_type,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_outcomes_update_aggregates_and_window() {
+        let dir = std::env::temp_dir().join(format!("vibe-metrics-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pool = crate::database::create_pool(&format!(
+            "sqlite:{}?mode=rwc",
+            dir.join("t.db").display()
+        ))
+        .await
+        .unwrap();
+
+        for (outcome, secs) in [
+            (Outcome::Success, Some(60)),
+            (Outcome::Success, Some(120)),
+            (Outcome::Failure, Some(5)),
+        ] {
+            WorkerTypeMetrics::record(&pool, "p", "implement", "T-1", outcome, secs)
+                .await
+                .unwrap();
+        }
+        // An outcome from before the window counts only towards the all-time totals
+        WorkerTypeMetrics::record(&pool, "p", "implement", "T-0", Outcome::Failure, None)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE worker_type_outcomes SET recorded_at = datetime('now', '-40 days') WHERE ticket_id = 'T-0'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let metrics = WorkerTypeMetrics::get(&pool, "p", "implement")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(metrics.all_time.successes, 2);
+        assert_eq!(metrics.all_time.failures, 2);
+        assert_eq!(metrics.all_time.success_rate, Some(0.5));
+        assert_eq!(metrics.all_time.avg_completion_secs, Some(90.0));
+        assert_eq!(metrics.last_30_days.failures, 1);
+        assert!((metrics.last_30_days.success_rate.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+
+        assert!(WorkerTypeMetrics::get(&pool, "p", "review")
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            WorkerTypeMetrics::list(&pool, Some("p"))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(WorkerTypeMetrics::list(&pool, Some("q"))
+            .await
+            .unwrap()
+            .is_empty());
+
+        pool.close().await;
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,458 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tracing::{error, warn};
+
+use super::{timing::timed, DbPool};
+use crate::workers::boundary::BoundaryViolation;
+
+const RUN_COLUMNS: &str = "id, worker_id, project_id, worker_type, ticket_id, pid, working_dir, \
+     max_runtime_secs, status, exit_status, enforcement, policy_violations, warm, boundary_violations, \
+     last_heartbeat_at, progress, stalled_at, started_at, finished_at";
+
+/// One spawned worker process and the sandbox it ran under
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WorkerRun {
+    pub id: i64,
+    pub worker_id: String,
+    pub project_id: String,
+    pub worker_type: String,
+    pub ticket_id: String,
+    pub pid: Option<i64>,
+    pub working_dir: String,
+    pub max_runtime_secs: i64,
+    /// running, completed, failed, killed or interrupted
+    pub status: String,
+    pub exit_status: Option<String>,
+    /// How the runtime limit was enforced, if it was: terminated or killed
+    pub enforcement: Option<String>,
+    pub policy_violations: Option<String>, // JSON array
+    /// Served by an idle worker from the warm pool rather than a new process
+    pub warm: bool,
+    /// Files changed outside the worker's workspace during the run
+    pub boundary_violations: Option<String>, // JSON array
+    /// Latest progress report of the worker, with its time
+    pub last_heartbeat_at: Option<String>,
+    pub progress: Option<String>,
+    /// Set while a running worker has gone quiet for longer than its stall threshold; kept
+    /// on runs that were stopped for it
+    pub stalled_at: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+/// An instruction written to a running worker's stdin
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WorkerRunInput {
+    pub run_id: i64,
+    pub input_id: String,
+    pub seq: i64,
+    pub content: String,
+    pub sent_by: Option<String>,
+    pub sent_at: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct StartRunRequest<'a> {
+    pub worker_id: &'a str,
+    pub project_id: &'a str,
+    pub worker_type: &'a str,
+    pub ticket_id: &'a str,
+    pub working_dir: &'a str,
+    pub max_runtime_secs: u64,
+    pub policy_violations: &'a [String],
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RunFilter {
+    pub project_id: Option<String>,
+    pub ticket_id: Option<String>,
+    pub worker_id: Option<String>,
+    pub limit: i64,
+}
+
+impl WorkerRun {
+    pub async fn start(pool: &DbPool, req: &StartRunRequest<'_>) -> Result<i64> {
+        let violations = if req.policy_violations.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(req.policy_violations)?)
+        };
+        let id = sqlx::query(
+            r#"
+            INSERT INTO worker_runs (
+                worker_id, project_id, worker_type, ticket_id, working_dir, max_runtime_secs,
+                policy_violations
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+        )
+        .bind(req.worker_id)
+        .bind(req.project_id)
+        .bind(req.worker_type)
+        .bind(req.ticket_id)
+        .bind(req.working_dir)
+        .bind(req.max_runtime_secs as i64)
+        .bind(violations)
+        .execute(pool)
+        .await
+        .inspect_err(|e| {
+            error!(
+                "Failed to record worker run for '{}': {:?}",
+                req.worker_id, e
+            )
+        })?
+        .last_insert_rowid();
+
+        Ok(id)
+    }
+
+    pub async fn finish(
+        pool: &DbPool,
+        id: i64,
+        status: &str,
+        pid: Option<u32>,
+        exit_status: Option<&str>,
+        enforcement: Option<&str>,
+        warm: bool,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE worker_runs
+            SET status = ?2, pid = ?3, exit_status = ?4, enforcement = ?5, warm = ?6,
+                finished_at = datetime('now')
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .bind(status)
+        .bind(pid.map(i64::from))
+        .bind(exit_status)
+        .bind(enforcement)
+        .bind(warm)
+        .execute(pool)
+        .await
+        .inspect_err(|e| error!("Failed to finish worker run {}: {:?}", id, e))?;
+
+        Ok(())
+    }
+
+    /// Delete the runs of a project, returning how many were removed
+    pub async fn delete_for_project(pool: &DbPool, project_id: &str) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM worker_runs WHERE project_id = ?1")
+            .bind(project_id)
+            .execute(pool)
+            .await
+            .inspect_err(|e| warn!("Failed to delete runs of '{}': {:?}", project_id, e))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Most recent runs first
+    pub async fn list(pool: &DbPool, filter: &RunFilter) -> Result<Vec<WorkerRun>> {
+        let runs = sqlx::query_as::<_, WorkerRun>(&format!(
+            r#"
+            SELECT {} FROM worker_runs
+            WHERE (?1 IS NULL OR project_id = ?1)
+              AND (?2 IS NULL OR ticket_id = ?2)
+              AND (?3 IS NULL OR worker_id = ?3)
+            ORDER BY id DESC
+            LIMIT ?4
+            "#,
+            RUN_COLUMNS
+        ))
+        .bind(&filter.project_id)
+        .bind(&filter.ticket_id)
+        .bind(&filter.worker_id)
+        .bind(filter.limit)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to list worker runs: {:?}", e))?;
+
+        Ok(runs)
+    }
+
+    /// Append an instruction the run received to its history
+    pub async fn record_input(pool: &DbPool, input: &WorkerRunInput) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO worker_run_inputs (run_id, input_id, seq, content, sent_by, sent_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(input.run_id)
+        .bind(&input.input_id)
+        .bind(input.seq)
+        .bind(&input.content)
+        .bind(&input.sent_by)
+        .bind(&input.sent_at)
+        .execute(pool)
+        .await
+        .inspect_err(|e| error!("Failed to record input for run {}: {:?}", input.run_id, e))?;
+
+        Ok(())
+    }
+
+    /// Instructions received by any of `run_ids`, in the order each run received them
+    pub async fn inputs(pool: &DbPool, run_ids: &[i64]) -> Result<Vec<WorkerRunInput>> {
+        let inputs = sqlx::query_as::<_, WorkerRunInput>(
+            r#"
+            SELECT run_id, input_id, seq, content, sent_by, sent_at
+            FROM worker_run_inputs
+            WHERE run_id IN (SELECT value FROM json_each(?1))
+            ORDER BY run_id, seq
+            "#,
+        )
+        .bind(serde_json::to_string(run_ids)?)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to load worker run inputs: {:?}", e))?;
+
+        Ok(inputs)
+    }
+
+    /// Record the files a run changed outside its workspace
+    pub async fn record_boundary_violations(
+        pool: &DbPool,
+        id: i64,
+        violations: &[BoundaryViolation],
+    ) -> Result<()> {
+        sqlx::query("UPDATE worker_runs SET boundary_violations = ?2 WHERE id = ?1")
+            .bind(id)
+            .bind(serde_json::to_string(violations)?)
+            .execute(pool)
+            .await
+            .inspect_err(|e| {
+                error!(
+                    "Failed to record boundary violations of run {}: {:?}",
+                    id, e
+                )
+            })?;
+
+        Ok(())
+    }
+
+    /// Record a progress report on the worker's running run, clearing any stall; None when
+    /// the worker has no running run
+    pub async fn heartbeat(
+        pool: &DbPool,
+        worker_id: &str,
+        progress: Option<&str>,
+    ) -> Result<Option<WorkerRun>> {
+        // Fetched in full so the statement completes and the update is committed
+        let mut runs = sqlx::query_as::<_, WorkerRun>(&format!(
+            r#"
+            UPDATE worker_runs
+            SET last_heartbeat_at = datetime('now'), progress = COALESCE(?2, progress),
+                stalled_at = NULL
+            WHERE id = (
+                SELECT MAX(id) FROM worker_runs WHERE worker_id = ?1 AND status = 'running'
+            )
+            RETURNING {}
+            "#,
+            RUN_COLUMNS
+        ))
+        .bind(worker_id)
+        .bind(progress)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| error!("Failed to record heartbeat of '{}': {:?}", worker_id, e))?;
+
+        Ok(runs.pop())
+    }
+
+    /// Seconds since a running run last reported progress, or since it started when it
+    /// never did; None once it has finished
+    pub async fn silent_secs(pool: &DbPool, id: i64) -> Result<Option<i64>> {
+        let secs = sqlx::query_scalar(
+            r#"
+            SELECT CAST((julianday('now') - julianday(COALESCE(last_heartbeat_at, started_at)))
+                        * 86400 AS INTEGER)
+            FROM worker_runs
+            WHERE id = ?1 AND status = 'running'
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to check heartbeat of run {}: {:?}", id, e))?;
+
+        Ok(secs)
+    }
+
+    /// Flag a running run as stalled; false when it already was or has finished
+    pub async fn mark_stalled(pool: &DbPool, id: i64) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE worker_runs SET stalled_at = datetime('now')
+            WHERE id = ?1 AND status = 'running' AND stalled_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .execute(pool)
+        .await
+        .inspect_err(|e| error!("Failed to flag run {} as stalled: {:?}", id, e))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Running runs currently flagged as stalled, optionally of one project
+    pub async fn count_stalled(pool: &DbPool, project_id: Option<&str>) -> Result<i64> {
+        let count = timed!(
+            pool,
+            "WorkerRun::count_stalled",
+            sqlx::query_scalar(
+                r#"
+                SELECT COUNT(*) FROM worker_runs
+                WHERE status = 'running' AND stalled_at IS NOT NULL
+                  AND (?1 IS NULL OR project_id = ?1)
+                "#,
+            )
+            .bind(project_id),
+            fetch_one
+        )
+        .inspect_err(|e| warn!("Failed to count stalled runs: {:?}", e))?;
+
+        Ok(count)
+    }
+
+    /// A running run that has gone quiet for longer than its stall threshold
+    pub fn is_stalled(&self) -> bool {
+        self.status == "running" && self.stalled_at.is_some()
+    }
+
+    pub fn policy_violations(&self) -> Vec<String> {
+        self.policy_violations
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn boundary_violations(&self) -> Vec<BoundaryViolation> {
+        self.boundary_violations
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_recorded_and_filtered() {
+        let dir = std::env::temp_dir().join(format!("vibe-worker-runs-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pool = crate::database::create_pool(&format!(
+            "sqlite:{}?mode=rwc",
+            dir.join("t.db").display()
+        ))
+        .await
+        .unwrap();
+
+        let violations = vec!["Override of 'nice' is not permitted".to_string()];
+        let first = WorkerRun::start(
+            &pool,
+            &StartRunRequest {
+                worker_id: "p:coding:P-1",
+                project_id: "p",
+                worker_type: "coding",
+                ticket_id: "P-1",
+                working_dir: "/tmp/p",
+                max_runtime_secs: 60,
+                policy_violations: &violations,
+            },
+        )
+        .await
+        .unwrap();
+        WorkerRun::start(
+            &pool,
+            &StartRunRequest {
+                worker_id: "p:coding:P-2",
+                project_id: "p",
+                worker_type: "coding",
+                ticket_id: "P-2",
+                working_dir: "/tmp/p",
+                max_runtime_secs: 60,
+                policy_violations: &[],
+            },
+        )
+        .await
+        .unwrap();
+        WorkerRun::finish(
+            &pool,
+            first,
+            "killed",
+            Some(42),
+            None,
+            Some("killed"),
+            false,
+        )
+        .await
+        .unwrap();
+
+        let runs = WorkerRun::list(
+            &pool,
+            &RunFilter {
+                ticket_id: Some("P-1".to_string()),
+                limit: 10,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].status, "killed");
+        assert_eq!(runs[0].pid, Some(42));
+        assert!(runs[0].finished_at.is_some());
+        assert_eq!(runs[0].policy_violations(), violations);
+
+        let all = WorkerRun::list(
+            &pool,
+            &RunFilter {
+                project_id: Some("p".to_string()),
+                limit: 10,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].ticket_id, "P-2");
+        assert_eq!(all[0].status, "running");
+
+        // Only the running run takes heartbeats, and a heartbeat clears a stall
+        assert!(WorkerRun::heartbeat(&pool, "p:coding:P-1", Some("x"))
+            .await
+            .unwrap()
+            .is_none());
+        let second = all[0].id;
+        assert!(WorkerRun::silent_secs(&pool, first)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(
+            WorkerRun::silent_secs(&pool, second)
+                .await
+                .unwrap()
+                .unwrap()
+                >= 0
+        );
+        assert!(WorkerRun::mark_stalled(&pool, second).await.unwrap());
+        assert!(!WorkerRun::mark_stalled(&pool, second).await.unwrap());
+        assert!(!WorkerRun::mark_stalled(&pool, first).await.unwrap());
+        assert_eq!(WorkerRun::count_stalled(&pool, Some("p")).await.unwrap(), 1);
+        let beat = WorkerRun::heartbeat(&pool, "p:coding:P-2", Some("tests pass"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(beat.progress.as_deref(), Some("tests pass"));
+        assert!(beat.last_heartbeat_at.is_some());
+        assert!(!beat.is_stalled());
+        assert_eq!(WorkerRun::count_stalled(&pool, None).await.unwrap(), 0);
+
+        pool.close().await;
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,47 +1,126 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::HashSet;
 use tracing::{error, warn};
+use utoipa::ToSchema;
 
-use super::DbPool;
+use super::{cache, returning::FetchReturning, DbPool};
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct WorkerType {
     pub id: i64,
     pub project_id: String,
     pub worker_type: String,
+    /// Name in URL-safe form, unique across projects, e.g. "code-reviewer-2"
+    pub slug: String,
     pub short_description: Option<String>,
     pub system_prompt: String,
+    /// JSON object of worker spawn policy overrides
+    pub spawn_overrides: Option<String>,
+    /// JSON array of the tags this worker type handles, for routing new tickets
+    pub capabilities: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// What deleting a worker type leaves behind: open tickets sitting at its stage and workers
+/// of that type that are still running
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerTypeDeletion {
+    pub project_id: String,
+    pub worker_type: String,
+    pub stranded_tickets: Vec<String>,
+    pub running_workers: Vec<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateWorkerTypeRequest {
     pub project_id: String,
     pub worker_type: String,
     pub short_description: Option<String>,
     pub system_prompt: String,
+    pub spawn_overrides: Option<String>,
+    pub capabilities: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateWorkerTypeRequest {
     pub short_description: Option<String>,
     pub system_prompt: Option<String>,
+    pub spawn_overrides: Option<String>,
+    pub capabilities: Option<String>,
+}
+
+/// Capabilities in canonical tag form (trimmed, lowercase, deduplicated), serialized for
+/// storage; an empty list clears them
+pub fn encode_capabilities(names: &[String]) -> Result<String> {
+    let mut names = names
+        .iter()
+        .map(|name| super::tags::canonical_name(name))
+        .collect::<Result<Vec<_>>>()?;
+    names.sort();
+    names.dedup();
+    Ok(serde_json::to_string(&names)?)
+}
+
+/// Slug form of a worker type name: lowercase letters and digits separated by single dashes
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    match slug.trim_end_matches('-') {
+        "" => "worker".to_string(),
+        slug => slug.to_string(),
+    }
+}
+
+/// The slug of `name`, with "-2", "-3", ... appended while other worker types hold it
+async fn unique_slug(pool: &DbPool, name: &str) -> Result<String> {
+    let base = slugify(name);
+    let taken: HashSet<String> =
+        sqlx::query_scalar("SELECT slug FROM worker_types WHERE slug = ?1 OR slug LIKE ?1 || '-%'")
+            .bind(&base)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+    let slug = std::iter::once(base.clone())
+        .chain((2..).map(|n| format!("{}-{}", base, n)))
+        .find(|slug| !taken.contains(slug))
+        .unwrap_or(base);
+    Ok(slug)
 }
 
 impl WorkerType {
+    /// Tags this worker type handles; empty when none are set
+    pub fn capabilities(&self) -> Vec<String> {
+        self.capabilities
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+
     pub async fn create(pool: &DbPool, req: CreateWorkerTypeRequest) -> Result<WorkerType> {
+        let slug = unique_slug(pool, &req.worker_type).await?;
         let worker_type = sqlx::query_as::<_, WorkerType>(r#"
-            INSERT INTO worker_types (project_id, worker_type, short_description, system_prompt)
-            VALUES (?1, ?2, ?3, ?4)
-            RETURNING id, project_id, worker_type, short_description, system_prompt, created_at, updated_at
+            INSERT INTO worker_types (project_id, worker_type, slug, short_description, system_prompt, spawn_overrides, capabilities)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            RETURNING id, project_id, worker_type, slug, short_description, system_prompt, spawn_overrides, capabilities, created_at, updated_at
         "#)
         .bind(&req.project_id)
         .bind(&req.worker_type)
+        .bind(&slug)
         .bind(&req.short_description)
         .bind(&req.system_prompt)
-        .fetch_one(pool)
+        .bind(&req.spawn_overrides)
+        .bind(&req.capabilities)
+        .fetch_returned_one(pool)
         .await
         .inspect_err(|e| error!("Failed to create worker type '{}' for project '{}': {:?}", req.worker_type, req.project_id, e))?;
 
@@ -53,18 +132,23 @@ impl WorkerType {
         project_id: &str,
         worker_type: &str,
     ) -> Result<Option<WorkerType>> {
-        let worker_type = sqlx::query_as::<_, WorkerType>(r#"
-            SELECT id, project_id, worker_type, short_description, system_prompt, created_at, updated_at
+        cache::for_pool(pool)
+            .worker_types
+            .get_or_load(&cache::worker_type_key(project_id, worker_type), || async {
+                let worker_type = sqlx::query_as::<_, WorkerType>(r#"
+            SELECT id, project_id, worker_type, slug, short_description, system_prompt, spawn_overrides, capabilities, created_at, updated_at
             FROM worker_types
             WHERE project_id = ?1 AND worker_type = ?2
         "#)
-        .bind(project_id)
-        .bind(worker_type)
-        .fetch_optional(pool)
-        .await
-        .inspect_err(|e| warn!("Failed to fetch worker type '{}' for project '{}': {:?}", worker_type, project_id, e))?;
+                .bind(project_id)
+                .bind(worker_type)
+                .fetch_optional(pool)
+                .await
+                .inspect_err(|e| warn!("Failed to fetch worker type '{}' for project '{}': {:?}", worker_type, project_id, e))?;
 
-        Ok(worker_type)
+                Ok(worker_type)
+            })
+            .await
     }
 
     pub async fn list_by_project(
@@ -73,7 +157,7 @@ impl WorkerType {
     ) -> Result<Vec<WorkerType>> {
         let worker_types = if let Some(project_id) = project_id {
             sqlx::query_as::<_, WorkerType>(r#"
-                SELECT id, project_id, worker_type, short_description, system_prompt, created_at, updated_at
+                SELECT id, project_id, worker_type, slug, short_description, system_prompt, spawn_overrides, capabilities, created_at, updated_at
                 FROM worker_types
                 WHERE project_id = ?1
                 ORDER BY created_at DESC
@@ -84,7 +168,7 @@ impl WorkerType {
             .inspect_err(|e| warn!("Failed to list worker types for project '{}': {:?}", project_id, e))?
         } else {
             sqlx::query_as::<_, WorkerType>(r#"
-                SELECT id, project_id, worker_type, short_description, system_prompt, created_at, updated_at
+                SELECT id, project_id, worker_type, slug, short_description, system_prompt, spawn_overrides, capabilities, created_at, updated_at
                 FROM worker_types
                 ORDER BY project_id ASC, created_at DESC
             "#)
@@ -96,6 +180,48 @@ impl WorkerType {
         Ok(worker_types)
     }
 
+    /// Up to `limit` worker types ordered by `created_at DESC, id DESC`, starting after the
+    /// `(created_at, id)` key of the previous page's last row
+    pub async fn list_page(
+        pool: &DbPool,
+        project_id: Option<&str>,
+        after: Option<(&str, i64)>,
+        limit: i64,
+    ) -> Result<Vec<WorkerType>> {
+        let (after_created_at, after_id) = after.unzip();
+        let worker_types = sqlx::query_as::<_, WorkerType>(
+            r#"
+            SELECT id, project_id, worker_type, slug, short_description, system_prompt, spawn_overrides, capabilities, created_at, updated_at
+            FROM worker_types
+            WHERE (?1 IS NULL OR project_id = ?1)
+              AND (?2 IS NULL OR created_at < ?2 OR (created_at = ?2 AND id < ?3))
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?4
+            "#,
+        )
+        .bind(project_id)
+        .bind(after_created_at)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to list worker types page: {:?}", e))?;
+
+        Ok(worker_types)
+    }
+
+    pub async fn count(pool: &DbPool, project_id: Option<&str>) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM worker_types WHERE (?1 IS NULL OR project_id = ?1)",
+        )
+        .bind(project_id)
+        .fetch_one(pool)
+        .await
+        .inspect_err(|e| warn!("Failed to count worker types: {:?}", e))?;
+
+        Ok(count)
+    }
+
     pub async fn update(
         pool: &DbPool,
         project_id: &str,
@@ -103,7 +229,11 @@ impl WorkerType {
         req: UpdateWorkerTypeRequest,
     ) -> Result<Option<WorkerType>> {
         // Check if any updates are needed
-        if req.short_description.is_none() && req.system_prompt.is_none() {
+        if req.short_description.is_none()
+            && req.system_prompt.is_none()
+            && req.spawn_overrides.is_none()
+            && req.capabilities.is_none()
+        {
             return Self::get_by_type(pool, project_id, worker_type).await;
         }
 
@@ -127,6 +257,22 @@ impl WorkerType {
             query_builder.push_bind(prompt);
             has_field = true;
         }
+        if let Some(ref overrides) = req.spawn_overrides {
+            if has_field {
+                query_builder.push(", ");
+            }
+            query_builder.push("spawn_overrides = ");
+            query_builder.push_bind(overrides);
+            has_field = true;
+        }
+        if let Some(ref capabilities) = req.capabilities {
+            if has_field {
+                query_builder.push(", ");
+            }
+            query_builder.push("capabilities = ");
+            query_builder.push_bind(capabilities);
+            has_field = true;
+        }
 
         if has_field {
             query_builder.push(", ");
@@ -137,11 +283,11 @@ impl WorkerType {
         query_builder.push_bind(project_id);
         query_builder.push(" AND worker_type = ");
         query_builder.push_bind(worker_type);
-        query_builder.push(" RETURNING id, project_id, worker_type, short_description, system_prompt, created_at, updated_at");
+        query_builder.push(" RETURNING id, project_id, worker_type, slug, short_description, system_prompt, spawn_overrides, capabilities, created_at, updated_at");
 
         let worker_type_result = query_builder
             .build_query_as::<WorkerType>()
-            .fetch_optional(pool)
+            .fetch_returned(pool)
             .await
             .inspect_err(|e| {
                 error!(
@@ -149,9 +295,47 @@ impl WorkerType {
                     worker_type, project_id, e
                 )
             })?;
+        cache::for_pool(pool)
+            .worker_types
+            .invalidate(&cache::worker_type_key(project_id, worker_type));
         Ok(worker_type_result)
     }
 
+    /// What deleting a worker type leaves behind; None when it does not exist
+    pub async fn plan_delete(
+        pool: &DbPool,
+        project_id: &str,
+        worker_type: &str,
+    ) -> Result<Option<WorkerTypeDeletion>> {
+        if Self::get_by_type(pool, project_id, worker_type)
+            .await?
+            .is_none()
+        {
+            return Ok(None);
+        }
+        let stranded_tickets = sqlx::query_scalar::<_, String>(
+            "SELECT ticket_id FROM tickets WHERE project_id = ?1 AND current_stage = ?2 AND state != 'closed' ORDER BY ticket_id",
+        )
+        .bind(project_id)
+        .bind(worker_type)
+        .fetch_all(pool)
+        .await?;
+        let running_workers = sqlx::query_scalar::<_, String>(
+            "SELECT worker_id FROM workers WHERE project_id = ?1 AND worker_type = ?2 AND status NOT IN ('finished', 'failed') ORDER BY worker_id",
+        )
+        .bind(project_id)
+        .bind(worker_type)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(Some(WorkerTypeDeletion {
+            project_id: project_id.to_string(),
+            worker_type: worker_type.to_string(),
+            stranded_tickets,
+            running_workers,
+        }))
+    }
+
     pub async fn delete(pool: &DbPool, project_id: &str, worker_type: &str) -> Result<bool> {
         let result =
             sqlx::query("DELETE FROM worker_types WHERE project_id = ?1 AND worker_type = ?2")
@@ -165,7 +349,46 @@ impl WorkerType {
                         worker_type, project_id, e
                     )
                 })?;
+        cache::for_pool(pool)
+            .worker_types
+            .invalidate(&cache::worker_type_key(project_id, worker_type));
 
         Ok(result.rows_affected() > 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Code Reviewer"), "code-reviewer");
+        assert_eq!(slugify("  backend_api--v2 "), "backend-api-v2");
+        assert_eq!(slugify("!!!"), "worker");
+    }
+
+    #[tokio::test]
+    async fn test_slug_collisions_get_suffixes() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let first = fx.project("first").create().await.unwrap();
+        let second = fx.project("second").create().await.unwrap();
+
+        let slugs = [
+            (&first, "Code Reviewer"),
+            (&second, "code_reviewer"),
+            (&second, "code reviewer"),
+        ];
+        let mut created = Vec::new();
+        for (project, name) in slugs {
+            let worker_type = fx.worker_type(project, name).create().await.unwrap();
+            created.push(worker_type.slug);
+        }
+        assert_eq!(
+            created,
+            ["code-reviewer", "code-reviewer-2", "code-reviewer-3"]
+        );
+    }
+}
@@ -1,9 +1,10 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::BTreeMap;
 use tracing::{error, warn};
 
-use super::DbPool;
+use super::{returning::FetchReturning, DbPool};
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Worker {
@@ -32,7 +33,7 @@ impl Worker {
         .bind(&worker.queue_name)
         .bind(&worker.started_at)
         .bind(&worker.last_activity)
-        .fetch_one(pool)
+        .fetch_returned_one(pool)
         .await
         .inspect_err(|e| error!("Failed to create worker '{}': {:?}", worker.worker_id, e))?;
 
@@ -93,6 +94,37 @@ impl Worker {
         Ok(workers)
     }
 
+    /// Tickets of a project being processed, by the worker processing them; the worker
+    /// may belong to another project
+    pub async fn assignments(
+        pool: &DbPool,
+        project_id: &str,
+    ) -> Result<BTreeMap<String, Vec<String>>> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT processing_worker_id, ticket_id
+            FROM tickets
+            WHERE project_id = ?1 AND processing_worker_id IS NOT NULL
+            ORDER BY processing_worker_id, ticket_id
+        "#,
+        )
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .inspect_err(|e| {
+            warn!(
+                "Failed to list assignments for project '{}': {:?}",
+                project_id, e
+            )
+        })?;
+
+        let mut assignments: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (worker_id, ticket_id) in rows {
+            assignments.entry(worker_id).or_default().push(ticket_id);
+        }
+        Ok(assignments)
+    }
+
     pub async fn list_by_type(pool: &DbPool, worker_type: &str) -> Result<Vec<Worker>> {
         let workers = sqlx::query_as::<_, Worker>(
             r#"
@@ -0,0 +1,489 @@
+//! Demo data for trying out the dashboard, UI work and screenshots: one project worked on
+//! by three workers, with tickets in every state, worker discussions, coordinator
+//! requests and worker runs. Everything is written through the model APIs, so the data
+//! looks like what the server produces itself.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::json;
+use tracing::info;
+use utoipa::ToSchema;
+
+use crate::database::{
+    agent_requests::{AgentRequest, RequestStatus},
+    comments::{Comment, CreateCommentRequest},
+    projects::{CreateProjectRequest, Project},
+    tags::Tag,
+    tickets::{CreateTicketRequest, Priority, Ticket},
+    worker_runs::{StartRunRequest, WorkerRun},
+    worker_types::{CreateWorkerTypeRequest, WorkerType},
+    workers::Worker,
+    DbPool,
+};
+use crate::workers::{
+    queue::QueueManager,
+    ticket_id::{generate_ticket_id, infer_subsystem_from_stages},
+};
+
+pub const DEMO_PROJECT: &str = "demo-webshop";
+
+/// Stages of every demo ticket, in order; each is also a demo worker type
+const STAGES: &[(&str, &str)] = &[
+    ("architect", "Plans the change and splits it into steps"),
+    ("developer", "Implements the planned change"),
+    (
+        "reviewer",
+        "Reviews the change and sends it back or approves it",
+    ),
+];
+
+/// Where a demo ticket ends up
+#[derive(Clone, Copy)]
+enum Outcome {
+    /// Open in this stage, waiting for a worker
+    Waiting(usize),
+    /// Open in this stage and picked up by the demo worker of that stage
+    Worked(usize),
+    OnHold(&'static str),
+    Completed,
+    Stopped,
+}
+
+struct DemoTicket {
+    title: &'static str,
+    description: &'static str,
+    priority: Priority,
+    tags: &'static [&'static str],
+    outcome: Outcome,
+}
+
+const TICKETS: &[DemoTicket] = &[
+    DemoTicket {
+        title: "Add product search to the catalog page",
+        description: "Shoppers should be able to search products by name and category.",
+        priority: Priority::High,
+        tags: &["frontend", "search"],
+        outcome: Outcome::Worked(1),
+    },
+    DemoTicket {
+        title: "Checkout fails for carts with gift cards",
+        description: "Payment returns 500 when a gift card covers part of the order.",
+        priority: Priority::Urgent,
+        tags: &["bug", "payments"],
+        outcome: Outcome::Worked(2),
+    },
+    DemoTicket {
+        title: "Design the order history API",
+        description: "Customers need a paginated list of their past orders.",
+        priority: Priority::Medium,
+        tags: &["api"],
+        outcome: Outcome::Worked(0),
+    },
+    DemoTicket {
+        title: "Send order confirmation emails",
+        description: "Email the customer a summary once the payment succeeds.",
+        priority: Priority::Medium,
+        tags: &["email"],
+        outcome: Outcome::Waiting(1),
+    },
+    DemoTicket {
+        title: "Cache product images behind the CDN",
+        description: "Product pages load slowly because images are served by the app.",
+        priority: Priority::Low,
+        tags: &["performance"],
+        outcome: Outcome::Waiting(0),
+    },
+    DemoTicket {
+        title: "Validate shipping addresses",
+        description: "Reject addresses without a postal code before the order is placed.",
+        priority: Priority::Medium,
+        tags: &["checkout"],
+        outcome: Outcome::Waiting(2),
+    },
+    DemoTicket {
+        title: "Migrate prices to integer cents",
+        description: "Floating point prices cause rounding errors in order totals.",
+        priority: Priority::High,
+        tags: &["database"],
+        outcome: Outcome::OnHold("Waiting for the finance team to confirm the rounding rules."),
+    },
+    DemoTicket {
+        title: "Support discount codes",
+        description: "Marketing wants percentage and fixed-amount codes with expiry dates.",
+        priority: Priority::Low,
+        tags: &["checkout"],
+        outcome: Outcome::OnHold("Blocked until prices are stored in integer cents."),
+    },
+    DemoTicket {
+        title: "Fix flaky cart total test",
+        description: "The cart total test fails now and then on CI because of a timing issue.",
+        priority: Priority::High,
+        tags: &["bug", "tests"],
+        outcome: Outcome::Completed,
+    },
+    DemoTicket {
+        title: "Add sitemap for product pages",
+        description: "Search engines should find every product page.",
+        priority: Priority::Low,
+        tags: &["seo"],
+        outcome: Outcome::Completed,
+    },
+    DemoTicket {
+        title: "Upgrade the payment SDK",
+        description: "The current SDK version is deprecated at the end of the quarter.",
+        priority: Priority::Medium,
+        tags: &["payments"],
+        outcome: Outcome::Completed,
+    },
+    DemoTicket {
+        title: "Rewrite the storefront in a new framework",
+        description: "Proposal to replace the storefront templates with a SPA.",
+        priority: Priority::Low,
+        tags: &["frontend"],
+        outcome: Outcome::Stopped,
+    },
+];
+
+/// What was seeded
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DemoSummary {
+    pub project_id: String,
+    pub worker_types: usize,
+    pub workers: usize,
+    pub tickets: usize,
+    pub comments: usize,
+    pub agent_requests: usize,
+    pub worker_runs: usize,
+}
+
+/// Whether the database has no projects, and so nothing demo data could be mixed into
+pub async fn is_empty(pool: &DbPool) -> Result<bool> {
+    Ok(Project::list_all(pool).await?.is_empty())
+}
+
+/// Seed the demo project into an empty database. With `force`, other data is left as is
+/// and an earlier demo project is replaced, so seeding can be repeated.
+pub async fn seed(pool: &DbPool, force: bool) -> Result<DemoSummary> {
+    if !force && !is_empty(pool).await? {
+        bail!("The database already has projects; refusing to add demo data without force");
+    }
+    remove(pool).await?;
+
+    let project = Project::create(
+        pool,
+        CreateProjectRequest {
+            repository_name: DEMO_PROJECT.to_string(),
+            path: std::env::temp_dir()
+                .join("vibe-ensemble-demo")
+                .join(DEMO_PROJECT)
+                .display()
+                .to_string(),
+            short_description: Some("Demo online shop for exploring the dashboard".to_string()),
+            rules: Some("Keep changes small and covered by tests.".to_string()),
+            patterns: None,
+        },
+    )
+    .await?;
+    let mut summary = DemoSummary {
+        project_id: project.repository_name.clone(),
+        worker_types: 0,
+        workers: 0,
+        tickets: 0,
+        comments: 0,
+        agent_requests: 0,
+        worker_runs: 0,
+    };
+
+    for (stage, description) in STAGES {
+        WorkerType::create(
+            pool,
+            CreateWorkerTypeRequest {
+                project_id: project.repository_name.clone(),
+                worker_type: stage.to_string(),
+                short_description: Some(description.to_string()),
+                system_prompt: format!(
+                    "You are the {{worker_type}} of {{project_id}}, working on {{ticket_id}}. {}.",
+                    description
+                ),
+                spawn_overrides: None,
+                capabilities: None,
+            },
+        )
+        .await?;
+        summary.worker_types += 1;
+    }
+
+    let execution_plan: Vec<String> = STAGES.iter().map(|(s, _)| s.to_string()).collect();
+    let subsystem = infer_subsystem_from_stages(&execution_plan);
+    let mut worked = Vec::new();
+    for demo in TICKETS {
+        let ticket_id = generate_ticket_id(pool, &project.project_prefix, &subsystem).await?;
+        let ticket = Ticket::create(
+            pool,
+            CreateTicketRequest {
+                ticket_id,
+                project_id: project.repository_name.clone(),
+                title: demo.title.to_string(),
+                description: demo.description.to_string(),
+                execution_plan: execution_plan.clone(),
+                parent_ticket_id: None,
+                ticket_type: None,
+                dependency_status: None,
+                created_by_worker_id: None,
+                priority: Some(demo.priority.to_string()),
+                custom_fields: None,
+                due_at: None,
+                sla_minutes: None,
+                estimate_minutes: None,
+            },
+        )
+        .await?;
+        summary.tickets += 1;
+        summary.comments += 1;
+
+        let tags: Vec<String> = demo.tags.iter().map(|t| t.to_string()).collect();
+        Tag::set_for_ticket(
+            pool,
+            &ticket.ticket_id,
+            &Tag::resolve(pool, &tags, true).await?,
+        )
+        .await?;
+
+        // Every stage before the ticket's current one leaves a report behind
+        let reached = match demo.outcome {
+            Outcome::Waiting(stage) | Outcome::Worked(stage) => stage,
+            Outcome::OnHold(_) => 1,
+            Outcome::Completed => STAGES.len(),
+            Outcome::Stopped => 1,
+        };
+        for (number, (stage, _)) in STAGES.iter().enumerate().take(reached) {
+            Comment::create_from_request(
+                pool,
+                CreateCommentRequest {
+                    ticket_id: ticket.ticket_id.clone(),
+                    worker_type: stage.to_string(),
+                    worker_id: worker_id(stage, &ticket.ticket_id),
+                    stage_number: number as i32 + 1,
+                    content: stage_report(stage, demo.title),
+                    reply_to_comment_id: None,
+                },
+            )
+            .await?;
+            summary.comments += 1;
+        }
+
+        match demo.outcome {
+            Outcome::Waiting(stage) | Outcome::Worked(stage) => {
+                Ticket::update_stage(pool, &ticket.ticket_id, STAGES[stage].0).await?;
+                if matches!(demo.outcome, Outcome::Worked(_)) {
+                    worked.push((stage, ticket.ticket_id.clone()));
+                }
+            }
+            Outcome::OnHold(reason) => {
+                Ticket::update_stage(pool, &ticket.ticket_id, STAGES[1].0).await?;
+                Ticket::place_on_hold(pool, &ticket.ticket_id, reason).await?;
+                summary.comments += 1;
+            }
+            Outcome::Completed => {
+                Ticket::close_ticket(pool, &ticket.ticket_id, "Completed").await?;
+                summary.comments += 1;
+            }
+            Outcome::Stopped => {
+                Ticket::close_ticket(pool, &ticket.ticket_id, "Stopped").await?;
+                summary.comments += 1;
+            }
+        }
+    }
+
+    // One worker per stage: busy, between tasks and crashed
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    for (stage, ticket_id) in &worked {
+        let worker_type = STAGES[*stage].0;
+        let worker_id = worker_id(worker_type, ticket_id);
+        let status = match *stage {
+            0 => "failed",
+            1 => "active",
+            _ => "idle",
+        };
+        Worker::create(
+            pool,
+            Worker {
+                worker_id: worker_id.clone(),
+                project_id: project.repository_name.clone(),
+                worker_type: worker_type.to_string(),
+                status: status.to_string(),
+                pid: None,
+                queue_name: QueueManager::generate_queue_name(
+                    &project.repository_name,
+                    worker_type,
+                ),
+                started_at: now.clone(),
+                last_activity: now.clone(),
+            },
+        )
+        .await?;
+        summary.workers += 1;
+        if status == "active" {
+            Ticket::claim_for_processing(pool, ticket_id, &worker_id).await?;
+        }
+
+        // Threads between the coordinator and the workers, one per request outcome
+        let question = match status {
+            "active" => "How far along is the search index?",
+            "idle" => "Is the gift card fix safe to release today?",
+            _ => "Why did the order history plan stop halfway?",
+        };
+        let request = AgentRequest::create(
+            pool,
+            "coordinator",
+            &worker_id,
+            Some(ticket_id),
+            &json!({ "question": question }).to_string(),
+            3600,
+        )
+        .await?;
+        summary.agent_requests += 1;
+        match status {
+            "active" => {
+                let answer = json!({ "answer": "Indexing works; wiring up the search box next." });
+                AgentRequest::settle(
+                    pool,
+                    &request.correlation_id,
+                    &worker_id,
+                    RequestStatus::Answered,
+                    &answer.to_string(),
+                )
+                .await?;
+            }
+            "failed" => {
+                let answer = json!({ "reason": "Worker exited before answering" });
+                AgentRequest::settle(
+                    pool,
+                    &request.correlation_id,
+                    &worker_id,
+                    RequestStatus::Rejected,
+                    &answer.to_string(),
+                )
+                .await?;
+            }
+            _ => {}
+        }
+
+        // The busy worker is still running; the crashed one left a failed run behind
+        if status != "idle" {
+            let run_id = WorkerRun::start(
+                pool,
+                &StartRunRequest {
+                    worker_id: &worker_id,
+                    project_id: &project.repository_name,
+                    worker_type,
+                    ticket_id,
+                    working_dir: &project.path,
+                    max_runtime_secs: 3600,
+                    policy_violations: &[],
+                },
+            )
+            .await?;
+            if status == "failed" {
+                WorkerRun::finish(
+                    pool,
+                    run_id,
+                    "failed",
+                    None,
+                    Some("exit status: 1"),
+                    None,
+                    false,
+                )
+                .await?;
+            }
+            summary.worker_runs += 1;
+        }
+    }
+
+    info!(
+        "Seeded demo project '{}' with {} tickets",
+        summary.project_id, summary.tickets
+    );
+    Ok(summary)
+}
+
+/// Remove an earlier demo project along with its runs and requests
+async fn remove(pool: &DbPool) -> Result<()> {
+    if Project::get_by_name(pool, DEMO_PROJECT).await?.is_some() {
+        WorkerRun::delete_for_project(pool, DEMO_PROJECT).await?;
+        AgentRequest::delete_for_project(pool, DEMO_PROJECT).await?;
+        Project::delete(pool, DEMO_PROJECT).await?;
+    }
+    Ok(())
+}
+
+/// Worker ids as the queue consumers build them
+fn worker_id(worker_type: &str, ticket_id: &str) -> String {
+    format!("{}:{}:{}", DEMO_PROJECT, worker_type, ticket_id)
+}
+
+fn stage_report(stage: &str, title: &str) -> String {
+    match stage {
+        "architect" => format!(
+            "Plan for \"{}\": split into a schema change, the handler and tests.",
+            title
+        ),
+        "developer" => format!("Implemented \"{}\" with tests; ready for review.", title),
+        _ => format!("Reviewed \"{}\"; looks good and the tests pass.", title),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{stats::SystemStats, worker_runs::RunFilter};
+    use crate::test_support::{memory_pool, Fixtures};
+
+    #[tokio::test]
+    async fn test_seed_refuses_data_unless_forced() {
+        let pool = memory_pool().await.unwrap();
+        let summary = seed(&pool, false).await.unwrap();
+        assert_eq!(summary.tickets, TICKETS.len());
+        assert_eq!(summary.workers, 3);
+
+        let stats = SystemStats::load(&pool, Some(DEMO_PROJECT)).await.unwrap();
+        assert_eq!(stats.tickets.total, 12);
+        assert_eq!(stats.tickets.by_state["open"], 6);
+        assert_eq!(stats.tickets.by_state["on_hold"], 2);
+        assert_eq!(stats.tickets.by_state["closed"], 4);
+        assert_eq!(stats.workers["active"], 1);
+        assert_eq!(stats.workers["idle"], 1);
+        assert_eq!(stats.workers["failed"], 1);
+        let comments: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM comments")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(comments as usize, summary.comments);
+
+        // A second seed would mix demo data into it
+        assert!(seed(&pool, false).await.is_err());
+
+        // Forcing replaces the demo project and keeps everything else
+        let fx = Fixtures::new(&pool);
+        fx.project("real-project").create().await.unwrap();
+        let again = seed(&pool, true).await.unwrap();
+        assert_eq!(again.tickets, TICKETS.len());
+        assert_eq!(Project::list_all(&pool).await.unwrap().len(), 2);
+        let runs = WorkerRun::list(
+            &pool,
+            &RunFilter {
+                limit: 100,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(runs.len(), again.worker_runs);
+        let requests = AgentRequest::list(&pool, &Default::default())
+            .await
+            .unwrap();
+        assert_eq!(requests.len(), again.agent_requests);
+
+        pool.close().await;
+    }
+}
@@ -0,0 +1,82 @@
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::{
+    database::{
+        digests::{Digest, DigestContent},
+        DbPool,
+    },
+    events::emitter::EventEmitter,
+    sse::EventBroadcaster,
+    workers::claims,
+};
+
+/// Every `interval_mins`, roll up the activity since the previous digest into one digest
+/// notification for the coordinator. Periods with nothing to report send nothing.
+pub fn start_digest_monitor(db: DbPool, broadcaster: EventBroadcaster, interval_mins: u64) {
+    tokio::spawn(async move {
+        let period = Duration::from_secs(interval_mins.max(1) * 60);
+        let mut since = match Digest::last_period_end(&db).await {
+            Ok(Some(end)) => end,
+            _ => match Digest::clock(&db).await {
+                Ok(now) => now,
+                Err(e) => {
+                    warn!("Digests disabled, failed to read the database clock: {}", e);
+                    return;
+                }
+            },
+        };
+
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        loop {
+            interval.tick().await;
+            if let Some(end) = compile_and_send(&db, &broadcaster, &since).await {
+                since = end;
+            }
+        }
+    });
+}
+
+/// Compile the digest for the period after `since`, storing and broadcasting it unless it is
+/// empty; returns where the period ended, or None when it could not be compiled
+async fn compile_and_send(
+    db: &DbPool,
+    broadcaster: &EventBroadcaster,
+    since: &str,
+) -> Option<String> {
+    let until = match Digest::clock(db).await {
+        Ok(until) => until,
+        Err(e) => {
+            warn!("Failed to read the database clock for the digest: {}", e);
+            return None;
+        }
+    };
+    let content = match DigestContent::compile(db, since, &until, claims::take_conflicts()).await {
+        Ok(content) => content,
+        Err(e) => {
+            warn!("Failed to compile digest since {}: {}", since, e);
+            return None;
+        }
+    };
+    if content.is_empty() {
+        debug!(
+            "No activity between {} and {}, skipping digest",
+            since, until
+        );
+        return Some(until);
+    }
+
+    match Digest::create(db, since, &until, &content).await {
+        Ok(digest) => {
+            info!("Digest {}: {}", digest.id, content.headline());
+            if let Err(e) = EventEmitter::new(db, broadcaster)
+                .emit_digest(&digest)
+                .await
+            {
+                warn!("Failed to emit digest {}: {}", digest.id, e);
+            }
+        }
+        Err(e) => warn!("Failed to store digest: {}", e),
+    }
+    Some(until)
+}
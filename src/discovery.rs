@@ -0,0 +1,233 @@
+//! Endpoint discovery file: a running server announces the address it actually bound in
+//! `.vibe-ensemble-mcp/endpoint.json`, so `.mcp.json` can be pointed at it rather than at
+//! whatever port the flags of some other run named.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+use tracing::{info, warn};
+
+use crate::{mcp::build_mcp_config, server_lock::process_alive};
+
+/// Directory of the discovery file, relative to the directory the server runs in
+pub const DISCOVERY_DIR: &str = ".vibe-ensemble-mcp";
+const DISCOVERY_FILE: &str = "endpoint.json";
+const MCP_SERVER_NAME: &str = "vibe-ensemble-mcp";
+
+/// Where a running server can be reached
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointInfo {
+    pub pid: u32,
+    pub host: String,
+    pub port: u16,
+    pub base_path: String,
+    /// Streamable HTTP MCP endpoint, as used in `.mcp.json`
+    pub http_url: String,
+    pub websocket_url: String,
+    pub sse_url: String,
+    pub started_at: String,
+}
+
+impl EndpointInfo {
+    /// This process, bound to `port`
+    pub fn new(host: &str, port: u16, base_path: &str) -> Self {
+        Self {
+            pid: std::process::id(),
+            host: host.to_string(),
+            port,
+            base_path: base_path.to_string(),
+            http_url: format!("http://{}:{}{}/mcp", host, port, base_path),
+            websocket_url: format!("ws://{}:{}{}/", host, port, base_path),
+            sse_url: format!("http://{}:{}{}/sse", host, port, base_path),
+            started_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+pub fn discovery_path(dir: &Path) -> PathBuf {
+    dir.join(DISCOVERY_FILE)
+}
+
+/// The endpoint announced in `dir`, if the server that wrote it is still running. A file
+/// left behind by a process that died without removing it is ignored.
+pub fn read_live(dir: &Path) -> Option<EndpointInfo> {
+    let path = discovery_path(dir);
+    let info: EndpointInfo = serde_json::from_str(&fs::read_to_string(&path).ok()?)
+        .inspect_err(|e| warn!("Ignoring unreadable {}: {}", path.display(), e))
+        .ok()?;
+    if !process_alive(info.pid) {
+        info!(
+            "Ignoring {}: PID {} is no longer running",
+            path.display(),
+            info.pid
+        );
+        return None;
+    }
+    Some(info)
+}
+
+/// Announces this server's endpoint while held; the file is removed when dropped
+#[derive(Debug)]
+pub struct EndpointFile {
+    path: PathBuf,
+}
+
+impl EndpointFile {
+    /// Write the discovery file atomically: readers see the previous file or the whole new
+    /// one, never a partial write
+    pub fn write(dir: &Path, info: &EndpointInfo) -> Result<EndpointFile> {
+        fs::create_dir_all(dir)?;
+        let path = discovery_path(dir);
+        let staging = dir.join(format!(".{}.{}", DISCOVERY_FILE, info.pid));
+        fs::write(&staging, serde_json::to_string_pretty(info)?)
+            .with_context(|| format!("Failed to write {}", staging.display()))?;
+        fs::rename(&staging, &path)
+            .with_context(|| format!("Failed to replace {}", path.display()))?;
+        Ok(EndpointFile { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for EndpointFile {
+    fn drop(&mut self) {
+        // Another instance started in the same directory may have announced itself since
+        let ours = fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<EndpointInfo>(&content).ok())
+            .is_some_and(|info| info.pid == std::process::id());
+        if ours {
+            match fs::remove_file(&self.path) {
+                Ok(()) => info!("Removed endpoint discovery file {}", self.path.display()),
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => warn!("Failed to remove {}: {}", self.path.display(), e),
+            }
+        }
+    }
+}
+
+/// Point the vibe-ensemble-mcp entry of the `.mcp.json` at `path` to `info`, keeping its
+/// other settings and every other server. Returns false when the file does not exist or
+/// already points there.
+pub fn update_mcp_json(path: &Path, info: &EndpointInfo) -> Result<bool> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let mut config: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    let servers = config
+        .as_object_mut()
+        .with_context(|| format!("{} is not a JSON object", path.display()))?
+        .entry("mcpServers")
+        .or_insert_with(|| Value::Object(Default::default()));
+    let servers = servers
+        .as_object_mut()
+        .with_context(|| format!("mcpServers in {} is not an object", path.display()))?;
+
+    match servers.get_mut(MCP_SERVER_NAME) {
+        Some(entry) if entry["url"].as_str() == Some(&info.http_url) => return Ok(false),
+        Some(entry) if entry.is_object() => {
+            entry["url"] = Value::String(info.http_url.clone());
+        }
+        _ => {
+            let fresh = build_mcp_config(&info.host, info.port, &info.base_path);
+            servers.insert(
+                MCP_SERVER_NAME.to_string(),
+                fresh["mcpServers"][MCP_SERVER_NAME].clone(),
+            );
+        }
+    }
+
+    let staging = path.with_extension("json.tmp");
+    fs::write(&staging, serde_json::to_string_pretty(&config)?)?;
+    fs::rename(&staging, path)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vibe-discovery-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_mcp_json_follows_a_server_on_another_port() {
+        let dir = scratch_dir();
+        let mcp_json = dir.join(".mcp.json");
+        // Generated while the server ran on the default port
+        let mut config = build_mcp_config("127.0.0.1", 3276, "");
+        config["mcpServers"]["vibe-ensemble-mcp"]["headers"] = json!({"x-team": "core"});
+        config["mcpServers"]["other"] = json!({"type": "stdio", "command": "other-mcp"});
+        fs::write(&mcp_json, config.to_string()).unwrap();
+
+        // This run had to bind another port
+        let announced =
+            EndpointFile::write(&dir, &EndpointInfo::new("127.0.0.1", 9090, "")).unwrap();
+        let live = read_live(&dir).unwrap();
+        assert_eq!(live.port, 9090);
+        assert_eq!(live.http_url, "http://127.0.0.1:9090/mcp");
+        assert_eq!(live.sse_url, "http://127.0.0.1:9090/sse");
+
+        assert!(update_mcp_json(&mcp_json, &live).unwrap());
+        assert!(!update_mcp_json(&mcp_json, &live).unwrap());
+        let updated: Value = serde_json::from_str(&fs::read_to_string(&mcp_json).unwrap()).unwrap();
+        assert_eq!(
+            updated["mcpServers"]["vibe-ensemble-mcp"]["url"],
+            "http://127.0.0.1:9090/mcp"
+        );
+        assert_eq!(
+            updated["mcpServers"]["vibe-ensemble-mcp"]["headers"]["x-team"],
+            "core"
+        );
+        assert_eq!(updated["mcpServers"]["other"]["command"], "other-mcp");
+
+        // A clean shutdown withdraws the announcement
+        drop(announced);
+        assert!(!discovery_path(&dir).exists());
+        assert!(read_live(&dir).is_none());
+        // Nothing to rewrite without a .mcp.json
+        assert!(!update_mcp_json(&dir.join("missing.json"), &live).unwrap());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_of_a_dead_server_is_ignored() {
+        let dir = scratch_dir();
+        let mut exited = std::process::Command::new("true").spawn().unwrap();
+        let dead_pid = exited.id();
+        exited.wait().unwrap();
+        let stale = EndpointInfo {
+            pid: dead_pid,
+            ..EndpointInfo::new("127.0.0.1", 9090, "")
+        };
+        fs::write(discovery_path(&dir), serde_json::to_string(&stale).unwrap()).unwrap();
+        assert!(read_live(&dir).is_none());
+
+        fs::write(discovery_path(&dir), "{not json").unwrap();
+        assert!(read_live(&dir).is_none());
+
+        // Dropping a file another process wrote leaves it alone
+        let ours = EndpointFile::write(&dir, &EndpointInfo::new("127.0.0.1", 3276, "")).unwrap();
+        fs::write(discovery_path(&dir), serde_json::to_string(&stale).unwrap()).unwrap();
+        drop(ours);
+        assert!(discovery_path(&dir).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
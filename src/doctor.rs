@@ -0,0 +1,474 @@
+use serde_json::Value;
+use sqlx::{sqlite::SqliteConnectOptions, ConnectOptions, Connection};
+use std::{
+    ffi::OsStr,
+    fmt, fs,
+    net::TcpListener,
+    path::{Path, PathBuf},
+};
+
+/// Directory holding logs, worker configs and the default database
+const DATA_DIR: &str = ".vibe-ensemble-mcp";
+/// Claude Code config generated by --configure-claude-code
+const MCP_CONFIG_PATH: &str = ".mcp.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStatus::Pass => write!(f, "✓"),
+            CheckStatus::Warn => write!(f, "⚠"),
+            CheckStatus::Fail => write!(f, "✗"),
+        }
+    }
+}
+
+/// Outcome of a single doctor check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn new(name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Settings the checks validate against, taken from the regular CLI options
+#[derive(Debug, Clone)]
+pub struct DoctorOptions {
+    pub database_path: String,
+    pub host: String,
+    pub port: u16,
+    pub base_path: String,
+}
+
+/// Run every check and return the results in display order
+pub async fn run_checks(options: &DoctorOptions) -> Vec<CheckResult> {
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+
+    let mut results = vec![
+        check_binary("claude", &path_var, true),
+        check_binary("git", &path_var, false),
+        check_directory(Path::new(DATA_DIR)),
+    ];
+    if let Some(parent) = Path::new(&options.database_path)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty() && *p != Path::new(".") && *p != Path::new(DATA_DIR))
+    {
+        results.push(check_directory(parent));
+    }
+    results.push(check_database(Path::new(&options.database_path)).await);
+    results.push(check_attachments(&options.database_path).await);
+    results.push(check_port(&options.host, options.port));
+    results.push(check_mcp_config(
+        Path::new(MCP_CONFIG_PATH),
+        &expected_mcp_url(options),
+    ));
+    results
+}
+
+/// Print a human-readable report; returns true if no check failed
+pub fn print_report(results: &[CheckResult]) -> bool {
+    println!("🩺 Vibe-Ensemble doctor\n");
+
+    let width = results.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    for result in results {
+        println!(
+            "  {} {:<width$}  {}",
+            result.status,
+            result.name,
+            result.detail,
+            width = width
+        );
+    }
+
+    let count = |status| results.iter().filter(|r| r.status == status).count();
+    let failed = count(CheckStatus::Fail);
+    println!(
+        "\n{} passed, {} warnings, {} failed",
+        count(CheckStatus::Pass),
+        count(CheckStatus::Warn),
+        failed
+    );
+    failed == 0
+}
+
+fn expected_mcp_url(options: &DoctorOptions) -> String {
+    format!(
+        "http://{}:{}{}/mcp",
+        options.host, options.port, options.base_path
+    )
+}
+
+/// Locate `name` in the directories of a PATH-style variable
+pub fn find_on_path(name: &str, path_var: &OsStr) -> Option<PathBuf> {
+    let candidates: Vec<String> = if cfg!(windows) {
+        vec![format!("{}.exe", name), format!("{}.cmd", name)]
+    } else {
+        vec![name.to_string()]
+    };
+
+    std::env::split_paths(path_var).find_map(|dir| {
+        candidates
+            .iter()
+            .map(|candidate| dir.join(candidate))
+            .find(|path| path.is_file())
+    })
+}
+
+/// Check that a binary is on PATH; missing required binaries fail, optional ones warn
+pub fn check_binary(name: &str, path_var: &OsStr, required: bool) -> CheckResult {
+    let check = format!("{} binary", name);
+    match find_on_path(name, path_var) {
+        Some(path) => CheckResult::new(
+            &check,
+            CheckStatus::Pass,
+            format!("found at {}", path.display()),
+        ),
+        None if required => CheckResult::new(
+            &check,
+            CheckStatus::Fail,
+            format!("'{}' not found on PATH; workers cannot be spawned", name),
+        ),
+        None => CheckResult::new(
+            &check,
+            CheckStatus::Warn,
+            format!("'{}' not found on PATH", name),
+        ),
+    }
+}
+
+/// Check that a directory exists (or can be created) and is writable
+pub fn check_directory(dir: &Path) -> CheckResult {
+    let check = format!("directory {}", dir.display());
+
+    if !dir.exists() {
+        // The server creates it on startup; make sure that will work
+        let parent = dir
+            .ancestors()
+            .skip(1)
+            .find(|p| !p.as_os_str().is_empty() && p.exists())
+            .unwrap_or(Path::new("."));
+        return match probe_write(parent) {
+            Ok(()) => CheckResult::new(
+                &check,
+                CheckStatus::Warn,
+                "does not exist yet; it will be created on startup",
+            ),
+            Err(e) => CheckResult::new(
+                &check,
+                CheckStatus::Fail,
+                format!("does not exist and cannot be created: {}", e),
+            ),
+        };
+    }
+
+    if !dir.is_dir() {
+        return CheckResult::new(&check, CheckStatus::Fail, "exists but is not a directory");
+    }
+
+    match probe_write(dir) {
+        Ok(()) => CheckResult::new(&check, CheckStatus::Pass, "writable"),
+        Err(e) => CheckResult::new(&check, CheckStatus::Fail, format!("not writable: {}", e)),
+    }
+}
+
+fn probe_write(dir: &Path) -> std::io::Result<()> {
+    let probe = dir.join(format!(".doctor-probe-{}", std::process::id()));
+    fs::write(&probe, b"")?;
+    fs::remove_file(&probe)
+}
+
+/// Open the database read-only and run `PRAGMA integrity_check`
+pub async fn check_database(path: &Path) -> CheckResult {
+    const CHECK: &str = "database";
+
+    if !path.exists() {
+        return CheckResult::new(
+            CHECK,
+            CheckStatus::Warn,
+            format!("{} does not exist yet; it will be created", path.display()),
+        );
+    }
+
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+
+    let mut conn = match options.connect().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            return CheckResult::new(
+                CHECK,
+                CheckStatus::Fail,
+                format!("cannot open {}: {}", path.display(), e),
+            )
+        }
+    };
+
+    let result = sqlx::query_scalar::<_, String>("PRAGMA integrity_check")
+        .fetch_all(&mut conn)
+        .await;
+    let _ = conn.close().await;
+
+    match result.map(crate::backup::integrity_problems) {
+        Ok(problems) if problems.is_empty() => CheckResult::new(
+            CHECK,
+            CheckStatus::Pass,
+            format!("{} passed integrity check", path.display()),
+        ),
+        Ok(problems) => CheckResult::new(
+            CHECK,
+            CheckStatus::Fail,
+            format!(
+                "{} is corrupt: {}",
+                path.display(),
+                problems.into_iter().take(3).collect::<Vec<_>>().join("; ")
+            ),
+        ),
+        Err(e) => CheckResult::new(
+            CHECK,
+            CheckStatus::Fail,
+            format!("integrity check on {} failed: {}", path.display(), e),
+        ),
+    }
+}
+
+/// Compare attachment records with the files in the attachments directory
+pub async fn check_attachments(database_path: &str) -> CheckResult {
+    const CHECK: &str = "attachments";
+
+    let path = Path::new(database_path);
+    if !path.exists() {
+        return CheckResult::new(CHECK, CheckStatus::Pass, "no database yet");
+    }
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    let mut conn = match options.connect().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            return CheckResult::new(
+                CHECK,
+                CheckStatus::Warn,
+                format!("cannot open {}: {}", path.display(), e),
+            )
+        }
+    };
+
+    let dir = crate::attachments::attachments_dir(database_path);
+    let result = crate::attachments::find_orphans(&mut conn, &dir).await;
+    let _ = conn.close().await;
+
+    match result {
+        Ok(orphans) if orphans.is_empty() => {
+            CheckResult::new(CHECK, CheckStatus::Pass, "records and files match")
+        }
+        Ok(orphans) => {
+            let mut examples: Vec<String> = orphans.missing_files.iter().take(3).cloned().collect();
+            examples.extend(
+                orphans
+                    .untracked_files
+                    .iter()
+                    .take(3)
+                    .map(|p| p.display().to_string()),
+            );
+            CheckResult::new(
+                CHECK,
+                CheckStatus::Warn,
+                format!(
+                    "{} record(s) without a file, {} file(s) without a record: {}",
+                    orphans.missing_files.len(),
+                    orphans.untracked_files.len(),
+                    examples.join("; ")
+                ),
+            )
+        }
+        Err(e) => CheckResult::new(
+            CHECK,
+            CheckStatus::Warn,
+            format!(
+                "cannot compare records and files (database not migrated?): {}",
+                e
+            ),
+        ),
+    }
+}
+
+/// Check that the configured address can be bound
+pub fn check_port(host: &str, port: u16) -> CheckResult {
+    let check = format!("port {}", port);
+    match TcpListener::bind((host, port)) {
+        Ok(_) => CheckResult::new(
+            &check,
+            CheckStatus::Pass,
+            format!("{}:{} is available", host, port),
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => CheckResult::new(
+            &check,
+            CheckStatus::Warn,
+            format!(
+                "{}:{} is in use (is the server already running?)",
+                host, port
+            ),
+        ),
+        Err(e) => CheckResult::new(
+            &check,
+            CheckStatus::Fail,
+            format!("cannot bind {}:{}: {}", host, port, e),
+        ),
+    }
+}
+
+/// Check that .mcp.json points Claude Code at the configured server URL
+pub fn check_mcp_config(path: &Path, expected_url: &str) -> CheckResult {
+    const CHECK: &str = ".mcp.json";
+
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return CheckResult::new(
+                CHECK,
+                CheckStatus::Warn,
+                "not found; run --configure-claude-code in your project",
+            )
+        }
+        Err(e) => return CheckResult::new(CHECK, CheckStatus::Fail, format!("cannot read: {}", e)),
+    };
+
+    let config: Value = match serde_json::from_str(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            return CheckResult::new(CHECK, CheckStatus::Fail, format!("invalid JSON: {}", e))
+        }
+    };
+
+    match config["mcpServers"]["vibe-ensemble-mcp"]["url"].as_str() {
+        Some(url) if url == expected_url => {
+            CheckResult::new(CHECK, CheckStatus::Pass, format!("points at {}", url))
+        }
+        Some(url) => CheckResult::new(
+            CHECK,
+            CheckStatus::Fail,
+            format!(
+                "points at {} but the server is configured for {}; rerun --configure-claude-code",
+                url, expected_url
+            ),
+        ),
+        None => CheckResult::new(
+            CHECK,
+            CheckStatus::Fail,
+            "has no vibe-ensemble-mcp server entry; rerun --configure-claude-code",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vibe-doctor-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_binary_lookup() {
+        let dir = scratch_dir();
+        fs::write(dir.join("claude"), b"").unwrap();
+        let path_var = std::env::join_paths([dir.clone()]).unwrap();
+
+        if !cfg!(windows) {
+            assert_eq!(
+                check_binary("claude", &path_var, true).status,
+                CheckStatus::Pass
+            );
+        }
+        assert_eq!(
+            check_binary("missing", &path_var, true).status,
+            CheckStatus::Fail
+        );
+        assert_eq!(
+            check_binary("missing", &path_var, false).status,
+            CheckStatus::Warn
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_mcp_config_consistency() {
+        let dir = scratch_dir();
+        let path = dir.join(".mcp.json");
+        let url = "http://127.0.0.1:3276/mcp";
+
+        assert_eq!(check_mcp_config(&path, url).status, CheckStatus::Warn);
+
+        let config = crate::mcp::build_mcp_config("127.0.0.1", 3276, "");
+        fs::write(&path, config.to_string()).unwrap();
+        assert_eq!(check_mcp_config(&path, url).status, CheckStatus::Pass);
+
+        let stale = crate::mcp::build_mcp_config("127.0.0.1", 4000, "");
+        fs::write(&path, stale.to_string()).unwrap();
+        assert_eq!(check_mcp_config(&path, url).status, CheckStatus::Fail);
+
+        fs::write(&path, "{not json").unwrap();
+        assert_eq!(check_mcp_config(&path, url).status, CheckStatus::Fail);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_database_integrity() {
+        let dir = scratch_dir();
+        let path = dir.join("test.db");
+
+        assert_eq!(check_database(&path).await.status, CheckStatus::Warn);
+
+        let pool = crate::database::create_pool(&format!("sqlite:{}?mode=rwc", path.display()))
+            .await
+            .unwrap();
+        pool.close().await;
+        assert_eq!(check_database(&path).await.status, CheckStatus::Pass);
+
+        // Replace the whole database, including WAL sidecars that still hold valid pages
+        for sidecar in ["test.db-wal", "test.db-shm"] {
+            let _ = fs::remove_file(dir.join(sidecar));
+        }
+        fs::write(
+            &path,
+            b"definitely not a sqlite database file, just junk bytes",
+        )
+        .unwrap();
+        assert_eq!(check_database(&path).await.status, CheckStatus::Fail);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_directory_checks() {
+        let dir = scratch_dir();
+        assert_eq!(check_directory(&dir).status, CheckStatus::Pass);
+        assert_eq!(
+            check_directory(&dir.join("not-yet")).status,
+            CheckStatus::Warn
+        );
+
+        let file = dir.join("file");
+        fs::write(&file, b"").unwrap();
+        assert_eq!(check_directory(&file).status, CheckStatus::Fail);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,6 +1,29 @@
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
+use serde::Serialize;
 use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::database::{quotas::QuotaExceeded, ticket_refs::AmbiguousTicketRef};
+
+/// Body of every REST API error response
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+impl ErrorResponse {
+    pub fn new(error: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+        }
+    }
+}
+
+/// A REST API error status with its [`ErrorResponse`] body
+pub fn error_response(status: StatusCode, error: impl Into<String>) -> Response {
+    (status, axum::Json(ErrorResponse::new(error))).into_response()
+}
 
 #[derive(Debug, thiserror::Error)]
 pub enum AppError {
@@ -28,7 +51,32 @@ pub enum AppError {
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
+        // Typed errors raised inside anyhow-returning code keep their status
+        let error = match self {
+            AppError::Internal(err) => match err.downcast::<AppError>() {
+                Ok(inner) => return inner.into_response(),
+                Err(err) => AppError::Internal(err),
+            },
+            other => other,
+        };
+        if let AppError::Internal(ref err) = error {
+            if let Some(exceeded) = err.downcast_ref::<QuotaExceeded>() {
+                let body = json!({
+                    "error": exceeded.to_string(),
+                    "quota": exceeded,
+                });
+                return (StatusCode::TOO_MANY_REQUESTS, axum::Json(body)).into_response();
+            }
+            if let Some(ambiguous) = err.downcast_ref::<AmbiguousTicketRef>() {
+                let body = json!({
+                    "error": ambiguous.to_string(),
+                    "candidates": ambiguous.candidates,
+                });
+                return (StatusCode::CONFLICT, axum::Json(body)).into_response();
+            }
+        }
+
+        let (status, error_message) = match error {
             AppError::Database(ref err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
             AppError::Json(ref err) => (StatusCode::BAD_REQUEST, err.to_string()),
             AppError::Io(ref err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
@@ -40,11 +88,7 @@ impl IntoResponse for AppError {
             AppError::Internal(ref err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
         };
 
-        let body = json!({
-            "error": error_message
-        });
-
-        (status, axum::Json(body)).into_response()
+        error_response(status, error_message)
     }
 }
 
@@ -0,0 +1,86 @@
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::{
+    config::Config,
+    database::{
+        escalation::{reevaluate, EscalationRules},
+        tickets::Ticket,
+        DbPool,
+    },
+    events::emitter::EventEmitter,
+    server::AppState,
+    sse::EventBroadcaster,
+};
+
+/// Periodically apply the escalation rules to every ticket that is not closed, so the
+/// effective priority follows age, blocked tickets and due dates
+pub fn start_escalation_monitor(
+    db: DbPool,
+    broadcaster: EventBroadcaster,
+    interval_secs: u64,
+    config: watch::Receiver<Config>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            let rules = EscalationRules::from_config(&config.borrow());
+            refresh(&db, &broadcaster, &rules, None).await;
+        }
+    });
+}
+
+/// Re-evaluate the tickets of one project, or of all when `project_id` is None, and emit an
+/// event for each whose effective priority changed. Failures are logged, not returned, so
+/// callers that just changed a ticket are not failed by it.
+pub async fn refresh(
+    db: &DbPool,
+    broadcaster: &EventBroadcaster,
+    rules: &EscalationRules,
+    project_id: Option<&str>,
+) {
+    let changes = match reevaluate(db, rules, project_id).await {
+        Ok(changes) => changes,
+        Err(e) => {
+            warn!("Failed to evaluate ticket escalation: {}", e);
+            return;
+        }
+    };
+
+    let emitter = EventEmitter::new(db, broadcaster);
+    for change in changes {
+        info!(
+            "Effective priority of ticket {} '{}' went from {} to {}",
+            change.ticket_id, change.title, change.from, change.to
+        );
+        if let Err(e) = emitter.emit_effective_priority_changed(&change).await {
+            warn!(
+                "Failed to emit effective priority change of {}: {}",
+                change.ticket_id, e
+            );
+        }
+    }
+}
+
+/// Re-evaluate the project of `ticket_id` right away, after a change that can move the
+/// effective priority of its tickets, such as a dependency added or a ticket closed
+pub async fn refresh_for_ticket(state: &AppState, ticket_id: &str) {
+    let project_id = match Ticket::get_by_id(&state.db, ticket_id).await {
+        Ok(Some(ticket)) => ticket.ticket.project_id,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("Failed to load ticket {} for escalation: {}", ticket_id, e);
+            return;
+        }
+    };
+    let rules = EscalationRules::from_config(&state.live_config.borrow());
+    refresh(
+        &state.db,
+        &state.event_broadcaster,
+        &rules,
+        Some(&project_id),
+    )
+    .await;
+}
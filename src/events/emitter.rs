@@ -3,9 +3,13 @@ use anyhow::Result;
 use serde_json::Value;
 
 use crate::{
-    database::{events::Event, DbPool},
+    database::{
+        digests::Digest, escalation::EscalationChange, events::Event, recovery::StartupRecovery,
+        DbPool,
+    },
     events::{EventPayload, EventType},
     sse::EventBroadcaster,
+    workers::boundary::BoundaryViolation,
 };
 
 /// Central event emitter that handles both DB persistence and SSE broadcasting
@@ -19,86 +23,6 @@ impl<'a> EventEmitter<'a> {
         Self { db, broadcaster }
     }
 
-    /// Emit ticket created event with both DB and SSE
-    pub async fn emit_ticket_created(
-        &self,
-        ticket_id: &str,
-        project_id: &str,
-        title: &str,
-        current_stage: &str,
-    ) -> Result<()> {
-        // Create DB event
-        Event::create(
-            self.db,
-            EventType::TicketCreated,
-            Some(ticket_id),
-            None,
-            Some(current_stage),
-            Some(&format!("Ticket '{}' created", title)),
-        )
-        .await?;
-
-        // Broadcast SSE event
-        let event =
-            EventPayload::ticket_created_with_data(ticket_id, project_id, title, current_stage);
-
-        // Log the complete JSON-RPC message at debug level
-        let jsonrpc_message = event.to_jsonrpc_notification();
-        tracing::debug!(
-            "Broadcasting ticket_created JSON-RPC: {}",
-            serde_json::to_string_pretty(&jsonrpc_message)
-                .unwrap_or_else(|_| "Failed to serialize".to_string())
-        );
-
-        self.broadcaster.broadcast(event);
-
-        tracing::debug!(
-            "Successfully emitted ticket_created event for: {}",
-            ticket_id
-        );
-        Ok(())
-    }
-
-    /// Emit ticket updated event with both DB and SSE
-    pub async fn emit_ticket_updated(
-        &self,
-        ticket_id: &str,
-        project_id: &str,
-        change_type: &str,
-        stage: Option<&str>,
-        reason: Option<&str>,
-    ) -> Result<()> {
-        // Create DB event
-        Event::create(
-            self.db,
-            EventType::TicketUpdated,
-            Some(ticket_id),
-            None,
-            stage,
-            reason,
-        )
-        .await?;
-
-        // Broadcast SSE event
-        let event = EventPayload::ticket_updated(ticket_id, project_id, change_type);
-
-        // Log the complete JSON-RPC message at debug level
-        let jsonrpc_message = event.to_jsonrpc_notification();
-        tracing::debug!(
-            "Broadcasting ticket_updated JSON-RPC: {}",
-            serde_json::to_string_pretty(&jsonrpc_message)
-                .unwrap_or_else(|_| "Failed to serialize".to_string())
-        );
-
-        self.broadcaster.broadcast(event);
-
-        tracing::debug!(
-            "Successfully emitted ticket_updated event for: {}",
-            ticket_id
-        );
-        Ok(())
-    }
-
     /// Emit ticket stage changed event with both DB and SSE
     pub async fn emit_ticket_stage_changed(
         &self,
@@ -142,40 +66,39 @@ impl<'a> EventEmitter<'a> {
         Ok(())
     }
 
-    /// Emit ticket closed event with both DB and SSE
-    pub async fn emit_ticket_closed(
-        &self,
-        ticket_id: &str,
-        project_id: &str,
-        resolution: &str,
-    ) -> Result<()> {
+    /// Emit effective priority changed event with both DB and SSE
+    pub async fn emit_effective_priority_changed(&self, change: &EscalationChange) -> Result<()> {
+        let message = if change.reasons.is_empty() {
+            format!(
+                "Effective priority {} -> {}; no escalation rule applies",
+                change.from, change.to
+            )
+        } else {
+            format!(
+                "Effective priority {} -> {}: {}",
+                change.from,
+                change.to,
+                change.reasons.join(", ")
+            )
+        };
         // Create DB event
         Event::create(
             self.db,
-            EventType::TicketClosed,
-            Some(ticket_id),
+            EventType::EffectivePriorityChanged,
+            Some(&change.ticket_id),
             None,
             None,
-            Some(&format!("Ticket closed with resolution: {}", resolution)),
+            Some(&message),
         )
         .await?;
 
         // Broadcast SSE event
-        let event = EventPayload::ticket_closed(ticket_id, project_id);
-
-        // Log the complete JSON-RPC message at debug level
-        let jsonrpc_message = event.to_jsonrpc_notification();
-        tracing::debug!(
-            "Broadcasting ticket_closed JSON-RPC: {}",
-            serde_json::to_string_pretty(&jsonrpc_message)
-                .unwrap_or_else(|_| "Failed to serialize".to_string())
-        );
-
+        let event = EventPayload::effective_priority_changed(change);
         self.broadcaster.broadcast(event);
 
         tracing::debug!(
-            "Successfully emitted ticket_closed event for: {}",
-            ticket_id
+            "Successfully emitted effective_priority_changed event for: {}",
+            change.ticket_id
         );
         Ok(())
     }
@@ -292,6 +215,85 @@ impl<'a> EventEmitter<'a> {
         Ok(())
     }
 
+    /// Emit project settings updated event (SSE only)
+    pub async fn emit_project_settings_updated(
+        &self,
+        project_id: &str,
+        settings: &Value,
+    ) -> Result<()> {
+        let event = EventPayload::project_settings_updated(project_id, settings);
+        self.broadcaster.broadcast(event);
+
+        tracing::debug!(
+            "Successfully emitted project_settings_updated event for: {}",
+            project_id
+        );
+        Ok(())
+    }
+
+    /// Emit digest event (SSE only; digests are stored in their own table)
+    pub async fn emit_digest(&self, digest: &Digest) -> Result<()> {
+        let event = EventPayload::digest(digest);
+        self.broadcaster.broadcast(event);
+
+        tracing::debug!("Successfully emitted digest event: {}", digest.id);
+        Ok(())
+    }
+
+    /// Emit project quota warning event with both DB and SSE
+    pub async fn emit_project_quota_warning(
+        &self,
+        project_id: &str,
+        quota: &str,
+        usage: u64,
+        limit: u64,
+    ) -> Result<()> {
+        // Create DB event
+        Event::create(
+            self.db,
+            EventType::ProjectQuotaWarning,
+            None,
+            None,
+            None,
+            Some(&format!(
+                "Project '{}' has used {} of its {} {} quota",
+                project_id, usage, limit, quota
+            )),
+        )
+        .await?;
+
+        // Broadcast SSE event
+        let event = EventPayload::project_quota_warning(project_id, quota, usage, limit);
+        self.broadcaster.broadcast(event);
+
+        tracing::debug!(
+            "Successfully emitted project_quota_warning event for: {}",
+            project_id
+        );
+        Ok(())
+    }
+
+    /// Emit recovery completed event with both DB and SSE
+    pub async fn emit_recovery_completed(&self, recovery: &StartupRecovery) -> Result<()> {
+        // Create DB event
+        Event::create(
+            self.db,
+            EventType::RecoveryCompleted,
+            None,
+            None,
+            None,
+            Some(&recovery.summary()),
+        )
+        .await?;
+
+        // Broadcast SSE event
+        let event = EventPayload::recovery_completed(recovery);
+        self.broadcaster.broadcast(event);
+
+        tracing::debug!("Successfully emitted recovery_completed event");
+        Ok(())
+    }
+
     /// Emit stage completed event with both DB and SSE
     pub async fn emit_stage_completed(
         &self,
@@ -508,6 +510,91 @@ impl<'a> EventEmitter<'a> {
         Ok(())
     }
 
+    /// Emit worker boundary violation event with both DB and SSE, naming the paths touched
+    pub async fn emit_worker_boundary_violation(
+        &self,
+        worker_id: &str,
+        worker_type: &str,
+        project_id: &str,
+        ticket_id: &str,
+        violations: &[BoundaryViolation],
+    ) -> Result<()> {
+        // Create DB event
+        let paths: Vec<_> = violations
+            .iter()
+            .map(|violation| violation.path.as_str())
+            .collect();
+        Event::create(
+            self.db,
+            EventType::WorkerBoundaryViolation,
+            Some(ticket_id),
+            Some(worker_id),
+            Some(worker_type),
+            Some(&format!(
+                "Worker {} changed files outside its workspace: {}",
+                worker_id,
+                paths.join(", ")
+            )),
+        )
+        .await?;
+
+        // Broadcast SSE event
+        let event = EventPayload::worker_boundary_violation(
+            worker_id,
+            worker_type,
+            project_id,
+            ticket_id,
+            violations,
+        );
+        self.broadcaster.broadcast(event);
+
+        tracing::debug!(
+            "Successfully emitted worker_boundary_violation event for: {}",
+            worker_id
+        );
+        Ok(())
+    }
+
+    /// Emit worker stalled event with both DB and SSE
+    pub async fn emit_worker_stalled(
+        &self,
+        worker_id: &str,
+        worker_type: &str,
+        project_id: &str,
+        ticket_id: &str,
+        silent_secs: i64,
+    ) -> Result<()> {
+        // Create DB event
+        Event::create(
+            self.db,
+            EventType::WorkerStalled,
+            Some(ticket_id),
+            Some(worker_id),
+            Some(worker_type),
+            Some(&format!(
+                "Worker {} has not reported progress for {} seconds",
+                worker_id, silent_secs
+            )),
+        )
+        .await?;
+
+        // Broadcast SSE event
+        let event = EventPayload::worker_stalled(
+            worker_id,
+            worker_type,
+            project_id,
+            ticket_id,
+            silent_secs,
+        );
+        self.broadcaster.broadcast(event);
+
+        tracing::debug!(
+            "Successfully emitted worker_stalled event for: {}",
+            worker_id
+        );
+        Ok(())
+    }
+
     /// Emit update check started event (SSE only)
     pub async fn emit_update_check_started(&self, current_version: &str) -> Result<()> {
         // Broadcast SSE event
@@ -536,6 +623,7 @@ impl<'a> EventEmitter<'a> {
         current_version: &str,
         latest_version: &str,
         release_url: &str,
+        channel: &str,
     ) -> Result<()> {
         // Create DB event
         Event::create(
@@ -552,7 +640,8 @@ impl<'a> EventEmitter<'a> {
         .await?;
 
         // Broadcast SSE event
-        let event = EventPayload::update_available(current_version, latest_version, release_url);
+        let event =
+            EventPayload::update_available(current_version, latest_version, release_url, channel);
 
         // Log the complete JSON-RPC message at debug level
         let jsonrpc_message = event.to_jsonrpc_notification();
@@ -3,6 +3,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::database::{escalation::EscalationChange, recovery::StartupRecovery};
+
 pub mod emitter;
 
 /// Strongly typed event payload - replaces String-based broadcasts
@@ -22,14 +24,22 @@ pub enum EventType {
     TicketStageChanged,
     TicketClosed,
     TicketUnblocked,
+    TicketSlaBreached,
+    TicketFlaky,
+    TicketBlockEscalated,
+    EffectivePriorityChanged,
     WorkerStarted,
     WorkerCompleted,
     WorkerFailed,
     WorkerStopped,
+    WorkerBoundaryViolation,
+    WorkerStalled,
     WorkerTypeCreated,
     WorkerTypeUpdated,
     WorkerTypeDeleted,
     ProjectCreated,
+    ProjectSettingsUpdated,
+    ProjectQuotaWarning,
     StageCompleted,
     TaskAssigned,
     QueueUpdated,
@@ -39,6 +49,9 @@ pub enum EventType {
     UpdateCheckStarted,
     UpdateAvailable,
     UpdateCheckFailed,
+    CoordinatorLost,
+    Digest,
+    RecoveryCompleted,
 }
 
 impl std::fmt::Display for EventType {
@@ -49,14 +62,22 @@ impl std::fmt::Display for EventType {
             EventType::TicketStageChanged => write!(f, "ticket_stage_changed"),
             EventType::TicketClosed => write!(f, "ticket_closed"),
             EventType::TicketUnblocked => write!(f, "ticket_unblocked"),
+            EventType::TicketSlaBreached => write!(f, "ticket_sla_breached"),
+            EventType::TicketFlaky => write!(f, "ticket_flaky"),
+            EventType::TicketBlockEscalated => write!(f, "ticket_block_escalated"),
+            EventType::EffectivePriorityChanged => write!(f, "effective_priority_changed"),
             EventType::WorkerStarted => write!(f, "worker_started"),
             EventType::WorkerCompleted => write!(f, "worker_completed"),
             EventType::WorkerFailed => write!(f, "worker_failed"),
             EventType::WorkerStopped => write!(f, "worker_stopped"),
+            EventType::WorkerBoundaryViolation => write!(f, "worker_boundary_violation"),
+            EventType::WorkerStalled => write!(f, "worker_stalled"),
             EventType::WorkerTypeCreated => write!(f, "worker_type_created"),
             EventType::WorkerTypeUpdated => write!(f, "worker_type_updated"),
             EventType::WorkerTypeDeleted => write!(f, "worker_type_deleted"),
             EventType::ProjectCreated => write!(f, "project_created"),
+            EventType::ProjectSettingsUpdated => write!(f, "project_settings_updated"),
+            EventType::ProjectQuotaWarning => write!(f, "project_quota_warning"),
             EventType::StageCompleted => write!(f, "stage_completed"),
             EventType::TaskAssigned => write!(f, "task_assigned"),
             EventType::QueueUpdated => write!(f, "queue_updated"),
@@ -66,6 +87,9 @@ impl std::fmt::Display for EventType {
             EventType::UpdateCheckStarted => write!(f, "update_check_started"),
             EventType::UpdateAvailable => write!(f, "update_available"),
             EventType::UpdateCheckFailed => write!(f, "update_check_failed"),
+            EventType::CoordinatorLost => write!(f, "coordinator_lost"),
+            EventType::Digest => write!(f, "digest"),
+            EventType::RecoveryCompleted => write!(f, "recovery_completed"),
         }
     }
 }
@@ -178,6 +202,52 @@ impl EventPayload {
         }
     }
 
+    /// Create a ticket SLA breached event
+    pub fn ticket_sla_breached(ticket_id: &str, project_id: &str, state: &str) -> Self {
+        Self {
+            event_type: EventType::TicketSlaBreached,
+            timestamp: Utc::now(),
+            data: EventData::Ticket(TicketEventData {
+                ticket_id: ticket_id.to_string(),
+                project_id: project_id.to_string(),
+                stage: None,
+                state: Some(state.to_string()),
+                change_type: "sla_breached".to_string(),
+            }),
+        }
+    }
+
+    /// Create a ticket flaky event, for a ticket that keeps being reopened
+    pub fn ticket_flaky(ticket_id: &str, project_id: &str) -> Self {
+        Self {
+            event_type: EventType::TicketFlaky,
+            timestamp: Utc::now(),
+            data: EventData::Ticket(TicketEventData {
+                ticket_id: ticket_id.to_string(),
+                project_id: project_id.to_string(),
+                stage: None,
+                state: None,
+                change_type: "flaky".to_string(),
+            }),
+        }
+    }
+
+    /// Create a ticket block escalated event, for a ticket still blocked after its
+    /// responsible agent was reminded
+    pub fn ticket_block_escalated(ticket_id: &str, project_id: &str) -> Self {
+        Self {
+            event_type: EventType::TicketBlockEscalated,
+            timestamp: Utc::now(),
+            data: EventData::Ticket(TicketEventData {
+                ticket_id: ticket_id.to_string(),
+                project_id: project_id.to_string(),
+                stage: None,
+                state: Some("on_hold".to_string()),
+                change_type: "block_escalated".to_string(),
+            }),
+        }
+    }
+
     /// Create a ticket unblocked event
     pub fn ticket_unblocked(ticket_id: &str, project_id: &str) -> Self {
         Self {
@@ -269,6 +339,90 @@ impl EventPayload {
         }
     }
 
+    /// Create a worker boundary violation event, for a worker that changed files outside
+    /// its workspace
+    pub fn worker_boundary_violation(
+        worker_id: &str,
+        worker_type: &str,
+        project_id: &str,
+        ticket_id: &str,
+        violations: &[crate::workers::boundary::BoundaryViolation],
+    ) -> Self {
+        Self {
+            event_type: EventType::WorkerBoundaryViolation,
+            timestamp: Utc::now(),
+            data: EventData::System(SystemEventData {
+                component: "security".to_string(),
+                message: format!(
+                    "Worker {} changed {} file(s) outside its workspace",
+                    worker_id,
+                    violations.len()
+                ),
+                metadata: Some(serde_json::json!({
+                    "worker_id": worker_id,
+                    "worker_type": worker_type,
+                    "project_id": project_id,
+                    "ticket_id": ticket_id,
+                    "violations": violations
+                })),
+            }),
+        }
+    }
+
+    /// Create an effective priority changed event, for a ticket the escalation rules moved
+    /// up a level or let fall back towards its explicit priority
+    pub fn effective_priority_changed(change: &EscalationChange) -> Self {
+        Self {
+            event_type: EventType::EffectivePriorityChanged,
+            timestamp: Utc::now(),
+            data: EventData::System(SystemEventData {
+                component: "tickets".to_string(),
+                message: format!(
+                    "Effective priority of ticket {} went from {} to {}",
+                    change.ticket_id, change.from, change.to
+                ),
+                metadata: Some(serde_json::json!({
+                    "ticket_id": change.ticket_id,
+                    "project_id": change.project_id,
+                    "priority": change.priority,
+                    "from": change.from,
+                    "to": change.to,
+                    "escalated": change.escalated(),
+                    "reasons": change.reasons
+                })),
+            }),
+        }
+    }
+
+    /// Create a worker stalled event, for a running worker that has not reported progress
+    /// within its stall threshold
+    pub fn worker_stalled(
+        worker_id: &str,
+        worker_type: &str,
+        project_id: &str,
+        ticket_id: &str,
+        silent_secs: i64,
+    ) -> Self {
+        Self {
+            event_type: EventType::WorkerStalled,
+            timestamp: Utc::now(),
+            data: EventData::System(SystemEventData {
+                component: "workers".to_string(),
+                message: format!(
+                    "Worker {} has not reported progress for {} seconds",
+                    worker_id, silent_secs
+                ),
+                metadata: Some(serde_json::json!({
+                    "worker_id": worker_id,
+                    "worker_type": worker_type,
+                    "project_id": project_id,
+                    "ticket_id": ticket_id,
+                    "silent_secs": silent_secs
+                })),
+            }),
+        }
+    }
+
     /// Create a queue update event
     pub fn queue_updated(
         queue_name: &str,
@@ -421,6 +575,43 @@ impl EventPayload {
         }
     }
 
+    /// Create a project settings updated event
+    pub fn project_settings_updated(project_id: &str, settings: &Value) -> Self {
+        Self {
+            event_type: EventType::ProjectSettingsUpdated,
+            timestamp: Utc::now(),
+            data: EventData::System(SystemEventData {
+                component: "project".to_string(),
+                message: format!("Settings of project '{}' updated", project_id),
+                metadata: Some(serde_json::json!({
+                    "project_id": project_id,
+                    "settings": settings
+                })),
+            }),
+        }
+    }
+
+    /// Create a project quota warning event, for a project that used 90% of a quota
+    pub fn project_quota_warning(project_id: &str, quota: &str, usage: u64, limit: u64) -> Self {
+        Self {
+            event_type: EventType::ProjectQuotaWarning,
+            timestamp: Utc::now(),
+            data: EventData::System(SystemEventData {
+                component: "project".to_string(),
+                message: format!(
+                    "Project '{}' has used {} of its {} {} quota",
+                    project_id, usage, limit, quota
+                ),
+                metadata: Some(serde_json::json!({
+                    "project_id": project_id,
+                    "quota": quota,
+                    "usage": usage,
+                    "limit": limit
+                })),
+            }),
+        }
+    }
+
     /// Create a stage completed event
     pub fn stage_completed(ticket_id: &str, stage: &str, worker_id: &str) -> Self {
         Self {
@@ -474,6 +665,7 @@ impl EventPayload {
         current_version: &str,
         latest_version: &str,
         release_url: &str,
+        channel: &str,
     ) -> Self {
         Self {
             event_type: EventType::UpdateAvailable,
@@ -484,7 +676,9 @@ impl EventPayload {
                 metadata: Some(serde_json::json!({
                     "current_version": current_version,
                     "latest_version": latest_version,
-                    "release_url": release_url
+                    "channel": channel,
+                    "release_url": release_url,
+                    "release_notes_url": release_url
                 })),
             }),
         }
@@ -506,6 +700,53 @@ impl EventPayload {
         }
     }
 
+    /// Create a coordinator lost event for a coordinator session that expired or was dropped
+    pub fn coordinator_lost(session_id: &str, transport: &str, reason: &str) -> Self {
+        Self {
+            event_type: EventType::CoordinatorLost,
+            timestamp: Utc::now(),
+            data: EventData::System(SystemEventData {
+                component: "session".to_string(),
+                message: reason.to_string(),
+                metadata: Some(serde_json::json!({
+                    "session_id": session_id,
+                    "transport": transport
+                })),
+            }),
+        }
+    }
+
+    /// Create a digest event carrying a roll-up of recent activity for the coordinator
+    pub fn digest(digest: &crate::database::digests::Digest) -> Self {
+        Self {
+            event_type: EventType::Digest,
+            timestamp: Utc::now(),
+            data: EventData::System(SystemEventData {
+                component: "digest".to_string(),
+                message: format!(
+                    "Activity from {} to {}: {}",
+                    digest.period_start,
+                    digest.period_end,
+                    digest.content.headline()
+                ),
+                metadata: serde_json::to_value(digest).ok(),
+            }),
+        }
+    }
+
+    /// Create a recovery completed event, listing what startup recovery reconciled
+    pub fn recovery_completed(recovery: &StartupRecovery) -> Self {
+        Self {
+            event_type: EventType::RecoveryCompleted,
+            timestamp: Utc::now(),
+            data: EventData::System(SystemEventData {
+                component: "recovery".to_string(),
+                message: recovery.summary(),
+                metadata: serde_json::to_value(recovery).ok(),
+            }),
+        }
+    }
+
     /// Convert to JSON-RPC notification format for SSE events and logging
     pub fn to_jsonrpc_notification(&self) -> Value {
         use crate::mcp::JsonRpcEnvelopes;
@@ -0,0 +1,50 @@
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::warn;
+
+use crate::{
+    config::Config,
+    database::{flaky::FlakyTicket, DbPool},
+    sse::EventBroadcaster,
+};
+
+/// Periodically tag tickets reopened `flaky_reopen_threshold` times and tell the
+/// coordinator about them; a threshold of 0 pauses the check
+pub fn start_flaky_monitor(
+    db: DbPool,
+    broadcaster: EventBroadcaster,
+    interval_secs: u64,
+    config: watch::Receiver<Config>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            let threshold = config.borrow().flaky_reopen_threshold;
+            if threshold > 0 {
+                evaluate(&db, &broadcaster, threshold).await;
+            }
+        }
+    });
+}
+
+async fn evaluate(db: &DbPool, broadcaster: &EventBroadcaster, threshold: u32) {
+    let flaky = match FlakyTicket::mark(db, threshold).await {
+        Ok(flaky) => flaky,
+        Err(e) => {
+            warn!("Failed to evaluate ticket reopens: {}", e);
+            return;
+        }
+    };
+
+    for ticket in &flaky {
+        warn!(
+            "Ticket {} '{}' has been reopened {} times",
+            ticket.ticket_id, ticket.title, ticket.reopen_count
+        );
+    }
+    // ticket_flaky was queued with each tag
+    if !flaky.is_empty() {
+        broadcaster.notify_outbox();
+    }
+}
@@ -1,17 +1,44 @@
+pub mod agent_requests;
 pub mod api;
+pub mod attachments;
+pub mod audit;
 pub mod auth;
+pub mod backup;
+pub mod blocks;
 pub mod config;
 pub mod configure;
+pub mod cron;
+pub mod crypto;
 pub mod dashboard;
 pub mod database;
+pub mod demo;
+pub mod digest;
+pub mod discovery;
+pub mod doctor;
 pub mod error;
+pub mod escalation;
 pub mod events;
+pub mod flaky;
 pub mod jbct;
 pub mod lockfile;
+pub mod logging;
 pub mod mcp;
+pub mod outbox;
 pub mod permissions;
+pub mod quotas;
+pub mod redaction;
+pub mod reload;
+pub mod schedules;
 pub mod server;
+pub mod server_lock;
+pub mod sla;
 pub mod sse;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+pub mod ticket_import;
+pub mod timeline;
 pub mod updates;
 pub mod validation;
+pub mod web;
+pub mod webhooks;
 pub mod workers;
@@ -0,0 +1,194 @@
+use anyhow::{anyhow, bail, Result};
+use serde::Serialize;
+use std::{
+    str::FromStr,
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+use tracing::info;
+use tracing_subscriber::{filter::LevelFilter, reload, EnvFilter, Registry};
+use utoipa::ToSchema;
+
+/// Longest a filter set at runtime may stay in effect before it reverts
+pub const MAX_FILTER_TTL_SECS: u64 = 24 * 60 * 60;
+
+static CONTROL: OnceLock<LogControl> = OnceLock::new();
+
+/// Log filter in effect, reported by /health and /api/admin/log-level
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LogFilterStatus {
+    pub filter: String,
+    /// Filter from RUST_LOG or --log-level, restored when a runtime filter expires
+    pub default_filter: String,
+    pub reverts_at: Option<String>,
+}
+
+/// Swaps the filter of the installed subscriber
+pub struct LogControl {
+    handle: reload::Handle<EnvFilter, Registry>,
+    state: Mutex<ControlState>,
+}
+
+struct ControlState {
+    status: LogFilterStatus,
+    /// Bumped on every change so an expired TTL doesn't revert a newer filter
+    generation: u64,
+}
+
+/// Startup filter: RUST_LOG when it parses, otherwise `log_level`
+pub fn default_filter(log_level: &str) -> String {
+    std::env::var(EnvFilter::DEFAULT_ENV)
+        .ok()
+        .filter(|directives| EnvFilter::try_new(directives).is_ok())
+        .unwrap_or_else(|| log_level.to_string())
+}
+
+/// Parse a level (`debug`) or EnvFilter directives (`vibe_ensemble_mcp=trace,sqlx=warn`).
+/// Unlike `EnvFilter::new`, invalid directives are an error rather than skipped, and a
+/// directive without `=` must be a level so a typo doesn't turn into a target name.
+pub fn parse_filter(directives: &str) -> Result<EnvFilter> {
+    let directives = directives.trim();
+    if directives.is_empty() {
+        bail!("Log filter must not be empty");
+    }
+    for directive in directives.split(',') {
+        if !directive.contains('=') && LevelFilter::from_str(directive.trim()).is_err() {
+            bail!(
+                "Invalid log filter directive '{}': expected a level (trace, debug, info, warn, \
+                 error, off) or target=level",
+                directive.trim()
+            );
+        }
+    }
+    EnvFilter::builder()
+        .parse(directives)
+        .map_err(|e| anyhow!("Invalid log filter '{}': {}", directives, e))
+}
+
+/// Filter layer for the global subscriber, registered for runtime control. Call once.
+pub fn install(default: &str) -> reload::Layer<EnvFilter, Registry> {
+    let (layer, handle) = reload::Layer::new(EnvFilter::new(default));
+    let _ = CONTROL.set(LogControl::new(handle, default));
+    layer
+}
+
+/// None when no subscriber was installed through [`install`]
+pub fn status() -> Option<LogFilterStatus> {
+    CONTROL.get().map(LogControl::status)
+}
+
+pub fn control() -> Option<&'static LogControl> {
+    CONTROL.get()
+}
+
+impl LogControl {
+    fn new(handle: reload::Handle<EnvFilter, Registry>, default: &str) -> Self {
+        Self {
+            handle,
+            state: Mutex::new(ControlState {
+                status: LogFilterStatus {
+                    filter: default.to_string(),
+                    default_filter: default.to_string(),
+                    reverts_at: None,
+                },
+                generation: 0,
+            }),
+        }
+    }
+
+    pub fn status(&self) -> LogFilterStatus {
+        self.state.lock().unwrap().status.clone()
+    }
+
+    /// Apply `directives`, reverting to the default filter after `ttl` when given
+    pub fn set(&'static self, directives: &str, ttl: Option<Duration>) -> Result<LogFilterStatus> {
+        if let Some(ttl) = ttl {
+            if ttl.is_zero() || ttl.as_secs() > MAX_FILTER_TTL_SECS {
+                bail!("TTL must be between 1 and {} seconds", MAX_FILTER_TTL_SECS);
+            }
+        }
+        let filter = parse_filter(directives)?;
+
+        let mut state = self.state.lock().unwrap();
+        self.handle
+            .reload(filter)
+            .map_err(|e| anyhow!("Failed to apply log filter: {}", e))?;
+        state.generation += 1;
+        state.status.filter = directives.trim().to_string();
+        state.status.reverts_at = ttl.map(|ttl| {
+            (chrono::Utc::now() + chrono::Duration::seconds(ttl.as_secs() as i64)).to_rfc3339()
+        });
+        info!(
+            "Log filter set to '{}'{}",
+            state.status.filter,
+            state
+                .status
+                .reverts_at
+                .as_deref()
+                .map(|at| format!(" until {}", at))
+                .unwrap_or_default()
+        );
+
+        if let Some(ttl) = ttl {
+            let generation = state.generation;
+            tokio::spawn(async move {
+                tokio::time::sleep(ttl).await;
+                self.revert(generation);
+            });
+        }
+        Ok(state.status.clone())
+    }
+
+    /// Restore the default filter unless it was changed again after `generation`
+    fn revert(&self, generation: u64) {
+        let mut state = self.state.lock().unwrap();
+        if state.generation != generation {
+            return;
+        }
+        let default = state.status.default_filter.clone();
+        if self.handle.reload(EnvFilter::new(&default)).is_ok() {
+            state.generation += 1;
+            state.status.filter = default;
+            state.status.reverts_at = None;
+            info!("Log filter reverted to '{}'", state.status.filter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filter() {
+        assert!(parse_filter("debug").is_ok());
+        assert!(parse_filter("vibe_ensemble_mcp=trace,sqlx=warn").is_ok());
+        assert!(parse_filter("info,tower_http=debug").is_ok());
+        assert!(parse_filter("  ").is_err());
+        assert!(parse_filter("verbose").is_err());
+        assert!(parse_filter("sqlx=loud").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_filter_reverts_after_ttl() {
+        let (_layer, handle) = reload::Layer::new(EnvFilter::new("info"));
+        let control: &'static LogControl = Box::leak(Box::new(LogControl::new(handle, "info")));
+
+        assert!(control.set("loud", None).is_err());
+        let status = control
+            .set("debug,sqlx=warn", Some(Duration::from_secs(1)))
+            .unwrap();
+        assert_eq!(status.filter, "debug,sqlx=warn");
+        assert!(status.reverts_at.is_some());
+
+        // A newer change isn't undone by the TTL of the previous one
+        control.set("trace", Some(Duration::from_secs(2))).unwrap();
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(control.status().filter, "trace");
+
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+        let status = control.status();
+        assert_eq!(status.filter, "info");
+        assert!(status.reverts_at.is_none());
+    }
+}
@@ -1,20 +1,81 @@
 use anyhow::Result;
-use clap::Parser;
-use tracing::info;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+use clap::{Parser, Subcommand};
+use std::path::Path;
+use tracing::{info, warn};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
 use vibe_ensemble_mcp::{
-    config::Config, configure::configure_claude_code, permissions::PermissionMode,
+    backup,
+    config::{Config, ConfigFile},
+    configure::configure_claude_code,
+    crypto::{self, Keyring},
+    database::{
+        create_pool, encryption,
+        migrations::{self, MigrationState},
+        open_pool,
+        recovery::{OrphanedTicketPolicy, TicketRecovery},
+    },
+    demo,
+    doctor::{self, DoctorOptions},
+    logging,
+    mcp::limits::OverflowPolicy,
+    permissions::PermissionMode,
+    redaction::Redacting,
+    reload,
     server::run_server,
+    server_lock::ServerLock,
+    updates::ReleaseChannel,
+    web::normalize_base_path,
 };
 
+/// Log file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum LogFormat {
+    /// Human-readable lines
+    Text,
+    /// One JSON object per line, including span fields such as trace_id
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Inspect or apply database migrations, then exit
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+    /// Re-encrypt stored messages with the current message key, then exit
+    Rekey {
+        /// Rows re-encrypted per transaction
+        #[arg(long, default_value = "500")]
+        batch_size: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum MigrateAction {
+    /// List applied and pending migrations with their checksums
+    Status,
+    /// Apply pending migrations
+    Run,
+    /// Exit non-zero if the database schema is newer than this build supports
+    Check,
+}
+
 #[derive(Parser)]
 #[command(name = "vibe-ensemble-mcp")]
 #[command(about = "A multi-agent coordination MCP server")]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Configure Claude Code integration (generates .mcp.json and .claude/ files)
     #[arg(long)]
     configure_claude_code: bool,
 
+    /// Check the environment (binaries, directories, database, port, .mcp.json) and exit
+    #[arg(long)]
+    doctor: bool,
+
     /// Database file path
     #[arg(long, default_value = "./.vibe-ensemble-mcp/vibe-ensemble.db")]
     database_path: String,
@@ -31,10 +92,42 @@ struct Args {
     #[arg(long, default_value = "info")]
     log_level: String,
 
+    /// Format of the log files in .vibe-ensemble-mcp/logs; console output stays human-readable
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// Regex of secrets to mask in logs and dashboard comments, in addition to the built-in
+    /// API key and token formats; repeat for several
+    #[arg(long = "redact-pattern")]
+    redact_patterns: Vec<String>,
+
+    /// Also mask secrets in comments before they are stored; the original text is not kept
+    #[arg(long)]
+    redact_at_rest: bool,
+
+    /// Encrypt comments and agent request payloads and responses before they are stored,
+    /// with keys from --message-key-file or VIBE_ENSEMBLE_MESSAGE_KEYS
+    #[arg(long)]
+    encrypt_messages: bool,
+
+    /// File of base64 message keys, one per line, current key first; older keys after it
+    /// stay readable
+    #[arg(long)]
+    message_key_file: Option<String>,
+
     /// Disable automatic respawning of workers on startup for unfinished tasks
     #[arg(long)]
     no_respawn: bool,
 
+    /// Do not apply pending migrations on startup; refuse to start until `migrate run` has
+    #[arg(long)]
+    no_migrate: bool,
+
+    /// What startup recovery does with open tickets a worker held when the server last
+    /// stopped: `release` queues them again, `review` blocks them on the coordinator
+    #[arg(long, value_enum, default_value_t = OrphanedTicketPolicy::Release)]
+    orphaned_tickets: OrphanedTicketPolicy,
+
     /// Permission mode for worker processes
     #[arg(long, default_value_t = PermissionMode::File)]
     permission_mode: PermissionMode,
@@ -55,6 +148,10 @@ struct Args {
     #[arg(long)]
     disable_update_checks: bool,
 
+    /// Release channel for update checks; `beta` also offers pre-releases
+    #[arg(long, value_enum, default_value_t = ReleaseChannel::Stable)]
+    release_channel: ReleaseChannel,
+
     /// Upgrade to the latest version
     #[arg(long)]
     upgrade: bool,
@@ -62,6 +159,243 @@ struct Args {
     /// Model name to use for workers
     #[arg(long)]
     model: Option<String>,
+
+    /// Path to a JSON file with per-role MCP tool allowlists
+    #[arg(long)]
+    tool_policy: Option<String>,
+
+    /// Path to a JSON file with the worker process sandbox policy
+    #[arg(long)]
+    worker_policy: Option<String>,
+
+    /// Number of days to keep audit log entries (0 keeps them forever)
+    #[arg(long, default_value = "90")]
+    audit_retention_days: u64,
+
+    /// Days to keep answered, rejected and expired agent requests (0 keeps them forever);
+    /// projects can override this in their settings
+    #[arg(long, default_value = "0")]
+    message_retention_days: u64,
+
+    /// Tickets a project may have that are not closed (0 for unlimited); projects can
+    /// override this in their settings
+    #[arg(long, default_value = "5000")]
+    max_open_tickets: u64,
+
+    /// Comments a project's tickets may receive per hour (0 for unlimited); projects can
+    /// override this in their settings
+    #[arg(long, default_value = "1000")]
+    max_comments_per_hour: u64,
+
+    /// Maximum number of worker processes running at once (0 for unlimited)
+    #[arg(long, default_value = "4")]
+    max_concurrent_workers: usize,
+
+    /// Maximum number of worker processes running at once per project (0 for unlimited)
+    #[arg(long, default_value = "2")]
+    max_workers_per_project: usize,
+
+    /// Seconds a queued worker spawn waits before moving up one priority level (0 disables aging)
+    #[arg(long, default_value = "300")]
+    spawn_priority_aging_secs: u64,
+
+    /// Idle workers kept started per project and worker type, so spawns skip the cold start (0 disables)
+    #[arg(long, default_value = "0")]
+    warm_pool_size: usize,
+
+    /// Seconds an idle warm worker is kept before it is stopped and replaced
+    #[arg(long, default_value = "900")]
+    warm_pool_max_idle_secs: u64,
+
+    /// Worker types kept warm, comma separated (default: all)
+    #[arg(long, value_delimiter = ',')]
+    warm_pool_worker_types: Vec<String>,
+
+    /// Reject initialize requests with unknown metadata fields instead of warning
+    #[arg(long)]
+    strict_client_metadata: bool,
+
+    /// Reject unknown tags on tickets instead of creating them
+    #[arg(long)]
+    strict_tags: bool,
+
+    /// TTL for cached project and worker type lookups in seconds (0 disables caching)
+    #[arg(long, default_value = "30")]
+    cache_ttl_secs: u64,
+
+    /// Log repository queries slower than this many milliseconds (0 disables it)
+    #[arg(long, default_value = "100")]
+    slow_query_ms: u64,
+
+    /// Log the query plan of each slow query once
+    #[arg(long)]
+    explain_slow_queries: bool,
+
+    /// How often to check tickets against their due dates, in seconds
+    #[arg(long, default_value = "60")]
+    sla_check_interval_secs: u64,
+
+    /// Warn about tickets due within this many minutes (0 disables warnings)
+    #[arg(long, default_value = "30")]
+    sla_warning_minutes: u64,
+
+    /// Tag tickets reopened this many times as flaky and notify the coordinator
+    /// (0 disables it)
+    #[arg(long, default_value = "3")]
+    flaky_reopen_threshold: u32,
+
+    /// Tell the coordinator about blocked tickets this many minutes after their responsible
+    /// agent was reminded of the expected unblock date (0 disables escalation)
+    #[arg(long, default_value = "60")]
+    block_escalation_mins: u64,
+
+    /// Raise the effective priority of tickets in the same state for this many hours
+    /// (0 disables the rule)
+    #[arg(long, default_value = "72")]
+    escalation_age_hours: u64,
+
+    /// Raise the effective priority of tickets one level for every this many open tickets
+    /// they block (0 disables the rule)
+    #[arg(long, default_value = "2")]
+    escalation_dependents: u64,
+
+    /// Send the coordinator a digest of recent activity every this many minutes (0 disables digests)
+    #[arg(long, default_value = "10")]
+    digest_interval_mins: u64,
+
+    /// Origins allowed to make cross-origin (credentialed) requests, comma separated
+    /// (default: any origin, without credentials)
+    #[arg(long, value_delimiter = ',')]
+    allowed_origins: Vec<String>,
+
+    /// Path prefix to mount all routes under when running behind a reverse proxy (e.g. /vibe)
+    #[arg(long, default_value = "")]
+    base_path: String,
+
+    /// Honor X-Forwarded-Proto/X-Forwarded-For from a reverse proxy in request logs
+    #[arg(long)]
+    trust_proxy_headers: bool,
+
+    /// Write a database backup to the backups directory next to the database and exit
+    #[arg(long)]
+    backup_now: bool,
+
+    /// Replace the database with this backup before starting (the old file is kept aside)
+    #[arg(long)]
+    restore_from: Option<String>,
+
+    /// Back up the database every N hours while running (0 disables scheduled backups)
+    #[arg(long, default_value = "0")]
+    backup_interval_hours: u64,
+
+    /// Number of backups to keep when pruning scheduled backups
+    #[arg(long, default_value = "7")]
+    backup_retention: usize,
+
+    /// Largest inbound WebSocket message or HTTP request body in bytes
+    #[arg(long, default_value = "1048576")]
+    max_message_bytes: usize,
+
+    /// Largest ticket attachment in bytes, for uploads and the add_ticket_attachment tool
+    #[arg(long, default_value = "10485760")]
+    max_attachment_bytes: usize,
+
+    /// Content types accepted for attachments, comma separated; `type/*` matches a whole family
+    /// (default: text, JSON, PDF, archives and common image formats)
+    #[arg(long, value_delimiter = ',')]
+    attachment_content_types: Vec<String>,
+
+    /// Largest CSV file accepted by the ticket import endpoint, in bytes
+    #[arg(long, default_value = "5242880")]
+    max_import_bytes: usize,
+
+    /// Most data rows accepted in one ticket import
+    #[arg(long, default_value = "5000")]
+    max_import_rows: usize,
+
+    /// Outbound notifications queued per WebSocket connection before the overflow policy applies (0 for unlimited)
+    #[arg(long, default_value = "256")]
+    max_queued_notifications: usize,
+
+    /// What to do when a connection's notification queue is full
+    #[arg(long, value_enum, default_value_t = OverflowPolicy::DropOldest)]
+    notification_overflow_policy: OverflowPolicy,
+
+    /// Seconds an HTTP JSON-RPC request may take before it fails with a timeout error (0 disables)
+    #[arg(long, default_value = "120")]
+    request_timeout_secs: u64,
+
+    /// Seconds without activity after which an MCP session is expired and its connection closed (0 disables)
+    #[arg(long, default_value = "86400")]
+    session_idle_timeout_secs: u64,
+
+    /// Seconds open connections get to finish after SIGINT or SIGTERM before the server exits
+    #[arg(long, default_value = "10")]
+    shutdown_grace_secs: u64,
+
+    /// Load a demo project with tickets, workers and requests before starting; refuses a
+    /// database that already has projects unless --force is given
+    #[arg(long)]
+    seed_demo_data: bool,
+
+    /// With --seed-demo-data, seed a database that has other projects, replacing an
+    /// earlier demo project
+    #[arg(long, requires = "seed_demo_data")]
+    force: bool,
+
+    /// Enable POST /api/admin/seed-demo, which loads the same demo data at runtime
+    #[arg(long)]
+    allow_demo_seeding: bool,
+
+    /// Failed attempts after which a webhook delivery is given up as dead
+    #[arg(long, default_value = "8")]
+    webhook_max_attempts: u32,
+
+    /// Path to a JSON file of static REST API tokens and their roles (viewer, operator or
+    /// admin), sent as `Authorization: Bearer <token>` to the REST API and MCP endpoints.
+    /// Once any is configured, requests without a token are viewers
+    #[arg(long)]
+    api_tokens: Option<String>,
+
+    /// Treat REST API and dashboard requests without a token as viewers, which may only
+    /// read; without this they are admins until a token is configured
+    #[arg(long)]
+    dashboard_read_only: bool,
+
+    /// Start new tickets in the worker type whose capabilities best match their tags;
+    /// projects can override this with their auto_assign setting
+    #[arg(long)]
+    auto_assign: bool,
+
+    /// Share of a ticket's tags a worker type's capabilities must cover to be assigned it
+    /// automatically (0 to 1)
+    #[arg(long, default_value = "0.5")]
+    auto_assign_min_score: f64,
+
+    /// Open tickets a worker type may already have before automatic assignment passes it
+    /// over (0 for unlimited)
+    #[arg(long, default_value = "5")]
+    auto_assign_max_open_tickets: usize,
+
+    /// Refuse to send tickets to a worker type that is busy at its max_open_tickets or
+    /// reported in maintenance or error; without this the assignment goes ahead with a warning
+    #[arg(long)]
+    refuse_assignments_at_capacity: bool,
+
+    /// Serve the OpenAPI document of the REST API at /api/openapi.json and a Swagger UI
+    /// for it at /api/docs
+    #[arg(long)]
+    api_docs: bool,
+
+    /// Once the port is bound, rewrite the vibe-ensemble-mcp entry of an existing .mcp.json
+    /// in the working directory to it, keeping the other servers in the file
+    #[arg(long)]
+    update_mcp_json: bool,
+
+    /// JSON file of settings replacing the matching flags; it is read again on SIGHUP and
+    /// `POST /api/admin/reload-config`, which apply the reloadable ones without a restart
+    #[arg(long)]
+    config: Option<String>,
 }
 
 #[tokio::main]
@@ -73,15 +407,52 @@ async fn main() -> Result<()> {
         return handle_upgrade();
     }
 
+    let base_path = normalize_base_path(&args.base_path)?;
+    if !(0.0..=1.0).contains(&args.auto_assign_min_score) {
+        anyhow::bail!(
+            "--auto-assign-min-score must be between 0 and 1, got {}",
+            args.auto_assign_min_score
+        );
+    }
+
+    if args.doctor {
+        let options = DoctorOptions {
+            database_path: args.database_path,
+            host: args.host,
+            port: args.port,
+            base_path,
+        };
+        let results = doctor::run_checks(&options).await;
+        if !doctor::print_report(&results) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    match args.command {
+        Some(Command::Migrate { action }) => {
+            return handle_migrate(action, &args.database_path).await
+        }
+        Some(Command::Rekey { batch_size }) => {
+            return handle_rekey(
+                &args.database_path,
+                args.message_key_file.as_deref(),
+                batch_size,
+            )
+            .await
+        }
+        None => {}
+    }
+
     // Handle configuration mode
     if args.configure_claude_code {
-        configure_claude_code(&args.host, args.port, args.permission_mode).await?;
+        configure_claude_code(&args.host, args.port, &base_path, args.permission_mode).await?;
         return Ok(());
     }
 
     // Initialize tracing with both console and file logging
-    let env_filter =
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&args.log_level));
+    // The filter can be changed at runtime through /api/admin/log-level
+    let log_filter = logging::install(&logging::default_filter(&args.log_level));
 
     // Create logs directory
     let logs_dir = std::path::Path::new(".vibe-ensemble-mcp/logs");
@@ -92,14 +463,22 @@ async fn main() -> Result<()> {
 
     // Guard is kept alive by the variable scope and will be properly cleaned up on exit
 
+    let file_layer = match args.log_format {
+        LogFormat::Text => tracing_subscriber::fmt::layer()
+            .with_writer(Redacting(non_blocking))
+            .with_ansi(false)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_writer(Redacting(non_blocking))
+            .boxed(),
+    };
+
+    // Both layers mask secrets; the patterns from the flags apply once the server starts
     tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer().with_filter(env_filter.clone()))
-        .with(
-            tracing_subscriber::fmt::layer()
-                .with_writer(non_blocking)
-                .with_ansi(false)
-                .with_filter(env_filter),
-        )
+        .with(log_filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(Redacting(std::io::stdout)))
+        .with(file_layer)
         .init();
 
     info!("Starting Vibe-Ensemble MCP Server");
@@ -108,25 +487,251 @@ async fn main() -> Result<()> {
     info!("Server: {}:{}", args.host, args.port);
     info!("Permission mode: {}", args.permission_mode.as_str());
     info!("Respawn disabled: {}", args.no_respawn);
+    info!("Migrations disabled: {}", args.no_migrate);
 
-    let config = Config {
+    let mut config = Config {
         database_path: args.database_path,
         host: args.host,
         port: args.port,
         no_respawn: args.no_respawn,
+        no_migrate: args.no_migrate,
+        orphaned_tickets: args.orphaned_tickets,
+        skip_recovery: false,
         permission_mode: args.permission_mode,
         client_tool_timeout_secs: args.client_tool_timeout_secs,
         max_concurrent_client_requests: args.max_concurrent_client_requests,
         update_check_interval_hours: args.update_check_interval_hours,
         disable_update_checks: args.disable_update_checks,
+        release_channel: args.release_channel,
         model: args.model,
+        tool_policy_path: args.tool_policy,
+        worker_policy_path: args.worker_policy,
+        audit_retention_days: args.audit_retention_days,
+        message_retention_days: args.message_retention_days,
+        max_open_tickets: args.max_open_tickets,
+        max_comments_per_hour: args.max_comments_per_hour,
+        max_concurrent_workers: args.max_concurrent_workers,
+        max_workers_per_project: args.max_workers_per_project,
+        spawn_priority_aging_secs: args.spawn_priority_aging_secs,
+        warm_pool_size: args.warm_pool_size,
+        warm_pool_max_idle_secs: args.warm_pool_max_idle_secs,
+        warm_pool_worker_types: args.warm_pool_worker_types,
+        strict_client_metadata: args.strict_client_metadata,
+        strict_tags: args.strict_tags,
+        cache_ttl_secs: args.cache_ttl_secs,
+        slow_query_ms: args.slow_query_ms,
+        explain_slow_queries: args.explain_slow_queries,
+        sla_check_interval_secs: args.sla_check_interval_secs,
+        sla_warning_minutes: args.sla_warning_minutes,
+        flaky_reopen_threshold: args.flaky_reopen_threshold,
+        block_escalation_mins: args.block_escalation_mins,
+        escalation_age_hours: args.escalation_age_hours,
+        escalation_dependents: args.escalation_dependents,
+        digest_interval_mins: args.digest_interval_mins,
+        allowed_origins: args.allowed_origins,
+        base_path,
+        trust_proxy_headers: args.trust_proxy_headers,
+        backup_interval_hours: args.backup_interval_hours,
+        backup_retention: args.backup_retention,
+        max_message_bytes: args.max_message_bytes,
+        max_attachment_bytes: args.max_attachment_bytes,
+        attachment_content_types: if args.attachment_content_types.is_empty() {
+            Config::default().attachment_content_types
+        } else {
+            args.attachment_content_types
+        },
+        max_import_bytes: args.max_import_bytes,
+        max_import_rows: args.max_import_rows,
+        redact_patterns: args.redact_patterns,
+        redact_at_rest: args.redact_at_rest,
+        encrypt_messages: args.encrypt_messages,
+        message_key_file: args.message_key_file,
+        max_queued_notifications: args.max_queued_notifications,
+        notification_overflow_policy: args.notification_overflow_policy,
+        request_timeout_secs: args.request_timeout_secs,
+        session_idle_timeout_secs: args.session_idle_timeout_secs,
+        shutdown_grace_secs: args.shutdown_grace_secs,
+        allow_demo_seeding: args.allow_demo_seeding,
+        webhook_max_attempts: args.webhook_max_attempts,
+        api_tokens_path: args.api_tokens,
+        dashboard_read_only: args.dashboard_read_only,
+        auto_assign: args.auto_assign,
+        auto_assign_min_score: args.auto_assign_min_score,
+        auto_assign_max_open_tickets: args.auto_assign_max_open_tickets,
+        refuse_assignments_at_capacity: args.refuse_assignments_at_capacity,
+        api_docs: args.api_docs,
+        update_mcp_json: args.update_mcp_json,
+        log_filter: None,
+        config_path: args.config,
     };
 
+    // Settings in the config file take precedence over the flags
+    if let Some(path) = config.config_path.clone() {
+        ConfigFile::load(&path)?.apply(&mut config);
+        config.base_path = normalize_base_path(&config.base_path)?;
+        reload::check_settings(&config)?;
+        info!("Loaded settings from {}", path);
+    }
+    if let (Some(filter), Some(control)) = (&config.log_filter, logging::control()) {
+        control.set(filter, None)?;
+    }
+
+    // One server per database file. A one-off backup can run next to a live server, but
+    // a restore must not replace the database under it.
+    let _server_lock = if args.backup_now && args.restore_from.is_none() {
+        None
+    } else {
+        Some(ServerLock::acquire(
+            &config.database_path,
+            &config.host,
+            config.port,
+        )?)
+    };
+
+    // Restoring replaces the database file, so it must happen before the pool opens
+    if let Some(source) = &args.restore_from {
+        if let Some(previous) =
+            backup::restore_from(Path::new(source), Path::new(&config.database_path)).await?
+        {
+            info!("Previous database kept at {}", previous.display());
+        }
+    }
+
+    if args.backup_now {
+        let pool = create_pool(&config.database_url()).await?;
+        let backup = backup::create_backup(&pool, &backup::backup_dir(&config.database_path)).await;
+        pool.close().await;
+        let backup = backup?;
+        println!(
+            "✓ Backup written to {} ({} bytes)",
+            backup.path, backup.size_bytes
+        );
+        return Ok(());
+    }
+
+    if args.seed_demo_data {
+        let pool = create_pool(&config.database_url()).await?;
+        // Recover from an earlier crash first, so the demo's busy workers are left running
+        let summary = async {
+            let recovery =
+                TicketRecovery::recover_after_restart(&pool, config.orphaned_tickets).await?;
+            if !recovery.is_empty() {
+                warn!("{}", recovery.summary());
+            }
+            demo::seed(&pool, args.force).await
+        }
+        .await;
+        pool.close().await;
+        let summary = summary?;
+        println!(
+            "✓ Seeded demo project '{}': {} tickets, {} worker types, {} workers, {} comments, {} requests, {} worker runs",
+            summary.project_id,
+            summary.tickets,
+            summary.worker_types,
+            summary.workers,
+            summary.comments,
+            summary.agent_requests,
+            summary.worker_runs
+        );
+        println!("  Dashboard: {}/dashboard", config.http_base_url());
+        // Demo tickets are not real work, so no workers are spawned for them
+        config.no_respawn = true;
+        config.skip_recovery = true;
+    }
+
     run_server(config).await?;
 
     Ok(())
 }
 
+async fn handle_rekey(database_path: &str, key_file: Option<&str>, batch_size: i64) -> Result<()> {
+    let Some(keyring) = Keyring::load(key_file)? else {
+        anyhow::bail!(
+            "rekey needs message keys: set {} or --message-key-file",
+            crypto::KEYS_ENV
+        );
+    };
+    let pool = create_pool(&format!("sqlite:{}?mode=rwc", database_path)).await?;
+    let report = match encryption::verify_keys(&pool, Some(&keyring)).await {
+        Ok(()) => encryption::rekey(&pool, &keyring, batch_size).await,
+        Err(e) => Err(e),
+    };
+    pool.close().await;
+    let report = report?;
+    println!(
+        "✓ Re-encrypted {} comments, {} request payloads, {} responses and {} thread summaries with key {} in {} batches",
+        report.comments,
+        report.request_payloads,
+        report.request_responses,
+        report.thread_summaries,
+        keyring.current().id(),
+        report.batches
+    );
+    Ok(())
+}
+
+async fn handle_migrate(action: MigrateAction, database_path: &str) -> Result<()> {
+    let url = format!("sqlite:{}?mode=rwc", database_path);
+    match action {
+        MigrateAction::Run => {
+            let pool = create_pool(&url).await?;
+            pool.close().await;
+            println!(
+                "✓ Database at {} is at schema version {}",
+                database_path,
+                migrations::latest_migration_version()
+            );
+        }
+        MigrateAction::Status | MigrateAction::Check => {
+            // Inspecting must not create a database that does not exist yet
+            if !Path::new(database_path).exists() {
+                println!(
+                    "No database at {}; all migrations up to version {} are pending",
+                    database_path,
+                    migrations::latest_migration_version()
+                );
+                return Ok(());
+            }
+            let pool = open_pool(&url).await?;
+            let statuses = migrations::migration_status(&pool).await;
+            let newer = migrations::check_not_newer(&pool).await;
+            pool.close().await;
+            let statuses = statuses?;
+
+            if matches!(action, MigrateAction::Status) {
+                println!(
+                    "{:>8}  {:<17}  {:<16}  {:<19}  DESCRIPTION",
+                    "VERSION", "STATE", "CHECKSUM", "APPLIED"
+                );
+                for status in &statuses {
+                    println!(
+                        "{:>8}  {:<17}  {:<16}  {:<19}  {}",
+                        status.version,
+                        status.state.as_str(),
+                        &status.checksum[..status.checksum.len().min(16)],
+                        status.installed_on.as_deref().unwrap_or("-"),
+                        status.description
+                    );
+                }
+            }
+
+            if let Err(e) = newer {
+                eprintln!("✗ {}", e);
+                std::process::exit(1);
+            }
+            let pending = statuses
+                .iter()
+                .filter(|s| s.state == MigrationState::Pending)
+                .count();
+            println!(
+                "✓ Schema is supported by this build ({} pending migration(s))",
+                pending
+            );
+        }
+    }
+    Ok(())
+}
+
 fn handle_upgrade() -> Result<()> {
     println!("Starting upgrade process...");
 
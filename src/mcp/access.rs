@@ -0,0 +1,187 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::api::access::ApiCaller;
+
+/// Identity of the party issuing an MCP request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolCaller {
+    Coordinator,
+    Worker {
+        worker_id: String,
+        worker_type: String,
+    },
+}
+
+impl ToolCaller {
+    /// The caller an authenticated request comes from: the worker its token was issued to,
+    /// or the coordinator for every other token
+    pub fn of(caller: &ApiCaller) -> Self {
+        match &caller.worker {
+            Some(worker) => ToolCaller::Worker {
+                worker_id: worker.worker_id.clone(),
+                worker_type: worker.worker_type.clone(),
+            },
+            None => ToolCaller::Coordinator,
+        }
+    }
+
+    /// Identifier recorded as the actor in audit entries
+    pub fn actor_id(&self) -> &str {
+        match self {
+            ToolCaller::Coordinator => "coordinator",
+            ToolCaller::Worker { worker_id, .. } => worker_id,
+        }
+    }
+
+    pub fn role(&self) -> &'static str {
+        match self {
+            ToolCaller::Coordinator => "coordinator",
+            ToolCaller::Worker { .. } => "worker",
+        }
+    }
+}
+
+/// Tool allowlist configuration, keyed by caller role with optional per-type and per-worker overrides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolAccessPolicy {
+    #[serde(default = "default_coordinator_tools")]
+    pub coordinator: Vec<String>,
+    #[serde(default = "default_worker_tools")]
+    pub worker: Vec<String>,
+    #[serde(default)]
+    pub worker_types: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub workers: HashMap<String, Vec<String>>,
+}
+
+fn default_coordinator_tools() -> Vec<String> {
+    vec!["*".to_string()]
+}
+
+/// Read-only lookups plus commenting, attaching, agent requests and agent group membership,
+/// which is all a worker needs to report progress, and estimating and closing the ticket it
+/// is processing; those tools refuse workers any other ticket
+fn default_worker_tools() -> Vec<String> {
+    [
+        "get_*",
+        "list_*",
+        "add_ticket_comment",
+        "set_ticket_estimate",
+        "close_ticket",
+        "add_ticket_attachment",
+        "block_ticket",
+        "unblock_ticket",
+        "report_worker_progress",
+        "load_worker_template",
+        "send_agent_request",
+        "respond_to_request",
+        "acknowledge_request",
+        "join_agent_group",
+        "leave_agent_group",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+impl Default for ToolAccessPolicy {
+    fn default() -> Self {
+        Self {
+            coordinator: default_coordinator_tools(),
+            worker: default_worker_tools(),
+            worker_types: HashMap::new(),
+            workers: HashMap::new(),
+        }
+    }
+}
+
+impl ToolAccessPolicy {
+    /// Load policy from a JSON file
+    pub fn load(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read tool policy file: {}", path))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse tool policy file: {}", path))
+    }
+
+    /// Patterns applicable to the caller; the most specific configured entry wins
+    fn patterns_for(&self, caller: &ToolCaller) -> &[String] {
+        match caller {
+            ToolCaller::Coordinator => &self.coordinator,
+            ToolCaller::Worker {
+                worker_id,
+                worker_type,
+            } => self
+                .workers
+                .get(worker_id)
+                .or_else(|| self.worker_types.get(worker_type))
+                .unwrap_or(&self.worker),
+        }
+    }
+
+    pub fn is_allowed(&self, caller: &ToolCaller, tool_name: &str) -> bool {
+        let name = tool_name
+            .strip_prefix(super::constants::MCP_TOOL_PREFIX)
+            .unwrap_or(tool_name);
+        self.patterns_for(caller)
+            .iter()
+            .any(|pattern| matches_pattern(pattern, name))
+    }
+}
+
+/// Match a name against a pattern supporting a single `*` wildcard
+pub(crate) fn matches_pattern(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, suffix)) => {
+            name.len() >= prefix.len() + suffix.len()
+                && name.starts_with(prefix)
+                && name.ends_with(suffix)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worker(id: &str, worker_type: &str) -> ToolCaller {
+        ToolCaller::Worker {
+            worker_id: id.to_string(),
+            worker_type: worker_type.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_default_policy() {
+        let policy = ToolAccessPolicy::default();
+        let caller = worker("w1", "implementation");
+
+        assert!(policy.is_allowed(&ToolCaller::Coordinator, "delete_project"));
+        assert!(policy.is_allowed(&caller, "get_ticket"));
+        assert!(policy.is_allowed(&caller, "mcp__vibe-ensemble-mcp__list_tickets"));
+        assert!(policy.is_allowed(&caller, "add_ticket_comment"));
+        assert!(!policy.is_allowed(&caller, "delete_project"));
+        assert!(policy.is_allowed(&caller, "close_ticket"));
+        assert!(!policy.is_allowed(&caller, "mark_ticket_duplicate"));
+        assert!(!policy.is_allowed(&caller, "vibe_debug_connections"));
+    }
+
+    #[test]
+    fn test_overrides_take_precedence() {
+        let mut policy = ToolAccessPolicy::default();
+        policy
+            .worker_types
+            .insert("planning".to_string(), vec!["*_ticket".to_string()]);
+        policy
+            .workers
+            .insert("w2".to_string(), vec!["close_ticket".to_string()]);
+
+        assert!(policy.is_allowed(&worker("w1", "planning"), "create_ticket"));
+        assert!(!policy.is_allowed(&worker("w1", "planning"), "list_projects"));
+        assert!(policy.is_allowed(&worker("w2", "planning"), "close_ticket"));
+        assert!(!policy.is_allowed(&worker("w2", "planning"), "create_ticket"));
+    }
+}
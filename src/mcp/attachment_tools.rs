@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine};
+use serde_json::{json, Value};
+
+use super::{
+    tools::{
+        create_json_error_response, create_json_success_response, extract_optional_param,
+        extract_param, ToolHandler,
+    },
+    types::{CallToolResponse, Tool},
+};
+use crate::{
+    attachments::AttachmentStore, database::project_settings::ProjectSettings, error::Result,
+    server::AppState,
+};
+
+pub struct AddTicketAttachmentTool;
+
+#[async_trait]
+impl ToolHandler for AddTicketAttachmentTool {
+    async fn call(&self, state: &AppState, arguments: Option<Value>) -> Result<CallToolResponse> {
+        let ticket_id: String = extract_param(&arguments, "ticket_id")?;
+        let filename: String = extract_param(&arguments, "filename")?;
+        let content_base64: String = extract_param(&arguments, "content_base64")?;
+        let content_type: Option<String> = extract_optional_param(&arguments, "content_type")?;
+        let worker_id: Option<String> = extract_optional_param(&arguments, "worker_id")?;
+
+        let Some((settings, claim)) = ProjectSettings::for_ticket(&state.db, &ticket_id).await?
+        else {
+            return Ok(create_json_error_response(&format!(
+                "Ticket '{}' not found",
+                ticket_id
+            )));
+        };
+        if let Some(denied) = settings.edit_denied(
+            &ticket_id,
+            claim.as_deref(),
+            worker_id.as_deref().unwrap_or_default(),
+        ) {
+            return Ok(create_json_error_response(&denied));
+        }
+        // Refuse before decoding when the encoded size alone shows the file is too big
+        if content_base64.len() / 4 * 3 > state.config.max_attachment_bytes + 3 {
+            return Ok(create_json_error_response(&format!(
+                "Attachment exceeds the {} byte limit",
+                state.config.max_attachment_bytes
+            )));
+        }
+        let content = match general_purpose::STANDARD.decode(content_base64.trim()) {
+            Ok(content) => content,
+            Err(e) => {
+                return Ok(create_json_error_response(&format!(
+                    "content_base64 is not valid base64: {}",
+                    e
+                )))
+            }
+        };
+
+        match AttachmentStore::new(&state.config)
+            .save(
+                &state.db,
+                &ticket_id,
+                &filename,
+                content_type.as_deref(),
+                &content,
+                worker_id.as_deref(),
+            )
+            .await
+        {
+            Ok(attachment) => Ok(create_json_success_response(json!({
+                "attachment": attachment
+            }))),
+            Err(e) => Ok(create_json_error_response(&e.to_string())),
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "add_ticket_attachment".to_string(),
+            description: "Attach a file such as a test log or screenshot to a ticket. The content is base64 encoded; the size limit and allowed content types are the same as for uploads through the REST API".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "ticket_id": {
+                        "type": "string",
+                        "description": "Ticket to attach the file to"
+                    },
+                    "filename": {
+                        "type": "string",
+                        "description": "File name shown to readers and used when downloading"
+                    },
+                    "content_base64": {
+                        "type": "string",
+                        "description": "File content, base64 encoded"
+                    },
+                    "content_type": {
+                        "type": "string",
+                        "description": "Optional MIME type; guessed from the file name if omitted"
+                    },
+                    "worker_id": {
+                        "type": "string",
+                        "description": "Identifier of the uploading worker; required to hold the ticket's claim when the project sets required_lock_for_edit"
+                    }
+                },
+                "required": ["ticket_id", "filename", "content_base64"]
+            }),
+        }
+    }
+}
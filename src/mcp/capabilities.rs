@@ -0,0 +1,109 @@
+use serde_json::Value;
+use tracing::debug;
+
+use super::types::ClientCapabilities;
+
+/// Capabilities a client declared in its initialize params; missing or malformed
+/// capabilities count as none declared
+pub fn declared(params: Option<&Value>) -> ClientCapabilities {
+    params
+        .and_then(|params| params.get("capabilities"))
+        .and_then(|capabilities| serde_json::from_value(capabilities.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Whether a client may be sent the server-initiated message `method`.
+///
+/// Messages tied to an optional capability only go to clients that declared it; a
+/// client that has not initialized declared nothing. Other notifications, such as
+/// vibe events, are always allowed.
+pub fn permits(capabilities: Option<&ClientCapabilities>, method: &str, client: &str) -> bool {
+    let declared: fn(&ClientCapabilities) -> bool = match method {
+        "notifications/message" => |c| c.logging.as_ref().is_some_and(|l| l.enabled),
+        "notifications/resources/updated" => |c| c.resources.as_ref().is_some_and(|r| r.subscribe),
+        "notifications/resources/list_changed" => {
+            |c| c.resources.as_ref().is_some_and(|r| r.list_changed)
+        }
+        "notifications/tools/list_changed" => |c| c.tools.list_changed,
+        "sampling/createMessage" => |c| c.sampling.as_ref().is_some_and(|s| s.enabled),
+        _ => return true,
+    };
+
+    if capabilities.is_some_and(declared) {
+        return true;
+    }
+    debug!(
+        "Suppressing {} for client {}: capability not declared",
+        method, client
+    );
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::{server::McpServer, tools::ToolRegistry};
+    use serde_json::json;
+
+    fn initialize_params(capabilities: Value) -> ClientCapabilities {
+        declared(Some(&json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": capabilities,
+            "clientInfo": {"name": "test", "version": "1.0"}
+        })))
+    }
+
+    #[test]
+    fn test_advertised_capabilities_follow_configured_features() {
+        let advertised = serde_json::to_value(McpServer::default().capabilities()).unwrap();
+        assert_eq!(advertised["tools"], json!({"listChanged": false}));
+        assert_eq!(advertised["prompts"], json!({"listChanged": false}));
+        assert_eq!(advertised["resources"]["subscribe"], true);
+
+        let without_tools = McpServer {
+            tools: ToolRegistry::new(),
+            ..McpServer::default()
+        };
+        let advertised = serde_json::to_value(without_tools.capabilities()).unwrap();
+        assert!(advertised.get("tools").is_none());
+        assert!(advertised.get("prompts").is_some());
+    }
+
+    #[test]
+    fn test_notifications_suppressed_without_declared_capability() {
+        let logging = "notifications/message";
+        let updated = "notifications/resources/updated";
+        let sampling = "sampling/createMessage";
+
+        let none = initialize_params(json!({}));
+        assert!(!permits(Some(&none), logging, "c"));
+        assert!(!permits(Some(&none), updated, "c"));
+        assert!(!permits(Some(&none), sampling, "c"));
+        assert!(permits(Some(&none), "notifications/events", "c"));
+
+        let full = initialize_params(json!({
+            "logging": {"enabled": true},
+            "resources": {"subscribe": true},
+            "sampling": {"enabled": true}
+        }));
+        assert!(permits(Some(&full), logging, "c"));
+        assert!(permits(Some(&full), updated, "c"));
+        assert!(permits(Some(&full), sampling, "c"));
+        assert!(!permits(
+            Some(&full),
+            "notifications/resources/list_changed",
+            "c"
+        ));
+
+        // Only the declared half of a mixed client gets through
+        let resources_only = initialize_params(json!({"resources": {"subscribe": true}}));
+        assert!(!permits(Some(&resources_only), logging, "c"));
+        assert!(permits(Some(&resources_only), updated, "c"));
+
+        // Uninitialized clients and malformed capabilities declare nothing
+        assert!(!permits(None, logging, "c"));
+        assert!(permits(None, "notifications/events", "c"));
+        let malformed = initialize_params(json!({"logging": "yes"}));
+        assert!(!permits(Some(&malformed), logging, "c"));
+    }
+}
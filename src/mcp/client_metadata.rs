@@ -0,0 +1,189 @@
+use serde_json::{Map, Value};
+
+use super::constants::SUPPORTED_PROTOCOL_VERSIONS;
+
+const KNOWN_TOP_LEVEL_KEYS: &[&str] = &[
+    "protocolVersion",
+    "protocol_version",
+    "capabilities",
+    "clientInfo",
+    "client_info",
+    "_meta",
+];
+
+const KNOWN_CAPABILITY_KEYS: &[&str] = &[
+    "tools",
+    "sampling",
+    "logging",
+    "resources",
+    "roots",
+    "prompts",
+    "elicitation",
+    "experimental",
+];
+
+/// Result of validating client-supplied initialize metadata
+#[derive(Debug, Default, PartialEq)]
+pub struct MetadataValidation {
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+impl MetadataValidation {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Validate initialize params before deserialization.
+///
+/// Unknown keys are reported as warnings so older and newer clients keep working;
+/// in strict mode they become errors instead.
+pub fn validate_initialize_params(params: &Value, strict: bool) -> MetadataValidation {
+    let mut result = MetadataValidation::default();
+
+    let Some(map) = params.as_object() else {
+        result
+            .errors
+            .push("initialize params must be an object".to_string());
+        return result;
+    };
+
+    let unknown = |message: String, result: &mut MetadataValidation| {
+        if strict {
+            result.errors.push(message);
+        } else {
+            result.warnings.push(message);
+        }
+    };
+
+    for key in map.keys() {
+        if !KNOWN_TOP_LEVEL_KEYS.contains(&key.as_str()) {
+            unknown(format!("unknown field '{}'", key), &mut result);
+        }
+    }
+
+    match field(map, "protocolVersion", "protocol_version") {
+        None => result
+            .errors
+            .push("missing required field 'protocolVersion'".to_string()),
+        Some(Value::String(version)) => {
+            if !SUPPORTED_PROTOCOL_VERSIONS.contains(&version.as_str()) {
+                unknown(
+                    format!(
+                        "unsupported protocolVersion '{}' (supported: {})",
+                        version,
+                        SUPPORTED_PROTOCOL_VERSIONS.join(", ")
+                    ),
+                    &mut result,
+                );
+            }
+        }
+        Some(_) => result
+            .errors
+            .push("field 'protocolVersion' must be a string".to_string()),
+    }
+
+    match field(map, "clientInfo", "client_info") {
+        None => result
+            .errors
+            .push("missing required field 'clientInfo'".to_string()),
+        Some(Value::Object(info)) => {
+            for key in ["name", "version"] {
+                match info.get(key) {
+                    Some(Value::String(_)) => {}
+                    Some(_) => result
+                        .errors
+                        .push(format!("field 'clientInfo.{}' must be a string", key)),
+                    None => result
+                        .errors
+                        .push(format!("missing required field 'clientInfo.{}'", key)),
+                }
+            }
+        }
+        Some(_) => result
+            .errors
+            .push("field 'clientInfo' must be an object".to_string()),
+    }
+
+    match map.get("capabilities") {
+        None | Some(Value::Null) => {}
+        Some(Value::Object(caps)) => {
+            for (key, value) in caps {
+                if !KNOWN_CAPABILITY_KEYS.contains(&key.as_str()) {
+                    unknown(
+                        format!("unknown capability 'capabilities.{}'", key),
+                        &mut result,
+                    );
+                } else if !value.is_object() && !value.is_null() {
+                    result.errors.push(format!(
+                        "capability 'capabilities.{}' must be an object",
+                        key
+                    ));
+                }
+            }
+        }
+        Some(_) => result
+            .errors
+            .push("field 'capabilities' must be an object".to_string()),
+    }
+
+    result
+}
+
+fn field<'a>(map: &'a Map<String, Value>, name: &str, alias: &str) -> Option<&'a Value> {
+    map.get(name).or_else(|| map.get(alias))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_minimal_metadata_is_accepted() {
+        let params = json!({
+            "protocolVersion": "2024-11-05",
+            "clientInfo": { "name": "legacy", "version": "0.1" }
+        });
+        assert_eq!(
+            validate_initialize_params(&params, true),
+            MetadataValidation::default()
+        );
+    }
+
+    #[test]
+    fn test_unknown_fields_warn_unless_strict() {
+        let params = json!({
+            "protocol_version": "2024-11-05",
+            "client_info": { "name": "c", "version": "1" },
+            "capabilities": { "tools": {}, "telepathy": {} },
+            "sesion_type": "worker"
+        });
+
+        let lenient = validate_initialize_params(&params, false);
+        assert!(lenient.is_valid());
+        assert_eq!(lenient.warnings.len(), 2);
+
+        let strict = validate_initialize_params(&params, true);
+        assert_eq!(strict.errors.len(), 2);
+    }
+
+    #[test]
+    fn test_shape_errors_are_specific() {
+        let params = json!({
+            "protocolVersion": 20241105,
+            "clientInfo": { "name": "c" },
+            "capabilities": { "tools": true }
+        });
+        let result = validate_initialize_params(&params, false);
+        assert_eq!(
+            result.errors,
+            vec![
+                "field 'protocolVersion' must be a string",
+                "missing required field 'clientInfo.version'",
+                "capability 'capabilities.tools' must be an object",
+            ]
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+use super::{
+    limits::{self, LimitStats},
+    session::{CloseClass, SessionPhase, Traffic, Transport},
+    types::ClientInfo,
+};
+use crate::server::AppState;
+
+/// Metadata keys containing any of these have their values masked
+const SENSITIVE_KEY_PARTS: &[&str] = &[
+    "token",
+    "secret",
+    "password",
+    "passwd",
+    "key",
+    "auth",
+    "cookie",
+    "credential",
+    "session",
+];
+
+const MASK: &str = "***";
+
+/// A live MCP connection, as reported by `vibe_debug_connections`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ConnectionInfo {
+    pub session_id: String,
+    pub transport: Transport,
+    pub phase: SessionPhase,
+    /// Worker the session is bound to, or "coordinator"
+    pub agent: String,
+    pub client_info: Option<ClientInfo>,
+    pub protocol_version: Option<String>,
+    pub traffic: Traffic,
+    pub connected_at: DateTime<Utc>,
+    pub last_activity: DateTime<Utc>,
+    pub idle_secs: u64,
+    /// Notifications waiting to be sent; None while nothing is listening for them
+    pub queued_notifications: Option<usize>,
+    /// `_meta` the client sent at initialize, with sensitive values masked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<Value>,
+}
+
+/// Connection counters since startup
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ConnectionTotals {
+    pub opened: u64,
+    pub active: usize,
+    pub closed: BTreeMap<CloseClass, u64>,
+    pub expired: u64,
+    pub dropped: u64,
+    pub limits: LimitStats,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ConnectionReport {
+    pub connections: Vec<ConnectionInfo>,
+    pub totals: ConnectionTotals,
+}
+
+/// Every tracked session with its traffic and queue depth, oldest first
+pub fn snapshot(state: &AppState) -> ConnectionReport {
+    let sessions = state.mcp_server.sessions.list();
+    let stats = state.mcp_server.sessions.stats();
+    let connections: Vec<ConnectionInfo> = sessions
+        .into_iter()
+        .map(|session| {
+            let queued_notifications = match session.transport {
+                Transport::WebSocket => state
+                    .websocket_manager
+                    .queued_notifications(&session.session_id),
+                Transport::Http => state.event_stream.queued_for(&session.session_id),
+            };
+            ConnectionInfo {
+                agent: session
+                    .worker_id
+                    .unwrap_or_else(|| "coordinator".to_string()),
+                queued_notifications,
+                metadata: session.metadata.map(|mut metadata| {
+                    mask_sensitive(&mut metadata);
+                    metadata
+                }),
+                session_id: session.session_id,
+                transport: session.transport,
+                phase: session.phase,
+                client_info: session.client_info,
+                protocol_version: session.protocol_version,
+                traffic: session.traffic,
+                connected_at: session.connected_at,
+                last_activity: session.last_activity,
+                idle_secs: session.idle_secs,
+            }
+        })
+        .collect();
+
+    ConnectionReport {
+        totals: ConnectionTotals {
+            opened: stats.opened,
+            active: connections.len(),
+            closed: stats.closed,
+            expired: stats.expired,
+            dropped: stats.dropped,
+            limits: limits::stats(),
+        },
+        connections,
+    }
+}
+
+/// Replace the values of sensitive-looking keys, at any depth, with a mask
+pub fn mask_sensitive(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_ascii_lowercase();
+                if SENSITIVE_KEY_PARTS.iter().any(|part| key.contains(part)) {
+                    *value = Value::String(MASK.to_string());
+                } else {
+                    mask_sensitive(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(mask_sensitive),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_mask_sensitive_metadata() {
+        let mut metadata = json!({
+            "client": "inspector",
+            "apiKey": "sk-123",
+            "auth": {"user": "me"},
+            "proxies": [{"host": "localhost", "Password": "hunter2"}],
+            "limits": {"max_tokens": 100}
+        });
+        mask_sensitive(&mut metadata);
+        assert_eq!(
+            metadata,
+            json!({
+                "client": "inspector",
+                "apiKey": "***",
+                "auth": "***",
+                "proxies": [{"host": "localhost", "Password": "***"}],
+                "limits": {"max_tokens": "***"}
+            })
+        );
+    }
+}
@@ -1,9 +1,15 @@
 /// Centralized constants and helpers for MCP protocol
 use serde_json::{json, Value};
+use std::sync::OnceLock;
+
+use super::server::McpServer;
 
 /// MCP Protocol Version - single source of truth
 pub const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
 
+/// Client protocol versions accepted during initialization
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+
 /// JSON-RPC envelope builders to ensure consistency
 pub struct JsonRpcEnvelopes;
 
@@ -156,64 +162,47 @@ impl JsonRpcEnvelopes {
     }
 }
 
-/// Complete list of MCP tools available on the server
-/// This must be kept in sync with the tools registered in server.rs
+/// Prefix Claude Code gives the tools of this server
+pub const MCP_TOOL_PREFIX: &str = "mcp__vibe-ensemble-mcp__";
+
+/// Complete list of MCP tools available on the server, as Claude Code names them.
+/// Derived from the tool registry, so a newly registered tool is included.
 pub fn get_all_mcp_tool_names() -> Vec<String> {
-    vec![
-        // Project management tools
-        "mcp__vibe-ensemble-mcp__create_project".to_string(),
-        "mcp__vibe-ensemble-mcp__list_projects".to_string(),
-        "mcp__vibe-ensemble-mcp__get_project".to_string(),
-        "mcp__vibe-ensemble-mcp__update_project".to_string(),
-        "mcp__vibe-ensemble-mcp__delete_project".to_string(),
-        // Worker type management tools
-        "mcp__vibe-ensemble-mcp__create_worker_type".to_string(),
-        "mcp__vibe-ensemble-mcp__list_worker_types".to_string(),
-        "mcp__vibe-ensemble-mcp__get_worker_type".to_string(),
-        "mcp__vibe-ensemble-mcp__update_worker_type".to_string(),
-        "mcp__vibe-ensemble-mcp__delete_worker_type".to_string(),
-        // Ticket management tools
-        "mcp__vibe-ensemble-mcp__create_ticket".to_string(),
-        "mcp__vibe-ensemble-mcp__get_ticket".to_string(),
-        "mcp__vibe-ensemble-mcp__list_tickets".to_string(),
-        "mcp__vibe-ensemble-mcp__add_ticket_comment".to_string(),
-        "mcp__vibe-ensemble-mcp__close_ticket".to_string(),
-        "mcp__vibe-ensemble-mcp__resume_ticket_processing".to_string(),
-        // Dependency management tools
-        "mcp__vibe-ensemble-mcp__add_ticket_dependency".to_string(),
-        "mcp__vibe-ensemble-mcp__remove_ticket_dependency".to_string(),
-        "mcp__vibe-ensemble-mcp__get_dependency_graph".to_string(),
-        "mcp__vibe-ensemble-mcp__list_ready_tickets".to_string(),
-        "mcp__vibe-ensemble-mcp__list_blocked_tickets".to_string(),
-        // Event and stage management tools
-        "mcp__vibe-ensemble-mcp__list_events".to_string(),
-        "mcp__vibe-ensemble-mcp__resolve_event".to_string(),
-        "mcp__vibe-ensemble-mcp__get_tickets_by_stage".to_string(),
-        // Permission management tools
-        "mcp__vibe-ensemble-mcp__get_permission_model".to_string(),
-        // Template management tools
-        "mcp__vibe-ensemble-mcp__list_worker_templates".to_string(),
-        "mcp__vibe-ensemble-mcp__load_worker_template".to_string(),
-        "mcp__vibe-ensemble-mcp__ensure_worker_templates_exist".to_string(),
-        // JBCT (Java Backend Coding Technology) integration tools
-        "mcp__vibe-ensemble-mcp__configure_jbct_for_project".to_string(),
-        "mcp__vibe-ensemble-mcp__check_jbct_updates".to_string(),
-    ]
+    static NAMES: OnceLock<Vec<String>> = OnceLock::new();
+    NAMES
+        .get_or_init(|| {
+            McpServer::tool_registry()
+                .list_tools()
+                .into_iter()
+                .map(|tool| format!("{}{}", MCP_TOOL_PREFIX, tool.name))
+                .collect()
+        })
+        .clone()
 }
 
 /// Build MCP config JSON for server endpoints
-pub fn build_mcp_config(host: &str, port: u16) -> Value {
+pub fn build_mcp_config(host: &str, port: u16, base_path: &str) -> Value {
     json!({
         "mcpServers": {
             "vibe-ensemble-mcp": {
                 "type": "http",
-                "url": format!("http://{}:{}/mcp", host, port),
+                "url": format!("http://{}:{}{}/mcp", host, port, base_path),
                 "protocol_version": MCP_PROTOCOL_VERSION
             }
         }
     })
 }
 
+/// Build MCP config JSON for a spawned worker, authenticating its requests with the token
+/// issued to it, which is also what identifies the worker to the server
+pub fn build_worker_mcp_config(host: &str, port: u16, base_path: &str, token: &str) -> Value {
+    let mut config = build_mcp_config(host, port, base_path);
+    config["mcpServers"]["vibe-ensemble-mcp"]["headers"] = json!({
+        "Authorization": format!("Bearer {}", token)
+    });
+    config
+}
+
 /// Build Claude Code permissions configuration with explicit tool names
 pub fn build_claude_permissions() -> Value {
     let mut tool_names = get_all_mcp_tool_names();
@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{
+    connections,
+    tools::{create_json_success_response, ToolHandler},
+    types::{CallToolResponse, Tool},
+};
+use crate::server::AppState;
+
+pub struct DebugConnectionsTool;
+
+#[async_trait]
+impl ToolHandler for DebugConnectionsTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        _arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        Ok(create_json_success_response(json!(connections::snapshot(
+            state
+        ))))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "vibe_debug_connections".to_string(),
+            description: "Dump live MCP connections for debugging: session id, bound worker, negotiated protocol version, messages and bytes in/out, last activity and queued notifications, plus connections opened and closed (by cause) since startup. Sensitive metadata values are masked".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {}
+            }),
+        }
+    }
+}
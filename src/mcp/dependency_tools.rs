@@ -9,7 +9,13 @@ use super::{
     },
     types::{CallToolResponse, PaginationCursor, Tool},
 };
-use crate::{database::dag::TicketDependency, server::AppState};
+use crate::{
+    database::{
+        blocks::{self, BlockOutcome, Blocker},
+        dag::TicketDependency,
+    },
+    server::AppState,
+};
 
 pub struct AddTicketDependencyTool;
 
@@ -57,6 +63,7 @@ impl ToolHandler for AddTicketDependencyTool {
                     )
                     .await;
                 }
+                crate::escalation::refresh_for_ticket(state, &parent_ticket_id).await;
 
                 Ok(create_json_success_response(json!({
                     "message": format!("Successfully created {} dependency from '{}' to '{}'", dependency_type, parent_ticket_id, child_ticket_id),
@@ -145,6 +152,7 @@ impl ToolHandler for RemoveTicketDependencyTool {
                     )
                     .await;
                 }
+                crate::escalation::refresh_for_ticket(state, &parent_ticket_id).await;
 
                 Ok(create_json_success_response(json!({
                     "message": format!("Successfully removed dependency from '{}' to '{}'", parent_ticket_id, child_ticket_id),
@@ -278,9 +286,12 @@ impl ToolHandler for ListReadyTicketsTool {
                 // Apply pagination using helper
                 let pagination_result = cursor.paginate(all_tickets);
 
+                let held = blocks::list_blocked(&state.db, project_id.as_deref()).await?;
+
                 // Create response with pagination info
                 let response_data = serde_json::json!({
                     "tickets": pagination_result.items,
+                    "blocks": held,
                     "pagination": {
                         "total": pagination_result.total,
                         "has_more": pagination_result.has_more,
@@ -380,7 +391,7 @@ impl ToolHandler for ListBlockedTicketsTool {
     fn definition(&self) -> Tool {
         Tool {
             name: "list_blocked_tickets".to_string(),
-            description: "List all tickets that are blocked by dependencies".to_string(),
+            description: "List tickets waiting on dependencies; `blocks` lists tickets put on hold with block_ticket, with their blocker, age and reminders".to_string(),
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
@@ -398,3 +409,150 @@ impl ToolHandler for ListBlockedTicketsTool {
         }
     }
 }
+
+pub struct BlockTicketTool;
+
+#[async_trait]
+impl ToolHandler for BlockTicketTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let args = arguments
+            .ok_or_else(|| crate::error::AppError::BadRequest("Missing arguments".to_string()))?;
+
+        let ticket_id: String = extract_param(&Some(args.clone()), "ticket_id")?;
+        let reason: String = extract_param(&Some(args.clone()), "reason")?;
+        let blocking_ticket_id: Option<String> =
+            extract_optional_param(&Some(args.clone()), "blocking_ticket_id")?;
+        let responsible_agent: Option<String> =
+            extract_optional_param(&Some(args.clone()), "responsible_agent")?;
+        let expected_unblock_at: Option<String> =
+            extract_optional_param(&Some(args.clone()), "expected_unblock_at")?;
+        let blocked_by: String = extract_optional_param(&Some(args.clone()), "blocked_by")?
+            .unwrap_or_else(|| "coordinator".to_string());
+
+        let blocker =
+            match Blocker::from_args(blocking_ticket_id, responsible_agent, expected_unblock_at) {
+                Ok(blocker) => blocker,
+                Err(reason) => return Ok(create_json_error_response(&reason)),
+            };
+
+        info!("Blocking ticket {}: {}", ticket_id, reason);
+        match state
+            .queue_manager
+            .block_ticket(&ticket_id, &reason, &blocker, &blocked_by)
+            .await?
+        {
+            BlockOutcome::Blocked(block) => Ok(create_json_success_response(json!({
+                "message": format!("Ticket {} is blocked and on hold until it is unblocked", ticket_id),
+                "block": block
+            }))),
+            BlockOutcome::Rejected(reason) => {
+                warn!("Refused to block ticket {}: {}", ticket_id, reason);
+                Ok(create_json_error_response(&reason))
+            }
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "block_ticket".to_string(),
+            description: "Block a ticket, putting it on hold and releasing its worker. Give either blocking_ticket_id (the block is lifted when that ticket closes) or responsible_agent with expected_unblock_at (the agent gets a reminder once the date passes, and the coordinator a ticket_block_escalated event if the block is still in force after --block-escalation-mins)".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "ticket_id": {
+                        "type": "string",
+                        "description": "Ticket to block"
+                    },
+                    "reason": {
+                        "type": "string",
+                        "description": "What the ticket is waiting for"
+                    },
+                    "blocking_ticket_id": {
+                        "type": "string",
+                        "description": "Ticket this one waits on"
+                    },
+                    "responsible_agent": {
+                        "type": "string",
+                        "description": "Worker id, or 'coordinator', expected to unblock the ticket"
+                    },
+                    "expected_unblock_at": {
+                        "type": "string",
+                        "description": "RFC 3339 timestamp the responsible agent expects to unblock the ticket by"
+                    },
+                    "blocked_by": {
+                        "type": "string",
+                        "description": "Who is blocking the ticket (defaults to 'coordinator')"
+                    }
+                },
+                "required": ["ticket_id", "reason"]
+            }),
+        }
+    }
+}
+
+pub struct UnblockTicketTool;
+
+#[async_trait]
+impl ToolHandler for UnblockTicketTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let args = arguments
+            .ok_or_else(|| crate::error::AppError::BadRequest("Missing arguments".to_string()))?;
+
+        let ticket_id: String = extract_param(&Some(args.clone()), "ticket_id")?;
+        let unblocked_by: String = extract_optional_param(&Some(args.clone()), "unblocked_by")?
+            .unwrap_or_else(|| "coordinator".to_string());
+        let note: Option<String> = extract_optional_param(&Some(args.clone()), "note")?;
+
+        match state
+            .queue_manager
+            .unblock_ticket(&ticket_id, &unblocked_by, note.as_deref())
+            .await?
+        {
+            Some(lifted) => Ok(create_json_success_response(json!({
+                "message": format!(
+                    "Ticket {} unblocked after {} minutes and queued again if nothing else holds it back",
+                    ticket_id, lifted.blocked_minutes
+                ),
+                "block": lifted.block,
+                "blocked_minutes": lifted.blocked_minutes
+            }))),
+            None => Ok(create_json_error_response(&format!(
+                "Ticket {} is not blocked",
+                ticket_id
+            ))),
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "unblock_ticket".to_string(),
+            description: "Lift a ticket's block, reopening it and queueing it again at its current stage. Records who unblocked it and how long it was blocked".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "ticket_id": {
+                        "type": "string",
+                        "description": "Blocked ticket"
+                    },
+                    "unblocked_by": {
+                        "type": "string",
+                        "description": "Who is unblocking the ticket (defaults to 'coordinator')"
+                    },
+                    "note": {
+                        "type": "string",
+                        "description": "How the block was resolved, added to the unblock comment"
+                    }
+                },
+                "required": ["ticket_id"]
+            }),
+        }
+    }
+}
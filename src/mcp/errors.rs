@@ -0,0 +1,597 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::types::*;
+use crate::{
+    database::{quotas::QuotaExceeded, ticket_refs::AmbiguousTicketRef},
+    error::AppError,
+};
+
+/// Stable, machine-readable error categories carried in `JsonRpcError.data.kind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    ParseError,
+    InvalidRequest,
+    MethodNotFound,
+    ValidationFailed,
+    NotFound,
+    Conflict,
+    QuotaExceeded,
+    PermissionDenied,
+    NotInitialized,
+    StorageUnavailable,
+    Timeout,
+    Internal,
+}
+
+/// Area of the server an error belongs to. Not-found and conflict errors carry the
+/// code of their domain, so clients can tell a missing ticket from a missing worker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorDomain {
+    Ticket,
+    Agent,
+    Coordination,
+    Worker,
+    Knowledge,
+}
+
+impl ErrorDomain {
+    /// Domain of the errors a tool reports; `None` for diagnostics outside every domain
+    pub fn of_tool(tool: &str) -> Option<Self> {
+        match tool {
+            "create_ticket"
+            | "get_ticket"
+            | "list_tickets"
+            | "search_tickets"
+            | "add_ticket_comment"
+            | "summarize_ticket_thread"
+            | "close_ticket"
+            | "mark_ticket_duplicate"
+            | "set_ticket_estimate"
+            | "transition_pipeline"
+            | "resume_ticket_processing"
+            | "get_tickets_by_stage"
+            | "list_tags"
+            | "set_ticket_tags"
+            | "add_ticket_attachment"
+            | "add_ticket_dependency"
+            | "remove_ticket_dependency"
+            | "get_dependency_graph"
+            | "list_ready_tickets"
+            | "list_blocked_tickets"
+            | "block_ticket"
+            | "unblock_ticket"
+            | "add_ticket_relation"
+            | "remove_ticket_relation"
+            | "list_ticket_relations" => Some(ErrorDomain::Ticket),
+            "create_worker_type"
+            | "list_worker_types"
+            | "get_worker_type"
+            | "update_worker_type"
+            | "report_worker_type_status"
+            | "delete_worker_type"
+            | "get_permission_model" => Some(ErrorDomain::Agent),
+            "create_project"
+            | "list_projects"
+            | "get_project"
+            | "update_project"
+            | "delete_project"
+            | "override_project_quota"
+            | "configure_jbct_for_project"
+            | "check_jbct_updates"
+            | "list_events"
+            | "resolve_event"
+            | "get_latest_digest"
+            | "send_agent_request"
+            | "respond_to_request"
+            | "get_request_status"
+            | "get_pending_requests"
+            | "acknowledge_request"
+            | "list_agent_groups"
+            | "join_agent_group"
+            | "leave_agent_group"
+            | "create_schedule"
+            | "list_schedules"
+            | "get_schedule"
+            | "update_schedule"
+            | "delete_schedule" => Some(ErrorDomain::Coordination),
+            "get_spawn_queue_status"
+            | "cancel_queued_spawn"
+            | "get_worker_status"
+            | "send_worker_input"
+            | "report_worker_progress" => Some(ErrorDomain::Worker),
+            "list_worker_templates" | "load_worker_template" | "ensure_worker_templates_exist" => {
+                Some(ErrorDomain::Knowledge)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Slice of the code space reserved for one family of errors, from `max` down to `min`
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ErrorCodeRange {
+    pub name: &'static str,
+    pub max: i32,
+    pub min: i32,
+    pub description: &'static str,
+}
+
+impl ErrorCodeRange {
+    pub fn contains(&self, code: i32) -> bool {
+        (self.min..=self.max).contains(&code)
+    }
+}
+
+/// One assigned error code
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ErrorCode {
+    pub code: i32,
+    pub kind: ErrorKind,
+    /// Domain the code is specific to; `None` for codes shared by every domain
+    pub domain: Option<ErrorDomain>,
+    pub retryable: bool,
+}
+
+pub const ERROR_CODE_RANGES: &[ErrorCodeRange] = &[
+    ErrorCodeRange {
+        name: "protocol",
+        max: -32600,
+        min: -32768,
+        description: "Malformed JSON-RPC, unknown methods and invalid parameters",
+    },
+    ErrorCodeRange {
+        name: "session",
+        max: -32000,
+        min: -32009,
+        description: "Session handshake, access control and lookups outside any domain",
+    },
+    ErrorCodeRange {
+        name: "transient",
+        max: -32010,
+        min: -32019,
+        description: "Storage and timeout failures; the same request may succeed later",
+    },
+    ErrorCodeRange {
+        name: "ticket",
+        max: -32020,
+        min: -32029,
+        description: "Tickets (issues), their comments, dependencies, relations and blocks",
+    },
+    ErrorCodeRange {
+        name: "agent",
+        max: -32030,
+        min: -32039,
+        description: "Worker types and their permissions",
+    },
+    ErrorCodeRange {
+        name: "coordination",
+        max: -32040,
+        min: -32049,
+        description: "Projects, quotas, events, schedules and agent requests",
+    },
+    ErrorCodeRange {
+        name: "worker",
+        max: -32050,
+        min: -32059,
+        description: "The spawn queue and running workers",
+    },
+    ErrorCodeRange {
+        name: "knowledge",
+        max: -32060,
+        min: -32069,
+        description: "Prompts and worker templates",
+    },
+];
+
+const fn code(code: i32, kind: ErrorKind, domain: Option<ErrorDomain>) -> ErrorCode {
+    ErrorCode {
+        code,
+        kind,
+        domain,
+        retryable: kind.retryable(),
+    }
+}
+
+/// Every code the server returns. Shared codes come before domain codes, so the first
+/// entry of a kind is its code when no domain applies.
+pub const ERROR_CODES: &[ErrorCode] = &[
+    code(PARSE_ERROR, ErrorKind::ParseError, None),
+    code(INVALID_REQUEST, ErrorKind::InvalidRequest, None),
+    code(METHOD_NOT_FOUND, ErrorKind::MethodNotFound, None),
+    code(INVALID_PARAMS, ErrorKind::ValidationFailed, None),
+    code(INTERNAL_ERROR, ErrorKind::Internal, None),
+    code(PERMISSION_DENIED, ErrorKind::PermissionDenied, None),
+    code(RESOURCE_NOT_FOUND, ErrorKind::NotFound, None),
+    code(RESOURCE_CONFLICT, ErrorKind::Conflict, None),
+    code(SERVER_NOT_INITIALIZED, ErrorKind::NotInitialized, None),
+    code(STORAGE_UNAVAILABLE, ErrorKind::StorageUnavailable, None),
+    code(TIMEOUT, ErrorKind::Timeout, None),
+    code(
+        TICKET_NOT_FOUND,
+        ErrorKind::NotFound,
+        Some(ErrorDomain::Ticket),
+    ),
+    code(
+        TICKET_CONFLICT,
+        ErrorKind::Conflict,
+        Some(ErrorDomain::Ticket),
+    ),
+    code(
+        AGENT_NOT_FOUND,
+        ErrorKind::NotFound,
+        Some(ErrorDomain::Agent),
+    ),
+    code(
+        AGENT_CONFLICT,
+        ErrorKind::Conflict,
+        Some(ErrorDomain::Agent),
+    ),
+    code(
+        COORDINATION_NOT_FOUND,
+        ErrorKind::NotFound,
+        Some(ErrorDomain::Coordination),
+    ),
+    code(
+        COORDINATION_CONFLICT,
+        ErrorKind::Conflict,
+        Some(ErrorDomain::Coordination),
+    ),
+    code(
+        QUOTA_EXCEEDED,
+        ErrorKind::QuotaExceeded,
+        Some(ErrorDomain::Coordination),
+    ),
+    code(
+        WORKER_NOT_FOUND,
+        ErrorKind::NotFound,
+        Some(ErrorDomain::Worker),
+    ),
+    code(
+        WORKER_CONFLICT,
+        ErrorKind::Conflict,
+        Some(ErrorDomain::Worker),
+    ),
+    code(
+        KNOWLEDGE_NOT_FOUND,
+        ErrorKind::NotFound,
+        Some(ErrorDomain::Knowledge),
+    ),
+    code(
+        KNOWLEDGE_CONFLICT,
+        ErrorKind::Conflict,
+        Some(ErrorDomain::Knowledge),
+    ),
+];
+
+/// The code ranges and assigned codes, as served by the `vibe://error-codes` resource
+pub fn error_code_table() -> Value {
+    json!({
+        "ranges": ERROR_CODE_RANGES,
+        "codes": ERROR_CODES,
+    })
+}
+
+impl ErrorKind {
+    /// Whether repeating the same request later may succeed
+    pub const fn retryable(&self) -> bool {
+        matches!(self, ErrorKind::StorageUnavailable | ErrorKind::Timeout)
+    }
+
+    pub fn code(&self) -> i32 {
+        self.code_in(None)
+    }
+
+    /// Code of this kind of error within `domain`, falling back to the shared code
+    pub fn code_in(&self, domain: Option<ErrorDomain>) -> i32 {
+        ERROR_CODES
+            .iter()
+            .find(|entry| entry.kind == *self && domain.is_some() && entry.domain == domain)
+            .or_else(|| ERROR_CODES.iter().find(|entry| entry.kind == *self))
+            .map(|entry| entry.code)
+            .unwrap_or(INTERNAL_ERROR)
+    }
+}
+
+impl JsonRpcError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self::in_domain(None, kind, message)
+    }
+
+    /// An error with its domain's code, for kinds that have one; the domain is also
+    /// reported in `data.domain`
+    pub fn in_domain(
+        domain: Option<ErrorDomain>,
+        kind: ErrorKind,
+        message: impl Into<String>,
+    ) -> Self {
+        let code = kind.code_in(domain);
+        let mut data = json!({
+            "kind": kind,
+            "retryable": kind.retryable(),
+            "details": Value::Null,
+        });
+        if code != kind.code() {
+            data["domain"] = json!(domain);
+        }
+        Self {
+            code,
+            message: message.into(),
+            data: Some(data),
+        }
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        if let Some(Value::Object(data)) = self.data.as_mut() {
+            data.insert("details".to_string(), details);
+        }
+        self
+    }
+}
+
+impl From<&AppError> for ErrorKind {
+    fn from(error: &AppError) -> Self {
+        match error {
+            AppError::Database(e) => classify_sqlx(e),
+            AppError::Internal(e) => classify_anyhow(e),
+            AppError::Json(_) | AppError::BadRequest(_) => ErrorKind::ValidationFailed,
+            AppError::NotFound(_) => ErrorKind::NotFound,
+            AppError::WebSocketProtocolError(_) => ErrorKind::InvalidRequest,
+            AppError::Io(e) if e.kind() == std::io::ErrorKind::TimedOut => ErrorKind::Timeout,
+            AppError::Io(_) => ErrorKind::Internal,
+        }
+    }
+}
+
+impl From<AppError> for JsonRpcError {
+    fn from(error: AppError) -> Self {
+        JsonRpcError::new(ErrorKind::from(&error), error.to_string())
+    }
+}
+
+/// Errors carried inside `anyhow` keep their category; anything unrecognised is internal
+fn classify_anyhow(error: &anyhow::Error) -> ErrorKind {
+    if let Some(e) = error.downcast_ref::<AppError>() {
+        return ErrorKind::from(e);
+    }
+    if let Some(e) = error.downcast_ref::<sqlx::Error>() {
+        return classify_sqlx(e);
+    }
+    if error.downcast_ref::<QuotaExceeded>().is_some() {
+        return ErrorKind::QuotaExceeded;
+    }
+    if error.downcast_ref::<AmbiguousTicketRef>().is_some() {
+        return ErrorKind::Conflict;
+    }
+    ErrorKind::Internal
+}
+
+fn classify_sqlx(error: &sqlx::Error) -> ErrorKind {
+    match error {
+        sqlx::Error::RowNotFound => ErrorKind::NotFound,
+        sqlx::Error::PoolTimedOut => ErrorKind::Timeout,
+        sqlx::Error::PoolClosed | sqlx::Error::Io(_) => ErrorKind::StorageUnavailable,
+        sqlx::Error::Database(db) => {
+            if db.is_unique_violation() || db.is_foreign_key_violation() {
+                return ErrorKind::Conflict;
+            }
+            if db.is_check_violation() {
+                return ErrorKind::ValidationFailed;
+            }
+            // SQLITE_BUSY (5) and SQLITE_LOCKED (6), including extended codes
+            match db.code().and_then(|c| c.parse::<i32>().ok()) {
+                Some(code) if matches!(code & 0xff, 5 | 6) => ErrorKind::StorageUnavailable,
+                _ => ErrorKind::Internal,
+            }
+        }
+        _ => ErrorKind::Internal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(error: &JsonRpcError) -> &Value {
+        error.data.as_ref().unwrap()
+    }
+
+    #[test]
+    fn test_app_errors_map_to_kinds() {
+        let cases = [
+            (
+                AppError::BadRequest("x".into()),
+                ErrorKind::ValidationFailed,
+            ),
+            (AppError::NotFound("x".into()), ErrorKind::NotFound),
+            (
+                AppError::Database(sqlx::Error::PoolTimedOut),
+                ErrorKind::Timeout,
+            ),
+            (
+                AppError::Database(sqlx::Error::PoolClosed),
+                ErrorKind::StorageUnavailable,
+            ),
+            (
+                AppError::Internal(anyhow::Error::new(sqlx::Error::RowNotFound)),
+                ErrorKind::NotFound,
+            ),
+            (
+                AppError::Internal(anyhow::anyhow!("boom")),
+                ErrorKind::Internal,
+            ),
+        ];
+
+        for (error, kind) in cases {
+            assert_eq!(ErrorKind::from(&error), kind, "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn test_error_data_shape() {
+        let error: JsonRpcError = AppError::Database(sqlx::Error::PoolClosed).into();
+        assert_eq!(error.code, STORAGE_UNAVAILABLE);
+        assert_eq!(data(&error)["kind"], "storage_unavailable");
+        assert_eq!(data(&error)["retryable"], true);
+
+        let error = JsonRpcError::new(ErrorKind::ValidationFailed, "bad")
+            .with_details(json!({ "errors": ["field 'x' is required"] }));
+        assert_eq!(error.code, INVALID_PARAMS);
+        assert_eq!(data(&error)["retryable"], false);
+        assert_eq!(
+            data(&error)["details"]["errors"][0],
+            "field 'x' is required"
+        );
+    }
+
+    #[test]
+    fn test_wrapped_errors_keep_their_kind() {
+        let quota = QuotaExceeded {
+            project_id: "p".into(),
+            quota: crate::database::quotas::Quota::OpenTickets,
+            limit: 1,
+            usage: 1,
+        };
+        let cases = [
+            (anyhow::Error::new(quota), ErrorKind::QuotaExceeded),
+            (
+                anyhow::Error::new(AmbiguousTicketRef {
+                    reference: "VEM-1".into(),
+                    candidates: vec![],
+                }),
+                ErrorKind::Conflict,
+            ),
+            (
+                AppError::NotFound("Ticket 'x' not found".into()).into(),
+                ErrorKind::NotFound,
+            ),
+            (
+                AppError::BadRequest("x".into()).into(),
+                ErrorKind::ValidationFailed,
+            ),
+        ];
+
+        for (error, kind) in cases {
+            assert_eq!(
+                ErrorKind::from(&AppError::Internal(error)),
+                kind,
+                "{:?}",
+                kind
+            );
+        }
+    }
+
+    /// Published codes must never move; update this table only when adding codes
+    #[test]
+    fn test_code_table_is_stable() {
+        let table: Vec<(i32, Value, Value)> = ERROR_CODES
+            .iter()
+            .map(|entry| (entry.code, json!(entry.kind), json!(entry.domain)))
+            .collect();
+        let expected = [
+            (-32700, "parse_error", None),
+            (-32600, "invalid_request", None),
+            (-32601, "method_not_found", None),
+            (-32602, "validation_failed", None),
+            (-32603, "internal", None),
+            (-32001, "permission_denied", None),
+            (-32002, "not_found", None),
+            (-32003, "conflict", None),
+            (-32006, "not_initialized", None),
+            (-32010, "storage_unavailable", None),
+            (-32011, "timeout", None),
+            (-32020, "not_found", Some("ticket")),
+            (-32021, "conflict", Some("ticket")),
+            (-32030, "not_found", Some("agent")),
+            (-32031, "conflict", Some("agent")),
+            (-32040, "not_found", Some("coordination")),
+            (-32041, "conflict", Some("coordination")),
+            (-32042, "quota_exceeded", Some("coordination")),
+            (-32050, "not_found", Some("worker")),
+            (-32051, "conflict", Some("worker")),
+            (-32060, "not_found", Some("knowledge")),
+            (-32061, "conflict", Some("knowledge")),
+        ]
+        .map(|(code, kind, domain)| (code, json!(kind), json!(domain)));
+        assert_eq!(table, expected);
+    }
+
+    #[test]
+    fn test_codes_fall_in_their_ranges() {
+        let mut seen = std::collections::HashSet::new();
+        for entry in ERROR_CODES {
+            assert!(seen.insert(entry.code), "duplicate code {}", entry.code);
+            let ranges: Vec<_> = ERROR_CODE_RANGES
+                .iter()
+                .filter(|range| range.contains(entry.code))
+                .collect();
+            assert_eq!(ranges.len(), 1, "code {} is not in one range", entry.code);
+            let expected = match entry.domain {
+                Some(domain) => json!(domain).as_str().unwrap().to_string(),
+                None if entry.retryable => "transient".to_string(),
+                None => ranges[0].name.to_string(),
+            };
+            assert_eq!(ranges[0].name, expected, "code {}", entry.code);
+        }
+        assert!(ERROR_CODES
+            .iter()
+            .filter(|entry| entry.retryable)
+            .all(|entry| entry.code <= -32010 && entry.code >= -32019));
+    }
+
+    #[test]
+    fn test_domain_codes() {
+        let error = JsonRpcError::in_domain(Some(ErrorDomain::Worker), ErrorKind::NotFound, "x");
+        assert_eq!(error.code, WORKER_NOT_FOUND);
+        assert_eq!(data(&error)["domain"], "worker");
+
+        // Kinds without a domain code keep the shared one
+        let error =
+            JsonRpcError::in_domain(Some(ErrorDomain::Ticket), ErrorKind::ValidationFailed, "x");
+        assert_eq!(error.code, INVALID_PARAMS);
+        assert!(data(&error).get("domain").is_none());
+
+        assert_eq!(ErrorKind::NotFound.code(), RESOURCE_NOT_FOUND);
+        assert_eq!(ErrorKind::QuotaExceeded.code(), QUOTA_EXCEEDED);
+    }
+
+    #[test]
+    fn test_every_tool_has_a_domain() {
+        let server = crate::mcp::server::McpServer::default();
+        for tool in server.tools.list_tools() {
+            if tool.name == "vibe_debug_connections" {
+                continue;
+            }
+            assert!(
+                ErrorDomain::of_tool(&tool.name).is_some(),
+                "tool '{}' has no error domain",
+                tool.name
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_unique_violation_is_conflict() {
+        use sqlx::{sqlite::SqlitePoolOptions, Executor};
+
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        pool.execute("CREATE TABLE t (id TEXT PRIMARY KEY)")
+            .await
+            .unwrap();
+        pool.execute("INSERT INTO t VALUES ('a')").await.unwrap();
+
+        let err = pool
+            .execute("INSERT INTO t VALUES ('a')")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            ErrorKind::from(&AppError::Database(err)),
+            ErrorKind::Conflict
+        );
+    }
+}
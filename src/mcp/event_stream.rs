@@ -0,0 +1,477 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::{broadcast, Notify};
+use tracing::{debug, info, warn};
+
+use super::{
+    capabilities,
+    limits::{record_violation, ConnectionLimits, Enqueued, NotificationQueue, Violation},
+    session::SESSION_ID_HEADER,
+    types::ClientCapabilities,
+};
+use crate::{audit::AuditLogger, events::EventPayload, server::AppState};
+
+/// Notifications kept for clients resuming with `Last-Event-ID`
+pub const REPLAY_BUFFER_SIZE: usize = 1024;
+
+const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+
+/// A JSON-RPC notification at its position in the event stream
+#[derive(Debug, Clone)]
+pub struct StreamedNotification {
+    pub id: u64,
+    /// Session the notification targets; None for every client
+    pub session_id: Option<Arc<str>>,
+    pub method: Option<Arc<str>>,
+    pub message: Arc<str>,
+}
+
+impl StreamedNotification {
+    /// Whether a client of `session_id` with the given capabilities gets this notification
+    fn is_for(&self, session_id: Option<&str>, declared: Option<&ClientCapabilities>) -> bool {
+        let addressed = match &self.session_id {
+            Some(target) => session_id == Some(&**target),
+            None => true,
+        };
+        addressed
+            && self.method.as_deref().is_none_or(|method| {
+                capabilities::permits(declared, method, session_id.unwrap_or("anonymous"))
+            })
+    }
+
+    fn to_event(&self) -> Event {
+        Event::default()
+            .id(self.id.to_string())
+            .event("message")
+            .data(&*self.message)
+    }
+}
+
+struct Subscriber {
+    session_id: Option<String>,
+    capabilities: Option<ClientCapabilities>,
+    queue: Arc<NotificationQueue<StreamedNotification>>,
+    disconnected: Arc<Notify>,
+}
+
+#[derive(Default)]
+struct StreamState {
+    last_id: u64,
+    replay: VecDeque<StreamedNotification>,
+    next_client_id: u64,
+    subscribers: HashMap<u64, Subscriber>,
+}
+
+/// Fan-out of server-initiated notifications to `/events` clients.
+///
+/// Every notification takes the next stream id and is kept in a bounded replay buffer,
+/// so a client reconnecting with `Last-Event-ID` receives what it missed. Each client
+/// reads from its own queue, bounded by the per-connection notification limit.
+pub struct EventStream {
+    state: Mutex<StreamState>,
+    limits: ConnectionLimits,
+    audit_logger: AuditLogger,
+}
+
+impl EventStream {
+    pub fn new(limits: ConnectionLimits, audit_logger: AuditLogger) -> Self {
+        Self {
+            state: Mutex::new(StreamState::default()),
+            limits,
+            audit_logger,
+        }
+    }
+
+    /// Stream a notification to the clients of `session_id`, or to every client when
+    /// None; returns its event id
+    pub fn publish(&self, session_id: Option<&str>, message: &Value) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        state.last_id += 1;
+        let notification = StreamedNotification {
+            id: state.last_id,
+            session_id: session_id.map(Arc::from),
+            method: message.get("method").and_then(Value::as_str).map(Arc::from),
+            message: Arc::from(message.to_string()),
+        };
+
+        if state.replay.len() >= REPLAY_BUFFER_SIZE {
+            state.replay.pop_front();
+        }
+        state.replay.push_back(notification.clone());
+
+        let mut overflowed = Vec::new();
+        for (client_id, subscriber) in &state.subscribers {
+            if !notification.is_for(
+                subscriber.session_id.as_deref(),
+                subscriber.capabilities.as_ref(),
+            ) {
+                continue;
+            }
+            match subscriber.queue.push(notification.clone()) {
+                Enqueued::Queued => {}
+                Enqueued::DroppedOldest { burst_start } => record_violation(
+                    Some(&self.audit_logger).filter(|_| burst_start),
+                    "sse",
+                    subscriber.session_id.as_deref(),
+                    Violation::NotificationDropped,
+                ),
+                Enqueued::Overflow => {
+                    record_violation(
+                        Some(&self.audit_logger),
+                        "sse",
+                        subscriber.session_id.as_deref(),
+                        Violation::NotificationOverflow,
+                    );
+                    overflowed.push(*client_id);
+                }
+            }
+        }
+        for client_id in overflowed {
+            if let Some(subscriber) = state.subscribers.remove(&client_id) {
+                subscriber.disconnected.notify_one();
+            }
+        }
+
+        notification.id
+    }
+
+    /// Register a client with the capabilities its session declared; notifications
+    /// after `last_event_id` that are still buffered are handed back for replay
+    pub fn subscribe(
+        self: &Arc<Self>,
+        session_id: Option<String>,
+        capabilities: Option<ClientCapabilities>,
+        last_event_id: Option<u64>,
+    ) -> Subscription {
+        let mut state = self.state.lock().unwrap();
+
+        let replay: Vec<_> = match last_event_id {
+            Some(last_event_id) => {
+                if let Some(oldest) = state.replay.front() {
+                    if oldest.id > last_event_id + 1 {
+                        warn!(
+                            "Event stream resume from {} is incomplete: events up to {} are no longer buffered",
+                            last_event_id,
+                            oldest.id - 1
+                        );
+                    }
+                }
+                state
+                    .replay
+                    .iter()
+                    .filter(|n| {
+                        n.id > last_event_id
+                            && n.is_for(session_id.as_deref(), capabilities.as_ref())
+                    })
+                    .cloned()
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+
+        state.next_client_id += 1;
+        let client_id = state.next_client_id;
+        let queue = Arc::new(NotificationQueue::new(
+            self.limits.max_queued_notifications,
+            self.limits.overflow_policy,
+        ));
+        let disconnected = Arc::new(Notify::new());
+        state.subscribers.insert(
+            client_id,
+            Subscriber {
+                session_id,
+                capabilities,
+                queue: Arc::clone(&queue),
+                disconnected: Arc::clone(&disconnected),
+            },
+        );
+
+        Subscription {
+            stream: Arc::clone(self),
+            client_id,
+            replay,
+            queue,
+            disconnected,
+        }
+    }
+
+    /// End the event streams of a closed session
+    pub fn close_session(&self, session_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.subscribers.retain(|_, subscriber| {
+            if subscriber.session_id.as_deref() == Some(session_id) {
+                subscriber.disconnected.notify_one();
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Notifications waiting on the streams of a session, if it has any open
+    pub fn queued_for(&self, session_id: &str) -> Option<usize> {
+        let state = self.state.lock().unwrap();
+        let mut queued = state
+            .subscribers
+            .values()
+            .filter(|subscriber| subscriber.session_id.as_deref() == Some(session_id))
+            .map(|subscriber| subscriber.queue.len())
+            .peekable();
+        queued.peek()?;
+        Some(queued.sum())
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.state.lock().unwrap().subscribers.len()
+    }
+
+    /// Stream every broadcast vibe event as its JSON-RPC notification
+    pub fn start_forwarding(self: &Arc<Self>, mut receiver: broadcast::Receiver<EventPayload>) {
+        let stream = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        stream.publish(None, &event.to_jsonrpc_notification());
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Event stream forwarder lagged, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
+
+/// One client's view of the event stream; unsubscribes when dropped
+pub struct Subscription {
+    stream: Arc<EventStream>,
+    client_id: u64,
+    /// Buffered notifications the client missed before reconnecting
+    pub replay: Vec<StreamedNotification>,
+    queue: Arc<NotificationQueue<StreamedNotification>>,
+    disconnected: Arc<Notify>,
+}
+
+impl Subscription {
+    /// Wait for the next live notification; None once the client was disconnected,
+    /// either for falling behind or because its session closed
+    pub async fn next(&self) -> Option<StreamedNotification> {
+        tokio::select! {
+            biased;
+            _ = self.disconnected.notified() => None,
+            notification = self.queue.pop() => Some(notification),
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.stream
+            .state
+            .lock()
+            .unwrap()
+            .subscribers
+            .remove(&self.client_id);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    pub session_id: Option<String>,
+}
+
+/// GET /events - Server-initiated JSON-RPC notifications over SSE.
+///
+/// The first event advertises the HTTP RPC endpoint. Notifications follow as `message`
+/// events with increasing ids; a `Last-Event-ID` header resumes after that id. Passing
+/// the MCP session id (`?session_id=` or the session header) also delivers the
+/// notifications addressed to that session.
+pub async fn events_handler(
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let session_id = query.session_id.or_else(|| {
+        headers
+            .get(SESSION_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string())
+    });
+    if let Some(session_id) = &session_id {
+        if !state.mcp_server.sessions.contains(session_id) {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "error": format!("Unknown MCP session '{}'", session_id) })),
+            )
+                .into_response();
+        }
+    }
+    let last_event_id = headers
+        .get(LAST_EVENT_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok());
+
+    let base_url = state.config.http_base_url();
+    let endpoint_discovery = EventPayload::endpoint_discovery(
+        &format!("{}/mcp", base_url),
+        &format!("{}/events", base_url),
+    )
+    .to_jsonrpc_notification();
+
+    let declared = session_id
+        .as_deref()
+        .and_then(|session_id| state.mcp_server.sessions.capabilities(session_id));
+    let mut subscription =
+        state
+            .event_stream
+            .subscribe(session_id.clone(), declared, last_event_id);
+    info!(
+        "Event stream client connected (session={:?}, resuming after {:?}, replaying {})",
+        session_id,
+        last_event_id,
+        subscription.replay.len()
+    );
+
+    let stream = async_stream::stream! {
+        yield Ok::<_, axum::Error>(Event::default()
+            .event("endpoint_discovery")
+            .data(endpoint_discovery.to_string()));
+
+        for notification in std::mem::take(&mut subscription.replay) {
+            yield Ok(notification.to_event());
+        }
+
+        while let Some(notification) = subscription.next().await {
+            yield Ok(notification.to_event());
+        }
+        debug!("Event stream closed (session={:?})", session_id);
+    };
+
+    Sse::new(stream)
+        .keep_alive(
+            KeepAlive::new()
+                .interval(Duration::from_secs(30))
+                .text("keep-alive-mcp"),
+        )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::limits::OverflowPolicy;
+
+    async fn event_stream(limits: ConnectionLimits) -> (Arc<EventStream>, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("vibe-events-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pool = crate::database::create_pool(&format!(
+            "sqlite:{}?mode=rwc",
+            dir.join("t.db").display()
+        ))
+        .await
+        .unwrap();
+        let stream = EventStream::new(limits, AuditLogger::start(pool));
+        (Arc::new(stream), dir)
+    }
+
+    fn ids(notifications: &[StreamedNotification]) -> Vec<u64> {
+        notifications.iter().map(|n| n.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_replay_and_session_targeting() {
+        let (stream, dir) = event_stream(ConnectionLimits::default()).await;
+        stream.publish(None, &json!({"n": 1}));
+        stream.publish(Some("a"), &json!({"n": 2}));
+        stream.publish(Some("b"), &json!({"n": 3}));
+        stream.publish(None, &json!({"n": 4}));
+
+        assert_eq!(
+            ids(&stream.subscribe(None, None, Some(0)).replay),
+            vec![1, 4]
+        );
+        assert_eq!(
+            ids(&stream.subscribe(Some("a".into()), None, Some(1)).replay),
+            vec![2, 4]
+        );
+        assert!(stream.subscribe(None, None, None).replay.is_empty());
+
+        let a = stream.subscribe(Some("a".into()), None, None);
+        let b = stream.subscribe(Some("b".into()), None, None);
+        stream.publish(Some("b"), &json!({"n": 5}));
+        stream.publish(None, &json!({"n": 6}));
+        assert_eq!(a.next().await.unwrap().id, 6);
+        assert_eq!(b.next().await.unwrap().id, 5);
+        assert_eq!(b.next().await.unwrap().id, 6);
+
+        stream.close_session("b");
+        assert!(b.next().await.is_none());
+        assert_eq!(stream.subscriber_count(), 1);
+        drop(a);
+        assert_eq!(stream.subscriber_count(), 0);
+
+        for n in 0..REPLAY_BUFFER_SIZE {
+            stream.publish(None, &json!({ "n": n }));
+        }
+        let resumed = stream.subscribe(None, None, Some(0));
+        assert_eq!(resumed.replay.len(), REPLAY_BUFFER_SIZE);
+        assert_eq!(resumed.replay[0].id, 7);
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_notifications_gated_by_session_capabilities() {
+        let (stream, dir) = event_stream(ConnectionLimits::default()).await;
+        let logging: ClientCapabilities =
+            serde_json::from_value(json!({"logging": {"enabled": true}})).unwrap();
+        let with_logging = stream.subscribe(Some("a".into()), Some(logging), None);
+        let without = stream.subscribe(Some("b".into()), Some(Default::default()), None);
+
+        stream.publish(None, &json!({"method": "notifications/message"}));
+        stream.publish(None, &json!({"method": "notifications/events"}));
+        assert_eq!(with_logging.next().await.unwrap().id, 1);
+        assert_eq!(with_logging.next().await.unwrap().id, 2);
+        assert_eq!(without.next().await.unwrap().id, 2);
+        assert!(stream
+            .subscribe(None, None, Some(0))
+            .replay
+            .iter()
+            .all(|n| n.id == 2));
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_slow_client_disconnected_on_overflow() {
+        let (stream, dir) = event_stream(ConnectionLimits {
+            max_queued_notifications: 2,
+            overflow_policy: OverflowPolicy::Disconnect,
+            ..ConnectionLimits::default()
+        })
+        .await;
+        let slow = stream.subscribe(None, None, None);
+        for n in 0..3 {
+            stream.publish(None, &json!({ "n": n }));
+        }
+        assert!(slow.next().await.is_none());
+        assert_eq!(stream.subscriber_count(), 0);
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -11,7 +11,7 @@ use super::{
     types::{CallToolResponse, Tool},
 };
 use crate::{
-    database::{events::Event, tickets::Ticket},
+    database::{digests::Digest, events::Event, tickets::Ticket},
     server::AppState,
 };
 
@@ -169,6 +169,38 @@ impl ToolHandler for ResolveEventTool {
     }
 }
 
+pub struct GetLatestDigestTool;
+
+#[async_trait]
+impl ToolHandler for GetLatestDigestTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        _arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        match Digest::latest(&state.db).await? {
+            Some(digest) => Ok(create_json_success_response(serde_json::to_value(
+                &digest,
+            )?)),
+            None => Ok(create_success_response(
+                "No digest has been sent yet. Digests are compiled every --digest-interval-mins and only when there was activity.",
+            )),
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "get_latest_digest".to_string(),
+            description: "Get the latest activity digest: tickets created and changed, failed workers, workers that went offline, claim conflicts and SLA escalations since the digest before it. Each section has a total count and its most recent items. The same digest is pushed to the coordinator as a 'digest' notification when it is compiled.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {},
+                "required": []
+            }),
+        }
+    }
+}
+
 pub struct GetTicketsByStageTool;
 
 #[async_trait]
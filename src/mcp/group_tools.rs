@@ -0,0 +1,186 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tracing::info;
+
+use super::{
+    tools::{
+        create_json_error_response, create_json_success_response, extract_optional_param,
+        extract_param, ToolHandler,
+    },
+    types::{CallToolResponse, Tool},
+};
+use crate::{database::agent_groups::AgentGroup, server::AppState};
+
+pub struct ListAgentGroupsTool;
+
+#[async_trait]
+impl ToolHandler for ListAgentGroupsTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let agent: Option<String> = extract_optional_param(&arguments, "agent")?;
+
+        let mut memberships = AgentGroup::memberships(&state.db).await?;
+        let mut groups = AgentGroup::list(&state.db).await?;
+        if let Some(agent) = &agent {
+            let joined = memberships.remove(agent).unwrap_or_default();
+            groups.retain(|group| joined.contains(&group.group_id));
+        }
+
+        let mut listed = Vec::with_capacity(groups.len());
+        for group in groups {
+            let members = AgentGroup::members(&state.db, &group.group_id)
+                .await?
+                .unwrap_or_default();
+            listed.push(json!({
+                "group_id": group.group_id,
+                "description": group.description,
+                "members": members
+                    .iter()
+                    .map(|member| member.agent_id.as_str())
+                    .collect::<Vec<_>>()
+            }));
+        }
+
+        Ok(create_json_success_response(json!({
+            "count": listed.len(),
+            "groups": listed
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "list_agent_groups".to_string(),
+            description: "List agent groups with their members. Send a request to every member of a group with send_agent_request and group_id".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "agent": {
+                        "type": "string",
+                        "description": "Only groups this agent belongs to ('coordinator' or a worker id)"
+                    }
+                }
+            }),
+        }
+    }
+}
+
+pub struct JoinAgentGroupTool;
+
+#[async_trait]
+impl ToolHandler for JoinAgentGroupTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let group_id: String = extract_param(&arguments, "group_id")?;
+        let agent: String = extract_optional_param(&arguments, "agent")?
+            .unwrap_or_else(|| "coordinator".to_string());
+
+        match AgentGroup::join(&state.db, &group_id, &agent, &agent).await? {
+            Some(joined) => {
+                if joined {
+                    info!("Agent '{}' joined group '{}'", agent, group_id);
+                }
+                Ok(create_json_success_response(json!({
+                    "message": if joined {
+                        format!("'{}' joined group '{}'", agent, group_id)
+                    } else {
+                        format!("'{}' is already a member of group '{}'", agent, group_id)
+                    },
+                    "group_id": group_id,
+                    "agent": agent,
+                    "joined": joined
+                })))
+            }
+            None => Ok(create_json_error_response(&format!(
+                "Agent group '{}' not found",
+                group_id
+            ))),
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "join_agent_group".to_string(),
+            description: "Join an existing agent group, so requests sent to the group reach you. Joining a group you belong to changes nothing".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "group_id": {
+                        "type": "string",
+                        "description": "Group to join"
+                    },
+                    "agent": {
+                        "type": "string",
+                        "description": "Agent joining ('coordinator' or a worker id, default: coordinator)"
+                    }
+                },
+                "required": ["group_id"]
+            }),
+        }
+    }
+}
+
+pub struct LeaveAgentGroupTool;
+
+#[async_trait]
+impl ToolHandler for LeaveAgentGroupTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let group_id: String = extract_param(&arguments, "group_id")?;
+        let agent: String = extract_optional_param(&arguments, "agent")?
+            .unwrap_or_else(|| "coordinator".to_string());
+
+        match AgentGroup::leave(&state.db, &group_id, &agent).await? {
+            Some(left) => {
+                if left {
+                    info!("Agent '{}' left group '{}'", agent, group_id);
+                }
+                Ok(create_json_success_response(json!({
+                    "message": if left {
+                        format!("'{}' left group '{}'", agent, group_id)
+                    } else {
+                        format!("'{}' is not a member of group '{}'", agent, group_id)
+                    },
+                    "group_id": group_id,
+                    "agent": agent,
+                    "left": left
+                })))
+            }
+            None => Ok(create_json_error_response(&format!(
+                "Agent group '{}' not found",
+                group_id
+            ))),
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "leave_agent_group".to_string(),
+            description:
+                "Leave an agent group. Requests already sent to the group stay addressed to you"
+                    .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "group_id": {
+                        "type": "string",
+                        "description": "Group to leave"
+                    },
+                    "agent": {
+                        "type": "string",
+                        "description": "Agent leaving ('coordinator' or a worker id, default: coordinator)"
+                    }
+                },
+                "required": ["group_id"]
+            }),
+        }
+    }
+}
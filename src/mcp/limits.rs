@@ -0,0 +1,326 @@
+use axum::{
+    body::Body,
+    extract::{ws::Message, State},
+    http::{header::CONTENT_LENGTH, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+use tokio::sync::Notify;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::session::SESSION_ID_HEADER;
+use crate::{
+    audit::{AuditLogger, AuditRecord},
+    server::AppState,
+};
+
+/// WebSocket close code for messages above the size limit (RFC 6455 "Message Too Big")
+pub const CLOSE_MESSAGE_TOO_BIG: u16 = 1009;
+/// WebSocket close code for a client that cannot keep up with its notifications
+pub const CLOSE_POLICY_VIOLATION: u16 = 1008;
+
+static OVERSIZED_MESSAGES: AtomicU64 = AtomicU64::new(0);
+static DROPPED_NOTIFICATIONS: AtomicU64 = AtomicU64::new(0);
+static OVERFLOW_DISCONNECTS: AtomicU64 = AtomicU64::new(0);
+
+/// What to do when a connection's outbound notification queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverflowPolicy {
+    /// Discard the oldest queued notification to make room
+    DropOldest,
+    /// Close the connection
+    Disconnect,
+}
+
+/// Per-connection transport limits
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionLimits {
+    /// Largest inbound message or HTTP body accepted, in bytes
+    pub max_message_bytes: usize,
+    /// Notifications queued per connection before the overflow policy applies (0 for unlimited)
+    pub max_queued_notifications: usize,
+    pub overflow_policy: OverflowPolicy,
+}
+
+impl Default for ConnectionLimits {
+    fn default() -> Self {
+        Self {
+            max_message_bytes: 1024 * 1024,
+            max_queued_notifications: 256,
+            overflow_policy: OverflowPolicy::DropOldest,
+        }
+    }
+}
+
+impl From<&crate::config::Config> for ConnectionLimits {
+    fn from(config: &crate::config::Config) -> Self {
+        Self {
+            max_message_bytes: config.max_message_bytes,
+            max_queued_notifications: config.max_queued_notifications,
+            overflow_policy: config.notification_overflow_policy,
+        }
+    }
+}
+
+/// A limit a connection ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    MessageTooLarge,
+    NotificationDropped,
+    NotificationOverflow,
+}
+
+impl Violation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Violation::MessageTooLarge => "message_too_large",
+            Violation::NotificationDropped => "notification_dropped",
+            Violation::NotificationOverflow => "notification_overflow",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LimitStats {
+    pub oversized_messages: u64,
+    pub dropped_notifications: u64,
+    pub overflow_disconnects: u64,
+}
+
+pub fn stats() -> LimitStats {
+    LimitStats {
+        oversized_messages: OVERSIZED_MESSAGES.load(Ordering::Relaxed),
+        dropped_notifications: DROPPED_NOTIFICATIONS.load(Ordering::Relaxed),
+        overflow_disconnects: OVERFLOW_DISCONNECTS.load(Ordering::Relaxed),
+    }
+}
+
+/// Count a violation and write an audit entry for the session that caused it
+pub fn record_violation(
+    audit_logger: Option<&AuditLogger>,
+    transport: &str,
+    session_id: Option<&str>,
+    violation: Violation,
+) {
+    let counter = match violation {
+        Violation::MessageTooLarge => &OVERSIZED_MESSAGES,
+        Violation::NotificationDropped => &DROPPED_NOTIFICATIONS,
+        Violation::NotificationOverflow => &OVERFLOW_DISCONNECTS,
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+    warn!(
+        "{} connection limit hit: {} (session={:?})",
+        transport,
+        violation.as_str(),
+        session_id
+    );
+
+    if let Some(audit_logger) = audit_logger {
+        audit_logger.record(
+            AuditRecord::new(
+                session_id.unwrap_or("anonymous"),
+                &format!("{}_limit", transport),
+                violation.as_str(),
+            )
+            .with_session(session_id),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Enqueued {
+    Queued,
+    /// The oldest notification was discarded; `burst_start` is set for the first drop
+    /// since the queue last had room, so a slow client is audited once per burst
+    DroppedOldest {
+        burst_start: bool,
+    },
+    /// The queue is full and the policy is to disconnect; the message was not queued
+    Overflow,
+}
+
+/// Bounded outbound notification queue of a single connection.
+///
+/// The queue only fills up while the socket is slower than the event rate, so the
+/// bound caps the memory a stalled client can pin.
+#[derive(Debug)]
+pub struct NotificationQueue<T = Message> {
+    state: Mutex<QueueState<T>>,
+    ready: Notify,
+    capacity: usize,
+    policy: OverflowPolicy,
+}
+
+#[derive(Debug)]
+struct QueueState<T> {
+    messages: VecDeque<T>,
+    dropping: bool,
+}
+
+impl<T> NotificationQueue<T> {
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                messages: VecDeque::new(),
+                dropping: false,
+            }),
+            ready: Notify::new(),
+            capacity,
+            policy,
+        }
+    }
+
+    pub fn push(&self, message: T) -> Enqueued {
+        let mut state = self.state.lock().unwrap();
+        let mut outcome = Enqueued::Queued;
+        if self.capacity > 0 && state.messages.len() >= self.capacity {
+            match self.policy {
+                OverflowPolicy::DropOldest => {
+                    state.messages.pop_front();
+                    outcome = Enqueued::DroppedOldest {
+                        burst_start: !state.dropping,
+                    };
+                    state.dropping = true;
+                }
+                OverflowPolicy::Disconnect => return Enqueued::Overflow,
+            }
+        } else {
+            state.dropping = false;
+        }
+        state.messages.push_back(message);
+        drop(state);
+        self.ready.notify_one();
+        outcome
+    }
+
+    /// Wait for the next notification; cancel-safe
+    pub async fn pop(&self) -> T {
+        loop {
+            if let Some(message) = self.state.lock().unwrap().messages.pop_front() {
+                return message;
+            }
+            self.ready.notified().await;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Room for multipart boundaries and part headers around an uploaded attachment
+const MULTIPART_OVERHEAD_BYTES: usize = 64 * 1024;
+
+/// Body limit for a request: attachment uploads get the attachment limit, ticket imports
+/// the import limit, everything else the message limit
+fn body_limit(state: &AppState, request: &Request<Body>) -> usize {
+    let path = request.uri().path();
+    if request.method() == Method::POST && path.ends_with("/attachments") {
+        state.config.max_attachment_bytes + MULTIPART_OVERHEAD_BYTES
+    } else if request.method() == Method::POST && path.ends_with("/tickets/import") {
+        state.config.max_import_bytes + MULTIPART_OVERHEAD_BYTES
+    } else {
+        state.config.max_message_bytes
+    }
+}
+
+/// Reject HTTP requests whose declared Content-Length exceeds the body limit before
+/// any of the body is read; bodies without a length are cut off once they pass it
+pub async fn reject_oversized_body(
+    State(state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let limit = body_limit(&state, &request);
+    let declared = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+
+    if let Some(length) = declared.filter(|length| *length > limit as u64) {
+        let session_id = request
+            .headers()
+            .get(SESSION_ID_HEADER)
+            .and_then(|value| value.to_str().ok());
+        record_violation(
+            Some(&state.audit_logger),
+            "http",
+            session_id,
+            Violation::MessageTooLarge,
+        );
+        return (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            axum::Json(json!({
+                "error": format!(
+                    "Request body of {} bytes exceeds the {} byte limit",
+                    length, limit
+                )
+            })),
+        )
+            .into_response();
+    }
+
+    let (parts, body) = request.into_parts();
+    let body = Body::new(http_body_util::Limited::new(body, limit));
+    next.run(Request::from_parts(parts, body)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(n: usize) -> Message {
+        Message::Text(n.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_queue_policies() {
+        let queue = NotificationQueue::new(2, OverflowPolicy::DropOldest);
+        assert_eq!(queue.push(text(1)), Enqueued::Queued);
+        assert_eq!(queue.push(text(2)), Enqueued::Queued);
+        assert_eq!(
+            queue.push(text(3)),
+            Enqueued::DroppedOldest { burst_start: true }
+        );
+        assert_eq!(
+            queue.push(text(4)),
+            Enqueued::DroppedOldest { burst_start: false }
+        );
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop().await, text(3));
+        assert_eq!(queue.push(text(5)), Enqueued::Queued);
+        assert_eq!(
+            queue.push(text(6)),
+            Enqueued::DroppedOldest { burst_start: true }
+        );
+        assert_eq!(queue.pop().await, text(5));
+        assert_eq!(queue.pop().await, text(6));
+        assert!(queue.is_empty());
+
+        let queue = NotificationQueue::new(1, OverflowPolicy::Disconnect);
+        assert_eq!(queue.push(text(1)), Enqueued::Queued);
+        assert_eq!(queue.push(text(2)), Enqueued::Overflow);
+        assert_eq!(queue.pop().await, text(1));
+
+        let unbounded = NotificationQueue::new(0, OverflowPolicy::Disconnect);
+        for n in 0..1000 {
+            assert_eq!(unbounded.push(text(n)), Enqueued::Queued);
+        }
+    }
+}
@@ -1,14 +1,32 @@
+pub mod access;
+pub mod attachment_tools;
+pub mod capabilities;
+pub mod client_metadata;
+pub mod connections;
 pub mod constants;
+pub mod debug_tools;
 pub mod dependency_tools;
+pub mod errors;
+pub mod event_stream;
 pub mod event_tools;
+pub mod group_tools;
 pub mod jbct_tools;
+pub mod limits;
 pub mod pagination;
 pub mod permission_tools;
 pub mod project_tools;
+pub mod queue_tools;
+pub mod relation_tools;
+pub mod request_tools;
+pub mod schedule_tools;
 pub mod server;
+pub mod session;
+pub mod subscriptions;
+pub mod tag_tools;
 pub mod template_tools;
 pub mod ticket_tools;
 pub mod tools;
+pub mod trace;
 pub mod types;
 pub mod websocket;
 pub mod worker_type_tools;
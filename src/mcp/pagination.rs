@@ -1,5 +1,7 @@
 use super::{tools::extract_optional_param, types::PaginationCursor};
 use crate::error::{AppError, Result};
+use base64::{engine::general_purpose, Engine};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 /// Extract pagination cursor from tool arguments
@@ -16,3 +18,164 @@ pub fn paginate_vec<T: Clone>(
 ) -> super::types::PaginationResult<T> {
     cursor.paginate(items)
 }
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+/// Cursor for keyset pagination over rows ordered by `created_at DESC, id DESC`.
+///
+/// It records the last row of the previous page, so pages stay stable while rows are
+/// added, and the filters it was issued for, so it cannot be replayed against another query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeysetCursor {
+    pub created_at: String,
+    pub id: String,
+    pub filters: String,
+}
+
+impl KeysetCursor {
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_string(self).expect("cursor serializes");
+        general_purpose::URL_SAFE_NO_PAD.encode(json.as_bytes())
+    }
+
+    /// Decode a cursor, rejecting malformed ones and ones issued for different filters
+    pub fn decode(cursor: &str, filters: &str) -> std::result::Result<Self, String> {
+        let cursor: KeysetCursor = general_purpose::URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| {
+                "Invalid cursor: it is malformed or from an older version".to_string()
+            })?;
+        if cursor.filters != filters {
+            return Err(
+                "Invalid cursor: the filters changed since it was issued; start again without a cursor"
+                    .to_string(),
+            );
+        }
+        Ok(cursor)
+    }
+
+    /// Sort key to resume after
+    pub fn after(&self) -> (&str, &str) {
+        (&self.created_at, &self.id)
+    }
+
+    /// Sort key to resume after, for tables with integer ids
+    pub fn after_numeric(&self) -> std::result::Result<(&str, i64), String> {
+        let id = self
+            .id
+            .parse()
+            .map_err(|_| "Invalid cursor: it is malformed or from an older version".to_string())?;
+        Ok((&self.created_at, id))
+    }
+}
+
+/// Validated `cursor` and `limit` of a keyset-paginated list request
+#[derive(Debug, Clone)]
+pub struct KeysetRequest {
+    pub after: Option<KeysetCursor>,
+    pub limit: i64,
+    filters: String,
+}
+
+impl KeysetRequest {
+    pub fn new(
+        cursor: Option<&str>,
+        limit: Option<i64>,
+        filters: String,
+    ) -> std::result::Result<Self, String> {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+            return Err(format!(
+                "Invalid limit {}: must be between 1 and {}",
+                limit, MAX_PAGE_SIZE
+            ));
+        }
+        let after = cursor
+            .map(|cursor| KeysetCursor::decode(cursor, &filters))
+            .transpose()?;
+        Ok(Self {
+            after,
+            limit,
+            filters,
+        })
+    }
+
+    /// Rows to fetch: one more than the page, to learn whether another page exists
+    pub fn fetch_limit(&self) -> i64 {
+        self.limit + 1
+    }
+
+    /// Trim rows fetched with `fetch_limit` to a page and issue the cursor for the next one
+    pub fn page<T>(&self, mut rows: Vec<T>, key: impl Fn(&T) -> (String, String)) -> KeysetPage<T> {
+        let has_more = rows.len() as i64 > self.limit;
+        rows.truncate(self.limit as usize);
+        let next_cursor = if has_more {
+            rows.last().map(|last| {
+                let (created_at, id) = key(last);
+                KeysetCursor {
+                    created_at,
+                    id,
+                    filters: self.filters.clone(),
+                }
+                .encode()
+            })
+        } else {
+            None
+        };
+        KeysetPage {
+            items: rows,
+            has_more,
+            next_cursor,
+        }
+    }
+}
+
+/// Extract keyset `cursor` and `limit` from tool arguments
+pub fn extract_keyset(args: &Option<Value>, filters: String) -> Result<KeysetRequest> {
+    let cursor: Option<String> = extract_optional_param(args, "cursor")?;
+    let limit: Option<i64> = extract_optional_param(args, "limit")?;
+    KeysetRequest::new(cursor.as_deref(), limit, filters).map_err(AppError::BadRequest)
+}
+
+#[derive(Debug, Clone)]
+pub struct KeysetPage<T> {
+    pub items: Vec<T>,
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyset_cursor_round_trip_and_validation() {
+        let filters = r#"["p",null]"#.to_string();
+        let request = KeysetRequest::new(None, Some(2), filters.clone()).unwrap();
+        let rows = vec![("c", "3"), ("b", "2"), ("a", "1")];
+        let page = request.page(rows, |(created, id)| (created.to_string(), id.to_string()));
+        assert_eq!(page.items.len(), 2);
+        assert!(page.has_more);
+
+        let cursor = page.next_cursor.unwrap();
+        let next = KeysetRequest::new(Some(&cursor), Some(2), filters).unwrap();
+        assert_eq!(next.after.as_ref().unwrap().after(), ("b", "2"));
+        let last = next.page(vec![("a", "1")], |(c, i)| (c.to_string(), i.to_string()));
+        assert!(!last.has_more && last.next_cursor.is_none());
+
+        assert!(
+            KeysetRequest::new(Some(&cursor), None, r#"["q",null]"#.to_string())
+                .unwrap_err()
+                .contains("filters changed")
+        );
+        assert!(
+            KeysetRequest::new(Some("not-a-cursor"), None, String::new())
+                .unwrap_err()
+                .contains("malformed")
+        );
+        assert!(KeysetRequest::new(None, Some(0), String::new()).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use chrono::{Duration, Utc};
 use serde_json::{json, Value};
 use std::fs;
 use std::process::Command;
@@ -6,12 +7,15 @@ use tracing::{debug, info, warn};
 
 use super::pagination::extract_cursor;
 use super::tools::{
-    create_json_error_response, create_json_success_response, extract_optional_param,
-    extract_param, ToolHandler,
+    create_dry_run_response, create_json_error_response, create_json_success_response,
+    dry_run_property, extract_optional_param, extract_param, is_dry_run, ToolHandler,
 };
 use super::types::{CallToolResponse, Tool};
 use crate::{
-    database::projects::{CreateProjectRequest, Project, UpdateProjectRequest},
+    database::{
+        projects::{CreateProjectRequest, Project, UpdateProjectRequest},
+        quotas::{self, OverrideOutcome, Quota},
+    },
     error::Result,
     permissions::create_project_permissions,
     server::AppState,
@@ -446,6 +450,27 @@ impl ToolHandler for DeleteProjectTool {
     async fn call(&self, state: &AppState, arguments: Option<Value>) -> Result<CallToolResponse> {
         let repository_name: String = extract_param(&arguments, "repository_name")?;
 
+        if is_dry_run("delete_project", &arguments) {
+            return Ok(
+                match Project::plan_delete(&state.db, &repository_name).await? {
+                    Some(plan) => create_dry_run_response(
+                        &format!(
+                            "Deleting project '{}' would remove {} tickets, {} worker types and {} workers",
+                            repository_name,
+                            plan.tickets.len(),
+                            plan.worker_types.len(),
+                            plan.workers.len()
+                        ),
+                        &plan,
+                    ),
+                    None => create_json_error_response(&format!(
+                        "Project '{}' not found",
+                        repository_name
+                    )),
+                },
+            );
+        }
+
         match Project::delete(&state.db, &repository_name).await {
             Ok(true) => Ok(create_json_success_response(json!({
                 "message": format!("Project '{}' deleted successfully", repository_name),
@@ -465,17 +490,121 @@ impl ToolHandler for DeleteProjectTool {
     fn definition(&self) -> Tool {
         Tool {
             name: "delete_project".to_string(),
-            description: "Delete a project by repository name".to_string(),
+            description:
+                "Delete a project by repository name, with its tickets, worker types and workers"
+                    .to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
                     "repository_name": {
                         "type": "string",
                         "description": "Repository name in org/repo format"
-                    }
+                    },
+                    "dry_run": dry_run_property()
                 },
                 "required": ["repository_name"]
             }),
         }
     }
 }
+
+/// Longest a quota override may last
+const MAX_OVERRIDE_MINUTES: u64 = 7 * 24 * 60;
+
+pub struct OverrideProjectQuotaTool;
+
+#[async_trait]
+impl ToolHandler for OverrideProjectQuotaTool {
+    async fn call(&self, state: &AppState, arguments: Option<Value>) -> Result<CallToolResponse> {
+        let project_id: String = extract_param(&arguments, "project_id")?;
+        let quota: String = extract_param(&arguments, "quota")?;
+        let limit: u64 = extract_param(&arguments, "limit")?;
+        let expires_in_minutes: u64 = extract_param(&arguments, "expires_in_minutes")?;
+        let reason: Option<String> = extract_optional_param(&arguments, "reason")?;
+
+        let quota: Quota = match quota.parse() {
+            Ok(quota) => quota,
+            Err(e) => return Ok(create_json_error_response(&e.to_string())),
+        };
+        if !(1..=MAX_OVERRIDE_MINUTES).contains(&expires_in_minutes) {
+            return Ok(create_json_error_response(&format!(
+                "expires_in_minutes must be between 1 and {}",
+                MAX_OVERRIDE_MINUTES
+            )));
+        }
+        if Project::get_by_name(&state.db, &project_id)
+            .await?
+            .is_none()
+        {
+            return Ok(create_json_error_response(&format!(
+                "Project '{}' not found",
+                project_id
+            )));
+        }
+
+        let expires_at = (Utc::now() + Duration::minutes(expires_in_minutes as i64))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        let created = match quotas::set_override(
+            &state.db,
+            &project_id,
+            quota,
+            limit,
+            &expires_at,
+            "coordinator",
+            reason.as_deref(),
+        )
+        .await?
+        {
+            OverrideOutcome::Created(created) => created,
+            OverrideOutcome::Rejected(reason) => return Ok(create_json_error_response(&reason)),
+        };
+        info!(
+            "Raised {} quota of project '{}' to {} until {}",
+            quota, project_id, limit, expires_at
+        );
+
+        Ok(create_json_success_response(json!({
+            "message": format!("{} quota of project '{}' raised until {}", quota, project_id, expires_at),
+            "override": created,
+            "quotas": quotas::report(&state.db, &project_id).await?,
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "override_project_quota".to_string(),
+            description: "Temporarily raise a project's quota, e.g. while a large batch of tickets is expected. The override expires on its own; the newest active override applies".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "project_id": {
+                        "type": "string",
+                        "description": "Repository name in org/repo format"
+                    },
+                    "quota": {
+                        "type": "string",
+                        "enum": ["open_tickets", "comments_per_hour"],
+                        "description": "Quota to raise"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "New limit, above the current one; 0 lifts the quota"
+                    },
+                    "expires_in_minutes": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": MAX_OVERRIDE_MINUTES,
+                        "description": "Minutes until the override expires"
+                    },
+                    "reason": {
+                        "type": "string",
+                        "description": "Why the limit is raised, kept with the override"
+                    }
+                },
+                "required": ["project_id", "quota", "limit", "expires_in_minutes"]
+            }),
+        }
+    }
+}
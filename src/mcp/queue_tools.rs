@@ -0,0 +1,368 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+use super::{
+    tools::{
+        create_json_error_response, create_json_success_response, extract_optional_param,
+        extract_param, ToolHandler,
+    },
+    types::{CallToolResponse, Tool},
+};
+use crate::{
+    database::worker_runs::{RunFilter, WorkerRun, WorkerRunInput},
+    server::AppState,
+    workers::input::MAX_WORKER_INPUT_BYTES,
+};
+
+pub struct GetSpawnQueueStatusTool;
+
+#[async_trait]
+impl ToolHandler for GetSpawnQueueStatusTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        _arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let limiter = state.queue_manager.spawn_limiter();
+        let (max_concurrent_workers, max_workers_per_project) = limiter.limits();
+        let queued = limiter.queue_status();
+
+        Ok(create_json_success_response(json!({
+            "running": limiter.running_count(),
+            "max_concurrent_workers": max_concurrent_workers,
+            "max_workers_per_project": max_workers_per_project,
+            "queued_count": queued.len(),
+            "queued": queued,
+            "stats": limiter.stats()
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "get_spawn_queue_status".to_string(),
+            description: "Show worker spawns waiting for a free slot, in promotion order (priority with aging), with queue positions, ages, per-priority stats and current concurrency limits".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {}
+            }),
+        }
+    }
+}
+
+pub struct CancelQueuedSpawnTool;
+
+#[async_trait]
+impl ToolHandler for CancelQueuedSpawnTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let ticket_id: String = extract_param(&arguments, "ticket_id")?;
+
+        if !state.queue_manager.spawn_limiter().cancel(&ticket_id) {
+            return Ok(create_json_error_response(&format!(
+                "No queued worker spawn for ticket '{}'",
+                ticket_id
+            )));
+        }
+
+        info!("Cancelled queued worker spawn for ticket {}", ticket_id);
+        Ok(create_json_success_response(json!({
+            "message": format!("Cancelled queued worker spawn for ticket '{}'; ticket placed on hold", ticket_id),
+            "ticket_id": ticket_id
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "cancel_queued_spawn".to_string(),
+            description: "Cancel a worker spawn that is waiting in the spawn queue. The ticket is placed on hold and can be resumed with resume_ticket_processing".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "ticket_id": {
+                        "type": "string",
+                        "description": "Ticket whose queued spawn should be cancelled"
+                    }
+                },
+                "required": ["ticket_id"]
+            }),
+        }
+    }
+}
+
+pub struct GetWorkerStatusTool;
+
+#[async_trait]
+impl ToolHandler for GetWorkerStatusTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let limit: Option<i64> = extract_optional_param(&arguments, "limit")?;
+        let filter = RunFilter {
+            project_id: extract_optional_param(&arguments, "project_id")?,
+            ticket_id: extract_optional_param(&arguments, "ticket_id")?,
+            worker_id: extract_optional_param(&arguments, "worker_id")?,
+            limit: limit.unwrap_or(20).clamp(1, 100),
+        };
+
+        let runs = match WorkerRun::list(&state.db, &filter).await {
+            Ok(runs) => runs,
+            Err(e) => {
+                return Ok(create_json_error_response(&format!(
+                    "Failed to list worker runs: {}",
+                    e
+                )))
+            }
+        };
+
+        let run_ids: Vec<i64> = runs.iter().map(|run| run.id).collect();
+        let inputs = match WorkerRun::inputs(&state.db, &run_ids).await {
+            Ok(inputs) => inputs,
+            Err(e) => {
+                return Ok(create_json_error_response(&format!(
+                    "Failed to load worker run inputs: {}",
+                    e
+                )))
+            }
+        };
+
+        let runs: Vec<Value> = runs
+            .into_iter()
+            .map(|run| {
+                let run_inputs: Vec<Value> = inputs
+                    .iter()
+                    .filter(|input| input.run_id == run.id)
+                    .map(|input| {
+                        json!({
+                            "input_id": input.input_id,
+                            "seq": input.seq,
+                            "content": input.content,
+                            "sent_by": input.sent_by,
+                            "sent_at": input.sent_at
+                        })
+                    })
+                    .collect();
+                json!({
+                    "worker_id": run.worker_id,
+                    "project_id": run.project_id,
+                    "worker_type": run.worker_type,
+                    "ticket_id": run.ticket_id,
+                    "status": run.status,
+                    "pid": run.pid,
+                    "working_dir": run.working_dir,
+                    "max_runtime_secs": run.max_runtime_secs,
+                    "exit_status": run.exit_status,
+                    "enforcement": run.enforcement,
+                    "spawn": if run.warm { "warm" } else { "cold" },
+                    "policy_violations": run.policy_violations(),
+                    "boundary_violations": run.boundary_violations(),
+                    "receiving_input": state.queue_manager.worker_inputs().is_running(&run.worker_id)
+                        && run.status == "running",
+                    "stalled": run.is_stalled(),
+                    "stalled_at": run.stalled_at,
+                    "last_heartbeat_at": run.last_heartbeat_at,
+                    "progress": run.progress,
+                    "inputs": run_inputs,
+                    "started_at": run.started_at,
+                    "finished_at": run.finished_at
+                })
+            })
+            .collect();
+
+        let warm_pool = state.queue_manager.warm_pool();
+        let idle_warm: Vec<_> = warm_pool
+            .idle_workers()
+            .into_iter()
+            .filter(|worker| {
+                filter
+                    .project_id
+                    .as_ref()
+                    .is_none_or(|project_id| *project_id == worker.project_id)
+            })
+            .collect();
+
+        Ok(create_json_success_response(json!({
+            "count": runs.len(),
+            "runs": runs,
+            "warm_pool": {
+                "idle": idle_warm,
+                "stats": warm_pool.stats()
+            }
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "get_worker_status".to_string(),
+            description: "Show recent worker runs, newest first, with the sandbox each ran under, exit status, whether it was terminated or killed for exceeding its max runtime, any spawn policy violations, the files it changed outside its workspace (boundary_violations), the follow-up instructions each received, and its last reported progress. A running worker that has not reported progress within its stall threshold is marked stalled. Each run's spawn is warm when an idle worker from the warm pool took the ticket and cold when a process was started for it; idle warm workers waiting for a ticket and the pool's claim latency are listed under warm_pool".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "project_id": {
+                        "type": "string",
+                        "description": "Only runs for this project"
+                    },
+                    "ticket_id": {
+                        "type": "string",
+                        "description": "Only runs for this ticket"
+                    },
+                    "worker_id": {
+                        "type": "string",
+                        "description": "Only runs of this worker"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum number of runs to return (default: 20, max: 100)"
+                    }
+                }
+            }),
+        }
+    }
+}
+
+pub struct SendWorkerInputTool;
+
+#[async_trait]
+impl ToolHandler for SendWorkerInputTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let worker_id: String = extract_param(&arguments, "worker_id")?;
+        let content: String = extract_param(&arguments, "content")?;
+        let sent_by: String = extract_optional_param(&arguments, "sent_by")?
+            .unwrap_or_else(|| "coordinator".to_string());
+
+        let inputs = state.queue_manager.worker_inputs();
+        let delivery = match inputs.send(&worker_id, &content).await {
+            Ok(delivery) => delivery,
+            Err(e) => {
+                let running = inputs.running();
+                return Ok(create_json_error_response(&if running.is_empty() {
+                    format!("{}. No workers are running", e)
+                } else {
+                    format!("{}. Running workers: {}", e, running.join(", "))
+                }));
+            }
+        };
+
+        if let Some(run_id) = delivery.run_id {
+            let input = WorkerRunInput {
+                run_id,
+                input_id: delivery.input_id.clone(),
+                seq: delivery.seq,
+                content,
+                sent_by: Some(sent_by),
+                sent_at: delivery.sent_at.clone(),
+            };
+            // The worker already has the instruction; only its history is incomplete
+            if let Err(e) = WorkerRun::record_input(&state.db, &input).await {
+                warn!(
+                    "Instruction {} reached worker {} but was not recorded: {}",
+                    delivery.input_id, worker_id, e
+                );
+            }
+        }
+
+        info!(
+            "Sent instruction #{} ({} bytes) to worker {}",
+            delivery.seq, delivery.bytes, worker_id
+        );
+        Ok(create_json_success_response(json!({
+            "message": format!("Instruction delivered to worker '{}'", worker_id),
+            "delivery": delivery
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "send_worker_input".to_string(),
+            description: format!(
+                "Send a follow-up instruction to a running worker. It is written to the worker's stdin as one JSON line and recorded in the worker's run history (see get_worker_status). Returns a delivery confirmation with the instruction's sequence number within the run. Instructions are limited to {} bytes",
+                MAX_WORKER_INPUT_BYTES
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "worker_id": {
+                        "type": "string",
+                        "description": "Running worker to instruct (as listed by get_worker_status)"
+                    },
+                    "content": {
+                        "type": "string",
+                        "description": "Instruction text"
+                    },
+                    "sent_by": {
+                        "type": "string",
+                        "description": "Who is sending the instruction (default: coordinator)"
+                    }
+                },
+                "required": ["worker_id", "content"]
+            }),
+        }
+    }
+}
+
+pub struct ReportWorkerProgressTool;
+
+#[async_trait]
+impl ToolHandler for ReportWorkerProgressTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let worker_id: String = extract_param(&arguments, "worker_id")?;
+        let progress: Option<String> = extract_optional_param(&arguments, "progress")?;
+
+        let run = match WorkerRun::heartbeat(&state.db, &worker_id, progress.as_deref()).await {
+            Ok(Some(run)) => run,
+            Ok(None) => {
+                return Ok(create_json_error_response(&format!(
+                    "Worker '{}' has no running run",
+                    worker_id
+                )))
+            }
+            Err(e) => {
+                return Ok(create_json_error_response(&format!(
+                    "Failed to record progress: {}",
+                    e
+                )))
+            }
+        };
+
+        Ok(create_json_success_response(json!({
+            "message": format!("Progress of worker '{}' recorded", worker_id),
+            "ticket_id": run.ticket_id,
+            "last_heartbeat_at": run.last_heartbeat_at,
+            "progress": run.progress
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "report_worker_progress".to_string(),
+            description: "Report that a running worker is still making progress. Workers call this periodically with a short note on what they are doing; a worker that stays quiet past its stall threshold is flagged as stalled and may be stopped".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "worker_id": {
+                        "type": "string",
+                        "description": "Reporting worker (given in its system prompt)"
+                    },
+                    "progress": {
+                        "type": "string",
+                        "description": "One-line note on the current step"
+                    }
+                },
+                "required": ["worker_id"]
+            }),
+        }
+    }
+}
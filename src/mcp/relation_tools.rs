@@ -0,0 +1,172 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tracing::info;
+
+use super::{
+    tools::{create_json_error_response, create_json_success_response, extract_param, ToolHandler},
+    types::{CallToolResponse, Tool},
+};
+use crate::{
+    database::relations::{self, RelationOutcome, RelationType},
+    server::AppState,
+};
+
+/// Relation type argument, or the error response naming the valid ones
+fn relation_type_arg(args: &Value) -> crate::error::Result<Result<RelationType, CallToolResponse>> {
+    let value: String = extract_param(&Some(args.clone()), "relation_type")?;
+    Ok(RelationType::parse(&value).ok_or_else(|| {
+        let valid: Vec<&str> = RelationType::all().iter().map(|t| t.as_str()).collect();
+        create_json_error_response(&format!(
+            "Invalid relation_type '{}'; expected one of: {}",
+            value,
+            valid.join(", ")
+        ))
+    }))
+}
+
+fn relation_schema(action: &str) -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "ticket_id": {
+                "type": "string",
+                "description": "Ticket the relation reads from, e.g. the parent of parent_of or the duplicate of duplicates"
+            },
+            "relation_type": {
+                "type": "string",
+                "enum": ["parent_of", "relates_to", "duplicates", "follows"],
+                "description": format!("Type of the relation to {}", action)
+            },
+            "related_ticket_id": {
+                "type": "string",
+                "description": "Ticket the relation points to"
+            }
+        },
+        "required": ["ticket_id", "relation_type", "related_ticket_id"]
+    })
+}
+
+pub struct AddTicketRelationTool;
+
+#[async_trait]
+impl ToolHandler for AddTicketRelationTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let args = arguments
+            .ok_or_else(|| crate::error::AppError::BadRequest("Missing arguments".to_string()))?;
+
+        let ticket_id: String = extract_param(&Some(args.clone()), "ticket_id")?;
+        let related_ticket_id: String = extract_param(&Some(args.clone()), "related_ticket_id")?;
+        let relation_type = match relation_type_arg(&args)? {
+            Ok(relation_type) => relation_type,
+            Err(response) => return Ok(response),
+        };
+
+        match state
+            .queue_manager
+            .add_relation(&ticket_id, &related_ticket_id, relation_type)
+            .await?
+        {
+            RelationOutcome::Applied => {
+                info!(
+                    "Related ticket {} {} {}",
+                    ticket_id, relation_type, related_ticket_id
+                );
+                Ok(create_json_success_response(json!({
+                    "message": format!("Ticket {} {} {}", ticket_id, relation_type, related_ticket_id),
+                    "relations": relations::list(&state.db, &ticket_id).await?
+                })))
+            }
+            RelationOutcome::Rejected(reason) => Ok(create_json_error_response(&reason)),
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "add_ticket_relation".to_string(),
+            description: "Relate two tickets: parent_of (an epic and its subtask; one parent per ticket, no cycles), relates_to (a plain link), duplicates (closes ticket_id as a duplicate of related_ticket_id when it is still open) or follows (stage pipeline order). Unlike dependencies, relations never block processing".to_string(),
+            input_schema: relation_schema("add"),
+        }
+    }
+}
+
+pub struct RemoveTicketRelationTool;
+
+#[async_trait]
+impl ToolHandler for RemoveTicketRelationTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let args = arguments
+            .ok_or_else(|| crate::error::AppError::BadRequest("Missing arguments".to_string()))?;
+
+        let ticket_id: String = extract_param(&Some(args.clone()), "ticket_id")?;
+        let related_ticket_id: String = extract_param(&Some(args.clone()), "related_ticket_id")?;
+        let relation_type = match relation_type_arg(&args)? {
+            Ok(relation_type) => relation_type,
+            Err(response) => return Ok(response),
+        };
+
+        match relations::remove(&state.db, &ticket_id, &related_ticket_id, relation_type).await? {
+            RelationOutcome::Applied => {
+                info!(
+                    "Removed relation {} {} {}",
+                    ticket_id, relation_type, related_ticket_id
+                );
+                Ok(create_json_success_response(json!({
+                    "message": format!("Ticket {} no longer {} {}", ticket_id, relation_type, related_ticket_id),
+                    "relations": relations::list(&state.db, &ticket_id).await?
+                })))
+            }
+            RelationOutcome::Rejected(reason) => Ok(create_json_error_response(&reason)),
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "remove_ticket_relation".to_string(),
+            description: "Remove a relation between two tickets. Removing duplicates leaves the duplicate closed".to_string(),
+            input_schema: relation_schema("remove"),
+        }
+    }
+}
+
+pub struct ListTicketRelationsTool;
+
+#[async_trait]
+impl ToolHandler for ListTicketRelationsTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let ticket_id: String = extract_param(&arguments, "ticket_id")?;
+        let relations = relations::list(&state.db, &ticket_id).await?;
+        Ok(create_json_success_response(json!({
+            "ticket_id": ticket_id,
+            "relations": relations
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "list_ticket_relations".to_string(),
+            description: "List a ticket's relations grouped by type and direction (parent_of, child_of, relates_to, duplicates, duplicated_by, follows, followed_by), with closed/total child progress for parent tickets".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "ticket_id": {
+                        "type": "string",
+                        "description": "Ticket identifier"
+                    }
+                },
+                "required": ["ticket_id"]
+            }),
+        }
+    }
+}
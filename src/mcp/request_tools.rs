@@ -0,0 +1,479 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tracing::info;
+
+use super::{
+    access::ToolCaller,
+    pagination::{extract_keyset, KeysetCursor},
+    tools::{
+        create_json_error_response, create_json_success_response, extract_optional_param,
+        extract_param, ToolHandler,
+    },
+    types::{CallToolResponse, Tool},
+};
+use crate::{
+    agent_requests::{deliver, notify},
+    database::{
+        agent_groups::AgentGroup,
+        agent_requests::{AckOutcome, AgentRequest, RequestStatus, SettleOutcome},
+    },
+    server::AppState,
+};
+
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 300;
+const MAX_REQUEST_TIMEOUT_SECS: u64 = 86400;
+
+fn request_json(request: &AgentRequest) -> Value {
+    json!({
+        "correlation_id": request.correlation_id,
+        "requester": request.requester,
+        "target": request.target,
+        "ticket_id": request.ticket_id,
+        "project_id": request.project_id,
+        "payload": serde_json::from_str::<Value>(&request.payload).unwrap_or(Value::Null),
+        "response": request
+            .response
+            .as_deref()
+            .and_then(|r| serde_json::from_str::<Value>(r).ok()),
+        "status": request.status,
+        "deadline": request.deadline,
+        "created_at": request.created_at,
+        "responded_at": request.responded_at,
+        "delivered_at": request.delivered_at,
+        "acknowledged_at": request.acknowledged_at,
+        "group_id": request.group_id
+    })
+}
+
+/// Send one copy of a request to each current member of a group. Each copy is delivered,
+/// acknowledged and answered like a request to that member alone.
+async fn send_to_group(
+    state: &AppState,
+    requester: &str,
+    group_id: &str,
+    ticket_id: Option<&str>,
+    payload: &Value,
+    timeout_secs: u64,
+) -> crate::error::Result<CallToolResponse> {
+    let Some(group) = AgentGroup::get(&state.db, group_id).await? else {
+        return Ok(create_json_error_response(&format!(
+            "Agent group '{}' not found",
+            group_id
+        )));
+    };
+    let requests = AgentRequest::create_for_group(
+        &state.db,
+        requester,
+        &group.group_id,
+        ticket_id,
+        &payload.to_string(),
+        timeout_secs,
+    )
+    .await?;
+    if requests.is_empty() {
+        return Ok(create_json_error_response(&format!(
+            "Agent group '{}' has no members",
+            group.group_id
+        )));
+    }
+
+    info!(
+        "Request from '{}' sent to {} members of group '{}' (timeout {}s)",
+        requester,
+        requests.len(),
+        group.group_id,
+        timeout_secs
+    );
+    for request in &requests {
+        deliver(&state.db, &state.event_broadcaster, request).await?;
+    }
+
+    Ok(create_json_success_response(json!({
+        "message": format!("Request sent to {} members of group '{}'. Each member answers its own copy; poll get_request_status with each correlation id", requests.len(), group.group_id),
+        "group_id": group.group_id,
+        "deadline": requests[0].deadline,
+        "requests": requests
+            .iter()
+            .map(|request| json!({
+                "target": request.target,
+                "correlation_id": request.correlation_id
+            }))
+            .collect::<Vec<_>>()
+    })))
+}
+
+pub struct SendAgentRequestTool;
+
+#[async_trait]
+impl ToolHandler for SendAgentRequestTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        self.call_as(state, &ToolCaller::Coordinator, arguments)
+            .await
+    }
+
+    /// The request is sent as the caller, so no agent can send on another's behalf
+    async fn call_as(
+        &self,
+        state: &AppState,
+        caller: &ToolCaller,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let requester = caller.actor_id();
+        let target: Option<String> = extract_optional_param(&arguments, "target")?;
+        let group_id: Option<String> = extract_optional_param(&arguments, "group_id")?;
+        let payload: Value = extract_param(&arguments, "payload")?;
+        let ticket_id: Option<String> = extract_optional_param(&arguments, "ticket_id")?;
+        let timeout_secs: u64 = extract_optional_param(&arguments, "timeout_secs")?
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS);
+
+        if timeout_secs == 0 || timeout_secs > MAX_REQUEST_TIMEOUT_SECS {
+            return Ok(create_json_error_response(&format!(
+                "timeout_secs must be between 1 and {}",
+                MAX_REQUEST_TIMEOUT_SECS
+            )));
+        }
+
+        let target = match (target, group_id) {
+            (Some(target), None) => target,
+            (None, Some(group_id)) => {
+                return send_to_group(
+                    state,
+                    requester,
+                    &group_id,
+                    ticket_id.as_deref(),
+                    &payload,
+                    timeout_secs,
+                )
+                .await;
+            }
+            _ => {
+                return Ok(create_json_error_response(
+                    "Provide either target or group_id",
+                ))
+            }
+        };
+        if target.trim().is_empty() {
+            return Ok(create_json_error_response("Target must not be empty"));
+        }
+
+        let request = AgentRequest::create(
+            &state.db,
+            requester,
+            &target,
+            ticket_id.as_deref(),
+            &payload.to_string(),
+            timeout_secs,
+        )
+        .await?;
+
+        info!(
+            "Request {} sent from '{}' to '{}' (timeout {}s)",
+            request.correlation_id, requester, target, timeout_secs
+        );
+        deliver(&state.db, &state.event_broadcaster, &request).await?;
+
+        Ok(create_json_success_response(json!({
+            "message": format!("Request sent to '{}'. Poll get_request_status with the correlation id for the response", target),
+            "correlation_id": request.correlation_id,
+            "deadline": request.deadline
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "send_agent_request".to_string(),
+            description: "Send a request from you to another agent (the coordinator or a worker id), or to every member of an agent group. Returns a correlation id per recipient; the target answers with respond_to_request and the requester polls get_request_status or waits for the push notification. Requests not answered before the deadline expire".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "target": {
+                        "type": "string",
+                        "description": "Agent the request is addressed to ('coordinator' or a worker id); give this or group_id"
+                    },
+                    "group_id": {
+                        "type": "string",
+                        "description": "Agent group to send the request to instead of a single target; each current member gets its own copy"
+                    },
+                    "payload": {
+                        "description": "Request payload (any JSON value)"
+                    },
+                    "ticket_id": {
+                        "type": "string",
+                        "description": "Ticket the request relates to (optional)"
+                    },
+                    "timeout_secs": {
+                        "type": "integer",
+                        "description": "Seconds until the request expires (default: 300, max: 86400)"
+                    }
+                },
+                "required": ["payload"]
+            }),
+        }
+    }
+}
+
+pub struct RespondToRequestTool;
+
+#[async_trait]
+impl ToolHandler for RespondToRequestTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        self.call_as(state, &ToolCaller::Coordinator, arguments)
+            .await
+    }
+
+    /// Only the request's target may answer it, so the responder is the caller
+    async fn call_as(
+        &self,
+        state: &AppState,
+        caller: &ToolCaller,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let responder = caller.actor_id();
+        let correlation_id: String = extract_param(&arguments, "correlation_id")?;
+        let result: Value = extract_optional_param(&arguments, "result")?.unwrap_or(Value::Null);
+        let reject: bool = extract_optional_param(&arguments, "reject")?.unwrap_or(false);
+
+        let status = if reject {
+            RequestStatus::Rejected
+        } else {
+            RequestStatus::Answered
+        };
+
+        match AgentRequest::settle(
+            &state.db,
+            &correlation_id,
+            responder,
+            status,
+            &result.to_string(),
+        )
+        .await?
+        {
+            SettleOutcome::Settled(request) => {
+                info!(
+                    "Request {} {} by '{}'",
+                    correlation_id, request.status, responder
+                );
+                notify(&state.event_broadcaster, &request, &request.requester);
+
+                Ok(create_json_success_response(json!({
+                    "message": format!("Request {} marked as {}", correlation_id, request.status),
+                    "correlation_id": correlation_id,
+                    "status": request.status
+                })))
+            }
+            SettleOutcome::NotFound => Ok(create_json_error_response(&format!(
+                "Request {} not found",
+                correlation_id
+            ))),
+            SettleOutcome::NotRecipient { target } => Ok(create_json_error_response(&format!(
+                "Request {} is addressed to '{}', not '{}'",
+                correlation_id, target, responder
+            ))),
+            SettleOutcome::PastDeadline => Ok(create_json_error_response(&format!(
+                "Request {} is past its deadline",
+                correlation_id
+            ))),
+            SettleOutcome::AlreadySettled { status } => Ok(create_json_error_response(&format!(
+                "Request {} is already {}",
+                correlation_id, status
+            ))),
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "respond_to_request".to_string(),
+            description: "Answer (or reject) a pending request addressed to you, identified by its correlation id. The requester is notified of the response".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "correlation_id": {
+                        "type": "string",
+                        "description": "Correlation id of the request being answered"
+                    },
+                    "result": {
+                        "description": "Response payload (any JSON value)"
+                    },
+                    "reject": {
+                        "type": "boolean",
+                        "description": "Reject the request instead of answering it (default: false)"
+                    }
+                },
+                "required": ["correlation_id"]
+            }),
+        }
+    }
+}
+
+pub struct GetRequestStatusTool;
+
+#[async_trait]
+impl ToolHandler for GetRequestStatusTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let correlation_id: String = extract_param(&arguments, "correlation_id")?;
+
+        match AgentRequest::get_by_id(&state.db, &correlation_id).await? {
+            Some(request) => Ok(create_json_success_response(request_json(&request))),
+            None => Ok(create_json_error_response(&format!(
+                "Request {} not found",
+                correlation_id
+            ))),
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "get_request_status".to_string(),
+            description: "Get the status (pending, answered, expired, rejected) and response of a request by correlation id".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "correlation_id": {
+                        "type": "string",
+                        "description": "Correlation id returned by send_agent_request"
+                    }
+                },
+                "required": ["correlation_id"]
+            }),
+        }
+    }
+}
+
+pub struct GetPendingRequestsTool;
+
+#[async_trait]
+impl ToolHandler for GetPendingRequestsTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        self.call_as(state, &ToolCaller::Coordinator, arguments)
+            .await
+    }
+
+    /// Fetching marks the requests delivered, so only their target may fetch them
+    async fn call_as(
+        &self,
+        state: &AppState,
+        caller: &ToolCaller,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let agent = caller.actor_id();
+
+        let page_request = extract_keyset(&arguments, json!([agent]).to_string())?;
+
+        let (rows, remaining) = tokio::try_join!(
+            AgentRequest::take_pending(
+                &state.db,
+                agent,
+                page_request.after.as_ref().map(KeysetCursor::after),
+                page_request.limit,
+            ),
+            AgentRequest::count_pending(&state.db, agent)
+        )?;
+        let page = page_request.page(rows, |r| (r.created_at.clone(), r.correlation_id.clone()));
+        let requests: Vec<Value> = page.items.iter().map(request_json).collect();
+
+        Ok(create_json_success_response(json!({
+            "agent": agent,
+            "count": requests.len(),
+            "requests": requests,
+            "pagination": {
+                "total": remaining,
+                "has_more": page.has_more,
+                "next_cursor": page.next_cursor
+            }
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "get_pending_requests".to_string(),
+            description: "Fetch pending requests addressed to you that you have not acknowledged yet, oldest first, e.g. after reconnecting. Returned requests are marked delivered; confirm each with acknowledge_request. Pass next_cursor back as cursor for the next batch".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum requests per batch (default: 50, max: 200)"
+                    },
+                    "cursor": {
+                        "type": "string",
+                        "description": "next_cursor from the previous batch"
+                    }
+                }
+            }),
+        }
+    }
+}
+
+pub struct AcknowledgeRequestTool;
+
+#[async_trait]
+impl ToolHandler for AcknowledgeRequestTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        self.call_as(state, &ToolCaller::Coordinator, arguments)
+            .await
+    }
+
+    /// Only the request's target may acknowledge it, so the acknowledging agent is the caller
+    async fn call_as(
+        &self,
+        state: &AppState,
+        caller: &ToolCaller,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let agent = caller.actor_id();
+        let correlation_id: String = extract_param(&arguments, "correlation_id")?;
+
+        match AgentRequest::acknowledge(&state.db, &correlation_id, agent).await? {
+            AckOutcome::Acknowledged(request) => Ok(create_json_success_response(json!({
+                "message": format!("Request {} acknowledged", correlation_id),
+                "correlation_id": correlation_id,
+                "acknowledged_at": request.acknowledged_at
+            }))),
+            AckOutcome::NotFound => Ok(create_json_error_response(&format!(
+                "Request {} not found",
+                correlation_id
+            ))),
+            AckOutcome::NotRecipient { target } => Ok(create_json_error_response(&format!(
+                "Request {} is addressed to '{}', not '{}'",
+                correlation_id, target, agent
+            ))),
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "acknowledge_request".to_string(),
+            description: "Confirm that you received a request addressed to you. Only the request's target may acknowledge it; answering still happens through respond_to_request".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "correlation_id": {
+                        "type": "string",
+                        "description": "Correlation id of the request being acknowledged"
+                    }
+                },
+                "required": ["correlation_id"]
+            }),
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tracing::info;
+
+use super::{
+    tools::{
+        create_json_error_response, create_json_success_response, extract_optional_param,
+        extract_param, ToolHandler,
+    },
+    types::{CallToolResponse, Tool},
+};
+use crate::{
+    database::schedules::{CreateScheduleRequest, TicketSchedule, UpdateScheduleRequest},
+    error::{AppError, Result},
+    schedules,
+    server::AppState,
+};
+
+/// Properties shared by create_schedule and update_schedule
+fn schedule_properties() -> Value {
+    json!({
+        "cron": {
+            "type": "string",
+            "description": "When to create tickets, in UTC: @hourly, @daily, @weekly, @monthly, @yearly, or five fields 'minute hour day-of-month month day-of-week' (e.g. '0 9 * * MON')"
+        },
+        "template_name": {
+            "type": "string",
+            "description": "Ticket template supplying the default priority and required custom fields"
+        },
+        "title": {
+            "type": "string",
+            "description": "Title of each created ticket"
+        },
+        "description": {
+            "type": "string",
+            "description": "Description of each created ticket"
+        },
+        "priority": {
+            "type": "string",
+            "enum": ["low", "medium", "high", "urgent"],
+            "description": "Ticket priority (default: the template's, otherwise medium)"
+        },
+        "custom_fields": {
+            "type": "object",
+            "description": "Custom fields of each created ticket, e.g. tags"
+        },
+        "initial_stage": {
+            "type": "string",
+            "description": "Worker type the created tickets are queued for (default: planning)"
+        },
+        "enabled": {
+            "type": "boolean",
+            "description": "Whether the schedule runs (default: true)"
+        },
+        "skip_if_open": {
+            "type": "boolean",
+            "description": "Skip a run while the ticket from the previous run is still open (default: true)"
+        }
+    })
+}
+
+fn parse_arguments<T: serde::de::DeserializeOwned>(arguments: Option<Value>) -> Result<T> {
+    let arguments = arguments.unwrap_or_else(|| json!({}));
+    serde_json::from_value(arguments).map_err(|e| AppError::BadRequest(e.to_string()))
+}
+
+pub struct CreateScheduleTool;
+
+#[async_trait]
+impl ToolHandler for CreateScheduleTool {
+    async fn call(&self, state: &AppState, arguments: Option<Value>) -> Result<CallToolResponse> {
+        let req: CreateScheduleRequest = parse_arguments(arguments)?;
+
+        if TicketSchedule::get_by_name(&state.db, &req.name)
+            .await?
+            .is_some()
+        {
+            return Ok(create_json_error_response(&format!(
+                "Schedule '{}' already exists",
+                req.name
+            )));
+        }
+        let next_run_at = match schedules::validate(state, &req).await {
+            Ok(next_run_at) => next_run_at,
+            Err(e) => return Ok(create_json_error_response(&e.to_string())),
+        };
+
+        let schedule = TicketSchedule::create(&state.db, &req, next_run_at.as_deref()).await?;
+        info!(
+            "Created schedule '{}' for project {} ({})",
+            schedule.name, schedule.project_id, schedule.cron
+        );
+
+        Ok(create_json_success_response(json!({
+            "message": format!("Created schedule '{}'", schedule.name),
+            "schedule": schedule
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        let mut properties = schedule_properties();
+        properties["name"] = json!({
+            "type": "string",
+            "description": "Unique schedule name"
+        });
+        properties["project_id"] = json!({
+            "type": "string",
+            "description": "Project the tickets are created in"
+        });
+
+        Tool {
+            name: "create_schedule".to_string(),
+            description: "Create a recurring schedule that opens a ticket whenever its cron expression comes due, e.g. a weekly dependency audit".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": properties,
+                "required": ["name", "project_id", "cron", "title"]
+            }),
+        }
+    }
+}
+
+pub struct ListSchedulesTool;
+
+#[async_trait]
+impl ToolHandler for ListSchedulesTool {
+    async fn call(&self, state: &AppState, arguments: Option<Value>) -> Result<CallToolResponse> {
+        let project_id: Option<String> = extract_optional_param(&arguments, "project_id")?;
+        let schedules = TicketSchedule::list(&state.db, project_id.as_deref()).await?;
+
+        Ok(create_json_success_response(json!({
+            "schedules": schedules,
+            "count": schedules.len()
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "list_schedules".to_string(),
+            description: "List recurring ticket schedules with their last and next runs"
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "project_id": {
+                        "type": "string",
+                        "description": "Only list schedules of this project"
+                    }
+                },
+                "required": []
+            }),
+        }
+    }
+}
+
+pub struct GetScheduleTool;
+
+#[async_trait]
+impl ToolHandler for GetScheduleTool {
+    async fn call(&self, state: &AppState, arguments: Option<Value>) -> Result<CallToolResponse> {
+        let name: String = extract_param(&arguments, "name")?;
+
+        match TicketSchedule::get_by_name(&state.db, &name).await? {
+            Some(schedule) => Ok(create_json_success_response(json!(schedule))),
+            None => Ok(create_json_error_response(&format!(
+                "Schedule '{}' not found",
+                name
+            ))),
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "get_schedule".to_string(),
+            description: "Get a recurring ticket schedule by name".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Schedule name"
+                    }
+                },
+                "required": ["name"]
+            }),
+        }
+    }
+}
+
+pub struct UpdateScheduleTool;
+
+#[async_trait]
+impl ToolHandler for UpdateScheduleTool {
+    async fn call(&self, state: &AppState, arguments: Option<Value>) -> Result<CallToolResponse> {
+        let name: String = extract_param(&arguments, "name")?;
+        let req: UpdateScheduleRequest = parse_arguments(arguments)?;
+
+        let Some(existing) = TicketSchedule::get_by_name(&state.db, &name).await? else {
+            return Ok(create_json_error_response(&format!(
+                "Schedule '{}' not found",
+                name
+            )));
+        };
+        let updated = req.apply_to(&existing)?;
+        let next_run_at = match schedules::validate(state, &updated).await {
+            Ok(next_run_at) => next_run_at,
+            Err(e) => return Ok(create_json_error_response(&e.to_string())),
+        };
+
+        match TicketSchedule::update(&state.db, &updated, next_run_at.as_deref()).await? {
+            Some(schedule) => Ok(create_json_success_response(json!({
+                "message": format!("Updated schedule '{}'", name),
+                "schedule": schedule
+            }))),
+            None => Ok(create_json_error_response(&format!(
+                "Schedule '{}' not found",
+                name
+            ))),
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        let mut properties = schedule_properties();
+        properties["name"] = json!({
+            "type": "string",
+            "description": "Schedule to update"
+        });
+
+        Tool {
+            name: "update_schedule".to_string(),
+            description: "Update a recurring ticket schedule; omitted fields keep their values and the next run is recomputed".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": properties,
+                "required": ["name"]
+            }),
+        }
+    }
+}
+
+pub struct DeleteScheduleTool;
+
+#[async_trait]
+impl ToolHandler for DeleteScheduleTool {
+    async fn call(&self, state: &AppState, arguments: Option<Value>) -> Result<CallToolResponse> {
+        let name: String = extract_param(&arguments, "name")?;
+
+        if TicketSchedule::delete(&state.db, &name).await? {
+            Ok(create_json_success_response(json!({
+                "message": format!("Deleted schedule '{}'", name)
+            })))
+        } else {
+            Ok(create_json_error_response(&format!(
+                "Schedule '{}' not found",
+                name
+            )))
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "delete_schedule".to_string(),
+            description: "Delete a recurring ticket schedule; tickets it already created are kept"
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Schedule to delete"
+                    }
+                },
+                "required": ["name"]
+            }),
+        }
+    }
+}
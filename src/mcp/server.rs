@@ -1,34 +1,142 @@
-use axum::{extract::State, http::HeaderMap, response::Json};
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
+    Extension,
+};
 use serde_json::Value;
-use tracing::{debug, error, info, trace, warn};
+use std::sync::RwLock;
+use tracing::{debug, error, info, info_span, trace, warn, Instrument};
 
 use super::{
-    dependency_tools::*, event_tools::*, jbct_tools::*, permission_tools::*, project_tools::*,
-    template_tools::*, ticket_tools::*, tools::ToolRegistry, types::*, worker_type_tools::*,
+    access::{ToolAccessPolicy, ToolCaller},
+    attachment_tools::*,
+    capabilities,
+    client_metadata::validate_initialize_params,
+    debug_tools::*,
+    dependency_tools::*,
+    errors::{error_code_table, ErrorDomain, ErrorKind},
+    event_tools::*,
+    group_tools::*,
+    jbct_tools::*,
+    permission_tools::*,
+    project_tools::*,
+    queue_tools::*,
+    relation_tools::*,
+    request_tools::*,
+    schedule_tools::*,
+    session::{declared_client, declared_meta, CloseClass, SessionTracker, SESSION_ID_HEADER},
+    tag_tools::*,
+    template_tools::*,
+    ticket_tools::*,
+    tools::{is_dry_run, ToolRegistry},
+    trace,
+    types::*,
+    worker_type_tools::*,
     MCP_PROTOCOL_VERSION,
 };
-use crate::{config::Config, error::Result, server::AppState};
+use crate::{
+    api::access::ApiCaller,
+    audit::AuditRecord,
+    config::Config,
+    database::{
+        agent_requests::AgentRequest,
+        ticket_refs::{self, AmbiguousTicketRef},
+    },
+    error::{AppError, Result},
+    server::AppState,
+};
+
+/// Tools whose invocations are recorded in the audit log
+const AUDITED_TOOLS: &[&str] = &[
+    "delete_project",
+    "override_project_quota",
+    "delete_worker_type",
+    "report_worker_type_status",
+    "close_ticket",
+    "mark_ticket_duplicate",
+    "add_ticket_relation",
+    "remove_ticket_relation",
+    "transition_pipeline",
+    "resume_ticket_processing",
+    "block_ticket",
+    "unblock_ticket",
+    "resolve_event",
+    "cancel_queued_spawn",
+    "send_worker_input",
+    "delete_schedule",
+    "join_agent_group",
+    "leave_agent_group",
+];
+
+/// Arguments naming an existing ticket, which also accept its short id
+const TICKET_REF_ARGUMENTS: &[&str] = &[
+    "ticket_id",
+    "parent_ticket_id",
+    "child_ticket_id",
+    "source_ticket_id",
+    "next_ticket_id",
+    "duplicate_of",
+    "related_ticket_id",
+    "blocking_ticket_id",
+];
+
+/// Replace short ids in ticket arguments with the ticket ids they name, within the call's
+/// project_id when it has one. References that match nothing are left for the tool to
+/// report.
+async fn resolve_ticket_arguments(
+    state: &AppState,
+    arguments: &mut Option<Value>,
+) -> std::result::Result<(), JsonRpcError> {
+    let Some(Value::Object(args)) = arguments else {
+        return Ok(());
+    };
+    let project_id = args
+        .get("project_id")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    for key in TICKET_REF_ARGUMENTS {
+        let Some(reference) = args.get(*key).and_then(Value::as_str) else {
+            continue;
+        };
+        match ticket_refs::resolve(&state.db, project_id.as_deref(), reference).await {
+            Ok(Some(ticket_id)) => {
+                args.insert(key.to_string(), Value::String(ticket_id));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                if let Some(ambiguous) = e.downcast_ref::<AmbiguousTicketRef>() {
+                    return Err(JsonRpcError::in_domain(
+                        Some(ErrorDomain::Ticket),
+                        ErrorKind::Conflict,
+                        ambiguous.to_string(),
+                    )
+                    .with_details(serde_json::json!({
+                        "argument": key,
+                        "candidates": ambiguous.candidates
+                    })));
+                }
+                return Err(AppError::Internal(e).into());
+            }
+        }
+    }
+    Ok(())
+}
 
 pub struct McpServer {
     pub tools: ToolRegistry,
+    /// Replaced when the `--tool-policy` file is reloaded
+    pub access_policy: RwLock<ToolAccessPolicy>,
+    /// File the access policy is loaded from; without one the tool list never changes
+    pub tool_policy_path: Option<String>,
+    pub strict_client_metadata: bool,
+    pub sessions: SessionTracker,
 }
 
 impl Default for McpServer {
     fn default() -> Self {
-        // Create a default config with WebSocket enabled
-        let config = Config {
-            database_path: String::new(),
-            host: String::new(),
-            port: 0,
-            no_respawn: false,
-            permission_mode: crate::permissions::PermissionMode::File,
-            client_tool_timeout_secs: 30,
-            max_concurrent_client_requests: 50,
-            update_check_interval_hours: 4,
-            disable_update_checks: false,
-            model: None,
-        };
-        Self::new(&config)
+        Self::new(&Config::default())
     }
 }
 
@@ -42,13 +150,41 @@ macro_rules! register_tools {
 }
 
 impl McpServer {
-    pub fn new(_config: &Config) -> Self {
+    pub fn new(config: &Config) -> Self {
+        let tools = Self::tool_registry();
+
+        let access_policy = match &config.tool_policy_path {
+            Some(path) => ToolAccessPolicy::load(path).unwrap_or_else(|e| {
+                error!("Failed to load tool policy, using defaults: {:#}", e);
+                ToolAccessPolicy::default()
+            }),
+            None => ToolAccessPolicy::default(),
+        };
+
+        Self {
+            tools,
+            access_policy: RwLock::new(access_policy),
+            tool_policy_path: config.tool_policy_path.clone(),
+            strict_client_metadata: config.strict_client_metadata,
+            sessions: SessionTracker::with_idle_timeout(std::time::Duration::from_secs(
+                config.session_idle_timeout_secs,
+            )),
+        }
+    }
+
+    /// Every tool the server offers. The registry is the single source of truth for the
+    /// tool list, argument validation, the access policy and worker permissions.
+    pub fn tool_registry() -> ToolRegistry {
         let mut tools = ToolRegistry::new();
 
         Self::register_project_tools(&mut tools);
         Self::register_ticket_tools(&mut tools);
         Self::register_event_tools(&mut tools);
+        Self::register_queue_tools(&mut tools);
+        Self::register_request_tools(&mut tools);
+        Self::register_schedule_tools(&mut tools);
         Self::register_permission_tools(&mut tools);
+        Self::register_debug_tools(&mut tools);
 
         // WebSocket infrastructure is available but MCP tools are removed
 
@@ -58,7 +194,7 @@ impl McpServer {
         // Register JBCT (Java Backend Coding Technology) integration tools
         Self::register_jbct_tools(&mut tools);
 
-        Self { tools }
+        tools
     }
 
     /// Register project and worker type management tools
@@ -71,16 +207,18 @@ impl McpServer {
             GetProjectTool,
             UpdateProjectTool,
             DeleteProjectTool,
+            OverrideProjectQuotaTool,
             // Worker type management tools
             CreateWorkerTypeTool,
             ListWorkerTypesTool,
             GetWorkerTypeTool,
             UpdateWorkerTypeTool,
+            ReportWorkerTypeStatusTool,
             DeleteWorkerTypeTool,
         );
     }
 
-    /// Register ticket and dependency management tools
+    /// Register ticket, dependency and relation management tools
     fn register_ticket_tools(tools: &mut ToolRegistry) {
         register_tools!(
             tools,
@@ -88,15 +226,31 @@ impl McpServer {
             CreateTicketTool,
             GetTicketTool,
             ListTicketsTool,
+            SearchTicketsTool,
             AddTicketCommentTool,
+            SummarizeTicketThreadTool,
             CloseTicketTool,
+            MarkTicketDuplicateTool,
+            SetTicketEstimateTool,
+            TransitionPipelineTool,
             ResumeTicketProcessingTool,
+            // Tag tools
+            ListTagsTool,
+            SetTicketTagsTool,
+            // Attachment tools
+            AddTicketAttachmentTool,
             // Dependency management tools
             AddTicketDependencyTool,
             RemoveTicketDependencyTool,
             GetDependencyGraphTool,
             ListReadyTicketsTool,
             ListBlockedTicketsTool,
+            BlockTicketTool,
+            UnblockTicketTool,
+            // Ticket relation tools
+            AddTicketRelationTool,
+            RemoveTicketRelationTool,
+            ListTicketRelationsTool,
         );
     }
 
@@ -106,10 +260,55 @@ impl McpServer {
             tools,
             ListEventsTool,
             ResolveEventTool,
+            GetLatestDigestTool,
             GetTicketsByStageTool,
         );
     }
 
+    /// Register worker spawn queue tools
+    fn register_queue_tools(tools: &mut ToolRegistry) {
+        register_tools!(
+            tools,
+            GetSpawnQueueStatusTool,
+            CancelQueuedSpawnTool,
+            GetWorkerStatusTool,
+            SendWorkerInputTool,
+            ReportWorkerProgressTool,
+        );
+    }
+
+    /// Register connection debugging tools
+    fn register_debug_tools(tools: &mut ToolRegistry) {
+        register_tools!(tools, DebugConnectionsTool);
+    }
+
+    /// Register agent request/response tools
+    fn register_request_tools(tools: &mut ToolRegistry) {
+        register_tools!(
+            tools,
+            SendAgentRequestTool,
+            RespondToRequestTool,
+            GetRequestStatusTool,
+            GetPendingRequestsTool,
+            AcknowledgeRequestTool,
+            ListAgentGroupsTool,
+            JoinAgentGroupTool,
+            LeaveAgentGroupTool,
+        );
+    }
+
+    /// Register recurring ticket schedule tools
+    fn register_schedule_tools(tools: &mut ToolRegistry) {
+        register_tools!(
+            tools,
+            CreateScheduleTool,
+            ListSchedulesTool,
+            GetScheduleTool,
+            UpdateScheduleTool,
+            DeleteScheduleTool,
+        );
+    }
+
     /// Register permission management tools
     fn register_permission_tools(tools: &mut ToolRegistry) {
         register_tools!(tools, GetPermissionModelTool,);
@@ -134,30 +333,156 @@ impl McpServer {
         &self,
         state: &AppState,
         request: JsonRpcRequest,
+        session_id: Option<&str>,
     ) -> JsonRpcResponse {
-        debug!("Handling MCP request: {}", request.method);
+        self.handle_request_as(state, request, &ToolCaller::Coordinator, session_id)
+            .await
+    }
+
+    /// Advance the handshake state of a session, returning an error response
+    /// if the request is not allowed in its current phase
+    pub fn check_session(
+        &self,
+        session_id: &str,
+        request: &JsonRpcRequest,
+    ) -> Option<JsonRpcResponse> {
+        match self.sessions.admit(session_id, &request.method) {
+            Ok(()) => None,
+            Err(error) => {
+                warn!(
+                    "Rejected '{}' for session {}: {}",
+                    request.method, session_id, error.message
+                );
+                Some(JsonRpcResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id: request.id.clone(),
+                    result: None,
+                    error: Some(error),
+                })
+            }
+        }
+    }
+
+    /// Tools `caller` may see and call under the current access policy
+    pub fn visible_tools(&self, caller: &ToolCaller) -> Vec<Tool> {
+        let policy = self.access_policy.read().unwrap();
+        self.tools
+            .list_tools()
+            .into_iter()
+            .filter(|tool| policy.is_allowed(caller, &tool.name))
+            .collect()
+    }
+
+    /// Load the `--tool-policy` file again and use it from now on. Returns whether the
+    /// coordinator's tool list changed, in which case clients should be told.
+    pub fn reload_access_policy(&self) -> anyhow::Result<bool> {
+        let Some(path) = &self.tool_policy_path else {
+            anyhow::bail!("No tool policy file is configured (--tool-policy)");
+        };
+        let policy = ToolAccessPolicy::load(path)?;
+
+        let names = |tools: Vec<Tool>| {
+            tools
+                .into_iter()
+                .map(|t| t.name)
+                .collect::<std::collections::BTreeSet<_>>()
+        };
+        let before = names(self.visible_tools(&ToolCaller::Coordinator));
+        *self.access_policy.write().unwrap() = policy;
+        let after = names(self.visible_tools(&ToolCaller::Coordinator));
+
+        info!(
+            "Reloaded tool policy from {}: {} coordinator tools (was {})",
+            path,
+            after.len(),
+            before.len()
+        );
+        Ok(before != after)
+    }
+
+    /// Capabilities advertised at initialize; only features the server actually has
+    /// are included, so clients never negotiate something that is not there
+    pub fn capabilities(&self) -> ServerCapabilities {
+        ServerCapabilities {
+            tools: (!self.tools.list_tools().is_empty()).then_some(ToolsCapability {
+                list_changed: self.tool_policy_path.is_some(),
+            }),
+            prompts: (!available_prompts().is_empty()).then_some(PromptsCapability {
+                list_changed: false,
+            }),
+            resources: (!available_resources().is_empty()).then_some(ResourcesCapability {
+                subscribe: true,
+                list_changed: false,
+            }),
+        }
+    }
+
+    /// Handle a request on behalf of a specific caller, applying its tool allowlist;
+    /// `session_id` is the MCP session it came in on, for the audit log.
+    ///
+    /// Everything logged while handling the request carries its trace id, which is
+    /// echoed back in the response so clients can quote it.
+    pub async fn handle_request_as(
+        &self,
+        state: &AppState,
+        request: JsonRpcRequest,
+        caller: &ToolCaller,
+        session_id: Option<&str>,
+    ) -> JsonRpcResponse {
+        let trace_id = trace::trace_id_for(&request);
+        let span = info_span!(
+            "mcp_request",
+            trace_id = %trace_id,
+            method = %request.method,
+            caller = %caller.role()
+        );
+
+        let mut response = self
+            .dispatch_request(state, request, caller, session_id)
+            .instrument(span)
+            .await;
+        trace::attach_trace_id(&mut response, &trace_id);
+        response
+    }
+
+    async fn dispatch_request(
+        &self,
+        state: &AppState,
+        request: JsonRpcRequest,
+        caller: &ToolCaller,
+        session_id: Option<&str>,
+    ) -> JsonRpcResponse {
+        debug!(
+            "Handling MCP request: {} (caller: {})",
+            request.method,
+            caller.role()
+        );
 
         let response = match request.method.as_str() {
-            "initialize" => self.handle_initialize(request.params).await,
+            "initialize" => self.handle_initialize(state, request.params, caller).await,
             "notifications/initialized" => self.handle_initialized().await,
+            "ping" => Ok(serde_json::json!({})),
             "tools/list" => {
                 // Check if this is a paginated request by looking for params
                 if request.params.is_some() {
-                    self.handle_list_tools_with_pagination(request.params).await
+                    self.handle_list_tools_with_pagination(request.params, caller)
+                        .await
                 } else {
-                    self.handle_list_tools().await
+                    self.handle_list_tools(caller).await
                 }
             }
-            "tools/call" => self.handle_call_tool(state, request.params).await,
+            "tools/call" => {
+                self.handle_call_tool(state, request.params, caller, session_id)
+                    .await
+            }
             "prompts/list" => self.handle_list_prompts().await,
             "prompts/get" => self.handle_get_prompt(request.params).await,
             "resources/list" => self.handle_list_resources().await,
             "resources/read" => self.handle_read_resource(request.params).await,
-            _ => Err(JsonRpcError {
-                code: METHOD_NOT_FOUND,
-                message: format!("Method '{}' not found", request.method),
-                data: None,
-            }),
+            _ => Err(JsonRpcError::new(
+                ErrorKind::MethodNotFound,
+                format!("Method '{}' not found", request.method),
+            )),
         };
 
         match response {
@@ -178,24 +503,41 @@ impl McpServer {
 
     async fn handle_initialize(
         &self,
+        state: &AppState,
         params: Option<Value>,
+        caller: &ToolCaller,
     ) -> std::result::Result<Value, JsonRpcError> {
         info!("Handling initialize request");
 
-        let request: InitializeRequest = match params {
-            Some(params) => serde_json::from_value(params).map_err(|e| JsonRpcError {
-                code: INVALID_PARAMS,
-                message: format!("Invalid initialize params: {}", e),
-                data: None,
-            })?,
-            None => {
-                return Err(JsonRpcError {
-                    code: INVALID_PARAMS,
-                    message: "Missing initialize parameters".to_string(),
-                    data: None,
-                })
-            }
-        };
+        let params = params.ok_or_else(|| {
+            JsonRpcError::new(
+                ErrorKind::ValidationFailed,
+                "Missing initialize parameters".to_string(),
+            )
+        })?;
+
+        let validation = validate_initialize_params(&params, self.strict_client_metadata);
+        if !validation.is_valid() {
+            warn!("Rejected initialize metadata: {:?}", validation.errors);
+            return Err(JsonRpcError::new(
+                ErrorKind::ValidationFailed,
+                format!(
+                    "Invalid initialize params: {}",
+                    validation.errors.join("; ")
+                ),
+            )
+            .with_details(serde_json::json!({ "errors": validation.errors })));
+        }
+        if !validation.warnings.is_empty() {
+            warn!("Initialize metadata warnings: {:?}", validation.warnings);
+        }
+
+        let request: InitializeRequest = serde_json::from_value(params).map_err(|e| {
+            JsonRpcError::new(
+                ErrorKind::ValidationFailed,
+                format!("Invalid initialize params: {}", e),
+            )
+        })?;
 
         // Log protocol version negotiation
         let client_version = &request.protocol_version;
@@ -216,33 +558,58 @@ impl McpServer {
 
         let response = InitializeResponse {
             protocol_version: server_supported_version.to_string(),
-            capabilities: ServerCapabilities {
-                tools: ToolsCapability {
-                    list_changed: false,
-                },
-                prompts: PromptsCapability {
-                    list_changed: false,
-                },
-                resources: Some(super::types::ResourcesCapability {
-                    subscribe: true,
-                    list_changed: false,
-                }),
-            },
+            capabilities: self.capabilities(),
             server_info: ServerInfo {
                 name: "vibe-ensemble-mcp".to_string(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
             },
+            meta: self
+                .initialize_meta(state, caller, validation.warnings)
+                .await,
         };
 
-        let result = serde_json::to_value(response).map_err(|e| JsonRpcError {
-            code: INTERNAL_ERROR,
-            message: format!("Failed to serialize response: {}", e),
-            data: None,
+        let result = serde_json::to_value(response).map_err(|e| {
+            JsonRpcError::new(
+                ErrorKind::Internal,
+                format!("Failed to serialize response: {}", e),
+            )
         })?;
 
         Ok(result)
     }
 
+    /// `_meta` of the initialize response: metadata warnings, and how many requests wait
+    /// for the connecting agent, so one reconnecting after being offline knows to replay them
+    async fn initialize_meta(
+        &self,
+        state: &AppState,
+        caller: &ToolCaller,
+        warnings: Vec<String>,
+    ) -> Option<Value> {
+        let mut meta = serde_json::Map::new();
+        if !warnings.is_empty() {
+            meta.insert("warnings".to_string(), serde_json::json!(warnings));
+        }
+        match AgentRequest::count_pending(&state.db, caller.actor_id()).await {
+            Ok(0) => {}
+            Ok(pending) => {
+                meta.insert(
+                    "pending_requests".to_string(),
+                    serde_json::json!({
+                        "count": pending,
+                        "hint": "Fetch them with get_pending_requests and confirm each with acknowledge_request"
+                    }),
+                );
+            }
+            Err(e) => warn!(
+                "Failed to count pending requests for '{}': {}",
+                caller.actor_id(),
+                e
+            ),
+        }
+        (!meta.is_empty()).then_some(Value::Object(meta))
+    }
+
     async fn handle_initialized(&self) -> std::result::Result<Value, JsonRpcError> {
         info!("Handling notifications/initialized request");
 
@@ -251,22 +618,27 @@ impl McpServer {
         Ok(Value::Null)
     }
 
-    async fn handle_list_tools(&self) -> std::result::Result<Value, JsonRpcError> {
-        self.handle_list_tools_with_pagination(None).await
+    async fn handle_list_tools(
+        &self,
+        caller: &ToolCaller,
+    ) -> std::result::Result<Value, JsonRpcError> {
+        self.handle_list_tools_with_pagination(None, caller).await
     }
 
     async fn handle_list_tools_with_pagination(
         &self,
         params: Option<Value>,
+        caller: &ToolCaller,
     ) -> std::result::Result<Value, JsonRpcError> {
         info!("Handling list_tools request with pagination");
 
         // Parse pagination parameters if provided
         let pagination_params = if let Some(params) = params {
-            serde_json::from_value::<PaginationParams>(params).map_err(|e| JsonRpcError {
-                code: INVALID_PARAMS,
-                message: format!("Invalid pagination params: {}", e),
-                data: None,
+            serde_json::from_value::<PaginationParams>(params).map_err(|e| {
+                JsonRpcError::new(
+                    ErrorKind::ValidationFailed,
+                    format!("Invalid pagination params: {}", e),
+                )
             })?
         } else {
             PaginationParams { cursor: None }
@@ -275,15 +647,14 @@ impl McpServer {
         // Parse cursor
         let cursor =
             PaginationCursor::from_cursor_string(pagination_params.cursor).map_err(|e| {
-                JsonRpcError {
-                    code: INVALID_PARAMS,
-                    message: format!("Invalid cursor: {}", e),
-                    data: None,
-                }
+                JsonRpcError::new(
+                    ErrorKind::ValidationFailed,
+                    format!("Invalid cursor: {}", e),
+                )
             })?;
 
-        // Get all tools and apply pagination
-        let all_tools = self.tools.list_tools();
+        // Get tools visible to the caller and apply pagination
+        let all_tools = self.visible_tools(caller);
         let total_tools = all_tools.len();
 
         let start = cursor.offset;
@@ -308,10 +679,11 @@ impl McpServer {
             next_cursor,
         };
 
-        let result = serde_json::to_value(response).map_err(|e| JsonRpcError {
-            code: INTERNAL_ERROR,
-            message: format!("Failed to serialize tools: {}", e),
-            data: None,
+        let result = serde_json::to_value(response).map_err(|e| {
+            JsonRpcError::new(
+                ErrorKind::Internal,
+                format!("Failed to serialize tools: {}", e),
+            )
         })?;
 
         Ok(result)
@@ -321,24 +693,82 @@ impl McpServer {
         &self,
         state: &AppState,
         params: Option<Value>,
+        caller: &ToolCaller,
+        session_id: Option<&str>,
     ) -> std::result::Result<Value, JsonRpcError> {
-        let request: CallToolRequest = match params {
-            Some(params) => serde_json::from_value(params).map_err(|e| JsonRpcError {
-                code: INVALID_PARAMS,
-                message: format!("Invalid call_tool params: {}", e),
-                data: None,
+        let mut request: CallToolRequest = match params {
+            Some(params) => serde_json::from_value(params).map_err(|e| {
+                JsonRpcError::new(
+                    ErrorKind::ValidationFailed,
+                    format!("Invalid call_tool params: {}", e),
+                )
             })?,
             None => {
-                return Err(JsonRpcError {
-                    code: INVALID_PARAMS,
-                    message: "Missing call_tool parameters".to_string(),
-                    data: None,
-                })
+                return Err(JsonRpcError::new(
+                    ErrorKind::ValidationFailed,
+                    "Missing call_tool parameters".to_string(),
+                ))
             }
         };
 
         info!("Calling tool: {}", request.name);
 
+        if !self
+            .access_policy
+            .read()
+            .unwrap()
+            .is_allowed(caller, &request.name)
+        {
+            warn!(
+                "Denied tool call: tool={}, caller={:?}",
+                request.name, caller
+            );
+            state.audit_logger.record(
+                AuditRecord::new(caller.actor_id(), &request.name, "denied")
+                    .with_session(session_id)
+                    .with_params(request.arguments.as_ref()),
+            );
+            return Err(JsonRpcError::new(
+                ErrorKind::PermissionDenied,
+                format!(
+                    "Tool '{}' is not permitted for {}",
+                    request.name,
+                    caller.role()
+                ),
+            )
+            .with_details(serde_json::json!({ "tool": request.name })));
+        }
+
+        if self.tools.get_tool(&request.name).is_none() {
+            return Err(JsonRpcError::new(
+                ErrorKind::ValidationFailed,
+                format!("Unknown tool '{}'", request.name),
+            )
+            .with_details(serde_json::json!({ "tool": request.name })));
+        }
+
+        // Reject arguments that do not match the advertised schema before dispatch
+        if let Err(violations) = self
+            .tools
+            .validate_arguments(&request.name, request.arguments.as_ref())
+        {
+            warn!(
+                "Rejected arguments for tool {}: {} schema violation(s)",
+                request.name,
+                violations.len()
+            );
+            return Err(JsonRpcError::new(
+                ErrorKind::ValidationFailed,
+                format!("Invalid arguments for tool '{}'", request.name),
+            )
+            .with_details(serde_json::json!({
+                "tool": request.name,
+                "violations": violations
+            })));
+        }
+
+        resolve_ticket_arguments(state, &mut request.arguments).await?;
+
         // Log parameters if they exist and are not empty
         if let Some(ref args) = request.arguments {
             let should_log = match args {
@@ -355,19 +785,40 @@ impl McpServer {
             }
         }
 
-        let response = self.tools.call_tool(state, request).await.map_err(|e| {
+        // A dry run changes nothing, so there is nothing to audit
+        let audit = (AUDITED_TOOLS.contains(&request.name.as_str())
+            && !is_dry_run(&request.name, &request.arguments))
+        .then(|| (request.name.clone(), request.arguments.clone()));
+
+        let domain = ErrorDomain::of_tool(&request.name);
+        let response = self.tools.call_tool(state, caller, request).await;
+
+        if let Some((tool_name, arguments)) = audit {
+            let outcome = match &response {
+                Ok(r) if r.is_error != Some(true) => "success",
+                _ => "error",
+            };
+            state.audit_logger.record(
+                AuditRecord::new(caller.actor_id(), &tool_name, outcome)
+                    .with_session(session_id)
+                    .with_params(arguments.as_ref()),
+            );
+        }
+
+        let response = response.map_err(|e| {
             error!("Tool execution error: {}", e);
-            JsonRpcError {
-                code: INTERNAL_ERROR,
-                message: format!("Tool execution failed: {}", e),
-                data: None,
-            }
+            JsonRpcError::in_domain(
+                domain,
+                ErrorKind::from(&e),
+                format!("Tool execution failed: {}", e),
+            )
         })?;
 
-        let result = serde_json::to_value(response).map_err(|e| JsonRpcError {
-            code: INTERNAL_ERROR,
-            message: format!("Failed to serialize tool response: {}", e),
-            data: None,
+        let result = serde_json::to_value(response).map_err(|e| {
+            JsonRpcError::new(
+                ErrorKind::Internal,
+                format!("Failed to serialize tool response: {}", e),
+            )
         })?;
 
         Ok(result)
@@ -376,45 +827,18 @@ impl McpServer {
     async fn handle_list_prompts(&self) -> std::result::Result<Value, JsonRpcError> {
         info!("Handling list_prompts request");
 
-        let prompts = vec![
-            Prompt {
-                name: "vibe-ensemble-overview".to_string(),
-                description: "Comprehensive overview of the Vibe Ensemble MCP server capabilities, tools, and how to use them effectively for multi-agent coordination".to_string(),
-                arguments: vec![],
-            },
-            Prompt {
-                name: "project-setup".to_string(),
-                description: "Step-by-step guide for setting up a new project with worker types and initial configuration".to_string(),
-                arguments: vec![
-                    PromptArgument {
-                        name: "project_name".to_string(),
-                        description: "Name of the project to set up".to_string(),
-                        required: true,
-                    }
-                ],
-            },
-            Prompt {
-                name: "multi-agent-workflow".to_string(),
-                description: "Best practices and examples for coordinating multiple agents on complex tasks".to_string(),
-                arguments: vec![
-                    PromptArgument {
-                        name: "task_type".to_string(),
-                        description: "Type of task (development, analysis, testing, etc.)".to_string(),
-                        required: false,
-                    }
-                ],
-            },
-        ];
+        let prompts = available_prompts();
 
         let response = ListPromptsResponse {
             prompts,
             next_cursor: None,
         };
 
-        let result = serde_json::to_value(response).map_err(|e| JsonRpcError {
-            code: INTERNAL_ERROR,
-            message: format!("Failed to serialize prompts: {}", e),
-            data: None,
+        let result = serde_json::to_value(response).map_err(|e| {
+            JsonRpcError::new(
+                ErrorKind::Internal,
+                format!("Failed to serialize prompts: {}", e),
+            )
         })?;
 
         Ok(result)
@@ -425,17 +849,17 @@ impl McpServer {
         params: Option<Value>,
     ) -> std::result::Result<Value, JsonRpcError> {
         let request: GetPromptRequest = match params {
-            Some(params) => serde_json::from_value(params).map_err(|e| JsonRpcError {
-                code: INVALID_PARAMS,
-                message: format!("Invalid get_prompt params: {}", e),
-                data: None,
+            Some(params) => serde_json::from_value(params).map_err(|e| {
+                JsonRpcError::new(
+                    ErrorKind::ValidationFailed,
+                    format!("Invalid get_prompt params: {}", e),
+                )
             })?,
             None => {
-                return Err(JsonRpcError {
-                    code: INVALID_PARAMS,
-                    message: "Missing get_prompt parameters".to_string(),
-                    data: None,
-                })
+                return Err(JsonRpcError::new(
+                    ErrorKind::ValidationFailed,
+                    "Missing get_prompt parameters".to_string(),
+                ))
             }
         };
 
@@ -485,20 +909,21 @@ impl McpServer {
                 }]
             }
             _ => {
-                return Err(JsonRpcError {
-                    code: INVALID_PARAMS,
-                    message: format!("Unknown prompt: {}", request.name),
-                    data: None,
-                })
+                return Err(JsonRpcError::in_domain(
+                    Some(ErrorDomain::Knowledge),
+                    ErrorKind::NotFound,
+                    format!("Unknown prompt: {}", request.name),
+                ))
             }
         };
 
         let response = GetPromptResponse { messages };
 
-        let result = serde_json::to_value(response).map_err(|e| JsonRpcError {
-            code: INTERNAL_ERROR,
-            message: format!("Failed to serialize prompt response: {}", e),
-            data: None,
+        let result = serde_json::to_value(response).map_err(|e| {
+            JsonRpcError::new(
+                ErrorKind::Internal,
+                format!("Failed to serialize prompt response: {}", e),
+            )
         })?;
 
         Ok(result)
@@ -507,24 +932,18 @@ impl McpServer {
     async fn handle_list_resources(&self) -> std::result::Result<Value, JsonRpcError> {
         info!("Handling list_resources request");
 
-        let resources = vec![
-            Resource {
-                uri: "ide://events".to_string(),
-                name: "Vibe Ensemble Events".to_string(),
-                description: Some("Real-time events from the Vibe Ensemble MCP server, including worker status, ticket updates, and system messages".to_string()),
-                mime_type: Some("application/json".to_string()),
-            }
-        ];
+        let resources = available_resources();
 
         let response = ListResourcesResponse {
             resources,
             next_cursor: None,
         };
 
-        let result = serde_json::to_value(response).map_err(|e| JsonRpcError {
-            code: INTERNAL_ERROR,
-            message: format!("Failed to serialize resources: {}", e),
-            data: None,
+        let result = serde_json::to_value(response).map_err(|e| {
+            JsonRpcError::new(
+                ErrorKind::Internal,
+                format!("Failed to serialize resources: {}", e),
+            )
         })?;
 
         Ok(result)
@@ -535,17 +954,17 @@ impl McpServer {
         params: Option<Value>,
     ) -> std::result::Result<Value, JsonRpcError> {
         let request: ReadResourceRequest = match params {
-            Some(params) => serde_json::from_value(params).map_err(|e| JsonRpcError {
-                code: INVALID_PARAMS,
-                message: format!("Invalid read_resource params: {}", e),
-                data: None,
+            Some(params) => serde_json::from_value(params).map_err(|e| {
+                JsonRpcError::new(
+                    ErrorKind::ValidationFailed,
+                    format!("Invalid read_resource params: {}", e),
+                )
             })?,
             None => {
-                return Err(JsonRpcError {
-                    code: INVALID_PARAMS,
-                    message: "Missing read_resource parameters".to_string(),
-                    data: None,
-                })
+                return Err(JsonRpcError::new(
+                    ErrorKind::ValidationFailed,
+                    "Missing read_resource parameters".to_string(),
+                ))
             }
         };
 
@@ -565,9 +984,18 @@ impl McpServer {
                             "TicketStageChanged",
                             "TicketClosed",
                             "TicketUnblocked",
+                            "TicketSlaBreached",
+                            "TicketFlaky",
+                            "TicketBlockEscalated",
+                            "EffectivePriorityChanged",
+                            "ProjectQuotaWarning",
+                            "Digest",
+                            "RecoveryCompleted",
                             "WorkerSpawned",
                             "WorkerFinished",
                             "WorkerFailed",
+                            "WorkerBoundaryViolation",
+                            "WorkerStalled",
                             "QueueUpdated",
                             "SystemInit",
                             "SystemMessage",
@@ -576,21 +1004,29 @@ impl McpServer {
                         "tools_to_use": [
                             "list_events - Get current events with filtering and pagination"
                         ]
-                    })).map_err(|e| JsonRpcError {
-                        code: INTERNAL_ERROR,
-                        message: format!("Failed to serialize event info: {}", e),
-                        data: None,
-                    })?),
+                    })).map_err(|e| JsonRpcError::new(ErrorKind::Internal, format!("Failed to serialize event info: {}", e)))?),
                     blob: None,
                     mime_type: Some("application/json".to_string()),
                 }
             }
+            "vibe://error-codes" => ResourceContent {
+                content_type: "text".to_string(),
+                text: Some(
+                    serde_json::to_string_pretty(&error_code_table()).map_err(|e| {
+                        JsonRpcError::new(
+                            ErrorKind::Internal,
+                            format!("Failed to serialize error codes: {}", e),
+                        )
+                    })?,
+                ),
+                blob: None,
+                mime_type: Some("application/json".to_string()),
+            },
             _ => {
-                return Err(JsonRpcError {
-                    code: INVALID_PARAMS,
-                    message: format!("Unknown resource URI: {}", request.uri),
-                    data: None,
-                })
+                return Err(JsonRpcError::new(
+                    ErrorKind::NotFound,
+                    format!("Unknown resource URI: {}", request.uri),
+                ))
             }
         };
 
@@ -598,27 +1034,82 @@ impl McpServer {
             contents: vec![content],
         };
 
-        let result = serde_json::to_value(response).map_err(|e| JsonRpcError {
-            code: INTERNAL_ERROR,
-            message: format!("Failed to serialize resource response: {}", e),
-            data: None,
+        let result = serde_json::to_value(response).map_err(|e| {
+            JsonRpcError::new(
+                ErrorKind::Internal,
+                format!("Failed to serialize resource response: {}", e),
+            )
         })?;
 
         Ok(result)
     }
 }
 
+/// Prompts served by prompts/list and prompts/get
+fn available_prompts() -> Vec<Prompt> {
+    vec![
+        Prompt {
+            name: "vibe-ensemble-overview".to_string(),
+            description: "Comprehensive overview of the Vibe Ensemble MCP server capabilities, tools, and how to use them effectively for multi-agent coordination".to_string(),
+            arguments: vec![],
+        },
+        Prompt {
+            name: "project-setup".to_string(),
+            description: "Step-by-step guide for setting up a new project with worker types and initial configuration".to_string(),
+            arguments: vec![
+                PromptArgument {
+                    name: "project_name".to_string(),
+                    description: "Name of the project to set up".to_string(),
+                    required: true,
+                }
+            ],
+        },
+        Prompt {
+            name: "multi-agent-workflow".to_string(),
+            description: "Best practices and examples for coordinating multiple agents on complex tasks".to_string(),
+            arguments: vec![
+                PromptArgument {
+                    name: "task_type".to_string(),
+                    description: "Type of task (development, analysis, testing, etc.)".to_string(),
+                    required: false,
+                }
+            ],
+        },
+    ]
+}
+
+/// Resources served by resources/list and resources/read
+fn available_resources() -> Vec<Resource> {
+    vec![
+        Resource {
+            uri: "ide://events".to_string(),
+            name: "Vibe Ensemble Events".to_string(),
+            description: Some("Real-time events from the Vibe Ensemble MCP server, including worker status, ticket updates, and system messages".to_string()),
+            mime_type: Some("application/json".to_string()),
+        },
+        Resource {
+            uri: "vibe://error-codes".to_string(),
+            name: "Vibe Ensemble Error Codes".to_string(),
+            description: Some("JSON-RPC error code ranges and every code the server returns, with its kind, domain and whether it is retryable".to_string()),
+            mime_type: Some("application/json".to_string()),
+        },
+    ]
+}
+
+/// POST /mcp - JSON-RPC over plain HTTP.
+///
+/// The body is a single request or a batch. Requests get a 200 with their response,
+/// a body of only notifications gets a 202 with no content. The session id issued at
+/// initialize is echoed in the session header, and every later request must send it
+/// back; one without it gets a 400, and one naming a session that expired, was dropped
+/// or never existed a 404, upon which the client has to initialize again. Requests made
+/// with a spawned worker's token act as that worker.
 pub async fn mcp_handler(
     State(state): State<AppState>,
+    Extension(api_caller): Extension<ApiCaller>,
     headers: HeaderMap,
-    Json(request): Json<JsonRpcRequest>,
-) -> Result<Json<JsonRpcResponse>> {
-    trace!(
-        "MCP request received: {}",
-        serde_json::to_string_pretty(&request)
-            .unwrap_or_else(|_| "Failed to serialize request".to_string())
-    );
-
+    body: Bytes,
+) -> Result<Response> {
     // Check for MCP-Protocol-Version header (2025-06-18 spec requirement)
     if let Some(header_version) = headers.get("MCP-Protocol-Version") {
         if let Ok(version_str) = header_version.to_str() {
@@ -638,7 +1129,181 @@ pub async fn mcp_handler(
         debug!("No MCP-Protocol-Version header present (optional for HTTP transport)");
     }
 
-    let response = state.mcp_server.handle_request(&state, request).await;
+    let payload: Value = match serde_json::from_slice(&body) {
+        Ok(payload) => payload,
+        Err(e) => {
+            return Ok(rpc_error(
+                StatusCode::BAD_REQUEST,
+                None,
+                ErrorKind::ParseError,
+                format!("Parse error: {}", e),
+            ))
+        }
+    };
+
+    let header_session = headers
+        .get(SESSION_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let caller = ToolCaller::of(&api_caller);
+
+    let items = match payload {
+        Value::Array(items) => items,
+        payload => {
+            return match parse_request(payload) {
+                Ok(request) if request.method != "initialize" && header_session.is_none() => {
+                    Ok(missing_session(request.id))
+                }
+                Ok(request) => {
+                    Ok(handle_single(&state, request, header_session, &caller, body.len()).await)
+                }
+                Err(error) => Ok((StatusCode::BAD_REQUEST, Json(error)).into_response()),
+            }
+        }
+    };
+
+    if items.is_empty() {
+        return Ok(rpc_error(
+            StatusCode::BAD_REQUEST,
+            None,
+            ErrorKind::InvalidRequest,
+            "Empty batch".to_string(),
+        ));
+    }
+
+    // initialize is refused in a batch, so every batch needs a session already
+    let Some(session_id) = header_session else {
+        return Ok(missing_session(None));
+    };
+    if !state.mcp_server.sessions.contains(&session_id) {
+        return Ok(unknown_session(None, &session_id));
+    }
+    state
+        .mcp_server
+        .sessions
+        .record_in(&session_id, items.len() as u64, body.len());
+
+    // Batch entries run in order so a session sees them as sent
+    let mut responses = Vec::new();
+    for item in items {
+        let request = match parse_request(item) {
+            Ok(request) => request,
+            Err(error) => {
+                responses.push(*error);
+                continue;
+            }
+        };
+        if request.method == "initialize" {
+            responses.push(error_response(
+                request.id,
+                JsonRpcError::new(
+                    ErrorKind::InvalidRequest,
+                    "initialize must not be part of a batch",
+                ),
+            ));
+            continue;
+        }
+
+        let is_notification = request.id.is_none();
+        let response = process_request(&state, request, &session_id, &caller).await;
+        if !is_notification {
+            responses.push(response);
+        }
+    }
+
+    if responses.is_empty() {
+        return Ok(StatusCode::ACCEPTED.into_response());
+    }
+    let count = responses.len() as u64;
+    Ok(json_response(&state, &session_id, count, &responses))
+}
+
+/// 404 for a session that is not tracked; the client has to initialize a new one
+fn unknown_session(id: Option<Value>, session_id: &str) -> Response {
+    rpc_error(
+        StatusCode::NOT_FOUND,
+        id,
+        ErrorKind::NotFound,
+        format!(
+            "Unknown MCP session '{}'; send initialize to start a new session",
+            session_id
+        ),
+    )
+}
+
+/// 400 for a request sent outside a session; only initialize opens one
+fn missing_session(id: Option<Value>) -> Response {
+    rpc_error(
+        StatusCode::BAD_REQUEST,
+        id,
+        ErrorKind::NotInitialized,
+        format!(
+            "Missing {} header; send initialize first and pass the session id it returns",
+            SESSION_ID_HEADER
+        ),
+    )
+}
+
+/// Serialize a response body once, counting it as sent on the session
+fn json_response<T: serde::Serialize>(
+    state: &AppState,
+    session_id: &str,
+    messages: u64,
+    body: &T,
+) -> Response {
+    let Ok(bytes) = serde_json::to_vec(body) else {
+        return Json(body).into_response();
+    };
+    state
+        .mcp_server
+        .sessions
+        .record_out(session_id, messages, bytes.len());
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        Bytes::from(bytes),
+    )
+        .into_response()
+}
+
+/// Handle a single (non-batch) request, opening a session on initialize
+async fn handle_single(
+    state: &AppState,
+    request: JsonRpcRequest,
+    header_session: Option<String>,
+    caller: &ToolCaller,
+    body_len: usize,
+) -> Response {
+    trace!(
+        "MCP request received: {}",
+        serde_json::to_string_pretty(&request)
+            .unwrap_or_else(|_| "Failed to serialize request".to_string())
+    );
+
+    // Sessions are assigned at initialize, and the handler has refused anything else
+    // that came without one
+    let is_initialize = request.method == "initialize";
+    let (session_id, opened) = match header_session {
+        // Re-initializing a live session is rejected by the handshake check
+        Some(id) if state.mcp_server.sessions.contains(&id) => (id, false),
+        Some(id) if !is_initialize => return unknown_session(request.id, &id),
+        _ => (state.mcp_server.sessions.open(), true),
+    };
+
+    let is_notification = request.id.is_none();
+    let declared_meta = is_initialize
+        .then(|| declared_meta(request.params.as_ref()))
+        .flatten();
+    let declared_capabilities =
+        is_initialize.then(|| capabilities::declared(request.params.as_ref()));
+    let declared_client = is_initialize
+        .then(|| declared_client(request.params.as_ref()))
+        .flatten();
+    let response = process_request(state, request, &session_id, caller).await;
+    // Counted once handled, as initialize only starts tracking the session
+    state
+        .mcp_server
+        .sessions
+        .record_in(&session_id, 1, body_len);
 
     trace!(
         "MCP response: {}",
@@ -646,5 +1311,144 @@ pub async fn mcp_handler(
             .unwrap_or_else(|_| "Failed to serialize response".to_string())
     );
 
-    Ok(Json(response))
+    if is_notification {
+        return StatusCode::ACCEPTED.into_response();
+    }
+
+    let session_header = match (is_initialize, &response.error) {
+        (true, None) => {
+            if let Some(capabilities) = declared_capabilities {
+                state
+                    .mcp_server
+                    .sessions
+                    .set_capabilities(&session_id, capabilities);
+            }
+            let worker_id = match caller {
+                ToolCaller::Worker { worker_id, .. } => Some(worker_id.as_str()),
+                ToolCaller::Coordinator => None,
+            };
+            state
+                .mcp_server
+                .sessions
+                .set_client(&session_id, declared_client, worker_id);
+            state.mcp_server.sessions.set_handshake(
+                &session_id,
+                negotiated_version(&response),
+                declared_meta,
+            );
+            HeaderValue::from_str(&session_id).ok()
+        }
+        // A failed initialize leaves no session behind, but never ends a live one
+        (true, Some(_)) if opened => {
+            state.mcp_server.sessions.close(&session_id);
+            None
+        }
+        _ => None,
+    };
+
+    let mut http_response = json_response(state, &session_id, 1, &response);
+    if let Some(value) = session_header {
+        http_response.headers_mut().insert(SESSION_ID_HEADER, value);
+    }
+    http_response
+}
+
+/// Protocol version an initialize response settled on
+pub fn negotiated_version(response: &JsonRpcResponse) -> Option<String> {
+    response
+        .result
+        .as_ref()
+        .and_then(|result| result.get("protocolVersion"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// Run a request through the session handshake check and the server, bounded by the
+/// configured request timeout
+async fn process_request(
+    state: &AppState,
+    request: JsonRpcRequest,
+    session_id: &str,
+    caller: &ToolCaller,
+) -> JsonRpcResponse {
+    if let Some(rejection) = state.mcp_server.check_session(session_id, &request) {
+        return rejection;
+    }
+
+    let timeout_secs = state.live_config.borrow().request_timeout_secs;
+    if timeout_secs == 0 {
+        return state
+            .mcp_server
+            .handle_request_as(state, request, caller, Some(session_id))
+            .await;
+    }
+
+    let id = request.id.clone();
+    let method = request.method.clone();
+    match tokio::time::timeout(
+        std::time::Duration::from_secs(timeout_secs),
+        state
+            .mcp_server
+            .handle_request_as(state, request, caller, Some(session_id)),
+    )
+    .await
+    {
+        Ok(response) => response,
+        Err(_) => {
+            warn!("MCP request '{}' timed out after {}s", method, timeout_secs);
+            error_response(
+                id,
+                JsonRpcError::new(
+                    ErrorKind::Timeout,
+                    format!("Request '{}' timed out after {}s", method, timeout_secs),
+                ),
+            )
+        }
+    }
+}
+
+/// Parse one JSON-RPC request, or produce the invalid-request response for it
+fn parse_request(payload: Value) -> std::result::Result<JsonRpcRequest, Box<JsonRpcResponse>> {
+    let id = payload.get("id").cloned();
+    serde_json::from_value(payload).map_err(|e| {
+        Box::new(error_response(
+            id,
+            JsonRpcError::new(ErrorKind::InvalidRequest, format!("Invalid request: {}", e)),
+        ))
+    })
+}
+
+fn error_response(id: Option<Value>, error: JsonRpcError) -> JsonRpcResponse {
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(error),
+    }
+}
+
+fn rpc_error(status: StatusCode, id: Option<Value>, kind: ErrorKind, message: String) -> Response {
+    let error = JsonRpcError::new(kind, message);
+    (status, Json(error_response(id, error))).into_response()
+}
+
+/// DELETE /mcp - Terminate the HTTP session named in the session header
+pub async fn mcp_session_delete_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> StatusCode {
+    match headers.get(SESSION_ID_HEADER).and_then(|v| v.to_str().ok()) {
+        Some(session_id) => {
+            if state.mcp_server.sessions.close(session_id) {
+                state
+                    .mcp_server
+                    .sessions
+                    .record_closed(CloseClass::ClientClosed);
+            }
+            state.event_stream.close_session(session_id);
+            info!("Closed MCP session {}", session_id);
+            StatusCode::NO_CONTENT
+        }
+        None => StatusCode::BAD_REQUEST,
+    }
 }
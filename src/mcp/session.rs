@@ -0,0 +1,668 @@
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tracing::{debug, info};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{
+    errors::ErrorKind,
+    types::{ClientCapabilities, ClientInfo, JsonRpcError},
+};
+use crate::{audit::AuditRecord, events::EventPayload, server::AppState};
+
+/// HTTP header carrying the session id assigned at initialize
+pub const SESSION_ID_HEADER: &str = "mcp-session-id";
+
+/// Sessions idle for longer than this are expired unless `--session-idle-timeout-secs` says otherwise
+pub const DEFAULT_SESSION_IDLE_TIMEOUT_SECS: u64 = 24 * 3600;
+
+const IDLE_SWEEP_INTERVAL_SECS: u64 = 60;
+
+/// Handshake progress of a single client session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionPhase {
+    /// Connected, waiting for `initialize`
+    Connected,
+    /// `initialize` answered, waiting for `notifications/initialized`
+    Initialized,
+    /// Handshake complete; all methods allowed
+    Ready,
+}
+
+impl SessionPhase {
+    /// Check whether `method` may run in this phase and return the phase that follows it
+    pub fn advance(self, method: &str) -> Result<SessionPhase, JsonRpcError> {
+        match (self, method) {
+            (_, "ping") => Ok(self),
+            (SessionPhase::Connected, "initialize") => Ok(SessionPhase::Initialized),
+            (_, "initialize") => Err(JsonRpcError::new(
+                ErrorKind::Conflict,
+                "Session is already initialized",
+            )),
+            (SessionPhase::Connected, "notifications/initialized") => Err(JsonRpcError::new(
+                ErrorKind::NotInitialized,
+                "Received notifications/initialized before initialize",
+            )),
+            (_, "notifications/initialized") => Ok(SessionPhase::Ready),
+            (SessionPhase::Ready, _) => Ok(self),
+            (SessionPhase::Connected, _) => Err(JsonRpcError::new(
+                ErrorKind::NotInitialized,
+                format!("Method '{}' requires initialize first", method),
+            )),
+            (SessionPhase::Initialized, _) => Err(JsonRpcError::new(
+                ErrorKind::NotInitialized,
+                format!(
+                    "Method '{}' requires notifications/initialized first",
+                    method
+                ),
+            )),
+        }
+    }
+}
+
+/// Connection a session arrived on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    Http,
+    WebSocket,
+}
+
+/// Messages and bytes exchanged over a session
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Traffic {
+    pub messages_in: u64,
+    pub messages_out: u64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+}
+
+/// Why a connection ended, for the counters in `GET /api/debug/connections`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseClass {
+    /// The client closed the connection or ended its session
+    ClientClosed,
+    /// The server closed it: idle expiry, a dropped or superseded session, or a limit
+    ServerClosed,
+    /// An inbound message exceeded `--max-message-bytes`
+    MessageTooLarge,
+    /// The socket failed
+    TransportError,
+}
+
+struct Session {
+    phase: SessionPhase,
+    transport: Transport,
+    last_seen: Instant,
+    /// Declared by the client at initialize
+    capabilities: Option<ClientCapabilities>,
+    client_info: Option<ClientInfo>,
+    /// Worker that initialized the session, for sessions opened by spawned workers
+    worker_id: Option<String>,
+    /// Protocol version the server answered initialize with
+    protocol_version: Option<String>,
+    /// `_meta` of the initialize request, as sent
+    metadata: Option<Value>,
+    traffic: Traffic,
+    connected_at: DateTime<Utc>,
+    last_activity: DateTime<Utc>,
+}
+
+impl Session {
+    fn new(transport: Transport) -> Self {
+        let now = Utc::now();
+        Self {
+            phase: SessionPhase::Connected,
+            transport,
+            last_seen: Instant::now(),
+            capabilities: None,
+            client_info: None,
+            worker_id: None,
+            protocol_version: None,
+            metadata: None,
+            traffic: Traffic::default(),
+            connected_at: now,
+            last_activity: now,
+        }
+    }
+
+    fn touch(&mut self) {
+        self.last_seen = Instant::now();
+        self.last_activity = Utc::now();
+    }
+
+    fn info(&self, session_id: &str) -> SessionInfo {
+        SessionInfo {
+            session_id: session_id.to_string(),
+            transport: self.transport,
+            phase: self.phase,
+            client_info: self.client_info.clone(),
+            worker_id: self.worker_id.clone(),
+            protocol_version: self.protocol_version.clone(),
+            metadata: self.metadata.clone(),
+            traffic: self.traffic,
+            connected_at: self.connected_at,
+            last_activity: self.last_activity,
+            idle_secs: self.last_seen.elapsed().as_secs(),
+        }
+    }
+}
+
+/// A tracked session as listed by `GET /api/sessions`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SessionInfo {
+    pub session_id: String,
+    pub transport: Transport,
+    pub phase: SessionPhase,
+    pub client_info: Option<ClientInfo>,
+    pub worker_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+    /// Client-supplied and possibly sensitive, so not listed by `GET /api/sessions`
+    #[serde(skip)]
+    pub metadata: Option<Value>,
+    pub traffic: Traffic,
+    pub connected_at: DateTime<Utc>,
+    pub last_activity: DateTime<Utc>,
+    pub idle_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SessionStats {
+    pub http: usize,
+    pub websocket: usize,
+    /// Sessions expired for inactivity since startup
+    pub expired: u64,
+    /// Sessions dropped through `DELETE /api/sessions/:id` since startup
+    pub dropped: u64,
+    /// Sessions started since startup
+    pub opened: u64,
+    /// Connections ended since startup, by why they ended
+    pub closed: BTreeMap<CloseClass, u64>,
+}
+
+impl SessionInfo {
+    /// Project the client named in its initialize `_meta`, as `project_id` or `projectId`
+    pub fn metadata_project(&self) -> Option<&str> {
+        let metadata = self.metadata.as_ref()?;
+        metadata
+            .get("project_id")
+            .or_else(|| metadata.get("projectId"))?
+            .as_str()
+    }
+}
+
+/// Handshake state and activity per connection (WebSocket client id or HTTP session id)
+pub struct SessionTracker {
+    sessions: DashMap<String, Session>,
+    /// In milliseconds; zero keeps idle sessions forever
+    idle_timeout_ms: AtomicU64,
+    expired: AtomicU64,
+    dropped: AtomicU64,
+    opened: AtomicU64,
+    closed: Mutex<BTreeMap<CloseClass, u64>>,
+}
+
+impl Default for SessionTracker {
+    fn default() -> Self {
+        Self::with_idle_timeout(Duration::from_secs(DEFAULT_SESSION_IDLE_TIMEOUT_SECS))
+    }
+}
+
+impl SessionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_idle_timeout(idle_timeout: Duration) -> Self {
+        Self {
+            sessions: DashMap::new(),
+            idle_timeout_ms: AtomicU64::new(idle_timeout.as_millis() as u64),
+            expired: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            opened: AtomicU64::new(0),
+            closed: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn idle_timeout(&self) -> Duration {
+        Duration::from_millis(self.idle_timeout_ms.load(Ordering::Relaxed))
+    }
+
+    /// Change the idle timeout; it applies to sessions already tracked on the next sweep
+    pub fn set_idle_timeout(&self, idle_timeout: Duration) {
+        self.idle_timeout_ms
+            .store(idle_timeout.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Start tracking a new HTTP session, waiting for `initialize`, and return its id
+    pub fn open(&self) -> String {
+        let session_id = Uuid::new_v4().to_string();
+        self.sessions
+            .insert(session_id.clone(), Session::new(Transport::Http));
+        self.opened.fetch_add(1, Ordering::Relaxed);
+        session_id
+    }
+
+    /// Start tracking a WebSocket client, replacing the handshake state of an earlier
+    /// connection under the same client id
+    pub fn attach_websocket(&self, client_id: &str) {
+        self.sessions
+            .insert(client_id.to_string(), Session::new(Transport::WebSocket));
+        self.opened.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Validate `method` against the session's phase and advance it. Sessions are only
+    /// started by [`SessionTracker::open`] and [`SessionTracker::attach_websocket`], so an
+    /// expired or dropped one stays gone.
+    pub fn admit(&self, session_id: &str, method: &str) -> Result<(), JsonRpcError> {
+        let mut session = self.sessions.get_mut(session_id).ok_or_else(|| {
+            JsonRpcError::new(
+                ErrorKind::NotFound,
+                format!("Unknown session '{}'", session_id),
+            )
+        })?;
+        let next = session.phase.advance(method)?;
+        if next != session.phase {
+            debug!(
+                "Session {} moved from {:?} to {:?}",
+                session_id, session.phase, next
+            );
+        }
+        session.phase = next;
+        session.touch();
+        Ok(())
+    }
+
+    /// Record activity that does not go through the handshake check, such as pings
+    pub fn touch(&self, session_id: &str) {
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            session.touch();
+        }
+    }
+
+    /// Remember the capabilities a client declared at initialize
+    pub fn set_capabilities(&self, session_id: &str, capabilities: ClientCapabilities) {
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            session.capabilities = Some(capabilities);
+        }
+    }
+
+    /// Remember who the client said it is at initialize, and the worker it runs for
+    pub fn set_client(
+        &self,
+        session_id: &str,
+        client_info: Option<ClientInfo>,
+        worker_id: Option<&str>,
+    ) {
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            session.client_info = client_info;
+            session.worker_id = worker_id.map(str::to_string);
+        }
+    }
+
+    /// Remember the protocol version initialize was answered with and the `_meta`
+    /// the client sent
+    pub fn set_handshake(
+        &self,
+        session_id: &str,
+        protocol_version: Option<String>,
+        metadata: Option<Value>,
+    ) {
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            session.protocol_version = protocol_version;
+            session.metadata = metadata;
+        }
+    }
+
+    /// Count messages received on a session; unknown sessions are ignored
+    pub fn record_in(&self, session_id: &str, messages: u64, bytes: usize) {
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            session.traffic.messages_in += messages;
+            session.traffic.bytes_in += bytes as u64;
+        }
+    }
+
+    /// Count messages sent on a session; unknown sessions are ignored
+    pub fn record_out(&self, session_id: &str, messages: u64, bytes: usize) {
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            session.traffic.messages_out += messages;
+            session.traffic.bytes_out += bytes as u64;
+        }
+    }
+
+    /// Count a connection that has ended
+    pub fn record_closed(&self, class: CloseClass) {
+        *self.closed.lock().unwrap().entry(class).or_default() += 1;
+    }
+
+    /// Capabilities of an initialized session; None before initialize
+    pub fn capabilities(&self, session_id: &str) -> Option<ClientCapabilities> {
+        self.sessions
+            .get(session_id)
+            .and_then(|session| session.capabilities.clone())
+    }
+
+    pub fn contains(&self, session_id: &str) -> bool {
+        self.sessions.contains_key(session_id)
+    }
+
+    /// Stop tracking a session; false if it was not tracked
+    pub fn close(&self, session_id: &str) -> bool {
+        self.sessions.remove(session_id).is_some()
+    }
+
+    /// Tracked sessions, oldest first
+    pub fn list(&self) -> Vec<SessionInfo> {
+        let mut sessions: Vec<SessionInfo> = self
+            .sessions
+            .iter()
+            .map(|session| session.info(session.key()))
+            .collect();
+        sessions
+            .sort_by(|a, b| (a.connected_at, &a.session_id).cmp(&(b.connected_at, &b.session_id)));
+        sessions
+    }
+
+    /// Stop tracking sessions idle for longer than the idle timeout and return them
+    pub fn expire_idle(&self) -> Vec<SessionInfo> {
+        let idle_timeout = self.idle_timeout();
+        if idle_timeout.is_zero() {
+            return Vec::new();
+        }
+        let mut expired = Vec::new();
+        self.sessions.retain(|session_id, session| {
+            if session.last_seen.elapsed() < idle_timeout {
+                return true;
+            }
+            expired.push(session.info(session_id));
+            false
+        });
+        self.expired
+            .fetch_add(expired.len() as u64, Ordering::Relaxed);
+        expired
+    }
+
+    /// Stop tracking a session on request; None if it is unknown
+    pub fn drop_session(&self, session_id: &str) -> Option<SessionInfo> {
+        let (_, session) = self.sessions.remove(session_id)?;
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        Some(session.info(session_id))
+    }
+
+    pub fn stats(&self) -> SessionStats {
+        let websocket = self
+            .sessions
+            .iter()
+            .filter(|session| session.transport == Transport::WebSocket)
+            .count();
+        SessionStats {
+            http: self.sessions.len() - websocket,
+            websocket,
+            expired: self.expired.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            opened: self.opened.load(Ordering::Relaxed),
+            closed: self.closed.lock().unwrap().clone(),
+        }
+    }
+}
+
+/// `clientInfo` from initialize params, if the client sent a well-formed one
+pub fn declared_client(params: Option<&Value>) -> Option<ClientInfo> {
+    params
+        .and_then(|params| params.get("clientInfo"))
+        .and_then(|client_info| serde_json::from_value(client_info.clone()).ok())
+}
+
+/// `_meta` from initialize params, if the client sent one
+pub fn declared_meta(params: Option<&Value>) -> Option<Value> {
+    params
+        .and_then(|params| params.get("_meta"))
+        .filter(|meta| meta.is_object())
+        .cloned()
+}
+
+/// Close the connection behind a session that is no longer tracked and audit why
+pub fn end_session(state: &AppState, session: &SessionInfo, outcome: &str, reason: &str) {
+    match session.transport {
+        // The connection's own task counts the close once the socket is gone
+        Transport::WebSocket => {
+            state
+                .websocket_manager
+                .disconnect(&session.session_id, reason);
+        }
+        Transport::Http => {
+            state.event_stream.close_session(&session.session_id);
+            state
+                .mcp_server
+                .sessions
+                .record_closed(CloseClass::ServerClosed);
+        }
+    }
+    info!(
+        "Session {} ({:?}) {}: {}",
+        session.session_id, session.transport, outcome, reason
+    );
+    state.audit_logger.record(
+        AuditRecord::new("system", "session_end", outcome).with_session(Some(&session.session_id)),
+    );
+    // Sessions that never identified as a worker belong to the coordinator
+    if session.worker_id.is_none() && session.phase != SessionPhase::Connected {
+        let transport = match session.transport {
+            Transport::Http => "http",
+            Transport::WebSocket => "websocket",
+        };
+        state
+            .event_broadcaster
+            .broadcast(EventPayload::coordinator_lost(
+                &session.session_id,
+                transport,
+                reason,
+            ));
+    }
+}
+
+/// Periodically expire sessions idle for longer than `--session-idle-timeout-secs`.
+/// The sweeper runs even while expiry is disabled, as a config reload may enable it.
+pub fn start_idle_sweeper(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(IDLE_SWEEP_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            for session in state.mcp_server.sessions.expire_idle() {
+                end_session(
+                    &state,
+                    &session,
+                    "expired",
+                    &format!("Session idle for {}s", session.idle_secs),
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(error: JsonRpcError) -> String {
+        error.data.unwrap()["kind"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_happy_path() {
+        let tracker = SessionTracker::new();
+        let id = tracker.open();
+
+        tracker.admit(&id, "initialize").unwrap();
+        tracker.admit(&id, "notifications/initialized").unwrap();
+        tracker.admit(&id, "tools/list").unwrap();
+        tracker.admit(&id, "tools/call").unwrap();
+    }
+
+    #[test]
+    fn test_requests_before_handshake_are_rejected() {
+        let tracker = SessionTracker::new();
+        let c1 = tracker.open();
+        let c2 = tracker.open();
+
+        let err = tracker.admit(&c1, "tools/call").unwrap_err();
+        assert_eq!(kind(err), "not_initialized");
+
+        tracker.admit(&c1, "initialize").unwrap();
+        let err = tracker.admit(&c1, "resources/read").unwrap_err();
+        assert_eq!(kind(err), "not_initialized");
+
+        assert!(tracker.admit(&c2, "notifications/initialized").is_err());
+    }
+
+    #[test]
+    fn test_double_initialize_is_rejected() {
+        let tracker = SessionTracker::new();
+        let c1 = tracker.open();
+        tracker.admit(&c1, "initialize").unwrap();
+
+        let err = tracker.admit(&c1, "initialize").unwrap_err();
+        assert_eq!(kind(err), "conflict");
+
+        // A closed session is not started again by reusing its id
+        tracker.close(&c1);
+        let err = tracker.admit(&c1, "initialize").unwrap_err();
+        assert_eq!(kind(err), "not_found");
+        tracker.admit(&tracker.open(), "initialize").unwrap();
+    }
+
+    #[test]
+    fn test_idle_sessions_expire() {
+        let tracker = SessionTracker::with_idle_timeout(Duration::from_millis(50));
+        tracker.attach_websocket("ws1");
+        let http1 = tracker.open();
+        tracker.admit(&http1, "initialize").unwrap();
+        tracker.set_client(
+            &http1,
+            declared_client(Some(
+                &serde_json::json!({"clientInfo": {"name": "claude-code", "version": "2.0"}}),
+            )),
+            Some("worker-1"),
+        );
+
+        let listed = tracker.list();
+        assert_eq!(listed.len(), 2);
+        let http = listed.iter().find(|s| s.session_id == http1).unwrap();
+        assert_eq!(http.transport, Transport::Http);
+        assert_eq!(http.phase, SessionPhase::Initialized);
+        assert_eq!(http.client_info.as_ref().unwrap().name, "claude-code");
+        assert_eq!(http.worker_id.as_deref(), Some("worker-1"));
+
+        // Activity keeps a session alive; the silent one expires
+        std::thread::sleep(Duration::from_millis(30));
+        tracker.touch(&http1);
+        std::thread::sleep(Duration::from_millis(30));
+        let expired = tracker.expire_idle();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].session_id, "ws1");
+        assert_eq!(expired[0].transport, Transport::WebSocket);
+        assert!(tracker.contains(&http1));
+
+        // Neither an expired nor a dropped session comes back when its id is reused
+        assert_eq!(kind(tracker.admit("ws1", "ping").unwrap_err()), "not_found");
+        assert!(tracker.drop_session(&http1).is_some());
+        assert!(tracker.drop_session(&http1).is_none());
+        assert_eq!(
+            kind(tracker.admit(&http1, "ping").unwrap_err()),
+            "not_found"
+        );
+        let stats = tracker.stats();
+        assert_eq!((stats.http, stats.websocket), (0, 0));
+        assert_eq!((stats.expired, stats.dropped), (1, 1));
+
+        // Zero disables expiry
+        let forever = SessionTracker::with_idle_timeout(Duration::ZERO);
+        forever.admit(&forever.open(), "ping").unwrap();
+        assert!(forever.expire_idle().is_empty());
+    }
+
+    #[test]
+    fn test_expired_session_is_not_revived() {
+        let tracker = SessionTracker::with_idle_timeout(Duration::from_millis(20));
+        let id = tracker.open();
+        tracker.admit(&id, "initialize").unwrap();
+        tracker.admit(&id, "notifications/initialized").unwrap();
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(tracker.expire_idle().len(), 1);
+
+        // The client is told the session is gone instead of getting a fresh one
+        let err = tracker.admit(&id, "tools/list").unwrap_err();
+        assert_eq!(kind(err), "not_found");
+        assert!(!tracker.contains(&id));
+        let stats = tracker.stats();
+        assert_eq!((stats.http, stats.opened), (0, 1));
+    }
+
+    #[test]
+    fn test_ping_allowed_in_every_phase() {
+        let tracker = SessionTracker::new();
+        let c1 = tracker.open();
+        tracker.admit(&c1, "ping").unwrap();
+        tracker.admit(&c1, "initialize").unwrap();
+        tracker.admit(&c1, "ping").unwrap();
+        tracker.admit(&c1, "notifications/initialized").unwrap();
+        tracker.admit(&c1, "ping").unwrap();
+    }
+    #[test]
+    fn test_traffic_and_closes_are_counted() {
+        let tracker = SessionTracker::new();
+        let id = tracker.open();
+        tracker.admit(&id, "initialize").unwrap();
+        tracker.attach_websocket("ws1");
+        tracker.record_in(&id, 1, 120);
+        tracker.record_out(&id, 1, 300);
+        tracker.record_in(&id, 3, 80);
+        tracker.record_in("unknown", 1, 10);
+        let params = serde_json::json!({"_meta": {"progressToken": 1}});
+        tracker.set_handshake(
+            &id,
+            Some("2025-06-18".to_string()),
+            declared_meta(Some(&params)),
+        );
+
+        let session = tracker
+            .list()
+            .into_iter()
+            .find(|s| s.session_id == id)
+            .unwrap();
+        assert_eq!(
+            session.traffic,
+            Traffic {
+                messages_in: 4,
+                messages_out: 1,
+                bytes_in: 200,
+                bytes_out: 300,
+            }
+        );
+        assert_eq!(session.protocol_version.as_deref(), Some("2025-06-18"));
+        assert_eq!(session.metadata.unwrap()["progressToken"], 1);
+
+        tracker.record_closed(CloseClass::TransportError);
+        tracker.record_closed(CloseClass::TransportError);
+        tracker.record_closed(CloseClass::ClientClosed);
+        let stats = tracker.stats();
+        assert_eq!(stats.opened, 2);
+        assert_eq!(stats.closed[&CloseClass::TransportError], 2);
+        assert_eq!(stats.closed[&CloseClass::ClientClosed], 1);
+        assert!(!stats.closed.contains_key(&CloseClass::ServerClosed));
+    }
+}
@@ -0,0 +1,231 @@
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{errors::ErrorKind, types::JsonRpcError};
+use crate::events::{EventData, EventPayload};
+
+/// Maximum number of subscriptions a single session may hold
+pub const MAX_SUBSCRIPTIONS_PER_SESSION: usize = 32;
+
+/// Groups of events a session can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventCategory {
+    /// Ticket lifecycle changes
+    Tickets,
+    /// Worker lifecycle (started, completed, failed, stopped)
+    Workers,
+    /// Worker queue updates
+    Queues,
+    /// Agent requests addressed to a given agent
+    Requests,
+    /// Other system notifications
+    System,
+}
+
+impl EventCategory {
+    pub fn of(event: &EventPayload) -> EventCategory {
+        match &event.data {
+            EventData::Ticket(_) => EventCategory::Tickets,
+            EventData::Worker(_) => EventCategory::Workers,
+            EventData::Queue(_) => EventCategory::Queues,
+            EventData::System(system) if system.component == "agent_requests" => {
+                EventCategory::Requests
+            }
+            EventData::System(_) => EventCategory::System,
+        }
+    }
+}
+
+/// Interest in one event category, optionally narrowed by filters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subscription {
+    #[serde(default)]
+    pub id: String,
+    pub category: EventCategory,
+    /// Only events for this project (tickets, workers, queues)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    /// Only requests addressed to this agent (requests)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<String>,
+}
+
+impl Subscription {
+    pub fn matches(&self, event: &EventPayload) -> bool {
+        if EventCategory::of(event) != self.category {
+            return false;
+        }
+
+        let project_id = match &event.data {
+            EventData::Ticket(data) => Some(data.project_id.as_str()),
+            EventData::Worker(data) => Some(data.project_id.as_str()),
+            EventData::Queue(data) => Some(data.project_id.as_str()),
+            EventData::System(_) => None,
+        };
+        if let Some(wanted) = &self.project_id {
+            if project_id != Some(wanted.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(wanted) = &self.recipient {
+            let recipient = match &event.data {
+                EventData::System(data) => data
+                    .metadata
+                    .as_ref()
+                    .and_then(|m| m.get("recipient"))
+                    .and_then(|r| r.as_str()),
+                _ => None,
+            };
+            if recipient != Some(wanted.as_str()) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Event subscriptions per session (WebSocket client id).
+///
+/// Sessions without subscriptions receive every event, so clients that never
+/// subscribe keep the previous broadcast-everything behaviour.
+#[derive(Default)]
+pub struct SubscriptionRegistry {
+    sessions: DashMap<String, Vec<Subscription>>,
+}
+
+impl SubscriptionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a subscription and return it with its assigned id
+    pub fn subscribe(
+        &self,
+        session_id: &str,
+        mut subscription: Subscription,
+    ) -> Result<Subscription, JsonRpcError> {
+        let mut subscriptions = self.sessions.entry(session_id.to_string()).or_default();
+        if subscriptions.len() >= MAX_SUBSCRIPTIONS_PER_SESSION {
+            return Err(JsonRpcError::new(
+                ErrorKind::QuotaExceeded,
+                format!(
+                    "Session already holds the maximum of {} subscriptions",
+                    MAX_SUBSCRIPTIONS_PER_SESSION
+                ),
+            ));
+        }
+
+        subscription.id = Uuid::new_v4().to_string();
+        subscriptions.push(subscription.clone());
+        Ok(subscription)
+    }
+
+    /// Remove a subscription; returns false if the session has no such subscription
+    pub fn unsubscribe(&self, session_id: &str, subscription_id: &str) -> bool {
+        let Some(mut subscriptions) = self.sessions.get_mut(session_id) else {
+            return false;
+        };
+        let before = subscriptions.len();
+        subscriptions.retain(|s| s.id != subscription_id);
+        let removed = subscriptions.len() != before;
+        let now_empty = subscriptions.is_empty();
+        drop(subscriptions);
+
+        if now_empty {
+            self.sessions.remove_if(session_id, |_, s| s.is_empty());
+        }
+        removed
+    }
+
+    pub fn list(&self, session_id: &str) -> Vec<Subscription> {
+        self.sessions
+            .get(session_id)
+            .map(|s| s.clone())
+            .unwrap_or_default()
+    }
+
+    /// Whether an event should be pushed to the session
+    pub fn wants(&self, session_id: &str, event: &EventPayload) -> bool {
+        match self.sessions.get(session_id) {
+            Some(subscriptions) => subscriptions.iter().any(|s| s.matches(event)),
+            None => true,
+        }
+    }
+
+    /// Drop all subscriptions of a closed session
+    pub fn remove_session(&self, session_id: &str) {
+        self.sessions.remove(session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn subscription(category: EventCategory) -> Subscription {
+        Subscription {
+            id: String::new(),
+            category,
+            project_id: None,
+            recipient: None,
+        }
+    }
+
+    #[test]
+    fn test_filters() {
+        let ticket = EventPayload::ticket_updated("t1", "p1", "open");
+        let request = EventPayload::system_message(
+            "agent_requests",
+            "Request r1 awaits a response",
+            Some(json!({ "recipient": "w1" })),
+        );
+
+        let mut tickets = subscription(EventCategory::Tickets);
+        assert!(tickets.matches(&ticket));
+        assert!(!tickets.matches(&request));
+        tickets.project_id = Some("p2".to_string());
+        assert!(!tickets.matches(&ticket));
+
+        let mut requests = subscription(EventCategory::Requests);
+        requests.recipient = Some("w1".to_string());
+        assert!(requests.matches(&request));
+        requests.recipient = Some("w2".to_string());
+        assert!(!requests.matches(&request));
+    }
+
+    #[test]
+    fn test_registry_lifecycle_and_cap() {
+        let registry = SubscriptionRegistry::new();
+        let ticket = EventPayload::ticket_updated("t1", "p1", "open");
+
+        // No subscriptions: everything is delivered
+        assert!(registry.wants("s1", &ticket));
+
+        let sub = registry
+            .subscribe("s1", subscription(EventCategory::Workers))
+            .unwrap();
+        assert!(!registry.wants("s1", &ticket));
+        assert_eq!(registry.list("s1").len(), 1);
+
+        assert!(registry.unsubscribe("s1", &sub.id));
+        assert!(!registry.unsubscribe("s1", &sub.id));
+        assert!(registry.wants("s1", &ticket));
+
+        for _ in 0..MAX_SUBSCRIPTIONS_PER_SESSION {
+            registry
+                .subscribe("s1", subscription(EventCategory::System))
+                .unwrap();
+        }
+        assert!(registry
+            .subscribe("s1", subscription(EventCategory::System))
+            .is_err());
+
+        registry.remove_session("s1");
+        assert!(registry.list("s1").is_empty());
+    }
+}
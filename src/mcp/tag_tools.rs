@@ -0,0 +1,94 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tracing::info;
+
+use super::{
+    tools::{create_json_error_response, create_json_success_response, extract_param, ToolHandler},
+    types::{CallToolResponse, Tool},
+};
+use crate::{
+    database::{tags::Tag, tickets::Ticket},
+    error::Result,
+    server::AppState,
+};
+
+pub struct ListTagsTool;
+
+#[async_trait]
+impl ToolHandler for ListTagsTool {
+    async fn call(&self, state: &AppState, _arguments: Option<Value>) -> Result<CallToolResponse> {
+        let tags = Tag::list(&state.db).await?;
+        Ok(create_json_success_response(json!({
+            "count": tags.len(),
+            "tags": tags
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "list_tags".to_string(),
+            description: "List canonical ticket tags with their colors and ticket counts"
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {}
+            }),
+        }
+    }
+}
+
+pub struct SetTicketTagsTool;
+
+#[async_trait]
+impl ToolHandler for SetTicketTagsTool {
+    async fn call(&self, state: &AppState, arguments: Option<Value>) -> Result<CallToolResponse> {
+        let ticket_id: String = extract_param(&arguments, "ticket_id")?;
+        let tags: Vec<String> = extract_param(&arguments, "tags")?;
+
+        if Ticket::get_by_id(&state.db, &ticket_id).await?.is_none() {
+            return Ok(create_json_error_response(&format!(
+                "Ticket '{}' not found",
+                ticket_id
+            )));
+        }
+        let tag_ids = match Tag::resolve(&state.db, &tags, !state.config.strict_tags).await {
+            Ok(ids) => ids,
+            Err(e) => return Ok(create_json_error_response(&e.to_string())),
+        };
+        Tag::set_for_ticket(&state.db, &ticket_id, &tag_ids).await?;
+
+        let tags = Tag::names_for_tickets(&state.db, &[&ticket_id])
+            .await?
+            .remove(&ticket_id)
+            .unwrap_or_default();
+        info!("Set tags of ticket {}: {:?}", ticket_id, tags);
+        Ok(create_json_success_response(json!({
+            "ticket_id": ticket_id,
+            "tags": tags
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "set_ticket_tags".to_string(),
+            description: "Replace a ticket's tags. Names are matched case-insensitively against existing tags; unknown tags are created unless the server runs with --strict-tags".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "ticket_id": {
+                        "type": "string",
+                        "description": "Ticket to tag"
+                    },
+                    "tags": {
+                        "type": "array",
+                        "items": {
+                            "type": "string"
+                        },
+                        "description": "Complete list of tag names; an empty list removes all tags"
+                    }
+                },
+                "required": ["ticket_id", "tags"]
+            }),
+        }
+    }
+}
@@ -3,20 +3,61 @@ use serde_json::{json, Value};
 use tracing::{info, warn};
 
 use super::{
+    access::ToolCaller,
+    pagination::{extract_keyset, KeysetCursor},
     tools::{
-        create_json_error_response, create_json_success_response, extract_optional_param,
-        extract_param, ToolHandler,
+        create_dry_run_response, create_json_error_response,
+        create_json_error_response_with_details, create_json_success_response, dry_run_property,
+        extract_optional_param, extract_param, is_dry_run, ToolHandler,
     },
-    types::{CallToolResponse, PaginationCursor, Tool},
+    types::{CallToolResponse, Tool},
 };
 use crate::{
     database::{
+        attachments::Attachment,
         comments::{Comment, CreateCommentRequest},
-        tickets::{CreateTicketRequest, Ticket, TicketState},
+        duplicates::{find_possible_duplicates, LinkOutcome},
+        pipeline::{transition_pipeline, PipelineTransitionRequest},
+        project_settings::ProjectSettings,
+        quotas::QuotaExceeded,
+        relations,
+        routing::RoutingPolicy,
+        sla::normalize_due_at,
+        tags::{canonical_name, Tag},
+        thread_summaries::{SummarizeOutcome, ThreadSummary},
+        ticket_search::{match_query, TicketSearchHit, DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT},
+        ticket_templates::TicketTemplate,
+        tickets::{
+            validate_estimate_minutes, CreateTicketRequest, Ticket, TicketFilter, TicketState,
+        },
+        worker_load::WorkerTypeLoad,
     },
     server::AppState,
 };
 
+/// Whether a ticket may be sent to `stage`: Err with the refusal when the worker type is
+/// busy or unavailable and assignments are refused at capacity, otherwise the warning to
+/// report, if any
+async fn capacity_gate(
+    state: &AppState,
+    project_id: &str,
+    stage: &str,
+) -> Result<Option<String>, CallToolResponse> {
+    let reason = match WorkerTypeLoad::check_assignment(&state.db, project_id, stage).await {
+        Ok(reason) => reason,
+        Err(e) => {
+            warn!("Failed to check the load of worker type '{}': {}", stage, e);
+            None
+        }
+    };
+    match reason {
+        Some(reason) if state.live_config.borrow().refuse_assignments_at_capacity => Err(
+            create_json_error_response(&format!("{}; not assigning the ticket", reason)),
+        ),
+        reason => Ok(reason),
+    }
+}
+
 pub struct CreateTicketTool;
 
 #[async_trait]
@@ -35,10 +76,42 @@ impl ToolHandler for CreateTicketTool {
             extract_optional_param(&Some(args.clone()), "description")?.unwrap_or_default();
         let ticket_type: String = extract_optional_param(&Some(args.clone()), "ticket_type")?
             .unwrap_or_else(|| "task".to_string());
-        let priority: String = extract_optional_param(&Some(args.clone()), "priority")?
-            .unwrap_or_else(|| "medium".to_string());
-        let initial_stage: String = extract_optional_param(&Some(args.clone()), "initial_stage")?
-            .unwrap_or_else(|| "planning".to_string());
+        let priority: Option<String> = extract_optional_param(&Some(args.clone()), "priority")?;
+        let template_name: Option<String> =
+            extract_optional_param(&Some(args.clone()), "template_name")?;
+        let custom_fields: Option<serde_json::Map<String, Value>> =
+            extract_optional_param(&Some(args.clone()), "custom_fields")?;
+        let initial_stage: Option<String> =
+            extract_optional_param(&Some(args.clone()), "initial_stage")?;
+        let due_at_input: Option<String> = extract_optional_param(&Some(args.clone()), "due_at")?;
+        let sla_minutes: Option<i64> = extract_optional_param(&Some(args.clone()), "sla_minutes")?;
+        let estimate_minutes: Option<i64> =
+            extract_optional_param(&Some(args.clone()), "estimate_minutes")?;
+        let mut tags: Vec<String> =
+            extract_optional_param(&Some(args.clone()), "tags")?.unwrap_or_default();
+        let check_only: bool =
+            extract_optional_param(&Some(args.clone()), "check_only")?.unwrap_or(false);
+
+        if let Some(Err(e)) = estimate_minutes.map(validate_estimate_minutes) {
+            return Ok(create_json_error_response(&e.to_string()));
+        }
+        if sla_minutes.is_some_and(|m| m <= 0) {
+            return Ok(create_json_error_response(
+                "sla_minutes must be a positive number of minutes",
+            ));
+        }
+        let due_at = match due_at_input {
+            Some(input) => match normalize_due_at(&input) {
+                Ok(due_at) => Some(due_at),
+                Err(e) => {
+                    return Ok(create_json_error_response(&format!(
+                        "Invalid due_at '{}': expected RFC 3339 timestamp ({})",
+                        input, e
+                    )))
+                }
+            },
+            None => None,
+        };
 
         // New DAG-related parameters
         let parent_ticket_id: Option<String> =
@@ -48,6 +121,23 @@ impl ToolHandler for CreateTicketTool {
         let created_by_worker_id: Option<String> =
             extract_optional_param(&Some(args.clone()), "created_by_worker_id")?;
 
+        // The project's settings supply the starting stage and priority when none are given
+        let settings = match ProjectSettings::get(&state.db, &project_id).await {
+            Ok(settings) => settings
+                .unwrap_or_default()
+                .effective(&state.live_config.borrow()),
+            Err(e) => {
+                return Ok(create_json_error_response(&format!(
+                    "Failed to load project settings: {}",
+                    e
+                )))
+            }
+        };
+        // Explicit stages take precedence over routing by capability
+        let auto_route =
+            settings.auto_assign && initial_stage.is_none() && execution_plan_input.is_none();
+        let initial_stage = initial_stage.unwrap_or(settings.auto_assign_capability);
+
         // Validate initial_stage only if no execution_plan is supplied
         if execution_plan_input.is_none() {
             if let Err(e) = crate::validation::PipelineValidator::validate_initial_stage(
@@ -61,13 +151,58 @@ impl ToolHandler for CreateTicketTool {
             }
         }
 
+        // Apply template defaults and enforce its required custom fields
+        let priority = match template_name {
+            Some(ref name) => {
+                let template = match TicketTemplate::get_by_name(&state.db, name).await {
+                    Ok(Some(t)) => t,
+                    Ok(None) => {
+                        return Ok(create_json_error_response(&format!(
+                            "Ticket template '{}' not found",
+                            name
+                        )))
+                    }
+                    Err(e) => {
+                        return Ok(create_json_error_response(&format!(
+                            "Failed to get ticket template: {}",
+                            e
+                        )))
+                    }
+                };
+                let field_errors =
+                    template.validate_custom_fields(&custom_fields.clone().unwrap_or_default())?;
+                if !field_errors.is_empty() {
+                    return Ok(create_json_error_response_with_details(
+                        &format!("Ticket does not satisfy template '{}'", name),
+                        json!({ "field_errors": field_errors }),
+                    ));
+                }
+                tags = template.tags_with(&tags);
+                priority.unwrap_or(template.default_priority)
+            }
+            None => priority.unwrap_or_else(|| settings.default_issue_priority.to_string()),
+        };
+
+        let tag_ids = match Tag::resolve(&state.db, &tags, !state.config.strict_tags).await {
+            Ok(ids) => ids,
+            Err(e) => return Ok(create_json_error_response(&e.to_string())),
+        };
+        let mut tag_names: Vec<String> = tags
+            .iter()
+            .filter_map(|name| canonical_name(name).ok())
+            .collect();
+        tag_names.sort();
+        tag_names.dedup();
+
         info!("Creating ticket: {} in project {}", title, project_id);
 
         // Use provided execution plan or default to single stage
         let execution_plan = execution_plan_input.unwrap_or_else(|| vec![initial_stage.clone()]);
-        let first_stage = execution_plan.first().cloned().ok_or_else(|| {
-            crate::error::AppError::BadRequest("Execution plan is empty".to_string())
-        })?;
+        if execution_plan.is_empty() {
+            return Err(crate::error::AppError::BadRequest(
+                "Execution plan is empty".to_string(),
+            ));
+        }
 
         // Validate all stages in execution plan exist as worker types
         if let Err(e) = crate::validation::PipelineValidator::validate_pipeline_stages(
@@ -99,6 +234,47 @@ impl ToolHandler for CreateTicketTool {
                 }
             };
 
+        let possible_duplicates = match find_possible_duplicates(
+            &state.db,
+            &project_id,
+            &title,
+            &tag_names,
+            None,
+        )
+        .await
+        {
+            Ok(duplicates) => duplicates,
+            Err(e) if check_only => {
+                return Ok(create_json_error_response(&format!(
+                    "Failed to check for duplicates: {}",
+                    e
+                )))
+            }
+            Err(e) => {
+                warn!("Failed to check ticket '{}' for duplicates: {}", title, e);
+                Vec::new()
+            }
+        };
+        if check_only {
+            return Ok(create_json_success_response(json!({
+                "message": match possible_duplicates.len() {
+                    0 => "No open ticket looks like a duplicate; nothing was created".to_string(),
+                    n => format!("{} open ticket(s) may be duplicates; nothing was created", n),
+                },
+                "check_only": true,
+                "possible_duplicates": possible_duplicates
+            })));
+        }
+
+        // Routing passes over busy worker types itself; its outcome is checked once it is known
+        let mut capacity_warning = None;
+        if !auto_route {
+            match capacity_gate(state, &project_id, &execution_plan[0]).await {
+                Ok(warning) => capacity_warning = warning,
+                Err(refusal) => return Ok(refusal),
+            }
+        }
+
         // Determine subsystem from execution plan for ticket ID generation
         let subsystem = crate::workers::ticket_id::infer_subsystem_from_stages(&execution_plan);
 
@@ -130,10 +306,18 @@ impl ToolHandler for CreateTicketTool {
             dependency_status: None, // Will default to 'ready' in database
             created_by_worker_id,
             priority: Some(priority),
+            custom_fields: custom_fields
+                .map(|fields| serde_json::to_string(&fields))
+                .transpose()?,
+            due_at,
+            sla_minutes,
+            estimate_minutes,
         };
 
-        let ticket = match Ticket::create(&state.db, req).await {
-            Ok(t) => t,
+        let policy = RoutingPolicy::from(&*state.live_config.borrow());
+        let routing = auto_route.then_some((tag_names.as_slice(), policy));
+        let (ticket, decision) = match Ticket::create_routed(&state.db, req, routing).await {
+            Ok(created) => created,
             Err(e) => {
                 return Ok(create_json_error_response(&format!(
                     "Failed to create ticket: {}",
@@ -142,21 +326,28 @@ impl ToolHandler for CreateTicketTool {
             }
         };
 
-        // Emit ticket_created event
-        if let Err(e) = state
-            .event_emitter()
-            .emit_ticket_created(
-                &ticket.ticket_id,
-                &ticket.project_id,
-                &ticket.title,
-                &ticket.current_stage,
-            )
-            .await
-        {
-            warn!("Failed to emit ticket_created event: {}", e);
+        if let Err(e) = Tag::set_for_ticket(&state.db, &ticket.ticket_id, &tag_ids).await {
+            warn!("Failed to tag ticket {}: {}", ticket.ticket_id, e);
+        }
+
+        // ticket_created was queued with the ticket
+        state.event_broadcaster.notify_outbox();
+
+        if let Some(decision) = &decision {
+            info!(
+                "Auto-assignment of ticket {}: {}",
+                ticket.ticket_id, decision.detail
+            );
+            if !decision.assigned {
+                capacity_warning =
+                    WorkerTypeLoad::check_assignment(&state.db, &project_id, &ticket.current_stage)
+                        .await
+                        .unwrap_or_default();
+            }
         }
 
         // Automatically submit the ticket to the first stage queue
+        let first_stage = ticket.current_stage.clone();
         match state
             .queue_manager
             .submit_task(&project_id, &first_stage, &ticket_id)
@@ -176,18 +367,32 @@ impl ToolHandler for CreateTicketTool {
             }
         }
 
+        let auto_assignment = match decision {
+            Some(decision) => json!(decision),
+            None if settings.auto_assign => json!({
+                "assigned": false,
+                "detail": "An initial stage or execution plan was given"
+            }),
+            None => Value::Null,
+        };
+
         Ok(create_json_success_response(json!({
             "message": format!("Created ticket '{}'", title),
             "ticket_id": ticket.ticket_id,
+            "short_id": ticket.short_id,
             "project_id": ticket.project_id,
-            "current_stage": ticket.current_stage
+            "current_stage": ticket.current_stage,
+            "tags": tag_names,
+            "auto_assignment": auto_assignment,
+            "capacity_warning": capacity_warning,
+            "possible_duplicates": possible_duplicates
         })))
     }
 
     fn definition(&self) -> Tool {
         Tool {
             name: "create_ticket".to_string(),
-            description: "Create a new ticket in a project".to_string(),
+            description: "Create a new ticket in a project. The result lists open tickets of the project whose title and tags look alike as possible_duplicates (ticket, similarity from 0 to 1); close a real duplicate with mark_ticket_duplicate".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -210,13 +415,11 @@ impl ToolHandler for CreateTicketTool {
                     },
                     "priority": {
                         "type": "string",
-                        "description": "Priority level (low, medium, high, critical)",
-                        "default": "medium"
+                        "description": "Priority level (low, medium, high, critical); defaults to the project's default_issue_priority setting, or medium"
                     },
                     "initial_stage": {
                         "type": "string",
-                        "description": "Initial stage for ticket processing (must be a valid worker type)",
-                        "default": "planning"
+                        "description": "Initial stage for ticket processing (must be a valid worker type); defaults to the project's auto_assign_capability setting, or planning"
                     },
                     "parent_ticket_id": {
                         "type": "string",
@@ -232,6 +435,38 @@ impl ToolHandler for CreateTicketTool {
                     "created_by_worker_id": {
                         "type": "string",
                         "description": "ID of the worker that created this ticket (for planner-created tickets)"
+                    },
+                    "template_name": {
+                        "type": "string",
+                        "description": "Optional ticket template; supplies the default priority and tags and requires its custom fields"
+                    },
+                    "custom_fields": {
+                        "type": "object",
+                        "description": "Custom field values, validated against the template's required fields"
+                    },
+                    "due_at": {
+                        "type": "string",
+                        "description": "Optional due date (RFC 3339). Overdue tickets are escalated to the coordinator"
+                    },
+                    "sla_minutes": {
+                        "type": "integer",
+                        "description": "Optional SLA in minutes; sets due_at relative to creation when due_at is not given"
+                    },
+                    "estimate_minutes": {
+                        "type": "integer",
+                        "description": "Optional estimate of the work in minutes, used for burndown charts"
+                    },
+                    "tags": {
+                        "type": "array",
+                        "items": {
+                            "type": "string"
+                        },
+                        "description": "Tag names; matched case-insensitively against existing tags, unknown tags are created unless the server runs with --strict-tags. In projects with auto_assign, a ticket without initial_stage or execution_plan starts in the worker type whose capabilities best cover its tags; the result's auto_assignment says whether that happened and why not"
+                    },
+                    "check_only": {
+                        "type": "boolean",
+                        "description": "Only report possible_duplicates, without creating the ticket",
+                        "default": false
                     }
                 },
                 "required": ["project_id", "title"]
@@ -253,6 +488,8 @@ impl ToolHandler for GetTicketTool {
             .ok_or_else(|| crate::error::AppError::BadRequest("Missing arguments".to_string()))?;
 
         let ticket_id: String = extract_param(&Some(args.clone()), "ticket_id")?;
+        let full_history: bool =
+            extract_optional_param(&Some(args.clone()), "full_history")?.unwrap_or(false);
 
         let ticket = Ticket::get_by_id(&state.db, &ticket_id)
             .await
@@ -262,10 +499,23 @@ impl ToolHandler for GetTicketTool {
             })?;
 
         match ticket {
-            Some(ticket_with_comments) => Ok(create_json_success_response(json!({
-                "ticket": ticket_with_comments.ticket,
-                "comments": ticket_with_comments.comments
-            }))),
+            Some(ticket_with_comments) => {
+                let ticket_with_comments = ticket_with_comments
+                    .with_summary(&state.db, full_history)
+                    .await?
+                    .with_relations(&state.db)
+                    .await?;
+                let attachments = Attachment::list_for_ticket(&state.db, &ticket_id).await?;
+                Ok(create_json_success_response(json!({
+                    "ticket": ticket_with_comments.ticket,
+                    "comments": ticket_with_comments.comments,
+                    "pipeline": ticket_with_comments.pipeline,
+                    "tags": ticket_with_comments.tags,
+                    "summary": ticket_with_comments.summary,
+                    "relations": ticket_with_comments.relations,
+                    "attachments": attachments
+                })))
+            }
             None => Ok(create_json_error_response(&format!(
                 "Ticket {} not found",
                 ticket_id
@@ -276,13 +526,18 @@ impl ToolHandler for GetTicketTool {
     fn definition(&self) -> Tool {
         Tool {
             name: "get_ticket".to_string(),
-            description: "Get ticket details including comments and history".to_string(),
+            description: "Get ticket details including comments, history and attachment metadata. Once the comment thread has been summarized, returns the summary plus only the comments after it"
+                .to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
                     "ticket_id": {
                         "type": "string",
                         "description": "Ticket identifier"
+                    },
+                    "full_history": {
+                        "type": "boolean",
+                        "description": "Also return the comments the thread summary covers (default: false)"
                     }
                 },
                 "required": ["ticket_id"]
@@ -304,34 +559,53 @@ impl ToolHandler for ListTicketsTool {
 
         let project_id: Option<String> = extract_optional_param(&Some(args.clone()), "project_id")?;
         let status: Option<String> = extract_optional_param(&Some(args.clone()), "status")?;
+        let tag: Option<String> = extract_optional_param(&Some(args.clone()), "tag")?;
+        let min_reopens: Option<i64> = extract_optional_param(&Some(args.clone()), "min_reopens")?;
+        if min_reopens.is_some_and(|n| n < 0) {
+            return Ok(create_json_error_response(
+                "min_reopens must not be negative",
+            ));
+        }
 
-        // Parse pagination parameters
-        let cursor_str: Option<String> = extract_optional_param(&Some(args.clone()), "cursor")?;
-        let cursor = PaginationCursor::from_cursor_string(cursor_str)
-            .map_err(crate::error::AppError::BadRequest)?;
+        let filter = TicketFilter {
+            project_id: project_id.as_deref(),
+            status: status.as_deref(),
+            tag: tag.as_deref(),
+            overdue: false,
+            min_reopens,
+        };
+        let page_request = extract_keyset(&Some(args.clone()), filter.fingerprint())?;
 
-        // Get all tickets first
-        let all_tickets =
-            Ticket::list_by_project(&state.db, project_id.as_deref(), status.as_deref())
-                .await
-                .map_err(|e| {
-                    warn!(
-                        "Failed to list tickets (project: {:?}, status: {:?}): {}",
-                        project_id, status, e
-                    );
-                    e
-                })?;
+        let (rows, total) = match tokio::try_join!(
+            Ticket::list_page(
+                &state.db,
+                &filter,
+                page_request.after.as_ref().map(KeysetCursor::after),
+                page_request.fetch_limit(),
+            ),
+            Ticket::count(&state.db, &filter)
+        ) {
+            Ok(result) => result,
+            Err(e) => {
+                warn!(
+                    "Failed to list tickets (project: {:?}, status: {:?}, tag: {:?}): {}",
+                    project_id, status, tag, e
+                );
+                return Err(e.into());
+            }
+        };
 
-        // Apply pagination using helper
-        let pagination_result = cursor.paginate(all_tickets);
+        let page = page_request.page(rows, |t| (t.created_at.clone(), t.ticket_id.clone()));
+        let mut tickets = Tag::tag_tickets(&state.db, page.items).await?;
+        ThreadSummary::annotate(&state.db, &mut tickets).await?;
 
         // Create response with pagination info
         let response_data = json!({
-            "tickets": pagination_result.items,
+            "tickets": tickets,
             "pagination": {
-                "total": pagination_result.total,
-                "has_more": pagination_result.has_more,
-                "next_cursor": pagination_result.next_cursor
+                "total": total,
+                "has_more": page.has_more,
+                "next_cursor": page.next_cursor
             }
         });
 
@@ -341,7 +615,7 @@ impl ToolHandler for ListTicketsTool {
     fn definition(&self) -> Tool {
         Tool {
             name: "list_tickets".to_string(),
-            description: "List tickets, optionally filtered by project or status".to_string(),
+            description: "List tickets newest first, optionally filtered by project, status, tag or reopen count. Pages are stable: pass next_cursor back as cursor to continue".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -354,9 +628,21 @@ impl ToolHandler for ListTicketsTool {
                         "description": "Optional status filter (open, closed)",
                         "enum": ["open", "closed"]
                     },
+                    "tag": {
+                        "type": "string",
+                        "description": "Optional tag filter (case-insensitive)"
+                    },
+                    "min_reopens": {
+                        "type": "integer",
+                        "description": "Only tickets reopened at least this many times after being closed"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum tickets per page (default: 50, max: 200)"
+                    },
                     "cursor": {
                         "type": "string",
-                        "description": "Optional cursor for pagination"
+                        "description": "next_cursor from the previous page; only valid with the same filters"
                     }
                 },
                 "required": []
@@ -365,6 +651,104 @@ impl ToolHandler for ListTicketsTool {
     }
 }
 
+pub struct SearchTicketsTool;
+
+#[async_trait]
+impl ToolHandler for SearchTicketsTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let args = arguments
+            .ok_or_else(|| crate::error::AppError::BadRequest("Missing arguments".to_string()))?;
+
+        let query: String = extract_param(&Some(args.clone()), "query")?;
+        let project_id: Option<String> = extract_optional_param(&Some(args.clone()), "project_id")?;
+        let status: Option<String> = extract_optional_param(&Some(args.clone()), "status")?;
+        let tag: Option<String> = extract_optional_param(&Some(args.clone()), "tag")?;
+        let limit: Option<i64> = extract_optional_param(&Some(args.clone()), "limit")?;
+
+        if match_query(&query).is_none() {
+            return Ok(create_json_error_response(
+                "query must contain at least one word",
+            ));
+        }
+        if status
+            .as_deref()
+            .is_some_and(|s| s != "open" && s != "closed")
+        {
+            return Ok(create_json_error_response(
+                "status must be 'open' or 'closed'",
+            ));
+        }
+        let tag = match tag.as_deref().map(canonical_name).transpose() {
+            Ok(tag) => tag,
+            Err(e) => return Ok(create_json_error_response(&e.to_string())),
+        };
+        let filter = TicketFilter {
+            project_id: project_id.as_deref(),
+            status: status.as_deref(),
+            tag: tag.as_deref(),
+            overdue: false,
+            min_reopens: None,
+        };
+
+        let hits = TicketSearchHit::search(
+            &state.db,
+            &query,
+            &filter,
+            limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        )
+        .await?;
+        let mut response = json!({
+            "query": query,
+            "count": hits.len(),
+            "results": hits,
+        });
+        if let Some(warning) =
+            TicketSearchHit::encryption_warning(&state.db, project_id.as_deref()).await?
+        {
+            response["warning"] = json!(warning);
+        }
+        Ok(create_json_success_response(response))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "search_tickets".to_string(),
+            description: "Full-text search over ticket titles, descriptions and comments (including resolutions), best match first, with matched terms marked in a snippet. Encrypted comments are not searched; a warning says how many were skipped".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "Words that must all appear in the ticket; title matches rank highest"
+                    },
+                    "project_id": {
+                        "type": "string",
+                        "description": "Optional project filter"
+                    },
+                    "status": {
+                        "type": "string",
+                        "description": "Optional status filter (open, closed)",
+                        "enum": ["open", "closed"]
+                    },
+                    "tag": {
+                        "type": "string",
+                        "description": "Optional tag filter (case-insensitive)"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": format!("Maximum results (default: {}, max: {})", DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT)
+                    }
+                },
+                "required": ["query"]
+            }),
+        }
+    }
+}
+
 pub struct AddTicketCommentTool;
 
 #[async_trait]
@@ -377,11 +761,44 @@ impl ToolHandler for AddTicketCommentTool {
         let args = arguments
             .ok_or_else(|| crate::error::AppError::BadRequest("Missing arguments".to_string()))?;
 
-        let ticket_id: String = extract_param(&Some(args.clone()), "ticket_id")?;
+        let ticket_id: Option<String> = extract_optional_param(&Some(args.clone()), "ticket_id")?;
         let worker_type: String = extract_param(&Some(args.clone()), "worker_type")?;
         let worker_id: String = extract_param(&Some(args.clone()), "worker_id")?;
         let stage_number: i32 = extract_param(&Some(args.clone()), "stage_number")?;
         let content: String = extract_param(&Some(args.clone()), "content")?;
+        let reply_to_comment_id: Option<i64> =
+            extract_optional_param(&Some(args.clone()), "reply_to_comment_id")?;
+
+        // A reply goes to the ticket of the comment it answers
+        let ticket_id = match (ticket_id, reply_to_comment_id) {
+            (ticket_id, Some(parent)) => {
+                match Comment::reply_target(&state.db, ticket_id.as_deref(), parent).await {
+                    Ok(ticket_id) => ticket_id,
+                    Err(e) => return Ok(create_json_error_response(&e.to_string())),
+                }
+            }
+            (Some(ticket_id), None) => ticket_id,
+            (None, None) => {
+                return Ok(create_json_error_response(
+                    "ticket_id is required unless reply_to_comment_id is given",
+                ))
+            }
+        };
+
+        match ProjectSettings::for_ticket(&state.db, &ticket_id).await? {
+            Some((settings, claim)) => {
+                if let Some(denied) = settings.edit_denied(&ticket_id, claim.as_deref(), &worker_id)
+                {
+                    return Ok(create_json_error_response(&denied));
+                }
+            }
+            None => {
+                return Ok(create_json_error_response(&format!(
+                    "Ticket '{}' not found",
+                    ticket_id
+                )))
+            }
+        }
 
         info!(
             "Adding comment to ticket {} by worker {}",
@@ -394,47 +811,46 @@ impl ToolHandler for AddTicketCommentTool {
             worker_id,
             stage_number,
             content: content.clone(),
+            reply_to_comment_id,
         };
 
-        let comment = Comment::create_from_request(&state.db, req)
-            .await
-            .map_err(|e| {
+        let comment = match Comment::create_from_request(&state.db, req).await {
+            Ok(comment) => comment,
+            Err(e) if e.downcast_ref::<QuotaExceeded>().is_some() => {
+                warn!("Comment on ticket {} refused: {}", ticket_id, e);
+                return Ok(create_json_error_response(&e.to_string()));
+            }
+            Err(e) => {
                 warn!("Failed to create comment for ticket {}: {}", ticket_id, e);
-                e
-            })?;
+                return Err(e.into());
+            }
+        };
 
-        // Emit ticket_updated event for comment added
-        if let Err(e) = state
-            .event_emitter()
-            .emit_ticket_updated(
-                &ticket_id,
-                "", // project_id will be looked up by the emitter if needed
-                "comment_added",
-                None,
-                Some(&format!("Comment added: {}", comment.id)),
-            )
-            .await
-        {
-            warn!("Failed to emit ticket_updated event: {}", e);
-        }
+        // ticket_updated was queued with the comment
+        state.event_broadcaster.notify_outbox();
 
         Ok(create_json_success_response(json!({
             "message": format!("Added comment to ticket {}", ticket_id),
             "ticket_id": ticket_id,
-            "comment_id": comment.id
+            "comment_id": comment.id,
+            "reply_to_comment_id": comment.reply_to_comment_id
         })))
     }
 
     fn definition(&self) -> Tool {
         Tool {
             name: "add_ticket_comment".to_string(),
-            description: "Add a worker report comment to a ticket".to_string(),
+            description: "Add a worker report comment to a ticket, optionally as a reply to an earlier comment on it".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
                     "ticket_id": {
                         "type": "string",
-                        "description": "Ticket identifier"
+                        "description": "Ticket identifier; may be omitted when replying, to post on the ticket of the comment replied to"
+                    },
+                    "reply_to_comment_id": {
+                        "type": "integer",
+                        "description": "Comment this one answers; it must be on the same ticket"
                     },
                     "worker_type": {
                         "type": "string",
@@ -453,16 +869,16 @@ impl ToolHandler for AddTicketCommentTool {
                         "description": "Comment content"
                     }
                 },
-                "required": ["ticket_id", "worker_type", "worker_id", "stage_number", "content"]
+                "required": ["worker_type", "worker_id", "stage_number", "content"]
             }),
         }
     }
 }
 
-pub struct CloseTicketTool;
+pub struct SummarizeTicketThreadTool;
 
 #[async_trait]
-impl ToolHandler for CloseTicketTool {
+impl ToolHandler for SummarizeTicketThreadTool {
     async fn call(
         &self,
         state: &AppState,
@@ -471,33 +887,168 @@ impl ToolHandler for CloseTicketTool {
         let args = arguments
             .ok_or_else(|| crate::error::AppError::BadRequest("Missing arguments".to_string()))?;
 
+        let ticket_id: String = extract_param(&Some(args.clone()), "ticket_id")?;
+        let summary: String = extract_param(&Some(args.clone()), "summary")?;
+        let through_comment_id: i64 = extract_param(&Some(args.clone()), "through_comment_id")?;
+        let from_comment_id: Option<i64> =
+            extract_optional_param(&Some(args.clone()), "from_comment_id")?;
+        let created_by: String = extract_optional_param(&Some(args.clone()), "created_by")?
+            .unwrap_or_else(|| "coordinator".to_string());
+
+        let outcome = ThreadSummary::create(
+            &state.db,
+            &ticket_id,
+            &summary,
+            from_comment_id,
+            through_comment_id,
+            &created_by,
+        )
+        .await
+        .map_err(|e| {
+            warn!("Failed to summarize thread of ticket {}: {}", ticket_id, e);
+            e
+        })?;
+
+        match outcome {
+            SummarizeOutcome::Created(summary) => {
+                info!(
+                    "Summarized {} comments of ticket {} through comment {}",
+                    summary.comment_count, ticket_id, summary.through_comment_id
+                );
+                Ok(create_json_success_response(json!({
+                    "message": format!("Summarized thread of ticket {}", ticket_id),
+                    "summary": summary
+                })))
+            }
+            SummarizeOutcome::Rejected(reason) => Ok(create_json_error_response(&reason)),
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "summarize_ticket_thread".to_string(),
+            description: "Condense a long ticket comment thread: record a summary covering its comments up to through_comment_id. get_ticket then returns the summary plus only the newer comments. A newer summary supersedes the previous one (which stays in the history), so fold the previous summary into it".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "ticket_id": {
+                        "type": "string",
+                        "description": "Ticket identifier"
+                    },
+                    "summary": {
+                        "type": "string",
+                        "description": "Summary text of the covered comments"
+                    },
+                    "through_comment_id": {
+                        "type": "integer",
+                        "description": "Last comment the summary covers; must be past the current summary's cutoff"
+                    },
+                    "from_comment_id": {
+                        "type": "integer",
+                        "description": "First comment the summary covers (default: the thread's first comment)"
+                    },
+                    "created_by": {
+                        "type": "string",
+                        "description": "Who wrote the summary (default: coordinator)"
+                    }
+                },
+                "required": ["ticket_id", "summary", "through_comment_id"]
+            }),
+        }
+    }
+}
+
+/// Refusal for a worker changing a ticket it is not processing; the coordinator may
+/// change any ticket
+async fn refuse_unless_owner(
+    state: &AppState,
+    caller: &ToolCaller,
+    ticket_id: &str,
+) -> crate::error::Result<Option<CallToolResponse>> {
+    let ToolCaller::Worker { worker_id, .. } = caller else {
+        return Ok(None);
+    };
+    let owner = Ticket::get_by_id(&state.db, ticket_id)
+        .await?
+        .and_then(|t| t.ticket.processing_worker_id);
+    if owner.as_deref() == Some(worker_id.as_str()) {
+        return Ok(None);
+    }
+    Ok(Some(create_json_error_response(&format!(
+        "Ticket {} is not being processed by worker '{}'",
+        ticket_id, worker_id
+    ))))
+}
+
+pub struct CloseTicketTool;
+
+impl CloseTicketTool {
+    async fn close(
+        &self,
+        state: &AppState,
+        closed_by: &str,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let args = arguments
+            .ok_or_else(|| crate::error::AppError::BadRequest("Missing arguments".to_string()))?;
+
         let ticket_id: String = extract_param(&Some(args.clone()), "ticket_id")?;
         let resolution: String = extract_optional_param(&Some(args.clone()), "resolution")?
             .unwrap_or_else(|| "completed".to_string());
 
+        let cascade: bool =
+            extract_optional_param(&Some(args.clone()), "cascade")?.unwrap_or(false);
+
+        let Some(plan) = relations::plan_close(&state.db, &ticket_id).await? else {
+            return Ok(create_json_error_response(&format!(
+                "Ticket {} not found",
+                ticket_id
+            )));
+        };
+        if !plan.open_children.is_empty() && !cascade {
+            return Ok(create_json_error_response(&format!(
+                "Ticket {} has open child tickets: {}. Close them first, or pass cascade=true to close them with it",
+                ticket_id,
+                plan.open_children.join(", ")
+            )));
+        }
+        if is_dry_run("close_ticket", &Some(args)) {
+            return Ok(create_dry_run_response(
+                &format!(
+                    "Closing ticket {} would close {} open descendants and lift blocks on {} tickets",
+                    ticket_id,
+                    plan.descendants.len(),
+                    plan.unblocks.len()
+                ),
+                &plan,
+            ));
+        }
+
         info!(
             "Closing ticket {} with resolution: {} (with dependency cascade)",
             ticket_id, resolution
         );
 
         // Use the unified completion function to close ticket and trigger dependency cascade
+        let comment = format!(
+            "Ticket closed by {} with resolution: {}",
+            closed_by, resolution
+        );
         match state
             .queue_manager
-            .complete_ticket_with_cascade(
-                &ticket_id,
-                &resolution,
-                &format!(
-                    "Ticket closed by coordinator with resolution: {}",
-                    resolution
-                ),
-            )
+            .close_with_descendants(&plan, &resolution, &comment)
             .await
         {
-            Ok(()) => Ok(create_json_success_response(json!({
-                "message": format!("Closed ticket {} with resolution: {} and processed dependencies", ticket_id, resolution),
-                "ticket_id": ticket_id,
-                "resolution": resolution
-            }))),
+            Ok(closed_descendants) => {
+                // Its blockers may no longer block enough open tickets to stay escalated
+                crate::escalation::refresh_for_ticket(state, &ticket_id).await;
+                Ok(create_json_success_response(json!({
+                    "message": format!("Closed ticket {} with resolution: {} and processed dependencies", ticket_id, resolution),
+                    "ticket_id": ticket_id,
+                    "resolution": resolution,
+                    "closed_descendants": closed_descendants
+                })))
+            }
             Err(e) => {
                 if e.to_string().contains("not found") {
                     Ok(create_json_error_response(&format!(
@@ -510,11 +1061,43 @@ impl ToolHandler for CloseTicketTool {
             }
         }
     }
+}
+
+#[async_trait]
+impl ToolHandler for CloseTicketTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        self.close(state, "coordinator", arguments).await
+    }
+
+    /// Workers may close the ticket they are processing, but not its descendants
+    async fn call_as(
+        &self,
+        state: &AppState,
+        caller: &ToolCaller,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        if let ToolCaller::Worker { .. } = caller {
+            let ticket_id: String = extract_param(&arguments, "ticket_id")?;
+            if let Some(refusal) = refuse_unless_owner(state, caller, &ticket_id).await? {
+                return Ok(refusal);
+            }
+            if extract_optional_param::<bool>(&arguments, "cascade")?.unwrap_or(false) {
+                return Ok(create_json_error_response(
+                    "Only the coordinator may close a ticket with cascade",
+                ));
+            }
+        }
+        self.close(state, caller.actor_id(), arguments).await
+    }
 
     fn definition(&self) -> Tool {
         Tool {
             name: "close_ticket".to_string(),
-            description: "Close a ticket with optional resolution note".to_string(),
+            description: "Close a ticket with optional resolution note. A parent ticket with open child tickets is only closed with cascade, which closes its open descendants too".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -526,7 +1109,13 @@ impl ToolHandler for CloseTicketTool {
                         "type": "string",
                         "description": "Resolution note",
                         "default": "completed"
-                    }
+                    },
+                    "cascade": {
+                        "type": "boolean",
+                        "description": "Also close the ticket's open descendants",
+                        "default": false
+                    },
+                    "dry_run": dry_run_property()
                 },
                 "required": ["ticket_id"]
             }),
@@ -534,6 +1123,348 @@ impl ToolHandler for CloseTicketTool {
     }
 }
 
+pub struct MarkTicketDuplicateTool;
+
+#[async_trait]
+impl ToolHandler for MarkTicketDuplicateTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let args = arguments
+            .ok_or_else(|| crate::error::AppError::BadRequest("Missing arguments".to_string()))?;
+
+        let ticket_id: String = extract_param(&Some(args.clone()), "ticket_id")?;
+        let duplicate_of: String = extract_param(&Some(args.clone()), "duplicate_of")?;
+
+        let (original, moved_dependents) = match state
+            .queue_manager
+            .close_as_duplicate(&ticket_id, &duplicate_of)
+            .await?
+        {
+            LinkOutcome::Linked {
+                original,
+                moved_dependents,
+            } => (original, moved_dependents),
+            LinkOutcome::Rejected(reason) => return Ok(create_json_error_response(&reason)),
+        };
+
+        Ok(create_json_success_response(json!({
+            "message": format!("Closed ticket {} as a duplicate of {}", ticket_id, original),
+            "ticket_id": ticket_id,
+            "duplicate_of": original,
+            "moved_dependents": moved_dependents
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "mark_ticket_duplicate".to_string(),
+            description: "Close a ticket as a duplicate of another ticket in the same project. The tickets that depend on the duplicate are moved over to the original, and the original gets a comment pointing back. Marking a ticket as a duplicate of a duplicate links it to the original".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "ticket_id": {
+                        "type": "string",
+                        "description": "The duplicate ticket, which is closed"
+                    },
+                    "duplicate_of": {
+                        "type": "string",
+                        "description": "The ticket it duplicates, which stays as it is"
+                    }
+                },
+                "required": ["ticket_id", "duplicate_of"]
+            }),
+        }
+    }
+}
+
+pub struct SetTicketEstimateTool;
+
+#[async_trait]
+impl ToolHandler for SetTicketEstimateTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let args = arguments
+            .ok_or_else(|| crate::error::AppError::BadRequest("Missing arguments".to_string()))?;
+
+        let ticket_id: String = extract_param(&Some(args.clone()), "ticket_id")?;
+        let estimate_minutes: Option<i64> =
+            extract_optional_param(&Some(args.clone()), "estimate_minutes")?;
+
+        let ticket = match Ticket::update_estimate(&state.db, &ticket_id, estimate_minutes).await {
+            Ok(Some(ticket)) => ticket,
+            Ok(None) => {
+                return Ok(create_json_error_response(&format!(
+                    "Ticket '{}' not found",
+                    ticket_id
+                )))
+            }
+            Err(e) => return Ok(create_json_error_response(&e.to_string())),
+        };
+        info!(
+            "Set estimate of ticket {} to {:?} minutes",
+            ticket_id, estimate_minutes
+        );
+
+        // ticket_updated was queued with the estimate
+        state.event_broadcaster.notify_outbox();
+
+        Ok(create_json_success_response(json!({
+            "ticket_id": ticket_id,
+            "short_id": ticket.short_id,
+            "estimate_minutes": ticket.estimate_minutes,
+            "actual_minutes": ticket.actual_minutes
+        })))
+    }
+
+    /// Workers may only estimate the ticket they are processing
+    async fn call_as(
+        &self,
+        state: &AppState,
+        caller: &ToolCaller,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let ticket_id: String = extract_param(&arguments, "ticket_id")?;
+        if let Some(refusal) = refuse_unless_owner(state, caller, &ticket_id).await? {
+            return Ok(refusal);
+        }
+        self.call(state, arguments).await
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "set_ticket_estimate".to_string(),
+            description: "Set or clear a ticket's estimate in minutes. Estimates of tickets that are not closed add up to the open work in stats and burndown charts".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "ticket_id": {
+                        "type": "string",
+                        "description": "Ticket identifier"
+                    },
+                    "estimate_minutes": {
+                        "type": ["integer", "null"],
+                        "description": "Estimated work in minutes; null clears the estimate"
+                    }
+                },
+                "required": ["ticket_id", "estimate_minutes"]
+            }),
+        }
+    }
+}
+
+pub struct TransitionPipelineTool;
+
+#[async_trait]
+impl ToolHandler for TransitionPipelineTool {
+    async fn call(
+        &self,
+        state: &AppState,
+        arguments: Option<Value>,
+    ) -> crate::error::Result<CallToolResponse> {
+        let args = arguments
+            .ok_or_else(|| crate::error::AppError::BadRequest("Missing arguments".to_string()))?;
+
+        let source_ticket_id: String = extract_param(&Some(args.clone()), "source_ticket_id")?;
+        let resolution: String = extract_optional_param(&Some(args.clone()), "resolution")?
+            .unwrap_or_else(|| "Completed".to_string());
+        let next_ticket_id: Option<String> =
+            extract_optional_param(&Some(args.clone()), "next_ticket_id")?;
+        let stage: Option<String> = extract_optional_param(&Some(args.clone()), "stage")?;
+        let title: Option<String> = extract_optional_param(&Some(args.clone()), "title")?;
+        let description: Option<String> =
+            extract_optional_param(&Some(args.clone()), "description")?;
+        let priority: Option<String> = extract_optional_param(&Some(args.clone()), "priority")?;
+        let template_name: Option<String> =
+            extract_optional_param(&Some(args.clone()), "template_name")?;
+        let custom_fields: serde_json::Map<String, Value> =
+            extract_optional_param(&Some(args.clone()), "custom_fields")?.unwrap_or_default();
+
+        let source = match Ticket::get_by_id(&state.db, &source_ticket_id).await? {
+            Some(t) => t.ticket,
+            None => {
+                return Ok(create_json_error_response(&format!(
+                    "Ticket {} not found",
+                    source_ticket_id
+                )))
+            }
+        };
+
+        // Without an explicit stage, follow the source ticket's execution plan
+        let stage = match stage.or(source.get_next_stage()?) {
+            Some(stage) => stage,
+            None => {
+                return Ok(create_json_error_response(&format!(
+                    "Ticket {} has no next stage in its execution plan; specify 'stage'",
+                    source_ticket_id
+                )))
+            }
+        };
+        if let Err(e) = crate::validation::PipelineValidator::validate_worker_type_exists(
+            &state.db,
+            &source.project_id,
+            &stage,
+        )
+        .await
+        {
+            return Ok(create_json_error_response(&e.to_string()));
+        }
+        let capacity_warning = match capacity_gate(state, &source.project_id, &stage).await {
+            Ok(warning) => warning,
+            Err(refusal) => return Ok(refusal),
+        };
+
+        // The template checks the merged custom fields the next ticket will carry
+        let priority = match template_name {
+            Some(ref name) => {
+                let template = match TicketTemplate::get_by_name(&state.db, name).await? {
+                    Some(t) => t,
+                    None => {
+                        return Ok(create_json_error_response(&format!(
+                            "Ticket template '{}' not found",
+                            name
+                        )))
+                    }
+                };
+                let mut merged = source.custom_field_map();
+                merged.extend(custom_fields.clone());
+                let field_errors = template.validate_custom_fields(&merged)?;
+                if !field_errors.is_empty() {
+                    return Ok(create_json_error_response_with_details(
+                        &format!("Next-stage ticket does not satisfy template '{}'", name),
+                        json!({ "field_errors": field_errors }),
+                    ));
+                }
+                Some(priority.unwrap_or(template.default_priority))
+            }
+            None => priority,
+        };
+
+        info!(
+            "Transitioning pipeline from ticket {} to stage {}",
+            source_ticket_id, stage
+        );
+
+        let transition = match transition_pipeline(
+            &state.db,
+            PipelineTransitionRequest {
+                source_ticket_id: source_ticket_id.clone(),
+                resolution: resolution.clone(),
+                next_ticket_id,
+                stage: stage.clone(),
+                title,
+                description,
+                priority,
+                custom_fields,
+            },
+        )
+        .await
+        {
+            Ok(t) => t,
+            Err(e) => {
+                return Ok(create_json_error_response(&format!(
+                    "Pipeline transition failed, no changes were made: {}",
+                    e
+                )))
+            }
+        };
+        let next = &transition.next;
+        // The transition queued the events of both tickets
+        state.event_broadcaster.notify_outbox();
+
+        if let Err(e) = state
+            .queue_manager
+            .check_and_unblock_dependents(&source_ticket_id)
+            .await
+        {
+            warn!(
+                "Failed to unblock dependents of ticket {}: {}",
+                source_ticket_id, e
+            );
+        }
+        if let Err(e) = state
+            .queue_manager
+            .submit_task(&next.project_id, &stage, &next.ticket_id)
+            .await
+        {
+            warn!(
+                "Failed to submit ticket {} to {}-queue: {}",
+                next.ticket_id, stage, e
+            );
+        }
+
+        Ok(create_json_success_response(json!({
+            "message": format!(
+                "Closed ticket {} and {} ticket {} at stage '{}'",
+                source_ticket_id,
+                if transition.reopened { "reopened" } else { "created" },
+                next.ticket_id,
+                stage
+            ),
+            "source_ticket_id": source_ticket_id,
+            "next_ticket_id": next.ticket_id,
+            "reopened": transition.reopened,
+            "relation": transition.relation,
+            "capacity_warning": capacity_warning
+        })))
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "transition_pipeline".to_string(),
+            description: "Atomically close a ticket and create or reopen the ticket for the next pipeline stage, linked with a 'follows' relation. Either both changes happen or neither does".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "source_ticket_id": {
+                        "type": "string",
+                        "description": "Ticket to close"
+                    },
+                    "resolution": {
+                        "type": "string",
+                        "description": "Resolution for the closed ticket",
+                        "default": "Completed"
+                    },
+                    "next_ticket_id": {
+                        "type": "string",
+                        "description": "Existing ticket in the same project to reopen; a new ticket is created when omitted"
+                    },
+                    "stage": {
+                        "type": "string",
+                        "description": "Worker type to assign the next ticket to; defaults to the stage after the source ticket's current stage in its execution plan"
+                    },
+                    "title": {
+                        "type": "string",
+                        "description": "Title of a newly created ticket (defaults to the source title)"
+                    },
+                    "description": {
+                        "type": "string",
+                        "description": "Description of a newly created ticket"
+                    },
+                    "priority": {
+                        "type": "string",
+                        "description": "Priority of the next ticket (defaults to the template's, then the source ticket's)"
+                    },
+                    "template_name": {
+                        "type": "string",
+                        "description": "Optional ticket template the next ticket must satisfy"
+                    },
+                    "custom_fields": {
+                        "type": "object",
+                        "description": "Custom fields merged over those copied from the source ticket"
+                    }
+                },
+                "required": ["source_ticket_id"]
+            }),
+        }
+    }
+}
+
 pub struct ResumeTicketProcessingTool;
 
 #[async_trait]
@@ -600,6 +1531,34 @@ impl ToolHandler for ResumeTicketProcessingTool {
         };
         let target_state = target_state_enum.to_string();
 
+        // Only a ticket the stage is not already counting adds to its load
+        let mut capacity_warning = None;
+        if matches!(target_state_enum, TicketState::Open)
+            && (target_stage != ticket_data.current_stage || ticket_data.state != target_state)
+        {
+            match capacity_gate(state, &ticket_data.project_id, &target_stage).await {
+                Ok(warning) => capacity_warning = warning,
+                Err(refusal) => return Ok(refusal),
+            }
+        }
+
+        // Resuming a blocked ticket lifts its block
+        if matches!(target_state_enum, TicketState::Open) {
+            if let Some(lifted) = crate::database::blocks::TicketBlock::unblock(
+                &state.db,
+                &ticket_id,
+                "coordinator",
+                Some("resumed with resume_ticket_processing"),
+            )
+            .await?
+            {
+                info!(
+                    "Lifted block on ticket {} after {} minutes",
+                    ticket_id, lifted.blocked_minutes
+                );
+            }
+        }
+
         // Update ticket stage if different
         if target_stage != ticket_data.current_stage {
             info!(
@@ -668,7 +1627,8 @@ impl ToolHandler for ResumeTicketProcessingTool {
                         "ticket_id": ticket_id,
                         "target_stage": target_stage,
                         "target_state": target_state,
-                        "task_id": task_id
+                        "task_id": task_id,
+                        "capacity_warning": capacity_warning
                     })))
                 }
                 Err(e) => {
@@ -682,7 +1642,8 @@ impl ToolHandler for ResumeTicketProcessingTool {
                         "ticket_id": ticket_id,
                         "target_stage": target_stage,
                         "target_state": target_state,
-                        "queue_error": e.to_string()
+                        "queue_error": e.to_string(),
+                        "capacity_warning": capacity_warning
                     })))
                 }
             }
@@ -1,18 +1,77 @@
 use async_trait::async_trait;
+use serde::Serialize;
 use serde_json::Value;
 use std::collections::HashMap;
 
-use super::types::{CallToolRequest, CallToolResponse, Tool, ToolContent};
+use super::{
+    access::ToolCaller,
+    types::{CallToolRequest, CallToolResponse, Tool, ToolContent},
+};
 use crate::{error::Result, server::AppState};
 
 #[async_trait]
 pub trait ToolHandler: Send + Sync {
     async fn call(&self, state: &AppState, arguments: Option<Value>) -> Result<CallToolResponse>;
+
+    /// Call on behalf of `caller`. Tools that act as the caller, rather than taking an
+    /// agent from their arguments, override this; the rest ignore who is calling.
+    async fn call_as(
+        &self,
+        state: &AppState,
+        _caller: &ToolCaller,
+        arguments: Option<Value>,
+    ) -> Result<CallToolResponse> {
+        self.call(state, arguments).await
+    }
+
     fn definition(&self) -> Tool;
 }
 
 pub struct ToolRegistry {
-    tools: HashMap<String, Box<dyn ToolHandler>>,
+    tools: HashMap<String, RegisteredTool>,
+}
+
+/// A tool handler together with its advertised definition and the compiled
+/// validator for that definition's input schema
+struct RegisteredTool {
+    handler: Box<dyn ToolHandler>,
+    definition: Tool,
+    validator: jsonschema::Validator,
+}
+
+/// One way in which tool arguments fail the tool's input schema
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaViolation {
+    /// JSON pointer to the offending value; empty for the arguments object itself
+    pub instance_path: String,
+    /// Schema keyword that failed, e.g. `type` or `required`
+    pub keyword: String,
+    /// Expected JSON type(s), for type mismatches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    pub message: String,
+}
+
+impl From<jsonschema::ValidationError<'_>> for SchemaViolation {
+    fn from(error: jsonschema::ValidationError<'_>) -> Self {
+        let expected = match error.kind() {
+            jsonschema::error::ValidationErrorKind::Type { kind } => Some(match kind {
+                jsonschema::error::TypeKind::Single(t) => t.to_string(),
+                jsonschema::error::TypeKind::Multiple(set) => set
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" or "),
+            }),
+            _ => None,
+        };
+        Self {
+            instance_path: error.instance_path().to_string(),
+            keyword: error.kind().keyword().to_string(),
+            expected,
+            message: error.to_string(),
+        }
+    }
 }
 
 impl Default for ToolRegistry {
@@ -28,26 +87,77 @@ impl ToolRegistry {
         }
     }
 
+    /// Register a tool; panics if its input schema is not a valid JSON Schema,
+    /// which is a programming error in the tool definition
     pub fn register<T: ToolHandler + 'static>(&mut self, tool: T) {
-        let name = tool.definition().name.clone();
-        self.tools.insert(name, Box::new(tool));
+        let definition = tool.definition();
+        let validator = jsonschema::validator_for(&definition.input_schema).unwrap_or_else(|e| {
+            panic!(
+                "Tool '{}' has an invalid input schema: {}",
+                definition.name, e
+            )
+        });
+        self.tools.insert(
+            definition.name.clone(),
+            RegisteredTool {
+                handler: Box::new(tool),
+                definition,
+                validator,
+            },
+        );
     }
 
     pub fn get_tool(&self, name: &str) -> Option<&dyn ToolHandler> {
-        self.tools.get(name).map(|boxed| boxed.as_ref())
+        self.tools.get(name).map(|tool| tool.handler.as_ref())
     }
 
+    /// Tool definitions ordered by name, so the advertised list is the same on every run
     pub fn list_tools(&self) -> Vec<Tool> {
-        self.tools.values().map(|tool| tool.definition()).collect()
+        let mut tools: Vec<Tool> = self
+            .tools
+            .values()
+            .map(|tool| tool.definition.clone())
+            .collect();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        tools
+    }
+
+    /// Check arguments against the tool's advertised input schema. Missing arguments
+    /// are treated as an empty object; unknown tools are left to `call_tool`.
+    pub fn validate_arguments(
+        &self,
+        name: &str,
+        arguments: Option<&Value>,
+    ) -> std::result::Result<(), Vec<SchemaViolation>> {
+        let Some(tool) = self.tools.get(name) else {
+            return Ok(());
+        };
+        let empty = Value::Object(Default::default());
+        let arguments = match arguments {
+            None | Some(Value::Null) => &empty,
+            Some(arguments) => arguments,
+        };
+
+        let violations: Vec<SchemaViolation> = tool
+            .validator
+            .iter_errors(arguments)
+            .map(SchemaViolation::from)
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
     }
 
     pub async fn call_tool(
         &self,
         state: &AppState,
+        caller: &ToolCaller,
         request: CallToolRequest,
     ) -> Result<CallToolResponse> {
         match self.get_tool(&request.name) {
-            Some(tool) => tool.call(state, request.arguments).await,
+            Some(tool) => tool.call_as(state, caller, request.arguments).await,
             None => Ok(CallToolResponse {
                 content: vec![ToolContent {
                     content_type: "text".to_string(),
@@ -105,6 +215,54 @@ pub fn create_json_error_response(error: &str) -> CallToolResponse {
     }
 }
 
+/// Create error response with JSON content and structured details
+pub fn create_json_error_response_with_details(error: &str, details: Value) -> CallToolResponse {
+    let error_data = serde_json::json!({
+        "error": error,
+        "details": details
+    });
+    CallToolResponse {
+        content: vec![ToolContent {
+            content_type: "text".to_string(),
+            text: serde_json::to_string_pretty(&error_data)
+                .unwrap_or_else(|_| r#"{"error": "Unknown error"}"#.to_string()),
+        }],
+        is_error: Some(true),
+    }
+}
+
+/// Tools that take `dry_run`: with it set they return what they would change under
+/// `would_do` and change nothing
+pub const DRY_RUN_TOOLS: &[&str] = &["close_ticket", "delete_project", "delete_worker_type"];
+
+/// Schema of the `dry_run` argument of [`DRY_RUN_TOOLS`]
+pub fn dry_run_property() -> Value {
+    serde_json::json!({
+        "type": "boolean",
+        "description": "Return what would change under would_do without changing anything",
+        "default": false
+    })
+}
+
+/// Whether a call to `tool` only previews its changes
+pub fn is_dry_run(tool: &str, arguments: &Option<Value>) -> bool {
+    DRY_RUN_TOOLS.contains(&tool)
+        && arguments
+            .as_ref()
+            .and_then(|args| args.get("dry_run"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+}
+
+/// Response of a dry run: the plan the call would have applied
+pub fn create_dry_run_response(message: &str, would_do: impl serde::Serialize) -> CallToolResponse {
+    create_json_success_response(serde_json::json!({
+        "message": message,
+        "dry_run": true,
+        "would_do": would_do
+    }))
+}
+
 // Utility function to extract and validate parameters
 pub fn extract_param<T>(arguments: &Option<Value>, key: &str) -> Result<T>
 where
@@ -146,3 +304,108 @@ where
         _ => Ok(None),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::server::McpServer;
+    use serde_json::json;
+
+    #[test]
+    fn test_malformed_arguments_report_violations() {
+        // Registering every tool also compiles every advertised schema
+        let tools = McpServer::default().tools;
+
+        let violations = tools
+            .validate_arguments(
+                "create_ticket",
+                Some(&json!({ "project_id": 5, "execution_plan": ["design", 7] })),
+            )
+            .unwrap_err();
+        assert!(violations.contains(&SchemaViolation {
+            instance_path: "/project_id".to_string(),
+            keyword: "type".to_string(),
+            expected: Some("string".to_string()),
+            message: "5 is not of type \"string\"".to_string(),
+        }));
+        assert!(violations
+            .iter()
+            .any(|v| v.instance_path == "/execution_plan/1" && v.keyword == "type"));
+        assert!(violations
+            .iter()
+            .any(|v| v.instance_path.is_empty() && v.keyword == "required"));
+
+        let violations = tools.validate_arguments("get_ticket", None).unwrap_err();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].keyword, "required");
+
+        let violations = tools
+            .validate_arguments("create_ticket", Some(&json!("not an object")))
+            .unwrap_err();
+        assert_eq!(violations[0].expected.as_deref(), Some("object"));
+
+        assert!(tools
+            .validate_arguments("get_ticket", Some(&json!({ "ticket_id": "T-1" })))
+            .is_ok());
+        assert!(tools.validate_arguments("no_such_tool", None).is_ok());
+    }
+
+    #[test]
+    fn test_dry_run_tools_declare_dry_run() {
+        let tools = McpServer::default().tools;
+        for name in DRY_RUN_TOOLS {
+            let tool = tools.get_tool(name).unwrap().definition();
+            assert_eq!(
+                tool.input_schema["properties"]["dry_run"]["type"], "boolean",
+                "{} does not declare dry_run",
+                name
+            );
+        }
+        assert!(is_dry_run(
+            "delete_project",
+            &Some(json!({ "repository_name": "a", "dry_run": true }))
+        ));
+        assert!(!is_dry_run(
+            "delete_project",
+            &Some(json!({ "repository_name": "a", "dry_run": false }))
+        ));
+        assert!(!is_dry_run(
+            "create_ticket",
+            &Some(json!({ "dry_run": true }))
+        ));
+    }
+
+    /// Advertised input schemas, checked against `snapshots/tool_list.json` so a schema
+    /// change shows up in review. Regenerate with `UPDATE_SNAPSHOTS=1 cargo test`.
+    #[test]
+    fn test_tool_list_matches_snapshot() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/mcp/snapshots/tool_list.json"
+        );
+        let tools: Vec<Value> = McpServer::tool_registry()
+            .list_tools()
+            .into_iter()
+            .map(|tool| json!({ "name": tool.name, "inputSchema": tool.input_schema }))
+            .collect();
+        let generated = serde_json::to_string_pretty(&tools).unwrap() + "\n";
+
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(path, &generated).unwrap();
+        }
+        let snapshot = std::fs::read_to_string(path).unwrap_or_default();
+        assert!(
+            generated == snapshot,
+            "Tool schemas differ from {}; if the change is intended, rerun with UPDATE_SNAPSHOTS=1",
+            path
+        );
+    }
+
+    #[test]
+    fn test_tool_names_follow_registry() {
+        let names = crate::mcp::constants::get_all_mcp_tool_names();
+        assert_eq!(names.len(), McpServer::tool_registry().list_tools().len());
+        assert!(names.contains(&"mcp__vibe-ensemble-mcp__search_tickets".to_string()));
+        assert!(names.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
@@ -0,0 +1,105 @@
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use super::types::{JsonRpcRequest, JsonRpcResponse};
+
+/// Longest client-supplied trace id that is honored
+const MAX_TRACE_ID_LEN: usize = 128;
+
+/// Trace id for a request: the client's `params._meta.traceId` when it is usable,
+/// otherwise a fresh one
+pub fn trace_id_for(request: &JsonRpcRequest) -> String {
+    request
+        .params
+        .as_ref()
+        .and_then(|params| params.get("_meta"))
+        .and_then(|meta| meta.get("traceId"))
+        .and_then(Value::as_str)
+        .filter(|id| is_valid_trace_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().simple().to_string())
+}
+
+/// Client trace ids end up in log lines, so only short printable tokens are accepted
+fn is_valid_trace_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_TRACE_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+/// Echo the trace id in the result's `_meta` or in the error's `data`
+pub fn attach_trace_id(response: &mut JsonRpcResponse, trace_id: &str) {
+    if let Some(Value::Object(result)) = response.result.as_mut() {
+        let meta = result
+            .entry("_meta")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(meta) = meta {
+            meta.insert("traceId".to_string(), json!(trace_id));
+        }
+    }
+
+    if let Some(error) = response.error.as_mut() {
+        error.data = Some(match error.data.take() {
+            Some(Value::Object(mut data)) => {
+                data.insert("traceId".to_string(), json!(trace_id));
+                Value::Object(data)
+            }
+            Some(details) => json!({ "details": details, "traceId": trace_id }),
+            None => json!({ "traceId": trace_id }),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::types::JsonRpcError;
+
+    fn request(params: Option<Value>) -> JsonRpcRequest {
+        JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(1)),
+            method: "tools/call".to_string(),
+            params,
+        }
+    }
+
+    #[test]
+    fn test_client_trace_id_honored_when_valid() {
+        let honored = request(Some(json!({ "_meta": { "traceId": "abc-123" } })));
+        assert_eq!(trace_id_for(&honored), "abc-123");
+
+        let injected = request(Some(json!({ "_meta": { "traceId": "a b\nfake log" } })));
+        assert_ne!(trace_id_for(&injected), "a b\nfake log");
+        assert_eq!(trace_id_for(&request(None)).len(), 32);
+    }
+
+    #[test]
+    fn test_trace_id_echoed_in_result_and_error() {
+        let mut ok = JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(1)),
+            result: Some(json!({ "content": [], "_meta": { "page": 2 } })),
+            error: None,
+        };
+        attach_trace_id(&mut ok, "t1");
+        assert_eq!(ok.result.as_ref().unwrap()["_meta"]["traceId"], "t1");
+        assert_eq!(ok.result.as_ref().unwrap()["_meta"]["page"], 2);
+
+        let mut failed = JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(1)),
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: "bad".to_string(),
+                data: Some(json!("raw")),
+            }),
+        };
+        attach_trace_id(&mut failed, "t2");
+        let data = failed.error.unwrap().data.unwrap();
+        assert_eq!(data, json!({ "details": "raw", "traceId": "t2" }));
+    }
+}
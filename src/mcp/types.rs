@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use utoipa::ToSchema;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JsonRpcRequest {
@@ -31,12 +32,13 @@ pub struct JsonRpcError {
 pub struct InitializeRequest {
     #[serde(rename = "protocolVersion", alias = "protocol_version")]
     pub protocol_version: String,
+    #[serde(default)]
     pub capabilities: ClientCapabilities,
     #[serde(rename = "clientInfo", alias = "client_info")]
     pub client_info: ClientInfo,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ClientCapabilities {
     #[serde(default)]
     pub tools: ToolsCapability,
@@ -54,7 +56,7 @@ pub struct ToolsCapability {
     pub list_changed: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ClientInfo {
     pub name: String,
     pub version: String,
@@ -67,13 +69,17 @@ pub struct InitializeResponse {
     pub capabilities: ServerCapabilities,
     #[serde(rename = "serverInfo")]
     pub server_info: ServerInfo,
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ServerCapabilities {
-    pub tools: ToolsCapability,
-    pub prompts: PromptsCapability,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<ToolsCapability>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompts: Option<PromptsCapability>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub resources: Option<ResourcesCapability>,
 }
 
@@ -174,13 +180,47 @@ pub struct PromptContent {
     pub text: String,
 }
 
-// MCP Error Codes
+// Error codes, grouped in the ranges listed by `errors::ERROR_CODE_RANGES`.
+// Codes never move once published; new ones take the next free slot in their range.
+
+// JSON-RPC protocol errors (-32768..=-32600)
 pub const PARSE_ERROR: i32 = -32700;
 pub const INVALID_REQUEST: i32 = -32600;
 pub const METHOD_NOT_FOUND: i32 = -32601;
 pub const INVALID_PARAMS: i32 = -32602;
 pub const INTERNAL_ERROR: i32 = -32603;
 
+// Session and access errors, and lookups outside any domain (-32009..=-32000)
+pub const PERMISSION_DENIED: i32 = -32001;
+pub const RESOURCE_NOT_FOUND: i32 = -32002;
+pub const RESOURCE_CONFLICT: i32 = -32003;
+pub const SERVER_NOT_INITIALIZED: i32 = -32006;
+
+// Transient errors; the same request may succeed later (-32019..=-32010)
+pub const STORAGE_UNAVAILABLE: i32 = -32010;
+pub const TIMEOUT: i32 = -32011;
+
+// Ticket (issue) errors (-32029..=-32020)
+pub const TICKET_NOT_FOUND: i32 = -32020;
+pub const TICKET_CONFLICT: i32 = -32021;
+
+// Agent errors: worker types and their permissions (-32039..=-32030)
+pub const AGENT_NOT_FOUND: i32 = -32030;
+pub const AGENT_CONFLICT: i32 = -32031;
+
+// Coordination errors: projects, events, schedules and agent requests (-32049..=-32040)
+pub const COORDINATION_NOT_FOUND: i32 = -32040;
+pub const COORDINATION_CONFLICT: i32 = -32041;
+pub const QUOTA_EXCEEDED: i32 = -32042;
+
+// Worker errors: the spawn queue and running workers (-32059..=-32050)
+pub const WORKER_NOT_FOUND: i32 = -32050;
+pub const WORKER_CONFLICT: i32 = -32051;
+
+// Knowledge errors: prompts and worker templates (-32069..=-32060)
+pub const KNOWLEDGE_NOT_FOUND: i32 = -32060;
+pub const KNOWLEDGE_CONFLICT: i32 = -32061;
+
 // Pagination types and utilities
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PaginationParams {
@@ -1,21 +1,41 @@
-use axum::extract::ws::{Message, WebSocket};
+use axum::extract::ws::{CloseFrame, Message, WebSocket};
 use axum::extract::{Query, State, WebSocketUpgrade};
-use axum::http::HeaderMap;
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 use dashmap::DashMap;
 use futures::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tokio::sync::{mpsc, Semaphore};
 use tracing::{error, info, trace, warn};
 use uuid::Uuid;
 
-use super::types::JsonRpcRequest;
-use crate::{error::AppError, server::AppState, sse::EventBroadcaster};
+use super::{
+    capabilities,
+    errors::{ErrorDomain, ErrorKind},
+    limits::{
+        record_violation, ConnectionLimits, Enqueued, NotificationQueue, Violation,
+        CLOSE_MESSAGE_TOO_BIG, CLOSE_POLICY_VIOLATION,
+    },
+    server::negotiated_version,
+    session::{declared_meta, CloseClass},
+    subscriptions::{Subscription, SubscriptionRegistry},
+    types::{JsonRpcError, JsonRpcRequest, JsonRpcResponse, INVALID_PARAMS},
+};
+use crate::{audit::AuditLogger, error::AppError, server::AppState, sse::EventBroadcaster};
 
 type Result<T> = std::result::Result<T, AppError>;
 
+/// How long a disconnected client's subscriptions and tools wait for it to resume
+pub const RESUME_GRACE: Duration = Duration::from_secs(60);
+
+/// Notification telling a client its id and the token to resume it with
+const SESSION_NOTIFICATION: &str = "notifications/vibe/session";
+
 /// WebSocket connection manager
 pub struct WebSocketManager {
     /// Active client connections
@@ -28,17 +48,47 @@ pub struct WebSocketManager {
     concurrency_semaphore: Option<Arc<Semaphore>>,
     /// Event broadcaster subscription (optional for independent operation)
     event_broadcaster: Option<EventBroadcaster>,
+    /// Per-client event subscriptions consulted before pushing events
+    subscriptions: Arc<SubscriptionRegistry>,
+    /// Inbound message size and outbound notification queue limits
+    limits: ConnectionLimits,
+    /// Audit log for limit violations (optional for independent operation)
+    audit_logger: Option<AuditLogger>,
+    /// Disconnected clients that may still resume, by resume token
+    detached: Arc<DashMap<String, DetachedClient>>,
 }
 
 /// Individual client connection
 #[derive(Debug, Clone)]
 pub struct ClientConnection {
     pub client_id: String,
+    /// Identifies this socket; a resumed client keeps its client id but not this
+    pub connection_id: String,
+    /// Secret that lets the client take this client id over from a new connection
+    pub resume_token: String,
     pub sender: mpsc::UnboundedSender<Message>,
+    /// Event notifications, bounded separately from request/response traffic
+    pub notifications: Arc<NotificationQueue>,
     pub capabilities: ClientCapabilities,
     pub connected_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Client that disconnected within the last `RESUME_GRACE`
+#[derive(Debug, Clone)]
+struct DetachedClient {
+    client_id: String,
+    connection_id: String,
+    detached_at: Instant,
+}
+
+/// Client id a new connection runs under
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Identity {
+    client_id: String,
+    resume_token: String,
+    resumed: bool,
+}
+
 /// Client capabilities negotiated during handshake
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClientCapabilities {
@@ -90,6 +140,8 @@ pub struct PendingRequest {
 #[derive(Debug, Deserialize)]
 pub struct WebSocketQuery {
     token: Option<String>,
+    /// Resume token from an earlier connection's session notification
+    resume: Option<String>,
 }
 
 impl Default for WebSocketManager {
@@ -106,6 +158,10 @@ impl WebSocketManager {
             pending_requests: Arc::new(DashMap::new()),
             concurrency_semaphore: None,
             event_broadcaster: None,
+            subscriptions: Arc::new(SubscriptionRegistry::new()),
+            limits: ConnectionLimits::default(),
+            audit_logger: None,
+            detached: Arc::new(DashMap::new()),
         }
     }
 
@@ -117,6 +173,10 @@ impl WebSocketManager {
             pending_requests: Arc::new(DashMap::new()),
             concurrency_semaphore: Some(Arc::new(Semaphore::new(max_concurrent))),
             event_broadcaster: None,
+            subscriptions: Arc::new(SubscriptionRegistry::new()),
+            limits: ConnectionLimits::default(),
+            audit_logger: None,
+            detached: Arc::new(DashMap::new()),
         }
     }
 
@@ -124,6 +184,8 @@ impl WebSocketManager {
     pub fn with_event_broadcasting(
         max_concurrent: usize,
         event_broadcaster: EventBroadcaster,
+        limits: ConnectionLimits,
+        audit_logger: AuditLogger,
     ) -> Self {
         let manager = Self {
             clients: Arc::new(DashMap::new()),
@@ -131,6 +193,10 @@ impl WebSocketManager {
             pending_requests: Arc::new(DashMap::new()),
             concurrency_semaphore: Some(Arc::new(Semaphore::new(max_concurrent))),
             event_broadcaster: Some(event_broadcaster.clone()),
+            subscriptions: Arc::new(SubscriptionRegistry::new()),
+            limits,
+            audit_logger: Some(audit_logger),
+            detached: Arc::new(DashMap::new()),
         };
 
         // Start event broadcasting task
@@ -179,9 +245,26 @@ impl WebSocketManager {
             return error.into_response();
         }
 
+        // A resume token that matches no session is refused rather than silently
+        // starting a new session under a different client id
+        if let Some(token) = &query.0.resume {
+            if !self.is_resumable(token) {
+                warn!("WebSocket connection rejected: unknown or expired resume token");
+                return (
+                    StatusCode::CONFLICT,
+                    "Unknown or expired resume token; connect without one to start a new session",
+                )
+                    .into_response();
+            }
+        }
+
         let manager = self.clone();
 
+        // Frames whose header already announces more than the limit are rejected before
+        // their payload is read
         ws_upgrade
+            .max_message_size(self.limits.max_message_bytes)
+            .max_frame_size(self.limits.max_message_bytes)
             .protocols(["mcp"]) // Explicitly accept only the "mcp" subprotocol
             .on_upgrade(move |socket| manager.handle_socket(socket, headers, query.0, state.0))
     }
@@ -194,8 +277,13 @@ impl WebSocketManager {
         query: WebSocketQuery,
         state: AppState,
     ) {
-        let client_id = Uuid::new_v4().to_string();
-        info!("New WebSocket connection attempt: client_id={}", client_id);
+        let identity = self.identify(query.resume.as_deref());
+        let client_id = identity.client_id.clone();
+        let connection_id = Uuid::new_v4().to_string();
+        info!(
+            "New WebSocket connection attempt: client_id={}, resumed={}",
+            client_id, identity.resumed
+        );
         trace!("Socket split starting for client: {}", client_id);
 
         // Authenticate connection
@@ -215,6 +303,10 @@ impl WebSocketManager {
 
         let (mut sender, mut receiver) = socket.split();
         let (tx, mut rx) = mpsc::unbounded_channel();
+        let notifications = Arc::new(NotificationQueue::new(
+            self.limits.max_queued_notifications,
+            self.limits.overflow_policy,
+        ));
         trace!(
             "WebSocket streams and channels created for client: {}",
             client_id
@@ -222,6 +314,8 @@ impl WebSocketManager {
 
         // Spawn task to handle outgoing messages
         let client_id_clone = client_id.clone();
+        let outgoing_notifications = Arc::clone(&notifications);
+        let mcp_server = Arc::clone(&state.mcp_server);
         trace!(
             "Spawning outgoing message handler for client: {}",
             client_id
@@ -231,8 +325,22 @@ impl WebSocketManager {
                 "Outgoing message handler started for client: {}",
                 client_id_clone
             );
-            while let Some(msg) = rx.recv().await {
+            loop {
+                // Responses go first so notifications cannot starve request handling
+                let msg = tokio::select! {
+                    biased;
+                    msg = rx.recv() => match msg {
+                        Some(msg) => msg,
+                        None => break,
+                    },
+                    msg = outgoing_notifications.pop() => msg,
+                };
                 trace!("Sending message to client {}: {:?}", client_id_clone, msg);
+                if let Message::Text(text) = &msg {
+                    mcp_server
+                        .sessions
+                        .record_out(&client_id_clone, 1, text.len());
+                }
                 if sender.send(msg).await.is_err() {
                     warn!(
                         "Failed to send message to client {}, connection broken",
@@ -260,7 +368,10 @@ impl WebSocketManager {
         // Register client connection
         let connection = ClientConnection {
             client_id: client_id.clone(),
+            connection_id: connection_id.clone(),
+            resume_token: identity.resume_token.clone(),
             sender: tx.clone(),
+            notifications,
             capabilities: capabilities.clone(),
             connected_at: chrono::Utc::now(),
         };
@@ -274,8 +385,24 @@ impl WebSocketManager {
         );
         trace!("Client {} registered in client registry", client_id);
 
+        // A resumed client starts a new MCP handshake; its subscriptions and tools carry over
+        state.mcp_server.sessions.attach_websocket(&client_id);
+        let _ = tx.send(Message::Text(
+            json!({
+                "jsonrpc": "2.0",
+                "method": SESSION_NOTIFICATION,
+                "params": {
+                    "client_id": client_id,
+                    "resume_token": identity.resume_token,
+                    "status": if identity.resumed { "reconnected" } else { "connected" },
+                }
+            })
+            .to_string(),
+        ));
+
         // Handle incoming messages
         trace!("Starting message reception loop for client: {}", client_id);
+        let mut close_class = CloseClass::ClientClosed;
         while let Some(msg) = receiver.next().await {
             trace!(
                 "Received WebSocket message from client {}: {:?}",
@@ -288,6 +415,10 @@ impl WebSocketManager {
                         "Processing text message from client {}: (message logged in handle_message)",
                         client_id
                     );
+                    state
+                        .mcp_server
+                        .sessions
+                        .record_in(&client_id, 1, text.len());
                     if let Err(e) = self.handle_message(&client_id, &text, &state).await {
                         error!(
                             "Error handling message from client_id={}: error={}, full_message={}",
@@ -304,6 +435,7 @@ impl WebSocketManager {
                 }
                 Ok(Message::Ping(data)) => {
                     trace!("Received ping from client {}, sending pong", client_id);
+                    state.mcp_server.sessions.touch(&client_id);
                     if tx.send(Message::Pong(data.clone())).is_err() {
                         warn!("Failed to send pong to client {}", client_id);
                         break;
@@ -320,20 +452,161 @@ impl WebSocketManager {
                         data.len()
                     );
                 }
+                Err(e) if is_message_too_large(&e) => {
+                    record_violation(
+                        self.audit_logger.as_ref(),
+                        "websocket",
+                        Some(&client_id),
+                        Violation::MessageTooLarge,
+                    );
+                    let _ = tx.send(close_message(
+                        CLOSE_MESSAGE_TOO_BIG,
+                        format!(
+                            "Message exceeds the {} byte limit",
+                            self.limits.max_message_bytes
+                        ),
+                    ));
+                    close_class = CloseClass::MessageTooLarge;
+                    break;
+                }
                 Err(e) => {
                     error!("WebSocket error for client_id={}: error={}", client_id, e);
                     trace!("WebSocket error details: {:?}", e);
+                    close_class = CloseClass::TransportError;
                     break;
                 }
             }
         }
 
-        // Cleanup on disconnect
+        // Cleanup on disconnect, unless a resumed connection has taken the client over
         trace!("Starting cleanup for disconnected client: {}", client_id);
-        self.clients.remove(&client_id);
-        self.tool_registry.remove_client_tools(&client_id);
+        let detached = self.detach(&client_id, &connection_id);
+        // A connection the server dropped or replaced usually ends without a socket error
+        if !detached && close_class == CloseClass::ClientClosed {
+            close_class = CloseClass::ServerClosed;
+        }
+        state.mcp_server.sessions.record_closed(close_class);
+        if detached {
+            state.mcp_server.sessions.close(&client_id);
+            info!(
+                "Client {} disconnected; it may resume within {}s",
+                client_id,
+                RESUME_GRACE.as_secs()
+            );
+        } else {
+            info!(
+                "Connection of client {} was superseded or dropped",
+                client_id
+            );
+        }
+    }
+
+    /// Whether `resume_token` belongs to a connected client or one that may still resume
+    fn is_resumable(&self, resume_token: &str) -> bool {
+        self.detached
+            .get(resume_token)
+            .is_some_and(|detached| detached.detached_at.elapsed() < RESUME_GRACE)
+            || self
+                .clients
+                .iter()
+                .any(|client| client.resume_token == resume_token)
+    }
+
+    /// Client id for a new connection: the resumed client's, or a fresh one.
+    /// A client still registered under the token is a stale connection; it is closed
+    /// and the new connection takes its place.
+    fn identify(&self, resume_token: Option<&str>) -> Identity {
+        let resumed = resume_token.and_then(|token| {
+            if let Some((_, detached)) = self.detached.remove(token) {
+                if detached.detached_at.elapsed() < RESUME_GRACE {
+                    return Some((detached.client_id, token));
+                }
+                self.forget(&detached.client_id);
+                return None;
+            }
+            let client_id = self
+                .clients
+                .iter()
+                .find(|client| client.resume_token == token)
+                .map(|client| client.client_id.clone())?;
+            if let Some((_, stale)) = self.clients.remove(&client_id) {
+                let _ = stale.sender.send(close_message(
+                    1000,
+                    "Superseded by a resumed connection".to_string(),
+                ));
+            }
+            Some((client_id, token))
+        });
+
+        match resumed {
+            Some((client_id, token)) => Identity {
+                client_id,
+                resume_token: token.to_string(),
+                resumed: true,
+            },
+            None => Identity {
+                client_id: Uuid::new_v4().to_string(),
+                resume_token: Uuid::new_v4().to_string(),
+                resumed: false,
+            },
+        }
+    }
+
+    /// Remove a closed connection from the connected clients, keeping its subscriptions
+    /// and tools for `RESUME_GRACE`. Returns false if another connection has taken the
+    /// client over.
+    fn detach(&self, client_id: &str, connection_id: &str) -> bool {
+        let Some((_, connection)) = self
+            .clients
+            .remove_if(client_id, |_, client| client.connection_id == connection_id)
+        else {
+            return false;
+        };
+        self.detached.insert(
+            connection.resume_token.clone(),
+            DetachedClient {
+                client_id: client_id.to_string(),
+                connection_id: connection_id.to_string(),
+                detached_at: Instant::now(),
+            },
+        );
+
+        let manager = self.clone();
+        let resume_token = connection.resume_token;
+        let connection_id = connection_id.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(RESUME_GRACE).await;
+            manager.expire(&resume_token, &connection_id);
+        });
+        true
+    }
+
+    /// Drop a detached client that did not resume
+    fn expire(&self, resume_token: &str, connection_id: &str) {
+        if let Some((_, detached)) = self.detached.remove_if(resume_token, |_, detached| {
+            detached.connection_id == connection_id
+        }) {
+            self.forget(&detached.client_id);
+        }
+    }
+
+    fn forget(&self, client_id: &str) {
+        self.tool_registry.remove_client_tools(client_id);
+        self.subscriptions.remove_session(client_id);
         info!("Cleaned up client {}", client_id);
-        trace!("Client {} fully removed from all registries", client_id);
+    }
+
+    /// Close a client's connection for good: unlike a dropped socket, it cannot resume.
+    /// Returns false if the client is not connected.
+    pub fn disconnect(&self, client_id: &str, reason: &str) -> bool {
+        let Some((_, connection)) = self.clients.remove(client_id) else {
+            return false;
+        };
+        let _ = connection
+            .sender
+            .send(close_message(1000, reason.to_string()));
+        self.forget(client_id);
+        true
     }
 
     /// Validate MCP subprotocol as required by Claude Code IDE integration
@@ -549,6 +822,7 @@ impl WebSocketManager {
             "WebSocket message received from client_id={}, full_message={}",
             client_id, message
         );
+        state.mcp_server.sessions.touch(client_id);
 
         let request: JsonRpcRequest = match serde_json::from_str::<JsonRpcRequest>(message) {
             Ok(req) => {
@@ -574,6 +848,23 @@ impl WebSocketManager {
             client_id
         );
 
+        // Enforce the initialize handshake for standard MCP methods
+        let is_pending_response = request
+            .id
+            .as_ref()
+            .is_some_and(|id| self.pending_requests.contains_key(&id.to_string()));
+        if !is_pending_response
+            && !matches!(request.method.as_str(), "tools/register" | "getDiagnostics")
+        {
+            if let Some(rejection) = state.mcp_server.check_session(client_id, &request) {
+                if request.id.is_some() {
+                    let response_value = serde_json::to_value(&rejection)?;
+                    self.send_message(client_id, &response_value).await?;
+                }
+                return Ok(());
+            }
+        }
+
         match request.method.as_str() {
             // WebSocket-specific methods that need special handling
             "tools/register" => {
@@ -588,6 +879,11 @@ impl WebSocketManager {
                     if let Ok(init_request) =
                         serde_json::from_value::<super::types::InitializeRequest>(params.clone())
                     {
+                        state.mcp_server.sessions.set_client(
+                            client_id,
+                            Some(init_request.client_info),
+                            None,
+                        );
                         if let Some(mut client) = self.clients.get_mut(client_id) {
                             client.capabilities.mcp_capabilities = Some(init_request.capabilities);
                             trace!("Stored MCP capabilities for client_id={}", client_id);
@@ -595,7 +891,18 @@ impl WebSocketManager {
                     }
                 }
 
-                let response = state.mcp_server.handle_request(state, request).await;
+                let meta = declared_meta(request.params.as_ref());
+                let response = state
+                    .mcp_server
+                    .handle_request(state, request, Some(client_id))
+                    .await;
+                if response.error.is_none() {
+                    state.mcp_server.sessions.set_handshake(
+                        client_id,
+                        negotiated_version(&response),
+                        meta,
+                    );
+                }
                 let response_value = serde_json::to_value(&response)?;
                 self.send_message(client_id, &response_value).await
             }
@@ -611,6 +918,10 @@ impl WebSocketManager {
                 self.handle_get_diagnostics(client_id, &request, state)
                     .await
             }
+            "vibe/subscribe" | "vibe/unsubscribe" | "vibe/list_subscriptions" => {
+                trace!("Handling {} for client_id={}", request.method, client_id);
+                self.handle_subscription_request(client_id, &request).await
+            }
 
             // Check if this is a response to a server-initiated request
             _ if request.id.is_some() => {
@@ -633,7 +944,10 @@ impl WebSocketManager {
                     "Forwarding request to MCP server: method={}",
                     request.method
                 );
-                let response = state.mcp_server.handle_request(state, request).await;
+                let response = state
+                    .mcp_server
+                    .handle_request(state, request, Some(client_id))
+                    .await;
                 let response_value = serde_json::to_value(&response)?;
                 trace!(
                     "Sending MCP response to client_id={}: {:?}",
@@ -649,7 +963,10 @@ impl WebSocketManager {
                     "Forwarding request to MCP server: method={}",
                     request.method
                 );
-                let response = state.mcp_server.handle_request(state, request).await;
+                let response = state
+                    .mcp_server
+                    .handle_request(state, request, Some(client_id))
+                    .await;
                 let response_value = serde_json::to_value(&response)?;
                 trace!(
                     "Sending MCP response to client_id={}: {:?}",
@@ -716,7 +1033,7 @@ impl WebSocketManager {
             "jsonrpc": "2.0",
             "id": request.id,
             "error": {
-                "code": -32602,
+                "code": INVALID_PARAMS,
                 "message": "Invalid tool registration parameters"
             }
         });
@@ -728,6 +1045,64 @@ impl WebSocketManager {
         self.send_message(client_id, &error_response).await
     }
 
+    /// Handle vibe/subscribe, vibe/unsubscribe and vibe/list_subscriptions
+    async fn handle_subscription_request(
+        &self,
+        client_id: &str,
+        request: &JsonRpcRequest,
+    ) -> Result<()> {
+        let params = request.params.clone().unwrap_or(Value::Null);
+
+        let outcome = match request.method.as_str() {
+            "vibe/subscribe" => serde_json::from_value::<Subscription>(params)
+                .map_err(|e| {
+                    JsonRpcError::new(
+                        ErrorKind::ValidationFailed,
+                        format!("Invalid subscription: {}", e),
+                    )
+                })
+                .and_then(|subscription| self.subscriptions.subscribe(client_id, subscription))
+                .map(|subscription| {
+                    info!(
+                        "Client {} subscribed to {:?} events ({})",
+                        client_id, subscription.category, subscription.id
+                    );
+                    json!({ "subscription": subscription })
+                }),
+            "vibe/unsubscribe" => match params.get("subscription_id").and_then(|v| v.as_str()) {
+                Some(subscription_id) => {
+                    if self.subscriptions.unsubscribe(client_id, subscription_id) {
+                        Ok(json!({ "removed": true }))
+                    } else {
+                        Err(JsonRpcError::in_domain(
+                            Some(ErrorDomain::Coordination),
+                            ErrorKind::NotFound,
+                            format!("Subscription '{}' not found", subscription_id),
+                        ))
+                    }
+                }
+                None => Err(JsonRpcError::new(
+                    ErrorKind::ValidationFailed,
+                    "Missing required parameter 'subscription_id'",
+                )),
+            },
+            _ => Ok(json!({ "subscriptions": self.subscriptions.list(client_id) })),
+        };
+
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        let response = JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: request.id.clone(),
+            result,
+            error,
+        };
+        self.send_message(client_id, &serde_json::to_value(&response)?)
+            .await
+    }
+
     /// Handle initialized notification
     async fn handle_initialized(&self, client_id: &str) -> Result<()> {
         info!("Client {} completed initialization", client_id);
@@ -982,32 +1357,48 @@ impl WebSocketManager {
             .collect()
     }
 
-    /// Send MCP notifications for an event to a specific client
+    /// Notifications waiting to be sent to a connected client
+    pub fn queued_notifications(&self, client_id: &str) -> Option<usize> {
+        self.clients
+            .get(client_id)
+            .map(|client| client.notifications.len())
+    }
+
+    /// Queue a server notification for every connected client that declared support for
+    /// its method; returns how many clients it was queued for
+    pub fn broadcast_notification(&self, message: &Value) -> usize {
+        let method = message
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let text = message.to_string();
+        let mut queued = 0;
+        for client in self.clients.iter() {
+            let declared = client.capabilities.mcp_capabilities.as_ref();
+            if capabilities::permits(declared, method, client.key())
+                && self.enqueue_notification(client.key(), &client, Message::Text(text.clone()))
+            {
+                queued += 1;
+            }
+        }
+        queued
+    }
+
+    /// Send MCP notifications for an event to a specific client; returns false when the
+    /// client was disconnected
     async fn send_mcp_notifications(
         &self,
         client_id: &str,
         client: &ClientConnection,
         event_payload: &crate::events::EventPayload,
-    ) {
+    ) -> bool {
         use super::types::*;
 
-        // Check if client supports the necessary MCP capabilities
-
-        let has_logging = client
-            .capabilities
-            .mcp_capabilities
-            .as_ref()
-            .and_then(|caps| caps.logging.as_ref())
-            .map(|logging| logging.enabled)
-            .unwrap_or(false);
-
-        let has_resources = client
-            .capabilities
-            .mcp_capabilities
-            .as_ref()
-            .and_then(|caps| caps.resources.as_ref())
-            .map(|resources| resources.subscribe)
-            .unwrap_or(false);
+        // Only send what the client declared support for at initialize
+        let declared = client.capabilities.mcp_capabilities.as_ref();
+        let has_logging = capabilities::permits(declared, "notifications/message", client_id);
+        let has_resources =
+            capabilities::permits(declared, "notifications/resources/updated", client_id);
 
         // 1. Send notifications/message for user-friendly event description
         if has_logging {
@@ -1020,12 +1411,20 @@ impl WebSocketManager {
                 crate::events::EventType::TicketUpdated => "info",
                 crate::events::EventType::TicketStageChanged => "info",
                 crate::events::EventType::TicketUnblocked => "info",
+                crate::events::EventType::TicketSlaBreached => "warning",
+                crate::events::EventType::TicketFlaky => "warning",
+                crate::events::EventType::TicketBlockEscalated => "warning",
+                crate::events::EventType::EffectivePriorityChanged => "info",
                 crate::events::EventType::QueueUpdated => "info",
                 crate::events::EventType::WorkerStopped => "info",
+                crate::events::EventType::WorkerBoundaryViolation => "error",
+                crate::events::EventType::WorkerStalled => "warning",
                 crate::events::EventType::WorkerTypeCreated => "info",
                 crate::events::EventType::WorkerTypeUpdated => "info",
                 crate::events::EventType::WorkerTypeDeleted => "info",
                 crate::events::EventType::ProjectCreated => "info",
+                crate::events::EventType::ProjectSettingsUpdated => "info",
+                crate::events::EventType::ProjectQuotaWarning => "warning",
                 crate::events::EventType::StageCompleted => "info",
                 crate::events::EventType::TaskAssigned => "info",
                 crate::events::EventType::SystemInit => "info",
@@ -1034,6 +1433,9 @@ impl WebSocketManager {
                 crate::events::EventType::UpdateCheckStarted => "info",
                 crate::events::EventType::UpdateAvailable => "info",
                 crate::events::EventType::UpdateCheckFailed => "warning",
+                crate::events::EventType::CoordinatorLost => "warning",
+                crate::events::EventType::Digest => "info",
+                crate::events::EventType::RecoveryCompleted => "warning",
             };
 
             let user_friendly_data = self.format_user_friendly_event(event_payload);
@@ -1055,18 +1457,14 @@ impl WebSocketManager {
             );
 
             let message_text = notification_message.to_string();
-            if let Err(e) = client.sender.send(Message::Text(message_text.clone())) {
-                error!(
-                    "Failed to send notifications/message to client {}: {}",
-                    client_id, e
-                );
-            } else {
-                trace!(
-                    "Sent notifications/message to client {}: {}",
-                    client_id,
-                    message_text
-                );
+            if !self.enqueue_notification(client_id, client, Message::Text(message_text.clone())) {
+                return false;
             }
+            trace!(
+                "Queued notifications/message for client {}: {}",
+                client_id,
+                message_text
+            );
         }
 
         // 2. Send notifications/resources/updated with stable URI
@@ -1086,17 +1484,54 @@ impl WebSocketManager {
             );
 
             let message_text = resource_updated.to_string();
-            if let Err(e) = client.sender.send(Message::Text(message_text.clone())) {
-                error!(
-                    "Failed to send notifications/resources/updated to client {}: {}",
-                    client_id, e
+            if !self.enqueue_notification(client_id, client, Message::Text(message_text.clone())) {
+                return false;
+            }
+            trace!(
+                "Queued notifications/resources/updated for client {}: {}",
+                client_id,
+                message_text
+            );
+        }
+
+        true
+    }
+
+    /// Queue a notification for a client, applying the overflow policy; returns false
+    /// when the client was disconnected for falling behind
+    fn enqueue_notification(
+        &self,
+        client_id: &str,
+        client: &ClientConnection,
+        message: Message,
+    ) -> bool {
+        match client.notifications.push(message) {
+            Enqueued::Queued => true,
+            Enqueued::DroppedOldest { burst_start } => {
+                let audit_logger = self.audit_logger.as_ref().filter(|_| burst_start);
+                record_violation(
+                    audit_logger,
+                    "websocket",
+                    Some(client_id),
+                    Violation::NotificationDropped,
                 );
-            } else {
-                trace!(
-                    "Sent notifications/resources/updated to client {}: {}",
-                    client_id,
-                    message_text
+                true
+            }
+            Enqueued::Overflow => {
+                record_violation(
+                    self.audit_logger.as_ref(),
+                    "websocket",
+                    Some(client_id),
+                    Violation::NotificationOverflow,
                 );
+                let _ = client.sender.send(close_message(
+                    CLOSE_POLICY_VIOLATION,
+                    format!(
+                        "More than {} notifications queued; client is not keeping up",
+                        self.limits.max_queued_notifications
+                    ),
+                ));
+                false
             }
         }
     }
@@ -1171,6 +1606,81 @@ impl WebSocketManager {
                     "ticket_id": ticket_data.ticket_id
                 })
             }
+            (EventType::TicketSlaBreached, EventData::Ticket(ticket_data)) => {
+                serde_json::json!({
+                    "kind": "ticket_sla_breached",
+                    "priority": "high",
+                    "message": format!("Ticket #{} in project '{}' is past its due date", ticket_data.ticket_id, ticket_data.project_id),
+                    "project_id": ticket_data.project_id,
+                    "ticket_id": ticket_data.ticket_id,
+                    "state": ticket_data.state
+                })
+            }
+            (EventType::TicketFlaky, EventData::Ticket(ticket_data)) => {
+                serde_json::json!({
+                    "kind": "ticket_flaky",
+                    "priority": "high",
+                    "message": format!("Ticket #{} in project '{}' keeps being reopened and was tagged '{}'", ticket_data.ticket_id, ticket_data.project_id, crate::database::flaky::FLAKY_TAG),
+                    "project_id": ticket_data.project_id,
+                    "ticket_id": ticket_data.ticket_id
+                })
+            }
+            (EventType::TicketBlockEscalated, EventData::Ticket(ticket_data)) => {
+                serde_json::json!({
+                    "kind": "ticket_block_escalated",
+                    "priority": "high",
+                    "message": format!("Ticket #{} in project '{}' is still blocked past its expected unblock date after its responsible agent was reminded", ticket_data.ticket_id, ticket_data.project_id),
+                    "project_id": ticket_data.project_id,
+                    "ticket_id": ticket_data.ticket_id
+                })
+            }
+            (EventType::ProjectQuotaWarning, EventData::System(system_data)) => {
+                serde_json::json!({
+                    "kind": "project_quota_warning",
+                    "priority": "high",
+                    "message": system_data.message,
+                    "quota": system_data.metadata
+                })
+            }
+            (EventType::EffectivePriorityChanged, EventData::System(system_data)) => {
+                serde_json::json!({
+                    "kind": "effective_priority_changed",
+                    "priority": "medium",
+                    "message": system_data.message,
+                    "escalation": system_data.metadata
+                })
+            }
+            (EventType::WorkerStalled, EventData::System(system_data)) => {
+                serde_json::json!({
+                    "kind": "worker_stalled",
+                    "priority": "high",
+                    "message": system_data.message,
+                    "stall": system_data.metadata
+                })
+            }
+            (EventType::WorkerBoundaryViolation, EventData::System(system_data)) => {
+                serde_json::json!({
+                    "kind": "worker_boundary_violation",
+                    "priority": "high",
+                    "message": system_data.message,
+                    "violation": system_data.metadata
+                })
+            }
+            (EventType::RecoveryCompleted, EventData::System(system_data)) => {
+                serde_json::json!({
+                    "kind": "recovery_completed",
+                    "priority": "high",
+                    "message": system_data.message,
+                    "recovery": system_data.metadata
+                })
+            }
+            (EventType::Digest, EventData::System(system_data)) => {
+                serde_json::json!({
+                    "kind": "digest",
+                    "message": system_data.message,
+                    "digest": system_data.metadata
+                })
+            }
             (EventType::QueueUpdated, EventData::Queue(queue_data)) => {
                 serde_json::json!({
                     "kind": "queue_updated",
@@ -1230,14 +1740,26 @@ impl WebSocketManager {
                             let client_id = entry.key().clone();
                             let client = entry.value().clone();
 
+                            if !self.subscriptions.wants(&client_id, &event_payload) {
+                                trace!(
+                                    "Skipping event for client {}: no matching subscription",
+                                    client_id
+                                );
+                                continue;
+                            }
+
                             // Send MCP notifications to client
 
                             // Count as successful for MCP notifications
                             successful_deliveries += 1;
 
                             // Send MCP notifications for each event
-                            self.send_mcp_notifications(&client_id, &client, &event_payload)
-                                .await;
+                            if !self
+                                .send_mcp_notifications(&client_id, &client, &event_payload)
+                                .await
+                            {
+                                clients_to_remove.lock().unwrap().push(client_id);
+                            }
                         }
 
                         // Remove broken client connections
@@ -1277,6 +1799,10 @@ impl Clone for WebSocketManager {
             pending_requests: Arc::clone(&self.pending_requests),
             concurrency_semaphore: self.concurrency_semaphore.clone(),
             event_broadcaster: self.event_broadcaster.clone(),
+            subscriptions: Arc::clone(&self.subscriptions),
+            limits: self.limits,
+            audit_logger: self.audit_logger.clone(),
+            detached: Arc::clone(&self.detached),
         }
     }
 }
@@ -1322,3 +1848,180 @@ impl Default for ClientToolRegistry {
         Self::new()
     }
 }
+
+/// Whether a receive error is the transport rejecting a message over the size limit
+fn is_message_too_large(error: &axum::Error) -> bool {
+    std::error::Error::source(error)
+        .and_then(|source| source.downcast_ref::<tungstenite::Error>())
+        .is_some_and(|error| matches!(error, tungstenite::Error::Capacity(_)))
+}
+
+fn close_message(code: u16, reason: String) -> Message {
+    Message::Close(Some(CloseFrame {
+        code,
+        reason: reason.into(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::{limits::OverflowPolicy, subscriptions::EventCategory};
+
+    /// Register a connection the way handle_socket does
+    fn connect(
+        manager: &WebSocketManager,
+        resume: Option<&str>,
+    ) -> (Identity, String, mpsc::UnboundedReceiver<Message>) {
+        let identity = manager.identify(resume);
+        let connection_id = Uuid::new_v4().to_string();
+        let (tx, rx) = mpsc::unbounded_channel();
+        manager.clients.insert(
+            identity.client_id.clone(),
+            ClientConnection {
+                client_id: identity.client_id.clone(),
+                connection_id: connection_id.clone(),
+                resume_token: identity.resume_token.clone(),
+                sender: tx,
+                notifications: Arc::new(NotificationQueue::new(8, OverflowPolicy::DropOldest)),
+                capabilities: ClientCapabilities {
+                    bidirectional: true,
+                    tools: vec![],
+                    client_info: ClientInfo {
+                        name: "test".to_string(),
+                        version: "1.0.0".to_string(),
+                        environment: "test".to_string(),
+                    },
+                    mcp_capabilities: None,
+                },
+                connected_at: chrono::Utc::now(),
+            },
+        );
+        (identity, connection_id, rx)
+    }
+
+    #[tokio::test]
+    async fn test_connect_disconnect_resume() {
+        let manager = WebSocketManager::new();
+        let (first, connection_id, _rx) = connect(&manager, None);
+        assert!(!first.resumed);
+        manager
+            .subscriptions
+            .subscribe(
+                &first.client_id,
+                Subscription {
+                    id: String::new(),
+                    category: EventCategory::Tickets,
+                    project_id: None,
+                    recipient: None,
+                },
+            )
+            .unwrap();
+
+        // Disconnected clients leave the connected list but keep their subscriptions
+        assert!(manager.detach(&first.client_id, &connection_id));
+        assert!(manager.list_clients().is_empty());
+        assert!(manager.is_resumable(&first.resume_token));
+
+        let (resumed, _, _rx) = connect(&manager, Some(&first.resume_token));
+        assert!(resumed.resumed);
+        assert_eq!(
+            resumed,
+            Identity {
+                resumed: true,
+                ..first.clone()
+            }
+        );
+        assert_eq!(manager.list_clients(), [first.client_id.as_str()]);
+        assert_eq!(manager.subscriptions.list(&first.client_id).len(), 1);
+
+        // The grace period of the first disconnect no longer applies
+        manager.expire(&first.resume_token, &connection_id);
+        assert_eq!(manager.subscriptions.list(&first.client_id).len(), 1);
+
+        // Tokens nobody holds start a fresh client instead
+        assert!(!manager.is_resumable("someone-else"));
+        let (other, _, _rx) = connect(&manager, Some("someone-else"));
+        assert!(!other.resumed);
+        assert_ne!(other.client_id, first.client_id);
+    }
+
+    #[tokio::test]
+    async fn test_resume_supersedes_stale_connection() {
+        let manager = WebSocketManager::new();
+        let (first, stale_connection, mut stale_rx) = connect(&manager, None);
+
+        // The old socket never noticed it dropped; the client reconnects anyway
+        let (resumed, connection_id, _rx) = connect(&manager, Some(&first.resume_token));
+        assert_eq!(resumed.client_id, first.client_id);
+        assert_eq!(manager.list_clients().len(), 1);
+        assert!(matches!(stale_rx.try_recv(), Ok(Message::Close(_))));
+
+        // When the stale socket finally closes it must not take the new one down
+        assert!(!manager.detach(&first.client_id, &stale_connection));
+        assert_eq!(manager.list_clients(), [first.client_id.as_str()]);
+        assert!(manager.detach(&first.client_id, &connection_id));
+
+        // Expiry forgets the client and its token
+        manager.tool_registry.register_tool(ClientToolDefinition {
+            name: "open_file".to_string(),
+            description: String::new(),
+            input_schema: json!({}),
+            client_id: first.client_id.clone(),
+            registered_at: chrono::Utc::now(),
+        });
+        manager.expire(&first.resume_token, &connection_id);
+        assert!(manager.tool_registry.list_tools().is_empty());
+        assert!(!manager.is_resumable(&first.resume_token));
+    }
+
+    #[tokio::test]
+    async fn test_tool_policy_reload_notifies_connected_clients() {
+        let dir = std::env::temp_dir().join(format!("vibe-policy-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("policy.json");
+        std::fs::write(&path, r#"{"coordinator": ["list_*"]}"#).unwrap();
+        let server = crate::mcp::server::McpServer::new(&crate::config::Config {
+            tool_policy_path: Some(path.display().to_string()),
+            ..Default::default()
+        });
+        let visible = |name: &str| {
+            server
+                .visible_tools(&crate::mcp::access::ToolCaller::Coordinator)
+                .iter()
+                .any(|tool| tool.name == name)
+        };
+        assert!(!visible("create_ticket"));
+
+        // Both clients connect before the change; only one declared tools.listChanged
+        let manager = WebSocketManager::new();
+        let (watching, _, _rx) = connect(&manager, None);
+        let (other, _, _rx) = connect(&manager, None);
+        manager
+            .clients
+            .get_mut(&watching.client_id)
+            .unwrap()
+            .capabilities
+            .mcp_capabilities = Some(capabilities::declared(Some(
+            &json!({"capabilities": {"tools": {"listChanged": true}}}),
+        )));
+
+        assert!(!server.reload_access_policy().unwrap());
+        std::fs::write(&path, r#"{"coordinator": ["*"]}"#).unwrap();
+        assert!(server.reload_access_policy().unwrap());
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/tools/list_changed",
+        });
+        assert_eq!(manager.broadcast_notification(&notification), 1);
+        let queued = |client_id: &str| manager.clients.get(client_id).unwrap().notifications.len();
+        assert_eq!(queued(&watching.client_id), 1);
+        assert_eq!(queued(&other.client_id), 0);
+        assert!(visible("create_ticket"));
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(server.reload_access_policy().is_err());
+        assert!(visible("create_ticket"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -2,17 +2,70 @@ use async_trait::async_trait;
 use serde_json::{json, Value};
 use tracing::warn;
 
+use super::pagination::{extract_keyset, KeysetCursor};
 use super::tools::{
-    create_json_error_response, create_json_success_response, extract_optional_param,
-    extract_param, ToolHandler,
+    create_dry_run_response, create_json_error_response, create_json_success_response,
+    dry_run_property, extract_optional_param, extract_param, is_dry_run, ToolHandler,
 };
-use super::types::{CallToolResponse, PaginationCursor, Tool};
+use super::types::{CallToolResponse, Tool};
 use crate::{
-    database::worker_types::{CreateWorkerTypeRequest, UpdateWorkerTypeRequest, WorkerType},
+    database::{
+        worker_load::{WorkerTypeLoad, WorkerTypeStatus},
+        worker_metrics::WorkerTypeMetrics,
+        worker_types::{
+            encode_capabilities, CreateWorkerTypeRequest, UpdateWorkerTypeRequest, WorkerType,
+        },
+    },
     error::Result,
     server::AppState,
+    workers::sandbox::SpawnOverrides,
 };
 
+/// The `spawn_overrides` argument checked against the override fields and serialized for storage
+fn spawn_overrides_param(arguments: &Option<Value>) -> Result<Option<String>> {
+    let overrides: Option<Value> = extract_optional_param(arguments, "spawn_overrides")?;
+    overrides
+        .map(|value| {
+            serde_json::from_value::<SpawnOverrides>(value.clone()).map_err(|e| {
+                crate::error::AppError::BadRequest(format!("Invalid spawn_overrides: {}", e))
+            })?;
+            Ok(value.to_string())
+        })
+        .transpose()
+}
+
+/// The `capabilities` argument in canonical tag form, serialized for storage
+fn capabilities_param(arguments: &Option<Value>) -> Result<Option<String>> {
+    let capabilities: Option<Vec<String>> = extract_optional_param(arguments, "capabilities")?;
+    capabilities
+        .map(|names| {
+            encode_capabilities(&names).map_err(|e| {
+                crate::error::AppError::BadRequest(format!("Invalid capabilities: {}", e))
+            })
+        })
+        .transpose()
+}
+
+/// The `max_open_tickets` argument, checked
+fn max_open_tickets_param(arguments: &Option<Value>) -> Result<Option<i64>> {
+    let max_open_tickets: Option<i64> = extract_optional_param(arguments, "max_open_tickets")?;
+    if max_open_tickets.is_some_and(|max| max < 0) {
+        return Err(crate::error::AppError::BadRequest(
+            "max_open_tickets must be 0 (no limit) or more".to_string(),
+        ));
+    }
+    Ok(max_open_tickets)
+}
+
+/// Status changes shown with a worker type
+const STATUS_HISTORY_LIMIT: i64 = 10;
+
+const CAPABILITIES_SCHEMA_DESCRIPTION: &str = "Tags this worker type handles. In projects with auto_assign, new tickets start in the worker type whose capabilities best cover their tags";
+
+const MAX_OPEN_TICKETS_SCHEMA_DESCRIPTION: &str = "Open tickets at which the worker type turns busy, and back online when it has fewer; 0 for no limit. Tickets sent to a busy worker type are refused or carry a capacity_warning, depending on the server's configuration";
+
+const SPAWN_OVERRIDES_SCHEMA_DESCRIPTION: &str = "Worker spawn policy overrides: working_dir, env_allow (extra patterns), max_runtime_secs, nice. Only fields the server's worker policy marks overridable take effect; others are recorded as policy violations";
+
 pub struct CreateWorkerTypeTool;
 
 #[async_trait]
@@ -23,24 +76,39 @@ impl ToolHandler for CreateWorkerTypeTool {
         let system_prompt: String = extract_param(&arguments, "system_prompt")?;
         let short_description: Option<String> =
             extract_optional_param(&arguments, "short_description")?;
+        let spawn_overrides = spawn_overrides_param(&arguments)?;
+        let capabilities = capabilities_param(&arguments)?;
+        let max_open_tickets = max_open_tickets_param(&arguments)?;
 
         let request = CreateWorkerTypeRequest {
             project_id: project_id.clone(),
             worker_type: worker_type.clone(),
             short_description: short_description.clone(),
             system_prompt: system_prompt.clone(),
+            spawn_overrides,
+            capabilities,
         };
 
         match WorkerType::create(&state.db, request).await {
             Ok(worker_type_info) => {
+                let load = match max_open_tickets {
+                    Some(max) => {
+                        WorkerTypeLoad::set_capacity(&state.db, &project_id, &worker_type, max)
+                            .await?
+                    }
+                    None => WorkerTypeLoad::get(&state.db, &project_id, &worker_type).await?,
+                };
                 let response = json!({
                     "id": worker_type_info.id,
                     "project_id": worker_type_info.project_id,
                     "worker_type": worker_type_info.worker_type,
                     "short_description": worker_type_info.short_description,
                     "system_prompt": worker_type_info.system_prompt,
+                    "spawn_overrides": worker_type_info.spawn_overrides,
+                    "capabilities": worker_type_info.capabilities(),
                     "created_at": worker_type_info.created_at,
-                    "updated_at": worker_type_info.updated_at
+                    "updated_at": worker_type_info.updated_at,
+                    "load": load
                 });
 
                 // Emit worker_type_created event
@@ -87,11 +155,26 @@ impl ToolHandler for CreateWorkerTypeTool {
                     },
                     "system_prompt": {
                         "type": "string",
-                        "description": "Specialized system prompt defining the worker's role and capabilities"
+                        "description": "Specialized system prompt defining the worker's role and capabilities; {project_id}, {project_path}, {workspace_path}, {ticket_id}, {worker_id} and {worker_type} are filled in when a worker is spawned"
                     },
                     "short_description": {
                         "type": "string",
                         "description": "Optional brief description of the worker type's purpose"
+                    },
+                    "spawn_overrides": {
+                        "type": "object",
+                        "description": SPAWN_OVERRIDES_SCHEMA_DESCRIPTION
+                    },
+                    "capabilities": {
+                        "type": "array",
+                        "items": {
+                            "type": "string"
+                        },
+                        "description": CAPABILITIES_SCHEMA_DESCRIPTION
+                    },
+                    "max_open_tickets": {
+                        "type": "integer",
+                        "description": MAX_OPEN_TICKETS_SCHEMA_DESCRIPTION
                     }
                 },
                 "required": ["project_id", "worker_type", "system_prompt"]
@@ -109,23 +192,35 @@ impl ToolHandler for ListWorkerTypesTool {
 
         let project_id: Option<String> = extract_optional_param(&Some(args.clone()), "project_id")?;
 
-        // Parse pagination parameters
-        let cursor_str: Option<String> = extract_optional_param(&Some(args.clone()), "cursor")?;
-        let cursor = PaginationCursor::from_cursor_string(cursor_str)
+        let page_request = extract_keyset(&Some(args.clone()), json!([project_id]).to_string())?;
+        let after = page_request
+            .after
+            .as_ref()
+            .map(KeysetCursor::after_numeric)
+            .transpose()
             .map_err(crate::error::AppError::BadRequest)?;
 
-        match WorkerType::list_by_project(&state.db, project_id.as_deref()).await {
-            Ok(all_worker_types) => {
-                // Apply pagination using helper
-                let pagination_result = cursor.paginate(all_worker_types);
+        let listed = tokio::try_join!(
+            WorkerType::list_page(
+                &state.db,
+                project_id.as_deref(),
+                after,
+                page_request.fetch_limit()
+            ),
+            WorkerType::count(&state.db, project_id.as_deref())
+        );
+        match listed {
+            Ok((rows, total)) => {
+                let page = page_request.page(rows, |w| (w.created_at.clone(), w.id.to_string()));
+                let worker_types = WorkerTypeMetrics::attach(&state.db, page.items).await?;
 
                 // Create response with pagination info
                 let response_data = json!({
-                    "worker_types": pagination_result.items,
+                    "worker_types": worker_types,
                     "pagination": {
-                        "total": pagination_result.total,
-                        "has_more": pagination_result.has_more,
-                        "next_cursor": pagination_result.next_cursor
+                        "total": total,
+                        "has_more": page.has_more,
+                        "next_cursor": page.next_cursor
                     }
                 });
 
@@ -141,7 +236,7 @@ impl ToolHandler for ListWorkerTypesTool {
     fn definition(&self) -> Tool {
         Tool {
             name: "list_worker_types".to_string(),
-            description: "List all worker types, optionally filtered by project".to_string(),
+            description: "List all worker types with their success rate and completion time metrics and their load, optionally filtered by project. A load's status is derived from open tickets or reported with report_worker_type_status; derived_status is what the load alone gives".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -149,9 +244,13 @@ impl ToolHandler for ListWorkerTypesTool {
                         "type": "string",
                         "description": "Optional project ID to filter worker types"
                     },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum worker types per page (default: 50, max: 200)"
+                    },
                     "cursor": {
                         "type": "string",
-                        "description": "Optional cursor for pagination"
+                        "description": "next_cursor from the previous page; only valid with the same filters"
                     }
                 },
                 "required": []
@@ -170,14 +269,28 @@ impl ToolHandler for GetWorkerTypeTool {
 
         match WorkerType::get_by_type(&state.db, &project_id, &worker_type).await {
             Ok(Some(worker_type_info)) => {
+                let metrics = WorkerTypeMetrics::get(&state.db, &project_id, &worker_type).await?;
+                let load = WorkerTypeLoad::get(&state.db, &project_id, &worker_type).await?;
+                let status_history = WorkerTypeLoad::history(
+                    &state.db,
+                    &project_id,
+                    &worker_type,
+                    STATUS_HISTORY_LIMIT,
+                )
+                .await?;
                 let response = json!({
                     "id": worker_type_info.id,
                     "project_id": worker_type_info.project_id,
                     "worker_type": worker_type_info.worker_type,
                     "short_description": worker_type_info.short_description,
                     "system_prompt": worker_type_info.system_prompt,
+                    "spawn_overrides": worker_type_info.spawn_overrides,
+                    "capabilities": worker_type_info.capabilities(),
                     "created_at": worker_type_info.created_at,
-                    "updated_at": worker_type_info.updated_at
+                    "updated_at": worker_type_info.updated_at,
+                    "metrics": metrics,
+                    "load": load,
+                    "status_history": status_history
                 });
                 Ok(create_json_success_response(response))
             }
@@ -195,7 +308,7 @@ impl ToolHandler for GetWorkerTypeTool {
     fn definition(&self) -> Tool {
         Tool {
             name: "get_worker_type".to_string(),
-            description: "Get details of a specific worker type".to_string(),
+            description: "Get details of a specific worker type, including its success rate and completion time metrics, its load (open tickets against max_open_tickets, status and the status the load alone gives) and its latest status changes".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -224,28 +337,48 @@ impl ToolHandler for UpdateWorkerTypeTool {
         let short_description: Option<String> =
             extract_optional_param(&arguments, "short_description")?;
         let system_prompt: Option<String> = extract_optional_param(&arguments, "system_prompt")?;
-
-        if short_description.is_none() && system_prompt.is_none() {
+        let spawn_overrides = spawn_overrides_param(&arguments)?;
+        let capabilities = capabilities_param(&arguments)?;
+        let max_open_tickets = max_open_tickets_param(&arguments)?;
+
+        if short_description.is_none()
+            && system_prompt.is_none()
+            && spawn_overrides.is_none()
+            && capabilities.is_none()
+            && max_open_tickets.is_none()
+        {
             return Ok(create_json_error_response(
-                "At least one of 'short_description' or 'system_prompt' must be provided for update"
+                "At least one of 'short_description', 'system_prompt', 'spawn_overrides', 'capabilities' or 'max_open_tickets' must be provided for update"
             ));
         }
 
         let request = UpdateWorkerTypeRequest {
             short_description,
             system_prompt,
+            spawn_overrides,
+            capabilities,
         };
 
         match WorkerType::update(&state.db, &project_id, &worker_type, request).await {
             Ok(Some(worker_type_info)) => {
+                let load = match max_open_tickets {
+                    Some(max) => {
+                        WorkerTypeLoad::set_capacity(&state.db, &project_id, &worker_type, max)
+                            .await?
+                    }
+                    None => WorkerTypeLoad::get(&state.db, &project_id, &worker_type).await?,
+                };
                 let response = json!({
                     "id": worker_type_info.id,
                     "project_id": worker_type_info.project_id,
                     "worker_type": worker_type_info.worker_type,
                     "short_description": worker_type_info.short_description,
                     "system_prompt": worker_type_info.system_prompt,
+                    "spawn_overrides": worker_type_info.spawn_overrides,
+                    "capabilities": worker_type_info.capabilities(),
                     "created_at": worker_type_info.created_at,
-                    "updated_at": worker_type_info.updated_at
+                    "updated_at": worker_type_info.updated_at,
+                    "load": load
                 });
 
                 // Emit worker_type_updated event
@@ -281,8 +414,9 @@ impl ToolHandler for UpdateWorkerTypeTool {
     fn definition(&self) -> Tool {
         Tool {
             name: "update_worker_type".to_string(),
-            description: "Update an existing worker type's description or system prompt"
-                .to_string(),
+            description:
+                "Update an existing worker type's description, system prompt, spawn overrides, capabilities or capacity"
+                    .to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -300,7 +434,22 @@ impl ToolHandler for UpdateWorkerTypeTool {
                     },
                     "system_prompt": {
                         "type": "string",
-                        "description": "Updated system prompt defining the worker's role and capabilities"
+                        "description": "Updated system prompt defining the worker's role and capabilities; may use the same placeholders as create_worker_type"
+                    },
+                    "spawn_overrides": {
+                        "type": "object",
+                        "description": SPAWN_OVERRIDES_SCHEMA_DESCRIPTION
+                    },
+                    "capabilities": {
+                        "type": "array",
+                        "items": {
+                            "type": "string"
+                        },
+                        "description": CAPABILITIES_SCHEMA_DESCRIPTION
+                    },
+                    "max_open_tickets": {
+                        "type": "integer",
+                        "description": MAX_OPEN_TICKETS_SCHEMA_DESCRIPTION
                     }
                 },
                 "required": ["project_id", "worker_type"]
@@ -309,6 +458,77 @@ impl ToolHandler for UpdateWorkerTypeTool {
     }
 }
 
+pub struct ReportWorkerTypeStatusTool;
+
+#[async_trait]
+impl ToolHandler for ReportWorkerTypeStatusTool {
+    async fn call(&self, state: &AppState, arguments: Option<Value>) -> Result<CallToolResponse> {
+        let project_id: String = extract_param(&arguments, "project_id")?;
+        let worker_type: String = extract_param(&arguments, "worker_type")?;
+        let status: String = extract_param(&arguments, "status")?;
+        let reason: Option<String> = extract_optional_param(&arguments, "reason")?;
+
+        let status: WorkerTypeStatus = match status.parse() {
+            Ok(status) => status,
+            Err(e) => return Ok(create_json_error_response(&e.to_string())),
+        };
+
+        match WorkerTypeLoad::report(
+            &state.db,
+            &project_id,
+            &worker_type,
+            status,
+            reason.as_deref(),
+            "coordinator",
+        )
+        .await
+        {
+            Ok(Some(load)) => Ok(create_json_success_response(json!({
+                "message": format!("Worker type '{}' reported {}", worker_type, status),
+                "load": load
+            }))),
+            Ok(None) => Ok(create_json_error_response(&format!(
+                "Worker type '{}' not found for project '{}'",
+                worker_type, project_id
+            ))),
+            Err(e) => Ok(create_json_error_response(&format!(
+                "Failed to report status of worker type '{}': {}",
+                worker_type, e
+            ))),
+        }
+    }
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "report_worker_type_status".to_string(),
+            description: "Report a worker type online, busy, in maintenance or in error. Maintenance and error hold until another status is reported; online and busy last until the worker type's open tickets next cross its max_open_tickets. Tickets sent to a worker type that is not online are refused or carry a capacity_warning".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "project_id": {
+                        "type": "string",
+                        "description": "Project repository name"
+                    },
+                    "worker_type": {
+                        "type": "string",
+                        "description": "Worker type identifier"
+                    },
+                    "status": {
+                        "type": "string",
+                        "enum": ["online", "busy", "maintenance", "error"],
+                        "description": "Status to report"
+                    },
+                    "reason": {
+                        "type": "string",
+                        "description": "Why, kept in the worker type's status history"
+                    }
+                },
+                "required": ["project_id", "worker_type", "status"]
+            }),
+        }
+    }
+}
+
 pub struct DeleteWorkerTypeTool;
 
 #[async_trait]
@@ -317,6 +537,26 @@ impl ToolHandler for DeleteWorkerTypeTool {
         let project_id: String = extract_param(&arguments, "project_id")?;
         let worker_type: String = extract_param(&arguments, "worker_type")?;
 
+        if is_dry_run("delete_worker_type", &arguments) {
+            return Ok(
+                match WorkerType::plan_delete(&state.db, &project_id, &worker_type).await? {
+                    Some(plan) => create_dry_run_response(
+                        &format!(
+                            "Deleting worker type '{}' would strand {} open tickets and {} running workers",
+                            worker_type,
+                            plan.stranded_tickets.len(),
+                            plan.running_workers.len()
+                        ),
+                        &plan,
+                    ),
+                    None => create_json_error_response(&format!(
+                        "Worker type '{}' not found for project '{}'",
+                        worker_type, project_id
+                    )),
+                },
+            );
+        }
+
         match WorkerType::delete(&state.db, &project_id, &worker_type).await {
             Ok(true) => {
                 // Emit worker_type_deleted event
@@ -359,7 +599,8 @@ impl ToolHandler for DeleteWorkerTypeTool {
                     "worker_type": {
                         "type": "string",
                         "description": "Worker type identifier to delete"
-                    }
+                    },
+                    "dry_run": dry_run_property()
                 },
                 "required": ["project_id", "worker_type"]
             }),
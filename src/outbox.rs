@@ -0,0 +1,440 @@
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::SqliteConnection;
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+
+use crate::{
+    database::{
+        events::Event,
+        outbox::{OutboxBacklog, OutboxEvent},
+        DbPool,
+    },
+    events::EventPayload,
+    sse::EventBroadcaster,
+    webhooks,
+};
+
+/// Failed dispatches before an outbox event is dead
+pub const MAX_ATTEMPTS: u32 = 5;
+
+const POLL_INTERVAL_SECS: u64 = 1;
+const EVENTS_PER_PASS: i64 = 100;
+const RETRY_BASE_SECS: u64 = 1;
+const RETRY_MAX_SECS: u64 = 300;
+
+static DISPATCHED: AtomicU64 = AtomicU64::new(0);
+static FAILED: AtomicU64 = AtomicU64::new(0);
+static DEAD: AtomicU64 = AtomicU64::new(0);
+
+/// Outbox backlog and dispatch counters since startup
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct OutboxStats {
+    #[serde(flatten)]
+    pub backlog: OutboxBacklog,
+    pub dispatched: u64,
+    pub failed_attempts: u64,
+    pub dead_lettered: u64,
+}
+
+pub async fn stats(db: &DbPool) -> Result<OutboxStats> {
+    Ok(OutboxStats {
+        backlog: OutboxEvent::backlog(db).await?,
+        dispatched: DISPATCHED.load(Ordering::Relaxed),
+        failed_attempts: FAILED.load(Ordering::Relaxed),
+        dead_lettered: DEAD.load(Ordering::Relaxed),
+    })
+}
+
+/// Wait before retrying after the given number of failed attempts: 1s, doubling up to 5 minutes
+pub fn retry_delay(failed_attempts: u32) -> Duration {
+    let exponent = failed_attempts.saturating_sub(1).min(16);
+    Duration::from_secs((RETRY_BASE_SECS << exponent).min(RETRY_MAX_SECS))
+}
+
+/// Log the event in `events` and queue its webhook deliveries, on the dispatch transaction
+async fn publish(conn: &mut SqliteConnection, entry: &OutboxEvent) -> Result<EventPayload> {
+    let event = entry.payload()?;
+    Event::create_in(
+        conn,
+        event.event_type.clone(),
+        entry.ticket_id.as_deref(),
+        entry.worker_id.as_deref(),
+        entry.stage.as_deref(),
+        entry.reason.as_deref(),
+    )
+    .await?;
+    webhooks::enqueue_event(conn, &event).await?;
+    Ok(event)
+}
+
+/// What became of an outbox event handed to `dispatch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dispatch {
+    Published,
+    /// Another dispatcher published it since it was fetched; nothing was committed
+    AlreadyDispatched,
+}
+
+/// Publish one outbox event. Its `events` row, webhook deliveries and dispatched mark are
+/// committed together, so a dispatch cut short leaves no trace and is simply done again;
+/// clients connected to this process get the broadcast once that commit succeeded.
+async fn dispatch(
+    db: &DbPool,
+    broadcaster: &EventBroadcaster,
+    entry: &OutboxEvent,
+) -> Result<Dispatch> {
+    let mut tx = db.begin().await?;
+    let event = publish(&mut tx, entry).await?;
+    if !OutboxEvent::mark_dispatched(&mut tx, entry.id).await? {
+        tx.rollback().await?;
+        return Ok(Dispatch::AlreadyDispatched);
+    }
+    tx.commit().await?;
+
+    broadcaster.broadcast_to_clients(event);
+    Ok(Dispatch::Published)
+}
+
+/// Dispatch every due outbox event, holding back the later events of an entity whose
+/// earlier event failed; returns how many were dispatched
+pub async fn dispatch_due(db: &DbPool, broadcaster: &EventBroadcaster) -> Result<usize> {
+    let mut dispatched = 0;
+    for entry in OutboxEvent::due(db, EVENTS_PER_PASS).await? {
+        let error = match dispatch(db, broadcaster, &entry).await {
+            Ok(Dispatch::Published) => {
+                debug!(
+                    "Dispatched outbox event {} ({} of {})",
+                    entry.id, entry.event_type, entry.entity_id
+                );
+                DISPATCHED.fetch_add(1, Ordering::Relaxed);
+                dispatched += 1;
+                continue;
+            }
+            Ok(Dispatch::AlreadyDispatched) => {
+                debug!(
+                    "Outbox event {} ({} of {}) was dispatched elsewhere",
+                    entry.id, entry.event_type, entry.entity_id
+                );
+                continue;
+            }
+            Err(e) => format!("{:#}", e),
+        };
+
+        FAILED.fetch_add(1, Ordering::Relaxed);
+        let failed_attempts = entry.attempts as u32 + 1;
+        let retry_in =
+            (failed_attempts < MAX_ATTEMPTS).then(|| retry_delay(failed_attempts).as_secs());
+        match retry_in {
+            Some(secs) => info!(
+                "Outbox event {} ({}) failed to dispatch (attempt {}), retrying in {}s: {}",
+                entry.id, entry.event_type, failed_attempts, secs, error
+            ),
+            None => {
+                DEAD.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "Outbox event {} ({}) is dead after {} attempts: {}",
+                    entry.id, entry.event_type, failed_attempts, error
+                )
+            }
+        }
+        OutboxEvent::record_failure(db, entry.id, &error, retry_in).await?;
+    }
+    Ok(dispatched)
+}
+
+/// Publish outbox events as they are written, and those left over from before a restart
+pub fn start_outbox_dispatcher(db: DbPool, broadcaster: EventBroadcaster) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(POLL_INTERVAL_SECS));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = broadcaster.outbox_notified() => {}
+            }
+            // An entity's next event becomes due once the one before it is dispatched
+            loop {
+                match dispatch_due(&db, &broadcaster).await {
+                    Ok(0) => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!("Failed to dispatch outbox events: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::{
+            blocks::{Blocker, TicketBlock},
+            comments::{Comment, CreateCommentRequest},
+            tickets::Ticket,
+            webhooks::{CreateWebhookRequest, Webhook, WebhookDelivery},
+        },
+        events::{EventData, EventType},
+        test_support::{memory_pool, Fixtures},
+    };
+    use serde_json::json;
+
+    async fn count(pool: &DbPool, sql: &str) -> i64 {
+        sqlx::query_scalar(sql).fetch_one(pool).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_event_survives_a_dispatcher_killed_between_write_and_publish() {
+        let pool = memory_pool().await.unwrap();
+        let req: CreateWebhookRequest = serde_json::from_value(json!({
+            "url": "http://127.0.0.1:9/hook",
+            "event_types": ["ticket_created"]
+        }))
+        .unwrap();
+        let webhook = Webhook::create(&pool, &req, "s3cret").await.unwrap();
+
+        // The ticket and its event are committed together; nothing is published yet, as if
+        // the process died right after the commit
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("outbox").create().await.unwrap();
+        fx.worker_type(&project, "coding").create().await.unwrap();
+        let ticket = fx.ticket(&project, "Add login").create().await.unwrap();
+        let queued = OutboxEvent::list(&pool, Some("pending"), 10).await.unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].event_type, "ticket_created");
+        assert_eq!(queued[0].entity_id, ticket.ticket_id);
+
+        // A dispatch killed before its commit leaves nothing behind
+        let mut tx = pool.begin().await.unwrap();
+        publish(&mut tx, &queued[0]).await.unwrap();
+        drop(tx);
+        assert_eq!(
+            count(&pool, "SELECT COUNT(*) FROM webhook_deliveries").await,
+            0
+        );
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM events").await, 0);
+
+        // After the restart the event is published exactly once
+        let broadcaster = EventBroadcaster::new();
+        let mut clients = broadcaster.subscribe_sse();
+        let mut webhook_feed = broadcaster.subscribe_webhooks();
+        assert_eq!(dispatch_due(&pool, &broadcaster).await.unwrap(), 1);
+        assert_eq!(dispatch_due(&pool, &broadcaster).await.unwrap(), 0);
+
+        let deliveries = WebhookDelivery::list(&pool, webhook.id, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].event_type, "ticket_created");
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM events").await, 1);
+        let broadcast = clients.try_recv().unwrap();
+        assert_eq!(broadcast.event_type, EventType::TicketCreated);
+        assert!(clients.try_recv().is_err());
+        // Its deliveries are already queued, so the webhook dispatcher does not see it
+        assert!(webhook_feed.try_recv().is_err());
+        assert_eq!(
+            OutboxEvent::get(&pool, queued[0].id)
+                .await
+                .unwrap()
+                .unwrap()
+                .status,
+            "dispatched"
+        );
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_failed_event_holds_back_its_entity_until_dead_and_requeued() {
+        let pool = memory_pool().await.unwrap();
+        let broadcaster = EventBroadcaster::new();
+        let mut conn = pool.acquire().await.unwrap();
+        let first = OutboxEvent::write(
+            &mut conn,
+            "T-1",
+            &EventPayload::ticket_created("T-1", "shop"),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let second = OutboxEvent::write(
+            &mut conn,
+            "T-1",
+            &EventPayload::ticket_closed("T-1", "shop"),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        OutboxEvent::write(
+            &mut conn,
+            "T-2",
+            &EventPayload::ticket_created("T-2", "shop"),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        drop(conn);
+        sqlx::query("UPDATE event_outbox SET payload = 'not json' WHERE id = ?1")
+            .bind(first)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // T-2 goes out; T-1's second event waits behind its failing first one
+        assert_eq!(dispatch_due(&pool, &broadcaster).await.unwrap(), 1);
+        let failed = OutboxEvent::get(&pool, first).await.unwrap().unwrap();
+        assert_eq!((failed.status.as_str(), failed.attempts), ("pending", 1));
+        assert!(failed.last_error.is_some());
+        let backlog = OutboxEvent::backlog(&pool).await.unwrap();
+        assert_eq!((backlog.pending, backlog.dead), (2, 0));
+
+        sqlx::query(
+            "UPDATE event_outbox SET attempts = ?2, next_attempt_at = datetime('now', '-1 second') WHERE id = ?1",
+        )
+        .bind(first)
+        .bind(MAX_ATTEMPTS as i64 - 1)
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert_eq!(dispatch_due(&pool, &broadcaster).await.unwrap(), 0);
+        assert_eq!(
+            OutboxEvent::get(&pool, first)
+                .await
+                .unwrap()
+                .unwrap()
+                .status,
+            "dead"
+        );
+        // A dead event no longer holds back the rest of its entity
+        assert_eq!(dispatch_due(&pool, &broadcaster).await.unwrap(), 1);
+        assert_eq!(
+            OutboxEvent::get(&pool, second)
+                .await
+                .unwrap()
+                .unwrap()
+                .status,
+            "dispatched"
+        );
+
+        let dead = OutboxEvent::list(&pool, Some("dead"), 10).await.unwrap();
+        assert_eq!(dead.len(), 1);
+        let requeued = OutboxEvent::requeue(&pool, first).await.unwrap().unwrap();
+        assert_eq!(
+            (requeued.status.as_str(), requeued.attempts),
+            ("pending", 0)
+        );
+        assert!(OutboxEvent::requeue(&pool, second).await.unwrap().is_none());
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_event_dispatched_elsewhere_is_not_published_again() {
+        let pool = memory_pool().await.unwrap();
+        let broadcaster = EventBroadcaster::new();
+        let mut clients = broadcaster.subscribe_sse();
+        let mut conn = pool.acquire().await.unwrap();
+        let id = OutboxEvent::write(
+            &mut conn,
+            "T-1",
+            &EventPayload::ticket_created("T-1", "shop"),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        drop(conn);
+        let fetched = OutboxEvent::due(&pool, 10).await.unwrap().remove(0);
+
+        // Another dispatcher publishes it between the fetch and this dispatch
+        let mut tx = pool.begin().await.unwrap();
+        assert!(OutboxEvent::mark_dispatched(&mut tx, id).await.unwrap());
+        tx.commit().await.unwrap();
+
+        assert_eq!(
+            dispatch(&pool, &broadcaster, &fetched).await.unwrap(),
+            Dispatch::AlreadyDispatched
+        );
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM events").await, 0);
+        assert!(clients.try_recv().is_err());
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_ticket_changes_queue_their_events_with_the_change() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("queued").create().await.unwrap();
+        fx.worker_type(&project, "coding").create().await.unwrap();
+        let ticket = fx.ticket(&project, "Add login").create().await.unwrap();
+        let id = ticket.ticket_id.as_str();
+
+        let comment = Comment::create_from_request(
+            &pool,
+            CreateCommentRequest {
+                ticket_id: id.to_string(),
+                worker_type: "coding".to_string(),
+                worker_id: "w1".to_string(),
+                stage_number: 1,
+                content: "Started".to_string(),
+                reply_to_comment_id: None,
+            },
+        )
+        .await
+        .unwrap();
+        Comment::delete(&pool, comment.id).await.unwrap();
+        Ticket::update_estimate(&pool, id, Some(30)).await.unwrap();
+        let blocker = Blocker::Agent {
+            agent_id: "reviewer".to_string(),
+            expected_unblock_at: "2000-01-01 00:00:00".to_string(),
+        };
+        TicketBlock::block(&pool, id, "Needs review", &blocker, "planner")
+            .await
+            .unwrap();
+        TicketBlock::mark_due_nudges(&pool).await.unwrap();
+        TicketBlock::mark_due_escalations(&pool, 0).await.unwrap();
+        TicketBlock::unblock(&pool, id, "reviewer", None)
+            .await
+            .unwrap();
+
+        let mut queued = OutboxEvent::list(&pool, Some("pending"), 20).await.unwrap();
+        queued.reverse();
+        let kinds: Vec<_> = queued
+            .iter()
+            .map(|e| match e.payload().unwrap().data {
+                EventData::Ticket(data) if e.event_type == "ticket_updated" => {
+                    format!("ticket_updated:{}", data.change_type)
+                }
+                _ => e.event_type.clone(),
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                "ticket_created",
+                "ticket_updated:comment_added",
+                "ticket_updated:comment_deleted",
+                "ticket_updated:estimate_changed",
+                "ticket_updated:blocked",
+                "ticket_block_escalated",
+                "ticket_unblocked",
+            ]
+        );
+        assert!(queued
+            .iter()
+            .all(|e| e.entity_id == id && e.ticket_id.as_deref() == Some(id)));
+
+        pool.close().await;
+    }
+}
@@ -312,22 +312,28 @@ pub fn create_project_permissions(project_path: &str) -> Result<()> {
                 "mcp__vibe-ensemble-mcp__get_project".to_string(),
                 "mcp__vibe-ensemble-mcp__update_project".to_string(),
                 "mcp__vibe-ensemble-mcp__delete_project".to_string(),
+                "mcp__vibe-ensemble-mcp__override_project_quota".to_string(),
                 "mcp__vibe-ensemble-mcp__create_worker_type".to_string(),
                 "mcp__vibe-ensemble-mcp__list_worker_types".to_string(),
                 "mcp__vibe-ensemble-mcp__get_worker_type".to_string(),
                 "mcp__vibe-ensemble-mcp__update_worker_type".to_string(),
+                "mcp__vibe-ensemble-mcp__report_worker_type_status".to_string(),
                 "mcp__vibe-ensemble-mcp__delete_worker_type".to_string(),
                 "mcp__vibe-ensemble-mcp__create_ticket".to_string(),
                 "mcp__vibe-ensemble-mcp__get_ticket".to_string(),
                 "mcp__vibe-ensemble-mcp__list_tickets".to_string(),
+                "mcp__vibe-ensemble-mcp__search_tickets".to_string(),
                 "mcp__vibe-ensemble-mcp__add_ticket_comment".to_string(),
                 "mcp__vibe-ensemble-mcp__close_ticket".to_string(),
+                "mcp__vibe-ensemble-mcp__mark_ticket_duplicate".to_string(),
                 "mcp__vibe-ensemble-mcp__resume_ticket_processing".to_string(),
                 "mcp__vibe-ensemble-mcp__add_ticket_dependency".to_string(),
                 "mcp__vibe-ensemble-mcp__remove_ticket_dependency".to_string(),
                 "mcp__vibe-ensemble-mcp__get_dependency_graph".to_string(),
                 "mcp__vibe-ensemble-mcp__list_ready_tickets".to_string(),
                 "mcp__vibe-ensemble-mcp__list_blocked_tickets".to_string(),
+                "mcp__vibe-ensemble-mcp__block_ticket".to_string(),
+                "mcp__vibe-ensemble-mcp__unblock_ticket".to_string(),
                 "mcp__vibe-ensemble-mcp__list_events".to_string(),
                 "mcp__vibe-ensemble-mcp__resolve_event".to_string(),
                 "mcp__vibe-ensemble-mcp__get_tickets_by_stage".to_string(),
@@ -0,0 +1,52 @@
+use std::time::Duration;
+use tracing::warn;
+
+use crate::{
+    database::{quotas, DbPool},
+    events::emitter::EventEmitter,
+    sse::EventBroadcaster,
+};
+
+/// Periodically warn about projects that have used 90% of a quota; each crossing is
+/// reported once, and again after usage dropped below it
+pub fn start_quota_monitor(db: DbPool, broadcaster: EventBroadcaster, interval_secs: u64) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            evaluate(&db, &broadcaster).await;
+        }
+    });
+}
+
+async fn evaluate(db: &DbPool, broadcaster: &EventBroadcaster) {
+    let warnings = match quotas::mark_warnings(db).await {
+        Ok(warnings) => warnings,
+        Err(e) => {
+            warn!("Failed to evaluate project quotas: {}", e);
+            return;
+        }
+    };
+
+    let emitter = EventEmitter::new(db, broadcaster);
+    for warning in warnings {
+        warn!(
+            "Project '{}' has used {} of its {} {} quota",
+            warning.project_id, warning.usage, warning.limit, warning.quota
+        );
+        if let Err(e) = emitter
+            .emit_project_quota_warning(
+                &warning.project_id,
+                warning.quota.as_str(),
+                warning.usage,
+                warning.limit,
+            )
+            .await
+        {
+            warn!(
+                "Failed to emit quota warning for project '{}': {}",
+                warning.project_id, e
+            );
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::json;
+use std::{sync::Mutex, time::Duration};
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+use crate::{
+    audit::AuditRecord,
+    config::{Config, ConfigFile, FieldChange},
+    logging,
+    server::AppState,
+};
+
+/// The config in effect. A reload of the `--config` file swaps it as a whole; background
+/// tasks hold a receiver from [`LiveConfig::subscribe`] and read it on every pass.
+pub struct LiveConfig {
+    sender: watch::Sender<Config>,
+    /// Serializes reloads so each diff is against the config it replaces
+    reloading: Mutex<()>,
+}
+
+/// What a reload changed
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReloadOutcome {
+    pub path: String,
+    pub applied: Vec<FieldChange>,
+    /// Changed in the file, but only taken up after a restart
+    pub requires_restart: Vec<FieldChange>,
+}
+
+impl LiveConfig {
+    pub fn new(config: Config) -> Self {
+        Self {
+            sender: watch::Sender::new(config),
+            reloading: Mutex::new(()),
+        }
+    }
+
+    pub fn current(&self) -> Config {
+        self.sender.borrow().clone()
+    }
+
+    /// Read a setting without copying the config; do not hold across an await
+    pub fn borrow(&self) -> watch::Ref<'_, Config> {
+        self.sender.borrow()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Config> {
+        self.sender.subscribe()
+    }
+
+    /// Read the config file again and swap in its reloadable settings
+    pub fn reload(&self) -> Result<ReloadOutcome> {
+        let _reloading = self.reloading.lock().unwrap();
+        let current = self.current();
+        let Some(path) = current.config_path.clone() else {
+            bail!("No config file is configured (--config)");
+        };
+
+        let mut updated = current.clone();
+        ConfigFile::load(&path)?.apply(&mut updated);
+        check_settings(&updated)?;
+        let (applied, requires_restart) = current.diff(&updated);
+        if !applied.is_empty() {
+            self.sender.send_replace(current.with_reloadable(&updated));
+        }
+        Ok(ReloadOutcome {
+            path,
+            applied,
+            requires_restart,
+        })
+    }
+}
+
+/// Reject settings that could not be applied
+pub fn check_settings(config: &Config) -> Result<()> {
+    if !(0.0..=1.0).contains(&config.auto_assign_min_score) {
+        bail!(
+            "auto_assign_min_score must be between 0 and 1, got {}",
+            config.auto_assign_min_score
+        );
+    }
+    if let Some(filter) = &config.log_filter {
+        logging::parse_filter(filter)?;
+    }
+    Ok(())
+}
+
+/// Reload the config file, hand the new settings to the subsystems that keep their own
+/// copy, and log and audit what changed
+pub fn reload(state: &AppState, actor: &str) -> Result<ReloadOutcome> {
+    let outcome = state
+        .live_config
+        .reload()
+        .inspect_err(|e| error!("Failed to reload config: {:#}", e))?;
+    let config = state.live_config.current();
+
+    if outcome.applied.iter().any(|c| c.field == "log_filter") {
+        if let (Some(filter), Some(control)) = (&config.log_filter, logging::control()) {
+            control.set(filter, None)?;
+        }
+    }
+    crate::database::timing::configure(config.slow_query_ms, config.explain_slow_queries);
+    state.queue_manager.spawn_limiter().set_limits(
+        config.max_concurrent_workers,
+        config.max_workers_per_project,
+    );
+    state
+        .mcp_server
+        .sessions
+        .set_idle_timeout(Duration::from_secs(config.session_idle_timeout_secs));
+
+    if outcome.applied.is_empty() {
+        info!("Reloaded {}: no reloadable settings changed", outcome.path);
+    } else {
+        info!(
+            "Reloaded {}: {}",
+            outcome.path,
+            outcome
+                .applied
+                .iter()
+                .map(|c| format!("{} {} -> {}", c.field, c.from, c.to))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    if !outcome.requires_restart.is_empty() {
+        warn!(
+            "Changes to {} in {} take effect after a restart",
+            outcome
+                .requires_restart
+                .iter()
+                .map(|c| c.field)
+                .collect::<Vec<_>>()
+                .join(", "),
+            outcome.path
+        );
+    }
+    state.audit_logger.record(
+        AuditRecord::new(actor, "reload_config", "success").with_params(Some(&json!({
+            "applied": outcome.applied.iter().map(|c| c.field).collect::<Vec<_>>(),
+            "requires_restart": outcome.requires_restart.iter().map(|c| c.field).collect::<Vec<_>>(),
+        }))),
+    );
+    Ok(outcome)
+}
+
+/// Reload the config file whenever the process receives SIGHUP
+pub fn start_sighup_listener(state: AppState) {
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(e) => {
+                warn!("Failed to listen for SIGHUP: {}", e);
+                return;
+            }
+        };
+        while hangups.recv().await.is_some() {
+            info!("Received SIGHUP, reloading config");
+            // Failures are logged by reload; the running config stays as it was
+            let _ = reload(&state, "signal");
+        }
+    });
+    #[cfg(not(unix))]
+    let _ = state;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload_applies_only_reloadable_settings() {
+        let path = std::env::temp_dir().join(format!("vibe-config-{}.json", uuid::Uuid::new_v4()));
+        let live = LiveConfig::new(Config {
+            config_path: Some(path.display().to_string()),
+            port: 3276,
+            ..Config::default()
+        });
+        let mut receiver = live.subscribe();
+
+        std::fs::write(
+            &path,
+            r#"{"request_timeout_secs": 5, "max_concurrent_workers": 4, "port": 4000}"#,
+        )
+        .unwrap();
+        let outcome = live.reload().unwrap();
+        let applied: Vec<&str> = outcome.applied.iter().map(|c| c.field).collect();
+        assert_eq!(applied, ["request_timeout_secs"]);
+        assert_eq!(outcome.requires_restart[0].field, "port");
+        assert!(receiver.has_changed().unwrap());
+        let config = receiver.borrow_and_update().clone();
+        assert_eq!((config.request_timeout_secs, config.port), (5, 3276));
+
+        // Invalid settings leave the running config alone
+        std::fs::write(&path, r#"{"log_filter": "verbose"}"#).unwrap();
+        assert!(live.reload().is_err());
+        std::fs::write(&path, r#"{"max_workers": 2}"#).unwrap();
+        assert!(live.reload().is_err());
+        assert!(!receiver.has_changed().unwrap());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
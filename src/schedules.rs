@@ -0,0 +1,238 @@
+use anyhow::{bail, Result};
+use chrono::Utc;
+use serde_json::Value;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::{
+    cron::CronSchedule,
+    database::{
+        projects::Project,
+        schedules::{CreateScheduleRequest, TicketSchedule},
+        tags::Tag,
+        ticket_templates::TicketTemplate,
+        tickets::{CreateTicketRequest, Priority, Ticket},
+    },
+    server::AppState,
+    validation::PipelineValidator,
+};
+
+const SCHEDULER_INTERVAL_SECS: u64 = 60;
+
+/// Custom field on scheduled tickets naming the schedule that created them
+pub const SCHEDULE_FIELD: &str = "schedule";
+
+/// Format matching SQLite's datetime('now'), so stored run times compare with it
+fn to_sql_datetime(time: chrono::DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Next run of a schedule definition, None while it is disabled
+pub fn next_run_at(req: &CreateScheduleRequest) -> Result<Option<String>> {
+    let cron: CronSchedule = req.cron.parse()?;
+    if !req.enabled.unwrap_or(true) {
+        return Ok(None);
+    }
+    Ok(cron.next_after(Utc::now()).map(to_sql_datetime))
+}
+
+/// Check a schedule definition before it is stored; returns its next run
+pub async fn validate(state: &AppState, req: &CreateScheduleRequest) -> Result<Option<String>> {
+    if req.name.trim().is_empty() {
+        bail!("Schedule name must not be empty");
+    }
+    if req.title.trim().is_empty() {
+        bail!("Schedule title must not be empty");
+    }
+    let next_run_at = next_run_at(req)?;
+
+    if let Some(priority) = &req.priority {
+        priority.parse::<Priority>()?;
+    }
+    if Project::get_by_name(&state.db, &req.project_id)
+        .await?
+        .is_none()
+    {
+        bail!("Project '{}' not found", req.project_id);
+    }
+    PipelineValidator::validate_initial_stage(&state.db, &req.project_id, req.initial_stage())
+        .await?;
+
+    if let Some(name) = &req.template_name {
+        let Some(template) = TicketTemplate::get_by_name(&state.db, name).await? else {
+            bail!("Ticket template '{}' not found", name);
+        };
+        let field_errors =
+            template.validate_custom_fields(&req.custom_fields.clone().unwrap_or_default())?;
+        if !field_errors.is_empty() {
+            bail!(
+                "Schedule does not satisfy template '{}': {}",
+                name,
+                serde_json::to_string(&field_errors)?
+            );
+        }
+    }
+
+    Ok(next_run_at)
+}
+
+/// Create tickets for due schedules every minute
+pub fn start_scheduler(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(SCHEDULER_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            run_due(&state).await;
+        }
+    });
+}
+
+async fn run_due(state: &AppState) {
+    let schedules = match TicketSchedule::due(&state.db).await {
+        Ok(schedules) => schedules,
+        Err(e) => {
+            warn!("Failed to load due schedules: {}", e);
+            return;
+        }
+    };
+
+    for schedule in schedules {
+        if let Err(e) = run_schedule(state, &schedule).await {
+            warn!(
+                "Schedule '{}' failed to create a ticket: {}",
+                schedule.name, e
+            );
+        }
+    }
+}
+
+/// Handle one due occurrence: claim it, then create the ticket unless the previous one
+/// is still open. Occurrences missed while the server was down collapse into one run.
+async fn run_schedule(state: &AppState, schedule: &TicketSchedule) -> Result<Option<Ticket>> {
+    let Some(due_at) = &schedule.next_run_at else {
+        return Ok(None);
+    };
+    let cron: CronSchedule = schedule.cron.parse()?;
+    let next_run_at = cron.next_after(Utc::now()).map(to_sql_datetime);
+    if !TicketSchedule::claim_run(&state.db, &schedule.name, due_at, next_run_at.as_deref()).await?
+    {
+        return Ok(None);
+    }
+
+    if schedule.skip_if_open && schedule.previous_ticket_open(&state.db).await? {
+        info!(
+            "Schedule '{}' skipped: previous ticket {} is still open",
+            schedule.name,
+            schedule.last_ticket_id.as_deref().unwrap_or_default()
+        );
+        return Ok(None);
+    }
+
+    let ticket = create_ticket(state, schedule).await?;
+    TicketSchedule::record_ticket(&state.db, &schedule.name, &ticket.ticket_id).await?;
+    info!(
+        "Schedule '{}' created ticket {} (next run: {})",
+        schedule.name,
+        ticket.ticket_id,
+        next_run_at.as_deref().unwrap_or("never")
+    );
+    Ok(Some(ticket))
+}
+
+async fn create_ticket(state: &AppState, schedule: &TicketSchedule) -> Result<Ticket> {
+    let mut custom_fields = schedule.custom_fields()?.unwrap_or_default();
+
+    // The template may have changed since the schedule was saved
+    let (template_priority, template_tags) = match &schedule.template_name {
+        Some(name) => {
+            let Some(template) = TicketTemplate::get_by_name(&state.db, name).await? else {
+                bail!("Ticket template '{}' not found", name);
+            };
+            let field_errors = template.validate_custom_fields(&custom_fields)?;
+            if !field_errors.is_empty() {
+                bail!(
+                    "Ticket does not satisfy template '{}': {}",
+                    name,
+                    serde_json::to_string(&field_errors)?
+                );
+            }
+            (Some(template.default_priority), template.default_tags.0)
+        }
+        None => (None, Vec::new()),
+    };
+    custom_fields.insert(
+        SCHEDULE_FIELD.to_string(),
+        Value::String(schedule.name.clone()),
+    );
+
+    let project = Project::get_by_name(&state.db, &schedule.project_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Project '{}' not found", schedule.project_id))?;
+    let execution_plan = vec![schedule.initial_stage.clone()];
+    PipelineValidator::validate_pipeline_stages(
+        &state.db,
+        &schedule.project_id,
+        &execution_plan,
+        "Scheduled ticket creation",
+    )
+    .await?;
+
+    let subsystem = crate::workers::ticket_id::infer_subsystem_from_stages(&execution_plan);
+    let ticket_id = crate::workers::ticket_id::generate_ticket_id(
+        &state.db,
+        &project.project_prefix,
+        &subsystem,
+    )
+    .await?;
+
+    let ticket = Ticket::create(
+        &state.db,
+        CreateTicketRequest {
+            ticket_id: ticket_id.clone(),
+            project_id: schedule.project_id.clone(),
+            title: schedule.title.clone(),
+            description: schedule
+                .description
+                .clone()
+                .unwrap_or_else(|| format!("Created by schedule '{}'", schedule.name)),
+            execution_plan,
+            parent_ticket_id: None,
+            ticket_type: Some("task".to_string()),
+            dependency_status: None,
+            created_by_worker_id: None,
+            priority: Some(
+                schedule
+                    .priority
+                    .clone()
+                    .or(template_priority)
+                    .unwrap_or_else(|| "medium".to_string()),
+            ),
+            custom_fields: Some(serde_json::to_string(&custom_fields)?),
+            due_at: None,
+            sla_minutes: None,
+            estimate_minutes: None,
+        },
+    )
+    .await?;
+
+    if !template_tags.is_empty() {
+        let tag_ids = Tag::resolve(&state.db, &template_tags, !state.config.strict_tags).await?;
+        Tag::set_for_ticket(&state.db, &ticket.ticket_id, &tag_ids).await?;
+    }
+
+    // ticket_created was queued with the ticket
+    state.event_broadcaster.notify_outbox();
+
+    if let Err(e) = state
+        .queue_manager
+        .submit_task(&ticket.project_id, &ticket.current_stage, &ticket_id)
+        .await
+    {
+        warn!(
+            "Failed to submit scheduled ticket {} to {}-queue: {}",
+            ticket_id, ticket.current_stage, e
+        );
+    }
+
+    Ok(ticket)
+}
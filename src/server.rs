@@ -1,26 +1,38 @@
 use axum::extract::WebSocketUpgrade;
 use axum::{
     extract::{Query, State},
-    http::{HeaderMap, Method},
+    http::HeaderMap,
+    middleware,
     response::{IntoResponse, Json, Response},
     routing::{any, get, post},
     Router,
 };
-use serde_json::{json, Value};
-use std::sync::Arc;
-use tower_http::{cors::CorsLayer, limit::RequestBodyLimitLayer, trace::TraceLayer};
-use tracing::{error, info};
+use serde::Serialize;
+use serde_json::json;
+use std::{future::IntoFuture, path::Path, sync::Arc, time::Duration};
+use tokio::sync::Notify;
+use tower_http::trace::TraceLayer;
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
 
 use crate::{
+    api::access::{authenticated, AccessControl, Permission},
+    audit::AuditLogger,
     auth::AuthTokenManager,
+    backup::BackupStatus,
     config::Config,
     database::{recovery::TicketRecovery, DbPool},
+    discovery::{EndpointFile, EndpointInfo, DISCOVERY_DIR},
     error::Result,
     lockfile::LockFileManager,
+    logging::LogFilterStatus,
     mcp::{
-        server::{mcp_handler, McpServer},
+        event_stream::{events_handler, EventStream},
+        limits::{reject_oversized_body, ConnectionLimits},
+        server::{mcp_handler, mcp_session_delete_handler, McpServer},
         websocket::{WebSocketManager, WebSocketQuery},
     },
+    reload::LiveConfig,
     sse::{sse_handler, sse_message_handler, EventBroadcaster},
     workers::queue::QueueManager,
 };
@@ -28,7 +40,9 @@ use dashmap::DashMap;
 
 #[derive(Clone)]
 pub struct AppState {
+    /// Config as of startup; read settings a reload may change from `live_config`
     pub config: Config,
+    pub live_config: Arc<LiveConfig>,
     pub db: DbPool,
     pub queue_manager: Arc<QueueManager>,
     pub event_broadcaster: EventBroadcaster,
@@ -37,6 +51,8 @@ pub struct AppState {
     pub websocket_token: Option<String>,
     pub auth_manager: Arc<AuthTokenManager>,
     pub coordinator_directories: Arc<dashmap::DashMap<String, String>>,
+    pub audit_logger: AuditLogger,
+    pub event_stream: Arc<EventStream>,
 }
 
 impl AppState {
@@ -48,11 +64,50 @@ impl AppState {
 
 pub async fn run_server(config: Config) -> Result<()> {
     // Initialize database
-    let db = crate::database::create_pool(&config.database_url()).await?;
+    let db = if config.no_migrate {
+        let db = crate::database::open_pool(&config.database_url()).await?;
+        crate::database::migrations::require_up_to_date(&db).await?;
+        db
+    } else {
+        crate::database::create_pool(&config.database_url()).await?
+    };
+
+    // Configure lookup caching for projects and worker types
+    crate::database::cache::for_pool(&db).configure(config.cache_ttl_secs);
+
+    // Latency histograms and slow query logging for repository calls
+    crate::database::timing::configure(config.slow_query_ms, config.explain_slow_queries);
+
+    // Quotas for projects that do not set their own
+    crate::database::quotas::configure(config.max_open_tickets, config.max_comments_per_hour);
+
+    // Secret patterns masked in logs and comments
+    crate::redaction::configure(&config.redact_patterns, config.redact_at_rest)?;
+
+    // Message keys; stored messages no configured key can open stop the server here
+    let keyring = crate::crypto::Keyring::load(config.message_key_file.as_deref())?;
+    if config.encrypt_messages && keyring.is_none() {
+        return Err(anyhow::anyhow!(
+            "--encrypt-messages needs message keys: set {} or --message-key-file",
+            crate::crypto::KEYS_ENV
+        )
+        .into());
+    }
+    crate::database::encryption::verify_keys(&db, keyring.as_ref()).await?;
+    if config.encrypt_messages {
+        warn!("Message encryption is on; encrypted comments are left out of full-text search");
+    }
+    crate::crypto::configure(keyring, config.encrypt_messages);
 
     // Initialize event broadcaster
     let event_broadcaster = EventBroadcaster::new();
 
+    // Initialize audit log writer (non-blocking, persists in background)
+    let live_config = Arc::new(LiveConfig::new(config.clone()));
+
+    let audit_logger = AuditLogger::start(db.clone());
+    crate::audit::start_retention_sweeper(db.clone(), live_config.subscribe());
+
     // Initialize coordinator directories (shared across components)
     let coordinator_directories = Arc::new(DashMap::new());
 
@@ -62,6 +117,7 @@ pub async fn run_server(config: Config) -> Result<()> {
         config.clone(),
         event_broadcaster.clone(),
         coordinator_directories.clone(),
+        audit_logger.clone(),
     );
 
     // Initialize single MCP server instance with config-based tool registration
@@ -71,13 +127,23 @@ pub async fn run_server(config: Config) -> Result<()> {
     let websocket_manager = Arc::new(WebSocketManager::with_event_broadcasting(
         config.max_concurrent_client_requests,
         event_broadcaster.clone(),
+        ConnectionLimits::from(&config),
+        audit_logger.clone(),
+    ));
+
+    // Stream server-initiated notifications to /events clients
+    let event_stream = Arc::new(EventStream::new(
+        ConnectionLimits::from(&config),
+        audit_logger.clone(),
     ));
+    event_stream.start_forwarding(event_broadcaster.subscribe_sse());
 
     // Create auth token manager (we'll add the websocket token after binding to the port)
     let auth_manager = Arc::new(AuthTokenManager::new());
 
     let state = AppState {
         config: config.clone(),
+        live_config,
         db,
         queue_manager,
         event_broadcaster,
@@ -86,116 +152,422 @@ pub async fn run_server(config: Config) -> Result<()> {
         websocket_token: None, // Will be set after binding to port
         auth_manager: Arc::clone(&auth_manager),
         coordinator_directories,
+        audit_logger,
+        event_stream,
     };
 
+    // Reconcile work a crash left in flight before anything is queued or connected
+    if !config.skip_recovery {
+        recover_interrupted_work(&state).await?;
+    }
+
     // Respawn workers for unfinished tasks if enabled
     if !config.no_respawn {
         respawn_workers_for_unfinished_tasks(&state).await?;
     }
 
+    // Expire agent requests that pass their deadline
+    crate::agent_requests::start_expiry_sweeper(state.db.clone(), state.event_broadcaster.clone());
+    crate::agent_requests::start_retention_sweeper(state.db.clone(), state.live_config.subscribe());
+
+    if config.backup_interval_hours > 0 {
+        let dir = crate::backup::backup_dir(&config.database_path);
+        info!(
+            "Scheduled backups every {}h to {} (keeping {})",
+            config.backup_interval_hours,
+            dir.display(),
+            config.backup_retention
+        );
+        crate::backup::start_backup_scheduler(
+            state.db.clone(),
+            dir,
+            config.backup_interval_hours,
+            config.backup_retention,
+        );
+    }
+
+    // Escalate tickets approaching or past their due date
+    crate::sla::start_sla_monitor(
+        state.db.clone(),
+        state.event_broadcaster.clone(),
+        config.sla_check_interval_secs,
+        state.live_config.subscribe(),
+    );
+
+    // Flag tickets that keep being reopened, checked as often as SLAs
+    crate::flaky::start_flaky_monitor(
+        state.db.clone(),
+        state.event_broadcaster.clone(),
+        config.sla_check_interval_secs,
+        state.live_config.subscribe(),
+    );
+
+    // Raise the effective priority of aging, blocking or nearly due tickets, checked as
+    // often as SLAs
+    crate::escalation::start_escalation_monitor(
+        state.db.clone(),
+        state.event_broadcaster.clone(),
+        config.sla_check_interval_secs,
+        state.live_config.subscribe(),
+    );
+
+    // Remind blockers of overdue blocks and escalate them, checked as often as SLAs
+    crate::blocks::start_block_monitor(
+        state.db.clone(),
+        state.event_broadcaster.clone(),
+        config.sla_check_interval_secs,
+        state.live_config.subscribe(),
+    );
+
+    // Warn about projects close to a quota, checked as often as SLAs
+    crate::quotas::start_quota_monitor(
+        state.db.clone(),
+        state.event_broadcaster.clone(),
+        config.sla_check_interval_secs,
+    );
+
+    // Snapshot ticket state for as-of queries, checked as often as SLAs
+    crate::timeline::start_snapshot_monitor(state.db.clone(), config.sla_check_interval_secs);
+
+    // Roll up recent activity into periodic digests for the coordinator
+    if config.digest_interval_mins > 0 {
+        crate::digest::start_digest_monitor(
+            state.db.clone(),
+            state.event_broadcaster.clone(),
+            config.digest_interval_mins,
+        );
+    }
+
+    // Create tickets from recurring schedules as they come due
+    crate::schedules::start_scheduler(state.clone());
+
+    // POST lifecycle events to registered webhooks
+    crate::webhooks::start_webhook_dispatcher(
+        state.db.clone(),
+        &state.event_broadcaster,
+        config.webhook_max_attempts,
+    )?;
+
+    // Publish events queued in the outbox, including those left over from before a restart
+    crate::outbox::start_outbox_dispatcher(state.db.clone(), state.event_broadcaster.clone());
+
+    // Close MCP sessions nobody has used for --session-idle-timeout-secs
+    crate::mcp::session::start_idle_sweeper(state.clone());
+
+    // Apply reloadable settings from --config on SIGHUP
+    if config.config_path.is_some() {
+        crate::reload::start_sighup_listener(state.clone());
+    }
+
     // Start update checking service if enabled
     if !config.disable_update_checks {
         info!(
             "Starting update check service (interval: {} hours)",
             config.update_check_interval_hours
         );
-        let update_service = crate::updates::UpdateService::new(config.update_check_interval_hours);
+        let update_service = crate::updates::UpdateService::new(
+            config.update_check_interval_hours,
+            config.release_channel,
+        );
         let _update_task =
             update_service.start_periodic_checks(state.db.clone(), state.event_broadcaster.clone());
         // Note: We don't need to keep the JoinHandle as the task will run until server shutdown
     }
 
-    let cors = CorsLayer::new()
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-        .allow_headers([
-            axum::http::header::CONTENT_TYPE,
-            axum::http::header::ACCEPT,
-            axum::http::header::CACHE_CONTROL,
-            axum::http::header::AUTHORIZATION,
-            axum::http::header::HeaderName::from_static("x-api-key"),
-            axum::http::header::HeaderName::from_static("x-claude-code-ide-authorization"),
-            axum::http::header::HeaderName::from_static("last-event-id"),
-            axum::http::header::HeaderName::from_static("mcp-protocol-version"),
-        ])
-        .allow_origin(axum::http::header::HeaderValue::from_static("*"));
+    let warm_pool = state.queue_manager.warm_pool().clone();
+    let app = build_router(state)?;
+
+    let address = config.server_address();
+    info!("Server listening on {}", address);
+
+    let listener = tokio::net::TcpListener::bind(&address).await?;
+    let bound_port = listener.local_addr()?.port();
+
+    // Announce where this instance can actually be reached
+    let endpoint = EndpointInfo::new(&config.host, bound_port, &config.base_path);
+    let endpoint_file = match EndpointFile::write(Path::new(DISCOVERY_DIR), &endpoint) {
+        Ok(file) => {
+            info!("Announced endpoint in {}", file.path().display());
+            Some(file)
+        }
+        Err(e) => {
+            warn!("Failed to write the endpoint discovery file: {:#}", e);
+            None
+        }
+    };
+    if config.update_mcp_json {
+        match crate::discovery::update_mcp_json(Path::new(".mcp.json"), &endpoint) {
+            Ok(true) => info!("Pointed .mcp.json at {}", endpoint.http_url),
+            Ok(false) => info!(".mcp.json is missing or already points at this server"),
+            Err(e) => warn!("Failed to update .mcp.json: {:#}", e),
+        }
+    }
+
+    // Now that we're successfully bound to the port, create/update the Claude IDE lock file
+    let lock_manager = LockFileManager::new(config.host.clone(), bound_port);
+    let _websocket_token = match lock_manager.create_or_update_claude_lock_file() {
+        Ok(token) => {
+            info!("Created/updated Claude IDE lock file with WebSocket token");
+            auth_manager.add_token(token.clone());
+            Some(token)
+        }
+        Err(e) => {
+            error!("Failed to create Claude IDE lock file: {}", e);
+            None
+        }
+    };
+
+    // Update the state with the websocket token (this is a bit tricky since state is immutable)
+    // For now, the token is added to the auth_manager which is what matters for authentication
+
+    let stopping = Arc::new(Notify::new());
+    let serve = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown({
+        let stopping = stopping.clone();
+        async move { stopping.notified().await }
+    })
+    .into_future();
+    tokio::pin!(serve);
+
+    let result = tokio::select! {
+        result = &mut serve => Some(result),
+        _ = shutdown_signal() => {
+            info!(
+                "Shutting down; waiting up to {}s for open connections",
+                config.shutdown_grace_secs
+            );
+            stopping.notify_one();
+            tokio::time::timeout(Duration::from_secs(config.shutdown_grace_secs), serve)
+                .await
+                .ok()
+        }
+    };
+    match result {
+        Some(Ok(_)) => info!("Server stopped gracefully"),
+        Some(Err(e)) => error!("Server error: {}", e),
+        None => warn!("Connections still open after the shutdown grace period; closing them"),
+    }
+
+    // Idle warm workers have no ticket to finish
+    warm_pool.drain().await;
 
+    // Running workers are killed as their tasks are dropped with the runtime
+    if let Err(e) = lock_manager.cleanup_claude_lock_file() {
+        warn!("Failed to remove Claude IDE lock file: {}", e);
+    }
+    drop(endpoint_file);
+
+    Ok(())
+}
+
+/// Build the HTTP router with all routes and middleware, mounted under the configured base path
+pub fn build_router(state: AppState) -> Result<Router> {
+    let config = state.config.clone();
+
+    // Reject misconfigured origins up front instead of falling back to '*'
+    let allowed_origins = crate::web::parse_allowed_origins(&config.allowed_origins)?;
+    if !allowed_origins.is_empty() {
+        info!("CORS restricted to origins: {:?}", allowed_origins);
+    }
+    let cors = crate::web::cors_layer(&allowed_origins);
+    let access = Arc::new(AccessControl::from_config(&config)?);
+
+    let mut api = crate::api::create_api_router();
+    if config.api_docs {
+        info!("OpenAPI document at {}/api/openapi.json", config.base_path);
+        api = api.merge(crate::api::openapi::router());
+    }
+
+    // MCP transports take the same tokens: with any configured, agents without one may
+    // only watch the event streams. Spawned workers' tokens are only good where a caller
+    // is told apart from the coordinator.
+    let agents = Arc::new(access.for_agents());
+    let workers = Arc::new(agents.with_workers());
     let mut app = Router::new()
         .route("/health", get(health_check))
-        .route("/mcp", post(mcp_handler))
-        .route("/sse", get(sse_handler))
-        .route("/messages", post(sse_message_handler))
-        .nest("/api", crate::api::create_api_router())
+        .route(
+            "/mcp",
+            authenticated(
+                &workers,
+                Permission::ManageWorkers,
+                post(mcp_handler).delete(mcp_session_delete_handler),
+            ),
+        )
+        .route(
+            "/events",
+            authenticated(&workers, Permission::Read, get(events_handler)),
+        )
+        .route(
+            "/sse",
+            authenticated(&workers, Permission::Read, get(sse_handler)),
+        )
+        .route(
+            "/messages",
+            authenticated(
+                &agents,
+                Permission::ManageWorkers,
+                post(sse_message_handler),
+            ),
+        )
+        .nest(
+            "/api",
+            api.layer(middleware::from_fn_with_state(
+                access,
+                crate::api::access::authenticate,
+            )),
+        )
         .route("/dashboard", get(crate::dashboard::serve_dashboard))
         .route("/dashboard/*path", get(crate::dashboard::serve_dashboard))
         .route("/assets/*path", get(crate::dashboard::serve_dashboard));
 
     // Add root route that handles both WebSocket upgrades and regular HTTP requests
-    app = app.route("/", any(root_handler));
-    info!("WebSocket support enabled at / (root path)");
-    info!("Dashboard available at /dashboard");
+    app = app.route(
+        "/",
+        authenticated(&agents, Permission::ManageWorkers, any(root_handler)),
+    );
+    info!(
+        "WebSocket support enabled at {}/ (root path)",
+        config.base_path
+    );
+    info!("Dashboard available at {}/dashboard", config.base_path);
 
-    let app = app
-        .layer(RequestBodyLimitLayer::new(1024 * 1024)) // 1 MiB
-        .layer(TraceLayer::new_for_http())
-        .layer(cors)
-        .with_state(state);
+    let state_for_limits = state.clone();
+    let mut app = app.with_state(state.clone());
+    if !config.base_path.is_empty() {
+        info!("Routes mounted under {}", config.base_path);
+        // The nested root only matches the bare prefix; proxies usually forward "<prefix>/"
+        app = Router::new().nest(&config.base_path, app).route(
+            &format!("{}/", config.base_path),
+            authenticated(&agents, Permission::ManageWorkers, any(root_handler)).with_state(state),
+        );
+    }
 
-    let address = config.server_address();
-    info!("Server listening on {}", address);
+    let app = app
+        .layer(middleware::from_fn_with_state(
+            state_for_limits,
+            reject_oversized_body,
+        ))
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(crate::web::make_request_span(config.trust_proxy_headers)),
+        )
+        .layer(cors);
 
-    let listener = tokio::net::TcpListener::bind(&address).await?;
+    Ok(app)
+}
 
-    // Now that we're successfully bound to the port, create/update the Claude IDE lock file
-    let _websocket_token = {
-        let lock_manager = LockFileManager::new(config.host.clone(), config.port);
-        match lock_manager.create_or_update_claude_lock_file() {
-            Ok(token) => {
-                info!("Created/updated Claude IDE lock file with WebSocket token");
-                auth_manager.add_token(token.clone());
-                Some(token)
+/// Resolves on Ctrl-C (SIGINT) or SIGTERM
+async fn shutdown_signal() {
+    let interrupt = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
             }
             Err(e) => {
-                error!("Failed to create Claude IDE lock file: {}", e);
-                None
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
             }
         }
     };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
 
-    // Update the state with the websocket token (this is a bit tricky since state is immutable)
-    // For now, the token is added to the auth_manager which is what matters for authentication
-
-    match axum::serve(listener, app).await {
-        Ok(_) => info!("Server stopped gracefully"),
-        Err(e) => error!("Server error: {}", e),
+    tokio::select! {
+        _ = interrupt => info!("Received Ctrl-C"),
+        _ = terminate => info!("Received SIGTERM"),
     }
+}
 
-    Ok(())
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DatabaseHealth {
+    /// SQLite version
+    pub version: String,
+    pub status: String,
+}
+
+/// Body of `GET /health`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthStatus {
+    /// healthy, or unhealthy when the database cannot be reached
+    pub status: String,
+    pub service: String,
+    pub timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database: Option<DatabaseHealth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup: Option<BackupStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_filter: Option<LogFilterStatus>,
 }
 
-async fn health_check(State(state): State<AppState>) -> Result<Json<Value>> {
+/// GET /health - Database connectivity, backup progress and the log filter in effect
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses((status = 200, description = "Server health", body = HealthStatus))
+)]
+pub async fn health_check(State(state): State<AppState>) -> Result<Json<HealthStatus>> {
+    let mut health = HealthStatus {
+        status: "healthy".to_string(),
+        service: "vibe-ensemble-mcp".to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        error: None,
+        database: None,
+        backup: None,
+        log_filter: None,
+    };
+
     // Test database connection
-    let db_version = match crate::database::schema::get_database_info(&state.db).await {
-        Ok(version) => version,
+    match crate::database::schema::get_database_info(&state.db).await {
+        Ok(version) => {
+            health.database = Some(DatabaseHealth {
+                version,
+                status: "connected".to_string(),
+            });
+            health.backup = Some(crate::backup::status());
+            health.log_filter = crate::logging::status();
+        }
         Err(e) => {
             error!("Database health check failed: {}", e);
-            return Ok(Json(json!({
-                "status": "unhealthy",
-                "service": "vibe-ensemble-mcp",
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-                "error": "Database connection failed"
-            })));
+            health.status = "unhealthy".to_string();
+            health.error = Some("Database connection failed".to_string());
         }
-    };
+    }
 
-    Ok(Json(json!({
-        "status": "healthy",
-        "service": "vibe-ensemble-mcp",
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-        "database": {
-            "version": db_version,
-            "status": "connected"
-        }
-    })))
+    Ok(Json(health))
+}
+
+async fn recover_interrupted_work(state: &AppState) -> Result<()> {
+    let recovery =
+        TicketRecovery::recover_after_restart(&state.db, state.config.orphaned_tickets).await?;
+    if recovery.is_empty() {
+        info!("Startup recovery: no work was left in flight");
+        return Ok(());
+    }
+
+    warn!("{}", recovery.summary());
+    if let Err(e) = state
+        .event_emitter()
+        .emit_recovery_completed(&recovery)
+        .await
+    {
+        warn!("Failed to emit recovery_completed event: {}", e);
+    }
+    Ok(())
 }
 
 async fn respawn_workers_for_unfinished_tasks(state: &AppState) -> Result<()> {
@@ -268,6 +640,7 @@ async fn root_handler(
             "/": "WebSocket MCP connection (with Upgrade: websocket header)",
             "/health": "Health check endpoint",
             "/mcp": "HTTP MCP endpoint",
+            "/events": "Server-Sent Events stream of MCP notifications",
             "/sse": "Server-Sent Events endpoint",
             "/messages": "SSE message endpoint"
         },
@@ -277,3 +650,1154 @@ async fn root_handler(
         }
     })).into_response()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::net::SocketAddr;
+
+    async fn spawn_server(config: Config) -> (String, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("vibe-http-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = Config {
+            database_path: dir.join("t.db").display().to_string(),
+            ..config
+        };
+        let db = crate::database::create_pool(&config.database_url())
+            .await
+            .unwrap();
+        let event_broadcaster = EventBroadcaster::new();
+        let audit_logger = AuditLogger::start(db.clone());
+        let coordinator_directories = Arc::new(DashMap::new());
+        let queue_manager = QueueManager::new(
+            db.clone(),
+            config.clone(),
+            event_broadcaster.clone(),
+            coordinator_directories.clone(),
+            audit_logger.clone(),
+        );
+        let event_stream = Arc::new(EventStream::new(
+            ConnectionLimits::from(&config),
+            audit_logger.clone(),
+        ));
+        let state = AppState {
+            mcp_server: Arc::new(McpServer::new(&config)),
+            live_config: Arc::new(LiveConfig::new(config.clone())),
+            config,
+            db,
+            queue_manager,
+            event_broadcaster,
+            websocket_manager: Arc::new(WebSocketManager::new()),
+            websocket_token: None,
+            auth_manager: Arc::new(AuthTokenManager::new()),
+            coordinator_directories,
+            audit_logger,
+            event_stream,
+        };
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = build_router(state).unwrap();
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .unwrap();
+        });
+        (format!("http://{}/mcp", address), dir)
+    }
+
+    #[tokio::test]
+    async fn test_http_initialize_list_and_call_flow() {
+        let (url, dir) = spawn_server(Config::default()).await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(&url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": {"name": "http-test", "version": "1.0"}
+                }
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let session_id = response
+            .headers()
+            .get("mcp-session-id")
+            .expect("initialize should issue a session id")
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body: Value = response.json().await.unwrap();
+        assert!(body["result"]["serverInfo"].is_object());
+        assert!(body["result"]["capabilities"]["tools"].is_object());
+
+        let post = |payload: Value| {
+            client
+                .post(&url)
+                .header("mcp-session-id", &session_id)
+                .body(payload.to_string())
+                .send()
+        };
+
+        let response = post(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 202);
+
+        let body: Value = post(json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let tools = body["result"]["tools"].as_array().unwrap();
+        assert!(tools.iter().any(|tool| tool["name"] == "list_projects"));
+
+        let body: Value = post(json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "tools/call",
+            "params": {"name": "list_projects", "arguments": {}}
+        }))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+        assert!(body["error"].is_null(), "tools/call failed: {}", body);
+        assert_eq!(body["result"]["isError"], false);
+
+        // Batches answer requests in order and drop notification responses
+        let response = post(json!([
+            {"jsonrpc": "2.0", "id": 4, "method": "ping"},
+            {"jsonrpc": "2.0", "method": "notifications/initialized"},
+            {"jsonrpc": "2.0", "id": 5, "method": "tools/list"}
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), 200);
+        let body: Value = response.json().await.unwrap();
+        let ids: Vec<_> = body
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["id"].clone())
+            .collect();
+        assert_eq!(ids, vec![json!(4), json!(5)]);
+
+        let response = post(json!([{"jsonrpc": "2.0", "method": "notifications/initialized"}]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 202);
+
+        // Only initialize may come without the session header
+        for payload in [
+            json!({"jsonrpc": "2.0", "id": 6, "method": "tools/list"}),
+            json!([{"jsonrpc": "2.0", "id": 7, "method": "ping"}]),
+        ] {
+            let response = client.post(&url).json(&payload).send().await.unwrap();
+            assert_eq!(response.status(), 400);
+            let body: Value = response.json().await.unwrap();
+            assert_eq!(body["error"]["data"]["kind"], "not_initialized");
+        }
+
+        let response = client.post(&url).body("{not json").send().await.unwrap();
+        assert_eq!(response.status(), 400);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["error"]["code"], crate::mcp::types::PARSE_ERROR);
+
+        // An ended session answers 404 rather than starting over uninitialized
+        let response = client
+            .delete(&url)
+            .header("mcp-session-id", &session_id)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 204);
+        for payload in [
+            json!({"jsonrpc": "2.0", "id": 8, "method": "tools/list"}),
+            json!([{"jsonrpc": "2.0", "id": 9, "method": "ping"}]),
+        ] {
+            let response = post(payload).await.unwrap();
+            assert_eq!(response.status(), 404);
+            let body: Value = response.json().await.unwrap();
+            assert_eq!(body["error"]["data"]["kind"], "not_found");
+        }
+        let response = client
+            .post(&url)
+            .header("mcp-session-id", &session_id)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 10,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": {"name": "http-test", "version": "1.0"}
+                }
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_ne!(response.headers()["mcp-session-id"], session_id.as_str());
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_api_permissions_by_role() {
+        let tokens_dir = std::env::temp_dir().join(format!("vibe-tokens-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&tokens_dir).unwrap();
+        let tokens_path = tokens_dir.join("tokens.json");
+        std::fs::write(
+            &tokens_path,
+            json!({"tokens": [
+                {"name": "ops", "token": "operator-token-0123456789", "role": "operator"},
+                {"name": "root", "token": "admin-token-0123456789", "role": "admin"}
+            ]})
+            .to_string(),
+        )
+        .unwrap();
+        let (url, dir) = spawn_server(Config {
+            api_tokens_path: Some(tokens_path.display().to_string()),
+            dashboard_read_only: true,
+            ..Config::default()
+        })
+        .await;
+        let api = url.trim_end_matches("/mcp").to_string() + "/api";
+        let client = reqwest::Client::new();
+
+        // Routes and the permission they need; None for reads open to every role
+        let routes = [
+            ("GET", "/projects", None),
+            ("GET", "/stats", None),
+            (
+                "PATCH",
+                "/projects/p/tickets/P-1/position",
+                Some("tickets:write"),
+            ),
+            ("POST", "/tags", Some("tickets:write")),
+            (
+                "DELETE",
+                "/projects/p/worker-types/coding",
+                Some("workers:write"),
+            ),
+            ("DELETE", "/sessions/s-1", Some("workers:write")),
+            ("PUT", "/projects/p/settings", Some("admin")),
+            ("GET", "/audit", Some("admin")),
+            ("POST", "/admin/backup", Some("admin")),
+        ];
+        let roles = [
+            (None, vec!["read"]),
+            (
+                Some("operator-token-0123456789"),
+                vec!["read", "tickets:write", "workers:write"],
+            ),
+            (
+                Some("admin-token-0123456789"),
+                vec!["read", "tickets:write", "workers:write", "admin"],
+            ),
+        ];
+
+        for (token, granted) in &roles {
+            let request = |method: &str, path: &str| {
+                let mut request = client
+                    .request(method.parse().unwrap(), format!("{}{}", api, path))
+                    .json(&json!({}));
+                if let Some(token) = token {
+                    request = request.bearer_auth(token);
+                }
+                request
+            };
+
+            let access: Value = request("GET", "/access")
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            assert_eq!(access["permissions"], json!(granted));
+
+            for (method, path, permission) in routes {
+                let response = request(method, path).send().await.unwrap();
+                let allowed = permission.is_none_or(|p| granted.contains(&p));
+                if allowed {
+                    assert_ne!(response.status(), 403, "{:?} {} {}", token, method, path);
+                } else {
+                    assert_eq!(response.status(), 403, "{:?} {} {}", token, method, path);
+                    let body: Value = response.json().await.unwrap();
+                    assert_eq!(body["missing_permission"], permission.unwrap());
+                }
+            }
+        }
+
+        let unknown = client
+            .get(format!("{}/projects", api))
+            .bearer_auth("not-a-token")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(unknown.status(), 401);
+
+        // MCP takes the same tokens: without one, agents may not call tools
+        let initialize = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "clientInfo": {"name": "access-test", "version": "1.0"}
+            }
+        });
+        let mcp_status = |token: Option<&str>| {
+            let mut request = client.post(&url).json(&initialize);
+            if let Some(token) = token {
+                request = request.bearer_auth(token);
+            }
+            async move { request.send().await.unwrap().status() }
+        };
+        assert_eq!(mcp_status(None).await, 403);
+        assert_eq!(mcp_status(Some("not-a-token")).await, 401);
+        assert_eq!(mcp_status(Some("operator-token-0123456789")).await, 200);
+        let worker_token = crate::api::access::issue_worker_token("w-1", "coding");
+        assert_eq!(mcp_status(Some(&worker_token)).await, 200);
+        // A worker's token is good for MCP only
+        let response = client
+            .get(format!("{}/projects", api))
+            .bearer_auth(&worker_token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 401);
+        crate::api::access::revoke_worker_token(&worker_token);
+        assert_eq!(mcp_status(Some(&worker_token)).await, 401);
+        let messages = client
+            .post(url.replace("/mcp", "/messages"))
+            .json(&json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(messages.status(), 403);
+        let websocket = client
+            .get(url.trim_end_matches("mcp"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(websocket.status(), 403);
+
+        std::fs::remove_dir_all(dir).ok();
+        std::fs::remove_dir_all(tokens_dir).ok();
+    }
+    #[tokio::test]
+    async fn test_reload_config_keeps_sessions() {
+        let config_dir = std::env::temp_dir().join(format!("vibe-config-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let config_path = config_dir.join("config.json");
+        std::fs::write(&config_path, "{}").unwrap();
+        let (url, dir) = spawn_server(Config {
+            config_path: Some(config_path.display().to_string()),
+            max_concurrent_workers: 4,
+            ..Config::default()
+        })
+        .await;
+        let api = url.trim_end_matches("/mcp").to_string() + "/api";
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(&url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": {"name": "reload-test", "version": "1.0"}
+                }
+            }))
+            .send()
+            .await
+            .unwrap();
+        let session_id = response.headers()["mcp-session-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let post = |payload: Value| {
+            client
+                .post(&url)
+                .header("mcp-session-id", &session_id)
+                .json(&payload)
+                .send()
+        };
+        post(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .await
+            .unwrap();
+        let queue_status = || async {
+            let body: Value = post(json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "tools/call",
+                "params": {"name": "get_spawn_queue_status", "arguments": {}}
+            }))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+            assert!(body["error"].is_null(), "tools/call failed: {}", body);
+            let text = body["result"]["content"][0]["text"].as_str().unwrap();
+            serde_json::from_str::<Value>(text).unwrap()
+        };
+        assert_eq!(queue_status().await["max_concurrent_workers"], 4);
+
+        std::fs::write(
+            &config_path,
+            json!({"max_concurrent_workers": 1, "port": 9999}).to_string(),
+        )
+        .unwrap();
+        let outcome: Value = client
+            .post(format!("{}/admin/reload-config", api))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(outcome["applied"][0]["field"], "max_concurrent_workers");
+        assert_eq!(outcome["requires_restart"][0]["field"], "port");
+
+        // The same session sees the new limit
+        assert_eq!(queue_status().await["max_concurrent_workers"], 1);
+
+        std::fs::write(&config_path, "{not json").unwrap();
+        let response = client
+            .post(format!("{}/admin/reload-config", api))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+        assert_eq!(queue_status().await["max_concurrent_workers"], 1);
+
+        std::fs::remove_dir_all(dir).ok();
+        std::fs::remove_dir_all(config_dir).ok();
+    }
+
+    /// Bad input of every kind gets the exact code from the published table
+    #[tokio::test]
+    async fn test_error_code_conformance() {
+        use crate::mcp::types::*;
+
+        let (url, dir) = spawn_server(Config::default()).await;
+        let client = reqwest::Client::new();
+        let response = client
+            .post(&url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": {"name": "conformance-test", "version": "1.0"}
+                }
+            }))
+            .send()
+            .await
+            .unwrap();
+        let session_id = response.headers()["mcp-session-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let call = |payload: Value| {
+            let request = client
+                .post(&url)
+                .header("mcp-session-id", &session_id)
+                .json(&payload)
+                .send();
+            async move { request.await.unwrap().json::<Value>().await.unwrap() }
+        };
+        client
+            .post(&url)
+            .header("mcp-session-id", &session_id)
+            .json(&json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .send()
+            .await
+            .unwrap();
+        let rpc = |method: &str, params: Value| json!({"jsonrpc": "2.0", "id": 7, "method": method, "params": params});
+
+        // Two projects whose first tickets share the short id "AB-1"
+        let pool = crate::database::create_pool(
+            &Config {
+                database_path: dir.join("t.db").display().to_string(),
+                ..Config::default()
+            }
+            .database_url(),
+        )
+        .await
+        .unwrap();
+        let fx = crate::test_support::Fixtures::new(&pool);
+        for name in ["alpha-beta", "another-build"] {
+            let project = fx.project(name).create().await.unwrap();
+            fx.ticket(&project, "first").create().await.unwrap();
+        }
+
+        let cases = [
+            (json!({"jsonrpc": "2.0", "id": 1}), INVALID_REQUEST),
+            (rpc("vibe/no_such_method", json!({})), METHOD_NOT_FOUND),
+            (
+                json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call"}),
+                INVALID_PARAMS,
+            ),
+            (
+                rpc(
+                    "tools/call",
+                    json!({"name": "no_such_tool", "arguments": {}}),
+                ),
+                INVALID_PARAMS,
+            ),
+            (
+                rpc(
+                    "tools/call",
+                    json!({"name": "get_ticket", "arguments": {"ticket_id": "AB-1"}}),
+                ),
+                TICKET_CONFLICT,
+            ),
+            (
+                rpc("prompts/get", json!({"name": "no-such-prompt"})),
+                KNOWLEDGE_NOT_FOUND,
+            ),
+            (
+                rpc("resources/read", json!({"uri": "vibe://nothing"})),
+                RESOURCE_NOT_FOUND,
+            ),
+        ];
+        for (payload, code) in cases {
+            let body = call(payload.clone()).await;
+            assert_eq!(body["error"]["code"], code, "{} -> {}", payload, body);
+        }
+
+        // Every tool rejects arguments that are not an object, and an empty object
+        // when it has required arguments, before running
+        let body = call(rpc("tools/list", json!({}))).await;
+        let tools = body["result"]["tools"].as_array().unwrap().clone();
+        for tool in tools {
+            let name = tool["name"].as_str().unwrap();
+            let mut bad = vec![json!(["not", "an", "object"])];
+            if tool["inputSchema"]["required"]
+                .as_array()
+                .is_some_and(|required| !required.is_empty())
+            {
+                bad.push(json!({}));
+            }
+            for arguments in bad {
+                let body = call(rpc(
+                    "tools/call",
+                    json!({"name": name, "arguments": arguments}),
+                ))
+                .await;
+                assert_eq!(
+                    body["error"]["code"], INVALID_PARAMS,
+                    "{} {} -> {}",
+                    name, arguments, body
+                );
+                assert_eq!(body["error"]["data"]["kind"], "validation_failed");
+            }
+        }
+
+        // The table itself is published as a resource
+        let body = call(rpc("resources/read", json!({"uri": "vibe://error-codes"}))).await;
+        let table: Value =
+            serde_json::from_str(body["result"]["contents"][0]["text"].as_str().unwrap()).unwrap();
+        assert!(table["codes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|entry| entry["code"] == TICKET_CONFLICT && entry["domain"] == "ticket"));
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_agent_requests_act_as_the_calling_agent() {
+        let (url, dir) = spawn_server(Config::default()).await;
+        let client = reqwest::Client::new();
+        let response = client
+            .post(&url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": {"name": "identity-test", "version": "1.0"}
+                }
+            }))
+            .send()
+            .await
+            .unwrap();
+        let session_id = response.headers()["mcp-session-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        client
+            .post(&url)
+            .header("mcp-session-id", &session_id)
+            .json(&json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .send()
+            .await
+            .unwrap();
+        // Calls as the worker the token was issued to, or as the coordinator without one
+        let tokens: std::collections::HashMap<_, _> = ["w-1", "w-2"]
+            .into_iter()
+            .map(|w| (w, crate::api::access::issue_worker_token(w, "reviewer")))
+            .collect();
+        let call = |worker: Option<&str>, name: &str, arguments: Value| {
+            let mut request = client
+                .post(&url)
+                .header("mcp-session-id", &session_id)
+                .json(&json!({
+                    "jsonrpc": "2.0",
+                    "id": 2,
+                    "method": "tools/call",
+                    "params": {"name": name, "arguments": arguments}
+                }));
+            if let Some(worker) = worker {
+                request = request.bearer_auth(&tokens[worker]);
+            }
+            async move {
+                let body: Value = request.send().await.unwrap().json().await.unwrap();
+                body
+            }
+        };
+        let text = |body: &Value| -> Value {
+            serde_json::from_str(body["result"]["content"][0]["text"].as_str().unwrap()).unwrap()
+        };
+
+        // The requester is the calling worker, whatever the arguments claim
+        let sent = call(
+            Some("w-1"),
+            "send_agent_request",
+            json!({"target": "w-2", "payload": {"q": "ready?"}, "requester": "coordinator"}),
+        )
+        .await;
+        assert_eq!(sent["result"]["isError"], false, "{}", sent);
+        let correlation_id = text(&sent)["correlation_id"].as_str().unwrap().to_string();
+        let status = call(
+            None,
+            "get_request_status",
+            json!({"correlation_id": correlation_id}),
+        )
+        .await;
+        assert_eq!(text(&status)["requester"], "w-1");
+
+        // Pending requests and acknowledgements are the caller's own
+        let pending = call(None, "get_pending_requests", json!({"agent": "w-2"})).await;
+        assert_eq!(text(&pending)["agent"], "coordinator");
+        assert_eq!(text(&pending)["count"], 0);
+        let pending = call(Some("w-2"), "get_pending_requests", json!({})).await;
+        assert_eq!(text(&pending)["count"], 1);
+        // Identity headers no longer name the caller, with or without a token
+        for token in [None, Some(&tokens["w-1"])] {
+            let mut request = client
+                .post(&url)
+                .header("mcp-session-id", &session_id)
+                .header("x-vibe-worker-id", "w-2")
+                .header("x-vibe-worker-type", "reviewer")
+                .json(&json!({
+                    "jsonrpc": "2.0",
+                    "id": 3,
+                    "method": "tools/call",
+                    "params": {"name": "get_pending_requests", "arguments": {}}
+                }));
+            if let Some(token) = token {
+                request = request.bearer_auth(token);
+            }
+            let body: Value = request.send().await.unwrap().json().await.unwrap();
+            let expected = if token.is_some() {
+                "w-1"
+            } else {
+                "coordinator"
+            };
+            assert_eq!(text(&body)["agent"], expected, "{}", body);
+        }
+        let forged = call(
+            Some("w-1"),
+            "acknowledge_request",
+            json!({"correlation_id": correlation_id, "agent": "w-2"}),
+        )
+        .await;
+        assert_eq!(forged["result"]["isError"], true, "{}", forged);
+        let acknowledged = call(
+            Some("w-2"),
+            "acknowledge_request",
+            json!({"correlation_id": correlation_id}),
+        )
+        .await;
+        assert_eq!(acknowledged["result"]["isError"], false, "{}", acknowledged);
+
+        // Neither the coordinator nor the requester may answer for the target
+        for impostor in [None, Some("w-1")] {
+            let body = call(
+                impostor,
+                "respond_to_request",
+                json!({"correlation_id": correlation_id, "result": "forged"}),
+            )
+            .await;
+            assert_eq!(body["result"]["isError"], true, "{}", body);
+        }
+        let answered = call(
+            Some("w-2"),
+            "respond_to_request",
+            json!({"correlation_id": correlation_id, "result": "yes"}),
+        )
+        .await;
+        assert_eq!(answered["result"]["isError"], false, "{}", answered);
+        let status = call(
+            None,
+            "get_request_status",
+            json!({"correlation_id": correlation_id}),
+        )
+        .await;
+        assert_eq!(text(&status)["response"], "yes");
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_workers_change_only_their_own_tickets() {
+        use crate::database::tickets::Ticket;
+
+        let (url, dir) = spawn_server(Config::default()).await;
+        let client = reqwest::Client::new();
+        let response = client
+            .post(&url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": {"name": "ownership-test", "version": "1.0"}
+                }
+            }))
+            .send()
+            .await
+            .unwrap();
+        let session_id = response.headers()["mcp-session-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        client
+            .post(&url)
+            .header("mcp-session-id", &session_id)
+            .json(&json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .send()
+            .await
+            .unwrap();
+        let token = crate::api::access::issue_worker_token("w-1", "coding");
+        let call = |name: &str, arguments: Value| {
+            let request = client
+                .post(&url)
+                .header("mcp-session-id", &session_id)
+                .bearer_auth(&token)
+                .json(&json!({
+                    "jsonrpc": "2.0",
+                    "id": 2,
+                    "method": "tools/call",
+                    "params": {"name": name, "arguments": arguments}
+                }));
+            async move { request.send().await.unwrap().json::<Value>().await.unwrap() }
+        };
+
+        let pool = crate::database::create_pool(
+            &Config {
+                database_path: dir.join("t.db").display().to_string(),
+                ..Config::default()
+            }
+            .database_url(),
+        )
+        .await
+        .unwrap();
+        let fx = crate::test_support::Fixtures::new(&pool);
+        let project = fx.project("ownership").create().await.unwrap();
+        let own = fx
+            .ticket(&project, "mine")
+            .claimed_by("w-1")
+            .create()
+            .await
+            .unwrap();
+        let other = fx
+            .ticket(&project, "theirs")
+            .claimed_by("w-2")
+            .create()
+            .await
+            .unwrap();
+
+        for (ticket, owned) in [(&own, true), (&other, false)] {
+            let estimated = call(
+                "set_ticket_estimate",
+                json!({"ticket_id": ticket.ticket_id, "estimate_minutes": 30}),
+            )
+            .await;
+            assert_eq!(estimated["result"]["isError"], !owned, "{}", estimated);
+            let closed = call("close_ticket", json!({"ticket_id": ticket.ticket_id})).await;
+            assert_eq!(closed["result"]["isError"], !owned, "{}", closed);
+
+            let ticket = Ticket::get_by_id(&pool, &ticket.ticket_id)
+                .await
+                .unwrap()
+                .unwrap()
+                .ticket;
+            assert_eq!(ticket.estimate_minutes.is_some(), owned);
+            assert_eq!(ticket.is_closed(), owned);
+        }
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    /// A dry run of each destructive tool leaves the database byte-identical, and the real
+    /// call then changes exactly what the dry run reported
+    #[tokio::test]
+    async fn test_dry_run_changes_nothing_and_matches_apply() {
+        use crate::database::{
+            blocks::{Blocker, TicketBlock},
+            tickets::Ticket,
+            worker_types::WorkerType,
+        };
+
+        let (url, dir) = spawn_server(Config::default()).await;
+        let client = reqwest::Client::new();
+        let response = client
+            .post(&url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": {"name": "dry-run-test", "version": "1.0"}
+                }
+            }))
+            .send()
+            .await
+            .unwrap();
+        let session_id = response.headers()["mcp-session-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        client
+            .post(&url)
+            .header("mcp-session-id", &session_id)
+            .json(&json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .send()
+            .await
+            .unwrap();
+        let call = |name: &str, arguments: Value| {
+            let request = client
+                .post(&url)
+                .header("mcp-session-id", &session_id)
+                .json(&json!({
+                    "jsonrpc": "2.0",
+                    "id": 2,
+                    "method": "tools/call",
+                    "params": {"name": name, "arguments": arguments}
+                }))
+                .send();
+            async move {
+                let body: Value = request.await.unwrap().json().await.unwrap();
+                let text = body["result"]["content"][0]["text"]
+                    .as_str()
+                    .unwrap_or_else(|| panic!("tools/call failed: {}", body));
+                serde_json::from_str::<Value>(text).unwrap()
+            }
+        };
+
+        let pool = crate::database::create_pool(
+            &Config {
+                database_path: dir.join("t.db").display().to_string(),
+                ..Config::default()
+            }
+            .database_url(),
+        )
+        .await
+        .unwrap();
+        let fx = crate::test_support::Fixtures::new(&pool);
+        let project = fx.project("dry-run").create().await.unwrap();
+        let coder = fx.worker_type(&project, "coder").create().await.unwrap();
+        let epic = fx
+            .ticket(&project, "Epic")
+            .assigned_to(&coder)
+            .create()
+            .await
+            .unwrap();
+        let story = fx.ticket(&project, "Story").create().await.unwrap();
+        let waits = fx.ticket(&project, "Waits").create().await.unwrap();
+        sqlx::query("UPDATE tickets SET parent_ticket_id = ?1 WHERE ticket_id = ?2")
+            .bind(&epic.ticket_id)
+            .bind(&story.ticket_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        TicketBlock::block(
+            &pool,
+            &waits.ticket_id,
+            "Needs the story",
+            &Blocker::Ticket(story.ticket_id.clone()),
+            "planner",
+        )
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO workers (worker_id, project_id, worker_type, status, queue_name)
+             VALUES ('coder-1', 'dry-run', 'coder', 'active', 'dry-run-coder')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let close = json!({"ticket_id": epic.ticket_id, "cascade": true});
+        let delete_worker_type = json!({"project_id": "dry-run", "worker_type": "coder"});
+        let delete_project = json!({"repository_name": "dry-run"});
+        let dry = |arguments: &Value| {
+            let mut arguments = arguments.clone();
+            arguments["dry_run"] = json!(true);
+            arguments
+        };
+        let snapshot = |name: &str| {
+            let pool = pool.clone();
+            let path = dir.join(name);
+            async move {
+                sqlx::query("VACUUM INTO ?1")
+                    .bind(path.display().to_string())
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+                std::fs::read(&path).unwrap()
+            }
+        };
+
+        // Let the audit record of the session start land before taking the snapshot
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        let before = snapshot("before.db").await;
+        let refused = call(
+            "close_ticket",
+            json!({"ticket_id": epic.ticket_id, "dry_run": true}),
+        )
+        .await;
+        assert!(refused["error"]
+            .as_str()
+            .unwrap()
+            .contains("open child tickets"));
+        let close_plan = call("close_ticket", dry(&close)).await;
+        let worker_type_plan = call("delete_worker_type", dry(&delete_worker_type)).await;
+        let project_plan = call("delete_project", dry(&delete_project)).await;
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        assert!(
+            before == snapshot("after.db").await,
+            "a dry run wrote to the database"
+        );
+
+        for plan in [&close_plan, &worker_type_plan, &project_plan] {
+            assert_eq!(plan["dry_run"], true);
+        }
+        let would_do = &close_plan["would_do"];
+        assert_eq!(would_do["ticket"]["ticket_id"], json!(epic.ticket_id));
+        assert_eq!(
+            would_do["descendants"][0]["ticket_id"],
+            json!(story.ticket_id)
+        );
+        assert_eq!(would_do["unblocks"], json!([waits.ticket_id]));
+        assert_eq!(
+            worker_type_plan["would_do"]["stranded_tickets"],
+            json!([epic.ticket_id])
+        );
+        assert_eq!(
+            worker_type_plan["would_do"]["running_workers"],
+            json!(["coder-1"])
+        );
+        let mut tickets = vec![&epic.ticket_id, &story.ticket_id, &waits.ticket_id];
+        tickets.sort();
+        assert_eq!(project_plan["would_do"]["tickets"], json!(tickets));
+        assert_eq!(project_plan["would_do"]["worker_types"], json!(["coder"]));
+        assert_eq!(project_plan["would_do"]["workers"], json!(["coder-1"]));
+
+        // Closing closes the ticket and its descendants and lifts the blocks on them
+        let closed = call("close_ticket", close.clone()).await;
+        assert_eq!(closed["closed_descendants"], json!([story.ticket_id]));
+        for ticket_id in [&epic.ticket_id, &story.ticket_id] {
+            let ticket = Ticket::get_by_id(&pool, ticket_id).await.unwrap().unwrap();
+            assert!(ticket.ticket.is_closed(), "{} left open", ticket_id);
+        }
+        assert!(!Ticket::get_by_id(&pool, &waits.ticket_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .ticket
+            .is_closed());
+        assert!(TicketBlock::active(&pool, &waits.ticket_id)
+            .await
+            .unwrap()
+            .is_none());
+
+        // Deleting the worker type removes only the worker type
+        let worker_type_plan = call("delete_worker_type", dry(&delete_worker_type)).await;
+        assert_eq!(worker_type_plan["would_do"]["stranded_tickets"], json!([]));
+        call("delete_worker_type", delete_worker_type).await;
+        assert!(WorkerType::get_by_type(&pool, "dry-run", "coder")
+            .await
+            .unwrap()
+            .is_none());
+
+        // Deleting the project removes everything its plan listed
+        let project_plan = call("delete_project", dry(&delete_project)).await;
+        assert_eq!(project_plan["would_do"]["worker_types"], json!([]));
+        call("delete_project", delete_project).await;
+        for (table, column, key) in [
+            ("tickets", "ticket_id", "tickets"),
+            ("workers", "worker_id", "workers"),
+        ] {
+            for id in project_plan["would_do"][key].as_array().unwrap() {
+                let left: i64 = sqlx::query_scalar(&format!(
+                    "SELECT COUNT(*) FROM {} WHERE {} = ?1",
+                    table, column
+                ))
+                .bind(id.as_str().unwrap())
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+                assert_eq!(left, 0, "{} {} left behind", table, id);
+            }
+        }
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_audit_entries_name_the_token_and_mcp_session() {
+        let tokens_dir = std::env::temp_dir().join(format!("vibe-tokens-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&tokens_dir).unwrap();
+        let tokens_path = tokens_dir.join("tokens.json");
+        std::fs::write(
+            &tokens_path,
+            json!({"tokens": [
+                {"name": "root", "token": "admin-token-0123456789", "role": "admin"}
+            ]})
+            .to_string(),
+        )
+        .unwrap();
+        let (url, dir) = spawn_server(Config {
+            api_tokens_path: Some(tokens_path.display().to_string()),
+            ..Config::default()
+        })
+        .await;
+        let api = url.trim_end_matches("/mcp").to_string() + "/api";
+        let client = reqwest::Client::new();
+        let token = "admin-token-0123456789";
+
+        // A REST change is recorded under the token's name
+        let response = client
+            .post(format!("{}/groups", api))
+            .bearer_auth(token)
+            .json(&json!({"group_id": "reviewers"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 201);
+
+        // An MCP tool call is recorded with the session it came in on
+        let response = client
+            .post(&url)
+            .bearer_auth(token)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": {"name": "audit-test", "version": "1.0"}
+                }
+            }))
+            .send()
+            .await
+            .unwrap();
+        let session_id = response.headers()["mcp-session-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        for body in [
+            json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "tools/call",
+                "params": {
+                    "name": "delete_worker_type",
+                    "arguments": {"project_id": "missing", "worker_type": "coding"}
+                }
+            }),
+        ] {
+            client
+                .post(&url)
+                .bearer_auth(token)
+                .header("mcp-session-id", &session_id)
+                .json(&body)
+                .send()
+                .await
+                .unwrap();
+        }
+
+        // Records are written in the background
+        let mut entries = Vec::new();
+        for _ in 0..50 {
+            let page: Value = client
+                .get(format!("{}/audit", api))
+                .bearer_auth(token)
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            entries = page["entries"].as_array().unwrap().clone();
+            if entries.len() >= 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        let entry = |method: &str| {
+            entries
+                .iter()
+                .find(|e| e["method"] == method)
+                .unwrap_or_else(|| panic!("no {} entry in {:?}", method, entries))
+                .clone()
+        };
+        let group = entry("create_agent_group");
+        assert_eq!(group["actor"], "root");
+        assert_eq!(group["session_id"], Value::Null);
+        let tool = entry("delete_worker_type");
+        assert_eq!(tool["actor"], "coordinator");
+        assert_eq!(tool["session_id"], session_id.as_str());
+        assert_eq!(tool["ticket_id"], Value::Null);
+
+        std::fs::remove_dir_all(dir).ok();
+        std::fs::remove_dir_all(tokens_dir).ok();
+    }
+}
@@ -0,0 +1,212 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tracing::{info, warn};
+
+/// A lock file this young without content belongs to an instance still writing it
+const UNWRITTEN_LOCK_GRACE: Duration = Duration::from_secs(10);
+
+/// Contents of the lock file naming the instance serving a database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerLockInfo {
+    pub pid: u32,
+    pub host: String,
+    pub port: u16,
+    pub started_at: String,
+}
+
+/// Held while a server instance uses a database file; removed when dropped. A lock left
+/// by a process that is no longer running is taken over.
+#[derive(Debug)]
+pub struct ServerLock {
+    path: PathBuf,
+}
+
+/// Lock file for a database: `<database>.server.lock`
+pub fn lock_path(database_path: &str) -> PathBuf {
+    let path = database_path
+        .strip_prefix("sqlite:")
+        .unwrap_or(database_path);
+    let path = path.split('?').next().unwrap_or(path);
+    PathBuf::from(format!("{}.server.lock", path))
+}
+
+/// Whether a process with this id is running on this machine
+pub fn process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        let Ok(pid) = libc::pid_t::try_from(pid) else {
+            return false;
+        };
+        if pid <= 0 {
+            return false;
+        }
+        // Signal 0 only checks the target; EPERM means it exists but belongs to another user
+        // SAFETY: kill with signal 0 sends nothing and has no memory effects
+        let signalled = unsafe { libc::kill(pid, 0) } == 0;
+        signalled || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+    // Without a cheap check elsewhere, locks are only advisory
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        false
+    }
+}
+
+impl ServerLock {
+    /// Fail when another live instance serves `database_path`, naming its PID and port
+    pub fn acquire(database_path: &str, host: &str, port: u16) -> Result<ServerLock> {
+        let path = lock_path(database_path);
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let info = ServerLockInfo {
+            pid: std::process::id(),
+            host: host.to_string(),
+            port,
+            started_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        // A second attempt follows removing a stale lock; losing that race to another
+        // instance is reported like any other held lock
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(serde_json::to_string_pretty(&info)?.as_bytes())?;
+                    return Ok(ServerLock { path });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Failed to create lock file {}", path.display()))
+                }
+            }
+
+            match Self::holder(&path) {
+                Some(holder) if process_alive(holder.pid) => bail!(
+                    "Another vibe-ensemble-mcp instance (PID {}, port {}) is already using \
+                     database {}; stop it or pass a different --database-path. If that process \
+                     is not vibe-ensemble-mcp, delete {}",
+                    holder.pid,
+                    holder.port,
+                    database_path,
+                    path.display()
+                ),
+                None if Self::recently_created(&path) => bail!(
+                    "Another vibe-ensemble-mcp instance is starting with database {}",
+                    database_path
+                ),
+                holder => {
+                    if let Some(holder) = holder {
+                        warn!(
+                            "Taking over lock file {} left by PID {}, which is no longer running",
+                            path.display(),
+                            holder.pid
+                        );
+                    }
+                    fs::remove_file(&path).or_else(|e| match e.kind() {
+                        ErrorKind::NotFound => Ok(()),
+                        _ => Err(e),
+                    })?;
+                }
+            }
+        }
+        bail!(
+            "Another vibe-ensemble-mcp instance is starting with database {}",
+            database_path
+        )
+    }
+
+    fn holder(path: &Path) -> Option<ServerLockInfo> {
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn recently_created(path: &Path) -> bool {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age < UNWRITTEN_LOCK_GRACE)
+    }
+}
+
+impl Drop for ServerLock {
+    fn drop(&mut self) {
+        // Only remove the file while it still names this process
+        if Self::holder(&self.path).is_some_and(|holder| holder.pid == std::process::id()) {
+            match fs::remove_file(&self.path) {
+                Ok(()) => info!("Removed server lock {}", self.path.display()),
+                Err(e) => warn!(
+                    "Failed to remove server lock {}: {}",
+                    self.path.display(),
+                    e
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_lock_refuses_live_holder_and_takes_over_stale_one() {
+        let dir = std::env::temp_dir().join(format!("vibe-server-lock-{}", uuid::Uuid::new_v4()));
+        let database = dir.join("db.sqlite").display().to_string();
+        let path = lock_path(&format!("sqlite:{}?mode=rwc", database));
+        assert_eq!(path, lock_path(&database));
+
+        // Left behind by a process that has exited
+        let mut exited = std::process::Command::new("true").spawn().unwrap();
+        let dead_pid = exited.id();
+        exited.wait().unwrap();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            &path,
+            serde_json::to_string(&ServerLockInfo {
+                pid: dead_pid,
+                host: "127.0.0.1".to_string(),
+                port: 3000,
+                started_at: chrono::Utc::now().to_rfc3339(),
+            })
+            .unwrap(),
+        )
+        .unwrap();
+
+        let lock = ServerLock::acquire(&database, "127.0.0.1", 3276).unwrap();
+        let error = ServerLock::acquire(&database, "127.0.0.1", 3277)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(&format!("PID {}, port 3276", std::process::id())));
+
+        drop(lock);
+        assert!(!path.exists());
+        drop(ServerLock::acquire(&database, "127.0.0.1", 3277).unwrap());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_liveness() {
+        assert!(process_alive(std::process::id()));
+        // init runs as root, so for other users this is the EPERM case
+        assert!(process_alive(1));
+        let mut exited = std::process::Command::new("true").spawn().unwrap();
+        let dead_pid = exited.id();
+        exited.wait().unwrap();
+        assert!(!process_alive(dead_pid));
+        // Not valid pids; 0 would address our own process group
+        assert!(!process_alive(0));
+        assert!(!process_alive(u32::MAX));
+    }
+}
@@ -0,0 +1,76 @@
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::{
+    config::Config,
+    database::{sla::SlaAlert, DbPool},
+    events::EventPayload,
+    sse::EventBroadcaster,
+};
+
+/// Periodically escalate tickets that are approaching or past their due date
+pub fn start_sla_monitor(
+    db: DbPool,
+    broadcaster: EventBroadcaster,
+    interval_secs: u64,
+    config: watch::Receiver<Config>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            let warning_minutes = config.borrow().sla_warning_minutes;
+            evaluate(&db, &broadcaster, warning_minutes).await;
+        }
+    });
+}
+
+async fn evaluate(db: &DbPool, broadcaster: &EventBroadcaster, warning_minutes: u64) {
+    match SlaAlert::mark_breaches(db).await {
+        Ok(breaches) => {
+            for alert in &breaches {
+                warn!(
+                    "Ticket {} ({} priority, {}) breached its SLA (due at {})",
+                    alert.ticket_id, alert.priority, alert.state, alert.due_at
+                );
+            }
+            // ticket_sla_breached was queued with each mark
+            if !breaches.is_empty() {
+                broadcaster.notify_outbox();
+            }
+        }
+        Err(e) => warn!("Failed to evaluate SLA breaches: {}", e),
+    }
+
+    if warning_minutes == 0 {
+        return;
+    }
+
+    match SlaAlert::mark_warnings(db, warning_minutes).await {
+        Ok(warnings) => {
+            for alert in warnings {
+                info!(
+                    "Ticket {} is due soon (due at {})",
+                    alert.ticket_id, alert.due_at
+                );
+                broadcaster.broadcast(EventPayload::system_message(
+                    "sla",
+                    &format!(
+                        "Ticket {} '{}' is due at {}",
+                        alert.ticket_id, alert.title, alert.due_at
+                    ),
+                    Some(json!({
+                        "ticket_id": alert.ticket_id,
+                        "project_id": alert.project_id,
+                        "priority": alert.priority,
+                        "state": alert.state,
+                        "due_at": alert.due_at,
+                    })),
+                ));
+            }
+        }
+        Err(e) => warn!("Failed to evaluate SLA warnings: {}", e),
+    }
+}
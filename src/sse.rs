@@ -10,7 +10,7 @@ use axum::{
 use futures::Stream;
 use serde_json::Value;
 use std::{sync::Arc, time::Duration};
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, Notify};
 use tokio::time::interval;
 use tracing::{debug, info, warn};
 
@@ -21,6 +21,9 @@ use crate::{events::EventPayload, mcp::types::JsonRpcRequest, server::AppState};
 pub struct EventBroadcaster {
     sse_sender: Arc<broadcast::Sender<EventPayload>>,
     websocket_sender: Arc<broadcast::Sender<EventPayload>>,
+    webhook_sender: Arc<broadcast::Sender<EventPayload>>,
+    /// Wakes the outbox dispatcher after a transaction queued events
+    outbox_written: Arc<Notify>,
 }
 
 impl Default for EventBroadcaster {
@@ -38,10 +41,13 @@ impl EventBroadcaster {
         let (sse_sender, _) = broadcast::channel::<EventPayload>(Self::BROADCAST_CHANNEL_SIZE);
         let (websocket_sender, _) =
             broadcast::channel::<EventPayload>(Self::BROADCAST_CHANNEL_SIZE);
+        let (webhook_sender, _) = broadcast::channel::<EventPayload>(Self::BROADCAST_CHANNEL_SIZE);
 
         let broadcaster = Self {
             sse_sender: Arc::new(sse_sender),
             websocket_sender: Arc::new(websocket_sender),
+            webhook_sender: Arc::new(webhook_sender),
+            outbox_written: Arc::new(Notify::new()),
         };
 
         // Spawn health monitoring task
@@ -89,8 +95,17 @@ impl EventBroadcaster {
         });
     }
 
-    /// Broadcast a typed event to all connected SSE and WebSocket clients
-    pub fn broadcast(&self, event: EventPayload) {
+    /// Broadcast a typed event to all connected SSE and WebSocket clients and queue it for
+    /// webhooks. Returns the number of clients it was handed to.
+    pub fn broadcast(&self, event: EventPayload) -> usize {
+        // No receiver until the webhook dispatcher has started
+        let _ = self.webhook_sender.send(event.clone());
+        self.broadcast_to_clients(event)
+    }
+
+    /// Broadcast a typed event to connected SSE and WebSocket clients only, for events whose
+    /// webhook deliveries are already queued. Returns the number of clients it was handed to.
+    pub fn broadcast_to_clients(&self, event: EventPayload) -> usize {
         use tracing::{info, trace};
 
         // Log the event being broadcast
@@ -113,7 +128,7 @@ impl EventBroadcaster {
         let sse_result = self.sse_sender.send(event.clone());
         let sse_receiver_count = self.sse_sender.receiver_count();
 
-        if let Err(e) = sse_result {
+        if let Err(e) = &sse_result {
             debug!("SSE broadcast failed: {}", e);
         } else {
             info!(
@@ -126,7 +141,7 @@ impl EventBroadcaster {
         let websocket_result = self.websocket_sender.send(event);
         let websocket_receiver_count = self.websocket_sender.receiver_count();
 
-        if let Err(e) = websocket_result {
+        if let Err(e) = &websocket_result {
             debug!("WebSocket broadcast failed: {}", e);
         } else {
             info!(
@@ -134,6 +149,8 @@ impl EventBroadcaster {
                 websocket_receiver_count
             );
         }
+
+        sse_result.unwrap_or(0) + websocket_result.unwrap_or(0)
     }
 
     /// Create a new receiver for SSE connections
@@ -146,6 +163,21 @@ impl EventBroadcaster {
         self.websocket_sender.subscribe()
     }
 
+    /// Create a new receiver for the webhook dispatcher
+    pub fn subscribe_webhooks(&self) -> broadcast::Receiver<EventPayload> {
+        self.webhook_sender.subscribe()
+    }
+
+    /// Tell the outbox dispatcher that a committed transaction queued events
+    pub fn notify_outbox(&self) {
+        self.outbox_written.notify_one();
+    }
+
+    /// Wait until `notify_outbox` is called
+    pub async fn outbox_notified(&self) {
+        self.outbox_written.notified().await;
+    }
+
     /// Legacy method for backward compatibility
     pub fn subscribe(&self) -> broadcast::Receiver<EventPayload> {
         self.subscribe_sse()
@@ -159,12 +191,11 @@ pub async fn sse_handler(
     let broadcaster = &state.event_broadcaster;
 
     // Create typed events for initialization
-    let host = &state.config.host;
-    let port = state.config.port;
+    let base_url = state.config.http_base_url();
     let system_init_event = EventPayload::system_init();
     let endpoint_discovery_event = EventPayload::endpoint_discovery(
-        &format!("http://{}:{}/messages", host, port),
-        &format!("http://{}:{}/sse", host, port),
+        &format!("{}/messages", base_url),
+        &format!("{}/sse", base_url),
     );
 
     // Create receiver for this SSE connection
@@ -225,8 +256,8 @@ pub async fn sse_message_handler(
         Err(e) => {
             use crate::mcp::constants::JsonRpcEnvelopes;
             let error_response = JsonRpcEnvelopes::error_response(
-                -32700,
-                &format!("Parse error: {}", e),
+                crate::mcp::types::INVALID_REQUEST,
+                &format!("Invalid request: {}", e),
                 payload.get("id").cloned(),
             );
             return Err((StatusCode::BAD_REQUEST, Json(error_response)));
@@ -236,7 +267,7 @@ pub async fn sse_message_handler(
     // Tool name extraction removed (was only used for SSE echo filtering)
 
     // Use stored MCP server and handle the request
-    let response = state.mcp_server.handle_request(&state, request).await;
+    let response = state.mcp_server.handle_request(&state, request, None).await;
 
     debug!("SSE message processed successfully");
 
@@ -246,7 +277,7 @@ pub async fn sse_message_handler(
         Err(e) => {
             use crate::mcp::constants::JsonRpcEnvelopes;
             let error_response = JsonRpcEnvelopes::error_response(
-                -32603,
+                crate::mcp::types::INTERNAL_ERROR,
                 &format!("Internal error: {}", e),
                 response.id,
             );
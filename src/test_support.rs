@@ -0,0 +1,527 @@
+//! Builders for test data, for this crate's tests and for downstream integration tests
+//! (enable the `test-support` feature).
+//!
+//! Entities are created through the regular model APIs and builders take the parent
+//! entities they belong to, so a ticket can only be built for a project that exists and
+//! a conversation only on a ticket that exists. Generated names, comment text and
+//! timestamps depend only on the seed, so the same seed builds the same data.
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use std::sync::Mutex;
+
+use crate::database::{
+    comments::{Comment, CreateCommentRequest, COMMENT_COLUMNS},
+    migrations::run_migrations,
+    projects::{CreateProjectRequest, Project},
+    returning::FetchReturning,
+    tickets::{CreateTicketRequest, Priority, Ticket, TICKET_COLUMNS},
+    worker_types::{CreateWorkerTypeRequest, WorkerType},
+    DbPool,
+};
+
+/// Fixture timestamps count up from here, one minute per created ticket or comment
+const CLOCK_START: &str = "2026-01-01 00:00:00";
+
+const WORDS: &[&str] = &[
+    "checked", "the", "build", "tests", "login", "failing", "fixed", "review", "again", "looks",
+    "good", "schema", "handler", "retry", "merged", "pending",
+];
+
+/// A migrated in-memory database, private to the returned pool. The pool keeps one
+/// connection open so the database lives as long as the pool does.
+///
+/// Connections share the database through the `memdb` VFS rather than a shared cache:
+/// in shared-cache mode FTS5 does not notice index changes made by another connection,
+/// so full-text searches could miss recent writes.
+pub async fn memory_pool() -> Result<DbPool> {
+    let options = SqliteConnectOptions::new()
+        .filename(format!("/fixtures-{}", uuid::Uuid::new_v4()))
+        .vfs("memdb")
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Delete)
+        .foreign_keys(true)
+        .busy_timeout(std::time::Duration::from_secs(5));
+    let pool = SqlitePoolOptions::new()
+        .max_connections(4)
+        .min_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(options)
+        .await?;
+    run_migrations(&pool).await?;
+    Ok(pool)
+}
+
+struct Sequence {
+    rng: u64,
+    minutes: i64,
+}
+
+/// Entry point for building test data in one database
+pub struct Fixtures {
+    pool: DbPool,
+    seed: u64,
+    sequence: Mutex<Sequence>,
+}
+
+impl Fixtures {
+    pub fn new(pool: &DbPool) -> Self {
+        Self::with_seed(pool, 0)
+    }
+
+    pub fn with_seed(pool: &DbPool, seed: u64) -> Self {
+        Self {
+            pool: pool.clone(),
+            seed,
+            sequence: Mutex::new(Sequence {
+                rng: seed,
+                minutes: 0,
+            }),
+        }
+    }
+
+    pub fn pool(&self) -> &DbPool {
+        &self.pool
+    }
+
+    /// Next value of a small LCG, so generated data needs no extra dependencies
+    fn next_random(&self) -> u64 {
+        let mut sequence = self.sequence.lock().unwrap();
+        sequence.rng = sequence
+            .rng
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        sequence.rng >> 33
+    }
+
+    fn next_timestamp(&self) -> String {
+        let mut sequence = self.sequence.lock().unwrap();
+        sequence.minutes += 1;
+        let start = chrono::NaiveDateTime::parse_from_str(CLOCK_START, "%Y-%m-%d %H:%M:%S")
+            .expect("valid fixture clock start");
+        (start + chrono::Duration::minutes(sequence.minutes))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    }
+
+    fn sentence(&self) -> String {
+        let len = 3 + self.next_random() as usize % 5;
+        (0..len)
+            .map(|_| WORDS[self.next_random() as usize % WORDS.len()])
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn project(&self, name: &str) -> ProjectFixture<'_> {
+        ProjectFixture {
+            fixtures: self,
+            name: name.to_string(),
+            path: None,
+            rules: None,
+        }
+    }
+
+    pub fn worker_type<'a>(&'a self, project: &Project, name: &str) -> WorkerTypeFixture<'a> {
+        WorkerTypeFixture {
+            fixtures: self,
+            project_id: project.repository_name.clone(),
+            name: name.to_string(),
+            system_prompt: None,
+            capabilities: Vec::new(),
+        }
+    }
+
+    /// A single-stage ticket in the `planning` stage unless told otherwise
+    pub fn ticket<'a>(&'a self, project: &Project, title: &str) -> TicketFixture<'a> {
+        TicketFixture {
+            fixtures: self,
+            project: project.clone(),
+            title: title.to_string(),
+            description: None,
+            ticket_id: None,
+            execution_plan: vec!["planning".to_string()],
+            priority: None,
+            custom_fields: None,
+            estimate_minutes: None,
+            claimed_by: None,
+            created_at: None,
+        }
+    }
+
+    /// Comments on a ticket alternating between two worker types, as their workers
+    pub fn conversation_between<'a>(
+        &'a self,
+        ticket: &Ticket,
+        first: &WorkerType,
+        second: &WorkerType,
+    ) -> ConversationFixture<'a> {
+        ConversationFixture {
+            fixtures: self,
+            ticket: ticket.clone(),
+            speakers: [first.clone(), second.clone()],
+            count: 1,
+        }
+    }
+}
+
+pub struct ProjectFixture<'a> {
+    fixtures: &'a Fixtures,
+    name: String,
+    path: Option<String>,
+    rules: Option<String>,
+}
+
+impl ProjectFixture<'_> {
+    pub fn path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    pub fn rules(mut self, rules: &str) -> Self {
+        self.rules = Some(rules.to_string());
+        self
+    }
+
+    pub async fn create(self) -> Result<Project> {
+        let fixtures = self.fixtures;
+        let path = self
+            .path
+            .unwrap_or_else(|| format!("/tmp/fixtures-{}/{}", fixtures.seed, self.name));
+        Project::create(
+            &fixtures.pool,
+            CreateProjectRequest {
+                repository_name: self.name,
+                path,
+                short_description: Some(fixtures.sentence()),
+                rules: self.rules,
+                patterns: None,
+            },
+        )
+        .await
+    }
+}
+
+pub struct WorkerTypeFixture<'a> {
+    fixtures: &'a Fixtures,
+    project_id: String,
+    name: String,
+    system_prompt: Option<String>,
+    capabilities: Vec<String>,
+}
+
+impl WorkerTypeFixture<'_> {
+    pub fn system_prompt(mut self, prompt: &str) -> Self {
+        self.system_prompt = Some(prompt.to_string());
+        self
+    }
+
+    pub fn capabilities(mut self, capabilities: &[&str]) -> Self {
+        self.capabilities = capabilities.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    pub async fn create(self) -> Result<WorkerType> {
+        let system_prompt = self
+            .system_prompt
+            .unwrap_or_else(|| format!("You are the {} worker.", self.name));
+        WorkerType::create(
+            &self.fixtures.pool,
+            CreateWorkerTypeRequest {
+                project_id: self.project_id,
+                worker_type: self.name,
+                short_description: None,
+                system_prompt,
+                spawn_overrides: None,
+                capabilities: (!self.capabilities.is_empty())
+                    .then(|| crate::database::worker_types::encode_capabilities(&self.capabilities))
+                    .transpose()?,
+            },
+        )
+        .await
+    }
+}
+
+pub struct TicketFixture<'a> {
+    fixtures: &'a Fixtures,
+    project: Project,
+    title: String,
+    description: Option<String>,
+    ticket_id: Option<String>,
+    execution_plan: Vec<String>,
+    priority: Option<Priority>,
+    custom_fields: Option<String>,
+    estimate_minutes: Option<i64>,
+    claimed_by: Option<String>,
+    created_at: Option<String>,
+}
+
+impl TicketFixture<'_> {
+    /// Use this id instead of generating one from the project prefix
+    pub fn id(mut self, ticket_id: &str) -> Self {
+        self.ticket_id = Some(ticket_id.to_string());
+        self
+    }
+
+    /// Use this description instead of generated text
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn stages(mut self, stages: &[&str]) -> Self {
+        self.execution_plan = stages.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    /// Start the ticket in the worker type's stage; it must belong to the ticket's project
+    pub fn assigned_to(mut self, worker_type: &WorkerType) -> Self {
+        self.execution_plan = vec![worker_type.worker_type.clone()];
+        if worker_type.project_id != self.project.repository_name {
+            // Reported by create(), where errors can be returned
+            self.execution_plan.clear();
+        }
+        self
+    }
+
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Stored as given, so tests can also store malformed JSON
+    pub fn custom_fields_raw(mut self, json: &str) -> Self {
+        self.custom_fields = Some(json.to_string());
+        self
+    }
+
+    pub fn custom_fields(self, fields: Value) -> Self {
+        let json = fields.to_string();
+        self.custom_fields_raw(&json)
+    }
+
+    pub fn estimate(mut self, minutes: i64) -> Self {
+        self.estimate_minutes = Some(minutes);
+        self
+    }
+
+    /// Mark the ticket as being processed by this worker
+    pub fn claimed_by(mut self, worker_id: &str) -> Self {
+        self.claimed_by = Some(worker_id.to_string());
+        self
+    }
+
+    /// Override the fixture clock, e.g. to create tickets with equal timestamps
+    pub fn created_at(mut self, timestamp: &str) -> Self {
+        self.created_at = Some(timestamp.to_string());
+        self
+    }
+
+    pub async fn create(self) -> Result<Ticket> {
+        let fixtures = self.fixtures;
+        if self.execution_plan.is_empty() {
+            bail!(
+                "Ticket '{}' needs a stage of project '{}'",
+                self.title,
+                self.project.repository_name
+            );
+        }
+        let ticket_id = match self.ticket_id {
+            Some(id) => id,
+            None => {
+                let subsystem =
+                    crate::workers::ticket_id::infer_subsystem_from_stages(&self.execution_plan);
+                crate::workers::ticket_id::generate_ticket_id(
+                    &fixtures.pool,
+                    &self.project.project_prefix,
+                    &subsystem,
+                )
+                .await?
+            }
+        };
+
+        let ticket = Ticket::create(
+            &fixtures.pool,
+            CreateTicketRequest {
+                ticket_id,
+                project_id: self.project.repository_name.clone(),
+                title: self.title,
+                description: self.description.unwrap_or_else(|| fixtures.sentence()),
+                execution_plan: self.execution_plan,
+                parent_ticket_id: None,
+                ticket_type: None,
+                dependency_status: None,
+                created_by_worker_id: None,
+                priority: self.priority.map(|p| p.to_string()),
+                custom_fields: self.custom_fields,
+                due_at: None,
+                sla_minutes: None,
+                estimate_minutes: self.estimate_minutes,
+            },
+        )
+        .await?;
+
+        let created_at = self.created_at.unwrap_or_else(|| fixtures.next_timestamp());
+        let ticket = sqlx::query_as::<_, Ticket>(&format!(
+            r#"
+            UPDATE tickets
+            SET created_at = ?2, updated_at = ?2, processing_worker_id = ?3
+            WHERE ticket_id = ?1
+            RETURNING {}
+            "#,
+            TICKET_COLUMNS
+        ))
+        .bind(&ticket.ticket_id)
+        .bind(&created_at)
+        .bind(self.claimed_by)
+        .fetch_returned_one(&fixtures.pool)
+        .await?;
+        // The description comment and history added on creation belong to the same moment
+        sqlx::query("UPDATE comments SET created_at = ?2 WHERE ticket_id = ?1")
+            .bind(&ticket.ticket_id)
+            .bind(&created_at)
+            .execute(&fixtures.pool)
+            .await?;
+        sqlx::query("UPDATE ticket_history SET created_at = ?2 WHERE ticket_id = ?1")
+            .bind(&ticket.ticket_id)
+            .bind(&created_at)
+            .execute(&fixtures.pool)
+            .await?;
+        Ok(ticket)
+    }
+}
+
+pub struct ConversationFixture<'a> {
+    fixtures: &'a Fixtures,
+    ticket: Ticket,
+    speakers: [WorkerType; 2],
+    count: usize,
+}
+
+impl ConversationFixture<'_> {
+    /// Number of comments, alternating speakers starting with the first
+    pub fn messages(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Comments in the order they were posted
+    pub async fn create(self) -> Result<Vec<Comment>> {
+        let fixtures = self.fixtures;
+        for speaker in &self.speakers {
+            if speaker.project_id != self.ticket.project_id {
+                bail!(
+                    "Worker type '{}' is not part of ticket {}'s project",
+                    speaker.worker_type,
+                    self.ticket.ticket_id
+                );
+            }
+        }
+
+        let mut comments = Vec::with_capacity(self.count);
+        for i in 0..self.count {
+            let speaker = &self.speakers[i % 2];
+            let comment = Comment::create_from_request(
+                &fixtures.pool,
+                CreateCommentRequest {
+                    ticket_id: self.ticket.ticket_id.clone(),
+                    worker_type: speaker.worker_type.clone(),
+                    worker_id: format!(
+                        "{}:{}:{}",
+                        speaker.project_id, speaker.worker_type, self.ticket.ticket_id
+                    ),
+                    stage_number: 1,
+                    content: fixtures.sentence(),
+                    reply_to_comment_id: None,
+                },
+            )
+            .await?;
+            let comment = sqlx::query_as::<_, Comment>(&format!(
+                "UPDATE comments SET created_at = ?2 WHERE id = ?1 RETURNING {}",
+                COMMENT_COLUMNS
+            ))
+            .bind(comment.id)
+            .bind(fixtures.next_timestamp())
+            .fetch_returned_one(&fixtures.pool)
+            .await?
+            .opened()?;
+            comments.push(comment);
+        }
+        Ok(comments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn build(seed: u64) -> (Ticket, Vec<Comment>) {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::with_seed(&pool, seed);
+        let project = fx.project("fixture-demo").create().await.unwrap();
+        let reviewer = fx.worker_type(&project, "reviewer").create().await.unwrap();
+        let coder = fx.worker_type(&project, "coder").create().await.unwrap();
+        let ticket = fx
+            .ticket(&project, "fix login")
+            .assigned_to(&reviewer)
+            .claimed_by("w1")
+            .create()
+            .await
+            .unwrap();
+        let comments = fx
+            .conversation_between(&ticket, &reviewer, &coder)
+            .messages(5)
+            .create()
+            .await
+            .unwrap();
+        pool.close().await;
+        (ticket, comments)
+    }
+
+    #[tokio::test]
+    async fn test_fixtures_are_consistent_and_deterministic() {
+        let (ticket, comments) = build(7).await;
+        assert_eq!(ticket.ticket_id, "FD-CORE-001");
+        assert_eq!(ticket.current_stage, "reviewer");
+        assert_eq!(ticket.processing_worker_id.as_deref(), Some("w1"));
+        assert_eq!(ticket.created_at, "2026-01-01 00:01:00");
+        assert_eq!(comments.len(), 5);
+        assert_eq!(comments[0].worker_type.as_deref(), Some("reviewer"));
+        assert_eq!(comments[1].worker_type.as_deref(), Some("coder"));
+        assert!(comments
+            .windows(2)
+            .all(|w| w[0].created_at < w[1].created_at));
+
+        let content = |comments: &[Comment]| {
+            comments
+                .iter()
+                .map(|c| c.content.clone())
+                .collect::<Vec<_>>()
+        };
+        let (_, same_seed) = build(7).await;
+        let (_, other_seed) = build(8).await;
+        assert_eq!(content(&comments), content(&same_seed));
+        assert_ne!(content(&comments), content(&other_seed));
+
+        // Parents from another project are refused instead of leaving dangling rows
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let one = fx.project("one").create().await.unwrap();
+        let two = fx.project("two").create().await.unwrap();
+        let outsider = fx.worker_type(&two, "coder").create().await.unwrap();
+        assert!(fx
+            .ticket(&one, "x")
+            .assigned_to(&outsider)
+            .create()
+            .await
+            .is_err());
+        let ticket = fx.ticket(&one, "x").create().await.unwrap();
+        assert!(fx
+            .conversation_between(&ticket, &outsider, &outsider)
+            .create()
+            .await
+            .is_err());
+        pool.close().await;
+    }
+}
@@ -0,0 +1,684 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::{error, info};
+use utoipa::ToSchema;
+
+use crate::{
+    crypto,
+    database::{
+        projects::Project,
+        quotas::{self, Quota},
+        tags::canonical_name,
+        tickets::TicketState,
+        worker_types::WorkerType,
+        DbPool,
+    },
+    workers::ticket_id::{generate_ticket_id_tx, infer_subsystem_from_stages},
+};
+
+/// Rows created per transaction; a failing batch is rolled back without undoing earlier ones
+pub const IMPORT_BATCH_SIZE: usize = 100;
+
+/// Columns read from the header row; matched case-insensitively, with spaces, dashes and
+/// underscores treated alike. Only `title` is required.
+const COLUMNS: &[(&str, &[&str])] = &[
+    ("title", &["title", "summary"]),
+    ("description", &["description", "body"]),
+    ("priority", &["priority"]),
+    ("status", &["status", "state"]),
+    ("tags", &["tags", "labels"]),
+    ("assignee", &["assignee", "assignee_name"]),
+    ("created_at", &["created_at", "created"]),
+];
+
+/// What to do with a row whose title matches an existing ticket or an earlier row
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    #[default]
+    Skip,
+    Create,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Validate every row without writing anything
+    pub dry_run: bool,
+    pub duplicates: DuplicatePolicy,
+    /// Unknown tags are created; when false they are a row error
+    pub create_missing_tags: bool,
+}
+
+/// A CSV file split into rows keyed by column. Rows are numbered as a spreadsheet shows
+/// them: the header is row 1.
+#[derive(Debug, Clone)]
+pub struct ImportFile {
+    rows: Vec<(usize, HashMap<&'static str, String>)>,
+    ignored_columns: Vec<String>,
+}
+
+/// A valid row, as it is (or would be) created
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportedTicket {
+    pub row: usize,
+    pub title: String,
+    pub description: String,
+    pub priority: &'static str,
+    pub state: TicketState,
+    pub tags: Vec<String>,
+    /// Worker type the assignee resolved to; the ticket starts in its stage
+    pub worker_type: Option<String>,
+    /// SQLite datetime (UTC); None for the import time
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RowIssue {
+    pub row: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<&'static str>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SkippedDuplicate {
+    pub row: usize,
+    pub title: String,
+    /// Ticket id, or `row N` for an earlier row of the same file
+    pub duplicate_of: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct ImportSummary {
+    pub rows: usize,
+    pub valid: usize,
+    pub invalid: usize,
+    pub duplicates_skipped: usize,
+    pub created: usize,
+    /// Valid rows whose batch failed to commit
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub summary: ImportSummary,
+    /// Row number → id of the ticket created from it
+    pub created: BTreeMap<usize, String>,
+    /// Rows a dry run would create
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub planned: Vec<ImportedTicket>,
+    pub errors: Vec<RowIssue>,
+    pub warnings: Vec<RowIssue>,
+    pub skipped: Vec<SkippedDuplicate>,
+}
+
+/// Records of an RFC 4180 document: fields separated by commas, quoted fields may hold
+/// commas, newlines and doubled quotes
+pub fn parse_csv(text: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut quote_line = 0;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => {
+                in_quotes = true;
+                quote_line = line;
+            }
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                line += 1;
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        bail!("Unterminated quoted field starting on line {}", quote_line);
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+fn column_key(header: &str) -> String {
+    header.trim().to_lowercase().replace([' ', '-'], "_")
+}
+
+impl ImportFile {
+    /// Read the header and rows, refusing files with no title column or more than `max_rows`
+    /// data rows. Blank rows are ignored.
+    pub fn parse(text: &str, max_rows: usize) -> Result<ImportFile> {
+        let mut records = parse_csv(text)?.into_iter();
+        let Some(header) = records.next() else {
+            bail!("The file is empty; expected a header row with at least a 'title' column");
+        };
+
+        let mut ignored_columns = Vec::new();
+        let columns: Vec<Option<&'static str>> = header
+            .iter()
+            .map(|name| {
+                let key = column_key(name);
+                let column = COLUMNS
+                    .iter()
+                    .find(|(_, aliases)| aliases.contains(&key.as_str()))
+                    .map(|(column, _)| *column);
+                if column.is_none() && !key.is_empty() {
+                    ignored_columns.push(name.trim().to_string());
+                }
+                column
+            })
+            .collect();
+        if !columns.contains(&Some("title")) {
+            bail!(
+                "The header has no 'title' column; recognized columns are: {}",
+                COLUMNS
+                    .iter()
+                    .map(|(column, _)| *column)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+
+        let rows: Vec<(usize, HashMap<&'static str, String>)> = records
+            .enumerate()
+            .filter(|(_, record)| record.iter().any(|field| !field.trim().is_empty()))
+            .map(|(index, record)| {
+                let fields = columns
+                    .iter()
+                    .zip(record)
+                    .filter_map(|(column, value)| Some(((*column)?, value.trim().to_string())))
+                    .collect();
+                (index + 2, fields)
+            })
+            .collect();
+        if rows.len() > max_rows {
+            bail!(
+                "The file has {} rows; at most {} can be imported at once",
+                rows.len(),
+                max_rows
+            );
+        }
+        Ok(ImportFile {
+            rows,
+            ignored_columns,
+        })
+    }
+}
+
+fn parse_priority(value: &str) -> Option<&'static str> {
+    match value.to_lowercase().as_str() {
+        "" | "medium" | "normal" => Some("medium"),
+        "low" | "lowest" | "minor" | "trivial" => Some("low"),
+        "high" | "major" => Some("high"),
+        "urgent" | "critical" | "highest" | "blocker" => Some("urgent"),
+        _ => None,
+    }
+}
+
+fn parse_status(value: &str) -> Option<TicketState> {
+    match value.to_lowercase().replace(['_', '-'], " ").trim() {
+        "" | "open" | "new" | "to do" | "todo" | "backlog" | "in progress" | "reopened" => {
+            Some(TicketState::Open)
+        }
+        "closed" | "done" | "resolved" | "fixed" | "complete" | "completed" | "won't fix"
+        | "wontfix" => Some(TicketState::Closed),
+        "on hold" | "blocked" | "paused" => Some(TicketState::OnHold),
+        _ => None,
+    }
+}
+
+/// An RFC 3339 timestamp, `YYYY-MM-DD HH:MM:SS` (UTC) or a date, as a SQLite datetime
+fn parse_created_at(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
+        return Some(parsed.with_timezone(&Utc));
+    }
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .map(|naive| naive.and_utc())
+}
+
+/// Validate `file` against the project and, unless this is a dry run, create its valid
+/// rows in batches of [`IMPORT_BATCH_SIZE`]. Imported tickets are not queued for workers.
+pub async fn import_tickets(
+    pool: &DbPool,
+    project: &Project,
+    file: ImportFile,
+    options: &ImportOptions,
+) -> Result<ImportReport> {
+    let mut report = ImportReport {
+        dry_run: options.dry_run,
+        summary: ImportSummary {
+            rows: file.rows.len(),
+            ..Default::default()
+        },
+        created: BTreeMap::new(),
+        planned: Vec::new(),
+        errors: Vec::new(),
+        warnings: Vec::new(),
+        skipped: Vec::new(),
+    };
+    if !file.ignored_columns.is_empty() {
+        report.warnings.push(RowIssue {
+            row: 1,
+            column: None,
+            message: format!("Ignored columns: {}", file.ignored_columns.join(", ")),
+        });
+    }
+
+    let worker_types: HashMap<String, String> =
+        WorkerType::list_by_project(pool, Some(&project.repository_name))
+            .await?
+            .into_iter()
+            .map(|wt| (wt.worker_type.to_lowercase(), wt.worker_type))
+            .collect();
+    let mut titles: HashMap<String, String> = sqlx::query_as::<_, (String, String)>(
+        "SELECT title, ticket_id FROM tickets WHERE project_id = ?1 ORDER BY created_at ASC",
+    )
+    .bind(&project.repository_name)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .rev()
+    .collect();
+    let known_tags: HashSet<String> = sqlx::query_scalar("SELECT name FROM tags")
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+
+    let now = Utc::now();
+    let mut valid = Vec::new();
+    for (row, fields) in file.rows {
+        let field = |column: &str| fields.get(column).map(String::as_str).unwrap_or("");
+        let mut errors = Vec::new();
+        let mut error = |column: &'static str, message: String| {
+            errors.push(RowIssue {
+                row,
+                column: Some(column),
+                message,
+            })
+        };
+
+        let title = field("title").to_string();
+        if title.is_empty() {
+            error("title", "Title is empty".to_string());
+        }
+        let priority = parse_priority(field("priority"));
+        if priority.is_none() {
+            error(
+                "priority",
+                format!(
+                    "Unknown priority '{}'; expected low, medium, high or urgent",
+                    field("priority")
+                ),
+            );
+        }
+        let state = parse_status(field("status"));
+        if state.is_none() {
+            error(
+                "status",
+                format!(
+                    "Unknown status '{}'; expected open, closed or on hold",
+                    field("status")
+                ),
+            );
+        }
+        let created_at = match field("created_at") {
+            "" => None,
+            value => match parse_created_at(value) {
+                Some(created_at) if created_at > now => {
+                    error(
+                        "created_at",
+                        format!("created_at '{}' is in the future", value),
+                    );
+                    None
+                }
+                Some(created_at) => Some(created_at.format("%Y-%m-%d %H:%M:%S").to_string()),
+                None => {
+                    error(
+                        "created_at",
+                        format!(
+                            "Invalid created_at '{}': expected an RFC 3339 timestamp or YYYY-MM-DD",
+                            value
+                        ),
+                    );
+                    None
+                }
+            },
+        };
+        let mut tags = Vec::new();
+        for name in field("tags").split([',', ';', '|']) {
+            if name.trim().is_empty() {
+                continue;
+            }
+            let Ok(name) = canonical_name(name) else {
+                continue;
+            };
+            if !options.create_missing_tags && !known_tags.contains(&name) {
+                error("tags", format!("Unknown tag '{}'", name));
+            } else if !tags.contains(&name) {
+                tags.push(name);
+            }
+        }
+
+        if !errors.is_empty() {
+            report.summary.invalid += 1;
+            report.errors.append(&mut errors);
+            continue;
+        }
+
+        let assignee = field("assignee");
+        let worker_type = worker_types.get(&assignee.to_lowercase()).cloned();
+        if worker_type.is_none() && !assignee.is_empty() {
+            report.warnings.push(RowIssue {
+                row,
+                column: Some("assignee"),
+                message: format!(
+                    "Assignee '{}' is not a worker type of project '{}'; the ticket is left unassigned",
+                    assignee, project.repository_name
+                ),
+            });
+        }
+
+        if let Some(duplicate_of) = titles.get(&title) {
+            match options.duplicates {
+                DuplicatePolicy::Skip => {
+                    report.summary.duplicates_skipped += 1;
+                    report.skipped.push(SkippedDuplicate {
+                        row,
+                        title,
+                        duplicate_of: duplicate_of.clone(),
+                    });
+                    continue;
+                }
+                DuplicatePolicy::Create => report.warnings.push(RowIssue {
+                    row,
+                    column: Some("title"),
+                    message: format!("Same title as {}; created anyway", duplicate_of),
+                }),
+            }
+        }
+        titles.insert(title.clone(), format!("row {}", row));
+
+        valid.push(ImportedTicket {
+            row,
+            title,
+            description: field("description").to_string(),
+            // Both are Some once the row has no errors
+            priority: priority.unwrap_or("medium"),
+            state: state.unwrap_or(TicketState::Open),
+            tags,
+            worker_type,
+            created_at,
+        });
+    }
+    report.summary.valid = valid.len();
+
+    if options.dry_run {
+        report.planned = valid;
+        return Ok(report);
+    }
+
+    for batch in valid.chunks(IMPORT_BATCH_SIZE) {
+        match create_batch(pool, project, batch).await {
+            Ok(created) => {
+                report.summary.created += created.len();
+                report.created.extend(created);
+            }
+            Err(e) => {
+                error!(
+                    "Failed to import rows {}-{} into project '{}': {:?}",
+                    batch[0].row,
+                    batch[batch.len() - 1].row,
+                    project.repository_name,
+                    e
+                );
+                report.summary.failed += batch.len();
+                report.errors.extend(batch.iter().map(|ticket| RowIssue {
+                    row: ticket.row,
+                    column: None,
+                    message: format!("Not imported; its batch was rolled back: {}", e),
+                }));
+            }
+        }
+    }
+    info!(
+        "Imported {} of {} rows into project '{}'",
+        report.summary.created, report.summary.rows, project.repository_name
+    );
+    Ok(report)
+}
+
+async fn create_batch(
+    pool: &DbPool,
+    project: &Project,
+    batch: &[ImportedTicket],
+) -> Result<Vec<(usize, String)>> {
+    let mut tx = pool.begin().await?;
+    let mut created = Vec::with_capacity(batch.len());
+    for ticket in batch {
+        let ticket_id = create_ticket(&mut tx, project, ticket).await?;
+        created.push((ticket.row, ticket_id));
+    }
+    tx.commit().await?;
+    Ok(created)
+}
+
+async fn create_ticket(
+    conn: &mut SqliteConnection,
+    project: &Project,
+    ticket: &ImportedTicket,
+) -> Result<String> {
+    let execution_plan: Vec<String> = ticket.worker_type.iter().cloned().collect();
+    let ticket_id = generate_ticket_id_tx(
+        conn,
+        &project.project_prefix,
+        &infer_subsystem_from_stages(&execution_plan),
+    )
+    .await?;
+    let initial_stage = ticket.worker_type.as_deref().unwrap_or("planning");
+    if ticket.state != TicketState::Closed {
+        quotas::check(conn, &project.repository_name, Quota::OpenTickets, 1).await?;
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO tickets (
+            ticket_id, project_id, title, execution_plan, current_stage, state, priority,
+            dependency_status, ticket_type, rules_version, patterns_version, created_at,
+            updated_at, closed_at
+        )
+        VALUES (
+            ?1, ?2, ?3, ?4, ?5, ?6, ?7, 'ready', 'task', ?8, ?9,
+            COALESCE(?10, datetime('now')), COALESCE(?10, datetime('now')),
+            CASE WHEN ?6 = 'closed' THEN COALESCE(?10, datetime('now')) END
+        )
+        "#,
+    )
+    .bind(&ticket_id)
+    .bind(&project.repository_name)
+    .bind(&ticket.title)
+    .bind(serde_json::to_string(&execution_plan)?)
+    .bind(initial_stage)
+    .bind(ticket.state.as_sql_value())
+    .bind(ticket.priority)
+    .bind(project.rules_version.unwrap_or(1))
+    .bind(project.patterns_version.unwrap_or(1))
+    .bind(&ticket.created_at)
+    .execute(&mut *conn)
+    .await?;
+
+    // The history row starts when the ticket did, so burndowns place it correctly
+    sqlx::query(
+        "UPDATE ticket_history SET created_at = COALESCE(?2, created_at), note = ?3
+         WHERE ticket_id = ?1 AND change = 'created'",
+    )
+    .bind(&ticket_id)
+    .bind(&ticket.created_at)
+    .bind(format!("Imported from CSV row {}", ticket.row))
+    .execute(&mut *conn)
+    .await?;
+
+    if !ticket.description.is_empty() {
+        let (description, key_id) = crypto::seal(&ticket.description)?;
+        sqlx::query(
+            r#"
+            INSERT INTO comments (ticket_id, worker_type, worker_id, stage_number, content, created_at, content_key_id)
+            VALUES (?1, 'coordinator', 'coordinator', 0, ?2, COALESCE(?3, datetime('now')), ?4)
+            "#,
+        )
+        .bind(&ticket_id)
+        .bind(&description)
+        .bind(&ticket.created_at)
+        .bind(key_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    for tag in &ticket.tags {
+        sqlx::query("INSERT OR IGNORE INTO tags (name) VALUES (?1)")
+            .bind(tag)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query(
+            "INSERT OR IGNORE INTO ticket_tags (ticket_id, tag_id)
+             SELECT ?1, id FROM tags WHERE name = ?2",
+        )
+        .bind(&ticket_id)
+        .bind(tag)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(ticket_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{tags::Tag, tickets::Ticket};
+    use crate::test_support::{memory_pool, Fixtures};
+
+    #[test]
+    fn test_parse_csv_quoting() {
+        let records = parse_csv(
+            "\u{feff}title,description\r\n\"Comma, inc\",\"Line one\nsaid \"\"hi\"\"\"\n\nlast,",
+        )
+        .unwrap();
+        assert_eq!(
+            records,
+            vec![
+                vec!["title", "description"],
+                vec!["Comma, inc", "Line one\nsaid \"hi\""],
+                vec![""],
+                vec!["last", ""],
+            ]
+        );
+        assert!(parse_csv("title\n\"open").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_then_commit() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("imports").create().await.unwrap();
+        fx.worker_type(&project, "coding").create().await.unwrap();
+        let existing = fx.ticket(&project, "Already here").create().await.unwrap();
+
+        let csv = "Title,Description,Priority,Status,Tags,Assignee Name,Created At,Votes\n\
+                   Login page,Build it,High,In Progress,\"ui, auth\",Coding,2024-03-01,3\n\
+                   Already here,,,,,,,\n\
+                   Old bug,,Minor,Done,,Someone,2024-01-15T10:00:00Z,\n\
+                   ,No title,,,,,,\n\
+                   Weird,,Meh,Sideways,,,,\n";
+        let file = || ImportFile::parse(csv, 10).unwrap();
+        let mut options = ImportOptions {
+            dry_run: true,
+            duplicates: DuplicatePolicy::Skip,
+            create_missing_tags: true,
+        };
+
+        let dry = import_tickets(&pool, &project, file(), &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            (
+                dry.summary.valid,
+                dry.summary.invalid,
+                dry.summary.duplicates_skipped
+            ),
+            (2, 2, 1)
+        );
+        assert_eq!(dry.skipped[0].duplicate_of, existing.ticket_id);
+        assert_eq!(
+            dry.errors.iter().map(|e| e.row).collect::<Vec<_>>(),
+            [5, 6, 6]
+        );
+        assert!(dry.warnings.iter().any(|w| w.column == Some("assignee")));
+        assert!(dry.created.is_empty());
+        assert_eq!(Tag::list(&pool).await.unwrap().len(), 0);
+
+        options.dry_run = false;
+        let report = import_tickets(&pool, &project, file(), &options)
+            .await
+            .unwrap();
+        assert_eq!(report.summary.created, 2);
+        let login = Ticket::get_by_id(&pool, &report.created[&2])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (
+                login.ticket.current_stage.as_str(),
+                login.ticket.priority.as_str()
+            ),
+            ("coding", "high")
+        );
+        assert_eq!(login.ticket.created_at, "2024-03-01 00:00:00");
+        assert_eq!(login.tags, ["auth", "ui"]);
+        assert_eq!(login.comments[0].content, "Build it");
+        let old = Ticket::get_by_id(&pool, &report.created[&4])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(old.ticket.state, "closed");
+        assert_eq!(old.ticket.current_stage, "planning");
+
+        assert!(ImportFile::parse("description\nno title column", 10).is_err());
+        assert!(ImportFile::parse(csv, 3).is_err());
+        pool.close().await;
+    }
+}
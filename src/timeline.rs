@@ -0,0 +1,40 @@
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::database::{projects::Project, ticket_timeline, DbPool};
+
+/// Periodically snapshot the ticket state of projects with enough new history, so as-of
+/// reconstructions replay only the rows since the nearest snapshot
+pub fn start_snapshot_monitor(db: DbPool, interval_secs: u64) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            evaluate(&db).await;
+        }
+    });
+}
+
+async fn evaluate(db: &DbPool) {
+    let projects = match Project::list_all(db).await {
+        Ok(projects) => projects,
+        Err(e) => {
+            warn!("Failed to list projects for ticket snapshots: {}", e);
+            return;
+        }
+    };
+
+    for project in projects {
+        match ticket_timeline::snapshot_if_due(db, &project.repository_name).await {
+            Ok(Some(id)) => debug!(
+                "Snapshotted tickets of '{}' (snapshot {})",
+                project.repository_name, id
+            ),
+            Ok(None) => {}
+            Err(e) => warn!(
+                "Failed to snapshot tickets of '{}': {}",
+                project.repository_name, e
+            ),
+        }
+    }
+}
@@ -1,27 +1,81 @@
-use anyhow::Result;
-use serde::Deserialize;
+use anyhow::{anyhow, bail, Result};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::time::Duration;
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 
-use crate::{database::DbPool, sse::EventBroadcaster};
+use crate::{
+    database::{update_checks::UpdateCheck, DbPool},
+    sse::EventBroadcaster,
+};
 
-const GITHUB_API_URL: &str = "https://api.github.com/repos/siy/vibe-ensemble-mcp/releases/latest";
+const GITHUB_API_URL: &str =
+    "https://api.github.com/repos/siy/vibe-ensemble-mcp/releases?per_page=30";
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Release asset describing the release, and the asset with its SHA-256
+const METADATA_ASSET: &str = "release.json";
+const METADATA_CHECKSUM_ASSET: &str = "release.json.sha256";
+
+/// Which releases count as updates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReleaseChannel {
+    /// Only full releases
+    Stable,
+    /// Pre-releases (e.g. 1.2.0-beta.1) as well
+    Beta,
+}
+
+impl ReleaseChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReleaseChannel::Stable => "stable",
+            ReleaseChannel::Beta => "beta",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct GithubRelease {
     tag_name: String,
     html_url: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseMetadata {
+    version: String,
+}
+
+/// A verified release newer than the running version
+#[derive(Debug, Clone, PartialEq)]
+struct AvailableUpdate {
+    version: String,
+    release_url: String,
 }
 
 pub struct UpdateService {
     check_interval: Duration,
+    channel: ReleaseChannel,
     http_client: reqwest::Client,
 }
 
 impl UpdateService {
-    pub fn new(check_interval_hours: u64) -> Self {
+    pub fn new(check_interval_hours: u64, channel: ReleaseChannel) -> Self {
         let http_client = reqwest::Client::builder()
             .timeout(Duration::from_secs(30))
             .user_agent(format!("vibe-ensemble-mcp/{}", CURRENT_VERSION))
@@ -30,13 +84,17 @@ impl UpdateService {
 
         Self {
             check_interval: Duration::from_secs(check_interval_hours * 3600),
+            channel,
             http_client,
         }
     }
 
-    /// Check for updates and return the latest version info if available
-    async fn check_for_updates(&self) -> Result<Option<(String, String)>> {
-        debug!("Checking for updates from GitHub API");
+    /// Check for updates and return the newest verified release if there is one
+    async fn check_for_updates(&self) -> Result<Option<AvailableUpdate>> {
+        debug!(
+            "Checking for updates on the {} channel from GitHub API",
+            self.channel.as_str()
+        );
 
         let response = self.http_client.get(GITHUB_API_URL).send().await?;
 
@@ -48,52 +106,48 @@ impl UpdateService {
             return Ok(None);
         }
 
-        let release: GithubRelease = response.json().await?;
+        let releases: Vec<GithubRelease> = response.json().await?;
+        let current_version = Version::parse(CURRENT_VERSION)?;
 
-        // Remove 'v' prefix if present for comparison
-        let latest_version = release.tag_name.trim_start_matches('v');
-        let current_version = CURRENT_VERSION;
+        let Some((release, latest_version)) =
+            select_update(&current_version, &releases, self.channel)
+        else {
+            debug!("Already on latest version");
+            return Ok(None);
+        };
 
-        debug!(
-            "Current version: {}, Latest version: {}",
+        // Never recommend a release whose metadata does not match its published checksum
+        self.verify_release(release, &latest_version).await?;
+
+        info!(
+            "Update available: {} -> {}",
             current_version, latest_version
         );
-
-        // Simple version comparison - if they're different, assume update available
-        // In production, should use semver crate for proper comparison
-        if latest_version != current_version
-            && Self::is_newer_version(current_version, latest_version)
-        {
-            info!(
-                "Update available: {} -> {}",
-                current_version, latest_version
-            );
-            Ok(Some((latest_version.to_string(), release.html_url)))
-        } else {
-            debug!("Already on latest version");
-            Ok(None)
-        }
+        Ok(Some(AvailableUpdate {
+            version: latest_version.to_string(),
+            release_url: release.html_url.clone(),
+        }))
     }
 
-    /// Simple version comparison - returns true if latest > current
-    fn is_newer_version(current: &str, latest: &str) -> bool {
-        // Parse versions as tuples of (major, minor, patch)
-        let parse_version = |v: &str| -> Option<(u32, u32, u32)> {
-            let parts: Vec<&str> = v.split('.').collect();
-            if parts.len() != 3 {
-                return None;
-            }
-            Some((
-                parts[0].parse().ok()?,
-                parts[1].parse().ok()?,
-                parts[2].parse().ok()?,
-            ))
+    async fn verify_release(&self, release: &GithubRelease, version: &Version) -> Result<()> {
+        let asset_url = |name: &str| {
+            release
+                .assets
+                .iter()
+                .find(|asset| asset.name == name)
+                .map(|asset| asset.browser_download_url.as_str())
+                .ok_or_else(|| anyhow!("Release {} has no {} asset", release.tag_name, name))
         };
+        let metadata = self.download(asset_url(METADATA_ASSET)?).await?;
+        let checksum = self.download(asset_url(METADATA_CHECKSUM_ASSET)?).await?;
 
-        match (parse_version(current), parse_version(latest)) {
-            (Some(curr), Some(lat)) => lat > curr,
-            _ => false,
-        }
+        verify_metadata(&metadata, &String::from_utf8_lossy(&checksum), version)
+            .map_err(|e| anyhow!("Release {} failed verification: {}", release.tag_name, e))
+    }
+
+    async fn download(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self.http_client.get(url).send().await?.error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
     }
 
     /// Start periodic update checks in a background task
@@ -103,14 +157,28 @@ impl UpdateService {
         broadcaster: EventBroadcaster,
     ) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
-            // Perform immediate check on startup
-            if let Err(e) = self.perform_check(&db, &broadcaster).await {
-                error!("Initial update check failed: {}", e);
-            }
+            // A restart within the interval reuses the last result instead of checking again
+            let mut wait = match UpdateCheck::get(&db, self.channel.as_str()).await {
+                Ok(Some(cached)) if (cached.age_secs as u64) < self.check_interval.as_secs() => {
+                    debug!(
+                        "Using cached update check from {} ({} channel)",
+                        cached.checked_at, cached.channel
+                    );
+                    self.announce_cached(&db, &broadcaster, &cached).await;
+                    self.check_interval - Duration::from_secs(cached.age_secs.max(0) as u64)
+                }
+                _ => {
+                    if let Err(e) = self.perform_check(&db, &broadcaster).await {
+                        error!("Initial update check failed: {}", e);
+                    }
+                    self.check_interval
+                }
+            };
 
             // Then check periodically
             loop {
-                sleep(self.check_interval).await;
+                sleep(wait).await;
+                wait = self.check_interval;
 
                 if let Err(e) = self.perform_check(&db, &broadcaster).await {
                     error!("Periodic update check failed: {}", e);
@@ -119,6 +187,33 @@ impl UpdateService {
         })
     }
 
+    /// Re-emit a cached update, unless this binary has since been upgraded past it
+    async fn announce_cached(
+        &self,
+        db: &DbPool,
+        broadcaster: &EventBroadcaster,
+        cached: &UpdateCheck,
+    ) {
+        let (Some(version), Some(release_url)) = (&cached.latest_version, &cached.release_url)
+        else {
+            return;
+        };
+        let newer = match (Version::parse(version), Version::parse(CURRENT_VERSION)) {
+            (Ok(cached), Ok(current)) => cached > current,
+            _ => false,
+        };
+        if !newer {
+            return;
+        }
+        let emitter = crate::events::emitter::EventEmitter::new(db, broadcaster);
+        if let Err(e) = emitter
+            .emit_update_available(CURRENT_VERSION, version, release_url, self.channel.as_str())
+            .await
+        {
+            error!("Failed to emit update_available event: {}", e);
+        }
+    }
+
     /// Perform a single update check and emit appropriate events
     async fn perform_check(&self, db: &DbPool, broadcaster: &EventBroadcaster) -> Result<()> {
         // Create event emitter for this check
@@ -130,19 +225,36 @@ impl UpdateService {
         }
 
         match self.check_for_updates().await {
-            Ok(Some((latest_version, release_url))) => {
+            Ok(Some(update)) => {
+                UpdateCheck::record(
+                    db,
+                    self.channel.as_str(),
+                    Some(&update.version),
+                    Some(&update.release_url),
+                )
+                .await?;
                 // Update available
                 if let Err(e) = emitter
-                    .emit_update_available(CURRENT_VERSION, &latest_version, &release_url)
+                    .emit_update_available(
+                        CURRENT_VERSION,
+                        &update.version,
+                        &update.release_url,
+                        self.channel.as_str(),
+                    )
                     .await
                 {
                     error!("Failed to emit update_available event: {}", e);
                 }
             }
             Ok(None) => {
+                UpdateCheck::record(db, self.channel.as_str(), None, None).await?;
                 // No update available - no event needed
                 debug!("No update available");
             }
+            Err(e) if is_offline(&e) => {
+                // No network is normal for offline machines; try again next interval
+                debug!("Skipping update check, GitHub is unreachable: {}", e);
+            }
             Err(e) => {
                 // Check failed
                 let error_msg = format!("Update check failed: {}", e);
@@ -159,3 +271,115 @@ impl UpdateService {
         Ok(())
     }
 }
+
+/// Newest release on the channel that is newer than `current`
+fn select_update<'a>(
+    current: &Version,
+    releases: &'a [GithubRelease],
+    channel: ReleaseChannel,
+) -> Option<(&'a GithubRelease, Version)> {
+    releases
+        .iter()
+        .filter(|release| !release.draft)
+        .filter_map(|release| {
+            // Remove 'v' prefix if present for comparison
+            let version = Version::parse(release.tag_name.trim_start_matches('v')).ok()?;
+            let prerelease = release.prerelease || !version.pre.is_empty();
+            (channel == ReleaseChannel::Beta || !prerelease).then_some((release, version))
+        })
+        .filter(|(_, version)| version > current)
+        .max_by(|(_, a), (_, b)| a.cmp(b))
+}
+
+/// Check `release.json` against the SHA-256 published next to it (`sha256sum` format) and
+/// that it describes the release it came with
+fn verify_metadata(metadata: &[u8], checksum_file: &str, version: &Version) -> Result<()> {
+    let expected = checksum_file
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| anyhow!("checksum file is empty"))?
+        .to_ascii_lowercase();
+    let actual: String = Sha256::digest(metadata)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    if actual != expected {
+        bail!("{} checksum mismatch", METADATA_ASSET);
+    }
+
+    let metadata: ReleaseMetadata = serde_json::from_slice(metadata)?;
+    if Version::parse(metadata.version.trim_start_matches('v'))? != *version {
+        bail!(
+            "{} describes version {}, not {}",
+            METADATA_ASSET,
+            metadata.version,
+            version
+        );
+    }
+    Ok(())
+}
+
+/// Whether the check failed only because there is no network
+fn is_offline(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect() || e.is_timeout())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, prerelease: bool) -> GithubRelease {
+        GithubRelease {
+            tag_name: tag.to_string(),
+            html_url: format!("https://example.com/{}", tag),
+            draft: false,
+            prerelease,
+            assets: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_select_update_respects_channel() {
+        let current = Version::parse("1.2.0").unwrap();
+        let releases = vec![
+            release("v1.3.0-beta.2", true),
+            release("v1.2.1", false),
+            release("v1.10.0-rc.1", false),
+            release("not-a-version", false),
+            release("v1.1.9", false),
+        ];
+
+        let (stable, version) = select_update(&current, &releases, ReleaseChannel::Stable).unwrap();
+        assert_eq!(stable.tag_name, "v1.2.1");
+        assert_eq!(version, Version::parse("1.2.1").unwrap());
+
+        // Semver ordering, not string ordering: 1.10 is newer than 1.3
+        let (beta, _) = select_update(&current, &releases, ReleaseChannel::Beta).unwrap();
+        assert_eq!(beta.tag_name, "v1.10.0-rc.1");
+
+        let newest = Version::parse("2.0.0").unwrap();
+        assert!(select_update(&newest, &releases, ReleaseChannel::Beta).is_none());
+    }
+
+    #[test]
+    fn test_verify_metadata() {
+        let version = Version::parse("1.2.1").unwrap();
+        let metadata = br#"{"version": "v1.2.1"}"#;
+        let checksum: String = Sha256::digest(metadata)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        verify_metadata(metadata, &format!("{}  release.json\n", checksum), &version).unwrap();
+        assert!(verify_metadata(b"{\"version\": \"1.2.2\"}", &checksum, &version).is_err());
+        assert!(
+            verify_metadata(metadata, &checksum, &Version::parse("1.3.0").unwrap())
+                .unwrap_err()
+                .to_string()
+                .contains("not 1.3.0")
+        );
+        assert!(verify_metadata(metadata, "", &version).is_err());
+    }
+}
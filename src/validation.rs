@@ -3,6 +3,7 @@ use anyhow::Result;
 use tracing::info;
 
 use crate::database::{tickets::Ticket, worker_types::WorkerType, DbPool};
+use crate::error::AppError;
 
 /// Validation helper for pipeline stages and worker types
 pub struct PipelineValidator;
@@ -39,7 +40,7 @@ impl PipelineValidator {
         // Get ticket to find project_id
         let ticket = Ticket::get_by_id(db, ticket_id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Ticket '{}' not found", ticket_id))?;
+            .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found", ticket_id)))?;
 
         Self::validate_worker_type_exists(db, &ticket.ticket.project_id, worker_type).await
     }
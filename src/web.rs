@@ -0,0 +1,254 @@
+use anyhow::{bail, Result};
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{HeaderMap, HeaderValue, Method, Request},
+};
+use std::{net::SocketAddr, time::Duration};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::Span;
+
+/// How long browsers may cache a preflight response
+const PREFLIGHT_MAX_AGE: Duration = Duration::from_secs(600);
+
+/// Normalize a `--base-path` value to `/segment[/segment...]`; `""` and `"/"` mean no prefix
+pub fn normalize_base_path(raw: &str) -> Result<String> {
+    let trimmed = raw.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        return Ok(String::new());
+    }
+
+    for segment in trimmed.split('/') {
+        if segment.is_empty() || segment == "." || segment == ".." {
+            bail!("Invalid base path '{}': empty or relative segment", raw);
+        }
+        if !segment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '~'))
+        {
+            bail!(
+                "Invalid base path '{}': only letters, digits, '-', '_', '.' and '~' are allowed",
+                raw
+            );
+        }
+    }
+
+    Ok(format!("/{}", trimmed))
+}
+
+/// Validate configured CORS origins; each must be `http(s)://host[:port]`
+pub fn parse_allowed_origins(origins: &[String]) -> Result<Vec<HeaderValue>> {
+    origins
+        .iter()
+        .map(|origin| {
+            let normalized = normalize_origin(origin)?;
+            Ok(HeaderValue::from_str(&normalized)?)
+        })
+        .collect()
+}
+
+fn normalize_origin(raw: &str) -> Result<String> {
+    let origin = raw.trim().trim_end_matches('/').to_ascii_lowercase();
+    if origin == "*" || origin == "null" {
+        bail!(
+            "Invalid allowed origin '{}': wildcard and null origins are not allowed, list explicit origins",
+            raw
+        );
+    }
+
+    let Some((scheme, authority)) = origin.split_once("://") else {
+        bail!(
+            "Invalid allowed origin '{}': expected scheme://host[:port]",
+            raw
+        );
+    };
+    if scheme != "http" && scheme != "https" {
+        bail!(
+            "Invalid allowed origin '{}': scheme must be http or https",
+            raw
+        );
+    }
+    if authority.contains(['/', '?', '#', '@', '*']) {
+        bail!(
+            "Invalid allowed origin '{}': origins must not contain a path, query, credentials or wildcard",
+            raw
+        );
+    }
+
+    // Bracketed IPv6 hosts contain colons of their own
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(rest) => match rest.split_once(']') {
+            Some((host, "")) => (host, None),
+            Some((host, port)) => (host, Some(port.strip_prefix(':').unwrap_or(port))),
+            None => bail!("Invalid allowed origin '{}': unterminated IPv6 host", raw),
+        },
+        None => match authority.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    if let Some(port) = port {
+        if port.parse::<u16>().is_err() {
+            bail!("Invalid allowed origin '{}': bad port '{}'", raw, port);
+        }
+    }
+    if host.is_empty() {
+        bail!("Invalid allowed origin '{}': missing host", raw);
+    }
+
+    Ok(origin)
+}
+
+/// CORS layer for the HTTP API.
+///
+/// Without configured origins any origin is allowed but credentials are not; with
+/// origins only those are allowed and credentialed requests are permitted.
+pub fn cors_layer(allowed_origins: &[HeaderValue]) -> CorsLayer {
+    let cors = CorsLayer::new()
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::DELETE,
+            Method::PATCH,
+            Method::OPTIONS,
+        ])
+        .allow_headers([
+            axum::http::header::CONTENT_TYPE,
+            axum::http::header::ACCEPT,
+            axum::http::header::CACHE_CONTROL,
+            axum::http::header::AUTHORIZATION,
+            axum::http::header::HeaderName::from_static("x-api-key"),
+            axum::http::header::HeaderName::from_static("x-claude-code-ide-authorization"),
+            axum::http::header::HeaderName::from_static("last-event-id"),
+            axum::http::header::HeaderName::from_static("mcp-protocol-version"),
+            axum::http::header::HeaderName::from_static("mcp-session-id"),
+        ])
+        .expose_headers([axum::http::header::HeaderName::from_static(
+            "mcp-session-id",
+        )])
+        .max_age(PREFLIGHT_MAX_AGE);
+
+    if allowed_origins.is_empty() {
+        cors.allow_origin(HeaderValue::from_static("*"))
+    } else {
+        cors.allow_origin(AllowOrigin::list(allowed_origins.iter().cloned()))
+            .allow_credentials(true)
+    }
+}
+
+/// Scheme and client address of a request, taking `X-Forwarded-Proto`/`X-Forwarded-For`
+/// into account when `trust_proxy` is set
+pub fn request_origin(
+    headers: &HeaderMap,
+    peer: Option<SocketAddr>,
+    trust_proxy: bool,
+) -> (String, String) {
+    let peer_ip = peer.map(|addr| addr.ip().to_string());
+    if !trust_proxy {
+        return ("http".to_string(), peer_ip.unwrap_or_default());
+    }
+
+    let first_value = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let scheme = first_value("x-forwarded-proto")
+        .map(|s| s.to_ascii_lowercase())
+        .filter(|s| s == "http" || s == "https")
+        .unwrap_or_else(|| "http".to_string());
+    let client_ip = first_value("x-forwarded-for")
+        .or(peer_ip)
+        .unwrap_or_default();
+
+    (scheme, client_ip)
+}
+
+/// Span for `TraceLayer` that records the (possibly proxied) scheme and client address
+pub fn make_request_span(trust_proxy: bool) -> impl Fn(&Request<Body>) -> Span + Clone {
+    move |request: &Request<Body>| {
+        let peer = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|info| info.0);
+        let (scheme, client_ip) = request_origin(request.headers(), peer, trust_proxy);
+        tracing::debug_span!(
+            "request",
+            method = %request.method(),
+            uri = %request.uri(),
+            version = ?request.version(),
+            scheme = %scheme,
+            client_ip = %client_ip,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_path_normalization() {
+        assert_eq!(normalize_base_path("").unwrap(), "");
+        assert_eq!(normalize_base_path("/").unwrap(), "");
+        assert_eq!(normalize_base_path("vibe").unwrap(), "/vibe");
+        assert_eq!(normalize_base_path("/vibe/").unwrap(), "/vibe");
+        assert_eq!(normalize_base_path("/tools/vibe").unwrap(), "/tools/vibe");
+
+        assert!(normalize_base_path("/a//b").is_err());
+        assert!(normalize_base_path("/../etc").is_err());
+        assert!(normalize_base_path("/a b").is_err());
+        assert!(normalize_base_path("/a?x=1").is_err());
+    }
+
+    #[test]
+    fn test_allowed_origins_validation() {
+        let origins = parse_allowed_origins(&[
+            "https://Dashboard.example.com/".to_string(),
+            "http://localhost:5173".to_string(),
+            "http://[::1]:8080".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(origins[0], "https://dashboard.example.com");
+        assert_eq!(origins[1], "http://localhost:5173");
+
+        for bad in [
+            "*",
+            "null",
+            "example.com",
+            "ftp://example.com",
+            "https://example.com/app",
+            "https://*.example.com",
+            "https://example.com:http",
+            "https://",
+        ] {
+            assert!(
+                parse_allowed_origins(&[bad.to_string()]).is_err(),
+                "{} should be rejected",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_forwarded_headers_only_honored_when_trusted() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-proto", "https".parse().unwrap());
+        headers.insert("x-forwarded-for", "203.0.113.7, 10.0.0.2".parse().unwrap());
+        let peer = Some("10.0.0.2:50000".parse().unwrap());
+
+        assert_eq!(
+            request_origin(&headers, peer, true),
+            ("https".to_string(), "203.0.113.7".to_string())
+        );
+        assert_eq!(
+            request_origin(&headers, peer, false),
+            ("http".to_string(), "10.0.0.2".to_string())
+        );
+    }
+}
@@ -0,0 +1,493 @@
+use anyhow::{bail, Result};
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::Sha256;
+use sqlx::SqliteConnection;
+use std::{
+    collections::{hash_map::Entry, HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+
+use crate::{
+    database::{
+        webhooks::{CreateWebhookRequest, Webhook, WebhookDelivery},
+        DbPool,
+    },
+    events::{EventPayload, EventType},
+    sse::EventBroadcaster,
+};
+
+/// Events webhooks can subscribe to; a webhook without filters receives all of them
+pub const WEBHOOK_EVENTS: &[EventType] = &[
+    EventType::TicketCreated,
+    EventType::TicketStageChanged,
+    EventType::TicketClosed,
+    EventType::TicketSlaBreached,
+    EventType::TicketFlaky,
+    EventType::TicketBlockEscalated,
+    EventType::EffectivePriorityChanged,
+    EventType::ProjectQuotaWarning,
+    EventType::WorkerStarted,
+    EventType::WorkerCompleted,
+    EventType::WorkerFailed,
+    EventType::WorkerStopped,
+    EventType::WorkerBoundaryViolation,
+    EventType::WorkerStalled,
+    EventType::CoordinatorLost,
+];
+
+pub const EVENT_HEADER: &str = "X-Vibe-Ensemble-Event";
+pub const DELIVERY_HEADER: &str = "X-Vibe-Ensemble-Delivery";
+/// `sha256=` followed by the hex HMAC-SHA256 of the request body, keyed with the webhook secret
+pub const SIGNATURE_HEADER: &str = "X-Vibe-Ensemble-Signature";
+
+/// Failed attempts before a delivery is dead, unless `--webhook-max-attempts` says otherwise
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 8;
+
+const DISPATCH_INTERVAL_SECS: u64 = 1;
+const DELIVERY_TIMEOUT_SECS: u64 = 10;
+const DELIVERIES_PER_PASS: i64 = 100;
+const RETRY_BASE_SECS: u64 = 10;
+const RETRY_MAX_SECS: u64 = 3600;
+
+/// Signature header value for a request body
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256={}", digest)
+}
+
+/// Whether `signature` is the signature header of `body`, compared in constant time
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(hex) = signature.strip_prefix("sha256=") else {
+        return false;
+    };
+    if hex.len() != 64 || !hex.is_ascii() {
+        return false;
+    }
+    let Ok(expected) = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+    else {
+        return false;
+    };
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Wait before retrying after the given number of failed attempts: 10s, doubling up to an hour
+pub fn retry_delay(failed_attempts: u32) -> Duration {
+    let exponent = failed_attempts.saturating_sub(1).min(16);
+    Duration::from_secs((RETRY_BASE_SECS << exponent).min(RETRY_MAX_SECS))
+}
+
+/// Check a webhook definition before it is stored
+pub fn validate(req: &CreateWebhookRequest) -> Result<()> {
+    let url = reqwest::Url::parse(&req.url)
+        .map_err(|e| anyhow::anyhow!("Invalid webhook url '{}': {}", req.url, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("Webhook url must use http or https");
+    }
+    if req.secret.as_deref().is_some_and(str::is_empty) {
+        bail!("Webhook secret must not be empty");
+    }
+    if req.max_per_minute.is_some_and(|max| max < 0) {
+        bail!("max_per_minute must not be negative");
+    }
+    for name in req.event_types.iter().flatten() {
+        if !WEBHOOK_EVENTS
+            .iter()
+            .any(|event| event.to_string() == *name)
+        {
+            bail!(
+                "Unknown webhook event '{}'. Valid events are: {}",
+                name,
+                WEBHOOK_EVENTS
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Secret for a webhook created without one
+pub fn generate_secret() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// Queue a delivery of `event` for every webhook subscribed to it; returns how many
+pub async fn enqueue_event(conn: &mut SqliteConnection, event: &EventPayload) -> Result<usize> {
+    if !WEBHOOK_EVENTS.contains(&event.event_type) {
+        return Ok(0);
+    }
+    let event_type = event.event_type.to_string();
+    let webhooks = Webhook::subscribed_to(conn, &event_type).await?;
+    if webhooks.is_empty() {
+        return Ok(0);
+    }
+
+    let payload = json!({
+        "event": event_type,
+        "timestamp": event.timestamp,
+        "data": event.data,
+    })
+    .to_string();
+    for webhook in &webhooks {
+        WebhookDelivery::enqueue(conn, webhook.id, &event_type, &payload).await?;
+    }
+    Ok(webhooks.len())
+}
+
+/// Delivery attempts per webhook in the last minute
+#[derive(Default)]
+pub struct RateLimiter {
+    sent: Mutex<HashMap<i64, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    /// Take one attempt from the webhook's budget for the minute before `now`; false
+    /// when it is used up. A limit of 0 is unlimited.
+    pub fn try_acquire(&self, webhook_id: i64, max_per_minute: i64, now: Instant) -> bool {
+        if max_per_minute <= 0 {
+            return true;
+        }
+        let mut sent = self.sent.lock().unwrap();
+        let window = sent.entry(webhook_id).or_default();
+        while window
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= Duration::from_secs(60))
+        {
+            window.pop_front();
+        }
+        if window.len() as i64 >= max_per_minute {
+            return false;
+        }
+        window.push_back(now);
+        true
+    }
+}
+
+pub struct WebhookDispatcher {
+    client: reqwest::Client,
+    limiter: RateLimiter,
+    max_attempts: u32,
+}
+
+impl WebhookDispatcher {
+    pub fn new(max_attempts: u32) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+            .user_agent(concat!("vibe-ensemble-mcp/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        Ok(Self {
+            client,
+            limiter: RateLimiter::default(),
+            max_attempts: max_attempts.max(1),
+        })
+    }
+
+    /// Attempt every due delivery whose webhook is enabled and within its rate limit;
+    /// returns how many were attempted
+    pub async fn deliver_due(&self, db: &DbPool) -> Result<usize> {
+        let mut webhooks: HashMap<i64, Option<Webhook>> = HashMap::new();
+        let mut attempts = Vec::new();
+        for delivery in WebhookDelivery::due(db, DELIVERIES_PER_PASS).await? {
+            if let Entry::Vacant(entry) = webhooks.entry(delivery.webhook_id) {
+                entry.insert(Webhook::get(db, delivery.webhook_id).await?);
+            }
+            let Some(webhook) = webhooks[&delivery.webhook_id].clone() else {
+                continue;
+            };
+            // Deliveries of disabled or rate limited webhooks wait for a later pass
+            if !webhook.enabled
+                || !self
+                    .limiter
+                    .try_acquire(webhook.id, webhook.max_per_minute, Instant::now())
+            {
+                continue;
+            }
+            attempts.push(async move {
+                if let Err(e) = self.attempt(db, &webhook, &delivery).await {
+                    warn!("Failed to record webhook delivery {}: {}", delivery.id, e);
+                }
+            });
+        }
+
+        let count = attempts.len();
+        futures::future::join_all(attempts).await;
+        Ok(count)
+    }
+
+    async fn attempt(
+        &self,
+        db: &DbPool,
+        webhook: &Webhook,
+        delivery: &WebhookDelivery,
+    ) -> Result<()> {
+        let response = self
+            .client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .header(
+                SIGNATURE_HEADER,
+                sign(&webhook.secret, delivery.payload.as_bytes()),
+            )
+            .body(delivery.payload.clone())
+            .send()
+            .await;
+
+        let (status, error) = match response {
+            Ok(response) if response.status().is_success() => {
+                debug!(
+                    "Delivered {} to webhook {} ({})",
+                    delivery.event_type, webhook.id, webhook.url
+                );
+                return WebhookDelivery::mark_delivered(
+                    db,
+                    delivery.id,
+                    response.status().as_u16(),
+                )
+                .await;
+            }
+            Ok(response) => (
+                Some(response.status().as_u16()),
+                format!("Webhook responded with {}", response.status()),
+            ),
+            Err(e) => (None, format!("Request failed: {}", e)),
+        };
+
+        let failed_attempts = delivery.attempts as u32 + 1;
+        let retry_in =
+            (failed_attempts < self.max_attempts).then(|| retry_delay(failed_attempts).as_secs());
+        match retry_in {
+            Some(secs) => info!(
+                "Webhook {} delivery {} failed (attempt {}), retrying in {}s: {}",
+                webhook.id, delivery.id, failed_attempts, secs, error
+            ),
+            None => warn!(
+                "Webhook {} delivery {} is dead after {} attempts: {}",
+                webhook.id, delivery.id, failed_attempts, error
+            ),
+        }
+        WebhookDelivery::record_failure(db, delivery.id, status, &error, retry_in).await
+    }
+}
+
+/// Queue webhook deliveries for broadcast events and send them as they come due. Events
+/// from the outbox have their deliveries queued when they are dispatched, so they do not
+/// come through the broadcast.
+pub fn start_webhook_dispatcher(
+    db: DbPool,
+    broadcaster: &EventBroadcaster,
+    max_attempts: u32,
+) -> Result<()> {
+    let dispatcher = WebhookDispatcher::new(max_attempts)?;
+
+    let mut events = broadcaster.subscribe_webhooks();
+    let queue_db = db.clone();
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    let queued = match queue_db.acquire().await {
+                        Ok(mut conn) => enqueue_event(&mut conn, &event).await,
+                        Err(e) => Err(e.into()),
+                    };
+                    if let Err(e) = queued {
+                        warn!(
+                            "Failed to queue webhook deliveries for {}: {}",
+                            event.event_type, e
+                        );
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(
+                        "Webhook dispatcher fell behind; {} events not delivered",
+                        skipped
+                    )
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(DISPATCH_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = dispatcher.deliver_due(&db).await {
+                warn!("Failed to deliver webhooks: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::memory_pool;
+    use axum::{
+        body::Bytes, extract::State, http::HeaderMap, http::StatusCode, routing::post, Router,
+    };
+    use std::sync::{
+        atomic::{AtomicU16, Ordering},
+        Arc,
+    };
+
+    #[derive(Clone, Default)]
+    struct Receiver {
+        status: Arc<AtomicU16>,
+        received: Arc<Mutex<Vec<(HeaderMap, Bytes)>>>,
+    }
+
+    async fn receive(
+        State(receiver): State<Receiver>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> StatusCode {
+        receiver.received.lock().unwrap().push((headers, body));
+        StatusCode::from_u16(receiver.status.load(Ordering::SeqCst)).unwrap()
+    }
+
+    /// Webhook receiver on a local port answering with the status in `status`
+    async fn start_receiver() -> (Receiver, String) {
+        let receiver = Receiver::default();
+        receiver.status.store(200, Ordering::SeqCst);
+        let app = Router::new()
+            .route("/hook", post(receive))
+            .with_state(receiver.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (receiver, url)
+    }
+
+    #[test]
+    fn test_signature_and_backoff() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        let signature = sign("s3cret", b"{}");
+        assert!(verify_signature("s3cret", b"{}", &signature));
+        assert!(!verify_signature("other", b"{}", &signature));
+        assert!(!verify_signature("s3cret", b"{ }", &signature));
+        assert!(!verify_signature("s3cret", b"{}", &signature[7..]));
+
+        assert_eq!(retry_delay(1), Duration::from_secs(10));
+        assert_eq!(retry_delay(3), Duration::from_secs(40));
+        assert_eq!(retry_delay(30), Duration::from_secs(3600));
+
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+        assert!(limiter.try_acquire(1, 2, start));
+        assert!(limiter.try_acquire(1, 2, start));
+        assert!(!limiter.try_acquire(1, 2, start + Duration::from_secs(59)));
+        assert!(limiter.try_acquire(2, 2, start));
+        assert!(limiter.try_acquire(1, 2, start + Duration::from_secs(60)));
+        assert!(limiter.try_acquire(1, 0, start));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_signed_retried_and_dead_lettered() {
+        let pool = memory_pool().await.unwrap();
+        let (receiver, url) = start_receiver().await;
+        let req: CreateWebhookRequest = serde_json::from_value(json!({
+            "url": url,
+            "event_types": ["ticket_closed"]
+        }))
+        .unwrap();
+        validate(&req).unwrap();
+        let webhook = Webhook::create(&pool, &req, "s3cret").await.unwrap();
+
+        // Only subscribed events are queued
+        let created = EventPayload::ticket_created("P-GEN-001", "shop");
+        assert_eq!(
+            enqueue_event(&mut pool.acquire().await.unwrap(), &created)
+                .await
+                .unwrap(),
+            0
+        );
+        let closed = EventPayload::ticket_closed("P-GEN-001", "shop");
+        assert_eq!(
+            enqueue_event(&mut pool.acquire().await.unwrap(), &closed)
+                .await
+                .unwrap(),
+            1
+        );
+
+        let dispatcher = WebhookDispatcher::new(2).unwrap();
+        assert_eq!(dispatcher.deliver_due(&pool).await.unwrap(), 1);
+        let (headers, body) = receiver.received.lock().unwrap().pop().unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["event"], "ticket_closed");
+        assert_eq!(payload["data"]["ticket_id"], "P-GEN-001");
+        assert_eq!(payload["data"]["project_id"], "shop");
+        assert!(payload["timestamp"].is_string());
+        assert_eq!(headers[EVENT_HEADER], "ticket_closed");
+        let signature = headers[SIGNATURE_HEADER].to_str().unwrap();
+        assert!(verify_signature("s3cret", &body, signature));
+        assert!(!verify_signature("wrong", &body, signature));
+
+        let deliveries = WebhookDelivery::list(&pool, webhook.id, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(deliveries[0].status, "delivered");
+        assert_eq!(
+            headers[DELIVERY_HEADER].to_str().unwrap(),
+            deliveries[0].id.to_string()
+        );
+        assert_eq!(dispatcher.deliver_due(&pool).await.unwrap(), 0);
+
+        // Failures back off, then the delivery is dead after the last attempt
+        receiver.status.store(500, Ordering::SeqCst);
+        enqueue_event(&mut pool.acquire().await.unwrap(), &closed)
+            .await
+            .unwrap();
+        assert_eq!(dispatcher.deliver_due(&pool).await.unwrap(), 1);
+        let failed = &WebhookDelivery::list(&pool, webhook.id, Some("pending"), 10)
+            .await
+            .unwrap()[0];
+        assert_eq!(failed.attempts, 1);
+        assert_eq!(failed.response_status, Some(500));
+        assert_eq!(dispatcher.deliver_due(&pool).await.unwrap(), 0);
+
+        sqlx::query("UPDATE webhook_deliveries SET next_attempt_at = datetime('now', '-1 second')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(dispatcher.deliver_due(&pool).await.unwrap(), 1);
+        let dead = WebhookDelivery::list(&pool, webhook.id, Some("dead"), 10)
+            .await
+            .unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].attempts, 2);
+        assert_eq!(receiver.received.lock().unwrap().len(), 2);
+
+        pool.close().await;
+    }
+}
@@ -0,0 +1,324 @@
+//! Workspace boundary checks. A worker may write inside its working directory and the spawn
+//! policy's allowed paths; everything else under the watched root is scanned when the run
+//! starts and compared by size and modification time with later scans.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
+use tracing::{debug, warn};
+
+use super::sandbox::WorkerSandbox;
+
+/// Watched roots with more files than this outside the allowed paths are not checked
+const MAX_SCANNED_FILES: usize = 200_000;
+
+/// Allowed paths of the workers whose runs are or were recently being checked, so one
+/// worker's writes in its own workspace are not blamed on another running at the same time
+static WORKSPACES: Mutex<Vec<Workspace>> = Mutex::new(Vec::new());
+static NEXT_WORKSPACE_ID: AtomicU64 = AtomicU64::new(1);
+
+struct Workspace {
+    id: u64,
+    allowed: Vec<PathBuf>,
+    started: Instant,
+    finished: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Change {
+    Created,
+    Modified,
+    Deleted,
+}
+
+/// A file outside the worker's workspace that changed during its run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoundaryViolation {
+    pub path: String,
+    pub change: Change,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+type Scan = HashMap<PathBuf, Stamp>;
+
+/// The directory watched for a worker and the paths under it the worker may write to
+#[derive(Debug, Clone)]
+pub struct WorkspaceBoundary {
+    pub watch_root: PathBuf,
+    pub allowed: Vec<PathBuf>,
+}
+
+impl WorkspaceBoundary {
+    pub fn of(sandbox: &WorkerSandbox) -> Self {
+        Self {
+            watch_root: sandbox.watch_root.clone(),
+            allowed: sandbox.allowed_paths.clone(),
+        }
+    }
+
+    fn allows(&self, path: &Path) -> bool {
+        self.allowed.iter().any(|allowed| path.starts_with(allowed))
+    }
+
+    /// Files under the watched root outside the allowed paths, without following symlinks;
+    /// None when there are too many to check
+    fn scan(&self) -> Option<Scan> {
+        let mut files = Scan::new();
+        let mut dirs = vec![self.watch_root.clone()];
+        while let Some(dir) = dirs.pop() {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    debug!("Skipping {} in boundary scan: {}", dir.display(), e);
+                    continue;
+                }
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if self.allows(&path) {
+                    continue;
+                }
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                if files.len() == MAX_SCANNED_FILES {
+                    return None;
+                }
+                files.insert(
+                    path,
+                    Stamp {
+                        len: metadata.len(),
+                        modified: metadata.modified().ok(),
+                    },
+                );
+            }
+        }
+        Some(files)
+    }
+}
+
+/// Changes between two scans, by path
+fn changes(before: &Scan, after: &Scan) -> Vec<(PathBuf, Change)> {
+    let mut changes: Vec<_> = after
+        .iter()
+        .filter_map(|(path, stamp)| match before.get(path) {
+            None => Some((path.clone(), Change::Created)),
+            Some(old) if old != stamp => Some((path.clone(), Change::Modified)),
+            Some(_) => None,
+        })
+        .chain(
+            before
+                .keys()
+                .filter(|path| !after.contains_key(*path))
+                .map(|path| (path.clone(), Change::Deleted)),
+        )
+        .collect();
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    changes
+}
+
+/// The boundary of one worker run, with the watched root as it was when the run started
+pub struct BoundaryWatch {
+    id: u64,
+    boundary: Arc<WorkspaceBoundary>,
+    /// None when nothing is checked
+    baseline: Option<Arc<Scan>>,
+    started: Instant,
+}
+
+impl BoundaryWatch {
+    /// Register the workspace of a run about to start and scan the watched root. Nothing is
+    /// checked when the allowed paths cover the whole root or it is too large to scan, but
+    /// the workspace still counts as another worker's for the runs that are checked.
+    pub async fn start(boundary: WorkspaceBoundary) -> Self {
+        let id = NEXT_WORKSPACE_ID.fetch_add(1, Ordering::Relaxed);
+        let started = Instant::now();
+        WORKSPACES.lock().unwrap().push(Workspace {
+            id,
+            allowed: boundary.allowed.clone(),
+            started,
+            finished: None,
+        });
+        let mut watch = Self {
+            id,
+            boundary: Arc::new(boundary),
+            baseline: None,
+            started,
+        };
+        if watch.boundary.allows(&watch.boundary.watch_root) {
+            return watch;
+        }
+
+        match watch.scan().await {
+            Some(baseline) => watch.baseline = Some(Arc::new(baseline)),
+            None => warn!(
+                "Not checking writes outside the workspace: more than {} files under {}",
+                MAX_SCANNED_FILES,
+                watch.boundary.watch_root.display()
+            ),
+        }
+        watch
+    }
+
+    /// Whether writes outside the workspace are checked for this run
+    pub fn is_checked(&self) -> bool {
+        self.baseline.is_some()
+    }
+
+    async fn scan(&self) -> Option<Scan> {
+        let boundary = self.boundary.clone();
+        tokio::task::spawn_blocking(move || boundary.scan())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Files outside the boundary that changed since the run started, leaving out those
+    /// inside the workspaces of other workers that ran meanwhile
+    pub async fn check(&self) -> Vec<BoundaryViolation> {
+        let Some(baseline) = &self.baseline else {
+            return Vec::new();
+        };
+        let Some(current) = self.scan().await else {
+            return Vec::new();
+        };
+        let others: Vec<PathBuf> = WORKSPACES
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|workspace| {
+                workspace.id != self.id
+                    && workspace
+                        .finished
+                        .is_none_or(|finished| finished >= self.started)
+            })
+            .flat_map(|workspace| workspace.allowed.iter().cloned())
+            .collect();
+
+        changes(baseline, &current)
+            .into_iter()
+            .filter(|(path, _)| !others.iter().any(|other| path.starts_with(other)))
+            .map(|(path, change)| BoundaryViolation {
+                path: path.to_string_lossy().into_owned(),
+                change,
+            })
+            .collect()
+    }
+}
+
+impl Drop for BoundaryWatch {
+    fn drop(&mut self) {
+        let mut workspaces = WORKSPACES.lock().unwrap();
+        if let Some(workspace) = workspaces.iter_mut().find(|w| w.id == self.id) {
+            workspace.finished = Some(Instant::now());
+        }
+        // Finished workspaces only matter to runs that were going on at the time
+        let oldest_running = workspaces
+            .iter()
+            .filter(|w| w.finished.is_none())
+            .map(|w| w.started)
+            .min();
+        workspaces.retain(|w| match (w.finished, oldest_running) {
+            (None, _) => true,
+            (Some(finished), Some(oldest)) => finished >= oldest,
+            (Some(_), None) => false,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workers::sandbox::{SpawnOverrides, WorkerSpawnPolicy};
+
+    #[tokio::test]
+    async fn test_write_outside_workspace_detected() {
+        let project = std::env::temp_dir().join(format!("vibe-boundary-{}", uuid::Uuid::new_v4()));
+        for dir in ["workspace", "other", ".git", "docs"] {
+            std::fs::create_dir_all(project.join(dir)).unwrap();
+        }
+        std::fs::write(project.join("README.md"), "readme").unwrap();
+        std::fs::write(project.join("docs/guide.md"), "guide").unwrap();
+        let project = std::fs::canonicalize(project).unwrap();
+
+        let policy: WorkerSpawnPolicy = serde_json::from_value(serde_json::json!({
+            "working_dir": "workspace"
+        }))
+        .unwrap();
+        let (sandbox, _) = policy.resolve(&project, &SpawnOverrides::default());
+        let watch = BoundaryWatch::start(WorkspaceBoundary::of(&sandbox)).await;
+        assert!(watch.is_checked());
+
+        // Another worker running meanwhile in its own workspace
+        let other_policy: WorkerSpawnPolicy = serde_json::from_value(serde_json::json!({
+            "working_dir": "other"
+        }))
+        .unwrap();
+        let (other_sandbox, _) = other_policy.resolve(&project, &SpawnOverrides::default());
+        let other_watch = BoundaryWatch::start(WorkspaceBoundary::of(&other_sandbox)).await;
+
+        // Writes the worker may make
+        std::fs::write(project.join("workspace/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(project.join(".git/index"), "index").unwrap();
+        std::fs::write(project.join("other/lib.rs"), "").unwrap();
+        assert!(watch.check().await.is_empty());
+
+        // Writes outside the workspace
+        std::fs::write(project.join("README.md"), "clobbered readme").unwrap();
+        std::fs::write(project.join("docs/new.md"), "new").unwrap();
+        std::fs::remove_file(project.join("docs/guide.md")).unwrap();
+        let violations = watch.check().await;
+        let found: Vec<_> = violations
+            .iter()
+            .map(|v| (v.path.as_str(), v.change))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    project.join("README.md").to_str().unwrap(),
+                    Change::Modified
+                ),
+                (
+                    project.join("docs/guide.md").to_str().unwrap(),
+                    Change::Deleted
+                ),
+                (
+                    project.join("docs/new.md").to_str().unwrap(),
+                    Change::Created
+                ),
+            ]
+        );
+
+        // The other worker sees the same writes, and the first worker's workspace as its own
+        let other_found = other_watch.check().await;
+        assert_eq!(other_found, violations);
+
+        drop(other_watch);
+        drop(watch);
+        std::fs::remove_dir_all(project).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_workspace_covering_root_not_watched() {
+        let project = std::fs::canonicalize(std::env::temp_dir()).unwrap();
+        let (sandbox, _) =
+            WorkerSpawnPolicy::default().resolve(&project, &SpawnOverrides::default());
+        assert!(!BoundaryWatch::start(WorkspaceBoundary::of(&sandbox))
+            .await
+            .is_checked());
+    }
+}
@@ -1,7 +1,54 @@
 use crate::{database::DbPool, workers::domain::TicketId};
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, sync::Mutex};
 use tracing::{error, info, warn};
 
+/// Most claim conflicts kept between digests; older ones are only counted
+const MAX_RECORDED_CONFLICTS: usize = 200;
+
+static CONFLICTS: Mutex<ConflictLog> = Mutex::new(ConflictLog {
+    recent: VecDeque::new(),
+    count: 0,
+});
+
+/// A worker that tried to claim a ticket another worker held
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimConflict {
+    pub ticket_id: String,
+    pub worker_id: String,
+    pub held_by: String,
+    pub at: String,
+}
+
+/// Claim conflicts since the last `take_conflicts`
+#[derive(Debug, Default)]
+pub struct ConflictLog {
+    /// The latest conflicts, oldest first
+    pub recent: VecDeque<ClaimConflict>,
+    /// All conflicts, including those no longer in `recent`
+    pub count: u64,
+}
+
+fn record_conflict(ticket_id: &str, worker_id: &str, held_by: &str) {
+    let mut log = CONFLICTS.lock().unwrap();
+    if log.recent.len() == MAX_RECORDED_CONFLICTS {
+        log.recent.pop_front();
+    }
+    log.recent.push_back(ClaimConflict {
+        ticket_id: ticket_id.to_string(),
+        worker_id: worker_id.to_string(),
+        held_by: held_by.to_string(),
+        at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    });
+    log.count += 1;
+}
+
+/// Claim conflicts recorded since the previous call, clearing the log
+pub fn take_conflicts() -> ConflictLog {
+    std::mem::take(&mut *CONFLICTS.lock().unwrap())
+}
+
 /// Result type for ticket claim operations
 #[derive(Debug)]
 pub enum ClaimResult {
@@ -96,6 +143,7 @@ impl ClaimManager {
                         ticket_id.as_str(),
                         current_worker
                     );
+                    record_conflict(ticket_id.as_str(), worker_id, &current_worker);
                     Ok(ClaimResult::AlreadyClaimed(current_worker))
                 }
                 Some((state, _, dep_status)) => {
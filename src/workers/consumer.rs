@@ -1,13 +1,30 @@
 use anyhow::Result;
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Notify};
 use tracing::{debug, error, info, warn};
 
 use super::types::TaskItem;
-use super::{claims::ClaimManager, process::ProcessManager};
+use super::{
+    claims::ClaimManager,
+    heartbeat::{self, StallPolicy, WatchedRun},
+    input::WorkerInputs,
+    process::{ProcessManager, RunReport},
+    sandbox::{SpawnOverrides, WorkerSpawnPolicy},
+    spawn_limiter::{SpawnLimiter, SpawnSlot},
+    warm_pool::WarmPool,
+};
 use crate::{
-    config::Config, database::DbPool, sse::EventBroadcaster,
-    workers::domain::WorkerCompletionEvent, workers::transitions::TicketTransitionManager,
+    audit::{AuditLogger, AuditRecord},
+    config::Config,
+    database::{
+        project_settings::ProjectSettings,
+        worker_metrics::{Outcome, WorkerTypeMetrics},
+        worker_runs::{StartRunRequest, WorkerRun},
+        DbPool,
+    },
+    sse::EventBroadcaster,
+    workers::domain::WorkerCompletionEvent,
+    workers::transitions::TicketTransitionManager,
 };
 
 /// Manages individual consumer threads for project/stage combinations
@@ -18,9 +35,14 @@ pub struct WorkerConsumer {
     db: DbPool,
     completion_sender: mpsc::Sender<WorkerCompletionEvent>,
     event_broadcaster: EventBroadcaster,
+    audit_logger: AuditLogger,
+    spawn_limiter: Arc<SpawnLimiter>,
+    worker_inputs: Arc<WorkerInputs>,
+    warm_pool: Arc<WarmPool>,
 }
 
 impl WorkerConsumer {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         project_id: String,
         stage: String,
@@ -28,6 +50,10 @@ impl WorkerConsumer {
         db: DbPool,
         completion_sender: mpsc::Sender<WorkerCompletionEvent>,
         event_broadcaster: EventBroadcaster,
+        audit_logger: AuditLogger,
+        spawn_limiter: Arc<SpawnLimiter>,
+        worker_inputs: Arc<WorkerInputs>,
+        warm_pool: Arc<WarmPool>,
     ) -> Self {
         Self {
             project_id,
@@ -36,6 +62,10 @@ impl WorkerConsumer {
             db,
             completion_sender,
             event_broadcaster,
+            audit_logger,
+            spawn_limiter,
+            worker_inputs,
+            warm_pool,
         }
     }
 
@@ -211,6 +241,22 @@ impl WorkerConsumer {
             }
         };
 
+        // Sandbox the worker according to the spawn policy and the worker type's overrides
+        let overrides = match worker_type_data.spawn_overrides.as_deref() {
+            Some(json) => SpawnOverrides::parse(json).unwrap_or_else(|e| {
+                warn!(
+                    worker_type = %self.stage,
+                    error = %e,
+                    "Ignoring invalid spawn overrides"
+                );
+                SpawnOverrides::default()
+            }),
+            None => SpawnOverrides::default(),
+        };
+        let (sandbox, policy_violations) =
+            WorkerSpawnPolicy::load_or_default(self.config.worker_policy_path.as_deref())
+                .resolve(std::path::Path::new(&project.path), &overrides);
+
         // Spawn the worker process
         let spawn_request = crate::workers::types::SpawnWorkerRequest {
             worker_id: worker_id.clone(),
@@ -224,8 +270,79 @@ impl WorkerConsumer {
             project_patterns: ticket_with_project.project_patterns,
             server_host: self.config.host.clone(),
             server_port: self.config.port,
+            server_base_path: self.config.base_path.clone(),
             permission_mode: self.config.permission_mode,
             model: self.config.model.clone(),
+            sandbox,
+        };
+
+        // The project's own worker limit, if it sets one, replaces the global per-project limit
+        match ProjectSettings::get(&self.db, &self.project_id).await {
+            Ok(settings) => self.spawn_limiter.set_project_limit(
+                &self.project_id,
+                settings.and_then(|s| s.max_concurrent_workers),
+            ),
+            Err(e) => warn!(
+                project_id = %self.project_id,
+                error = %e,
+                "Failed to load project settings; keeping the current worker limit"
+            ),
+        }
+
+        // Wait for a spawn slot, by effective priority so escalated tickets go first; the
+        // permit is held until the worker process exits
+        let priority = ticket_with_project
+            .ticket
+            .effective_priority
+            .parse()
+            .unwrap_or(crate::database::tickets::Priority::Medium);
+        let _spawn_permit = match self
+            .spawn_limiter
+            .acquire(&self.project_id, &self.stage, &task.ticket_id, priority)
+            .await
+        {
+            SpawnSlot::Immediate(permit) => permit,
+            SpawnSlot::Promoted(permit) => {
+                info!(
+                    ticket_id = %task.ticket_id,
+                    "Queued worker spawn promoted to running"
+                );
+                self.event_broadcaster
+                    .broadcast(crate::events::EventPayload::system_message(
+                        "spawn_queue",
+                        &format!(
+                            "Queued worker for ticket {} promoted to running",
+                            task.ticket_id
+                        ),
+                        Some(serde_json::json!({
+                            "ticket_id": task.ticket_id,
+                            "project_id": self.project_id,
+                            "worker_type": self.stage,
+                            "worker_id": worker_id
+                        })),
+                    ));
+                permit
+            }
+            SpawnSlot::Cancelled => {
+                info!(
+                    ticket_id = %task.ticket_id,
+                    "Queued worker spawn cancelled"
+                );
+                if let Err(e) = crate::database::tickets::Ticket::place_on_hold(
+                    &self.db,
+                    &task.ticket_id,
+                    "Queued worker spawn cancelled by coordinator. Use resume_ticket_processing() to retry.",
+                )
+                .await
+                {
+                    error!(
+                        ticket_id = %task.ticket_id,
+                        error = %e,
+                        "Failed to place ticket on-hold after spawn cancellation"
+                    );
+                }
+                return Ok(()); // scopeguard will handle cleanup
+            }
         };
 
         // Emit event for worker processing start with both DB and SSE
@@ -237,8 +354,61 @@ impl WorkerConsumer {
             warn!("Failed to emit worker_started event: {}", e);
         }
 
-        match ProcessManager::spawn_worker(spawn_request).await {
+        self.audit_logger.record(
+            AuditRecord::new(&worker_id, "worker_spawn", "started").with_ticket(&task.ticket_id),
+        );
+
+        let run_id = self
+            .start_run(&worker_id, &spawn_request, &policy_violations)
+            .await;
+
+        let stop = Arc::new(Notify::new());
+        let stall_watch =
+            run_id
+                .zip(StallPolicy::of(&spawn_request.sandbox))
+                .map(|(run_id, policy)| {
+                    heartbeat::start_stall_watch(
+                        self.db.clone(),
+                        self.event_broadcaster.clone(),
+                        WatchedRun {
+                            run_id,
+                            worker_id: worker_id.clone(),
+                            worker_type: self.stage.clone(),
+                            project_id: self.project_id.clone(),
+                            ticket_id: task.ticket_id.clone(),
+                        },
+                        policy,
+                        stop.clone(),
+                    )
+                });
+
+        let started = std::time::Instant::now();
+        let mut report = RunReport::default();
+        let result = ProcessManager::spawn_worker(
+            spawn_request,
+            &self.worker_inputs,
+            &self.warm_pool,
+            run_id,
+            &stop,
+            &mut report,
+        )
+        .await;
+        if let Some(stall_watch) = stall_watch {
+            stall_watch.abort();
+        }
+        if let Some(run_id) = run_id {
+            self.finish_run(run_id, result.is_ok(), &report).await;
+        }
+        if !report.boundary_violations.is_empty() {
+            self.report_boundary_violations(run_id, &worker_id, &task.ticket_id, &report)
+                .await;
+        }
+        match result {
             Ok(output) => {
+                self.audit_logger.record(
+                    AuditRecord::new(&worker_id, "worker_shutdown", "completed")
+                        .with_ticket(&task.ticket_id),
+                );
                 debug!(
                     worker_id = %worker_id,
                     ticket_id = %task.ticket_id,
@@ -392,6 +562,49 @@ impl WorkerConsumer {
                     }
                 };
 
+                let duration_secs = Some(started.elapsed().as_secs() as i64);
+                match &command {
+                    crate::workers::domain::WorkerCommand::RequestCoordinatorAttention {
+                        ..
+                    } => {
+                        self.record_outcome(
+                            &task.ticket_id,
+                            &self.stage,
+                            Outcome::Failure,
+                            duration_secs,
+                        )
+                        .await;
+                    }
+                    crate::workers::domain::WorkerCommand::ReturnToStage {
+                        target_stage, ..
+                    } => {
+                        self.record_outcome(
+                            &task.ticket_id,
+                            &self.stage,
+                            Outcome::Success,
+                            duration_secs,
+                        )
+                        .await;
+                        // The stage the ticket is sent back to has its work rejected
+                        self.record_outcome(
+                            &task.ticket_id,
+                            target_stage.as_str(),
+                            Outcome::Failure,
+                            None,
+                        )
+                        .await;
+                    }
+                    _ => {
+                        self.record_outcome(
+                            &task.ticket_id,
+                            &self.stage,
+                            Outcome::Success,
+                            duration_secs,
+                        )
+                        .await;
+                    }
+                }
+
                 let completion_event = WorkerCompletionEvent {
                     ticket_id: ticket_id.clone(),
                     command,
@@ -421,6 +634,10 @@ impl WorkerConsumer {
                 }
             }
             Err(e) => {
+                self.audit_logger.record(
+                    AuditRecord::new(&worker_id, "worker_shutdown", "failed")
+                        .with_ticket(&task.ticket_id),
+                );
                 error!(
                     worker_id = %worker_id,
                     ticket_id = %task.ticket_id,
@@ -467,6 +684,13 @@ impl WorkerConsumer {
                         ticket_id = %task.ticket_id,
                         "Worker spawn failed with non-validation error, claim will be released"
                     );
+                    self.record_outcome(
+                        &task.ticket_id,
+                        &self.stage,
+                        Outcome::Failure,
+                        Some(started.elapsed().as_secs() as i64),
+                    )
+                    .await;
                 }
 
                 // Emit event for worker failure with both DB and SSE
@@ -489,4 +713,144 @@ impl WorkerConsumer {
 
         Ok(())
     }
+
+    /// Record the run in the worker run history; policy violations are also audited and
+    /// broadcast so the coordinator sees them before the worker finishes
+    async fn start_run(
+        &self,
+        worker_id: &str,
+        request: &crate::workers::types::SpawnWorkerRequest,
+        policy_violations: &[String],
+    ) -> Option<i64> {
+        if !policy_violations.is_empty() {
+            warn!(
+                worker_id = %worker_id,
+                violations = ?policy_violations,
+                "Worker spawn policy violations"
+            );
+            self.audit_logger.record(
+                AuditRecord::new(worker_id, "worker_spawn_policy", "violation")
+                    .with_ticket(&request.ticket_id)
+                    .with_params(Some(&serde_json::json!(policy_violations))),
+            );
+            self.event_broadcaster
+                .broadcast(crate::events::EventPayload::system_message(
+                    "worker_policy",
+                    &format!(
+                        "Worker {} spawned with {} spawn policy violation(s)",
+                        worker_id,
+                        policy_violations.len()
+                    ),
+                    Some(serde_json::json!({
+                        "worker_id": worker_id,
+                        "ticket_id": request.ticket_id,
+                        "project_id": self.project_id,
+                        "worker_type": self.stage,
+                        "violations": policy_violations
+                    })),
+                ));
+        }
+
+        let run = StartRunRequest {
+            worker_id,
+            project_id: &self.project_id,
+            worker_type: &self.stage,
+            ticket_id: &request.ticket_id,
+            working_dir: &request.sandbox.working_dir.to_string_lossy(),
+            max_runtime_secs: request.sandbox.max_runtime_secs,
+            policy_violations,
+        };
+        match WorkerRun::start(&self.db, &run).await {
+            Ok(id) => Some(id),
+            Err(e) => {
+                warn!("Failed to record worker run for {}: {}", worker_id, e);
+                None
+            }
+        }
+    }
+
+    async fn finish_run(&self, run_id: i64, succeeded: bool, report: &RunReport) {
+        let status = match (succeeded, report.enforcement) {
+            (_, Some(_)) => "killed",
+            (true, None) => "completed",
+            (false, None) => "failed",
+        };
+        if let Err(e) = WorkerRun::finish(
+            &self.db,
+            run_id,
+            status,
+            report.pid,
+            report.exit_status.as_deref(),
+            report.enforcement.map(|enforcement| enforcement.as_str()),
+            report.warm,
+        )
+        .await
+        {
+            warn!("Failed to finish worker run {}: {}", run_id, e);
+        }
+    }
+
+    /// Record the files a worker changed outside its workspace on its run, and audit and
+    /// announce them as a security event
+    async fn report_boundary_violations(
+        &self,
+        run_id: Option<i64>,
+        worker_id: &str,
+        ticket_id: &str,
+        report: &RunReport,
+    ) {
+        let violations = &report.boundary_violations;
+        if let Some(run_id) = run_id {
+            if let Err(e) =
+                WorkerRun::record_boundary_violations(&self.db, run_id, violations).await
+            {
+                warn!(
+                    "Failed to record boundary violations of run {}: {}",
+                    run_id, e
+                );
+            }
+        }
+        self.audit_logger.record(
+            AuditRecord::new(worker_id, "worker_boundary", "violation")
+                .with_ticket(ticket_id)
+                .with_params(Some(&serde_json::json!(violations))),
+        );
+        if let Err(e) = crate::events::emitter::EventEmitter::new(&self.db, &self.event_broadcaster)
+            .emit_worker_boundary_violation(
+                worker_id,
+                &self.stage,
+                &self.project_id,
+                ticket_id,
+                violations,
+            )
+            .await
+        {
+            warn!("Failed to emit worker_boundary_violation event: {}", e);
+        }
+    }
+
+    /// Feed a worker outcome into the worker type's performance metrics
+    async fn record_outcome(
+        &self,
+        ticket_id: &str,
+        worker_type: &str,
+        outcome: Outcome,
+        duration_secs: Option<i64>,
+    ) {
+        if let Err(e) = WorkerTypeMetrics::record(
+            &self.db,
+            &self.project_id,
+            worker_type,
+            ticket_id,
+            outcome,
+            duration_secs,
+        )
+        .await
+        {
+            warn!(
+                "Failed to record {:?} for worker type '{}': {}",
+                outcome, worker_type, e
+            );
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use crate::{
-    database::{tickets::Ticket, DbPool},
+    database::{outbox::OutboxEvent, tickets::Ticket, DbPool},
+    events::EventPayload,
     sse::EventBroadcaster,
     workers::{domain::TicketId, queue::QueueManager},
 };
@@ -39,7 +40,9 @@ impl DependencyManager {
             SELECT t.ticket_id, t.project_id, t.title, t.execution_plan, t.current_stage,
                    t.state, t.priority, t.processing_worker_id, t.created_at, t.updated_at,
                    t.closed_at, t.parent_ticket_id, t.dependency_status, t.created_by_worker_id,
-                   t.ticket_type, t.rules_version, t.patterns_version, t.inherited_from_parent
+                   t.ticket_type, t.rules_version, t.patterns_version, t.inherited_from_parent,
+                   t.custom_fields, t.due_at, t.sla_minutes, t.sla_breached, t.estimate_minutes, t.actual_minutes, t.reopen_count, t.short_id, t.duplicate_of,
+                   t.effective_priority, t.escalation_reasons
             FROM tickets t
             INNER JOIN ticket_dependencies td ON t.ticket_id = td.child_ticket_id
             WHERE td.parent_ticket_id = ?1 AND t.state = 'open' AND t.dependency_status = 'blocked'
@@ -89,13 +92,28 @@ impl DependencyManager {
                     dependent_ticket.ticket_id
                 );
 
+                let mut tx = db.begin().await?;
                 sqlx::query(
                     "UPDATE tickets SET dependency_status = 'ready', updated_at = datetime('now') WHERE ticket_id = ?1"
                 )
                 .bind(&dependent_ticket.ticket_id)
-                .execute(db)
+                .execute(&mut *tx)
                 .await
                 .inspect_err(|e| error!("Failed to update dependency status to 'ready' for ticket {}: {}", dependent_ticket.ticket_id, e))?;
+                OutboxEvent::write(
+                    &mut tx,
+                    &dependent_ticket.ticket_id,
+                    &EventPayload::ticket_updated(
+                        &dependent_ticket.ticket_id,
+                        &dependent_ticket.project_id,
+                        "dependency_resolved",
+                    ),
+                    None,
+                    Some("All dependencies satisfied, ticket unblocked"),
+                )
+                .await?;
+                tx.commit().await?;
+                event_broadcaster.notify_outbox();
 
                 // Resubmit to queue for processing
                 let ticket_id = match TicketId::new(dependent_ticket.ticket_id.clone()) {
@@ -118,21 +136,6 @@ impl DependencyManager {
                     &dependent_ticket.current_stage,
                 )
                 .await?;
-
-                // Publish event with both DB and SSE
-                let emitter = crate::events::emitter::EventEmitter::new(db, event_broadcaster);
-                if let Err(e) = emitter
-                    .emit_ticket_updated(
-                        &dependent_ticket.ticket_id,
-                        &dependent_ticket.project_id,
-                        "dependency_resolved",
-                        None,
-                        Some("All dependencies satisfied, ticket unblocked"),
-                    )
-                    .await
-                {
-                    warn!("Failed to emit dependency_resolved event: {}", e);
-                }
             } else {
                 info!(
                     "Ticket {} still has {} blocking dependencies",
@@ -182,20 +185,19 @@ impl DependencyManager {
             Ok(_) => {
                 info!("Successfully resubmitted ticket {} to queue", ticket_id);
 
-                // Publish event with both DB and SSE
-                let emitter = crate::events::emitter::EventEmitter::new(db, event_broadcaster);
-                if let Err(e) = emitter
-                    .emit_ticket_updated(
-                        ticket_id.as_str(),
-                        project_id,
-                        "resubmitted",
-                        Some(current_stage),
-                        Some("Ticket resubmitted for processing after dependencies resolved"),
-                    )
-                    .await
+                // The queue lives in memory, so there is no change to commit the event with
+                if let Err(e) = OutboxEvent::write(
+                    &mut *db.acquire().await?,
+                    ticket_id.as_str(),
+                    &EventPayload::ticket_updated(ticket_id.as_str(), project_id, "resubmitted"),
+                    Some(current_stage),
+                    Some("Ticket resubmitted for processing after dependencies resolved"),
+                )
+                .await
                 {
-                    warn!("Failed to emit ticket_resubmitted event: {}", e);
+                    warn!("Failed to queue ticket_resubmitted event: {}", e);
                 }
+                event_broadcaster.notify_outbox();
             }
             Err(e) => {
                 error!("Failed to resubmit ticket {} to queue: {}", ticket_id, e);
@@ -0,0 +1,292 @@
+//! Hung-worker detection. Workers report progress with `report_worker_progress`; a running
+//! worker that stays quiet past its stall threshold is flagged as stalled, announced and
+//! noted on its ticket, and optionally stopped once it stays quiet past a second threshold.
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use super::sandbox::WorkerSandbox;
+use crate::{
+    database::{comments::Comment, worker_runs::WorkerRun, DbPool},
+    events::emitter::EventEmitter,
+    sse::EventBroadcaster,
+};
+
+/// Longest time between two heartbeat checks of a run
+const MAX_CHECK_SECS: u64 = 30;
+
+/// Silence after which a running worker is flagged as stalled, and optionally stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StallPolicy {
+    pub stall_after_secs: u64,
+    pub terminate_after_secs: Option<u64>,
+}
+
+impl StallPolicy {
+    /// Thresholds of a worker's sandbox; None when it is not checked for stalls
+    pub fn of(sandbox: &WorkerSandbox) -> Option<Self> {
+        let stall_after_secs = sandbox.stall_after_secs.filter(|secs| *secs > 0)?;
+        Some(Self {
+            stall_after_secs,
+            terminate_after_secs: sandbox.stall_terminate_secs.filter(|secs| *secs > 0),
+        })
+    }
+
+    fn check_interval(&self) -> Duration {
+        Duration::from_secs((self.stall_after_secs / 4).clamp(1, MAX_CHECK_SECS))
+    }
+
+    /// What to do about a run that has been silent for `silent_secs`, None once it finished
+    pub fn verdict(&self, silent_secs: Option<i64>) -> Verdict {
+        let Some(silent_secs) = silent_secs else {
+            return Verdict::Finished;
+        };
+        let silent_secs = silent_secs.max(0) as u64;
+        if self
+            .terminate_after_secs
+            .is_some_and(|secs| silent_secs >= secs)
+        {
+            Verdict::Terminate
+        } else if silent_secs >= self.stall_after_secs {
+            Verdict::Stalled
+        } else {
+            Verdict::Healthy
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Healthy,
+    Stalled,
+    Terminate,
+    Finished,
+}
+
+/// The run a stall watch looks after
+#[derive(Debug, Clone)]
+pub struct WatchedRun {
+    pub run_id: i64,
+    pub worker_id: String,
+    pub worker_type: String,
+    pub project_id: String,
+    pub ticket_id: String,
+}
+
+/// Check `run` periodically until it finishes or the returned task is aborted. `stop` is
+/// notified when the run has been silent past the termination threshold.
+pub fn start_stall_watch(
+    db: DbPool,
+    broadcaster: EventBroadcaster,
+    run: WatchedRun,
+    policy: StallPolicy,
+    stop: Arc<Notify>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(policy.check_interval());
+        loop {
+            interval.tick().await;
+            match check(&db, &broadcaster, &run, &policy).await {
+                Verdict::Finished => return,
+                Verdict::Terminate => {
+                    warn!(
+                        "Stopping worker {} (ticket {}) after {} seconds without progress",
+                        run.worker_id,
+                        run.ticket_id,
+                        policy.terminate_after_secs.unwrap_or_default()
+                    );
+                    stop.notify_one();
+                    return;
+                }
+                Verdict::Healthy | Verdict::Stalled => {}
+            }
+        }
+    })
+}
+
+/// Flag the run as stalled when it has gone quiet for too long
+async fn check(
+    db: &DbPool,
+    broadcaster: &EventBroadcaster,
+    run: &WatchedRun,
+    policy: &StallPolicy,
+) -> Verdict {
+    let silent_secs = match WorkerRun::silent_secs(db, run.run_id).await {
+        Ok(silent_secs) => silent_secs,
+        Err(e) => {
+            warn!(
+                "Failed to check heartbeat of worker {}: {}",
+                run.worker_id, e
+            );
+            return Verdict::Healthy;
+        }
+    };
+    let verdict = policy.verdict(silent_secs);
+    if matches!(verdict, Verdict::Stalled | Verdict::Terminate) {
+        flag_stalled(db, broadcaster, run, silent_secs.unwrap_or_default()).await;
+    }
+    verdict
+}
+
+/// Record the stall on the run, announce it and warn on the ticket, once per stall
+async fn flag_stalled(
+    db: &DbPool,
+    broadcaster: &EventBroadcaster,
+    run: &WatchedRun,
+    silent_secs: i64,
+) {
+    match WorkerRun::mark_stalled(db, run.run_id).await {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            warn!("Failed to flag worker {} as stalled: {}", run.worker_id, e);
+            return;
+        }
+    }
+    warn!(
+        "Worker {} (ticket {}) is running but has not reported progress for {} seconds",
+        run.worker_id, run.ticket_id, silent_secs
+    );
+
+    if let Err(e) = EventEmitter::new(db, broadcaster)
+        .emit_worker_stalled(
+            &run.worker_id,
+            &run.worker_type,
+            &run.project_id,
+            &run.ticket_id,
+            silent_secs,
+        )
+        .await
+    {
+        warn!("Failed to emit worker_stalled event: {}", e);
+    }
+
+    let comment = format!(
+        "⚠️ Worker {} is still running but has not reported progress for {} minute(s) and is flagged as stalled. It may be waiting on a prompt; send_worker_input can nudge it.",
+        run.worker_id,
+        silent_secs / 60
+    );
+    if let Err(e) = Comment::create(
+        db,
+        &run.ticket_id,
+        Some("system"),
+        Some("system"),
+        None,
+        &comment,
+    )
+    .await
+    {
+        warn!(
+            "Failed to add stall warning to ticket {}: {}",
+            run.ticket_id, e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::worker_runs::StartRunRequest;
+    use crate::test_support::{memory_pool, Fixtures};
+
+    #[test]
+    fn test_verdict_thresholds() {
+        let policy = StallPolicy {
+            stall_after_secs: 300,
+            terminate_after_secs: Some(900),
+        };
+        assert_eq!(policy.verdict(None), Verdict::Finished);
+        assert_eq!(policy.verdict(Some(299)), Verdict::Healthy);
+        assert_eq!(policy.verdict(Some(300)), Verdict::Stalled);
+        assert_eq!(policy.verdict(Some(900)), Verdict::Terminate);
+
+        let flag_only = StallPolicy {
+            terminate_after_secs: None,
+            ..policy
+        };
+        assert_eq!(flag_only.verdict(Some(86_400)), Verdict::Stalled);
+        assert_eq!(flag_only.check_interval(), Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn test_quiet_run_flagged_once_until_next_heartbeat() {
+        let pool = memory_pool().await.unwrap();
+        let fx = Fixtures::new(&pool);
+        let project = fx.project("stalls").create().await.unwrap();
+        let ticket = fx.ticket(&project, "Wedged").create().await.unwrap();
+        let worker_id = format!("stalls:coding:{}", ticket.ticket_id);
+        let run_id = WorkerRun::start(
+            &pool,
+            &StartRunRequest {
+                worker_id: &worker_id,
+                project_id: "stalls",
+                worker_type: "coding",
+                ticket_id: &ticket.ticket_id,
+                working_dir: "/tmp/stalls",
+                max_runtime_secs: 3600,
+                policy_violations: &[],
+            },
+        )
+        .await
+        .unwrap();
+        sqlx::query("UPDATE worker_runs SET started_at = datetime('now', '-10 minutes')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let broadcaster = EventBroadcaster::new();
+        let run = WatchedRun {
+            run_id,
+            worker_id: worker_id.clone(),
+            worker_type: "coding".to_string(),
+            project_id: "stalls".to_string(),
+            ticket_id: ticket.ticket_id.clone(),
+        };
+        let policy = StallPolicy {
+            stall_after_secs: 300,
+            terminate_after_secs: Some(1800),
+        };
+        let warnings = || async {
+            Comment::get_by_ticket_id(&pool, &ticket.ticket_id)
+                .await
+                .unwrap()
+                .into_iter()
+                .filter(|c| c.content.contains("flagged as stalled"))
+                .count()
+        };
+
+        assert_eq!(
+            check(&pool, &broadcaster, &run, &policy).await,
+            Verdict::Stalled
+        );
+        assert_eq!(
+            check(&pool, &broadcaster, &run, &policy).await,
+            Verdict::Stalled
+        );
+        assert_eq!(warnings().await, 1);
+        assert_eq!(WorkerRun::count_stalled(&pool, None).await.unwrap(), 1);
+
+        WorkerRun::heartbeat(&pool, &worker_id, Some("unstuck"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            check(&pool, &broadcaster, &run, &policy).await,
+            Verdict::Healthy
+        );
+        assert_eq!(WorkerRun::count_stalled(&pool, None).await.unwrap(), 0);
+
+        WorkerRun::finish(&pool, run_id, "completed", None, None, None, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            check(&pool, &broadcaster, &run, &policy).await,
+            Verdict::Finished
+        );
+
+        pool.close().await;
+    }
+}
@@ -0,0 +1,246 @@
+use anyhow::{bail, Result};
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Largest instruction accepted for a running worker
+pub const MAX_WORKER_INPUT_BYTES: usize = 16 * 1024;
+
+type Stdin = Box<dyn AsyncWrite + Unpin + Send>;
+
+struct WorkerStdin {
+    run_id: Option<i64>,
+    stdin: Stdin,
+    /// Instructions written so far
+    sent: i64,
+}
+
+/// One line written to a worker's stdin. Each instruction is a single JSON object
+/// terminated by a newline; `seq` counts up from 1 within a run.
+#[derive(Debug, Clone, Serialize)]
+pub struct InputFrame<'a> {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub id: &'a str,
+    pub seq: i64,
+    pub content: &'a str,
+    pub sent_at: &'a str,
+}
+
+/// Confirmation that an instruction reached a worker's stdin
+#[derive(Debug, Clone, Serialize)]
+pub struct Delivery {
+    pub worker_id: String,
+    /// Run history entry of the process that received it, when one was recorded
+    pub run_id: Option<i64>,
+    pub input_id: String,
+    pub seq: i64,
+    pub bytes: usize,
+    pub sent_at: String,
+}
+
+/// Stdin pipes of running worker processes, keyed by worker id, so follow-up
+/// instructions can be sent to a worker while it runs
+#[derive(Default)]
+pub struct WorkerInputs {
+    workers: DashMap<String, Arc<Mutex<WorkerStdin>>>,
+}
+
+/// Keeps a worker's stdin reachable until dropped, which closes it
+pub struct AttachedInput<'a> {
+    inputs: &'a WorkerInputs,
+    worker_id: String,
+    entry: Arc<Mutex<WorkerStdin>>,
+}
+
+impl WorkerInputs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `stdin` the input of `worker_id` until the returned guard is dropped
+    pub fn attach(
+        &self,
+        worker_id: &str,
+        run_id: Option<i64>,
+        stdin: impl AsyncWrite + Unpin + Send + 'static,
+    ) -> AttachedInput<'_> {
+        let entry = Arc::new(Mutex::new(WorkerStdin {
+            run_id,
+            stdin: Box::new(stdin),
+            sent: 0,
+        }));
+        self.workers.insert(worker_id.to_string(), entry.clone());
+        AttachedInput {
+            inputs: self,
+            worker_id: worker_id.to_string(),
+            entry,
+        }
+    }
+
+    pub fn is_running(&self, worker_id: &str) -> bool {
+        self.workers.contains_key(worker_id)
+    }
+
+    /// Ids of workers that can currently receive input
+    pub fn running(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.workers.iter().map(|e| e.key().clone()).collect();
+        ids.sort();
+        ids
+    }
+
+    /// Write `content` to the worker's stdin as one framed line. Instructions to the
+    /// same worker are written in the order their calls acquire the pipe.
+    pub async fn send(&self, worker_id: &str, content: &str) -> Result<Delivery> {
+        self.write(worker_id, "instruction", content).await
+    }
+
+    /// Send a warm worker its ticket, framed like an instruction with type "assignment"
+    pub async fn assign(&self, worker_id: &str, content: &str) -> Result<Delivery> {
+        self.write(worker_id, "assignment", content).await
+    }
+
+    async fn write(&self, worker_id: &str, kind: &'static str, content: &str) -> Result<Delivery> {
+        if content.trim().is_empty() {
+            bail!("Instruction must not be empty");
+        }
+        if content.len() > MAX_WORKER_INPUT_BYTES {
+            bail!(
+                "Instruction is {} bytes; the limit is {} bytes",
+                content.len(),
+                MAX_WORKER_INPUT_BYTES
+            );
+        }
+        let Some(entry) = self.workers.get(worker_id).map(|e| e.value().clone()) else {
+            bail!("Worker '{}' is not running", worker_id);
+        };
+
+        let mut worker = entry.lock().await;
+        let input_id = Uuid::new_v4().to_string();
+        let sent_at = chrono::Utc::now().to_rfc3339();
+        let seq = worker.sent + 1;
+        let mut line = serde_json::to_string(&InputFrame {
+            kind,
+            id: &input_id,
+            seq,
+            content,
+            sent_at: &sent_at,
+        })?;
+        line.push('\n');
+
+        let written = async {
+            worker.stdin.write_all(line.as_bytes()).await?;
+            worker.stdin.flush().await
+        }
+        .await;
+        if let Err(e) = written {
+            warn!("Failed to write to stdin of worker {}: {}", worker_id, e);
+            bail!(
+                "Worker '{}' is no longer reading its input: {}",
+                worker_id,
+                e
+            );
+        }
+        worker.sent = seq;
+        debug!(
+            "Sent {} {} (#{}) to worker {}",
+            kind, input_id, seq, worker_id
+        );
+
+        Ok(Delivery {
+            worker_id: worker_id.to_string(),
+            run_id: worker.run_id,
+            input_id,
+            seq,
+            bytes: line.len(),
+            sent_at,
+        })
+    }
+}
+
+impl Drop for AttachedInput<'_> {
+    fn drop(&mut self) {
+        // A respawned worker with the same id may have attached since
+        self.inputs
+            .workers
+            .remove_if(&self.worker_id, |_, entry| Arc::ptr_eq(entry, &self.entry));
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+    use tokio::io::AsyncReadExt;
+    use tokio::process::Command;
+
+    #[tokio::test]
+    async fn test_instructions_are_framed_in_order() {
+        let inputs = WorkerInputs::new();
+        // Stands in for a worker: echoes everything it receives on stdin
+        let mut child = Command::new("cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let attached = inputs.attach("p:coding:P-1", Some(7), child.stdin.take().unwrap());
+        assert_eq!(inputs.running(), ["p:coding:P-1"]);
+
+        let first = inputs
+            .send("p:coding:P-1", "Also update the changelog")
+            .await
+            .unwrap();
+        let second = inputs
+            .send("p:coding:P-1", "Skip the docs\nfor now")
+            .await
+            .unwrap();
+        assert_eq!((first.seq, second.seq), (1, 2));
+        assert_eq!(first.run_id, Some(7));
+
+        let too_large = "x".repeat(MAX_WORKER_INPUT_BYTES + 1);
+        assert!(inputs.send("p:coding:P-1", &too_large).await.is_err());
+        assert!(inputs.send("p:coding:P-1", "  ").await.is_err());
+        assert!(inputs.send("p:coding:P-2", "hello").await.is_err());
+
+        // Detaching closes stdin, so the stand-in exits
+        drop(attached);
+        assert!(!inputs.is_running("p:coding:P-1"));
+        let mut echoed = String::new();
+        child
+            .stdout
+            .take()
+            .unwrap()
+            .read_to_string(&mut echoed)
+            .await
+            .unwrap();
+        child.wait().await.unwrap();
+
+        let frames: Vec<serde_json::Value> = echoed
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0]["type"], "instruction");
+        assert_eq!(frames[0]["seq"], 1);
+        assert_eq!(frames[0]["id"], first.input_id.as_str());
+        assert_eq!(frames[0]["content"], "Also update the changelog");
+        assert_eq!(frames[1]["seq"], 2);
+        assert_eq!(frames[1]["content"], "Skip the docs\nfor now");
+        assert_eq!(echoed.len(), first.bytes + second.bytes);
+    }
+
+    #[tokio::test]
+    async fn test_reattached_worker_survives_old_guard() {
+        let inputs = WorkerInputs::new();
+        let old = inputs.attach("w", None, tokio::io::sink());
+        let new = inputs.attach("w", Some(2), tokio::io::sink());
+        drop(old);
+        assert_eq!(inputs.send("w", "go").await.unwrap().run_id, Some(2));
+        drop(new);
+        assert!(inputs.send("w", "go").await.is_err());
+    }
+}
@@ -1,12 +1,19 @@
+pub mod boundary;
 pub mod claims;
 pub mod completion_processor;
 pub mod consumer;
 pub mod dependencies;
 pub mod domain;
+pub mod heartbeat;
+pub mod input;
 pub mod pipeline;
 pub mod process;
+pub mod prompt;
 pub mod queue;
+pub mod sandbox;
+pub mod spawn_limiter;
 pub mod ticket_id;
 pub mod transitions;
 pub mod types;
 pub mod validation;
+pub mod warm_pool;
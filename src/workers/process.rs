@@ -1,20 +1,126 @@
 use anyhow::{Context, Result};
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
-use std::time::Duration;
-use tokio::process::Command;
-use tokio::time::timeout;
+use std::time::Instant;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
+use super::boundary::{BoundaryViolation, BoundaryWatch, WorkspaceBoundary};
 use super::completion_processor::{WorkerOutcome, WorkerOutput};
+use super::input::WorkerInputs;
+use super::sandbox::{self, Enforcement};
 use super::types::SpawnWorkerRequest;
 use super::validation::WorkerInputValidator;
+use super::warm_pool::WarmPool;
+use crate::api::access;
 use crate::permissions::{
     load_permission_policy, ClaudePermissions, PermissionMode, PermissionPolicy,
 };
 
 pub struct ProcessManager;
 
+/// Ticket id in a warm worker's prompts until it is sent its assignment
+pub const WARM_TICKET_PLACEHOLDER: &str = "<the ticket in your assignment>";
+
+/// What happened to a worker process, for the run history
+#[derive(Debug, Default)]
+pub struct RunReport {
+    pub pid: Option<u32>,
+    pub exit_status: Option<String>,
+    /// Set when the worker outlived its max runtime
+    pub enforcement: Option<Enforcement>,
+    /// Whether the worker was claimed from the warm pool rather than spawned for the run
+    pub warm: bool,
+    /// Files the worker changed outside its workspace
+    pub boundary_violations: Vec<BoundaryViolation>,
+}
+
+/// A spawned worker's MCP config file and the token in it; dropping it removes the file
+/// and revokes the token
+struct WorkerMcpConfig {
+    path: String,
+    token: String,
+}
+
+impl Drop for WorkerMcpConfig {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+        access::revoke_worker_token(&self.token);
+    }
+}
+
+/// A worker process whose output is being collected
+pub struct StartedWorker {
+    child: Child,
+    /// Taken when the stdin is attached to `WorkerInputs`
+    stdin: Option<ChildStdin>,
+    stdout: JoinHandle<Vec<u8>>,
+    stderr: JoinHandle<Vec<u8>>,
+    config: Option<WorkerMcpConfig>,
+}
+
+impl StartedWorker {
+    fn new(mut child: Child, config: Option<WorkerMcpConfig>) -> Self {
+        Self {
+            stdin: child.stdin.take(),
+            stdout: collect_output(child.stdout.take()),
+            stderr: collect_output(child.stderr.take()),
+            child,
+            config,
+        }
+    }
+
+    /// Make the worker's MCP requests act as `worker_id` from now on
+    pub fn act_as(&self, worker_id: &str) {
+        if let Some(config) = &self.config {
+            access::rebind_worker_token(&config.token, worker_id);
+        }
+    }
+
+    pub fn pid(&self) -> Option<u32> {
+        self.child.id()
+    }
+
+    /// Whether the process has exited, or can no longer be checked
+    pub fn has_exited(&mut self) -> bool {
+        !matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Kill the process, then remove its MCP config and revoke its token
+    pub async fn stop(mut self) {
+        if let Err(e) = self.child.kill().await {
+            debug!("Failed to kill worker process: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+impl StartedWorker {
+    /// Wrap a stand-in process, such as `cat`, with no MCP config
+    pub fn for_test(child: Child) -> Self {
+        Self::new(child, None)
+    }
+}
+
+/// Read a child's output pipe to the end in the background
+fn collect_output(
+    pipe: Option<impl AsyncRead + Unpin + Send + 'static>,
+) -> tokio::task::JoinHandle<Vec<u8>> {
+    tokio::spawn(async move {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            if let Err(e) = pipe.read_to_end(&mut buf).await {
+                warn!("Failed to read worker output: {}", e);
+            }
+        }
+        buf
+    })
+}
+
 impl ProcessManager {
     /// Apply permissions to Claude command based on mode
     fn apply_permissions_to_command(
@@ -58,13 +164,13 @@ impl ProcessManager {
         let mut enhanced_allow_list = permissions.allow.clone();
 
         // Ensure our vibe-ensemble-mcp tools are always allowed (using explicit tool names)
-        use crate::mcp::constants::get_all_mcp_tool_names;
+        use crate::mcp::constants::{get_all_mcp_tool_names, MCP_TOOL_PREFIX};
         let mcp_tools = get_all_mcp_tool_names();
 
         // Check if we already have explicit MCP tools or wildcard
         let has_mcp_tools = enhanced_allow_list
             .iter()
-            .any(|tool| tool.starts_with("mcp__vibe-ensemble-mcp__") || tool == "mcp__*");
+            .any(|tool| tool.starts_with(MCP_TOOL_PREFIX) || tool == "mcp__*");
 
         if !has_mcp_tools {
             enhanced_allow_list.extend(mcp_tools);
@@ -256,18 +362,16 @@ impl ProcessManager {
     fn create_mcp_config(
         project_path: &str,
         worker_id: &str,
+        worker_type: &str,
         host: &str,
         server_port: u16,
-    ) -> Result<String> {
+        server_base_path: &str,
+    ) -> Result<WorkerMcpConfig> {
         debug!(
             "Creating MCP config for worker {} in project path: {}",
             worker_id, project_path
         );
 
-        use crate::mcp::constants::build_mcp_config;
-        let config = build_mcp_config(host, server_port);
-        debug!("MCP config JSON created successfully");
-
         // Create .vibe-ensemble-mcp directory for worker configs
         let config_dir = format!("{}/.vibe-ensemble-mcp", project_path);
         fs::create_dir_all(&config_dir)
@@ -283,6 +387,16 @@ impl ProcessManager {
         );
         debug!("Target config file path: {}", config_path);
 
+        // Revokes the token again should anything below fail
+        let mcp_config = WorkerMcpConfig {
+            path: config_path,
+            token: access::issue_worker_token(worker_id, worker_type),
+        };
+        use crate::mcp::constants::build_worker_mcp_config;
+        let config =
+            build_worker_mcp_config(host, server_port, server_base_path, &mcp_config.token);
+        debug!("MCP config JSON created successfully");
+
         debug!("Serializing config to pretty JSON...");
         let config_json = serde_json::to_string_pretty(&config)
             .with_context(|| "Failed to serialize MCP config to JSON")?;
@@ -291,27 +405,21 @@ impl ProcessManager {
             config_json.len()
         );
 
-        debug!("Writing config file to: {}", config_path);
-        fs::write(&config_path, config_json)
-            .with_context(|| format!("Failed to write MCP config to {}", config_path))?;
+        debug!("Writing config file to: {}", mcp_config.path);
+        fs::write(&mcp_config.path, config_json)
+            .with_context(|| format!("Failed to write MCP config to {}", mcp_config.path))?;
         debug!("File write successful");
 
-        info!("Generated MCP config file: {}", config_path);
-        Ok(config_path)
+        info!("Generated MCP config file: {}", mcp_config.path);
+        Ok(mcp_config)
     }
 
-    pub async fn spawn_worker(request: SpawnWorkerRequest) -> Result<WorkerOutput> {
-        info!(
-            "Spawning worker: {} for ticket: {} (project: {}, type: {})",
-            request.worker_id, request.ticket_id, request.project_id, request.worker_type
-        );
-
-        // Validate inputs before proceeding
+    /// Validate a spawn request's worker id, project path and prompts, returning the
+    /// validated project path. Ticket ids are checked by the caller, since a warm worker
+    /// has none yet.
+    fn validate_request(request: &SpawnWorkerRequest) -> Result<PathBuf> {
         info!("Validating worker spawn request inputs");
 
-        WorkerInputValidator::validate_ticket_id(&request.ticket_id)
-            .context("Invalid ticket ID")?;
-
         WorkerInputValidator::validate_worker_id(&request.worker_id)
             .context("Invalid worker ID")?;
 
@@ -336,20 +444,59 @@ impl ProcessManager {
         }
 
         info!("Input validation passed");
+        Ok(validated_path)
+    }
+
+    /// Prompt that sends a worker to its ticket
+    fn ticket_prompt(ticket_id: &str) -> String {
+        format!(
+            "You are working on ticket: {}. Use the get_ticket MCP tool to retrieve the ticket details and proceed with your assigned role.",
+            ticket_id
+        )
+    }
 
+    /// How often the worker must report progress, appended to its system prompt when its
+    /// sandbox checks for stalls
+    fn heartbeat_instructions(request: &SpawnWorkerRequest) -> Option<String> {
+        let minutes = request.sandbox.heartbeat_minutes()?;
+        Some(format!(
+            "\n\n💓 PROGRESS HEARTBEAT:\nCall the `report_worker_progress` MCP tool with worker_id \"{}\" and a one-line `progress` note at least every {} minute(s) while you work, including during long builds or test runs. A worker that stays quiet for longer is flagged as stalled{}.\n",
+            request.worker_id,
+            minutes,
+            if request.sandbox.stall_terminate_secs.is_some_and(|secs| secs > 0) {
+                " and eventually stopped"
+            } else {
+                ""
+            }
+        ))
+    }
+
+    /// Start a Claude Code process for `request` with `input_prompt` as its first message
+    fn start(
+        request: &SpawnWorkerRequest,
+        validated_path: &Path,
+        input_prompt: &str,
+    ) -> Result<StartedWorker> {
         // Create MCP config file using validated path
-        let config_path = Self::create_mcp_config(
+        // Removed, and its token revoked, on every early return below
+        let mcp_config = Self::create_mcp_config(
             validated_path.to_str().unwrap(),
             &request.worker_id,
+            &request.worker_type,
             &request.server_host,
             request.server_port,
+            &request.server_base_path,
         )?;
 
         // Create comprehensive system prompt with project rules and patterns
         let template = include_str!("../../templates/system_prompts/worker_spawn.md");
 
-        // Build the full prompt with worker template, project rules, and patterns
-        let mut full_prompt = request.system_prompt.clone();
+        // Build the full prompt with worker template, project rules, and patterns.
+        // Only the worker type's own prompt is rendered; rules and patterns are used as written.
+        let mut full_prompt = super::prompt::render(
+            &request.system_prompt,
+            &super::prompt::spawn_variables(request),
+        );
 
         // Add project rules if available
         if let Some(ref rules) = request.project_rules {
@@ -371,29 +518,26 @@ impl ProcessManager {
             }
         }
 
-        let system_prompt = template
+        let mut system_prompt = template
             .replace("{ticket_id}", &request.ticket_id)
             .replace("{system_prompt}", &full_prompt);
-
-        // Create simple input prompt that instructs worker to get ticket details
-        let input_prompt = format!(
-            "You are working on ticket: {}. Use the get_ticket MCP tool to retrieve the ticket details and proceed with your assigned role.",
-            request.ticket_id
-        );
+        if let Some(heartbeat) = Self::heartbeat_instructions(request) {
+            system_prompt.push_str(&heartbeat);
+        }
 
         // Spawn Claude Code process with the system prompt
         info!(
             "Spawning Claude Code with working directory: {}",
-            validated_path.display()
+            request.sandbox.working_dir.display()
         );
-        let mut cmd = Command::new("claude");
+        let mut cmd = request.sandbox.command("claude");
         cmd.arg("-p")
             .arg(&system_prompt)
-            .arg(&input_prompt)
+            .arg(input_prompt)
             .arg("--debug")
             //.arg("--verbose")
             .arg("--mcp-config")
-            .arg(&config_path)
+            .arg(&mcp_config.path)
             .arg("--output-format")
             .arg("json");
 
@@ -421,7 +565,7 @@ impl ProcessManager {
             }
         }
 
-        cmd.current_dir(&validated_path)
+        cmd.stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
@@ -437,39 +581,239 @@ impl ProcessManager {
         )?;
 
         debug!("Executing command: {:?}", cmd);
-        let child = match cmd.spawn() {
-            Ok(c) => c,
-            Err(e) => {
-                let _ = std::fs::remove_file(&config_path);
-                return Err(e.into());
+        match cmd.spawn() {
+            Ok(child) => {
+                info!(
+                    "Worker process spawned with PID: {}",
+                    child.id().unwrap_or(0)
+                );
+                Ok(StartedWorker::new(child, Some(mcp_config)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Start a warm worker from `template`, a spawn request without a ticket. It is told
+    /// to wait for an assignment on stdin.
+    pub fn spawn_warm(template: &SpawnWorkerRequest) -> Result<StartedWorker> {
+        info!(
+            "Starting warm worker: {} (project: {}, type: {})",
+            template.worker_id, template.project_id, template.worker_type
+        );
+        let validated_path = Self::validate_request(template)?;
+        let input_prompt = format!(
+            "You are a {} worker on standby. Do nothing until an assignment arrives on your standard input as one line of JSON: {{\"type\": \"assignment\", \"id\": \"<uuid>\", \"seq\": 1, \"content\": \"You are working on ticket: <ticket_id>. ...\", \"sent_at\": \"<RFC 3339 timestamp>\"}}. The ticket it names is the one your instructions refer to as {}; then follow its content.",
+            template.worker_type, WARM_TICKET_PLACEHOLDER
+        );
+        Self::start(template, &validated_path, &input_prompt)
+    }
+
+    /// Run a worker to completion. A matching idle worker is claimed from `warm_pool` and
+    /// sent its ticket on stdin when there is one; otherwise a new process is spawned.
+    /// While the worker runs, its stdin is attached to `inputs` so follow-up instructions
+    /// can be sent to it. The worker is stopped when `stop` is notified.
+    pub async fn spawn_worker(
+        request: SpawnWorkerRequest,
+        inputs: &WorkerInputs,
+        warm_pool: &WarmPool,
+        run_id: Option<i64>,
+        stop: &Notify,
+        report: &mut RunReport,
+    ) -> Result<WorkerOutput> {
+        info!(
+            "Spawning worker: {} for ticket: {} (project: {}, type: {})",
+            request.worker_id, request.ticket_id, request.project_id, request.worker_type
+        );
+
+        WorkerInputValidator::validate_ticket_id(&request.ticket_id)
+            .context("Invalid ticket ID")?;
+        let validated_path = Self::validate_request(&request)?;
+        let watch = BoundaryWatch::start(WorkspaceBoundary::of(&request.sandbox)).await;
+
+        let requested = Instant::now();
+        let mut assigned = None;
+        if let Some(mut warm) = warm_pool.claim(&request) {
+            warm.act_as(&request.worker_id);
+            let input = warm
+                .stdin
+                .take()
+                .map(|stdin| inputs.attach(&request.worker_id, run_id, stdin));
+            match inputs
+                .assign(&request.worker_id, &Self::ticket_prompt(&request.ticket_id))
+                .await
+            {
+                Ok(_) => {
+                    warm_pool.record_claim(requested.elapsed());
+                    info!(
+                        "Claimed warm worker (PID: {}) for ticket {}",
+                        warm.pid().unwrap_or(0),
+                        request.ticket_id
+                    );
+                    assigned = Some((warm, input));
+                }
+                Err(e) => {
+                    warn!(
+                        "Warm worker for ticket {} did not take its assignment, spawning a new one: {}",
+                        request.ticket_id, e
+                    );
+                    drop(input);
+                    warm.stop().await;
+                }
+            }
+        }
+
+        let (worker, _input) = match assigned {
+            Some(assigned) => {
+                report.warm = true;
+                assigned
+            }
+            None => {
+                let mut worker = Self::start(
+                    &request,
+                    &validated_path,
+                    &Self::ticket_prompt(&request.ticket_id),
+                )?;
+                warm_pool.record_cold_spawn(requested.elapsed());
+                // Detached (closing stdin) on every return below
+                let input = worker
+                    .stdin
+                    .take()
+                    .map(|stdin| inputs.attach(&request.worker_id, run_id, stdin));
+                (worker, input)
             }
         };
-        let pid = child.id().unwrap_or(0);
-        info!("Worker process spawned with PID: {}", pid);
-
-        // Add timeout to worker execution (default: 10 minutes)
-        let worker_timeout = Duration::from_secs(
-            std::env::var("WORKER_TIMEOUT_SECS")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(600), // Default: 600 seconds (10 minutes)
+
+        // Replace the warm worker just used, or start the pool for this worker type
+        warm_pool.replenish(&request);
+
+        Self::wait(worker, &request, watch, stop, report).await
+    }
+
+    /// Check the run for writes outside the workspace, recording any in `report`
+    async fn verify_boundary(
+        watch: &BoundaryWatch,
+        request: &SpawnWorkerRequest,
+        report: &mut RunReport,
+    ) -> bool {
+        let violations = watch.check().await;
+        if violations.is_empty() {
+            return true;
+        }
+        error!(
+            "Worker {} changed {} file(s) outside its workspace (ticket: {}): {:?}",
+            request.worker_id,
+            violations.len(),
+            request.ticket_id,
+            violations
         );
+        report.boundary_violations = violations;
+        false
+    }
+
+    fn boundary_error(request: &SpawnWorkerRequest, report: &RunReport) -> anyhow::Error {
+        let paths: Vec<_> = report
+            .boundary_violations
+            .iter()
+            .map(|violation| violation.path.as_str())
+            .collect();
+        anyhow::anyhow!(
+            "Worker {} wrote outside its workspace {}: {}",
+            request.worker_id,
+            request.sandbox.working_dir.display(),
+            paths.join(", ")
+        )
+    }
+
+    /// Wait for a started worker to exit, stopping it once it exceeds its max runtime or
+    /// `stop` is notified, and parse its output
+    async fn wait(
+        worker: StartedWorker,
+        request: &SpawnWorkerRequest,
+        watch: BoundaryWatch,
+        stop: &Notify,
+        report: &mut RunReport,
+    ) -> Result<WorkerOutput> {
+        let StartedWorker {
+            mut child,
+            stdout,
+            stderr,
+            // Removes the MCP config and revokes the token on every return
+            config: _config,
+            ..
+        } = worker;
+        report.pid = child.id();
+        let pid = child.id().unwrap_or(0);
 
+        let max_runtime = request.sandbox.max_runtime();
         info!(
-            "Waiting for worker to complete (timeout: {} seconds)",
-            worker_timeout.as_secs()
+            "Waiting for worker to complete (max runtime: {} seconds)",
+            max_runtime.as_secs()
         );
 
-        // Wait for the process to complete and capture output with timeout
+        // Wait for the process to exit, stopping it once it exceeds its max runtime or, with
+        // periodic boundary checks, writes outside its workspace
         let start_time = std::time::Instant::now();
-        let output_result = timeout(worker_timeout, child.wait_with_output()).await;
-
-        let output = match output_result {
-            Ok(Ok(output)) => {
+        let deadline = tokio::time::Instant::now() + max_runtime;
+        let mut checks = match request.sandbox.boundary_check_secs {
+            Some(secs) if secs > 0 && watch.is_checked() => {
+                let period = std::time::Duration::from_secs(secs);
+                Some(tokio::time::interval_at(
+                    tokio::time::Instant::now() + period,
+                    period,
+                ))
+            }
+            _ => None,
+        };
+        let waited = loop {
+            let next_check = async {
+                match checks.as_mut() {
+                    Some(checks) => {
+                        checks.tick().await;
+                    }
+                    None => std::future::pending::<()>().await,
+                }
+            };
+            let exited = tokio::select! {
+                waited = tokio::time::timeout_at(deadline, child.wait()) => Some(waited),
+                _ = next_check => None,
+                _ = stop.notified() => {
+                    let enforcement =
+                        sandbox::terminate(&mut child, request.sandbox.kill_grace()).await;
+                    warn!(
+                        "Worker process (PID: {}) {} after reporting no progress",
+                        pid,
+                        enforcement.as_str()
+                    );
+                    report.enforcement = Some(enforcement);
+                    Self::verify_boundary(&watch, request, report).await;
+                    return Err(anyhow::anyhow!(
+                        "Worker {} reported no progress for {} seconds and was {}",
+                        request.worker_id,
+                        request.sandbox.stall_terminate_secs.unwrap_or_default(),
+                        enforcement.as_str()
+                    ));
+                }
+            };
+            if let Some(waited) = exited {
+                break waited;
+            }
+            if !Self::verify_boundary(&watch, request, report).await {
+                let enforcement =
+                    sandbox::terminate(&mut child, request.sandbox.kill_grace()).await;
+                warn!(
+                    "Worker process (PID: {}) {} after writing outside its workspace",
+                    pid,
+                    enforcement.as_str()
+                );
+                return Err(Self::boundary_error(request, report));
+            }
+        };
+        let status = match waited {
+            Ok(Ok(status)) => {
                 let duration = start_time.elapsed();
                 info!(
                     "Worker process completed with status: {} (duration: {:.2}s)",
-                    output.status,
+                    status,
                     duration.as_secs_f64()
                 );
 
@@ -481,7 +825,7 @@ impl ProcessManager {
                     );
                 }
 
-                output
+                status
             }
             Ok(Err(e)) => {
                 error!(
@@ -489,35 +833,42 @@ impl ProcessManager {
                     start_time.elapsed().as_secs_f64(),
                     e
                 );
-                let _ = std::fs::remove_file(&config_path);
                 return Err(e.into());
             }
             Err(_) => {
-                // Timeout occurred - note that child has been consumed by wait_with_output
                 error!(
-                    "Worker process timed out after {} seconds (PID: {}, ticket: {})",
-                    worker_timeout.as_secs(),
+                    "Worker process exceeded its max runtime of {} seconds (PID: {}, ticket: {})",
+                    max_runtime.as_secs(),
                     pid,
                     request.ticket_id
                 );
-
+                let enforcement =
+                    sandbox::terminate(&mut child, request.sandbox.kill_grace()).await;
                 warn!(
-                    "Worker process (PID: {}) timed out. The process may still be running and should be terminated manually if necessary.",
-                    pid
+                    "Worker process (PID: {}) {} after exceeding its max runtime",
+                    pid,
+                    enforcement.as_str()
                 );
-
-                let _ = std::fs::remove_file(&config_path);
+                report.enforcement = Some(enforcement);
+                Self::verify_boundary(&watch, request, report).await;
 
                 return Err(anyhow::anyhow!(
-                    "Worker process timed out after {} seconds",
-                    worker_timeout.as_secs()
+                    "Worker process exceeded its max runtime of {} seconds and was {}",
+                    max_runtime.as_secs(),
+                    enforcement.as_str()
                 ));
             }
         };
+        report.exit_status = Some(status.to_string());
+        if !Self::verify_boundary(&watch, request, report).await {
+            return Err(Self::boundary_error(request, report));
+        }
+        let stdout = stdout.await.unwrap_or_default();
+        let stderr = stderr.await.unwrap_or_default();
 
         // Parse stdout for WorkerOutput JSON
-        let stdout_str = String::from_utf8_lossy(&output.stdout);
-        let stderr_str = String::from_utf8_lossy(&output.stderr);
+        let stdout_str = String::from_utf8_lossy(&stdout);
+        let stderr_str = String::from_utf8_lossy(&stderr);
 
         debug!("Worker stdout: {}", stdout_str);
         debug!("Worker stderr: {}", stderr_str);
@@ -531,15 +882,9 @@ impl ProcessManager {
 
             // Validate and auto-correct planning worker output
             Self::validate_and_correct_planning_output(&mut parsed_output, &request.worker_type);
-
-            // Clean up
-            let _ = std::fs::remove_file(&config_path);
             return Ok(parsed_output);
         }
 
-        // Clean up config file
-        let _ = std::fs::remove_file(&config_path);
-
         // If we get here, the worker didn't produce valid output
         // This should be handled by the caller via WorkerOutput::CoordinatorAttention
         // rather than directly releasing tickets here since process.rs doesn't have DB access
@@ -0,0 +1,76 @@
+use super::types::SpawnWorkerRequest;
+
+/// Placeholders a worker type's system prompt may use; they are filled in when a worker
+/// is spawned
+pub const PROMPT_VARIABLES: &[&str] = &[
+    "project_id",
+    "project_path",
+    "workspace_path",
+    "ticket_id",
+    "worker_id",
+    "worker_type",
+];
+
+/// Values of `PROMPT_VARIABLES` for a spawn
+pub fn spawn_variables(request: &SpawnWorkerRequest) -> Vec<(&'static str, String)> {
+    vec![
+        ("project_id", request.project_id.clone()),
+        ("project_path", request.project_path.clone()),
+        (
+            "workspace_path",
+            request.sandbox.working_dir.display().to_string(),
+        ),
+        ("ticket_id", request.ticket_id.clone()),
+        ("worker_id", request.worker_id.clone()),
+        ("worker_type", request.worker_type.clone()),
+    ]
+}
+
+/// Replace `{name}` placeholders with their values in one pass. Anything else in braces,
+/// such as JSON examples in the prompt, is left as written, and substituted values are
+/// not scanned again.
+pub fn render(template: &str, variables: &[(&str, String)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            variables
+                .iter()
+                .find(|(name, _)| *name == &after[..end])
+                .map(|(_, value)| (value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                rendered.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_fills_known_placeholders_only() {
+        let variables = [
+            ("ticket_id", "P-GEN-001".to_string()),
+            ("workspace_path", "/work/{ticket_id}".to_string()),
+        ];
+        let template = "Fix {ticket_id} in {workspace_path}; reply {\"ticket_id\": \"{ticket_id}\"} {unknown} {";
+        assert_eq!(
+            render(template, &variables),
+            "Fix P-GEN-001 in /work/{ticket_id}; reply {\"ticket_id\": \"P-GEN-001\"} {unknown} {"
+        );
+        assert_eq!(render("no placeholders", &variables), "no placeholders");
+    }
+}
@@ -1,20 +1,32 @@
 use anyhow::Result;
 use dashmap::DashMap;
+use sqlx::SqliteConnection;
 use std::fmt;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
 use super::{
-    claims::ClaimManager, consumer::WorkerConsumer, dependencies::DependencyManager,
+    claims::ClaimManager,
+    consumer::WorkerConsumer,
+    dependencies::DependencyManager,
+    input::WorkerInputs,
+    spawn_limiter::SpawnLimiter,
     types::TaskItem,
+    warm_pool::{WarmPool, WarmPoolConfig},
 };
 use crate::{
+    audit::AuditLogger,
     config::Config,
     database::{
-        tickets::{DependencyStatus, TicketState},
+        blocks::{BlockOutcome, Blocker, LiftedBlock, TicketBlock},
+        comments::Comment,
+        duplicates::{link_duplicate, LinkOutcome},
+        relations::{self, ClosePlan, RelationOutcome, RelationType},
+        tickets::{DependencyStatus, Ticket, TicketState},
         DbPool,
     },
+    error::AppError,
     sse::EventBroadcaster,
     workers::domain::{TicketId, WorkerCommand, WorkerCompletionEvent, WorkerType},
 };
@@ -31,10 +43,14 @@ pub struct QueueManager {
     event_broadcaster: EventBroadcaster,
     db: DbPool,
     coordinator_directories: Arc<dashmap::DashMap<String, String>>,
+    audit_logger: AuditLogger,
+    spawn_limiter: Arc<SpawnLimiter>,
+    worker_inputs: Arc<WorkerInputs>,
+    warm_pool: Arc<WarmPool>,
 }
 
 // QueueManager intentionally does not implement Default to prevent misuse
-// Always use QueueManager::new(db, config, event_broadcaster, coordinator_directories) for proper initialization
+// Always use QueueManager::new(db, config, event_broadcaster, coordinator_directories, audit_logger) for proper initialization
 
 impl fmt::Debug for QueueManager {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -50,9 +66,19 @@ impl QueueManager {
         config: Config,
         event_broadcaster: EventBroadcaster,
         coordinator_directories: Arc<dashmap::DashMap<String, String>>,
+        audit_logger: AuditLogger,
     ) -> Arc<Self> {
         let (completion_sender, completion_receiver) = mpsc::channel(DEFAULT_CHANNEL_BUFFER_SIZE);
 
+        let spawn_limiter = Arc::new(SpawnLimiter::new(
+            config.max_concurrent_workers,
+            config.max_workers_per_project,
+            config.spawn_priority_aging_secs,
+        ));
+
+        let warm_pool = Arc::new(WarmPool::new(WarmPoolConfig::from_config(&config)));
+        warm_pool.start_sweeper();
+
         let queue_manager = Arc::new(Self {
             queues: DashMap::new(),
             completion_sender,
@@ -60,6 +86,10 @@ impl QueueManager {
             event_broadcaster,
             db,
             coordinator_directories,
+            audit_logger,
+            spawn_limiter,
+            worker_inputs: Arc::new(WorkerInputs::new()),
+            warm_pool,
         });
 
         // Spawn the completion event processor thread internally
@@ -78,6 +108,21 @@ impl QueueManager {
         self.completion_sender.clone()
     }
 
+    /// Limiter gating how many worker processes may run at once
+    pub fn spawn_limiter(&self) -> &Arc<SpawnLimiter> {
+        &self.spawn_limiter
+    }
+
+    /// Stdin of running worker processes, for sending them follow-up instructions
+    pub fn worker_inputs(&self) -> &Arc<WorkerInputs> {
+        &self.worker_inputs
+    }
+
+    /// Idle workers started ahead of demand, claimed by spawns of their worker type
+    pub fn warm_pool(&self) -> &Arc<WarmPool> {
+        &self.warm_pool
+    }
+
     /// Generate standardized queue name: "{project_id}-{worker_type}-queue"
     pub fn generate_queue_name(project_id: &str, worker_type: &str) -> String {
         format!("{}-{}-queue", project_id, worker_type)
@@ -141,7 +186,7 @@ impl QueueManager {
                 ));
             }
         } else {
-            return Err(anyhow::anyhow!(format!("Ticket '{}' not found", ticket_id)));
+            return Err(AppError::NotFound(format!("Ticket '{}' not found", ticket_id)).into());
         }
 
         // Claim the ticket before submitting to queue
@@ -244,6 +289,10 @@ impl QueueManager {
         let db_clone = self.db.clone();
         let config_clone = self.config.clone();
         let event_broadcaster_clone = self.event_broadcaster.clone();
+        let audit_logger_clone = self.audit_logger.clone();
+        let spawn_limiter_clone = self.spawn_limiter.clone();
+        let worker_inputs_clone = self.worker_inputs.clone();
+        let warm_pool_clone = self.warm_pool.clone();
 
         tokio::spawn(async move {
             let db_for_cleanup = db_clone.clone();
@@ -255,6 +304,10 @@ impl QueueManager {
                 db_clone,
                 completion_sender,
                 event_broadcaster_clone,
+                audit_logger_clone,
+                spawn_limiter_clone,
+                worker_inputs_clone,
+                warm_pool_clone,
             ));
 
             if let Err(e) = consumer.run(receiver).await {
@@ -426,7 +479,7 @@ impl QueueManager {
         // Get ticket to find project_id
         let ticket_with_comments = crate::database::tickets::Ticket::get_by_id(&self.db, ticket_id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Ticket '{}' not found", ticket_id))?;
+            .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found", ticket_id)))?;
 
         let project_id = &ticket_with_comments.ticket.project_id;
 
@@ -596,7 +649,7 @@ impl QueueManager {
         // Get the ticket to check its current state
         let ticket_with_comments = crate::database::tickets::Ticket::get_by_id(&self.db, ticket_id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Ticket '{}' not found", ticket_id))?;
+            .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found", ticket_id)))?;
 
         let ticket = &ticket_with_comments.ticket;
 
@@ -630,7 +683,7 @@ impl QueueManager {
     }
 
     /// Check if ticket completion unblocks any dependent tickets
-    async fn check_and_unblock_dependents(
+    pub async fn check_and_unblock_dependents(
         self: &Arc<Self>,
         completed_ticket_id: &str,
     ) -> Result<()> {
@@ -641,7 +694,76 @@ impl QueueManager {
             self.clone(),
             &ticket_id,
         )
-        .await
+        .await?;
+
+        // Tickets blocked on this one are unblocked once it closes
+        let closed = crate::database::tickets::Ticket::get_by_id(&self.db, completed_ticket_id)
+            .await?
+            .is_some_and(|t| t.ticket.is_closed());
+        if !closed {
+            return Ok(());
+        }
+        for waiting in TicketBlock::waiting_on(&self.db, completed_ticket_id).await? {
+            let note = format!("blocking ticket {} was closed", completed_ticket_id);
+            if let Err(e) = self.unblock_ticket(&waiting, "system", Some(&note)).await {
+                warn!(
+                    "Failed to unblock ticket {} after {} closed: {}",
+                    waiting, completed_ticket_id, e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Block `ticket_id` on another ticket or on an agent; the ticket is put on hold and
+    /// its worker released until it is unblocked
+    pub async fn block_ticket(
+        self: &Arc<Self>,
+        ticket_id: &str,
+        reason: &str,
+        blocker: &Blocker,
+        blocked_by: &str,
+    ) -> Result<BlockOutcome> {
+        let outcome = TicketBlock::block(&self.db, ticket_id, reason, blocker, blocked_by).await?;
+        if let BlockOutcome::Blocked(_) = &outcome {
+            info!("Ticket {} blocked by {}: {}", ticket_id, blocked_by, reason);
+            // ticket_updated was queued with the block
+            self.event_broadcaster.notify_outbox();
+        }
+        Ok(outcome)
+    }
+
+    /// Lift the block on `ticket_id` and queue the ticket again at its current stage when
+    /// nothing else holds it back; None when the ticket is not blocked
+    pub async fn unblock_ticket(
+        self: &Arc<Self>,
+        ticket_id: &str,
+        unblocked_by: &str,
+        note: Option<&str>,
+    ) -> Result<Option<LiftedBlock>> {
+        let Some(lifted) = TicketBlock::unblock(&self.db, ticket_id, unblocked_by, note).await?
+        else {
+            return Ok(None);
+        };
+        info!(
+            "Ticket {} unblocked by {} after {} minutes",
+            ticket_id, unblocked_by, lifted.blocked_minutes
+        );
+        // ticket_unblocked was queued with the unblock
+        self.event_broadcaster.notify_outbox();
+
+        if let Some(ticket) = crate::database::tickets::Ticket::get_by_id(&self.db, ticket_id)
+            .await?
+            .map(|t| t.ticket)
+        {
+            if ticket.is_open()
+                && ticket.dependency_status == DependencyStatus::Ready.as_sql_value()
+            {
+                self.auto_enqueue_ticket(ticket_id, &ticket.current_stage)
+                    .await?;
+            }
+        }
+        Ok(Some(lifted))
     }
 
     /// Resubmit parent ticket when child completes or needs attention
@@ -699,25 +821,35 @@ impl QueueManager {
             ticket_id, resolution
         );
 
-        // Get ticket information before closing for event emission
-        let ticket_with_comments = crate::database::tickets::Ticket::get_by_id(&self.db, ticket_id)
-            .await?
-            .ok_or_else(|| anyhow::anyhow!("Ticket '{}' not found", ticket_id))?;
-        let project_id = ticket_with_comments.ticket.project_id.clone();
+        let mut tx = self.db.begin().await?;
+        let closed = Self::close_with_comment(&mut tx, ticket_id, resolution, comment).await?;
+        tx.commit().await?;
 
+        self.after_close(&closed).await
+    }
+
+    /// Close the ticket and add the closing comment within the caller's transaction; once
+    /// that is committed, `after_close` carries the close on to related tickets
+    pub async fn close_with_comment(
+        conn: &mut SqliteConnection,
+        ticket_id: &str,
+        resolution: &str,
+        comment: &str,
+    ) -> Result<Ticket> {
         // Close the ticket in the database
-        crate::database::tickets::Ticket::close_ticket(&self.db, ticket_id, resolution)
+        let closed = Ticket::close_in(conn, ticket_id, resolution)
             .await
             .inspect_err(|e| {
                 error!(
                     "Failed to close ticket {} with resolution '{}': {}",
                     ticket_id, resolution, e
                 )
-            })?;
+            })?
+            .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found", ticket_id)))?;
 
         // Add closing comment
-        crate::database::comments::Comment::create(
-            &self.db,
+        Comment::create_in(
+            conn,
             ticket_id,
             Some("system"),
             Some("coordinator"),
@@ -732,14 +864,15 @@ impl QueueManager {
             )
         })?;
 
-        // Emit ticket closed event with both DB and SSE
-        let emitter = crate::events::emitter::EventEmitter::new(&self.db, &self.event_broadcaster);
-        if let Err(e) = emitter
-            .emit_ticket_closed(ticket_id, &project_id, resolution)
-            .await
-        {
-            warn!("Failed to emit ticket_closed event: {}", e);
-        }
+        Ok(closed)
+    }
+
+    /// Publish the events of a committed close, unblock the tickets depending on it and
+    /// have its parent reassessed
+    pub async fn after_close(self: &Arc<Self>, closed: &Ticket) -> Result<()> {
+        let ticket_id = closed.ticket_id.as_str();
+        // ticket_closed was queued with the close
+        self.event_broadcaster.notify_outbox();
 
         // Trigger dependency cascade to unblock dependent tickets
         info!(
@@ -749,7 +882,7 @@ impl QueueManager {
         self.check_and_unblock_dependents(ticket_id).await?;
 
         // If this ticket has a parent, resubmit parent for reassessment
-        if let Some(parent_id) = &ticket_with_comments.ticket.parent_ticket_id {
+        if let Some(parent_id) = &closed.parent_ticket_id {
             info!(
                 "Resubmitting parent ticket {} after child {} completion",
                 parent_id, ticket_id
@@ -764,6 +897,117 @@ impl QueueManager {
         Ok(())
     }
 
+    /// Close `ticket_id` as a duplicate of `duplicate_of`: link it to the original, move the
+    /// tickets waiting on it over, close it and leave a note on the original
+    pub async fn close_as_duplicate(
+        self: &Arc<Self>,
+        ticket_id: &str,
+        duplicate_of: &str,
+    ) -> Result<LinkOutcome> {
+        let outcome = link_duplicate(&self.db, ticket_id, duplicate_of).await?;
+        let LinkOutcome::Linked {
+            original,
+            moved_dependents,
+        } = &outcome
+        else {
+            return Ok(outcome);
+        };
+        info!(
+            "Closing ticket {} as a duplicate of {} ({} dependent tickets moved)",
+            ticket_id,
+            original,
+            moved_dependents.len()
+        );
+
+        self.complete_ticket_with_cascade(
+            ticket_id,
+            "duplicate",
+            &format!("Closed as a duplicate of {}", original),
+        )
+        .await?;
+
+        let original_ticket = crate::database::tickets::Ticket::get_by_id(&self.db, original)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Ticket '{}' not found", original)))?;
+        let note = format!(
+            "Ticket {} was closed as a duplicate of this ticket",
+            ticket_id
+        );
+        if let Err(e) = crate::database::comments::Comment::create(
+            &self.db,
+            original,
+            Some("system"),
+            Some("coordinator"),
+            None,
+            &note,
+        )
+        .await
+        {
+            warn!(
+                "Failed to note duplicate {} on {}: {}",
+                ticket_id, original, e
+            );
+        }
+        // Tickets moved over to an original that is already closed may go ahead
+        if !moved_dependents.is_empty() && original_ticket.ticket.is_completed() {
+            self.check_and_unblock_dependents(original).await?;
+        }
+        // ticket_updated of the original was queued with the link
+        self.event_broadcaster.notify_outbox();
+        Ok(outcome)
+    }
+
+    /// Relate two tickets; an open ticket recorded as a duplicate is closed as one
+    pub async fn add_relation(
+        self: &Arc<Self>,
+        from_ticket_id: &str,
+        to_ticket_id: &str,
+        relation_type: RelationType,
+    ) -> Result<RelationOutcome> {
+        if relation_type == RelationType::Duplicates {
+            let open = crate::database::tickets::Ticket::get_by_id(&self.db, from_ticket_id)
+                .await?
+                .is_some_and(|t| !t.ticket.is_closed());
+            if open {
+                return Ok(
+                    match self
+                        .close_as_duplicate(from_ticket_id, to_ticket_id)
+                        .await?
+                    {
+                        LinkOutcome::Linked { .. } => RelationOutcome::Applied,
+                        LinkOutcome::Rejected(reason) => RelationOutcome::Rejected(reason),
+                    },
+                );
+            }
+        }
+        relations::add(&self.db, from_ticket_id, to_ticket_id, relation_type).await
+    }
+
+    /// Close `ticket_id` and then its open descendants, parents before children, with the
+    /// same resolution; returns the descendants closed
+    pub async fn close_with_descendants(
+        self: &Arc<Self>,
+        plan: &ClosePlan,
+        resolution: &str,
+        comment: &str,
+    ) -> Result<Vec<String>> {
+        let ticket_id = plan.ticket.ticket_id.as_str();
+        // The parent goes first so closing its children does not resubmit it
+        self.complete_ticket_with_cascade(ticket_id, resolution, comment)
+            .await?;
+        let mut closed = Vec::with_capacity(plan.descendants.len());
+        for child in &plan.descendants {
+            self.complete_ticket_with_cascade(
+                &child.ticket_id,
+                resolution,
+                &format!("Closed with ancestor ticket {}", ticket_id),
+            )
+            .await?;
+            closed.push(child.ticket_id.clone());
+        }
+        Ok(closed)
+    }
+
     /// Execute planning completion: create worker types, create child tickets, close planning ticket
     async fn execute_planning_completion(
         self: &Arc<Self>,
@@ -807,7 +1051,7 @@ impl QueueManager {
                     project_id, e
                 )
             })?
-            .ok_or_else(|| anyhow::anyhow!("Project '{}' not found", project_id))?;
+            .ok_or_else(|| AppError::NotFound(format!("Project '{}' not found", project_id)))?;
 
         // Step 1: Create worker types if needed
         for worker_type_spec in worker_types_needed {
@@ -846,6 +1090,7 @@ impl QueueManager {
             )
         })?;
 
+        self.event_broadcaster.notify_outbox();
         info!("Closed planning ticket {}", planning_ticket_id.as_str());
 
         // Step 4: Auto-enqueue ready child tickets (those without dependencies)
@@ -925,6 +1170,8 @@ impl QueueManager {
             worker_type: worker_type_spec.worker_type.clone(),
             short_description: worker_type_spec.short_description.clone(),
             system_prompt: template_content,
+            spawn_overrides: None,
+            capabilities: None,
         };
 
         crate::database::worker_types::WorkerType::create(&self.db, request)
@@ -1032,14 +1279,16 @@ impl QueueManager {
 
             // Store description in comments table as first comment
             if !ticket_spec.description.is_empty() {
+                let (description, key_id) = crate::crypto::seal(&ticket_spec.description)?;
                 sqlx::query(
                     r#"
-                    INSERT INTO comments (ticket_id, worker_type, worker_id, content)
-                    VALUES (?1, 'planning', 'system', ?2)
+                    INSERT INTO comments (ticket_id, worker_type, worker_id, content, content_key_id)
+                    VALUES (?1, 'planning', 'system', ?2, ?3)
                     "#,
                 )
                 .bind(&ticket_id)
-                .bind(&ticket_spec.description)
+                .bind(&description)
+                .bind(key_id)
                 .execute(&mut *tx)
                 .await
                 .inspect_err(|e| {
@@ -0,0 +1,494 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::{Child, Command};
+use tracing::{error, warn};
+
+use crate::mcp::access::matches_pattern;
+
+/// Spawn policy fields a worker type may override
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyField {
+    WorkingDir,
+    EnvAllow,
+    MaxRuntimeSecs,
+    Nice,
+    AllowedPaths,
+    StallAfterSecs,
+    StallTerminateSecs,
+}
+
+impl PolicyField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PolicyField::WorkingDir => "working_dir",
+            PolicyField::EnvAllow => "env_allow",
+            PolicyField::MaxRuntimeSecs => "max_runtime_secs",
+            PolicyField::Nice => "nice",
+            PolicyField::AllowedPaths => "allowed_paths",
+            PolicyField::StallAfterSecs => "stall_after_secs",
+            PolicyField::StallTerminateSecs => "stall_terminate_secs",
+        }
+    }
+}
+
+/// Sandbox applied to every worker process, loaded from the `--worker-policy` JSON file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerSpawnPolicy {
+    /// Working directory; relative paths resolve against the project path (default: the project path)
+    #[serde(default)]
+    pub working_dir: Option<String>,
+    /// Server environment variables passed to workers; patterns support a single `*` wildcard
+    #[serde(default = "default_env_allow")]
+    pub env_allow: Vec<String>,
+    /// Variables never passed to workers, even when allowlisted
+    #[serde(default)]
+    pub env_deny: Vec<String>,
+    /// Runtime after which a worker is sent SIGTERM
+    #[serde(default = "default_max_runtime_secs")]
+    pub max_runtime_secs: u64,
+    /// Time a terminated worker gets to exit before it is sent SIGKILL
+    #[serde(default = "default_kill_grace_secs")]
+    pub kill_grace_secs: u64,
+    /// Scheduling priority adjustment for worker processes (unix only)
+    #[serde(default)]
+    pub nice: Option<i32>,
+    /// Fields worker types may override through their spawn overrides
+    #[serde(default)]
+    pub overridable: Vec<PolicyField>,
+    /// Directory checked for writes outside the worker's workspace; relative paths resolve
+    /// against the project path (default: the project path)
+    #[serde(default)]
+    pub watch_root: Option<String>,
+    /// Paths besides the working directory that workers may write to, relative to the project
+    #[serde(default = "default_allowed_paths")]
+    pub allowed_paths: Vec<String>,
+    /// Also check the workspace boundary this often while the worker runs, stopping it at
+    /// the first write outside (default: only once it exits)
+    #[serde(default)]
+    pub boundary_check_secs: Option<u64>,
+    /// Flag a running worker as stalled once it has not reported progress for this long;
+    /// workers are told to report at least twice as often (null disables the check)
+    #[serde(default = "default_stall_after_secs")]
+    pub stall_after_secs: Option<u64>,
+    /// Stop a worker that has not reported progress for this long (default: never)
+    #[serde(default)]
+    pub stall_terminate_secs: Option<u64>,
+}
+
+/// What a worker needs to run the claude CLI, reach the network, and keep a sane locale
+fn default_env_allow() -> Vec<String> {
+    [
+        "PATH",
+        "HOME",
+        "USER",
+        "LOGNAME",
+        "SHELL",
+        "TERM",
+        "TMPDIR",
+        "TZ",
+        "LANG",
+        "LC_*",
+        "XDG_*",
+        "CLAUDE_*",
+        "ANTHROPIC_*",
+        "HTTP_PROXY",
+        "HTTPS_PROXY",
+        "NO_PROXY",
+        "http_proxy",
+        "https_proxy",
+        "no_proxy",
+        "SYSTEMROOT",
+        "USERPROFILE",
+        "APPDATA",
+        "LOCALAPPDATA",
+        "TEMP",
+        "TMP",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+/// WORKER_TIMEOUT_SECS predates the spawn policy and still sets the default
+fn default_max_runtime_secs() -> u64 {
+    std::env::var("WORKER_TIMEOUT_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(600)
+}
+
+fn default_kill_grace_secs() -> u64 {
+    10
+}
+
+fn default_stall_after_secs() -> Option<u64> {
+    Some(300)
+}
+
+/// Workers commit from their working directory into the project's repository
+fn default_allowed_paths() -> Vec<String> {
+    vec![".git".to_string()]
+}
+
+/// The server writes each worker's MCP config here, whichever directory the worker runs in
+const WORKER_CONFIG_DIR: &str = ".vibe-ensemble-mcp";
+
+impl Default for WorkerSpawnPolicy {
+    fn default() -> Self {
+        Self {
+            working_dir: None,
+            env_allow: default_env_allow(),
+            env_deny: Vec::new(),
+            max_runtime_secs: default_max_runtime_secs(),
+            kill_grace_secs: default_kill_grace_secs(),
+            nice: None,
+            overridable: Vec::new(),
+            watch_root: None,
+            allowed_paths: default_allowed_paths(),
+            boundary_check_secs: None,
+            stall_after_secs: default_stall_after_secs(),
+            stall_terminate_secs: None,
+        }
+    }
+}
+
+/// Per-worker-type overrides of spawn policy fields, stored as JSON on the worker type
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpawnOverrides {
+    pub working_dir: Option<String>,
+    /// Extra patterns added to the policy's allowlist
+    pub env_allow: Option<Vec<String>>,
+    pub max_runtime_secs: Option<u64>,
+    pub nice: Option<i32>,
+    /// Extra paths added to the policy's allowed paths
+    pub allowed_paths: Option<Vec<String>>,
+    pub stall_after_secs: Option<u64>,
+    pub stall_terminate_secs: Option<u64>,
+}
+
+impl SpawnOverrides {
+    pub fn parse(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Invalid spawn overrides")
+    }
+}
+
+/// Sandbox settings for a single worker spawn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerSandbox {
+    pub working_dir: PathBuf,
+    pub env_allow: Vec<String>,
+    pub env_deny: Vec<String>,
+    pub max_runtime_secs: u64,
+    pub kill_grace_secs: u64,
+    pub nice: Option<i32>,
+    /// Directory checked for writes outside `allowed_paths`
+    pub watch_root: PathBuf,
+    /// Where the worker may write: its working directory and the allowed paths
+    pub allowed_paths: Vec<PathBuf>,
+    pub boundary_check_secs: Option<u64>,
+    pub stall_after_secs: Option<u64>,
+    pub stall_terminate_secs: Option<u64>,
+}
+
+impl WorkerSpawnPolicy {
+    /// Load policy from a JSON file
+    pub fn load(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read worker policy file: {}", path))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse worker policy file: {}", path))
+    }
+
+    /// Policy from `path`, or the defaults when none is configured or it cannot be loaded
+    pub fn load_or_default(path: Option<&str>) -> Self {
+        match path {
+            Some(path) => Self::load(path).unwrap_or_else(|e| {
+                error!("Failed to load worker policy, using defaults: {:#}", e);
+                Self::default()
+            }),
+            None => Self::default(),
+        }
+    }
+
+    /// Sandbox for a worker of a project at `project_path`, with the permitted overrides
+    /// applied. Overrides of fields the policy does not mark overridable and working
+    /// directories outside the project are not applied; each is returned as a violation.
+    pub fn resolve(
+        &self,
+        project_path: &Path,
+        overrides: &SpawnOverrides,
+    ) -> (WorkerSandbox, Vec<String>) {
+        let mut violations = Vec::new();
+        let mut permitted = |field: PolicyField, requested: bool| {
+            if requested && !self.overridable.contains(&field) {
+                violations.push(format!(
+                    "Override of '{}' is not permitted by the worker spawn policy",
+                    field.as_str()
+                ));
+                return false;
+            }
+            requested
+        };
+
+        let working_dir = if permitted(PolicyField::WorkingDir, overrides.working_dir.is_some()) {
+            overrides.working_dir.as_deref()
+        } else {
+            self.working_dir.as_deref()
+        };
+        let mut env_allow = self.env_allow.clone();
+        if permitted(PolicyField::EnvAllow, overrides.env_allow.is_some()) {
+            env_allow.extend(overrides.env_allow.iter().flatten().cloned());
+        }
+        let max_runtime_secs = match overrides.max_runtime_secs {
+            Some(secs) if permitted(PolicyField::MaxRuntimeSecs, true) => secs,
+            _ => self.max_runtime_secs,
+        };
+        let nice = match overrides.nice {
+            Some(nice) if permitted(PolicyField::Nice, true) => Some(nice),
+            _ => self.nice,
+        };
+        let mut allowed_paths = self.allowed_paths.clone();
+        if permitted(PolicyField::AllowedPaths, overrides.allowed_paths.is_some()) {
+            allowed_paths.extend(overrides.allowed_paths.iter().flatten().cloned());
+        }
+        let stall_after_secs = match overrides.stall_after_secs {
+            Some(secs) if permitted(PolicyField::StallAfterSecs, true) => Some(secs),
+            _ => self.stall_after_secs,
+        };
+        let stall_terminate_secs = match overrides.stall_terminate_secs {
+            Some(secs) if permitted(PolicyField::StallTerminateSecs, true) => Some(secs),
+            _ => self.stall_terminate_secs,
+        };
+
+        let working_dir = match working_dir {
+            Some(dir) => match Self::project_dir(project_path, dir) {
+                Ok(dir) => dir,
+                Err(e) => {
+                    violations.push(format!("{:#}; using the project path", e));
+                    project_path.to_path_buf()
+                }
+            },
+            None => project_path.to_path_buf(),
+        };
+
+        let project = std::fs::canonicalize(project_path).unwrap_or(project_path.to_path_buf());
+        let watch_root = Self::boundary_path(&project, self.watch_root.as_deref().unwrap_or("."));
+        let allowed_paths = std::iter::once(working_dir.clone())
+            .chain(
+                std::iter::once(WORKER_CONFIG_DIR)
+                    .chain(allowed_paths.iter().map(String::as_str))
+                    .map(|path| Self::boundary_path(&project, path)),
+            )
+            .collect();
+
+        let sandbox = WorkerSandbox {
+            working_dir,
+            env_allow,
+            env_deny: self.env_deny.clone(),
+            max_runtime_secs,
+            kill_grace_secs: self.kill_grace_secs,
+            nice,
+            watch_root,
+            allowed_paths,
+            boundary_check_secs: self.boundary_check_secs,
+            stall_after_secs,
+            stall_terminate_secs,
+        };
+        (sandbox, violations)
+    }
+
+    /// Existing directory `dir` resolved against the project, which it must stay inside
+    fn project_dir(project_path: &Path, dir: &str) -> Result<PathBuf> {
+        let resolved = std::fs::canonicalize(project_path.join(dir))
+            .with_context(|| format!("Working directory '{}' does not exist", dir))?;
+        let project = std::fs::canonicalize(project_path)
+            .with_context(|| format!("Project path '{}' does not exist", project_path.display()))?;
+        if !resolved.starts_with(&project) || !resolved.is_dir() {
+            anyhow::bail!(
+                "Working directory '{}' is not a directory inside the project",
+                dir
+            );
+        }
+        Ok(resolved)
+    }
+
+    /// `path` resolved against the project, canonical when it exists so it compares with
+    /// the paths found by scanning the watched root
+    fn boundary_path(project: &Path, path: &str) -> PathBuf {
+        let path = project.join(path);
+        std::fs::canonicalize(&path).unwrap_or(path)
+    }
+}
+
+impl WorkerSandbox {
+    /// Variables from `vars` that are allowlisted and not denied
+    pub fn filter_env(
+        &self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Vec<(String, String)> {
+        let matches = |patterns: &[String], name: &str| {
+            patterns
+                .iter()
+                .any(|pattern| matches_pattern(pattern, name))
+        };
+        vars.into_iter()
+            .filter(|(name, _)| matches(&self.env_allow, name) && !matches(&self.env_deny, name))
+            .collect()
+    }
+
+    /// Command running `program` under this sandbox's priority, working directory and environment
+    pub fn command(&self, program: &str) -> Command {
+        let mut cmd = match self.nice {
+            #[cfg(unix)]
+            Some(nice) => {
+                let mut cmd = Command::new("nice");
+                cmd.arg("-n").arg(nice.to_string()).arg(program);
+                cmd
+            }
+            _ => Command::new(program),
+        };
+        // Workers do not outlive the server: a process still running when its task is
+        // dropped at shutdown is killed
+        cmd.current_dir(&self.working_dir)
+            .env_clear()
+            .envs(self.filter_env(std::env::vars()))
+            .kill_on_drop(true);
+        cmd
+    }
+
+    pub fn max_runtime(&self) -> Duration {
+        Duration::from_secs(self.max_runtime_secs)
+    }
+
+    pub fn kill_grace(&self) -> Duration {
+        Duration::from_secs(self.kill_grace_secs)
+    }
+
+    /// How often, in whole minutes, the worker is told to report progress: half the stall
+    /// threshold and at least a minute; None when stalls are not checked
+    pub fn heartbeat_minutes(&self) -> Option<u64> {
+        self.stall_after_secs
+            .filter(|secs| *secs > 0)
+            .map(|secs| (secs / 120).max(1))
+    }
+}
+
+/// How a worker that outlived its max runtime was stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Enforcement {
+    /// Exited after SIGTERM
+    Terminated,
+    /// Still running after the grace period and sent SIGKILL
+    Killed,
+}
+
+impl Enforcement {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Enforcement::Terminated => "terminated",
+            Enforcement::Killed => "killed",
+        }
+    }
+}
+
+/// Stop a worker that exceeded its runtime: SIGTERM first, SIGKILL once `grace` passes
+pub async fn terminate(child: &mut Child, grace: Duration) -> Enforcement {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        let signalled = Command::new("kill")
+            .arg("-TERM")
+            .arg(pid.to_string())
+            .status()
+            .await
+            .is_ok_and(|status| status.success());
+        if signalled && tokio::time::timeout(grace, child.wait()).await.is_ok() {
+            return Enforcement::Terminated;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = grace;
+
+    if let Err(e) = child.kill().await {
+        warn!("Failed to kill worker process: {}", e);
+    }
+    Enforcement::Killed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(names: &[&str]) -> Vec<(String, String)> {
+        names
+            .iter()
+            .map(|name| (name.to_string(), "v".to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_env_filtered_by_allow_and_deny_lists() {
+        let policy: WorkerSpawnPolicy = serde_json::from_value(serde_json::json!({
+            "env_deny": ["ANTHROPIC_ADMIN_*"]
+        }))
+        .unwrap();
+        let (sandbox, violations) = policy.resolve(Path::new("/tmp"), &SpawnOverrides::default());
+        assert!(violations.is_empty());
+
+        let passed = sandbox.filter_env(vars(&[
+            "PATH",
+            "LC_ALL",
+            "ANTHROPIC_API_KEY",
+            "ANTHROPIC_ADMIN_KEY",
+            "AWS_SECRET_ACCESS_KEY",
+            "DATABASE_URL",
+        ]));
+        let names: Vec<_> = passed.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["PATH", "LC_ALL", "ANTHROPIC_API_KEY"]);
+    }
+
+    #[test]
+    fn test_only_overridable_fields_are_overridden() {
+        let project = std::env::temp_dir().join(format!("vibe-sandbox-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        let project = std::fs::canonicalize(project).unwrap();
+
+        let policy: WorkerSpawnPolicy = serde_json::from_value(serde_json::json!({
+            "max_runtime_secs": 60,
+            "overridable": ["working_dir", "env_allow"]
+        }))
+        .unwrap();
+        let overrides = SpawnOverrides::parse(
+            r#"{"working_dir": "src", "env_allow": ["CARGO_*"], "max_runtime_secs": 9999, "nice": 5}"#,
+        )
+        .unwrap();
+        let (sandbox, violations) = policy.resolve(&project, &overrides);
+        assert_eq!(sandbox.working_dir, project.join("src"));
+        assert!(sandbox.env_allow.contains(&"CARGO_*".to_string()));
+        assert_eq!(sandbox.max_runtime_secs, 60);
+        assert_eq!(sandbox.nice, None);
+        assert_eq!(violations.len(), 2);
+        assert!(violations[0].contains("max_runtime_secs"));
+        assert!(violations[1].contains("nice"));
+
+        // Working directories may not escape the project
+        let escape = SpawnOverrides::parse(r#"{"working_dir": ".."}"#).unwrap();
+        let (sandbox, violations) = policy.resolve(&project, &escape);
+        assert_eq!(sandbox.working_dir, project);
+        assert_eq!(violations.len(), 1);
+
+        std::fs::remove_dir_all(project).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_overrunning_worker_terminated() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let enforcement = terminate(&mut child, Duration::from_secs(5)).await;
+        assert_eq!(enforcement, Enforcement::Terminated);
+        assert!(child.try_wait().unwrap().is_some());
+    }
+}
@@ -0,0 +1,629 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+use tokio::sync::oneshot;
+use tracing::{debug, info};
+use utoipa::ToSchema;
+
+use crate::database::tickets::Priority;
+
+/// Scheduling rank of a priority; higher runs first
+fn base_rank(priority: &Priority) -> u8 {
+    match priority {
+        Priority::Low => 0,
+        Priority::Medium => 1,
+        Priority::High => 2,
+        Priority::Urgent => 3,
+    }
+}
+
+/// Rank after aging: every `aging_secs` of waiting lifts a spawn one level, up to
+/// High, so only genuinely urgent tickets can preempt an aged one. 0 disables aging.
+fn effective_rank(priority: &Priority, waited_secs: i64, aging_secs: u64) -> u8 {
+    let base = base_rank(priority);
+    if base >= 3 || aging_secs == 0 {
+        return base;
+    }
+    let boost = (waited_secs.max(0) as u64 / aging_secs).min(2) as u8;
+    (base + boost).min(2)
+}
+
+/// Slot held by a running worker; released (and the next queued spawn promoted) on drop
+pub struct SpawnPermit {
+    shared: Arc<Shared>,
+    project_id: String,
+    armed: bool,
+}
+
+impl Drop for SpawnPermit {
+    fn drop(&mut self) {
+        if self.armed {
+            self.shared.release(&self.project_id);
+        }
+    }
+}
+
+/// Outcome of waiting for a spawn slot
+pub enum SpawnSlot {
+    /// Started immediately without queueing
+    Immediate(SpawnPermit),
+    /// Promoted from the queue after waiting
+    Promoted(SpawnPermit),
+    /// Cancelled while queued
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedSpawnInfo {
+    pub position: usize,
+    pub project_id: String,
+    pub worker_type: String,
+    pub ticket_id: String,
+    pub priority: Priority,
+    /// Priority after aging, which decides the queue order
+    pub effective_priority: Priority,
+    pub queued_at: DateTime<Utc>,
+    pub age_secs: i64,
+}
+
+/// Queue depth and longest wait per priority
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct SpawnQueueStats {
+    pub running: usize,
+    pub queued: usize,
+    pub depth_by_priority: BTreeMap<String, usize>,
+    pub max_wait_secs_by_priority: BTreeMap<String, i64>,
+    pub max_wait_secs: i64,
+    pub aging_secs: u64,
+}
+
+struct Waiter {
+    seq: u64,
+    project_id: String,
+    worker_type: String,
+    ticket_id: String,
+    priority: Priority,
+    queued_at: DateTime<Utc>,
+    wake: oneshot::Sender<SpawnPermit>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Limits live with the counters so a reload changes them atomically
+    max_global: usize,
+    max_per_project: usize,
+    running_global: usize,
+    running_by_project: HashMap<String, usize>,
+    /// Per-project limits from project settings, replacing `max_per_project`
+    project_limits: HashMap<String, usize>,
+    waiters: Vec<Waiter>,
+    next_seq: u64,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    aging_secs: u64,
+}
+
+/// Index of the waiter to promote next among those `eligible`: highest effective
+/// rank first, arrival order within a rank
+fn pick_next(
+    waiters: &[Waiter],
+    now: DateTime<Utc>,
+    aging_secs: u64,
+    eligible: impl Fn(&Waiter) -> bool,
+) -> Option<usize> {
+    waiters
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| eligible(w) && !w.wake.is_closed())
+        .max_by_key(|(_, w)| {
+            let waited = (now - w.queued_at).num_seconds();
+            (
+                effective_rank(&w.priority, waited, aging_secs),
+                std::cmp::Reverse(w.seq),
+            )
+        })
+        .map(|(i, _)| i)
+}
+
+impl Shared {
+    fn has_capacity(&self, state: &State, project_id: &str) -> bool {
+        let project_running = state
+            .running_by_project
+            .get(project_id)
+            .copied()
+            .unwrap_or(0);
+        let max_per_project = state
+            .project_limits
+            .get(project_id)
+            .copied()
+            .unwrap_or(state.max_per_project);
+        (state.max_global == 0 || state.running_global < state.max_global)
+            && (max_per_project == 0 || project_running < max_per_project)
+    }
+
+    fn occupy(state: &mut State, project_id: &str) {
+        state.running_global += 1;
+        *state
+            .running_by_project
+            .entry(project_id.to_string())
+            .or_default() += 1;
+    }
+
+    fn vacate(state: &mut State, project_id: &str) {
+        state.running_global = state.running_global.saturating_sub(1);
+        if let Some(count) = state.running_by_project.get_mut(project_id) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                state.running_by_project.remove(project_id);
+            }
+        }
+    }
+
+    fn release(self: &Arc<Self>, project_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        Self::vacate(&mut state, project_id);
+        self.dispatch(&mut state);
+    }
+
+    /// Promote queued spawns while slots are free
+    fn dispatch(self: &Arc<Self>, state: &mut State) {
+        // Waiters whose acquire() was dropped can never be promoted
+        state.waiters.retain(|w| !w.wake.is_closed());
+
+        loop {
+            let now = Utc::now();
+            let Some(index) = pick_next(&state.waiters, now, self.aging_secs, |w| {
+                self.has_capacity(state, &w.project_id)
+            }) else {
+                break;
+            };
+
+            let waiter = state.waiters.remove(index);
+            Self::occupy(state, &waiter.project_id);
+            let permit = SpawnPermit {
+                shared: self.clone(),
+                project_id: waiter.project_id.clone(),
+                armed: true,
+            };
+            if let Err(mut permit) = waiter.wake.send(permit) {
+                // The waiter went away in the meantime; undo without re-entering release()
+                permit.armed = false;
+                Self::vacate(state, &waiter.project_id);
+            }
+        }
+    }
+}
+
+/// Limits concurrently running worker processes globally and per project.
+///
+/// Spawns beyond the limits wait in a priority queue: urgent tickets always go
+/// first, and waiting spawns age upwards so low-priority tickets cannot starve.
+pub struct SpawnLimiter {
+    shared: Arc<Shared>,
+}
+
+impl SpawnLimiter {
+    /// A limit of 0 means unlimited; an aging interval of 0 disables aging
+    pub fn new(max_global: usize, max_per_project: usize, aging_secs: u64) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    max_global,
+                    max_per_project,
+                    ..State::default()
+                }),
+                aging_secs,
+            }),
+        }
+    }
+
+    /// Wait for a spawn slot for the ticket, queueing if limits are reached
+    pub async fn acquire(
+        &self,
+        project_id: &str,
+        worker_type: &str,
+        ticket_id: &str,
+        priority: Priority,
+    ) -> SpawnSlot {
+        let receiver = {
+            let mut state = self.shared.state.lock().unwrap();
+            if self.shared.has_capacity(&state, project_id) {
+                Shared::occupy(&mut state, project_id);
+                return SpawnSlot::Immediate(SpawnPermit {
+                    shared: self.shared.clone(),
+                    project_id: project_id.to_string(),
+                    armed: true,
+                });
+            }
+
+            let (wake, receiver) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiters.push(Waiter {
+                seq,
+                project_id: project_id.to_string(),
+                worker_type: worker_type.to_string(),
+                ticket_id: ticket_id.to_string(),
+                priority: priority.clone(),
+                queued_at: Utc::now(),
+                wake,
+            });
+            info!(
+                "Spawn for ticket {} queued (project={}, type={}, priority={}, queued={})",
+                ticket_id,
+                project_id,
+                worker_type,
+                priority,
+                state.waiters.len()
+            );
+            receiver
+        };
+
+        let slot = match receiver.await {
+            Ok(permit) => SpawnSlot::Promoted(permit),
+            Err(_) => SpawnSlot::Cancelled,
+        };
+        debug!("Spawn for ticket {} left the queue", ticket_id);
+        slot
+    }
+
+    /// Cancel a queued spawn; returns false if the ticket is not queued
+    pub fn cancel(&self, ticket_id: &str) -> bool {
+        let mut state = self.shared.state.lock().unwrap();
+        let before = state.waiters.len();
+        // Dropping the waiter's sender resolves its acquire() as cancelled
+        state.waiters.retain(|w| w.ticket_id != ticket_id);
+        state.waiters.len() != before
+    }
+
+    /// Queued spawns in the order they will be promoted, with positions and ages
+    pub fn queue_status(&self) -> Vec<QueuedSpawnInfo> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Utc::now();
+        let aging_secs = self.shared.aging_secs;
+
+        let mut entries: Vec<(u8, &Waiter)> = state
+            .waiters
+            .iter()
+            .filter(|w| !w.wake.is_closed())
+            .map(|w| {
+                let waited = (now - w.queued_at).num_seconds();
+                (effective_rank(&w.priority, waited, aging_secs), w)
+            })
+            .collect();
+        entries.sort_by_key(|(rank, w)| (std::cmp::Reverse(*rank), w.seq));
+
+        entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, (rank, w))| QueuedSpawnInfo {
+                position: i + 1,
+                project_id: w.project_id.clone(),
+                worker_type: w.worker_type.clone(),
+                ticket_id: w.ticket_id.clone(),
+                priority: w.priority.clone(),
+                effective_priority: match rank {
+                    0 => Priority::Low,
+                    1 => Priority::Medium,
+                    2 => Priority::High,
+                    _ => Priority::Urgent,
+                },
+                queued_at: w.queued_at,
+                age_secs: (now - w.queued_at).num_seconds(),
+            })
+            .collect()
+    }
+
+    pub fn stats(&self) -> SpawnQueueStats {
+        let queued = self.queue_status();
+        let mut stats = SpawnQueueStats {
+            running: self.running_count(),
+            queued: queued.len(),
+            aging_secs: self.shared.aging_secs,
+            ..Default::default()
+        };
+        for entry in &queued {
+            let priority = entry.priority.to_string();
+            *stats.depth_by_priority.entry(priority.clone()).or_default() += 1;
+            let max_wait = stats.max_wait_secs_by_priority.entry(priority).or_default();
+            *max_wait = (*max_wait).max(entry.age_secs);
+            stats.max_wait_secs = stats.max_wait_secs.max(entry.age_secs);
+        }
+        stats
+    }
+
+    /// Override the per-project limit for one project, or restore the default with None.
+    /// Raising a limit promotes queued spawns right away.
+    pub fn set_project_limit(&self, project_id: &str, limit: Option<usize>) {
+        let mut state = self.shared.state.lock().unwrap();
+        let previous = match limit {
+            Some(limit) => state.project_limits.insert(project_id.to_string(), limit),
+            None => state.project_limits.remove(project_id),
+        };
+        if previous != limit {
+            debug!("Spawn limit for project {} set to {:?}", project_id, limit);
+            self.shared.dispatch(&mut state);
+        }
+    }
+
+    /// Project limits overriding the default per-project limit
+    pub fn project_limits(&self) -> HashMap<String, usize> {
+        self.shared.state.lock().unwrap().project_limits.clone()
+    }
+
+    /// Number of worker processes currently running
+    pub fn running_count(&self) -> usize {
+        self.shared.state.lock().unwrap().running_global
+    }
+
+    pub fn limits(&self) -> (usize, usize) {
+        let state = self.shared.state.lock().unwrap();
+        (state.max_global, state.max_per_project)
+    }
+
+    /// Replace the global and default per-project limits. Running workers keep their
+    /// slots; raising a limit promotes queued spawns right away.
+    pub fn set_limits(&self, max_global: usize, max_per_project: usize) {
+        let mut state = self.shared.state.lock().unwrap();
+        if (state.max_global, state.max_per_project) == (max_global, max_per_project) {
+            return;
+        }
+        state.max_global = max_global;
+        state.max_per_project = max_per_project;
+        debug!(
+            "Spawn limits set to {} global, {} per project",
+            max_global, max_per_project
+        );
+        self.shared.dispatch(&mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_queues_beyond_project_limit_and_promotes() {
+        let limiter = Arc::new(SpawnLimiter::new(0, 1, 0));
+
+        let first = limiter.acquire("p1", "impl", "T-1", Priority::Medium).await;
+        assert!(matches!(first, SpawnSlot::Immediate(_)));
+
+        let waiter = {
+            let limiter = limiter.clone();
+            tokio::spawn(
+                async move { limiter.acquire("p1", "impl", "T-2", Priority::Medium).await },
+            )
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let status = limiter.queue_status();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].ticket_id, "T-2");
+        assert_eq!(status[0].position, 1);
+
+        // Other projects are unaffected
+        assert!(matches!(
+            limiter.acquire("p2", "impl", "T-3", Priority::Medium).await,
+            SpawnSlot::Immediate(_)
+        ));
+
+        drop(first);
+        let promoted = tokio::time::timeout(Duration::from_millis(100), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(promoted, SpawnSlot::Promoted(_)));
+        assert!(limiter.queue_status().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_queued_spawn() {
+        let limiter = Arc::new(SpawnLimiter::new(1, 0, 0));
+        let _running = limiter.acquire("p1", "impl", "T-1", Priority::Medium).await;
+
+        let waiter = {
+            let limiter = limiter.clone();
+            tokio::spawn(
+                async move { limiter.acquire("p2", "impl", "T-2", Priority::Medium).await },
+            )
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert!(limiter.cancel("T-2"));
+        assert!(!limiter.cancel("T-unknown"));
+        let slot = tokio::time::timeout(Duration::from_millis(100), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(slot, SpawnSlot::Cancelled));
+    }
+
+    #[tokio::test]
+    async fn test_raising_limits_promotes_queued_spawns() {
+        let limiter = Arc::new(SpawnLimiter::new(1, 0, 0));
+        let _running = limiter.acquire("p1", "impl", "T-1", Priority::Medium).await;
+
+        let waiter = {
+            let limiter = limiter.clone();
+            tokio::spawn(
+                async move { limiter.acquire("p2", "impl", "T-2", Priority::Medium).await },
+            )
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(limiter.queue_status().len(), 1);
+
+        limiter.set_limits(2, 0);
+        assert_eq!(limiter.limits(), (2, 0));
+        let promoted = tokio::time::timeout(Duration::from_millis(100), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(promoted, SpawnSlot::Promoted(_)));
+
+        // Lowering a limit keeps running workers and only holds back new spawns
+        limiter.set_limits(1, 0);
+        assert_eq!(limiter.running_count(), 2);
+        let waiter = {
+            let limiter = limiter.clone();
+            tokio::spawn(
+                async move { limiter.acquire("p3", "impl", "T-3", Priority::Medium).await },
+            )
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(limiter.queue_status().len(), 1);
+        waiter.abort();
+    }
+
+    #[tokio::test]
+    async fn test_project_limits_override_default() {
+        let limiter = Arc::new(SpawnLimiter::new(0, 1, 0));
+        limiter.set_project_limit("wide", Some(2));
+
+        let _a = limiter
+            .acquire("wide", "impl", "W-1", Priority::Medium)
+            .await;
+        let b = limiter
+            .acquire("wide", "impl", "W-2", Priority::Medium)
+            .await;
+        assert!(matches!(b, SpawnSlot::Immediate(_)));
+        let _c = limiter
+            .acquire("narrow", "impl", "N-1", Priority::Medium)
+            .await;
+
+        let waiter = {
+            let limiter = limiter.clone();
+            tokio::spawn(async move {
+                limiter
+                    .acquire("narrow", "impl", "N-2", Priority::Medium)
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(limiter.queue_status().len(), 1);
+
+        // Raising the narrow project's limit promotes its queued spawn without a release
+        limiter.set_project_limit("narrow", Some(2));
+        let promoted = tokio::time::timeout(Duration::from_millis(100), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(promoted, SpawnSlot::Promoted(_)));
+        assert_eq!(limiter.project_limits().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_urgent_preempts_earlier_arrivals() {
+        let limiter = Arc::new(SpawnLimiter::new(1, 0, 300));
+        let running = limiter.acquire("p1", "impl", "T-0", Priority::Low).await;
+
+        let (done_tx, mut done_rx) = tokio::sync::mpsc::unbounded_channel();
+        for (ticket, priority) in [
+            ("T-low", Priority::Low),
+            ("T-medium", Priority::Medium),
+            ("T-urgent", Priority::Urgent),
+        ] {
+            let limiter = limiter.clone();
+            let done_tx = done_tx.clone();
+            tokio::spawn(async move {
+                let slot = limiter.acquire("p1", "impl", ticket, priority).await;
+                done_tx.send(ticket).unwrap();
+                // Hold the slot briefly so promotions happen one at a time
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                drop(slot);
+            });
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        assert_eq!(limiter.stats().depth_by_priority.get("urgent"), Some(&1));
+        drop(running);
+
+        let mut order = Vec::new();
+        for _ in 0..3 {
+            order.push(done_rx.recv().await.unwrap());
+        }
+        assert_eq!(order, ["T-urgent", "T-medium", "T-low"]);
+    }
+
+    /// Tiny deterministic PRNG so the randomized test needs no extra dependencies
+    fn lcg(seed: &mut u64) -> u64 {
+        *seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        *seed >> 33
+    }
+
+    #[test]
+    fn test_pick_order_invariants_on_random_mix() {
+        let aging_secs = 60;
+        let now = Utc::now();
+        let mut seed = 42;
+
+        for _round in 0..200 {
+            let count = 1 + lcg(&mut seed) as usize % 40;
+            let mut waiters: Vec<Waiter> = (0..count)
+                .map(|i| {
+                    let priority = match lcg(&mut seed) % 4 {
+                        0 => Priority::Low,
+                        1 => Priority::Medium,
+                        2 => Priority::High,
+                        _ => Priority::Urgent,
+                    };
+                    // Older arrivals have smaller sequence numbers
+                    let waited = (count - i) as i64 * (lcg(&mut seed) % 40) as i64;
+                    Waiter {
+                        seq: i as u64,
+                        project_id: "p".to_string(),
+                        worker_type: "impl".to_string(),
+                        ticket_id: format!("T-{}", i),
+                        priority,
+                        queued_at: now - chrono::Duration::seconds(waited),
+                        wake: oneshot::channel().0,
+                    }
+                })
+                .collect();
+            // Keep receivers alive so no waiter looks abandoned
+            let mut receivers = Vec::new();
+            for waiter in &mut waiters {
+                let (tx, rx) = oneshot::channel();
+                waiter.wake = tx;
+                receivers.push(rx);
+            }
+
+            let mut lower_priority_promoted_while_urgent_waiting = 0;
+            while let Some(index) = pick_next(&waiters, now, aging_secs, |_| true) {
+                let picked = waiters.remove(index);
+                let waited = (now - picked.queued_at).num_seconds();
+                let picked_rank = effective_rank(&picked.priority, waited, aging_secs);
+
+                for other in &waiters {
+                    let other_rank = effective_rank(
+                        &other.priority,
+                        (now - other.queued_at).num_seconds(),
+                        aging_secs,
+                    );
+                    // Nothing left behind outranks the pick, and equal ranks go in arrival order
+                    assert!(
+                        other_rank < picked_rank
+                            || (other_rank == picked_rank && other.seq > picked.seq)
+                    );
+                    if other.priority == Priority::Urgent && picked.priority != Priority::Urgent {
+                        lower_priority_promoted_while_urgent_waiting += 1;
+                    }
+                }
+
+                // Anything that waited two aging intervals competes at High at least
+                if waited >= 2 * aging_secs as i64 {
+                    assert!(picked_rank >= 2);
+                }
+            }
+            assert_eq!(lower_priority_promoted_while_urgent_waiting, 0);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use tracing::{debug, info};
 
-use crate::database::DbPool;
+use crate::database::{comments::Comment, DbPool};
 
 /// Manages ticket state transitions and stage progressions
 pub struct TicketTransitionManager {
@@ -44,16 +44,7 @@ impl TicketTransitionManager {
 
         // Add comment about the transition
         let comment_text = format!("Stage transition: {}", comment);
-        sqlx::query!(
-            r#"
-            INSERT INTO comments (ticket_id, content, created_at)
-            VALUES (?1, ?2, datetime('now'))
-            "#,
-            ticket_id,
-            comment_text
-        )
-        .execute(&self.db)
-        .await?;
+        Comment::create(&self.db, ticket_id, None, None, None, &comment_text).await?;
 
         info!(
             ticket_id = %ticket_id,
@@ -88,16 +79,7 @@ impl TicketTransitionManager {
 
         // Add comment about being placed on hold
         let comment_text = format!("Placed on hold: {}", reason);
-        sqlx::query!(
-            r#"
-            INSERT INTO comments (ticket_id, content, created_at)
-            VALUES (?1, ?2, datetime('now'))
-            "#,
-            ticket_id,
-            comment_text
-        )
-        .execute(&self.db)
-        .await?;
+        Comment::create(&self.db, ticket_id, None, None, None, &comment_text).await?;
 
         info!(
             ticket_id = %ticket_id,
@@ -239,16 +221,7 @@ impl TicketTransitionManager {
 
         // Add final comment
         let comment_text = format!("Completed: {}", final_comment);
-        sqlx::query!(
-            r#"
-            INSERT INTO comments (ticket_id, content, created_at)
-            VALUES (?1, ?2, datetime('now'))
-            "#,
-            ticket_id,
-            comment_text
-        )
-        .execute(&self.db)
-        .await?;
+        Comment::create(&self.db, ticket_id, None, None, None, &comment_text).await?;
 
         info!(
             ticket_id = %ticket_id,
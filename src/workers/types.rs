@@ -1,3 +1,4 @@
+use super::sandbox::WorkerSandbox;
 use crate::permissions::PermissionMode;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -57,9 +58,12 @@ pub struct SpawnWorkerRequest {
     pub project_patterns: Option<String>,
     pub server_host: String,
     pub server_port: u16,
+    /// Path prefix the server's routes are mounted under (empty for none)
+    pub server_base_path: String,
     pub permission_mode: PermissionMode,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
+    pub sandbox: WorkerSandbox,
 }
 
 pub type WorkerRegistry = RwLock<HashMap<String, WorkerProcess>>;
@@ -0,0 +1,552 @@
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+
+use super::process::{ProcessManager, StartedWorker, WARM_TICKET_PLACEHOLDER};
+use super::types::SpawnWorkerRequest;
+use crate::config::Config;
+
+/// Longest pause between checks for expired or exited idle workers
+const MAX_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Which workers are kept warm, from `--warm-pool-*`
+#[derive(Debug, Clone, Default)]
+pub struct WarmPoolConfig {
+    /// Idle workers kept per project and worker type; 0 disables the pool
+    pub size: usize,
+    /// Idle workers older than this are stopped and replaced
+    pub max_idle: Duration,
+    /// Worker types kept warm; empty for every worker type
+    pub worker_types: Vec<String>,
+}
+
+impl WarmPoolConfig {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            size: config.warm_pool_size,
+            max_idle: Duration::from_secs(config.warm_pool_max_idle_secs),
+            worker_types: config.warm_pool_worker_types.clone(),
+        }
+    }
+
+    pub fn is_eligible(&self, worker_type: &str) -> bool {
+        self.size > 0
+            && (self.worker_types.is_empty() || self.worker_types.iter().any(|t| t == worker_type))
+    }
+}
+
+type PoolKey = (String, String);
+
+struct WarmWorker {
+    warm_id: String,
+    /// Hash of the template it was started from; a worker whose worker type, project
+    /// rules or sandbox changed since is not handed out
+    fingerprint: u64,
+    process: StartedWorker,
+    started: Instant,
+    started_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl WarmWorker {
+    fn is_usable(&mut self, fingerprint: u64, max_idle: Duration) -> bool {
+        self.fingerprint == fingerprint
+            && self.started.elapsed() < max_idle
+            && !self.process.has_exited()
+    }
+}
+
+/// An idle warm worker, as listed by get_worker_status
+#[derive(Debug, Clone, Serialize)]
+pub struct IdleWarmWorker {
+    pub warm_id: String,
+    pub project_id: String,
+    pub worker_type: String,
+    pub pid: Option<u32>,
+    pub started_at: String,
+    pub idle_secs: u64,
+}
+
+#[derive(Default)]
+struct Latencies {
+    count: u64,
+    total_ms: u64,
+    max_ms: u64,
+}
+
+impl Latencies {
+    fn record(&mut self, latency: Duration) {
+        let ms = latency.as_millis() as u64;
+        self.count += 1;
+        self.total_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+    }
+
+    fn average_ms(&self) -> Option<u64> {
+        (self.count > 0).then(|| self.total_ms / self.count)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WarmPoolStats {
+    pub size: usize,
+    pub max_idle_secs: u64,
+    pub worker_types: Vec<String>,
+    pub idle: usize,
+    /// Warm workers started, and starts that failed
+    pub started: u64,
+    pub start_failures: u64,
+    /// Spawns served by a warm worker, and spawns that had to start a process
+    pub claims: u64,
+    pub misses: u64,
+    /// Idle workers stopped for outliving --warm-pool-max-idle-secs or a changed worker
+    /// type, and idle workers that exited on their own
+    pub recycled: u64,
+    pub exited: u64,
+    /// From the spawn request until a warm worker had its assignment on stdin
+    pub avg_claim_latency_ms: Option<u64>,
+    pub max_claim_latency_ms: u64,
+    /// From the spawn request until a new process was started, without its startup
+    pub avg_cold_spawn_latency_ms: Option<u64>,
+    pub max_cold_spawn_latency_ms: u64,
+}
+
+/// Idle worker processes started ahead of demand per project and worker type, so a spawn
+/// can hand its ticket to one over stdin instead of waiting for a cold start. Pools fill
+/// when a worker type first spawns a worker and are topped up after every spawn.
+pub struct WarmPool {
+    config: WarmPoolConfig,
+    idle: Mutex<HashMap<PoolKey, Vec<WarmWorker>>>,
+    /// Latest spawn request per pool, used to refill it
+    templates: Mutex<HashMap<PoolKey, SpawnWorkerRequest>>,
+    next_id: AtomicU64,
+    draining: AtomicBool,
+    started: AtomicU64,
+    start_failures: AtomicU64,
+    claims: AtomicU64,
+    misses: AtomicU64,
+    recycled: AtomicU64,
+    exited: AtomicU64,
+    claim_latency: Mutex<Latencies>,
+    cold_spawn_latency: Mutex<Latencies>,
+}
+
+/// `request` without its ticket, as idle workers of its pool are started
+fn warm_template(request: &SpawnWorkerRequest) -> SpawnWorkerRequest {
+    let mut template = request.clone();
+    template.ticket_id = WARM_TICKET_PLACEHOLDER.to_string();
+    template.worker_id = String::new();
+    template
+}
+
+fn fingerprint(template: &SpawnWorkerRequest) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(template)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+impl WarmPool {
+    pub fn new(config: WarmPoolConfig) -> Self {
+        Self {
+            config,
+            idle: Mutex::new(HashMap::new()),
+            templates: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            draining: AtomicBool::new(false),
+            started: AtomicU64::new(0),
+            start_failures: AtomicU64::new(0),
+            claims: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            recycled: AtomicU64::new(0),
+            exited: AtomicU64::new(0),
+            claim_latency: Mutex::new(Latencies::default()),
+            cold_spawn_latency: Mutex::new(Latencies::default()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.size > 0
+    }
+
+    /// Take an idle worker started from the same worker type, project rules and sandbox as
+    /// `request`, oldest first. Workers that expired, exited or no longer match are stopped.
+    pub fn claim(&self, request: &SpawnWorkerRequest) -> Option<StartedWorker> {
+        if !self.config.is_eligible(&request.worker_type) {
+            return None;
+        }
+        let wanted = fingerprint(&warm_template(request));
+        let key = (request.project_id.clone(), request.worker_type.clone());
+
+        let mut discarded = Vec::new();
+        let claimed = {
+            let mut idle = self.idle.lock().unwrap();
+            let workers = idle.entry(key).or_default();
+            let mut claimed = None;
+            while !workers.is_empty() {
+                let mut worker = workers.remove(0);
+                if worker.is_usable(wanted, self.config.max_idle) {
+                    claimed = Some(worker);
+                    break;
+                }
+                discarded.push(worker);
+            }
+            claimed
+        };
+        self.stop_all(discarded);
+
+        match claimed {
+            Some(worker) => {
+                self.claims.fetch_add(1, Ordering::Relaxed);
+                debug!(
+                    "Claimed warm worker {} after {}s idle",
+                    worker.warm_id,
+                    worker.started.elapsed().as_secs()
+                );
+                Some(worker.process)
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub fn record_claim(&self, latency: Duration) {
+        self.claim_latency.lock().unwrap().record(latency);
+    }
+
+    pub fn record_cold_spawn(&self, latency: Duration) {
+        self.cold_spawn_latency.lock().unwrap().record(latency);
+    }
+
+    /// Start idle workers like `request` until its pool is full again
+    pub fn replenish(&self, request: &SpawnWorkerRequest) {
+        if !self.config.is_eligible(&request.worker_type) || self.draining.load(Ordering::SeqCst) {
+            return;
+        }
+        let key = (request.project_id.clone(), request.worker_type.clone());
+        let template = warm_template(request);
+        self.templates
+            .lock()
+            .unwrap()
+            .insert(key.clone(), template.clone());
+        self.fill(&key, &template);
+    }
+
+    fn fill(&self, key: &PoolKey, template: &SpawnWorkerRequest) {
+        let wanted = fingerprint(template);
+        let (discarded, missing) = {
+            let mut idle = self.idle.lock().unwrap();
+            let workers = idle.entry(key.clone()).or_default();
+            let (keep, discarded): (Vec<_>, Vec<_>) = std::mem::take(workers)
+                .into_iter()
+                .partition(|w| w.fingerprint == wanted);
+            *workers = keep;
+            (discarded, self.config.size.saturating_sub(workers.len()))
+        };
+        self.stop_all(discarded);
+
+        for _ in 0..missing {
+            let warm_id = format!(
+                "{}:{}:warm-{}",
+                key.0.replace('/', "-"),
+                key.1,
+                self.next_id.fetch_add(1, Ordering::Relaxed)
+            );
+            let mut request = template.clone();
+            request.worker_id = warm_id.clone();
+            match ProcessManager::spawn_warm(&request) {
+                Ok(process) => {
+                    self.started.fetch_add(1, Ordering::Relaxed);
+                    self.park(
+                        key.clone(),
+                        WarmWorker {
+                            warm_id,
+                            fingerprint: wanted,
+                            process,
+                            started: Instant::now(),
+                            started_at: chrono::Utc::now(),
+                        },
+                    );
+                }
+                Err(e) => {
+                    self.start_failures.fetch_add(1, Ordering::Relaxed);
+                    warn!(
+                        "Failed to start warm {} worker for project {}: {}",
+                        key.1, key.0, e
+                    );
+                    return;
+                }
+            }
+        }
+    }
+
+    fn park(&self, key: PoolKey, worker: WarmWorker) {
+        if self.draining.load(Ordering::SeqCst) {
+            // Dropping kills it
+            return;
+        }
+        self.idle
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_default()
+            .push(worker);
+    }
+
+    /// Stop idle workers in the background
+    fn stop_all(&self, workers: Vec<WarmWorker>) {
+        if workers.is_empty() {
+            return;
+        }
+        let max_idle = self.config.max_idle;
+        let mut stopped = Vec::with_capacity(workers.len());
+        for mut worker in workers {
+            if worker.process.has_exited() {
+                self.exited.fetch_add(1, Ordering::Relaxed);
+            } else {
+                self.recycled.fetch_add(1, Ordering::Relaxed);
+                debug!(
+                    "Recycling warm worker {} ({}s idle, limit {}s)",
+                    worker.warm_id,
+                    worker.started.elapsed().as_secs(),
+                    max_idle.as_secs()
+                );
+            }
+            stopped.push(worker.process);
+        }
+        tokio::spawn(async move {
+            for process in stopped {
+                process.stop().await;
+            }
+        });
+    }
+
+    /// Replace idle workers that expired or exited
+    pub fn sweep(&self) {
+        let templates: Vec<(PoolKey, SpawnWorkerRequest)> = self
+            .templates
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(key, template)| (key.clone(), template.clone()))
+            .collect();
+        for (key, template) in templates {
+            let wanted = fingerprint(&template);
+            let discarded: Vec<WarmWorker> = {
+                let mut idle = self.idle.lock().unwrap();
+                let workers = idle.entry(key.clone()).or_default();
+                let mut discarded = Vec::new();
+                let mut index = 0;
+                while index < workers.len() {
+                    if workers[index].is_usable(wanted, self.config.max_idle) {
+                        index += 1;
+                    } else {
+                        discarded.push(workers.remove(index));
+                    }
+                }
+                discarded
+            };
+            self.stop_all(discarded);
+            if !self.draining.load(Ordering::SeqCst) {
+                self.fill(&key, &template);
+            }
+        }
+    }
+
+    /// Sweep the pools periodically; does nothing while the pool is disabled
+    pub fn start_sweeper(self: &Arc<Self>) {
+        if !self.is_enabled() {
+            return;
+        }
+        let interval = (self.config.max_idle / 4).clamp(Duration::from_secs(1), MAX_SWEEP_INTERVAL);
+        let pool = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(pool) = pool.upgrade() else { break };
+                if pool.draining.load(Ordering::SeqCst) {
+                    break;
+                }
+                pool.sweep();
+            }
+        });
+    }
+
+    /// Stop every idle worker and start no more; called on shutdown
+    pub async fn drain(&self) {
+        self.draining.store(true, Ordering::SeqCst);
+        let workers: Vec<WarmWorker> = self
+            .idle
+            .lock()
+            .unwrap()
+            .drain()
+            .flat_map(|(_, workers)| workers)
+            .collect();
+        if workers.is_empty() {
+            return;
+        }
+        info!("Stopping {} idle warm worker(s)", workers.len());
+        for worker in workers {
+            worker.process.stop().await;
+        }
+    }
+
+    /// Idle workers by project and worker type, oldest first
+    pub fn idle_workers(&self) -> Vec<IdleWarmWorker> {
+        let idle = self.idle.lock().unwrap();
+        let mut listed: Vec<IdleWarmWorker> = idle
+            .iter()
+            .flat_map(|((project_id, worker_type), workers)| {
+                workers.iter().map(move |worker| IdleWarmWorker {
+                    warm_id: worker.warm_id.clone(),
+                    project_id: project_id.clone(),
+                    worker_type: worker_type.clone(),
+                    pid: worker.process.pid(),
+                    started_at: worker.started_at.to_rfc3339(),
+                    idle_secs: worker.started.elapsed().as_secs(),
+                })
+            })
+            .collect();
+        listed.sort_by(|a, b| {
+            (&a.project_id, &a.worker_type, b.idle_secs).cmp(&(
+                &b.project_id,
+                &b.worker_type,
+                a.idle_secs,
+            ))
+        });
+        listed
+    }
+
+    pub fn stats(&self) -> WarmPoolStats {
+        let claim_latency = self.claim_latency.lock().unwrap();
+        let cold_spawn_latency = self.cold_spawn_latency.lock().unwrap();
+        WarmPoolStats {
+            size: self.config.size,
+            max_idle_secs: self.config.max_idle.as_secs(),
+            worker_types: self.config.worker_types.clone(),
+            idle: self.idle.lock().unwrap().values().map(Vec::len).sum(),
+            started: self.started.load(Ordering::Relaxed),
+            start_failures: self.start_failures.load(Ordering::Relaxed),
+            claims: self.claims.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            recycled: self.recycled.load(Ordering::Relaxed),
+            exited: self.exited.load(Ordering::Relaxed),
+            avg_claim_latency_ms: claim_latency.average_ms(),
+            max_claim_latency_ms: claim_latency.max_ms,
+            avg_cold_spawn_latency_ms: cold_spawn_latency.average_ms(),
+            max_cold_spawn_latency_ms: cold_spawn_latency.max_ms,
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::permissions::PermissionMode;
+    use crate::workers::sandbox::{SpawnOverrides, WorkerSpawnPolicy};
+    use std::process::Stdio;
+    use tokio::process::Command;
+
+    fn request(worker_type: &str, ticket_id: &str) -> SpawnWorkerRequest {
+        let (sandbox, _) =
+            WorkerSpawnPolicy::default().resolve(&std::env::temp_dir(), &SpawnOverrides::default());
+        SpawnWorkerRequest {
+            worker_id: format!("p:{}:{}", worker_type, ticket_id),
+            project_id: "p".to_string(),
+            worker_type: worker_type.to_string(),
+            queue_name: format!("p:{}", worker_type),
+            ticket_id: ticket_id.to_string(),
+            project_path: std::env::temp_dir().display().to_string(),
+            system_prompt: "Write the code".to_string(),
+            project_rules: None,
+            project_patterns: None,
+            server_host: "127.0.0.1".to_string(),
+            server_port: 3276,
+            server_base_path: String::new(),
+            permission_mode: PermissionMode::Bypass,
+            model: None,
+            sandbox,
+        }
+    }
+
+    /// Park a stand-in worker (`cat`, which waits on stdin) as if started from `template`
+    fn park_stand_in(pool: &WarmPool, template: &SpawnWorkerRequest, age: Duration) {
+        let child = Command::new("cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        pool.park(
+            (template.project_id.clone(), template.worker_type.clone()),
+            WarmWorker {
+                warm_id: format!("p:{}:warm-test", template.worker_type),
+                fingerprint: fingerprint(&warm_template(template)),
+                process: StartedWorker::for_test(child),
+                started: Instant::now() - age,
+                started_at: chrono::Utc::now(),
+            },
+        );
+    }
+
+    #[tokio::test]
+    async fn test_claims_only_matching_fresh_workers() {
+        let pool = WarmPool::new(WarmPoolConfig {
+            size: 2,
+            max_idle: Duration::from_secs(600),
+            worker_types: vec!["coding".to_string()],
+        });
+        let coding = request("coding", "P-1");
+        park_stand_in(&pool, &coding, Duration::from_secs(5));
+        assert_eq!(pool.idle_workers().len(), 1);
+
+        // Other worker types are not pooled, and a changed prompt makes the worker stale
+        assert!(pool.claim(&request("review", "P-1")).is_none());
+        let mut changed = request("coding", "P-2");
+        changed.system_prompt = "Write the code, with tests".to_string();
+        assert!(pool.claim(&changed).is_none());
+        assert_eq!(pool.idle_workers().len(), 0);
+
+        // Any ticket of the same worker type can claim a matching worker
+        park_stand_in(&pool, &coding, Duration::from_secs(5));
+        let claimed = pool.claim(&request("coding", "P-3"));
+        assert!(claimed.is_some());
+        claimed.unwrap().stop().await;
+
+        // Expired workers are recycled instead of claimed
+        park_stand_in(&pool, &coding, Duration::from_secs(601));
+        assert!(pool.claim(&coding).is_none());
+
+        let stats = pool.stats();
+        assert_eq!((stats.claims, stats.misses, stats.recycled), (1, 2, 2));
+        pool.drain().await;
+    }
+
+    #[tokio::test]
+    async fn test_drain_stops_idle_workers_and_refuses_new_ones() {
+        let pool = WarmPool::new(WarmPoolConfig {
+            size: 1,
+            max_idle: Duration::from_secs(600),
+            worker_types: Vec::new(),
+        });
+        let coding = request("coding", "P-1");
+        park_stand_in(&pool, &coding, Duration::ZERO);
+        pool.drain().await;
+        assert!(pool.idle_workers().is_empty());
+
+        park_stand_in(&pool, &coding, Duration::ZERO);
+        pool.replenish(&coding);
+        assert!(pool.idle_workers().is_empty());
+    }
+}
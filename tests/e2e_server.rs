@@ -0,0 +1,130 @@
+//! Runs the real server binary: CLI parsing, WebSocket upgrade and MCP over it, the
+//! dashboard API, and graceful shutdown on SIGINT.
+
+mod support;
+
+use serde_json::json;
+use std::time::Duration;
+use support::{process_running, TestServer};
+
+const SHUTDOWN_GRACE_SECS: u64 = 3;
+
+#[tokio::test]
+async fn test_server_binary_end_to_end() {
+    let mut server =
+        TestServer::start(&["--shutdown-grace-secs", &SHUTDOWN_GRACE_SECS.to_string()]).unwrap();
+    // Workers are this stand-in for claude, which runs until it is stopped
+    server
+        .install_command("claude", "#!/bin/sh\nexec sleep 300\n")
+        .unwrap();
+    server.wait_ready(Duration::from_secs(30)).await.unwrap();
+
+    let mut client = server.connect().await.unwrap();
+    let init = client.initialize("e2e").await.unwrap();
+    assert!(init["serverInfo"]["name"].is_string());
+
+    let project_path = server.dir.join("project");
+    std::fs::create_dir_all(&project_path).unwrap();
+    let project = client
+        .call_tool(
+            "create_project",
+            json!({"repository_name": "e2e/app", "path": project_path}),
+        )
+        .await
+        .unwrap();
+    let project_id = project["repository_name"].as_str().unwrap().to_string();
+    client
+        .call_tool(
+            "create_worker_type",
+            json!({
+                "project_id": project_id,
+                "worker_type": "coding",
+                "system_prompt": "Write the code"
+            }),
+        )
+        .await
+        .unwrap();
+    let ticket = client
+        .call_tool(
+            "create_ticket",
+            json!({
+                "project_id": project_id,
+                "title": "End to end",
+                "description": "Exercise the binary",
+                "initial_stage": "coding"
+            }),
+        )
+        .await
+        .unwrap();
+    let ticket_id = ticket["ticket_id"].as_str().unwrap().to_string();
+    client
+        .call_tool(
+            "add_ticket_comment",
+            json!({
+                "ticket_id": ticket_id,
+                "worker_type": "coding",
+                "worker_id": "coordinator",
+                "stage_number": 1,
+                "content": "Hello from the e2e client"
+            }),
+        )
+        .await
+        .unwrap();
+
+    let tools = client.list_tools().await.unwrap();
+    assert!(tools.iter().any(|name| name == "create_ticket"));
+
+    // The dashboard API sees what the MCP client created
+    let projects = server.api_get("/api/projects").await.unwrap();
+    assert!(projects
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|p| p["repository_name"] == project_id.as_str()));
+    let detail = server
+        .api_get(&format!(
+            "/api/projects/{}/tickets/{}",
+            project_id.replace('/', "%2F"),
+            ticket_id
+        ))
+        .await
+        .unwrap();
+    assert_eq!(detail["ticket"]["title"], "End to end");
+    assert!(detail["comments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|c| c["content"] == "Hello from the e2e client"));
+    let short_id = ticket["short_id"].as_str().unwrap();
+    let by_short_id = server
+        .api_get(&format!("/api/tickets/{}", short_id))
+        .await
+        .unwrap();
+    assert_eq!(by_short_id["ticket"]["ticket_id"], ticket_id.as_str());
+
+    // The ticket's stage spawns a worker, which must not outlive the server
+    let workers = support::poll_until(Duration::from_secs(20), Duration::from_millis(100), || {
+        std::future::ready(Some(server.child_pids()).filter(|pids| !pids.is_empty()))
+    })
+    .await
+    .expect("no worker process was spawned");
+
+    // The client stays connected: shutdown must not wait on it past the grace period
+    server.interrupt().unwrap();
+    let status = server
+        .wait_exit(Duration::from_secs(SHUTDOWN_GRACE_SECS + 5))
+        .await
+        .unwrap();
+    assert!(status.success(), "server exited with {}", status);
+    for pid in workers {
+        assert!(!process_running(pid), "worker {} outlived the server", pid);
+    }
+    assert!(server
+        .logs()
+        .contains("Shutting down; waiting up to 3s for open connections"));
+    assert!(!server
+        .dir
+        .join(format!("home/.claude/ide/{}.lock", server.port))
+        .exists());
+    drop(client);
+}
@@ -0,0 +1,335 @@
+//! Shared helpers for end-to-end tests that run the real `vibe-ensemble-mcp` binary: port
+//! picking, readiness polling, a WebSocket MCP client and log capture on failure.
+//! Each test file includes this module, and none uses every helper.
+#![allow(dead_code)]
+
+use anyhow::{anyhow, bail, Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::{
+    fs,
+    future::Future,
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    time::{Duration, Instant},
+};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    tungstenite::{client::IntoClientRequest, Message},
+    MaybeTlsStream, WebSocketStream,
+};
+
+/// A port nothing listens on right now
+pub fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .expect("no free port")
+}
+
+/// Poll `check` every `interval` until it yields a value or `timeout` passes
+pub async fn poll_until<T, F, Fut>(timeout: Duration, interval: Duration, mut check: F) -> Option<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Option<T>>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(value) = check().await {
+            return Some(value);
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// The server binary running against its own temp directory, database and port. Its output
+/// goes to `server.log` there, printed when a test panics; the process is killed and the
+/// directory removed on drop.
+pub struct TestServer {
+    child: Child,
+    pub port: u16,
+    pub dir: PathBuf,
+}
+
+impl TestServer {
+    /// Start the binary with `args` on top of the test defaults
+    pub fn start(args: &[&str]) -> Result<TestServer> {
+        let dir = std::env::temp_dir().join(format!("vibe-e2e-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("bin"))?;
+        fs::create_dir_all(dir.join("home"))?;
+        let port = free_port();
+        let log = fs::File::create(dir.join("server.log"))?;
+
+        let path = format!(
+            "{}:{}",
+            dir.join("bin").display(),
+            std::env::var("PATH").unwrap_or_default()
+        );
+        let child = Command::new(env!("CARGO_BIN_EXE_vibe-ensemble-mcp"))
+            .current_dir(&dir)
+            .env("HOME", dir.join("home"))
+            .env("PATH", path)
+            .env_remove("RUST_LOG")
+            .arg("--database-path")
+            .arg(dir.join("vibe-ensemble.db"))
+            .args(["--host", "127.0.0.1", "--port", &port.to_string()])
+            .args(["--permission-mode", "bypass", "--disable-update-checks"])
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log)
+            .spawn()
+            .context("Failed to start the server binary")?;
+        Ok(TestServer { child, port, dir })
+    }
+
+    /// Put an executable script on the server's PATH, e.g. a stand-in `claude`
+    pub fn install_command(&self, name: &str, script: &str) -> Result<PathBuf> {
+        let path = self.dir.join("bin").join(name);
+        fs::write(&path, script)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(path)
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.child.id()
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    /// Wait until `/health` reports healthy
+    pub async fn wait_ready(&mut self, timeout: Duration) -> Result<()> {
+        let client = reqwest::Client::new();
+        let url = format!("{}/health", self.base_url());
+        let ready = poll_until(timeout, Duration::from_millis(100), || async {
+            let response = client.get(&url).send().await.ok()?;
+            let body: Value = response.json().await.ok()?;
+            (body["status"] == "healthy").then_some(())
+        })
+        .await;
+        if ready.is_none() {
+            if let Some(status) = self.child.try_wait()? {
+                bail!("Server exited before becoming ready: {}", status);
+            }
+            bail!("Server not ready after {:?}", timeout);
+        }
+        Ok(())
+    }
+
+    /// Token the server wrote to its Claude IDE lock file for WebSocket clients
+    pub fn websocket_token(&self) -> Result<String> {
+        let path = self
+            .dir
+            .join("home/.claude/ide")
+            .join(format!("{}.lock", self.port));
+        let lock: Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        lock["authToken"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("No authToken in {}", path.display()))
+    }
+
+    /// Connect a WebSocket MCP client authenticated with the lock file token
+    pub async fn connect(&self) -> Result<McpClient> {
+        let mut request = format!("ws://127.0.0.1:{}/", self.port).into_client_request()?;
+        let headers = request.headers_mut();
+        headers.insert("sec-websocket-protocol", "mcp".parse()?);
+        headers.insert(
+            "x-claude-code-ide-authorization",
+            self.websocket_token()?.parse()?,
+        );
+        let (socket, _) = tokio_tungstenite::connect_async(request).await?;
+        Ok(McpClient { socket, next_id: 1 })
+    }
+
+    /// GET a dashboard API path, e.g. `/api/projects`
+    pub async fn api_get(&self, path: &str) -> Result<Value> {
+        let response = reqwest::get(format!("{}{}", self.base_url(), path)).await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            bail!("GET {} failed with {}: {}", path, status, body);
+        }
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Send SIGINT, as Ctrl-C would
+    pub fn interrupt(&self) -> Result<()> {
+        let status = Command::new("kill")
+            .arg("-INT")
+            .arg(self.pid().to_string())
+            .status()?;
+        if !status.success() {
+            bail!("Failed to signal the server");
+        }
+        Ok(())
+    }
+
+    /// Wait for the process to exit
+    pub async fn wait_exit(&mut self, timeout: Duration) -> Result<ExitStatus> {
+        let child = &mut self.child;
+        let mut exited = None;
+        poll_until(timeout, Duration::from_millis(50), || {
+            exited = child.try_wait().ok().flatten();
+            std::future::ready(exited.map(|_| ()))
+        })
+        .await;
+        exited.ok_or_else(|| anyhow!("Server still running after {:?}", timeout))
+    }
+
+    /// Processes the server started that are still its children
+    pub fn child_pids(&self) -> Vec<u32> {
+        child_pids(self.pid())
+    }
+
+    pub fn logs(&self) -> String {
+        fs::read_to_string(self.dir.join("server.log")).unwrap_or_default()
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            eprintln!(
+                "--- server log ({}) ---\n{}",
+                self.dir.display(),
+                self.logs()
+            );
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Direct children of `pid`, read from /proc
+pub fn child_pids(pid: u32) -> Vec<u32> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|candidate| parent_pid(*candidate) == Some(pid))
+        .collect()
+}
+
+fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = fs::read_to_string(Path::new("/proc").join(pid.to_string()).join("stat")).ok()?;
+    // The command name is parenthesized and may contain spaces; fields follow the last ')'
+    let fields = &stat[stat.rfind(')')? + 1..];
+    fields.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Whether a process with this id is still running (zombies count as gone)
+pub fn process_running(pid: u32) -> bool {
+    match fs::read_to_string(Path::new("/proc").join(pid.to_string()).join("stat")) {
+        Ok(stat) => stat
+            .rfind(')')
+            .and_then(|end| stat[end + 1..].split_whitespace().next())
+            .is_some_and(|state| state != "Z"),
+        Err(_) => false,
+    }
+}
+
+/// JSON-RPC over the server's WebSocket endpoint
+pub struct McpClient {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    next_id: i64,
+}
+
+impl McpClient {
+    /// Send a request and wait for its response, skipping notifications in between
+    pub async fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        self.socket.send(Message::Text(message.to_string())).await?;
+
+        let wait = async {
+            while let Some(message) = self.socket.next().await {
+                let Message::Text(text) = message? else {
+                    continue;
+                };
+                let response: Value = serde_json::from_str(&text)?;
+                if response["id"] != id || response.get("method").is_some() {
+                    continue;
+                }
+                if let Some(error) = response.get("error") {
+                    bail!("{} failed: {}", method, error);
+                }
+                return Ok(response["result"].clone());
+            }
+            bail!("Connection closed while waiting for {}", method)
+        };
+        tokio::time::timeout(Duration::from_secs(10), wait)
+            .await
+            .map_err(|_| anyhow!("No response to {}", method))?
+    }
+
+    pub async fn notify(&mut self, method: &str) -> Result<()> {
+        let message = json!({"jsonrpc": "2.0", "method": method});
+        self.socket.send(Message::Text(message.to_string())).await?;
+        Ok(())
+    }
+
+    /// initialize followed by notifications/initialized
+    pub async fn initialize(&mut self, client_name: &str) -> Result<Value> {
+        let result = self
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": {"name": client_name, "version": "1.0.0"}
+                }),
+            )
+            .await?;
+        self.notify("notifications/initialized").await?;
+        Ok(result)
+    }
+
+    /// Names of all tools, following `nextCursor` across pages
+    pub async fn list_tools(&mut self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        let mut cursor = Value::Null;
+        loop {
+            let page = self
+                .request("tools/list", json!({"cursor": cursor}))
+                .await?;
+            names.extend(
+                page["tools"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|tool| tool["name"].as_str().map(str::to_string)),
+            );
+            match &page["nextCursor"] {
+                Value::Null => return Ok(names),
+                next => cursor = next.clone(),
+            }
+        }
+    }
+
+    /// Call a tool and parse its JSON text content; tool errors fail the call
+    pub async fn call_tool(&mut self, name: &str, arguments: Value) -> Result<Value> {
+        let result = self
+            .request("tools/call", json!({"name": name, "arguments": arguments}))
+            .await?;
+        let text = result["content"][0]["text"]
+            .as_str()
+            .ok_or_else(|| anyhow!("{} returned no text content: {}", name, result))?;
+        if result["isError"] == true {
+            bail!("{} failed: {}", name, text);
+        }
+        Ok(serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string())))
+    }
+}